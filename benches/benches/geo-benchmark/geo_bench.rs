@@ -2,7 +2,10 @@ use std::hint::black_box;
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use zumic::{GeoEntry, GeoPoint, GeoSet, Geohash, GeohashPrecision, RadiusOptions};
+use zumic::{
+    haversine_distance, GeoAddOptions, GeoEntry, GeoPoint, GeoSet, Geohash, GeohashPrecision,
+    RadiusOptions,
+};
 
 fn generate_entries(count: usize) -> Vec<GeoEntry> {
     let mut rng = StdRng::seed_from_u64(42);
@@ -56,7 +59,12 @@ fn bench_insertion(c: &mut Criterion) {
                 b.iter(|| {
                     let mut gs = GeoSet::new();
                     for e in entries {
-                        gs.add(e.member.clone(), e.point.lon, e.point.lat);
+                        gs.add(
+                            e.member.clone(),
+                            e.point.lon,
+                            e.point.lat,
+                            GeoAddOptions::default(),
+                        );
                     }
                     black_box(gs);
                 });
@@ -101,7 +109,7 @@ fn bench_radius(c: &mut Criterion) {
     let mut gs = GeoSet::new();
 
     for (m, lon, lat) in points {
-        gs.add(m, lon, lat);
+        gs.add(m, lon, lat, GeoAddOptions::default());
     }
 
     for &radius in &[1_000.0, 10_000.0, 100_000.0] {
@@ -151,6 +159,64 @@ fn bench_radius(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_radius_vs_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radius_vs_scan");
+
+    let size = 100_000;
+    let gs = GeoSet::from_entries(generate_entries(size));
+    let center = GeoPoint {
+        lon: 10.0,
+        lat: 45.0,
+    };
+
+    for &radius in &[10_000.0, 100_000.0, 500_000.0] {
+        group.bench_with_input(
+            BenchmarkId::new("rtree", radius as u64),
+            &radius,
+            |b, &r| {
+                b.iter(|| black_box(gs.search_radius(black_box(center), r)));
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("linear_scan", radius as u64),
+            &radius,
+            |b, &r| {
+                b.iter(|| {
+                    let res: Vec<(&String, f64)> = gs
+                        .iter()
+                        .map(|(m, p)| (m, haversine_distance(black_box(center), *p)))
+                        .filter(|(_, d)| *d <= r)
+                        .collect();
+                    black_box(res);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_update_remove(c: &mut Criterion) {
+    let entries = generate_entries(100_000);
+    let mut gs = GeoSet::from_entries(entries.clone());
+    let mut i = 0usize;
+
+    c.bench_function("update_remove_100k", |b| {
+        b.iter(|| {
+            let e = &entries[i % entries.len()];
+            i += 1;
+            gs.remove(&e.member);
+            gs.add(
+                e.member.clone(),
+                e.point.lon,
+                e.point.lat,
+                GeoAddOptions::default(),
+            );
+        });
+    });
+}
+
 fn bench_knn(c: &mut Criterion) {
     let mut group = c.benchmark_group("knn");
 
@@ -195,7 +261,7 @@ fn bench_false_positive(c: &mut Criterion) {
     let mut gs = GeoSet::new();
 
     for (m, lon, lat) in points {
-        gs.add(m, lon, lat);
+        gs.add(m, lon, lat, GeoAddOptions::default());
     }
 
     c.bench_function("false_positive_rate", |b| {
@@ -249,6 +315,8 @@ criterion_group!(
         bench_insertion,
         bench_get_and_dist,
         bench_radius,
+        bench_radius_vs_scan,
+        bench_update_remove,
         bench_knn,
         bench_geohash,
         bench_false_positive
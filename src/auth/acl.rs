@@ -36,6 +36,39 @@ static COMMAND_INDEX: phf::Map<&'static str, usize> = phf_map! {
     // тут можно добавить в будущем остальные команды с уникальным индексом.
 };
 
+/// Имена категорий команд в порядке `ACL CAT`.
+pub const CATEGORY_NAMES: [&str; 3] = ["read", "write", "admin"];
+
+/// Команды категории `@read`.
+const READ_COMMANDS: &[&str] = &[
+    "exists",
+    "get",
+    "hget",
+    "hgetall",
+    "llen",
+    "lrange",
+    "mget",
+    "scard",
+    "sismember",
+    "smembers",
+    "strlen",
+    "ttl",
+    "type",
+    "zcard",
+    "zscore",
+];
+/// Команды категории `@write`.
+const WRITE_COMMANDS: &[&str] = &[
+    "append", "decr", "decrby", "del", "expire", "getdel", "hdel", "hset", "incr", "incrby",
+    "lpop", "lpush", "mset", "persist", "rename", "renamenx", "rpop", "rpush", "sadd", "set",
+    "setnx", "srem", "zadd", "zrem",
+];
+/// Команды категории `@admin`.
+const ADMIN_COMMANDS: &[&str] = &[
+    "acl", "client", "config", "debug", "flushall", "flushdb", "info", "monitor", "save",
+    "shutdown", "slowlog",
+];
+
 /// Представляет одно ACL-правило, разобранное из строки конфигурации.
 #[derive(Debug)]
 #[repr(u8)]
@@ -44,7 +77,7 @@ pub enum AclRule {
     On,
     /// Выключить пользователя (`off`).
     Off,
-    /// Добавить хэш пароля (`>hash`).
+    /// Добавить хэш пароля (`>hash`; в ACL-файле — `#hash`).
     PasswordHash(String),
     /// Разрешить всю категорию (`+@read`, `+@write`, `+@admin`, `+@all`).
     AllowCategory(CmdCategory),
@@ -64,6 +97,8 @@ pub enum AclRule {
     DenyChannelPattern(String),
     /// Пользователь. не требуется пароля (nopass).
     NoPass,
+    /// Сбросить все правила пользователя (`reset`).
+    Reset,
 }
 
 /// Конфигурация пользователя ACL.
@@ -271,6 +306,97 @@ impl AclUser {
         }
     }
 
+    /// Возвращает правила, из которых `Acl::acl_setuser` воссоздаёт этого
+    /// пользователя (формат строки `user` ACL-файла без имени). Хеши
+    /// паролей записываются как `#hash`: `>` в файле означает пароль
+    /// открытым текстом.
+    pub fn to_rules(&self) -> Vec<String> {
+        let mut rules = vec![if self.enabled { "on" } else { "off" }.to_string()];
+        if self.password_hashes.is_empty() {
+            rules.push("nopass".into());
+        }
+        rules.extend(self.password_hashes.iter().map(|h| format!("#{h}")));
+        rules.extend(
+            self.raw_key_patterns
+                .iter()
+                .map(|g| format!("~{}", g.glob())),
+        );
+        rules.extend(
+            self.raw_deny_key_patterns
+                .iter()
+                .map(|g| format!("-~{}", g.glob())),
+        );
+        rules.extend(
+            self.raw_channel_patterns
+                .iter()
+                .map(|g| format!("&{}", g.glob())),
+        );
+        rules.extend(
+            self.raw_deny_channel_patterns
+                .iter()
+                .map(|g| format!("-&{}", g.glob())),
+        );
+
+        if self.allowed_categories.is_all() {
+            rules.push("+@all".into());
+        } else {
+            for (name, _) in self.allowed_categories.iter_names() {
+                rules.push(format!("+@{}", name.to_ascii_lowercase()));
+            }
+        }
+
+        let mut commands: Vec<_> = COMMAND_INDEX.entries().collect();
+        commands.sort_by_key(|(_, idx)| **idx);
+        for (name, idx) in commands {
+            let bit = 1u128 << idx;
+            if self.allowed_commands & bit != 0 {
+                rules.push(format!("+{name}"));
+            }
+            if self.denied_commands & bit != 0 {
+                rules.push(format!("-{name}"));
+            }
+        }
+        rules
+    }
+
+    /// Применяет разобранные правила поверх текущих настроек пользователя.
+    pub fn apply_rules(
+        &mut self,
+        rules: Vec<AclRule>,
+    ) -> Result<(), AclError> {
+        for rule in rules {
+            match rule {
+                AclRule::On => self.enabled = true,
+                AclRule::Off => self.enabled = false,
+                AclRule::PasswordHash(h) => {
+                    if !self.password_hashes.contains(&h) {
+                        self.password_hashes.push(h);
+                    }
+                }
+                AclRule::AllowCategory(c) => self.allowed_categories |= c,
+                AclRule::DenyCategory(c) => self.allowed_categories.remove(c),
+                AclRule::AllowCommand(i) => {
+                    self.allowed_commands |= 1u128 << i;
+                    self.denied_commands &= !(1u128 << i);
+                }
+                AclRule::DenyCommand(i) => {
+                    self.denied_commands |= 1u128 << i;
+                    self.allowed_commands &= !(1u128 << i);
+                }
+                AclRule::AllowKeyPattern(p) => self.allow_key_pattern(&p)?,
+                AclRule::DenyKeyPattern(p) => self.deny_key_pattern(&p)?,
+                AclRule::AllowChannelPattern(p) => self.allow_channel_pattern(&p)?,
+                AclRule::DenyChannelPattern(p) => self.deny_channel_pattern(&p)?,
+                AclRule::NoPass => self.password_hashes.clear(),
+                AclRule::Reset => self.reset_rules(),
+            }
+        }
+
+        // Eager-rebuild после всех изменений
+        self.rebuild_all_patterns()?;
+        self.rebuild_all_deny_patterns()
+    }
+
     /// Сбрасывает все правила ACL, оставляя только username.
     pub fn reset_rules(&mut self) {
         // Выключаем пользователя и чистим команды
@@ -317,27 +443,32 @@ impl Acl {
 
         // Очищаем прежние настройки (за исключением имени пользователя).
         user.reset_rules();
+        user.apply_rules(parsed)
+    }
 
-        for rule in parsed {
-            match rule {
-                AclRule::On => user.enabled = true,
-                AclRule::Off => user.enabled = false,
-                AclRule::PasswordHash(h) => user.password_hashes.push(h),
-                AclRule::AllowCategory(c) => user.allowed_categories |= c,
-                AclRule::DenyCategory(c) => user.allowed_categories.remove(c),
-                AclRule::AllowCommand(i) => user.allowed_commands |= 1u128 << i,
-                AclRule::DenyCommand(i) => user.denied_commands |= 1u128 << i,
-                AclRule::AllowKeyPattern(p) => user.allow_key_pattern(&p)?,
-                AclRule::DenyKeyPattern(p) => user.deny_key_pattern(&p)?,
-                AclRule::AllowChannelPattern(p) => user.allow_channel_pattern(&p)?,
-                AclRule::DenyChannelPattern(p) => user.deny_channel_pattern(&p)?,
-                AclRule::NoPass => {}
-            }
-        }
+    /// Изменяет пользователя правилами `rules` поверх уже заданных
+    /// (`ACL SETUSER`). Новый пользователь создаётся выключенным и без прав.
+    ///
+    /// Правила проверяются до изменения: при ошибке в любом из них
+    /// пользователь остаётся прежним.
+    pub fn acl_modifyuser(
+        &self,
+        username: &str,
+        rules: &[&str],
+    ) -> Result<(), AclError> {
+        let parsed: Vec<AclRule> = rules.iter().map(|s| s.parse()).collect::<Result<_, _>>()?;
 
-        // Eager-rebuild после всех изменений
-        user.rebuild_all_patterns()?;
-        user.rebuild_all_deny_patterns()?;
+        let mut user = match self.acl_getuser(username) {
+            Some(user) => user,
+            None => {
+                let mut user = AclUser::new(username)?;
+                user.reset_rules();
+                user
+            }
+        };
+        user.apply_rules(parsed)?;
+        self.users
+            .insert(username.to_string(), Arc::new(RwLock::new(user)));
         Ok(())
     }
 
@@ -377,10 +508,35 @@ pub fn parse_category(cat: &str) -> CmdCategory {
         "read" => CmdCategory::READ,
         "write" => CmdCategory::WRITE,
         "admin" => CmdCategory::ADMIN,
+        "all" => CmdCategory::all(),
         _ => CmdCategory::empty(),
     }
 }
 
+/// Возвращает имя категории команды `cmd` для проверки прав: `admin`,
+/// `write` или `read`.
+pub fn command_category(cmd: &str) -> &'static str {
+    let lower = cmd.to_ascii_lowercase();
+    if ADMIN_COMMANDS.contains(&lower.as_str()) {
+        "admin"
+    } else if WRITE_COMMANDS.contains(&lower.as_str()) {
+        "write"
+    } else {
+        "read"
+    }
+}
+
+/// Возвращает команды категории `category` (`ACL CAT category`) или `None`
+/// для неизвестной категории.
+pub fn category_commands(category: &str) -> Option<&'static [&'static str]> {
+    match category.to_ascii_lowercase().as_str() {
+        "read" => Some(READ_COMMANDS),
+        "write" => Some(WRITE_COMMANDS),
+        "admin" => Some(ADMIN_COMMANDS),
+        _ => None,
+    }
+}
+
 /// Парсим имя команды один раз в индекс.
 /// Возвращает `None` для незнакомых команд.
 pub fn lookup_cmd_idx(cmd: &str) -> Option<usize> {
@@ -402,12 +558,18 @@ impl FromStr for AclRule {
         if s == "off" {
             return Ok(AclRule::Off);
         }
-        if s == "nopass" {
-            return Ok(AclRule::NoPass);
+        match s {
+            "nopass" => return Ok(AclRule::NoPass),
+            "reset" => return Ok(AclRule::Reset),
+            "allkeys" => return Ok(AclRule::AllowKeyPattern("*".into())),
+            "allchannels" => return Ok(AclRule::AllowChannelPattern("*".into())),
+            "allcommands" => return Ok(AclRule::AllowCategory(CmdCategory::all())),
+            "" => return Err(AclError::InvalidAclRule(s.into())),
+            _ => {}
         }
         let (head, rest) = s.split_at(1);
         match head {
-            ">" => Ok(AclRule::PasswordHash(rest.into())),
+            ">" | "#" => Ok(AclRule::PasswordHash(rest.into())),
             "+" if rest.starts_with('@') => {
                 let c = parse_category(&rest[1..]);
                 Ok(AclRule::AllowCategory(c))
@@ -846,4 +1008,35 @@ mod tests {
         assert!(user.check_channel("chanGood"));
         assert!(!user.check_channel("chanbad123"));
     }
+
+    /// Тест проверяет, что `acl_modifyuser` дополняет правила, а не заменяет
+    /// их, и понимает псевдонимы `allkeys`, `allcommands` и `reset`.
+    #[test]
+    fn test_modifyuser_is_incremental() {
+        let acl = Acl::default();
+        acl.acl_modifyuser("anton", &["on", "~data:*"]).unwrap();
+        let user = acl.acl_getuser("anton").unwrap();
+        assert!(user.enabled);
+        assert!(!user.check_idx(CmdCategory::READ, lookup_cmd_idx("get")));
+
+        acl.acl_modifyuser("anton", &["+get", "-set"]).unwrap();
+        let user = acl.acl_getuser("anton").unwrap();
+        assert!(user.check_idx(CmdCategory::READ, lookup_cmd_idx("get")));
+        assert!(user.check_key("data:1"));
+        assert!(!user.check_key("other"));
+
+        acl.acl_modifyuser("anton", &["allkeys", "allcommands"])
+            .unwrap();
+        let user = acl.acl_getuser("anton").unwrap();
+        assert!(user.check_key("other"));
+        assert!(!user.check_idx(CmdCategory::WRITE, lookup_cmd_idx("set")));
+        assert!(user.check_idx(CmdCategory::WRITE, lookup_cmd_idx("del")));
+
+        // Ошибка в любом правиле оставляет пользователя прежним.
+        assert!(acl.acl_modifyuser("anton", &["reset", "+nosuch"]).is_err());
+        assert!(acl.acl_getuser("anton").unwrap().enabled);
+
+        acl.acl_modifyuser("anton", &["reset"]).unwrap();
+        assert!(!acl.acl_getuser("anton").unwrap().enabled);
+    }
 }
@@ -0,0 +1,273 @@
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{Sds, Value};
+
+/// Ёмкость журнала по умолчанию.
+pub const DEFAULT_ACL_LOG_MAX_LEN: usize = 128;
+/// Число записей, возвращаемых `ACL LOG` без аргумента.
+pub const DEFAULT_ACL_LOG_GET_COUNT: usize = 10;
+/// Окно, в течение которого повторный отказ того же пользователя по тому же
+/// объекту увеличивает счётчик существующей записи.
+const ACL_LOG_GROUPING_WINDOW: Duration = Duration::from_secs(60);
+
+/// Причина отказа в доступе.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclDenyReason {
+    /// Команда запрещена правилами пользователя.
+    Command,
+    /// Доступ к ключу запрещён шаблонами ключей пользователя.
+    Key,
+    /// Доступ к каналу Pub/Sub запрещён.
+    Channel,
+    /// Неудачная аутентификация.
+    Auth,
+}
+
+/// Запись журнала отказов ACL (`ACL LOG`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclLogEntry {
+    /// Число одинаковых отказов, сгруппированных в запись.
+    pub count: u64,
+    /// Причина отказа: `command`, `key`, `channel` или `auth`.
+    pub reason: String,
+    /// Запрещённая команда, ключ или канал.
+    pub object: String,
+    /// Имя пользователя.
+    pub username: String,
+    /// Время с момента создания записи в секундах.
+    pub age: f64,
+    /// Описание клиента, получившего отказ.
+    pub client_info: String,
+}
+
+/// Журнал отказов ACL.
+///
+/// Кольцевой буфер фиксированного размера, общий для всех соединений.
+/// Клонирование дёшево: все клоны ссылаются на один буфер.
+#[derive(Debug, Clone)]
+pub struct AclLog {
+    /// Записи, от новых к старым.
+    entries: Arc<Mutex<VecDeque<AclLogRecord>>>,
+    /// Максимальное число записей.
+    max_len: usize,
+}
+
+/// Запись журнала вместе с моментами создания и последнего обновления.
+#[derive(Debug, Clone)]
+struct AclLogRecord {
+    entry: AclLogEntry,
+    created: Instant,
+    updated: Instant,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl AclDenyReason {
+    /// Возвращает имя причины, как в ответе `ACL LOG`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AclDenyReason::Command => "command",
+            AclDenyReason::Key => "key",
+            AclDenyReason::Channel => "channel",
+            AclDenyReason::Auth => "auth",
+        }
+    }
+}
+
+impl AclLogEntry {
+    /// Преобразует запись в ответ `ACL LOG`: плоский массив пар
+    /// «поле — значение».
+    pub fn to_value(&self) -> Value {
+        let str = |s: &str| Value::Str(Sds::from_str(s));
+        Value::Array(vec![
+            str("count"),
+            Value::Int(self.count as i64),
+            str("reason"),
+            str(&self.reason),
+            str("object"),
+            str(&self.object),
+            str("username"),
+            str(&self.username),
+            str("age-seconds"),
+            Value::Float(self.age),
+            str("client-info"),
+            str(&self.client_info),
+        ])
+    }
+}
+
+impl AclLog {
+    /// Создаёт журнал ёмкостью `max_len` записей.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(max_len))),
+            max_len,
+        }
+    }
+
+    /// Записывает отказ в доступе.
+    ///
+    /// Если за последние 60 секунд уже был отказ того же пользователя по той
+    /// же причине и объекту, увеличивает счётчик этой записи и поднимает её в
+    /// начало журнала вместо добавления новой.
+    pub fn record(
+        &self,
+        reason: AclDenyReason,
+        object: &str,
+        username: &str,
+        client_info: &str,
+    ) {
+        if self.max_len == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+
+        let existing = entries.iter().position(|record| {
+            now.duration_since(record.updated) < ACL_LOG_GROUPING_WINDOW
+                && record.entry.reason == reason.as_str()
+                && record.entry.object == object
+                && record.entry.username == username
+        });
+        if let Some(mut record) = existing.and_then(|idx| entries.remove(idx)) {
+            record.entry.count += 1;
+            record.entry.client_info = client_info.to_string();
+            record.updated = now;
+            entries.push_front(record);
+            return;
+        }
+
+        entries.push_front(AclLogRecord {
+            entry: AclLogEntry {
+                count: 1,
+                reason: reason.as_str().to_string(),
+                object: object.to_string(),
+                username: username.to_string(),
+                age: 0.0,
+                client_info: client_info.to_string(),
+            },
+            created: now,
+            updated: now,
+        });
+        entries.truncate(self.max_len);
+    }
+
+    /// Возвращает до `count` последних записей (по умолчанию 10), от новых к
+    /// старым.
+    pub fn get(
+        &self,
+        count: Option<usize>,
+    ) -> Vec<AclLogEntry> {
+        let count = count.unwrap_or(DEFAULT_ACL_LOG_GET_COUNT);
+        self.entries
+            .lock()
+            .iter()
+            .take(count)
+            .map(|record| AclLogEntry {
+                age: record.created.elapsed().as_secs_f64(),
+                ..record.entry.clone()
+            })
+            .collect()
+    }
+
+    /// Возвращает число записей в журнале.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Проверяет, пуст ли журнал.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Очищает журнал.
+    pub fn reset(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для AclLog
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for AclLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACL_LOG_MAX_LEN)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет группировку повторных отказов в одну запись.
+    #[test]
+    fn test_record_groups_repeated_denials() {
+        let log = AclLog::default();
+        log.record(AclDenyReason::Command, "set", "alice", "id=1");
+        log.record(AclDenyReason::Command, "set", "alice", "id=2");
+        log.record(AclDenyReason::Command, "set", "bob", "id=3");
+        log.record(AclDenyReason::Key, "set", "alice", "id=4");
+
+        let entries = log.get(None);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].reason, "key");
+        assert_eq!(entries[1].username, "bob");
+        assert_eq!(entries[2].count, 2);
+        assert_eq!(entries[2].client_info, "id=2");
+
+        // Повторный отказ поднимает запись в начало журнала.
+        log.record(AclDenyReason::Command, "set", "alice", "id=5");
+        let entries = log.get(Some(1));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            (entries[0].username.as_str(), entries[0].count),
+            ("alice", 3)
+        );
+    }
+
+    /// Тест проверяет ограничение ёмкости и очистку журнала.
+    #[test]
+    fn test_capacity_and_reset() {
+        let log = AclLog::new(2);
+        for cmd in ["get", "set", "del"] {
+            log.record(AclDenyReason::Command, cmd, "alice", "");
+        }
+        let objects: Vec<_> = log.get(None).into_iter().map(|e| e.object).collect();
+        assert_eq!(objects, vec!["del", "set"]);
+
+        log.reset();
+        assert!(log.is_empty());
+
+        let disabled = AclLog::new(0);
+        disabled.record(AclDenyReason::Auth, "AUTH", "alice", "");
+        assert_eq!(disabled.len(), 0);
+    }
+
+    /// Тест проверяет формат записи в ответе `ACL LOG`.
+    #[test]
+    fn test_entry_to_value() {
+        let log = AclLog::default();
+        log.record(AclDenyReason::Key, "secret", "alice", "addr=127.0.0.1:1");
+        let Value::Array(fields) = log.get(None)[0].to_value() else {
+            panic!("expected array");
+        };
+        assert_eq!(fields.len(), 12);
+        assert_eq!(fields[0], Value::Str(Sds::from_str("count")));
+        assert_eq!(fields[1], Value::Int(1));
+        assert_eq!(fields[5], Value::Str(Sds::from_str("secret")));
+        assert!(matches!(fields[9], Value::Float(age) if age >= 0.0));
+    }
+}
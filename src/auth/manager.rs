@@ -1,11 +1,14 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, fs, io, path::Path, sync::Arc};
 
 use tokio::{
     sync::RwLock,
     time::{Duration, Instant},
 };
 
-use super::{hash_password, lookup_cmd_idx, parse_category, verify_password, Acl, ServerConfig};
+use super::{
+    hash_password, lookup_cmd_idx, parse_category, verify_password, Acl, AclDenyReason, AclLog,
+    AclUser, ServerConfig,
+};
 use crate::{AclError, AuthError, PasswordError};
 
 /// Максимальное количество неудачных попыток входа перед
@@ -29,6 +32,8 @@ pub struct AuthManager {
     /// Счётчик неудачных попыток входа: имя пользователя →
     /// (кол-во, время первой неудачи).
     failures: Arc<RwLock<HashMap<String, (u8, Instant)>>>,
+    /// Журнал отказов в доступе (`ACL LOG`).
+    log: AclLog,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -42,6 +47,7 @@ impl AuthManager {
             acl: Arc::new(RwLock::new(Acl::default())),
             pepper: None,
             failures: Arc::new(RwLock::new(HashMap::new())),
+            log: AclLog::default(),
         }
     }
 
@@ -52,6 +58,7 @@ impl AuthManager {
             acl: Arc::new(RwLock::new(Acl::default())),
             pepper: Some(pepper.into()),
             failures: Arc::new(RwLock::new(HashMap::new())),
+            log: AclLog::default(),
         }
     }
 
@@ -97,6 +104,9 @@ impl AuthManager {
         // Получаем шаблоны паролей из ACL
         let acl = self.acl.read().await;
         let user = acl.acl_getuser(username).ok_or(AuthError::UserNotFound)?;
+        if !user.enabled {
+            return Err(AuthError::AuthenticationFailed);
+        }
         let pepper = self.pepper.clone();
         let hashes = user.password_hashes.clone();
         let password = password.to_owned();
@@ -128,6 +138,20 @@ impl AuthManager {
         }
     }
 
+    /// Проверяет, должны ли соединения аутентифицироваться перед выполнением
+    /// команд.
+    ///
+    /// Аутентификация обязательна, если у пользователя `default` задан пароль
+    /// (`requirepass`) или он выключен, а также если настроен хотя бы один
+    /// пользователь помимо `default`.
+    pub async fn requires_auth(&self) -> bool {
+        let acl = self.acl.read().await;
+        let default_open = acl
+            .acl_getuser("default")
+            .is_none_or(|user| user.enabled && user.password_hashes.is_empty());
+        !default_open || acl.acl_users().iter().any(|name| name != "default")
+    }
+
     /// Проверяет, разрешена ли пользователю команда в заданной
     /// категории.
     pub async fn authorize_command(
@@ -167,6 +191,137 @@ impl AuthManager {
         }
     }
 
+    /// Проверяет право пользователя выполнить команду над ключами `keys`.
+    ///
+    /// Отказ записывается в журнал ACL (см. [`AuthManager::acl_log`]) с
+    /// описанием клиента `client_info`.
+    pub async fn check_permission(
+        &self,
+        username: &str,
+        category: &str,
+        command: &str,
+        keys: &[&str],
+        client_info: &str,
+    ) -> Result<(), AuthError> {
+        let result = self.authorize_command(username, category, command).await;
+        if let Err(AuthError::Acl(AclError::PermissionDenied)) = result {
+            self.log
+                .record(AclDenyReason::Command, command, username, client_info);
+        }
+        result?;
+
+        for key in keys {
+            let result = self.authorize_key(username, key).await;
+            if let Err(AuthError::Acl(AclError::PermissionDenied)) = result {
+                self.log
+                    .record(AclDenyReason::Key, key, username, client_info);
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Создаёт или изменяет пользователя правилами `rules` (`ACL SETUSER`).
+    /// Пароли в правилах `>password` хешируются; правила применяются поверх
+    /// текущих.
+    pub fn set_user(
+        &self,
+        username: &str,
+        rules: &[&str],
+    ) -> Result<(), AuthError> {
+        let mut hashed = Vec::with_capacity(rules.len());
+        for rule in rules {
+            match rule.strip_prefix('>') {
+                Some(password) => hashed.push(format!(
+                    ">{}",
+                    hash_password(password, self.pepper.as_deref())?
+                )),
+                None => hashed.push(rule.to_string()),
+            }
+        }
+        let refs: Vec<&str> = hashed.iter().map(|s| s.as_str()).collect();
+        self.acl_sync()?.acl_modifyuser(username, &refs)?;
+        Ok(())
+    }
+
+    /// Возвращает копию пользователя `username` (`ACL GETUSER`).
+    pub fn get_user(
+        &self,
+        username: &str,
+    ) -> Result<Option<AclUser>, AuthError> {
+        Ok(self.acl_sync()?.acl_getuser(username))
+    }
+
+    /// Удаляет пользователей `usernames` (`ACL DELUSER`) и возвращает число
+    /// удалённых. Пользователя `default` удалить нельзя.
+    pub fn delete_users(
+        &self,
+        usernames: &[&str],
+    ) -> Result<usize, AuthError> {
+        if usernames.contains(&"default") {
+            return Err(AclError::Internal("The 'default' user cannot be removed".into()).into());
+        }
+        let acl = self.acl_sync()?;
+        Ok(usernames
+            .iter()
+            .filter(|name| acl.acl_deluser(name).is_ok())
+            .count())
+    }
+
+    /// Возвращает журнал отказов в доступе. Клоны журнала общие.
+    pub fn acl_log(&self) -> AclLog {
+        self.log.clone()
+    }
+
+    /// Сохраняет пользователей ACL в файл в формате ACL-файла Redis: по строке
+    /// `user <name> <rules...>` на пользователя. Пароли сохраняются в виде
+    /// хешей.
+    ///
+    /// Файл записывается во временный и затем переименовывается, поэтому
+    /// при сбое прежнее содержимое не теряется.
+    pub fn save_to_file(
+        &self,
+        path: &Path,
+    ) -> io::Result<()> {
+        let content = {
+            let acl = self.acl.try_read().map_err(|_| acl_busy())?;
+            let mut users = acl.acl_users();
+            users.sort();
+            users
+                .iter()
+                .filter_map(|name| acl.acl_getuser(name))
+                .map(|user| format!("user {} {}\n", user.username, user.to_rules().join(" ")))
+                .collect::<String>()
+        };
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Создаёт `AuthManager` без «pepper» с пользователями из ACL-файла.
+    pub fn load_from_file(path: &Path) -> io::Result<Self> {
+        let manager = Self::new();
+        manager.reload_from_file(path)?;
+        Ok(manager)
+    }
+
+    /// Заменяет пользователей ACL содержимым файла (`ACL LOAD`).
+    ///
+    /// Файл сначала разбирается целиком: при любой ошибке текущие
+    /// пользователи остаются без изменений. Пароли, заданные открытым
+    /// текстом (`>password`), хешируются с «pepper» этого менеджера, а
+    /// сохранённые хеши (`#hash`) принимаются как есть.
+    pub fn reload_from_file(
+        &self,
+        path: &Path,
+    ) -> io::Result<()> {
+        let content = fs::read_to_string(path)?;
+        let acl = parse_acl_file(&content, self.pepper.as_deref())?;
+        *self.acl.try_write().map_err(|_| acl_busy())? = acl;
+        Ok(())
+    }
+
     /// Инициализирует `AuthManager` из конфигурации сервера.
     pub async fn from_config(config: &ServerConfig) -> Result<Self, AuthError> {
         let pepper = config.auth_pepper.clone();
@@ -215,6 +370,7 @@ impl AuthManager {
             acl: Arc::new(RwLock::new(acl)),
             pepper,
             failures: Arc::new(RwLock::new(HashMap::new())),
+            log: AclLog::default(),
         })
     }
 
@@ -223,6 +379,14 @@ impl AuthManager {
     pub fn acl(&self) -> Arc<RwLock<Acl>> {
         Arc::clone(&self.acl)
     }
+
+    /// Захватывает ACL на чтение из синхронного кода. Изменения пользователей
+    /// идут через `DashMap` внутри `Acl`, поэтому чтения достаточно.
+    fn acl_sync(&self) -> Result<tokio::sync::RwLockReadGuard<'_, Acl>, AuthError> {
+        self.acl
+            .try_read()
+            .map_err(|_| AclError::Internal("ACL is being modified, try again".into()).into())
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -242,8 +406,71 @@ impl Clone for AuthManager {
             acl: Arc::clone(&self.acl),
             pepper: self.pepper.clone(),
             failures: Arc::clone(&self.failures),
+            log: self.log.clone(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Разбирает ACL-файл в новый набор пользователей.
+///
+/// Пустые строки и строки-комментарии (`#`) пропускаются. Пароль `>value`
+/// считается хешем, если начинается с `$` (формат PHC), иначе хешируется.
+fn parse_acl_file(
+    content: &str,
+    pepper: Option<&str>,
+) -> io::Result<Acl> {
+    let acl = Acl::default();
+    for (line_no, line) in content.lines().enumerate() {
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {msg}", line_no + 1),
+            )
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        if parts.next() != Some("user") {
+            return Err(invalid("line should start with user keyword".into()));
+        }
+        let username = parts
+            .next()
+            .ok_or_else(|| invalid("missing username".into()))?;
+        if acl.acl_getuser(username).is_some() {
+            return Err(invalid(format!("duplicate user '{username}'")));
+        }
+
+        let mut rules = Vec::new();
+        for rule in parts {
+            match rule.strip_prefix('>') {
+                Some(password) => {
+                    let hash =
+                        hash_password(password, pepper).map_err(|e| invalid(e.to_string()))?;
+                    rules.push(format!(">{hash}"));
+                }
+                None => rules.push(rule.to_string()),
+            }
         }
+        let refs: Vec<&str> = rules.iter().map(|s| s.as_str()).collect();
+        acl.acl_setuser(username, &refs)
+            .map_err(|e| invalid(format!("{e} for user '{username}'")))?;
     }
+    Ok(acl)
+}
+
+/// Ошибка при конкурентном доступе к ACL из синхронного кода.
+fn acl_busy() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::WouldBlock,
+        "ACL is being modified, try again",
+    )
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -328,6 +555,36 @@ mod tests {
 
     /// Тест проверяет, что при множестве неудачных попыток входа
     /// срабатывает rate-limiting.
+    /// Тест проверяет, когда аутентификация обязательна.
+    #[tokio::test]
+    async fn test_requires_auth() {
+        let manager = AuthManager::new();
+        assert!(!manager.requires_auth().await);
+
+        manager
+            .acl()
+            .read()
+            .await
+            .acl_setuser("default", &["on", "nopass"])
+            .unwrap();
+        assert!(!manager.requires_auth().await);
+
+        manager.create_user("anton", "secret", &[]).await.unwrap();
+        assert!(manager.requires_auth().await);
+
+        // Выключенный пользователь не проходит аутентификацию.
+        manager
+            .acl()
+            .read()
+            .await
+            .acl_setuser("anton", &["off"])
+            .unwrap();
+        assert!(matches!(
+            manager.authenticate("anton", "secret").await,
+            Err(AuthError::AuthenticationFailed)
+        ));
+    }
+
     #[tokio::test]
     async fn test_rate_limiting() {
         let manager = AuthManager::new();
@@ -403,4 +660,135 @@ mod tests {
             AuthError::TooManyAttempts
         ));
     }
+
+    /// Тест проверяет, что отказы `check_permission` попадают в журнал ACL,
+    /// а повторные отказы группируются.
+    #[tokio::test]
+    async fn test_check_permission_records_denials() {
+        let manager = AuthManager::new();
+        manager
+            .create_user("anton", "pw", &["+get", "~data:*"])
+            .await
+            .unwrap();
+
+        assert!(manager
+            .check_permission("anton", "read", "get", &["data:1"], "id=1")
+            .await
+            .is_ok());
+        assert!(manager.acl_log().is_empty());
+
+        for _ in 0..2 {
+            assert!(manager
+                .check_permission("anton", "write", "set", &["data:1"], "id=1")
+                .await
+                .is_err());
+        }
+        assert!(manager
+            .check_permission("anton", "read", "get", &["other"], "id=2")
+            .await
+            .is_err());
+
+        let entries = manager.clone().acl_log().get(None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            (entries[0].reason.as_str(), entries[0].object.as_str()),
+            ("key", "other")
+        );
+        assert_eq!(
+            (entries[1].reason.as_str(), entries[1].object.as_str()),
+            ("command", "set")
+        );
+        assert_eq!(entries[1].count, 2);
+    }
+
+    /// Тест проверяет, что ACL SAVE и ACL LOAD сохраняют пользователей и их
+    /// правила без повторного хеширования паролей.
+    #[tokio::test]
+    async fn test_save_and_load_acl_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.acl");
+
+        let manager = AuthManager::new();
+        manager
+            .create_user(
+                "anton",
+                "pw",
+                &["~data:*", "-~data:secret", "&news", "+@read", "-del"],
+            )
+            .await
+            .unwrap();
+        manager.save_to_file(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("user anton on #$argon2"));
+        assert!(content.contains(" ~data:* -~data:secret &news +@read -del"));
+
+        let loaded = AuthManager::load_from_file(&path).unwrap();
+        assert!(loaded.authenticate("anton", "pw").await.is_ok());
+        assert!(loaded
+            .authorize_command("anton", "read", "get")
+            .await
+            .is_ok());
+        assert!(loaded
+            .authorize_command("anton", "write", "del")
+            .await
+            .is_err());
+        assert!(loaded.authorize_key("anton", "data:1").await.is_ok());
+        assert!(loaded.authorize_key("anton", "data:secret").await.is_err());
+    }
+
+    /// Тест проверяет, что пароль открытым текстом, начинающийся с `$`,
+    /// хешируется при загрузке и переживает ACL SAVE и ACL LOAD.
+    #[tokio::test]
+    async fn test_acl_file_dollar_password_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.acl");
+        fs::write(&path, "user alice on >$ecret +@all\n").unwrap();
+
+        let manager = AuthManager::load_from_file(&path).unwrap();
+        assert!(manager.authenticate("alice", "$ecret").await.is_ok());
+
+        manager.save_to_file(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(">$ecret"));
+
+        let reloaded = AuthManager::load_from_file(&path).unwrap();
+        assert!(reloaded.authenticate("alice", "$ecret").await.is_ok());
+        assert!(matches!(
+            reloaded.authenticate("alice", "ecret").await,
+            Err(AuthError::AuthenticationFailed)
+        ));
+    }
+
+    /// Тест проверяет, что ACL LOAD хеширует пароли открытым текстом и не
+    /// меняет пользователей, если в файле есть ошибка.
+    #[tokio::test]
+    async fn test_reload_validates_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.acl");
+        let manager = AuthManager::new();
+        manager.create_user("old", "pw", &[]).await.unwrap();
+
+        fs::write(
+            &path,
+            "user alice on >secret ~* +@all\nuser bob on nopass +nosuchcmd\n",
+        )
+        .unwrap();
+        let err = manager.reload_from_file(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 2:"));
+        assert!(manager.authenticate("old", "pw").await.is_ok());
+
+        fs::write(&path, "# users\nuser alice on >secret ~* +@all\n").unwrap();
+        manager.reload_from_file(&path).unwrap();
+        assert!(manager.authenticate("alice", "secret").await.is_ok());
+        assert!(manager
+            .authorize_command("alice", "admin", "flushall")
+            .await
+            .is_ok());
+        assert!(matches!(
+            manager.authenticate("old", "pw").await,
+            Err(AuthError::UserNotFound)
+        ));
+    }
 }
@@ -4,12 +4,14 @@
 //! users, access rights, and authentication mechanisms:
 //!
 //! - `acl`: access control logic for managing user permissions.
+//! - `acl_log`: ring buffer of denied access attempts (`ACL LOG`).
 //! - `config`: configuration structures and utilities for authentication
 //!   settings.
 //! - `manager`: central manager for users and access control rules.
 //! - `password`: utilities for password validation and hashing.
 
 pub mod acl;
+pub mod acl_log;
 pub mod config;
 pub mod manager;
 pub mod password;
@@ -19,6 +21,7 @@ pub mod tokens;
 // Publicly re-export all error types and functions from the submodules to
 // simplify access from external code.
 pub use acl::*;
+pub use acl_log::*;
 pub use config::*;
 pub use manager::*;
 pub use password::*;
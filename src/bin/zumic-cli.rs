@@ -320,7 +320,7 @@ fn format_value_for_cli(v: &ZumicValue) -> String {
         ZumicValue::List(list) => {
             let items: Vec<String> = list
                 .iter()
-                .map(|s| String::from_utf8_lossy(s).to_string())
+                .map(|s| String::from_utf8_lossy(&s).to_string())
                 .collect();
             format!("[{}]", items.join(", "))
         }
@@ -374,7 +374,7 @@ fn format_value_raw(v: &ZumicValue) -> String {
         ZumicValue::List(list) => {
             let mut out = format!("*{}\r\n", list.len());
             for item in list.iter() {
-                let s = String::from_utf8_lossy(item);
+                let s = String::from_utf8_lossy(&item);
                 out.push_str(&format!("${}\r\n{}\r\n", s.len(), s));
             }
             out
@@ -416,7 +416,7 @@ fn to_json_value(v: &ZumicValue) -> serde_json::Value {
         ZumicValue::Array(arr) => serde_json::Value::Array(arr.iter().map(to_json_value).collect()),
         ZumicValue::List(list) => serde_json::Value::Array(
             list.iter()
-                .map(|b| json!(String::from_utf8_lossy(b).to_string()))
+                .map(|b| json!(String::from_utf8_lossy(&b).to_string()))
                 .collect(),
         ),
         ZumicValue::Set(set) => {
@@ -1,17 +1,31 @@
-use crate::{CommandExecute, Sds, StorageEngine, StoreError, Value};
+use std::path::PathBuf;
 
+use crate::{
+    category_commands, AclLog, AclLogEntry, AuthManager, CommandExecute, Sds, StorageEngine,
+    StoreError, Value, CATEGORY_NAMES,
+};
+
+/// `AUTH [username] password`. Без имени пользователя (устаревшая форма)
+/// аутентифицирует пользователя `default`.
 #[derive(Debug)]
 pub struct AuthCommand {
-    pub user: String,
+    pub user: Option<String>,
     pub pass: String,
 }
 
+impl AuthCommand {
+    /// Возвращает имя аутентифицируемого пользователя.
+    pub fn username(&self) -> &str {
+        self.user.as_deref().unwrap_or("default")
+    }
+}
+
 impl CommandExecute for AuthCommand {
     fn execute(
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        let user_key = Sds::from_str(&format!("user:{}", self.user));
+        let user_key = Sds::from_str(&format!("user:{}", self.username()));
         match store.get(&user_key)? {
             Some(Value::Str(ref stored_password)) => {
                 let stored_password = stored_password
@@ -34,8 +48,62 @@ impl CommandExecute for AuthCommand {
     }
 }
 
+/// Команда ACL WHOAMI — возвращает имя пользователя текущего соединения.
+#[derive(Debug)]
+pub struct AclWhoAmICommand {
+    /// Пользователь соединения; `default`, пока соединение не прошло `AUTH`.
+    pub username: String,
+}
+
+impl CommandExecute for AclWhoAmICommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        Ok(Value::Str(Sds::from_str(&self.username)))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ACL WHOAMI"
+    }
+}
+
+/// Команда ACL CAT [category] — возвращает категории команд или команды
+/// категории `category`.
+#[derive(Debug)]
+pub struct AclCatCommand {
+    pub category: Option<String>,
+}
+
+impl CommandExecute for AclCatCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let names: &[&str] = match &self.category {
+            None => &CATEGORY_NAMES,
+            Some(category) => category_commands(category).ok_or_else(|| {
+                StoreError::InvalidOperation(format!("Unknown category '{category}'"))
+            })?,
+        };
+        Ok(Value::Array(
+            names
+                .iter()
+                .map(|name| Value::Str(Sds::from_str(name)))
+                .collect(),
+        ))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ACL CAT"
+    }
+}
+
+/// Команда ACL SETUSER username rule [rule ...] — создаёт пользователя или
+/// изменяет его правила.
 #[derive(Debug)]
 pub struct AclSetUserCommand {
+    pub auth: AuthManager,
     pub user: String,
     pub rules: Vec<String>,
 }
@@ -45,7 +113,11 @@ impl CommandExecute for AclSetUserCommand {
         &self,
         _store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ACL SETUSER is not implemented yet")
+        let rules: Vec<&str> = self.rules.iter().map(String::as_str).collect();
+        self.auth
+            .set_user(&self.user, &rules)
+            .map_err(|e| StoreError::InvalidOperation(format!("Error in ACL SETUSER: {e}")))?;
+        Ok(Value::Str(Sds::from_str("OK")))
     }
 
     fn command_name(&self) -> &'static str {
@@ -53,8 +125,12 @@ impl CommandExecute for AclSetUserCommand {
     }
 }
 
+/// Команда ACL GETUSER username — возвращает правила пользователя плоским
+/// массивом пар «поле — значение»: `flags`, `passwords`, `commands`, `keys`
+/// и `channels`.
 #[derive(Debug)]
 pub struct AclGetUserCommand {
+    pub auth: AuthManager,
     pub user: String,
 }
 
@@ -63,7 +139,42 @@ impl CommandExecute for AclGetUserCommand {
         &self,
         _store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ACL GETUSER is not implemented yet")
+        let user = self
+            .auth
+            .get_user(&self.user)
+            .map_err(|e| StoreError::InvalidOperation(e.to_string()))?;
+        let Some(user) = user else {
+            return Ok(Value::Null);
+        };
+
+        let str = |s: &str| Value::Str(Sds::from_str(s));
+        let mut flags = vec![str(if user.enabled { "on" } else { "off" })];
+        if user.password_hashes.is_empty() {
+            flags.push(str("nopass"));
+        }
+        let (mut commands, mut keys, mut channels) = (Vec::new(), Vec::new(), Vec::new());
+        for rule in user.to_rules() {
+            if rule.starts_with('~') || rule.starts_with("-~") {
+                keys.push(rule);
+            } else if rule.starts_with('&') || rule.starts_with("-&") {
+                channels.push(rule);
+            } else if rule.starts_with('+') || rule.starts_with('-') {
+                commands.push(rule);
+            }
+        }
+
+        Ok(Value::Array(vec![
+            str("flags"),
+            Value::Array(flags),
+            str("passwords"),
+            Value::Array(user.password_hashes.iter().map(|h| str(h)).collect()),
+            str("commands"),
+            str(&commands.join(" ")),
+            str("keys"),
+            str(&keys.join(" ")),
+            str("channels"),
+            str(&channels.join(" ")),
+        ]))
     }
 
     fn command_name(&self) -> &'static str {
@@ -71,9 +182,12 @@ impl CommandExecute for AclGetUserCommand {
     }
 }
 
+/// Команда ACL DELUSER username [username ...] — удаляет пользователей и
+/// возвращает число удалённых.
 #[derive(Debug)]
 pub struct AclDelUserCommand {
-    pub user: String,
+    pub auth: AuthManager,
+    pub users: Vec<String>,
 }
 
 impl CommandExecute for AclDelUserCommand {
@@ -81,7 +195,12 @@ impl CommandExecute for AclDelUserCommand {
         &self,
         _store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ACL DELUSER is not implemented yet")
+        let users: Vec<&str> = self.users.iter().map(String::as_str).collect();
+        let deleted = self
+            .auth
+            .delete_users(&users)
+            .map_err(|e| StoreError::InvalidOperation(e.to_string()))?;
+        Ok(Value::Int(deleted as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -89,6 +208,108 @@ impl CommandExecute for AclDelUserCommand {
     }
 }
 
+/// Команда ACL LOG — возвращает последние отказы в доступе, от новых к
+/// старым.
+#[derive(Debug)]
+pub struct AclLogCommand {
+    pub log: AclLog,
+    pub count: Option<usize>,
+}
+
+impl CommandExecute for AclLogCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let entries = self.log.get(self.count);
+        Ok(Value::Array(
+            entries.iter().map(AclLogEntry::to_value).collect(),
+        ))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ACL LOG"
+    }
+}
+
+/// Команда ACL LOG RESET — очищает журнал отказов в доступе.
+#[derive(Debug)]
+pub struct AclLogResetCommand {
+    pub log: AclLog,
+}
+
+impl CommandExecute for AclLogResetCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        self.log.reset();
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ACL LOG RESET"
+    }
+}
+
+/// Команда ACL SAVE — сохраняет пользователей ACL в `aclfile`.
+#[derive(Debug)]
+pub struct AclSaveCommand {
+    pub auth: AuthManager,
+    /// Путь из `Settings::aclfile`; `None`, если ACL-файл не настроен.
+    pub aclfile: Option<PathBuf>,
+}
+
+impl CommandExecute for AclSaveCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let path = self.aclfile.as_ref().ok_or_else(no_aclfile)?;
+        self.auth.save_to_file(path)?;
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ACL SAVE"
+    }
+}
+
+/// Команда ACL LOAD — заменяет пользователей ACL содержимым `aclfile`.
+/// Файл с ошибкой отклоняется целиком, текущие пользователи сохраняются.
+#[derive(Debug)]
+pub struct AclLoadCommand {
+    pub auth: AuthManager,
+    /// Путь из `Settings::aclfile`; `None`, если ACL-файл не настроен.
+    pub aclfile: Option<PathBuf>,
+}
+
+impl CommandExecute for AclLoadCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let path = self.aclfile.as_ref().ok_or_else(no_aclfile)?;
+        self.auth
+            .reload_from_file(path)
+            .map_err(|e| StoreError::InvalidOperation(format!("Error loading ACL file: {e}")))?;
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ACL LOAD"
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Ошибка ACL SAVE/LOAD без настроенного `aclfile`.
+fn no_aclfile() -> StoreError {
+    StoreError::InvalidOperation("This instance is not configured to use an ACL file".into())
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -113,7 +334,7 @@ mod tests {
             .unwrap();
 
         let cmd = AuthCommand {
-            user: "admin".into(),
+            user: Some("admin".into()),
             pass: "secret".into(),
         };
         let result = cmd.execute(&mut store).unwrap();
@@ -131,7 +352,7 @@ mod tests {
             .unwrap();
 
         let cmd = AuthCommand {
-            user: "admin".into(),
+            user: Some("admin".into()),
             pass: "wrongpass".into(),
         };
         let result = cmd.execute(&mut store).unwrap();
@@ -143,7 +364,7 @@ mod tests {
         let mut store = create_store();
 
         let cmd = AuthCommand {
-            user: "ghost".into(),
+            user: Some("ghost".into()),
             pass: "nopass".into(),
         };
         let result = cmd.execute(&mut store).unwrap();
@@ -158,10 +379,160 @@ mod tests {
             .unwrap(); // должно быть строка
 
         let cmd = AuthCommand {
-            user: "admin".into(),
+            user: Some("admin".into()),
             pass: "secret".into(),
         };
         let result = cmd.execute(&mut store);
         assert!(matches!(result, Err(StoreError::InvalidType)));
     }
+
+    #[test]
+    fn test_acl_log_and_reset() {
+        let mut store = create_store();
+        let log = AclLog::default();
+        for object in ["get", "set", "del"] {
+            log.record(crate::AclDenyReason::Command, object, "anton", "");
+        }
+
+        let result = AclLogCommand {
+            log: log.clone(),
+            count: Some(2),
+        }
+        .execute(&mut store)
+        .unwrap();
+        let Value::Array(entries) = result else {
+            panic!("expected array");
+        };
+        assert_eq!(entries.len(), 2);
+        let Value::Array(fields) = &entries[0] else {
+            panic!("expected array");
+        };
+        assert_eq!(fields[5], Value::Str(Sds::from_str("del")));
+
+        let result = AclLogResetCommand { log: log.clone() }
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(result, Value::Str(Sds::from_str("OK")));
+        assert!(log.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_acl_save_and_load() {
+        let mut store = create_store();
+        let dir = tempfile::tempdir().unwrap();
+        let aclfile = Some(dir.path().join("users.acl"));
+        let auth = AuthManager::new();
+        auth.create_user("anton", "pw", &["+@read"]).await.unwrap();
+
+        let missing = AclSaveCommand {
+            auth: auth.clone(),
+            aclfile: None,
+        };
+        assert!(matches!(
+            missing.execute(&mut store),
+            Err(StoreError::InvalidOperation(_))
+        ));
+
+        let save = AclSaveCommand {
+            auth: auth.clone(),
+            aclfile: aclfile.clone(),
+        };
+        assert_eq!(
+            save.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+
+        let other = AuthManager::new();
+        let load = AclLoadCommand {
+            auth: other.clone(),
+            aclfile,
+        };
+        load.execute(&mut store).unwrap();
+        assert!(other.authenticate("anton", "pw").await.is_ok());
+    }
+
+    #[test]
+    fn test_acl_whoami_and_cat() {
+        let mut store = create_store();
+        let whoami = AclWhoAmICommand {
+            username: "anton".into(),
+        };
+        assert_eq!(
+            whoami.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("anton"))
+        );
+
+        let Value::Array(categories) = AclCatCommand { category: None }
+            .execute(&mut store)
+            .unwrap()
+        else {
+            panic!("expected array");
+        };
+        assert_eq!(categories.len(), CATEGORY_NAMES.len());
+
+        let Value::Array(commands) = AclCatCommand {
+            category: Some("write".into()),
+        }
+        .execute(&mut store)
+        .unwrap() else {
+            panic!("expected array");
+        };
+        assert!(commands.contains(&Value::Str(Sds::from_str("set"))));
+
+        let unknown = AclCatCommand {
+            category: Some("nosuch".into()),
+        };
+        assert!(unknown.execute(&mut store).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acl_setuser_getuser_deluser() {
+        let mut store = create_store();
+        let auth = AuthManager::new();
+        let setuser = |rules: &[&str]| AclSetUserCommand {
+            auth: auth.clone(),
+            user: "anton".into(),
+            rules: rules.iter().map(|r| r.to_string()).collect(),
+        };
+
+        setuser(&["on", ">secret", "~data:*", "+@read"])
+            .execute(&mut store)
+            .unwrap();
+        setuser(&["&news", "-get"]).execute(&mut store).unwrap();
+        assert!(setuser(&["+nosuch"]).execute(&mut store).is_err());
+        assert!(auth.authenticate("anton", "secret").await.is_ok());
+
+        let getuser = AclGetUserCommand {
+            auth: auth.clone(),
+            user: "anton".into(),
+        };
+        let Value::Array(fields) = getuser.execute(&mut store).unwrap() else {
+            panic!("expected array");
+        };
+        assert_eq!(fields[0], Value::Str(Sds::from_str("flags")));
+        assert_eq!(
+            fields[1],
+            Value::Array(vec![Value::Str(Sds::from_str("on"))])
+        );
+        assert_eq!(fields[5], Value::Str(Sds::from_str("+@read -get")));
+        assert_eq!(fields[7], Value::Str(Sds::from_str("~data:*")));
+        assert_eq!(fields[9], Value::Str(Sds::from_str("&news")));
+
+        let missing = AclGetUserCommand {
+            auth: auth.clone(),
+            user: "ghost".into(),
+        };
+        assert_eq!(missing.execute(&mut store).unwrap(), Value::Null);
+
+        let deluser = AclDelUserCommand {
+            auth: auth.clone(),
+            users: vec!["anton".into(), "ghost".into()],
+        };
+        assert_eq!(deluser.execute(&mut store).unwrap(), Value::Int(1));
+        let default = AclDelUserCommand {
+            auth,
+            users: vec!["default".into()],
+        };
+        assert!(default.execute(&mut store).is_err());
+    }
 }
@@ -1,7 +1,10 @@
 use std::ops::Not;
 
 use super::CommandExecute;
-use crate::{database::Bitmap, Sds, StorageEngine, StoreError, Value};
+use crate::{
+    database::{BitUnit, Bitmap},
+    Sds, StorageEngine, StoreError, Value,
+};
 
 /// Команда SETBIT — устанавливает значение бита по смещению.
 #[derive(Debug)]
@@ -55,12 +58,13 @@ impl CommandExecute for GetBitCommand {
     }
 }
 
-/// Команда BITCOUNT — считает количество установленных битов в диапазоне.
+/// Команда BITCOUNT — считает количество установленных битов, при наличии
+/// диапазона — только в нём (в байтах или битах согласно `unit`).
 #[derive(Debug)]
 pub struct BitCountCommand {
     pub key: String,
-    pub start: usize,
-    pub end: usize,
+    pub range: Option<(i64, i64)>,
+    pub unit: BitUnit,
 }
 
 impl CommandExecute for BitCountCommand {
@@ -69,7 +73,10 @@ impl CommandExecute for BitCountCommand {
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
         let cnt = if let Some(Value::Bitmap(b)) = store.get(&Sds::from_str(&self.key))? {
-            b.bitcount(self.start, self.end)
+            match self.range {
+                Some((start, end)) => b.bit_count_range(start, end, self.unit),
+                None => b.bitcount_all() as u64,
+            }
         } else {
             0
         };
@@ -131,21 +138,212 @@ impl CommandExecute for BitOpCommand {
     }
 }
 
+/// Тип целочисленного поля BITFIELD (`i1`..`i64`, `u1`..`u63`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitFieldType {
+    pub signed: bool,
+    pub bits: u8,
+}
+
+/// Политика обработки переполнения в BITFIELD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitFieldOverflow {
+    /// Модульная арифметика (по умолчанию).
+    Wrap,
+    /// Насыщение до минимума/максимума типа.
+    Sat,
+    /// Операция не выполняется, в ответе возвращается nil.
+    Fail,
+}
+
+/// Подкоманда BITFIELD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitFieldOp {
+    Get {
+        ty: BitFieldType,
+        offset: u64,
+    },
+    Set {
+        ty: BitFieldType,
+        offset: u64,
+        value: i64,
+    },
+    IncrBy {
+        ty: BitFieldType,
+        offset: u64,
+        increment: i64,
+    },
+    Overflow(BitFieldOverflow),
+}
+
+/// Команда BITFIELD — операции над целыми полями произвольной ширины внутри
+/// bitmap. Все подкоманды выполняются как одна операция: результат
+/// сохраняется один раз в конце.
+#[derive(Debug)]
+pub struct BitFieldCommand {
+    pub key: String,
+    pub ops: Vec<BitFieldOp>,
+}
+
+impl BitFieldCommand {
+    /// Разбирает аргументы команды после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        Ok(Self {
+            key,
+            ops: parse_bitfield_ops(args, false)?,
+        })
+    }
+}
+
+impl CommandExecute for BitFieldCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+        let mut bmp = match store.get(&key)? {
+            Some(Value::Bitmap(b)) => b,
+            Some(_) => return Err(StoreError::InvalidType),
+            None => Bitmap::new(),
+        };
+
+        let mut overflow = BitFieldOverflow::Wrap;
+        let mut modified = false;
+        let mut results = Vec::with_capacity(self.ops.len());
+
+        for op in &self.ops {
+            match *op {
+                BitFieldOp::Get { ty, offset } => {
+                    results.push(Value::Int(read_field(&bmp, ty, offset) as i64));
+                }
+                BitFieldOp::Set { ty, offset, value } => {
+                    let old = read_field(&bmp, ty, offset);
+                    match apply_overflow(value as i128, ty, overflow) {
+                        Some(new) => {
+                            bmp.set_bits(offset, ty.bits, new as u64);
+                            modified = true;
+                            results.push(Value::Int(old as i64));
+                        }
+                        None => results.push(Value::Null),
+                    }
+                }
+                BitFieldOp::IncrBy {
+                    ty,
+                    offset,
+                    increment,
+                } => {
+                    let old = read_field(&bmp, ty, offset);
+                    match apply_overflow(old + increment as i128, ty, overflow) {
+                        Some(new) => {
+                            bmp.set_bits(offset, ty.bits, new as u64);
+                            modified = true;
+                            results.push(Value::Int(new as i64));
+                        }
+                        None => results.push(Value::Null),
+                    }
+                }
+                BitFieldOp::Overflow(policy) => overflow = policy,
+            }
+        }
+
+        if modified {
+            store.set(&key, Value::Bitmap(bmp))?;
+        }
+        Ok(Value::Array(results))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "BITFIELD"
+    }
+}
+
+/// Команда BITFIELD_RO — вариант BITFIELD только для чтения (допускает лишь
+/// `GET`).
+#[derive(Debug)]
+pub struct BitFieldRoCommand {
+    pub key: String,
+    pub fields: Vec<(BitFieldType, u64)>,
+}
+
+impl BitFieldRoCommand {
+    /// Разбирает аргументы команды после ключа; `SET`, `INCRBY` и `OVERFLOW`
+    /// отклоняются до обращения к хранилищу.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let fields = parse_bitfield_ops(args, true)?
+            .into_iter()
+            .filter_map(|op| match op {
+                BitFieldOp::Get { ty, offset } => Some((ty, offset)),
+                _ => None,
+            })
+            .collect();
+        Ok(Self { key, fields })
+    }
+}
+
+impl CommandExecute for BitFieldRoCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let bmp = match store.get(&Sds::from_str(&self.key))? {
+            Some(Value::Bitmap(b)) => b,
+            Some(_) => return Err(StoreError::InvalidType),
+            None => Bitmap::new(),
+        };
+        let results = self
+            .fields
+            .iter()
+            .map(|&(ty, offset)| Value::Int(read_field(&bmp, ty, offset) as i64))
+            .collect();
+        Ok(Value::Array(results))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "BITFIELD_RO"
+    }
+}
+
 /// Команда BITPOS — находит позицию первого бита со значением 0 или 1 в bitmap.
 #[derive(Debug)]
 pub struct BitPosCommand {
     pub key: String,
     pub bit: bool,
-    pub start: Option<usize>,
-    pub end: Option<usize>,
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub unit: BitUnit,
 }
 
 impl CommandExecute for BitPosCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("BITPOS is not implemented yet")
+        let bmp = match store.get(&Sds::from_str(&self.key))? {
+            Some(Value::Bitmap(b)) => b,
+            _ => Bitmap::new(),
+        };
+        let start = self.start.unwrap_or(0);
+        let end = self.end.unwrap_or(-1);
+
+        let pos = match bmp.bitpos(self.bit, start, end, self.unit) {
+            Some(pos) => pos,
+            // Поиск нулевого бита без явного конца диапазона: если все биты
+            // строки равны 1, ответом служит первый бит за её концом.
+            None if !self.bit
+                && self.end.is_none()
+                && (bmp.bit_len() == 0 || bmp.bit_range(start, end, self.unit).is_some()) =>
+            {
+                bmp.bit_len() as i64
+            }
+            None => -1,
+        };
+        Ok(Value::Int(pos))
     }
 
     fn command_name(&self) -> &'static str {
@@ -153,6 +351,178 @@ impl CommandExecute for BitPosCommand {
     }
 }
 
+impl BitFieldType {
+    /// Минимальное значение, представимое типом.
+    fn min(&self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    /// Максимальное значение, представимое типом.
+    fn max(&self) -> i128 {
+        if self.signed {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+}
+
+/// Разбирает тип поля BITFIELD (`i8`, `u16`, ...).
+fn parse_bitfield_type(raw: &str) -> Result<BitFieldType, StoreError> {
+    let invalid = || {
+        StoreError::InvalidArgument(
+            "Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is."
+                .into(),
+        )
+    };
+    let (signed, bits) = match raw.as_bytes().first() {
+        Some(b'i' | b'I') => (true, &raw[1..]),
+        Some(b'u' | b'U') => (false, &raw[1..]),
+        _ => return Err(invalid()),
+    };
+    let bits: u8 = bits.parse().map_err(|_| invalid())?;
+    let max_bits = if signed { 64 } else { 63 };
+    if bits == 0 || bits > max_bits {
+        return Err(invalid());
+    }
+    Ok(BitFieldType { signed, bits })
+}
+
+/// Разбирает смещение поля: `N` — в битах, `#N` — в единицах ширины типа.
+fn parse_bitfield_offset(
+    raw: &str,
+    ty: BitFieldType,
+) -> Result<u64, StoreError> {
+    let invalid =
+        || StoreError::InvalidArgument("bit offset is not an integer or out of range".into());
+    let offset = match raw.strip_prefix('#') {
+        Some(index) => index
+            .parse::<u64>()
+            .ok()
+            .and_then(|i| i.checked_mul(ty.bits as u64)),
+        None => raw.parse::<u64>().ok(),
+    }
+    .ok_or_else(invalid)?;
+    // Ограничение как у SETBIT в Redis: не более 512 МБ данных.
+    if offset + ty.bits as u64 > (u32::MAX as u64) + 1 {
+        return Err(invalid());
+    }
+    Ok(offset)
+}
+
+/// Разбирает список подкоманд BITFIELD. При `read_only` (BITFIELD_RO)
+/// допускается только `GET`, остальные подкоманды отклоняются при разборе.
+pub fn parse_bitfield_ops(
+    args: &[&str],
+    read_only: bool,
+) -> Result<Vec<BitFieldOp>, StoreError> {
+    let syntax = || StoreError::Syntax("syntax error".into());
+    let parse_int = |raw: &str| {
+        raw.parse::<i64>().map_err(|_| {
+            StoreError::InvalidArgument("value is not an integer or out of range".into())
+        })
+    };
+
+    let mut ops = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let sub = args[i].to_ascii_uppercase();
+        if read_only && sub != "GET" {
+            return Err(StoreError::InvalidArgument(
+                "BITFIELD_RO only supports the GET subcommand".into(),
+            ));
+        }
+        match sub.as_str() {
+            "GET" => {
+                let [ty, offset] = args.get(i + 1..i + 3).ok_or_else(syntax)? else {
+                    return Err(syntax());
+                };
+                let ty = parse_bitfield_type(ty)?;
+                let offset = parse_bitfield_offset(offset, ty)?;
+                ops.push(BitFieldOp::Get { ty, offset });
+                i += 3;
+            }
+            "SET" | "INCRBY" => {
+                let [ty, offset, num] = args.get(i + 1..i + 4).ok_or_else(syntax)? else {
+                    return Err(syntax());
+                };
+                let ty = parse_bitfield_type(ty)?;
+                let offset = parse_bitfield_offset(offset, ty)?;
+                let num = parse_int(num)?;
+                ops.push(if sub == "SET" {
+                    BitFieldOp::Set {
+                        ty,
+                        offset,
+                        value: num,
+                    }
+                } else {
+                    BitFieldOp::IncrBy {
+                        ty,
+                        offset,
+                        increment: num,
+                    }
+                });
+                i += 4;
+            }
+            "OVERFLOW" => {
+                let policy = args.get(i + 1).ok_or_else(syntax)?;
+                let policy = match policy.to_ascii_uppercase().as_str() {
+                    "WRAP" => BitFieldOverflow::Wrap,
+                    "SAT" => BitFieldOverflow::Sat,
+                    "FAIL" => BitFieldOverflow::Fail,
+                    _ => {
+                        return Err(StoreError::InvalidArgument(
+                            "Invalid OVERFLOW type specified".into(),
+                        ))
+                    }
+                };
+                ops.push(BitFieldOp::Overflow(policy));
+                i += 2;
+            }
+            _ => return Err(syntax()),
+        }
+    }
+    Ok(ops)
+}
+
+/// Читает поле BITFIELD с учётом знаковости типа.
+fn read_field(
+    bmp: &Bitmap,
+    ty: BitFieldType,
+    offset: u64,
+) -> i128 {
+    let raw = bmp.get_bits(offset, ty.bits) as i128;
+    if ty.signed && raw > ty.max() {
+        raw - (1i128 << ty.bits)
+    } else {
+        raw
+    }
+}
+
+/// Применяет политику переполнения к значению. Возвращает `None`, если
+/// операция должна быть пропущена (`OVERFLOW FAIL`).
+fn apply_overflow(
+    value: i128,
+    ty: BitFieldType,
+    overflow: BitFieldOverflow,
+) -> Option<i128> {
+    if (ty.min()..=ty.max()).contains(&value) {
+        return Some(value);
+    }
+    match overflow {
+        BitFieldOverflow::Wrap => {
+            let modulus = 1i128 << ty.bits;
+            Some((value - ty.min()).rem_euclid(modulus) + ty.min())
+        }
+        BitFieldOverflow::Sat => Some(value.clamp(ty.min(), ty.max())),
+        BitFieldOverflow::Fail => None,
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -232,8 +602,8 @@ mod tests {
         // считаем с 0 до 8 -> должно быть 3
         let cnt_all = BitCountCommand {
             key: key.clone(),
-            start: 0,
-            end: 8,
+            range: Some((0, 7)),
+            unit: BitUnit::Bit,
         }
         .execute(&mut store)
         .unwrap();
@@ -242,8 +612,8 @@ mod tests {
         // с 1 до 3 -> только бит 3 -> 1
         let cnt_sub = BitCountCommand {
             key: key.clone(),
-            start: 1,
-            end: 4,
+            range: Some((1, 3)),
+            unit: BitUnit::Bit,
         }
         .execute(&mut store)
         .unwrap();
@@ -300,8 +670,8 @@ mod tests {
         .unwrap();
         let and_cnt = BitCountCommand {
             key: "AND".into(),
-            start: 0,
-            end: 8,
+            range: None,
+            unit: BitUnit::Byte,
         }
         .execute(&mut store)
         .unwrap();
@@ -317,8 +687,8 @@ mod tests {
         .unwrap();
         let or_cnt = BitCountCommand {
             key: "OR".into(),
-            start: 0,
-            end: 8,
+            range: None,
+            unit: BitUnit::Byte,
         }
         .execute(&mut store)
         .unwrap();
@@ -334,8 +704,8 @@ mod tests {
         .unwrap();
         let xor_cnt = BitCountCommand {
             key: "XOR".into(),
-            start: 0,
-            end: 8,
+            range: None,
+            unit: BitUnit::Byte,
         }
         .execute(&mut store)
         .unwrap();
@@ -350,4 +720,255 @@ mod tests {
         .execute(&mut store);
         assert!(err.is_err());
     }
+
+    fn bitfield(
+        store: &mut StorageEngine,
+        args: &[&str],
+    ) -> Value {
+        BitFieldCommand::parse("bf".into(), args)
+            .unwrap()
+            .execute(store)
+            .unwrap()
+    }
+
+    /// Тест проверяет SET/GET/INCRBY для знаковых и беззнаковых полей,
+    /// включая невыровненные смещения и смещения вида `#N`.
+    #[test]
+    fn test_bitfield_get_set_incrby() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+
+        assert_eq!(
+            bitfield(&mut store, &["SET", "u8", "0", "200", "GET", "u8", "0"]),
+            Value::Array(vec![Value::Int(0), Value::Int(200)])
+        );
+        // Те же биты как i8: 200 - 256 = -56.
+        assert_eq!(
+            bitfield(&mut store, &["GET", "i8", "0"]),
+            Value::Array(vec![Value::Int(-56)])
+        );
+        assert_eq!(
+            bitfield(
+                &mut store,
+                &["set", "i5", "#2", "-3", "incrby", "i5", "#2", "2"]
+            ),
+            Value::Array(vec![Value::Int(0), Value::Int(-1)])
+        );
+        assert_eq!(
+            bitfield(&mut store, &["INCRBY", "u4", "20", "7", "GET", "u4", "20"]),
+            Value::Array(vec![Value::Int(7), Value::Int(7)])
+        );
+        assert_eq!(
+            bitfield(&mut store, &["SET", "i64", "64", "-1", "GET", "i64", "64"]),
+            Value::Array(vec![Value::Int(0), Value::Int(-1)])
+        );
+    }
+
+    /// Тест проверяет политики переполнения WRAP, SAT и FAIL.
+    #[test]
+    fn test_bitfield_overflow() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+        bitfield(
+            &mut store,
+            &["SET", "u8", "0", "250", "SET", "i8", "8", "120"],
+        );
+
+        assert_eq!(
+            bitfield(&mut store, &["INCRBY", "u8", "0", "10"]),
+            Value::Array(vec![Value::Int(4)])
+        );
+        assert_eq!(
+            bitfield(
+                &mut store,
+                &["OVERFLOW", "SAT", "INCRBY", "u8", "0", "-10", "INCRBY", "i8", "8", "100"]
+            ),
+            Value::Array(vec![Value::Int(0), Value::Int(127)])
+        );
+        assert_eq!(
+            bitfield(
+                &mut store,
+                &["OVERFLOW", "FAIL", "INCRBY", "i8", "8", "1", "GET", "i8", "8"]
+            ),
+            Value::Array(vec![Value::Null, Value::Int(127)])
+        );
+        // WRAP для знакового типа.
+        assert_eq!(
+            bitfield(&mut store, &["OVERFLOW", "WRAP", "INCRBY", "i8", "8", "1"]),
+            Value::Array(vec![Value::Int(-128)])
+        );
+    }
+
+    /// Тест проверяет, что только чтение не создаёт ключ, а некорректные
+    /// аргументы отклоняются при разборе.
+    #[test]
+    fn test_bitfield_readonly_and_parse_errors() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+        assert_eq!(
+            bitfield(&mut store, &["GET", "u8", "0"]),
+            Value::Array(vec![Value::Int(0)])
+        );
+        assert_eq!(store.get(&Sds::from_str("bf")).unwrap(), None);
+
+        let cases: [&[&str]; 8] = [
+            &["GET", "u64", "0"],
+            &["GET", "i0", "0"],
+            &["GET", "x8", "0"],
+            &["GET", "u8", "-1"],
+            &["GET", "u8"],
+            &["SET", "u8", "0", "abc"],
+            &["OVERFLOW", "NONE"],
+            &["FOO"],
+        ];
+        for args in cases {
+            assert!(
+                BitFieldCommand::parse("bf".into(), args).is_err(),
+                "{args:?}"
+            );
+        }
+    }
+
+    fn bitpos(
+        store: &mut StorageEngine,
+        bit: bool,
+        start: Option<i64>,
+        end: Option<i64>,
+        unit: BitUnit,
+    ) -> Value {
+        BitPosCommand {
+            key: "bp".into(),
+            bit,
+            start,
+            end,
+            unit,
+        }
+        .execute(store)
+        .unwrap()
+    }
+
+    /// Тест проверяет BITPOS для единичного бита: поиск в байтовом и битовом
+    /// диапазонах и -1, если бит не найден.
+    #[test]
+    fn test_bitpos_set_bit() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+        assert_eq!(
+            bitpos(&mut store, true, None, None, BitUnit::Byte),
+            Value::Int(-1)
+        );
+
+        SetBitCommand {
+            key: "bp".into(),
+            offset: 19,
+            value: true,
+        }
+        .execute(&mut store)
+        .unwrap();
+
+        assert_eq!(
+            bitpos(&mut store, true, None, None, BitUnit::Byte),
+            Value::Int(19)
+        );
+        assert_eq!(
+            bitpos(&mut store, true, Some(-1), None, BitUnit::Byte),
+            Value::Int(19)
+        );
+        assert_eq!(
+            bitpos(&mut store, true, Some(0), Some(1), BitUnit::Byte),
+            Value::Int(-1)
+        );
+        assert_eq!(
+            bitpos(&mut store, true, Some(17), Some(20), BitUnit::Bit),
+            Value::Int(19)
+        );
+    }
+
+    /// Тест проверяет BITPOS для нулевого бита: без явного конца диапазона
+    /// результат указывает за конец строки, с явным концом — -1.
+    #[test]
+    fn test_bitpos_clear_bit() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+        assert_eq!(
+            bitpos(&mut store, false, None, None, BitUnit::Byte),
+            Value::Int(0)
+        );
+
+        for offset in 0..16 {
+            SetBitCommand {
+                key: "bp".into(),
+                offset,
+                value: true,
+            }
+            .execute(&mut store)
+            .unwrap();
+        }
+
+        assert_eq!(
+            bitpos(&mut store, false, None, None, BitUnit::Byte),
+            Value::Int(16)
+        );
+        assert_eq!(
+            bitpos(&mut store, false, Some(0), Some(-1), BitUnit::Byte),
+            Value::Int(-1)
+        );
+        assert_eq!(
+            bitpos(&mut store, false, Some(5), None, BitUnit::Byte),
+            Value::Int(-1)
+        );
+    }
+
+    /// Тест проверяет BITCOUNT с байтовым диапазоном (включая отрицательные
+    /// границы), `start > end` и отсутствующий ключ.
+    #[test]
+    fn test_bitcount_byte_range() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+        let count = |store: &mut StorageEngine, range| {
+            BitCountCommand {
+                key: "bc".into(),
+                range,
+                unit: BitUnit::Byte,
+            }
+            .execute(store)
+            .unwrap()
+        };
+        assert_eq!(count(&mut store, None), Value::Int(0));
+
+        for &off in &[1, 9, 10, 23] {
+            SetBitCommand {
+                key: "bc".into(),
+                offset: off,
+                value: true,
+            }
+            .execute(&mut store)
+            .unwrap();
+        }
+
+        assert_eq!(count(&mut store, None), Value::Int(4));
+        assert_eq!(count(&mut store, Some((1, 1))), Value::Int(2));
+        assert_eq!(count(&mut store, Some((-1, -1))), Value::Int(1));
+        assert_eq!(count(&mut store, Some((2, 0))), Value::Int(0));
+    }
+
+    /// Тест проверяет, что BITFIELD_RO читает поля, записанные BITFIELD, и
+    /// отклоняет изменяющие подкоманды на этапе разбора.
+    #[test]
+    fn test_bitfield_ro() {
+        let mut store = StorageEngine::Memory(InMemoryStore::new());
+        bitfield(&mut store, &["SET", "i8", "#1", "-5"]);
+
+        let cmd =
+            BitFieldRoCommand::parse("bf".into(), &["GET", "i8", "#1", "get", "u4", "0"]).unwrap();
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Array(vec![Value::Int(-5), Value::Int(0)])
+        );
+
+        for args in [
+            ["SET", "u8", "0", "1"],
+            ["INCRBY", "u8", "0", "1"],
+            ["GET", "u8", "0", "OVERFLOW"],
+        ] {
+            assert!(matches!(
+                BitFieldRoCommand::parse("bf".into(), &args),
+                Err(StoreError::InvalidArgument(_))
+            ));
+        }
+    }
 }
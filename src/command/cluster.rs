@@ -0,0 +1,246 @@
+use super::CommandExecute;
+use crate::{engine::InClusterStore, Sds, StorageEngine, StoreError, Value};
+
+/// Действие команды CLUSTER SETSLOT над слотом.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetSlotAction {
+    /// `IMPORTING <shard>` — перенести ключи слота с указанного shard'а на
+    /// shard, за которым слот закреплён.
+    Importing(usize),
+    /// `MIGRATING <shard>` — начать фоновый перенос ключей слота на shard.
+    Migrating(usize),
+    /// `NODE <shard>` — закрепить слот за shard'ом, дождавшись переноса.
+    Node(usize),
+    /// `STABLE` — остановить миграцию слота.
+    Stable,
+}
+
+/// Команда CLUSTER SETSLOT — управляет миграцией слота между shard'ами.
+///
+/// Миграции `IMPORTING` и `MIGRATING` идут в фоне: команда возвращает `OK`
+/// сразу после запуска.
+#[derive(Debug)]
+pub struct ClusterSetSlotCommand {
+    pub slot: u16,
+    pub action: SetSlotAction,
+}
+
+impl CommandExecute for ClusterSetSlotCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let cluster = cluster_store(store)?;
+        match self.action {
+            SetSlotAction::Importing(source) => {
+                cluster.import_slot(self.slot, source)?;
+            }
+            SetSlotAction::Migrating(target) => {
+                cluster.migrate_slot(self.slot, target)?;
+            }
+            SetSlotAction::Node(target) => cluster.set_slot_node(self.slot, target)?,
+            SetSlotAction::Stable => {
+                cluster.cancel_slot_migration(self.slot);
+            }
+        }
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "CLUSTER SETSLOT"
+    }
+}
+
+/// Команда CLUSTER INFO — сводка состояния кластера.
+#[derive(Debug)]
+pub struct ClusterInfoCommand;
+
+impl CommandExecute for ClusterInfoCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let info = cluster_store(store)?.cluster_info();
+        Ok(Value::Str(Sds::from_str(&info.to_info_string())))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "CLUSTER INFO"
+    }
+}
+
+/// Команда CLUSTER NODES — таблица узлов кластера.
+#[derive(Debug)]
+pub struct ClusterNodesCommand;
+
+impl CommandExecute for ClusterNodesCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let info = cluster_store(store)?.cluster_info();
+        Ok(Value::Str(Sds::from_str(&info.to_nodes_table())))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "CLUSTER NODES"
+    }
+}
+
+/// Команда CLUSTER MYID — идентификатор этого узла.
+#[derive(Debug)]
+pub struct ClusterMyIdCommand;
+
+impl CommandExecute for ClusterMyIdCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        Ok(Value::Str(Sds::from_str(cluster_store(store)?.node_id())))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "CLUSTER MYID"
+    }
+}
+
+/// Команда CLUSTER FAILOVER — переключает shard на его реплику.
+#[derive(Debug)]
+pub struct ClusterFailoverCommand {
+    pub shard: usize,
+}
+
+impl CommandExecute for ClusterFailoverCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        cluster_store(store)?.failover(self.shard)?;
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "CLUSTER FAILOVER"
+    }
+}
+
+fn cluster_store(store: &StorageEngine) -> Result<&InClusterStore, StoreError> {
+    match store {
+        StorageEngine::Cluster(cluster) => Ok(cluster),
+        _ => Err(StoreError::InvalidOperation(
+            "This instance has cluster support disabled".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        engine::{metrics::HealthStatus, MigrationState},
+        InMemoryStore, SlotManager, Storage,
+    };
+
+    /// Тест проверяет, что SETSLOT NODE закрепляет слот за shard'ом, а без
+    /// кластера команда возвращает ошибку.
+    #[test]
+    fn test_cluster_setslot_node() {
+        let shards: Vec<Arc<dyn Storage>> = (0..2)
+            .map(|_| Arc::new(InMemoryStore::new()) as Arc<dyn Storage>)
+            .collect();
+        let mut store = StorageEngine::Cluster(InClusterStore::new(shards.clone()));
+        store.set(&Sds::from_str("{tag}:a"), Value::Int(1)).unwrap();
+        let owner = shards
+            .iter()
+            .position(|s| s.dbsize().unwrap() == 1)
+            .unwrap();
+        let slot = SlotManager::new(2).calculate_slot("{tag}:a");
+
+        let cmd = ClusterSetSlotCommand {
+            slot,
+            action: SetSlotAction::Node(1 - owner),
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        let StorageEngine::Cluster(cluster) = &store else {
+            unreachable!()
+        };
+        assert_eq!(
+            cluster.slot_state(slot),
+            MigrationState::Complete { to: 1 - owner }
+        );
+        assert_eq!(shards[1 - owner].dbsize().unwrap(), 1);
+        assert_eq!(
+            store.get(&Sds::from_str("{tag}:a")).unwrap(),
+            Some(Value::Int(1))
+        );
+
+        let mut memory = StorageEngine::Memory(InMemoryStore::new());
+        assert!(matches!(
+            cmd.execute(&mut memory),
+            Err(StoreError::InvalidOperation(_))
+        ));
+    }
+
+    /// Тест проверяет CLUSTER INFO, NODES и MYID, а также failover shard'а
+    /// на реплику с обновлением топологии.
+    #[test]
+    fn test_cluster_info_nodes_failover() {
+        let shards: Vec<Arc<dyn Storage>> = (0..2)
+            .map(|_| Arc::new(InMemoryStore::new()) as Arc<dyn Storage>)
+            .collect();
+        let cluster = InClusterStore::new(shards.clone()).with_node_id("node-a");
+        let replica: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        cluster.add_replica(0, replica.clone()).unwrap();
+        let mut store = StorageEngine::Cluster(cluster);
+
+        let info = ClusterInfoCommand.execute(&mut store).unwrap();
+        let Value::Str(info) = info else {
+            panic!("expected string")
+        };
+        let info = info.to_string();
+        assert!(info.contains("cluster_enabled:1\r\n"));
+        assert!(info.contains("cluster_state:ok\r\n"));
+        assert!(info.contains("cluster_slots_assigned:16384\r\n"));
+        assert!(info.contains("cluster_known_nodes:3\r\n"));
+
+        assert_eq!(
+            ClusterMyIdCommand.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("node-a"))
+        );
+
+        let StorageEngine::Cluster(cluster) = &store else {
+            unreachable!()
+        };
+        let before = cluster.cluster_info();
+        assert_eq!(before.state(), HealthStatus::Healthy);
+        let primary_id = before.shards[0].primary.node_id.clone();
+        let replica_id = before.shards[0].replicas[0].node_id.clone();
+        let Value::Str(nodes) = ClusterNodesCommand.execute(&mut store).unwrap() else {
+            panic!("expected string")
+        };
+        let nodes = nodes.to_string();
+        assert_eq!(nodes.lines().count(), 3);
+        assert!(nodes.contains(&format!("{replica_id} :0@0 slave {primary_id} ")));
+
+        ClusterFailoverCommand { shard: 0 }
+            .execute(&mut store)
+            .unwrap();
+        let StorageEngine::Cluster(cluster) = &store else {
+            unreachable!()
+        };
+        let after = cluster.cluster_info();
+        assert_eq!(after.shards[0].primary.node_id, replica_id);
+        assert_eq!(after.shards[0].replicas[0].node_id, primary_id);
+        assert_eq!(after.current_epoch, before.current_epoch + 1);
+
+        assert!(matches!(
+            ClusterFailoverCommand { shard: 1 }.execute(&mut store),
+            Err(StoreError::InvalidOperation(_))
+        ));
+    }
+}
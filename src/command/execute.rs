@@ -8,27 +8,45 @@ use std::time::Instant;
 
 use super::{
     pubsub::{PSubscribeCommand, PUnsubscribeCommand},
-    AppendCommand, AuthCommand, BitCountCommand, BitOpCommand, BitPosCommand, DecrByCommand,
-    DecrCommand, DelCommand, ExistsCommand, FlushDbCommand, GeoAddCommand, GeoPosCommand,
-    GeoRadiusByMemberCommand, GeoRadiusCommand, GetBitCommand, GetCommand, GetDistCommand,
-    GetRangeCommand, HDelCommand, HExistsCommand, HGetAllCommand, HGetCommand, HIncrByCommand,
-    HIncrByFloatCommand, HKeysCommand, HLenCommand, HRandFieldCommand, HSetCommand, HValsCommand,
-    HmGetCommand, IncrByCommand, IncrByFloatCommand, IncrCommand, LLenCommand, LPopCommand,
-    LPushCommand, LRangeCommand, LRemCommand, LSetCommand, MGetCommand, MSetCommand, PfAddCommand,
-    PfCountCommand, PfMergeCommand, RPopCommand, RPushCommand, RenameCommand, RenameNxCommand,
-    SAddCommand, SCardCommand, SDiffCommand, SInterCommand, SIsMemberCommand, SMembersCommand,
-    SPopCommand, SRandMemberCommand, SRemCommand, SUnionCommand, SetBitCommand, SetCommand,
-    SetFloatCommand, SetNxCommand, StrLenCommand, XAckCommand, XAddCommand, XDelCommand,
-    XGroupCreateCommand, XLenCommand, XRangeCommand, XReadCommand, XRevRangeCommand, XTrimCommand,
-    ZAddCommand, ZCardCommand, ZCountCommand, ZIncrByCommand, ZRangeCommand, ZRankCommand,
-    ZRemCommand, ZRevRangeCommand, ZRevRankCommand, ZScoreCommand,
+    AclCatCommand, AclDelUserCommand, AclGetUserCommand, AclLoadCommand, AclLogCommand,
+    AclLogResetCommand, AclSaveCommand, AclSetUserCommand, AclWhoAmICommand, AppendCommand,
+    AuthCommand, BitCountCommand, BitFieldCommand, BitFieldRoCommand, BitOpCommand, BitPosCommand,
+    CopyCommand, DecrByCommand, DecrCommand, DelCommand, DumpCommand, ExistsCommand,
+    ExpireTimeCommand, FlushAllCommand, FlushDbCommand, GeoAddCommand, GeoPosCommand,
+    GeoRadiusByMemberCommand, GeoRadiusCommand, GeoSearchCommand, GeoSearchStoreCommand,
+    GetBitCommand, GetCommand, GetDelCommand, GetDistCommand, GetExCommand, GetRangeCommand,
+    HDelCommand, HExistsCommand, HGetAllCommand, HGetCommand, HIncrByCommand, HIncrByFloatCommand,
+    HKeysCommand, HLenCommand, HRandFieldCommand, HScanCommand, HSetCommand, HValsCommand,
+    HmGetCommand, IncrByCommand, IncrByFloatCommand, IncrCommand, KeysCommand, LIndexCommand,
+    LInsertCommand, LLenCommand, LMoveCommand, LPopCommand, LPosCommand, LPushCommand,
+    LRangeCommand, LRemCommand, LSetCommand, LTrimCommand, LmPopCommand, MGetCommand, MSetCommand,
+    ObjectEncodingCommand, ObjectFreqCommand, ObjectIdleTimeCommand, PExpireTimeCommand,
+    PTtlCommand, PersistCommand, PfAddCommand, PfCountCommand, PfMergeCommand, RPopCommand,
+    RPushCommand, RenameCommand, RenameNxCommand, RestoreCommand, SAddCommand, SCardCommand,
+    SDiffCommand, SDiffStoreCommand, SInterCardCommand, SInterCommand, SInterStoreCommand,
+    SIsMemberCommand, SMembersCommand, SMoveCommand, SPopCommand, SRandMemberCommand, SRemCommand,
+    SScanCommand, SUnionCommand, SUnionStoreCommand, SetBitCommand, SetCommand, SetFloatCommand,
+    SetNxCommand, SetRangeCommand, SmIsMemberCommand, SortCommand, StrLenCommand, TouchCommand,
+    TtlCommand, TypeCommand, XAckCommand, XAddCommand, XAutoClaimCommand, XClaimCommand,
+    XDelCommand, XGroupCreateCommand, XInfoCommand, XLenCommand, XPendingCommand, XRangeCommand,
+    XReadCommand, XRevRangeCommand, XTrimCommand, ZAddCommand, ZCardCommand, ZCountCommand,
+    ZDiffCommand, ZDiffStoreCommand, ZIncrByCommand, ZInterCommand, ZInterStoreCommand,
+    ZLexCountCommand, ZPopMaxCommand, ZPopMinCommand, ZRandMemberCommand, ZRangeByLexCommand,
+    ZRangeCommand, ZRankCommand, ZRemCommand, ZRevRangeByLexCommand, ZRevRangeCommand,
+    ZRevRankCommand, ZScanCommand, ZScoreCommand, ZUnionCommand, ZUnionStoreCommand, ZmPopCommand,
+    ZmScoreCommand,
 };
 use crate::{
     command::{
         pubsub::{PubSubCommand, PublishCommand, SubscribeCommand, UnsubscribeCommand},
-        BgSaveCommand, DbSizeCommand, EchoCommand, InfoCommand, PingCommand, SaveCommand,
-        SelectCommand, ShutdownCommand, TimeCommand, TsAddCommand, TsCreateCommand, TsDelCommand,
-        TsGetCommand, TsRangeCommand,
+        BgSaveCommand, ClientKillCommand, ClusterFailoverCommand, ClusterInfoCommand,
+        ClusterMyIdCommand, ClusterNodesCommand, ClusterSetSlotCommand, DbSizeCommand, EchoCommand,
+        EvalCommand, EvalShaCommand, FCallCommand, FCallRoCommand, FunctionDeleteCommand,
+        FunctionDumpCommand, FunctionListCommand, FunctionLoadCommand, FunctionRestoreCommand,
+        InfoCommand, MemoryUsageCommand, MonitorCommand, PingCommand, ResetCommand, SaveCommand,
+        SelectCommand, ShutdownCommand, SlowLogGetCommand, SlowLogLenCommand, SlowLogResetCommand,
+        SwapDbCommand, TimeCommand, TsAddCommand, TsCreateCommand, TsDelCommand, TsGetCommand,
+        TsRangeCommand,
     },
     logging::slow_log::SlowQueryTracker,
     StorageEngine, StoreError, Value,
@@ -87,12 +105,31 @@ pub enum Command {
     MGet(MGetCommand),
     Strlen(StrLenCommand),
     Append(AppendCommand),
+    SetRange(SetRangeCommand),
     GetRange(GetRangeCommand),
+    GetDel(GetDelCommand),
+    GetEx(GetExCommand),
     Del(DelCommand),
     Exists(ExistsCommand),
     Rename(RenameCommand),
     Renamenx(RenameNxCommand),
     Flushdb(FlushDbCommand),
+    Flushall(FlushAllCommand),
+    Copy(CopyCommand),
+    ExpireTime(ExpireTimeCommand),
+    PExpireTime(PExpireTimeCommand),
+    Persist(PersistCommand),
+    Ttl(TtlCommand),
+    PTtl(PTtlCommand),
+    Dump(DumpCommand),
+    Restore(RestoreCommand),
+    Sort(SortCommand),
+    Keys(KeysCommand),
+    Type(TypeCommand),
+    Touch(TouchCommand),
+    ObjectEncoding(ObjectEncodingCommand),
+    ObjectIdleTime(ObjectIdleTimeCommand),
+    ObjectFreq(ObjectFreqCommand),
     Incr(IncrCommand),
     Incrby(IncrByCommand),
     Decr(DecrCommand),
@@ -110,6 +147,7 @@ pub enum Command {
     HVals(HValsCommand),
     HGetall(HGetAllCommand),
     HRandField(HRandFieldCommand),
+    HScan(HScanCommand),
     HIncrBy(HIncrByCommand),
     HIncrByFloat(HIncrByFloatCommand),
     HPfAdd(PfAddCommand),
@@ -119,9 +157,16 @@ pub enum Command {
     SRem(SRemCommand),
     SCard(SCardCommand),
     SMembers(SMembersCommand),
+    SScan(SScanCommand),
     SIsmember(SIsMemberCommand),
+    SmIsMember(SmIsMemberCommand),
     SRandMember(SRandMemberCommand),
     SPop(SPopCommand),
+    SMove(SMoveCommand),
+    SInterCard(SInterCardCommand),
+    SDiffStore(SDiffStoreCommand),
+    SUnionStore(SUnionStoreCommand),
+    SInterStore(SInterStoreCommand),
     SInter(SInterCommand),
     SUnion(SUnionCommand),
     SDiff(SDiffCommand),
@@ -129,12 +174,27 @@ pub enum Command {
     ZRem(ZRemCommand),
     ZRange(ZRangeCommand),
     ZScore(ZScoreCommand),
+    ZScan(ZScanCommand),
+    ZmScore(ZmScoreCommand),
     ZCard(ZCardCommand),
     ZRevrange(ZRevRangeCommand),
     ZRank(ZRankCommand),
     ZRevRank(ZRevRankCommand),
     ZCount(ZCountCommand),
     ZIncrBy(ZIncrByCommand),
+    ZRangeByLex(ZRangeByLexCommand),
+    ZRevRangeByLex(ZRevRangeByLexCommand),
+    ZLexCount(ZLexCountCommand),
+    ZPopMin(ZPopMinCommand),
+    ZmPop(ZmPopCommand),
+    ZUnion(ZUnionCommand),
+    ZInter(ZInterCommand),
+    ZDiff(ZDiffCommand),
+    ZUnionStore(ZUnionStoreCommand),
+    ZInterStore(ZInterStoreCommand),
+    ZDiffStore(ZDiffStoreCommand),
+    ZPopMax(ZPopMaxCommand),
+    ZRandMember(ZRandMemberCommand),
     LSet(LSetCommand),
     LRem(LRemCommand),
     LPush(LPushCommand),
@@ -143,17 +203,36 @@ pub enum Command {
     RPop(RPopCommand),
     LLen(LLenCommand),
     LRange(LRangeCommand),
+    LPos(LPosCommand),
+    LMove(LMoveCommand),
+    LmPop(LmPopCommand),
+    LInsert(LInsertCommand),
+    LIndex(LIndexCommand),
+    LTrim(LTrimCommand),
     Auth(AuthCommand),
+    AclLog(AclLogCommand),
+    AclLogReset(AclLogResetCommand),
+    AclSave(AclSaveCommand),
+    AclLoad(AclLoadCommand),
+    AclWhoAmI(AclWhoAmICommand),
+    AclCat(AclCatCommand),
+    AclSetUser(AclSetUserCommand),
+    AclGetUser(AclGetUserCommand),
+    AclDelUser(AclDelUserCommand),
     GeoAdd(GeoAddCommand),
     GeoDist(GetDistCommand),
     GeoPos(GeoPosCommand),
     GeoRadius(GeoRadiusCommand),
     GeoRadiusByMember(GeoRadiusByMemberCommand),
+    GeoSearchStore(GeoSearchStoreCommand),
+    GeoSearch(GeoSearchCommand),
     SetBit(SetBitCommand),
     GetBit(GetBitCommand),
     BitCount(BitCountCommand),
     BitOp(BitOpCommand),
     BitPos(BitPosCommand),
+    BitField(BitFieldCommand),
+    BitFieldRo(BitFieldRoCommand),
     Subscribe(SubscribeCommand),
     Unsubscribe(UnsubscribeCommand),
     Publish(PublishCommand),
@@ -166,9 +245,31 @@ pub enum Command {
     Info(InfoCommand),
     Time(TimeCommand),
     Select(SelectCommand),
+    SwapDb(SwapDbCommand),
+    ClusterSetSlot(ClusterSetSlotCommand),
+    ClusterInfo(ClusterInfoCommand),
+    ClusterNodes(ClusterNodesCommand),
+    ClusterMyId(ClusterMyIdCommand),
+    ClusterFailover(ClusterFailoverCommand),
     Save(SaveCommand),
     BgSave(BgSaveCommand),
     Shutdown(ShutdownCommand),
+    ClientKill(ClientKillCommand),
+    Monitor(MonitorCommand),
+    Reset(ResetCommand),
+    MemoryUsage(MemoryUsageCommand),
+    SlowLogGet(SlowLogGetCommand),
+    SlowLogLen(SlowLogLenCommand),
+    SlowLogReset(SlowLogResetCommand),
+    Eval(EvalCommand),
+    EvalSha(EvalShaCommand),
+    FunctionLoad(FunctionLoadCommand),
+    FCall(FCallCommand),
+    FCallRo(FCallRoCommand),
+    FunctionList(FunctionListCommand),
+    FunctionDelete(FunctionDeleteCommand),
+    FunctionDump(FunctionDumpCommand),
+    FunctionRestore(FunctionRestoreCommand),
     XAdd(XAddCommand),
     XRead(XReadCommand),
     XRange(XRangeCommand),
@@ -178,6 +279,10 @@ pub enum Command {
     XTrim(XTrimCommand),
     XGroupCreate(XGroupCreateCommand),
     XAck(XAckCommand),
+    XInfo(XInfoCommand),
+    XPending(XPendingCommand),
+    XClaim(XClaimCommand),
+    XAutoClaim(XAutoClaimCommand),
     TsCreate(TsCreateCommand),
     TsAdd(TsAddCommand),
     TsGet(TsGetCommand),
@@ -199,9 +304,28 @@ impl Command {
             Command::Rename(_) => "RENAME",
             Command::Renamenx(_) => "RENAMENX",
             Command::Flushdb(_) => "FLUSHDB",
+            Command::Flushall(_) => "FLUSHALL",
+            Command::Copy(_) => "COPY",
+            Command::ExpireTime(_) => "EXPIRETIME",
+            Command::PExpireTime(_) => "PEXPIRETIME",
+            Command::Persist(_) => "PERSIST",
+            Command::Ttl(_) => "TTL",
+            Command::PTtl(_) => "PTTL",
+            Command::Dump(_) => "DUMP",
+            Command::Restore(_) => "RESTORE",
+            Command::Sort(_) => "SORT",
+            Command::Keys(_) => "KEYS",
+            Command::Type(_) => "TYPE",
+            Command::Touch(_) => "TOUCH",
+            Command::ObjectEncoding(_) => "OBJECT ENCODING",
+            Command::ObjectIdleTime(_) => "OBJECT IDLETIME",
+            Command::ObjectFreq(_) => "OBJECT FREQ",
             Command::Strlen(_) => "STRLEN",
             Command::Append(_) => "APPEND",
+            Command::SetRange(_) => "SETRANGE",
             Command::GetRange(_) => "GETRANGE",
+            Command::GetDel(_) => "GETDEL",
+            Command::GetEx(_) => "GETEX",
             Command::Incr(_) => "INCR",
             Command::Incrby(_) => "INCRBY",
             Command::Decr(_) => "DECR",
@@ -219,6 +343,7 @@ impl Command {
             Command::HVals(_) => "HVALS",
             Command::HGetall(_) => "HGETALL",
             Command::HRandField(_) => "HRANDFIELD",
+            Command::HScan(_) => "HSCAN",
             Command::HIncrBy(_) => "HINCRBY",
             Command::HIncrByFloat(_) => "HINCRBYFLOAT",
             Command::HPfAdd(_) => "PFADD",
@@ -228,9 +353,16 @@ impl Command {
             Command::SRem(_) => "SREM",
             Command::SCard(_) => "SCARD",
             Command::SMembers(_) => "SMEMBERS",
+            Command::SScan(_) => "SSCAN",
             Command::SIsmember(_) => "SISMEMBER",
+            Command::SmIsMember(_) => "SMISMEMBER",
             Command::SRandMember(_) => "SRANDMEMBER",
             Command::SPop(_) => "SPOP",
+            Command::SMove(_) => "SMOVE",
+            Command::SInterCard(_) => "SINTERCARD",
+            Command::SDiffStore(_) => "SDIFFSTORE",
+            Command::SUnionStore(_) => "SUNIONSTORE",
+            Command::SInterStore(_) => "SINTERSTORE",
             Command::SInter(_) => "SINTER",
             Command::SUnion(_) => "SUNION",
             Command::SDiff(_) => "SDIFF",
@@ -238,12 +370,27 @@ impl Command {
             Command::ZRem(_) => "ZREM",
             Command::ZRange(_) => "ZRANGE",
             Command::ZScore(_) => "ZSCORE",
+            Command::ZScan(_) => "ZSCAN",
+            Command::ZmScore(_) => "ZMSCORE",
             Command::ZCard(_) => "ZCARD",
             Command::ZRevrange(_) => "ZREVRANGE",
             Command::ZRank(_) => "ZRANK",
             Command::ZRevRank(_) => "ZREVRANK",
             Command::ZCount(_) => "ZCOUNT",
             Command::ZIncrBy(_) => "ZINCRBY",
+            Command::ZRangeByLex(_) => "ZRANGEBYLEX",
+            Command::ZRevRangeByLex(_) => "ZREVRANGEBYLEX",
+            Command::ZLexCount(_) => "ZLEXCOUNT",
+            Command::ZPopMin(_) => "ZPOPMIN",
+            Command::ZmPop(_) => "ZMPOP",
+            Command::ZUnion(_) => "ZUNION",
+            Command::ZInter(_) => "ZINTER",
+            Command::ZDiff(_) => "ZDIFF",
+            Command::ZUnionStore(_) => "ZUNIONSTORE",
+            Command::ZInterStore(_) => "ZINTERSTORE",
+            Command::ZDiffStore(_) => "ZDIFFSTORE",
+            Command::ZPopMax(_) => "ZPOPMAX",
+            Command::ZRandMember(_) => "ZRANDMEMBER",
             Command::LSet(_) => "LSET",
             Command::LRem(_) => "LREM",
             Command::LPush(_) => "LPUSH",
@@ -252,17 +399,36 @@ impl Command {
             Command::RPop(_) => "RPOP",
             Command::LLen(_) => "LLEN",
             Command::LRange(_) => "LRANGE",
+            Command::LPos(_) => "LPOS",
+            Command::LMove(_) => "LMOVE",
+            Command::LmPop(_) => "LMPOP",
+            Command::LInsert(_) => "LINSERT",
+            Command::LIndex(_) => "LINDEX",
+            Command::LTrim(_) => "LTRIM",
             Command::Auth(_) => "AUTH",
+            Command::AclLog(_) => "ACL LOG",
+            Command::AclLogReset(_) => "ACL LOG RESET",
+            Command::AclSave(_) => "ACL SAVE",
+            Command::AclLoad(_) => "ACL LOAD",
+            Command::AclWhoAmI(_) => "ACL WHOAMI",
+            Command::AclCat(_) => "ACL CAT",
+            Command::AclSetUser(_) => "ACL SETUSER",
+            Command::AclGetUser(_) => "ACL GETUSER",
+            Command::AclDelUser(_) => "ACL DELUSER",
             Command::GeoAdd(_) => "GEOADD",
             Command::GeoDist(_) => "GEODIST",
             Command::GeoPos(_) => "GEOPOS",
             Command::GeoRadius(_) => "GEORADIUS",
             Command::GeoRadiusByMember(_) => "GEORADIUSBYMEMBER",
+            Command::GeoSearchStore(_) => "GEOSEARCHSTORE",
+            Command::GeoSearch(_) => "GEOSEARCH",
             Command::SetBit(_) => "SETBIT",
             Command::GetBit(_) => "GETBIT",
             Command::BitCount(_) => "BITCOUNT",
             Command::BitOp(_) => "BITOP",
             Command::BitPos(_) => "BITPOS",
+            Command::BitField(_) => "BITFIELD",
+            Command::BitFieldRo(_) => "BITFIELD_RO",
             Command::Subscribe(_) => "SUBSCRIBE",
             Command::Unsubscribe(_) => "UNSUBSCRIBE",
             Command::Publish(_) => "PUBLISH",
@@ -275,9 +441,31 @@ impl Command {
             Command::Info(_) => "INFO",
             Command::Time(_) => "TIME",
             Command::Select(_) => "SELECT",
+            Command::SwapDb(_) => "SWAPDB",
+            Command::ClusterSetSlot(_) => "CLUSTER SETSLOT",
+            Command::ClusterInfo(_) => "CLUSTER INFO",
+            Command::ClusterNodes(_) => "CLUSTER NODES",
+            Command::ClusterMyId(_) => "CLUSTER MYID",
+            Command::ClusterFailover(_) => "CLUSTER FAILOVER",
             Command::Save(_) => "SAVE",
             Command::BgSave(_) => "BGSAVE",
             Command::Shutdown(_) => "SHUTDOWN",
+            Command::ClientKill(_) => "CLIENT KILL",
+            Command::Monitor(_) => "MONITOR",
+            Command::Reset(_) => "RESET",
+            Command::MemoryUsage(_) => "MEMORY USAGE",
+            Command::SlowLogGet(_) => "SLOWLOG GET",
+            Command::SlowLogLen(_) => "SLOWLOG LEN",
+            Command::SlowLogReset(_) => "SLOWLOG RESET",
+            Command::Eval(_) => "EVAL",
+            Command::EvalSha(_) => "EVALSHA",
+            Command::FunctionLoad(_) => "FUNCTION LOAD",
+            Command::FCall(_) => "FCALL",
+            Command::FCallRo(_) => "FCALL_RO",
+            Command::FunctionList(_) => "FUNCTION LIST",
+            Command::FunctionDelete(_) => "FUNCTION DELETE",
+            Command::FunctionDump(_) => "FUNCTION DUMP",
+            Command::FunctionRestore(_) => "FUNCTION RESTORE",
             Command::XAdd(_) => "XADD",
             Command::XRead(_) => "XREAD",
             Command::XRange(_) => "XRANGE",
@@ -287,6 +475,10 @@ impl Command {
             Command::XTrim(_) => "XTRIM",
             Command::XGroupCreate(_) => "XGROUP CREATE",
             Command::XAck(_) => "XACK",
+            Command::XInfo(_) => "XINFO",
+            Command::XPending(_) => "XPENDING",
+            Command::XClaim(_) => "XCLAIM",
+            Command::XAutoClaim(_) => "XAUTOCLAIM",
             Command::TsCreate(_) => "TS.CREATE",
             Command::TsAdd(_) => "TS.ADD",
             Command::TsGet(_) => "TS.GET",
@@ -305,12 +497,31 @@ impl Command {
             Command::MGet(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
             Command::Strlen(cmd) => Some(cmd.key.as_bytes()),
             Command::Append(cmd) => Some(cmd.key.as_bytes()),
+            Command::SetRange(cmd) => Some(cmd.key.as_bytes()),
             Command::GetRange(cmd) => Some(cmd.key.as_bytes()),
+            Command::GetDel(cmd) => Some(cmd.key.as_bytes()),
+            Command::GetEx(cmd) => Some(cmd.key.as_bytes()),
             Command::Del(cmd) => Some(cmd.key.as_bytes()),
             Command::Exists(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
             Command::Rename(cmd) => Some(cmd.from.as_bytes()),
             Command::Renamenx(cmd) => Some(cmd.from.as_bytes()),
             Command::Flushdb(_) => None,
+            Command::Flushall(_) => None,
+            Command::Copy(cmd) => Some(cmd.source.as_bytes()),
+            Command::ExpireTime(cmd) => Some(cmd.key.as_bytes()),
+            Command::PExpireTime(cmd) => Some(cmd.key.as_bytes()),
+            Command::Persist(cmd) => Some(cmd.key.as_bytes()),
+            Command::Ttl(cmd) => Some(cmd.key.as_bytes()),
+            Command::PTtl(cmd) => Some(cmd.key.as_bytes()),
+            Command::Dump(cmd) => Some(cmd.key.as_bytes()),
+            Command::Restore(cmd) => Some(cmd.key.as_bytes()),
+            Command::Sort(cmd) => Some(cmd.key.as_bytes()),
+            Command::Keys(_) => None,
+            Command::Type(cmd) => Some(cmd.key.as_bytes()),
+            Command::Touch(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::ObjectEncoding(cmd) => Some(cmd.key.as_bytes()),
+            Command::ObjectIdleTime(cmd) => Some(cmd.key.as_bytes()),
+            Command::ObjectFreq(cmd) => Some(cmd.key.as_bytes()),
             Command::Incr(cmd) => Some(cmd.key.as_bytes()),
             Command::Incrby(cmd) => Some(cmd.key.as_bytes()),
             Command::Decr(cmd) => Some(cmd.key.as_bytes()),
@@ -328,6 +539,7 @@ impl Command {
             Command::HVals(cmd) => Some(cmd.key.as_bytes()),
             Command::HGetall(cmd) => Some(cmd.key.as_bytes()),
             Command::HRandField(cmd) => Some(cmd.key.as_bytes()),
+            Command::HScan(cmd) => Some(cmd.key.as_bytes()),
             Command::HIncrBy(cmd) => Some(cmd.key.as_bytes()),
             Command::HIncrByFloat(cmd) => Some(cmd.key.as_bytes()),
             Command::HPfAdd(cmd) => Some(cmd.key.as_bytes()),
@@ -337,9 +549,16 @@ impl Command {
             Command::SRem(cmd) => Some(cmd.key.as_bytes()),
             Command::SCard(cmd) => Some(cmd.key.as_bytes()),
             Command::SMembers(cmd) => Some(cmd.key.as_bytes()),
+            Command::SScan(cmd) => Some(cmd.key.as_bytes()),
             Command::SIsmember(cmd) => Some(cmd.key.as_bytes()),
+            Command::SmIsMember(cmd) => Some(cmd.key.as_bytes()),
             Command::SRandMember(cmd) => Some(cmd.key.as_bytes()),
             Command::SPop(cmd) => Some(cmd.key.as_bytes()),
+            Command::SMove(cmd) => Some(cmd.source.as_bytes()),
+            Command::SInterCard(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::SDiffStore(cmd) => Some(cmd.destination.as_bytes()),
+            Command::SUnionStore(cmd) => Some(cmd.destination.as_bytes()),
+            Command::SInterStore(cmd) => Some(cmd.destination.as_bytes()),
             Command::SInter(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
             Command::SUnion(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
             Command::SDiff(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
@@ -347,12 +566,27 @@ impl Command {
             Command::ZRem(cmd) => Some(cmd.key.as_bytes()),
             Command::ZRange(cmd) => Some(cmd.key.as_bytes()),
             Command::ZScore(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZScan(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZmScore(cmd) => Some(cmd.key.as_bytes()),
             Command::ZCard(cmd) => Some(cmd.key.as_bytes()),
             Command::ZRevrange(cmd) => Some(cmd.key.as_bytes()),
             Command::ZRank(cmd) => Some(cmd.key.as_bytes()),
             Command::ZRevRank(cmd) => Some(cmd.key.as_bytes()),
             Command::ZCount(cmd) => Some(cmd.key.as_bytes()),
             Command::ZIncrBy(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZRangeByLex(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZRevRangeByLex(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZLexCount(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZPopMin(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZmPop(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::ZUnion(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::ZInter(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::ZDiff(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::ZUnionStore(cmd) => Some(cmd.destination.as_bytes()),
+            Command::ZInterStore(cmd) => Some(cmd.destination.as_bytes()),
+            Command::ZDiffStore(cmd) => Some(cmd.destination.as_bytes()),
+            Command::ZPopMax(cmd) => Some(cmd.key.as_bytes()),
+            Command::ZRandMember(cmd) => Some(cmd.key.as_bytes()),
             Command::LSet(cmd) => Some(cmd.key.as_bytes()),
             Command::LRem(cmd) => Some(cmd.key.as_bytes()),
             Command::LPush(cmd) => Some(cmd.key.as_bytes()),
@@ -361,17 +595,36 @@ impl Command {
             Command::RPop(cmd) => Some(cmd.key.as_bytes()),
             Command::LLen(cmd) => Some(cmd.key.as_bytes()),
             Command::LRange(cmd) => Some(cmd.key.as_bytes()),
+            Command::LPos(cmd) => Some(cmd.key.as_bytes()),
+            Command::LMove(cmd) => Some(cmd.source.as_bytes()),
+            Command::LmPop(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::LInsert(cmd) => Some(cmd.key.as_bytes()),
+            Command::LIndex(cmd) => Some(cmd.key.as_bytes()),
+            Command::LTrim(cmd) => Some(cmd.key.as_bytes()),
             Command::Auth(_) => None,
+            Command::AclLog(_) => None,
+            Command::AclLogReset(_) => None,
+            Command::AclSave(_) => None,
+            Command::AclLoad(_) => None,
+            Command::AclWhoAmI(_) => None,
+            Command::AclCat(_) => None,
+            Command::AclSetUser(_) => None,
+            Command::AclGetUser(_) => None,
+            Command::AclDelUser(_) => None,
             Command::GeoAdd(cmd) => Some(cmd.key.as_bytes()),
             Command::GeoDist(cmd) => Some(cmd.key.as_bytes()),
             Command::GeoPos(cmd) => Some(cmd.key.as_bytes()),
             Command::GeoRadius(cmd) => Some(cmd.key.as_bytes()),
             Command::GeoRadiusByMember(cmd) => Some(cmd.key.as_bytes()),
+            Command::GeoSearchStore(cmd) => Some(cmd.source.as_bytes()),
+            Command::GeoSearch(cmd) => Some(cmd.key.as_bytes()),
             Command::SetBit(cmd) => Some(cmd.key.as_bytes()),
             Command::GetBit(cmd) => Some(cmd.key.as_bytes()),
             Command::BitCount(cmd) => Some(cmd.key.as_bytes()),
             Command::BitOp(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
             Command::BitPos(cmd) => Some(cmd.key.as_bytes()),
+            Command::BitField(cmd) => Some(cmd.key.as_bytes()),
+            Command::BitFieldRo(cmd) => Some(cmd.key.as_bytes()),
             Command::Subscribe(_) => None,
             Command::Unsubscribe(_) => None,
             Command::Publish(_) => None,
@@ -384,9 +637,31 @@ impl Command {
             Command::Info(_) => None,
             Command::Time(_) => None,
             Command::Select(_) => None,
+            Command::SwapDb(_) => None,
+            Command::ClusterSetSlot(_) => None,
+            Command::ClusterInfo(_) => None,
+            Command::ClusterNodes(_) => None,
+            Command::ClusterMyId(_) => None,
+            Command::ClusterFailover(_) => None,
             Command::Save(_) => None,
             Command::BgSave(_) => None,
             Command::Shutdown(_) => None,
+            Command::ClientKill(_) => None,
+            Command::Monitor(_) => None,
+            Command::Reset(_) => None,
+            Command::MemoryUsage(cmd) => Some(cmd.key.as_bytes()),
+            Command::SlowLogGet(_) => None,
+            Command::SlowLogLen(_) => None,
+            Command::SlowLogReset(_) => None,
+            Command::Eval(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::EvalSha(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::FunctionLoad(_) => None,
+            Command::FCall(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::FCallRo(cmd) => cmd.keys.first().map(|k| k.as_bytes()),
+            Command::FunctionList(_) => None,
+            Command::FunctionDelete(_) => None,
+            Command::FunctionDump(_) => None,
+            Command::FunctionRestore(_) => None,
             Command::XAdd(cmd) => Some(cmd.key.as_bytes()),
             Command::XRead(_) => None,
             Command::XRange(cmd) => Some(cmd.key.as_bytes()),
@@ -396,6 +671,10 @@ impl Command {
             Command::XTrim(cmd) => Some(cmd.key.as_bytes()),
             Command::XGroupCreate(cmd) => Some(cmd.key.as_bytes()),
             Command::XAck(cmd) => Some(cmd.key.as_bytes()),
+            Command::XInfo(cmd) => Some(cmd.key.as_bytes()),
+            Command::XPending(cmd) => Some(cmd.key.as_bytes()),
+            Command::XClaim(cmd) => Some(cmd.key.as_bytes()),
+            Command::XAutoClaim(cmd) => Some(cmd.key.as_bytes()),
             Command::TsCreate(cmd) => Some(cmd.key.as_bytes()),
             Command::TsAdd(cmd) => Some(cmd.key.as_bytes()),
             Command::TsGet(cmd) => Some(cmd.key.as_bytes()),
@@ -433,9 +712,28 @@ impl CommandExecute for Command {
             Command::Rename(cmd) => cmd.execute(store),
             Command::Renamenx(cmd) => cmd.execute(store),
             Command::Flushdb(cmd) => cmd.execute(store),
+            Command::Flushall(cmd) => cmd.execute(store),
+            Command::Copy(cmd) => cmd.execute(store),
+            Command::ExpireTime(cmd) => cmd.execute(store),
+            Command::PExpireTime(cmd) => cmd.execute(store),
+            Command::Persist(cmd) => cmd.execute(store),
+            Command::Ttl(cmd) => cmd.execute(store),
+            Command::PTtl(cmd) => cmd.execute(store),
+            Command::Dump(cmd) => cmd.execute(store),
+            Command::Restore(cmd) => cmd.execute(store),
+            Command::Sort(cmd) => cmd.execute(store),
+            Command::Keys(cmd) => cmd.execute(store),
+            Command::Type(cmd) => cmd.execute(store),
+            Command::Touch(cmd) => cmd.execute(store),
+            Command::ObjectEncoding(cmd) => cmd.execute(store),
+            Command::ObjectIdleTime(cmd) => cmd.execute(store),
+            Command::ObjectFreq(cmd) => cmd.execute(store),
             Command::Strlen(cmd) => cmd.execute(store),
             Command::Append(cmd) => cmd.execute(store),
+            Command::SetRange(cmd) => cmd.execute(store),
             Command::GetRange(cmd) => cmd.execute(store),
+            Command::GetDel(cmd) => cmd.execute(store),
+            Command::GetEx(cmd) => cmd.execute(store),
             Command::Incr(cmd) => cmd.execute(store),
             Command::Incrby(cmd) => cmd.execute(store),
             Command::Decr(cmd) => cmd.execute(store),
@@ -453,6 +751,7 @@ impl CommandExecute for Command {
             Command::HVals(cmd) => cmd.execute(store),
             Command::HGetall(cmd) => cmd.execute(store),
             Command::HRandField(cmd) => cmd.execute(store),
+            Command::HScan(cmd) => cmd.execute(store),
             Command::HIncrBy(cmd) => cmd.execute(store),
             Command::HIncrByFloat(cmd) => cmd.execute(store),
             Command::HPfAdd(cmd) => cmd.execute(store),
@@ -462,9 +761,16 @@ impl CommandExecute for Command {
             Command::SRem(cmd) => cmd.execute(store),
             Command::SCard(cmd) => cmd.execute(store),
             Command::SMembers(cmd) => cmd.execute(store),
+            Command::SScan(cmd) => cmd.execute(store),
             Command::SIsmember(cmd) => cmd.execute(store),
+            Command::SmIsMember(cmd) => cmd.execute(store),
             Command::SRandMember(cmd) => cmd.execute(store),
             Command::SPop(cmd) => cmd.execute(store),
+            Command::SMove(cmd) => cmd.execute(store),
+            Command::SInterCard(cmd) => cmd.execute(store),
+            Command::SDiffStore(cmd) => cmd.execute(store),
+            Command::SUnionStore(cmd) => cmd.execute(store),
+            Command::SInterStore(cmd) => cmd.execute(store),
             Command::SInter(cmd) => cmd.execute(store),
             Command::SUnion(cmd) => cmd.execute(store),
             Command::SDiff(cmd) => cmd.execute(store),
@@ -472,12 +778,27 @@ impl CommandExecute for Command {
             Command::ZRem(cmd) => cmd.execute(store),
             Command::ZRange(cmd) => cmd.execute(store),
             Command::ZScore(cmd) => cmd.execute(store),
+            Command::ZScan(cmd) => cmd.execute(store),
+            Command::ZmScore(cmd) => cmd.execute(store),
             Command::ZCard(cmd) => cmd.execute(store),
             Command::ZRevrange(cmd) => cmd.execute(store),
             Command::ZRank(cmd) => cmd.execute(store),
             Command::ZRevRank(cmd) => cmd.execute(store),
             Command::ZCount(cmd) => cmd.execute(store),
             Command::ZIncrBy(cmd) => cmd.execute(store),
+            Command::ZRangeByLex(cmd) => cmd.execute(store),
+            Command::ZRevRangeByLex(cmd) => cmd.execute(store),
+            Command::ZLexCount(cmd) => cmd.execute(store),
+            Command::ZPopMin(cmd) => cmd.execute(store),
+            Command::ZmPop(cmd) => cmd.execute(store),
+            Command::ZUnion(cmd) => cmd.execute(store),
+            Command::ZInter(cmd) => cmd.execute(store),
+            Command::ZDiff(cmd) => cmd.execute(store),
+            Command::ZUnionStore(cmd) => cmd.execute(store),
+            Command::ZInterStore(cmd) => cmd.execute(store),
+            Command::ZDiffStore(cmd) => cmd.execute(store),
+            Command::ZPopMax(cmd) => cmd.execute(store),
+            Command::ZRandMember(cmd) => cmd.execute(store),
             Command::LSet(cmd) => cmd.execute(store),
             Command::LRem(cmd) => cmd.execute(store),
             Command::LPush(cmd) => cmd.execute(store),
@@ -486,17 +807,36 @@ impl CommandExecute for Command {
             Command::RPop(cmd) => cmd.execute(store),
             Command::LLen(cmd) => cmd.execute(store),
             Command::LRange(cmd) => cmd.execute(store),
+            Command::LPos(cmd) => cmd.execute(store),
+            Command::LMove(cmd) => cmd.execute(store),
+            Command::LmPop(cmd) => cmd.execute(store),
+            Command::LInsert(cmd) => cmd.execute(store),
+            Command::LIndex(cmd) => cmd.execute(store),
+            Command::LTrim(cmd) => cmd.execute(store),
             Command::Auth(cmd) => cmd.execute(store),
+            Command::AclLog(cmd) => cmd.execute(store),
+            Command::AclLogReset(cmd) => cmd.execute(store),
+            Command::AclSave(cmd) => cmd.execute(store),
+            Command::AclLoad(cmd) => cmd.execute(store),
+            Command::AclWhoAmI(cmd) => cmd.execute(store),
+            Command::AclCat(cmd) => cmd.execute(store),
+            Command::AclSetUser(cmd) => cmd.execute(store),
+            Command::AclGetUser(cmd) => cmd.execute(store),
+            Command::AclDelUser(cmd) => cmd.execute(store),
             Command::GeoAdd(cmd) => cmd.execute(store),
             Command::GeoDist(cmd) => cmd.execute(store),
             Command::GeoPos(cmd) => cmd.execute(store),
             Command::GeoRadius(cmd) => cmd.execute(store),
             Command::GeoRadiusByMember(cmd) => cmd.execute(store),
+            Command::GeoSearchStore(cmd) => cmd.execute(store),
+            Command::GeoSearch(cmd) => cmd.execute(store),
             Command::SetBit(cmd) => cmd.execute(store),
             Command::GetBit(cmd) => cmd.execute(store),
             Command::BitCount(cmd) => cmd.execute(store),
             Command::BitOp(cmd) => cmd.execute(store),
             Command::BitPos(cmd) => cmd.execute(store),
+            Command::BitField(cmd) => cmd.execute(store),
+            Command::BitFieldRo(cmd) => cmd.execute(store),
             Command::Subscribe(cmd) => cmd.execute(store),
             Command::Unsubscribe(cmd) => cmd.execute(store),
             Command::Publish(cmd) => cmd.execute(store),
@@ -509,9 +849,31 @@ impl CommandExecute for Command {
             Command::Info(cmd) => cmd.execute(store),
             Command::Time(cmd) => cmd.execute(store),
             Command::Select(cmd) => cmd.execute(store),
+            Command::SwapDb(cmd) => cmd.execute(store),
+            Command::ClusterSetSlot(cmd) => cmd.execute(store),
+            Command::ClusterInfo(cmd) => cmd.execute(store),
+            Command::ClusterNodes(cmd) => cmd.execute(store),
+            Command::ClusterMyId(cmd) => cmd.execute(store),
+            Command::ClusterFailover(cmd) => cmd.execute(store),
             Command::Save(cmd) => cmd.execute(store),
             Command::BgSave(cmd) => cmd.execute(store),
             Command::Shutdown(cmd) => cmd.execute(store),
+            Command::ClientKill(cmd) => cmd.execute(store),
+            Command::Monitor(cmd) => cmd.execute(store),
+            Command::Reset(cmd) => cmd.execute(store),
+            Command::MemoryUsage(cmd) => cmd.execute(store),
+            Command::SlowLogGet(cmd) => cmd.execute(store),
+            Command::SlowLogLen(cmd) => cmd.execute(store),
+            Command::SlowLogReset(cmd) => cmd.execute(store),
+            Command::Eval(cmd) => cmd.execute(store),
+            Command::EvalSha(cmd) => cmd.execute(store),
+            Command::FunctionLoad(cmd) => cmd.execute(store),
+            Command::FCall(cmd) => cmd.execute(store),
+            Command::FCallRo(cmd) => cmd.execute(store),
+            Command::FunctionList(cmd) => cmd.execute(store),
+            Command::FunctionDelete(cmd) => cmd.execute(store),
+            Command::FunctionDump(cmd) => cmd.execute(store),
+            Command::FunctionRestore(cmd) => cmd.execute(store),
             Command::XAdd(cmd) => cmd.execute(store),
             Command::XRead(cmd) => cmd.execute(store),
             Command::XRange(cmd) => cmd.execute(store),
@@ -521,6 +883,10 @@ impl CommandExecute for Command {
             Command::XTrim(cmd) => cmd.execute(store),
             Command::XGroupCreate(cmd) => cmd.execute(store),
             Command::XAck(cmd) => cmd.execute(store),
+            Command::XInfo(cmd) => cmd.execute(store),
+            Command::XPending(cmd) => cmd.execute(store),
+            Command::XClaim(cmd) => cmd.execute(store),
+            Command::XAutoClaim(cmd) => cmd.execute(store),
             Command::TsCreate(cmd) => cmd.execute(store),
             Command::TsAdd(cmd) => cmd.execute(store),
             Command::TsGet(cmd) => cmd.execute(store),
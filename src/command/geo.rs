@@ -1,5 +1,8 @@
 use super::CommandExecute;
-use crate::{GeoPoint, Sds, StorageEngine, StoreError, Value};
+use crate::{
+    database::geo_distance::DistanceUnit, GeoAddOptions, GeoPoint, GeoSearchFrom, GeoSearchParams,
+    GeoSearchShape, GeoSortOrder, Sds, StorageEngine, StoreError, Value,
+};
 
 /// Команда GEOADD — добавляет одну или несколько точек в гео-набор.
 #[derive(Debug)]
@@ -7,6 +10,57 @@ pub struct GeoAddCommand {
     pub key: String,
     /// Срез трёх кортежей (lon, lat, member)
     pub points: Vec<(f64, f64, String)>,
+    /// Флаги `NX`/`XX`/`CH`.
+    pub options: GeoAddOptions,
+}
+
+impl GeoAddCommand {
+    /// Разбирает аргументы после ключа: необязательные флаги `NX`/`XX`/`CH`,
+    /// за которыми следуют тройки `lon lat member`.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let mut options = GeoAddOptions::default();
+        let mut rest = args;
+        while let Some((flag, tail)) = rest.split_first() {
+            match flag.to_ascii_uppercase().as_str() {
+                "NX" => options.nx = true,
+                "XX" => options.xx = true,
+                "CH" => options.ch = true,
+                _ => break,
+            }
+            rest = tail;
+        }
+        if options.nx && options.xx {
+            return Err(StoreError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if rest.is_empty() || !rest.len().is_multiple_of(3) {
+            return Err(StoreError::Syntax(
+                "GEOADD expects lon lat member triplets".to_string(),
+            ));
+        }
+
+        let points = rest
+            .chunks_exact(3)
+            .map(
+                |triplet| match (triplet[0].parse::<f64>(), triplet[1].parse::<f64>()) {
+                    (Ok(lon), Ok(lat)) => Ok((lon, lat, triplet[2].to_string())),
+                    _ => Err(StoreError::InvalidArgument(
+                        "value is not a valid float".to_string(),
+                    )),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            key,
+            points,
+            options,
+        })
+    }
 }
 
 impl CommandExecute for GeoAddCommand {
@@ -15,14 +69,14 @@ impl CommandExecute for GeoAddCommand {
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
         let key = Sds::from_str(&self.key);
-        let mut added = 0;
+        let mut counted = 0;
         for (lon, lat, member) in &self.points {
             let member_sds = Sds::from_str(member);
-            if store.geo_add(&key, *lon, *lat, &member_sds)? {
-                added += 1;
+            if store.geo_add(&key, *lon, *lat, &member_sds, self.options)? {
+                counted += 1;
             }
         }
-        Ok(Value::Int(added))
+        Ok(Value::Int(counted))
     }
 
     fn command_name(&self) -> &'static str {
@@ -136,12 +190,19 @@ impl CommandExecute for GeoRadiusCommand {
 }
 
 /// Команда GEORADIUSBYMEMBER — ищет точки в радиусе вокруг другой точки.
+///
+/// Формат: `GEORADIUSBYMEMBER key member radius unit [ASC|DESC] [COUNT n]
+/// [WITHCOORD] [WITHDIST]`.
 #[derive(Debug)]
 pub struct GeoRadiusByMemberCommand {
     pub key: String,
     pub member: String,
     pub radius: f64,
     pub unit: Option<String>,
+    pub order: Option<GeoSortOrder>,
+    pub count: Option<usize>,
+    pub with_coord: bool,
+    pub with_dist: bool,
 }
 
 impl CommandExecute for GeoRadiusByMemberCommand {
@@ -150,29 +211,29 @@ impl CommandExecute for GeoRadiusByMemberCommand {
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
         let key = Sds::from_str(&self.key);
-        let unit = self.unit.as_deref().unwrap_or("m");
+        let unit = parse_geo_unit(self.unit.as_deref().unwrap_or("m"))?.name();
         let member = Sds::from_str(&self.member);
 
-        let pos = match store.geo_pos(&key, &member)? {
-            Some(pos) => pos,
-            None => return Ok(Value::Array(vec![])), // member не найден
-        };
+        if self.count == Some(0) {
+            return Err(StoreError::InvalidArgument("COUNT must be > 0".to_string()));
+        }
 
-        let members = store.geo_radius(&key, pos.lon, pos.lat, self.radius, unit)?;
+        let pos = store
+            .geo_pos(&key, &member)?
+            .ok_or(StoreError::KeyNotFound)?;
 
-        let result = members
-            .into_iter()
-            .map(|(member, dist, GeoPoint { lon, lat })| {
-                Value::Array(vec![
-                    Value::Str(Sds::from_str(&member)),
-                    Value::Float(dist),
-                    Value::Float(lon),
-                    Value::Float(lat),
-                ])
-            })
-            .collect();
+        let mut members = store.geo_radius(&key, pos.lon, pos.lat, self.radius, unit)?;
 
-        Ok(Value::Array(result))
+        match self.order {
+            Some(GeoSortOrder::Asc) => members.sort_by(|a, b| a.1.total_cmp(&b.1)),
+            Some(GeoSortOrder::Desc) => members.sort_by(|a, b| b.1.total_cmp(&a.1)),
+            None => {}
+        }
+        if let Some(count) = self.count {
+            members.truncate(count);
+        }
+
+        Ok(geo_reply(members, self.with_coord, self.with_dist))
     }
 
     fn command_name(&self) -> &'static str {
@@ -200,31 +261,43 @@ impl CommandExecute for GoHashCommand {
     }
 }
 
-/// Источник поиска для GEOSEARCH
-#[derive(Debug)]
-pub enum GeoSearchFrom {
-    Member(String),
-    LoLat { lon: f64, lat: f64 },
-}
-
 /// Команда GEOSEARCH — универсальный поиск по гео-набору.
+///
+/// Формат: `GEOSEARCH key FROMMEMBER member|FROMLONLAT lon lat
+/// BYRADIUS radius unit|BYBOX width height unit [ASC|DESC] [COUNT n]
+/// [WITHCOORD] [WITHDIST]`.
 #[derive(Debug)]
 pub struct GeoSearchCommand {
     pub key: String,
-    pub from: GeoSearchFrom,
-    pub radius: f64,
-    pub unit: Option<String>, // м, км и т.д.
-    pub with_dist: bool,
+    pub params: GeoSearchParams,
     pub with_coord: bool,
-    pub count: Option<usize>,
+    pub with_dist: bool,
+}
+
+impl GeoSearchCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let parsed = parse_geo_search("GEOSEARCH", args)?;
+        Ok(Self {
+            key,
+            params: parsed.params,
+            with_coord: parsed.with_coord,
+            with_dist: parsed.with_dist,
+        })
+    }
 }
 
 impl CommandExecute for GeoSearchCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("GEOSEARCH is not implemented yet")
+        let key = Sds::from_str(&self.key);
+        let hits = store.geo_search(&key, &self.params)?;
+        Ok(geo_reply(hits, self.with_coord, self.with_dist))
     }
 
     fn command_name(&self) -> &'static str {
@@ -232,6 +305,206 @@ impl CommandExecute for GeoSearchCommand {
     }
 }
 
+/// Команда GEOSEARCHSTORE — сохраняет результаты гео-поиска в
+/// отсортированное множество.
+///
+/// Формат: `GEOSEARCHSTORE destination source FROMMEMBER member|FROMLONLAT
+/// lon lat BYRADIUS radius unit|BYBOX width height unit [ASC|DESC]
+/// [COUNT n] [STOREDIST]`.
+#[derive(Debug)]
+pub struct GeoSearchStoreCommand {
+    pub destination: String,
+    pub source: String,
+    pub params: GeoSearchParams,
+}
+
+impl GeoSearchStoreCommand {
+    /// Разбирает аргументы после `destination` и `source`.
+    pub fn parse(
+        destination: String,
+        source: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let parsed = parse_geo_search("GEOSEARCHSTORE", args)?;
+        Ok(Self {
+            destination,
+            source,
+            params: parsed.params,
+        })
+    }
+}
+
+impl CommandExecute for GeoSearchStoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let destination = Sds::from_str(&self.destination);
+        let source = Sds::from_str(&self.source);
+        let stored = store.geosearchstore(&destination, &source, self.params.clone())?;
+        Ok(Value::Int(stored))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "GEOSEARCHSTORE"
+    }
+}
+
+/// Разобранные аргументы GEOSEARCH/GEOSEARCHSTORE.
+struct GeoSearchArgs {
+    params: GeoSearchParams,
+    with_coord: bool,
+    with_dist: bool,
+}
+
+/// Разбирает общие аргументы GEOSEARCH и GEOSEARCHSTORE. `WITHCOORD` и
+/// `WITHDIST` допустимы только для GEOSEARCH, `STOREDIST` — только для
+/// GEOSEARCHSTORE.
+fn parse_geo_search(
+    cmd: &str,
+    args: &[&str],
+) -> Result<GeoSearchArgs, StoreError> {
+    let store = cmd == "GEOSEARCHSTORE";
+    let mut from = None;
+    let mut shape = None;
+    let mut unit = DistanceUnit::Meters;
+    let mut order = None;
+    let mut count = None;
+    let mut store_dist = false;
+    let mut with_coord = false;
+    let mut with_dist = false;
+
+    let missing = || StoreError::Syntax(format!("{cmd}: missing argument"));
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.to_ascii_uppercase().as_str() {
+            "FROMMEMBER" if from.is_none() => {
+                let member = iter.next().ok_or_else(missing)?;
+                from = Some(GeoSearchFrom::Member(member.to_string()));
+            }
+            "FROMLONLAT" if from.is_none() => {
+                let lon = parse_geo_float(iter.next().ok_or_else(missing)?)?;
+                let lat = parse_geo_float(iter.next().ok_or_else(missing)?)?;
+                from = Some(GeoSearchFrom::LoLat { lon, lat });
+            }
+            "BYRADIUS" if shape.is_none() => {
+                let radius = parse_geo_float(iter.next().ok_or_else(missing)?)?;
+                unit = parse_geo_unit(iter.next().ok_or_else(missing)?)?;
+                shape = Some(GeoSearchShape::Radius(radius));
+            }
+            "BYBOX" if shape.is_none() => {
+                let width = parse_geo_float(iter.next().ok_or_else(missing)?)?;
+                let height = parse_geo_float(iter.next().ok_or_else(missing)?)?;
+                unit = parse_geo_unit(iter.next().ok_or_else(missing)?)?;
+                shape = Some(GeoSearchShape::Box { width, height });
+            }
+            "ASC" => order = Some(GeoSortOrder::Asc),
+            "DESC" => order = Some(GeoSortOrder::Desc),
+            "COUNT" => {
+                let n = iter
+                    .next()
+                    .ok_or_else(missing)?
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| StoreError::InvalidArgument("COUNT must be > 0".to_string()))?;
+                count = Some(n);
+            }
+            "STOREDIST" if store => store_dist = true,
+            "WITHCOORD" if !store => with_coord = true,
+            "WITHDIST" if !store => with_dist = true,
+            "FROMMEMBER" | "FROMLONLAT" => {
+                return Err(StoreError::InvalidArgument(
+                    "exactly one of FROMMEMBER or FROMLONLAT can be specified".to_string(),
+                ))
+            }
+            "BYRADIUS" | "BYBOX" => {
+                return Err(StoreError::InvalidArgument(
+                    "exactly one of BYRADIUS and BYBOX can be specified".to_string(),
+                ))
+            }
+            other => {
+                return Err(StoreError::Syntax(format!(
+                    "{cmd}: unexpected argument '{other}'"
+                )))
+            }
+        }
+    }
+
+    let from = from.ok_or_else(|| {
+        StoreError::InvalidArgument(
+            "exactly one of FROMMEMBER or FROMLONLAT can be specified".to_string(),
+        )
+    })?;
+    let shape = shape.ok_or_else(|| {
+        StoreError::InvalidArgument(
+            "exactly one of BYRADIUS and BYBOX can be specified".to_string(),
+        )
+    })?;
+
+    Ok(GeoSearchArgs {
+        params: GeoSearchParams {
+            from,
+            shape,
+            unit,
+            order,
+            count,
+            store_dist,
+        },
+        with_coord,
+        with_dist,
+    })
+}
+
+/// Формирует ответ гео-поиска: только имена или, при `WITHDIST` /
+/// `WITHCOORD`, массивы `[member, dist?, [lon, lat]?]`.
+fn geo_reply(
+    hits: Vec<(String, f64, GeoPoint)>,
+    with_coord: bool,
+    with_dist: bool,
+) -> Value {
+    let result = hits
+        .into_iter()
+        .map(|(member, dist, GeoPoint { lon, lat })| {
+            let name = Value::Str(Sds::from_str(&member));
+            if !with_coord && !with_dist {
+                return name;
+            }
+            let mut item = vec![name];
+            if with_dist {
+                item.push(Value::Float(dist));
+            }
+            if with_coord {
+                item.push(Value::Array(vec![Value::Float(lon), Value::Float(lat)]));
+            }
+            Value::Array(item)
+        })
+        .collect();
+
+    Value::Array(result)
+}
+
+/// Разбирает единицу измерения расстояния гео-команд.
+///
+/// Допустимы только `m`, `km`, `mi` и `ft`.
+fn parse_geo_unit(unit: &str) -> Result<DistanceUnit, StoreError> {
+    match unit.to_ascii_lowercase().as_str() {
+        "m" => Ok(DistanceUnit::Meters),
+        "km" => Ok(DistanceUnit::Kilometers),
+        "mi" => Ok(DistanceUnit::Miles),
+        "ft" => Ok(DistanceUnit::Feet),
+        _ => Err(StoreError::InvalidArgument(format!(
+            "unsupported unit provided. please use m, km, ft, mi (got '{unit}')"
+        ))),
+    }
+}
+
+/// Разбирает число с плавающей точкой из аргумента гео-команды.
+fn parse_geo_float(arg: &str) -> Result<f64, StoreError> {
+    arg.parse::<f64>()
+        .map_err(|_| StoreError::InvalidArgument("value is not a valid float".to_string()))
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -246,13 +519,31 @@ mod tests {
         let engine = StorageEngine::Memory(InMemoryStore::new());
         let key = "places";
         engine
-            .geo_add(&Sds::from_str(key), 0.0, 0.0, &Sds::from_str("origin"))
+            .geo_add(
+                &Sds::from_str(key),
+                0.0,
+                0.0,
+                &Sds::from_str("origin"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         engine
-            .geo_add(&Sds::from_str(key), 0.001, 0.0, &Sds::from_str("east"))
+            .geo_add(
+                &Sds::from_str(key),
+                0.001,
+                0.0,
+                &Sds::from_str("east"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         engine
-            .geo_add(&Sds::from_str(key), 0.0, 0.001, &Sds::from_str("north"))
+            .geo_add(
+                &Sds::from_str(key),
+                0.0,
+                0.001,
+                &Sds::from_str("north"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         engine
     }
@@ -269,6 +560,7 @@ mod tests {
                 (2.3522, 48.8566, "paris".into()),
                 (13.4050, 52.5200, "berlin".into()),
             ],
+            options: GeoAddOptions::default(),
         };
         let res = cmd.execute(&mut engine).unwrap();
         assert_eq!(res, Value::Int(2));
@@ -277,6 +569,57 @@ mod tests {
         assert_eq!(res2, Value::Int(0));
     }
 
+    /// Тест проверяет, что `GEOADD NX` не обновляет существующего участника
+    /// и возвращает 0, оставляя его координаты прежними.
+    #[test]
+    fn test_geoadd_nx_keeps_existing() {
+        let mut engine = setup_store();
+        let cmd = GeoAddCommand::parse("places".into(), &["NX", "1.0", "1.0", "origin"]).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(0));
+        assert_eq!(
+            engine
+                .geo_pos(&Sds::from_str("places"), &Sds::from_str("origin"))
+                .unwrap(),
+            Some(GeoPoint { lon: 0.0, lat: 0.0 })
+        );
+    }
+
+    /// Тест проверяет, что `GEOADD XX` только обновляет существующих
+    /// участников, а `CH` считает и добавленные, и изменённые точки.
+    #[test]
+    fn test_geoadd_xx_and_ch() {
+        let mut engine = setup_store();
+        let args = ["XX", "1.0", "1.0", "origin", "2.0", "2.0", "new"];
+        let cmd = GeoAddCommand::parse("places".into(), &args).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(0));
+        assert!(engine
+            .geo_pos(&Sds::from_str("places"), &Sds::from_str("new"))
+            .unwrap()
+            .is_none());
+
+        let args = [
+            "CH", "0.5", "0.5", "origin", "2.0", "2.0", "new", "0.001", "0.0", "east",
+        ];
+        let cmd = GeoAddCommand::parse("places".into(), &args).unwrap();
+        // origin изменён, new добавлен, east не изменился
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(2));
+    }
+
+    /// Тест проверяет разбор аргументов `GEOADD`: `NX` и `XX` несовместимы,
+    /// количество аргументов должно быть кратно трём.
+    #[test]
+    fn test_geoadd_parse_errors() {
+        let cases: [&[&str]; 4] = [
+            &["NX", "XX", "1.0", "1.0", "a"],
+            &["1.0", "1.0"],
+            &["CH"],
+            &["lon", "1.0", "a"],
+        ];
+        for args in cases {
+            assert!(GeoAddCommand::parse("places".into(), args).is_err());
+        }
+    }
+
     /// Тест проверяет, что команда `GEODIST`:
     /// - правильно считает расстояние между двумя точками
     /// - возвращает `Null`, если один из участников не найден
@@ -368,29 +711,32 @@ mod tests {
         }
     }
 
+    fn radius_by_member(member: &str) -> GeoRadiusByMemberCommand {
+        GeoRadiusByMemberCommand {
+            key: "places".into(),
+            member: member.into(),
+            radius: 200.0,
+            unit: None, // по умолчанию метры
+            order: None,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+        }
+    }
+
     /// Тест проверяет, что команда `GEORADIUSBYMEMBER`:
     /// - использует координаты указанного участника (origin)
     /// - возвращает участников в радиусе 200м от него
     #[test]
     fn test_georadiusbymember_command() {
         let mut engine = setup_store();
-        let cmd = GeoRadiusByMemberCommand {
-            key: "places".into(),
-            member: "origin".into(),
-            radius: 200.0,
-            unit: None, // по умолчанию метры
-        };
-        let res = cmd.execute(&mut engine).unwrap();
+        let res = radius_by_member("origin").execute(&mut engine).unwrap();
         if let Value::Array(arr) = res {
             let members: Vec<String> = arr
                 .into_iter()
                 .map(|item| {
-                    if let Value::Array(inner) = item {
-                        if let Value::Str(s) = &inner[0] {
-                            s.to_string()
-                        } else {
-                            panic!()
-                        }
+                    if let Value::Str(s) = item {
+                        s.to_string()
                     } else {
                         panic!()
                     }
@@ -403,4 +749,262 @@ mod tests {
             panic!("Expected Array");
         }
     }
+
+    /// Тест проверяет, что `GEORADIUSBYMEMBER` с `DESC`, `COUNT`,
+    /// `WITHDIST` и `WITHCOORD` сортирует по расстоянию, обрезает результат
+    /// и добавляет расстояние и координаты.
+    #[test]
+    fn test_georadiusbymember_options() {
+        let mut engine = setup_store();
+        let cmd = GeoRadiusByMemberCommand {
+            order: Some(GeoSortOrder::Asc),
+            count: Some(2),
+            with_dist: true,
+            with_coord: true,
+            unit: Some("km".into()),
+            radius: 0.2,
+            ..radius_by_member("origin")
+        };
+        let Value::Array(arr) = cmd.execute(&mut engine).unwrap() else {
+            panic!("Expected Array");
+        };
+        assert_eq!(arr.len(), 2);
+        let Value::Array(first) = &arr[0] else {
+            panic!("Expected Array");
+        };
+        assert_eq!(first[0], Value::Str(Sds::from_str("origin")));
+        assert_eq!(first[1], Value::Float(0.0));
+        assert_eq!(
+            first[2],
+            Value::Array(vec![Value::Float(0.0), Value::Float(0.0)])
+        );
+
+        let cmd = GeoRadiusByMemberCommand {
+            order: Some(GeoSortOrder::Desc),
+            with_dist: true,
+            ..radius_by_member("origin")
+        };
+        let Value::Array(arr) = cmd.execute(&mut engine).unwrap() else {
+            panic!("Expected Array");
+        };
+        assert_eq!(arr.len(), 3);
+        let Value::Array(last) = &arr[2] else {
+            panic!("Expected Array");
+        };
+        assert_eq!(last[0], Value::Str(Sds::from_str("origin")));
+        assert_eq!(last.len(), 2);
+    }
+
+    /// Тест проверяет, что `GEORADIUSBYMEMBER` возвращает ошибку для
+    /// отсутствующего участника и неизвестной единицы измерения.
+    #[test]
+    fn test_georadiusbymember_errors() {
+        let mut engine = setup_store();
+        assert!(matches!(
+            radius_by_member("missing").execute(&mut engine),
+            Err(StoreError::KeyNotFound)
+        ));
+
+        let cmd = GeoRadiusByMemberCommand {
+            unit: Some("yd".into()),
+            ..radius_by_member("origin")
+        };
+        assert!(matches!(
+            cmd.execute(&mut engine),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    fn stored_scores(
+        engine: &StorageEngine,
+        key: &str,
+    ) -> Vec<(String, f64)> {
+        match engine.get(&Sds::from_str(key)).unwrap() {
+            Some(Value::ZSet { dict, .. }) => {
+                let mut scores: Vec<(String, f64)> = dict
+                    .iter()
+                    .map(|(member, score)| (member.to_string(), *score))
+                    .collect();
+                scores.sort_by(|a, b| a.0.cmp(&b.0));
+                scores
+            }
+            other => panic!("Expected ZSet, got {other:?}"),
+        }
+    }
+
+    /// Тест проверяет, что `GEOSEARCHSTORE ... BYRADIUS` сохраняет всех
+    /// найденных участников в ZSet со скором-geohash, а `STOREDIST` —
+    /// с расстоянием в запрошенных единицах.
+    #[test]
+    fn test_geosearchstore_byradius() {
+        let mut engine = setup_store();
+        let args = ["FROMMEMBER", "origin", "BYRADIUS", "200", "m"];
+        let cmd = GeoSearchStoreCommand::parse("dst".into(), "places".into(), &args).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(3));
+        let scores = stored_scores(&engine, "dst");
+        assert_eq!(scores.len(), 3);
+        // geohash разных точек различается
+        assert_ne!(scores[0].1, scores[1].1);
+
+        let args = [
+            "FROMLONLAT",
+            "0",
+            "0",
+            "BYRADIUS",
+            "0.2",
+            "km",
+            "DESC",
+            "COUNT",
+            "1",
+            "STOREDIST",
+        ];
+        let cmd = GeoSearchStoreCommand::parse("dst".into(), "places".into(), &args).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(1));
+        let scores = stored_scores(&engine, "dst");
+        assert_eq!(scores.len(), 1);
+        assert_ne!(scores[0].0, "origin");
+        assert!((scores[0].1 - 0.111).abs() < 0.01);
+    }
+
+    /// Тест проверяет, что `GEOSEARCHSTORE ... BYBOX` учитывает ширину и
+    /// высоту прямоугольника по отдельности.
+    #[test]
+    fn test_geosearchstore_bybox() {
+        let mut engine = setup_store();
+        // 300м по долготе, 100м по широте: east попадает, north — нет
+        let args = ["FROMMEMBER", "origin", "BYBOX", "300", "100", "m", "ASC"];
+        let cmd = GeoSearchStoreCommand::parse("dst".into(), "places".into(), &args).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(2));
+        let members: Vec<String> = stored_scores(&engine, "dst")
+            .into_iter()
+            .map(|(m, _)| m)
+            .collect();
+        assert_eq!(members, vec!["east".to_string(), "origin".to_string()]);
+
+        // пустой результат удаляет destination
+        let args = ["FROMLONLAT", "50", "50", "BYBOX", "1", "1", "km"];
+        let cmd = GeoSearchStoreCommand::parse("dst".into(), "places".into(), &args).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Int(0));
+        assert_eq!(engine.get(&Sds::from_str("dst")).unwrap(), None);
+    }
+
+    /// Тест проверяет ошибки `GEOSEARCHSTORE`: отсутствующий участник
+    /// `FROMMEMBER` и некорректные аргументы.
+    #[test]
+    fn test_geosearchstore_errors() {
+        let mut engine = setup_store();
+        let args = ["FROMMEMBER", "missing", "BYRADIUS", "1", "km"];
+        let cmd = GeoSearchStoreCommand::parse("dst".into(), "places".into(), &args).unwrap();
+        assert!(matches!(
+            cmd.execute(&mut engine),
+            Err(StoreError::KeyNotFound)
+        ));
+
+        let cases: [&[&str]; 5] = [
+            &["BYRADIUS", "1", "km"],
+            &["FROMMEMBER", "origin"],
+            &["FROMMEMBER", "origin", "BYRADIUS", "1", "yd"],
+            &[
+                "FROMMEMBER",
+                "origin",
+                "BYRADIUS",
+                "1",
+                "km",
+                "BYBOX",
+                "1",
+                "1",
+                "km",
+            ],
+            &["FROMMEMBER", "origin", "BYRADIUS", "1", "km", "COUNT", "0"],
+        ];
+        for args in cases {
+            assert!(GeoSearchStoreCommand::parse("dst".into(), "places".into(), args).is_err());
+        }
+    }
+
+    /// Тест проверяет `GEOSEARCH ... BYBOX` с разными единицами, сортировкой
+    /// и флагами `WITHDIST`/`WITHCOORD`.
+    #[test]
+    fn test_geosearch_bybox() {
+        let mut engine = setup_store();
+        // 300м по долготе, 100м по широте: east попадает, north — нет
+        let args = ["FROMLONLAT", "0", "0", "BYBOX", "0.3", "0.1", "km", "DESC"];
+        let cmd = GeoSearchCommand::parse("places".into(), &args).unwrap();
+        assert_eq!(
+            cmd.execute(&mut engine).unwrap(),
+            Value::Array(vec![
+                Value::Str(Sds::from_str("east")),
+                Value::Str(Sds::from_str("origin")),
+            ])
+        );
+
+        // те же размеры в футах и милях дают тот же результат
+        for args in [
+            ["FROMMEMBER", "origin", "BYBOX", "984.252", "328.084", "ft"],
+            [
+                "FROMMEMBER",
+                "origin",
+                "BYBOX",
+                "0.186411",
+                "0.0621371",
+                "mi",
+            ],
+        ] {
+            let cmd = GeoSearchCommand::parse("places".into(), &args).unwrap();
+            let Value::Array(found) = cmd.execute(&mut engine).unwrap() else {
+                panic!("Expected Array");
+            };
+            assert_eq!(found.len(), 2);
+        }
+
+        let args = [
+            "FROMMEMBER",
+            "origin",
+            "BYBOX",
+            "1000",
+            "1000",
+            "ft",
+            "ASC",
+            "COUNT",
+            "2",
+            "WITHCOORD",
+            "WITHDIST",
+        ];
+        let cmd = GeoSearchCommand::parse("places".into(), &args).unwrap();
+        let Value::Array(found) = cmd.execute(&mut engine).unwrap() else {
+            panic!("Expected Array");
+        };
+        assert_eq!(found.len(), 2);
+        let Value::Array(item) = &found[1] else {
+            panic!("Expected Array item");
+        };
+        let Value::Float(dist) = item[1] else {
+            panic!("Expected distance");
+        };
+        // ~111м в футах
+        assert!((dist - 365.0).abs() < 5.0);
+        assert!(matches!(&item[2], Value::Array(coord) if coord.len() == 2));
+    }
+
+    /// Тест проверяет `GEOSEARCH` для отсутствующего ключа и участника, а
+    /// также отказ от `STOREDIST`.
+    #[test]
+    fn test_geosearch_errors() {
+        let mut engine = setup_store();
+        let args = ["FROMLONLAT", "0", "0", "BYRADIUS", "1", "km"];
+        let cmd = GeoSearchCommand::parse("missing".into(), &args).unwrap();
+        assert_eq!(cmd.execute(&mut engine).unwrap(), Value::Array(vec![]));
+
+        let args = ["FROMMEMBER", "missing", "BYBOX", "1", "1", "km"];
+        let cmd = GeoSearchCommand::parse("places".into(), &args).unwrap();
+        assert!(matches!(
+            cmd.execute(&mut engine),
+            Err(StoreError::KeyNotFound)
+        ));
+
+        let args = ["FROMMEMBER", "origin", "BYBOX", "1", "1", "km", "STOREDIST"];
+        assert!(GeoSearchCommand::parse("places".into(), &args).is_err());
+        let args = ["FROMMEMBER", "origin", "BYBOX", "1", "1", "km", "WITHDIST"];
+        assert!(GeoSearchStoreCommand::parse("dst".into(), "places".into(), &args).is_err());
+    }
 }
@@ -1,8 +1,9 @@
 use std::iter::empty;
 
-use rand::seq::IteratorRandom;
-
-use crate::{CommandExecute, QuickList, Sds, SmartHash, StorageEngine, StoreError, Value};
+use crate::{
+    CommandExecute, KeyspaceEvent, QuickList, Sds, SmartHash, StorageEngine, StoreError, Value,
+    DEFAULT_SCAN_COUNT,
+};
 
 /// Команда HSET — устанавливает одно или несколько полей хеша.
 #[derive(Debug)]
@@ -32,6 +33,7 @@ impl CommandExecute for HSetCommand {
                 }
 
                 store.set(&key, Value::Hash(sh))?;
+                store.notify_keyspace_event(KeyspaceEvent::HSet, &key);
                 Ok(Value::Int(added))
             }
             Some(_) => Err(StoreError::InvalidType),
@@ -49,6 +51,7 @@ impl CommandExecute for HSetCommand {
                 }
 
                 store.set(&key, Value::Hash(sh))?;
+                store.notify_keyspace_event(KeyspaceEvent::HSet, &key);
                 Ok(Value::Int(added))
             }
         }
@@ -104,26 +107,18 @@ impl CommandExecute for HmGetCommand {
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        let key = Sds::from_str(&self.key);
-
-        match store.get(&key)? {
-            Some(Value::Hash(sh)) => {
-                let result = self
-                    .fields
-                    .iter()
-                    .map(|f| {
-                        let field = Sds::from_str(f);
-                        match sh.get(&field) {
-                            Some(v) => Value::Str(v.clone()),
-                            None => Value::Null,
-                        }
-                    })
-                    .collect();
-                Ok(Value::Array(result))
-            }
-            Some(_) => Err(StoreError::InvalidType),
-            None => Ok(Value::Array(vec![Value::Null; self.fields.len()])),
-        }
+        let fields: Vec<Sds> = self.fields.iter().map(|f| Sds::from_str(f)).collect();
+        let values = store.hmget(
+            &Sds::from_str(&self.key),
+            &fields.iter().collect::<Vec<_>>(),
+        )?;
+
+        Ok(Value::Array(
+            values
+                .into_iter()
+                .map(|v| v.map_or(Value::Null, Value::Str))
+                .collect(),
+        ))
     }
 
     fn command_name(&self) -> &'static str {
@@ -190,7 +185,7 @@ impl CommandExecute for HExistsCommand {
         let field = Sds::from_str(&self.field);
 
         match store.get(&key)? {
-            Some(Value::Hash(sh)) => Ok(Value::Int(i64::from(sh.contains(&field)))),
+            Some(Value::Hash(sh)) => Ok(Value::Int(i64::from(sh.contains_key(&field)))),
             Some(_) => Err(StoreError::InvalidType),
             None => Ok(Value::Int(0)),
         }
@@ -241,7 +236,7 @@ impl CommandExecute for HKeysCommand {
 
         match store.get(&key)? {
             Some(Value::Hash(sh)) => {
-                let keys = sh.keys();
+                let keys = sh.keys().cloned();
                 Ok(Value::List(QuickList::from_iter(keys, 64)))
             }
             Some(_) => Err(StoreError::InvalidType),
@@ -269,7 +264,7 @@ impl CommandExecute for HValsCommand {
 
         match store.get(&key)? {
             Some(Value::Hash(sh)) => {
-                let vals = sh.values();
+                let vals = sh.values().cloned();
                 Ok(Value::List(QuickList::from_iter(vals, 64)))
             }
             Some(_) => Err(StoreError::InvalidType),
@@ -298,15 +293,18 @@ impl CommandExecute for HGetAllCommand {
         let key = Sds::from_str(&self.key);
 
         match store.get(&key)? {
-            Some(Value::Hash(mut sh)) => {
+            Some(Value::Hash(sh)) => {
                 // сортируем ключи для предсказуемого порядка
-                let mut entries: Vec<_> = sh.iter().collect();
-                entries.sort_by_key(|(k1, _)| *k1);
+                let mut fields: Vec<&Sds> = sh.keys().collect();
+                fields.sort();
+                let values = sh.get_many(&fields);
 
                 let result: QuickList<Sds> = QuickList::from_iter(
-                    entries
+                    fields
                         .into_iter()
-                        .flat_map(|(k, v)| [k.clone(), v.clone()]),
+                        .zip(values)
+                        .filter_map(|(k, v)| v.map(|v| [k.clone(), v]))
+                        .flatten(),
                     64,
                 );
                 Ok(Value::List(result))
@@ -323,7 +321,7 @@ impl CommandExecute for HGetAllCommand {
 
 /// Команда HRANDFIELD — возвращает одно или несколько случайных полей хеша.
 /// Если count отрицательный — возвращает ровно |count| элементов, повторения
-/// возможны.
+/// возможны; |count| больше `MAX_RANDOM_COUNT` отклоняется.
 #[derive(Debug)]
 pub struct HRandFieldCommand {
     pub key: String,
@@ -338,49 +336,16 @@ impl CommandExecute for HRandFieldCommand {
     ) -> Result<Value, StoreError> {
         let key = Sds::from_str(&self.key);
 
-        match store.get(&key)? {
-            Some(Value::Hash(sh)) => {
-                let entries = sh.entries();
-                let mut rng = rand::thread_rng();
-
-                match self.count {
-                    Some(count) if count < 0 => {
-                        let n = count.unsigned_abs() as usize;
-                        let items: Vec<_> = (0..n)
-                            .filter_map(|_| entries.iter().choose(&mut rng).cloned())
-                            .collect();
-
-                        let flat: Vec<Sds> = if self.with_values {
-                            items.into_iter().flat_map(|(f, v)| [f, v]).collect()
-                        } else {
-                            items.into_iter().map(|(f, _)| f).collect()
-                        };
-
-                        Ok(Value::List(QuickList::from_iter(flat, 64)))
-                    }
-                    Some(count) => {
-                        let n = (count as usize).min(entries.len());
-                        let items: Vec<_> = entries.into_iter().choose_multiple(&mut rng, n);
-
-                        let flat: Vec<Sds> = if self.with_values {
-                            items.into_iter().flat_map(|(f, v)| [f, v]).collect()
-                        } else {
-                            items.into_iter().map(|(f, _)| f).collect()
-                        };
-
-                        Ok(Value::List(QuickList::from_iter(flat, 64)))
-                    }
-                    None => match entries.into_iter().choose(&mut rng) {
-                        Some((field, _)) => Ok(Value::Str(field)),
-                        None => Ok(Value::Null),
-                    },
-                }
+        match self.count {
+            Some(count) => {
+                let items = store.hrandfield(&key, count as isize, self.with_values)?;
+                Ok(Value::List(QuickList::from_iter(items, 64)))
             }
-            Some(_) => Err(StoreError::InvalidType),
-            None => match self.count {
-                Some(_) => Ok(Value::List(QuickList::from_iter(empty(), 64))),
-                None => Ok(Value::Null),
-            },
+            None => Ok(store
+                .hrandfield(&key, 1, false)?
+                .into_iter()
+                .next()
+                .map_or(Value::Null, Value::Str)),
         }
     }
 
@@ -389,49 +354,72 @@ impl CommandExecute for HRandFieldCommand {
     }
 }
 
-/// Команда HINCRBY — автомарно увеличивает целочисленное поле хеша.
+/// Команда HSCAN — постранично обходит поля хеша по курсору.
+///
+/// Возвращает массив `[next_cursor, [field, value, ...]]`; курсор `0` означает
+/// конец обхода.
 #[derive(Debug)]
-pub struct HIncrByCommand {
+pub struct HScanCommand {
     pub key: String,
-    pub field: String,
-    pub increment: i64,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: Option<usize>,
 }
 
-impl CommandExecute for HIncrByCommand {
+impl CommandExecute for HScanCommand {
     fn execute(
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
         let key = Sds::from_str(&self.key);
-        let field = Sds::from_str(&self.field);
 
-        // Получаем хеш или создаём новый
-        let mut hash = match store.get(&key)? {
-            Some(Value::Hash(h)) => h,
+        let (next, page) = match store.get(&key)? {
+            Some(Value::Hash(sh)) => sh.scan(
+                self.cursor,
+                self.pattern.as_deref(),
+                self.count.unwrap_or(DEFAULT_SCAN_COUNT),
+            ),
             Some(_) => return Err(StoreError::InvalidType),
-            None => SmartHash::new(),
+            None => (0, Vec::new()),
         };
 
-        // Получаем текущее значение (или 0 если не существует)
-        let current: i64 = match hash.get(&field) {
-            Some(v) => {
-                let s = v.as_str().map_err(|_| StoreError::InvalidValue)?;
-                s.parse().map_err(|_| StoreError::InvalidValue)?
-            }
-            None => 0,
-        };
+        let items = page
+            .into_iter()
+            .flat_map(|(field, value)| [Value::Str(field), Value::Str(value)])
+            .collect();
+        Ok(Value::Array(vec![
+            Value::Str(Sds::from_str(&next.to_string())),
+            Value::Array(items),
+        ]))
+    }
 
-        // Выполняем checked_add для обнаружения overflow
-        let new_value = current
-            .checked_add(self.increment)
-            .ok_or(StoreError::Overflow)?;
+    fn command_name(&self) -> &'static str {
+        "HSCAN"
+    }
+}
 
-        // Сохраняем новое значение
-        let new_str = new_value.to_string();
-        hash.insert(field, Sds::from_str(&new_str));
-        store.set(&key, Value::Hash(hash))?;
+/// Команда HINCRBY — атомарно увеличивает целочисленное поле хеша.
+///
+/// Отсутствующее поле создаётся со значением `increment`. Переполнение `i64`
+/// возвращает `Overflow`, нечисловое значение поля — `InvalidValue`.
+#[derive(Debug)]
+pub struct HIncrByCommand {
+    pub key: String,
+    pub field: String,
+    pub increment: i64,
+}
 
-        Ok(Value::Int(new_value))
+impl CommandExecute for HIncrByCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let value = store.hincrby(
+            &Sds::from_str(&self.key),
+            &Sds::from_str(&self.field),
+            self.increment,
+        )?;
+        Ok(Value::Int(value))
     }
 
     fn command_name(&self) -> &'static str {
@@ -440,6 +428,9 @@ impl CommandExecute for HIncrByCommand {
 }
 
 /// Команда HINCRBYFLOAT — атомарно увеличивает поле с плавающей точкой.
+///
+/// Результат `NaN` или бесконечность отклоняется с `InvalidValue`, поле при
+/// этом не изменяется.
 #[derive(Debug)]
 pub struct HIncrByFloatCommand {
     pub key: String,
@@ -452,39 +443,12 @@ impl CommandExecute for HIncrByFloatCommand {
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        let key = Sds::from_str(&self.key);
-        let field = Sds::from_str(&self.field);
-
-        let mut hash = match store.get(&key)? {
-            Some(Value::Hash(h)) => h,
-            Some(_) => return Err(StoreError::InvalidType),
-            None => SmartHash::new(),
-        };
-
-        // Получаем текущее значение (или 0.0 если не существует)
-        let current: f64 = match hash.get(&field) {
-            Some(v) => {
-                let s = v.as_str().map_err(|_| StoreError::InvalidValue)?;
-                s.parse().map_err(|_| StoreError::InvalidValue)?
-            }
-            None => 0.0,
-        };
-
-        if self.increment.is_nan() {
-            return Err(StoreError::InvalidValue);
-        }
-
-        let new_value = current + self.increment;
-
-        if !new_value.is_finite() {
-            return Err(StoreError::InvalidValue);
-        }
-
-        // Сохраняем новое значение
-        hash.insert(field, Sds::from_str(&new_value.to_string()));
-        store.set(&key, Value::Hash(hash))?;
-
-        Ok(Value::Float(new_value))
+        let value = store.hincrbyfloat(
+            &Sds::from_str(&self.key),
+            &Sds::from_str(&self.field),
+            self.increment,
+        )?;
+        Ok(Value::Float(value))
     }
 
     fn command_name(&self) -> &'static str {
@@ -685,6 +649,56 @@ mod tests {
         );
     }
 
+    /// Тест проверяет HMGET, когда все запрошенные поля присутствуют, включая
+    /// повторно запрошенное поле.
+    #[test]
+    fn test_hmget_all_present() {
+        let mut store = create_store();
+        setup_hash(&mut store);
+
+        let res = HmGetCommand {
+            key: "user:1".into(),
+            fields: vec!["city".into(), "name".into(), "city".into()],
+        }
+        .execute(&mut store)
+        .unwrap();
+
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("Kungur")),
+                Value::Str(Sds::from_str("Anton")),
+                Value::Str(Sds::from_str("Kungur")),
+            ])
+        );
+    }
+
+    /// Тест проверяет HMGET, когда ни одного поля нет в существующем хеше, и
+    /// ошибку типа для ключа, не являющегося хешем.
+    #[test]
+    fn test_hmget_none_present_and_wrong_type() {
+        let mut store = create_store();
+        setup_hash(&mut store);
+
+        let res = HmGetCommand {
+            key: "user:1".into(),
+            fields: vec!["x".into(), "y".into()],
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Array(vec![Value::Null, Value::Null]));
+
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        let res = HmGetCommand {
+            key: "str".into(),
+            fields: vec!["x".into()],
+        }
+        .execute(&mut store);
+        assert!(matches!(res, Err(StoreError::WrongType(_))));
+    }
+
     #[test]
     fn test_hmget_missing_key() {
         let mut store = create_store();
@@ -865,6 +879,22 @@ mod tests {
         }
     }
 
+    /// Тест проверяет, что HRANDFIELD отклоняет слишком большой
+    /// отрицательный count до выборки.
+    #[test]
+    fn test_hrandfield_count_out_of_range() {
+        let mut store = create_store();
+        setup_hash(&mut store);
+
+        let res = HRandFieldCommand {
+            key: "user:1".into(),
+            count: Some(-i64::MAX),
+            with_values: true,
+        }
+        .execute(&mut store);
+        assert!(matches!(res, Err(StoreError::InvalidArgument(_))));
+    }
+
     #[test]
     fn test_hrandfield_with_values() {
         let mut store = create_store();
@@ -1052,7 +1082,7 @@ mod tests {
         }
         .execute(&mut store);
 
-        assert!(matches!(res, Err(StoreError::InvalidType)));
+        assert!(matches!(res, Err(StoreError::WrongType(_))));
     }
 
     #[test]
@@ -1204,6 +1234,141 @@ mod tests {
         }
         .execute(&mut store);
 
-        assert!(matches!(res, Err(StoreError::InvalidType)));
+        assert!(matches!(res, Err(StoreError::WrongType(_))));
+    }
+
+    /// Тест проверяет, что HSCAN с малым COUNT за несколько шагов обходит все
+    /// поля, а MATCH отбирает только подходящие.
+    #[test]
+    fn test_hscan_iterates_all_fields() {
+        let mut store = create_store();
+        HSetCommand {
+            key: "h".into(),
+            entries: (0..5)
+                .map(|i| (format!("f{i}"), format!("v{i}")))
+                .chain([("other".to_string(), "x".to_string())])
+                .collect(),
+        }
+        .execute(&mut store)
+        .unwrap();
+
+        let mut cursor = 0;
+        let mut fields = Vec::new();
+        loop {
+            let reply = HScanCommand {
+                key: "h".into(),
+                cursor,
+                pattern: Some("f*".into()),
+                count: Some(2),
+            }
+            .execute(&mut store)
+            .unwrap();
+            let Value::Array(parts) = reply else {
+                panic!("expected array");
+            };
+            let (Value::Str(next), Value::Array(items)) = (&parts[0], &parts[1]) else {
+                panic!("unexpected reply shape");
+            };
+            for pair in items.chunks(2) {
+                if let [Value::Str(f), Value::Str(v)] = pair {
+                    assert_eq!(&f.as_slice()[1..], &v.as_slice()[1..]);
+                    fields.push(f.as_str().unwrap().to_string());
+                }
+            }
+            cursor = next.as_str().unwrap().parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        fields.sort();
+        assert_eq!(fields, vec!["f0", "f1", "f2", "f3", "f4"]);
+    }
+
+    /// Тест проверяет HSCAN для отсутствующего ключа и ключа другого типа.
+    #[test]
+    fn test_hscan_missing_and_wrong_type() {
+        let mut store = create_store();
+        let scan = |key: &str| HScanCommand {
+            key: key.into(),
+            cursor: 0,
+            pattern: None,
+            count: None,
+        };
+
+        assert_eq!(
+            scan("nope").execute(&mut store).unwrap(),
+            Value::Array(vec![Value::Str(Sds::from_str("0")), Value::Array(vec![])])
+        );
+
+        store
+            .set(&Sds::from_str("s"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        assert!(matches!(
+            scan("s").execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    /// Тест проверяет, что неудачный HINCRBY не создаёт ключ и не меняет
+    /// существующее поле, а ошибка переполнения отображается как `overflow`.
+    #[test]
+    fn test_hincrby_error_leaves_hash_untouched() {
+        let mut store = create_store();
+
+        let res = HIncrByFloatCommand {
+            key: "fresh".into(),
+            field: "f".into(),
+            increment: f64::NAN,
+        }
+        .execute(&mut store);
+        assert!(matches!(res, Err(StoreError::InvalidValue)));
+        assert_eq!(store.get(&Sds::from_str("fresh")).unwrap(), None);
+
+        HSetCommand {
+            key: "h".into(),
+            entries: vec![("n".into(), i64::MAX.to_string())],
+        }
+        .execute(&mut store)
+        .unwrap();
+        let err = HIncrByCommand {
+            key: "h".into(),
+            field: "n".into(),
+            increment: 1,
+        }
+        .execute(&mut store)
+        .unwrap_err();
+        assert_eq!(err.to_string(), "overflow");
+
+        let res = HGetCommand {
+            key: "h".into(),
+            field: "n".into(),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Str(Sds::from_str(&i64::MAX.to_string())));
+    }
+
+    /// Тест проверяет, что HEXISTS, HKEYS и HVALS на ключе другого типа
+    /// возвращают `InvalidType`.
+    #[test]
+    fn test_field_inspection_wrong_type() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        let hexists = HExistsCommand {
+            key: "str".into(),
+            field: "f".into(),
+        }
+        .execute(&mut store);
+        assert!(matches!(hexists, Err(StoreError::InvalidType)));
+
+        let hkeys = HKeysCommand { key: "str".into() }.execute(&mut store);
+        assert!(matches!(hkeys, Err(StoreError::InvalidType)));
+
+        let hvals = HValsCommand { key: "str".into() }.execute(&mut store);
+        assert!(matches!(hvals, Err(StoreError::InvalidType)));
     }
 }
@@ -1,6 +1,9 @@
 use super::CommandExecute;
+use crate::{Hll, Sds, StorageEngine, StoreError, Value};
 
 /// Команда PFADD добавляет элементы в структуру HyperLogLog.
+///
+/// Возвращает 1, если ключ создан или изменился хотя бы один регистр.
 #[derive(Debug)]
 pub struct PfAddCommand {
     pub key: String,
@@ -10,9 +13,23 @@ pub struct PfAddCommand {
 impl CommandExecute for PfAddCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("PFADD is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+        let (mut hll, created) = match store.get(&key)? {
+            Some(Value::HyperLogLog(hll)) => (hll, false),
+            Some(_) => return Err(StoreError::InvalidType),
+            None => (Box::new(Hll::new()), true),
+        };
+
+        let mut changed = created;
+        for element in &self.elements {
+            changed |= hll.add(element.as_bytes());
+        }
+        if changed {
+            store.set(&key, Value::HyperLogLog(hll))?;
+        }
+        Ok(Value::Int(changed as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -30,9 +47,15 @@ pub struct PfCountCommand {
 impl CommandExecute for PfCountCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("PFCOUNT is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        match store.get(&Sds::from_str(&self.key))? {
+            Some(Value::HyperLogLog(hll)) => {
+                Ok(Value::Int(hll.estimate_cardinality().round() as i64))
+            }
+            Some(_) => Err(StoreError::InvalidType),
+            None => Ok(Value::Int(0)),
+        }
     }
 
     fn command_name(&self) -> &'static str {
@@ -43,6 +66,7 @@ impl CommandExecute for PfCountCommand {
 /// Команда PFMERGE объединяет несколько HyperLogLog структур в одну.
 #[derive(Debug)]
 pub struct PfMergeCommand {
+    /// Ключ результата; его прежнее значение участвует в объединении
     pub data: String,
     pub sources: Vec<String>,
 }
@@ -50,12 +74,166 @@ pub struct PfMergeCommand {
 impl CommandExecute for PfMergeCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("PFMERGE is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let dest = Sds::from_str(&self.data);
+        let sources: Vec<Sds> = self.sources.iter().map(|s| Sds::from_str(s)).collect();
+        let sources: Vec<&Sds> = sources.iter().collect();
+        store.pfmerge(&dest, &sources)?;
+        Ok(Value::Str(Sds::from_str("OK")))
     }
 
     fn command_name(&self) -> &'static str {
         "PFMERGE"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryStore;
+
+    // Вспомогательная функция для создания нового хранилища в памяти.
+    fn create_store() -> StorageEngine {
+        StorageEngine::Memory(InMemoryStore::new())
+    }
+
+    // Создаёт HLL с элементами `{prefix}_0..{prefix}_{count}`.
+    fn hll_of(
+        prefix: &str,
+        count: usize,
+    ) -> Value {
+        let mut hll = Hll::new();
+        for i in 0..count {
+            hll.add(format!("{prefix}_{i}").as_bytes());
+        }
+        Value::HyperLogLog(Box::new(hll))
+    }
+
+    fn cardinality(
+        store: &StorageEngine,
+        key: &str,
+    ) -> f64 {
+        match store.get(&Sds::from_str(key)).unwrap() {
+            Some(Value::HyperLogLog(hll)) => hll.estimate_cardinality(),
+            other => panic!("Expected HyperLogLog, got {other:?}"),
+        }
+    }
+
+    /// Тест проверяет PFADD/PFCOUNT в разреженном представлении и после
+    /// автоматического перехода в плотное.
+    #[test]
+    fn test_pfadd_pfcount_sparse_and_dense() {
+        let mut store = create_store();
+        let pfadd = |elements: Vec<String>| PfAddCommand {
+            key: "hll".to_string(),
+            elements,
+        };
+        let pfcount = PfCountCommand {
+            key: "hll".to_string(),
+        };
+        assert_eq!(pfcount.execute(&mut store).unwrap(), Value::Int(0));
+
+        let small: Vec<String> = (0..100).map(|i| format!("e_{i}")).collect();
+        assert_eq!(
+            pfadd(small.clone()).execute(&mut store).unwrap(),
+            Value::Int(1)
+        );
+        // Повторное добавление не меняет регистров.
+        assert_eq!(pfadd(small).execute(&mut store).unwrap(), Value::Int(0));
+        match store.get(&Sds::from_str("hll")).unwrap() {
+            Some(Value::HyperLogLog(hll)) => assert!(hll.is_sparse()),
+            other => panic!("Expected HyperLogLog, got {other:?}"),
+        }
+        let Value::Int(count) = pfcount.execute(&mut store).unwrap() else {
+            panic!("Expected integer reply");
+        };
+        assert!((count - 100).abs() <= 2);
+
+        let large: Vec<String> = (0..20_000).map(|i| format!("e_{i}")).collect();
+        pfadd(large).execute(&mut store).unwrap();
+        match store.get(&Sds::from_str("hll")).unwrap() {
+            Some(Value::HyperLogLog(hll)) => assert!(!hll.is_sparse()),
+            other => panic!("Expected HyperLogLog, got {other:?}"),
+        }
+        let Value::Int(count) = pfcount.execute(&mut store).unwrap() else {
+            panic!("Expected integer reply");
+        };
+        assert!((count - 20_000).abs() < 400);
+    }
+
+    /// Тест проверяет, что PFADD с пустым списком создаёт пустой HLL, а
+    /// по ключу другого типа возвращается ошибка.
+    #[test]
+    fn test_pfadd_creates_key_and_rejects_wrong_type() {
+        let mut store = create_store();
+        let cmd = PfAddCommand {
+            key: "empty".to_string(),
+            elements: vec![],
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(1));
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(0));
+
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+        let cmd = PfAddCommand {
+            key: "str".to_string(),
+            elements: vec!["a".to_string()],
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    /// Тест проверяет, что объединение двух непересекающихся HLL даёт
+    /// оценку, примерно равную сумме их мощностей.
+    #[test]
+    fn test_pfmerge_disjoint() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("a"), hll_of("a", 3000)).unwrap();
+        store.set(&Sds::from_str("b"), hll_of("b", 2000)).unwrap();
+        let sum = cardinality(&store, "a") + cardinality(&store, "b");
+
+        let cmd = PfMergeCommand {
+            data: "dest".to_string(),
+            sources: vec!["a".to_string(), "b".to_string(), "missing".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+
+        let merged = cardinality(&store, "dest");
+        assert!((merged - sum).abs() / sum < 0.02);
+    }
+
+    /// Тест проверяет, что прежнее значение `destkey` участвует в
+    /// объединении, а не-HLL источник отклоняется.
+    #[test]
+    fn test_pfmerge_includes_dest_and_rejects_wrong_type() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("dest"), hll_of("d", 100)).unwrap();
+        store.set(&Sds::from_str("a"), hll_of("a", 100)).unwrap();
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+
+        let cmd = PfMergeCommand {
+            data: "dest".to_string(),
+            sources: vec!["a".to_string()],
+        };
+        cmd.execute(&mut store).unwrap();
+        assert!((cardinality(&store, "dest") - 200.0).abs() < 5.0);
+
+        let cmd = PfMergeCommand {
+            data: "dest".to_string(),
+            sources: vec!["str".to_string()],
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+}
@@ -1,4 +1,19 @@
-use crate::{CommandExecute, Sds, StorageEngine, StoreError, Value};
+use std::{
+    cmp::Ordering,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tracing::warn;
+
+use crate::{
+    engine::{dump_payload, restore_payload},
+    network::connection_state::ConnectionInfo,
+    CommandExecute, FlushMode, QuickList, Sds, StorageEngine, StoreError, Value,
+};
+
+/// Максимальный размер сегмента списка, сохраняемого `SORT ... STORE`.
+const SORT_STORE_SEGMENT: usize = 64;
 
 /// Команда DEL — удаляет значение по ключу.
 #[derive(Debug)]
@@ -90,16 +105,20 @@ impl CommandExecute for RenameNxCommand {
     }
 }
 
-/// Команда FLUSHDB — удаляет все ключи из текущей базы данных.
-#[derive(Debug)]
-pub struct FlushDbCommand;
+/// Команда FLUSHDB [ASYNC|SYNC] — удаляет все ключи из текущей базы данных.
+///
+/// В режиме `ASYNC` ответ возвращается сразу, а память освобождается в фоне.
+#[derive(Debug, Default)]
+pub struct FlushDbCommand {
+    pub mode: FlushMode,
+}
 
 impl CommandExecute for FlushDbCommand {
     fn execute(
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        store.flushdb()?;
+        store.flushdb_mode(self.mode)?;
         Ok(Value::Str(Sds::from_str("OK")))
     }
 
@@ -108,14 +127,602 @@ impl CommandExecute for FlushDbCommand {
     }
 }
 
+/// Команда FLUSHALL [ASYNC|SYNC] — удаляет все ключи из всех логических баз.
+#[derive(Debug, Default)]
+pub struct FlushAllCommand {
+    pub mode: FlushMode,
+}
+
+impl CommandExecute for FlushAllCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        store.flushall(self.mode)?;
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FLUSHALL"
+    }
+}
+
+/// Команда SELECT — переключает соединение на логическую базу `db`.
+///
+/// Последующие команды соединения обработчик выполняет на представлении
+/// этой базы (см. [`StorageEngine::select`]).
+#[derive(Debug)]
+pub struct SelectCommand {
+    pub db: usize,
+    pub connection: Arc<ConnectionInfo>,
+}
+
+impl CommandExecute for SelectCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if self.db >= store.databases() {
+            return Err(StoreError::InvalidArgument(
+                "DB index is out of range".to_string(),
+            ));
+        }
+        self.connection.set_db_index(self.db);
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SELECT"
+    }
+}
+
+/// Команда COPY — копирует значение ключа (вместе с TTL) в другой ключ.
+#[derive(Debug)]
+pub struct CopyCommand {
+    pub source: String,
+    pub destination: String,
+    pub db: Option<usize>,
+    pub replace: bool,
+}
+
+impl CommandExecute for CopyCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let copied = store.copy(
+            &Sds::from_str(&self.source),
+            &Sds::from_str(&self.destination),
+            self.db,
+            self.replace,
+        )?;
+        Ok(Value::Int(if copied { 1 } else { 0 }))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "COPY"
+    }
+}
+
+/// Команда EXPIRETIME — возвращает абсолютное время истечения ключа в
+/// unix-секундах, `-1` для ключа без TTL и `-2` для отсутствующего ключа.
+#[derive(Debug)]
+pub struct ExpireTimeCommand {
+    pub key: String,
+}
+
+impl CommandExecute for ExpireTimeCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let at = match store.expiretime(&Sds::from_str(&self.key))? {
+            Some(ms) if ms >= 0 => (ms + 500) / 1000,
+            Some(no_ttl) => no_ttl,
+            None => -2,
+        };
+        Ok(Value::Int(at))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "EXPIRETIME"
+    }
+}
+
+/// Команда PEXPIRETIME — то же, что EXPIRETIME, но в unix-миллисекундах.
+#[derive(Debug)]
+pub struct PExpireTimeCommand {
+    pub key: String,
+}
+
+impl CommandExecute for PExpireTimeCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let at = store.expiretime(&Sds::from_str(&self.key))?;
+        Ok(Value::Int(at.unwrap_or(-2)))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "PEXPIRETIME"
+    }
+}
+
+/// Размер пространства ключей, начиная с которого `KEYS *` пишет
+/// предупреждение в лог.
+pub const KEYS_WARN_THRESHOLD: usize = 10_000;
+
+/// Команда KEYS — возвращает все ключи, подходящие под glob-шаблон.
+///
+/// Обходит всё пространство ключей (O(n)), поэтому на больших базах
+/// предпочтительнее `SCAN`.
+#[derive(Debug)]
+pub struct KeysCommand {
+    pub pattern: String,
+}
+
+impl CommandExecute for KeysCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let keys = store.keys(&self.pattern)?;
+        if self.pattern == "*" && keys.len() >= KEYS_WARN_THRESHOLD {
+            warn!(
+                keys = keys.len(),
+                "KEYS * scanned a large key space; consider SCAN instead"
+            );
+        }
+        Ok(Value::Array(keys.into_iter().map(Value::Str).collect()))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "KEYS"
+    }
+}
+
+/// Команда TYPE — возвращает тип значения ключа (`string`, `list`, `set`,
+/// `zset`, `hash`, `stream`) или `none` для отсутствующего ключа.
+///
+/// Ответ — статусная строка; в текстовом протоколе она передаётся как
+/// `+type`.
+#[derive(Debug)]
+pub struct TypeCommand {
+    pub key: String,
+}
+
+impl CommandExecute for TypeCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let ty = store.type_of(&Sds::from_str(&self.key))?;
+        Ok(Value::Str(Sds::from_str(
+            ty.map_or("none", |ty| ty.as_str()),
+        )))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "TYPE"
+    }
+}
+
+/// Команда TOUCH — отмечает обращение к ключам (время последнего доступа
+/// для LRU) и возвращает число существующих ключей из списка.
+#[derive(Debug)]
+pub struct TouchCommand {
+    pub keys: Vec<String>,
+}
+
+impl CommandExecute for TouchCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let keys: Vec<Sds> = self.keys.iter().map(|key| Sds::from_str(key)).collect();
+        let refs: Vec<&Sds> = keys.iter().collect();
+        Ok(Value::Int(store.touch(&refs)? as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "TOUCH"
+    }
+}
+
+/// Команда PERSIST — снимает TTL с ключа.
+///
+/// Возвращает `1`, если TTL был снят, и `0`, если у ключа нет TTL или ключ
+/// отсутствует.
+#[derive(Debug)]
+pub struct PersistCommand {
+    pub key: String,
+}
+
+impl CommandExecute for PersistCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let persisted = store.persist(&Sds::from_str(&self.key))?;
+        Ok(Value::Int(if persisted { 1 } else { 0 }))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "PERSIST"
+    }
+}
+
+/// Команда TTL — возвращает оставшееся время жизни ключа в секундах, `-1`
+/// для ключа без TTL и `-2` для отсутствующего ключа.
+#[derive(Debug)]
+pub struct TtlCommand {
+    pub key: String,
+}
+
+impl CommandExecute for TtlCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let ttl = match remaining_ttl_ms(store, &self.key)? {
+            ms if ms >= 0 => (ms + 500) / 1000,
+            no_ttl => no_ttl,
+        };
+        Ok(Value::Int(ttl))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "TTL"
+    }
+}
+
+/// Команда PTTL — то же, что TTL, но в миллисекундах.
+#[derive(Debug)]
+pub struct PTtlCommand {
+    pub key: String,
+}
+
+impl CommandExecute for PTtlCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        Ok(Value::Int(remaining_ttl_ms(store, &self.key)?))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "PTTL"
+    }
+}
+
+/// Команда DUMP — сериализует значение ключа в полезную нагрузку ZDB с
+/// версией и CRC64 (см. [`dump_payload`]).
+///
+/// Возвращает двоичную строку или `Null` для отсутствующего ключа.
+#[derive(Debug)]
+pub struct DumpCommand {
+    pub key: String,
+}
+
+impl CommandExecute for DumpCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(value) = store.get(&Sds::from_str(&self.key))? else {
+            return Ok(Value::Null);
+        };
+        let payload = dump_payload(&value).map_err(|e| StoreError::InvalidData(e.to_string()))?;
+        Ok(Value::Str(Sds::from_vec(payload)))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "DUMP"
+    }
+}
+
+/// Команда RESTORE — восстанавливает ключ из полезной нагрузки DUMP.
+///
+/// `ttl` задаётся в миллисекундах (`0` — без TTL), с `absttl` — как
+/// абсолютное unix-время в миллисекундах. Без `replace` существующий ключ не
+/// перезаписывается. `idletime` и `freq` задают метаданные доступа и
+/// взаимоисключающие.
+#[derive(Debug)]
+pub struct RestoreCommand {
+    pub key: String,
+    pub ttl: u64,
+    pub payload: Vec<u8>,
+    pub replace: bool,
+    pub absttl: bool,
+    pub idletime: Option<u64>,
+    pub freq: Option<u8>,
+}
+
+impl CommandExecute for RestoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if self.idletime.is_some() && self.freq.is_some() {
+            return Err(StoreError::InvalidArgument(
+                "IDLETIME and FREQ are mutually exclusive".into(),
+            ));
+        }
+        let value = restore_payload(&self.payload).map_err(|_| {
+            StoreError::InvalidArgument("DUMP payload version or checksum are wrong".into())
+        })?;
+
+        let key = Sds::from_str(&self.key);
+        if self.replace {
+            store.del(&key)?;
+        } else if store.type_of(&key)?.is_some() {
+            return Err(StoreError::InvalidOperation(
+                "BUSYKEY Target key name already exists.".into(),
+            ));
+        }
+
+        let ttl = match (self.ttl, self.absttl) {
+            (0, _) => None,
+            (at_ms, true) => {
+                let now_ms = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                match at_ms.checked_sub(now_ms).filter(|ms| *ms > 0) {
+                    Some(ms) => Some(Duration::from_millis(ms)),
+                    // Уже истёкший ключ не создаётся.
+                    None => return Ok(Value::Str(Sds::from_str("OK"))),
+                }
+            }
+            (ms, false) => Some(Duration::from_millis(ms)),
+        };
+
+        store.set(&key, value)?;
+        if ttl.is_some() {
+            store.set_expiry(&key, ttl)?;
+        }
+        if self.idletime.is_some() || self.freq.is_some() {
+            store.set_access_meta(&key, self.idletime, self.freq)?;
+        }
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "RESTORE"
+    }
+}
+
+/// Команда SORT — сортирует элементы списка, множества или отсортированного
+/// множества (скоры игнорируются).
+///
+/// - Без `by` элементы сравниваются как числа, с `alpha` — как строки;
+///   нечисловой элемент без `alpha` — ошибка.
+/// - `by` задаёт шаблон внешних ключей-весов (`weight_*`, `obj_*->field`);
+///   шаблон без `*` отключает сортировку.
+/// - `get` — шаблоны значений, возвращаемых вместо элементов (`#` — сам
+///   элемент).
+/// - `limit` — `(offset, count)` после сортировки.
+/// - `store` сохраняет результат списком и возвращает его длину.
+#[derive(Debug, Default)]
+pub struct SortCommand {
+    pub key: String,
+    pub by: Option<String>,
+    pub limit: Option<(usize, usize)>,
+    pub get: Vec<String>,
+    pub desc: bool,
+    pub alpha: bool,
+    pub store: Option<String>,
+}
+
+/// Ключ сравнения элемента в `SORT`.
+enum SortKey {
+    Num(f64),
+    Alpha(Option<Sds>),
+}
+
+impl CommandExecute for SortCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let elements: Vec<Sds> = match store.get(&Sds::from_str(&self.key))? {
+            None => Vec::new(),
+            Some(Value::List(list)) => list.into_iter().collect(),
+            Some(Value::Set(set)) => set.into_iter().collect(),
+            Some(Value::ZSet { sorted, .. }) => sorted.iter().map(|(_, m)| m.clone()).collect(),
+            Some(_) => {
+                return Err(StoreError::WrongType(
+                    "SORT on a key that is not a list, set or sorted set".into(),
+                ))
+            }
+        };
+
+        let by = self.by.as_deref().filter(|pattern| pattern.contains('*'));
+        let sort = self.by.is_none() || by.is_some();
+        let mut elements = if sort {
+            let mut items = elements
+                .into_iter()
+                .map(|element| {
+                    let key = self.sort_key(store, by, &element)?;
+                    Ok((element, key))
+                })
+                .collect::<Result<Vec<_>, StoreError>>()?;
+            items.sort_by(|(a, ka), (b, kb)| {
+                let ord = match (ka, kb) {
+                    (SortKey::Num(x), SortKey::Num(y)) => x.total_cmp(y),
+                    (SortKey::Alpha(x), SortKey::Alpha(y)) => x.cmp(y),
+                    _ => Ordering::Equal,
+                };
+                let ord = ord.then_with(|| a.cmp(b));
+                if self.desc {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            });
+            items.into_iter().map(|(element, _)| element).collect()
+        } else {
+            elements
+        };
+
+        if let Some((offset, count)) = self.limit {
+            elements = elements.into_iter().skip(offset).take(count).collect();
+        }
+
+        let result: Vec<Option<Sds>> = if self.get.is_empty() {
+            elements.into_iter().map(Some).collect()
+        } else {
+            let mut result = Vec::with_capacity(elements.len() * self.get.len());
+            for element in &elements {
+                for pattern in &self.get {
+                    result.push(match pattern.as_str() {
+                        "#" => Some(element.clone()),
+                        pattern => lookup_pattern(store, pattern, element)?,
+                    });
+                }
+            }
+            result
+        };
+
+        match &self.store {
+            Some(destination) => {
+                let destination = Sds::from_str(destination);
+                let len = result.len();
+                store.del(&destination)?;
+                if len > 0 {
+                    let list = QuickList::from_iter(
+                        result.into_iter().map(Option::unwrap_or_default),
+                        SORT_STORE_SEGMENT,
+                    );
+                    store.set(&destination, Value::List(list))?;
+                }
+                Ok(Value::Int(len as i64))
+            }
+            None => Ok(Value::Array(
+                result
+                    .into_iter()
+                    .map(|item| item.map_or(Value::Null, Value::Str))
+                    .collect(),
+            )),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SORT"
+    }
+}
+
+impl SortCommand {
+    /// Вычисляет ключ сравнения элемента: сам элемент или значение по
+    /// шаблону `by`. Отсутствующий вес при числовой сортировке равен нулю.
+    fn sort_key(
+        &self,
+        store: &StorageEngine,
+        by: Option<&str>,
+        element: &Sds,
+    ) -> Result<SortKey, StoreError> {
+        let weight = match by {
+            Some(pattern) => lookup_pattern(store, pattern, element)?,
+            None => Some(element.clone()),
+        };
+        if self.alpha {
+            return Ok(SortKey::Alpha(weight));
+        }
+        let Some(weight) = weight else {
+            return Ok(SortKey::Num(0.0));
+        };
+        std::str::from_utf8(&weight)
+            .ok()
+            .and_then(|s| s.trim().parse::<f64>().ok())
+            .filter(|n| !n.is_nan())
+            .map(SortKey::Num)
+            .ok_or_else(|| {
+                StoreError::InvalidArgument(
+                    "One or more scores can't be converted into double".into(),
+                )
+            })
+    }
+}
+
+/// Подставляет `element` вместо первого `*` шаблона `SORT` и читает значение:
+/// строку по ключу или, для шаблона `key->field`, поле хеша.
+///
+/// # Возвращает
+/// - `None` для отсутствующего ключа, поля или значения нестрокового типа
+fn lookup_pattern(
+    store: &StorageEngine,
+    pattern: &str,
+    element: &Sds,
+) -> Result<Option<Sds>, StoreError> {
+    let star = pattern.find('*');
+    let (key_pattern, field) = match pattern.rfind("->") {
+        Some(arrow) if star.is_none_or(|star| arrow > star) && arrow + 2 < pattern.len() => {
+            (&pattern[..arrow], Some(&pattern[arrow + 2..]))
+        }
+        _ => (pattern, None),
+    };
+
+    let mut key = Vec::with_capacity(key_pattern.len() + element.len());
+    match key_pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            key.extend_from_slice(prefix.as_bytes());
+            key.extend_from_slice(element);
+            key.extend_from_slice(suffix.as_bytes());
+        }
+        None => key.extend_from_slice(key_pattern.as_bytes()),
+    }
+    let key = Sds::from_vec(key);
+
+    if let Some(field) = field {
+        return match store.hmget(&key, &[&Sds::from_str(field)]) {
+            Ok(mut values) => Ok(values.pop().flatten()),
+            Err(StoreError::WrongType(_)) => Ok(None),
+            Err(e) => Err(e),
+        };
+    }
+    Ok(match store.get(&key)? {
+        Some(Value::Str(s)) => Some(s),
+        Some(Value::Int(n)) => Some(Sds::from_str(&n.to_string())),
+        Some(Value::Float(f)) => Some(Sds::from_str(&f.to_string())),
+        _ => None,
+    })
+}
+
+/// Вычисляет оставшееся время жизни ключа в миллисекундах по абсолютному
+/// времени истечения: `-1` — ключ без TTL, `-2` — ключ отсутствует.
+fn remaining_ttl_ms(
+    store: &StorageEngine,
+    key: &str,
+) -> Result<i64, StoreError> {
+    Ok(match store.expiretime(&Sds::from_str(key))? {
+        Some(at_ms) if at_ms >= 0 => {
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as i64;
+            (at_ms - now_ms).max(0)
+        }
+        Some(no_ttl) => no_ttl,
+        None => -2,
+    })
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use ordered_float::OrderedFloat;
+
     use super::*;
-    use crate::{GetCommand, InMemoryStore, SetCommand, Value};
+    use crate::{
+        Bitmap, Dict, GetCommand, GetExExpiry, Hll, InMemoryStore, QuickList, SetCommand,
+        SetOptions, SkipList, SmartHash, Stream, Value, DEFAULT_DATABASES,
+    };
 
     // Вспомогательная функция для создания нового хранилища в памяти.
     fn create_store() -> StorageEngine {
@@ -131,6 +738,7 @@ mod tests {
         let set_cmd = SetCommand {
             key: "test_key".to_string(),
             value: Value::Str(Sds::from_str("test_value")),
+            options: SetOptions::default(),
         };
 
         let result = set_cmd.execute(&mut store);
@@ -186,6 +794,7 @@ mod tests {
         let set_cmd = SetCommand {
             key: "test_key1".to_string(),
             value: Value::Str(Sds::from_str("value")),
+            options: SetOptions::default(),
         };
         set_cmd.execute(&mut store).unwrap();
 
@@ -196,6 +805,7 @@ mod tests {
         let set_cmd2 = SetCommand {
             key: "test_key2".to_string(),
             value: Value::Str(Sds::from_str("another")),
+            options: SetOptions::default(),
         };
         set_cmd2.execute(&mut store).unwrap();
 
@@ -223,6 +833,7 @@ mod tests {
         let set_cmd = SetCommand {
             key: "key1".to_string(),
             value: Value::Str(Sds::from_str("value1")),
+            options: SetOptions::default(),
         };
         set_cmd.execute(&mut store).unwrap();
 
@@ -265,6 +876,7 @@ mod tests {
         let set_cmd = SetCommand {
             key: "key1".to_string(),
             value: Value::Str(Sds::from_str("value1")),
+            options: SetOptions::default(),
         };
         set_cmd.execute(&mut store).unwrap();
 
@@ -308,6 +920,7 @@ mod tests {
         let set_cmd = SetCommand {
             key: "key1".to_string(),
             value: Value::Str(Sds::from_str("value1")),
+            options: SetOptions::default(),
         };
         set_cmd.execute(&mut store).unwrap();
 
@@ -380,16 +993,18 @@ mod tests {
         let set_cmd1 = SetCommand {
             key: "key1".to_string(),
             value: Value::Str(Sds::from_str("value1")),
+            options: SetOptions::default(),
         };
         set_cmd1.execute(&mut store).unwrap();
 
         let set_cmd2 = SetCommand {
             key: "key2".to_string(),
             value: Value::Str(Sds::from_str("value2")),
+            options: SetOptions::default(),
         };
         set_cmd2.execute(&mut store).unwrap();
 
-        let flush_cmd = FlushDbCommand;
+        let flush_cmd = FlushDbCommand::default();
 
         let result = flush_cmd.execute(&mut store);
         assert!(result.is_ok(), "FlushDbCommand failed: {result:?}");
@@ -409,4 +1024,821 @@ mod tests {
         assert!(get_result2.is_ok(), "GetCommand failed: {get_result2:?}");
         assert_eq!(get_result2.unwrap(), Value::Null);
     }
+
+    fn copy(
+        store: &mut StorageEngine,
+        source: &str,
+        destination: &str,
+        replace: bool,
+    ) -> Result<Value, StoreError> {
+        CopyCommand {
+            source: source.into(),
+            destination: destination.into(),
+            db: None,
+            replace,
+        }
+        .execute(store)
+    }
+
+    /// Тест проверяет, что COPY создаёт независимую копию значения: изменение
+    /// копии не затрагивает исходный ключ.
+    #[test]
+    fn test_copy_is_deep() {
+        let mut store = create_store();
+        let mut list = QuickList::new(64);
+        list.push_back(Sds::from_str("a"));
+        store.set(&Sds::from_str("src"), Value::List(list)).unwrap();
+
+        assert_eq!(
+            copy(&mut store, "src", "dst", false).unwrap(),
+            Value::Int(1)
+        );
+
+        if let Some(Value::List(mut copied)) = store.get(&Sds::from_str("dst")).unwrap() {
+            copied.push_back(Sds::from_str("b"));
+            store
+                .set(&Sds::from_str("dst"), Value::List(copied))
+                .unwrap();
+        } else {
+            panic!("destination is not a list");
+        }
+
+        match store.get(&Sds::from_str("src")).unwrap() {
+            Some(Value::List(list)) => assert_eq!(list.len(), 1),
+            other => panic!("unexpected source value: {other:?}"),
+        }
+    }
+
+    /// Тест проверяет, что без REPLACE существующий ключ назначения не
+    /// перезаписывается, а с REPLACE — перезаписывается; отсутствующий
+    /// источник возвращает 0.
+    #[test]
+    fn test_copy_replace() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("src"), Value::Str(Sds::from_str("new")))
+            .unwrap();
+        store
+            .set(&Sds::from_str("dst"), Value::Str(Sds::from_str("old")))
+            .unwrap();
+
+        assert_eq!(
+            copy(&mut store, "src", "dst", false).unwrap(),
+            Value::Int(0)
+        );
+        assert_eq!(
+            store.get(&Sds::from_str("dst")).unwrap(),
+            Some(Value::Str(Sds::from_str("old")))
+        );
+
+        assert_eq!(copy(&mut store, "src", "dst", true).unwrap(), Value::Int(1));
+        assert_eq!(
+            store.get(&Sds::from_str("dst")).unwrap(),
+            Some(Value::Str(Sds::from_str("new")))
+        );
+
+        assert_eq!(
+            copy(&mut store, "missing", "dst", true).unwrap(),
+            Value::Int(0)
+        );
+    }
+
+    /// Тест проверяет ошибки COPY: совпадающие ключи (даже с REPLACE) и
+    /// несуществующая база назначения.
+    #[test]
+    fn test_copy_errors() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        assert!(matches!(
+            copy(&mut store, "k", "k", true),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        let cmd = CopyCommand {
+            source: "k".into(),
+            destination: "k2".into(),
+            db: Some(DEFAULT_DATABASES),
+            replace: false,
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    /// Тест проверяет, что `EXPIRETIME`/`PEXPIRETIME` возвращают `-2` для
+    /// отсутствующего ключа, `-1` для ключа без TTL и абсолютное время,
+    /// согласованное с установленным TTL.
+    #[test]
+    fn test_expiretime_and_pexpiretime() {
+        let mut store = create_store();
+        let key = Sds::from_str("k");
+        let seconds = |key: &str| ExpireTimeCommand { key: key.into() };
+        let millis = |key: &str| PExpireTimeCommand { key: key.into() };
+
+        assert_eq!(seconds("k").execute(&mut store).unwrap(), Value::Int(-2));
+        assert_eq!(millis("k").execute(&mut store).unwrap(), Value::Int(-2));
+
+        store.set(&key, Value::Str(Sds::from_str("v"))).unwrap();
+        assert_eq!(seconds("k").execute(&mut store).unwrap(), Value::Int(-1));
+        assert_eq!(millis("k").execute(&mut store).unwrap(), Value::Int(-1));
+
+        store.getex(&key, GetExExpiry::Ex(100)).unwrap();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let Value::Int(at_ms) = millis("k").execute(&mut store).unwrap() else {
+            panic!("Expected Int");
+        };
+        let expected_ms = now.as_millis() as i64 + 100_000;
+        assert!((at_ms - expected_ms).abs() < 100);
+
+        let Value::Int(at_s) = seconds("k").execute(&mut store).unwrap() else {
+            panic!("Expected Int");
+        };
+        assert!((at_s - (now.as_secs() as i64 + 100)).abs() <= 1);
+    }
+
+    /// Выполняет KEYS и возвращает отсортированные ключи.
+    fn keys(
+        store: &mut StorageEngine,
+        pattern: &str,
+    ) -> Vec<String> {
+        let Value::Array(items) = KeysCommand {
+            pattern: pattern.into(),
+        }
+        .execute(store)
+        .unwrap() else {
+            panic!("Expected Array");
+        };
+        let mut keys: Vec<String> = items
+            .into_iter()
+            .map(|item| match item {
+                Value::Str(s) => s.as_str().unwrap().to_string(),
+                other => panic!("Expected Str, got {other:?}"),
+            })
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Тест проверяет KEYS с шаблонами `*`, точным совпадением, `?` и
+    /// классами символов.
+    #[test]
+    fn test_keys_glob_patterns() {
+        let mut store = create_store();
+        for key in ["hello", "hallo", "hxllo", "hllo", "user:1", "user:22"] {
+            store
+                .set(&Sds::from_str(key), Value::Str(Sds::from_str("v")))
+                .unwrap();
+        }
+
+        assert_eq!(keys(&mut store, "*").len(), 6);
+        assert_eq!(keys(&mut store, "hello"), vec!["hello"]);
+        assert!(keys(&mut store, "missing").is_empty());
+        assert_eq!(keys(&mut store, "h?llo"), vec!["hallo", "hello", "hxllo"]);
+        assert_eq!(keys(&mut store, "user:?"), vec!["user:1"]);
+        assert_eq!(keys(&mut store, "h[ae]llo"), vec!["hallo", "hello"]);
+        assert_eq!(keys(&mut store, "h[^e]llo"), vec!["hallo", "hxllo"]);
+        assert_eq!(keys(&mut store, "user:*"), vec!["user:1", "user:22"]);
+    }
+
+    /// Тест проверяет, что KEYS не возвращает истёкшие ключи и видит только
+    /// текущую базу.
+    #[test]
+    fn test_keys_skips_expired_and_other_dbs() {
+        let mut store = create_store();
+        let other = store.select(1).unwrap();
+        store
+            .set(&Sds::from_str("a"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        store
+            .set(&Sds::from_str("b"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        other.set(&Sds::from_str("c"), Value::Int(1)).unwrap();
+        store
+            .getex(&Sds::from_str("b"), GetExExpiry::Px(10))
+            .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert_eq!(keys(&mut store, "*"), vec!["a"]);
+    }
+
+    /// Тест проверяет ответ TYPE для каждого типа значений и `none` для
+    /// отсутствующего ключа.
+    #[test]
+    fn test_type_for_each_value() {
+        let mut store = create_store();
+        let values = [
+            ("str", Value::Str(Sds::from_str("v")), "string"),
+            ("int", Value::Int(1), "string"),
+            ("float", Value::Float(1.5), "string"),
+            ("bool", Value::Bool(true), "string"),
+            ("hll", Value::HyperLogLog(Box::new(Hll::new())), "string"),
+            ("bitmap", Value::Bitmap(Bitmap::new()), "string"),
+            ("list", Value::List(QuickList::new(8)), "list"),
+            ("set", Value::Set(HashSet::new()), "set"),
+            (
+                "zset",
+                Value::ZSet {
+                    dict: Dict::new(),
+                    sorted: SkipList::new(),
+                },
+                "zset",
+            ),
+            ("hash", Value::Hash(SmartHash::new()), "hash"),
+            ("stream", Value::SStream(Stream::new()), "stream"),
+        ];
+        for (key, value, _) in &values {
+            store.set(&Sds::from_str(key), value.clone()).unwrap();
+        }
+
+        let ty = |store: &mut StorageEngine, key: &str| {
+            TypeCommand { key: key.into() }.execute(store).unwrap()
+        };
+        for (key, _, expected) in &values {
+            assert_eq!(
+                ty(&mut store, key),
+                Value::Str(Sds::from_str(expected)),
+                "key {key}"
+            );
+        }
+        assert_eq!(ty(&mut store, "missing"), Value::Str(Sds::from_str("none")));
+    }
+
+    /// Тест проверяет, что TOUCH считает только существующие ключи (повторы —
+    /// каждый раз).
+    #[test]
+    fn test_touch_counts_existing_keys() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("a"), Value::Str(Sds::from_str("1")))
+            .unwrap();
+        store
+            .set(&Sds::from_str("b"), Value::Str(Sds::from_str("2")))
+            .unwrap();
+
+        let touch = |keys: &[&str]| TouchCommand {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        };
+        assert_eq!(
+            touch(&["a", "b", "missing"]).execute(&mut store).unwrap(),
+            Value::Int(2)
+        );
+        assert_eq!(
+            touch(&["a", "a"]).execute(&mut store).unwrap(),
+            Value::Int(2)
+        );
+        assert_eq!(
+            touch(&["missing"]).execute(&mut store).unwrap(),
+            Value::Int(0)
+        );
+    }
+
+    /// Тест проверяет, что PERSIST снимает TTL (после чего TTL возвращает
+    /// `-1`), а ключ переживает прежнее время истечения.
+    #[test]
+    fn test_persist_removes_expiry() {
+        let mut store = create_store();
+        let key = Sds::from_str("k");
+        let persist = || PersistCommand { key: "k".into() };
+        let ttl = || TtlCommand { key: "k".into() };
+
+        assert_eq!(persist().execute(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(ttl().execute(&mut store).unwrap(), Value::Int(-2));
+
+        store.set(&key, Value::Str(Sds::from_str("v"))).unwrap();
+        assert_eq!(persist().execute(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(ttl().execute(&mut store).unwrap(), Value::Int(-1));
+
+        store.getex(&key, GetExExpiry::Px(30)).unwrap();
+        let Value::Int(ms) = PTtlCommand { key: "k".into() }.execute(&mut store).unwrap() else {
+            panic!("Expected Int");
+        };
+        assert!((0..=30).contains(&ms));
+        assert_eq!(persist().execute(&mut store).unwrap(), Value::Int(1));
+        assert_eq!(ttl().execute(&mut store).unwrap(), Value::Int(-1));
+        assert_eq!(persist().execute(&mut store).unwrap(), Value::Int(0));
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(
+            store.get(&key).unwrap(),
+            Some(Value::Str(Sds::from_str("v")))
+        );
+        assert_eq!(ttl().execute(&mut store).unwrap(), Value::Int(-1));
+    }
+
+    /// Тест проверяет, что `GETEX ... PERSIST` и PERSIST снимают TTL одним
+    /// механизмом: после GETEX PERSIST снимать уже нечего.
+    #[test]
+    fn test_persist_and_getex_persist_share_expiry() {
+        let mut store = create_store();
+        let key = Sds::from_str("k");
+        let persist = || PersistCommand { key: "k".into() };
+        store.set(&key, Value::Str(Sds::from_str("v"))).unwrap();
+
+        store.getex(&key, GetExExpiry::Ex(100)).unwrap();
+        assert_eq!(
+            TtlCommand { key: "k".into() }.execute(&mut store).unwrap(),
+            Value::Int(100)
+        );
+        store.getex(&key, GetExExpiry::Persist).unwrap();
+        assert_eq!(persist().execute(&mut store).unwrap(), Value::Int(0));
+
+        store.getex(&key, GetExExpiry::Ex(100)).unwrap();
+        assert_eq!(persist().execute(&mut store).unwrap(), Value::Int(1));
+        assert_eq!(
+            store.expiretime(&key).unwrap(),
+            Some(-1),
+            "GETEX sees the same expiry state as PERSIST"
+        );
+    }
+
+    /// Тест проверяет, что SELECT запоминает базу соединения, отклоняет
+    /// несуществующий индекс, а FLUSHDB очищает только выбранную базу.
+    #[test]
+    fn test_select_and_flushdb_current_db() {
+        let registry = crate::network::connection_registry::ConnectionRegistry::new();
+        let (_, connection) = registry.register("127.0.0.1:1234".parse().unwrap());
+        let mut db0 = create_store();
+        let mut db1 = db0.select(1).unwrap();
+        db0.set(&Sds::from_str("k"), Value::Int(0)).unwrap();
+        db1.set(&Sds::from_str("k"), Value::Int(1)).unwrap();
+
+        let select = |db| SelectCommand {
+            db,
+            connection: connection.clone(),
+        };
+        assert_eq!(
+            select(1).execute(&mut db0).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(connection.db_index(), 1);
+
+        let err = select(16).execute(&mut db0).unwrap_err();
+        assert!(err.to_string().contains("DB index is out of range"));
+        assert_eq!(connection.db_index(), 1);
+
+        FlushDbCommand::default().execute(&mut db1).unwrap();
+        assert_eq!(db1.dbsize().unwrap(), 0);
+        assert_eq!(db0.get(&Sds::from_str("k")).unwrap(), Some(Value::Int(0)));
+    }
+
+    /// Тест проверяет, что FLUSHDB ASYNC сразу отдаёт пустую базу, не
+    /// затрагивает другие базы и ключи, записанные после очистки.
+    #[tokio::test]
+    async fn test_flushdb_async_keeps_new_writes() {
+        let mut db0 = create_store();
+        let db1 = db0.select(1).unwrap();
+        for i in 0..1000 {
+            db0.set(
+                &Sds::from_str(&format!("k{i}")),
+                Value::Str(Sds::from_str("v")),
+            )
+            .unwrap();
+        }
+        db0.getex(&Sds::from_str("k0"), GetExExpiry::Px(20))
+            .unwrap();
+        db1.set(&Sds::from_str("other"), Value::Int(1)).unwrap();
+
+        let flush = FlushDbCommand {
+            mode: FlushMode::Async,
+        };
+        assert_eq!(
+            flush.execute(&mut db0).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(db0.dbsize().unwrap(), 0);
+
+        // TTL старой базы не действует на новые ключи с тем же именем.
+        db0.set(&Sds::from_str("k0"), Value::Int(42)).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+        assert_eq!(db0.get(&Sds::from_str("k0")).unwrap(), Some(Value::Int(42)));
+        assert_eq!(db0.dbsize().unwrap(), 1);
+        assert_eq!(
+            db1.get(&Sds::from_str("other")).unwrap(),
+            Some(Value::Int(1))
+        );
+    }
+
+    /// Тест проверяет FLUSHALL в обоих режимах: очищаются все базы, а новые
+    /// записи сохраняются.
+    #[tokio::test]
+    async fn test_flushall_sync_and_async() {
+        let mut db0 = create_store();
+        let db1 = db0.select(1).unwrap();
+
+        for mode in [FlushMode::Sync, FlushMode::Async] {
+            db0.set(&Sds::from_str("a"), Value::Int(0)).unwrap();
+            db1.set(&Sds::from_str("b"), Value::Int(1)).unwrap();
+
+            FlushAllCommand { mode }.execute(&mut db0).unwrap();
+            assert_eq!(db0.dbsize().unwrap(), 0);
+            assert_eq!(db1.dbsize().unwrap(), 0);
+
+            db1.set(&Sds::from_str("c"), Value::Int(2)).unwrap();
+            assert_eq!(db1.get(&Sds::from_str("c")).unwrap(), Some(Value::Int(2)));
+            db1.flushdb().unwrap();
+        }
+    }
+
+    fn restore(
+        key: &str,
+        payload: Vec<u8>,
+    ) -> RestoreCommand {
+        RestoreCommand {
+            key: key.into(),
+            ttl: 0,
+            payload,
+            replace: false,
+            absttl: false,
+            idletime: None,
+            freq: None,
+        }
+    }
+
+    fn dump(
+        store: &mut StorageEngine,
+        key: &str,
+    ) -> Vec<u8> {
+        match (DumpCommand { key: key.into() }).execute(store).unwrap() {
+            Value::Str(payload) => payload.to_vec(),
+            other => panic!("Expected binary string, got {other:?}"),
+        }
+    }
+
+    /// Тест проверяет перенос ключа между хранилищами через DUMP/RESTORE и
+    /// отказ перезаписывать существующий ключ без REPLACE.
+    #[test]
+    fn test_dump_restore_roundtrip() {
+        let mut src = create_store();
+        let mut dst = create_store();
+        let mut hash = SmartHash::new();
+        hash.insert(Sds::from_str("field"), Sds::from_str("value"));
+        src.set(&Sds::from_str("h"), Value::Hash(hash.clone()))
+            .unwrap();
+
+        assert_eq!(
+            DumpCommand {
+                key: "missing".into()
+            }
+            .execute(&mut src)
+            .unwrap(),
+            Value::Null
+        );
+
+        let payload = dump(&mut src, "h");
+        assert_eq!(
+            restore("copy", payload.clone()).execute(&mut dst).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(
+            dst.get(&Sds::from_str("copy")).unwrap(),
+            Some(Value::Hash(hash))
+        );
+        assert_eq!(
+            TtlCommand { key: "copy".into() }.execute(&mut dst).unwrap(),
+            Value::Int(-1)
+        );
+
+        assert!(matches!(
+            restore("copy", payload.clone()).execute(&mut dst),
+            Err(StoreError::InvalidOperation(_))
+        ));
+        dst.set(&Sds::from_str("copy"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+        dst.getex(&Sds::from_str("copy"), GetExExpiry::Ex(100))
+            .unwrap();
+        let replace = RestoreCommand {
+            replace: true,
+            ..restore("copy", payload)
+        };
+        replace.execute(&mut dst).unwrap();
+        assert!(matches!(
+            dst.get(&Sds::from_str("copy")).unwrap(),
+            Some(Value::Hash(_))
+        ));
+        assert_eq!(
+            TtlCommand { key: "copy".into() }.execute(&mut dst).unwrap(),
+            Value::Int(-1)
+        );
+    }
+
+    /// Тест проверяет, что RESTORE отклоняет нагрузку с неверной CRC и не
+    /// трогает существующий ключ даже с REPLACE.
+    #[test]
+    fn test_restore_rejects_bad_checksum() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        let mut payload = dump(&mut store, "k");
+        let last = payload.len() - 1;
+        payload[last] ^= 0x01;
+
+        let cmd = RestoreCommand {
+            replace: true,
+            ..restore("k", payload)
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            store.get(&Sds::from_str("k")).unwrap(),
+            Some(Value::Str(Sds::from_str("v")))
+        );
+    }
+
+    /// Тест проверяет TTL при RESTORE: относительный, абсолютный (ABSTTL) и
+    /// уже истёкший абсолютный, при котором ключ не создаётся.
+    #[test]
+    fn test_restore_ttl_and_absttl() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("k"), Value::Int(7)).unwrap();
+        let payload = dump(&mut store, "k");
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        RestoreCommand {
+            ttl: 100_000,
+            ..restore("rel", payload.clone())
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            TtlCommand { key: "rel".into() }
+                .execute(&mut store)
+                .unwrap(),
+            Value::Int(100)
+        );
+
+        RestoreCommand {
+            ttl: now_ms + 50_000,
+            absttl: true,
+            ..restore("abs", payload.clone())
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            TtlCommand { key: "abs".into() }
+                .execute(&mut store)
+                .unwrap(),
+            Value::Int(50)
+        );
+
+        RestoreCommand {
+            ttl: now_ms - 1_000,
+            absttl: true,
+            ..restore("past", payload)
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(store.get(&Sds::from_str("past")).unwrap(), None);
+    }
+
+    /// Тест проверяет, что IDLETIME и FREQ задают метаданные доступа и не
+    /// могут использоваться вместе.
+    #[test]
+    fn test_restore_idletime_and_freq() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        let payload = dump(&mut store, "k");
+
+        RestoreCommand {
+            idletime: Some(1000),
+            ..restore("idle", payload.clone())
+        }
+        .execute(&mut store)
+        .unwrap();
+        let idle = store.idletime(&Sds::from_str("idle")).unwrap().unwrap();
+        assert!((1000..=1001).contains(&idle));
+
+        store
+            .set_eviction_policy(crate::EvictionPolicy::AllKeysLfu)
+            .unwrap();
+        RestoreCommand {
+            freq: Some(200),
+            ..restore("hot", payload.clone())
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(store.freq(&Sds::from_str("hot")).unwrap(), Some(200));
+
+        let both = RestoreCommand {
+            idletime: Some(1),
+            freq: Some(1),
+            ..restore("both", payload)
+        };
+        assert!(matches!(
+            both.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    fn sort(key: &str) -> SortCommand {
+        SortCommand {
+            key: key.into(),
+            ..Default::default()
+        }
+    }
+
+    fn strs(values: &[&str]) -> Value {
+        Value::Array(
+            values
+                .iter()
+                .map(|v| Value::Str(Sds::from_str(v)))
+                .collect(),
+        )
+    }
+
+    fn push_list(
+        store: &mut StorageEngine,
+        key: &str,
+        items: &[&str],
+    ) {
+        let list = QuickList::from_iter(items.iter().map(|i| Sds::from_str(i)), 64);
+        store.set(&Sds::from_str(key), Value::List(list)).unwrap();
+    }
+
+    /// Тест проверяет числовую и строковую сортировку списка, DESC и LIMIT,
+    /// а также ошибку числовой сортировки нечисловых элементов.
+    #[test]
+    fn test_sort_numeric_alpha_and_limit() {
+        let mut store = create_store();
+        push_list(&mut store, "nums", &["10", "2", "-1.5", "3"]);
+
+        assert_eq!(
+            sort("nums").execute(&mut store).unwrap(),
+            strs(&["-1.5", "2", "3", "10"])
+        );
+        let cmd = SortCommand {
+            desc: true,
+            limit: Some((1, 2)),
+            ..sort("nums")
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), strs(&["3", "2"]));
+        let cmd = SortCommand {
+            alpha: true,
+            ..sort("nums")
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            strs(&["-1.5", "10", "2", "3"])
+        );
+
+        push_list(&mut store, "words", &["b", "a", "c"]);
+        assert!(matches!(
+            sort("words").execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            SortCommand {
+                alpha: true,
+                ..sort("words")
+            }
+            .execute(&mut store)
+            .unwrap(),
+            strs(&["a", "b", "c"])
+        );
+        assert_eq!(sort("missing").execute(&mut store).unwrap(), strs(&[]));
+    }
+
+    /// Тест проверяет сортировку множества и отсортированного множества:
+    /// скоры ZSet игнорируются.
+    #[test]
+    fn test_sort_set_and_zset() {
+        let mut store = create_store();
+        let set = Sds::from_str("set");
+        store
+            .sadd(&set, &["3", "1", "2"].map(Sds::from_str))
+            .unwrap();
+        assert_eq!(
+            sort("set").execute(&mut store).unwrap(),
+            strs(&["1", "2", "3"])
+        );
+
+        let mut dict = Dict::new();
+        let mut sorted = SkipList::new();
+        for (member, score) in [("30", 1.0), ("10", 2.0), ("20", 3.0)] {
+            dict.insert(Sds::from_str(member), score);
+            sorted.insert(OrderedFloat(score), Sds::from_str(member));
+        }
+        store
+            .set(&Sds::from_str("zset"), Value::ZSet { dict, sorted })
+            .unwrap();
+        assert_eq!(
+            sort("zset").execute(&mut store).unwrap(),
+            strs(&["10", "20", "30"])
+        );
+
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        assert!(matches!(
+            sort("str").execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+    }
+
+    /// Тест проверяет BY по внешним ключам и полям хеша, GET с `#` и
+    /// отсутствующими ключами, а также BY без `*` (без сортировки).
+    #[test]
+    fn test_sort_by_and_get() {
+        let mut store = create_store();
+        push_list(&mut store, "ids", &["1", "2", "3"]);
+        for (id, weight, name) in [("1", "30", "one"), ("2", "10", "two"), ("3", "20", "three")] {
+            store
+                .set(
+                    &Sds::from_str(&format!("weight_{id}")),
+                    Value::Str(Sds::from_str(weight)),
+                )
+                .unwrap();
+            if id != "3" {
+                store
+                    .set(
+                        &Sds::from_str(&format!("name_{id}")),
+                        Value::Str(Sds::from_str(name)),
+                    )
+                    .unwrap();
+            }
+        }
+        let mut hash = SmartHash::new();
+        hash.insert(Sds::from_str("rank"), Sds::from_str("-1"));
+        store
+            .set(&Sds::from_str("obj_3"), Value::Hash(hash))
+            .unwrap();
+
+        let cmd = SortCommand {
+            by: Some("weight_*".into()),
+            ..sort("ids")
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), strs(&["2", "3", "1"]));
+
+        let cmd = SortCommand {
+            by: Some("obj_*->rank".into()),
+            get: vec!["#".into(), "name_*".into()],
+            ..sort("ids")
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Array(vec![
+                Value::Str(Sds::from_str("3")),
+                Value::Null,
+                Value::Str(Sds::from_str("1")),
+                Value::Str(Sds::from_str("one")),
+                Value::Str(Sds::from_str("2")),
+                Value::Str(Sds::from_str("two")),
+            ])
+        );
+
+        let cmd = SortCommand {
+            by: Some("nosort".into()),
+            ..sort("ids")
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), strs(&["1", "2", "3"]));
+    }
+
+    /// Тест проверяет STORE: результат сохраняется списком, TTL приёмника
+    /// сбрасывается, а пустой результат удаляет приёмник.
+    #[test]
+    fn test_sort_store() {
+        let mut store = create_store();
+        push_list(&mut store, "src", &["3", "1", "2"]);
+        push_list(&mut store, "dst", &["old"]);
+        store
+            .set_expiry(&Sds::from_str("dst"), Some(Duration::from_secs(100)))
+            .unwrap();
+
+        let cmd = SortCommand {
+            store: Some("dst".into()),
+            ..sort("src")
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(3));
+        let Some(Value::List(list)) = store.get(&Sds::from_str("dst")).unwrap() else {
+            panic!("Expected list");
+        };
+        assert_eq!(
+            list.iter().map(|s| s.into_owned()).collect::<Vec<_>>(),
+            ["1", "2", "3"].map(Sds::from_str)
+        );
+        assert_eq!(
+            TtlCommand { key: "dst".into() }
+                .execute(&mut store)
+                .unwrap(),
+            Value::Int(-1)
+        );
+
+        let cmd = SortCommand {
+            store: Some("dst".into()),
+            ..sort("missing")
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(store.get(&Sds::from_str("dst")).unwrap(), None);
+    }
 }
@@ -1,4 +1,8 @@
-use crate::{CommandExecute, QuickList, Sds, StorageEngine, StoreError, Value};
+use std::borrow::Cow;
+
+use crate::{
+    CommandExecute, KeyspaceEvent, ListDir, QuickList, Sds, StorageEngine, StoreError, Value,
+};
 
 /// Команда LPUSH — добавляет элемент в начало списка.
 #[derive(Debug)]
@@ -24,6 +28,7 @@ impl CommandExecute for LPushCommand {
         list.push_front(element);
         let len = list.len() as i64;
         store.set(&key, Value::List(list))?;
+        store.notify_keyspace_event(KeyspaceEvent::LPush, &key);
         Ok(Value::Int(len))
     }
 
@@ -56,6 +61,7 @@ impl CommandExecute for RPushCommand {
         list.push_back(element);
         let len = list.len() as i64;
         store.set(&key, Value::List(list))?;
+        store.notify_keyspace_event(KeyspaceEvent::RPush, &key);
         Ok(Value::Int(len))
     }
 
@@ -199,6 +205,10 @@ impl CommandExecute for LRangeCommand {
 }
 
 /// Команда LSET — устанавливает значение элемента списка по индексу.
+///
+/// Отрицательный индекс считается с хвоста. Для индекса вне диапазона
+/// возвращает ошибку `index out of range`, для отсутствующего ключа —
+/// `KeyNotFound`.
 #[derive(Debug)]
 pub struct LSetCommand {
     pub key: String,
@@ -209,9 +219,12 @@ pub struct LSetCommand {
 impl CommandExecute for LSetCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("LSET is not implemented yet")
+        let index = isize::try_from(self.index)
+            .map_err(|_| StoreError::InvalidArgument("index out of range".into()))?;
+        store.lset(&Sds::from_str(&self.key), index, Sds::from_str(&self.value))?;
+        Ok(Value::Str(Sds::from_str("OK")))
     }
 
     fn command_name(&self) -> &'static str {
@@ -219,6 +232,77 @@ impl CommandExecute for LSetCommand {
     }
 }
 
+/// Команда LINDEX — возвращает элемент списка по индексу, не удаляя его.
+///
+/// Отрицательный индекс считается с хвоста; вне диапазона возвращается nil.
+#[derive(Debug)]
+pub struct LIndexCommand {
+    pub key: String,
+    pub index: i64,
+}
+
+impl CommandExecute for LIndexCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+        match store.get(&key)? {
+            Some(Value::List(mut list)) => {
+                let item = isize::try_from(self.index)
+                    .ok()
+                    .and_then(|index| list.at(index).cloned());
+                Ok(item.map_or(Value::Null, Value::Str))
+            }
+            Some(_) => Err(StoreError::InvalidType),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "LINDEX"
+    }
+}
+
+/// Команда LTRIM — оставляет в списке только элементы диапазона
+/// `start..=stop`.
+///
+/// Отрицательные индексы считаются с хвоста; пустой диапазон удаляет ключ.
+/// Всегда возвращает `OK`.
+#[derive(Debug)]
+pub struct LTrimCommand {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+}
+
+impl CommandExecute for LTrimCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+        match store.get(&key)? {
+            Some(Value::List(mut list)) => {
+                let clamp = |i: i64| i.clamp(isize::MIN as i64, isize::MAX as i64) as isize;
+                list.trim(clamp(self.start), clamp(self.stop));
+                if list.is_empty() {
+                    store.del(&key)?;
+                } else {
+                    store.set(&key, Value::List(list))?;
+                }
+            }
+            Some(_) => return Err(StoreError::InvalidType),
+            None => {}
+        }
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "LTRIM"
+    }
+}
+
 /// Команда LREM — удаляет элементы из списка по значению.
 #[derive(Debug)]
 pub struct LRemCommand {
@@ -240,6 +324,199 @@ impl CommandExecute for LRemCommand {
     }
 }
 
+/// Команда LPOS — возвращает позицию элемента в списке.
+///
+/// `RANK` задаёт, какое по счёту совпадение вернуть (отрицательный — поиск с
+/// хвоста), `COUNT` — сколько позиций вернуть (`0` — все), `MAXLEN` —
+/// сколько элементов просмотреть (`0` — без ограничения).
+#[derive(Debug)]
+pub struct LPosCommand {
+    pub key: String,
+    pub element: String,
+    pub rank: Option<i64>,
+    pub count: Option<usize>,
+    pub maxlen: Option<usize>,
+}
+
+impl CommandExecute for LPosCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let rank = self.rank.unwrap_or(1);
+        if rank == 0 {
+            return Err(StoreError::InvalidArgument(
+                "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".into(),
+            ));
+        }
+
+        let key = Sds::from_str(&self.key);
+        let element = Sds::from_str(&self.element);
+
+        let list = match store.get(&key)? {
+            Some(Value::List(list)) => list,
+            Some(_) => return Err(StoreError::InvalidType),
+            None => QuickList::new(64),
+        };
+
+        let len = list.len();
+        let maxlen = match self.maxlen {
+            None | Some(0) => len,
+            Some(n) => n.min(len),
+        };
+        let limit = match self.count {
+            None => 1,
+            Some(0) => usize::MAX,
+            Some(n) => n,
+        };
+
+        // Сегменты обходятся последовательно: с головы или с хвоста.
+        let items: Box<dyn Iterator<Item = (usize, Cow<'_, Sds>)>> = if rank > 0 {
+            Box::new(list.iter().enumerate())
+        } else {
+            Box::new(
+                list.iter_rev()
+                    .enumerate()
+                    .map(|(i, item)| (len - 1 - i, item)),
+            )
+        };
+
+        let positions: Vec<i64> = items
+            .take(maxlen)
+            .filter(|(_, item)| **item == element)
+            .skip(rank.unsigned_abs() as usize - 1)
+            .take(limit)
+            .map(|(idx, _)| idx as i64)
+            .collect();
+
+        if self.count.is_some() {
+            Ok(Value::Array(
+                positions.into_iter().map(Value::Int).collect(),
+            ))
+        } else {
+            Ok(positions
+                .first()
+                .map_or(Value::Null, |&idx| Value::Int(idx)))
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "LPOS"
+    }
+}
+
+/// Команда LMOVE — атомарно перемещает элемент с края одного списка на край
+/// другого и возвращает его.
+#[derive(Debug)]
+pub struct LMoveCommand {
+    pub source: String,
+    pub destination: String,
+    pub from: ListDir,
+    pub to: ListDir,
+}
+
+impl CommandExecute for LMoveCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let src = Sds::from_str(&self.source);
+        let dst = Sds::from_str(&self.destination);
+
+        match store.lmove(&src, &dst, self.from, self.to)? {
+            Some(item) => Ok(Value::Str(item)),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "LMOVE"
+    }
+}
+
+/// Команда LMPOP — извлекает до `count` элементов из первого непустого списка.
+///
+/// Возвращает массив `[key, [elem, ...]]` или nil, если все списки пусты.
+#[derive(Debug)]
+pub struct LmPopCommand {
+    pub keys: Vec<String>,
+    pub dir: ListDir,
+    pub count: Option<usize>,
+}
+
+impl CommandExecute for LmPopCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let count = self.count.unwrap_or(1);
+        if count == 0 {
+            return Err(StoreError::InvalidArgument(
+                "count should be greater than 0".into(),
+            ));
+        }
+
+        let keys: Vec<Sds> = self.keys.iter().map(|k| Sds::from_str(k)).collect();
+        let key_refs: Vec<&Sds> = keys.iter().collect();
+
+        match store.lmpop(&key_refs, self.dir, count)? {
+            Some((key, items)) => Ok(Value::Array(vec![
+                Value::Str(key),
+                Value::Array(items.into_iter().map(Value::Str).collect()),
+            ])),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "LMPOP"
+    }
+}
+
+/// Команда LINSERT — вставляет `value` перед (`BEFORE`) или после (`AFTER`)
+/// первого вхождения `pivot`.
+///
+/// Возвращает новую длину списка, `-1`, если `pivot` не найден, и `0`, если
+/// ключ отсутствует.
+#[derive(Debug)]
+pub struct LInsertCommand {
+    pub key: String,
+    /// `true` — `BEFORE`, `false` — `AFTER`.
+    pub before: bool,
+    pub pivot: String,
+    pub value: String,
+}
+
+impl CommandExecute for LInsertCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+
+        let mut list = match store.get(&key)? {
+            Some(Value::List(list)) => list,
+            Some(_) => return Err(StoreError::InvalidType),
+            None => return Ok(Value::Int(0)),
+        };
+
+        let pivot = Sds::from_str(&self.pivot);
+        let Some(pos) = list.iter().position(|item| *item == pivot) else {
+            return Ok(Value::Int(-1));
+        };
+        let index = if self.before { pos } else { pos + 1 };
+
+        list.insert(index, Sds::from_str(&self.value));
+        let len = list.len() as i64;
+        store.set(&key, Value::List(list))?;
+        Ok(Value::Int(len))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "LINSERT"
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -389,4 +666,528 @@ mod tests {
             Err(StoreError::InvalidType)
         ));
     }
+
+    // Вспомогательная функция: заполняет список через RPUSH.
+    fn setup_list(
+        store: &mut StorageEngine,
+        key: &str,
+        items: &[&str],
+    ) {
+        for item in items {
+            RPushCommand {
+                key: key.into(),
+                value: item.to_string(),
+            }
+            .execute(store)
+            .unwrap();
+        }
+    }
+
+    fn lpos(
+        element: &str,
+        rank: Option<i64>,
+        count: Option<usize>,
+        maxlen: Option<usize>,
+    ) -> LPosCommand {
+        LPosCommand {
+            key: "lp".into(),
+            element: element.into(),
+            rank,
+            count,
+            maxlen,
+        }
+    }
+
+    /// Тест проверяет LPOS для первого совпадения, RANK и отсутствующего
+    /// элемента.
+    #[test]
+    fn test_lpos_single() {
+        let mut store = create_store();
+        setup_list(&mut store, "lp", &["a", "b", "c", "1", "2", "3", "c", "c"]);
+
+        assert_eq!(
+            lpos("c", None, None, None).execute(&mut store).unwrap(),
+            Value::Int(2)
+        );
+        assert_eq!(
+            lpos("c", Some(2), None, None).execute(&mut store).unwrap(),
+            Value::Int(6)
+        );
+        assert_eq!(
+            lpos("c", Some(-1), None, None).execute(&mut store).unwrap(),
+            Value::Int(7)
+        );
+        assert_eq!(
+            lpos("x", None, None, None).execute(&mut store).unwrap(),
+            Value::Null
+        );
+    }
+
+    /// Тест проверяет LPOS с COUNT и MAXLEN.
+    #[test]
+    fn test_lpos_count_and_maxlen() {
+        let mut store = create_store();
+        setup_list(&mut store, "lp", &["a", "b", "c", "1", "2", "3", "c", "c"]);
+
+        assert_eq!(
+            lpos("c", None, Some(0), None).execute(&mut store).unwrap(),
+            Value::Array(vec![Value::Int(2), Value::Int(6), Value::Int(7)])
+        );
+        assert_eq!(
+            lpos("c", Some(-1), Some(2), None)
+                .execute(&mut store)
+                .unwrap(),
+            Value::Array(vec![Value::Int(7), Value::Int(6)])
+        );
+        assert_eq!(
+            lpos("c", None, Some(0), Some(3))
+                .execute(&mut store)
+                .unwrap(),
+            Value::Array(vec![Value::Int(2)])
+        );
+        assert_eq!(
+            lpos("x", None, Some(1), None).execute(&mut store).unwrap(),
+            Value::Array(vec![])
+        );
+    }
+
+    /// Тест проверяет ошибки LPOS: RANK 0 и неверный тип ключа.
+    #[test]
+    fn test_lpos_errors() {
+        let mut store = create_store();
+        setup_list(&mut store, "lp", &["a"]);
+
+        assert!(matches!(
+            lpos("a", Some(0), None, None).execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        store.set(&Sds::from_str("lp"), Value::Int(1)).unwrap();
+        assert!(matches!(
+            lpos("a", None, None, None).execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    // Вспомогательная функция: возвращает содержимое списка.
+    fn list_items(
+        store: &mut StorageEngine,
+        key: &str,
+    ) -> Vec<Sds> {
+        match store.get(&Sds::from_str(key)).unwrap() {
+            Some(Value::List(list)) => list.into_iter().collect(),
+            None => Vec::new(),
+            other => panic!("unexpected value {other:?}"),
+        }
+    }
+
+    fn sds_vec(items: &[&str]) -> Vec<Sds> {
+        items.iter().map(|s| Sds::from_str(s)).collect()
+    }
+
+    /// Тест проверяет LMOVE для всех четырёх комбинаций направлений.
+    #[test]
+    fn test_lmove_all_directions() {
+        let cases = [
+            (ListDir::Left, ListDir::Left, "a", &["a", "x", "y"]),
+            (ListDir::Left, ListDir::Right, "a", &["x", "y", "a"]),
+            (ListDir::Right, ListDir::Left, "c", &["c", "x", "y"]),
+            (ListDir::Right, ListDir::Right, "c", &["x", "y", "c"]),
+        ];
+
+        for (from, to, moved, expected_dst) in cases {
+            let mut store = create_store();
+            setup_list(&mut store, "src", &["a", "b", "c"]);
+            setup_list(&mut store, "dst", &["x", "y"]);
+
+            let res = LMoveCommand {
+                source: "src".into(),
+                destination: "dst".into(),
+                from,
+                to,
+            }
+            .execute(&mut store)
+            .unwrap();
+
+            assert_eq!(res, Value::Str(Sds::from_str(moved)));
+            assert_eq!(list_items(&mut store, "src").len(), 2);
+            assert_eq!(list_items(&mut store, "dst"), sds_vec(expected_dst));
+        }
+    }
+
+    /// Тест проверяет LMOVE в тот же список (ротация) и перемещение
+    /// последнего элемента, после которого источник удаляется.
+    #[test]
+    fn test_lmove_rotate_and_drain() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a", "b", "c"]);
+
+        let rotate = LMoveCommand {
+            source: "l".into(),
+            destination: "l".into(),
+            from: ListDir::Left,
+            to: ListDir::Right,
+        };
+        assert_eq!(
+            rotate.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("a"))
+        );
+        assert_eq!(list_items(&mut store, "l"), sds_vec(&["b", "c", "a"]));
+
+        setup_list(&mut store, "one", &["z"]);
+        LMoveCommand {
+            source: "one".into(),
+            destination: "new".into(),
+            from: ListDir::Right,
+            to: ListDir::Left,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(store.get(&Sds::from_str("one")).unwrap(), None);
+        assert_eq!(list_items(&mut store, "new"), sds_vec(&["z"]));
+    }
+
+    /// Тест проверяет LMOVE для отсутствующего источника и приёмника
+    /// неверного типа.
+    #[test]
+    fn test_lmove_missing_and_wrong_type() {
+        let mut store = create_store();
+
+        let res = LMoveCommand {
+            source: "none".into(),
+            destination: "dst".into(),
+            from: ListDir::Left,
+            to: ListDir::Left,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Null);
+        assert_eq!(store.get(&Sds::from_str("dst")).unwrap(), None);
+
+        setup_list(&mut store, "src", &["a"]);
+        store.set(&Sds::from_str("str"), Value::Int(1)).unwrap();
+        let err = LMoveCommand {
+            source: "src".into(),
+            destination: "str".into(),
+            from: ListDir::Left,
+            to: ListDir::Left,
+        }
+        .execute(&mut store)
+        .unwrap_err();
+        assert!(matches!(err, StoreError::WrongType(_)));
+        assert_eq!(list_items(&mut store, "src"), sds_vec(&["a"]));
+    }
+
+    fn lmpop(
+        keys: &[&str],
+        dir: ListDir,
+        count: Option<usize>,
+    ) -> LmPopCommand {
+        LmPopCommand {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            dir,
+            count,
+        }
+    }
+
+    /// Тест проверяет, что LMPOP выбирает первый непустой список и
+    /// возвращает столько элементов, сколько есть.
+    #[test]
+    fn test_lmpop_first_non_empty() {
+        let mut store = create_store();
+        setup_list(&mut store, "b", &["1", "2", "3"]);
+        setup_list(&mut store, "c", &["x"]);
+
+        let res = lmpop(&["a", "b", "c"], ListDir::Left, Some(2))
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("b")),
+                Value::Array(vec![
+                    Value::Str(Sds::from_str("1")),
+                    Value::Str(Sds::from_str("2")),
+                ]),
+            ])
+        );
+
+        let res = lmpop(&["b", "c"], ListDir::Right, Some(10))
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("b")),
+                Value::Array(vec![Value::Str(Sds::from_str("3"))]),
+            ])
+        );
+        assert_eq!(store.get(&Sds::from_str("b")).unwrap(), None);
+
+        let res = lmpop(&["b", "c"], ListDir::Right, None)
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("c")),
+                Value::Array(vec![Value::Str(Sds::from_str("x"))]),
+            ])
+        );
+    }
+
+    /// Тест проверяет LMPOP без непустых списков, с COUNT 0 и с ключом
+    /// неверного типа.
+    #[test]
+    fn test_lmpop_empty_and_errors() {
+        let mut store = create_store();
+
+        assert_eq!(
+            lmpop(&["a", "b"], ListDir::Left, None)
+                .execute(&mut store)
+                .unwrap(),
+            Value::Null
+        );
+        assert!(matches!(
+            lmpop(&["a"], ListDir::Left, Some(0)).execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        store.set(&Sds::from_str("s"), Value::Int(1)).unwrap();
+        assert!(matches!(
+            lmpop(&["s"], ListDir::Left, None).execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+    }
+
+    fn linsert(
+        before: bool,
+        pivot: &str,
+        value: &str,
+    ) -> LInsertCommand {
+        LInsertCommand {
+            key: "l".into(),
+            before,
+            pivot: pivot.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Тест проверяет LINSERT перед головой, после хвоста и в середине
+    /// списка.
+    #[test]
+    fn test_linsert_head_middle_tail() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a", "b", "c"]);
+
+        assert_eq!(
+            linsert(true, "a", "h").execute(&mut store).unwrap(),
+            Value::Int(4)
+        );
+        assert_eq!(
+            linsert(false, "c", "t").execute(&mut store).unwrap(),
+            Value::Int(5)
+        );
+        assert_eq!(
+            linsert(false, "a", "m").execute(&mut store).unwrap(),
+            Value::Int(6)
+        );
+        assert_eq!(
+            list_items(&mut store, "l"),
+            sds_vec(&["h", "a", "m", "b", "c", "t"])
+        );
+    }
+
+    /// Тест проверяет ответы LINSERT для отсутствующего `pivot`,
+    /// отсутствующего ключа и ключа другого типа.
+    #[test]
+    fn test_linsert_missing_pivot_and_key() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a"]);
+
+        assert_eq!(
+            linsert(true, "zz", "x").execute(&mut store).unwrap(),
+            Value::Int(-1)
+        );
+        assert_eq!(list_items(&mut store, "l"), sds_vec(&["a"]));
+
+        let missing = LInsertCommand {
+            key: "none".into(),
+            before: true,
+            pivot: "a".into(),
+            value: "x".into(),
+        };
+        assert_eq!(missing.execute(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(store.get(&Sds::from_str("none")).unwrap(), None);
+
+        store
+            .set(&Sds::from_str("s"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        let wrong = LInsertCommand {
+            key: "s".into(),
+            before: true,
+            pivot: "a".into(),
+            value: "x".into(),
+        };
+        assert!(matches!(
+            wrong.execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    /// Тест проверяет LSET по положительному и отрицательному индексу.
+    #[test]
+    fn test_lset() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a", "b", "c"]);
+
+        let lset = |index: i64, value: &str| LSetCommand {
+            key: "l".into(),
+            index,
+            value: value.into(),
+        };
+        assert_eq!(
+            lset(0, "x").execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        lset(-1, "longer value").execute(&mut store).unwrap();
+        assert_eq!(
+            list_items(&mut store, "l"),
+            sds_vec(&["x", "b", "longer value"])
+        );
+    }
+
+    /// Тест проверяет ошибки LSET: индекс вне диапазона, отсутствующий ключ
+    /// и ключ другого типа.
+    #[test]
+    fn test_lset_errors() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a"]);
+
+        let out_of_range = LSetCommand {
+            key: "l".into(),
+            index: -2,
+            value: "x".into(),
+        };
+        assert!(matches!(
+            out_of_range.execute(&mut store),
+            Err(StoreError::InvalidArgument(msg)) if msg == "index out of range"
+        ));
+
+        let missing = LSetCommand {
+            key: "none".into(),
+            index: 0,
+            value: "x".into(),
+        };
+        assert!(matches!(
+            missing.execute(&mut store),
+            Err(StoreError::KeyNotFound)
+        ));
+
+        store
+            .set(&Sds::from_str("s"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        let wrong = LSetCommand {
+            key: "s".into(),
+            index: 0,
+            value: "x".into(),
+        };
+        assert!(matches!(
+            wrong.execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+    }
+
+    fn lindex(
+        key: &str,
+        index: i64,
+    ) -> LIndexCommand {
+        LIndexCommand {
+            key: key.into(),
+            index,
+        }
+    }
+
+    /// Тест проверяет LINDEX с начала и с хвоста, включая первый и
+    /// последний элементы.
+    #[test]
+    fn test_lindex_forward_and_backward() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a", "b", "c"]);
+
+        let s = |v: &str| Value::Str(Sds::from_str(v));
+        assert_eq!(lindex("l", 0).execute(&mut store).unwrap(), s("a"));
+        assert_eq!(lindex("l", 1).execute(&mut store).unwrap(), s("b"));
+        assert_eq!(lindex("l", 2).execute(&mut store).unwrap(), s("c"));
+        assert_eq!(lindex("l", -1).execute(&mut store).unwrap(), s("c"));
+        assert_eq!(lindex("l", -3).execute(&mut store).unwrap(), s("a"));
+        assert_eq!(lindex("l", 3).execute(&mut store).unwrap(), Value::Null);
+        assert_eq!(lindex("l", -4).execute(&mut store).unwrap(), Value::Null);
+        assert_eq!(list_items(&mut store, "l").len(), 3);
+    }
+
+    /// Тест проверяет LINDEX для пустого (отсутствующего) списка и ключа
+    /// другого типа.
+    #[test]
+    fn test_lindex_empty_and_wrong_type() {
+        let mut store = create_store();
+        assert_eq!(lindex("none", 0).execute(&mut store).unwrap(), Value::Null);
+        assert_eq!(lindex("none", -1).execute(&mut store).unwrap(), Value::Null);
+
+        store
+            .set(&Sds::from_str("s"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        assert!(matches!(
+            lindex("s", 0).execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    fn ltrim(
+        start: i64,
+        stop: i64,
+    ) -> LTrimCommand {
+        LTrimCommand {
+            key: "l".into(),
+            start,
+            stop,
+        }
+    }
+
+    /// Тест проверяет LTRIM на списке из одного сегмента и канонизацию
+    /// отрицательных индексов.
+    #[test]
+    fn test_ltrim_ranges() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a", "b", "c", "d", "e"]);
+
+        assert_eq!(
+            ltrim(1, -2).execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(list_items(&mut store, "l"), sds_vec(&["b", "c", "d"]));
+
+        ltrim(-2, 10).execute(&mut store).unwrap();
+        assert_eq!(list_items(&mut store, "l"), sds_vec(&["c", "d"]));
+    }
+
+    /// Тест проверяет, что пустой диапазон удаляет список, а LTRIM по
+    /// отсутствующему ключу возвращает `OK`.
+    #[test]
+    fn test_ltrim_discard_all() {
+        let mut store = create_store();
+        setup_list(&mut store, "l", &["a", "b", "c"]);
+
+        ltrim(2, 1).execute(&mut store).unwrap();
+        assert_eq!(store.get(&Sds::from_str("l")).unwrap(), None);
+
+        setup_list(&mut store, "l", &["a", "b", "c"]);
+        ltrim(5, 10).execute(&mut store).unwrap();
+        assert_eq!(store.get(&Sds::from_str("l")).unwrap(), None);
+
+        assert_eq!(
+            ltrim(0, -1).execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+    }
 }
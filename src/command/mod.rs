@@ -7,12 +7,15 @@
 //! - [`keys`] — базовые утилитарные команды (например, `ping`, `echo`,
 //!   `select`).
 //! - [`bitmap`] — битовые операции (`SETBIT`, `GETBIT`, `BITCOUNT`, `BITOP`).
+//! - [`cluster`] — управление кластером (`CLUSTER SETSLOT`, `CLUSTER INFO`,
+//!   `CLUSTER NODES`, `CLUSTER MYID`, `CLUSTER FAILOVER`).
 //! - [`execute`] — диспетчеризация и единый интерфейс выполнения команд.
 //! - [`float`] — операции с числами с плавающей точкой.
 //! - [`geo`] — географические структуры и команды.
 //! - [`hash`] — ассоциативные массивы (hash).
 //! - [`int`] — целочисленные операции и счётчики.
 //! - [`list`] — списки (push, pop, range и т. д.).
+//! - [`object`] — интроспекция значений (`OBJECT ENCODING`).
 //! - [`set`] — неупорядоченные множества.
 //! - [`string`] — строки и операции над ними.
 //! - [`zset`] — отсортированные множества (sorted set).
@@ -22,6 +25,7 @@
 
 pub mod auth;
 pub mod bitmap;
+pub mod cluster;
 pub mod execute;
 pub mod float;
 pub mod geo;
@@ -30,6 +34,7 @@ pub mod hll;
 pub mod int;
 pub mod keys;
 pub mod list;
+pub mod object;
 pub mod pubsub;
 pub mod server;
 pub mod set;
@@ -42,6 +47,7 @@ pub mod zset;
 // simplify access from external code.
 pub use auth::*;
 pub use bitmap::*;
+pub use cluster::*;
 pub use execute::*;
 pub use float::*;
 pub use geo::*;
@@ -50,6 +56,7 @@ pub use hll::*;
 pub use int::*;
 pub use keys::*;
 pub use list::*;
+pub use object::*;
 pub use server::*;
 pub use set::*;
 pub use stream::*;
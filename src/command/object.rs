@@ -0,0 +1,214 @@
+use crate::{CommandExecute, Sds, StorageEngine, StoreError, Value};
+
+/// Команда OBJECT ENCODING — возвращает имя внутреннего представления
+/// значения по ключу.
+#[derive(Debug)]
+pub struct ObjectEncodingCommand {
+    pub key: String,
+}
+
+impl CommandExecute for ObjectEncodingCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        match store.get(&Sds::from_str(&self.key))? {
+            Some(value) => Ok(Value::Str(Sds::from_str(value.encoding()))),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "OBJECT ENCODING"
+    }
+}
+
+/// Команда OBJECT IDLETIME — возвращает число целых секунд с последнего
+/// обращения (чтения или записи) к ключу. Сама команда обращением не
+/// считается.
+#[derive(Debug)]
+pub struct ObjectIdleTimeCommand {
+    pub key: String,
+}
+
+impl CommandExecute for ObjectIdleTimeCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        match store.idletime(&Sds::from_str(&self.key))? {
+            Some(secs) => Ok(Value::Int(secs as i64)),
+            None => Err(StoreError::KeyNotFound),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "OBJECT IDLETIME"
+    }
+}
+
+/// Команда OBJECT FREQ — возвращает логарифмический LFU-счётчик обращений к
+/// ключу. Доступна только при LFU-политике вытеснения (`allkeys-lfu`,
+/// `volatile-lfu`). Сама команда обращением не считается.
+#[derive(Debug)]
+pub struct ObjectFreqCommand {
+    pub key: String,
+}
+
+impl CommandExecute for ObjectFreqCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        match store.freq(&Sds::from_str(&self.key))? {
+            Some(freq) => Ok(Value::Int(freq as i64)),
+            None => Err(StoreError::KeyNotFound),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "OBJECT FREQ"
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{EvictionPolicy, GetCommand, InMemoryStore, QuickList, SmartHash};
+
+    // Вспомогательная функция для создания нового хранилища в памяти.
+    fn create_store() -> StorageEngine {
+        StorageEngine::Memory(InMemoryStore::new())
+    }
+
+    fn encoding(
+        store: &mut StorageEngine,
+        key: &str,
+    ) -> Value {
+        ObjectEncodingCommand { key: key.into() }
+            .execute(store)
+            .unwrap()
+    }
+
+    fn bulk(s: &str) -> Value {
+        Value::Str(Sds::from_str(s))
+    }
+
+    /// Тест проверяет кодировки строк: число, короткая и длинная строка.
+    #[test]
+    fn test_object_encoding_strings() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("num"), bulk("12345")).unwrap();
+        store.set(&Sds::from_str("short"), bulk("hello")).unwrap();
+        store
+            .set(&Sds::from_str("long"), bulk(&"x".repeat(100)))
+            .unwrap();
+        store
+            .set(&Sds::from_str("huge"), bulk("99999999999999999999"))
+            .unwrap();
+
+        assert_eq!(encoding(&mut store, "num"), bulk("int"));
+        assert_eq!(encoding(&mut store, "short"), bulk("embstr"));
+        assert_eq!(encoding(&mut store, "long"), bulk("raw"));
+        // Не помещается в i64 — обычная строка.
+        assert_eq!(encoding(&mut store, "huge"), bulk("embstr"));
+    }
+
+    /// Тест проверяет, что кодировка хеша зависит от его представления
+    /// (`listpack` для компактного, `hashtable` для полного).
+    #[test]
+    fn test_object_encoding_hash() {
+        let mut store = create_store();
+        let mut small = SmartHash::new();
+        small.insert(Sds::from_str("f"), Sds::from_str("v"));
+        store
+            .set(&Sds::from_str("small"), Value::Hash(small))
+            .unwrap();
+
+        let mut big = SmartHash::new();
+        for i in 0..200 {
+            big.insert(Sds::from_str(&i.to_string()), Sds::from_str("v"));
+        }
+        store.set(&Sds::from_str("big"), Value::Hash(big)).unwrap();
+
+        assert_eq!(encoding(&mut store, "small"), bulk("listpack"));
+        assert_eq!(encoding(&mut store, "big"), bulk("hashtable"));
+    }
+
+    /// Тест проверяет кодировки коллекций и отсутствующего ключа.
+    #[test]
+    fn test_object_encoding_collections() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("list"), Value::List(QuickList::new(64)))
+            .unwrap();
+        store
+            .set(&Sds::from_str("set"), Value::Set(HashSet::new()))
+            .unwrap();
+
+        assert_eq!(encoding(&mut store, "list"), bulk("quicklist"));
+        assert_eq!(encoding(&mut store, "set"), bulk("hashtable"));
+        assert_eq!(encoding(&mut store, "missing"), Value::Null);
+    }
+
+    /// Тест проверяет, что OBJECT IDLETIME возвращает 0 для только что
+    /// записанного ключа, не отмечает обращение сам и возвращает ошибку для
+    /// отсутствующего ключа.
+    #[test]
+    fn test_object_idletime() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("k"), bulk("v")).unwrap();
+
+        let idletime = |key: &str| ObjectIdleTimeCommand { key: key.into() };
+        assert_eq!(idletime("k").execute(&mut store).unwrap(), Value::Int(0));
+        assert!(matches!(
+            idletime("missing").execute(&mut store),
+            Err(StoreError::KeyNotFound)
+        ));
+    }
+
+    /// Тест проверяет, что OBJECT FREQ недоступна без LFU-политики, а при
+    /// ней часто читаемый ключ получает больший счётчик, чем редко читаемый.
+    #[test]
+    fn test_object_freq() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("hot"), bulk("v")).unwrap();
+        store.set(&Sds::from_str("cold"), bulk("v")).unwrap();
+
+        let freq = |key: &str| ObjectFreqCommand { key: key.into() };
+        assert!(matches!(
+            freq("hot").execute(&mut store),
+            Err(StoreError::InvalidOperation(_))
+        ));
+
+        store
+            .set_eviction_policy(EvictionPolicy::AllKeysLfu)
+            .unwrap();
+        for _ in 0..1000 {
+            GetCommand { key: "hot".into() }
+                .execute(&mut store)
+                .unwrap();
+        }
+        GetCommand { key: "cold".into() }
+            .execute(&mut store)
+            .unwrap();
+
+        let (Value::Int(hot), Value::Int(cold)) = (
+            freq("hot").execute(&mut store).unwrap(),
+            freq("cold").execute(&mut store).unwrap(),
+        ) else {
+            panic!("OBJECT FREQ must return an integer");
+        };
+        assert!(hot > cold, "hot={hot} cold={cold}");
+        assert!(matches!(
+            freq("missing").execute(&mut store),
+            Err(StoreError::KeyNotFound)
+        ));
+    }
+}
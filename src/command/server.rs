@@ -1,6 +1,24 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::{CommandExecute, Sds, StorageEngine, StoreError, Value};
+use crate::{
+    command::Command,
+    engine::{dump_payload, restore_payload},
+    glob_match,
+    network::{
+        connection_registry::{ClientSelector, ConnectionRegistry},
+        connection_state::ConnectionInfo,
+    },
+    AppendCommand, CommandExecute, DecrByCommand, DecrCommand, DelCommand, ExistsCommand,
+    FunctionRestorePolicy, GetCommand, HDelCommand, HGetAllCommand, HGetCommand, HSetCommand,
+    IncrByCommand, IncrCommand, LLenCommand, LPopCommand, LPushCommand, LRangeCommand, LuaEngine,
+    MGetCommand, MSetCommand, PersistCommand, RPopCommand, RPushCommand, SAddCommand, SCardCommand,
+    SIsMemberCommand, SMembersCommand, SRemCommand, Sds, SetCommand, SetOptions, SlowLog,
+    SlowLogEntry, SmartHash, StorageEngine, StoreError, StrLenCommand, TtlCommand, TypeCommand,
+    Value, ZAddCommand, ZCardCommand, ZRemCommand, ZScoreCommand,
+};
 
 /// Команда PING — проверка соединения с сервером.
 #[derive(Debug)]
@@ -91,6 +109,10 @@ impl CommandExecute for InfoCommand {
                 let dbsize = store.dbsize().unwrap_or(0);
                 info.push_str(&format!("total_keys:{dbsize}\r\n"));
                 info.push_str("total_commands_processed:0\r\n");
+                info.push_str(&format!(
+                    "hash_listpack_promotions:{}\r\n",
+                    SmartHash::promote_stats()
+                ));
             }
             Some(section) => {
                 return Err(StoreError::InvalidArgument(format!(
@@ -134,30 +156,25 @@ impl CommandExecute for TimeCommand {
     }
 }
 
-/// Команда SELECT — выбирает базу данных по индексу.
+/// Команда SWAPDB — атомарно меняет местами содержимое двух логических баз
+/// вместе с TTL ключей.
 #[derive(Debug)]
-pub struct SelectCommand {
-    pub db: usize,
+pub struct SwapDbCommand {
+    pub db1: usize,
+    pub db2: usize,
 }
 
-impl CommandExecute for SelectCommand {
+impl CommandExecute for SwapDbCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        // В Zumic пока поддерживается только одна БД (индекс 0)
-        if self.db == 0 {
-            Ok(Value::Str(Sds::from_str("OK")))
-        } else {
-            Err(StoreError::InvalidArgument(format!(
-                "DB index is out of range. Zumic supports only DB 0, got {}",
-                self.db
-            )))
-        }
+        store.swapdb(self.db1, self.db2)?;
+        Ok(Value::Str(Sds::from_str("OK")))
     }
 
     fn command_name(&self) -> &'static str {
-        "SELECT"
+        "SWAPDB"
     }
 }
 
@@ -221,3 +238,1205 @@ impl CommandExecute for ShutdownCommand {
         "SHUTDOWN"
     }
 }
+
+/// Команда CLIENT KILL — закрывает соединения, подходящие под селектор, и
+/// возвращает их количество.
+#[derive(Debug)]
+pub struct ClientKillCommand {
+    pub registry: Arc<ConnectionRegistry>,
+    pub selector: ClientSelector,
+}
+
+impl CommandExecute for ClientKillCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let killed = self.registry.kill(self.selector.clone());
+        Ok(Value::Int(killed as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "CLIENT KILL"
+    }
+}
+
+/// Команда MONITOR — переводит соединение в режим потоковой рассылки всех
+/// команд сервера.
+///
+/// Подписку на поток оформляет обработчик соединения; в этом режиме
+/// соединению доступна только команда `RESET`.
+#[derive(Debug)]
+pub struct MonitorCommand {
+    pub connection: Arc<ConnectionInfo>,
+}
+
+impl CommandExecute for MonitorCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        self.connection.set_monitor(true);
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "MONITOR"
+    }
+}
+
+/// Команда RESET — сбрасывает состояние соединения: выводит его из режима
+/// `MONITOR` и снимает `CLIENT NO-EVICT`.
+#[derive(Debug)]
+pub struct ResetCommand {
+    pub connection: Arc<ConnectionInfo>,
+}
+
+impl CommandExecute for ResetCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        self.connection.set_monitor(false);
+        self.connection.set_no_evict(false);
+        Ok(Value::Str(Sds::from_str("RESET")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "RESET"
+    }
+}
+
+/// Число элементов коллекции, по которым `MEMORY USAGE` оценивает размер,
+/// если `SAMPLES` не указан.
+pub const DEFAULT_MEMORY_SAMPLES: usize = 5;
+
+/// Команда MEMORY USAGE — оценивает число байт, занятых ключом и его
+/// значением.
+///
+/// `samples` задаёт число элементов коллекции для оценки (`0` — точный
+/// подсчёт, по умолчанию [`DEFAULT_MEMORY_SAMPLES`]).
+#[derive(Debug)]
+pub struct MemoryUsageCommand {
+    pub key: String,
+    pub samples: Option<usize>,
+}
+
+impl CommandExecute for MemoryUsageCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let samples = self.samples.unwrap_or(DEFAULT_MEMORY_SAMPLES);
+        match store.memory_usage(&Sds::from_str(&self.key), samples)? {
+            Some(bytes) => Ok(Value::Int(bytes as i64)),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "MEMORY USAGE"
+    }
+}
+
+/// Команда SLOWLOG GET — возвращает последние записи журнала медленных
+/// команд, от новых к старым.
+#[derive(Debug)]
+pub struct SlowLogGetCommand {
+    pub slowlog: SlowLog,
+    pub count: Option<usize>,
+}
+
+impl CommandExecute for SlowLogGetCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let entries = self.slowlog.get(self.count);
+        Ok(Value::Array(
+            entries.iter().map(SlowLogEntry::to_value).collect(),
+        ))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SLOWLOG GET"
+    }
+}
+
+/// Команда SLOWLOG LEN — возвращает число записей журнала медленных команд.
+#[derive(Debug)]
+pub struct SlowLogLenCommand {
+    pub slowlog: SlowLog,
+}
+
+impl CommandExecute for SlowLogLenCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        Ok(Value::Int(self.slowlog.len() as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SLOWLOG LEN"
+    }
+}
+
+/// Команда SLOWLOG RESET — очищает журнал медленных команд.
+#[derive(Debug)]
+pub struct SlowLogResetCommand {
+    pub slowlog: SlowLog,
+}
+
+impl CommandExecute for SlowLogResetCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        self.slowlog.reset();
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SLOWLOG RESET"
+    }
+}
+
+/// Команда FUNCTION LOAD — загружает библиотеку функций.
+///
+/// Первая строка `code` — заголовок `#!lua name=<library>`, движок в нём
+/// должен совпадать с `engine`. Без `replace` существующая библиотека с тем
+/// же именем не перезаписывается.
+#[derive(Debug)]
+pub struct FunctionLoadCommand {
+    pub engine: String,
+    pub code: String,
+    pub replace: bool,
+}
+
+impl CommandExecute for FunctionLoadCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let name = LuaEngine::with_scripts(|engine| {
+            engine.load_library(&self.engine, &self.code, self.replace)
+        })?;
+        Ok(Value::Str(Sds::from_str(&name)))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FUNCTION LOAD"
+    }
+}
+
+/// Команда FCALL — вызывает функцию, загруженную `FUNCTION LOAD`.
+#[derive(Debug)]
+pub struct FCallCommand {
+    pub function: String,
+    pub keys: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl CommandExecute for FCallCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        run_function(store, &self.function, &self.keys, &self.args, false)
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FCALL"
+    }
+}
+
+/// Команда FCALL_RO — вызывает функцию без права изменять данные: команды
+/// записи из `redis.call` завершаются ошибкой.
+#[derive(Debug)]
+pub struct FCallRoCommand {
+    pub function: String,
+    pub keys: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl CommandExecute for FCallRoCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        run_function(store, &self.function, &self.keys, &self.args, true)
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FCALL_RO"
+    }
+}
+
+/// Команда FUNCTION LIST — описывает загруженные библиотеки, по желанию
+/// отфильтрованные glob-шаблоном имени (`LIBRARYNAME`) и с исходным текстом
+/// (`WITHCODE`).
+#[derive(Debug, Default)]
+pub struct FunctionListCommand {
+    pub library_pattern: Option<String>,
+    pub with_code: bool,
+}
+
+impl CommandExecute for FunctionListCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let str = |s: &str| Value::Str(Sds::from_str(s));
+        let libraries = LuaEngine::with_scripts(|engine| {
+            Ok(engine
+                .functions()
+                .libraries()
+                .filter(|library| {
+                    self.library_pattern.as_ref().is_none_or(|pattern| {
+                        glob_match(pattern.as_bytes(), library.name.as_bytes())
+                    })
+                })
+                .map(|library| {
+                    let functions = library
+                        .functions
+                        .iter()
+                        .map(|function| {
+                            let flags = if function.no_writes {
+                                vec![str("no-writes")]
+                            } else {
+                                Vec::new()
+                            };
+                            Value::Array(vec![
+                                str("name"),
+                                str(&function.name),
+                                str("description"),
+                                Value::Null,
+                                str("flags"),
+                                Value::Array(flags),
+                            ])
+                        })
+                        .collect();
+                    let mut entry = vec![
+                        str("library_name"),
+                        str(&library.name),
+                        str("engine"),
+                        str(&library.engine),
+                        str("functions"),
+                        Value::Array(functions),
+                    ];
+                    if self.with_code {
+                        entry.extend([str("library_code"), str(&library.code)]);
+                    }
+                    Value::Array(entry)
+                })
+                .collect())
+        })?;
+        Ok(Value::Array(libraries))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FUNCTION LIST"
+    }
+}
+
+/// Команда FUNCTION DELETE — удаляет библиотеку и все её функции.
+#[derive(Debug)]
+pub struct FunctionDeleteCommand {
+    pub library: String,
+}
+
+impl CommandExecute for FunctionDeleteCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        LuaEngine::with_scripts(|engine| engine.delete_library(&self.library))?;
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FUNCTION DELETE"
+    }
+}
+
+/// Команда FUNCTION DUMP — сериализует исходные тексты всех библиотек в
+/// нагрузку формата `DUMP` (ZDB-значение с версией и CRC64).
+#[derive(Debug)]
+pub struct FunctionDumpCommand;
+
+impl CommandExecute for FunctionDumpCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let codes = LuaEngine::with_scripts(|engine| {
+            Ok(engine
+                .functions()
+                .libraries()
+                .map(|library| Value::Str(Sds::from_str(&library.code)))
+                .collect())
+        })?;
+        let payload = dump_payload(&Value::Array(codes))
+            .map_err(|e| StoreError::InvalidData(e.to_string()))?;
+        Ok(Value::Str(Sds::from_vec(payload)))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FUNCTION DUMP"
+    }
+}
+
+/// Команда FUNCTION RESTORE — загружает библиотеки из нагрузки
+/// `FUNCTION DUMP` по политике `FLUSH`/`APPEND`/`REPLACE`.
+#[derive(Debug)]
+pub struct FunctionRestoreCommand {
+    pub payload: Vec<u8>,
+    pub policy: FunctionRestorePolicy,
+}
+
+impl CommandExecute for FunctionRestoreCommand {
+    fn execute(
+        &self,
+        _store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let invalid =
+            || StoreError::InvalidArgument("payload version or checksum are wrong".into());
+        let codes = match restore_payload(&self.payload).map_err(|_| invalid())? {
+            Value::Array(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::Str(code) => Ok(String::from_utf8_lossy(code.as_bytes()).into_owned()),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(invalid()),
+        };
+        LuaEngine::with_scripts(|engine| engine.restore_libraries(&codes, self.policy))?;
+        Ok(Value::Str(Sds::from_str("OK")))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "FUNCTION RESTORE"
+    }
+}
+
+/// Команда EVAL — выполняет Lua-скрипт в песочнице.
+///
+/// Скрипт компилируется один раз и кэшируется по SHA1, поэтому повторный
+/// EVAL того же текста и EVALSHA используют готовую функцию. `keys` и
+/// `args` доступны скрипту как `KEYS` и `ARGV`.
+#[derive(Debug)]
+pub struct EvalCommand {
+    pub script: String,
+    pub keys: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl CommandExecute for EvalCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let sha = LuaEngine::with_scripts(|engine| engine.load_script(&self.script))?;
+        run_script(store, &sha, &self.keys, &self.args)
+    }
+
+    fn command_name(&self) -> &'static str {
+        "EVAL"
+    }
+}
+
+/// Команда EVALSHA — выполняет закэшированный скрипт по его SHA1.
+#[derive(Debug)]
+pub struct EvalShaCommand {
+    pub sha: String,
+    pub keys: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl CommandExecute for EvalShaCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        run_script(store, &self.sha, &self.keys, &self.args)
+    }
+
+    fn command_name(&self) -> &'static str {
+        "EVALSHA"
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Команды, изменяющие данные: запрещены в `FCALL_RO` и функциях с флагом
+/// `no-writes`.
+const SCRIPT_WRITE_COMMANDS: [&str; 19] = [
+    "SET", "MSET", "DEL", "APPEND", "INCR", "INCRBY", "DECR", "DECRBY", "HSET", "HDEL", "LPUSH",
+    "RPUSH", "LPOP", "RPOP", "SADD", "SREM", "ZADD", "ZREM", "PERSIST",
+];
+
+/// Выполняет закэшированный скрипт `sha`, направляя `redis.call` в `store`.
+fn run_script(
+    store: &mut StorageEngine,
+    sha: &str,
+    keys: &[String],
+    args: &[String],
+) -> Result<Value, StoreError> {
+    let keys: Vec<Sds> = keys.iter().map(|k| Sds::from_str(k)).collect();
+    let args: Vec<Sds> = args.iter().map(|a| Sds::from_str(a)).collect();
+    let mut call = |argv: &[Sds]| script_call(store, argv, false);
+    Ok(LuaEngine::with_scripts(|engine| {
+        engine.eval_script(sha, &keys, &args, &mut call)
+    })?)
+}
+
+/// Вызывает функцию библиотеки `name`. Для `FCALL_RO` и функций с флагом
+/// `no-writes` команды записи из `redis.call` отклоняются.
+fn run_function(
+    store: &mut StorageEngine,
+    name: &str,
+    keys: &[String],
+    args: &[String],
+    read_only: bool,
+) -> Result<Value, StoreError> {
+    let keys: Vec<Sds> = keys.iter().map(|k| Sds::from_str(k)).collect();
+    let args: Vec<Sds> = args.iter().map(|a| Sds::from_str(a)).collect();
+    Ok(LuaEngine::with_scripts(|engine| {
+        let read_only = read_only
+            || engine
+                .functions()
+                .function(name)
+                .is_some_and(|function| function.no_writes);
+        let mut call = |argv: &[Sds]| script_call(store, argv, read_only);
+        engine.fcall(name, &keys, &args, &mut call)
+    })?)
+}
+
+/// Выполняет команду, вызванную скриптом через `redis.call`/`redis.pcall`.
+///
+/// `argv[0]` — имя команды (без учёта регистра), остальные элементы — её
+/// аргументы. При `read_only` команды из [`SCRIPT_WRITE_COMMANDS`]
+/// отклоняются.
+fn script_call(
+    store: &mut StorageEngine,
+    argv: &[Sds],
+    read_only: bool,
+) -> Result<Value, StoreError> {
+    let argv: Vec<String> = argv
+        .iter()
+        .map(|a| String::from_utf8_lossy(a.as_bytes()).into_owned())
+        .collect();
+    let name = argv[0].to_ascii_uppercase();
+    let args = &argv[1..];
+    if read_only && SCRIPT_WRITE_COMMANDS.contains(&name.as_str()) {
+        return Err(StoreError::Script(
+            "Write commands are not allowed from read-only scripts".into(),
+        ));
+    }
+
+    let wrong_args = || {
+        StoreError::InvalidArgument(format!(
+            "Wrong number of args calling Redis command '{name}' from script"
+        ))
+    };
+    let arity = |min: usize, max: Option<usize>| {
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            Err(wrong_args())
+        } else {
+            Ok(())
+        }
+    };
+    let int = |arg: &str| {
+        arg.parse::<i64>().map_err(|_| {
+            StoreError::InvalidArgument("value is not an integer or out of range".into())
+        })
+    };
+    let key = || args[0].clone();
+
+    let command = match name.as_str() {
+        "PING" => {
+            arity(0, Some(1))?;
+            Command::Ping(PingCommand {
+                message: args.first().cloned(),
+            })
+        }
+        "ECHO" => {
+            arity(1, Some(1))?;
+            Command::Echo(EchoCommand { message: key() })
+        }
+        "GET" => {
+            arity(1, Some(1))?;
+            Command::Get(GetCommand { key: key() })
+        }
+        "SET" => {
+            arity(2, Some(2))?;
+            Command::Set(SetCommand {
+                key: key(),
+                value: Value::Str(Sds::from_str(&args[1])),
+                options: SetOptions::default(),
+            })
+        }
+        "MGET" => {
+            arity(1, None)?;
+            Command::MGet(MGetCommand {
+                keys: args.to_vec(),
+            })
+        }
+        "MSET" => {
+            if args.is_empty() || !args.len().is_multiple_of(2) {
+                return Err(wrong_args());
+            }
+            Command::MSet(MSetCommand {
+                entries: args
+                    .chunks_exact(2)
+                    .map(|kv| (kv[0].clone(), Value::Str(Sds::from_str(&kv[1]))))
+                    .collect(),
+            })
+        }
+        "DEL" | "SADD" | "SREM" => {
+            arity(if name == "DEL" { 1 } else { 2 }, None)?;
+            // Многоаргументные формы выполняются поэлементно, ответы
+            // суммируются.
+            let mut total = 0;
+            let items = if name == "DEL" { args } else { &args[1..] };
+            for item in items {
+                let command = match name.as_str() {
+                    "DEL" => Command::Del(DelCommand { key: item.clone() }),
+                    "SADD" => Command::SAdd(SAddCommand {
+                        key: key(),
+                        member: item.clone(),
+                    }),
+                    _ => Command::SRem(SRemCommand {
+                        key: key(),
+                        member: item.clone(),
+                    }),
+                };
+                total += match command.execute(store)? {
+                    Value::Int(n) => n,
+                    Value::Bool(b) => b as i64,
+                    _ => 0,
+                };
+            }
+            return Ok(Value::Int(total));
+        }
+        "LPUSH" | "RPUSH" => {
+            arity(2, None)?;
+            // Ответ последнего вставления — итоговая длина списка.
+            let mut reply = Value::Null;
+            for value in &args[1..] {
+                let command = if name == "LPUSH" {
+                    Command::LPush(LPushCommand {
+                        key: key(),
+                        value: value.clone(),
+                    })
+                } else {
+                    Command::RPush(RPushCommand {
+                        key: key(),
+                        value: value.clone(),
+                    })
+                };
+                reply = command.execute(store)?;
+            }
+            return Ok(reply);
+        }
+        "EXISTS" => {
+            arity(1, None)?;
+            Command::Exists(ExistsCommand {
+                keys: args.to_vec(),
+            })
+        }
+        "INCR" => {
+            arity(1, Some(1))?;
+            Command::Incr(IncrCommand { key: key() })
+        }
+        "INCRBY" => {
+            arity(2, Some(2))?;
+            Command::Incrby(IncrByCommand {
+                key: key(),
+                increment: int(&args[1])?,
+            })
+        }
+        "DECR" => {
+            arity(1, Some(1))?;
+            Command::Decr(DecrCommand { key: key() })
+        }
+        "DECRBY" => {
+            arity(2, Some(2))?;
+            Command::Decrby(DecrByCommand {
+                key: key(),
+                decrement: int(&args[1])?,
+            })
+        }
+        "APPEND" => {
+            arity(2, Some(2))?;
+            Command::Append(AppendCommand {
+                key: key(),
+                value: args[1].clone(),
+            })
+        }
+        "STRLEN" => {
+            arity(1, Some(1))?;
+            Command::Strlen(StrLenCommand { key: key() })
+        }
+        "HSET" => {
+            if args.len() < 3 || args.len().is_multiple_of(2) {
+                return Err(wrong_args());
+            }
+            Command::HSet(HSetCommand {
+                key: key(),
+                entries: args[1..]
+                    .chunks_exact(2)
+                    .map(|fv| (fv[0].clone(), fv[1].clone()))
+                    .collect(),
+            })
+        }
+        "HGET" => {
+            arity(2, Some(2))?;
+            Command::HGet(HGetCommand {
+                key: key(),
+                field: args[1].clone(),
+            })
+        }
+        "HDEL" => {
+            arity(2, None)?;
+            Command::HDel(HDelCommand {
+                key: key(),
+                fields: args[1..].to_vec(),
+            })
+        }
+        "HGETALL" => {
+            arity(1, Some(1))?;
+            Command::HGetall(HGetAllCommand { key: key() })
+        }
+        "LPOP" => {
+            arity(1, Some(1))?;
+            Command::LPop(LPopCommand { key: key() })
+        }
+        "RPOP" => {
+            arity(1, Some(1))?;
+            Command::RPop(RPopCommand { key: key() })
+        }
+        "LLEN" => {
+            arity(1, Some(1))?;
+            Command::LLen(LLenCommand { key: key() })
+        }
+        "LRANGE" => {
+            arity(3, Some(3))?;
+            Command::LRange(LRangeCommand {
+                key: key(),
+                start: int(&args[1])?,
+                stop: int(&args[2])?,
+            })
+        }
+        "SISMEMBER" => {
+            arity(2, Some(2))?;
+            Command::SIsmember(SIsMemberCommand {
+                key: key(),
+                member: args[1].clone(),
+            })
+        }
+        "SCARD" => {
+            arity(1, Some(1))?;
+            Command::SCard(SCardCommand { key: key() })
+        }
+        "SMEMBERS" => {
+            arity(1, Some(1))?;
+            Command::SMembers(SMembersCommand { key: key() })
+        }
+        "ZADD" => {
+            arity(3, Some(3))?;
+            Command::ZAdd(ZAddCommand {
+                key: key(),
+                score: args[1].parse().map_err(|_| {
+                    StoreError::InvalidArgument("value is not a valid float".into())
+                })?,
+                member: args[2].clone(),
+            })
+        }
+        "ZREM" => {
+            arity(2, Some(2))?;
+            Command::ZRem(ZRemCommand {
+                key: key(),
+                member: args[1].clone(),
+            })
+        }
+        "ZSCORE" => {
+            arity(2, Some(2))?;
+            Command::ZScore(ZScoreCommand {
+                key: key(),
+                member: args[1].clone(),
+            })
+        }
+        "ZCARD" => {
+            arity(1, Some(1))?;
+            Command::ZCard(ZCardCommand { key: key() })
+        }
+        "TTL" => {
+            arity(1, Some(1))?;
+            Command::Ttl(TtlCommand { key: key() })
+        }
+        "PERSIST" => {
+            arity(1, Some(1))?;
+            Command::Persist(PersistCommand { key: key() })
+        }
+        "TYPE" => {
+            arity(1, Some(1))?;
+            Command::Type(TypeCommand { key: key() })
+        }
+        _ => {
+            return Err(StoreError::InvalidCommand(
+                "Unknown Redis command called from script".into(),
+            ))
+        }
+    };
+    command.execute(store)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::{mem::size_of, time::Duration};
+
+    use super::*;
+    use crate::{InMemoryStore, Storage};
+
+    // Вспомогательная функция для создания нового хранилища в памяти.
+    fn create_store() -> StorageEngine {
+        StorageEngine::Memory(InMemoryStore::new())
+    }
+
+    fn memory_usage(
+        store: &mut StorageEngine,
+        key: &str,
+        samples: Option<usize>,
+    ) -> Value {
+        MemoryUsageCommand {
+            key: key.into(),
+            samples,
+        }
+        .execute(store)
+        .unwrap()
+    }
+
+    /// Тест проверяет MEMORY USAGE для строки известного размера и
+    /// отсутствующего ключа.
+    #[test]
+    fn test_memory_usage_string() {
+        let mut store = create_store();
+        let value = Sds::from_vec(vec![b'x'; 100]);
+        store.set(&Sds::from_str("k"), Value::Str(value)).unwrap();
+
+        assert_eq!(
+            memory_usage(&mut store, "k", None),
+            Value::Int((size_of::<Sds>() + size_of::<Value>() + 100) as i64)
+        );
+        assert_eq!(memory_usage(&mut store, "missing", None), Value::Null);
+    }
+
+    /// Тест проверяет, что оценка по выборке для однородной коллекции
+    /// совпадает с точным подсчётом.
+    #[test]
+    fn test_memory_usage_samples() {
+        let mut store = create_store();
+        let key = Sds::from_str("set");
+        for i in 0..100 {
+            store
+                .sadd(&key, &[Sds::from_str(&format!("member-{i:040}"))])
+                .unwrap();
+        }
+
+        let exact = memory_usage(&mut store, "set", Some(0));
+        assert_eq!(memory_usage(&mut store, "set", Some(10)), exact);
+        assert_eq!(memory_usage(&mut store, "set", None), exact);
+    }
+
+    /// Тест проверяет, что CLIENT KILL возвращает число закрытых соединений.
+    #[test]
+    fn test_client_kill() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let addr = "127.0.0.1:1234".parse().unwrap();
+        let (id, _) = registry.register(addr);
+        registry.register(addr);
+
+        let kill = |selector| {
+            ClientKillCommand {
+                registry: registry.clone(),
+                selector,
+            }
+            .execute(&mut create_store())
+            .unwrap()
+        };
+        assert_eq!(
+            kill(ClientSelector {
+                addr: Some(addr),
+                skip: Some(id),
+                ..Default::default()
+            }),
+            Value::Int(1)
+        );
+        assert_eq!(
+            kill(ClientSelector {
+                user: Some("alice".into()),
+                ..Default::default()
+            }),
+            Value::Int(0)
+        );
+    }
+
+    /// Тест проверяет, что SWAPDB меняет базы местами вместе с TTL.
+    #[test]
+    fn test_swapdb() {
+        let memory = InMemoryStore::new();
+        let db1 = memory.select(1).unwrap();
+        memory.set(&Sds::from_str("a"), Value::Int(0)).unwrap();
+        memory
+            .set_expiry(&Sds::from_str("a"), Some(Duration::from_secs(60)))
+            .unwrap();
+        db1.set(&Sds::from_str("b"), Value::Int(1)).unwrap();
+        let mut store = StorageEngine::Memory(memory);
+
+        let swap = SwapDbCommand { db1: 0, db2: 1 };
+        assert_eq!(
+            swap.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(store.get(&Sds::from_str("b")).unwrap(), Some(Value::Int(1)));
+        assert_eq!(db1.get(&Sds::from_str("a")).unwrap(), Some(Value::Int(0)));
+        assert!(db1.expiretime(&Sds::from_str("a")).unwrap().unwrap() > 0);
+
+        let out_of_range = SwapDbCommand { db1: 0, db2: 16 };
+        assert!(out_of_range.execute(&mut store).is_err());
+    }
+
+    /// Тест проверяет вход в режим MONITOR и выход из него через RESET.
+    #[test]
+    fn test_monitor_and_reset() {
+        let registry = ConnectionRegistry::new();
+        let (_, connection) = registry.register("127.0.0.1:1234".parse().unwrap());
+        connection.set_no_evict(true);
+
+        let monitor = MonitorCommand {
+            connection: connection.clone(),
+        };
+        assert_eq!(
+            monitor.execute(&mut create_store()).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert!(connection.is_monitor());
+
+        let reset = ResetCommand {
+            connection: connection.clone(),
+        };
+        assert_eq!(
+            reset.execute(&mut create_store()).unwrap(),
+            Value::Str(Sds::from_str("RESET"))
+        );
+        assert!(!connection.is_monitor());
+        assert!(!connection.is_no_evict());
+    }
+
+    /// Тест проверяет команды SLOWLOG на общем журнале.
+    #[test]
+    fn test_slowlog_commands() {
+        let mut store = create_store();
+        let slowlog = SlowLog::new(0, 128);
+        let addr = "127.0.0.1:1234".parse().unwrap();
+        slowlog.record(Duration::from_micros(7), || vec!["PING".into()], addr);
+
+        let len = SlowLogLenCommand {
+            slowlog: slowlog.clone(),
+        };
+        assert_eq!(len.execute(&mut store).unwrap(), Value::Int(1));
+
+        let entries = SlowLogGetCommand {
+            slowlog: slowlog.clone(),
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(entries, Value::Array(vec![slowlog.get(None)[0].to_value()]));
+
+        SlowLogResetCommand {
+            slowlog: slowlog.clone(),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(len.execute(&mut store).unwrap(), Value::Int(0));
+    }
+
+    fn eval(
+        store: &mut StorageEngine,
+        script: &str,
+        keys: &[&str],
+        args: &[&str],
+    ) -> Result<Value, StoreError> {
+        EvalCommand {
+            script: script.into(),
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+        .execute(store)
+    }
+
+    /// Тест проверяет EVAL: KEYS/ARGV и вызовы команд через redis.call.
+    #[test]
+    fn test_eval_redis_call() {
+        let mut store = create_store();
+        let script = "redis.call('SET', KEYS[1], ARGV[1]); \
+                      redis.call('RPUSH', KEYS[2], 'a', 'b', ARGV[1]); \
+                      return { redis.call('GET', KEYS[1]), redis.call('LLEN', KEYS[2]), \
+                               redis.call('del', KEYS[1], KEYS[2], 'missing') }";
+        assert_eq!(
+            eval(&mut store, script, &["k", "l"], &["v"]).unwrap(),
+            Value::Array(vec![
+                Value::Str(Sds::from_str("v")),
+                Value::Int(3),
+                Value::Int(2),
+            ])
+        );
+        assert_eq!(store.get(&Sds::from_str("k")).unwrap(), None);
+
+        // Отсутствующий ключ в Lua — false, в ответе — Null.
+        assert_eq!(
+            eval(&mut store, "return redis.call('GET', 'nope')", &[], &[]).unwrap(),
+            Value::Null
+        );
+        assert!(eval(&mut store, "return redis.call('NOSUCH')", &[], &[]).is_err());
+    }
+
+    /// Тест проверяет, что redis.pcall возвращает ошибку команды таблицей.
+    #[test]
+    fn test_eval_pcall() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("s"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+
+        let script = "local r = redis.pcall('LPOP', 's'); \
+                      if r.err then return 'caught' end; return r";
+        assert_eq!(
+            eval(&mut store, script, &[], &[]).unwrap(),
+            Value::Str(Sds::from_str("caught"))
+        );
+        assert!(eval(&mut store, "return redis.call('LPOP', 's')", &[], &[]).is_err());
+    }
+
+    /// Тест проверяет EVALSHA: поиск скрипта по SHA1, загруженного EVAL.
+    #[test]
+    fn test_evalsha() {
+        let mut store = create_store();
+        let script = "return redis.call('INCRBY', KEYS[1], ARGV[1])";
+        assert_eq!(
+            eval(&mut store, script, &["n"], &["2"]).unwrap(),
+            Value::Int(2)
+        );
+
+        let evalsha = |store: &mut StorageEngine, sha: &str| {
+            EvalShaCommand {
+                sha: sha.into(),
+                keys: vec!["n".into()],
+                args: vec!["3".into()],
+            }
+            .execute(store)
+        };
+        // Повторная загрузка возвращает SHA1 уже закэшированного скрипта.
+        let sha = LuaEngine::with_scripts(|engine| engine.load_script(script)).unwrap();
+        assert_eq!(evalsha(&mut store, &sha).unwrap(), Value::Int(5));
+        assert!(matches!(
+            evalsha(&mut store, &"0".repeat(40)),
+            Err(StoreError::NoScript)
+        ));
+    }
+
+    const LIBRARY: &str = "#!lua name=mylib\n\
+        redis.register_function('setget', function(keys, args)\n\
+            redis.call('SET', keys[1], args[1])\n\
+            return redis.call('GET', keys[1])\n\
+        end)\n\
+        redis.register_function{\n\
+            function_name = 'peek',\n\
+            callback = function(keys) return redis.call('GET', keys[1]) end,\n\
+            flags = { 'no-writes' },\n\
+        }\n\
+        redis.register_function{\n\
+            function_name = 'sneaky',\n\
+            callback = function(keys) return redis.call('DEL', keys[1]) end,\n\
+            flags = { 'no-writes' },\n\
+        }";
+
+    fn load(
+        code: &str,
+        replace: bool,
+    ) -> Result<Value, StoreError> {
+        FunctionLoadCommand {
+            engine: "LUA".into(),
+            code: code.into(),
+            replace,
+        }
+        .execute(&mut create_store())
+    }
+
+    fn fcall(
+        store: &mut StorageEngine,
+        function: &str,
+        read_only: bool,
+        keys: &[&str],
+        args: &[&str],
+    ) -> Result<Value, StoreError> {
+        let keys = keys.iter().map(|k| k.to_string()).collect();
+        let args = args.iter().map(|a| a.to_string()).collect();
+        if read_only {
+            FCallRoCommand {
+                function: function.into(),
+                keys,
+                args,
+            }
+            .execute(store)
+        } else {
+            FCallCommand {
+                function: function.into(),
+                keys,
+                args,
+            }
+            .execute(store)
+        }
+    }
+
+    /// Тест проверяет FUNCTION LOAD, FCALL и запрет записи в FCALL_RO и
+    /// функциях с флагом no-writes.
+    #[test]
+    fn test_function_load_and_fcall() {
+        let mut store = create_store();
+        assert_eq!(
+            load(LIBRARY, false).unwrap(),
+            Value::Str(Sds::from_str("mylib"))
+        );
+        assert!(load(LIBRARY, false).is_err());
+        assert!(load(LIBRARY, true).is_ok());
+
+        assert_eq!(
+            fcall(&mut store, "setget", false, &["k"], &["v"]).unwrap(),
+            Value::Str(Sds::from_str("v"))
+        );
+        assert_eq!(
+            fcall(&mut store, "peek", true, &["k"], &[]).unwrap(),
+            Value::Str(Sds::from_str("v"))
+        );
+        assert!(fcall(&mut store, "setget", true, &["k"], &["w"]).is_err());
+        assert!(fcall(&mut store, "sneaky", false, &["k"], &[]).is_err());
+        assert_eq!(
+            store.get(&Sds::from_str("k")).unwrap(),
+            Some(Value::Str(Sds::from_str("v")))
+        );
+        assert!(fcall(&mut store, "nope", false, &[], &[]).is_err());
+
+        // Некорректные заголовки и библиотеки без функций отклоняются.
+        assert!(load("return 1", false).is_err());
+        assert!(load("#!js name=x\nreturn 1", false).is_err());
+        assert!(load("#!lua name=empty\nlocal x = 1", false).is_err());
+        // Имя функции уже занято другой библиотекой.
+        assert!(load(&LIBRARY.replace("mylib", "other"), false).is_err());
+    }
+
+    /// Тест проверяет FUNCTION LIST с фильтром и кодом и FUNCTION DELETE.
+    #[test]
+    fn test_function_list_and_delete() {
+        let mut store = create_store();
+        load(LIBRARY, false).unwrap();
+        load(
+            "#!lua name=second\nredis.register_function('one', function() return 1 end)",
+            false,
+        )
+        .unwrap();
+
+        let list = |pattern: Option<&str>, with_code| {
+            let Value::Array(items) = FunctionListCommand {
+                library_pattern: pattern.map(str::to_string),
+                with_code,
+            }
+            .execute(&mut create_store())
+            .unwrap() else {
+                panic!("expected array");
+            };
+            items
+        };
+        assert_eq!(list(None, false).len(), 2);
+        let filtered = list(Some("my*"), true);
+        assert_eq!(filtered.len(), 1);
+        let Value::Array(entry) = &filtered[0] else {
+            panic!("expected array");
+        };
+        assert_eq!(entry[1], Value::Str(Sds::from_str("mylib")));
+        assert_eq!(entry.len(), 8);
+        assert_eq!(entry[7], Value::Str(Sds::from_str(LIBRARY)));
+
+        FunctionDeleteCommand {
+            library: "mylib".into(),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert!(FunctionDeleteCommand {
+            library: "mylib".into(),
+        }
+        .execute(&mut store)
+        .is_err());
+        assert!(fcall(&mut store, "setget", false, &["k"], &["v"]).is_err());
+        assert_eq!(
+            fcall(&mut store, "one", false, &[], &[]).unwrap(),
+            Value::Int(1)
+        );
+    }
+
+    /// Тест проверяет FUNCTION DUMP и FUNCTION RESTORE с разными политиками.
+    #[test]
+    fn test_function_dump_and_restore() {
+        let mut store = create_store();
+        load(LIBRARY, false).unwrap();
+        let Value::Str(payload) = FunctionDumpCommand.execute(&mut store).unwrap() else {
+            panic!("expected payload");
+        };
+        let restore = |store: &mut StorageEngine, payload: &[u8], policy| {
+            FunctionRestoreCommand {
+                payload: payload.to_vec(),
+                policy,
+            }
+            .execute(store)
+        };
+
+        assert!(restore(
+            &mut store,
+            payload.as_bytes(),
+            FunctionRestorePolicy::Append
+        )
+        .is_err());
+        assert!(restore(
+            &mut store,
+            payload.as_bytes(),
+            FunctionRestorePolicy::Replace
+        )
+        .is_ok());
+
+        FunctionDeleteCommand {
+            library: "mylib".into(),
+        }
+        .execute(&mut store)
+        .unwrap();
+        restore(&mut store, payload.as_bytes(), FunctionRestorePolicy::Flush).unwrap();
+        assert_eq!(
+            fcall(&mut store, "setget", false, &["k"], &["v"]).unwrap(),
+            Value::Str(Sds::from_str("v"))
+        );
+
+        let mut corrupted = payload.as_bytes().to_vec();
+        corrupted[3] ^= 0xff;
+        assert!(restore(&mut store, &corrupted, FunctionRestorePolicy::Flush).is_err());
+        assert!(fcall(&mut store, "peek", false, &["k"], &[]).is_ok());
+    }
+}
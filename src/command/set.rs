@@ -1,4 +1,6 @@
-use crate::{CommandExecute, QuickList, Sds, StorageEngine, StoreError, Value};
+use crate::{
+    scan_page, CommandExecute, QuickList, Sds, StorageEngine, StoreError, Value, DEFAULT_SCAN_COUNT,
+};
 
 /// Команда SADD — добавляет элемент во множество.
 #[derive(Debug)]
@@ -98,6 +100,48 @@ impl CommandExecute for SMembersCommand {
     }
 }
 
+/// Команда SSCAN — постранично обходит элементы множества по курсору.
+///
+/// Возвращает массив `[next_cursor, [member, ...]]`; курсор `0` означает конец
+/// обхода.
+#[derive(Debug)]
+pub struct SScanCommand {
+    pub key: String,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: Option<usize>,
+}
+
+impl CommandExecute for SScanCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+
+        let (next, page) = match store.get(&key)? {
+            Some(Value::Set(set)) => scan_page(
+                set,
+                self.cursor,
+                self.count.unwrap_or(DEFAULT_SCAN_COUNT),
+                self.pattern.as_deref(),
+                |m| m.as_slice(),
+            ),
+            Some(_) => return Err(StoreError::InvalidType),
+            None => (0, Vec::new()),
+        };
+
+        Ok(Value::Array(vec![
+            Value::Str(Sds::from_str(&next.to_string())),
+            Value::Array(page.into_iter().map(Value::Str).collect()),
+        ]))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SSCAN"
+    }
+}
+
 /// Команда SISMEMBER — проверяет наличие элемента во множестве.
 #[derive(Debug)]
 pub struct SIsMemberCommand {
@@ -122,6 +166,35 @@ impl CommandExecute for SIsMemberCommand {
     }
 }
 
+/// Команда SMISMEMBER — проверяет наличие нескольких элементов во множестве.
+///
+/// Возвращает массив из `1` и `0`, по одному значению на каждый элемент.
+#[derive(Debug)]
+pub struct SmIsMemberCommand {
+    pub key: String,
+    pub members: Vec<String>,
+}
+
+impl CommandExecute for SmIsMemberCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+        let members: Vec<Sds> = self.members.iter().map(|m| Sds::from_str(m)).collect();
+        let member_refs: Vec<&Sds> = members.iter().collect();
+
+        let flags = store.smismember(&key, &member_refs)?;
+        Ok(Value::Array(
+            flags.into_iter().map(|f| Value::Int(f as i64)).collect(),
+        ))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SMISMEMBER"
+    }
+}
+
 /// Команда SRANDMEMBER — возвращает случайный(е) элемент(ы) множества.
 #[derive(Debug)]
 pub struct SRandMemberCommand {
@@ -188,6 +261,165 @@ impl CommandExecute for SPopCommand {
     }
 }
 
+/// Команда SMOVE — атомарно перемещает элемент из одного множества в другое.
+#[derive(Debug)]
+pub struct SMoveCommand {
+    pub source: String,
+    pub destination: String,
+    pub member: String,
+}
+
+impl CommandExecute for SMoveCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let source = Sds::from_str(&self.source);
+        let destination = Sds::from_str(&self.destination);
+        let member = Sds::from_str(&self.member);
+
+        let moved = store.smove(&source, &destination, &member)?;
+        Ok(Value::Int(moved as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SMOVE"
+    }
+}
+
+/// Команда SINTERCARD — возвращает мощность пересечения множеств.
+///
+/// При `LIMIT` подсчёт прекращается, как только достигнут предел; `LIMIT 0`
+/// означает отсутствие предела.
+#[derive(Debug)]
+pub struct SInterCardCommand {
+    pub keys: Vec<String>,
+    pub limit: Option<usize>,
+}
+
+impl CommandExecute for SInterCardCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if self.keys.is_empty() {
+            return Err(StoreError::InvalidArgument(
+                "numkeys should be greater than 0".into(),
+            ));
+        }
+
+        let keys: Vec<Sds> = self.keys.iter().map(|k| Sds::from_str(k)).collect();
+        let key_refs: Vec<&Sds> = keys.iter().collect();
+
+        let count = store.sintercard(&key_refs, self.limit)?;
+        Ok(Value::Int(count as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SINTERCARD"
+    }
+}
+
+/// Команда SINTERSTORE — записывает пересечение множеств в `destination`.
+///
+/// Возвращает мощность результата; пустой результат удаляет `destination`.
+#[derive(Debug)]
+pub struct SInterStoreCommand {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+impl CommandExecute for SInterStoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if self.keys.is_empty() {
+            return Err(StoreError::InvalidArgument(
+                "at least one key is required".into(),
+            ));
+        }
+
+        let destination = Sds::from_str(&self.destination);
+        let keys: Vec<Sds> = self.keys.iter().map(|k| Sds::from_str(k)).collect();
+        let key_refs: Vec<&Sds> = keys.iter().collect();
+
+        let count = store.sinterstore(&destination, &key_refs)?;
+        Ok(Value::Int(count as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SINTERSTORE"
+    }
+}
+
+/// Команда SUNIONSTORE — записывает объединение множеств в `destination`.
+///
+/// Возвращает мощность результата; пустой результат удаляет `destination`.
+#[derive(Debug)]
+pub struct SUnionStoreCommand {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+impl CommandExecute for SUnionStoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if self.keys.is_empty() {
+            return Err(StoreError::InvalidArgument(
+                "at least one key is required".into(),
+            ));
+        }
+
+        let destination = Sds::from_str(&self.destination);
+        let keys: Vec<Sds> = self.keys.iter().map(|k| Sds::from_str(k)).collect();
+        let key_refs: Vec<&Sds> = keys.iter().collect();
+
+        let count = store.sunionstore(&destination, &key_refs)?;
+        Ok(Value::Int(count as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SUNIONSTORE"
+    }
+}
+
+/// Команда SDIFFSTORE — записывает разность первого множества и остальных в
+/// `destination`.
+///
+/// Возвращает мощность результата; пустой результат удаляет `destination`.
+#[derive(Debug)]
+pub struct SDiffStoreCommand {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+impl CommandExecute for SDiffStoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if self.keys.is_empty() {
+            return Err(StoreError::InvalidArgument(
+                "at least one key is required".into(),
+            ));
+        }
+
+        let destination = Sds::from_str(&self.destination);
+        let keys: Vec<Sds> = self.keys.iter().map(|k| Sds::from_str(k)).collect();
+        let key_refs: Vec<&Sds> = keys.iter().collect();
+
+        let count = store.sdiffstore(&destination, &key_refs)?;
+        Ok(Value::Int(count as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SDIFFSTORE"
+    }
+}
+
 /// Команда SINTER — возвращает пересечение множеств.
 #[derive(Debug)]
 pub struct SInterCommand {
@@ -506,4 +738,386 @@ mod tests {
             _ => panic!("Expected list or null"),
         }
     }
+
+    /// Тест, который проверяет, что SMoveCommand переносит элемент и удаляет
+    /// опустевший источник, а повторный вызов возвращает 0.
+    #[test]
+    fn test_smove_moves_member() {
+        let mut store = create_store();
+        store
+            .sadd(&Sds::from_str("src"), &[Sds::from_str("a")])
+            .unwrap();
+        store
+            .sadd(&Sds::from_str("dst"), &[Sds::from_str("b")])
+            .unwrap();
+
+        let cmd = SMoveCommand {
+            source: "src".into(),
+            destination: "dst".into(),
+            member: "a".into(),
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(1));
+        assert_eq!(store.get(&Sds::from_str("src")).unwrap(), None);
+        assert!(store
+            .sismember(&Sds::from_str("dst"), &Sds::from_str("a"))
+            .unwrap());
+        assert_eq!(store.scard(&Sds::from_str("dst")).unwrap(), 2);
+
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(0));
+    }
+
+    /// Тест, который проверяет, что SMoveCommand создаёт приёмник и корректно
+    /// обрабатывает совпадающие ключи.
+    #[test]
+    fn test_smove_creates_destination_and_same_key() {
+        let mut store = create_store();
+        store
+            .sadd(
+                &Sds::from_str("src"),
+                &[Sds::from_str("a"), Sds::from_str("b")],
+            )
+            .unwrap();
+
+        let cmd = SMoveCommand {
+            source: "src".into(),
+            destination: "new".into(),
+            member: "a".into(),
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(1));
+        assert_eq!(store.scard(&Sds::from_str("src")).unwrap(), 1);
+        assert_eq!(store.scard(&Sds::from_str("new")).unwrap(), 1);
+
+        let same = SMoveCommand {
+            source: "src".into(),
+            destination: "src".into(),
+            member: "b".into(),
+        };
+        assert_eq!(same.execute(&mut store).unwrap(), Value::Int(1));
+        assert_eq!(store.scard(&Sds::from_str("src")).unwrap(), 1);
+
+        let missing = SMoveCommand {
+            source: "src".into(),
+            destination: "src".into(),
+            member: "zzz".into(),
+        };
+        assert_eq!(missing.execute(&mut store).unwrap(), Value::Int(0));
+    }
+
+    /// Тест, который проверяет, что SMoveCommand отклоняет приёмник неверного
+    /// типа и не изменяет источник.
+    #[test]
+    fn test_smove_wrong_type_destination() {
+        let mut store = create_store();
+        store
+            .sadd(&Sds::from_str("src"), &[Sds::from_str("a")])
+            .unwrap();
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+
+        let cmd = SMoveCommand {
+            source: "src".into(),
+            destination: "str".into(),
+            member: "a".into(),
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+        assert!(store
+            .sismember(&Sds::from_str("src"), &Sds::from_str("a"))
+            .unwrap());
+    }
+
+    // Вспомогательная функция для заполнения множества элементами.
+    fn fill_set(
+        store: &mut StorageEngine,
+        key: &str,
+        members: &[&str],
+    ) {
+        let members: Vec<Sds> = members.iter().map(|m| Sds::from_str(m)).collect();
+        store.sadd(&Sds::from_str(key), &members).unwrap();
+    }
+
+    /// Тест, который проверяет, что SInterCardCommand считает общие элементы
+    /// и возвращает 0 для непересекающихся множеств и отсутствующего ключа.
+    #[test]
+    fn test_sintercard_counts_intersection() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1", "2", "3", "4"]);
+        fill_set(&mut store, "b", &["2", "3", "5"]);
+        fill_set(&mut store, "c", &["7", "8"]);
+
+        let cmd = SInterCardCommand {
+            keys: vec!["a".into(), "b".into()],
+            limit: None,
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(2));
+
+        let disjoint = SInterCardCommand {
+            keys: vec!["a".into(), "c".into()],
+            limit: None,
+        };
+        assert_eq!(disjoint.execute(&mut store).unwrap(), Value::Int(0));
+
+        let missing = SInterCardCommand {
+            keys: vec!["a".into(), "nokey".into()],
+            limit: None,
+        };
+        assert_eq!(missing.execute(&mut store).unwrap(), Value::Int(0));
+    }
+
+    /// Тест, который проверяет, что для одного ключа SInterCardCommand
+    /// возвращает мощность множества.
+    #[test]
+    fn test_sintercard_single_key() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1", "2", "3"]);
+
+        let cmd = SInterCardCommand {
+            keys: vec!["a".into()],
+            limit: None,
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(3));
+    }
+
+    /// Тест, который проверяет, что LIMIT ограничивает результат, а LIMIT 0
+    /// равносилен отсутствию предела.
+    #[test]
+    fn test_sintercard_limit() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1", "2", "3", "4"]);
+        fill_set(&mut store, "b", &["1", "2", "3", "4", "5"]);
+
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let limited = SInterCardCommand {
+            keys: keys.clone(),
+            limit: Some(1),
+        };
+        assert_eq!(limited.execute(&mut store).unwrap(), Value::Int(1));
+
+        let zero = SInterCardCommand {
+            keys,
+            limit: Some(0),
+        };
+        assert_eq!(zero.execute(&mut store).unwrap(), Value::Int(4));
+    }
+
+    /// Тест, который проверяет, что SInterCardCommand отклоняет ключ не того
+    /// типа и пустой список ключей.
+    #[test]
+    fn test_sintercard_errors() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1"]);
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+
+        let wrong = SInterCardCommand {
+            keys: vec!["a".into(), "str".into()],
+            limit: None,
+        };
+        assert!(matches!(
+            wrong.execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+
+        let empty = SInterCardCommand {
+            keys: vec![],
+            limit: None,
+        };
+        assert!(matches!(
+            empty.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    /// Тест, который проверяет SInterStoreCommand, SUnionStoreCommand и
+    /// SDiffStoreCommand: результат записывается в приёмник, прежнее значение
+    /// приёмника перезаписывается.
+    #[test]
+    fn test_set_store_commands() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1", "2", "3"]);
+        fill_set(&mut store, "b", &["2", "3", "4"]);
+        store
+            .set(&Sds::from_str("dst"), Value::Str(Sds::from_str("old")))
+            .unwrap();
+        let keys = vec!["a".to_string(), "b".to_string()];
+
+        let inter = SInterStoreCommand {
+            destination: "dst".into(),
+            keys: keys.clone(),
+        };
+        assert_eq!(inter.execute(&mut store).unwrap(), Value::Int(2));
+        assert_eq!(store.scard(&Sds::from_str("dst")).unwrap(), 2);
+
+        let union = SUnionStoreCommand {
+            destination: "dst".into(),
+            keys: keys.clone(),
+        };
+        assert_eq!(union.execute(&mut store).unwrap(), Value::Int(4));
+
+        let diff = SDiffStoreCommand {
+            destination: "dst".into(),
+            keys,
+        };
+        assert_eq!(diff.execute(&mut store).unwrap(), Value::Int(1));
+        assert!(store
+            .sismember(&Sds::from_str("dst"), &Sds::from_str("1"))
+            .unwrap());
+    }
+
+    /// Тест, который проверяет, что пустой результат удаляет приёмник, а
+    /// приёмник не наследует TTL.
+    #[test]
+    fn test_set_store_empty_result_and_ttl() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1"]);
+        fill_set(&mut store, "b", &["2"]);
+        fill_set(&mut store, "dst", &["x"]);
+        store
+            .set_expiry(
+                &Sds::from_str("dst"),
+                Some(std::time::Duration::from_secs(100)),
+            )
+            .unwrap();
+
+        let inter = SInterStoreCommand {
+            destination: "dst".into(),
+            keys: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(inter.execute(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(store.get(&Sds::from_str("dst")).unwrap(), None);
+
+        let union = SUnionStoreCommand {
+            destination: "dst".into(),
+            keys: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(union.execute(&mut store).unwrap(), Value::Int(2));
+        assert_eq!(store.expiretime(&Sds::from_str("dst")).unwrap(), Some(-1));
+    }
+
+    /// Тест, который проверяет, что приёмник может совпадать с одним из
+    /// источников.
+    #[test]
+    fn test_set_store_destination_is_source() {
+        let mut store = create_store();
+        fill_set(&mut store, "a", &["1", "2", "3"]);
+        fill_set(&mut store, "b", &["2", "3", "4"]);
+
+        let diff = SDiffStoreCommand {
+            destination: "a".into(),
+            keys: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(diff.execute(&mut store).unwrap(), Value::Int(1));
+
+        let union = SUnionStoreCommand {
+            destination: "b".into(),
+            keys: vec!["a".into(), "b".into()],
+        };
+        assert_eq!(union.execute(&mut store).unwrap(), Value::Int(4));
+        assert_eq!(store.scard(&Sds::from_str("b")).unwrap(), 4);
+    }
+
+    // Вспомогательная функция для выполнения SMISMEMBER.
+    fn smismember(
+        store: &mut StorageEngine,
+        key: &str,
+        members: &[&str],
+    ) -> Value {
+        SmIsMemberCommand {
+            key: key.into(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+        .execute(store)
+        .unwrap()
+    }
+
+    /// Тест, который проверяет SmIsMemberCommand для отсутствующего ключа,
+    /// полного, пустого и частичного совпадения.
+    #[test]
+    fn test_smismember() {
+        let mut store = create_store();
+        fill_set(&mut store, "s", &["a", "b", "c"]);
+        let ints = |v: &[i64]| Value::Array(v.iter().map(|&i| Value::Int(i)).collect());
+
+        assert_eq!(smismember(&mut store, "empty", &["a", "b"]), ints(&[0, 0]));
+        assert_eq!(
+            smismember(&mut store, "s", &["a", "b", "c"]),
+            ints(&[1, 1, 1])
+        );
+        assert_eq!(smismember(&mut store, "s", &["x", "y"]), ints(&[0, 0]));
+        assert_eq!(
+            smismember(&mut store, "s", &["a", "x", "c"]),
+            ints(&[1, 0, 1])
+        );
+    }
+
+    /// Тест, который проверяет, что повторяющиеся элементы запроса получают
+    /// каждый свой ответ.
+    #[test]
+    fn test_smismember_duplicates() {
+        let mut store = create_store();
+        fill_set(&mut store, "s", &["a"]);
+
+        match smismember(&mut store, "s", &["a", "a", "b", "a"]) {
+            Value::Array(items) => assert_eq!(
+                items,
+                vec![Value::Int(1), Value::Int(1), Value::Int(0), Value::Int(1)]
+            ),
+            other => panic!("Expected array, got {other:?}"),
+        }
+    }
+
+    /// Тест проверяет, что SSCAN обходит все элементы множества по курсору и
+    /// фильтрует их по MATCH.
+    #[test]
+    fn test_sscan_iterates_all_members() {
+        let mut store = create_store();
+        fill_set(&mut store, "s", &["a1", "a2", "a3", "b1", "b2"]);
+
+        let mut cursor = 0;
+        let mut members = Vec::new();
+        loop {
+            let reply = SScanCommand {
+                key: "s".into(),
+                cursor,
+                pattern: Some("a?".into()),
+                count: Some(2),
+            }
+            .execute(&mut store)
+            .unwrap();
+            let Value::Array(parts) = reply else {
+                panic!("expected array");
+            };
+            let (Value::Str(next), Value::Array(items)) = (&parts[0], &parts[1]) else {
+                panic!("unexpected reply shape");
+            };
+            for item in items {
+                if let Value::Str(m) = item {
+                    members.push(m.as_str().unwrap().to_string());
+                }
+            }
+            cursor = next.as_str().unwrap().parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        members.sort();
+        assert_eq!(members, vec!["a1", "a2", "a3"]);
+
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+        let err = SScanCommand {
+            key: "str".into(),
+            cursor: 0,
+            pattern: None,
+            count: None,
+        }
+        .execute(&mut store);
+        assert!(matches!(err, Err(StoreError::InvalidType)));
+    }
 }
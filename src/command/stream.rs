@@ -1,18 +1,141 @@
-use super::CommandExecute;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::{self, Future},
+    ops::Bound,
+    task::Poll,
+    time::Duration,
+};
+
+use tokio::time::{self, Instant};
+
+use crate::{
+    ClaimOptions, CommandExecute, KeyspaceEvent, Sds, StorageEngine, StoreError, Stream,
+    StreamEntry, StreamId, StreamTrim, StreamTrimStrategy, Value,
+};
+
+/// Наибольший интервал между проверками `XREAD BLOCK`: поток, записанный
+/// не через `XADD` (например, `RENAME` или `RESTORE`), не будит ожидающих.
+pub const STREAM_BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Идентификатор новой записи в `XADD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XAddId {
+    /// `*` — идентификатор генерируется по текущему времени.
+    Auto,
+    /// `ms-*` — sequence подбирается в заданной миллисекунде.
+    AutoSeq(u64),
+    /// `ms-seq` — явный идентификатор.
+    Explicit(StreamId),
+}
 
 /// Команда XADD — добавляет запись в поток.
+///
+/// Формат: `XADD key [NOMKSTREAM] [MAXLEN|MINID [=|~] threshold [LIMIT
+/// count]] *|id field value [field value ...]`.
 #[derive(Debug)]
 pub struct XAddCommand {
     pub key: String,
-    pub fileds: Vec<(String, String)>,
+    pub id: XAddId,
+    pub fields: Vec<(String, String)>,
+    pub trim: Option<StreamTrim>,
+    pub nomkstream: bool,
+}
+
+impl XAddCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let mut nomkstream = false;
+        let mut trim = None;
+        let mut rest = args;
+        while let Some((arg, tail)) = rest.split_first() {
+            match arg.to_ascii_uppercase().as_str() {
+                "NOMKSTREAM" => {
+                    nomkstream = true;
+                    rest = tail;
+                }
+                "MAXLEN" | "MINID" => {
+                    let (parsed, tail) = parse_stream_trim("XADD", rest)?;
+                    trim = Some(parsed);
+                    rest = tail;
+                }
+                _ => break,
+            }
+        }
+
+        let Some((id, pairs)) = rest.split_first() else {
+            return Err(StoreError::Syntax("XADD: missing ID".to_string()));
+        };
+        if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+            return Err(StoreError::Syntax(
+                "XADD expects field value pairs".to_string(),
+            ));
+        }
+        let fields = pairs
+            .chunks(2)
+            .map(|pair| (pair[0].to_string(), pair[1].to_string()))
+            .collect();
+
+        Ok(Self {
+            key,
+            id: parse_xadd_id(id)?,
+            fields,
+            trim,
+            nomkstream,
+        })
+    }
 }
 
 impl CommandExecute for XAddCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XADD is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let mut stream = match get_stream(store, &self.key)? {
+            Some(stream) => stream,
+            None if self.nomkstream => return Ok(Value::Null),
+            None => Stream::new(),
+        };
+
+        let id = match self.id {
+            XAddId::Auto => stream.next_id(),
+            XAddId::AutoSeq(ms_time) => stream.next_id_at(ms_time),
+            XAddId::Explicit(id) => Some(id),
+        };
+        let too_small = || {
+            StoreError::InvalidArgument(
+                "The ID specified in XADD is equal or smaller than the target stream top item"
+                    .to_string(),
+            )
+        };
+        let id = match id {
+            Some(StreamId::MIN) => {
+                return Err(StoreError::InvalidArgument(
+                    "The ID specified in XADD must be greater than 0-0".to_string(),
+                ))
+            }
+            Some(id) => id,
+            None => return Err(too_small()),
+        };
+
+        let data: HashMap<String, Value> = self
+            .fields
+            .iter()
+            .map(|(field, value)| (field.clone(), Value::Str(Sds::from_str(value))))
+            .collect();
+        stream.add_with_id(id, data).ok_or_else(too_small)?;
+        let trimmed = self.trim.map_or(0, |trim| stream.trim(&trim));
+
+        let key = Sds::from_str(&self.key);
+        store.set(&key, Value::SStream(stream))?;
+        store.stream_notifiers().notify(&key);
+        store.notify_keyspace_event(KeyspaceEvent::XAdd, &key);
+        if trimmed > 0 {
+            store.notify_keyspace_event(KeyspaceEvent::XTrim, &key);
+        }
+        Ok(id_reply(id))
     }
 
     fn command_name(&self) -> &'static str {
@@ -20,19 +143,192 @@ impl CommandExecute for XAddCommand {
     }
 }
 
+/// Начальная позиция чтения потока в `XREAD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XReadId {
+    /// Записи с идентификатором больше заданного.
+    After(StreamId),
+    /// `$` — только записи, добавленные после вызова команды.
+    New,
+}
+
 /// Команда XREAD — читает записи из одного или нескольких потоков.
+///
+/// Формат: `XREAD [COUNT count] [BLOCK milliseconds] STREAMS key [key ...]
+/// id [id ...]`. [`CommandExecute::execute`] читает без ожидания; `BLOCK`
+/// выполняется через [`XReadCommand::execute_blocking`].
 #[derive(Debug)]
 pub struct XReadCommand {
-    pub streams: Vec<(String, String)>,
+    pub streams: Vec<(String, XReadId)>,
     pub count: Option<usize>,
+    /// Время ожидания новых записей; `Duration::ZERO` — ждать бесконечно.
+    pub block: Option<Duration>,
+}
+
+impl XReadCommand {
+    /// Разбирает аргументы после имени команды.
+    pub fn parse(args: &[&str]) -> Result<Self, StoreError> {
+        let missing = || StoreError::Syntax("XREAD: missing argument".to_string());
+        let not_integer =
+            || StoreError::InvalidArgument("value is not an integer or out of range".to_string());
+        let mut count = None;
+        let mut block = None;
+        let mut rest = args;
+        loop {
+            let (arg, tail) = rest.split_first().ok_or_else(missing)?;
+            let (value, tail) = match arg.to_ascii_uppercase().as_str() {
+                "STREAMS" => {
+                    rest = tail;
+                    break;
+                }
+                "COUNT" | "BLOCK" => tail.split_first().ok_or_else(missing)?,
+                other => {
+                    return Err(StoreError::Syntax(format!(
+                        "XREAD: unexpected argument '{other}'"
+                    )))
+                }
+            };
+            if arg.eq_ignore_ascii_case("COUNT") {
+                // `COUNT 0` не ограничивает число записей, как в Redis.
+                count = Some(value.parse::<usize>().map_err(|_| not_integer())?).filter(|&n| n > 0);
+            } else {
+                let ms = value.parse::<u64>().map_err(|_| {
+                    StoreError::InvalidArgument(
+                        "timeout is not an integer or out of range".to_string(),
+                    )
+                })?;
+                block = Some(Duration::from_millis(ms));
+            }
+            rest = tail;
+        }
+
+        if rest.is_empty() || !rest.len().is_multiple_of(2) {
+            return Err(StoreError::Syntax(
+                "Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified"
+                    .to_string(),
+            ));
+        }
+        let (keys, ids) = rest.split_at(rest.len() / 2);
+        let streams = keys
+            .iter()
+            .zip(ids)
+            .map(|(key, id)| {
+                let id = match *id {
+                    "$" => XReadId::New,
+                    id => XReadId::After(id.parse().map_err(StoreError::InvalidArgument)?),
+                };
+                Ok((key.to_string(), id))
+            })
+            .collect::<Result<_, StoreError>>()?;
+
+        Ok(Self {
+            streams,
+            count,
+            block,
+        })
+    }
+
+    /// Выполняет `XREAD`, с `BLOCK` ожидая новых записей.
+    ///
+    /// Ожидание просыпается по уведомлению из реестра движка
+    /// ([`StorageEngine::stream_notifiers`]), которое `XADD` отправляет
+    /// после записи, в том числе в ещё не созданный поток. Кроме того,
+    /// потоки проверяются заново не реже раза в
+    /// [`STREAM_BLOCK_POLL_INTERVAL`].
+    ///
+    /// # Возвращает
+    /// - массив пар `[key, entries]` по потокам с новыми записями
+    /// - `Value::Null` — если новых записей нет, а время ожидания истекло
+    pub async fn execute_blocking(
+        &self,
+        store: &StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let after = self.resolve_ids(store)?;
+        let Some(block) = self.block else {
+            return Ok(self.read(store, &after)?.unwrap_or(Value::Null));
+        };
+        let deadline = (!block.is_zero()).then(|| Instant::now() + block);
+        let subscriptions: Vec<_> = self
+            .streams
+            .iter()
+            .map(|(key, _)| store.stream_notifiers().subscribe(&Sds::from_str(key)))
+            .collect();
+
+        loop {
+            // Ожидания создаются до чтения, поэтому `XADD` между чтением и
+            // ожиданием не теряется.
+            let mut waits: Vec<_> = subscriptions
+                .iter()
+                .map(|subscription| Box::pin(subscription.notified()))
+                .collect();
+            if let Some(reply) = self.read(store, &after)? {
+                return Ok(reply);
+            }
+
+            let poll_at = Instant::now() + STREAM_BLOCK_POLL_INTERVAL;
+            let wake_at = deadline.map_or(poll_at, |deadline| deadline.min(poll_at));
+            let woken = future::poll_fn(|cx| {
+                if waits.iter_mut().any(|w| w.as_mut().poll(cx).is_ready()) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            });
+            if time::timeout_at(wake_at, woken).await.is_err()
+                && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return Ok(Value::Null);
+            }
+        }
+    }
+
+    /// Заменяет `$` на последний идентификатор потока на момент вызова.
+    fn resolve_ids(
+        &self,
+        store: &StorageEngine,
+    ) -> Result<Vec<StreamId>, StoreError> {
+        self.streams
+            .iter()
+            .map(|(key, id)| match id {
+                XReadId::After(id) => Ok(*id),
+                XReadId::New => Ok(get_stream(store, key)?.map_or(StreamId::MIN, |s| s.last_id())),
+            })
+            .collect()
+    }
+
+    /// Читает записи после `after` из каждого потока.
+    ///
+    /// # Возвращает
+    /// - `None` — если ни в одном потоке нет новых записей
+    fn read(
+        &self,
+        store: &StorageEngine,
+        after: &[StreamId],
+    ) -> Result<Option<Value>, StoreError> {
+        let mut reply = Vec::new();
+        for ((key, _), after) in self.streams.iter().zip(after) {
+            let Some(stream) = get_stream(store, key)? else {
+                continue;
+            };
+            let entries = stream.range(Bound::Excluded(*after), Bound::Unbounded, self.count);
+            if !entries.is_empty() {
+                reply.push(Value::Array(vec![
+                    Value::Str(Sds::from_str(key)),
+                    entries_reply(entries),
+                ]));
+            }
+        }
+        Ok((!reply.is_empty()).then_some(Value::Array(reply)))
+    }
 }
 
 impl CommandExecute for XReadCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XREAD is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let after = self.resolve_ids(store)?;
+        Ok(self.read(store, &after)?.unwrap_or(Value::Null))
     }
 
     fn command_name(&self) -> &'static str {
@@ -41,19 +337,44 @@ impl CommandExecute for XReadCommand {
 }
 
 /// Команда XRANGE — возвращает записи из потока в диапазоне ID.
+///
+/// Формат: `XRANGE key start end [COUNT count]`; `-` и `+` — наименьший и
+/// наибольший ID, префикс `(` делает границу исключающей.
 #[derive(Debug)]
 pub struct XRangeCommand {
     pub key: String,
-    pub start: String,
-    pub end: String,
+    pub start: Bound<StreamId>,
+    pub end: Bound<StreamId>,
+    pub count: Option<usize>,
+}
+
+impl XRangeCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let (start, end, count) = parse_range_args("XRANGE", args)?;
+        Ok(Self {
+            key,
+            start: parse_range_bound(start, false)?,
+            end: parse_range_bound(end, true)?,
+            count,
+        })
+    }
 }
 
 impl CommandExecute for XRangeCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XRANGE is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(stream) = get_stream(store, &self.key)? else {
+            return Ok(Value::Array(Vec::new()));
+        };
+        Ok(entries_reply(
+            stream.range(self.start, self.end, self.count),
+        ))
     }
 
     fn command_name(&self) -> &'static str {
@@ -62,19 +383,43 @@ impl CommandExecute for XRangeCommand {
 }
 
 /// Команда XREVRANGE — возвращает записи из потока в обратном порядке.
+///
+/// Формат: `XREVRANGE key end start [COUNT count]`.
 #[derive(Debug)]
 pub struct XRevRangeCommand {
     pub key: String,
-    pub start: String,
-    pub end: String,
+    pub start: Bound<StreamId>,
+    pub end: Bound<StreamId>,
+    pub count: Option<usize>,
+}
+
+impl XRevRangeCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let (end, start, count) = parse_range_args("XREVRANGE", args)?;
+        Ok(Self {
+            key,
+            start: parse_range_bound(start, false)?,
+            end: parse_range_bound(end, true)?,
+            count,
+        })
+    }
 }
 
 impl CommandExecute for XRevRangeCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XREVRANGE is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(stream) = get_stream(store, &self.key)? else {
+            return Ok(Value::Array(Vec::new()));
+        };
+        Ok(entries_reply(
+            stream.rev_range(self.start, self.end, self.count),
+        ))
     }
 
     fn command_name(&self) -> &'static str {
@@ -91,9 +436,10 @@ pub struct XLenCommand {
 impl CommandExecute for XLenCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XLEN is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let len = get_stream(store, &self.key)?.map_or(0, |stream| stream.len());
+        Ok(Value::Int(len as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -102,18 +448,47 @@ impl CommandExecute for XLenCommand {
 }
 
 /// Команда XDEL — удаляет записи по их ID.
+///
+/// Формат: `XDEL key id [id ...]`. Удалённые записи убираются и из PEL
+/// групп; последующие записи не сдвигаются, а ID потока не уменьшается.
 #[derive(Debug)]
 pub struct XDelCommand {
     pub key: String,
-    pub ids: Vec<String>,
+    pub ids: Vec<StreamId>,
+}
+
+impl XDelCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        if args.is_empty() {
+            return Err(StoreError::Syntax("XDEL: missing ID".to_string()));
+        }
+        let ids = args
+            .iter()
+            .map(|id| id.parse().map_err(StoreError::InvalidArgument))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { key, ids })
+    }
 }
 
 impl CommandExecute for XDelCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XDEL is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(mut stream) = get_stream(store, &self.key)? else {
+            return Ok(Value::Int(0));
+        };
+        let deleted = stream.delete(&self.ids);
+        if deleted > 0 {
+            let key = Sds::from_str(&self.key);
+            store.set(&key, Value::SStream(stream))?;
+            store.notify_keyspace_event(KeyspaceEvent::XDel, &key);
+        }
+        Ok(Value::Int(deleted as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -121,20 +496,47 @@ impl CommandExecute for XDelCommand {
     }
 }
 
-/// Команда XTRIM — обрезает поток, оставляя только последние `max_len`
-/// записей.
+/// Команда XTRIM — обрезает поток, удаляя самые старые записи.
+///
+/// Формат: `XTRIM key MAXLEN|MINID [=|~] threshold [LIMIT count]`; с `~`
+/// в потоке может остаться больше записей, чем требует порог.
 #[derive(Debug)]
 pub struct XTrimCommand {
     pub key: String,
-    pub max_len: usize,
+    pub trim: StreamTrim,
+}
+
+impl XTrimCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let (trim, rest) = parse_stream_trim("XTRIM", args)?;
+        if let Some(arg) = rest.first() {
+            return Err(StoreError::Syntax(format!(
+                "XTRIM: unexpected argument '{arg}'"
+            )));
+        }
+        Ok(Self { key, trim })
+    }
 }
 
 impl CommandExecute for XTrimCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XTRIM is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(mut stream) = get_stream(store, &self.key)? else {
+            return Ok(Value::Int(0));
+        };
+        let trimmed = stream.trim(&self.trim);
+        if trimmed > 0 {
+            let key = Sds::from_str(&self.key);
+            store.set(&key, Value::SStream(stream))?;
+            store.notify_keyspace_event(KeyspaceEvent::XTrim, &key);
+        }
+        Ok(Value::Int(trimmed as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -143,19 +545,71 @@ impl CommandExecute for XTrimCommand {
 }
 
 /// Команда XGROUP CREATE — создаёт группу потребителей для потока.
+///
+/// Формат: `XGROUP CREATE key group id|$ [MKSTREAM]`; группе считаются
+/// выданными записи до `id` включительно.
 #[derive(Debug)]
 pub struct XGroupCreateCommand {
     pub key: String,
     pub group: String,
-    pub id: String,
+    pub id: XReadId,
+    pub mkstream: bool,
+}
+
+impl XGroupCreateCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let (group, id, mkstream) = match args {
+            [group, id] => (group, id, false),
+            [group, id, opt] if opt.eq_ignore_ascii_case("MKSTREAM") => (group, id, true),
+            _ => {
+                return Err(StoreError::Syntax(
+                    "XGROUP CREATE expects group id|$ [MKSTREAM]".to_string(),
+                ))
+            }
+        };
+        let id = match *id {
+            "$" => XReadId::New,
+            id => XReadId::After(id.parse().map_err(StoreError::InvalidArgument)?),
+        };
+        Ok(Self {
+            key,
+            group: group.to_string(),
+            id,
+            mkstream,
+        })
+    }
 }
 
 impl CommandExecute for XGroupCreateCommand {
     fn execute(
         &self,
-        _store: &mut crate::StorageEngine,
-    ) -> Result<crate::Value, crate::StoreError> {
-        unimplemented!("XGROUP CREATE is not implemented yet")
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let mut stream = match get_stream(store, &self.key)? {
+            Some(stream) => stream,
+            None if self.mkstream => Stream::new(),
+            None => {
+                return Err(StoreError::InvalidArgument(
+                    "The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically."
+                        .to_string(),
+                ))
+            }
+        };
+        let last_delivered_id = match self.id {
+            XReadId::After(id) => id,
+            XReadId::New => stream.last_id(),
+        };
+        if !stream.create_group(&self.group, last_delivered_id) {
+            return Err(StoreError::InvalidArgument(
+                "BUSYGROUP Consumer Group name already exists".to_string(),
+            ));
+        }
+        store.set(&Sds::from_str(&self.key), Value::SStream(stream))?;
+        Ok(Value::Str(Sds::from_str("OK")))
     }
 
     fn command_name(&self) -> &'static str {
@@ -183,3 +637,1820 @@ impl CommandExecute for XAckCommand {
         "XACK"
     }
 }
+
+/// Подкоманда `XINFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XInfoKind {
+    /// `STREAM key` — сводка по потоку.
+    Stream,
+    /// `STREAM key FULL [COUNT n]` — полное состояние потока вместе с PEL;
+    /// `count` ограничивает число записей и элементов PEL (`None` — все).
+    StreamFull { count: Option<usize> },
+    /// `GROUPS key` — сводки по группам.
+    Groups,
+    /// `CONSUMERS key group` — сводки по потребителям группы.
+    Consumers(String),
+}
+
+/// Команда XINFO — возвращает сведения о потоке, его группах и
+/// потребителях.
+///
+/// Формат: `XINFO STREAM key [FULL [COUNT count]]`, `XINFO GROUPS key`,
+/// `XINFO CONSUMERS key group`.
+#[derive(Debug)]
+pub struct XInfoCommand {
+    pub key: String,
+    pub kind: XInfoKind,
+}
+
+impl XInfoCommand {
+    /// Число записей в ответе `XINFO STREAM FULL` без `COUNT`, как в Redis.
+    pub const DEFAULT_FULL_COUNT: usize = 10;
+
+    /// Разбирает аргументы после имени команды.
+    pub fn parse(args: &[&str]) -> Result<Self, StoreError> {
+        let Some((sub, rest)) = args.split_first() else {
+            return Err(StoreError::Syntax("XINFO: missing subcommand".to_string()));
+        };
+        let sub = sub.to_ascii_uppercase();
+        let (kind, key) = match (sub.as_str(), rest) {
+            ("STREAM", [key]) => (XInfoKind::Stream, key),
+            ("STREAM", [key, full]) if full.eq_ignore_ascii_case("FULL") => (
+                XInfoKind::StreamFull {
+                    count: Some(Self::DEFAULT_FULL_COUNT),
+                },
+                key,
+            ),
+            ("STREAM", [key, full, opt, count])
+                if full.eq_ignore_ascii_case("FULL") && opt.eq_ignore_ascii_case("COUNT") =>
+            {
+                let count = count.parse::<usize>().map_err(|_| {
+                    StoreError::InvalidArgument(
+                        "value is not an integer or out of range".to_string(),
+                    )
+                })?;
+                // `COUNT 0` выводит все записи, как в Redis.
+                let count = (count > 0).then_some(count);
+                (XInfoKind::StreamFull { count }, key)
+            }
+            ("GROUPS", [key]) => (XInfoKind::Groups, key),
+            ("CONSUMERS", [key, group]) => (XInfoKind::Consumers(group.to_string()), key),
+            _ => {
+                return Err(StoreError::Syntax(format!(
+                    "XINFO: unknown subcommand or wrong number of arguments for '{sub}'"
+                )))
+            }
+        };
+        Ok(Self {
+            key: key.to_string(),
+            kind,
+        })
+    }
+}
+
+impl CommandExecute for XInfoCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let stream = get_stream(store, &self.key)?.ok_or(StoreError::KeyNotFound)?;
+        let now = Stream::current_millis();
+        match &self.kind {
+            XInfoKind::Stream => {
+                let info = stream.info();
+                Ok(info_reply(vec![
+                    ("length", Value::Int(info.length as i64)),
+                    ("last-generated-id", id_reply(info.last_generated_id)),
+                    ("groups", Value::Int(info.groups as i64)),
+                    (
+                        "first-entry",
+                        info.first_entry.map_or(Value::Null, entry_reply),
+                    ),
+                    (
+                        "last-entry",
+                        info.last_entry.map_or(Value::Null, entry_reply),
+                    ),
+                ]))
+            }
+            XInfoKind::StreamFull { count } => Ok(stream_full_reply(&stream, *count)),
+            XInfoKind::Groups => Ok(Value::Array(
+                stream
+                    .groups_info()
+                    .into_iter()
+                    .map(|group| {
+                        info_reply(vec![
+                            ("name", Value::Str(Sds::from_str(&group.name))),
+                            ("consumers", Value::Int(group.consumers as i64)),
+                            ("pending", Value::Int(group.pending as i64)),
+                            ("last-delivered-id", id_reply(group.last_delivered_id)),
+                        ])
+                    })
+                    .collect(),
+            )),
+            XInfoKind::Consumers(group) => {
+                let consumers = stream.consumers_info(group, now).ok_or_else(|| {
+                    StoreError::InvalidArgument(format!(
+                        "NOGROUP No such consumer group '{group}' for key name '{}'",
+                        self.key
+                    ))
+                })?;
+                Ok(Value::Array(
+                    consumers
+                        .into_iter()
+                        .map(|consumer| {
+                            info_reply(vec![
+                                ("name", Value::Str(Sds::from_str(&consumer.name))),
+                                ("pending", Value::Int(consumer.pending as i64)),
+                                ("idle", Value::Int(consumer.idle as i64)),
+                            ])
+                        })
+                        .collect(),
+                ))
+            }
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "XINFO"
+    }
+}
+
+/// Диапазон расширенной формы `XPENDING`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XPendingRange {
+    /// Наименьший простой сообщения в миллисекундах (`IDLE`).
+    pub min_idle: Option<u64>,
+    pub start: Bound<StreamId>,
+    pub end: Bound<StreamId>,
+    pub count: usize,
+    /// Только сообщения этого потребителя.
+    pub consumer: Option<String>,
+}
+
+/// Команда XPENDING — показывает сообщения, выданные потребителям группы,
+/// но ещё не подтверждённые.
+///
+/// Формат: `XPENDING key group [[IDLE min-idle-time] start end count
+/// [consumer]]`. Без диапазона возвращает сводку: число сообщений,
+/// наименьший и наибольший ID и число сообщений у каждого потребителя.
+#[derive(Debug)]
+pub struct XPendingCommand {
+    pub key: String,
+    pub group: String,
+    pub range: Option<XPendingRange>,
+}
+
+impl XPendingCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let Some((group, mut rest)) = args.split_first() else {
+            return Err(StoreError::Syntax("XPENDING: missing group".to_string()));
+        };
+        if rest.is_empty() {
+            return Ok(Self {
+                key,
+                group: group.to_string(),
+                range: None,
+            });
+        }
+
+        let mut min_idle = None;
+        if let [opt, idle, tail @ ..] = rest {
+            if opt.eq_ignore_ascii_case("IDLE") {
+                min_idle = Some(parse_millis(idle)?);
+                rest = tail;
+            }
+        }
+        let (start, end, count, consumer) = match rest {
+            [start, end, count] => (start, end, count, None),
+            [start, end, count, consumer] => (start, end, count, Some(consumer.to_string())),
+            _ => {
+                return Err(StoreError::Syntax(
+                    "XPENDING expects [[IDLE min-idle-time] start end count [consumer]]"
+                        .to_string(),
+                ))
+            }
+        };
+        let count = count.parse::<usize>().map_err(|_| {
+            StoreError::InvalidArgument("value is not an integer or out of range".to_string())
+        })?;
+
+        Ok(Self {
+            key,
+            group: group.to_string(),
+            range: Some(XPendingRange {
+                min_idle,
+                start: parse_range_bound(start, false)?,
+                end: parse_range_bound(end, true)?,
+                count,
+                consumer,
+            }),
+        })
+    }
+}
+
+impl CommandExecute for XPendingCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let stream = get_stream(store, &self.key)?;
+        let Some(group) = stream.as_ref().and_then(|s| s.group(&self.group)) else {
+            return Err(no_group(&self.key, &self.group));
+        };
+        let pel = group.pel();
+
+        let Some(range) = &self.range else {
+            let (Some((first, _)), Some((last, _))) = (pel.iter().next(), pel.iter().next_back())
+            else {
+                return Ok(Value::Array(vec![
+                    Value::Int(0),
+                    Value::Null,
+                    Value::Null,
+                    Value::Null,
+                ]));
+            };
+            let mut per_consumer: BTreeMap<&str, usize> = BTreeMap::new();
+            for (_, entry) in pel.iter() {
+                *per_consumer.entry(&entry.consumer).or_default() += 1;
+            }
+            return Ok(Value::Array(vec![
+                Value::Int(pel.len() as i64),
+                id_reply(*first),
+                id_reply(*last),
+                Value::Array(
+                    per_consumer
+                        .into_iter()
+                        .map(|(consumer, count)| {
+                            Value::Array(vec![
+                                Value::Str(Sds::from_str(consumer)),
+                                Value::Str(Sds::from_str(&count.to_string())),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ]));
+        };
+
+        let now = Stream::current_millis();
+        let min_idle = range.min_idle.unwrap_or(0);
+        Ok(Value::Array(
+            pel.range(range.start, range.end)
+                .filter(|(_, entry)| {
+                    range
+                        .consumer
+                        .as_ref()
+                        .is_none_or(|consumer| entry.consumer == *consumer)
+                })
+                .map(|(id, entry)| (id, entry, now.saturating_sub(entry.delivery_time)))
+                .filter(|(_, _, idle)| *idle >= min_idle)
+                .take(range.count)
+                .map(|(id, entry, idle)| {
+                    Value::Array(vec![
+                        id_reply(*id),
+                        Value::Str(Sds::from_str(&entry.consumer)),
+                        Value::Int(idle as i64),
+                        Value::Int(entry.delivery_count as i64),
+                    ])
+                })
+                .collect(),
+        ))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "XPENDING"
+    }
+}
+
+/// Команда XCLAIM — передаёт ожидающие подтверждения сообщения другому
+/// потребителю.
+///
+/// Формат: `XCLAIM key group consumer min-idle-time id [id ...] [IDLE ms]
+/// [TIME unix-time-ms] [RETRYCOUNT count] [FORCE] [JUSTID]`. Передаются
+/// только сообщения, простаивающие не меньше `min-idle-time`.
+#[derive(Debug)]
+pub struct XClaimCommand {
+    pub key: String,
+    pub group: String,
+    pub consumer: String,
+    pub min_idle: u64,
+    pub ids: Vec<StreamId>,
+    /// Простой, записываемый переданным сообщениям (`IDLE`).
+    pub idle: Option<u64>,
+    /// Время доставки, записываемое переданным сообщениям (`TIME`).
+    pub time: Option<u64>,
+    pub retry_count: Option<u64>,
+    pub force: bool,
+    pub just_id: bool,
+}
+
+impl XClaimCommand {
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let [group, consumer, min_idle, rest @ ..] = args else {
+            return Err(StoreError::Syntax("XCLAIM: missing argument".to_string()));
+        };
+        // Идентификаторы идут до первого аргумента, не являющегося ID.
+        let id_count = rest
+            .iter()
+            .take_while(|arg| arg.parse::<StreamId>().is_ok())
+            .count();
+        if id_count == 0 {
+            return Err(StoreError::Syntax("XCLAIM: missing ID".to_string()));
+        }
+        let (ids, mut options) = rest.split_at(id_count);
+
+        let mut cmd = Self {
+            key,
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            min_idle: parse_millis(min_idle)?,
+            ids: ids.iter().filter_map(|id| id.parse().ok()).collect(),
+            idle: None,
+            time: None,
+            retry_count: None,
+            force: false,
+            just_id: false,
+        };
+        while let Some((opt, tail)) = options.split_first() {
+            options = tail;
+            let option = opt.to_ascii_uppercase();
+            match option.as_str() {
+                "FORCE" => cmd.force = true,
+                "JUSTID" => cmd.just_id = true,
+                "IDLE" | "TIME" | "RETRYCOUNT" => {
+                    let (value, tail) = options.split_first().ok_or_else(|| {
+                        StoreError::Syntax(format!("XCLAIM: missing value for {option}"))
+                    })?;
+                    options = tail;
+                    let value = parse_millis(value)?;
+                    match option.as_str() {
+                        "IDLE" => cmd.idle = Some(value),
+                        "TIME" => cmd.time = Some(value),
+                        _ => cmd.retry_count = Some(value),
+                    }
+                }
+                _ => {
+                    return Err(StoreError::Syntax(format!(
+                        "XCLAIM: unexpected argument '{opt}'"
+                    )))
+                }
+            }
+        }
+        Ok(cmd)
+    }
+}
+
+impl CommandExecute for XClaimCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(mut stream) = get_stream(store, &self.key)? else {
+            return Err(no_group(&self.key, &self.group));
+        };
+        let now = Stream::current_millis();
+        let opts = ClaimOptions {
+            delivery_time: self
+                .time
+                .or_else(|| self.idle.map(|idle| now.saturating_sub(idle))),
+            retry_count: self.retry_count,
+            force: self.force,
+            just_id: self.just_id,
+        };
+        let claimed = stream
+            .claim(
+                &self.group,
+                &self.consumer,
+                &self.ids,
+                self.min_idle,
+                &opts,
+                now,
+            )
+            .ok_or_else(|| no_group(&self.key, &self.group))?;
+
+        let reply = claimed_reply(&stream, &claimed, self.just_id);
+        store.set(&Sds::from_str(&self.key), Value::SStream(stream))?;
+        Ok(reply)
+    }
+
+    fn command_name(&self) -> &'static str {
+        "XCLAIM"
+    }
+}
+
+/// Команда XAUTOCLAIM — передаёт потребителю простаивающие сообщения,
+/// просматривая PEL группы с заданного ID.
+///
+/// Формат: `XAUTOCLAIM key group consumer min-idle-time start [COUNT count]
+/// [JUSTID]`. Ответ — `[next-start, claimed, deleted-ids]`, где
+/// `next-start` равен `0-0`, если PEL просмотрен до конца.
+#[derive(Debug)]
+pub struct XAutoClaimCommand {
+    pub key: String,
+    pub group: String,
+    pub consumer: String,
+    pub min_idle: u64,
+    pub start: StreamId,
+    pub count: usize,
+    pub just_id: bool,
+}
+
+impl XAutoClaimCommand {
+    /// Число сообщений, передаваемых без `COUNT`, как в Redis.
+    pub const DEFAULT_COUNT: usize = 100;
+
+    /// Разбирает аргументы после ключа.
+    pub fn parse(
+        key: String,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let [group, consumer, min_idle, start, options @ ..] = args else {
+            return Err(StoreError::Syntax(
+                "XAUTOCLAIM: missing argument".to_string(),
+            ));
+        };
+        let start = match parse_range_bound(start, false)? {
+            Bound::Included(id) => id,
+            _ => {
+                return Err(StoreError::InvalidArgument(format!(
+                    "Invalid stream ID specified as stream command argument: '{start}'"
+                )))
+            }
+        };
+
+        let mut count = Self::DEFAULT_COUNT;
+        let mut just_id = false;
+        let mut options = options;
+        while let Some((opt, tail)) = options.split_first() {
+            options = tail;
+            if opt.eq_ignore_ascii_case("JUSTID") {
+                just_id = true;
+            } else if opt.eq_ignore_ascii_case("COUNT") {
+                let (value, tail) = options.split_first().ok_or_else(|| {
+                    StoreError::Syntax("XAUTOCLAIM: missing value for COUNT".to_string())
+                })?;
+                options = tail;
+                count = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&count| count > 0)
+                    .ok_or_else(|| StoreError::InvalidArgument("COUNT must be > 0".to_string()))?;
+            } else {
+                return Err(StoreError::Syntax(format!(
+                    "XAUTOCLAIM: unexpected argument '{opt}'"
+                )));
+            }
+        }
+
+        Ok(Self {
+            key,
+            group: group.to_string(),
+            consumer: consumer.to_string(),
+            min_idle: parse_millis(min_idle)?,
+            start,
+            count,
+            just_id,
+        })
+    }
+}
+
+impl CommandExecute for XAutoClaimCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(mut stream) = get_stream(store, &self.key)? else {
+            return Err(no_group(&self.key, &self.group));
+        };
+        let result = stream
+            .auto_claim(
+                &self.group,
+                &self.consumer,
+                self.min_idle,
+                self.start,
+                self.count,
+                self.just_id,
+                Stream::current_millis(),
+            )
+            .ok_or_else(|| no_group(&self.key, &self.group))?;
+
+        let reply = Value::Array(vec![
+            id_reply(result.next),
+            claimed_reply(&stream, &result.claimed, self.just_id),
+            Value::Array(result.deleted.into_iter().map(id_reply).collect()),
+        ]);
+        store.set(&Sds::from_str(&self.key), Value::SStream(stream))?;
+        Ok(reply)
+    }
+
+    fn command_name(&self) -> &'static str {
+        "XAUTOCLAIM"
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Возвращает поток по ключу `key`.
+///
+/// # Возвращает
+/// - `Ok(None)` — если ключа нет
+/// - `Err(StoreError::InvalidType)` — если по ключу лежит не поток
+fn get_stream(
+    store: &StorageEngine,
+    key: &str,
+) -> Result<Option<Stream>, StoreError> {
+    match store.get(&Sds::from_str(key))? {
+        Some(Value::SStream(stream)) => Ok(Some(stream)),
+        Some(_) => Err(StoreError::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Преобразует запись в ответ `[id, [field, value, ...]]`; поля
+/// упорядочены по имени.
+fn entry_reply(entry: &StreamEntry) -> Value {
+    let mut fields: Vec<_> = entry.data.iter().collect();
+    fields.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    Value::Array(vec![
+        id_reply(entry.id),
+        Value::Array(
+            fields
+                .into_iter()
+                .flat_map(|(field, value)| [Value::Str(Sds::from_str(field)), value.clone()])
+                .collect(),
+        ),
+    ])
+}
+
+/// Преобразует идентификатор в строковый ответ `ms-seq`.
+fn id_reply(id: StreamId) -> Value {
+    Value::Str(Sds::from_str(&id.to_string()))
+}
+
+/// Ошибка отсутствующего ключа или группы потребителей.
+fn no_group(
+    key: &str,
+    group: &str,
+) -> StoreError {
+    StoreError::InvalidArgument(format!(
+        "NOGROUP No such key '{key}' or consumer group '{group}'"
+    ))
+}
+
+/// Разбирает неотрицательное число миллисекунд (или счётчик).
+fn parse_millis(arg: &str) -> Result<u64, StoreError> {
+    arg.parse().map_err(|_| {
+        StoreError::InvalidArgument("value is not an integer or out of range".to_string())
+    })
+}
+
+/// Собирает ответ `XCLAIM`/`XAUTOCLAIM`: записи переданных сообщений или,
+/// с `just_id`, только их идентификаторы.
+fn claimed_reply(
+    stream: &Stream,
+    claimed: &[StreamId],
+    just_id: bool,
+) -> Value {
+    if just_id {
+        return Value::Array(claimed.iter().copied().map(id_reply).collect());
+    }
+    entries_reply(claimed.iter().filter_map(|id| stream.entry(*id)).collect())
+}
+
+/// Собирает ответ `XINFO` из пар «имя поля — значение» в плоский массив.
+fn info_reply(fields: Vec<(&str, Value)>) -> Value {
+    Value::Array(
+        fields
+            .into_iter()
+            .flat_map(|(name, value)| [Value::Str(Sds::from_str(name)), value])
+            .collect(),
+    )
+}
+
+/// Собирает ответ `XINFO STREAM FULL`: записи и группы вместе с PEL
+/// группы и каждого потребителя, не более `count` элементов в каждом
+/// списке.
+fn stream_full_reply(
+    stream: &Stream,
+    count: Option<usize>,
+) -> Value {
+    let limit = count.unwrap_or(usize::MAX);
+    let groups = stream
+        .groups()
+        .map(|(name, group)| {
+            let pending = group
+                .pel()
+                .iter()
+                .take(limit)
+                .map(|(id, entry)| {
+                    Value::Array(vec![
+                        id_reply(*id),
+                        Value::Str(Sds::from_str(&entry.consumer)),
+                        Value::Int(entry.delivery_time as i64),
+                        Value::Int(entry.delivery_count as i64),
+                    ])
+                })
+                .collect();
+            let consumers = group
+                .consumers()
+                .map(|(consumer, state)| {
+                    let owned: Vec<Value> = group
+                        .pel()
+                        .iter()
+                        .filter(|(_, entry)| entry.consumer == *consumer)
+                        .take(limit)
+                        .map(|(id, entry)| {
+                            Value::Array(vec![
+                                id_reply(*id),
+                                Value::Int(entry.delivery_time as i64),
+                                Value::Int(entry.delivery_count as i64),
+                            ])
+                        })
+                        .collect();
+                    info_reply(vec![
+                        ("name", Value::Str(Sds::from_str(consumer))),
+                        ("seen-time", Value::Int(state.seen_time as i64)),
+                        (
+                            "pel-count",
+                            Value::Int(group.pel().count_for(consumer) as i64),
+                        ),
+                        ("pending", Value::Array(owned)),
+                    ])
+                })
+                .collect();
+            info_reply(vec![
+                ("name", Value::Str(Sds::from_str(name))),
+                ("last-delivered-id", id_reply(group.last_delivered_id)),
+                ("pel-count", Value::Int(group.pel().len() as i64)),
+                ("pending", Value::Array(pending)),
+                ("consumers", Value::Array(consumers)),
+            ])
+        })
+        .collect();
+
+    info_reply(vec![
+        ("length", Value::Int(stream.len() as i64)),
+        ("last-generated-id", id_reply(stream.last_id())),
+        (
+            "entries",
+            entries_reply(stream.iter().take(limit).collect()),
+        ),
+        ("groups", Value::Array(groups)),
+    ])
+}
+
+/// Преобразует записи в массив ответов [`entry_reply`].
+fn entries_reply(entries: Vec<&StreamEntry>) -> Value {
+    Value::Array(entries.into_iter().map(entry_reply).collect())
+}
+
+/// Разбирает `first second [COUNT count]` у `XRANGE`/`XREVRANGE`.
+fn parse_range_args<'a>(
+    cmd: &str,
+    args: &[&'a str],
+) -> Result<(&'a str, &'a str, Option<usize>), StoreError> {
+    match args {
+        [first, second] => Ok((first, second, None)),
+        [first, second, opt, count] if opt.eq_ignore_ascii_case("COUNT") => {
+            let count = count.parse::<usize>().map_err(|_| {
+                StoreError::InvalidArgument("value is not an integer or out of range".to_string())
+            })?;
+            Ok((first, second, Some(count)))
+        }
+        _ => Err(StoreError::Syntax(format!(
+            "{cmd} expects start end [COUNT count]"
+        ))),
+    }
+}
+
+/// Разбирает границу диапазона: `-`, `+`, `id` или `(id`.
+///
+/// У конечной границы (`is_end`) ID без sequence охватывает всю
+/// миллисекунду (`ms-<max>`), у начальной — начинается с `ms-0`.
+fn parse_range_bound(
+    arg: &str,
+    is_end: bool,
+) -> Result<Bound<StreamId>, StoreError> {
+    match arg {
+        "-" => return Ok(Bound::Included(StreamId::MIN)),
+        "+" => return Ok(Bound::Included(StreamId::MAX)),
+        _ => {}
+    }
+    let (exclusive, id) = match arg.strip_prefix('(') {
+        Some(id) => (true, id),
+        None => (false, arg),
+    };
+    let mut parsed: StreamId = id.parse().map_err(StoreError::InvalidArgument)?;
+    if is_end && !id.contains('-') {
+        parsed.sequence = u64::MAX;
+    }
+    Ok(if exclusive {
+        Bound::Excluded(parsed)
+    } else {
+        Bound::Included(parsed)
+    })
+}
+
+/// Разбирает `MAXLEN|MINID [=|~] threshold [LIMIT count]` в начале `args`.
+///
+/// # Возвращает
+/// - параметры обрезки и оставшиеся аргументы
+fn parse_stream_trim<'a, 'b>(
+    cmd: &str,
+    args: &'a [&'b str],
+) -> Result<(StreamTrim, &'a [&'b str]), StoreError> {
+    let missing = || StoreError::Syntax(format!("{cmd}: missing argument"));
+    let (kind, mut rest) = args.split_first().ok_or_else(missing)?;
+
+    let mut approx = false;
+    if let Some((&op @ ("=" | "~"), tail)) = rest.split_first() {
+        approx = op == "~";
+        rest = tail;
+    }
+
+    let (threshold, mut rest) = rest.split_first().ok_or_else(missing)?;
+    let strategy = match kind.to_ascii_uppercase().as_str() {
+        "MAXLEN" => StreamTrimStrategy::MaxLen(threshold.parse().map_err(|_| {
+            StoreError::InvalidArgument("The MAXLEN argument must be >= 0".to_string())
+        })?),
+        "MINID" => {
+            StreamTrimStrategy::MinId(threshold.parse().map_err(StoreError::InvalidArgument)?)
+        }
+        other => {
+            return Err(StoreError::Syntax(format!(
+                "{cmd}: unexpected argument '{other}'"
+            )))
+        }
+    };
+
+    let mut limit = None;
+    if let Some((arg, tail)) = rest.split_first() {
+        if arg.eq_ignore_ascii_case("LIMIT") {
+            if !approx {
+                return Err(StoreError::Syntax(
+                    "syntax error, LIMIT cannot be used without the special ~ option".to_string(),
+                ));
+            }
+            let (count, tail) = tail.split_first().ok_or_else(missing)?;
+            let count: usize = count.parse().map_err(|_| {
+                StoreError::InvalidArgument("The LIMIT argument must be >= 0".to_string())
+            })?;
+            // `LIMIT 0` снимает ограничение, как в Redis.
+            limit = (count > 0).then_some(count);
+            rest = tail;
+        }
+    }
+
+    Ok((
+        StreamTrim {
+            strategy,
+            approx,
+            limit,
+        },
+        rest,
+    ))
+}
+
+/// Разбирает идентификатор `XADD`: `*`, `ms-*` или `ms-seq`.
+fn parse_xadd_id(id: &str) -> Result<XAddId, StoreError> {
+    if id == "*" {
+        return Ok(XAddId::Auto);
+    }
+    if let Some(ms) = id.strip_suffix("-*") {
+        return ms.parse().map(XAddId::AutoSeq).map_err(|_| {
+            StoreError::InvalidArgument(format!(
+                "Invalid stream ID specified as stream command argument: '{id}'"
+            ))
+        });
+    }
+    id.parse()
+        .map(XAddId::Explicit)
+        .map_err(StoreError::InvalidArgument)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::persistent::PersistentStoreConfig, InMemoryStore, InPersistentStore};
+
+    // Вспомогательная функция для создания нового хранилища в памяти.
+    fn create_store() -> StorageEngine {
+        StorageEngine::Memory(InMemoryStore::new())
+    }
+
+    /// Выполняет `XADD key args...` и возвращает результат.
+    fn xadd(
+        store: &mut StorageEngine,
+        key: &str,
+        args: &[&str],
+    ) -> Result<Value, StoreError> {
+        XAddCommand::parse(key.to_string(), args)?.execute(store)
+    }
+
+    /// Возвращает поток по ключу.
+    fn stream(
+        store: &mut StorageEngine,
+        key: &str,
+    ) -> Stream {
+        match store.get(&Sds::from_str(key)).unwrap() {
+            Some(Value::SStream(stream)) => stream,
+            other => panic!("expected stream, got {other:?}"),
+        }
+    }
+
+    /// Тест проверяет, что явный ID должен быть больше последнего, а `ms-*`
+    /// и `*` генерируют возрастающие идентификаторы.
+    #[test]
+    fn test_xadd_explicit_and_generated_ids() {
+        let mut store = create_store();
+
+        assert_eq!(
+            xadd(&mut store, "s", &["5-1", "f", "v"]).unwrap(),
+            Value::Str(Sds::from_str("5-1"))
+        );
+        assert_eq!(
+            xadd(&mut store, "s", &["5-*", "f", "v"]).unwrap(),
+            Value::Str(Sds::from_str("5-2"))
+        );
+        assert_eq!(
+            xadd(&mut store, "s", &["7", "f", "v"]).unwrap(),
+            Value::Str(Sds::from_str("7-0"))
+        );
+        for id in ["7-0", "6-9", "5-*"] {
+            assert!(
+                matches!(
+                    xadd(&mut store, "s", &[id, "f", "v"]),
+                    Err(StoreError::InvalidArgument(_))
+                ),
+                "{id}"
+            );
+        }
+
+        let Value::Str(auto) = xadd(&mut store, "s", &["*", "a", "1", "b", "2"]).unwrap() else {
+            panic!("expected ID");
+        };
+        let auto: StreamId = auto.as_str().unwrap().parse().unwrap();
+        assert!(auto > StreamId::new(7, 0));
+
+        let s = stream(&mut store, "s");
+        assert_eq!(s.len(), 4);
+        let last = s.last_entry().unwrap();
+        assert_eq!(last.id, auto);
+        assert_eq!(last.data.get("b"), Some(&Value::Str(Sds::from_str("2"))));
+    }
+
+    /// Тест проверяет `0-0`, `NOMKSTREAM` и ошибку типа.
+    #[test]
+    fn test_xadd_nomkstream_and_errors() {
+        let mut store = create_store();
+
+        assert!(xadd(&mut store, "s", &["0-0", "f", "v"]).is_err());
+        assert_eq!(
+            xadd(&mut store, "s", &["NOMKSTREAM", "*", "f", "v"]).unwrap(),
+            Value::Null
+        );
+        assert_eq!(store.get(&Sds::from_str("s")).unwrap(), None);
+
+        xadd(&mut store, "s", &["1-1", "f", "v"]).unwrap();
+        assert_eq!(
+            xadd(&mut store, "s", &["nomkstream", "2-1", "f", "v"]).unwrap(),
+            Value::Str(Sds::from_str("2-1"))
+        );
+
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+        assert!(matches!(
+            xadd(&mut store, "str", &["*", "f", "v"]),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    /// Тест проверяет разбор аргументов `XADD`.
+    #[test]
+    fn test_xadd_parse() {
+        let cmd = XAddCommand::parse(
+            "s".into(),
+            &[
+                "NOMKSTREAM",
+                "MAXLEN",
+                "~",
+                "1000",
+                "LIMIT",
+                "10",
+                "*",
+                "f",
+                "v",
+            ],
+        )
+        .unwrap();
+        assert!(cmd.nomkstream);
+        assert_eq!(cmd.id, XAddId::Auto);
+        assert_eq!(cmd.fields, [("f".to_string(), "v".to_string())]);
+        assert_eq!(
+            cmd.trim,
+            Some(StreamTrim {
+                strategy: StreamTrimStrategy::MaxLen(1000),
+                approx: true,
+                limit: Some(10),
+            })
+        );
+
+        let cmd = XAddCommand::parse("s".into(), &["MINID", "=", "12", "12-*", "f", "v"]).unwrap();
+        assert_eq!(cmd.id, XAddId::AutoSeq(12));
+        assert_eq!(
+            cmd.trim,
+            Some(StreamTrim::new(StreamTrimStrategy::MinId(StreamId::new(
+                12, 0
+            ))))
+        );
+
+        for args in [
+            &["*"][..],
+            &["*", "f"],
+            &["*", "f", "v", "g"],
+            &["MAXLEN", "=", "10", "LIMIT", "5", "*", "f", "v"],
+            &["MAXLEN", "-1", "*", "f", "v"],
+            &["MINID", "x", "*", "f", "v"],
+            &["1-x", "f", "v"],
+            &["x-*", "f", "v"],
+        ] {
+            assert!(XAddCommand::parse("s".into(), args).is_err(), "{args:?}");
+        }
+    }
+
+    /// Тест проверяет обрезку потока в `XADD` по `MAXLEN` и `MINID`.
+    #[test]
+    fn test_xadd_trims_stream() {
+        let mut store = create_store();
+        for i in 1..=5 {
+            let id = format!("{i}-0");
+            xadd(&mut store, "s", &["MAXLEN", "3", &id, "f", "v"]).unwrap();
+        }
+        let ids: Vec<_> = stream(&mut store, "s").iter().map(|e| e.id).collect();
+        assert_eq!(
+            ids,
+            [
+                StreamId::new(3, 0),
+                StreamId::new(4, 0),
+                StreamId::new(5, 0)
+            ]
+        );
+
+        // Приближённая обрезка оставляет записи, не набравшие целого узла.
+        xadd(&mut store, "s", &["MAXLEN", "~", "1", "6-0", "f", "v"]).unwrap();
+        assert_eq!(stream(&mut store, "s").len(), 4);
+
+        xadd(&mut store, "s", &["MINID", "5", "7-0", "f", "v"]).unwrap();
+        let s = stream(&mut store, "s");
+        assert_eq!(s.first_entry().unwrap().id, StreamId::new(5, 0));
+        assert_eq!(s.len(), 3);
+    }
+
+    /// Заполняет поток `key` записями `1-0 .. n-0` с полем `n`.
+    fn fill(
+        store: &mut StorageEngine,
+        key: &str,
+        n: u64,
+    ) {
+        for i in 1..=n {
+            let id = format!("{i}-0");
+            xadd(store, key, &[&id, "n", &i.to_string()]).unwrap();
+        }
+    }
+
+    /// Извлекает ID из ответа `XRANGE`/`XREVRANGE`.
+    fn reply_ids(reply: Value) -> Vec<String> {
+        let Value::Array(entries) = reply else {
+            panic!("expected array, got {reply:?}");
+        };
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                Value::Array(mut pair) => match pair.swap_remove(0) {
+                    Value::Str(id) => id.as_str().unwrap().to_string(),
+                    other => panic!("expected ID, got {other:?}"),
+                },
+                other => panic!("expected entry, got {other:?}"),
+            })
+            .collect()
+    }
+
+    /// Тест проверяет `XRANGE` со специальными, исключающими и неполными
+    /// границами, а также `COUNT`.
+    #[test]
+    fn test_xrange() {
+        let mut store = create_store();
+        fill(&mut store, "s", 5);
+        let xrange = |store: &mut StorageEngine, args: &[&str]| {
+            reply_ids(
+                XRangeCommand::parse("s".into(), args)
+                    .unwrap()
+                    .execute(store)
+                    .unwrap(),
+            )
+        };
+
+        assert_eq!(xrange(&mut store, &["-", "+"]).len(), 5);
+        assert_eq!(xrange(&mut store, &["(2-0", "(5-0"]), ["3-0", "4-0"]);
+        assert_eq!(xrange(&mut store, &["2", "3"]), ["2-0", "3-0"]);
+        assert_eq!(xrange(&mut store, &["(3", "+"]), ["4-0", "5-0"]);
+        assert_eq!(
+            xrange(&mut store, &["-", "+", "COUNT", "2"]),
+            ["1-0", "2-0"]
+        );
+        assert!(xrange(&mut store, &["-", "+", "count", "0"]).is_empty());
+        assert!(xrange(&mut store, &["4", "2"]).is_empty());
+
+        let reply = XRangeCommand::parse("s".into(), &["1-0", "1-0"])
+            .unwrap()
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(
+            reply,
+            Value::Array(vec![Value::Array(vec![
+                Value::Str(Sds::from_str("1-0")),
+                Value::Array(vec![
+                    Value::Str(Sds::from_str("n")),
+                    Value::Str(Sds::from_str("1")),
+                ]),
+            ])])
+        );
+    }
+
+    /// Тест проверяет `XREVRANGE`: порядок от `end` к `start` и `COUNT`.
+    #[test]
+    fn test_xrevrange() {
+        let mut store = create_store();
+        fill(&mut store, "s", 5);
+        let xrevrange = |store: &mut StorageEngine, args: &[&str]| {
+            reply_ids(
+                XRevRangeCommand::parse("s".into(), args)
+                    .unwrap()
+                    .execute(store)
+                    .unwrap(),
+            )
+        };
+
+        assert_eq!(
+            xrevrange(&mut store, &["+", "-", "COUNT", "3"]),
+            ["5-0", "4-0", "3-0"]
+        );
+        assert_eq!(xrevrange(&mut store, &["(4-0", "(1"]), ["3-0", "2-0"]);
+        assert!(xrevrange(&mut store, &["-", "+"]).is_empty());
+    }
+
+    /// Тест проверяет `XLEN` и диапазоны на отсутствующем и пустом потоке,
+    /// а также ошибки разбора и типа.
+    #[test]
+    fn test_xlen_and_empty_streams() {
+        let mut store = create_store();
+        let xlen = |store: &mut StorageEngine| XLenCommand { key: "s".into() }.execute(store);
+        let range = XRangeCommand::parse("s".into(), &["-", "+"]).unwrap();
+
+        assert_eq!(xlen(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(range.execute(&mut store).unwrap(), Value::Array(vec![]));
+
+        fill(&mut store, "s", 3);
+        assert_eq!(xlen(&mut store).unwrap(), Value::Int(3));
+
+        // Поток, обрезанный до нуля записей, остаётся пустым потоком.
+        xadd(&mut store, "s", &["MAXLEN", "0", "4-0", "f", "v"]).unwrap();
+        assert_eq!(xlen(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(range.execute(&mut store).unwrap(), Value::Array(vec![]));
+
+        store
+            .set(&Sds::from_str("s"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+        assert!(matches!(xlen(&mut store), Err(StoreError::InvalidType)));
+        assert!(matches!(
+            range.execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+
+        for args in [
+            &["-"][..],
+            &["-", "+", "COUNT"],
+            &["-", "+", "COUNT", "-1"],
+            &["-", "+", "LIMIT", "1"],
+            &["(-", "+"],
+            &["x", "+"],
+        ] {
+            assert!(XRangeCommand::parse("s".into(), args).is_err(), "{args:?}");
+        }
+    }
+
+    /// Выполняет `XREAD args...` без ожидания.
+    fn xread(
+        store: &mut StorageEngine,
+        args: &[&str],
+    ) -> Value {
+        XReadCommand::parse(args).unwrap().execute(store).unwrap()
+    }
+
+    /// Извлекает из ответа `XREAD` пары (ключ, ID записей).
+    fn xread_ids(reply: Value) -> Vec<(String, Vec<String>)> {
+        let Value::Array(streams) = reply else {
+            panic!("expected array, got {reply:?}");
+        };
+        streams
+            .into_iter()
+            .map(|pair| match pair {
+                Value::Array(mut pair) => {
+                    let entries = reply_ids(pair.pop().unwrap());
+                    match pair.pop() {
+                        Some(Value::Str(key)) => (key.as_str().unwrap().to_string(), entries),
+                        other => panic!("expected key, got {other:?}"),
+                    }
+                }
+                other => panic!("expected pair, got {other:?}"),
+            })
+            .collect()
+    }
+
+    /// Тест проверяет разбор аргументов `XREAD`.
+    #[test]
+    fn test_xread_parse() {
+        let cmd = XReadCommand::parse(&[
+            "COUNT", "2", "block", "150", "STREAMS", "a", "b", "1-1", "$",
+        ])
+        .unwrap();
+        assert_eq!(cmd.count, Some(2));
+        assert_eq!(cmd.block, Some(Duration::from_millis(150)));
+        assert_eq!(
+            cmd.streams,
+            [
+                ("a".to_string(), XReadId::After(StreamId::new(1, 1))),
+                ("b".to_string(), XReadId::New),
+            ]
+        );
+        assert_eq!(
+            XReadCommand::parse(&["COUNT", "0", "STREAMS", "a", "0"])
+                .unwrap()
+                .count,
+            None
+        );
+
+        for args in [
+            &["STREAMS"][..],
+            &["STREAMS", "a"],
+            &["STREAMS", "a", "b", "0"],
+            &["COUNT", "STREAMS", "a", "0"],
+            &["BLOCK", "-1", "STREAMS", "a", "0"],
+            &["LIMIT", "1", "STREAMS", "a", "0"],
+            &["STREAMS", "a", "x"],
+            &["a", "0"],
+        ] {
+            assert!(XReadCommand::parse(args).is_err(), "{args:?}");
+        }
+    }
+
+    /// Тест проверяет чтение без ожидания из нескольких потоков: `COUNT`
+    /// на каждый поток, пропуск пустых и отсутствующих потоков и `nil`,
+    /// когда читать нечего.
+    #[test]
+    fn test_xread_non_blocking() {
+        let mut store = create_store();
+        fill(&mut store, "a", 3);
+        fill(&mut store, "b", 2);
+
+        assert_eq!(
+            xread_ids(xread(
+                &mut store,
+                &["COUNT", "2", "STREAMS", "a", "b", "missing", "1-0", "0", "0"]
+            )),
+            [
+                ("a".to_string(), vec!["2-0".to_string(), "3-0".to_string()]),
+                ("b".to_string(), vec!["1-0".to_string(), "2-0".to_string()]),
+            ]
+        );
+        assert_eq!(
+            xread_ids(xread(&mut store, &["STREAMS", "a", "b", "3-0", "1"])),
+            [("b".to_string(), vec!["2-0".to_string()])]
+        );
+        assert_eq!(
+            xread(&mut store, &["STREAMS", "a", "b", "$", "$"]),
+            Value::Null
+        );
+        // Без ожидания `BLOCK` в синхронном `execute` не действует.
+        assert_eq!(
+            xread(&mut store, &["BLOCK", "0", "STREAMS", "a", "$"]),
+            Value::Null
+        );
+    }
+
+    /// Тест проверяет, что `XREAD BLOCK` просыпается по `XADD` и получает
+    /// только новую запись.
+    #[tokio::test(start_paused = true)]
+    async fn test_xread_block_wakes_on_xadd() {
+        let store = create_store();
+        let StorageEngine::Memory(memory) = &store else {
+            unreachable!()
+        };
+        let mut writer = StorageEngine::Memory(memory.select(0).unwrap());
+        fill(&mut writer, "s", 2);
+
+        let cmd = XReadCommand::parse(&["BLOCK", "0", "STREAMS", "s", "$"]).unwrap();
+        let started = Instant::now();
+        let (reply, _) = tokio::join!(cmd.execute_blocking(&store), async {
+            time::sleep(Duration::from_millis(30)).await;
+            xadd(&mut writer, "s", &["9-0", "f", "v"]).unwrap();
+        });
+
+        assert_eq!(
+            xread_ids(reply.unwrap()),
+            [("s".to_string(), vec!["9-0".to_string()])]
+        );
+        // Уведомление будит раньше очередной проверки по таймеру.
+        assert!(started.elapsed() < STREAM_BLOCK_POLL_INTERVAL);
+    }
+
+    /// Тест проверяет, что `XADD` будит `XREAD BLOCK` в персистентном
+    /// хранилище, которое восстанавливает поток из байтов при каждом чтении,
+    /// и что реестр уведомлений очищается после ответа.
+    #[tokio::test(start_paused = true)]
+    async fn test_xread_block_wakes_on_xadd_persistent() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistent = InPersistentStore::new(
+            dir.path().join("store.aof"),
+            PersistentStoreConfig::default(),
+        )
+        .unwrap();
+        let store = StorageEngine::Persistent(persistent.clone());
+        let mut writer = StorageEngine::Persistent(persistent);
+        fill(&mut writer, "s", 2);
+
+        let cmd = XReadCommand::parse(&["BLOCK", "0", "STREAMS", "s", "$"]).unwrap();
+        let started = Instant::now();
+        let (reply, _) = tokio::join!(cmd.execute_blocking(&store), async {
+            time::sleep(Duration::from_millis(30)).await;
+            xadd(&mut writer, "s", &["9-0", "f", "v"]).unwrap();
+        });
+
+        assert_eq!(
+            xread_ids(reply.unwrap()),
+            [("s".to_string(), vec!["9-0".to_string()])]
+        );
+        assert!(started.elapsed() < STREAM_BLOCK_POLL_INTERVAL);
+        assert!(store.stream_notifiers().is_empty());
+    }
+
+    /// Тест проверяет, что `XREAD BLOCK` замечает поток, созданный во время
+    /// ожидания, и возвращает `nil` по истечении времени.
+    #[tokio::test(start_paused = true)]
+    async fn test_xread_block_new_stream_and_timeout() {
+        let store = create_store();
+        let StorageEngine::Memory(memory) = &store else {
+            unreachable!()
+        };
+        let mut writer = StorageEngine::Memory(memory.select(0).unwrap());
+
+        let cmd = XReadCommand::parse(&["BLOCK", "1000", "STREAMS", "s", "$"]).unwrap();
+        let (reply, _) = tokio::join!(cmd.execute_blocking(&store), async {
+            time::sleep(Duration::from_millis(250)).await;
+            xadd(&mut writer, "s", &["1-0", "f", "v"]).unwrap();
+        });
+        assert_eq!(
+            xread_ids(reply.unwrap()),
+            [("s".to_string(), vec!["1-0".to_string()])]
+        );
+
+        let started = Instant::now();
+        assert_eq!(cmd.execute_blocking(&store).await.unwrap(), Value::Null);
+        assert_eq!(started.elapsed(), Duration::from_millis(1000));
+
+        // Без `BLOCK` ответ возвращается сразу.
+        let cmd = XReadCommand::parse(&["STREAMS", "s", "0"]).unwrap();
+        assert_eq!(
+            xread_ids(cmd.execute_blocking(&store).await.unwrap()),
+            [("s".to_string(), vec!["1-0".to_string()])]
+        );
+    }
+
+    /// Выдаёт записи группы потребителю, как это сделал бы `XREADGROUP`.
+    fn read_group(
+        store: &mut StorageEngine,
+        key: &str,
+        group: &str,
+        consumer: &str,
+        count: Option<usize>,
+    ) {
+        let mut s = stream(store, key);
+        s.read_group(group, consumer, count, Stream::current_millis())
+            .unwrap();
+        store.set(&Sds::from_str(key), Value::SStream(s)).unwrap();
+    }
+
+    /// Выполняет `XINFO args...` и возвращает результат.
+    fn xinfo(
+        store: &mut StorageEngine,
+        args: &[&str],
+    ) -> Result<Value, StoreError> {
+        XInfoCommand::parse(args)?.execute(store)
+    }
+
+    /// Возвращает значение поля `name` из плоского ответа `XINFO`.
+    fn info_field<'a>(
+        reply: &'a Value,
+        name: &str,
+    ) -> &'a Value {
+        let Value::Array(items) = reply else {
+            panic!("expected array, got {reply:?}");
+        };
+        items
+            .chunks(2)
+            .find(|pair| pair[0] == Value::Str(Sds::from_str(name)))
+            .map(|pair| &pair[1])
+            .unwrap_or_else(|| panic!("no field {name} in {reply:?}"))
+    }
+
+    /// Тест проверяет `XGROUP CREATE`: `$`, `MKSTREAM` и ошибки.
+    #[test]
+    fn test_xgroup_create() {
+        let mut store = create_store();
+        let create = |store: &mut StorageEngine, args: &[&str]| {
+            XGroupCreateCommand::parse("s".into(), args)?.execute(store)
+        };
+
+        assert!(matches!(
+            create(&mut store, &["g", "$"]),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            create(&mut store, &["g", "$", "MKSTREAM"]).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert!(stream(&mut store, "s").is_empty());
+        assert!(create(&mut store, &["g", "0"]).is_err());
+
+        fill(&mut store, "s", 3);
+        create(&mut store, &["tail", "$"]).unwrap();
+        create(&mut store, &["head", "0"]).unwrap();
+        let s = stream(&mut store, "s");
+        assert_eq!(s.group("tail").unwrap().last_delivered_id, s.last_id());
+        assert_eq!(s.group("head").unwrap().last_delivered_id, StreamId::MIN);
+
+        assert!(XGroupCreateCommand::parse("s".into(), &["g"]).is_err());
+        assert!(XGroupCreateCommand::parse("s".into(), &["g", "x"]).is_err());
+    }
+
+    /// Тест проверяет `XINFO STREAM`, `XINFO GROUPS` и `XINFO CONSUMERS`.
+    #[test]
+    fn test_xinfo_stream_groups_consumers() {
+        let mut store = create_store();
+        assert!(matches!(
+            xinfo(&mut store, &["STREAM", "s"]),
+            Err(StoreError::KeyNotFound)
+        ));
+
+        fill(&mut store, "s", 3);
+        XGroupCreateCommand::parse("s".into(), &["g", "0"])
+            .unwrap()
+            .execute(&mut store)
+            .unwrap();
+        read_group(&mut store, "s", "g", "alice", Some(2));
+
+        let reply = xinfo(&mut store, &["stream", "s"]).unwrap();
+        assert_eq!(info_field(&reply, "length"), &Value::Int(3));
+        assert_eq!(info_field(&reply, "groups"), &Value::Int(1));
+        assert_eq!(
+            info_field(&reply, "last-generated-id"),
+            &Value::Str(Sds::from_str("3-0"))
+        );
+        let Value::Array(first) = info_field(&reply, "first-entry") else {
+            panic!("expected entry");
+        };
+        assert_eq!(first[0], Value::Str(Sds::from_str("1-0")));
+
+        let Value::Array(groups) = xinfo(&mut store, &["GROUPS", "s"]).unwrap() else {
+            panic!("expected array");
+        };
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            info_field(&groups[0], "name"),
+            &Value::Str(Sds::from_str("g"))
+        );
+        assert_eq!(info_field(&groups[0], "consumers"), &Value::Int(1));
+        assert_eq!(info_field(&groups[0], "pending"), &Value::Int(2));
+        assert_eq!(
+            info_field(&groups[0], "last-delivered-id"),
+            &Value::Str(Sds::from_str("2-0"))
+        );
+
+        let Value::Array(consumers) = xinfo(&mut store, &["CONSUMERS", "s", "g"]).unwrap() else {
+            panic!("expected array");
+        };
+        assert_eq!(consumers.len(), 1);
+        assert_eq!(
+            info_field(&consumers[0], "name"),
+            &Value::Str(Sds::from_str("alice"))
+        );
+        assert_eq!(info_field(&consumers[0], "pending"), &Value::Int(2));
+        assert!(matches!(
+            info_field(&consumers[0], "idle"),
+            Value::Int(idle) if *idle >= 0
+        ));
+        assert!(matches!(
+            xinfo(&mut store, &["CONSUMERS", "s", "missing"]),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    /// Тест проверяет `XINFO STREAM FULL`: записи, PEL группы и
+    /// потребителей, ограничение `COUNT`.
+    #[test]
+    fn test_xinfo_stream_full() {
+        let mut store = create_store();
+        fill(&mut store, "s", 12);
+        XGroupCreateCommand::parse("s".into(), &["g", "0"])
+            .unwrap()
+            .execute(&mut store)
+            .unwrap();
+        read_group(&mut store, "s", "g", "alice", Some(2));
+        read_group(&mut store, "s", "g", "bob", Some(1));
+
+        let len = |value: &Value| match value {
+            Value::Array(items) => items.len(),
+            other => panic!("expected array, got {other:?}"),
+        };
+
+        let reply = xinfo(&mut store, &["STREAM", "s", "FULL"]).unwrap();
+        assert_eq!(len(info_field(&reply, "entries")), 10);
+        let Value::Array(groups) = info_field(&reply, "groups") else {
+            panic!("expected array");
+        };
+        let group = &groups[0];
+        assert_eq!(info_field(group, "pel-count"), &Value::Int(3));
+        let Value::Array(pending) = info_field(group, "pending") else {
+            panic!("expected array");
+        };
+        let Value::Array(first) = &pending[0] else {
+            panic!("expected array");
+        };
+        assert_eq!(first[0], Value::Str(Sds::from_str("1-0")));
+        assert_eq!(first[1], Value::Str(Sds::from_str("alice")));
+        assert_eq!(first[3], Value::Int(1));
+        let Value::Array(consumers) = info_field(group, "consumers") else {
+            panic!("expected array");
+        };
+        assert_eq!(consumers.len(), 2);
+        assert_eq!(info_field(&consumers[0], "pel-count"), &Value::Int(2));
+        assert_eq!(len(info_field(&consumers[1], "pending")), 1);
+
+        let reply = xinfo(&mut store, &["STREAM", "s", "FULL", "COUNT", "0"]).unwrap();
+        assert_eq!(len(info_field(&reply, "entries")), 12);
+        let reply = xinfo(&mut store, &["STREAM", "s", "FULL", "COUNT", "1"]).unwrap();
+        assert_eq!(len(info_field(&reply, "entries")), 1);
+        let Value::Array(groups) = info_field(&reply, "groups") else {
+            panic!("expected array");
+        };
+        assert_eq!(len(info_field(&groups[0], "pending")), 1);
+
+        for args in [
+            &["STREAM"][..],
+            &["GROUPS", "s", "x"],
+            &["CONSUMERS", "s"],
+            &["STREAM", "s", "FULL", "COUNT"],
+            &["HELP2", "s"],
+        ] {
+            assert!(XInfoCommand::parse(args).is_err(), "{args:?}");
+        }
+    }
+
+    /// Создаёт группу `g` с начала потока и выдаёт `alice` две записи, а
+    /// `bob` — одну.
+    fn setup_pending(store: &mut StorageEngine) {
+        fill(store, "s", 4);
+        XGroupCreateCommand::parse("s".into(), &["g", "0"])
+            .unwrap()
+            .execute(store)
+            .unwrap();
+        read_group(store, "s", "g", "alice", Some(2));
+        read_group(store, "s", "g", "bob", Some(1));
+    }
+
+    /// Извлекает ID из ответа `XCLAIM` с `JUSTID`.
+    fn just_ids(reply: &Value) -> Vec<String> {
+        let Value::Array(ids) = reply else {
+            panic!("expected array, got {reply:?}");
+        };
+        ids.iter()
+            .map(|id| match id {
+                Value::Str(id) => id.as_str().unwrap().to_string(),
+                other => panic!("expected ID, got {other:?}"),
+            })
+            .collect()
+    }
+
+    /// Тест проверяет сводную и расширенную формы `XPENDING`.
+    #[test]
+    fn test_xpending() {
+        let mut store = create_store();
+        let xpending = |store: &mut StorageEngine, args: &[&str]| {
+            XPendingCommand::parse("s".into(), args)?.execute(store)
+        };
+        assert!(matches!(
+            xpending(&mut store, &["g"]),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        setup_pending(&mut store);
+        assert_eq!(
+            xpending(&mut store, &["g"]).unwrap(),
+            Value::Array(vec![
+                Value::Int(3),
+                Value::Str(Sds::from_str("1-0")),
+                Value::Str(Sds::from_str("3-0")),
+                Value::Array(vec![
+                    Value::Array(vec![
+                        Value::Str(Sds::from_str("alice")),
+                        Value::Str(Sds::from_str("2")),
+                    ]),
+                    Value::Array(vec![
+                        Value::Str(Sds::from_str("bob")),
+                        Value::Str(Sds::from_str("1")),
+                    ]),
+                ]),
+            ])
+        );
+
+        let Value::Array(entries) = xpending(&mut store, &["g", "-", "+", "10"]).unwrap() else {
+            panic!("expected array");
+        };
+        assert_eq!(entries.len(), 3);
+        let Value::Array(first) = &entries[0] else {
+            panic!("expected array");
+        };
+        assert_eq!(first[0], Value::Str(Sds::from_str("1-0")));
+        assert_eq!(first[1], Value::Str(Sds::from_str("alice")));
+        assert_eq!(first[3], Value::Int(1));
+
+        let count = |reply: Value| match reply {
+            Value::Array(items) => items.len(),
+            other => panic!("expected array, got {other:?}"),
+        };
+        assert_eq!(
+            count(xpending(&mut store, &["g", "-", "+", "1"]).unwrap()),
+            1
+        );
+        assert_eq!(
+            count(xpending(&mut store, &["g", "(1-0", "+", "10", "alice"]).unwrap()),
+            1
+        );
+        assert_eq!(
+            count(xpending(&mut store, &["g", "IDLE", "3600000", "-", "+", "10"]).unwrap()),
+            0
+        );
+
+        XGroupCreateCommand::parse("s".into(), &["empty", "$"])
+            .unwrap()
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(
+            xpending(&mut store, &["empty"]).unwrap(),
+            Value::Array(vec![Value::Int(0), Value::Null, Value::Null, Value::Null])
+        );
+
+        for args in [
+            &[][..],
+            &["g", "-", "+"],
+            &["g", "-", "+", "x"],
+            &["g", "IDLE"],
+        ] {
+            assert!(
+                XPendingCommand::parse("s".into(), args).is_err(),
+                "{args:?}"
+            );
+        }
+    }
+
+    /// Тест проверяет `XCLAIM`: порог простоя, `FORCE`, `RETRYCOUNT`,
+    /// `TIME` и `JUSTID`.
+    #[test]
+    fn test_xclaim() {
+        let mut store = create_store();
+        setup_pending(&mut store);
+        let xclaim = |store: &mut StorageEngine, args: &[&str]| {
+            XClaimCommand::parse("s".into(), args)?.execute(store)
+        };
+
+        // Сообщения только что выданы — час простоя ещё не прошёл.
+        assert_eq!(
+            xclaim(&mut store, &["g", "carol", "3600000", "1-0"]).unwrap(),
+            Value::Array(vec![])
+        );
+
+        let reply = xclaim(&mut store, &["g", "carol", "0", "1-0", "4-0"]).unwrap();
+        assert_eq!(reply_ids(reply), ["1-0"]);
+        let s = stream(&mut store, "s");
+        let pending = s
+            .group("g")
+            .unwrap()
+            .pel()
+            .get(&StreamId::new(1, 0))
+            .unwrap();
+        assert_eq!(pending.consumer, "carol");
+        assert_eq!(pending.delivery_count, 2);
+
+        let reply = xclaim(
+            &mut store,
+            &[
+                "g",
+                "dave",
+                "0",
+                "4-0",
+                "2-0",
+                "FORCE",
+                "RETRYCOUNT",
+                "5",
+                "TIME",
+                "1000",
+                "JUSTID",
+            ],
+        )
+        .unwrap();
+        assert_eq!(just_ids(&reply), ["4-0", "2-0"]);
+        let s = stream(&mut store, "s");
+        let pel = s.group("g").unwrap().pel();
+        assert_eq!(pel.len(), 4);
+        for id in [StreamId::new(2, 0), StreamId::new(4, 0)] {
+            let pending = pel.get(&id).unwrap();
+            assert_eq!(pending.consumer, "dave");
+            assert_eq!(pending.delivery_count, 5);
+            assert_eq!(pending.delivery_time, 1000);
+        }
+        assert!(s.group("g").unwrap().consumer("dave").is_some());
+
+        assert!(matches!(
+            xclaim(&mut store, &["nogroup", "c", "0", "1-0"]),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        for args in [
+            &["g", "c", "0"][..],
+            &["g", "c", "x", "1-0"],
+            &["g", "c", "0", "1-0", "TIME"],
+            &["g", "c", "0", "1-0", "BOGUS"],
+        ] {
+            assert!(XClaimCommand::parse("s".into(), args).is_err(), "{args:?}");
+        }
+    }
+
+    /// Тест проверяет `XAUTOCLAIM`: курсор, `COUNT`, `JUSTID` и удалённые
+    /// сообщения.
+    #[test]
+    fn test_xautoclaim() {
+        let mut store = create_store();
+        setup_pending(&mut store);
+        let xautoclaim = |store: &mut StorageEngine, args: &[&str]| {
+            XAutoClaimCommand::parse("s".into(), args)?.execute(store)
+        };
+
+        let Value::Array(reply) =
+            xautoclaim(&mut store, &["g", "carol", "0", "-", "COUNT", "2"]).unwrap()
+        else {
+            panic!("expected array");
+        };
+        assert_eq!(reply[0], Value::Str(Sds::from_str("3-0")));
+        assert_eq!(reply_ids(reply[1].clone()), ["1-0", "2-0"]);
+        assert_eq!(reply[2], Value::Array(vec![]));
+
+        let mut s = stream(&mut store, "s");
+        s.trim_by_maxlen(1, false);
+        store.set(&Sds::from_str("s"), Value::SStream(s)).unwrap();
+
+        let Value::Array(reply) =
+            xautoclaim(&mut store, &["g", "dave", "0", "0", "JUSTID"]).unwrap()
+        else {
+            panic!("expected array");
+        };
+        assert_eq!(reply[0], Value::Str(Sds::from_str("0-0")));
+        assert!(just_ids(&reply[1]).is_empty());
+        assert_eq!(just_ids(&reply[2]), ["1-0", "2-0", "3-0"]);
+        assert!(stream(&mut store, "s").group("g").unwrap().pel().is_empty());
+
+        assert!(matches!(
+            xautoclaim(&mut store, &["nogroup", "c", "0", "0"]),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        for args in [
+            &["g", "c", "0"][..],
+            &["g", "c", "0", "0", "COUNT", "0"],
+            &["g", "c", "0", "(0", "COUNT", "1"],
+            &["g", "c", "0", "0", "BOGUS"],
+        ] {
+            assert!(
+                XAutoClaimCommand::parse("s".into(), args).is_err(),
+                "{args:?}"
+            );
+        }
+    }
+
+    /// Тест проверяет `XDEL`: число удалённых записей, очистку PEL и
+    /// монотонность ID после удаления.
+    #[test]
+    fn test_xdel() {
+        let mut store = create_store();
+        let xdel = |store: &mut StorageEngine, ids: &[&str]| {
+            XDelCommand::parse("s".into(), ids)?.execute(store)
+        };
+        assert_eq!(xdel(&mut store, &["1-0"]).unwrap(), Value::Int(0));
+
+        setup_pending(&mut store);
+        assert_eq!(
+            xdel(&mut store, &["2-0", "2-0", "9-0"]).unwrap(),
+            Value::Int(1)
+        );
+        let all = || XRangeCommand::parse("s".into(), &["-", "+"]).unwrap();
+        assert_eq!(
+            reply_ids(all().execute(&mut store).unwrap()),
+            ["1-0", "3-0", "4-0"]
+        );
+        assert_eq!(
+            XLenCommand { key: "s".into() }.execute(&mut store).unwrap(),
+            Value::Int(3)
+        );
+        let s = stream(&mut store, "s");
+        assert!(s
+            .group("g")
+            .unwrap()
+            .pel()
+            .get(&StreamId::new(2, 0))
+            .is_none());
+        assert_eq!(s.group("g").unwrap().pel().len(), 2);
+
+        assert_eq!(xdel(&mut store, &["4-0"]).unwrap(), Value::Int(1));
+        assert!(xadd(&mut store, "s", &["4-0", "f", "v"]).is_err());
+        assert_eq!(
+            xadd(&mut store, "s", &["4-1", "f", "v"]).unwrap(),
+            Value::Str(Sds::from_str("4-1"))
+        );
+
+        assert!(XDelCommand::parse("s".into(), &[]).is_err());
+        assert!(XDelCommand::parse("s".into(), &["x"]).is_err());
+    }
+
+    /// Тест проверяет `XTRIM` по `MAXLEN` и `MINID`, в том числе
+    /// приближённую обрезку и `LIMIT`.
+    #[test]
+    fn test_xtrim() {
+        let mut store = create_store();
+        let xtrim = |store: &mut StorageEngine, args: &[&str]| {
+            XTrimCommand::parse("s".into(), args)?.execute(store)
+        };
+        assert_eq!(xtrim(&mut store, &["MAXLEN", "0"]).unwrap(), Value::Int(0));
+
+        fill(&mut store, "s", 250);
+        assert_eq!(
+            xtrim(&mut store, &["MAXLEN", "~", "120"]).unwrap(),
+            Value::Int(100)
+        );
+        assert_eq!(stream(&mut store, "s").len(), 150);
+        assert_eq!(
+            xtrim(&mut store, &["MAXLEN", "~", "120"]).unwrap(),
+            Value::Int(0)
+        );
+
+        assert_eq!(
+            xtrim(&mut store, &["MINID", "=", "200"]).unwrap(),
+            Value::Int(99)
+        );
+        assert_eq!(
+            stream(&mut store, "s").first_entry().unwrap().id,
+            StreamId::new(200, 0)
+        );
+
+        XDelCommand::parse("s".into(), &["201-0", "202-0"])
+            .unwrap()
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(xtrim(&mut store, &["MAXLEN", "45"]).unwrap(), Value::Int(4));
+        assert_eq!(
+            stream(&mut store, "s").first_entry().unwrap().id,
+            StreamId::new(206, 0)
+        );
+
+        for args in [
+            &[][..],
+            &["MAXLEN"],
+            &["MAXLEN", "-1"],
+            &["MAXLEN", "10", "LIMIT", "5"],
+            &["MAXLEN", "10", "extra"],
+            &["SIZE", "10"],
+        ] {
+            assert!(XTrimCommand::parse("s".into(), args).is_err(), "{args:?}");
+        }
+    }
+}
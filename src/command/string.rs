@@ -1,10 +1,98 @@
-use crate::{CommandExecute, QuickList, Sds, StorageEngine, StoreError, Value};
+use crate::{
+    CommandExecute, GetExExpiry, KeyspaceEvent, QuickList, Sds, StorageEngine, StoreError, Value,
+};
+
+/// Максимальный размер строкового значения (512 MB).
+const MAX_STRING_SIZE: usize = 512 * 1024 * 1024;
+
+/// Параметры команды SET.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SetOptions {
+    /// `EX`/`PX`/`EXAT`/`PXAT` или `KEEPTTL` (`GetExExpiry::Keep`). Без
+    /// опции TTL ключа снимается.
+    pub expiry: Option<GetExExpiry>,
+    /// `NX` — устанавливать, только если ключ отсутствует.
+    pub nx: bool,
+    /// `XX` — устанавливать, только если ключ существует.
+    pub xx: bool,
+    /// `GET` — вернуть прежнее значение ключа.
+    pub get: bool,
+}
 
 /// Команда SET — устанавливает значение по ключу.
+///
+/// Возвращает `OK` (или `nil`, если условие `NX`/`XX` не выполнено); с
+/// флагом `GET` — прежнее значение ключа или `nil`.
 #[derive(Debug)]
 pub struct SetCommand {
     pub key: String,
     pub value: Value,
+    pub options: SetOptions,
+}
+
+impl SetCommand {
+    /// Разбирает необязательные аргументы после значения:
+    /// `[NX | XX] [GET] [EX seconds | PX milliseconds |
+    /// EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]`.
+    pub fn parse(
+        key: String,
+        value: Value,
+        args: &[&str],
+    ) -> Result<Self, StoreError> {
+        let mut options = SetOptions::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let upper = arg.to_ascii_uppercase();
+            let expiry = match upper.as_str() {
+                "NX" => {
+                    options.nx = true;
+                    continue;
+                }
+                "XX" => {
+                    options.xx = true;
+                    continue;
+                }
+                "GET" => {
+                    options.get = true;
+                    continue;
+                }
+                "KEEPTTL" => GetExExpiry::Keep,
+                "EX" | "PX" | "EXAT" | "PXAT" => {
+                    let n = iter
+                        .next()
+                        .and_then(|n| n.parse::<u64>().ok())
+                        .ok_or_else(|| {
+                            StoreError::InvalidArgument(
+                                "value is not an integer or out of range".to_string(),
+                            )
+                        })?;
+                    match upper.as_str() {
+                        "EX" => GetExExpiry::Ex(n),
+                        "PX" => GetExExpiry::Px(n),
+                        "EXAT" => GetExExpiry::ExAt(n),
+                        _ => GetExExpiry::PxAt(n),
+                    }
+                }
+                _ => return Err(StoreError::Syntax(format!("SET: unknown option '{arg}'"))),
+            };
+            // Допускается только одна опция времени жизни (включая KEEPTTL).
+            if options.expiry.replace(expiry).is_some() {
+                return Err(StoreError::Syntax(
+                    "SET: only one of EX, PX, EXAT, PXAT, KEEPTTL is allowed".to_string(),
+                ));
+            }
+        }
+        if options.nx && options.xx {
+            return Err(StoreError::Syntax(
+                "SET: XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        Ok(Self {
+            key,
+            value,
+            options,
+        })
+    }
 }
 
 impl CommandExecute for SetCommand {
@@ -12,8 +100,62 @@ impl CommandExecute for SetCommand {
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        store.set(&Sds::from_str(self.key.as_str()), self.value.clone())?;
-        Ok(Value::Null)
+        let opts = self.options;
+        if matches!(
+            opts.expiry,
+            Some(
+                GetExExpiry::Ex(0)
+                    | GetExExpiry::Px(0)
+                    | GetExExpiry::ExAt(0)
+                    | GetExExpiry::PxAt(0)
+            )
+        ) {
+            return Err(StoreError::InvalidArgument(
+                "invalid expire time in 'set' command".into(),
+            ));
+        }
+
+        let key = Sds::from_str(self.key.as_str());
+        if opts.nx || opts.xx || opts.get {
+            let current = store.get(&key)?;
+            // GET допустим только для строковых значений.
+            if opts.get && current.as_ref().is_some_and(|v| !is_string_value(v)) {
+                return Err(StoreError::InvalidType);
+            }
+            // Условие NX/XX не выполнено: значение не меняется, но с GET
+            // прежнее значение всё равно возвращается.
+            if (opts.nx && current.is_some()) || (opts.xx && current.is_none()) {
+                return Ok(match current {
+                    Some(value) if opts.get => value,
+                    _ => Value::Null,
+                });
+            }
+        }
+
+        let previous = if opts.get {
+            store.set_get(&key, self.value.clone())?
+        } else {
+            store.set(&key, self.value.clone())?;
+            store.notify_keyspace_event(KeyspaceEvent::Set, &key);
+            None
+        };
+        match opts.expiry {
+            // KEEPTTL: `set` не трогает TTL существующего ключа.
+            Some(GetExExpiry::Keep) => {}
+            // Абсолютное время в прошлом даёт нулевой TTL — ключ удаляется.
+            Some(expiry) => {
+                store.set_expiry(&key, expiry.ttl())?;
+            }
+            None => {
+                store.set_expiry(&key, None)?;
+            }
+        }
+
+        if opts.get {
+            Ok(previous.unwrap_or(Value::Null))
+        } else {
+            Ok(Value::Str(Sds::from_str("OK")))
+        }
     }
 
     fn command_name(&self) -> &'static str {
@@ -21,6 +163,14 @@ impl CommandExecute for SetCommand {
     }
 }
 
+/// Проверяет, что значение строкового типа (строка или число).
+fn is_string_value(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Str(_) | Value::Int(_) | Value::Float(_) | Value::Bool(_)
+    )
+}
+
 /// Команда GET — получает значение по ключу.
 #[derive(Debug)]
 pub struct GetCommand {
@@ -32,9 +182,12 @@ impl CommandExecute for GetCommand {
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        let result = store.get(&Sds::from_str(self.key.as_str()));
-        match result {
-            Ok(Some(value)) => Ok(value),
+        let key = Sds::from_str(self.key.as_str());
+        match store.get(&key) {
+            Ok(Some(value)) => {
+                store.notify_keyspace_event(KeyspaceEvent::Get, &key);
+                Ok(value)
+            }
             Ok(None) => Ok(Value::Null),
             Err(e) => Err(e),
         }
@@ -58,9 +211,11 @@ impl CommandExecute for SetNxCommand {
         &self,
         store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        let exists = store.get(&Sds::from_str(&self.key))?.is_some();
+        let key = Sds::from_str(&self.key);
+        let exists = store.get(&key)?.is_some();
         if !exists {
-            store.set(&Sds::from_str(&self.key), self.value.clone())?;
+            store.set(&key, self.value.clone())?;
+            store.notify_keyspace_event(KeyspaceEvent::Set, &key);
             Ok(Value::Int(1))
         } else {
             Ok(Value::Int(0))
@@ -204,6 +359,50 @@ impl CommandExecute for AppendCommand {
     }
 }
 
+/// Команда SETRANGE — перезаписывает часть строки начиная с байтового
+/// смещения, дополняя строку нулевыми байтами при необходимости.
+#[derive(Debug)]
+pub struct SetRangeCommand {
+    pub key: String,
+    pub offset: usize,
+    pub value: String,
+}
+
+impl CommandExecute for SetRangeCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let key = Sds::from_str(&self.key);
+        let data = self.value.as_bytes();
+
+        let mut current = match store.get(&key)? {
+            Some(Value::Str(s)) => s,
+            Some(_) => return Err(StoreError::InvalidType),
+            None => Sds::default(),
+        };
+
+        // Пустое значение ничего не меняет и не создаёт ключ.
+        if data.is_empty() {
+            return Ok(Value::Int(current.len() as i64));
+        }
+        if self.offset.saturating_add(data.len()) > MAX_STRING_SIZE {
+            return Err(StoreError::InvalidArgument(
+                "string exceeds maximum allowed size (512MB)".into(),
+            ));
+        }
+
+        current.set_range(self.offset, data);
+        let len = current.len();
+        store.set(&key, Value::Str(current))?;
+        Ok(Value::Int(len as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "SETRANGE"
+    }
+}
+
 /// Команда GETRANGE — возвращает подстроку по диапазону индексов.
 #[derive(Debug)]
 pub struct GetRangeCommand {
@@ -255,12 +454,70 @@ impl CommandExecute for GetRangeCommand {
     }
 }
 
+/// Команда GETDEL — атомарно возвращает строковое значение ключа и удаляет
+/// его.
+#[derive(Debug)]
+pub struct GetDelCommand {
+    pub key: String,
+}
+
+impl CommandExecute for GetDelCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        match store.getdel(&Sds::from_str(&self.key))? {
+            Some(value) => Ok(Value::Str(value)),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "GETDEL"
+    }
+}
+
+/// Команда GETEX — возвращает строковое значение ключа и изменяет его TTL
+/// (`EX`/`PX`/`EXAT`/`PXAT`/`PERSIST`).
+#[derive(Debug)]
+pub struct GetExCommand {
+    pub key: String,
+    pub expiry: GetExExpiry,
+}
+
+impl CommandExecute for GetExCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        if matches!(
+            self.expiry,
+            GetExExpiry::Ex(0) | GetExExpiry::Px(0) | GetExExpiry::ExAt(0) | GetExExpiry::PxAt(0)
+        ) {
+            return Err(StoreError::InvalidArgument(
+                "invalid expire time in 'getex' command".into(),
+            ));
+        }
+
+        match store.getex(&Sds::from_str(&self.key), self.expiry)? {
+            Some(value) => Ok(Value::Str(value)),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "GETEX"
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
     use super::*;
     use crate::InMemoryStore;
 
@@ -279,6 +536,7 @@ mod tests {
         let set_cmd = SetCommand {
             key: "test_key".to_string(),
             value: Value::Str(Sds::from_str("test_value")),
+            options: SetOptions::default(),
         };
 
         let result = set_cmd.execute(&mut store);
@@ -590,4 +848,403 @@ mod tests {
             panic!("Expected InvalidType error, but got a different error");
         }
     }
+
+    /// Тест проверяет, что GETDEL возвращает значение и удаляет ключ, а для
+    /// отсутствующего ключа и ключа другого типа возвращает nil.
+    #[test]
+    fn test_getdel() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("token"), Value::Str(Sds::from_str("abc")))
+            .unwrap();
+
+        let cmd = GetDelCommand {
+            key: "token".into(),
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("abc"))
+        );
+        assert_eq!(store.get(&Sds::from_str("token")).unwrap(), None);
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Null);
+
+        store.set(&Sds::from_str("n"), Value::Int(1)).unwrap();
+        let res = GetDelCommand { key: "n".into() }
+            .execute(&mut store)
+            .unwrap();
+        assert_eq!(res, Value::Null);
+        assert_eq!(store.get(&Sds::from_str("n")).unwrap(), Some(Value::Int(1)));
+    }
+
+    /// Тест проверяет, что GETEX с PX устанавливает TTL, по истечении которого
+    /// ключ удаляется, а без опций возвращает значение, не меняя TTL.
+    #[test]
+    fn test_getex_sets_ttl() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        let keep = GetExCommand {
+            key: "k".into(),
+            expiry: GetExExpiry::Keep,
+        };
+        assert_eq!(
+            keep.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("v"))
+        );
+
+        let cmd = GetExCommand {
+            key: "k".into(),
+            expiry: GetExExpiry::Px(50),
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("v"))
+        );
+        assert!(store.get(&Sds::from_str("k")).unwrap().is_some());
+
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        assert_eq!(store.get(&Sds::from_str("k")).unwrap(), None);
+        assert_eq!(keep.execute(&mut store).unwrap(), Value::Null);
+    }
+
+    /// Тест проверяет, что PERSIST снимает ранее установленный TTL.
+    #[test]
+    fn test_getex_persist() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        GetExCommand {
+            key: "k".into(),
+            expiry: GetExExpiry::Px(50),
+        }
+        .execute(&mut store)
+        .unwrap();
+        GetExCommand {
+            key: "k".into(),
+            expiry: GetExExpiry::Persist,
+        }
+        .execute(&mut store)
+        .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        assert_eq!(
+            store.get(&Sds::from_str("k")).unwrap(),
+            Some(Value::Str(Sds::from_str("v")))
+        );
+    }
+
+    /// Тест проверяет, что PXAT в прошлом удаляет ключ сразу, возвращая его
+    /// значение, а отсутствующий ключ не создаётся.
+    #[test]
+    fn test_getex_past_deadline_and_missing_key() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        let cmd = GetExCommand {
+            key: "k".into(),
+            expiry: GetExExpiry::PxAt(1),
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("v"))
+        );
+        assert_eq!(store.get(&Sds::from_str("k")).unwrap(), None);
+
+        let missing = GetExCommand {
+            key: "missing".into(),
+            expiry: GetExExpiry::Ex(10),
+        };
+        assert_eq!(missing.execute(&mut store).unwrap(), Value::Null);
+        assert_eq!(store.get(&Sds::from_str("missing")).unwrap(), None);
+    }
+
+    /// Тест проверяет ошибки GETEX: нулевой TTL и ключ не строкового типа.
+    #[test]
+    fn test_getex_errors() {
+        let mut store = create_store();
+        store.set(&Sds::from_str("n"), Value::Int(1)).unwrap();
+        store
+            .set(&Sds::from_str("k"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        let zero = GetExCommand {
+            key: "k".into(),
+            expiry: GetExExpiry::Ex(0),
+        };
+        assert!(matches!(
+            zero.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        let wrong = GetExCommand {
+            key: "n".into(),
+            expiry: GetExExpiry::Keep,
+        };
+        assert!(matches!(
+            wrong.execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+    }
+
+    /// Тест проверяет, что `SETRANGE` перезаписывает часть строки и
+    /// возвращает новую длину.
+    #[test]
+    fn test_setrange_overwrites() {
+        let mut store = create_store();
+        let key = Sds::from_str("greeting");
+        store
+            .set(&key, Value::Str(Sds::from_str("Hello World")))
+            .unwrap();
+
+        let cmd = SetRangeCommand {
+            key: "greeting".into(),
+            offset: 6,
+            value: "Zumic".into(),
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(11));
+        assert_eq!(
+            store.get(&key).unwrap(),
+            Some(Value::Str(Sds::from_str("Hello Zumic")))
+        );
+    }
+
+    /// Тест проверяет, что `SETRANGE` на отсутствующем ключе создаёт строку,
+    /// дополненную нулевыми байтами, а пустое значение ключ не создаёт.
+    #[test]
+    fn test_setrange_pads_missing_key() {
+        let mut store = create_store();
+        let cmd = SetRangeCommand {
+            key: "empty".into(),
+            offset: 3,
+            value: "".into(),
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(0));
+        assert_eq!(store.get(&Sds::from_str("empty")).unwrap(), None);
+
+        let cmd = SetRangeCommand {
+            key: "padded".into(),
+            offset: 3,
+            value: "ab".into(),
+        };
+        assert_eq!(cmd.execute(&mut store).unwrap(), Value::Int(5));
+        assert_eq!(
+            store.get(&Sds::from_str("padded")).unwrap(),
+            Some(Value::Str(Sds::from_bytes(b"\0\0\0ab")))
+        );
+    }
+
+    /// Тест проверяет, что `SETRANGE` отклоняет смещение за пределами 512 MB
+    /// и значения нестрокового типа.
+    #[test]
+    fn test_setrange_errors() {
+        let mut store = create_store();
+        let cmd = SetRangeCommand {
+            key: "big".into(),
+            offset: MAX_STRING_SIZE,
+            value: "x".into(),
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        store.set(&Sds::from_str("n"), Value::Int(1)).unwrap();
+        let cmd = SetRangeCommand {
+            key: "n".into(),
+            offset: 0,
+            value: "x".into(),
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    /// Тест проверяет разбор опций `SET`: `KEEPTTL` несовместим с
+    /// `EX`/`PX`/`EXAT`/`PXAT`, а число после `EX` обязательно.
+    #[test]
+    fn test_set_parse_options() {
+        let value = || Value::Str(Sds::from_str("v"));
+
+        let cmd = SetCommand::parse("k".into(), value(), &["exat", "1700000000"]).unwrap();
+        assert_eq!(cmd.options.expiry, Some(GetExExpiry::ExAt(1_700_000_000)));
+
+        let cmd = SetCommand::parse("k".into(), value(), &["KEEPTTL"]).unwrap();
+        assert_eq!(cmd.options.expiry, Some(GetExExpiry::Keep));
+
+        let cmd = SetCommand::parse("k".into(), value(), &[]).unwrap();
+        assert_eq!(cmd.options, SetOptions::default());
+
+        assert!(matches!(
+            SetCommand::parse("k".into(), value(), &["KEEPTTL", "PX", "100"]),
+            Err(StoreError::Syntax(_))
+        ));
+        assert!(matches!(
+            SetCommand::parse("k".into(), value(), &["EX", "10", "PXAT", "100"]),
+            Err(StoreError::Syntax(_))
+        ));
+        assert!(matches!(
+            SetCommand::parse("k".into(), value(), &["EX"]),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            SetCommand::parse("k".into(), value(), &["FOO"]),
+            Err(StoreError::Syntax(_))
+        ));
+    }
+
+    /// Тест проверяет, что обычный `SET` снимает TTL, `KEEPTTL` сохраняет
+    /// его, а `PXAT` задаёт абсолютное время истечения.
+    #[test]
+    fn test_set_keepttl_and_absolute_expiry() {
+        let mut store = create_store();
+        let key = Sds::from_str("k");
+        let set = |expiry| SetCommand {
+            key: "k".into(),
+            value: Value::Str(Sds::from_str("v")),
+            options: SetOptions {
+                expiry,
+                ..SetOptions::default()
+            },
+        };
+
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+            + 60_000;
+        set(Some(GetExExpiry::PxAt(at)))
+            .execute(&mut store)
+            .unwrap();
+        let stored = store.expiretime(&key).unwrap().unwrap();
+        assert!((stored - at as i64).abs() <= 5);
+
+        set(Some(GetExExpiry::Keep)).execute(&mut store).unwrap();
+        assert!(store.expiretime(&key).unwrap().unwrap() > 0);
+
+        set(None).execute(&mut store).unwrap();
+        assert_eq!(store.expiretime(&key).unwrap(), Some(-1));
+        assert_eq!(
+            store.get(&key).unwrap(),
+            Some(Value::Str(Sds::from_str("v")))
+        );
+    }
+
+    /// Тест проверяет, что `EXAT` в прошлом удаляет ключ сразу после записи,
+    /// а нулевой TTL отклоняется.
+    #[test]
+    fn test_set_exat_in_past_and_invalid_ttl() {
+        let mut store = create_store();
+        let cmd = SetCommand {
+            key: "k".into(),
+            value: Value::Str(Sds::from_str("v")),
+            options: SetOptions {
+                expiry: Some(GetExExpiry::ExAt(1)),
+                ..SetOptions::default()
+            },
+        };
+        assert_eq!(
+            cmd.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(store.get(&Sds::from_str("k")).unwrap(), None);
+
+        let cmd = SetCommand {
+            key: "k".into(),
+            value: Value::Str(Sds::from_str("v")),
+            options: SetOptions {
+                expiry: Some(GetExExpiry::Ex(0)),
+                ..SetOptions::default()
+            },
+        };
+        assert!(matches!(
+            cmd.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert_eq!(store.get(&Sds::from_str("k")).unwrap(), None);
+    }
+
+    /// Тест проверяет `SET ... GET`: возвращается прежнее значение (или
+    /// `nil`), а `NX` на существующем ключе не перезаписывает его, но всё
+    /// равно возвращает текущее значение.
+    #[test]
+    fn test_set_get_flag() {
+        let mut store = create_store();
+        let set = |value: &str, args: &[&str]| {
+            SetCommand::parse("k".into(), Value::Str(Sds::from_str(value)), args).unwrap()
+        };
+
+        assert_eq!(
+            set("v1", &["GET"]).execute(&mut store).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            set("v2", &["GET", "EX", "60"]).execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("v1"))
+        );
+        assert!(store.expiretime(&Sds::from_str("k")).unwrap().unwrap() > 0);
+
+        assert_eq!(
+            set("v3", &["NX", "GET"]).execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("v2"))
+        );
+        assert_eq!(
+            store.get(&Sds::from_str("k")).unwrap(),
+            Some(Value::Str(Sds::from_str("v2")))
+        );
+
+        assert_eq!(
+            set("v4", &["XX", "GET", "KEEPTTL"])
+                .execute(&mut store)
+                .unwrap(),
+            Value::Str(Sds::from_str("v2"))
+        );
+        assert!(store.expiretime(&Sds::from_str("k")).unwrap().unwrap() > 0);
+    }
+
+    /// Тест проверяет условия `NX`/`XX` без `GET`, их несовместимость и
+    /// ошибку `GET` для нестрокового значения.
+    #[test]
+    fn test_set_nx_xx_and_get_wrong_type() {
+        let mut store = create_store();
+        let value = || Value::Str(Sds::from_str("v"));
+
+        let xx = SetCommand::parse("k".into(), value(), &["XX"]).unwrap();
+        assert_eq!(xx.execute(&mut store).unwrap(), Value::Null);
+        assert_eq!(store.get(&Sds::from_str("k")).unwrap(), None);
+
+        let nx = SetCommand::parse("k".into(), value(), &["nx"]).unwrap();
+        assert_eq!(
+            nx.execute(&mut store).unwrap(),
+            Value::Str(Sds::from_str("OK"))
+        );
+        assert_eq!(nx.execute(&mut store).unwrap(), Value::Null);
+
+        assert!(matches!(
+            SetCommand::parse("k".into(), value(), &["NX", "XX"]),
+            Err(StoreError::Syntax(_))
+        ));
+
+        store
+            .set(
+                &Sds::from_str("list"),
+                Value::List(QuickList::from_iter([Sds::from_str("a")], 64)),
+            )
+            .unwrap();
+        let get = SetCommand::parse("list".into(), value(), &["GET"]).unwrap();
+        assert!(matches!(
+            get.execute(&mut store),
+            Err(StoreError::InvalidType)
+        ));
+        assert!(matches!(
+            store.get(&Sds::from_str("list")).unwrap(),
+            Some(Value::List(_))
+        ));
+    }
 }
@@ -1,4 +1,10 @@
-use crate::{CommandExecute, StorageEngine, StoreError, Value};
+use rand::Rng;
+
+use crate::{
+    database::{zset_pop, MAX_RANDOM_COUNT},
+    glob_match, CommandExecute, Dict, LexBound, Sds, StorageEngine, StoreError, Value,
+    ZCombineOptions, ZPopDir, DEFAULT_SCAN_COUNT,
+};
 
 /// Команда ZADD — добавляет элемент с баллом (score) в упорядоченное множество.
 #[derive(Debug)]
@@ -82,6 +88,93 @@ impl CommandExecute for ZScoreCommand {
     }
 }
 
+/// Команда ZSCAN — постранично обходит элементы упорядоченного множества по
+/// курсору.
+///
+/// Возвращает массив `[next_cursor, [member, score, ...]]`; курсор `0`
+/// означает конец обхода. Курсор — позиция в хеш-таблице множества (см.
+/// [`Dict::scan`]), поэтому элементы, существовавшие весь обход, будут
+/// возвращены, даже если множество менялось между вызовами.
+#[derive(Debug)]
+pub struct ZScanCommand {
+    pub key: String,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: Option<usize>,
+}
+
+impl CommandExecute for ZScanCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let Some(dict) = load_zset(store, &self.key)? else {
+            return Ok(Value::Array(vec![
+                Value::Str(Sds::from_str("0")),
+                Value::Array(Vec::new()),
+            ]));
+        };
+
+        // Курсор Dict::scan переживает изменения множества между вызовами
+        let (next, page) = dict.scan(self.cursor, self.count.unwrap_or(DEFAULT_SCAN_COUNT));
+        let entries = page
+            .into_iter()
+            .filter(|(m, _)| {
+                self.pattern
+                    .as_deref()
+                    .is_none_or(|p| glob_match(p.as_bytes(), m.as_slice()))
+            })
+            .map(|(m, s)| (m.clone(), *s))
+            .collect();
+
+        Ok(Value::Array(vec![
+            Value::Str(Sds::from_str(&next.to_string())),
+            entries_to_array(entries, true),
+        ]))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZSCAN"
+    }
+}
+
+/// Команда ZMSCORE — возвращает score нескольких элементов.
+///
+/// Каждый найденный score возвращается строкой (`"1.5"`), для отсутствующих
+/// элементов — `nil` на соответствующей позиции.
+#[derive(Debug)]
+pub struct ZmScoreCommand {
+    pub key: String,
+    pub members: Vec<String>,
+}
+
+impl CommandExecute for ZmScoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let members = to_sds(&self.members);
+        let scores = store.zmscore(
+            &Sds::from_str(&self.key),
+            &members.iter().collect::<Vec<_>>(),
+        )?;
+
+        Ok(Value::Array(
+            scores
+                .into_iter()
+                .map(|score| match score {
+                    Some(score) => Value::Str(Sds::from(score.to_string())),
+                    None => Value::Null,
+                })
+                .collect(),
+        ))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZMSCORE"
+    }
+}
+
 /// Команда ZCARD — возвращает количество элементов в упорядоченном множестве.
 #[derive(Debug)]
 pub struct ZCardCommand {
@@ -227,19 +320,33 @@ impl CommandExecute for ZRangeByScoreCommand {
 }
 
 /// Команда ZRANGEBYLEX — возвращает элементы в лексикографическом диапазоне.
+///
+/// Границы задаются в формате `[member` (включительно), `(member`
+/// (исключая), `-` и `+` (минус/плюс бесконечность). Предполагается, что все
+/// элементы множества имеют одинаковый score.
 #[derive(Debug)]
 pub struct ZRangeByLexCommand {
     pub key: String,
     pub min: String,
     pub max: String,
+    /// Необязательный `LIMIT offset count`.
+    pub limit: Option<(i64, i64)>,
 }
 
 impl CommandExecute for ZRangeByLexCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ZRANGEBYLEX is not implemented yet")
+        let min = parse_lex_bound(&self.min)?;
+        let max = parse_lex_bound(&self.max)?;
+
+        let members = match load_zset(store, &self.key)? {
+            Some(dict) => lex_range(&dict, &min, &max),
+            None => Vec::new(),
+        };
+
+        Ok(members_to_array(apply_limit(members, self.limit)))
     }
 
     fn command_name(&self) -> &'static str {
@@ -247,20 +354,175 @@ impl CommandExecute for ZRangeByLexCommand {
     }
 }
 
+/// Команда ZREVRANGEBYLEX — то же, что ZRANGEBYLEX, но в обратном порядке.
+///
+/// В протоколе границы передаются в порядке `max min`.
+#[derive(Debug)]
+pub struct ZRevRangeByLexCommand {
+    pub key: String,
+    pub max: String,
+    pub min: String,
+    /// Необязательный `LIMIT offset count`.
+    pub limit: Option<(i64, i64)>,
+}
+
+impl CommandExecute for ZRevRangeByLexCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let min = parse_lex_bound(&self.min)?;
+        let max = parse_lex_bound(&self.max)?;
+
+        let members = match load_zset(store, &self.key)? {
+            Some(dict) => {
+                let mut members = lex_range(&dict, &min, &max);
+                members.reverse();
+                members
+            }
+            None => Vec::new(),
+        };
+
+        Ok(members_to_array(apply_limit(members, self.limit)))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZREVRANGEBYLEX"
+    }
+}
+
+/// Команда ZLEXCOUNT — возвращает количество элементов в лексикографическом
+/// диапазоне.
+#[derive(Debug)]
+pub struct ZLexCountCommand {
+    pub key: String,
+    pub min: String,
+    pub max: String,
+}
+
+impl CommandExecute for ZLexCountCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let min = parse_lex_bound(&self.min)?;
+        let max = parse_lex_bound(&self.max)?;
+
+        let count = match load_zset(store, &self.key)? {
+            Some(dict) => lex_count(&dict, &min, &max),
+            None => 0,
+        };
+
+        Ok(Value::Int(count as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZLEXCOUNT"
+    }
+}
+
+/// Команда ZUNION — возвращает объединение нескольких ZSET.
+///
+/// `WEIGHTS` умножает score каждого входного множества, `AGGREGATE` задаёт
+/// способ объединения score элемента из нескольких множеств. С `WITHSCORES`
+/// после каждого элемента следует его score.
+#[derive(Debug)]
+pub struct ZUnionCommand {
+    pub keys: Vec<String>,
+    pub options: ZCombineOptions,
+    pub with_scores: bool,
+}
+
+impl CommandExecute for ZUnionCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        check_combine_args(&self.keys, Some(&self.options))?;
+        let keys = to_sds(&self.keys);
+        let entries = store.zunion(&keys.iter().collect::<Vec<_>>(), &self.options)?;
+        Ok(entries_to_array(entries, self.with_scores))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZUNION"
+    }
+}
+
+/// Команда ZINTER — возвращает пересечение нескольких ZSET.
+///
+/// Опции такие же, как у ZUNION.
+#[derive(Debug)]
+pub struct ZInterCommand {
+    pub keys: Vec<String>,
+    pub options: ZCombineOptions,
+    pub with_scores: bool,
+}
+
+impl CommandExecute for ZInterCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        check_combine_args(&self.keys, Some(&self.options))?;
+        let keys = to_sds(&self.keys);
+        let entries = store.zinter(&keys.iter().collect::<Vec<_>>(), &self.options)?;
+        Ok(entries_to_array(entries, self.with_scores))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZINTER"
+    }
+}
+
+/// Команда ZDIFF — возвращает элементы первого ZSET, отсутствующие в
+/// остальных.
+#[derive(Debug)]
+pub struct ZDiffCommand {
+    pub keys: Vec<String>,
+    pub with_scores: bool,
+}
+
+impl CommandExecute for ZDiffCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        check_combine_args(&self.keys, None)?;
+        let keys = to_sds(&self.keys);
+        let entries = store.zdiff(&keys.iter().collect::<Vec<_>>())?;
+        Ok(entries_to_array(entries, self.with_scores))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZDIFF"
+    }
+}
+
 /// Команда ZUNIONSTORE — объединяет несколько ZSET и сохраняет результат в
 /// dest.
+///
+/// Возвращает мощность результата; пустой результат удаляет `destination`.
 #[derive(Debug)]
 pub struct ZUnionStoreCommand {
     pub destination: String,
     pub keys: Vec<String>,
+    pub options: ZCombineOptions,
 }
 
 impl CommandExecute for ZUnionStoreCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ZUNIONSTORE is not implemented yet")
+        check_combine_args(&self.keys, Some(&self.options))?;
+        let keys = to_sds(&self.keys);
+        let count = store.zunionstore(
+            &Sds::from_str(&self.destination),
+            &keys.iter().collect::<Vec<_>>(),
+            &self.options,
+        )?;
+        Ok(Value::Int(count as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -269,18 +531,28 @@ impl CommandExecute for ZUnionStoreCommand {
 }
 
 /// Команда ZINTERSTORE — пересечение нескольких ZSET.
+///
+/// Возвращает мощность результата; пустой результат удаляет `destination`.
 #[derive(Debug)]
 pub struct ZInterStoreCommand {
     pub destination: String,
     pub keys: Vec<String>,
+    pub options: ZCombineOptions,
 }
 
 impl CommandExecute for ZInterStoreCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ZINTERSTORE is not implemented yet")
+        check_combine_args(&self.keys, Some(&self.options))?;
+        let keys = to_sds(&self.keys);
+        let count = store.zinterstore(
+            &Sds::from_str(&self.destination),
+            &keys.iter().collect::<Vec<_>>(),
+            &self.options,
+        )?;
+        Ok(Value::Int(count as i64))
     }
 
     fn command_name(&self) -> &'static str {
@@ -288,7 +560,38 @@ impl CommandExecute for ZInterStoreCommand {
     }
 }
 
+/// Команда ZDIFFSTORE — сохраняет разность ZSET в `destination`.
+///
+/// Возвращает мощность результата; пустой результат удаляет `destination`.
+#[derive(Debug)]
+pub struct ZDiffStoreCommand {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+impl CommandExecute for ZDiffStoreCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        check_combine_args(&self.keys, None)?;
+        let keys = to_sds(&self.keys);
+        let count = store.zdiffstore(
+            &Sds::from_str(&self.destination),
+            &keys.iter().collect::<Vec<_>>(),
+        )?;
+        Ok(Value::Int(count as i64))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZDIFFSTORE"
+    }
+}
+
 /// Команда ZPOPMIN — удаляет и возвращает элемент с минимальным score.
+///
+/// Возвращает плоский массив `member, score, ...`; если `count` больше
+/// размера множества, возвращаются все элементы.
 #[derive(Debug)]
 pub struct ZPopMinCommand {
     pub key: String,
@@ -298,9 +601,9 @@ pub struct ZPopMinCommand {
 impl CommandExecute for ZPopMinCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ZPOPMIN is not implemented yet");
+        pop_zset(store, &self.key, self.count.unwrap_or(1), ZPopDir::Min)
     }
 
     fn command_name(&self) -> &'static str {
@@ -309,6 +612,8 @@ impl CommandExecute for ZPopMinCommand {
 }
 
 /// Команда ZPOPMAX — удаляет и возвращает элемент с максимальным score.
+///
+/// Формат ответа такой же, как у ZPOPMIN.
 #[derive(Debug)]
 pub struct ZPopMaxCommand {
     pub key: String,
@@ -318,12 +623,1151 @@ pub struct ZPopMaxCommand {
 impl CommandExecute for ZPopMaxCommand {
     fn execute(
         &self,
-        _store: &mut StorageEngine,
+        store: &mut StorageEngine,
     ) -> Result<Value, StoreError> {
-        unimplemented!("ZPOPMAX is not implemented yet")
+        pop_zset(store, &self.key, self.count.unwrap_or(1), ZPopDir::Max)
     }
 
     fn command_name(&self) -> &'static str {
         "ZPOPMAX"
     }
 }
+
+/// Команда ZMPOP — извлекает до `count` элементов с минимальным (`MIN`) или
+/// максимальным (`MAX`) score из первого непустого ZSET среди `keys`.
+///
+/// Возвращает `[key, [[member, score], ...]]` или `nil`, если все множества
+/// пусты или отсутствуют.
+#[derive(Debug)]
+pub struct ZmPopCommand {
+    pub keys: Vec<String>,
+    pub dir: ZPopDir,
+    pub count: Option<usize>,
+}
+
+impl CommandExecute for ZmPopCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let count = self.count.unwrap_or(1);
+        if count == 0 {
+            return Err(StoreError::InvalidArgument(
+                "count should be greater than 0".into(),
+            ));
+        }
+        check_combine_args(&self.keys, None)?;
+
+        let keys = to_sds(&self.keys);
+        match store.zmpop(&keys.iter().collect::<Vec<_>>(), self.dir, count)? {
+            Some((key, entries)) => Ok(Value::Array(vec![
+                Value::Str(key),
+                Value::Array(
+                    entries
+                        .into_iter()
+                        .map(|(member, score)| {
+                            Value::Array(vec![Value::Str(member), Value::Float(score)])
+                        })
+                        .collect(),
+                ),
+            ])),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZMPOP"
+    }
+}
+
+/// Команда ZRANDMEMBER — возвращает случайные элементы ZSET.
+///
+/// Положительный `count` возвращает до `count` различных элементов,
+/// отрицательный допускает повторы, но не больше [`MAX_RANDOM_COUNT`]
+/// элементов. С `WITHSCORES` после каждого элемента добавляется его score.
+#[derive(Debug)]
+pub struct ZRandMemberCommand {
+    pub key: String,
+    pub count: Option<i64>,
+    pub with_scores: bool,
+}
+
+impl CommandExecute for ZRandMemberCommand {
+    fn execute(
+        &self,
+        store: &mut StorageEngine,
+    ) -> Result<Value, StoreError> {
+        let dict = load_zset(store, &self.key)?;
+        let mut rng = rand::thread_rng();
+
+        let Some(count) = self.count else {
+            return Ok(match dict {
+                Some(dict) => {
+                    let members: Vec<&Sds> = dict.iter().map(|(m, _)| m).collect();
+                    match members.len() {
+                        0 => Value::Null,
+                        n => Value::Str(members[rng.gen_range(0..n)].clone()),
+                    }
+                }
+                None => Value::Null,
+            });
+        };
+
+        if count < 0 && count.unsigned_abs() > MAX_RANDOM_COUNT as u64 {
+            return Err(StoreError::InvalidArgument("value is out of range".into()));
+        }
+        let entries: Vec<(Sds, f64)> = match dict {
+            Some(dict) => dict.iter().map(|(m, s)| (m.clone(), *s)).collect(),
+            None => Vec::new(),
+        };
+        if entries.is_empty() || count == 0 {
+            return Ok(Value::Array(Vec::new()));
+        }
+
+        let picked: Vec<(Sds, f64)> = if count < 0 {
+            let n = count.unsigned_abs() as usize;
+            (0..n)
+                .map(|_| entries[rng.gen_range(0..entries.len())].clone())
+                .collect()
+        } else {
+            // Частичная перетасовка Фишера-Йетса: перемешиваем только первые
+            // `n` позиций.
+            let mut entries = entries;
+            let n = (count as usize).min(entries.len());
+            for i in 0..n {
+                let j = rng.gen_range(i..entries.len());
+                entries.swap(i, j);
+            }
+            entries.truncate(n);
+            entries
+        };
+
+        let items = if self.with_scores {
+            picked
+                .into_iter()
+                .flat_map(|(member, score)| [Value::Str(member), Value::Float(score)])
+                .collect()
+        } else {
+            picked
+                .into_iter()
+                .map(|(member, _)| Value::Str(member))
+                .collect()
+        };
+
+        Ok(Value::Array(items))
+    }
+
+    fn command_name(&self) -> &'static str {
+        "ZRANDMEMBER"
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Загружает словарь `member -> score` упорядоченного множества.
+///
+/// Возвращает `None`, если ключ отсутствует, и `InvalidType`, если по ключу
+/// хранится значение другого типа.
+fn load_zset(
+    store: &StorageEngine,
+    key: &str,
+) -> Result<Option<Dict<Sds, f64>>, StoreError> {
+    match store.get(&Sds::from_str(key))? {
+        Some(Value::ZSet { dict, .. }) => Ok(Some(dict)),
+        Some(_) => Err(StoreError::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Разбирает границу лексикографического диапазона.
+fn parse_lex_bound(raw: &str) -> Result<LexBound<Sds>, StoreError> {
+    match raw {
+        "-" => Ok(LexBound::NegInf),
+        "+" => Ok(LexBound::PosInf),
+        _ => {
+            if let Some(member) = raw.strip_prefix('[') {
+                Ok(LexBound::Inclusive(Sds::from_str(member)))
+            } else if let Some(member) = raw.strip_prefix('(') {
+                Ok(LexBound::Exclusive(Sds::from_str(member)))
+            } else {
+                Err(StoreError::InvalidArgument(
+                    "min or max is not valid string range item".into(),
+                ))
+            }
+        }
+    }
+}
+
+/// Возвращает элементы, попадающие в лексикографический диапазон, в
+/// порядке `(score, member)`.
+///
+/// Порядок строится по `dict`, так как `sorted` индексирован по score и не
+/// хранит несколько элементов с одинаковым score. Сортируются только
+/// попавшие в диапазон элементы.
+fn lex_range(
+    dict: &Dict<Sds, f64>,
+    min: &LexBound<Sds>,
+    max: &LexBound<Sds>,
+) -> Vec<Sds> {
+    let mut entries: Vec<(&Sds, f64)> = dict
+        .iter()
+        .filter(|(m, _)| min.is_above_min(m) && max.is_below_max(m))
+        .map(|(m, s)| (m, *s))
+        .collect();
+    entries.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+    entries.into_iter().map(|(m, _)| m.clone()).collect()
+}
+
+/// Считает элементы, попадающие в лексикографический диапазон, без
+/// сортировки.
+fn lex_count(
+    dict: &Dict<Sds, f64>,
+    min: &LexBound<Sds>,
+    max: &LexBound<Sds>,
+) -> usize {
+    dict.iter()
+        .filter(|(m, _)| min.is_above_min(m) && max.is_below_max(m))
+        .count()
+}
+
+/// Применяет `LIMIT offset count`: отрицательный `count` означает «все
+/// оставшиеся», отрицательный `offset` даёт пустой результат.
+fn apply_limit(
+    items: Vec<Sds>,
+    limit: Option<(i64, i64)>,
+) -> Vec<Sds> {
+    match limit {
+        None => items,
+        Some((offset, _)) if offset < 0 => Vec::new(),
+        Some((offset, count)) => {
+            let iter = items.into_iter().skip(offset as usize);
+            if count < 0 {
+                iter.collect()
+            } else {
+                iter.take(count as usize).collect()
+            }
+        }
+    }
+}
+
+/// Удаляет до `count` элементов с минимальным (или максимальным) score и
+/// возвращает их плоским массивом `member, score, ...`.
+fn pop_zset(
+    store: &mut StorageEngine,
+    key: &str,
+    count: usize,
+    dir: ZPopDir,
+) -> Result<Value, StoreError> {
+    let key = Sds::from_str(key);
+    let (mut dict, mut sorted) = match store.get(&key)? {
+        Some(Value::ZSet { dict, sorted }) => (dict, sorted),
+        Some(_) => return Err(StoreError::InvalidType),
+        None => return Ok(Value::Array(Vec::new())),
+    };
+
+    let popped = zset_pop(&mut dict, &mut sorted, count, dir);
+
+    if dict.is_empty() {
+        store.del(&key)?;
+    } else {
+        store.set(&key, Value::ZSet { dict, sorted })?;
+    }
+
+    Ok(entries_to_array(popped, true))
+}
+
+/// Проверяет аргументы ZUNION/ZINTER/ZDIFF: нужен хотя бы один ключ, а
+/// число весов должно совпадать с числом ключей.
+fn check_combine_args(
+    keys: &[String],
+    options: Option<&ZCombineOptions>,
+) -> Result<(), StoreError> {
+    if keys.is_empty() {
+        return Err(StoreError::InvalidArgument(
+            "at least 1 input key is needed".into(),
+        ));
+    }
+    if let Some(weights) = options.and_then(|o| o.weights.as_ref()) {
+        if weights.len() != keys.len() {
+            return Err(StoreError::Syntax("syntax error".into()));
+        }
+    }
+    Ok(())
+}
+
+/// Преобразует строковые ключи в `Sds`.
+fn to_sds(keys: &[String]) -> Vec<Sds> {
+    keys.iter().map(|k| Sds::from_str(k)).collect()
+}
+
+/// Преобразует пары `(member, score)` в массив; с `with_scores` после
+/// каждого элемента следует его score.
+fn entries_to_array(
+    entries: Vec<(Sds, f64)>,
+    with_scores: bool,
+) -> Value {
+    if with_scores {
+        Value::Array(
+            entries
+                .into_iter()
+                .flat_map(|(member, score)| [Value::Str(member), Value::Float(score)])
+                .collect(),
+        )
+    } else {
+        members_to_array(entries.into_iter().map(|(member, _)| member).collect())
+    }
+}
+
+/// Преобразует список элементов в массив строк.
+fn members_to_array(members: Vec<Sds>) -> Value {
+    Value::Array(members.into_iter().map(Value::Str).collect())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use super::*;
+    use crate::{InMemoryStore, SkipList, ZAggregate};
+
+    // Вспомогательная функция для создания нового хранилища в памяти.
+    fn create_store() -> StorageEngine {
+        StorageEngine::Memory(InMemoryStore::new())
+    }
+
+    // Вспомогательная функция: записывает ZSET напрямую в хранилище.
+    fn setup_zset(
+        store: &mut StorageEngine,
+        key: &str,
+        entries: &[(&str, f64)],
+    ) {
+        let mut dict = Dict::new();
+        let mut sorted = SkipList::new();
+        for (member, score) in entries {
+            dict.insert(Sds::from_str(member), *score);
+            sorted.insert(OrderedFloat(*score), Sds::from_str(member));
+        }
+        store
+            .set(&Sds::from_str(key), Value::ZSet { dict, sorted })
+            .unwrap();
+    }
+
+    fn strs(items: &[&str]) -> Value {
+        Value::Array(items.iter().map(|s| Value::Str(Sds::from_str(s))).collect())
+    }
+
+    // Множество, где у всех элементов одинаковый score.
+    fn setup_lex(store: &mut StorageEngine) {
+        setup_zset(
+            store,
+            "lex",
+            &[("a", 0.0), ("b", 0.0), ("c", 0.0), ("d", 0.0), ("e", 0.0)],
+        );
+    }
+
+    /// Тест проверяет ZRANGEBYLEX с включительными, исключающими и
+    /// бесконечными границами.
+    #[test]
+    fn test_zrangebylex_bounds() {
+        let mut store = create_store();
+        setup_lex(&mut store);
+
+        let cmd = |min: &str, max: &str| ZRangeByLexCommand {
+            key: "lex".into(),
+            min: min.into(),
+            max: max.into(),
+            limit: None,
+        };
+
+        assert_eq!(
+            cmd("[b", "[d").execute(&mut store).unwrap(),
+            strs(&["b", "c", "d"])
+        );
+        assert_eq!(cmd("(b", "(d").execute(&mut store).unwrap(), strs(&["c"]));
+        assert_eq!(
+            cmd("-", "+").execute(&mut store).unwrap(),
+            strs(&["a", "b", "c", "d", "e"])
+        );
+        assert_eq!(
+            cmd("-", "[b").execute(&mut store).unwrap(),
+            strs(&["a", "b"])
+        );
+        assert_eq!(cmd("+", "-").execute(&mut store).unwrap(), strs(&[]));
+    }
+
+    /// Тест проверяет LIMIT в ZRANGEBYLEX.
+    #[test]
+    fn test_zrangebylex_limit() {
+        let mut store = create_store();
+        setup_lex(&mut store);
+
+        let res = ZRangeByLexCommand {
+            key: "lex".into(),
+            min: "-".into(),
+            max: "+".into(),
+            limit: Some((1, 2)),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, strs(&["b", "c"]));
+
+        let res = ZRangeByLexCommand {
+            key: "lex".into(),
+            min: "-".into(),
+            max: "+".into(),
+            limit: Some((3, -1)),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, strs(&["d", "e"]));
+    }
+
+    /// Тест проверяет ZREVRANGEBYLEX: обратный порядок и LIMIT.
+    #[test]
+    fn test_zrevrangebylex() {
+        let mut store = create_store();
+        setup_lex(&mut store);
+
+        let res = ZRevRangeByLexCommand {
+            key: "lex".into(),
+            max: "[d".into(),
+            min: "(a".into(),
+            limit: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, strs(&["d", "c", "b"]));
+
+        let res = ZRevRangeByLexCommand {
+            key: "lex".into(),
+            max: "+".into(),
+            min: "-".into(),
+            limit: Some((0, 2)),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, strs(&["e", "d"]));
+    }
+
+    /// Тест проверяет ZLEXCOUNT, в том числе для отсутствующего ключа.
+    #[test]
+    fn test_zlexcount() {
+        let mut store = create_store();
+        setup_lex(&mut store);
+
+        let count = ZLexCountCommand {
+            key: "lex".into(),
+            min: "[b".into(),
+            max: "(e".into(),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(count, Value::Int(3));
+
+        let missing = ZLexCountCommand {
+            key: "nope".into(),
+            min: "-".into(),
+            max: "+".into(),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(missing, Value::Int(0));
+    }
+
+    /// Тест проверяет, что некорректные границы отклоняются.
+    #[test]
+    fn test_lex_invalid_bounds() {
+        let mut store = create_store();
+        setup_lex(&mut store);
+
+        let err = ZRangeByLexCommand {
+            key: "lex".into(),
+            min: "b".into(),
+            max: "+".into(),
+            limit: None,
+        }
+        .execute(&mut store)
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("min or max is not valid string range item"));
+
+        let err = ZLexCountCommand {
+            key: "lex".into(),
+            min: "-".into(),
+            max: "".into(),
+        }
+        .execute(&mut store)
+        .unwrap_err();
+        assert!(matches!(err, StoreError::InvalidArgument(_)));
+    }
+
+    /// Тест проверяет ошибку при работе с ключом другого типа.
+    #[test]
+    fn test_lex_wrong_type() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+
+        let err = ZLexCountCommand {
+            key: "str".into(),
+            min: "-".into(),
+            max: "+".into(),
+        }
+        .execute(&mut store)
+        .unwrap_err();
+        assert!(matches!(err, StoreError::InvalidType));
+    }
+
+    /// Тест проверяет ZPOPMIN и ZPOPMAX с count по умолчанию.
+    #[test]
+    fn test_zpopmin_zpopmax_single() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+
+        let min = ZPopMinCommand {
+            key: "z".into(),
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            min,
+            Value::Array(vec![Value::Str(Sds::from_str("a")), Value::Float(1.0)])
+        );
+
+        let max = ZPopMaxCommand {
+            key: "z".into(),
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            max,
+            Value::Array(vec![Value::Str(Sds::from_str("c")), Value::Float(3.0)])
+        );
+
+        let dict = load_zset(&store, "z").unwrap().unwrap();
+        assert_eq!(dict.len(), 1);
+        assert_eq!(dict.get(&Sds::from_str("b")), Some(&2.0));
+    }
+
+    /// Тест проверяет, что count больше размера множества возвращает все
+    /// элементы и удаляет ключ.
+    #[test]
+    fn test_zpop_count_exceeds_len() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0), ("b", 2.0)]);
+
+        let res = ZPopMaxCommand {
+            key: "z".into(),
+            count: Some(10),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("b")),
+                Value::Float(2.0),
+                Value::Str(Sds::from_str("a")),
+                Value::Float(1.0),
+            ])
+        );
+        assert_eq!(store.get(&Sds::from_str("z")).unwrap(), None);
+    }
+
+    /// Тест проверяет порядок извлечения при совпадающих score.
+    #[test]
+    fn test_zpop_equal_scores() {
+        let mut store = create_store();
+        setup_lex(&mut store);
+
+        let res = ZPopMinCommand {
+            key: "lex".into(),
+            count: Some(2),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("a")),
+                Value::Float(0.0),
+                Value::Str(Sds::from_str("b")),
+                Value::Float(0.0),
+            ])
+        );
+
+        let res = ZPopMaxCommand {
+            key: "lex".into(),
+            count: Some(1),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![Value::Str(Sds::from_str("e")), Value::Float(0.0)])
+        );
+    }
+
+    /// Тест проверяет ZPOPMIN для отсутствующего ключа и ключа другого типа.
+    #[test]
+    fn test_zpop_missing_and_wrong_type() {
+        let mut store = create_store();
+
+        let res = ZPopMinCommand {
+            key: "none".into(),
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Array(vec![]));
+
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+        let err = ZPopMaxCommand {
+            key: "str".into(),
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap_err();
+        assert!(matches!(err, StoreError::InvalidType));
+    }
+
+    /// Тест проверяет ZRANDMEMBER без count и с положительным count.
+    #[test]
+    fn test_zrandmember_distinct() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+
+        let res = ZRandMemberCommand {
+            key: "z".into(),
+            count: None,
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert!(matches!(res, Value::Str(_)));
+
+        let res = ZRandMemberCommand {
+            key: "z".into(),
+            count: Some(10),
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        let Value::Array(items) = res else {
+            panic!("expected array");
+        };
+        let mut members: Vec<Sds> = items
+            .into_iter()
+            .map(|v| match v {
+                Value::Str(s) => s,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        members.sort();
+        assert_eq!(
+            members,
+            vec![Sds::from_str("a"), Sds::from_str("b"), Sds::from_str("c")]
+        );
+    }
+
+    /// Тест проверяет ZRANDMEMBER с отрицательным count и WITHSCORES.
+    #[test]
+    fn test_zrandmember_negative_with_scores() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0)]);
+
+        let res = ZRandMemberCommand {
+            key: "z".into(),
+            count: Some(-3),
+            with_scores: true,
+        }
+        .execute(&mut store)
+        .unwrap();
+        let expected: Vec<Value> = (0..3)
+            .flat_map(|_| [Value::Str(Sds::from_str("a")), Value::Float(1.0)])
+            .collect();
+        assert_eq!(res, Value::Array(expected));
+    }
+
+    /// Тест проверяет граничные случаи ZRANDMEMBER: count=0 и отсутствующий
+    /// ключ.
+    #[test]
+    fn test_zrandmember_edge_cases() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0)]);
+
+        let res = ZRandMemberCommand {
+            key: "z".into(),
+            count: Some(0),
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Array(vec![]));
+
+        let res = ZRandMemberCommand {
+            key: "ghost".into(),
+            count: None,
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Null);
+
+        let res = ZRandMemberCommand {
+            key: "ghost".into(),
+            count: Some(2),
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, Value::Array(vec![]));
+    }
+
+    /// Тест проверяет, что ZRANDMEMBER отклоняет слишком большой
+    /// отрицательный count до выборки.
+    #[test]
+    fn test_zrandmember_count_out_of_range() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0)]);
+
+        for count in [i64::MIN, -i64::MAX, -(MAX_RANDOM_COUNT as i64) - 1] {
+            let res = ZRandMemberCommand {
+                key: "z".into(),
+                count: Some(count),
+                with_scores: true,
+            }
+            .execute(&mut store);
+            assert!(matches!(res, Err(StoreError::InvalidArgument(_))));
+        }
+    }
+
+    fn keys(items: &[&str]) -> Vec<String> {
+        items.iter().map(|k| k.to_string()).collect()
+    }
+
+    /// Тест проверяет ZUNION с WITHSCORES и агрегацией по умолчанию (SUM).
+    #[test]
+    fn test_zunion_withscores() {
+        let mut store = create_store();
+        setup_zset(&mut store, "a", &[("x", 1.0), ("y", 2.0)]);
+        setup_zset(&mut store, "b", &[("y", 3.0), ("z", 4.0)]);
+
+        let res = ZUnionCommand {
+            keys: keys(&["a", "b", "missing"]),
+            options: ZCombineOptions::default(),
+            with_scores: true,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("x")),
+                Value::Float(1.0),
+                Value::Str(Sds::from_str("z")),
+                Value::Float(4.0),
+                Value::Str(Sds::from_str("y")),
+                Value::Float(5.0),
+            ])
+        );
+    }
+
+    /// Тест проверяет ZINTER с весами 0 и отрицательным весом и агрегацией
+    /// MAX.
+    #[test]
+    fn test_zinter_weights_zero_and_negative() {
+        let mut store = create_store();
+        setup_zset(&mut store, "a", &[("x", 1.0), ("y", 2.0)]);
+        setup_zset(&mut store, "b", &[("x", 3.0), ("y", 4.0), ("z", 5.0)]);
+
+        let res = ZInterCommand {
+            keys: keys(&["a", "b"]),
+            options: ZCombineOptions {
+                weights: Some(vec![0.0, -1.0]),
+                aggregate: ZAggregate::Max,
+            },
+            with_scores: true,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("x")),
+                Value::Float(0.0),
+                Value::Str(Sds::from_str("y")),
+                Value::Float(0.0),
+            ])
+        );
+
+        let sum = ZInterCommand {
+            keys: keys(&["a", "b"]),
+            options: ZCombineOptions {
+                weights: Some(vec![0.0, -1.0]),
+                aggregate: ZAggregate::Sum,
+            },
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(sum, strs(&["y", "x"]));
+    }
+
+    /// Тест проверяет ZDIFF и ZDIFFSTORE, включая удаление приёмника при
+    /// пустом результате.
+    #[test]
+    fn test_zdiff_and_store() {
+        let mut store = create_store();
+        setup_zset(&mut store, "a", &[("x", 1.0), ("y", 2.0), ("z", 3.0)]);
+        setup_zset(&mut store, "b", &[("y", 9.0)]);
+
+        let res = ZDiffCommand {
+            keys: keys(&["a", "b"]),
+            with_scores: false,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(res, strs(&["x", "z"]));
+
+        let stored = ZDiffStoreCommand {
+            destination: "dst".into(),
+            keys: keys(&["a", "b"]),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(stored, Value::Int(2));
+
+        let empty = ZDiffStoreCommand {
+            destination: "dst".into(),
+            keys: keys(&["a", "a"]),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(empty, Value::Int(0));
+        assert_eq!(store.get(&Sds::from_str("dst")).unwrap(), None);
+    }
+
+    /// Тест проверяет ZUNIONSTORE и ZINTERSTORE, в том числе когда приёмник
+    /// совпадает с источником.
+    #[test]
+    fn test_zunionstore_zinterstore() {
+        let mut store = create_store();
+        setup_zset(&mut store, "a", &[("x", 1.0), ("y", 2.0)]);
+        setup_zset(&mut store, "b", &[("y", 3.0), ("z", 4.0)]);
+
+        let union = ZUnionStoreCommand {
+            destination: "a".into(),
+            keys: keys(&["a", "b"]),
+            options: ZCombineOptions {
+                weights: Some(vec![2.0, 1.0]),
+                aggregate: ZAggregate::Sum,
+            },
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(union, Value::Int(3));
+        let dict = load_zset(&store, "a").unwrap().unwrap();
+        assert_eq!(dict.get(&Sds::from_str("y")), Some(&7.0));
+        assert_eq!(dict.get(&Sds::from_str("x")), Some(&2.0));
+
+        let inter = ZInterStoreCommand {
+            destination: "dst".into(),
+            keys: keys(&["a", "b"]),
+            options: ZCombineOptions {
+                weights: None,
+                aggregate: ZAggregate::Min,
+            },
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(inter, Value::Int(2));
+        let dict = load_zset(&store, "dst").unwrap().unwrap();
+        assert_eq!(dict.get(&Sds::from_str("y")), Some(&3.0));
+    }
+
+    /// Тест проверяет ошибки: число весов не совпадает с числом ключей,
+    /// пустой список ключей и ключ другого типа.
+    #[test]
+    fn test_zunion_errors() {
+        let mut store = create_store();
+        setup_zset(&mut store, "a", &[("x", 1.0)]);
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        let bad_weights = ZUnionCommand {
+            keys: keys(&["a"]),
+            options: ZCombineOptions {
+                weights: Some(vec![1.0, 2.0]),
+                aggregate: ZAggregate::Sum,
+            },
+            with_scores: false,
+        };
+        assert!(matches!(
+            bad_weights.execute(&mut store),
+            Err(StoreError::Syntax(_))
+        ));
+
+        let no_keys = ZDiffCommand {
+            keys: vec![],
+            with_scores: false,
+        };
+        assert!(matches!(
+            no_keys.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        let wrong_type = ZInterCommand {
+            keys: keys(&["a", "str"]),
+            options: ZCombineOptions::default(),
+            with_scores: false,
+        };
+        assert!(matches!(
+            wrong_type.execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+    }
+
+    /// Тест проверяет ZMPOP: пропуск отсутствующих и пустых ключей, формат
+    /// ответа и удаление опустевшего множества.
+    #[test]
+    fn test_zmpop() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+
+        let min = ZmPopCommand {
+            keys: keys(&["missing", "z"]),
+            dir: ZPopDir::Min,
+            count: Some(2),
+        }
+        .execute(&mut store)
+        .unwrap();
+        let pair =
+            |m: &str, s: f64| Value::Array(vec![Value::Str(Sds::from_str(m)), Value::Float(s)]);
+        assert_eq!(
+            min,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("z")),
+                Value::Array(vec![pair("a", 1.0), pair("b", 2.0)]),
+            ])
+        );
+
+        let max = ZmPopCommand {
+            keys: keys(&["z"]),
+            dir: ZPopDir::Max,
+            count: Some(10),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            max,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("z")),
+                Value::Array(vec![pair("c", 3.0)]),
+            ])
+        );
+        assert_eq!(store.get(&Sds::from_str("z")).unwrap(), None);
+
+        let none = ZmPopCommand {
+            keys: keys(&["z", "missing"]),
+            dir: ZPopDir::Min,
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(none, Value::Null);
+    }
+
+    /// Тест проверяет, что ZMPOP отклоняет COUNT 0 и ключ другого типа.
+    #[test]
+    fn test_zmpop_errors() {
+        let mut store = create_store();
+        store
+            .set(&Sds::from_str("str"), Value::Str(Sds::from_str("v")))
+            .unwrap();
+
+        let zero = ZmPopCommand {
+            keys: keys(&["z"]),
+            dir: ZPopDir::Min,
+            count: Some(0),
+        };
+        assert!(matches!(
+            zero.execute(&mut store),
+            Err(StoreError::InvalidArgument(_))
+        ));
+
+        let wrong = ZmPopCommand {
+            keys: keys(&["str"]),
+            dir: ZPopDir::Max,
+            count: None,
+        };
+        assert!(matches!(
+            wrong.execute(&mut store),
+            Err(StoreError::WrongType(_))
+        ));
+    }
+
+    /// Тест проверяет ZMSCORE: score возвращаются строками, `nil` стоит на
+    /// позициях отсутствующих элементов.
+    #[test]
+    fn test_zmscore() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.5), ("b", 2.0)]);
+
+        let res = ZmScoreCommand {
+            key: "z".into(),
+            members: keys(&["x", "a", "y", "b", "a"]),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            res,
+            Value::Array(vec![
+                Value::Null,
+                Value::Str(Sds::from_str("1.5")),
+                Value::Null,
+                Value::Str(Sds::from_str("2")),
+                Value::Str(Sds::from_str("1.5")),
+            ])
+        );
+
+        let missing = ZmScoreCommand {
+            key: "nokey".into(),
+            members: keys(&["a", "b"]),
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(missing, Value::Array(vec![Value::Null, Value::Null]));
+    }
+
+    /// Тест проверяет, что ZSCAN возвращает пары member/score, обходит всё
+    /// множество и для отсутствующего ключа сразу возвращает курсор 0.
+    #[test]
+    fn test_zscan_iterates_all_members() {
+        let mut store = create_store();
+        setup_zset(&mut store, "z", &[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+
+        let mut cursor = 0;
+        let mut entries = Vec::new();
+        loop {
+            let reply = ZScanCommand {
+                key: "z".into(),
+                cursor,
+                pattern: None,
+                count: Some(1),
+            }
+            .execute(&mut store)
+            .unwrap();
+            let Value::Array(parts) = reply else {
+                panic!("expected array");
+            };
+            let (Value::Str(next), Value::Array(items)) = (&parts[0], &parts[1]) else {
+                panic!("unexpected reply shape");
+            };
+            for pair in items.chunks(2) {
+                if let [Value::Str(m), Value::Float(s)] = pair {
+                    entries.push((m.as_str().unwrap().to_string(), *s));
+                }
+            }
+            cursor = next.as_str().unwrap().parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            entries,
+            vec![
+                ("a".to_string(), 1.0),
+                ("b".to_string(), 2.0),
+                ("c".to_string(), 3.0)
+            ]
+        );
+
+        let reply = ZScanCommand {
+            key: "missing".into(),
+            cursor: 0,
+            pattern: Some("*".into()),
+            count: None,
+        }
+        .execute(&mut store)
+        .unwrap();
+        assert_eq!(
+            reply,
+            Value::Array(vec![Value::Str(Sds::from_str("0")), Value::Array(vec![])])
+        );
+    }
+
+    /// Тест проверяет, что ZSCAN не теряет исходные элементы, если между
+    /// вызовами в множество добавляются новые и таблица расширяется.
+    #[test]
+    fn test_zscan_survives_inserts() {
+        let mut store = create_store();
+        let initial: Vec<(String, f64)> = (0..50).map(|i| (format!("m{i}"), i as f64)).collect();
+        let refs: Vec<(&str, f64)> = initial.iter().map(|(m, s)| (m.as_str(), *s)).collect();
+        setup_zset(&mut store, "z", &refs);
+
+        let mut cursor = 0;
+        let mut seen = std::collections::HashSet::new();
+        let mut added = 50;
+        loop {
+            let reply = ZScanCommand {
+                key: "z".into(),
+                cursor,
+                pattern: Some("m*".into()),
+                count: Some(5),
+            }
+            .execute(&mut store)
+            .unwrap();
+            let Value::Array(parts) = reply else {
+                panic!("expected array");
+            };
+            let (Value::Str(next), Value::Array(items)) = (&parts[0], &parts[1]) else {
+                panic!("unexpected reply shape");
+            };
+            for pair in items.chunks(2) {
+                if let [Value::Str(m), _] = pair {
+                    seen.insert(m.as_str().unwrap().to_string());
+                }
+            }
+            cursor = next.as_str().unwrap().parse().unwrap();
+            if cursor == 0 {
+                break;
+            }
+
+            let key = Sds::from_str("z");
+            let Some(Value::ZSet {
+                mut dict,
+                mut sorted,
+            }) = store.get(&key).unwrap()
+            else {
+                panic!("expected zset");
+            };
+            for _ in 0..20 {
+                let member = Sds::from_str(&format!("m{added}"));
+                dict.insert(member.clone(), added as f64);
+                sorted.insert(OrderedFloat(added as f64), member);
+                added += 1;
+            }
+            store.set(&key, Value::ZSet { dict, sorted }).unwrap();
+        }
+
+        for (m, _) in &initial {
+            assert!(seen.contains(m), "{m} missed");
+        }
+    }
+}
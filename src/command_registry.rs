@@ -1,37 +1,399 @@
-use std::collections::HashMap;
+//! Реестр команд: обработчики и метаданные.
+//!
+//! Каждая команда объявляет [`CommandSpec`] — арность, флаги и позиции
+//! ключей в том же виде, что и `COMMAND INFO` в Redis. По этим данным
+//! сервер отвечает на `COMMAND`, извлекает ключи для проверки ACL и
+//! маршрутизации по слотам кластера. Встроенные команды сервера собраны в
+//! [`builtin_commands`].
 
-use crate::db_context::DbContext;
+use std::collections::{BTreeMap, HashMap};
+
+use once_cell::sync::Lazy;
+
+use crate::{db_context::DbContext, glob_match, Sds, Value};
 
 /// Ф-я обработчик команды: получает &mut DbContext и сырые аргументы.
 pub type Handler = Box<dyn Fn(&mut DbContext, &[u8]) -> Vec<u8> + Send + Sync>;
 
+static BUILTIN_COMMANDS: Lazy<CommandTable> = Lazy::new(CommandTable::builtin);
+
+/// Возвращает таблицу встроенных команд сервера.
+#[inline]
+pub fn builtin_commands() -> &'static CommandTable {
+    &BUILTIN_COMMANDS
+}
+
+/// Флаг команды в ответе `COMMAND INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandFlag {
+    /// Команда только читает данные.
+    Readonly,
+    /// Команда изменяет данные.
+    Write,
+    /// Административная команда.
+    Admin,
+    /// Команда может увеличить расход памяти и отклоняется при `maxmemory`.
+    DenyOom,
+    /// Команда выполняется за O(1) или O(log N).
+    Fast,
+    /// Команда Pub/Sub.
+    PubSub,
+    /// Команда недоступна в Lua-скриптах.
+    NoScript,
+    /// Команда разрешена во время загрузки данных.
+    Loading,
+    /// Команда разрешена на реплике с устаревшими данными.
+    Stale,
+    /// Команда разрешена до аутентификации.
+    NoAuth,
+    /// Команда может блокировать клиента.
+    Blocking,
+}
+
+/// Метаданные команды: арность, флаги, позиции ключей и описание.
+///
+/// Позиции ключей заданы как в Redis: индексы считаются от имени команды
+/// (`0`), отрицательный `last_key` отсчитывается от конца аргументов, а
+/// `first_key == 0` означает, что ключей нет.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSpec {
+    /// Имя команды в нижнем регистре.
+    pub name: String,
+    /// Число аргументов вместе с именем; отрицательное — минимальное число.
+    pub arity: i64,
+    /// Флаги команды.
+    pub flags: Vec<CommandFlag>,
+    /// Позиция первого ключа.
+    pub first_key: i64,
+    /// Позиция последнего ключа.
+    pub last_key: i64,
+    /// Шаг между ключами.
+    pub step: i64,
+    /// Краткое описание для `COMMAND DOCS`.
+    pub doc: String,
+    /// Модуль, зарегистрировавший команду; `None` для встроенных.
+    pub module: Option<String>,
+}
+
+/// Фильтр `COMMAND LIST FILTERBY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandFilter {
+    /// Команды модуля (`MODULE name`).
+    Module(String),
+    /// Команды ACL-категории (`ACLCAT category`).
+    AclCategory(String),
+    /// Команды, имя которых подходит под glob-шаблон (`PATTERN pattern`).
+    Pattern(String),
+}
+
+/// Ошибка извлечения ключей из вызова команды (`COMMAND GETKEYS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetKeysError {
+    /// Команда не зарегистрирована.
+    UnknownCommand,
+    /// Число аргументов не соответствует арности.
+    WrongArity,
+    /// У команды нет ключей.
+    NoKeys,
+}
+
+/// Таблица метаданных команд, упорядоченная по имени.
+#[derive(Debug, Clone, Default)]
+pub struct CommandTable {
+    specs: BTreeMap<String, CommandSpec>,
+}
+
 pub struct CommandRegistry {
     handlers: HashMap<String, Handler>,
+    table: CommandTable,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl CommandFlag {
+    /// Возвращает имя флага в ответе `COMMAND INFO`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommandFlag::Readonly => "readonly",
+            CommandFlag::Write => "write",
+            CommandFlag::Admin => "admin",
+            CommandFlag::DenyOom => "denyoom",
+            CommandFlag::Fast => "fast",
+            CommandFlag::PubSub => "pubsub",
+            CommandFlag::NoScript => "noscript",
+            CommandFlag::Loading => "loading",
+            CommandFlag::Stale => "stale",
+            CommandFlag::NoAuth => "no_auth",
+            CommandFlag::Blocking => "blocking",
+        }
+    }
+}
+
+impl CommandSpec {
+    /// Создаёт описание команды `name` с арностью `arity`, без флагов и
+    /// ключей.
+    pub fn new(
+        name: impl Into<String>,
+        arity: i64,
+    ) -> Self {
+        Self {
+            name: name.into().to_ascii_lowercase(),
+            arity,
+            flags: Vec::new(),
+            first_key: 0,
+            last_key: 0,
+            step: 0,
+            doc: String::new(),
+            module: None,
+        }
+    }
+
+    /// Задаёт флаги команды.
+    pub fn flags(
+        mut self,
+        flags: &[CommandFlag],
+    ) -> Self {
+        self.flags = flags.to_vec();
+        self
+    }
+
+    /// Задаёт позиции ключей: первый, последний и шаг.
+    pub fn keys(
+        mut self,
+        first_key: i64,
+        last_key: i64,
+        step: i64,
+    ) -> Self {
+        self.first_key = first_key;
+        self.last_key = last_key;
+        self.step = step;
+        self
+    }
+
+    /// Задаёт описание для `COMMAND DOCS`.
+    pub fn doc(
+        mut self,
+        doc: impl Into<String>,
+    ) -> Self {
+        self.doc = doc.into();
+        self
+    }
+
+    /// Помечает команду как зарегистрированную модулем `module`.
+    pub fn module(
+        mut self,
+        module: impl Into<String>,
+    ) -> Self {
+        self.module = Some(module.into());
+        self
+    }
+
+    /// Возвращает `true`, если у команды есть флаг `flag`.
+    pub fn has_flag(
+        &self,
+        flag: CommandFlag,
+    ) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// Преобразует описание в кортеж `COMMAND INFO`: имя, арность, флаги,
+    /// первый ключ, последний ключ и шаг.
+    pub fn to_value(&self) -> Value {
+        Value::Array(vec![
+            Value::Str(Sds::from_str(&self.name)),
+            Value::Int(self.arity),
+            Value::Array(
+                self.flags
+                    .iter()
+                    .map(|flag| Value::Str(Sds::from_str(flag.as_str())))
+                    .collect(),
+            ),
+            Value::Int(self.first_key),
+            Value::Int(self.last_key),
+            Value::Int(self.step),
+        ])
+    }
+
+    /// Возвращает ACL-категории команды (`read`, `write`, `admin`),
+    /// выведенные из её флагов.
+    pub fn acl_categories(&self) -> Vec<&'static str> {
+        [
+            (CommandFlag::Readonly, "read"),
+            (CommandFlag::Write, "write"),
+            (CommandFlag::Admin, "admin"),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| self.has_flag(*flag))
+        .map(|(_, category)| category)
+        .collect()
+    }
+
+    /// Проверяет, что `argc` аргументов (вместе с именем) соответствует
+    /// арности команды.
+    pub fn check_arity(
+        &self,
+        argc: usize,
+    ) -> bool {
+        match usize::try_from(self.arity.unsigned_abs()) {
+            Ok(arity) if self.arity >= 0 => argc == arity,
+            Ok(arity) => argc >= arity,
+            Err(_) => false,
+        }
+    }
+
+    /// Возвращает позиции ключей в вызове из `argc` аргументов (вместе с
+    /// именем).
+    ///
+    /// # Возвращает
+    /// - индексы ключей по возрастанию; пустой вектор, если у команды нет
+    ///   ключей или аргументов не хватает
+    pub fn key_positions(
+        &self,
+        argc: usize,
+    ) -> Vec<usize> {
+        if self.first_key <= 0 || self.step <= 0 {
+            return Vec::new();
+        }
+        let argc = argc as i64;
+        let last = if self.last_key < 0 {
+            argc + self.last_key
+        } else {
+            self.last_key.min(argc - 1)
+        };
+        (self.first_key..=last)
+            .step_by(self.step as usize)
+            .map(|pos| pos as usize)
+            .collect()
+    }
+}
+
+impl CommandTable {
+    /// Создаёт пустую таблицу.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Создаёт таблицу встроенных команд сервера.
+    pub fn builtin() -> Self {
+        let mut table = Self::new();
+        for spec in builtin_specs() {
+            table.insert(spec);
+        }
+        table
+    }
+
+    /// Добавляет описание команды; прежнее описание с тем же именем
+    /// заменяется.
+    pub fn insert(
+        &mut self,
+        spec: CommandSpec,
+    ) {
+        self.specs.insert(spec.name.clone(), spec);
+    }
+
+    /// Возвращает описание команды `name` (без учёта регистра).
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<&CommandSpec> {
+        self.specs.get(&name.to_ascii_lowercase())
+    }
+
+    /// Возвращает число команд.
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    /// Возвращает `true`, если таблица пуста.
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    /// Возвращает описания команд, упорядоченные по имени.
+    pub fn iter(&self) -> impl Iterator<Item = &CommandSpec> {
+        self.specs.values()
+    }
+
+    /// Возвращает имена команд, подходящих под `filter` (`COMMAND LIST`).
+    pub fn list(
+        &self,
+        filter: Option<&CommandFilter>,
+    ) -> Vec<&str> {
+        self.iter()
+            .filter(|spec| match filter {
+                None => true,
+                Some(CommandFilter::Module(module)) => spec.module.as_deref() == Some(module),
+                Some(CommandFilter::AclCategory(category)) => spec
+                    .acl_categories()
+                    .iter()
+                    .any(|c| c.eq_ignore_ascii_case(category)),
+                Some(CommandFilter::Pattern(pattern)) => glob_match(
+                    pattern.to_ascii_lowercase().as_bytes(),
+                    spec.name.as_bytes(),
+                ),
+            })
+            .map(|spec| spec.name.as_str())
+            .collect()
+    }
+
+    /// Возвращает ключи вызова `args` (первый элемент — имя команды).
+    ///
+    /// # Возвращает
+    /// - `Ok(keys)` — аргументы, являющиеся ключами
+    /// - `Err(GetKeysError)` — если команда неизвестна, арность не соблюдена
+    ///   или у команды нет ключей
+    pub fn get_keys<'a, S: AsRef<str>>(
+        &self,
+        args: &'a [S],
+    ) -> Result<Vec<&'a str>, GetKeysError> {
+        let spec = args
+            .first()
+            .and_then(|name| self.get(name.as_ref()))
+            .ok_or(GetKeysError::UnknownCommand)?;
+        if !spec.check_arity(args.len()) {
+            return Err(GetKeysError::WrongArity);
+        }
+        let keys: Vec<&str> = spec
+            .key_positions(args.len())
+            .into_iter()
+            .filter_map(|pos| args.get(pos).map(AsRef::as_ref))
+            .collect();
+        if keys.is_empty() {
+            return Err(GetKeysError::NoKeys);
+        }
+        Ok(keys)
+    }
+}
+
 impl CommandRegistry {
     pub fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            table: CommandTable::new(),
         }
     }
 
-    /// Регистрирует команду `name` с обработчиком `h`.
+    /// Регистрирует команду, описанную `spec`, с обработчиком `h`.
     pub fn register<F>(
         &mut self,
-        name: impl Into<String>,
+        spec: CommandSpec,
         h: F,
     ) where
         F: Fn(&mut DbContext, &[u8]) -> Vec<u8> + Send + Sync + 'static,
     {
-        self.handlers.insert(name.into(), Box::new(h));
+        self.handlers.insert(spec.name.clone(), Box::new(h));
+        self.table.insert(spec);
+    }
+
+    /// Возвращает метаданные зарегистрированных команд.
+    pub fn table(&self) -> &CommandTable {
+        &self.table
     }
 
-    /// Вызывает handler для `name`. Паникует, если команда не найдена.
+    /// Вызывает handler для `name` (без учёта регистра). Паникует, если
+    /// команда не найдена.
     pub fn call(
         &self,
         name: &str,
@@ -40,26 +402,31 @@ impl CommandRegistry {
     ) -> Vec<u8> {
         let h = self
             .handlers
-            .get(name)
+            .get(&name.to_ascii_lowercase())
             .unwrap_or_else(|| panic!("Unknown command: {name}"));
         h(ctx, data)
     }
 
     /// Регистрирует все стандартные команды ZSP.
     pub fn register_builtin_commands(&mut self) {
-        use crate::{Sds, Value};
+        let spec = |name: &str| {
+            builtin_commands()
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| panic!("Missing builtin spec: {name}"))
+        };
 
         // === PING ===
-        self.register("PING", |_ctx, _| b"+PONG\r\n".to_vec());
+        self.register(spec("PING"), |_ctx, _| b"+PONG\r\n".to_vec());
 
         // === ECHO ===
-        self.register("ECHO", |_ctx, data| {
+        self.register(spec("ECHO"), |_ctx, data| {
             let val = Value::from_bytes(data).unwrap_or(Value::Null);
             val.to_bytes()
         });
 
         // === SADD ===
-        self.register("SADD", |ctx, data| {
+        self.register(spec("SADD"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.len() < 2 {
@@ -75,7 +442,7 @@ impl CommandRegistry {
         });
 
         // === SMEMBERS ===
-        self.register("SMEMBERS", |ctx, data| {
+        self.register(spec("SMEMBERS"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.len() != 1 {
@@ -88,7 +455,7 @@ impl CommandRegistry {
         });
 
         // === SCARD ===
-        self.register("SCARD", |ctx, data| {
+        self.register(spec("SCARD"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.len() != 1 {
@@ -100,7 +467,7 @@ impl CommandRegistry {
         });
 
         // === SREM ===
-        self.register("SREM", |ctx, data| {
+        self.register(spec("SREM"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.len() < 2 {
@@ -116,7 +483,7 @@ impl CommandRegistry {
         });
 
         // === SISMEMBER ===
-        self.register("SISMEMBER", |ctx, data| {
+        self.register(spec("SISMEMBER"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.len() != 2 {
@@ -129,7 +496,7 @@ impl CommandRegistry {
         });
 
         // === SRANDMEMBER ===
-        self.register("SRANDMEMBER", |ctx, data| {
+        self.register(spec("SRANDMEMBER"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.is_empty() {
@@ -149,7 +516,7 @@ impl CommandRegistry {
         });
 
         // === SPOP ===
-        self.register("SPOP", |ctx, data| {
+        self.register(spec("SPOP"), |ctx, data| {
             let args = Value::from_bytes(data).unwrap();
             let arr = args.as_array().unwrap();
             if arr.is_empty() {
@@ -170,6 +537,233 @@ impl CommandRegistry {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Возвращает описания встроенных команд сервера.
+fn builtin_specs() -> Vec<CommandSpec> {
+    use CommandFlag::*;
+
+    vec![
+        // Соединение
+        CommandSpec::new("auth", -2)
+            .flags(&[NoScript, Loading, Stale, Fast, NoAuth])
+            .doc("Authenticates the connection."),
+        CommandSpec::new("command", -1)
+            .flags(&[Loading, Stale])
+            .doc("Returns detailed information about commands."),
+        CommandSpec::new("echo", 2)
+            .flags(&[Fast])
+            .doc("Returns the given string."),
+        CommandSpec::new("hello", -1)
+            .flags(&[NoScript, Loading, Stale, Fast, NoAuth])
+            .doc("Handshakes with the server and negotiates the protocol."),
+        CommandSpec::new("ping", -1)
+            .flags(&[Fast])
+            .doc("Returns the server's liveliness response."),
+        CommandSpec::new("quit", -1)
+            .flags(&[NoScript, Loading, Stale, Fast, NoAuth])
+            .doc("Closes the connection."),
+        CommandSpec::new("reset", 1)
+            .flags(&[NoScript, Loading, Stale, Fast, NoAuth])
+            .doc("Resets the connection."),
+        CommandSpec::new("select", 2)
+            .flags(&[Loading, Stale, Fast])
+            .doc("Changes the selected database."),
+        // Строки и ключи
+        CommandSpec::new("del", -2)
+            .flags(&[Write])
+            .keys(1, -1, 1)
+            .doc("Deletes one or more keys."),
+        CommandSpec::new("get", 2)
+            .flags(&[Readonly, Fast])
+            .keys(1, 1, 1)
+            .doc("Returns the string value of a key."),
+        CommandSpec::new("getdel", 2)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .doc("Returns the string value of a key after deleting the key."),
+        CommandSpec::new("mget", -2)
+            .flags(&[Readonly, Fast])
+            .keys(1, -1, 1)
+            .doc("Atomically returns the string values of one or more keys."),
+        CommandSpec::new("mset", -3)
+            .flags(&[Write, DenyOom])
+            .keys(1, -1, 2)
+            .doc("Atomically creates or modifies the string values of one or more keys."),
+        CommandSpec::new("object", -2)
+            .flags(&[Readonly])
+            .keys(2, 2, 1)
+            .doc("Returns the internal encoding of a key's value."),
+        CommandSpec::new("rename", 3)
+            .flags(&[Write])
+            .keys(1, 2, 1)
+            .doc("Renames a key and overwrites the destination."),
+        CommandSpec::new("renamenx", 3)
+            .flags(&[Write, Fast])
+            .keys(1, 2, 1)
+            .doc("Renames a key only when the target key name doesn't exist."),
+        CommandSpec::new("set", -3)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .doc("Sets the string value of a key."),
+        CommandSpec::new("setnx", 3)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .doc("Sets the string value of a key only when the key doesn't exist."),
+        CommandSpec::new("type", 2)
+            .flags(&[Readonly, Fast])
+            .keys(1, 1, 1)
+            .doc("Determines the type of value stored at a key."),
+        // Гео-индексы
+        CommandSpec::new("geoadd", -5)
+            .flags(&[Write, DenyOom])
+            .keys(1, 1, 1)
+            .doc("Adds one or more members to a geospatial index."),
+        CommandSpec::new("geodist", -4)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns the distance between two members of a geospatial index."),
+        CommandSpec::new("geopos", -2)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns the longitude and latitude of members from a geospatial index."),
+        CommandSpec::new("georadius", -5)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Queries a geospatial index for members within a distance from a coordinate."),
+        // Множества
+        CommandSpec::new("sadd", -3)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .doc("Adds one or more members to a set."),
+        CommandSpec::new("scard", 2)
+            .flags(&[Readonly, Fast])
+            .keys(1, 1, 1)
+            .doc("Returns the number of members in a set."),
+        CommandSpec::new("sismember", 3)
+            .flags(&[Readonly, Fast])
+            .keys(1, 1, 1)
+            .doc("Determines whether a member belongs to a set."),
+        CommandSpec::new("smembers", 2)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns all members of a set."),
+        CommandSpec::new("spop", -2)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .doc("Returns one or more random members from a set after removing them."),
+        CommandSpec::new("srandmember", -2)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns one or more random members from a set."),
+        CommandSpec::new("srem", -3)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .doc("Removes one or more members from a set."),
+        // Потоки
+        CommandSpec::new("xadd", -5)
+            .flags(&[Write, DenyOom, Fast])
+            .keys(1, 1, 1)
+            .doc("Appends a new message to a stream. Creates the key if it doesn't exist."),
+        CommandSpec::new("xautoclaim", -6)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .doc("Changes, or acquires, ownership of messages in a consumer group, as if the messages were delivered to a consumer group member."),
+        CommandSpec::new("xclaim", -6)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .doc("Changes, or acquires, ownership of a message in a consumer group, as if the message was delivered to a consumer group member."),
+        CommandSpec::new("xdel", -3)
+            .flags(&[Write, Fast])
+            .keys(1, 1, 1)
+            .doc("Returns the number of messages after removing them from a stream."),
+        CommandSpec::new("xgroup", -2)
+            .flags(&[Write])
+            .doc("Manages consumer groups of a stream."),
+        CommandSpec::new("xinfo", -2)
+            .flags(&[Readonly])
+            .doc("Returns information about a stream, its consumer groups or consumers."),
+        CommandSpec::new("xlen", 2)
+            .flags(&[Readonly, Fast])
+            .keys(1, 1, 1)
+            .doc("Returns the number of messages in a stream."),
+        CommandSpec::new("xread", -4)
+            .flags(&[Readonly, Blocking])
+            .doc(
+                "Returns messages from multiple streams with IDs greater than the ones requested.",
+            ),
+        CommandSpec::new("xpending", -3)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns the information and entries from a stream consumer group's pending entries list."),
+        CommandSpec::new("xrange", -4)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns the messages from a stream within a range of IDs."),
+        CommandSpec::new("xrevrange", -4)
+            .flags(&[Readonly])
+            .keys(1, 1, 1)
+            .doc("Returns the messages from a stream within a range of IDs in reverse order."),
+        CommandSpec::new("xtrim", -4)
+            .flags(&[Write])
+            .keys(1, 1, 1)
+            .doc("Deletes messages from the beginning of a stream."),
+        // Pub/Sub
+        CommandSpec::new("psubscribe", -2)
+            .flags(&[PubSub, NoScript, Loading, Stale])
+            .doc("Listens for messages published to channels that match one or more patterns."),
+        CommandSpec::new("publish", 3)
+            .flags(&[PubSub, Loading, Stale, Fast])
+            .doc("Posts a message to a channel."),
+        CommandSpec::new("pubsub", -2)
+            .flags(&[PubSub, Loading, Stale])
+            .doc("Inspects the state of the Pub/Sub subsystem."),
+        CommandSpec::new("punsubscribe", -1)
+            .flags(&[PubSub, NoScript, Loading, Stale])
+            .doc("Stops listening to messages published to channels that match patterns."),
+        CommandSpec::new("subscribe", -2)
+            .flags(&[PubSub, NoScript, Loading, Stale])
+            .doc("Listens for messages published to channels."),
+        CommandSpec::new("unsubscribe", -1)
+            .flags(&[PubSub, NoScript, Loading, Stale])
+            .doc("Stops listening to messages posted to channels."),
+        // Транзакции
+        CommandSpec::new("discard", 1)
+            .flags(&[NoScript, Loading, Stale, Fast])
+            .doc("Discards a transaction."),
+        CommandSpec::new("exec", 1)
+            .flags(&[NoScript, Loading, Stale])
+            .doc("Executes all commands in a transaction."),
+        CommandSpec::new("multi", 1)
+            .flags(&[NoScript, Loading, Stale, Fast])
+            .doc("Starts a transaction."),
+        // Администрирование
+        CommandSpec::new("client", -2)
+            .flags(&[Admin, NoScript, Loading, Stale])
+            .doc("Inspects and manages client connections."),
+        CommandSpec::new("config", -2)
+            .flags(&[Admin, NoScript, Loading, Stale])
+            .doc("Reads and changes runtime configuration parameters."),
+        CommandSpec::new("flushall", -1)
+            .flags(&[Write])
+            .doc("Removes all keys from all databases."),
+        CommandSpec::new("flushdb", -1)
+            .flags(&[Write])
+            .doc("Removes all keys from the current database."),
+        CommandSpec::new("monitor", 1)
+            .flags(&[Admin, NoScript, Loading, Stale])
+            .doc("Listens for all requests received by the server in real time."),
+        CommandSpec::new("server", 2)
+            .flags(&[Admin, Loading, Stale])
+            .doc("Returns server connection statistics."),
+        CommandSpec::new("slowlog", -2)
+            .flags(&[Admin, Loading, Stale])
+            .doc("Inspects and resets the slow log."),
+    ]
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для CommandRegistry, ConnectionRegistry
 ////////////////////////////////////////////////////////////////////////////////
@@ -187,7 +781,6 @@ impl Default for CommandRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Sds, Value};
 
     /// Тест проверяет базовую регистрацию и вызов простой команды (`ping`),
     /// возвращающей предопределённый результат.
@@ -196,7 +789,7 @@ mod tests {
         let mut registry = CommandRegistry::new();
         let mut ctx = DbContext::new_inmemory();
 
-        registry.register("ping", |_ctx, _data| b"pong".to_vec());
+        registry.register(CommandSpec::new("ping", 1), |_ctx, _data| b"pong".to_vec());
 
         let result = registry.call("ping", &mut ctx, b"");
         assert_eq!(result, b"pong");
@@ -209,7 +802,7 @@ mod tests {
         let mut registry = CommandRegistry::new();
         let mut ctx = DbContext::new_inmemory();
 
-        registry.register("echo", |_ctx, data| {
+        registry.register(CommandSpec::new("echo", 2), |_ctx, data| {
             let mut out = b"echo: ".to_vec();
             out.extend_from_slice(data);
             out
@@ -227,14 +820,14 @@ mod tests {
         let mut registry = CommandRegistry::new();
         let mut ctx = DbContext::new_inmemory();
 
-        registry.register("store", |ctx, data| {
+        registry.register(CommandSpec::new("store", 2), |ctx, data| {
             let key = Sds::from(b"mykey".as_ref());
             let value = Value::from_bytes(data).unwrap();
             ctx.set(key, value).unwrap();
             b"OK".to_vec()
         });
 
-        registry.register("load", |ctx, _| {
+        registry.register(CommandSpec::new("load", 1), |ctx, _| {
             let key = Sds::from(b"mykey".as_ref());
             if let Ok(Some(v)) = ctx.get(key) {
                 v.to_bytes()
@@ -261,4 +854,73 @@ mod tests {
         let mut ctx = DbContext::new_inmemory();
         registry.call("missing", &mut ctx, b"");
     }
+
+    /// Тест проверяет, что зарегистрированная команда попадает в таблицу
+    /// метаданных, а вызов не зависит от регистра имени.
+    #[test]
+    fn test_register_records_spec() {
+        let mut registry = CommandRegistry::new();
+        let mut ctx = DbContext::new_inmemory();
+
+        registry.register(
+            CommandSpec::new("MOD.GET", 2)
+                .flags(&[CommandFlag::Readonly])
+                .keys(1, 1, 1)
+                .module("mod"),
+            |_ctx, _| b"ok".to_vec(),
+        );
+
+        assert_eq!(registry.call("mod.get", &mut ctx, b""), b"ok");
+        let spec = registry.table().get("MOD.GET").unwrap();
+        assert_eq!(spec.name, "mod.get");
+        assert_eq!(spec.acl_categories(), ["read"]);
+        let filter = CommandFilter::Module("mod".into());
+        assert_eq!(registry.table().list(Some(&filter)), ["mod.get"]);
+    }
+
+    /// Тест проверяет арность и извлечение ключей по позициям, включая
+    /// отрицательный `last_key` и шаг.
+    #[test]
+    fn test_get_keys_by_positions() {
+        let table = builtin_commands();
+
+        assert_eq!(
+            table.get_keys(&["MSET", "a", "1", "b", "2"]),
+            Ok(vec!["a", "b"])
+        );
+        assert_eq!(
+            table.get_keys(&["del", "a", "b", "c"]),
+            Ok(vec!["a", "b", "c"])
+        );
+        assert_eq!(table.get_keys(&["object", "encoding", "k"]), Ok(vec!["k"]));
+        assert_eq!(table.get_keys(&["get"]), Err(GetKeysError::WrongArity));
+        assert_eq!(table.get_keys(&["ping"]), Err(GetKeysError::NoKeys));
+        assert_eq!(
+            table.get_keys(&["nope", "k"]),
+            Err(GetKeysError::UnknownCommand)
+        );
+
+        let get = table.get("get").unwrap();
+        assert!(get.check_arity(2) && !get.check_arity(3));
+        let set = table.get("set").unwrap();
+        assert!(set.check_arity(5) && !set.check_arity(2));
+    }
+
+    /// Тест проверяет фильтры `COMMAND LIST` по ACL-категории и шаблону.
+    #[test]
+    fn test_list_filters() {
+        let table = builtin_commands();
+
+        let admin = table.list(Some(&CommandFilter::AclCategory("ADMIN".into())));
+        assert!(admin.contains(&"config") && admin.contains(&"client"));
+        assert!(!admin.contains(&"get"));
+
+        let pattern = table.list(Some(&CommandFilter::Pattern("s*member*".into())));
+        assert_eq!(pattern, ["sismember", "smembers", "srandmember"]);
+
+        assert_eq!(table.list(None).len(), table.len());
+        assert!(table
+            .list(Some(&CommandFilter::Module("missing".into())))
+            .is_empty());
+    }
 }
@@ -34,6 +34,11 @@
 //! - `settings::StorageType` — enum с вариантами `memory`, `persistent`,
 //!   `cluster`.
 //! - `settings::StorageConfig` — производная конфигурация для слоя хранения.
+//! - `settings::RuntimeConfig` — разделяемые параметры, изменяемые во время
+//!   работы через `CONFIG SET`.
+//! - `reload::SettingsReloader` — перезагрузка настроек без перезапуска (по
+//!   `SIGHUP`), `reload::SharedSettings` — текущие настройки для сервера и
+//!   фоновых задач.
 //! - `LoggingConfig` (импортируется/используется внутри `settings`) —
 //!   расширенные параметры логирования.
 //!
@@ -59,4 +64,5 @@
 //! - Расширение конфигурации (новые поля) следует добавлять в `Settings` и
 //!   документировать в `src/config/default.toml`.
 
+pub mod reload;
 pub mod settings;
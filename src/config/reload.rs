@@ -0,0 +1,371 @@
+//! Перезагрузка настроек без перезапуска сервера.
+//!
+//! Текущие [`Settings`] хранятся в [`SharedSettings`]: сервер и фоновые
+//! задачи читают их без блокировок, а [`SettingsReloader`] атомарно
+//! подменяет их новой версией (по `SIGHUP` в `main.rs`).
+//!
+//! Применяются только неразрушающие изменения: уровень логирования, лимиты
+//! соединений, таймауты и параметры брокера Pub/Sub. Изменение
+//! `listen_address` и `storage_type` требует перезапуска, поэтому
+//! логируется как предупреждение и игнорируется.
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use config::ConfigError;
+use tracing::{info, warn};
+
+use super::settings::{RuntimeConfig, RuntimeParams, Settings};
+use crate::{logging::LogFilterHandle, Broker};
+
+/// Настройки, разделяемые между сервером и фоновыми задачами.
+pub type SharedSettings = Arc<ArcSwap<Settings>>;
+
+/// Параметры [`RuntimeParams`], которые обновляются при перезагрузке.
+const RELOADABLE_PARAMS: [&str; 6] = [
+    "max_connections",
+    "max_connections_per_ip",
+    "connection_timeout",
+    "read_timeout",
+    "write_timeout",
+    "log_level",
+];
+
+/// Изменение одного параметра настроек.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    /// Имя параметра.
+    pub name: &'static str,
+    /// Прежнее значение.
+    pub old: String,
+    /// Новое значение.
+    pub new: String,
+}
+
+/// Итог перезагрузки настроек.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// Применённые изменения.
+    pub applied: Vec<SettingChange>,
+    /// Изменения, требующие перезапуска; они проигнорированы.
+    pub ignored: Vec<SettingChange>,
+}
+
+/// Применяет новые настройки к работающему серверу.
+///
+/// Лимиты и таймауты обновляются в [`RuntimeConfig`], который читают
+/// соединения; уровень логирования — через [`LogFilterHandle`]; параметры
+/// Pub/Sub — в [`Broker`].
+pub struct SettingsReloader {
+    /// Текущие настройки.
+    settings: SharedSettings,
+    /// Параметры, изменяемые во время работы.
+    runtime: RuntimeConfig,
+    /// Брокер Pub/Sub.
+    broker: Option<Arc<Broker>>,
+    /// Фильтр уровней логирования.
+    log_filter: Option<LogFilterHandle>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl ReloadReport {
+    /// Возвращает `true`, если настройки не изменились.
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.ignored.is_empty()
+    }
+}
+
+impl SettingsReloader {
+    /// Создаёт перезагрузчик для настроек `settings` и параметров `runtime`.
+    pub fn new(
+        settings: SharedSettings,
+        runtime: RuntimeConfig,
+    ) -> Self {
+        Self {
+            settings,
+            runtime,
+            broker: None,
+            log_filter: None,
+        }
+    }
+
+    /// Добавляет брокер, к которому применяется секция `[pubsub]`.
+    pub fn with_broker(
+        mut self,
+        broker: Arc<Broker>,
+    ) -> Self {
+        self.broker = Some(broker);
+        self
+    }
+
+    /// Добавляет фильтр логирования, к которому применяется
+    /// `logging.level`.
+    pub fn with_log_filter(
+        mut self,
+        log_filter: Option<LogFilterHandle>,
+    ) -> Self {
+        self.log_filter = log_filter;
+        self
+    }
+
+    /// Перечитывает настройки через [`Settings::load`] и применяет их.
+    ///
+    /// # Возвращает
+    /// - `Ok(ReloadReport)` — применённые и проигнорированные изменения
+    /// - `Err(ConfigError)` — если настройки не загрузились или содержат
+    ///   недопустимые значения; текущие настройки не меняются
+    pub fn reload(&self) -> Result<ReloadReport, ConfigError> {
+        self.apply(Settings::load()?)
+    }
+
+    /// Применяет настройки `new` вместо текущих и логирует изменения.
+    ///
+    /// # Возвращает
+    /// - `Ok(ReloadReport)` — применённые и проигнорированные изменения
+    /// - `Err(ConfigError)` — если значения недопустимы; текущие настройки не
+    ///   меняются
+    pub fn apply(
+        &self,
+        mut new: Settings,
+    ) -> Result<ReloadReport, ConfigError> {
+        let old = self.settings.load_full();
+        let mut report = ReloadReport::default();
+
+        let (before, after) = (
+            RuntimeParams::from_settings(&old),
+            RuntimeParams::from_settings(&new),
+        );
+        for name in RELOADABLE_PARAMS {
+            push_change(&mut report.applied, name, before.get(name), after.get(name));
+        }
+        let changes: Vec<(&str, &str)> = report
+            .applied
+            .iter()
+            .map(|change| (change.name, change.new.as_str()))
+            .collect();
+        self.runtime.set(&changes)?;
+
+        if old.logging.level != new.logging.level {
+            if let Some(filter) = &self.log_filter {
+                filter.apply(&new.logging).map_err(ConfigError::Message)?;
+            }
+        }
+
+        let pubsub_changes = [
+            (
+                "pubsub.channel_capacity",
+                old.pubsub.channel_capacity,
+                new.pubsub.channel_capacity,
+            ),
+            (
+                "pubsub.max_subscribers_per_channel",
+                old.pubsub.max_subscribers_per_channel,
+                new.pubsub.max_subscribers_per_channel,
+            ),
+            (
+                "pubsub.max_message_size",
+                old.pubsub.max_message_size,
+                new.pubsub.max_message_size,
+            ),
+        ];
+        let applied_before = report.applied.len();
+        for (name, old, new) in pubsub_changes {
+            push_change(&mut report.applied, name, old, new);
+        }
+        if report.applied.len() > applied_before {
+            if let Some(broker) = &self.broker {
+                broker.set_config(new.pubsub.broker_config());
+            }
+        }
+
+        push_change(
+            &mut report.ignored,
+            "listen_address",
+            Some(old.listen_address),
+            Some(new.listen_address),
+        );
+        push_change(
+            &mut report.ignored,
+            "storage_type",
+            Some(format!("{:?}", old.storage_type).to_lowercase()),
+            Some(format!("{:?}", new.storage_type).to_lowercase()),
+        );
+        new.listen_address = old.listen_address;
+        new.storage_type = old.storage_type.clone();
+
+        self.settings.store(Arc::new(new));
+
+        for change in &report.applied {
+            info!(
+                "Config reloaded: {} changed from {} to {}",
+                change.name, change.old, change.new
+            );
+        }
+        for change in &report.ignored {
+            warn!(
+                "Config reload: {} changed from {} to {}, but requires a restart; ignored",
+                change.name, change.old, change.new
+            );
+        }
+        if report.is_empty() {
+            info!("Config reloaded: no changes");
+        }
+        Ok(report)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Добавляет в `changes` изменение параметра `name`, если значение
+/// изменилось. Незаданное значение выводится как `default`.
+fn push_change<T: ToString + PartialEq>(
+    changes: &mut Vec<SettingChange>,
+    name: &'static str,
+    old: Option<T>,
+    new: Option<T>,
+) {
+    if old == new {
+        return;
+    }
+    let show = |value: Option<T>| value.map_or_else(|| "default".to_string(), |v| v.to_string());
+    changes.push(SettingChange {
+        name,
+        old: show(old),
+        new: show(new),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use config::Config;
+
+    use super::*;
+    use crate::BrokerConfig;
+
+    /// Собирает настройки из пар `(ключ, значение)` без файлов и env.
+    fn settings(overrides: &[(&str, &str)]) -> Settings {
+        overrides
+            .iter()
+            .fold(Config::builder(), |builder, (key, value)| {
+                builder.set_override(*key, *value).unwrap()
+            })
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    fn reloader(initial: Settings) -> (SettingsReloader, RuntimeConfig, Arc<Broker>) {
+        let runtime = RuntimeConfig::new(RuntimeParams::from_settings(&initial));
+        let broker = Arc::new(Broker::new());
+        let shared = Arc::new(ArcSwap::from_pointee(initial));
+        let reloader = SettingsReloader::new(shared, runtime.clone()).with_broker(broker.clone());
+        (reloader, runtime, broker)
+    }
+
+    /// Тест проверяет, что лимиты, таймауты, уровень логирования и параметры
+    /// Pub/Sub применяются к работающему серверу и попадают в отчёт.
+    #[test]
+    fn test_reload_applies_runtime_changes() {
+        let (reloader, runtime, broker) = reloader(settings(&[]));
+
+        let report = reloader
+            .apply(settings(&[
+                ("max_connections", "500"),
+                ("read_timeout", "7"),
+                ("logging.level", "debug"),
+                ("pubsub.max_message_size", "64"),
+            ]))
+            .unwrap();
+
+        let names: Vec<_> = report.applied.iter().map(|c| c.name).collect();
+        assert_eq!(
+            names,
+            [
+                "max_connections",
+                "read_timeout",
+                "log_level",
+                "pubsub.max_message_size"
+            ]
+        );
+        assert!(report.ignored.is_empty());
+        assert_eq!(report.applied[0].old, "100");
+        assert_eq!(report.applied[0].new, "500");
+
+        let params = runtime.snapshot();
+        assert_eq!(params.max_connections, 500);
+        assert_eq!(params.read_timeout, 7);
+        assert_eq!(params.log_level, "debug");
+        assert_eq!(broker.config().max_message_size, Some(64));
+        assert_eq!(
+            broker.config().default_channel_capacity,
+            BrokerConfig::default().default_channel_capacity
+        );
+        assert_eq!(reloader.settings.load().max_connections, 500);
+
+        assert!(reloader
+            .apply(settings(&[
+                ("max_connections", "500"),
+                ("read_timeout", "7"),
+                ("logging.level", "debug"),
+                ("pubsub.max_message_size", "64"),
+            ]))
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Тест проверяет, что смена адреса и типа хранилища игнорируется, а
+    /// сохранённые настройки сохраняют прежние значения.
+    #[test]
+    fn test_reload_ignores_destructive_changes() {
+        let (reloader, runtime, _) = reloader(settings(&[]));
+        let before = runtime.snapshot();
+
+        let report = reloader
+            .apply(settings(&[
+                ("listen_address", "0.0.0.0:7000"),
+                ("storage_type", "persistent"),
+            ]))
+            .unwrap();
+
+        assert!(report.applied.is_empty());
+        let ignored: Vec<_> = report
+            .ignored
+            .iter()
+            .map(|c| (c.name, c.new.as_str()))
+            .collect();
+        assert_eq!(
+            ignored,
+            [
+                ("listen_address", "0.0.0.0:7000"),
+                ("storage_type", "persistent")
+            ]
+        );
+        let current = reloader.settings.load();
+        assert_eq!(current.listen_address.to_string(), "127.0.0.1:6174");
+        assert!(matches!(current.storage_type, crate::StorageType::Memory));
+        assert_eq!(runtime.snapshot(), before);
+    }
+
+    /// Тест проверяет, что недопустимое значение отклоняет перезагрузку
+    /// целиком: ни параметры, ни сохранённые настройки не меняются.
+    #[test]
+    fn test_reload_rejects_invalid_values() {
+        let (reloader, runtime, _) = reloader(settings(&[]));
+        let before = runtime.snapshot();
+
+        let result = reloader.apply(settings(&[
+            ("read_timeout", "3"),
+            ("max_connections_per_ip", "0"),
+        ]));
+
+        assert!(result.is_err());
+        assert_eq!(runtime.snapshot(), before);
+        assert_eq!(reloader.settings.load().read_timeout, None);
+    }
+}
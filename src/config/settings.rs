@@ -6,12 +6,19 @@
 //! - предоставление типобезопасного API для доступа к настройкам,
 //! - валидацию критичных параметров (например, логирования).
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 use config::{Config, ConfigError, Environment, File};
+use parking_lot::RwLock;
 use serde::Deserialize;
 
-use crate::logging::config::LoggingConfig;
+use crate::{
+    database::{DEFAULT_MAX_LISTPACK_ENTRIES, DEFAULT_MAX_LISTPACK_VALUE},
+    glob_match,
+    logging::{config::LoggingConfig, TelemetryConfig},
+    network::admin_api::AdminApiConfig,
+    BrokerConfig, EvictionPolicy,
+};
 
 /// Тип хранилища, используемого сервером.
 ///
@@ -34,6 +41,39 @@ pub enum StorageType {
     Cluster,
 }
 
+/// Проверка сертификатов клиентов при TLS-соединениях (mutual TLS).
+///
+/// Имеет смысл только вместе с `tls_ca_cert_file`: без корневого
+/// сертификата сертификаты клиентов не запрашиваются.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsAuthClients {
+    /// Клиент обязан предъявить сертификат, подписанный указанным CA.
+    #[default]
+    Yes,
+    /// Сертификат клиента не запрашивается.
+    No,
+    /// Сертификат проверяется, если клиент его предъявил.
+    Optional,
+}
+
+/// Параметры брокера Pub/Sub (`[pubsub]`).
+///
+/// Незаданные поля берутся из [`BrokerConfig::default`]. Параметры можно
+/// изменить без перезапуска через `SIGHUP`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PubSubSettings {
+    /// Ёмкость буфера новых каналов (сообщений).
+    #[serde(default)]
+    pub channel_capacity: Option<usize>,
+    /// Максимальное число подписчиков на канал.
+    #[serde(default)]
+    pub max_subscribers_per_channel: Option<usize>,
+    /// Максимальный размер сообщения в байтах.
+    #[serde(default)]
+    pub max_message_size: Option<usize>,
+}
+
 /// Конфигурация движка хранения (StorageEngine).
 ///
 /// Это производная структура, которая формируется на основе глобальных
@@ -44,6 +84,24 @@ pub struct StorageConfig {
     pub storage_type: StorageType,
 }
 
+impl PubSubSettings {
+    /// Собирает конфигурацию брокера, подставляя значения по умолчанию для
+    /// незаданных полей.
+    pub fn broker_config(&self) -> BrokerConfig {
+        let defaults = BrokerConfig::default();
+        BrokerConfig {
+            default_channel_capacity: self
+                .channel_capacity
+                .unwrap_or(defaults.default_channel_capacity),
+            max_subscribers_per_channel: self
+                .max_subscribers_per_channel
+                .or(defaults.max_subscribers_per_channel),
+            max_message_size: self.max_message_size.or(defaults.max_message_size),
+            ..defaults
+        }
+    }
+}
+
 impl StorageConfig {
     /// Создаёт конфигурацию хранилища на основе глобальных настроек приложения.
     pub fn new(settings: &Settings) -> Self {
@@ -94,6 +152,16 @@ where
     s.parse().map_err(serde::de::Error::custom)
 }
 
+/// Десериализация необязательного [`SocketAddr`] из строки.
+fn de_opt_socket_addr<'de, D>(deserializer: D) -> Result<Option<SocketAddr>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| s.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
 /// Строковое представление типа хранилища по умолчанию.
 fn default_storage_str() -> &'static str {
     "memory"
@@ -160,6 +228,47 @@ pub struct Settings {
     #[serde(default)]
     pub aof_path: Option<String>,
 
+    /// Путь до ACL-файла для `ACL SAVE` и `ACL LOAD`.
+    #[serde(default)]
+    pub aclfile: Option<String>,
+
+    /// PEM-файл с сертификатом сервера; вместе с `tls_key_file` включает
+    /// TLS для всех соединений (требует Cargo-фичу `tls`).
+    #[serde(default)]
+    pub tls_cert_file: Option<String>,
+
+    /// PEM-файл с закрытым ключом сервера.
+    #[serde(default)]
+    pub tls_key_file: Option<String>,
+
+    /// PEM-файл с корневыми сертификатами для проверки клиентов (mutual
+    /// TLS).
+    #[serde(default)]
+    pub tls_ca_cert_file: Option<String>,
+
+    /// Обязательность сертификата клиента при заданном `tls_ca_cert_file`.
+    #[serde(default)]
+    pub tls_auth_clients: TlsAuthClients,
+
+    /// Адрес HTTP-эндпоинта `GET /metrics` в формате Prometheus (требует
+    /// Cargo-фичу `metrics`); должен отличаться от `listen_address`.
+    #[serde(default, deserialize_with = "de_opt_socket_addr")]
+    pub metrics_bind: Option<SocketAddr>,
+
+    /// Флаги `notify-keyspace-events` (например, `"Ex"`); по умолчанию
+    /// уведомления о событиях пространства ключей выключены.
+    #[serde(default)]
+    pub notify_keyspace_events: Option<String>,
+
+    /// Максимальное число полей хеша в компактном представлении.
+    #[serde(default)]
+    pub hash_max_listpack_entries: Option<usize>,
+
+    /// Максимальная длина поля или значения хеша в компактном
+    /// представлении (байт).
+    #[serde(default)]
+    pub hash_max_listpack_value: Option<usize>,
+
     /// Частота создания снапшотов (секунды).
     #[serde(default)]
     pub snapshot_freq: Option<u64>,
@@ -181,6 +290,19 @@ pub struct Settings {
     /// Конфигурация логирования (новая, расширенная)
     #[serde(default)]
     pub logging: LoggingConfig,
+
+    /// Экспорт трассировки в OpenTelemetry (`[telemetry]`).
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+
+    /// Административный HTTP JSON API (`[admin]`, требует Cargo-фичу
+    /// `admin`).
+    #[serde(default)]
+    pub admin: AdminApiConfig,
+
+    /// Параметры брокера Pub/Sub (`[pubsub]`).
+    #[serde(default)]
+    pub pubsub: PubSubSettings,
 }
 
 impl Settings {
@@ -229,6 +351,238 @@ impl Settings {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Параметры, изменяемые во время работы
+////////////////////////////////////////////////////////////////////////////////
+
+/// Допустимые значения `log_level` для `CONFIG SET`.
+const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Часть настроек [`Settings`], которую можно изменить без перезапуска
+/// сервера (`CONFIG SET`).
+///
+/// Имена параметров совпадают с именами полей `Settings`; таймауты задаются в
+/// секундах. Параметры журнала медленных команд (`slowlog-*`), политика
+/// вытеснения (`maxmemory-policy`) и лимит времени скриптов
+/// (`lua-time-limit`) в `Settings` не входят и называются так же, как в Redis;
+/// так же называются лимиты компактных хешей (`hash-max-listpack-*`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeParams {
+    /// Максимальное число одновременно открытых соединений.
+    pub max_connections: usize,
+    /// Максимальное число соединений с одного IP.
+    pub max_connections_per_ip: usize,
+    /// Таймаут бездействия соединения.
+    pub connection_timeout: u64,
+    /// Таймаут чтения команды от клиента.
+    pub read_timeout: u64,
+    /// Таймаут записи ответа клиенту.
+    pub write_timeout: u64,
+    /// Уровень логирования.
+    pub log_level: String,
+    /// Порог попадания команды в slowlog в микросекундах; отрицательное
+    /// значение отключает журнал.
+    pub slowlog_log_slower_than: i64,
+    /// Максимальное число записей в slowlog.
+    pub slowlog_max_len: usize,
+    /// Политика вытеснения ключей.
+    pub maxmemory_policy: EvictionPolicy,
+    /// Максимальное время выполнения Lua-скрипта в миллисекундах.
+    pub lua_time_limit: u64,
+    /// Максимальное число полей хеша в компактном представлении.
+    pub hash_max_listpack_entries: usize,
+    /// Максимальная длина поля или значения хеша в компактном
+    /// представлении.
+    pub hash_max_listpack_value: usize,
+}
+
+/// Разделяемые между соединениями параметры времени выполнения.
+///
+/// Клонирование дёшево: все клоны ссылаются на одни и те же параметры, так
+/// что изменение через `CONFIG SET` сразу видно всем владельцам.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeConfig {
+    inner: Arc<RwLock<RuntimeParams>>,
+}
+
+impl RuntimeParams {
+    /// Имена всех параметров в порядке вывода `CONFIG GET`.
+    pub const NAMES: [&'static str; 12] = [
+        "max_connections",
+        "max_connections_per_ip",
+        "connection_timeout",
+        "read_timeout",
+        "write_timeout",
+        "log_level",
+        "slowlog-log-slower-than",
+        "slowlog-max-len",
+        "maxmemory-policy",
+        "lua-time-limit",
+        "hash-max-listpack-entries",
+        "hash-max-listpack-value",
+    ];
+
+    /// Собирает параметры из загруженных настроек, подставляя значения по
+    /// умолчанию для незаданных полей.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let defaults = Self::default();
+        Self {
+            max_connections: usize::try_from(settings.max_connections)
+                .unwrap_or(defaults.max_connections),
+            max_connections_per_ip: settings
+                .max_connections_per_ip
+                .unwrap_or(defaults.max_connections_per_ip),
+            connection_timeout: settings
+                .connection_timeout
+                .unwrap_or(defaults.connection_timeout),
+            read_timeout: settings.read_timeout.unwrap_or(defaults.read_timeout),
+            write_timeout: settings.write_timeout.unwrap_or(defaults.write_timeout),
+            log_level: settings.logging.level.clone(),
+            hash_max_listpack_entries: settings
+                .hash_max_listpack_entries
+                .unwrap_or(defaults.hash_max_listpack_entries),
+            hash_max_listpack_value: settings
+                .hash_max_listpack_value
+                .unwrap_or(defaults.hash_max_listpack_value),
+            ..defaults
+        }
+    }
+
+    /// Возвращает текущее значение параметра или `None` для неизвестного
+    /// имени.
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Option<String> {
+        let value = match name {
+            "max_connections" => self.max_connections.to_string(),
+            "max_connections_per_ip" => self.max_connections_per_ip.to_string(),
+            "connection_timeout" => self.connection_timeout.to_string(),
+            "read_timeout" => self.read_timeout.to_string(),
+            "write_timeout" => self.write_timeout.to_string(),
+            "log_level" => self.log_level.clone(),
+            "slowlog-log-slower-than" => self.slowlog_log_slower_than.to_string(),
+            "slowlog-max-len" => self.slowlog_max_len.to_string(),
+            "maxmemory-policy" => self.maxmemory_policy.to_string(),
+            "lua-time-limit" => self.lua_time_limit.to_string(),
+            "hash-max-listpack-entries" => self.hash_max_listpack_entries.to_string(),
+            "hash-max-listpack-value" => self.hash_max_listpack_value.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Изменяет один параметр, проверяя имя и значение.
+    fn set(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ConfigError> {
+        let invalid = || {
+            ConfigError::Message(format!(
+                "Invalid argument '{value}' for CONFIG SET '{name}'"
+            ))
+        };
+        let positive = |v: &str| v.parse::<u64>().ok().filter(|n| *n > 0).ok_or_else(invalid);
+
+        match name {
+            "max_connections" => self.max_connections = positive(value)? as usize,
+            "max_connections_per_ip" => self.max_connections_per_ip = positive(value)? as usize,
+            "connection_timeout" => self.connection_timeout = positive(value)?,
+            "read_timeout" => self.read_timeout = positive(value)?,
+            "write_timeout" => self.write_timeout = positive(value)?,
+            "log_level" => {
+                let level = value.to_lowercase();
+                if !LOG_LEVELS.contains(&level.as_str()) {
+                    return Err(invalid());
+                }
+                self.log_level = level;
+            }
+            "slowlog-log-slower-than" => {
+                self.slowlog_log_slower_than = value.parse().map_err(|_| invalid())?
+            }
+            "slowlog-max-len" => self.slowlog_max_len = value.parse().map_err(|_| invalid())?,
+            "maxmemory-policy" => self.maxmemory_policy = value.parse().map_err(|_| invalid())?,
+            "lua-time-limit" => self.lua_time_limit = positive(value)?,
+            "hash-max-listpack-entries" => {
+                self.hash_max_listpack_entries = value.parse().map_err(|_| invalid())?
+            }
+            "hash-max-listpack-value" => {
+                self.hash_max_listpack_value = value.parse().map_err(|_| invalid())?
+            }
+            _ => {
+                return Err(ConfigError::Message(format!(
+                    "Unknown option or number of arguments for CONFIG SET - '{name}'"
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RuntimeConfig {
+    /// Создаёт разделяемую конфигурацию с начальными параметрами.
+    pub fn new(params: RuntimeParams) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(params)),
+        }
+    }
+
+    /// Возвращает копию текущих параметров.
+    pub fn snapshot(&self) -> RuntimeParams {
+        self.inner.read().clone()
+    }
+
+    /// Возвращает пары `(имя, значение)` параметров, имена которых
+    /// соответствуют glob-шаблону `pattern` (`CONFIG GET`).
+    pub fn get_matching(
+        &self,
+        pattern: &str,
+    ) -> Vec<(&'static str, String)> {
+        let pattern = pattern.to_lowercase();
+        let params = self.inner.read();
+        RuntimeParams::NAMES
+            .into_iter()
+            .filter(|name| glob_match(pattern.as_bytes(), name.as_bytes()))
+            .filter_map(|name| params.get(name).map(|value| (name, value)))
+            .collect()
+    }
+
+    /// Атомарно применяет набор изменений (`CONFIG SET`): при ошибке в любом
+    /// из параметров ни одно значение не изменяется.
+    pub fn set(
+        &self,
+        changes: &[(&str, &str)],
+    ) -> Result<(), ConfigError> {
+        let mut params = self.inner.write();
+        let mut updated = params.clone();
+        for (name, value) in changes {
+            updated.set(&name.to_lowercase(), value)?;
+        }
+        *params = updated;
+        Ok(())
+    }
+}
+
+impl Default for RuntimeParams {
+    fn default() -> Self {
+        Self {
+            max_connections: default_max_connections() as usize,
+            max_connections_per_ip: 100,
+            connection_timeout: 300,
+            read_timeout: 30,
+            write_timeout: 10,
+            log_level: default_log_level(),
+            slowlog_log_slower_than: 10_000,
+            slowlog_max_len: 128,
+            maxmemory_policy: EvictionPolicy::default(),
+            lua_time_limit: 5_000,
+            hash_max_listpack_entries: DEFAULT_MAX_LISTPACK_ENTRIES,
+            hash_max_listpack_value: DEFAULT_MAX_LISTPACK_VALUE,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{env, fs, net::SocketAddr};
@@ -276,6 +630,38 @@ mod tests {
         assert!(matches!(settings.storage_type, StorageType::Memory));
         assert_eq!(settings.log_level, "info");
         assert_eq!(settings.thread_pool_size, num_cpus::get());
+        assert!(settings.metrics_bind.is_none());
+        assert!(!settings.admin.enabled);
+        assert_eq!(settings.admin.bind.port(), 6175);
+        assert_eq!(settings.pubsub, PubSubSettings::default());
+        assert_eq!(
+            settings.pubsub.broker_config().default_channel_capacity,
+            BrokerConfig::default().default_channel_capacity
+        );
+    }
+
+    /// Тест проверяет разбор адреса эндпоинта метрик.
+    #[test]
+    fn test_metrics_bind_deserialization() {
+        let settings: Settings = Config::builder()
+            .set_default("metrics_bind", "127.0.0.1:9174")
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(
+            settings.metrics_bind,
+            Some("127.0.0.1:9174".parse::<SocketAddr>().unwrap())
+        );
+
+        let invalid: Result<Settings, _> = Config::builder()
+            .set_default("metrics_bind", "not-an-address")
+            .unwrap()
+            .build()
+            .unwrap()
+            .try_deserialize();
+        assert!(invalid.is_err());
     }
 
     /// Тест проверяет десериализации SocketAddr
@@ -416,4 +802,120 @@ mod tests {
         let res = settings.logging.validate();
         assert!(res.is_ok());
     }
+
+    /// Тест проверяет CONFIG GET по шаблону и атомарность CONFIG SET: при
+    /// ошибке в одном параметре остальные не изменяются.
+    #[test]
+    fn test_runtime_config_get_and_set() {
+        let runtime = RuntimeConfig::new(RuntimeParams::default());
+        let shared = runtime.clone();
+
+        assert_eq!(
+            runtime.get_matching("*_timeout"),
+            vec![
+                ("connection_timeout", "300".to_string()),
+                ("read_timeout", "30".to_string()),
+                ("write_timeout", "10".to_string()),
+            ]
+        );
+        assert_eq!(runtime.get_matching("*").len(), RuntimeParams::NAMES.len());
+
+        runtime
+            .set(&[("MAX_CONNECTIONS", "5"), ("log_level", "DEBUG")])
+            .unwrap();
+        assert_eq!(shared.snapshot().max_connections, 5);
+        assert_eq!(shared.snapshot().log_level, "debug");
+
+        assert!(runtime
+            .set(&[("read_timeout", "7"), ("write_timeout", "0")])
+            .is_err());
+        assert!(runtime.set(&[("listen_address", "0.0.0.0:1")]).is_err());
+        assert_eq!(shared.snapshot().read_timeout, 30);
+    }
+
+    /// Тест проверяет параметры slowlog: отрицательный порог допустим,
+    /// нечисловые значения отклоняются.
+    #[test]
+    fn test_runtime_config_slowlog_params() {
+        let runtime = RuntimeConfig::new(RuntimeParams::default());
+
+        assert_eq!(
+            runtime.get_matching("slowlog-*"),
+            vec![
+                ("slowlog-log-slower-than", "10000".to_string()),
+                ("slowlog-max-len", "128".to_string()),
+            ]
+        );
+
+        runtime
+            .set(&[("slowlog-log-slower-than", "-1"), ("slowlog-max-len", "0")])
+            .unwrap();
+        assert_eq!(runtime.snapshot().slowlog_log_slower_than, -1);
+        assert_eq!(runtime.snapshot().slowlog_max_len, 0);
+
+        assert!(runtime.set(&[("slowlog-max-len", "-1")]).is_err());
+        assert!(runtime.set(&[("slowlog-log-slower-than", "fast")]).is_err());
+    }
+
+    /// Тест проверяет параметр maxmemory-policy: имя политики разбирается
+    /// без учёта регистра, неизвестные политики отклоняются.
+    #[test]
+    fn test_runtime_config_maxmemory_policy() {
+        let runtime = RuntimeConfig::new(RuntimeParams::default());
+
+        assert_eq!(
+            runtime.get_matching("maxmemory-*"),
+            vec![("maxmemory-policy", "noeviction".to_string())]
+        );
+
+        runtime.set(&[("maxmemory-policy", "ALLKEYS-LFU")]).unwrap();
+        assert_eq!(
+            runtime.snapshot().maxmemory_policy,
+            EvictionPolicy::AllKeysLfu
+        );
+        assert!(runtime.set(&[("maxmemory-policy", "random")]).is_err());
+    }
+
+    /// Тест проверяет параметры hash-max-listpack-*: значения по умолчанию
+    /// совпадают с лимитами `SmartHash`, допустим ноль, нечисловые значения
+    /// отклоняются.
+    #[test]
+    fn test_runtime_config_hash_max_listpack() {
+        let runtime = RuntimeConfig::new(RuntimeParams::default());
+
+        assert_eq!(
+            runtime.get_matching("hash-max-listpack-*"),
+            vec![
+                ("hash-max-listpack-entries", "128".to_string()),
+                ("hash-max-listpack-value", "64".to_string()),
+            ]
+        );
+
+        runtime
+            .set(&[
+                ("hash-max-listpack-entries", "0"),
+                ("hash-max-listpack-value", "16"),
+            ])
+            .unwrap();
+        let params = runtime.snapshot();
+        assert_eq!(params.hash_max_listpack_entries, 0);
+        assert_eq!(params.hash_max_listpack_value, 16);
+        assert!(runtime.set(&[("hash-max-listpack-value", "-1")]).is_err());
+    }
+
+    /// Тест проверяет параметр lua-time-limit: по умолчанию 5 секунд,
+    /// допустимы только положительные значения.
+    #[test]
+    fn test_runtime_config_lua_time_limit() {
+        let runtime = RuntimeConfig::new(RuntimeParams::default());
+
+        assert_eq!(
+            runtime.get_matching("lua-*"),
+            vec![("lua-time-limit", "5000".to_string())]
+        );
+
+        runtime.set(&[("lua-time-limit", "250")]).unwrap();
+        assert_eq!(runtime.snapshot().lua_time_limit, 250);
+        assert!(runtime.set(&[("lua-time-limit", "0")]).is_err());
+    }
 }
@@ -16,7 +16,9 @@ use std::ops::{BitAnd, BitOr, BitXor, Not};
 use serde::{Deserialize, Serialize};
 
 use crate::database::{
-    bitmap_simd::{bitcount_auto, bitcount_with_strategy, BitcountStrategy, CpuFeatures},
+    bitmap_simd::{
+        bitcount_auto, bitcount_with_strategy, find_byte_not_auto, BitcountStrategy, CpuFeatures,
+    },
     BIT_COUNT_TABLE,
 };
 
@@ -34,6 +36,16 @@ pub struct Bitmap {
     pub strategy: Option<BitcountStrategy>,
 }
 
+/// Единица измерения границ диапазона в `BITPOS`/`BITCOUNT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitUnit {
+    /// Границы заданы в байтах.
+    #[default]
+    Byte,
+    /// Границы заданы в битах.
+    Bit,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
@@ -111,6 +123,38 @@ impl Bitmap {
         (byte >> (7 - bit_index)) & 1 == 1
     }
 
+    /// Читает `width` битов (1..=64), начиная со смещения `offset`, как
+    /// беззнаковое число (старший бит — первый).
+    ///
+    /// Биты за границей массива считаются нулевыми.
+    pub fn get_bits(
+        &self,
+        offset: u64,
+        width: u8,
+    ) -> u64 {
+        debug_assert!((1..=64).contains(&width));
+        (0..width as u64).fold(0u64, |acc, i| {
+            (acc << 1) | self.get_bit((offset + i) as usize) as u64
+        })
+    }
+
+    /// Записывает младшие `width` битов (1..=64) значения `value`, начиная со
+    /// смещения `offset` (старший бит — первый).
+    ///
+    /// При необходимости массив расширяется автоматически.
+    pub fn set_bits(
+        &mut self,
+        offset: u64,
+        width: u8,
+        value: u64,
+    ) {
+        debug_assert!((1..=64).contains(&width));
+        for i in 0..width as u64 {
+            let bit = (value >> (width as u64 - 1 - i)) & 1 == 1;
+            self.set_bit((offset + i) as usize, bit);
+        }
+    }
+
     /// Подсчитывает количество установленных (`true`) битов в диапазоне
     /// `[start, end)`.
     ///
@@ -155,6 +199,78 @@ impl Bitmap {
         count + BIT_COUNT_TABLE[(self.bytes[end_byte] & last_mask) as usize] as usize
     }
 
+    /// Находит позицию первого бита со значением `bit` в диапазоне
+    /// `[start, end]` (включительно). Границы задаются в байтах или битах
+    /// согласно `unit`; отрицательные значения отсчитываются от конца.
+    ///
+    /// # Возвращает
+    /// Позицию бита от начала массива или `None`, если бит в диапазоне не
+    /// найден.
+    pub fn bitpos(
+        &self,
+        bit: bool,
+        start: i64,
+        end: i64,
+        unit: BitUnit,
+    ) -> Option<i64> {
+        let (start, end) = self.bit_range(start, end, unit)?;
+        let mut pos = start;
+
+        // Невыровненное начало проверяем побитово.
+        while pos <= end && pos % 8 != 0 {
+            if self.get_bit(pos) == bit {
+                return Some(pos as i64);
+            }
+            pos += 1;
+        }
+
+        // Целые байты внутри диапазона пропускаем через SIMD-поиск.
+        let first_byte = pos / 8;
+        let full_end = (end + 1) / 8;
+        if pos <= end && full_end > first_byte {
+            let skip = if bit { 0x00 } else { 0xFF };
+            match find_byte_not_auto(&self.bytes[first_byte..full_end], skip) {
+                Some(i) => {
+                    let byte = self.bytes[first_byte + i];
+                    let lead = if bit {
+                        byte.leading_zeros()
+                    } else {
+                        (!byte).leading_zeros()
+                    };
+                    return Some(((first_byte + i) * 8 + lead as usize) as i64);
+                }
+                None => pos = full_end * 8,
+            }
+        }
+
+        // Хвост диапазона.
+        while pos <= end {
+            if self.get_bit(pos) == bit {
+                return Some(pos as i64);
+            }
+            pos += 1;
+        }
+        None
+    }
+
+    /// Подсчитывает установленные биты в диапазоне `[start, end]`
+    /// (включительно). Границы задаются в байтах или битах согласно `unit`;
+    /// отрицательные значения отсчитываются от конца.
+    ///
+    /// Целые байты диапазона считаются через SIMD popcount, если он доступен,
+    /// иначе через таблицу поиска.
+    pub fn bit_count_range(
+        &self,
+        start: i64,
+        end: i64,
+        unit: BitUnit,
+    ) -> u64 {
+        match self.bit_range(start, end, unit) {
+            Some((start, end)) => self.bitcount(start, end + 1) as u64,
+            None => 0,
+        }
+    }
+
     /// Подсчитывает все установленные биты используя SIMD-ускорение
     pub fn bitcount_all(&self) -> usize {
         bitcount_auto(&self.bytes)
@@ -201,6 +317,38 @@ impl Bitmap {
         count + BIT_COUNT_TABLE[(self.bytes[end_byte] & last_mask) as usize] as usize
     }
 
+    /// Переводит диапазон `[start, end]` в байтах или битах (отрицательные
+    /// значения — от конца) в включительный диапазон битов внутри массива.
+    ///
+    /// Возвращает `None`, если диапазон пуст.
+    pub fn bit_range(
+        &self,
+        start: i64,
+        end: i64,
+        unit: BitUnit,
+    ) -> Option<(usize, usize)> {
+        let len = match unit {
+            BitUnit::Byte => self.bytes.len(),
+            BitUnit::Bit => self.bit_len(),
+        } as i64;
+        if len == 0 {
+            return None;
+        }
+
+        let start = if start < 0 { start + len } else { start };
+        let end = if end < 0 { end + len } else { end };
+        let (start, end) = (start.max(0), end.clamp(0, len - 1));
+        if start > end {
+            return None;
+        }
+
+        let (start, end) = (start as usize, end as usize);
+        match unit {
+            BitUnit::Byte => Some((start * 8, end * 8 + 7)),
+            BitUnit::Bit => Some((start, end)),
+        }
+    }
+
     /// Возвращает длину битового массива в битах (кратно 8).
     pub fn bit_len(&self) -> usize {
         self.bytes.len() * 8
@@ -323,6 +471,52 @@ mod tests {
         assert!(!bitmap.get_bit(5));
     }
 
+    /// Тест проверяет чтение и запись многобитовых полей по произвольному
+    /// (невыровненному) смещению.
+    #[test]
+    fn test_get_set_bits() {
+        let mut bitmap = Bitmap::new();
+        bitmap.set_bits(3, 8, 0xAB);
+        assert_eq!(bitmap.get_bits(3, 8), 0xAB);
+        assert_eq!(bitmap.bytes, vec![0b0001_0101, 0b0110_0000]);
+
+        // Записываются только младшие `width` битов.
+        bitmap.set_bits(0, 3, 0xFF);
+        assert_eq!(bitmap.get_bits(0, 3), 0b111);
+        assert_eq!(bitmap.get_bits(3, 8), 0xAB);
+
+        bitmap.set_bits(100, 64, u64::MAX);
+        assert_eq!(bitmap.get_bits(100, 64), u64::MAX);
+        // За границей массива — нули.
+        assert_eq!(bitmap.get_bits(1000, 16), 0);
+    }
+
+    /// Тест проверяет поиск первого установленного и сброшенного бита в
+    /// байтовых и битовых диапазонах, включая отрицательные границы.
+    #[test]
+    fn test_bitpos() {
+        let mut bitmap = Bitmap::with_capacity(80);
+        bitmap.set_bit(13, true);
+        bitmap.set_bit(70, true);
+
+        assert_eq!(bitmap.bitpos(true, 0, -1, BitUnit::Byte), Some(13));
+        assert_eq!(bitmap.bitpos(true, 2, -1, BitUnit::Byte), Some(70));
+        assert_eq!(bitmap.bitpos(true, 14, 69, BitUnit::Bit), None);
+        assert_eq!(bitmap.bitpos(true, -10, -1, BitUnit::Bit), Some(70));
+        assert_eq!(bitmap.bitpos(false, 13, 20, BitUnit::Bit), Some(14));
+        assert_eq!(bitmap.bitpos(true, 5, 2, BitUnit::Byte), None);
+
+        // Длинный диапазон проходит через SIMD-поиск.
+        let mut ones = Bitmap::with_capacity(8 * 100);
+        ones.bytes.fill(0xFF);
+        ones.set_bit(8 * 77 + 3, false);
+        assert_eq!(ones.bitpos(false, 0, -1, BitUnit::Byte), Some(8 * 77 + 3));
+        ones.set_bit(8 * 77 + 3, true);
+        assert_eq!(ones.bitpos(false, 0, -1, BitUnit::Byte), None);
+
+        assert_eq!(Bitmap::new().bitpos(true, 0, -1, BitUnit::Byte), None);
+    }
+
     /// Тест проверяет подсчёт установленных битов в заданном
     /// диапазоне.
     #[test]
@@ -335,6 +529,28 @@ mod tests {
         assert_eq!(bitmap.bitcount(4, 15), 0);
     }
 
+    /// Тест проверяет подсчёт битов в байтовых и битовых диапазонах:
+    /// отрицательные границы, `start > end`, пустой bitmap и диапазоны,
+    /// пересекающие границы байтов и SIMD-блоков.
+    #[test]
+    fn test_bit_count_range() {
+        let mut bitmap = Bitmap::with_capacity(8 * 64);
+        bitmap.bytes.fill(0xFF);
+
+        assert_eq!(bitmap.bit_count_range(0, -1, BitUnit::Byte), 512);
+        assert_eq!(bitmap.bit_count_range(1, 1, BitUnit::Byte), 8);
+        assert_eq!(bitmap.bit_count_range(-2, -1, BitUnit::Byte), 16);
+        assert_eq!(bitmap.bit_count_range(3, 12, BitUnit::Bit), 10);
+        assert_eq!(bitmap.bit_count_range(5, 300, BitUnit::Bit), 296);
+        assert_eq!(bitmap.bit_count_range(-3, -1, BitUnit::Bit), 3);
+        assert_eq!(bitmap.bit_count_range(10, 2, BitUnit::Byte), 0);
+        assert_eq!(bitmap.bit_count_range(10, 2, BitUnit::Bit), 0);
+        assert_eq!(bitmap.bit_count_range(0, 1_000, BitUnit::Byte), 512);
+
+        assert_eq!(Bitmap::new().bit_count_range(0, -1, BitUnit::Byte), 0);
+        assert_eq!(Bitmap::new().bit_count_range(0, -1, BitUnit::Bit), 0);
+    }
+
     /// Тест проверяет побитовые операции `AND`, `OR`, `XOR`
     /// между двумя Bitmap.
     #[test]
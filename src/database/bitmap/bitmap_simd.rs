@@ -132,10 +132,88 @@ pub fn bitcount_with_strategy(
     }
 }
 
+/// Находит индекс первого байта, отличного от `skip`, без SIMD.
+///
+/// Сравнивает по 8 байт за раз, остаток проверяется побайтово.
+pub fn find_byte_not_scalar(
+    bytes: &[u8],
+    skip: u8,
+) -> Option<usize> {
+    let pattern = u64::from_ne_bytes([skip; 8]);
+    let mut i = 0usize;
+
+    while i + 8 <= bytes.len() {
+        let mut chunk_bytes = [0u8; 8];
+        chunk_bytes.copy_from_slice(&bytes[i..i + 8]);
+        if u64::from_ne_bytes(chunk_bytes) != pattern {
+            break;
+        }
+        i += 8;
+    }
+
+    bytes[i..]
+        .iter()
+        .position(|&b| b != skip)
+        .map(|pos| i + pos)
+}
+
+/// Находит индекс первого байта, отличного от `skip`, автоматически выбирая
+/// AVX2 или скалярную реализацию.
+///
+/// Используется `BITPOS`: при поиске единичного бита пропускаются байты
+/// `0x00`, при поиске нулевого — байты `0xFF`.
+#[inline]
+pub fn find_byte_not_auto(
+    bytes: &[u8],
+    skip: u8,
+) -> Option<usize> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            unsafe { find_byte_not_avx2_impl(bytes, skip) }
+        } else {
+            find_byte_not_scalar(bytes, skip)
+        }
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        find_byte_not_scalar(bytes, skip)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Внутренние методы и функции
 ////////////////////////////////////////////////////////////////////////////////
 
+/// Поиск первого байта, отличного от `skip`, с использованием AVX2
+/// (сравнение 32 байт за раз).
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn find_byte_not_avx2_impl(
+    bytes: &[u8],
+    skip: u8,
+) -> Option<usize> {
+    use std::arch::x86_64::{
+        __m256i, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_movemask_epi8, _mm256_set1_epi8,
+    };
+
+    let needle = _mm256_set1_epi8(skip as i8);
+    let mut i = 0usize;
+
+    while i + 32 <= bytes.len() {
+        let vec = unsafe { _mm256_loadu_si256(bytes.as_ptr().add(i) as *const __m256i) };
+        // Бит маски равен 1 для байтов, совпадающих с `skip`.
+        let eq = _mm256_movemask_epi8(_mm256_cmpeq_epi8(vec, needle)) as u32;
+        if eq != u32::MAX {
+            return Some(i + (!eq).trailing_zeros() as usize);
+        }
+        i += 32;
+    }
+
+    find_byte_not_scalar(&bytes[i..], skip).map(|pos| i + pos)
+}
+
 /// Подсчёт битов с использованием инструкции POPCNT (x86_64).
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "popcnt")]
@@ -253,4 +331,20 @@ mod tests {
             BitcountStrategy::LookupTable | BitcountStrategy::Popcnt | BitcountStrategy::Avx2
         ));
     }
+
+    #[test]
+    fn test_find_byte_not() {
+        let mut bytes = vec![0u8; 100];
+        assert_eq!(find_byte_not_scalar(&bytes, 0), None);
+        assert_eq!(find_byte_not_auto(&bytes, 0), None);
+        assert_eq!(find_byte_not_auto(&bytes, 0xFF), Some(0));
+
+        for pos in [0, 7, 8, 31, 32, 63, 99] {
+            bytes.fill(0);
+            bytes[pos] = 0x10;
+            assert_eq!(find_byte_not_scalar(&bytes, 0), Some(pos));
+            assert_eq!(find_byte_not_auto(&bytes, 0), Some(pos));
+        }
+        assert_eq!(find_byte_not_auto(&[], 0), None);
+    }
 }
@@ -403,6 +403,14 @@ where
         self.ht[0].capacity()
     }
 
+    /// Возвращает память кучи, занятую бакетами обеих таблиц и узлами
+    /// цепочек, без учёта памяти кучи самих ключей и значений.
+    pub fn heap_size(&self) -> usize {
+        let buckets: usize = self.ht.iter().map(|t| t.buckets.capacity()).sum();
+        buckets * std::mem::size_of::<Option<Box<DictNode<K, V>>>>()
+            + self.len() * std::mem::size_of::<DictNode<K, V>>()
+    }
+
     /// Очищает словарь и сбрасывает рехешинг.
     pub fn clear(&mut self) {
         self.ht[0].clear();
@@ -455,6 +463,71 @@ where
         }
     }
 
+    /// Одна страница курсорного обхода (как `dictScan` в Redis).
+    ///
+    /// Курсор `0` начинает обход; возвращённый курсор `0` означает, что
+    /// обход завершён. За вызов просматриваются бакеты, пока не набрано
+    /// `count` элементов (или не встречено `count * 10` пустых бакетов).
+    ///
+    /// Курсор хранит индекс бакета с обращённым порядком битов и
+    /// увеличивается со старших разрядов маски. Поэтому между вызовами
+    /// словарь можно свободно изменять — расширение, сжатие и
+    /// инкрементальный рехеш не ломают обход: каждый ключ, присутствовавший
+    /// от начала до конца обхода, будет возвращён хотя бы один раз (но может
+    /// повториться). Во время рехеша бакет курсора читается в меньшей
+    /// таблице вместе со всеми его «расширениями» в большей.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+    ) -> (u64, Vec<(&K, &V)>) {
+        let mut out = Vec::new();
+        if self.is_empty() {
+            return (0, out);
+        }
+
+        let mut v = cursor;
+        let mut empty_visits = count.max(1) * 10;
+
+        loop {
+            let before = out.len();
+
+            if !self.is_rehashing() {
+                let m0 = self.ht[0].size_mask as u64;
+                Self::scan_bucket(&self.ht[0], v & m0, &mut out);
+                v = Self::next_cursor(v, m0);
+            } else {
+                let (small, large) = if self.ht[0].capacity() <= self.ht[1].capacity() {
+                    (&self.ht[0], &self.ht[1])
+                } else {
+                    (&self.ht[1], &self.ht[0])
+                };
+                let m0 = small.size_mask as u64;
+                let m1 = large.size_mask as u64;
+
+                Self::scan_bucket(small, v & m0, &mut out);
+                // Все бакеты большей таблицы, в которые раскладывается
+                // бакет `v & m0` меньшей.
+                loop {
+                    Self::scan_bucket(large, v & m1, &mut out);
+                    v = Self::next_cursor(v, m1);
+                    if v & (m0 ^ m1) == 0 {
+                        break;
+                    }
+                }
+            }
+
+            if out.len() == before {
+                empty_visits -= 1;
+            }
+            if v == 0 || out.len() >= count.max(1) || empty_visits == 0 {
+                break;
+            }
+        }
+
+        (v, out)
+    }
+
     /// Вычисляет хеш ключа через `self.hasher_builder`.
     #[inline]
     fn make_hash(
@@ -525,6 +598,29 @@ where
         }
     }
 
+    /// Добавляет в `out` все элементы бакета `idx` таблицы `table`.
+    fn scan_bucket<'a>(
+        table: &'a HashTable<K, V>,
+        idx: u64,
+        out: &mut Vec<(&'a K, &'a V)>,
+    ) {
+        let mut cur = table.buckets.get(idx as usize).and_then(|b| b.as_deref());
+        while let Some(node) = cur {
+            out.push((&node.key, &node.val));
+            cur = node.next.as_deref();
+        }
+    }
+
+    /// Следующий курсор обхода таблицы с маской `mask`: инкремент индекса
+    /// бакета с обращённым порядком битов.
+    #[inline]
+    fn next_cursor(
+        v: u64,
+        mask: u64,
+    ) -> u64 {
+        ((v | !mask).reverse_bits().wrapping_add(1)).reverse_bits()
+    }
+
     /// Возвращает true, если в процессе рехеширования.
     #[inline]
     fn is_rehashing(&self) -> bool {
@@ -1572,4 +1668,133 @@ mod tests {
 
         assert_eq!(count, 50);
     }
+
+    /// Делает шаг SCAN и возвращает следующий курсор, складывая ключи в
+    /// `seen`.
+    fn scan_step(
+        d: &Dict<i32, i32>,
+        cursor: u64,
+        count: usize,
+        seen: &mut std::collections::HashSet<i32>,
+    ) -> u64 {
+        let (next, page) = d.scan(cursor, count);
+        for (k, v) in page {
+            assert_eq!(k, v);
+            seen.insert(*k);
+        }
+        next
+    }
+
+    #[test]
+    fn test_scan_empty_dict() {
+        let d: Dict<i32, i32> = Dict::new();
+        let (next, page) = d.scan(0, 10);
+        assert_eq!(next, 0);
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn test_scan_full_iteration() {
+        let mut d = Dict::new();
+        for i in 0..1000 {
+            d.insert(i, i);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = scan_step(&d, 0, 10, &mut seen);
+        let mut calls = 1;
+        while cursor != 0 {
+            cursor = scan_step(&d, cursor, 10, &mut seen);
+            calls += 1;
+        }
+
+        assert_eq!(seen.len(), 1000);
+        assert!(calls > 1);
+    }
+
+    #[test]
+    fn test_scan_with_inserts_during_scan() {
+        let mut d = Dict::new();
+        for i in 0..100 {
+            d.insert(i, i);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = 0;
+        for _ in 0..3 {
+            cursor = scan_step(&d, cursor, 5, &mut seen);
+        }
+        assert_ne!(cursor, 0);
+
+        // Вставки между шагами расширяют таблицу и запускают рехеш
+        let mut next_key = 100;
+        while cursor != 0 {
+            for _ in 0..50 {
+                d.insert(next_key, next_key);
+                next_key += 1;
+            }
+            cursor = scan_step(&d, cursor, 5, &mut seen);
+        }
+
+        assert!(d.capacity() > 128);
+        for i in 0..100 {
+            assert!(seen.contains(&i), "key {i} missed");
+        }
+    }
+
+    #[test]
+    fn test_scan_with_removals_during_scan() {
+        let mut d = Dict::new();
+        for i in 0..2000 {
+            d.insert(i, i);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = scan_step(&d, 0, 20, &mut seen);
+
+        // Удаления между шагами сжимают таблицу; ключи >= 1800 остаются
+        let mut next_removed = 0;
+        while cursor != 0 {
+            for _ in 0..100 {
+                if next_removed < 1800 {
+                    d.remove(&next_removed);
+                    next_removed += 1;
+                }
+            }
+            cursor = scan_step(&d, cursor, 20, &mut seen);
+        }
+
+        for i in 1800..2000 {
+            assert!(seen.contains(&i), "key {i} missed");
+        }
+    }
+
+    #[test]
+    fn test_scan_while_rehashing() {
+        let mut d = Dict::new();
+        for i in 0..64 {
+            d.insert(i, i);
+        }
+        // Load factor 1: рехеш начнётся без переноса элементов
+        d.expand_if_needed();
+        assert!(d.is_rehashing());
+        let total = d.len() as i32;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut cursor = scan_step(&d, 0, 3, &mut seen);
+        cursor = scan_step(&d, cursor, 3, &mut seen);
+        assert_ne!(cursor, 0);
+
+        // Изменения двигают рехеш, обход продолжается по новой таблице
+        let mut next_key = total;
+        while cursor != 0 {
+            d.insert(next_key, next_key);
+            next_key += 1;
+            cursor = scan_step(&d, cursor, 3, &mut seen);
+        }
+
+        for k in 0..total {
+            assert!(seen.contains(&k), "key {k} missed");
+        }
+    }
 }
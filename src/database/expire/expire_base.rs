@@ -1,30 +1,84 @@
 use std::{
     cmp::Reverse,
     collections::{BinaryHeap, HashMap},
-    time::{Duration, Instant},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use tokio::time::Instant;
+
 /// `ExpireMap` хранит ключи с временем жизни (TTL) и автоматически
 /// очищает просроченные записи.
 ///
 /// Internally uses:
 /// - `deadlines` для быстрого поиска активных ключей.
 /// - `queue` (минимальная куча по времени) для эффективной очистки.
+/// - `keys` — плотный список ключей с TTL для случайной выборки
+///   ([`ExpireMap::expired_sample`]).
+///
+/// Время берётся из [`tokio::time::Instant`]: вне остановленного
+/// (`tokio::time::pause`) рантайма это обычные монотонные часы.
+#[derive(Debug)]
 pub struct ExpireMap {
-    deadlines: HashMap<Vec<u8>, Instant>,
+    deadlines: HashMap<Vec<u8>, Slot>,
     queue: BinaryHeap<Reverse<(Instant, Vec<u8>)>>,
+    keys: Vec<Vec<u8>>,
+}
+
+/// Срок жизни ключа и его позиция в `ExpireMap::keys`.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    deadline: Instant,
+    pos: usize,
+}
+
+/// Изменение времени жизни ключа, запрошенное командой `GETEX` (или опциями
+/// `SET`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetExExpiry {
+    /// Оставить текущий TTL без изменений (`KEEPTTL` для `SET`).
+    Keep,
+    /// `EX seconds` — TTL в секундах.
+    Ex(u64),
+    /// `PX milliseconds` — TTL в миллисекундах.
+    Px(u64),
+    /// `EXAT unix-time-seconds` — абсолютное время истечения в секундах.
+    ExAt(u64),
+    /// `PXAT unix-time-milliseconds` — абсолютное время истечения в
+    /// миллисекундах.
+    PxAt(u64),
+    /// `PERSIST` — снять TTL с ключа.
+    Persist,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl GetExExpiry {
+    /// Возвращает TTL относительно текущего момента.
+    ///
+    /// # Возвращает
+    /// - `Some(ttl)` для `EX`/`PX`/`EXAT`/`PXAT`; для абсолютного времени в
+    ///   прошлом — нулевую длительность (ключ должен быть удалён сразу)
+    /// - `None` для `Keep` и `Persist`
+    pub fn ttl(&self) -> Option<Duration> {
+        match *self {
+            GetExExpiry::Ex(secs) => Some(Duration::from_secs(secs)),
+            GetExExpiry::Px(ms) => Some(Duration::from_millis(ms)),
+            GetExExpiry::ExAt(secs) => Some(duration_until_unix_ms(secs.saturating_mul(1000))),
+            GetExExpiry::PxAt(ms) => Some(duration_until_unix_ms(ms)),
+            GetExExpiry::Keep | GetExExpiry::Persist => None,
+        }
+    }
+}
+
 impl ExpireMap {
     /// Создаёт новый пустой `ExpireMap`.
     pub fn new() -> Self {
         Self {
             deadlines: HashMap::new(),
             queue: BinaryHeap::new(),
+            keys: Vec::new(),
         }
     }
 
@@ -42,7 +96,14 @@ impl ExpireMap {
         ttl: Duration,
     ) {
         let deadline = Instant::now() + ttl;
-        self.deadlines.insert(key.clone(), deadline);
+        match self.deadlines.get_mut(&key) {
+            Some(slot) => slot.deadline = deadline,
+            None => {
+                let pos = self.keys.len();
+                self.keys.push(key.clone());
+                self.deadlines.insert(key.clone(), Slot { deadline, pos });
+            }
+        }
         self.queue.push(Reverse((deadline, key)));
     }
 
@@ -69,15 +130,100 @@ impl ExpireMap {
     /// # Параметры
     /// - `key`: ссылка на вектор-ключ.
     ///
+    /// # Возвращает
+    /// - `true`, если у ключа был TTL; иначе `false`.
+    ///
     /// Замечание: из-за особенностей `BinaryHeap` физическое удаление из неё
     /// не происходит, но при `purge` просроченные записи будут игнорироваться.
     pub fn remove(
         &mut self,
         key: &[u8],
-    ) {
-        self.deadlines.remove(key);
+    ) -> bool {
         // BinaryHeap не поддерживает удаление по ключу, но это не критично:
         // просроченные ключи будут проигнорированы при purge.
+        self.forget(key).is_some()
+    }
+
+    /// Возвращает оставшееся время жизни `key`.
+    ///
+    /// # Возвращает
+    /// - `Some(ttl)`, если для ключа установлен TTL (для уже истёкшего, но ещё
+    ///   не очищенного ключа — нулевая длительность)
+    /// - `None`, если TTL не установлен
+    pub fn ttl(
+        &self,
+        key: &[u8],
+    ) -> Option<Duration> {
+        self.deadlines
+            .get(key)
+            .map(|slot| slot.deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Возвращает абсолютное время истечения `key` в unix-миллисекундах,
+    /// вычисленное как `SystemTime::now() + (deadline - Instant::now())`.
+    ///
+    /// # Возвращает
+    /// - `Some(ms)`, если для ключа установлен TTL
+    /// - `None`, если TTL не установлен
+    pub fn expire_at_unix_ms(
+        &self,
+        key: &[u8],
+    ) -> Option<u64> {
+        self.ttl(key).map(|ttl| {
+            (SystemTime::now() + ttl)
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        })
+    }
+
+    /// Удаляет все записи без ожидания их TTL.
+    pub fn clear(&mut self) {
+        self.deadlines.clear();
+        self.queue.clear();
+        self.keys.clear();
+    }
+
+    /// Возвращает число ключей с TTL (включая истёкшие, но ещё не
+    /// очищенные).
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    /// Проверяет, есть ли ключи с TTL.
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    /// Проверяет до `count` случайных ключей с TTL и удаляет истёкшие из
+    /// них — один шаг активного удаления, как `activeExpireCycle` в Redis.
+    ///
+    /// Выборка без возвращения: если ключей с TTL не больше `count`,
+    /// проверяются все.
+    ///
+    /// # Возвращает
+    /// Пару `(sampled, expired)`: сколько ключей проверено (не больше
+    /// `count` и числа ключей с TTL) и какие из них удалены как истёкшие.
+    pub fn expired_sample(
+        &mut self,
+        count: usize,
+    ) -> (usize, Vec<Vec<u8>>) {
+        let sampled = count.min(self.keys.len());
+        let now = Instant::now();
+        let picked: Vec<Vec<u8>> =
+            rand::seq::index::sample(&mut rand::thread_rng(), self.keys.len(), sampled)
+                .into_iter()
+                .map(|i| self.keys[i].clone())
+                .collect();
+
+        let mut expired = Vec::new();
+        for key in picked {
+            if self.deadlines[&key].deadline <= now {
+                self.forget(&key);
+                expired.push(key);
+            }
+        }
+        (sampled, expired)
     }
 
     /// Очищает все записи, срок жизни которых истёк.
@@ -94,15 +240,38 @@ impl ExpireMap {
             let key = key.clone();
             self.queue.pop();
             // Если в deadlines тот же крайний срок и он уже в прошлом, удаляем.
-            if let Some(sorted_deadline) = self.deadlines.get(&key) {
-                if *sorted_deadline <= now {
-                    self.deadlines.remove(&key);
-                    expired.push(key);
-                }
+            if self.deadlines.get(&key).is_some_and(|s| s.deadline <= now) {
+                self.forget(&key);
+                expired.push(key);
             }
         }
         expired
     }
+
+    /// Убирает `key` из `deadlines` и `keys` (swap-remove с исправлением
+    /// позиции перенесённого ключа).
+    fn forget(
+        &mut self,
+        key: &[u8],
+    ) -> Option<Slot> {
+        let slot = self.deadlines.remove(key)?;
+        self.keys.swap_remove(slot.pos);
+        if let Some(moved) = self.keys.get(slot.pos) {
+            if let Some(moved_slot) = self.deadlines.get_mut(moved) {
+                moved_slot.pos = slot.pos;
+            }
+        }
+        Some(slot)
+    }
+}
+
+/// Вычисляет длительность от текущего момента до unix-времени `ms`
+/// (в миллисекундах). Для времени в прошлом возвращает нулевую длительность.
+fn duration_until_unix_ms(ms: u64) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Duration::from_millis(ms).saturating_sub(now)
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -155,8 +324,9 @@ mod tests {
         map.set(key("delete_me"), Duration::from_secs(10));
         assert!(map.get(b"delete_me"));
 
-        map.remove(b"delete_me");
+        assert!(map.remove(b"delete_me"));
         assert!(!map.get(b"delete_me"));
+        assert!(!map.remove(b"delete_me"));
     }
 
     #[test]
@@ -185,6 +355,100 @@ mod tests {
         assert!(map.get(b"foo"));
     }
 
+    #[test]
+    fn test_ttl_and_clear() {
+        let mut map = ExpireMap::new();
+        map.set(key("foo"), Duration::from_secs(10));
+
+        let ttl = map.ttl(b"foo").unwrap();
+        assert!(ttl > Duration::from_secs(9) && ttl <= Duration::from_secs(10));
+        assert_eq!(map.ttl(b"bar"), None);
+
+        map.clear();
+        assert_eq!(map.ttl(b"foo"), None);
+        assert!(map.purge().is_empty());
+    }
+
+    #[test]
+    fn test_expire_at_unix_ms() {
+        let mut map = ExpireMap::new();
+        map.set(key("foo"), Duration::from_secs(10));
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let at = map.expire_at_unix_ms(b"foo").unwrap();
+        assert!(at > now_ms + 9_000 && at <= now_ms + 10_100);
+        assert_eq!(map.expire_at_unix_ms(b"bar"), None);
+    }
+
+    #[test]
+    fn test_getex_expiry_ttl() {
+        assert_eq!(GetExExpiry::Ex(5).ttl(), Some(Duration::from_secs(5)));
+        assert_eq!(GetExExpiry::Px(50).ttl(), Some(Duration::from_millis(50)));
+        assert_eq!(GetExExpiry::ExAt(1).ttl(), Some(Duration::ZERO));
+        assert_eq!(GetExExpiry::PxAt(1).ttl(), Some(Duration::ZERO));
+        assert_eq!(GetExExpiry::Keep.ttl(), None);
+        assert_eq!(GetExExpiry::Persist.ttl(), None);
+
+        let future = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 100;
+        let ttl = GetExExpiry::ExAt(future).ttl().unwrap();
+        assert!(ttl > Duration::from_secs(98));
+    }
+
+    #[test]
+    fn test_expired_sample() {
+        let mut map = ExpireMap::new();
+        for i in 0..10 {
+            map.set(key(&format!("old{i}")), Duration::ZERO);
+            map.set(key(&format!("live{i}")), Duration::from_secs(60));
+        }
+        assert_eq!(map.len(), 20);
+
+        let (sampled, expired) = map.expired_sample(5);
+        assert_eq!(sampled, 5);
+        assert!(expired.iter().all(|k| k.starts_with(b"old")));
+
+        // Повторные выборки в итоге находят все истёкшие ключи
+        let mut removed = expired.len();
+        while removed < 10 {
+            removed += map.expired_sample(20).1.len();
+        }
+        assert_eq!(map.len(), 10);
+        assert_eq!(map.expired_sample(100), (10, Vec::new()));
+        assert!(map.purge().is_empty());
+    }
+
+    #[test]
+    fn test_remove_keeps_sample_positions() {
+        let mut map = ExpireMap::new();
+        for i in 0..50 {
+            map.set(key(&format!("k{i}")), Duration::ZERO);
+        }
+        for i in (0..50).step_by(2) {
+            assert!(map.remove(&key(&format!("k{i}"))));
+        }
+        map.set(key("k1"), Duration::from_secs(60));
+        assert_eq!(map.len(), 25);
+
+        let mut expired = Vec::new();
+        while expired.len() < 24 {
+            expired.extend(map.expired_sample(20).1);
+        }
+        expired.sort();
+        let mut expected: Vec<Vec<u8>> =
+            (3..50).step_by(2).map(|i| key(&format!("k{i}"))).collect();
+        expected.sort();
+        assert_eq!(expired, expected);
+        assert!(map.get(b"k1"));
+        assert_eq!(map.len(), 1);
+    }
+
     #[test]
     fn test_default_impl() {
         let mut map: ExpireMap = Default::default();
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
+use ordered_float::OrderedFloat;
+
 use crate::database::{
     geo_distance::{calculate_distance, haversine_dist, DistanceMethod, DistanceUnit},
-    geohash_ranges_for_bbox, BoundingBox, Geohash, GeohashPrecision, RTree, TreeStats,
+    geohash_ranges_for_bbox, BoundingBox, Dict, Geohash, GeohashPrecision, RTree, Sds, SkipList,
+    TreeStats, Value,
 };
 
 /// Географическая точка (долгота и широта).
@@ -20,6 +23,56 @@ pub struct GeoEntry {
     pub score: u64, // 52-битный interleaved hash
 }
 
+/// Флаги добавления точки (`GEOADD [NX|XX] [CH]`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GeoAddOptions {
+    /// `NX` — только добавлять новые точки, не обновляя существующие.
+    pub nx: bool,
+    /// `XX` — только обновлять существующие точки, не добавляя новые.
+    pub xx: bool,
+    /// `CH` — считать изменённые точки вместе с добавленными.
+    pub ch: bool,
+}
+
+/// Порядок сортировки результатов гео-поиска по расстоянию.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoSortOrder {
+    /// `ASC` — от ближайших к дальним.
+    Asc,
+    /// `DESC` — от дальних к ближайшим.
+    Desc,
+}
+
+/// Источник поиска для GEOSEARCH
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoSearchFrom {
+    Member(String),
+    LoLat { lon: f64, lat: f64 },
+}
+
+/// Форма области поиска для GEOSEARCH; размеры задаются в единицах
+/// `GeoSearchParams::unit`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoSearchShape {
+    /// `BYRADIUS radius` — круг с центром в источнике поиска.
+    Radius(f64),
+    /// `BYBOX width height` — прямоугольник с центром в источнике поиска.
+    Box { width: f64, height: f64 },
+}
+
+/// Параметры GEOSEARCH/GEOSEARCHSTORE.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoSearchParams {
+    pub from: GeoSearchFrom,
+    pub shape: GeoSearchShape,
+    /// Единица измерения размеров области и возвращаемых расстояний.
+    pub unit: DistanceUnit,
+    pub order: Option<GeoSortOrder>,
+    pub count: Option<usize>,
+    /// `STOREDIST` — сохранять расстояние вместо geohash в качестве скора.
+    pub store_dist: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct RadiusOptions {
     pub use_geohash: bool,
@@ -36,8 +89,6 @@ pub struct GeoSet {
     member_index: HashMap<String, GeoPoint>,
     /// Geohash index для approximate filtering
     geohash_index: HashMap<String, Vec<String>>,
-    /// Флаг для отложенной пересборки индекса
-    needs_rebuild: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -59,7 +110,6 @@ impl GeoSet {
             rtree: RTree::new(),
             member_index: HashMap::new(),
             geohash_index: HashMap::new(),
-            needs_rebuild: false,
         }
     }
 
@@ -85,44 +135,99 @@ impl GeoSet {
             rtree,
             member_index,
             geohash_index,
-            needs_rebuild: false,
         }
     }
 
-    /// Добавляет или обновляет точку по имени.
+    /// Собирает GeoSet из пар `(member, GeoPoint)` одним bulk loading'ом —
+    /// так набор восстанавливается из сериализованного списка участников.
+    /// Точки с некорректными координатами пропускаются, при повторе имени
+    /// побеждает последняя.
+    pub fn from_members<I>(members: I) -> Self
+    where
+        I: IntoIterator<Item = (String, GeoPoint)>,
+    {
+        let mut unique: HashMap<String, GeoPoint> = HashMap::new();
+        for (member, point) in members {
+            if Self::validate_coords(point.lon, point.lat) {
+                unique.insert(member, point);
+            }
+        }
+
+        let entries = unique
+            .into_iter()
+            .map(|(member, point)| GeoEntry {
+                member,
+                point,
+                score: encode_geohash_bits(point.lon, point.lat),
+            })
+            .collect();
+        Self::from_entries(entries)
+    }
+
+    /// Добавляет или обновляет точку по имени с учётом флагов `opts`.
+    ///
+    /// # Возвращает
+    /// - `true`, если точка добавлена, или, при `opts.ch`, если координаты
+    ///   существующей точки изменились
+    /// - `false`, если координаты некорректны, операция отклонена флагами
+    ///   `NX`/`XX` или ничего не изменилось
     pub fn add(
         &mut self,
         member: String,
         lon: f64,
         lat: f64,
-    ) {
+        opts: GeoAddOptions,
+    ) -> bool {
         if !Self::validate_coords(lon, lat) {
-            return;
+            return false;
+        }
+
+        let existing = self.member_index.get(&member).copied();
+        match existing {
+            Some(_) if opts.nx => return false,
+            None if opts.xx => return false,
+            _ => {}
         }
 
         let point = GeoPoint { lon, lat };
+        if existing == Some(point) {
+            return false;
+        }
         let score = encode_geohash_bits(lon, lat);
 
-        // Проверяем, нужна ли пересборка
-        if let Some(old_point) = self.member_index.insert(member.clone(), point) {
-            if old_point != point {
-                self.needs_rebuild = true;
+        // Обновление координат: старая запись убирается из обоих индексов
+        let counted = match existing {
+            Some(old) => {
+                self.rtree.remove(&member, old);
+                self.unindex_geohash(&member, old);
+                opts.ch
             }
-        } else {
-            // Новая точка - добавляем в geohash_index
-            let gh = Geohash::encode(point, GeohashPrecision::High);
-            self.geohash_index
-                .entry(gh.as_str().to_string())
-                .or_default()
-                .push(member.clone());
-        }
+            None => true,
+        };
 
-        // Вставка в R-tree
+        self.member_index.insert(member.clone(), point);
+        self.index_geohash(member.clone(), point);
         self.rtree.insert(GeoEntry {
             member,
             point,
             score,
         });
+        counted
+    }
+
+    /// Удаляет точку по имени из множества и всех индексов.
+    ///
+    /// Возвращает `true`, если точка была.
+    pub fn remove(
+        &mut self,
+        member: &str,
+    ) -> bool {
+        let Some(point) = self.member_index.remove(member) else {
+            return false;
+        };
+        self.rtree.remove(member, point);
+        self.unindex_geohash(member, point);
+        true
     }
 
     /// Валидирует координаты.
@@ -186,7 +291,7 @@ impl GeoSet {
     /// Возвращает всех членов в радиусе `radius_m` метров от точки (`lon`,
     /// `lat`). Использует R-tree для эффективного поиска.
     pub fn radius(
-        &mut self,
+        &self,
         lon: f64,
         lat: f64,
         radius_m: f64,
@@ -195,7 +300,7 @@ impl GeoSet {
     }
 
     pub fn radius_with_options(
-        &mut self,
+        &self,
         lon: f64,
         lat: f64,
         radius_m: f64,
@@ -205,11 +310,6 @@ impl GeoSet {
             return Vec::new();
         }
 
-        // Пересобираем индекс
-        if self.needs_rebuild {
-            self.rebuild_index();
-        }
-
         let center = GeoPoint { lon, lat };
 
         // Если включена фильтрация геохеша
@@ -218,25 +318,40 @@ impl GeoSet {
         }
 
         // В качестве резервного варианта используется только R-дерево
-        let bbox = Self::radius_to_bbox(center, radius_m);
-        let candidates = self.rtree.range_query(&bbox);
+        self.search_radius(center, radius_m)
+            .into_iter()
+            .map(|(entry, dist)| (entry.member, dist))
+            .collect()
+    }
 
-        candidates
+    /// Возвращает все точки в радиусе `radius_m` метров от `center` вместе с
+    /// расстоянием до центра по формуле гаверсина.
+    ///
+    /// Кандидаты берутся из R-дерева по прямоугольникам, описанным вокруг
+    /// круга (см. [`BoundingBox::around_radius`]), поэтому запрос стоит
+    /// O(log n + k) и корректен у антимеридиана и полюсов.
+    pub fn search_radius(
+        &self,
+        center: GeoPoint,
+        radius_m: f64,
+    ) -> Vec<(GeoEntry, f64)> {
+        if !Self::validate_coords(center.lon, center.lat) {
+            return Vec::new();
+        }
+
+        self.rtree
+            .box_query(&BoundingBox::around_radius(center, radius_m))
             .into_iter()
             .filter_map(|entry| {
                 let dist = haversine_distance(center, entry.point);
-                if dist <= radius_m {
-                    Some((entry.member, dist))
-                } else {
-                    None
-                }
+                (dist <= radius_m).then_some((entry, dist))
             })
             .collect()
     }
 
     /// Запрос радиуса с настраиваемым методом дальности.
     pub fn radius_with_method(
-        &mut self,
+        &self,
         lon: f64,
         lat: f64,
         radius: f64,
@@ -248,10 +363,6 @@ impl GeoSet {
             return Vec::new();
         }
 
-        if self.needs_rebuild {
-            self.rebuild_index();
-        }
-
         let center = GeoPoint { lon, lat };
         let radius_m = radius_unit.convert_to_meters(radius);
         let use_geohash = options.use_geohash;
@@ -260,8 +371,8 @@ impl GeoSet {
         let candidates = if use_geohash {
             self.radius_candidates_geohash(center, radius_m, options)
         } else {
-            let bbox = Self::radius_to_bbox(center, radius_m);
-            self.rtree.range_query(&bbox)
+            self.rtree
+                .box_query(&BoundingBox::around_radius(center, radius_m))
         };
 
         // Точная фильтрация с выбранным методом
@@ -312,6 +423,107 @@ impl GeoSet {
         results
     }
 
+    /// Возвращает всех членов внутри прямоугольника `width_m` × `height_m`
+    /// метров с центром в `center`.
+    ///
+    /// Стороны прямоугольника идут по меридианам и параллелям: высота
+    /// отсчитывается в градусах широты, ширина — в градусах долготы на
+    /// широте центра (см. [`BoundingBox::around`]). Прямоугольник,
+    /// пересекающий антимеридиан, ищется двумя диапазонами долготы за один
+    /// обход R-дерева; накрывающий полюс — по всем долготам.
+    pub fn search_box(
+        &self,
+        center: GeoPoint,
+        width_m: f64,
+        height_m: f64,
+    ) -> Vec<GeoEntry> {
+        if !Self::validate_coords(center.lon, center.lat) {
+            return Vec::new();
+        }
+
+        self.rtree
+            .box_query(&BoundingBox::around(center, width_m, height_m))
+    }
+
+    /// То же, что [`GeoSet::search_box`], но возвращает пары `(member,
+    /// расстояние до центра в метрах)`.
+    pub fn bbox_search(
+        &self,
+        lon: f64,
+        lat: f64,
+        width_m: f64,
+        height_m: f64,
+    ) -> Vec<(String, f64)> {
+        let center = GeoPoint { lon, lat };
+        self.search_box(center, width_m, height_m)
+            .into_iter()
+            .map(|entry| {
+                let dist = haversine_distance(center, entry.point);
+                (entry.member, dist)
+            })
+            .collect()
+    }
+
+    /// Выполняет поиск GEOSEARCH по параметрам `params`.
+    ///
+    /// # Возвращает
+    /// - `None`, если участник, заданный через `FROMMEMBER`, отсутствует
+    /// - иначе список `(member, distance, GeoPoint)` с расстоянием в
+    ///   `params.unit`, отсортированный по `params.order` и обрезанный по
+    ///   `params.count` (`COUNT` без явного порядка сортирует по возрастанию)
+    pub fn search(
+        &self,
+        params: &GeoSearchParams,
+    ) -> Option<Vec<(String, f64, GeoPoint)>> {
+        let center = match &params.from {
+            GeoSearchFrom::Member(member) => self.get(member)?,
+            GeoSearchFrom::LoLat { lon, lat } => GeoPoint {
+                lon: *lon,
+                lat: *lat,
+            },
+        };
+
+        let unit = params.unit;
+        let raw = match params.shape {
+            // Геохеш-индекс хранит ячейки высокой точности и не находит точки
+            // при поиске по более грубым ячейкам, поэтому ищем по R-дереву.
+            GeoSearchShape::Radius(radius) => self.radius_with_options(
+                center.lon,
+                center.lat,
+                unit.convert_to_meters(radius),
+                RadiusOptions {
+                    use_geohash: false,
+                    ..RadiusOptions::default()
+                },
+            ),
+            GeoSearchShape::Box { width, height } => self.bbox_search(
+                center.lon,
+                center.lat,
+                unit.convert_to_meters(width),
+                unit.convert_to_meters(height),
+            ),
+        };
+
+        let mut out: Vec<(String, f64, GeoPoint)> = raw
+            .into_iter()
+            .filter_map(|(member, dist_m)| {
+                let point = self.get(&member)?;
+                Some((member, unit.convert_from_meters(dist_m), point))
+            })
+            .collect();
+
+        let order = params.order.or(params.count.map(|_| GeoSortOrder::Asc));
+        match order {
+            Some(GeoSortOrder::Asc) => out.sort_by(|a, b| a.1.total_cmp(&b.1)),
+            Some(GeoSortOrder::Desc) => out.sort_by(|a, b| b.1.total_cmp(&a.1)),
+            None => {}
+        }
+        if let Some(count) = params.count {
+            out.truncate(count);
+        }
+        Some(out)
+    }
+
     /// Поиск k ближайших соседей к точке.
     pub fn nearest(
         &self,
@@ -462,29 +674,12 @@ impl GeoSet {
             .collect()
     }
 
-    /// Преобразует радиус в метрах в bounding box (приблизительно).
-    fn radius_to_bbox(
-        center: GeoPoint,
-        radius_m: f64,
-    ) -> BoundingBox {
-        // Примерное преобразование: 1 градус ≈ 111km на экваторе
-        let lat_delta = radius_m / 111_000.0;
-        let lon_delta = radius_m / (111_000.0 * center.lat.to_radians().cos().abs().max(0.01));
-
-        BoundingBox::new(
-            (center.lon - lon_delta).max(-180.0),
-            (center.lon + lon_delta).min(180.0),
-            (center.lat - lat_delta).max(-90.0),
-            (center.lat + lat_delta).min(90.0),
-        )
-    }
-
-    /// Пересобирает R-tree (если были обновления существующих точек).
+    /// Пересобирает R-tree и geohash-индекс с нуля bulk loading'ом.
+    ///
+    /// Индексы поддерживаются в актуальном состоянии при каждом `add` и
+    /// `remove`; пересборка лишь заново упаковывает дерево, узлы которого
+    /// после множества обновлений перекрываются сильнее, чем после STR.
     pub fn rebuild_index(&mut self) {
-        if !self.needs_rebuild {
-            return;
-        }
-
         let entries: Vec<GeoEntry> = self
             .member_index
             .iter()
@@ -506,7 +701,6 @@ impl GeoSet {
         }
 
         self.rtree = RTree::bulk_load(entries);
-        self.needs_rebuild = false;
     }
 
     /// Итератор по всем элементам: (member, GeoPoint).
@@ -514,6 +708,16 @@ impl GeoSet {
         self.member_index.iter()
     }
 
+    /// Все точки, отсортированные по имени: детерминированная форма для
+    /// сериализации (индексы по ней строятся заново, см.
+    /// [`GeoSet::from_members`]).
+    pub fn sorted_members(&self) -> Vec<(&String, GeoPoint)> {
+        let mut members: Vec<(&String, GeoPoint)> =
+            self.member_index.iter().map(|(m, p)| (m, *p)).collect();
+        members.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        members
+    }
+
     /// Возвращает количество точек.
     pub fn len(&self) -> usize {
         self.member_index.len()
@@ -552,6 +756,59 @@ impl GeoSet {
             max_bucket_size,
         }
     }
+
+    /// Добавляет member в ячейку geohash-индекса для точки `point`.
+    fn index_geohash(
+        &mut self,
+        member: String,
+        point: GeoPoint,
+    ) {
+        let gh = Geohash::encode(point, GeohashPrecision::High);
+        self.geohash_index
+            .entry(gh.as_str().to_string())
+            .or_default()
+            .push(member);
+    }
+
+    /// Убирает member из ячейки geohash-индекса для точки `point`; пустая
+    /// ячейка удаляется.
+    fn unindex_geohash(
+        &mut self,
+        member: &str,
+        point: GeoPoint,
+    ) {
+        let gh = Geohash::encode(point, GeohashPrecision::High);
+        if let Some(members) = self.geohash_index.get_mut(gh.as_str()) {
+            members.retain(|m| m != member);
+            if members.is_empty() {
+                self.geohash_index.remove(gh.as_str());
+            }
+        }
+    }
+}
+
+impl GeoSearchParams {
+    /// Собирает результаты поиска в `Value::ZSet` для GEOSEARCHSTORE:
+    /// скором служит 52-битный geohash точки или, при `STOREDIST`,
+    /// расстояние в `unit`.
+    pub fn to_zset(
+        &self,
+        hits: Vec<(String, f64, GeoPoint)>,
+    ) -> Value {
+        let mut dict = Dict::new();
+        let mut sorted = SkipList::new();
+        for (member, dist, point) in hits {
+            let score = if self.store_dist {
+                dist
+            } else {
+                encode_geohash_bits(point.lon, point.lat) as f64
+            };
+            let member = Sds::from_str(&member);
+            dict.insert(member.clone(), score);
+            sorted.insert(OrderedFloat(score), member);
+        }
+        Value::ZSet { dict, sorted }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -693,8 +950,8 @@ mod tests {
     #[test]
     fn test_add_get_with_rtree() {
         let mut gs = GeoSet::new();
-        gs.add("A".to_string(), 10.0, 20.0);
-        gs.add("B".to_string(), -5.5, 42.1);
+        gs.add("A".to_string(), 10.0, 20.0, GeoAddOptions::default());
+        gs.add("B".to_string(), -5.5, 42.1, GeoAddOptions::default());
         assert_eq!(
             gs.get("A").unwrap(),
             GeoPoint {
@@ -717,8 +974,8 @@ mod tests {
     #[test]
     fn test_dist_method() {
         let mut gs = GeoSet::new();
-        gs.add("X".into(), 0.0, 0.0);
-        gs.add("Y".into(), 0.0, 1.0);
+        gs.add("X".into(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("Y".into(), 0.0, 1.0, GeoAddOptions::default());
         let d = gs.dist("X", "Y").unwrap();
         assert!((d - 111_195.0).abs() < 100.0);
         assert!(gs.dist("X", "Z").is_none());
@@ -729,8 +986,8 @@ mod tests {
     #[test]
     fn test_radius_with_rtree() {
         let mut gs = GeoSet::new();
-        gs.add("near".into(), 0.1, 0.0);
-        gs.add("far".into(), 1.0, 0.0);
+        gs.add("near".into(), 0.1, 0.0, GeoAddOptions::default());
+        gs.add("far".into(), 1.0, 0.0, GeoAddOptions::default());
 
         let opts = RadiusOptions {
             use_geohash: false,
@@ -748,10 +1005,10 @@ mod tests {
     #[test]
     fn test_nearest_neighbors() {
         let mut gs = GeoSet::new();
-        gs.add("A".to_string(), 0.0, 0.0);
-        gs.add("B".to_string(), 0.1, 0.0);
-        gs.add("C".to_string(), 0.2, 0.0);
-        gs.add("D".to_string(), 1.0, 0.0);
+        gs.add("A".to_string(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("B".to_string(), 0.1, 0.0, GeoAddOptions::default());
+        gs.add("C".to_string(), 0.2, 0.0, GeoAddOptions::default());
+        gs.add("D".to_string(), 1.0, 0.0, GeoAddOptions::default());
 
         let results = gs.nearest(0.0, 0.0, 2);
 
@@ -788,35 +1045,170 @@ mod tests {
     #[test]
     fn test_coordinate_validation() {
         let mut gs = GeoSet::new();
-        gs.add("valid".into(), 0.0, 0.0);
+        gs.add("valid".into(), 0.0, 0.0, GeoAddOptions::default());
 
         assert!(gs.get("valid").is_some());
 
         let initial_len = gs.len();
-        gs.add("invalid_lon".into(), 200.0, 0.0);
-        gs.add("invalid_lat".into(), 0.0, 100.0);
+        gs.add("invalid_lon".into(), 200.0, 0.0, GeoAddOptions::default());
+        gs.add("invalid_lat".into(), 0.0, 100.0, GeoAddOptions::default());
 
         assert_eq!(gs.len(), initial_len);
     }
 
-    /// Тест проверяет необходимость пересборки R-tree при обновлении
-    /// существующей точки и корректную работу rebuild_index.
+    /// Тест проверяет, что обновление координат сразу переносит точку в
+    /// R-tree и geohash-индексе, без устаревших записей, а rebuild_index
+    /// сохраняет результат.
     #[test]
     fn test_index_rebuild() {
         let mut gs = GeoSet::new();
-        gs.add("A".into(), 0.0, 0.0);
-        gs.add("A".into(), 1.0, 1.0);
-        assert!(gs.needs_rebuild);
+        gs.add("A".into(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("A".into(), 1.0, 1.0, GeoAddOptions::default());
+        assert_eq!(gs.index_stats().leaf_count, 1);
+        assert_eq!(gs.geohash_stats().total_members, 1);
+
+        assert!(gs
+            .search_radius(GeoPoint { lon: 0.0, lat: 0.0 }, 1_000.0)
+            .is_empty());
+        assert_eq!(gs.nearest(0.0, 0.0, 5).len(), 1);
+        assert_eq!(
+            gs.search_radius(GeoPoint { lon: 1.0, lat: 1.0 }, 1_000.0)[0]
+                .0
+                .member,
+            "A"
+        );
 
         gs.rebuild_index();
-        assert!(!gs.needs_rebuild);
+        assert_eq!(
+            gs.search_radius(GeoPoint { lon: 1.0, lat: 1.0 }, 1_000.0)[0]
+                .0
+                .member,
+            "A"
+        );
+    }
+
+    /// Тест проверяет удаление точки из множества и всех индексов.
+    #[test]
+    fn test_remove() {
+        let mut gs = GeoSet::new();
+        for i in 0..100 {
+            let lon = (i % 10) as f64 * 0.01;
+            let lat = (i / 10) as f64 * 0.01;
+            gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
+        }
+
+        assert!(gs.remove("P0"));
+        assert!(!gs.remove("P0"));
+        assert!(!gs.remove("missing"));
+        assert_eq!(gs.len(), 99);
+        assert_eq!(gs.get("P0"), None);
+        assert_eq!(gs.geohash_stats().total_members, 99);
+
+        let hits = gs.search_radius(GeoPoint { lon: 0.0, lat: 0.0 }, 100.0);
+        assert!(hits.is_empty());
+        assert!(gs.nearest(0.0, 0.0, 100).iter().all(|(m, _)| m != "P0"));
+
+        for i in 1..100 {
+            assert!(gs.remove(&format!("P{i}")));
+        }
+        assert!(gs.is_empty());
+        assert_eq!(gs.index_stats().node_count, 0);
+        assert_eq!(gs.geohash_stats().bucket_count, 0);
+    }
+
+    /// Тест проверяет, что после множества обновлений и удалений
+    /// поиск по радиусу через R-tree совпадает с полным перебором.
+    #[test]
+    fn test_search_radius_matches_scan() {
+        let mut gs = GeoSet::new();
+        for i in 0..2_000 {
+            let lon = ((i * 37) % 360) as f64 - 180.0 + 0.5;
+            let lat = ((i * 53) % 170) as f64 - 85.0;
+            gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
+        }
+        for i in (0..2_000).step_by(3) {
+            let lon = ((i * 11) % 360) as f64 - 180.0;
+            gs.add(format!("P{i}"), lon, 10.0, GeoAddOptions::default());
+        }
+        for i in (0..2_000).step_by(7) {
+            gs.remove(&format!("P{i}"));
+        }
+
+        let centers = [
+            (0.0, 10.0),
+            (179.5, 0.0),
+            (-179.5, 60.0),
+            (100.0, 75.0),
+            (30.0, 88.0),
+        ];
+        for (lon, lat) in centers {
+            let center = GeoPoint { lon, lat };
+            let radius = 800_000.0;
+
+            let mut indexed: Vec<String> = gs
+                .search_radius(center, radius)
+                .into_iter()
+                .map(|(e, _)| e.member)
+                .collect();
+            indexed.sort();
+
+            let mut scanned: Vec<String> = gs
+                .iter()
+                .filter(|(_, p)| haversine_distance(center, **p) <= radius)
+                .map(|(m, _)| m.clone())
+                .collect();
+            scanned.sort();
+
+            assert!(!scanned.is_empty());
+            assert_eq!(indexed, scanned, "center {center:?}");
+        }
+    }
+
+    /// Тест проверяет сборку GeoSet из списка участников и обратный
+    /// отсортированный список.
+    #[test]
+    fn test_from_members_roundtrip() {
+        let members = vec![
+            ("b".to_string(), GeoPoint { lon: 1.0, lat: 1.0 }),
+            ("a".to_string(), GeoPoint { lon: 2.0, lat: 2.0 }),
+            (
+                "bad".to_string(),
+                GeoPoint {
+                    lon: 200.0,
+                    lat: 0.0,
+                },
+            ),
+            ("b".to_string(), GeoPoint { lon: 3.0, lat: 3.0 }),
+        ];
+        let gs = GeoSet::from_members(members);
+
+        assert_eq!(gs.len(), 2);
+        assert_eq!(gs.index_stats().leaf_count, 1);
+        let sorted: Vec<(String, GeoPoint)> = gs
+            .sorted_members()
+            .into_iter()
+            .map(|(m, p)| (m.clone(), p))
+            .collect();
+        assert_eq!(
+            sorted,
+            vec![
+                ("a".to_string(), GeoPoint { lon: 2.0, lat: 2.0 }),
+                ("b".to_string(), GeoPoint { lon: 3.0, lat: 3.0 }),
+            ]
+        );
+        assert_eq!(
+            gs.search_radius(GeoPoint { lon: 3.0, lat: 3.0 }, 10.0)[0]
+                .0
+                .member,
+            "b"
+        );
     }
 
     #[test]
     fn test_geohash_integration() {
         let mut gs = GeoSet::new();
-        gs.add("A".to_string(), 10.0, 20.0);
-        gs.add("B".to_string(), 10.1, 20.1);
+        gs.add("A".to_string(), 10.0, 20.0, GeoAddOptions::default());
+        gs.add("B".to_string(), 10.1, 20.1, GeoAddOptions::default());
 
         let gh = gs.get_geohash("A", GeohashPrecision::High).unwrap();
         assert_eq!(gh.precision(), 7);
@@ -829,7 +1221,7 @@ mod tests {
         for i in 0..100 {
             let lon = (i % 10) as f64 * 0.01;
             let lat = (i % 10) as f64 * 0.01;
-            gs.add(format!("P{i}"), lon, lat);
+            gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
         }
 
         let opts = RadiusOptions {
@@ -845,9 +1237,9 @@ mod tests {
     #[test]
     fn test_bbox_query() {
         let mut gs = GeoSet::new();
-        gs.add("A".to_string(), 0.0, 0.0);
-        gs.add("B".to_string(), 1.0, 1.0);
-        gs.add("C".to_string(), 5.0, 5.0);
+        gs.add("A".to_string(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("B".to_string(), 1.0, 1.0, GeoAddOptions::default());
+        gs.add("C".to_string(), 5.0, 5.0, GeoAddOptions::default());
 
         let bbox = BoundingBox::new(-0.5, 1.5, -0.5, 1.5);
         let results = gs.bbox_query(&bbox);
@@ -859,7 +1251,12 @@ mod tests {
         let mut gs = GeoSet::new();
 
         for i in 0..50 {
-            gs.add(format!("P{i}"), (i as f64) * 0.1, 0.0);
+            gs.add(
+                format!("P{i}"),
+                (i as f64) * 0.1,
+                0.0,
+                GeoAddOptions::default(),
+            );
         }
 
         let stats = gs.geohash_stats();
@@ -875,7 +1272,7 @@ mod tests {
         for i in 0..1000 {
             let lon = (i % 100) as f64 * 0.01;
             let lat = (i / 100) as f64 * 0.01;
-            gs.add(format!("P{i}"), lon, lat);
+            gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
         }
 
         // C geohash filtering
@@ -902,8 +1299,8 @@ mod tests {
     #[test]
     fn test_dist_with_different_methods() {
         let mut gs = GeoSet::new();
-        gs.add("A".into(), 0.0, 0.0);
-        gs.add("B".into(), 1.0, 0.0);
+        gs.add("A".into(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("B".into(), 1.0, 0.0, GeoAddOptions::default());
 
         let haversine = gs
             .dist_with_method("A", "B", DistanceMethod::Haversine)
@@ -922,8 +1319,8 @@ mod tests {
     #[test]
     fn test_dist_in_units() {
         let mut gs = GeoSet::new();
-        gs.add("X".into(), 0.0, 0.0);
-        gs.add("Y".into(), 0.0, 1.0); // ~111км
+        gs.add("X".into(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("Y".into(), 0.0, 1.0, GeoAddOptions::default()); // ~111км
 
         let meters = gs
             .dist_in_units("X", "Y", DistanceMethod::Haversine, DistanceUnit::Meters)
@@ -948,7 +1345,7 @@ mod tests {
         for i in 0..100 {
             let lon = (i % 10) as f64 * 0.01;
             let lat = (i / 10) as f64 * 0.01;
-            gs.add(format!("P{i}"), lon, lat);
+            gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
         }
 
         let opts = RadiusOptions {
@@ -977,10 +1374,10 @@ mod tests {
     #[test]
     fn test_nearest_with_manhattan() {
         let mut gs = GeoSet::new();
-        gs.add("A".into(), 0.0, 0.0);
-        gs.add("B".into(), 0.1, 0.0);
-        gs.add("C".into(), 0.0, 0.1);
-        gs.add("D".into(), 1.0, 1.0);
+        gs.add("A".into(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("B".into(), 0.1, 0.0, GeoAddOptions::default());
+        gs.add("C".into(), 0.0, 0.1, GeoAddOptions::default());
+        gs.add("D".into(), 1.0, 1.0, GeoAddOptions::default());
 
         let results = gs.nearest_with_method(0.0, 0.0, 2, DistanceMethod::Manhattan);
 
@@ -988,4 +1385,160 @@ mod tests {
         // A должен быть первым (расстояние 0)
         assert_eq!(results[0].0, "A");
     }
+
+    /// Тест проверяет флаги `GeoAddOptions`: `NX` не обновляет точку, `XX`
+    /// не добавляет новую, `CH` учитывает изменение координат.
+    #[test]
+    fn test_add_with_options() {
+        let mut gs = GeoSet::new();
+        let nx = GeoAddOptions {
+            nx: true,
+            ..Default::default()
+        };
+        let xx = GeoAddOptions {
+            xx: true,
+            ..Default::default()
+        };
+        let ch = GeoAddOptions {
+            ch: true,
+            ..Default::default()
+        };
+
+        assert!(!gs.add("A".into(), 1.0, 1.0, xx));
+        assert!(gs.is_empty());
+        assert!(gs.add("A".into(), 1.0, 1.0, nx));
+        assert!(!gs.add("A".into(), 2.0, 2.0, nx));
+        assert_eq!(gs.get("A"), Some(GeoPoint { lon: 1.0, lat: 1.0 }));
+
+        assert!(!gs.add("A".into(), 2.0, 2.0, GeoAddOptions::default()));
+        assert!(gs.add("A".into(), 3.0, 3.0, ch));
+        assert!(!gs.add("A".into(), 3.0, 3.0, ch));
+        assert_eq!(gs.get("A"), Some(GeoPoint { lon: 3.0, lat: 3.0 }));
+    }
+
+    /// Тест проверяет `bbox_search` и `search`: прямоугольник учитывает
+    /// ширину и высоту отдельно, `COUNT` без порядка сортирует по
+    /// возрастанию, а отсутствующий `FROMMEMBER` даёт `None`.
+    #[test]
+    fn test_bbox_and_search() {
+        let mut gs = GeoSet::new();
+        gs.add("center".into(), 0.0, 0.0, GeoAddOptions::default());
+        gs.add("east".into(), 0.01, 0.0, GeoAddOptions::default()); // ~1.1км
+        gs.add("north".into(), 0.0, 0.01, GeoAddOptions::default()); // ~1.1км
+
+        let mut found: Vec<String> = gs
+            .bbox_search(0.0, 0.0, 3000.0, 1000.0)
+            .into_iter()
+            .map(|(m, _)| m)
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["center".to_string(), "east".to_string()]);
+
+        let mut params = GeoSearchParams {
+            from: GeoSearchFrom::Member("east".into()),
+            shape: GeoSearchShape::Radius(5.0),
+            unit: DistanceUnit::Kilometers,
+            order: None,
+            count: Some(2),
+            store_dist: false,
+        };
+        let hits = gs.search(&params).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].0, "east");
+        assert_eq!(hits[1].0, "center");
+        assert!((hits[1].1 - 1.11).abs() < 0.01);
+
+        params.from = GeoSearchFrom::Member("missing".into());
+        assert!(gs.search(&params).is_none());
+    }
+
+    /// Тест проверяет, что `search_box` находит точки по обе стороны
+    /// антимеридиана и не захватывает точки за пределами ширины.
+    #[test]
+    fn test_search_box_antimeridian() {
+        let mut gs = GeoSet::new();
+        gs.add("west".into(), 179.9, 0.0, GeoAddOptions::default());
+        gs.add("east".into(), -179.9, 0.0, GeoAddOptions::default());
+        gs.add("far".into(), 178.0, 0.0, GeoAddOptions::default());
+        gs.add("north".into(), 180.0, 1.0, GeoAddOptions::default());
+
+        // 50км × 50км вокруг 180°: ±0.22° по долготе и широте
+        let center = GeoPoint {
+            lon: 180.0,
+            lat: 0.0,
+        };
+        let mut found: Vec<String> = gs
+            .search_box(center, 50_000.0, 50_000.0)
+            .into_iter()
+            .map(|e| e.member)
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["east".to_string(), "west".to_string()]);
+
+        // центр по другую сторону антимеридиана даёт тот же результат
+        let center = GeoPoint {
+            lon: -179.95,
+            lat: 0.0,
+        };
+        assert_eq!(gs.search_box(center, 50_000.0, 50_000.0).len(), 2);
+    }
+
+    /// Тест проверяет, что прямоугольник, накрывающий полюс, не ограничен
+    /// по долготе, а по широте ограничен высотой.
+    #[test]
+    fn test_search_box_poles() {
+        let mut gs = GeoSet::new();
+        gs.add("a".into(), 0.0, 89.9, GeoAddOptions::default());
+        gs.add("b".into(), 180.0, 89.5, GeoAddOptions::default());
+        gs.add("c".into(), -90.0, 89.0, GeoAddOptions::default());
+        gs.add("low".into(), 0.0, 85.0, GeoAddOptions::default());
+        gs.add("south".into(), 0.0, -89.9, GeoAddOptions::default());
+
+        // высота 400км: от полюса вниз на ~1.8°
+        let center = GeoPoint {
+            lon: 0.0,
+            lat: 89.9,
+        };
+        let mut found: Vec<String> = gs
+            .search_box(center, 1_000.0, 400_000.0)
+            .into_iter()
+            .map(|e| e.member)
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+
+        let center = GeoPoint {
+            lon: 0.0,
+            lat: -90.0,
+        };
+        let found = gs.search_box(center, 10.0, 100_000.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].member, "south");
+    }
+
+    /// Тест проверяет, что `search_box` учитывает обновлённые координаты и
+    /// ширину в градусах долготы на широте центра.
+    #[test]
+    fn test_search_box_geographic_width() {
+        let mut gs = GeoSet::new();
+        // на 60° широты градус долготы вдвое короче, чем на экваторе
+        gs.add("p".into(), 1.8, 60.0, GeoAddOptions::default());
+        let center = GeoPoint {
+            lon: 0.0,
+            lat: 60.0,
+        };
+        assert_eq!(gs.search_box(center, 220_000.0, 1_000.0).len(), 1);
+        assert!(gs.search_box(center, 180_000.0, 1_000.0).is_empty());
+
+        // на экваторе та же ширина покрывает меньше градусов
+        gs.add("q".into(), 1.8, 0.0, GeoAddOptions::default());
+        let equator = GeoPoint { lon: 0.0, lat: 0.0 };
+        assert!(gs.search_box(equator, 220_000.0, 1_000.0).is_empty());
+
+        gs.add("p".into(), 10.0, 60.0, GeoAddOptions::default());
+        assert!(gs.search_box(center, 220_000.0, 1_000.0).is_empty());
+    }
 }
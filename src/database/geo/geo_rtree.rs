@@ -1,9 +1,15 @@
-use std::{cmp::Ordering, collections::BinaryHeap, f64};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    f64::{self, consts::PI},
+};
 
-use crate::{database::haversine_distance, GeoEntry, GeoPoint};
+use crate::{
+    database::{geo_distance::Ellipsoid, haversine_distance},
+    GeoEntry, GeoPoint,
+};
 
 /// Минимальное количество элементов в узле (обычно M/2).
-#[allow(dead_code)]
 const MIN_ENTRIES: usize = 2;
 /// Максимальное количество элементов в узле.
 const MAX_ENTRIES: usize = 8;
@@ -160,6 +166,87 @@ impl BoundingBox {
             lat: (self.min_lat + self.max_lat) * 0.5,
         }
     }
+
+    /// Строит прямоугольник `width_m` × `height_m` метров с центром в
+    /// `center`, стороны которого идут по меридианам и параллелям.
+    ///
+    /// Высота переводится в градусы широты, ширина — в градусы долготы на
+    /// широте центра. Прямоугольник, пересекающий антимеридиан (±180°),
+    /// делится на два. Если прямоугольник накрывает полюс или шире всей
+    /// параллели, долгота не ограничивается.
+    pub fn around(
+        center: GeoPoint,
+        width_m: f64,
+        height_m: f64,
+    ) -> Vec<BoundingBox> {
+        let meters_per_degree = Ellipsoid::SPHERE.a * PI / 180.0;
+        let lat_delta = height_m * 0.5 / meters_per_degree;
+        let min_lat = (center.lat - lat_delta).max(-90.0);
+        let max_lat = (center.lat + lat_delta).min(90.0);
+
+        let lon_delta = width_m * 0.5 / (meters_per_degree * center.lat.to_radians().cos());
+        Self::lon_span(center.lon, lon_delta, min_lat, max_lat)
+    }
+
+    /// Строит прямоугольники, описанные вокруг круга радиуса `radius_m`
+    /// метров с центром в `center`.
+    ///
+    /// В отличие от [`BoundingBox::around`] учитывает, что круг шире всего
+    /// не на широте центра, а ближе к полюсу: полуширина по долготе равна
+    /// `asin(sin(r / R) / cos(lat))`. Разрез по антимеридиану и полюса
+    /// обрабатываются так же.
+    pub fn around_radius(
+        center: GeoPoint,
+        radius_m: f64,
+    ) -> Vec<BoundingBox> {
+        let angle = radius_m / Ellipsoid::SPHERE.a;
+        let lat_delta = angle.to_degrees();
+        let min_lat = (center.lat - lat_delta).max(-90.0);
+        let max_lat = (center.lat + lat_delta).min(90.0);
+
+        let lon_delta = if angle >= PI * 0.5 {
+            f64::INFINITY
+        } else {
+            let sin_delta = angle.sin() / center.lat.to_radians().cos();
+            if sin_delta >= 1.0 {
+                f64::INFINITY
+            } else {
+                sin_delta.asin().to_degrees()
+            }
+        };
+        Self::lon_span(center.lon, lon_delta, min_lat, max_lat)
+    }
+
+    /// Диапазон долгот `center_lon ± lon_delta` в полосе широт
+    /// `min_lat..=max_lat`: делится по антимеридиану, а у полюса или при
+    /// ширине от 180° не ограничивается.
+    fn lon_span(
+        center_lon: f64,
+        lon_delta: f64,
+        min_lat: f64,
+        max_lat: f64,
+    ) -> Vec<BoundingBox> {
+        let covers_pole = min_lat <= -90.0 || max_lat >= 90.0;
+        if covers_pole || !lon_delta.is_finite() || lon_delta >= 180.0 {
+            return vec![Self::new(-180.0, 180.0, min_lat, max_lat)];
+        }
+
+        let min_lon = center_lon - lon_delta;
+        let max_lon = center_lon + lon_delta;
+        if min_lon < -180.0 {
+            vec![
+                Self::new(min_lon + 360.0, 180.0, min_lat, max_lat),
+                Self::new(-180.0, max_lon, min_lat, max_lat),
+            ]
+        } else if max_lon > 180.0 {
+            vec![
+                Self::new(min_lon, 180.0, min_lat, max_lat),
+                Self::new(-180.0, max_lon - 360.0, min_lat, max_lat),
+            ]
+        } else {
+            vec![Self::new(min_lon, max_lon, min_lat, max_lat)]
+        }
+    }
 }
 
 impl RTreeNode {
@@ -193,6 +280,29 @@ impl RTreeNode {
         matches!(self, RTreeNode::Leaf { .. })
     }
 
+    /// Количество точек листа или дочерних узлов внутреннего узла.
+    fn fanout(&self) -> usize {
+        match self {
+            RTreeNode::Leaf { entries, .. } => entries.len(),
+            RTreeNode::Internal { children, .. } => children.len(),
+        }
+    }
+
+    /// Забирает все точки поддерева в `out`.
+    fn drain_into(
+        self,
+        out: &mut Vec<GeoEntry>,
+    ) {
+        match self {
+            RTreeNode::Leaf { entries, .. } => out.extend(entries),
+            RTreeNode::Internal { children, .. } => {
+                for child in children {
+                    child.drain_into(out);
+                }
+            }
+        }
+    }
+
     /// Обновляет bounding box узла на основе дочерних элементов.
     fn update_bbox(&mut self) {
         match self {
@@ -535,6 +645,89 @@ impl RTree {
         }
     }
 
+    /// Удаляет точку `member` с координатами `point`.
+    ///
+    /// Спуск идёт только в поддеревья, чей bbox содержит точку, поэтому
+    /// удаление стоит O(log n). Узел, в котором после удаления осталось
+    /// меньше [`MIN_ENTRIES`] элементов, изымается из дерева, а его точки
+    /// вставляются заново; корень с единственным потомком схлопывается.
+    ///
+    /// Возвращает `true`, если точка найдена и удалена.
+    pub fn remove(
+        &mut self,
+        member: &str,
+        point: GeoPoint,
+    ) -> bool {
+        let mut orphans = Vec::new();
+        let removed = match self.root.as_mut() {
+            Some(root) => Self::remove_recursive(root, member, point, &mut orphans),
+            None => false,
+        };
+        if !removed {
+            return false;
+        }
+
+        // Схлопываем корень, пока у него остаётся один потомок
+        while let Some(root) = self.root.take() {
+            match *root {
+                RTreeNode::Internal { mut children, .. } if children.len() <= 1 => {
+                    self.root = children.pop();
+                }
+                RTreeNode::Leaf { ref entries, .. } if entries.is_empty() => {}
+                node => {
+                    self.root = Some(Box::new(node));
+                    break;
+                }
+            }
+        }
+
+        // Точки изъятых узлов уже учтены в size
+        self.size -= 1 + orphans.len();
+        for entry in orphans {
+            self.insert(entry);
+        }
+        true
+    }
+
+    /// Рекурсивное удаление. Недозаполненные дочерние узлы на пути к точке
+    /// изымаются, их точки складываются в `orphans` для повторной вставки.
+    fn remove_recursive(
+        node: &mut RTreeNode,
+        member: &str,
+        point: GeoPoint,
+        orphans: &mut Vec<GeoEntry>,
+    ) -> bool {
+        if !node.bbox().contains_point(point) {
+            return false;
+        }
+
+        match node {
+            RTreeNode::Leaf { entries, .. } => {
+                let Some(pos) = entries
+                    .iter()
+                    .position(|e| e.member == member && e.point == point)
+                else {
+                    return false;
+                };
+                entries.swap_remove(pos);
+            }
+            RTreeNode::Internal { children, .. } => {
+                let Some(idx) = children
+                    .iter_mut()
+                    .position(|child| Self::remove_recursive(child, member, point, orphans))
+                else {
+                    return false;
+                };
+                if children[idx].fanout() < MIN_ENTRIES {
+                    children.swap_remove(idx).drain_into(orphans);
+                }
+            }
+        }
+
+        node.update_bbox();
+        true
+    }
+
     /// Выбирает лучший дочерний узел для вставки (минимальное увеличение
     /// площади).
     fn choose_subtree(
@@ -765,6 +958,45 @@ impl RTree {
         }
     }
 
+    /// Box query: все точки, попавшие хотя бы в один из `boxes`, за один
+    /// обход дерева. Поддеревья, не пересекающие ни один прямоугольник,
+    /// пропускаются целиком.
+    pub fn box_query(
+        &self,
+        boxes: &[BoundingBox],
+    ) -> Vec<GeoEntry> {
+        let mut results = Vec::new();
+        if let Some(ref root) = self.root {
+            Self::box_query_recursive(root, boxes, &mut results);
+        }
+        results
+    }
+
+    fn box_query_recursive(
+        node: &RTreeNode,
+        boxes: &[BoundingBox],
+        results: &mut Vec<GeoEntry>,
+    ) {
+        if !boxes.iter().any(|b| node.bbox().intersects(b)) {
+            return;
+        }
+
+        match node {
+            RTreeNode::Leaf { entries, .. } => {
+                for entry in entries {
+                    if boxes.iter().any(|b| b.contains_point(entry.point)) {
+                        results.push(entry.clone());
+                    }
+                }
+            }
+            RTreeNode::Internal { children, .. } => {
+                for child in children {
+                    Self::box_query_recursive(child, boxes, results);
+                }
+            }
+        }
+    }
+
     /// k-NN search: k ближайших соседей к точке.
     /// k-NN search: k ближайших соседей к точке.
     pub fn knn(
@@ -1022,6 +1254,76 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    /// Тест проверяет `BoundingBox::around`: обычный прямоугольник, разрез
+    /// по антимеридиану и снятие ограничения долготы у полюса.
+    #[test]
+    fn test_bbox_around() {
+        let meters_per_degree = Ellipsoid::SPHERE.a * PI / 180.0;
+
+        let boxes = BoundingBox::around(
+            GeoPoint {
+                lon: 10.0,
+                lat: 0.0,
+            },
+            2.0 * meters_per_degree,
+            meters_per_degree,
+        );
+        assert_eq!(boxes.len(), 1);
+        assert!((boxes[0].min_lon - 9.0).abs() < 1e-9);
+        assert!((boxes[0].max_lon - 11.0).abs() < 1e-9);
+        assert!((boxes[0].max_lat - 0.5).abs() < 1e-9);
+
+        let boxes = BoundingBox::around(
+            GeoPoint {
+                lon: 179.5,
+                lat: 0.0,
+            },
+            2.0 * meters_per_degree,
+            meters_per_degree,
+        );
+        assert_eq!(boxes.len(), 2);
+        assert!((boxes[0].min_lon - 178.5).abs() < 1e-9);
+        assert_eq!(boxes[0].max_lon, 180.0);
+        assert_eq!(boxes[1].min_lon, -180.0);
+        assert!((boxes[1].max_lon + 179.5).abs() < 1e-9);
+
+        let boxes = BoundingBox::around(
+            GeoPoint {
+                lon: 0.0,
+                lat: 89.5,
+            },
+            1000.0,
+            2.0 * meters_per_degree,
+        );
+        assert_eq!(boxes, vec![BoundingBox::new(-180.0, 180.0, 88.5, 90.0)]);
+    }
+
+    /// Тест проверяет, что `box_query` за один обход собирает точки из
+    /// нескольких прямоугольников и не дублирует их.
+    #[test]
+    fn test_box_query() {
+        let entries: Vec<GeoEntry> = (0..360)
+            .map(|i| make_entry(&format!("p{i}"), -179.5 + i as f64, 0.0))
+            .collect();
+        let tree = RTree::bulk_load(entries);
+
+        let boxes = [
+            BoundingBox::new(178.0, 180.0, -1.0, 1.0),
+            BoundingBox::new(-180.0, -178.0, -1.0, 1.0),
+        ];
+        let mut found: Vec<f64> = tree
+            .box_query(&boxes)
+            .into_iter()
+            .map(|e| e.point.lon)
+            .collect();
+        found.sort_by(f64::total_cmp);
+        assert_eq!(found, vec![-179.5, -178.5, 178.5, 179.5]);
+
+        assert!(tree
+            .box_query(&[BoundingBox::new(0.0, 1.0, 5.0, 6.0)])
+            .is_empty());
+    }
+
     /// Тест проверяет корректность bulk loading R-tree и адекватную глубину
     /// дерева после построения.
     #[test]
@@ -1055,6 +1357,60 @@ mod tests {
         assert_eq!(results[1].0.member, "B");
     }
 
+    /// Тест проверяет удаление точек: оставшиеся находятся запросами,
+    /// удалённые — нет, повторное удаление и удаление по чужим координатам
+    /// ничего не меняют.
+    #[test]
+    fn test_remove() {
+        let mut tree = RTree::new();
+        for i in 0..200 {
+            tree.insert(make_entry(
+                &format!("P{i}"),
+                (i % 20) as f64,
+                (i / 20) as f64,
+            ));
+        }
+
+        assert!(!tree.remove("P0", GeoPoint { lon: 5.0, lat: 5.0 }));
+        for i in (0..200).step_by(2) {
+            let point = GeoPoint {
+                lon: (i % 20) as f64,
+                lat: (i / 20) as f64,
+            };
+            assert!(tree.remove(&format!("P{i}"), point));
+            assert!(!tree.remove(&format!("P{i}"), point));
+        }
+        assert_eq!(tree.len(), 100);
+
+        let mut found = tree.range_query(&BoundingBox::new(-1.0, 20.0, -1.0, 10.0));
+        found.sort_by_key(|e| e.member[1..].parse::<usize>().unwrap());
+        let expected: Vec<String> = (1..200).step_by(2).map(|i| format!("P{i}")).collect();
+        let members: Vec<String> = found.into_iter().map(|e| e.member).collect();
+        assert_eq!(members, expected);
+    }
+
+    /// Тест проверяет, что после удаления всех точек дерево пустеет и снова
+    /// принимает вставки.
+    #[test]
+    fn test_remove_all() {
+        let entries: Vec<GeoEntry> = (0..50)
+            .map(|i| make_entry(&format!("P{i}"), i as f64 * 0.5, -(i as f64) * 0.5))
+            .collect();
+        let mut tree = RTree::bulk_load(entries.clone());
+
+        for e in &entries {
+            assert!(tree.remove(&e.member, e.point));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.stats().node_count, 0);
+
+        tree.insert(make_entry("A", 1.0, 1.0));
+        assert_eq!(
+            tree.knn(GeoPoint { lon: 0.0, lat: 0.0 }, 1)[0].0.member,
+            "A"
+        );
+    }
+
     /// Тест проверяет корректность работы R-tree на большом количестве данных и
     /// ограниченную глубину дерева.
     #[test]
@@ -0,0 +1,141 @@
+//! Сопоставление с glob-шаблонами в стиле Redis.
+//!
+//! Используется командами `KEYS` и параметром `MATCH` у `SCAN`, `HSCAN`,
+//! `SSCAN` и `ZSCAN`. Поддерживаются:
+//!
+//! - `?` — любой один байт;
+//! - `*` — любая (в том числе пустая) последовательность;
+//! - `[abc]`, `[a-z]` — класс символов, `[^abc]` — отрицание класса;
+//! - `\x` — экранирование специального символа.
+
+/// Проверяет, соответствует ли `input` glob-шаблону `pattern`.
+///
+/// Сравнение побайтовое, поэтому работает и для не-UTF-8 ключей.
+pub fn glob_match(
+    pattern: &[u8],
+    input: &[u8],
+) -> bool {
+    let (mut p, mut i) = (0, 0);
+    // Позиция последней `*` в шаблоне и позиция во входе, с которой она
+    // начала сопоставляться.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while i < input.len() {
+        let step = match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, i));
+                p += 1;
+                continue;
+            }
+            Some(b'?') => Some(p + 1),
+            Some(b'[') => match_class(pattern, p, input[i]),
+            Some(b'\\') if p + 1 < pattern.len() => (pattern[p + 1] == input[i]).then_some(p + 2),
+            Some(&c) => (c == input[i]).then_some(p + 1),
+            None => None,
+        };
+
+        match step {
+            Some(next) => {
+                p = next;
+                i += 1;
+            }
+            None => match backtrack {
+                // `*` поглощает ещё один байт входа.
+                Some((star_p, star_i)) => {
+                    backtrack = Some((star_p, star_i + 1));
+                    p = star_p + 1;
+                    i = star_i + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    // Остаток шаблона может состоять только из `*`.
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Сопоставляет байт `c` с классом `[...]`, начинающимся в `pattern[start]`.
+///
+/// Возвращает позицию сразу после класса, если байт подходит. Незакрытый
+/// класс распространяется до конца шаблона.
+fn match_class(
+    pattern: &[u8],
+    start: usize,
+    c: u8,
+) -> Option<usize> {
+    let mut p = start + 1;
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+
+    let mut matched = false;
+    while p < pattern.len() && pattern[p] != b']' {
+        if pattern[p] == b'\\' && p + 1 < pattern.len() {
+            matched |= pattern[p + 1] == c;
+            p += 2;
+        } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' && pattern[p + 2] != b']' {
+            let (lo, hi) = (
+                pattern[p].min(pattern[p + 2]),
+                pattern[p].max(pattern[p + 2]),
+            );
+            matched |= (lo..=hi).contains(&c);
+            p += 3;
+        } else {
+            matched |= pattern[p] == c;
+            p += 1;
+        }
+    }
+
+    (matched != negate).then_some((p + 1).min(pattern.len()))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(
+        pattern: &str,
+        input: &str,
+    ) -> bool {
+        glob_match(pattern.as_bytes(), input.as_bytes())
+    }
+
+    /// Тест проверяет `*`, `?` и точное совпадение.
+    #[test]
+    fn test_star_and_question() {
+        assert!(m("*", ""));
+        assert!(m("*", "anything"));
+        assert!(m("user:*", "user:42"));
+        assert!(!m("user:*", "session:42"));
+        assert!(m("h?llo", "hello"));
+        assert!(!m("h?llo", "hllo"));
+        assert!(m("*o*o*", "foobar"));
+        assert!(m("exact", "exact"));
+        assert!(!m("exact", "exactly"));
+    }
+
+    /// Тест проверяет классы символов, диапазоны и отрицание.
+    #[test]
+    fn test_classes() {
+        assert!(m("h[ae]llo", "hallo"));
+        assert!(!m("h[ae]llo", "hillo"));
+        assert!(m("h[^e]llo", "hallo"));
+        assert!(!m("h[^e]llo", "hello"));
+        assert!(m("key[0-9]", "key7"));
+        assert!(!m("key[0-9]", "keyx"));
+    }
+
+    /// Тест проверяет экранирование специальных символов.
+    #[test]
+    fn test_escape() {
+        assert!(m("a\\*b", "a*b"));
+        assert!(!m("a\\*b", "axb"));
+        assert!(m("[\\]]", "]"));
+    }
+}
@@ -158,31 +158,34 @@ impl<const P: usize, H: HllHasher> Hll<P, H> {
         self.hasher.name()
     }
 
-    /// Добавляет элемент `value` в структуру HLL
+    /// Добавляет элемент `value` в структуру HLL.
+    ///
+    /// Возвращает `true`, если изменился хотя бы один регистр.
     pub fn add(
         &mut self,
         value: &[u8],
-    ) {
+    ) -> bool {
         let hash = self.hasher.hash_bytes(value);
         let (index, rho) = Self::index_and_rho(hash);
 
         match &mut self.encoding {
             HllEncoding::Sparse(sparse) => {
-                let current = sparse.get_register(index);
-                if rho > current {
-                    sparse.set_register(index, rho);
-
-                    // Проверяем, нужно ли переключиться на dense
-                    if sparse.should_convert_to_dense() {
-                        self.convert_to_dense();
-                    }
+                if !sparse.set_register(index, rho) {
+                    return false;
                 }
+                // Проверяем, нужно ли переключиться на dense
+                if sparse.should_convert_to_dense() {
+                    self.convert_to_dense();
+                }
+                true
             }
             HllEncoding::Dense(dense) => {
                 let current = dense.get_register(index);
                 if rho > current {
                     dense.set_register(index, rho);
+                    return true;
                 }
+                false
             }
         }
     }
@@ -268,6 +271,31 @@ impl<const P: usize, H: HllHasher> Hll<P, H> {
         }
     }
 
+    /// Создаёт HLL, оценивающий мощность объединения всех `sources`
+    /// (регистр результата — максимум соответствующих регистров).
+    ///
+    /// Хешер и порог конверсии берутся из первого источника; для пустого
+    /// списка возвращается пустой HLL.
+    pub fn merge_all(sources: &[&Hll<P, H>]) -> Self {
+        let Some((first, rest)) = sources.split_first() else {
+            return Self::new();
+        };
+        let mut merged = (*first).clone();
+        for source in rest {
+            merged.merge(source);
+        }
+        merged
+    }
+
+    /// Возвращает память кучи, занятую регистрами (без
+    /// `size_of::<Hll>()`).
+    pub fn heap_size(&self) -> usize {
+        match &self.encoding {
+            HllEncoding::Sparse(sparse) => sparse.memory_footprint(),
+            HllEncoding::Dense(dense) => dense.memory_footprint(),
+        }
+    }
+
     /// Возвращает статистику использования HLL.
     pub fn stats(&self) -> HllStats {
         match &self.encoding {
@@ -932,6 +960,29 @@ mod tests {
         assert!((card_merged - (card1 + card2)).abs() < 20.0);
     }
 
+    #[test]
+    fn test_merge_all_disjoint() {
+        let mut hll1 = H::new();
+        let mut hll2 = H::new();
+        let mut hll3 = H::new();
+
+        for i in 0..5000 {
+            hll1.add(format!("a_{i}").as_bytes());
+            hll2.add(format!("b_{i}").as_bytes());
+        }
+        for i in 0..50 {
+            hll3.add(format!("c_{i}").as_bytes());
+        }
+        assert!(hll3.is_sparse());
+
+        let sum =
+            hll1.estimate_cardinality() + hll2.estimate_cardinality() + hll3.estimate_cardinality();
+        let merged = H::merge_all(&[&hll1, &hll2, &hll3]);
+
+        assert!((merged.estimate_cardinality() - sum).abs() / sum < 0.02);
+        assert_eq!(H::merge_all(&[]).estimate_cardinality(), 0.0);
+    }
+
     #[test]
     fn test_stats_includes_hasher_name() {
         let hll = Hll::<14, XxHasher>::new();
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{cmp::Ordering, mem::size_of};
 
 use serde::{Deserialize, Serialize};
 
@@ -6,10 +6,14 @@ use serde::{Deserialize, Serialize};
 pub const DEFAULT_SPARSE_THRESHOLD: usize = 3000;
 
 /// Разреженное представление HyperLogLog с настраиваемой точностью.
+///
+/// Хранит только ненулевые регистры парами `(индекс, значение)`,
+/// упорядоченными по индексу. Индекс — `u32`, так как при `P > 16`
+/// регистров больше, чем вмещает `u16`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HllSparse<const P: usize> {
-    /// Карта ненулевых регистров: индекс -> значение
-    registers: BTreeMap<usize, u8>,
+    /// Ненулевые регистры, отсортированные по индексу
+    registers: Vec<(u32, u8)>,
     /// Порог для переключения на dense encoding
     threshold: usize,
 }
@@ -27,12 +31,14 @@ impl<const P: usize> HllSparse<P> {
     /// Создаёт новый sparse HLL с заданным порогом.
     pub fn with_threshold(threshold: usize) -> Self {
         Self {
-            registers: BTreeMap::new(),
+            registers: Vec::new(),
             threshold,
         }
     }
 
-    /// Устанавливает значение регистра.
+    /// Устанавливает значение регистра, если оно больше текущего.
+    ///
+    /// Возвращает `true`, если регистр изменился.
     #[inline]
     pub fn set_register(
         &mut self,
@@ -43,10 +49,15 @@ impl<const P: usize> HllSparse<P> {
             return false;
         }
 
-        match self.registers.get(&index) {
-            Some(&current) if current >= value => false,
-            _ => {
-                self.registers.insert(index, value);
+        let index = index as u32;
+        match self.registers.binary_search_by_key(&index, |&(i, _)| i) {
+            Ok(pos) if self.registers[pos].1 >= value => false,
+            Ok(pos) => {
+                self.registers[pos].1 = value;
+                true
+            }
+            Err(pos) => {
+                self.registers.insert(pos, (index, value));
                 true
             }
         }
@@ -58,7 +69,9 @@ impl<const P: usize> HllSparse<P> {
         &self,
         index: usize,
     ) -> u8 {
-        self.registers.get(&index).copied().unwrap_or(0)
+        self.registers
+            .binary_search_by_key(&(index as u32), |&(i, _)| i)
+            .map_or(0, |pos| self.registers[pos].1)
     }
 
     /// Проверяет, нужно ли конвертировать в dense.
@@ -66,6 +79,11 @@ impl<const P: usize> HllSparse<P> {
         self.registers.len() > self.threshold
     }
 
+    /// Возвращает порог конверсии в dense.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
     /// Проверяет, пустой ли sparse HLL.
     pub fn is_empty(&self) -> bool {
         self.registers.is_empty()
@@ -75,26 +93,55 @@ impl<const P: usize> HllSparse<P> {
         self.registers.len()
     }
 
+    /// Возвращает ненулевые регистры в порядке возрастания индекса.
     pub fn iter(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
-        self.registers.iter().map(|(&k, &v)| (k, v))
+        self.registers.iter().map(|&(i, v)| (i as usize, v))
     }
 
-    /// Возвращает приблизительную память кучи, используемую BTreeMap в байтах.
+    /// Возвращает память кучи, занятую вектором регистров, в байтах.
     /// (не включает size_of::<HllSparse>(), чтобы не дублировать при суммарном
     /// подсчёте)
     pub fn memory_footprint(&self) -> usize {
-        // Эвристика: ~32 байта на узел BTreeMap (можно скорректировать)
-        const BTREE_NODE_OVERHEAD: usize = 32;
-        self.registers.len().saturating_mul(BTREE_NODE_OVERHEAD)
+        self.registers.capacity() * size_of::<(u32, u8)>()
     }
 
+    /// Объединяет регистры с `other`, оставляя максимум по каждому индексу.
     pub fn merge(
         &mut self,
         other: &HllSparse<P>,
     ) {
-        for (&index, &value) in other.registers.iter() {
-            self.set_register(index, value);
+        let mut merged = Vec::with_capacity(self.registers.len() + other.registers.len());
+        let (mut left, mut right) = (self.registers.iter(), other.registers.iter());
+        let (mut a, mut b) = (left.next(), right.next());
+        loop {
+            match (a, b) {
+                (Some(&x), Some(&y)) => match x.0.cmp(&y.0) {
+                    Ordering::Less => {
+                        merged.push(x);
+                        a = left.next();
+                    }
+                    Ordering::Greater => {
+                        merged.push(y);
+                        b = right.next();
+                    }
+                    Ordering::Equal => {
+                        merged.push((x.0, x.1.max(y.1)));
+                        a = left.next();
+                        b = right.next();
+                    }
+                },
+                (Some(&x), None) => {
+                    merged.push(x);
+                    a = left.next();
+                }
+                (None, Some(&y)) => {
+                    merged.push(y);
+                    b = right.next();
+                }
+                (None, None) => break,
+            }
         }
+        self.registers = merged;
     }
 
     pub fn clear(&mut self) {
@@ -225,6 +272,18 @@ mod tests {
         assert!(filled_size > empty_size);
     }
 
+    #[test]
+    fn test_memory_far_below_dense() {
+        let mut sparse = HllSparse::<14>::new();
+        for i in (0..100).rev() {
+            sparse.set_register(i * 150, 1);
+        }
+
+        let dense = crate::HllDense::<14>::from_sparse(&sparse);
+        assert!(sparse.memory_footprint() * 10 < dense.size());
+        assert!(sparse.iter().is_sorted_by_key(|(i, _)| i));
+    }
+
     #[test]
     fn test_clear() {
         let mut sparse = HllSparse::<14>::new();
@@ -246,7 +305,7 @@ mod tests {
 
         let collected: Vec<_> = sparse.iter().collect();
 
-        // BTreeMap гарантирует упорядоченность
+        // Регистры хранятся упорядоченными по индексу
         assert_eq!(collected.len(), 3);
         assert_eq!(collected[0], (50, 3));
         assert_eq!(collected[1], (100, 5));
@@ -1,3 +1,5 @@
+use std::iter::FusedIterator;
+
 /// Внутренний тип кодирования значений в `IntSet`.
 ///
 /// Определяет, какой тип используется для хранения чисел:
@@ -11,21 +13,17 @@ enum Encoding {
     Int64,
 }
 
-/// Итератор по всем элементам `IntSet`.
+/// Итератор по элементам `IntSet` (всем или диапазону).
+///
+/// Проходит по срезу хранилища текущего кодирования без копирования и
+/// выделений памяти, расширяя значения до `i64` на лету.
+#[derive(Debug, Clone)]
 pub enum IntSetIter<'a> {
     Int16(std::slice::Iter<'a, i16>),
     Int32(std::slice::Iter<'a, i32>),
     Int64(std::slice::Iter<'a, i64>),
 }
 
-/// Итератор по диапазону элементов `IntSet` `[start, end]` (inclusive).
-pub enum IntSetRangeIter<'a> {
-    Int16(std::slice::Iter<'a, i16>),
-    Int32(std::slice::Iter<'a, i32>),
-    Int64(std::slice::Iter<'a, i64>),
-    Empty,
-}
-
 /// Компактное множество уникальных целых чисел с адаптивным хранением.
 ///
 /// - Хранит элементы в отсортированном порядке.
@@ -201,13 +199,15 @@ impl IntSet {
     }
 
     /// Создаёт итератор по диапазону значений `[start, end]` включительно.
+    ///
+    /// Границы находятся бинарным поиском за O(log n), обход занимает O(k).
     pub fn iter_range(
         &self,
         start: i64,
         end: i64,
-    ) -> IntSetRangeIter<'_> {
+    ) -> IntSetIter<'_> {
         if start > end {
-            return IntSetRangeIter::empty();
+            return IntSetIter::empty();
         }
 
         match self.enc {
@@ -216,9 +216,9 @@ impl IntSet {
                 let end_idx = self.find_range_end_i16(end);
 
                 if start_idx >= end_idx {
-                    IntSetRangeIter::empty()
+                    IntSetIter::empty()
                 } else {
-                    IntSetRangeIter::Int16(self.data16[start_idx..end_idx].iter())
+                    IntSetIter::Int16(self.data16[start_idx..end_idx].iter())
                 }
             }
             Encoding::Int32 => {
@@ -226,9 +226,9 @@ impl IntSet {
                 let end_idx = self.find_range_end_i32(end);
 
                 if start_idx >= end_idx {
-                    IntSetRangeIter::empty()
+                    IntSetIter::empty()
                 } else {
-                    IntSetRangeIter::Int32(self.data32[start_idx..end_idx].iter())
+                    IntSetIter::Int32(self.data32[start_idx..end_idx].iter())
                 }
             }
             Encoding::Int64 => {
@@ -236,9 +236,9 @@ impl IntSet {
                 let end_idx = self.find_range_end_i64(end);
 
                 if start_idx >= end_idx {
-                    IntSetRangeIter::empty()
+                    IntSetIter::empty()
                 } else {
-                    IntSetRangeIter::Int64(self.data64[start_idx..end_idx].iter())
+                    IntSetIter::Int64(self.data64[start_idx..end_idx].iter())
                 }
             }
         }
@@ -381,6 +381,14 @@ impl IntSet {
 // Общие реализации трейтов для IntSet, IntSetIter
 ////////////////////////////////////////////////////////////////////////////////
 
+impl<'a> IntSetIter<'a> {
+    /// Возвращает итератор без элементов.
+    #[inline]
+    fn empty() -> Self {
+        IntSetIter::Int16([].iter())
+    }
+}
+
 impl<'a> Iterator for IntSetIter<'a> {
     type Item = i64;
 
@@ -425,60 +433,7 @@ impl<'a> DoubleEndedIterator for IntSetIter<'a> {
     }
 }
 
-impl<'a> IntSetRangeIter<'a> {
-    #[inline]
-    fn empty() -> Self {
-        IntSetRangeIter::Empty
-    }
-}
-
-impl<'a> Iterator for IntSetRangeIter<'a> {
-    type Item = i64;
-
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            IntSetRangeIter::Int16(iter) => iter.next().map(|&x| x as i64),
-            IntSetRangeIter::Int32(iter) => iter.next().map(|&x| x as i64),
-            IntSetRangeIter::Int64(iter) => iter.next().copied(),
-            IntSetRangeIter::Empty => None,
-        }
-    }
-
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        match self {
-            IntSetRangeIter::Int16(iter) => iter.size_hint(),
-            IntSetRangeIter::Int32(iter) => iter.size_hint(),
-            IntSetRangeIter::Int64(iter) => iter.size_hint(),
-            IntSetRangeIter::Empty => (0, Some(0)),
-        }
-    }
-}
-
-impl<'a> ExactSizeIterator for IntSetRangeIter<'a> {
-    #[inline]
-    fn len(&self) -> usize {
-        match self {
-            IntSetRangeIter::Int16(iter) => iter.len(),
-            IntSetRangeIter::Int32(iter) => iter.len(),
-            IntSetRangeIter::Int64(iter) => iter.len(),
-            IntSetRangeIter::Empty => 0,
-        }
-    }
-}
-
-impl<'a> DoubleEndedIterator for IntSetRangeIter<'a> {
-    #[inline]
-    fn next_back(&mut self) -> Option<Self::Item> {
-        match self {
-            IntSetRangeIter::Int16(iter) => iter.next_back().map(|&x| x as i64),
-            IntSetRangeIter::Int32(iter) => iter.next_back().map(|&x| x as i64),
-            IntSetRangeIter::Int64(iter) => iter.next_back().copied(),
-            IntSetRangeIter::Empty => None,
-        }
-    }
-}
+impl FusedIterator for IntSetIter<'_> {}
 
 impl Default for IntSet {
     fn default() -> Self {
@@ -742,4 +697,27 @@ mod tests {
             (1000..1010).map(|x| x as i64).collect::<Vec<_>>()
         );
     }
+
+    /// Тест проверяет диапазон и обход с обоих концов для каждого
+    /// кодирования, а также то, что `iter_range` возвращает `IntSetIter`.
+    #[test]
+    fn test_iter_range_double_ended_all_encodings() {
+        for base in [0i64, 1 << 20, 1 << 40] {
+            let mut set = IntSet::new();
+            for i in 0..10 {
+                set.insert(base + i);
+            }
+
+            let mut iter: IntSetIter<'_> = set.iter_range(base + 2, base + 7);
+            assert_eq!(iter.len(), 6);
+            assert_eq!(iter.next(), Some(base + 2));
+            assert_eq!(iter.next_back(), Some(base + 7));
+            let middle: Vec<_> = iter.rev().collect();
+            assert_eq!(middle, vec![base + 6, base + 5, base + 4, base + 3]);
+
+            let all: Vec<_> = set.iter().rev().collect();
+            assert_eq!(all, (0..10).rev().map(|i| base + i).collect::<Vec<_>>());
+            assert_eq!(set.iter_range(base + 20, base + 30).next(), None);
+        }
+    }
 }
@@ -8,16 +8,20 @@
 //! - `dict`: реализация словаря на основе хеш-таблицы.
 //! - `geo`: географические множества и операции (GEOADD, GEORADIUS, GEODIST и
 //!   т.п.).
+//! - `glob`: сопоставление с glob-шаблонами (`KEYS`, `MATCH` у `SCAN`).
 //! - `int_set`: компактное множество целых чисел для небольших коллекций.
 //! - `list_pack`: компактная структура списка для эффективного хранения.
 //! - `quicklist`: гибридный список, сочетающий связные списки и зиплисты.
+//! - `scan`: курсорный обход коллекций (`HSCAN`, `SSCAN`, `ZSCAN`).
 //! - `sds`: простые динамические строки (SDS), похожие на внутренние строки
 //!   Redis.
 //! - `skip_list`: скип-лист для быстрого доступа к отсортированным данным.
 //! - `smart_hash`: автоматически масштабируемая хеш-таблица с оптимизациями.
 //! - `stream`: структуры для работы с потоками данных.
 //! - `types`: определяет корневые типы `Value`, хранящиеся в базе.
-//! - `pubsub_manager`: менеджер Pub/Sub — публикация, подписка и статистика.
+//! - `zset`: операции над упорядоченными множествами (ZUNION, ZINTER, ZDIFF).
+//! - `pubsub_manager`: уведомления о событиях пространства ключей
+//!   (`__keyevent@<db>__:<event>`).
 //!
 //! Публичный экспорт всех подмодулей и их функций упрощает доступ из внешнего
 //! кода.
@@ -26,15 +30,19 @@ pub mod bitmap;
 pub mod dict;
 pub mod expire;
 pub mod geo;
+pub mod glob;
 pub mod hll;
 pub mod intset;
 pub mod listpack;
+pub mod pubsub_manager;
 pub mod quicklist;
+pub mod scan;
 pub mod sds;
 pub mod skiplist;
 pub mod smarthash;
 pub mod stream;
 pub mod types;
+pub mod zset;
 
 // Publicly re-export all error types and functions from the submodules to
 // simplify access from external code.
@@ -42,12 +50,16 @@ pub use bitmap::*;
 pub use dict::*;
 pub use expire::*;
 pub use geo::*;
+pub use glob::*;
 pub use hll::*;
 pub use intset::*;
 pub use listpack::*;
+pub use pubsub_manager::*;
 pub use quicklist::*;
+pub use scan::*;
 pub use sds::*;
 pub use skiplist::*;
 pub use smarthash::*;
 pub use stream::*;
 pub use types::*;
+pub use zset::*;
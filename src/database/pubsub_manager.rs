@@ -0,0 +1,320 @@
+//! Уведомления о событиях пространства ключей (`notify-keyspace-events`).
+//!
+//! Хранилище сообщает [`KeyspaceNotifier`] о каждой операции записи, а он
+//! публикует имя ключа в канал `__keyevent@<db>__:<event>` брокера Pub/Sub.
+//! Пока уведомления выключены, вызов сводится к одному relaxed-чтению
+//! атомика.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU16, Ordering},
+        Arc, RwLock, Weak,
+    },
+};
+
+use bytes::Bytes;
+
+use crate::{Broker, MessagePayload, StoreError, StoreResult};
+
+/// Событие пространства ключей; имена совпадают с событиями Redis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyspaceEvent {
+    Set,
+    Get,
+    Del,
+    Expire,
+    Rename,
+    LPush,
+    RPush,
+    SAdd,
+    ZAdd,
+    HSet,
+    XAdd,
+    XDel,
+    XTrim,
+    /// Ключ удалён по истечении TTL
+    Expired,
+    /// Ключ вытеснен политикой `maxmemory`
+    Evicted,
+}
+
+/// Рассылает события пространства ключей через брокер Pub/Sub.
+///
+/// Хранит слабую ссылку на брокер, чтобы хранилище не продлевало его жизнь.
+/// Набор событий задаётся флагами `notify-keyspace-events` (см.
+/// [`KeyspaceNotifier::set_flags`]).
+#[derive(Debug, Default)]
+pub struct KeyspaceNotifier {
+    /// Включённые классы событий; `0` — уведомления выключены
+    classes: AtomicU16,
+    /// Брокер, в который публикуются события
+    broker: RwLock<Weak<Broker>>,
+}
+
+/// Классы событий и их флаги в `notify-keyspace-events`.
+const CLASS_FLAGS: [(u16, char); 9] = [
+    (KeyspaceNotifier::GENERIC, 'g'),
+    (KeyspaceNotifier::STRING, '$'),
+    (KeyspaceNotifier::LIST, 'l'),
+    (KeyspaceNotifier::SET, 's'),
+    (KeyspaceNotifier::HASH, 'h'),
+    (KeyspaceNotifier::ZSET, 'z'),
+    (KeyspaceNotifier::STREAM, 't'),
+    (KeyspaceNotifier::EXPIRED, 'x'),
+    (KeyspaceNotifier::EVICTED, 'e'),
+];
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl KeyspaceEvent {
+    /// Возвращает имя события, используемое в имени канала.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Set => "set",
+            Self::Get => "get",
+            Self::Del => "del",
+            Self::Expire => "expire",
+            Self::Rename => "rename",
+            Self::LPush => "lpush",
+            Self::RPush => "rpush",
+            Self::SAdd => "sadd",
+            Self::ZAdd => "zadd",
+            Self::HSet => "hset",
+            Self::XAdd => "xadd",
+            Self::XDel => "xdel",
+            Self::XTrim => "xtrim",
+            Self::Expired => "expired",
+            Self::Evicted => "evicted",
+        }
+    }
+
+    /// Возвращает класс события (бит `KeyspaceNotifier::*`).
+    fn class(self) -> u16 {
+        match self {
+            Self::Del | Self::Expire | Self::Rename => KeyspaceNotifier::GENERIC,
+            Self::Set | Self::Get => KeyspaceNotifier::STRING,
+            Self::LPush | Self::RPush => KeyspaceNotifier::LIST,
+            Self::SAdd => KeyspaceNotifier::SET,
+            Self::HSet => KeyspaceNotifier::HASH,
+            Self::ZAdd => KeyspaceNotifier::ZSET,
+            Self::XAdd | Self::XDel | Self::XTrim => KeyspaceNotifier::STREAM,
+            Self::Expired => KeyspaceNotifier::EXPIRED,
+            Self::Evicted => KeyspaceNotifier::EVICTED,
+        }
+    }
+}
+
+impl KeyspaceNotifier {
+    const GENERIC: u16 = 1 << 0;
+    const STRING: u16 = 1 << 1;
+    const LIST: u16 = 1 << 2;
+    const SET: u16 = 1 << 3;
+    const HASH: u16 = 1 << 4;
+    const ZSET: u16 = 1 << 5;
+    const STREAM: u16 = 1 << 6;
+    const EXPIRED: u16 = 1 << 7;
+    const EVICTED: u16 = 1 << 8;
+    /// Флаг `A`: все классы, кроме `m` и `n`
+    const ALL: u16 = (1 << 9) - 1;
+
+    /// Создаёт выключенный уведомитель без брокера.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Задаёт брокер, в который публикуются события.
+    pub fn set_broker(
+        &self,
+        broker: &Arc<Broker>,
+    ) {
+        *self.broker.write().unwrap() = Arc::downgrade(broker);
+    }
+
+    /// Задаёт набор событий строкой флагов `notify-keyspace-events` Redis:
+    /// `E` включает каналы `__keyevent@<db>__:<event>`, классы — `g`, `$`,
+    /// `l`, `s`, `h`, `z`, `t`, `x`, `e` или `A` (все). Флаг `K`
+    /// принимается, но каналы `__keyspace@` не поддерживаются. Пустая
+    /// строка или флаги без `E` выключают уведомления.
+    ///
+    /// # Возвращает
+    /// - `Err(StoreError::InvalidArgument)` — неизвестный флаг; текущие
+    ///   настройки не меняются
+    pub fn set_flags(
+        &self,
+        flags: &str,
+    ) -> StoreResult<()> {
+        let mut classes = 0;
+        let mut keyevent = false;
+        for flag in flags.chars() {
+            match flag {
+                'A' => classes |= Self::ALL,
+                'E' => keyevent = true,
+                'K' => {}
+                _ => {
+                    let (class, _) =
+                        CLASS_FLAGS
+                            .iter()
+                            .find(|(_, c)| *c == flag)
+                            .ok_or_else(|| {
+                                StoreError::InvalidArgument(format!(
+                                    "invalid notify-keyspace-events flag '{flag}'"
+                                ))
+                            })?;
+                    classes |= class;
+                }
+            }
+        }
+        let classes = if keyevent { classes } else { 0 };
+        self.classes.store(classes, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Возвращает текущие флаги в каноническом виде (`""`, если
+    /// уведомления выключены).
+    pub fn flags(&self) -> String {
+        let classes = self.classes.load(Ordering::Relaxed);
+        if classes == 0 {
+            return String::new();
+        }
+        let mut flags = String::new();
+        if classes & Self::ALL == Self::ALL {
+            flags.push('A');
+        } else {
+            for (class, flag) in CLASS_FLAGS {
+                if classes & class != 0 {
+                    flags.push(flag);
+                }
+            }
+        }
+        flags.push('E');
+        flags
+    }
+
+    /// Проверяет, включены ли уведомления.
+    pub fn is_enabled(&self) -> bool {
+        self.classes.load(Ordering::Relaxed) != 0
+    }
+
+    /// Сообщает о событии `event` для ключа `key` базы `db`.
+    ///
+    /// При выключенных уведомлениях выполняет только одно relaxed-чтение.
+    #[inline]
+    pub fn notify(
+        &self,
+        db: usize,
+        event: KeyspaceEvent,
+        key: &[u8],
+    ) {
+        let classes = self.classes.load(Ordering::Relaxed);
+        if classes & event.class() != 0 {
+            self.publish(db, event, key);
+        }
+    }
+
+    /// Учитывает в метриках брокера `count` ключей, удалённых по истечении
+    /// TTL. Работает независимо от флагов уведомлений.
+    pub fn record_expired(
+        &self,
+        count: usize,
+    ) {
+        if count == 0 {
+            return;
+        }
+        if let Some(broker) = self.broker.read().unwrap().upgrade() {
+            broker.record_expired_keys(count as u64);
+        }
+    }
+
+    /// Публикует имя ключа в канал `__keyevent@<db>__:<event>`.
+    #[cold]
+    fn publish(
+        &self,
+        db: usize,
+        event: KeyspaceEvent,
+        key: &[u8],
+    ) {
+        let Some(broker) = self.broker.read().unwrap().upgrade() else {
+            return;
+        };
+        let channel = format!("__keyevent@{db}__:{}", event.as_str());
+        // Уведомления не должны влиять на результат команды.
+        let _ = broker.publish(channel, MessagePayload::Bytes(Bytes::copy_from_slice(key)));
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для KeyspaceEvent
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for KeyspaceEvent {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет разбор флагов: без `E` уведомления выключены, `A`
+    /// включает все классы, неизвестный флаг отклоняется без изменения
+    /// настроек.
+    #[test]
+    fn test_set_flags() {
+        let notifier = KeyspaceNotifier::new();
+        assert!(!notifier.is_enabled());
+
+        notifier.set_flags("Kx").unwrap();
+        assert!(!notifier.is_enabled());
+        assert_eq!(notifier.flags(), "");
+
+        notifier.set_flags("Ex").unwrap();
+        assert!(notifier.is_enabled());
+        assert_eq!(notifier.flags(), "xE");
+
+        notifier.set_flags("KEA").unwrap();
+        assert_eq!(notifier.flags(), "AE");
+
+        assert!(notifier.set_flags("E?").is_err());
+        assert_eq!(notifier.flags(), "AE");
+
+        notifier.set_flags("").unwrap();
+        assert!(!notifier.is_enabled());
+    }
+
+    /// Тест проверяет, что событие публикуется в канал
+    /// `__keyevent@<db>__:<event>` только для включённого класса и пока
+    /// брокер существует.
+    #[tokio::test]
+    async fn test_notify_publishes_to_keyevent_channel() {
+        let broker = Arc::new(Broker::new());
+        let notifier = KeyspaceNotifier::new();
+        notifier.set_broker(&broker);
+        notifier.set_flags("Eg").unwrap();
+
+        let mut del = broker.subscribe("__keyevent@3__:del").unwrap();
+        let mut set = broker.subscribe("__keyevent@3__:set").unwrap();
+
+        notifier.notify(3, KeyspaceEvent::Set, b"skipped");
+        notifier.notify(3, KeyspaceEvent::Del, b"k1");
+
+        let msg = del.recv().await.unwrap();
+        assert_eq!(msg.payload, MessagePayload::Bytes(Bytes::from("k1")));
+        assert!(set.try_recv().is_err());
+
+        drop(del);
+        drop(set);
+        drop(broker);
+        notifier.notify(3, KeyspaceEvent::Del, b"k2");
+    }
+}
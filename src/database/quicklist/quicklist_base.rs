@@ -1,35 +1,87 @@
 //! QuickList — это сегментированная структура списка, оптимизированная для
 //! операций добавления/удаления элементов с обеих сторон и адаптивного
 //! управления памятью.
+//!
+//! Внутренние узлы (дальше `compress_depth` от каждого края) хранятся сжатыми
+//! zstd и распаковываются лениво при обращении, как в Redis.
 
-use std::collections::VecDeque;
+use std::{
+    borrow::Cow,
+    collections::{vec_deque, VecDeque},
+    iter::FusedIterator,
+};
 
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{
+    de::DeserializeOwned, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Глубина сжатия по умолчанию: по одному несжатому узлу с каждого края.
+pub const DEFAULT_COMPRESS_DEPTH: usize = 1;
+
+/// Узлы меньше этого размера (в сериализованном виде) не сжимаются.
+const MIN_COMPRESS_BYTES: usize = 48;
+
+/// Минимальный выигрыш от сжатия в байтах; иначе узел остаётся несжатым.
+const MIN_COMPRESS_IMPROVE: usize = 8;
+
+/// Уровень сжатия zstd для узлов.
+const COMPRESS_LEVEL: i32 = 1;
 
 /// Сегментированный список с ограниченными по размеру сегментами
 /// и оптимизированным доступом к элементам.
-#[derive(Clone, Debug, Serialize, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct QuickList<T> {
-    /// Сегменты списка; каждый — это `VecDeque` с ограниченным размером
-    segments: Vec<VecDeque<T>>,
+    /// Сегменты списка; каждый — несжатый `VecDeque` или сжатый блок
+    segments: Vec<QuickListNode<T>>,
     /// Кумулятивные длины сегментов для быстрого поиска
     segment_starts: Vec<usize>,
     /// Максимальное количество элементов в одном сегменте
     max_segment_size: usize,
     /// Общее количество элементов во всех сегментах
     len: usize,
+    /// Количество несжатых узлов с каждого края; `0` отключает сжатие
+    compress_depth: usize,
     /// Кэш последнего accessed сегмента для sequential access patterns
-    #[serde(skip)]
     last_accessed: Option<(usize, usize)>, // (segment_idx, global_index)
     /// Флаг указывающий что индекс нуждается в обновлении
-    #[serde(skip)]
     index_dirty: bool,
     /// Счётчик операций с последней оптимизации
-    #[serde(skip)]
     ops_since_optimize: usize,
     /// Порог операций перед проверкой необходимости оптимизации
-    #[serde(skip)]
     optimize_threshold: usize,
+    /// Узел, распакованный для чтения и ожидающий повторного сжатия
+    pending_recompress: Option<usize>,
+}
+
+/// Узел QuickList: несжатый сегмент или сегмент, сжатый zstd.
+#[derive(Clone, Debug)]
+pub enum QuickListNode<T> {
+    /// Несжатый сегмент; крайние узлы всегда хранятся в этом виде.
+    Plain(VecDeque<T>),
+    /// Сериализованный и сжатый zstd сегмент.
+    Compressed {
+        /// Сжатые байты сегмента.
+        data: Vec<u8>,
+        /// Количество элементов в сегменте.
+        count: usize,
+    },
+}
+
+/// Итератор по элементам [`QuickList`].
+///
+/// Сжатые узлы распаковываются по мере продвижения, поэтому элементы
+/// несжатых узлов возвращаются по ссылке, а сжатых — по значению.
+pub struct QuickListIter<'a, T: Clone> {
+    nodes: std::slice::Iter<'a, QuickListNode<T>>,
+    current: NodeCursor<'a, T>,
+    remaining: usize,
+    rev: bool,
+}
+
+/// Позиция итератора внутри текущего узла.
+enum NodeCursor<'a, T> {
+    Borrowed(vec_deque::Iter<'a, T>),
+    Owned(vec_deque::IntoIter<T>),
 }
 
 /// Информация о фрагментации памяти QuickList.
@@ -41,23 +93,23 @@ pub struct FragmentationInfo {
     pub average_fill_rate: f64,
 }
 
+/// Сторона списка, с которой выполняется вставка или извлечение.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListDir {
+    /// Голова списка (`LEFT`).
+    Left,
+    /// Хвост списка (`RIGHT`).
+    Right,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
-impl<T> QuickList<T> {
+impl<T: Clone + Serialize + DeserializeOwned> QuickList<T> {
     /// Создаёт новый пустой `QuickList` с заданным размером сегмента.
     pub fn new(max_segment_size: usize) -> Self {
-        Self {
-            segments: Vec::new(),
-            segment_starts: vec![0],
-            max_segment_size,
-            len: 0,
-            last_accessed: None,
-            index_dirty: false,
-            ops_since_optimize: 0,
-            optimize_threshold: 1000,
-        }
+        Self::with_optimize_threshold(max_segment_size, 1000)
     }
 
     /// Создаёт QuickList с кастомным порогом оптимизации.
@@ -70,13 +122,25 @@ impl<T> QuickList<T> {
             segment_starts: vec![0],
             max_segment_size,
             len: 0,
+            compress_depth: DEFAULT_COMPRESS_DEPTH,
             last_accessed: None,
             index_dirty: false,
             ops_since_optimize: 0,
             optimize_threshold: threshold,
+            pending_recompress: None,
         }
     }
 
+    /// Создаёт QuickList с заданной глубиной сжатия (`0` — без сжатия).
+    pub fn with_compress_depth(
+        max_segment_size: usize,
+        compress_depth: usize,
+    ) -> Self {
+        let mut qlist = Self::new(max_segment_size);
+        qlist.compress_depth = compress_depth;
+        qlist
+    }
+
     /// Возвращает общее количество элементов.
     pub fn len(&self) -> usize {
         self.len
@@ -87,13 +151,40 @@ impl<T> QuickList<T> {
         self.len == 0
     }
 
+    /// Возвращает количество несжатых узлов с каждого края списка.
+    pub fn compress_depth(&self) -> usize {
+        self.compress_depth
+    }
+
+    /// Меняет глубину сжатия и пересжимает узлы под новую границу.
+    pub fn set_compress_depth(
+        &mut self,
+        compress_depth: usize,
+    ) {
+        self.flush_recompress();
+        self.compress_depth = compress_depth;
+        self.apply_compression();
+    }
+
+    /// Возвращает количество сжатых узлов.
+    pub fn compressed_nodes(&self) -> usize {
+        self.segments.iter().filter(|n| n.is_compressed()).count()
+    }
+
     /// Возвращает ссылку на элемент по логическому индексу.
+    ///
+    /// Сжатый узел распаковывается на месте и сжимается снова при следующей
+    /// операции над списком.
     pub fn get(
         &mut self,
         index: usize,
     ) -> Option<&T> {
+        self.flush_recompress();
         let (seg_idx, offset) = self.find_segment(index)?;
-        self.segments[seg_idx].get(offset)
+        if self.segments[seg_idx].is_compressed() {
+            self.pending_recompress = Some(seg_idx);
+        }
+        self.plain_mut(seg_idx).get(offset)
     }
 
     /// Возвращает изменяемую ссылку на элемент по индексу.
@@ -101,8 +192,46 @@ impl<T> QuickList<T> {
         &mut self,
         index: usize,
     ) -> Option<&mut T> {
+        self.flush_recompress();
         let (seg_idx, offset) = self.find_segment(index)?;
-        self.segments.get_mut(seg_idx)?.get_mut(offset)
+        if self.segments[seg_idx].is_compressed() {
+            self.pending_recompress = Some(seg_idx);
+        }
+        self.plain_mut(seg_idx).get_mut(offset)
+    }
+
+    /// Возвращает элемент по индексу со знаком; отрицательный индекс
+    /// считается с хвоста (`-1` — последний элемент). Сегмент находится
+    /// двоичным поиском по кумулятивным длинам, без обхода элементов.
+    pub fn at(
+        &mut self,
+        index: isize,
+    ) -> Option<&T> {
+        let index = self.resolve_index(index)?;
+        self.get(index)
+    }
+
+    /// Заменяет элемент по индексу; отрицательный индекс считается с хвоста
+    /// (`-1` — последний элемент).
+    ///
+    /// Возвращает `false`, если индекс вне диапазона.
+    pub fn set(
+        &mut self,
+        index: isize,
+        item: T,
+    ) -> bool {
+        let Some(index) = self.resolve_index(index) else {
+            return false;
+        };
+        let replaced = match self.get_mut(index) {
+            Some(slot) => {
+                *slot = item;
+                true
+            }
+            None => false,
+        };
+        self.flush_recompress();
+        replaced
     }
 
     /// Вставляет элемент в начало списка.
@@ -110,13 +239,17 @@ impl<T> QuickList<T> {
         &mut self,
         item: T,
     ) {
+        self.flush_recompress();
         if self.segments.is_empty() || self.segments[0].len() >= self.max_segment_size {
-            self.segments
-                .insert(0, VecDeque::with_capacity(self.max_segment_size));
+            self.segments.insert(
+                0,
+                QuickListNode::Plain(VecDeque::with_capacity(self.max_segment_size)),
+            );
             self.mark_index_dirty();
+            self.refresh_boundaries();
         }
 
-        self.segments[0].push_front(item);
+        self.plain_mut(0).push_front(item);
         self.len += 1;
 
         if !self.index_dirty {
@@ -131,17 +264,21 @@ impl<T> QuickList<T> {
         &mut self,
         item: T,
     ) {
+        self.flush_recompress();
         let needed_new_segment = self.segments.is_empty()
             || self.segments.last().unwrap().len() >= self.max_segment_size;
 
         if needed_new_segment {
             self.segments
-                .push(VecDeque::with_capacity(self.max_segment_size));
+                .push(QuickListNode::Plain(VecDeque::with_capacity(
+                    self.max_segment_size,
+                )));
             self.mark_index_dirty();
+            self.refresh_boundaries();
         }
 
         let last_idx = self.segments.len() - 1;
-        self.segments[last_idx].push_back(item);
+        self.plain_mut(last_idx).push_back(item);
         self.len += 1;
 
         if !self.index_dirty {
@@ -153,11 +290,12 @@ impl<T> QuickList<T> {
 
     /// Удаляет и возвращает первый элемент.
     pub fn pop_front(&mut self) -> Option<T> {
+        self.flush_recompress();
         if self.segments.is_empty() {
             return None;
         }
 
-        let item = self.segments[0].pop_front();
+        let item = self.plain_mut(0).pop_front();
 
         if let Some(item) = item {
             self.len -= 1;
@@ -165,6 +303,7 @@ impl<T> QuickList<T> {
             if self.segments[0].is_empty() {
                 self.segments.remove(0);
                 self.mark_index_dirty();
+                self.refresh_boundaries();
             } else if !self.index_dirty {
                 self.update_segment_starts_from(0);
             }
@@ -178,12 +317,13 @@ impl<T> QuickList<T> {
 
     /// Удаляет и возвращает последний элемент.
     pub fn pop_back(&mut self) -> Option<T> {
+        self.flush_recompress();
         if self.segments.is_empty() {
             return None;
         }
 
         let last_idx = self.segments.len() - 1;
-        let item = self.segments[last_idx].pop_back();
+        let item = self.plain_mut(last_idx).pop_back();
 
         if let Some(item) = item {
             self.len -= 1;
@@ -191,6 +331,7 @@ impl<T> QuickList<T> {
             if self.segments[last_idx].is_empty() {
                 self.segments.pop();
                 self.mark_index_dirty();
+                self.refresh_boundaries();
             } else if !self.index_dirty {
                 self.update_segment_starts_from(last_idx);
             }
@@ -202,9 +343,144 @@ impl<T> QuickList<T> {
         }
     }
 
-    /// Возвращает итератор по элементам.
-    pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.segments.iter().flat_map(|seg| seg.iter())
+    /// Вставляет элемент с указанной стороны списка.
+    pub fn push(
+        &mut self,
+        dir: ListDir,
+        item: T,
+    ) {
+        match dir {
+            ListDir::Left => self.push_front(item),
+            ListDir::Right => self.push_back(item),
+        }
+    }
+
+    /// Удаляет и возвращает элемент с указанной стороны списка.
+    pub fn pop(
+        &mut self,
+        dir: ListDir,
+    ) -> Option<T> {
+        match dir {
+            ListDir::Left => self.pop_front(),
+            ListDir::Right => self.pop_back(),
+        }
+    }
+
+    /// Вставляет элемент перед позицией `index`; при `index >= len` элемент
+    /// добавляется в конец. Переполненный сегмент делится пополам.
+    pub fn insert(
+        &mut self,
+        index: usize,
+        item: T,
+    ) {
+        if index >= self.len {
+            self.push_back(item);
+            return;
+        }
+        if index == 0 {
+            self.push_front(item);
+            return;
+        }
+
+        self.flush_recompress();
+        let Some((seg_idx, offset)) = self.find_segment(index) else {
+            return;
+        };
+        let max_segment_size = self.max_segment_size;
+        let segment = self.plain_mut(seg_idx);
+        segment.insert(offset, item);
+        let tail = (segment.len() > max_segment_size).then(|| segment.split_off(segment.len() / 2));
+        self.len += 1;
+
+        if let Some(tail) = tail {
+            self.segments
+                .insert(seg_idx + 1, QuickListNode::Plain(tail));
+            self.mark_index_dirty();
+            self.refresh_node(seg_idx);
+            self.refresh_node(seg_idx + 1);
+            self.refresh_boundaries();
+        } else {
+            self.compress_node(seg_idx);
+            if !self.index_dirty {
+                self.update_segment_starts_from(seg_idx);
+            }
+        }
+
+        self.auto_optimize();
+    }
+
+    /// Оставляет только элементы в диапазоне `start..=stop` (отрицательные
+    /// индексы считаются с хвоста). Пустой или перевёрнутый диапазон очищает
+    /// список.
+    ///
+    /// Сегменты, целиком попадающие за границы диапазона, отбрасываются без
+    /// поэлементного копирования и распаковки.
+    pub fn trim(
+        &mut self,
+        start: isize,
+        stop: isize,
+    ) {
+        self.flush_recompress();
+        let len = self.len as isize;
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let stop = if stop < 0 {
+            len + stop
+        } else {
+            stop.min(len - 1)
+        };
+        if start > stop || start >= len {
+            self.clear();
+            return;
+        }
+        let (start, stop) = (start as usize, stop as usize);
+
+        // Голова: сначала целые сегменты, затем часть первого оставшегося.
+        let mut front = start;
+        let mut whole = 0;
+        while self.segments[whole].len() <= front {
+            front -= self.segments[whole].len();
+            whole += 1;
+        }
+        self.segments.drain(..whole);
+        if front > 0 {
+            self.plain_mut(0).drain(..front);
+        }
+
+        // Хвост: аналогично с конца.
+        let mut back = self.len - 1 - stop;
+        while let Some(last) = self.segments.last() {
+            if last.len() > back {
+                break;
+            }
+            back -= last.len();
+            self.segments.pop();
+        }
+        if back > 0 {
+            let last_idx = self.segments.len() - 1;
+            let last = self.plain_mut(last_idx);
+            last.truncate(last.len() - back);
+        }
+
+        self.len = stop - start + 1;
+        self.mark_index_dirty();
+        self.rebuild_segment_starts();
+        self.apply_compression();
+    }
+
+    /// Возвращает итератор по элементам; сжатые узлы распаковываются по мере
+    /// продвижения.
+    pub fn iter(&self) -> QuickListIter<'_, T> {
+        QuickListIter::new(self, false)
+    }
+
+    /// Возвращает итератор по элементам в обратном порядке (от хвоста к
+    /// голове).
+    pub fn iter_rev(&self) -> QuickListIter<'_, T> {
+        QuickListIter::new(self, true)
     }
 
     /// Очищает список.
@@ -215,16 +491,20 @@ impl<T> QuickList<T> {
         self.last_accessed = None;
         self.index_dirty = false;
         self.ops_since_optimize = 0;
+        self.pending_recompress = None;
     }
 
     /// Проверяет корректность структуры.
     pub fn validate(&self) -> Result<(), &'static str> {
         let mut total_len = 0;
 
-        for segment in &self.segments {
+        for (idx, segment) in self.segments.iter().enumerate() {
             if segment.capacity() > self.max_segment_size * 2 {
                 return Err("Segment capacity exceeds limit");
             }
+            if segment.is_compressed() && !self.is_inner(idx) {
+                return Err("Compressed segment outside of compress depth");
+            }
             total_len += segment.len();
         }
 
@@ -254,14 +534,21 @@ impl<T> QuickList<T> {
     }
 
     /// Оптимизирует сегменты: объединяет малозаполненные и удаляет пустые.
+    ///
+    /// Заполненные сжатые узлы переносятся как есть, без распаковки.
     pub fn optimize(&mut self) {
+        self.flush_recompress();
         let mut new_segments = Vec::new();
         let mut current_segment = VecDeque::with_capacity(self.max_segment_size);
 
-        for segment in self.segments.drain(..) {
-            for item in segment {
+        for segment in std::mem::take(&mut self.segments) {
+            if current_segment.is_empty() && segment.len() == self.max_segment_size {
+                new_segments.push(segment);
+                continue;
+            }
+            for item in segment.into_items() {
                 if current_segment.len() >= self.max_segment_size {
-                    new_segments.push(current_segment);
+                    new_segments.push(QuickListNode::Plain(current_segment));
                     current_segment = VecDeque::with_capacity(self.max_segment_size);
                 }
                 current_segment.push_back(item);
@@ -269,12 +556,13 @@ impl<T> QuickList<T> {
         }
 
         if !current_segment.is_empty() {
-            new_segments.push(current_segment);
+            new_segments.push(QuickListNode::Plain(current_segment));
         }
 
         self.segments = new_segments;
         self.mark_index_dirty();
         self.rebuild_segment_starts();
+        self.apply_compression();
         self.ops_since_optimize = 0;
     }
 
@@ -293,8 +581,8 @@ impl<T> QuickList<T> {
     /// Преобразует список в один `VecDeque`.
     pub fn into_vecdeque(self) -> VecDeque<T> {
         let mut result = VecDeque::with_capacity(self.len);
-        for mut segment in self.segments {
-            result.append(&mut segment);
+        for segment in self.segments {
+            result.append(&mut segment.into_items());
         }
         result
     }
@@ -346,17 +634,16 @@ impl<T> QuickList<T> {
     /// Сжимает сегменты до размера фактических данных.
     pub fn shrink_to_fit(&mut self) {
         for segment in &mut self.segments {
-            segment.shrink_to_fit();
+            match segment {
+                QuickListNode::Plain(items) => items.shrink_to_fit(),
+                QuickListNode::Compressed { data, .. } => data.shrink_to_fit(),
+            }
         }
     }
 
     /// Оценивает использование памяти в байтах.
     pub fn memory_usage(&self) -> usize {
-        let segments_memory: usize = self
-            .segments
-            .iter()
-            .map(|s| s.capacity() * std::mem::size_of::<T>())
-            .sum();
+        let segments_memory: usize = self.segments.iter().map(|s| s.heap_size()).sum();
 
         let index_memory = self.segment_starts.capacity() * std::mem::size_of::<usize>();
         let struct_memory = std::mem::size_of::<Self>();
@@ -364,6 +651,16 @@ impl<T> QuickList<T> {
         segments_memory + index_memory + struct_memory
     }
 
+    /// Возвращает память кучи, занятую сегментами и индексом, без учёта
+    /// памяти кучи самих элементов несжатых сегментов.
+    pub fn heap_size(&self) -> usize {
+        let segments_memory: usize = self.segments.iter().map(|s| s.heap_size()).sum();
+
+        self.segments.capacity() * std::mem::size_of::<QuickListNode<T>>()
+            + segments_memory
+            + self.segment_starts.capacity() * std::mem::size_of::<usize>()
+    }
+
     /// Возвращает информацию о фрагментации и эффективности использования
     /// памяти.
     pub fn fragmentation_info(&self) -> FragmentationInfo {
@@ -394,6 +691,19 @@ impl<T> QuickList<T> {
         }
     }
 
+    /// Переводит индекс со знаком (отрицательный — от хвоста) в абсолютный.
+    fn resolve_index(
+        &self,
+        index: isize,
+    ) -> Option<usize> {
+        let index = if index < 0 {
+            self.len.checked_sub(index.unsigned_abs())?
+        } else {
+            index as usize
+        };
+        (index < self.len).then_some(index)
+    }
+
     /// Находит сегмент и локальный offset для глобального индекса.
     /// Использует binary search по segment_starts для O(log n) lookup.
     ///
@@ -533,6 +843,7 @@ impl<T> QuickList<T> {
 
         self.mark_index_dirty();
         self.rebuild_segment_starts();
+        self.apply_compression();
 
         // Инициализация optimization полей после десериализации
         self.ops_since_optimize = 0;
@@ -540,55 +851,266 @@ impl<T> QuickList<T> {
             self.optimize_threshold = 1000;
         }
     }
+
+    /// Возвращает `true`, если узел лежит дальше `compress_depth` от обоих
+    /// краёв и должен храниться сжатым.
+    fn is_inner(
+        &self,
+        idx: usize,
+    ) -> bool {
+        self.compress_depth > 0
+            && idx >= self.compress_depth
+            && idx + self.compress_depth < self.segments.len()
+    }
+
+    /// Возвращает несжатый сегмент, при необходимости распаковывая его на
+    /// месте.
+    fn plain_mut(
+        &mut self,
+        idx: usize,
+    ) -> &mut VecDeque<T> {
+        let node = &mut self.segments[idx];
+        if let QuickListNode::Compressed { data, .. } = node {
+            *node = QuickListNode::Plain(decompress_items(data));
+        }
+        match node {
+            QuickListNode::Plain(items) => items,
+            QuickListNode::Compressed { .. } => unreachable!("node was just decompressed"),
+        }
+    }
+
+    /// Сжимает внутренний несжатый узел, если это даёт выигрыш.
+    fn compress_node(
+        &mut self,
+        idx: usize,
+    ) {
+        if !self.is_inner(idx) {
+            return;
+        }
+        if let Some(QuickListNode::Plain(items)) = self.segments.get(idx) {
+            if let Some(data) = compress_items(items) {
+                let count = items.len();
+                self.segments[idx] = QuickListNode::Compressed { data, count };
+            }
+        }
+    }
+
+    /// Приводит узел к виду, соответствующему его позиции: внутренний
+    /// сжимается, крайний распаковывается.
+    fn refresh_node(
+        &mut self,
+        idx: usize,
+    ) {
+        if idx >= self.segments.len() {
+            return;
+        }
+        if self.is_inner(idx) {
+            self.compress_node(idx);
+        } else {
+            self.plain_mut(idx);
+        }
+    }
+
+    /// Обновляет узлы на границах несжатых зон после добавления или удаления
+    /// одного узла: только у них может смениться внутренний/крайний статус.
+    fn refresh_boundaries(&mut self) {
+        let depth = self.compress_depth;
+        if depth == 0 {
+            return;
+        }
+        let n = self.segments.len();
+        for idx in [
+            depth - 1,
+            depth,
+            n.saturating_sub(depth + 1),
+            n.saturating_sub(depth),
+        ] {
+            self.refresh_node(idx);
+        }
+    }
+
+    /// Приводит все узлы к виду, соответствующему их позиции.
+    fn apply_compression(&mut self) {
+        for idx in 0..self.segments.len() {
+            self.refresh_node(idx);
+        }
+    }
+
+    /// Сжимает обратно узел, распакованный для чтения.
+    fn flush_recompress(&mut self) {
+        if let Some(idx) = self.pending_recompress.take() {
+            self.compress_node(idx);
+        }
+    }
+}
+
+impl<T> QuickListNode<T> {
+    /// Возвращает количество элементов в узле.
+    pub fn len(&self) -> usize {
+        match self {
+            QuickListNode::Plain(items) => items.len(),
+            QuickListNode::Compressed { count, .. } => *count,
+        }
+    }
+
+    /// Возвращает `true`, если узел пуст.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Возвращает `true`, если узел хранится сжатым.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, QuickListNode::Compressed { .. })
+    }
+
+    /// Вместимость узла в элементах; у сжатого узла равна числу элементов.
+    fn capacity(&self) -> usize {
+        match self {
+            QuickListNode::Plain(items) => items.capacity(),
+            QuickListNode::Compressed { count, .. } => *count,
+        }
+    }
+
+    /// Память кучи узла без учёта памяти кучи самих элементов.
+    fn heap_size(&self) -> usize {
+        match self {
+            QuickListNode::Plain(items) => items.capacity() * std::mem::size_of::<T>(),
+            QuickListNode::Compressed { data, .. } => data.capacity(),
+        }
+    }
+}
+
+impl<T: Clone + DeserializeOwned> QuickListNode<T> {
+    /// Возвращает элементы узла; сжатый узел распаковывается в новый буфер.
+    pub fn items(&self) -> Cow<'_, VecDeque<T>> {
+        match self {
+            QuickListNode::Plain(items) => Cow::Borrowed(items),
+            QuickListNode::Compressed { data, .. } => Cow::Owned(decompress_items(data)),
+        }
+    }
+
+    /// Забирает элементы узла, распаковывая сжатый узел.
+    pub fn into_items(self) -> VecDeque<T> {
+        match self {
+            QuickListNode::Plain(items) => items,
+            QuickListNode::Compressed { data, .. } => decompress_items(&data),
+        }
+    }
+}
+
+impl<'a, T: Clone + Serialize + DeserializeOwned> QuickListIter<'a, T> {
+    fn new(
+        list: &'a QuickList<T>,
+        rev: bool,
+    ) -> Self {
+        Self {
+            nodes: list.segments.iter(),
+            current: NodeCursor::Owned(VecDeque::new().into_iter()),
+            remaining: list.len,
+            rev,
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для QuickList
 ////////////////////////////////////////////////////////////////////////////////
 
-impl<T> IntoIterator for QuickList<T> {
+impl<T: Clone + Serialize + DeserializeOwned> IntoIterator for QuickList<T> {
     type Item = T;
     type IntoIter = std::vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.segments
             .into_iter()
-            .flat_map(|seg| seg.into_iter())
+            .flat_map(QuickListNode::into_items)
             .collect::<Vec<_>>()
             .into_iter()
     }
 }
 
-impl<T> Default for QuickList<T> {
+impl<T: Clone + Serialize + DeserializeOwned> Default for QuickList<T> {
     fn default() -> Self {
         Self::new(512)
     }
 }
 
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for QuickList<T> {
+impl<T: Clone + PartialEq + Serialize + DeserializeOwned> PartialEq for QuickList<T> {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> Serialize for QuickList<T> {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        /// Сегменты сериализуются распакованными, чтобы формат не зависел
+        /// от того, какие узлы сжаты.
+        struct Segments<'a, T>(&'a [QuickListNode<T>]);
+
+        impl<T: Clone + Serialize + DeserializeOwned> Serialize for Segments<'_, T> {
+            fn serialize<S>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.collect_seq(self.0.iter().map(QuickListNode::items))
+            }
+        }
+
+        let mut state = serializer.serialize_struct("QuickList", 5)?;
+        state.serialize_field("segments", &Segments(&self.segments))?;
+        state.serialize_field("segment_starts", &self.segment_starts)?;
+        state.serialize_field("max_segment_size", &self.max_segment_size)?;
+        state.serialize_field("len", &self.len)?;
+        state.serialize_field("compress_depth", &self.compress_depth)?;
+        state.end()
+    }
+}
+
+impl<'de, T: Clone + Serialize + DeserializeOwned> Deserialize<'de> for QuickList<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         #[derive(Deserialize)]
+        #[serde(bound = "T: DeserializeOwned")]
         struct QuickListHelper<T> {
             segments: Vec<VecDeque<T>>,
             segment_starts: Vec<usize>,
             max_segment_size: usize,
             len: usize,
+            #[serde(default = "default_compress_depth")]
+            compress_depth: usize,
         }
 
         let helper = QuickListHelper::deserialize(deserializer)?;
 
         let mut qlist = QuickList {
-            segments: helper.segments,
+            segments: helper
+                .segments
+                .into_iter()
+                .map(QuickListNode::Plain)
+                .collect(),
             segment_starts: helper.segment_starts,
             max_segment_size: helper.max_segment_size,
             len: helper.len,
+            compress_depth: helper.compress_depth,
             last_accessed: None,
             index_dirty: false,
             ops_since_optimize: 0,
             optimize_threshold: 1000,
+            pending_recompress: None,
         };
 
         qlist.ensure_valid_state();
@@ -596,6 +1118,70 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for QuickList<T> {
     }
 }
 
+impl<'a, T: Clone + Serialize + DeserializeOwned> Iterator for QuickListIter<'a, T> {
+    type Item = Cow<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = match &mut self.current {
+                NodeCursor::Borrowed(it) if self.rev => it.next_back().map(Cow::Borrowed),
+                NodeCursor::Borrowed(it) => it.next().map(Cow::Borrowed),
+                NodeCursor::Owned(it) if self.rev => it.next_back().map(Cow::Owned),
+                NodeCursor::Owned(it) => it.next().map(Cow::Owned),
+            };
+            if item.is_some() {
+                self.remaining -= 1;
+                return item;
+            }
+
+            let node = if self.rev {
+                self.nodes.next_back()?
+            } else {
+                self.nodes.next()?
+            };
+            self.current = match node {
+                QuickListNode::Plain(items) => NodeCursor::Borrowed(items.iter()),
+                QuickListNode::Compressed { data, .. } => {
+                    NodeCursor::Owned(decompress_items(data).into_iter())
+                }
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> ExactSizeIterator for QuickListIter<'_, T> {}
+
+impl<T: Clone + Serialize + DeserializeOwned> FusedIterator for QuickListIter<'_, T> {}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn default_compress_depth() -> usize {
+    DEFAULT_COMPRESS_DEPTH
+}
+
+/// Сериализует и сжимает сегмент; возвращает `None`, если сегмент слишком
+/// мал или сжатие не даёт выигрыша.
+fn compress_items<T: Serialize>(items: &VecDeque<T>) -> Option<Vec<u8>> {
+    let raw = bincode::serialize(items).ok()?;
+    if raw.len() < MIN_COMPRESS_BYTES {
+        return None;
+    }
+    let data = zstd::bulk::compress(&raw, COMPRESS_LEVEL).ok()?;
+    (data.len() + MIN_COMPRESS_IMPROVE <= raw.len()).then_some(data)
+}
+
+/// Распаковывает сегмент, сжатый [`compress_items`].
+fn decompress_items<T: DeserializeOwned>(data: &[u8]) -> VecDeque<T> {
+    let raw = zstd::stream::decode_all(data).expect("corrupted QuickList node: zstd");
+    bincode::deserialize(&raw).expect("corrupted QuickList node: bincode")
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -718,6 +1304,26 @@ mod tests {
 
     /// Тестирует метод `clear`.
     /// Проверяет, что после очистки список становится пустым.
+    #[test]
+    fn test_push_pop_with_dir() {
+        let mut ql = QuickList::new(2);
+        ql.push(ListDir::Right, 2);
+        ql.push(ListDir::Left, 1);
+        ql.push(ListDir::Right, 3);
+
+        assert_eq!(ql.pop(ListDir::Left), Some(1));
+        assert_eq!(ql.pop(ListDir::Right), Some(3));
+        assert_eq!(ql.pop(ListDir::Right), Some(2));
+        assert_eq!(ql.pop(ListDir::Left), None);
+    }
+
+    #[test]
+    fn test_iter_rev() {
+        let ql = QuickList::from_iter(0..10, 3);
+        let rev: Vec<_> = ql.iter_rev().map(|x| *x).collect();
+        assert_eq!(rev, (0..10).rev().collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_clear() {
         let mut list: QuickList<i32> = QuickList::new(3);
@@ -917,4 +1523,215 @@ mod tests {
         list.push_back(10);
         assert_eq!(list.ops_since_optimize, 0); // Counter reset
     }
+
+    /// Тест проверяет `insert` в голову, середину и хвост, а также деление
+    /// переполненного сегмента.
+    #[test]
+    fn test_insert() {
+        let mut list = QuickList::from_iter([1, 2, 3, 4], 4);
+        list.insert(0, 0);
+        list.insert(3, 10);
+        list.insert(100, 5);
+
+        assert_eq!(
+            list.iter().map(|x| *x).collect::<Vec<_>>(),
+            vec![0, 1, 2, 10, 3, 4, 5]
+        );
+        assert_eq!(list.len(), 7);
+        assert_eq!(list.get(3), Some(&10));
+        assert!(list.segments.iter().all(|s| s.len() <= 4));
+        assert!(list.validate().is_ok());
+    }
+
+    /// Тест проверяет `set` по положительному и отрицательному индексу и
+    /// индекс вне диапазона.
+    #[test]
+    fn test_set_by_signed_index() {
+        let mut list = QuickList::from_iter([1, 2, 3, 4, 5], 2);
+        assert!(list.set(0, 10));
+        assert!(list.set(-1, 50));
+        assert!(list.set(-3, 30));
+        assert!(!list.set(5, 0));
+        assert!(!list.set(-6, 0));
+
+        assert_eq!(
+            list.iter().map(|x| *x).collect::<Vec<_>>(),
+            vec![10, 2, 30, 4, 50]
+        );
+    }
+
+    /// Тест проверяет `at` с начала и с хвоста, границы и пустой список.
+    #[test]
+    fn test_at_signed_index() {
+        let mut list = QuickList::from_iter(0..10, 3);
+        assert_eq!(list.at(0), Some(&0));
+        assert_eq!(list.at(4), Some(&4));
+        assert_eq!(list.at(9), Some(&9));
+        assert_eq!(list.at(-1), Some(&9));
+        assert_eq!(list.at(-10), Some(&0));
+        assert_eq!(list.at(10), None);
+        assert_eq!(list.at(-11), None);
+
+        let mut empty: QuickList<i32> = QuickList::new(3);
+        assert_eq!(empty.at(0), None);
+        assert_eq!(empty.at(-1), None);
+    }
+
+    /// Тест проверяет `trim` внутри одного сегмента, с отбрасыванием целых
+    /// сегментов, с отрицательными индексами и пустым диапазоном.
+    #[test]
+    fn test_trim() {
+        let mut single = QuickList::from_iter(0..4, 8);
+        single.trim(1, 2);
+        assert_eq!(single.iter().map(|x| *x).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(single.validate().is_ok());
+
+        let mut list = QuickList::from_iter(0..10, 3);
+        list.trim(4, -3);
+        assert_eq!(
+            list.iter().map(|x| *x).collect::<Vec<_>>(),
+            vec![4, 5, 6, 7]
+        );
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.get(0), Some(&4));
+        assert!(list.validate().is_ok());
+
+        let mut list = QuickList::from_iter(0..10, 3);
+        list.trim(-100, 100);
+        assert_eq!(list.len(), 10);
+
+        list.trim(5, 2);
+        assert!(list.is_empty());
+        assert!(list.validate().is_ok());
+    }
+
+    /// Строит список из сжимаемых строк: `n` элементов, по `segment` в узле.
+    fn compressible(
+        n: usize,
+        segment: usize,
+    ) -> QuickList<String> {
+        QuickList::from_iter((0..n).map(|i| format!("value-{i:06}")), segment)
+    }
+
+    /// Тест проверяет, что внутренние узлы сжимаются, крайние
+    /// `compress_depth` узлов остаются несжатыми, а обход возвращает все
+    /// элементы в исходном порядке.
+    #[test]
+    fn test_compression_roundtrip() {
+        let list = compressible(1000, 16);
+        let expected: Vec<_> = (0..1000).map(|i| format!("value-{i:06}")).collect();
+
+        assert_eq!(list.compress_depth(), DEFAULT_COMPRESS_DEPTH);
+        assert_eq!(list.compressed_nodes(), list.segments.len() - 2);
+        assert!(!list.segments[0].is_compressed());
+        assert!(!list.segments.last().unwrap().is_compressed());
+        assert!(list.validate().is_ok());
+
+        assert_eq!(
+            list.iter().map(|s| s.into_owned()).collect::<Vec<_>>(),
+            expected
+        );
+        let mut rev = expected.clone();
+        rev.reverse();
+        assert_eq!(
+            list.iter_rev().map(|s| s.into_owned()).collect::<Vec<_>>(),
+            rev
+        );
+        assert_eq!(list.iter().len(), 1000);
+        assert_eq!(list.clone().into_vecdeque(), expected);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), expected);
+    }
+
+    /// Тест проверяет, что `compress_depth` задаёт число несжатых узлов с
+    /// каждого края, а `0` отключает сжатие.
+    #[test]
+    fn test_compress_depth() {
+        let mut list = compressible(160, 16);
+        assert_eq!(list.segments.len(), 10);
+
+        list.set_compress_depth(3);
+        assert_eq!(list.compressed_nodes(), 4);
+        assert!(list.validate().is_ok());
+
+        list.set_compress_depth(0);
+        assert_eq!(list.compressed_nodes(), 0);
+
+        let mut plain = QuickList::with_compress_depth(16, 0);
+        assert_eq!(plain.compress_depth(), 0);
+        for i in 0..160 {
+            plain.push_back(format!("value-{i:06}"));
+        }
+        assert_eq!(plain.compressed_nodes(), 0);
+        assert_eq!(plain, list);
+    }
+
+    /// Тест проверяет чтение и изменение элементов во сжатом узле: узел
+    /// распаковывается для доступа и снова сжимается после изменения.
+    #[test]
+    fn test_access_compressed_node() {
+        let mut list = compressible(160, 16);
+        let compressed = list.compressed_nodes();
+
+        assert_eq!(list.get(80).map(String::as_str), Some("value-000080"));
+        assert_eq!(list.compressed_nodes(), compressed - 1);
+
+        assert!(list.set(80, "changed".repeat(8)));
+        assert_eq!(list.compressed_nodes(), compressed);
+        assert_eq!(list.get(80), Some(&"changed".repeat(8)));
+
+        list.insert(40, "inserted".to_string());
+        assert_eq!(list.len(), 161);
+        assert_eq!(list.get(40).map(String::as_str), Some("inserted"));
+        assert_eq!(list.get(41).map(String::as_str), Some("value-000040"));
+        assert!(list.validate().is_ok());
+    }
+
+    /// Тест проверяет, что при извлечении с краёв сжатые узлы, ставшие
+    /// крайними, распаковываются.
+    #[test]
+    fn test_pop_through_compressed_nodes() {
+        let mut list = compressible(160, 16);
+
+        for i in 0..100 {
+            assert_eq!(list.pop_front(), Some(format!("value-{i:06}")));
+            assert!(list.validate().is_ok());
+        }
+        for i in (100..160).rev() {
+            assert_eq!(list.pop_back(), Some(format!("value-{i:06}")));
+        }
+        assert!(list.is_empty());
+        assert_eq!(list.compressed_nodes(), 0);
+    }
+
+    /// Тест проверяет, что `trim`, `optimize` и serde сохраняют элементы и
+    /// восстанавливают сжатие внутренних узлов.
+    #[test]
+    fn test_compression_after_rebuild() {
+        let mut list = compressible(320, 16);
+        list.trim(20, 299);
+        assert_eq!(list.len(), 280);
+        assert_eq!(
+            list.iter().next().as_deref().map(String::as_str),
+            Some("value-000020")
+        );
+        assert!(list.compressed_nodes() > 0);
+        assert!(list.validate().is_ok());
+
+        list.optimize();
+        assert!(list.compressed_nodes() > 0);
+        assert!(list.validate().is_ok());
+
+        let json = serde_json::to_string(&list).unwrap();
+        let restored: QuickList<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, list);
+        assert_eq!(restored.compressed_nodes(), list.compressed_nodes());
+    }
+
+    /// Тест проверяет, что мелкие и несжимаемые узлы остаются несжатыми.
+    #[test]
+    fn test_small_nodes_stay_plain() {
+        let list = QuickList::from_iter(0..100, 3);
+        assert_eq!(list.compressed_nodes(), 0);
+        assert!(list.validate().is_ok());
+    }
 }
@@ -0,0 +1,84 @@
+//! Курсорный обход коллекций для `HSCAN`, `SSCAN` и `ZSCAN`.
+
+use super::glob::glob_match;
+
+/// Число элементов, просматриваемых за вызов, если `COUNT` не указан.
+pub const DEFAULT_SCAN_COUNT: usize = 10;
+
+/// Возвращает одну страницу курсорного обхода `items`.
+///
+/// Курсор — смещение элемента в порядке обхода коллекции (порядок стабилен,
+/// пока коллекция не изменяется). За вызов просматривается до `count`
+/// элементов, из них возвращаются подходящие под `pattern`; имя для
+/// сопоставления берётся через `name_of`. Следующий курсор `0` означает
+/// завершение обхода.
+pub fn scan_page<T, I, F>(
+    items: I,
+    cursor: u64,
+    count: usize,
+    pattern: Option<&str>,
+    name_of: F,
+) -> (u64, Vec<T>)
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(&T) -> &[u8],
+{
+    let mut iter = items.into_iter().skip(cursor as usize).peekable();
+    let mut page = Vec::new();
+    let mut seen = 0u64;
+
+    while seen < count.max(1) as u64 {
+        let Some(item) = iter.next() else {
+            break;
+        };
+        seen += 1;
+        if pattern.is_none_or(|p| glob_match(p.as_bytes(), name_of(&item))) {
+            page.push(item);
+        }
+    }
+
+    let next = if iter.peek().is_some() {
+        cursor + seen
+    } else {
+        0
+    };
+    (next, page)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет, что последовательные вызовы обходят все элементы и
+    /// завершаются курсором 0.
+    #[test]
+    fn test_scan_page_full_iteration() {
+        let items: Vec<String> = (0..7).map(|i| format!("k{i}")).collect();
+        let mut cursor = 0;
+        let mut seen = Vec::new();
+        loop {
+            let (next, page) = scan_page(items.iter(), cursor, 3, None, |s| s.as_bytes());
+            seen.extend(page.into_iter().cloned());
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        assert_eq!(seen, items);
+    }
+
+    /// Тест проверяет фильтрацию по шаблону: COUNT ограничивает число
+    /// просмотренных, а не возвращённых элементов.
+    #[test]
+    fn test_scan_page_match() {
+        let items = ["a1", "b1", "a2", "b2"];
+        let (next, page) = scan_page(items, 0, 2, Some("a*"), |s| s.as_bytes());
+        assert_eq!((next, page), (2, vec!["a1"]));
+        let (next, page) = scan_page(items, 2, 10, Some("a*"), |s| s.as_bytes());
+        assert_eq!((next, page), (0, vec!["a2"]));
+    }
+}
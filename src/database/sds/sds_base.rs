@@ -245,6 +245,15 @@ impl Sds {
         }
     }
 
+    /// Возвращает число байт, занятых строкой в куче (`0` для inline-строк).
+    #[inline]
+    pub fn heap_size(&self) -> usize {
+        match &self.0 {
+            Repr::Inline { .. } => 0,
+            Repr::Heap { buf } => buf.capacity(),
+        }
+    }
+
     /// Возвращает `true`, если строка хранится на стеке (inline).
     #[inline]
     pub fn is_inline(&self) -> bool {
@@ -361,6 +370,37 @@ impl Sds {
         self.inline_downgrade();
     }
 
+    /// Перезаписывает байты начиная с `offset` содержимым `data`.
+    ///
+    /// Если строка короче `offset + data.len()`, она расширяется, а разрыв
+    /// между прежним концом и `offset` явно заполняется нулевыми байтами.
+    /// Пустой `data` строку не изменяет.
+    pub fn set_range(
+        &mut self,
+        offset: usize,
+        data: &[u8],
+    ) {
+        if data.is_empty() {
+            return;
+        }
+
+        let end = offset + data.len();
+        let cur_len = self.len();
+        if end > cur_len {
+            self.reserve(end - cur_len);
+            match &mut self.0 {
+                Repr::Inline { len, buf } => {
+                    // В inline-буфере за `len` могут остаться старые байты.
+                    buf[cur_len..end].fill(0);
+                    *len = end as u8;
+                }
+                Repr::Heap { buf } => buf.resize(end, 0),
+            }
+        }
+
+        self.as_mut_slice()[offset..end].copy_from_slice(data);
+    }
+
     /// Возвращает копию подстроки в диапазоне `[start, end)`.
     pub fn slice_range(
         &self,
@@ -1580,4 +1620,29 @@ mod tests {
 
         assert_eq!(map.get(b"valid_key".as_ref()), Some(&99));
     }
+
+    /// Тест проверяет, что `set_range` перезаписывает байты внутри строки,
+    /// дополняет её нулями при выходе за конец (в том числе после `truncate`
+    /// inline-строки) и переводит строку в heap при превышении inline-ёмкости.
+    #[test]
+    fn test_set_range() {
+        let mut s = Sds::from_str("Hello World");
+        s.set_range(6, b"Redis");
+        assert_eq!(s.as_slice(), b"Hello Redis");
+
+        let mut s = Sds::from_str("abcdef");
+        s.truncate(2);
+        s.set_range(4, b"xy");
+        assert_eq!(s.as_slice(), b"ab\0\0xy");
+
+        let mut s = Sds::default();
+        s.set_range(Sds::INLINE_CAP, b"z");
+        assert!(!s.is_inline());
+        assert_eq!(s.len(), Sds::INLINE_CAP + 1);
+        assert!(s.as_slice()[..Sds::INLINE_CAP].iter().all(|&b| b == 0));
+
+        let mut s = Sds::from_str("abc");
+        s.set_range(10, b"");
+        assert_eq!(s.as_slice(), b"abc");
+    }
 }
@@ -60,10 +60,55 @@ pub struct RangeIter<'a, K, V> {
     _marker: PhantomData<&'a Node<K, V>>,
 }
 
+/// Граница лексикографического диапазона (как в ZRANGEBYLEX).
+///
+/// - `[member` — включительная граница;
+/// - `(member` — исключающая граница;
+/// - `-` / `+` — минус и плюс бесконечность.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound<T> {
+    /// `-` — меньше любого значения.
+    NegInf,
+    /// `+` — больше любого значения.
+    PosInf,
+    /// `[value` — граница включается в диапазон.
+    Inclusive(T),
+    /// `(value` — граница не включается в диапазон.
+    Exclusive(T),
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl<T: Ord> LexBound<T> {
+    /// Проверяет, что `value` не меньше нижней границы.
+    pub fn is_above_min(
+        &self,
+        value: &T,
+    ) -> bool {
+        match self {
+            LexBound::NegInf => true,
+            LexBound::PosInf => false,
+            LexBound::Inclusive(b) => value >= b,
+            LexBound::Exclusive(b) => value > b,
+        }
+    }
+
+    /// Проверяет, что `value` не больше верхней границы.
+    pub fn is_below_max(
+        &self,
+        value: &T,
+    ) -> bool {
+        match self {
+            LexBound::NegInf => false,
+            LexBound::PosInf => true,
+            LexBound::Inclusive(b) => value <= b,
+            LexBound::Exclusive(b) => value < b,
+        }
+    }
+}
+
 impl<K, V> Node<K, V> {
     /// Создаёт новый узел с заданным уровнем.
     fn new(
@@ -282,6 +327,12 @@ where
         self.length
     }
 
+    /// Возвращает память кучи, занятую узлами (включая головной), без учёта
+    /// памяти кучи самих ключей и значений.
+    pub fn heap_size(&self) -> usize {
+        (self.length + 1) * std::mem::size_of::<Node<K, V>>()
+    }
+
     /// Возвращает итератор по (&K, &V) в порядке возрастания ключа.
     pub fn iter(&self) -> SkipListIter<'_, K, V> {
         unsafe {
@@ -398,6 +449,20 @@ where
         }
     }
 
+    /// Удаляет и возвращает элемент с минимальным ключом.
+    pub fn pop_min(&mut self) -> Option<(K, V)> {
+        let key = self.first()?.0.clone();
+        let value = self.remove(&key)?;
+        Some((key, value))
+    }
+
+    /// Удаляет и возвращает элемент с максимальным ключом.
+    pub fn pop_max(&mut self) -> Option<(K, V)> {
+        let key = self.last()?.0.clone();
+        let value = self.remove(&key)?;
+        Some((key, value))
+    }
+
     /// Возвращает указатель на последний элемент (хвост) списка (исключая
     /// голову).
     pub fn last_node(&self) -> Option<NonNull<Node<K, V>>> {
@@ -781,6 +846,19 @@ mod tests {
         assert_eq!(collected, vec![2, 3, 4]);
     }
 
+    #[test]
+    fn test_pop_min_max() {
+        let mut sl = make_list(&[(2, 20), (1, 10), (3, 30)]);
+
+        assert_eq!(sl.pop_min(), Some((1, 10)));
+        assert_eq!(sl.pop_max(), Some((3, 30)));
+        assert_eq!(sl.len(), 1);
+        assert_eq!(sl.pop_max(), Some((2, 20)));
+        assert_eq!(sl.pop_min(), None);
+        assert_eq!(sl.pop_max(), None);
+        assert!(sl.validate_invariants().is_ok());
+    }
+
     #[test]
     fn test_clear() {
         let mut sl = make_list(&[(1, 10), (2, 20), (3, 30)]);
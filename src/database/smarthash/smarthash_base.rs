@@ -9,16 +9,40 @@
 
 use std::{
     collections::{hash_map, HashMap},
-    slice,
+    fmt, slice,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
 };
 
+use rand::{seq::SliceRandom, thread_rng};
 use serde::{Deserialize, Serialize};
 
-use crate::Sds;
-
-/// Порог, при достижении которого `SmartHash` переключается с
-/// `Zip` на `Map`.
-const THRESHOLD: usize = 32;
+use crate::{scan_page, Sds, StoreError, StoreResult};
+
+/// Максимальное число полей в `Zip` по умолчанию
+/// (`hash-max-listpack-entries`).
+pub const DEFAULT_MAX_LISTPACK_ENTRIES: usize = 128;
+/// Максимальная длина поля или значения в `Zip` по умолчанию
+/// (`hash-max-listpack-value`).
+pub const DEFAULT_MAX_LISTPACK_VALUE: usize = 64;
+/// Наибольшее число элементов с повторами (отрицательный `count`) у
+/// `HRANDFIELD` и `ZRANDMEMBER`: ответ собирается в памяти целиком.
+pub const MAX_RANDOM_COUNT: usize = 1 << 20;
+
+/// Текущий лимит числа полей `Zip`, общий для всех хешей процесса.
+static MAX_LISTPACK_ENTRIES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_LISTPACK_ENTRIES);
+/// Текущий лимит длины поля или значения `Zip`.
+static MAX_LISTPACK_VALUE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_LISTPACK_VALUE);
+/// Число переходов `Zip` → `Map` с момента запуска.
+static PROMOTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Внутреннее представление хеша, видимое снаружи (`OBJECT ENCODING`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashRepr {
+    /// Компактный вектор пар (`Zip`)
+    ListPack,
+    /// Хеш-таблица (`Map`)
+    HashMap,
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 enum Repr {
@@ -44,7 +68,42 @@ pub struct SmartHash {
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl HashRepr {
+    /// Возвращает имя кодировки для `OBJECT ENCODING`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ListPack => "listpack",
+            Self::HashMap => "hashtable",
+        }
+    }
+}
+
 impl SmartHash {
+    /// Задаёт лимиты компактного представления для всех хешей
+    /// (`hash-max-listpack-entries` и `hash-max-listpack-value`). Хеш,
+    /// превысивший любой из них, при следующей вставке переходит в `Map`.
+    pub fn set_listpack_limits(
+        max_entries: usize,
+        max_value_bytes: usize,
+    ) {
+        MAX_LISTPACK_ENTRIES.store(max_entries, Ordering::Relaxed);
+        MAX_LISTPACK_VALUE.store(max_value_bytes, Ordering::Relaxed);
+    }
+
+    /// Возвращает текущие лимиты `(max_entries, max_value_bytes)`.
+    pub fn listpack_limits() -> (usize, usize) {
+        (
+            MAX_LISTPACK_ENTRIES.load(Ordering::Relaxed),
+            MAX_LISTPACK_VALUE.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Возвращает число переходов `Zip` → `Map` во всех хешах с момента
+    /// запуска (`INFO stats`).
+    pub fn promote_stats() -> u64 {
+        PROMOTIONS.load(Ordering::Relaxed)
+    }
+
     /// Создаёт пустой SmartHash (начинаем в Zip‑режиме).
     pub fn new() -> Self {
         SmartHash {
@@ -53,6 +112,17 @@ impl SmartHash {
         }
     }
 
+    /// Возвращает память кучи, занятую таблицей пар, без учёта памяти кучи
+    /// самих ключей и значений.
+    pub fn heap_size(&self) -> usize {
+        let pair = std::mem::size_of::<(Sds, Sds)>();
+        match &self.repr {
+            Repr::Zip(v) => v.capacity() * pair,
+            // HashMap хранит дополнительно по управляющему байту на слот.
+            Repr::Map(m) => m.capacity() * (pair + 1),
+        }
+    }
+
     /// Возвращает количество элементов в структуре.
     pub fn len(&self) -> usize {
         match &self.repr {
@@ -66,8 +136,21 @@ impl SmartHash {
         self.len() == 0
     }
 
-    /// Проверяет наличие ключа.
-    pub fn contains(
+    /// Возвращает `true`, если используется компактное представление `Zip`.
+    pub fn is_zip(&self) -> bool {
+        matches!(self.repr, Repr::Zip(_))
+    }
+
+    /// Возвращает текущее представление хеша.
+    pub fn repr(&self) -> HashRepr {
+        match self.repr {
+            Repr::Zip(_) => HashRepr::ListPack,
+            Repr::Map(_) => HashRepr::HashMap,
+        }
+    }
+
+    /// Проверяет наличие ключа без обращения к значению (`HEXISTS`).
+    pub fn contains_key(
         &self,
         key: &Sds,
     ) -> bool {
@@ -79,36 +162,48 @@ impl SmartHash {
 
     /// Вставляем или обновляем пару (key, value).
     ///
-    /// При превышении количества элементов порогового значения
-    /// происходит автоматическое переключение представления с
-    /// `Zip` на `Map`.
+    /// Если после вставки число полей превышает `hash-max-listpack-entries`
+    /// или длина поля либо значения — `hash-max-listpack-value`,
+    /// представление переключается с `Zip` на `Map`.
     pub fn insert(
         &mut self,
         key: Sds,
         value: Sds,
+    ) -> bool {
+        self.insert_with_limits(key, value, Self::listpack_limits())
+    }
+
+    /// Вставка с явными лимитами `(max_entries, max_value_bytes)`
+    /// компактного представления.
+    fn insert_with_limits(
+        &mut self,
+        key: Sds,
+        value: Sds,
+        (max_entries, max_value): (usize, usize),
     ) -> bool {
         if self.pending_downgrade {
-            self.do_downgrade();
+            self.do_downgrade(max_value);
         }
 
         match &mut self.repr {
             Repr::Zip(vec) => {
-                if let Some((_, v)) = vec.iter_mut().find(|(k, _)| *k == key) {
-                    *v = value;
-                    return false;
-                }
-
-                vec.push((key, value));
+                let oversized = key.len() > max_value || value.len() > max_value;
 
-                if vec.len() >= THRESHOLD {
-                    let mut map = HashMap::with_capacity(vec.len());
-                    for (k, v) in vec.drain(..) {
-                        map.insert(k, v);
+                let added = match vec.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, v)) => {
+                        *v = value;
+                        false
                     }
-                    self.repr = Repr::Map(map);
-                }
+                    None => {
+                        vec.push((key, value));
+                        true
+                    }
+                };
 
-                true
+                if oversized || vec.len() > max_entries {
+                    self.promote();
+                }
+                added
             }
             Repr::Map(map) => {
                 map.insert(key, value).is_none()
@@ -131,11 +226,21 @@ impl SmartHash {
         }
     }
 
+    /// Возвращает значения нескольких полей в порядке запроса (`HMGET`):
+    /// `None` для отсутствующих полей.
+    pub fn get_many(
+        &self,
+        fields: &[&Sds],
+    ) -> Vec<Option<Sds>> {
+        fields.iter().map(|f| self.get(f).cloned()).collect()
+    }
+
     /// Удаляет значение, соответствующее заданному ключу.
     ///
     /// Возвращает `true`, если ключ найден и значение удалено.
-    /// При уменьшении размера структуры ниже половины порогового
-    /// значения происходит downgrade до представления `Zip`.
+    /// При уменьшении размера структуры ниже половины
+    /// `hash-max-listpack-entries` происходит downgrade до представления
+    /// `Zip`.
     pub fn remove(
         &mut self,
         key: &Sds,
@@ -158,7 +263,7 @@ impl SmartHash {
             if let Repr::Map(map) = &self.repr {
                 // Если размер сильно упал, и, скажем, мы ещё не проводили downgrade,
                 // пометим, что он должен быть выполнен при следующей операции.
-                if map.len() < THRESHOLD / 2 {
+                if map.len() < Self::listpack_limits().0 / 2 {
                     self.pending_downgrade = true;
                 }
             }
@@ -180,7 +285,22 @@ impl SmartHash {
     }
 
     /// Проводит реальный даунгрейд из Map в Zip (вызывается лениво).
-    fn do_downgrade(&mut self) {
+    ///
+    /// Хеш с полем или значением длиннее `hash-max-listpack-value`
+    /// остаётся в `Map`.
+    fn do_downgrade(
+        &mut self,
+        max_value: usize,
+    ) {
+        self.pending_downgrade = false;
+        if let Repr::Map(map) = &self.repr {
+            if map
+                .iter()
+                .any(|(k, v)| k.len() > max_value || v.len() > max_value)
+            {
+                return;
+            }
+        }
         if let Repr::Map(mut map) = std::mem::replace(&mut self.repr, Repr::Zip(Vec::new())) {
             let mut vec = Vec::with_capacity(map.len());
             for (k, v) in map.drain() {
@@ -188,7 +308,15 @@ impl SmartHash {
             }
             self.repr = Repr::Zip(vec);
         }
-        self.pending_downgrade = false;
+    }
+
+    /// Переводит `Zip` в `Map` и учитывает переход в статистике.
+    fn promote(&mut self) {
+        if let Repr::Zip(vec) = &mut self.repr {
+            let map: HashMap<Sds, Sds> = vec.drain(..).collect();
+            self.repr = Repr::Map(map);
+            PROMOTIONS.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     /// Очищает все записи.
@@ -197,19 +325,20 @@ impl SmartHash {
         self.pending_downgrade = false;
     }
 
-    /// Список всех ключей (ненумерованный порядок).
-    pub fn keys(&self) -> Vec<Sds> {
+    /// Итератор по ключам (ненумерованный порядок) без копирования.
+    pub fn keys(&self) -> SmartHashKeys<'_> {
         match &self.repr {
-            Repr::Zip(v) => v.iter().map(|(k, _)| k.clone()).collect(),
-            Repr::Map(m) => m.keys().cloned().collect(),
+            Repr::Zip(v) => SmartHashKeys::Zip(v.iter()),
+            Repr::Map(m) => SmartHashKeys::Map(m.keys()),
         }
     }
 
-    /// Список всех значений.
-    pub fn values(&self) -> Vec<Sds> {
+    /// Итератор по значениям в том же порядке, что и `keys`, без
+    /// копирования.
+    pub fn values(&self) -> SmartHashValues<'_> {
         match &self.repr {
-            Repr::Zip(v) => v.iter().map(|(_, v)| v.clone()).collect(),
-            Repr::Map(m) => m.values().cloned().collect(),
+            Repr::Zip(v) => SmartHashValues::Zip(v.iter()),
+            Repr::Map(m) => SmartHashValues::Map(m.values()),
         }
     }
 
@@ -221,10 +350,116 @@ impl SmartHash {
         }
     }
 
+    /// Возвращает случайные поля (и, при `with_values`, их значения) плоским
+    /// списком.
+    ///
+    /// Положительный `count` выбирает до `count` различных полей,
+    /// отрицательный — ровно `|count|` полей с возможными повторами.
+    /// `|count|` больше [`MAX_RANDOM_COUNT`] даёт `InvalidArgument`.
+    pub fn random_fields(
+        &self,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        if count < 0 && count.unsigned_abs() > MAX_RANDOM_COUNT {
+            return Err(StoreError::InvalidArgument("value is out of range".into()));
+        }
+        let entries = self.entries();
+        if entries.is_empty() || count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut rng = thread_rng();
+        let picked: Vec<&(Sds, Sds)> = if count > 0 {
+            entries.choose_multiple(&mut rng, count as usize).collect()
+        } else {
+            (0..count.unsigned_abs())
+                .filter_map(|_| entries.choose(&mut rng))
+                .collect()
+        };
+
+        let width = if with_values { 2 } else { 1 };
+        let mut out = Vec::with_capacity(picked.len() * width);
+        for (field, value) in picked {
+            out.push(field.clone());
+            if with_values {
+                out.push(value.clone());
+            }
+        }
+        Ok(out)
+    }
+
+    /// Увеличивает целочисленное значение поля на `delta` (`HINCRBY`).
+    ///
+    /// Отсутствующее поле считается равным `0`. Возвращает `InvalidValue`,
+    /// если текущее значение не является целым числом, и `Overflow` при
+    /// переполнении `i64`.
+    pub fn incr_by(
+        &mut self,
+        field: Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
+        let current: i64 = match self.get(&field) {
+            Some(v) => parse_field(v)?,
+            None => 0,
+        };
+        let value = current.checked_add(delta).ok_or(StoreError::Overflow)?;
+        self.insert(field, Sds::from_str(&value.to_string()));
+        Ok(value)
+    }
+
+    /// Увеличивает значение поля с плавающей точкой на `delta`
+    /// (`HINCRBYFLOAT`).
+    ///
+    /// Отсутствующее поле считается равным `0.0`. Возвращает `InvalidValue`,
+    /// если текущее значение не является числом или результат получается
+    /// `NaN`/бесконечным; в этом случае поле не изменяется.
+    pub fn incr_by_float(
+        &mut self,
+        field: Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
+        let current: f64 = match self.get(&field) {
+            Some(v) => parse_field(v)?,
+            None => 0.0,
+        };
+        let value = current + delta;
+        if !value.is_finite() {
+            return Err(StoreError::InvalidValue);
+        }
+        self.insert(field, Sds::from_str(&value.to_string()));
+        Ok(value)
+    }
+
+    /// Одна страница курсорного обхода (`HSCAN`).
+    ///
+    /// Курсор — смещение в порядке обхода: позиция в векторе для `Zip` и в
+    /// порядке обхода хеш-таблицы для `Map`. За вызов просматривается до
+    /// `count` пар, возвращаются те, чьё поле подходит под `match_pat`.
+    /// Следующий курсор `0` означает завершение обхода.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        match_pat: Option<&str>,
+        count: usize,
+    ) -> (u64, Vec<(Sds, Sds)>) {
+        let pairs: Box<dyn Iterator<Item = (&Sds, &Sds)>> = match &self.repr {
+            Repr::Zip(v) => Box::new(v.iter().map(|(k, v)| (k, v))),
+            Repr::Map(m) => Box::new(m.iter()),
+        };
+        let (next, page) = scan_page(pairs, cursor, count, match_pat, |(k, _)| k.as_slice());
+        (
+            next,
+            page.into_iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        )
+    }
+
     /// Возвращает итератор по парам ключ-значение.
     pub fn iter(&mut self) -> SmartHashIter<'_> {
         if self.pending_downgrade {
-            self.do_downgrade();
+            self.do_downgrade(Self::listpack_limits().1);
         }
 
         match &self.repr {
@@ -238,6 +473,15 @@ impl SmartHash {
 // Общие реализации трейтов для SmartHash
 ////////////////////////////////////////////////////////////////////////////////
 
+impl fmt::Display for HashRepr {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl Default for SmartHash {
     fn default() -> Self {
         SmartHash::new()
@@ -284,6 +528,53 @@ impl<'a> Iterator for SmartHashIter<'a> {
     }
 }
 
+/// Итератор по ключам `SmartHash`.
+pub enum SmartHashKeys<'a> {
+    /// Итератор по компактному представлению `Zip`.
+    Zip(slice::Iter<'a, (Sds, Sds)>),
+
+    /// Итератор по представлению `Map`.
+    Map(hash_map::Keys<'a, Sds, Sds>),
+}
+
+impl<'a> Iterator for SmartHashKeys<'a> {
+    type Item = &'a Sds;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SmartHashKeys::Zip(iter) => iter.next().map(|(k, _)| k),
+            SmartHashKeys::Map(iter) => iter.next(),
+        }
+    }
+}
+
+/// Итератор по значениям `SmartHash`.
+pub enum SmartHashValues<'a> {
+    /// Итератор по компактному представлению `Zip`.
+    Zip(slice::Iter<'a, (Sds, Sds)>),
+
+    /// Итератор по представлению `Map`.
+    Map(hash_map::Values<'a, Sds, Sds>),
+}
+
+impl<'a> Iterator for SmartHashValues<'a> {
+    type Item = &'a Sds;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SmartHashValues::Zip(iter) => iter.next().map(|(_, v)| v),
+            SmartHashValues::Map(iter) => iter.next(),
+        }
+    }
+}
+
+/// Разбирает значение поля хеша как число.
+fn parse_field<T: std::str::FromStr>(value: &Sds) -> StoreResult<T> {
+    value
+        .as_str()
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or(StoreError::InvalidValue)
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -309,7 +600,7 @@ mod tests {
         let v = Sds::from_str("za");
         sh.insert(k.clone(), v);
         assert!(sh.remove(&k));
-        assert!(!sh.contains(&k));
+        assert!(!sh.contains_key(&k));
         assert!(sh.is_empty());
     }
 
@@ -317,14 +608,15 @@ mod tests {
     fn test_auto_convert_to_map_and_lazy_downgrade() {
         let mut sh = SmartHash::new();
         // переполним Zip → Map
-        for i in 0..(THRESHOLD + 1) {
+        for i in 0..(DEFAULT_MAX_LISTPACK_ENTRIES + 1) {
             let k = Sds::from_str(&i.to_string());
             sh.insert(k, Sds::from_str("v"));
         }
         assert!(matches!(sh.repr, Repr::Map(_)));
+        assert!(!sh.is_zip());
 
         // удалим всё
-        for i in 0..(THRESHOLD + 1) {
+        for i in 0..(DEFAULT_MAX_LISTPACK_ENTRIES + 1) {
             let k = Sds::from_str(&i.to_string());
             assert!(sh.remove(&k));
         }
@@ -335,6 +627,7 @@ mod tests {
         sh.insert(Sds::from_str("x"), Sds::from_str("y"));
         assert!(!sh.pending_downgrade);
         assert!(matches!(sh.repr, Repr::Zip(_)));
+        assert!(sh.is_zip());
     }
 
     #[test]
@@ -368,10 +661,8 @@ mod tests {
         let mut sh = SmartHash::new();
         sh.insert(Sds::from_str("x"), Sds::from_str("10"));
         sh.insert(Sds::from_str("y"), Sds::from_str("20"));
-        let keys = sh.keys();
-        assert!(keys.contains(&Sds::from_str("x")));
-        let values = sh.values();
-        assert!(values.contains(&Sds::from_str("20")));
+        assert!(sh.keys().any(|k| k == &Sds::from_str("x")));
+        assert!(sh.values().any(|v| v == &Sds::from_str("20")));
         let entries = sh.entries();
         assert!(entries.iter().any(|(k, _)| k == &Sds::from_str("y")));
         let frame = sh.get_all();
@@ -409,4 +700,132 @@ mod tests {
             assert_eq!(sh.get(&k), Some(&v));
         }
     }
+
+    #[test]
+    fn test_random_fields() {
+        let mut sh = SmartHash::new();
+        for i in 0..40 {
+            sh.insert(Sds::from(format!("f{i}")), Sds::from_str("v"));
+        }
+
+        let mut distinct = sh.random_fields(50, false).unwrap();
+        assert_eq!(distinct.len(), 40);
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 40);
+
+        assert_eq!(sh.random_fields(-100, false).unwrap().len(), 100);
+        assert!(sh.random_fields(0, true).unwrap().is_empty());
+
+        let pair = sh.random_fields(1, true).unwrap();
+        assert_eq!(pair.len(), 2);
+        assert_eq!(pair[1], Sds::from_str("v"));
+        assert!(SmartHash::new()
+            .random_fields(-3, false)
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Тест проверяет, что слишком большой отрицательный `count`
+    /// отклоняется до выборки, а граничный допускается.
+    #[test]
+    fn test_random_fields_count_out_of_range() {
+        let mut sh = SmartHash::new();
+        sh.insert(Sds::from_str("f"), Sds::from_str("v"));
+
+        assert!(matches!(
+            sh.random_fields(-isize::MAX, true),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            SmartHash::new().random_fields(-(MAX_RANDOM_COUNT as isize) - 1, false),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            sh.random_fields(-(MAX_RANDOM_COUNT as isize), false)
+                .unwrap()
+                .len(),
+            MAX_RANDOM_COUNT
+        );
+    }
+
+    /// Тест проверяет, что `keys` и `values` обходят поля в согласованном
+    /// порядке в обоих представлениях, а `contains_key` видит все поля.
+    #[test]
+    fn test_keys_values_iterators_both_reprs() {
+        for n in [3, DEFAULT_MAX_LISTPACK_ENTRIES + 5] {
+            let mut sh = SmartHash::new();
+            for i in 0..n {
+                sh.insert(Sds::from(format!("k{i}")), Sds::from(format!("v{i}")));
+            }
+            assert_eq!(sh.is_zip(), n <= DEFAULT_MAX_LISTPACK_ENTRIES);
+
+            let pairs: Vec<_> = sh.keys().zip(sh.values()).collect();
+            assert_eq!(pairs.len(), n);
+            for (k, v) in pairs {
+                assert_eq!(&k.as_slice()[1..], &v.as_slice()[1..]);
+                assert!(sh.contains_key(k));
+            }
+            assert!(!sh.contains_key(&Sds::from_str("missing")));
+        }
+    }
+
+    /// Тест проверяет автоматический переход в `Map` при превышении числа
+    /// полей или длины значения (в том числе при обновлении поля) и то, что
+    /// все значения после перехода остаются актуальными.
+    #[test]
+    fn test_promotion_on_insert_respects_limits() {
+        const LIMITS: (usize, usize) = (4, 8);
+        let before = SmartHash::promote_stats();
+
+        let mut sh = SmartHash::new();
+        for i in 0..4 {
+            sh.insert_with_limits(Sds::from(format!("f{i}")), Sds::from_str("v"), LIMITS);
+        }
+        assert_eq!(sh.repr(), HashRepr::ListPack);
+        sh.insert_with_limits(Sds::from_str("f4"), Sds::from_str("v"), LIMITS);
+        assert_eq!(sh.repr(), HashRepr::HashMap);
+        assert_eq!(sh.repr().as_str(), "hashtable");
+        assert_eq!(sh.len(), 5);
+
+        let mut long = SmartHash::new();
+        long.insert_with_limits(Sds::from_str("f"), Sds::from_str("short"), LIMITS);
+        assert_eq!(long.repr(), HashRepr::ListPack);
+        long.insert_with_limits(Sds::from_str("f"), Sds::from_str("too long value"), LIMITS);
+        assert_eq!(long.repr(), HashRepr::HashMap);
+        assert_eq!(
+            long.get(&Sds::from_str("f")),
+            Some(&Sds::from_str("too long value"))
+        );
+
+        assert!(SmartHash::promote_stats() >= before + 2);
+    }
+
+    /// Тест проверяет, что ленивый downgrade не возвращает в `Zip` хеш со
+    /// значением длиннее лимита.
+    #[test]
+    fn test_downgrade_skips_oversized_values() {
+        const LIMITS: (usize, usize) = (4, 8);
+        let mut sh = SmartHash::new();
+        for i in 0..5 {
+            sh.insert_with_limits(Sds::from(format!("f{i}")), Sds::from_str("v"), LIMITS);
+        }
+        sh.insert_with_limits(
+            Sds::from_str("big"),
+            Sds::from_str("too long value"),
+            LIMITS,
+        );
+        for i in 0..5 {
+            assert!(sh.remove(&Sds::from(format!("f{i}"))));
+        }
+        assert!(sh.pending_downgrade);
+
+        sh.insert_with_limits(Sds::from_str("x"), Sds::from_str("y"), LIMITS);
+        assert_eq!(sh.repr(), HashRepr::HashMap);
+
+        assert!(sh.remove(&Sds::from_str("big")));
+        sh.insert_with_limits(Sds::from_str("z"), Sds::from_str("w"), LIMITS);
+        assert_eq!(sh.repr(), HashRepr::ListPack);
+        assert_eq!(sh.len(), 2);
+    }
 }
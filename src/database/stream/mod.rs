@@ -1,5 +1,9 @@
 pub mod stream_base;
+pub mod stream_group;
+pub mod stream_notifiers;
 
 // Publicly re-export all error types and functions from the submodules to
 // simplify access from external code.
 pub use stream_base::*;
+pub use stream_group::*;
+pub use stream_notifiers::*;
@@ -1,16 +1,25 @@
 use std::{
-    collections::{HashMap, VecDeque},
-    sync::atomic::{AtomicU64, Ordering},
+    collections::{btree_map, BTreeMap, HashMap, VecDeque},
+    fmt,
+    ops::{Bound, Range},
+    str::FromStr,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use serde::{Deserialize, Serialize};
 
+use super::{AutoClaim, ClaimOptions, ConsumerInfo, GroupInfo, PendingEntry, StreamGroup};
 use crate::Value;
 
+/// Число записей в одном узле потока: приближённая обрезка (`~`) удаляет
+/// записи только целыми узлами, как Redis с `stream-node-max-entries`.
+pub const STREAM_NODE_MAX_ENTRIES: usize = 100;
+
 /// Уникальный идентификатор записи в потоке.
 /// Состоит из времени в миллисекундах и порядкового номера (sequence).
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
 pub struct StreamId {
     /// Время создания записи в миллисекундах с эпохи UNIX
     pub ms_time: u64,
@@ -27,69 +36,356 @@ pub struct StreamEntry {
     pub data: HashMap<String, Value>,
 }
 
+/// Критерий обрезки потока (`MAXLEN` или `MINID`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamTrimStrategy {
+    /// Оставить не более `n` последних записей.
+    MaxLen(usize),
+    /// Удалить записи с идентификатором меньше заданного.
+    MinId(StreamId),
+}
+
+/// Параметры обрезки потока: `MAXLEN|MINID [=|~] threshold [LIMIT count]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamTrim {
+    /// Критерий обрезки.
+    pub strategy: StreamTrimStrategy,
+    /// Приближённая обрезка (`~`): записи удаляются целыми узлами.
+    pub approx: bool,
+    /// Наибольшее число удаляемых записей (`LIMIT`).
+    pub limit: Option<usize>,
+}
+
 /// Поток — структура, хранящая упорядоченный список записей.
-/// Использует атомарный счётчик для генерации уникальных идентификаторов.
-#[derive(Debug, Default)]
+///
+/// Помнит наибольший выданный идентификатор, поэтому новые записи получают
+/// возрастающие ID даже после обрезки и удаления.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Stream {
-    /// Очередь записей
-    entries: VecDeque<StreamEntry>,
-    /// Атомарный счетчик для sequence
-    next_sequence: AtomicU64,
+    /// Очередь записей вместе с надгробиями удалённых; первая и последняя
+    /// ячейки всегда живые
+    entries: VecDeque<StreamSlot>,
+    /// Число надгробий в `entries`
+    deleted: usize,
+    /// Идентификатор последней добавленной записи
+    last_id: StreamId,
+    /// Группы потребителей по имени
+    groups: BTreeMap<String, StreamGroup>,
+}
+
+/// Ячейка очереди записей. Запись, удалённая `XDEL` из середины потока,
+/// остаётся на месте надгробием без данных, пока её не уберёт обрезка:
+/// последующие записи не сдвигаются, а поиск по ID остаётся двоичным.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct StreamSlot {
+    entry: StreamEntry,
+    deleted: bool,
+}
+
+/// Сводка по потоку для `XINFO STREAM`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo<'a> {
+    /// Число записей
+    pub length: usize,
+    /// Последний выданный идентификатор
+    pub last_generated_id: StreamId,
+    /// Самая старая запись
+    pub first_entry: Option<&'a StreamEntry>,
+    /// Самая новая запись
+    pub last_entry: Option<&'a StreamEntry>,
+    /// Число групп потребителей
+    pub groups: usize,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl StreamId {
+    /// Наименьший идентификатор (`0-0`).
+    pub const MIN: StreamId = StreamId::new(0, 0);
+    /// Наибольший идентификатор.
+    pub const MAX: StreamId = StreamId::new(u64::MAX, u64::MAX);
+
+    /// Создаёт идентификатор `ms_time-sequence`.
+    pub const fn new(
+        ms_time: u64,
+        sequence: u64,
+    ) -> Self {
+        Self { ms_time, sequence }
+    }
+}
+
+impl StreamTrim {
+    /// Создаёт точную обрезку без ограничения числа удаляемых записей.
+    pub fn new(strategy: StreamTrimStrategy) -> Self {
+        Self {
+            strategy,
+            approx: false,
+            limit: None,
+        }
+    }
+}
+
 impl Stream {
     /// Создает новый пустой поток
     pub fn new() -> Self {
-        Self {
-            entries: VecDeque::new(),
-            next_sequence: AtomicU64::new(1),
-        }
+        Self::default()
     }
 
     /// Добавляет новую запись с данными в поток.
     /// Автоматически создаёт уникальный идентификатор на основе текущего
     /// времени и sequence.
     /// Возвращает созданный StreamId.
+    ///
+    /// Паникует, если идентификаторы потока исчерпаны (последний ID равен
+    /// [`StreamId::MAX`]).
     pub fn add(
         &mut self,
         data: HashMap<String, Value>,
     ) -> StreamId {
-        let ms_time = Self::current_millis();
-        // Получаем следующий sequence атомарно (с использованием Relaxed порядка)
-        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
-        let id = StreamId { ms_time, sequence };
-        self.entries.push_back(StreamEntry {
-            id: id.clone(),
-            data,
-        });
+        let id = self.next_id().expect("stream IDs exhausted");
+        self.push(id, data);
         id
     }
 
-    /// Возвращает срез записей в диапазоне идентификаторов [start, end].
-    /// Идентификаторы сравниваются с помощью PartialOrd и Ord.
+    /// Добавляет запись с явным идентификатором `id`.
+    ///
+    /// # Возвращает
+    /// - `Some(id)` — если запись добавлена
+    /// - `None` — если `id` не больше последнего идентификатора потока (или
+    ///   равен `0-0`)
+    pub fn add_with_id(
+        &mut self,
+        id: StreamId,
+        data: HashMap<String, Value>,
+    ) -> Option<StreamId> {
+        if id <= self.last_id {
+            return None;
+        }
+        self.push(id, data);
+        Some(id)
+    }
+
+    /// Возвращает идентификатор, который получит следующая запись `XADD *`:
+    /// текущее время, а при совпадении (или отставании часов) — следующий
+    /// sequence последнего идентификатора.
+    ///
+    /// # Возвращает
+    /// - `None` — если последний идентификатор равен [`StreamId::MAX`]
+    pub fn next_id(&self) -> Option<StreamId> {
+        let ms_time = Self::current_millis();
+        if ms_time > self.last_id.ms_time {
+            return Some(StreamId::new(ms_time, 0));
+        }
+        match self.last_id.sequence.checked_add(1) {
+            Some(sequence) => Some(StreamId::new(self.last_id.ms_time, sequence)),
+            None => Some(StreamId::new(self.last_id.ms_time.checked_add(1)?, 0)),
+        }
+    }
+
+    /// Возвращает идентификатор для `XADD ms-*`: наименьший sequence в
+    /// миллисекунде `ms_time`, больший последнего идентификатора.
+    ///
+    /// # Возвращает
+    /// - `None` — если `ms_time` меньше времени последней записи или sequence в
+    ///   этой миллисекунде исчерпан
+    pub fn next_id_at(
+        &self,
+        ms_time: u64,
+    ) -> Option<StreamId> {
+        if ms_time > self.last_id.ms_time {
+            return Some(StreamId::new(ms_time, 0));
+        }
+        if ms_time < self.last_id.ms_time {
+            return None;
+        }
+        self.last_id
+            .sequence
+            .checked_add(1)
+            .map(|sequence| StreamId::new(ms_time, sequence))
+    }
+
+    /// Возвращает идентификатор последней добавленной записи (`0-0` для
+    /// нового потока). Обрезка его не уменьшает.
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    /// Поднимает последний идентификатор до `id`, если тот больше текущего.
+    /// Используется при восстановлении потока, последние записи которого
+    /// были удалены.
+    pub fn advance_last_id(
+        &mut self,
+        id: StreamId,
+    ) {
+        self.last_id = self.last_id.max(id);
+    }
+
+    /// Возвращает первую (самую старую) запись.
+    pub fn first_entry(&self) -> Option<&StreamEntry> {
+        self.entries.front().map(|slot| &slot.entry)
+    }
+
+    /// Возвращает последнюю (самую новую) запись.
+    pub fn last_entry(&self) -> Option<&StreamEntry> {
+        self.entries.back().map(|slot| &slot.entry)
+    }
+
+    /// Обрезает поток согласно `trim`, удаляя самые старые записи.
+    ///
+    /// При `approx` записи удаляются только целыми узлами по
+    /// [`STREAM_NODE_MAX_ENTRIES`], поэтому в потоке может остаться больше
+    /// записей, чем требует порог. `limit` ограничивает число удалённых
+    /// записей. Надгробия `XDEL` занимают место в узлах, но в порог
+    /// `MAXLEN` не засчитываются.
+    ///
+    /// # Возвращает
+    /// - число удалённых записей
+    pub fn trim(
+        &mut self,
+        trim: &StreamTrim,
+    ) -> usize {
+        let mut cut = match trim.strategy {
+            StreamTrimStrategy::MaxLen(max_len) => {
+                let excess = self.len().saturating_sub(max_len);
+                if self.deleted == 0 {
+                    excess
+                } else {
+                    // Ячейка сразу за `excess`-й живой записью.
+                    self.entries
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, slot)| !slot.deleted)
+                        .nth(excess)
+                        .map_or(self.entries.len(), |(index, _)| index)
+                }
+            }
+            StreamTrimStrategy::MinId(min_id) => {
+                self.entries.partition_point(|slot| slot.entry.id < min_id)
+            }
+        };
+        if let Some(limit) = trim.limit {
+            cut = cut.min(limit);
+        }
+        if trim.approx {
+            cut -= cut % STREAM_NODE_MAX_ENTRIES;
+        }
+
+        let tombstones = self
+            .entries
+            .drain(..cut)
+            .filter(|slot| slot.deleted)
+            .count();
+        self.deleted -= tombstones;
+        self.pop_tombstones();
+        cut - tombstones
+    }
+
+    /// Удаляет записи `ids`, оставляя на их месте надгробия, и убирает их
+    /// из PEL всех групп. Отсутствующие и уже удалённые ID пропускаются.
+    ///
+    /// # Возвращает
+    /// - число удалённых записей
+    pub fn delete(
+        &mut self,
+        ids: &[StreamId],
+    ) -> usize {
+        let mut count = 0;
+        for id in ids {
+            let Ok(index) = self.entries.binary_search_by(|slot| slot.entry.id.cmp(id)) else {
+                continue;
+            };
+            let slot = &mut self.entries[index];
+            if slot.deleted {
+                continue;
+            }
+            slot.deleted = true;
+            slot.entry.data = HashMap::new();
+            self.deleted += 1;
+            count += 1;
+            for group in self.groups.values_mut() {
+                group.pel_mut().remove(id);
+            }
+        }
+        self.pop_tombstones();
+        count
+    }
+
+    /// Оставляет в потоке не более `count` последних записей.
+    ///
+    /// # Возвращает
+    /// - число удалённых записей
+    pub fn trim_by_maxlen(
+        &mut self,
+        count: usize,
+        approx: bool,
+    ) -> usize {
+        self.trim(&StreamTrim {
+            approx,
+            ..StreamTrim::new(StreamTrimStrategy::MaxLen(count))
+        })
+    }
+
+    /// Удаляет записи с идентификатором меньше `min_id`.
+    ///
+    /// # Возвращает
+    /// - число удалённых записей
+    pub fn trim_by_minid(
+        &mut self,
+        min_id: StreamId,
+        approx: bool,
+    ) -> usize {
+        self.trim(&StreamTrim {
+            approx,
+            ..StreamTrim::new(StreamTrimStrategy::MinId(min_id))
+        })
+    }
+
+    /// Возвращает записи с идентификаторами между `start` и `end` в порядке
+    /// возрастания, не более `count` штук.
     pub fn range(
         &self,
-        start: &StreamId,
-        end: &StreamId,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+        count: Option<usize>,
     ) -> Vec<&StreamEntry> {
         self.entries
-            .iter()
-            .filter(|entry| entry.id >= *start && entry.id <= *end)
+            .range(self.bounds_to_indices(start, end))
+            .filter(|slot| !slot.deleted)
+            .map(|slot| &slot.entry)
+            .take(count.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Возвращает записи с идентификаторами между `start` и `end` в порядке
+    /// убывания (от `end` к `start`), не более `count` штук.
+    pub fn rev_range(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+        count: Option<usize>,
+    ) -> Vec<&StreamEntry> {
+        self.entries
+            .range(self.bounds_to_indices(start, end))
+            .rev()
+            .filter(|slot| !slot.deleted)
+            .map(|slot| &slot.entry)
+            .take(count.unwrap_or(usize::MAX))
             .collect()
     }
 
     /// Итератор по всем записям в потоке в порядке их добавления.
     pub fn iter(&self) -> impl Iterator<Item = &StreamEntry> {
-        self.entries.iter()
+        self.entries
+            .iter()
+            .filter(|slot| !slot.deleted)
+            .map(|slot| &slot.entry)
     }
 
-    /// Возвращает количество записей в потоке.
+    /// Возвращает количество записей в потоке (без надгробий).
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.entries.len() - self.deleted
     }
 
     /// Проверяет, пуст ли поток.
@@ -97,9 +393,293 @@ impl Stream {
         self.entries.is_empty()
     }
 
+    /// Создаёт группу `name`, которой выданы записи до `last_delivered_id`
+    /// включительно.
+    ///
+    /// # Возвращает
+    /// - `false` — если группа с таким именем уже есть
+    pub fn create_group(
+        &mut self,
+        name: &str,
+        last_delivered_id: StreamId,
+    ) -> bool {
+        match self.groups.entry(name.to_string()) {
+            btree_map::Entry::Occupied(_) => false,
+            btree_map::Entry::Vacant(slot) => {
+                slot.insert(StreamGroup::new(last_delivered_id));
+                true
+            }
+        }
+    }
+
+    /// Возвращает группу `name`.
+    pub fn group(
+        &self,
+        name: &str,
+    ) -> Option<&StreamGroup> {
+        self.groups.get(name)
+    }
+
+    /// Возвращает изменяемую группу `name`.
+    pub fn group_mut(
+        &mut self,
+        name: &str,
+    ) -> Option<&mut StreamGroup> {
+        self.groups.get_mut(name)
+    }
+
+    /// Итератор по группам в порядке имён.
+    pub fn groups(&self) -> btree_map::Iter<'_, String, StreamGroup> {
+        self.groups.iter()
+    }
+
+    /// Выдаёт потребителю `consumer` группы `group` не более `count`
+    /// записей, ещё не выданных группе, и заносит их в PEL с временем
+    /// доставки `now`.
+    ///
+    /// # Возвращает
+    /// - `None` — если группы нет
+    pub fn read_group(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        count: Option<usize>,
+        now: u64,
+    ) -> Option<Vec<&StreamEntry>> {
+        let group = self.groups.get_mut(group)?;
+        group.touch_consumer(consumer, now);
+        let from = self
+            .entries
+            .partition_point(|slot| slot.entry.id <= group.last_delivered_id);
+        let entries: Vec<&StreamEntry> = self
+            .entries
+            .range(from..)
+            .filter(|slot| !slot.deleted)
+            .map(|slot| &slot.entry)
+            .take(count.unwrap_or(usize::MAX))
+            .collect();
+        for entry in &entries {
+            group.pel_mut().deliver(entry.id, consumer, now);
+        }
+        if let Some(last) = entries.last() {
+            group.last_delivered_id = last.id;
+        }
+        Some(entries)
+    }
+
+    /// Передаёт потребителю `consumer` сообщения `ids` из PEL группы
+    /// `group`, простаивающие не меньше `min_idle` миллисекунд к моменту
+    /// `now`.
+    ///
+    /// Сообщения, удалённые из потока, убираются из PEL и не передаются.
+    /// С `opts.force` сообщения потока, которых нет в PEL, заносятся туда.
+    ///
+    /// # Возвращает
+    /// - идентификаторы переданных сообщений
+    /// - `None` — если группы нет
+    pub fn claim(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        ids: &[StreamId],
+        min_idle: u64,
+        opts: &ClaimOptions,
+        now: u64,
+    ) -> Option<Vec<StreamId>> {
+        let group = self.groups.get_mut(group)?;
+        group.touch_consumer(consumer, now);
+        let mut claimed = Vec::new();
+        for &id in ids {
+            let exists = Self::find(&self.entries, id).is_some();
+            let pel = group.pel_mut();
+            match pel.get_mut(&id) {
+                Some(_) if !exists => {
+                    pel.remove(&id);
+                    continue;
+                }
+                Some(pending) if now.saturating_sub(pending.delivery_time) >= min_idle => {
+                    pending.claim(consumer, now, opts);
+                }
+                Some(_) => continue,
+                None if opts.force && exists => {
+                    let mut pending = PendingEntry {
+                        consumer: String::new(),
+                        delivery_time: now,
+                        delivery_count: 0,
+                    };
+                    pending.claim(consumer, now, opts);
+                    pel.insert(id, pending);
+                }
+                None => continue,
+            }
+            claimed.push(id);
+        }
+        Some(claimed)
+    }
+
+    /// Передаёт потребителю `consumer` не более `count` сообщений из PEL
+    /// группы `group`, начиная с `start`, простаивающих не меньше
+    /// `min_idle` миллисекунд к моменту `now`.
+    ///
+    /// Как и Redis, просматривает не более `count * 10` записей PEL за
+    /// вызов; удалённые из потока сообщения убираются из PEL.
+    ///
+    /// # Возвращает
+    /// - `None` — если группы нет
+    #[allow(clippy::too_many_arguments)]
+    pub fn auto_claim(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        min_idle: u64,
+        start: StreamId,
+        count: usize,
+        just_id: bool,
+        now: u64,
+    ) -> Option<AutoClaim> {
+        let group = self.groups.get_mut(group)?;
+        group.touch_consumer(consumer, now);
+        let mut attempts = count.saturating_mul(10);
+        let candidates: Vec<StreamId> = group
+            .pel()
+            .range(Bound::Included(start), Bound::Unbounded)
+            .take(attempts.saturating_add(1))
+            .map(|(id, _)| *id)
+            .collect();
+        let opts = ClaimOptions {
+            just_id,
+            ..ClaimOptions::default()
+        };
+
+        let mut result = AutoClaim::default();
+        let mut candidates = candidates.into_iter();
+        while result.claimed.len() < count && attempts > 0 {
+            let Some(id) = candidates.next() else {
+                break;
+            };
+            attempts -= 1;
+            let pel = group.pel_mut();
+            if Self::find(&self.entries, id).is_none() {
+                pel.remove(&id);
+                result.deleted.push(id);
+                continue;
+            }
+            let Some(pending) = pel.get_mut(&id) else {
+                continue;
+            };
+            if now.saturating_sub(pending.delivery_time) >= min_idle {
+                pending.claim(consumer, now, &opts);
+                result.claimed.push(id);
+            }
+        }
+        result.next = candidates.next().unwrap_or(StreamId::MIN);
+        Some(result)
+    }
+
+    /// Возвращает запись с идентификатором `id`.
+    pub fn entry(
+        &self,
+        id: StreamId,
+    ) -> Option<&StreamEntry> {
+        Self::find(&self.entries, id)
+    }
+
+    /// Возвращает сводку по потоку.
+    pub fn info(&self) -> StreamInfo<'_> {
+        StreamInfo {
+            length: self.len(),
+            last_generated_id: self.last_id,
+            first_entry: self.first_entry(),
+            last_entry: self.last_entry(),
+            groups: self.groups.len(),
+        }
+    }
+
+    /// Возвращает сводки по всем группам в порядке имён.
+    pub fn groups_info(&self) -> Vec<GroupInfo> {
+        self.groups
+            .iter()
+            .map(|(name, group)| group.info(name))
+            .collect()
+    }
+
+    /// Возвращает сводки по потребителям группы `group`; простой
+    /// отсчитывается от `now`.
+    ///
+    /// # Возвращает
+    /// - `None` — если группы нет
+    pub fn consumers_info(
+        &self,
+        group: &str,
+        now: u64,
+    ) -> Option<Vec<ConsumerInfo>> {
+        self.groups
+            .get(group)
+            .map(|group| group.consumers_info(now))
+    }
+
+    /// Переводит границы идентификаторов в диапазон индексов `entries`.
+    fn bounds_to_indices(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+    ) -> Range<usize> {
+        let from = match start {
+            Bound::Included(id) => self.entries.partition_point(|slot| slot.entry.id < id),
+            Bound::Excluded(id) => self.entries.partition_point(|slot| slot.entry.id <= id),
+            Bound::Unbounded => 0,
+        };
+        let to = match end {
+            Bound::Included(id) => self.entries.partition_point(|slot| slot.entry.id <= id),
+            Bound::Excluded(id) => self.entries.partition_point(|slot| slot.entry.id < id),
+            Bound::Unbounded => self.entries.len(),
+        };
+        from..to.max(from)
+    }
+
+    /// Добавляет запись в конец потока; `id` уже проверен вызывающим.
+    fn push(
+        &mut self,
+        id: StreamId,
+        data: HashMap<String, Value>,
+    ) {
+        self.last_id = id;
+        self.entries.push_back(StreamSlot {
+            entry: StreamEntry { id, data },
+            deleted: false,
+        });
+    }
+
+    /// Ищет живую запись `id` в `entries`. Принимает очередь, а не `self`,
+    /// чтобы её можно было вызывать при изменяемой ссылке на группу.
+    fn find(
+        entries: &VecDeque<StreamSlot>,
+        id: StreamId,
+    ) -> Option<&StreamEntry> {
+        entries
+            .binary_search_by(|slot| slot.entry.id.cmp(&id))
+            .ok()
+            .map(|index| &entries[index])
+            .filter(|slot| !slot.deleted)
+            .map(|slot| &slot.entry)
+    }
+
+    /// Убирает надгробия с концов очереди, чтобы первая и последняя ячейки
+    /// были живыми.
+    fn pop_tombstones(&mut self) {
+        while self.entries.front().is_some_and(|slot| slot.deleted) {
+            self.entries.pop_front();
+            self.deleted -= 1;
+        }
+        while self.entries.back().is_some_and(|slot| slot.deleted) {
+            self.entries.pop_back();
+            self.deleted -= 1;
+        }
+    }
+
     /// Вспомогательная функция для получения текущего времени в
     /// миллисекундах с эпохи UNIX.
-    fn current_millis() -> u64 {
+    pub(crate) fn current_millis() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
@@ -107,6 +687,38 @@ impl Stream {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для StreamId
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for StreamId {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}-{}", self.ms_time, self.sequence)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = String;
+
+    /// Разбирает `ms-seq`; `ms` без sequence означает `ms-0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid stream ID specified as stream command argument: '{s}'");
+        let (ms, seq) = match s.split_once('-') {
+            Some((ms, seq)) => (ms, Some(seq)),
+            None => (s, None),
+        };
+        let ms_time = ms.parse::<u64>().map_err(|_| invalid())?;
+        let sequence = match seq {
+            Some(seq) => seq.parse::<u64>().map_err(|_| invalid())?,
+            None => 0,
+        };
+        Ok(StreamId::new(ms_time, sequence))
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -177,8 +789,9 @@ mod tests {
         if id2.ms_time == id1.ms_time {
             assert_eq!(id2.sequence, id1.sequence + 1);
         } else {
-            assert!(id2.sequence >= 1);
+            assert_eq!(id2.sequence, 0);
         }
+        assert_eq!(stream.last_id(), id2);
     }
 
     /// Тест проверяет метод range, который должен вернуть записи,
@@ -195,13 +808,13 @@ mod tests {
         let id3 = stream.add(data3.clone());
 
         // Диапазон от id1 до id2 должен содержать первые две записи
-        let slice = stream.range(&id1, &id2);
+        let slice = stream.range(Bound::Included(id1), Bound::Included(id2), None);
         assert_eq!(slice.len(), 2);
         assert_eq!(slice[0].id, id1);
         assert_eq!(slice[1].id, id2);
 
         // Диапазон от id2 до id3 должен содержать последние две записи
-        let slice2 = stream.range(&id2, &id3);
+        let slice2 = stream.range(Bound::Included(id2), Bound::Included(id3), None);
         assert_eq!(slice2.len(), 2);
         assert_eq!(slice2[0].id, id2);
         assert_eq!(slice2[1].id, id3);
@@ -215,7 +828,373 @@ mod tests {
             ms_time: u64::MAX,
             sequence: u64::MAX,
         };
-        let full = stream.range(&before, &after);
+        let full = stream.range(Bound::Included(before), Bound::Included(after), None);
         assert_eq!(full.len(), 3);
     }
+
+    /// Тест проверяет разбор и вывод идентификаторов: `ms` без sequence
+    /// означает `ms-0`, мусор отклоняется.
+    #[test]
+    fn test_stream_id_parse_and_display() {
+        assert_eq!("5-3".parse::<StreamId>(), Ok(StreamId::new(5, 3)));
+        assert_eq!("7".parse::<StreamId>(), Ok(StreamId::new(7, 0)));
+        assert!("".parse::<StreamId>().is_err());
+        assert!("1-x".parse::<StreamId>().is_err());
+        assert!("-1".parse::<StreamId>().is_err());
+        assert_eq!(StreamId::new(12, 4).to_string(), "12-4");
+    }
+
+    /// Тест проверяет, что явный идентификатор должен быть больше
+    /// последнего, а `next_id_at` подбирает sequence в заданной миллисекунде.
+    #[test]
+    fn test_add_with_id_requires_increasing_ids() {
+        let mut stream = Stream::new();
+        assert_eq!(stream.add_with_id(StreamId::MIN, make_entry("a", 1)), None);
+        assert_eq!(stream.next_id_at(0), Some(StreamId::new(0, 1)));
+
+        let id = StreamId::new(5, 1);
+        assert_eq!(stream.add_with_id(id, make_entry("a", 1)), Some(id));
+        assert_eq!(stream.add_with_id(id, make_entry("b", 2)), None);
+        assert_eq!(
+            stream.add_with_id(StreamId::new(4, 9), make_entry("b", 2)),
+            None
+        );
+
+        assert_eq!(stream.next_id_at(5), Some(StreamId::new(5, 2)));
+        assert_eq!(stream.next_id_at(6), Some(StreamId::new(6, 0)));
+        assert_eq!(stream.next_id_at(4), None);
+        assert!(stream.next_id().unwrap() > id);
+
+        stream.add_with_id(StreamId::MAX, make_entry("c", 3));
+        assert_eq!(stream.next_id(), None);
+    }
+
+    /// Тест проверяет точную и приближённую обрезку по длине, а также
+    /// `LIMIT`.
+    #[test]
+    fn test_trim_by_maxlen() {
+        let mut stream = Stream::new();
+        for i in 1..=250 {
+            stream.add_with_id(StreamId::new(i, 0), make_entry("n", i as i64));
+        }
+
+        // Приближённо удаляются только целые узлы: 150 лишних -> 100.
+        assert_eq!(stream.trim_by_maxlen(100, true), 100);
+        assert_eq!(stream.len(), 150);
+        assert_eq!(stream.first_entry().unwrap().id, StreamId::new(101, 0));
+
+        // Меньше узла лишних — приближённая обрезка ничего не делает.
+        assert_eq!(stream.trim_by_maxlen(100, true), 0);
+
+        let limited = StreamTrim {
+            limit: Some(10),
+            ..StreamTrim::new(StreamTrimStrategy::MaxLen(100))
+        };
+        assert_eq!(stream.trim(&limited), 10);
+        assert_eq!(stream.len(), 140);
+
+        assert_eq!(stream.trim_by_maxlen(3, false), 137);
+        let ids: Vec<_> = stream.iter().map(|e| e.id.ms_time).collect();
+        assert_eq!(ids, [248, 249, 250]);
+
+        // Обрезка не уменьшает последний идентификатор.
+        assert_eq!(stream.trim_by_maxlen(0, false), 3);
+        assert!(stream.is_empty());
+        assert_eq!(stream.last_id(), StreamId::new(250, 0));
+        assert_eq!(
+            stream.add_with_id(StreamId::new(10, 0), HashMap::new()),
+            None
+        );
+    }
+
+    /// Тест проверяет обрезку по минимальному идентификатору.
+    #[test]
+    fn test_trim_by_minid() {
+        let mut stream = Stream::new();
+        for i in 1..=5 {
+            stream.add_with_id(StreamId::new(i, 0), make_entry("n", i as i64));
+            stream.add_with_id(StreamId::new(i, 1), make_entry("n", i as i64));
+        }
+
+        assert_eq!(stream.trim_by_minid(StreamId::new(3, 1), false), 5);
+        assert_eq!(stream.first_entry().unwrap().id, StreamId::new(3, 1));
+        assert_eq!(stream.trim_by_minid(StreamId::new(3, 0), false), 0);
+        assert_eq!(stream.trim_by_minid(StreamId::new(5, 1), true), 0);
+        assert_eq!(stream.len(), 5);
+        assert_eq!(stream.trim_by_minid(StreamId::MAX, false), 5);
+        assert!(stream.is_empty());
+    }
+
+    /// Тест проверяет исключающие и открытые границы, обратный порядок,
+    /// ограничение `count` и пустой поток.
+    #[test]
+    fn test_range_bounds_and_count() {
+        let mut stream = Stream::new();
+        let ids = |entries: Vec<&StreamEntry>| -> Vec<u64> {
+            entries.iter().map(|e| e.id.ms_time).collect()
+        };
+        assert!(stream
+            .range(Bound::Unbounded, Bound::Unbounded, None)
+            .is_empty());
+        assert!(stream
+            .rev_range(Bound::Unbounded, Bound::Unbounded, Some(1))
+            .is_empty());
+
+        for i in 1..=5 {
+            stream.add_with_id(StreamId::new(i, 0), make_entry("n", i as i64));
+        }
+        let id = |ms| StreamId::new(ms, 0);
+
+        assert_eq!(
+            ids(stream.range(Bound::Excluded(id(2)), Bound::Excluded(id(5)), None)),
+            [3, 4]
+        );
+        assert_eq!(
+            ids(stream.range(Bound::Included(id(2)), Bound::Unbounded, Some(2))),
+            [2, 3]
+        );
+        assert_eq!(
+            ids(stream.rev_range(Bound::Unbounded, Bound::Excluded(id(5)), Some(3))),
+            [4, 3, 2]
+        );
+        assert_eq!(
+            ids(stream.range(Bound::Unbounded, Bound::Unbounded, Some(0))),
+            Vec::<u64>::new()
+        );
+        // Пустой и перевёрнутый диапазоны.
+        assert!(stream
+            .range(Bound::Excluded(id(3)), Bound::Excluded(id(4)), None)
+            .is_empty());
+        assert!(stream
+            .range(Bound::Included(id(4)), Bound::Included(id(2)), None)
+            .is_empty());
+    }
+
+    /// Тест проверяет создание групп, выдачу записей группе через
+    /// `read_group` и сводки `info`/`groups_info`/`consumers_info`.
+    #[test]
+    fn test_groups_and_info() {
+        let mut stream = Stream::new();
+        for ms in 1..=3 {
+            stream
+                .add_with_id(StreamId::new(ms, 0), make_entry("n", ms as i64))
+                .unwrap();
+        }
+        assert!(stream.create_group("g", StreamId::new(1, 0)));
+        assert!(!stream.create_group("g", StreamId::MIN));
+        assert!(stream.read_group("missing", "c", None, 0).is_none());
+
+        let read: Vec<_> = stream
+            .read_group("g", "alice", Some(1), 100)
+            .unwrap()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(read, [StreamId::new(2, 0)]);
+        let read = stream.read_group("g", "bob", None, 120).unwrap();
+        assert_eq!(read.len(), 1);
+        assert!(stream.read_group("g", "bob", None, 130).unwrap().is_empty());
+
+        let info = stream.info();
+        assert_eq!(info.length, 3);
+        assert_eq!(info.last_generated_id, StreamId::new(3, 0));
+        assert_eq!(info.first_entry.map(|e| e.id), Some(StreamId::new(1, 0)));
+        assert_eq!(info.last_entry.map(|e| e.id), Some(StreamId::new(3, 0)));
+        assert_eq!(info.groups, 1);
+
+        assert_eq!(
+            stream.groups_info(),
+            [GroupInfo {
+                name: "g".into(),
+                consumers: 2,
+                pending: 2,
+                last_delivered_id: StreamId::new(3, 0),
+            }]
+        );
+        let consumers = stream.consumers_info("g", 150).unwrap();
+        assert_eq!(
+            consumers
+                .iter()
+                .map(|c| (c.name.as_str(), c.pending, c.idle))
+                .collect::<Vec<_>>(),
+            [("alice", 1, 50), ("bob", 1, 20)]
+        );
+        assert!(stream.consumers_info("missing", 0).is_none());
+    }
+
+    /// Тест проверяет `claim`: порог простоя, `FORCE`, `RETRYCOUNT`,
+    /// `JUSTID` и удаление из PEL сообщений, которых нет в потоке.
+    #[test]
+    fn test_claim() {
+        let mut stream = Stream::new();
+        for ms in 1..=3 {
+            stream
+                .add_with_id(StreamId::new(ms, 0), make_entry("n", ms as i64))
+                .unwrap();
+        }
+        stream.create_group("g", StreamId::MIN);
+        stream.read_group("g", "alice", Some(2), 100).unwrap();
+        let (id1, id2, id3) = (
+            StreamId::new(1, 0),
+            StreamId::new(2, 0),
+            StreamId::new(3, 0),
+        );
+        let pending = |stream: &Stream, id| stream.group("g").unwrap().pel().get(&id).cloned();
+
+        let opts = ClaimOptions::default();
+        assert!(stream
+            .claim("missing", "bob", &[id1], 0, &opts, 0)
+            .is_none());
+        assert!(stream
+            .claim("g", "bob", &[id1, id3], 50, &opts, 120)
+            .unwrap()
+            .is_empty());
+
+        let claimed = stream
+            .claim("g", "bob", &[id1, id3], 50, &opts, 150)
+            .unwrap();
+        assert_eq!(claimed, [id1]);
+        assert_eq!(
+            pending(&stream, id1),
+            Some(PendingEntry {
+                consumer: "bob".into(),
+                delivery_time: 150,
+                delivery_count: 2,
+            })
+        );
+
+        let opts = ClaimOptions {
+            delivery_time: Some(10),
+            retry_count: Some(7),
+            force: true,
+            just_id: false,
+        };
+        let claimed = stream
+            .claim("g", "carol", &[id2, id3], 0, &opts, 200)
+            .unwrap();
+        assert_eq!(claimed, [id2, id3]);
+        assert_eq!(pending(&stream, id3).unwrap().delivery_count, 7);
+        assert_eq!(pending(&stream, id2).unwrap().delivery_time, 10);
+
+        let opts = ClaimOptions {
+            just_id: true,
+            ..ClaimOptions::default()
+        };
+        stream.claim("g", "dave", &[id2], 0, &opts, 300).unwrap();
+        assert_eq!(pending(&stream, id2).unwrap().delivery_count, 7);
+
+        stream.trim_by_maxlen(1, false);
+        assert!(stream
+            .claim("g", "dave", &[id1], 0, &opts, 300)
+            .unwrap()
+            .is_empty());
+        assert_eq!(pending(&stream, id1), None);
+    }
+
+    /// Тест проверяет `auto_claim`: курсор, ограничение `count` и
+    /// удалённые сообщения.
+    #[test]
+    fn test_auto_claim() {
+        let mut stream = Stream::new();
+        for ms in 1..=5 {
+            stream
+                .add_with_id(StreamId::new(ms, 0), make_entry("n", ms as i64))
+                .unwrap();
+        }
+        stream.create_group("g", StreamId::MIN);
+        stream.read_group("g", "alice", Some(4), 100).unwrap();
+        stream.read_group("g", "alice", None, 190).unwrap();
+        stream.trim_by_minid(StreamId::new(2, 0), false);
+
+        let result = stream
+            .auto_claim("g", "bob", 50, StreamId::MIN, 2, false, 200)
+            .unwrap();
+        assert_eq!(result.deleted, [StreamId::new(1, 0)]);
+        assert_eq!(result.claimed, [StreamId::new(2, 0), StreamId::new(3, 0)]);
+        assert_eq!(result.next, StreamId::new(4, 0));
+
+        let result = stream
+            .auto_claim("g", "bob", 50, result.next, 2, true, 200)
+            .unwrap();
+        assert_eq!(result.claimed, [StreamId::new(4, 0)]);
+        assert!(result.deleted.is_empty());
+        assert_eq!(result.next, StreamId::MIN);
+
+        let pel = stream.group("g").unwrap().pel();
+        assert_eq!(pel.len(), 4);
+        assert_eq!(pel.get(&StreamId::new(2, 0)).unwrap().delivery_count, 2);
+        assert_eq!(pel.get(&StreamId::new(4, 0)).unwrap().delivery_count, 1);
+        assert_eq!(pel.get(&StreamId::new(5, 0)).unwrap().consumer, "alice");
+        assert!(stream
+            .auto_claim("missing", "bob", 0, StreamId::MIN, 1, false, 0)
+            .is_none());
+    }
+
+    /// Тест проверяет `delete`: надгробия в середине не видны диапазонам и
+    /// не сдвигают записи, ID не уменьшается, PEL очищается, а надгробия
+    /// на концах убираются.
+    #[test]
+    fn test_delete_leaves_tombstones() {
+        let mut stream = Stream::new();
+        let id = |ms| StreamId::new(ms, 0);
+        for ms in 1..=5 {
+            stream
+                .add_with_id(id(ms), make_entry("n", ms as i64))
+                .unwrap();
+        }
+        stream.create_group("g", StreamId::MIN);
+        stream.read_group("g", "c", None, 0).unwrap();
+
+        assert_eq!(stream.delete(&[id(3), id(3), id(9)]), 1);
+        assert_eq!(stream.len(), 4);
+        assert_eq!(stream.entries.len(), 5);
+        assert!(stream.entry(id(3)).is_none());
+        assert!(stream.entry(id(4)).is_some());
+        let ids = |entries: Vec<&StreamEntry>| -> Vec<u64> {
+            entries.iter().map(|e| e.id.ms_time).collect()
+        };
+        assert_eq!(
+            ids(stream.range(Bound::Unbounded, Bound::Unbounded, Some(3))),
+            [1, 2, 4]
+        );
+        assert_eq!(
+            ids(stream.rev_range(Bound::Unbounded, Bound::Unbounded, Some(2))),
+            [5, 4]
+        );
+        assert_eq!(stream.iter().count(), 4);
+        assert!(stream.group("g").unwrap().pel().get(&id(3)).is_none());
+        assert_eq!(stream.group("g").unwrap().pel().len(), 4);
+
+        assert_eq!(stream.delete(&[id(5), id(4)]), 2);
+        assert_eq!(stream.last_entry().unwrap().id, id(2));
+        assert_eq!(stream.entries.len(), 2);
+        assert_eq!(stream.last_id(), id(5));
+        assert!(stream.add_with_id(id(5), make_entry("n", 5)).is_none());
+        assert!(stream.add(make_entry("n", 6)).ms_time > 5);
+    }
+
+    /// Тест проверяет, что обрезка не засчитывает надгробия в `MAXLEN`.
+    #[test]
+    fn test_trim_skips_tombstones() {
+        let mut stream = Stream::new();
+        let id = |ms| StreamId::new(ms, 0);
+        for ms in 1..=6 {
+            stream
+                .add_with_id(id(ms), make_entry("n", ms as i64))
+                .unwrap();
+        }
+        stream.delete(&[id(2), id(3)]);
+
+        assert_eq!(stream.trim_by_maxlen(2, false), 2);
+        assert_eq!(stream.len(), 2);
+        assert_eq!(stream.first_entry().unwrap().id, id(5));
+        assert_eq!(stream.entries.len(), 2);
+        assert_eq!(stream.deleted, 0);
+
+        stream.add_with_id(id(7), make_entry("n", 7)).unwrap();
+        stream.delete(&[id(6)]);
+        assert_eq!(stream.trim_by_minid(id(7), false), 1);
+        assert_eq!(stream.len(), 1);
+        assert_eq!(stream.deleted, 0);
+    }
 }
@@ -0,0 +1,398 @@
+use std::{
+    collections::{btree_map, BTreeMap},
+    ops::Bound,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::StreamId;
+
+/// Запись списка ожидающих подтверждения (PEL): кому и когда доставлено
+/// сообщение.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingEntry {
+    /// Потребитель, владеющий сообщением
+    pub consumer: String,
+    /// Время последней доставки в миллисекундах с эпохи UNIX
+    pub delivery_time: u64,
+    /// Число доставок сообщения
+    pub delivery_count: u64,
+}
+
+/// Список сообщений, доставленных потребителям группы, но ещё не
+/// подтверждённых (`XACK`). Упорядочен по идентификатору сообщения.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingEntryList {
+    entries: BTreeMap<StreamId, PendingEntry>,
+}
+
+/// Потребитель группы.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StreamConsumer {
+    /// Время последнего обращения потребителя в миллисекундах с эпохи UNIX
+    pub seen_time: u64,
+}
+
+/// Группа потребителей потока.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StreamGroup {
+    /// Идентификатор последнего сообщения, выданного группе
+    pub last_delivered_id: StreamId,
+    /// Потребители группы по имени
+    consumers: BTreeMap<String, StreamConsumer>,
+    /// Сообщения, ожидающие подтверждения
+    pel: PendingEntryList,
+}
+
+/// Сводка по группе для `XINFO GROUPS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupInfo {
+    pub name: String,
+    pub consumers: usize,
+    pub pending: usize,
+    pub last_delivered_id: StreamId,
+}
+
+/// Сводка по потребителю для `XINFO CONSUMERS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerInfo {
+    pub name: String,
+    pub pending: usize,
+    /// Миллисекунды с последнего обращения потребителя
+    pub idle: u64,
+}
+
+/// Параметры передачи сообщений другому потребителю (`XCLAIM`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClaimOptions {
+    /// Время доставки, записываемое в PEL (`IDLE`/`TIME`); по умолчанию —
+    /// момент передачи.
+    pub delivery_time: Option<u64>,
+    /// Новый счётчик доставок (`RETRYCOUNT`).
+    pub retry_count: Option<u64>,
+    /// Заводить запись PEL для существующего сообщения, которого в PEL нет
+    /// (`FORCE`).
+    pub force: bool,
+    /// Не увеличивать счётчик доставок (`JUSTID`).
+    pub just_id: bool,
+}
+
+/// Результат `XAUTOCLAIM`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AutoClaim {
+    /// Идентификатор, с которого продолжить просмотр; `0-0` — PEL
+    /// просмотрен до конца
+    pub next: StreamId,
+    /// Переданные сообщения
+    pub claimed: Vec<StreamId>,
+    /// Сообщения, удалённые из потока и поэтому убранные из PEL
+    pub deleted: Vec<StreamId>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl PendingEntryList {
+    /// Создаёт пустой список.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Отмечает доставку сообщения `id` потребителю `consumer` в момент
+    /// `now`: новая запись получает счётчик доставок 1, существующая
+    /// переходит к `consumer` с увеличенным счётчиком.
+    pub fn deliver(
+        &mut self,
+        id: StreamId,
+        consumer: &str,
+        now: u64,
+    ) {
+        let entry = self.entries.entry(id).or_insert_with(|| PendingEntry {
+            consumer: String::new(),
+            delivery_time: now,
+            delivery_count: 0,
+        });
+        consumer.clone_into(&mut entry.consumer);
+        entry.delivery_time = now;
+        entry.delivery_count += 1;
+    }
+
+    /// Возвращает запись сообщения `id`.
+    pub fn get(
+        &self,
+        id: &StreamId,
+    ) -> Option<&PendingEntry> {
+        self.entries.get(id)
+    }
+
+    /// Возвращает изменяемую запись сообщения `id`.
+    pub fn get_mut(
+        &mut self,
+        id: &StreamId,
+    ) -> Option<&mut PendingEntry> {
+        self.entries.get_mut(id)
+    }
+
+    /// Вставляет или заменяет запись сообщения `id`.
+    pub fn insert(
+        &mut self,
+        id: StreamId,
+        entry: PendingEntry,
+    ) {
+        self.entries.insert(id, entry);
+    }
+
+    /// Удаляет запись сообщения `id`.
+    pub fn remove(
+        &mut self,
+        id: &StreamId,
+    ) -> Option<PendingEntry> {
+        self.entries.remove(id)
+    }
+
+    /// Возвращает записи с идентификаторами между `start` и `end`.
+    pub fn range(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+    ) -> btree_map::Range<'_, StreamId, PendingEntry> {
+        if Self::is_empty_range(start, end) {
+            return self.entries.range(StreamId::MIN..StreamId::MIN);
+        }
+        self.entries.range((start, end))
+    }
+
+    /// Итератор по записям в порядке идентификаторов.
+    pub fn iter(&self) -> btree_map::Iter<'_, StreamId, PendingEntry> {
+        self.entries.iter()
+    }
+
+    /// Возвращает число записей потребителя `consumer`.
+    pub fn count_for(
+        &self,
+        consumer: &str,
+    ) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| entry.consumer == consumer)
+            .count()
+    }
+
+    /// Возвращает число записей.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Проверяет, пуст ли список.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Проверяет, что диапазон пуст: `BTreeMap::range` паникует на
+    /// перевёрнутых границах.
+    fn is_empty_range(
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+    ) -> bool {
+        match (start, end) {
+            (Bound::Included(s), Bound::Included(e)) => s > e,
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) => {
+                s >= e
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PendingEntry {
+    /// Передаёт сообщение потребителю `consumer` в момент `now` согласно
+    /// `opts`.
+    pub fn claim(
+        &mut self,
+        consumer: &str,
+        now: u64,
+        opts: &ClaimOptions,
+    ) {
+        consumer.clone_into(&mut self.consumer);
+        self.delivery_time = opts.delivery_time.unwrap_or(now);
+        self.delivery_count = match opts.retry_count {
+            Some(count) => count,
+            None if opts.just_id => self.delivery_count,
+            None => self.delivery_count + 1,
+        };
+    }
+}
+
+impl StreamGroup {
+    /// Создаёт группу, которой выданы сообщения до `last_delivered_id`
+    /// включительно.
+    pub fn new(last_delivered_id: StreamId) -> Self {
+        Self {
+            last_delivered_id,
+            ..Self::default()
+        }
+    }
+
+    /// Возвращает потребителя `name`, создавая его при отсутствии, и
+    /// отмечает обращение в момент `now`.
+    pub fn touch_consumer(
+        &mut self,
+        name: &str,
+        now: u64,
+    ) -> &mut StreamConsumer {
+        let consumer = self.consumers.entry(name.to_string()).or_default();
+        consumer.seen_time = now;
+        consumer
+    }
+
+    /// Возвращает потребителя `name`.
+    pub fn consumer(
+        &self,
+        name: &str,
+    ) -> Option<&StreamConsumer> {
+        self.consumers.get(name)
+    }
+
+    /// Итератор по потребителям в порядке имён.
+    pub fn consumers(&self) -> btree_map::Iter<'_, String, StreamConsumer> {
+        self.consumers.iter()
+    }
+
+    /// Возвращает список ожидающих подтверждения сообщений.
+    pub fn pel(&self) -> &PendingEntryList {
+        &self.pel
+    }
+
+    /// Возвращает изменяемый список ожидающих подтверждения сообщений.
+    pub fn pel_mut(&mut self) -> &mut PendingEntryList {
+        &mut self.pel
+    }
+
+    /// Возвращает сводку по группе `name`.
+    pub fn info(
+        &self,
+        name: &str,
+    ) -> GroupInfo {
+        GroupInfo {
+            name: name.to_string(),
+            consumers: self.consumers.len(),
+            pending: self.pel.len(),
+            last_delivered_id: self.last_delivered_id,
+        }
+    }
+
+    /// Возвращает сводки по потребителям; простой отсчитывается от `now`.
+    pub fn consumers_info(
+        &self,
+        now: u64,
+    ) -> Vec<ConsumerInfo> {
+        self.consumers
+            .iter()
+            .map(|(name, consumer)| ConsumerInfo {
+                name: name.clone(),
+                pending: self.pel.count_for(name),
+                idle: now.saturating_sub(consumer.seen_time),
+            })
+            .collect()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет доставку сообщений: повторная доставка передаёт
+    /// сообщение другому потребителю и увеличивает счётчик.
+    #[test]
+    fn test_pel_deliver_and_count() {
+        let mut pel = PendingEntryList::new();
+        let id = StreamId::new(1, 0);
+
+        pel.deliver(id, "alice", 10);
+        pel.deliver(StreamId::new(2, 0), "alice", 10);
+        assert_eq!(pel.count_for("alice"), 2);
+
+        pel.deliver(id, "bob", 25);
+        assert_eq!(
+            pel.get(&id),
+            Some(&PendingEntry {
+                consumer: "bob".into(),
+                delivery_time: 25,
+                delivery_count: 2,
+            })
+        );
+        assert_eq!(pel.count_for("alice"), 1);
+        assert_eq!(pel.len(), 2);
+
+        assert!(pel.remove(&id).is_some());
+        assert_eq!(pel.count_for("bob"), 0);
+    }
+
+    /// Тест проверяет выборку диапазона, в том числе перевёрнутого.
+    #[test]
+    fn test_pel_range() {
+        let mut pel = PendingEntryList::new();
+        for ms in 1..=4 {
+            pel.deliver(StreamId::new(ms, 0), "c", 0);
+        }
+        let ids =
+            |start, end| -> Vec<u64> { pel.range(start, end).map(|(id, _)| id.ms_time).collect() };
+
+        assert_eq!(
+            ids(
+                Bound::Excluded(StreamId::new(1, 0)),
+                Bound::Included(StreamId::new(3, 0))
+            ),
+            [2, 3]
+        );
+        assert!(ids(
+            Bound::Included(StreamId::new(3, 0)),
+            Bound::Included(StreamId::new(2, 0))
+        )
+        .is_empty());
+        assert!(ids(
+            Bound::Excluded(StreamId::new(2, 0)),
+            Bound::Excluded(StreamId::new(2, 0))
+        )
+        .is_empty());
+    }
+
+    /// Тест проверяет сводки по группе и потребителям.
+    #[test]
+    fn test_group_info() {
+        let mut group = StreamGroup::new(StreamId::new(5, 0));
+        group.touch_consumer("alice", 100);
+        group.touch_consumer("bob", 40);
+        group.pel_mut().deliver(StreamId::new(4, 0), "bob", 40);
+
+        assert_eq!(
+            group.info("g"),
+            GroupInfo {
+                name: "g".into(),
+                consumers: 2,
+                pending: 1,
+                last_delivered_id: StreamId::new(5, 0),
+            }
+        );
+        assert_eq!(
+            group.consumers_info(150),
+            [
+                ConsumerInfo {
+                    name: "alice".into(),
+                    pending: 0,
+                    idle: 50,
+                },
+                ConsumerInfo {
+                    name: "bob".into(),
+                    pending: 1,
+                    idle: 110,
+                },
+            ]
+        );
+    }
+}
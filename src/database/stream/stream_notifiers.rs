@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use tokio::sync::{futures::Notified, Notify};
+
+use crate::Sds;
+
+/// Уведомления о новых записях потоков по ключу.
+///
+/// Хранятся в движке, а не в самом [`Stream`](super::Stream): движки,
+/// сериализующие значения, при каждом чтении восстанавливают поток заново,
+/// и уведомление внутри значения не дошло бы до ожидающих. Запись о ключе
+/// существует, пока его ждёт хотя бы один `XREAD BLOCK`.
+#[derive(Debug, Default)]
+pub struct StreamNotifiers {
+    notifiers: DashMap<Sds, Arc<Notify>>,
+}
+
+/// Подписка на новые записи потока. При удалении последней подписки на
+/// ключ его уведомление убирается из реестра.
+#[derive(Debug)]
+pub struct StreamSubscription<'a> {
+    registry: &'a StreamNotifiers,
+    key: Sds,
+    notify: Arc<Notify>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl StreamNotifiers {
+    /// Создаёт пустой реестр.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Подписывается на новые записи потока `key`.
+    pub fn subscribe(
+        &self,
+        key: &Sds,
+    ) -> StreamSubscription<'_> {
+        let notify = self.notifiers.entry(key.clone()).or_default().clone();
+        StreamSubscription {
+            registry: self,
+            key: key.clone(),
+            notify,
+        }
+    }
+
+    /// Будит всех, кто ждёт поток `key`. `XADD` вызывает его после того,
+    /// как сохранил поток.
+    pub fn notify(
+        &self,
+        key: &Sds,
+    ) {
+        if let Some(notify) = self.notifiers.get(key) {
+            notify.notify_waiters();
+        }
+    }
+
+    /// Возвращает число ключей, которые кто-то ждёт.
+    pub fn len(&self) -> usize {
+        self.notifiers.len()
+    }
+
+    /// Проверяет, что потоки никто не ждёт.
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+}
+
+impl StreamSubscription<'_> {
+    /// Возвращает future, который завершится при следующем
+    /// [`StreamNotifiers::notify`] для ключа подписки.
+    pub fn notified(&self) -> Notified<'_> {
+        self.notify.notified()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для StreamSubscription
+////////////////////////////////////////////////////////////////////////////////
+
+impl Drop for StreamSubscription<'_> {
+    fn drop(&mut self) {
+        // Ссылки держат реестр и эта подписка: других ожидающих нет.
+        self.registry
+            .notifiers
+            .remove_if(&self.key, |_, notify| Arc::strong_count(notify) == 2);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time;
+
+    use super::*;
+
+    /// Тест проверяет, что уведомление ключа живёт, пока есть подписки.
+    #[test]
+    fn test_subscription_lifetime() {
+        let registry = StreamNotifiers::new();
+        let key = Sds::from_str("s");
+
+        let first = registry.subscribe(&key);
+        let second = registry.subscribe(&key);
+        assert_eq!(registry.len(), 1);
+
+        drop(first);
+        assert_eq!(registry.len(), 1);
+        drop(second);
+        assert!(registry.is_empty());
+
+        // Уведомление без подписчиков ничего не заводит.
+        registry.notify(&key);
+        assert!(registry.is_empty());
+    }
+
+    /// Тест проверяет, что `notify` будит подписчиков только своего ключа.
+    #[tokio::test(start_paused = true)]
+    async fn test_notify_wakes_subscribers() {
+        let registry = StreamNotifiers::new();
+        let (a, b) = (Sds::from_str("a"), Sds::from_str("b"));
+        let sub_a = registry.subscribe(&a);
+        let sub_b = registry.subscribe(&b);
+
+        let woken_a = sub_a.notified();
+        let woken_b = sub_b.notified();
+        tokio::pin!(woken_a, woken_b);
+        woken_a.as_mut().enable();
+        woken_b.as_mut().enable();
+
+        registry.notify(&a);
+        woken_a.await;
+        assert!(time::timeout(Duration::from_millis(10), woken_b)
+            .await
+            .is_err());
+    }
+}
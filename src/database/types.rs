@@ -1,9 +1,9 @@
-use std::{collections::HashSet, io::Cursor};
+use std::{collections::HashSet, fmt, io::Cursor, mem::size_of};
 
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 
-use super::{Bitmap, Dict, Hll, QuickList, Sds, SkipList, SmartHash, StreamEntry};
+use super::{Bitmap, Dict, Hll, QuickList, Sds, SkipList, SmartHash, Stream, StreamEntry};
 use crate::{
     engine::{decode, encode},
     StoreError, StoreResult,
@@ -51,16 +51,78 @@ pub enum Value {
     /// уникальных элементов.
     HyperLogLog(Box<Hll>),
     /// Поток записей, каждая из которых идентифицируется ID и набором полей.
-    SStream(Vec<StreamEntry>),
+    SStream(Stream),
     /// Битовый массив для команд SETBIT/GETBIT/BITCOUNT/BITOP
     Bitmap(Bitmap),
 }
 
+/// Тип значения ключа с точки зрения клиента (ответ `TYPE`, фильтр `TYPE` у
+/// `SCAN`).
+///
+/// Скалярные значения, HyperLogLog и битовые массивы хранятся как строки и
+/// сообщаются как `string`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    String,
+    List,
+    Set,
+    ZSet,
+    Hash,
+    Stream,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl ValueType {
+    /// Возвращает имя типа в ответе `TYPE`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::String => "string",
+            ValueType::List => "list",
+            ValueType::Set => "set",
+            ValueType::ZSet => "zset",
+            ValueType::Hash => "hash",
+            ValueType::Stream => "stream",
+        }
+    }
+
+    /// Разбирает имя типа (без учёта регистра), например аргумент
+    /// `SCAN ... TYPE type`.
+    pub fn parse(name: &str) -> Option<Self> {
+        [
+            ValueType::String,
+            ValueType::List,
+            ValueType::Set,
+            ValueType::ZSet,
+            ValueType::Hash,
+            ValueType::Stream,
+        ]
+        .into_iter()
+        .find(|ty| ty.as_str().eq_ignore_ascii_case(name))
+    }
+}
+
 impl Value {
+    /// Возвращает тип значения для `TYPE`; `None` — для `Null`.
+    pub fn value_type(&self) -> Option<ValueType> {
+        match self {
+            Value::Str(_)
+            | Value::Int(_)
+            | Value::Float(_)
+            | Value::Bool(_)
+            | Value::HyperLogLog(_)
+            | Value::Bitmap(_) => Some(ValueType::String),
+            Value::List(_) | Value::Array(_) => Some(ValueType::List),
+            Value::Set(_) => Some(ValueType::Set),
+            Value::ZSet { .. } => Some(ValueType::ZSet),
+            Value::Hash(_) => Some(ValueType::Hash),
+            Value::SStream(_) => Some(ValueType::Stream),
+            Value::Null => None,
+        }
+    }
+
     /// Сериализует значение в бинарный формат через ZDB encode.
     ///
     /// Возвращает вектор байт с закодированным значением.
@@ -110,4 +172,306 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Возвращает имя внутреннего представления значения (для
+    /// `OBJECT ENCODING`).
+    ///
+    /// Строка из одних цифр, помещающаяся в `i64`, сообщается как `"int"`,
+    /// короткая (inline) строка — как `"embstr"`, остальные — как `"raw"`.
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Value::Str(s) => {
+                let bytes = s.as_bytes();
+                let is_int = !bytes.is_empty()
+                    && bytes.iter().all(u8::is_ascii_digit)
+                    && s.as_str().is_ok_and(|s| s.parse::<i64>().is_ok());
+                if is_int {
+                    "int"
+                } else if s.is_inline() {
+                    "embstr"
+                } else {
+                    "raw"
+                }
+            }
+            Value::Int(_) | Value::Bool(_) => "int",
+            Value::Float(_) => "embstr",
+            Value::Null => "none",
+            Value::List(_) => "quicklist",
+            Value::Array(_) => "listpack",
+            Value::Hash(hash) => hash.repr().as_str(),
+            Value::ZSet { .. } => "skiplist",
+            Value::Set(_) => "hashtable",
+            Value::HyperLogLog(hll) => {
+                if hll.is_sparse() {
+                    "sparse"
+                } else {
+                    "dense"
+                }
+            }
+            Value::SStream(_) => "stream",
+            Value::Bitmap(_) => "raw",
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Оценка занимаемой памяти
+////////////////////////////////////////////////////////////////////////////////
+
+impl Value {
+    /// Возвращает точный объём памяти кучи, занятой значением, не включая
+    /// `size_of::<Value>()`.
+    ///
+    /// Для буферов учитывается ёмкость (`capacity()`), а не длина.
+    pub fn heap_size(&self) -> usize {
+        self.estimate_heap_size(0)
+    }
+
+    /// Оценивает объём памяти кучи, занятой значением (`MEMORY USAGE`).
+    ///
+    /// Для коллекций учитываются только первые `samples` элементов, а их
+    /// средний размер экстраполируется на всю коллекцию; `0` означает точный
+    /// подсчёт по всем элементам.
+    pub fn estimate_heap_size(
+        &self,
+        samples: usize,
+    ) -> usize {
+        match self {
+            Value::Str(s) => s.heap_size(),
+            Value::Int(_) | Value::Float(_) | Value::Bool(_) | Value::Null => 0,
+            Value::List(list) => {
+                list.heap_size() + sampled_sum(list.iter(), list.len(), samples, |s| s.heap_size())
+            }
+            Value::Array(items) => {
+                items.capacity() * size_of::<Value>()
+                    + sampled_sum(items.iter(), items.len(), samples, |v| {
+                        v.estimate_heap_size(samples)
+                    })
+            }
+            Value::Hash(hash) => {
+                hash.heap_size()
+                    + sampled_sum(hash.keys(), hash.len(), samples, Sds::heap_size)
+                    + sampled_sum(hash.values(), hash.len(), samples, Sds::heap_size)
+            }
+            Value::ZSet { dict, sorted } => {
+                dict.heap_size()
+                    + sorted.heap_size()
+                    + sampled_sum(dict.iter(), dict.len(), samples, |(m, _)| m.heap_size())
+                    + sampled_sum(sorted.iter(), sorted.len(), samples, |(_, m)| m.heap_size())
+            }
+            Value::Set(set) => {
+                // HashSet хранит дополнительно по управляющему байту на слот.
+                set.capacity() * (size_of::<Sds>() + 1)
+                    + sampled_sum(set.iter(), set.len(), samples, Sds::heap_size)
+            }
+            Value::HyperLogLog(hll) => size_of::<Hll>() + hll.heap_size(),
+            Value::SStream(stream) => {
+                stream.len() * size_of::<StreamEntry>()
+                    + sampled_sum(stream.iter(), stream.len(), samples, |entry| {
+                        entry.data.capacity() * (size_of::<(String, Value)>() + 1)
+                            + entry
+                                .data
+                                .iter()
+                                .map(|(k, v)| k.capacity() + v.estimate_heap_size(samples))
+                                .sum::<usize>()
+                    })
+            }
+            Value::Bitmap(bitmap) => bitmap.bytes.capacity(),
+        }
+    }
+
+    /// Оценивает полный объём памяти, занятой значением: размер самого
+    /// `Value` плюс [`Value::estimate_heap_size`].
+    pub fn memory_usage(
+        &self,
+        samples: usize,
+    ) -> usize {
+        size_of::<Value>() + self.estimate_heap_size(samples)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для ValueType
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for ValueType {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Суммирует `size` по элементам коллекции из `len` элементов.
+///
+/// При `samples > 0` суммируются только первые `samples` элементов, а
+/// результат масштабируется на всю коллекцию.
+fn sampled_sum<I, F>(
+    iter: I,
+    len: usize,
+    samples: usize,
+    size: F,
+) -> usize
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> usize,
+{
+    if samples == 0 || samples >= len {
+        return iter.map(size).sum();
+    }
+    let sampled: usize = iter.take(samples).map(size).sum();
+    sampled.saturating_mul(len) / samples
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heap_str(len: usize) -> Sds {
+        Sds::from_vec(vec![b'x'; len])
+    }
+
+    /// Тест проверяет соответствие вариантов `Value` типам `TYPE` и разбор
+    /// имени типа.
+    #[test]
+    fn test_value_type() {
+        assert_eq!(
+            Value::Str(Sds::from_str("v")).value_type(),
+            Some(ValueType::String)
+        );
+        assert_eq!(Value::Int(1).value_type(), Some(ValueType::String));
+        assert_eq!(Value::Float(1.5).value_type(), Some(ValueType::String));
+        assert_eq!(Value::Bool(true).value_type(), Some(ValueType::String));
+        assert_eq!(
+            Value::HyperLogLog(Box::new(Hll::new())).value_type(),
+            Some(ValueType::String)
+        );
+        assert_eq!(
+            Value::Bitmap(Bitmap::new()).value_type(),
+            Some(ValueType::String)
+        );
+        assert_eq!(
+            Value::List(QuickList::new(8)).value_type(),
+            Some(ValueType::List)
+        );
+        assert_eq!(Value::Array(vec![]).value_type(), Some(ValueType::List));
+        assert_eq!(
+            Value::Set(HashSet::new()).value_type(),
+            Some(ValueType::Set)
+        );
+        assert_eq!(
+            Value::ZSet {
+                dict: Dict::new(),
+                sorted: SkipList::new()
+            }
+            .value_type(),
+            Some(ValueType::ZSet)
+        );
+        assert_eq!(
+            Value::Hash(SmartHash::new()).value_type(),
+            Some(ValueType::Hash)
+        );
+        assert_eq!(
+            Value::SStream(Stream::new()).value_type(),
+            Some(ValueType::Stream)
+        );
+        assert_eq!(Value::Null.value_type(), None);
+
+        assert_eq!(ValueType::parse("ZSET"), Some(ValueType::ZSet));
+        assert_eq!(ValueType::parse("stream"), Some(ValueType::Stream));
+        assert_eq!(ValueType::parse("none"), None);
+        assert_eq!(ValueType::Hash.to_string(), "hash");
+    }
+
+    /// Тест проверяет размеры скалярных значений и строк: inline-строка не
+    /// занимает кучу, heap-строка занимает ровно свою ёмкость.
+    #[test]
+    fn test_heap_size_scalars() {
+        assert_eq!(Value::Int(42).heap_size(), 0);
+        assert_eq!(Value::Null.heap_size(), 0);
+        assert_eq!(Value::Str(Sds::from_str("short")).heap_size(), 0);
+        assert_eq!(Value::Str(heap_str(100)).heap_size(), 100);
+        assert_eq!(
+            Value::Str(heap_str(100)).memory_usage(0),
+            size_of::<Value>() + 100
+        );
+    }
+
+    /// Тест проверяет, что массив учитывает свою ёмкость и рекурсивно —
+    /// вложенные значения.
+    #[test]
+    fn test_heap_size_array() {
+        let mut items = Vec::with_capacity(4);
+        items.push(Value::Str(heap_str(64)));
+        items.push(Value::Array(vec![Value::Str(heap_str(32))]));
+
+        let expected = 4 * size_of::<Value>() + 64 + size_of::<Value>() + 32;
+        assert_eq!(Value::Array(items).heap_size(), expected);
+    }
+
+    /// Тест проверяет размер хеша в компактном представлении: слоты пар плюс
+    /// буферы ключей и значений.
+    #[test]
+    fn test_heap_size_hash() {
+        let mut hash = SmartHash::new();
+        hash.insert(heap_str(40), heap_str(50));
+        hash.insert(Sds::from_str("f"), Sds::from_str("v"));
+
+        let value = Value::Hash(hash);
+        let Value::Hash(hash) = &value else {
+            unreachable!()
+        };
+        assert_eq!(value.heap_size(), hash.heap_size() + 40 + 50);
+        assert!(hash.heap_size() >= 2 * size_of::<(Sds, Sds)>());
+    }
+
+    /// Тест проверяет размер битовой карты и отсортированного множества.
+    #[test]
+    fn test_heap_size_bitmap_and_zset() {
+        let mut bitmap = Bitmap::new();
+        bitmap.bytes = Vec::with_capacity(256);
+        assert_eq!(Value::Bitmap(bitmap).heap_size(), 256);
+
+        let mut dict = Dict::new();
+        let mut sorted = SkipList::new();
+        dict.insert(heap_str(30), 1.0);
+        sorted.insert(OrderedFloat(1.0), heap_str(30));
+
+        let value = Value::ZSet { dict, sorted };
+        let Value::ZSet { dict, sorted } = &value else {
+            unreachable!()
+        };
+        assert_eq!(
+            value.heap_size(),
+            dict.heap_size() + sorted.heap_size() + 60
+        );
+    }
+
+    /// Тест проверяет выборку: оценка экстраполирует средний размер первых
+    /// элементов на всю коллекцию, а `0` даёт точный подсчёт.
+    #[test]
+    fn test_estimate_heap_size_samples() {
+        let mut list = QuickList::new(128);
+        for _ in 0..10 {
+            list.push_back(heap_str(100));
+        }
+        for _ in 0..10 {
+            list.push_back(Sds::from_str("x"));
+        }
+        let structure = list.heap_size();
+        let value = Value::List(list);
+
+        assert_eq!(value.heap_size(), structure + 10 * 100);
+        assert_eq!(value.estimate_heap_size(10), structure + 20 * 100);
+        assert_eq!(value.estimate_heap_size(20), value.heap_size());
+    }
 }
@@ -0,0 +1,5 @@
+pub mod zset_base;
+
+// Publicly re-export all error types and functions from the submodules to
+// simplify access from external code.
+pub use zset_base::*;
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use ordered_float::OrderedFloat;
+
+use crate::{Dict, Sds, SkipList, Value};
+
+/// Пары `(member, score)` упорядоченного множества.
+pub type ZSetEntries = Vec<(Sds, f64)>;
+
+/// Способ объединения score элемента, входящего в несколько множеств
+/// (`AGGREGATE` у `ZUNION`/`ZINTER`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ZAggregate {
+    /// Сумма score (по умолчанию).
+    #[default]
+    Sum,
+    /// Минимальный score.
+    Min,
+    /// Максимальный score.
+    Max,
+}
+
+/// Край упорядоченного множества, с которого извлекаются элементы
+/// (`MIN`/`MAX` у `ZMPOP`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZPopDir {
+    /// Элементы с минимальным score.
+    Min,
+    /// Элементы с максимальным score.
+    Max,
+}
+
+/// Параметры `ZUNION`/`ZINTER`: веса входных множеств (`WEIGHTS`) и способ
+/// агрегации (`AGGREGATE`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZCombineOptions {
+    /// Множитель score для каждого входного множества; `None` — все веса
+    /// равны 1.
+    pub weights: Option<Vec<f64>>,
+    /// Способ объединения score.
+    pub aggregate: ZAggregate,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl ZAggregate {
+    /// Объединяет два score. `inf + -inf` даёт 0, как в Redis.
+    pub fn combine(
+        self,
+        a: f64,
+        b: f64,
+    ) -> f64 {
+        match self {
+            ZAggregate::Sum => nan_to_zero(a + b),
+            ZAggregate::Min => a.min(b),
+            ZAggregate::Max => a.max(b),
+        }
+    }
+}
+
+impl ZCombineOptions {
+    /// Возвращает score элемента `i`-го множества с учётом веса. `0 * inf`
+    /// даёт 0.
+    fn weighted(
+        &self,
+        i: usize,
+        score: f64,
+    ) -> f64 {
+        let weight = self
+            .weights
+            .as_ref()
+            .and_then(|w| w.get(i))
+            .copied()
+            .unwrap_or(1.0);
+        nan_to_zero(score * weight)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Операции над упорядоченными множествами
+////////////////////////////////////////////////////////////////////////////////
+
+/// Объединение упорядоченных множеств; отсутствующий ключ (`None`) считается
+/// пустым множеством.
+///
+/// Результат упорядочен по `(score, member)`.
+pub fn zunion(
+    sets: &[Option<Dict<Sds, f64>>],
+    options: &ZCombineOptions,
+) -> Vec<(Sds, f64)> {
+    let mut acc: HashMap<Sds, f64> = HashMap::new();
+    for (i, dict) in sets.iter().enumerate() {
+        let Some(dict) = dict else {
+            continue;
+        };
+        for (member, score) in dict.iter() {
+            let score = options.weighted(i, *score);
+            acc.entry(member.clone())
+                .and_modify(|cur| *cur = options.aggregate.combine(*cur, score))
+                .or_insert(score);
+        }
+    }
+    sort_entries(acc.into_iter().collect())
+}
+
+/// Пересечение упорядоченных множеств; отсутствующий ключ делает результат
+/// пустым.
+///
+/// Результат упорядочен по `(score, member)`.
+pub fn zinter(
+    sets: &[Option<Dict<Sds, f64>>],
+    options: &ZCombineOptions,
+) -> Vec<(Sds, f64)> {
+    let Some(sets) = sets.iter().map(Option::as_ref).collect::<Option<Vec<_>>>() else {
+        return Vec::new();
+    };
+    let Some((first, rest)) = sets.split_first() else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    'members: for (member, score) in first.iter() {
+        let mut acc = options.weighted(0, *score);
+        for (i, dict) in rest.iter().enumerate() {
+            match dict.get(member) {
+                Some(score) => {
+                    acc = options
+                        .aggregate
+                        .combine(acc, options.weighted(i + 1, *score))
+                }
+                None => continue 'members,
+            }
+        }
+        entries.push((member.clone(), acc));
+    }
+    sort_entries(entries)
+}
+
+/// Разность первого упорядоченного множества и остальных; score берутся из
+/// первого множества.
+///
+/// Результат упорядочен по `(score, member)`.
+pub fn zdiff(sets: &[Option<Dict<Sds, f64>>]) -> Vec<(Sds, f64)> {
+    let Some((Some(first), rest)) = sets.split_first() else {
+        return Vec::new();
+    };
+
+    let entries = first
+        .iter()
+        .filter(|(member, _)| rest.iter().flatten().all(|dict| dict.get(member).is_none()))
+        .map(|(member, score)| (member.clone(), *score))
+        .collect();
+    sort_entries(entries)
+}
+
+/// Строит `Value::ZSet` из пар `(member, score)`.
+pub fn zset_from_entries<I>(entries: I) -> Value
+where
+    I: IntoIterator<Item = (Sds, f64)>,
+{
+    let mut dict = Dict::new();
+    let mut sorted = SkipList::new();
+    for (member, score) in entries {
+        dict.insert(member.clone(), score);
+        sorted.insert(OrderedFloat(score), member);
+    }
+    Value::ZSet { dict, sorted }
+}
+
+/// Удаляет до `count` элементов с минимальным (или максимальным) score и
+/// возвращает их в порядке извлечения.
+///
+/// Если индекс `sorted` содержит все элементы (нет совпадающих score),
+/// элементы снимаются с его головы/хвоста. Иначе порядок берётся из `dict`,
+/// а `sorted` обновляется точечно.
+pub fn zset_pop(
+    dict: &mut Dict<Sds, f64>,
+    sorted: &mut SkipList<OrderedFloat<f64>, Sds>,
+    count: usize,
+    dir: ZPopDir,
+) -> Vec<(Sds, f64)> {
+    let mut popped = Vec::with_capacity(count.min(dict.len()));
+    if sorted.len() == dict.len() {
+        while popped.len() < count {
+            let entry = match dir {
+                ZPopDir::Min => sorted.pop_min(),
+                ZPopDir::Max => sorted.pop_max(),
+            };
+            let Some((score, member)) = entry else {
+                break;
+            };
+            dict.remove(&member);
+            popped.push((member, score.into_inner()));
+        }
+    } else {
+        let mut entries = sort_entries(dict.iter().map(|(m, s)| (m.clone(), *s)).collect());
+        if dir == ZPopDir::Max {
+            entries.reverse();
+        }
+        for (member, score) in entries.into_iter().take(count) {
+            dict.remove(&member);
+            if sorted.search(&OrderedFloat(score)) == Some(&member) {
+                sorted.remove(&OrderedFloat(score));
+            }
+            popped.push((member, score));
+        }
+    }
+    popped
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Упорядочивает пары по `(score, member)`.
+fn sort_entries(mut entries: Vec<(Sds, f64)>) -> Vec<(Sds, f64)> {
+    entries.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+/// Заменяет `NaN` нулём.
+fn nan_to_zero(x: f64) -> f64 {
+    if x.is_nan() {
+        0.0
+    } else {
+        x
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dict(entries: &[(&str, f64)]) -> Option<Dict<Sds, f64>> {
+        let mut dict = Dict::new();
+        for (member, score) in entries {
+            dict.insert(Sds::from_str(member), *score);
+        }
+        Some(dict)
+    }
+
+    fn pairs(entries: &[(&str, f64)]) -> Vec<(Sds, f64)> {
+        entries
+            .iter()
+            .map(|(m, s)| (Sds::from_str(m), *s))
+            .collect()
+    }
+
+    /// Тест проверяет объединение с агрегацией SUM, MIN и MAX.
+    #[test]
+    fn test_zunion_aggregate() {
+        let sets = [
+            dict(&[("a", 1.0), ("b", 2.0)]),
+            dict(&[("b", 5.0), ("c", 3.0)]),
+            None,
+        ];
+
+        let sum = zunion(&sets, &ZCombineOptions::default());
+        assert_eq!(sum, pairs(&[("a", 1.0), ("c", 3.0), ("b", 7.0)]));
+
+        let min = ZCombineOptions {
+            aggregate: ZAggregate::Min,
+            ..Default::default()
+        };
+        assert_eq!(
+            zunion(&sets, &min),
+            pairs(&[("a", 1.0), ("b", 2.0), ("c", 3.0)])
+        );
+
+        let max = ZCombineOptions {
+            aggregate: ZAggregate::Max,
+            ..Default::default()
+        };
+        assert_eq!(
+            zunion(&sets, &max),
+            pairs(&[("a", 1.0), ("c", 3.0), ("b", 5.0)])
+        );
+    }
+
+    /// Тест проверяет нулевые и отрицательные веса, а также то, что
+    /// `0 * inf` даёт 0.
+    #[test]
+    fn test_weights_zero_and_negative() {
+        let sets = [
+            dict(&[("a", 1.0), ("b", f64::INFINITY)]),
+            dict(&[("a", 2.0)]),
+        ];
+
+        let options = ZCombineOptions {
+            weights: Some(vec![0.0, -1.0]),
+            aggregate: ZAggregate::Sum,
+        };
+        assert_eq!(zunion(&sets, &options), pairs(&[("a", -2.0), ("b", 0.0)]));
+        assert_eq!(zinter(&sets, &options), pairs(&[("a", -2.0)]));
+    }
+
+    /// Тест проверяет пересечение и разность, включая отсутствующие ключи.
+    #[test]
+    fn test_zinter_and_zdiff() {
+        let a = dict(&[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+        let b = dict(&[("b", 10.0), ("c", 20.0)]);
+
+        assert_eq!(
+            zinter(&[a.clone(), b.clone()], &ZCombineOptions::default()),
+            pairs(&[("b", 12.0), ("c", 23.0)])
+        );
+        assert!(zinter(&[a.clone(), None], &ZCombineOptions::default()).is_empty());
+
+        assert_eq!(zdiff(&[a.clone(), b, None]), pairs(&[("a", 1.0)]));
+        assert!(zdiff(&[None, a]).is_empty());
+    }
+}
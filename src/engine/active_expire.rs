@@ -0,0 +1,189 @@
+//! Фоновое активное удаление ключей с истёкшим TTL.
+//!
+//! Без него ключ удаляется только при обращении к хранилищу (ленивое
+//! удаление), и ключи, к которым больше не обращаются, занимают память
+//! бесконечно. Задача [`start_cleanup_task`] раз в `interval` запускает
+//! [`InMemoryStore::active_expire_cycle`].
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::InMemoryStore;
+
+/// Период активного удаления по умолчанию (`hz 10` в Redis).
+pub const ACTIVE_EXPIRE_PERIOD: Duration = Duration::from_millis(100);
+
+/// Токен отмены фоновой задачи.
+///
+/// Клоны разделяют состояние: [`CancellationToken::cancel`] на любом из них
+/// будит всех, кто ждёт в [`CancellationToken::cancelled`].
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl CancellationToken {
+    /// Создаёт неотменённый токен.
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        Self { tx: Arc::new(tx) }
+    }
+
+    /// Отменяет токен. Повторная отмена ничего не делает.
+    pub fn cancel(&self) {
+        self.tx.send_replace(true);
+    }
+
+    /// Проверяет, отменён ли токен.
+    pub fn is_cancelled(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Завершается, когда токен отменён (сразу, если уже отменён).
+    pub async fn cancelled(&self) {
+        let mut rx = self.tx.subscribe();
+        // Отправитель живёт в самом токене, поэтому ошибки быть не может.
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внешние функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Запускает задачу активного удаления истёкших ключей `store`.
+///
+/// Каждые `interval` задача выполняет
+/// [`InMemoryStore::active_expire_cycle`]; удалённые ключи учитываются в
+/// `BrokerMetrics::expired_keys`, если у хранилища есть брокер. Задача
+/// завершается после отмены `cancel`.
+pub fn start_cleanup_task(
+    store: Arc<InMemoryStore>,
+    interval: Duration,
+    cancel: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {
+                    let removed = store.active_expire_cycle();
+                    if removed > 0 {
+                        tracing::debug!("Actively expired {} keys", removed);
+                    }
+                }
+            }
+        }
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для CancellationToken
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::{Broker, Sds, Storage, Value};
+
+    fn key(s: &str) -> Sds {
+        Sds::from_str(s)
+    }
+
+    /// Тест проверяет, что задача удаляет истёкшие ключи без обращений к
+    /// хранилищу и учитывает их в метриках брокера.
+    #[tokio::test]
+    async fn test_cleanup_removes_expired_keys() {
+        tokio::time::pause();
+
+        let broker = Arc::new(Broker::new());
+        let store = Arc::new(InMemoryStore::new());
+        store.keyspace_notifier().set_broker(&broker);
+
+        for i in 0..100 {
+            let k = key(&format!("tmp{i}"));
+            store.set(&k, Value::Int(i)).unwrap();
+            store.set_expiry(&k, Some(Duration::from_secs(1))).unwrap();
+        }
+        store.set(&key("keep"), Value::Int(0)).unwrap();
+
+        let cancel = CancellationToken::new();
+        let handle = start_cleanup_task(store.clone(), Duration::from_millis(100), cancel.clone());
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(store.iter().count(), 101);
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        let keys: Vec<Sds> = store.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![key("keep")]);
+        assert_eq!(broker.metrics().expired_keys.load(Ordering::Relaxed), 100);
+
+        cancel.cancel();
+        handle.await.unwrap();
+    }
+
+    /// Тест проверяет, что задача обходит все базы и оставляет ключи с
+    /// непрошедшим TTL.
+    #[tokio::test]
+    async fn test_cleanup_all_databases() {
+        tokio::time::pause();
+
+        let db0 = Arc::new(InMemoryStore::new());
+        let db3 = db0.select(3).unwrap();
+
+        db3.set(&key("short"), Value::Int(1)).unwrap();
+        db3.set_expiry(&key("short"), Some(Duration::from_millis(200)))
+            .unwrap();
+        db3.set(&key("long"), Value::Int(2)).unwrap();
+        db3.set_expiry(&key("long"), Some(Duration::from_secs(60)))
+            .unwrap();
+
+        let cancel = CancellationToken::new();
+        let handle = start_cleanup_task(db0, Duration::from_millis(100), cancel.clone());
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let keys: Vec<Sds> = db3.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![key("long")]);
+
+        cancel.cancel();
+        handle.await.unwrap();
+    }
+
+    /// Тест проверяет, что отмена токена завершает задачу, даже если она
+    /// отменена до первого срабатывания.
+    #[tokio::test]
+    async fn test_cleanup_cancel() {
+        tokio::time::pause();
+
+        let store = Arc::new(InMemoryStore::new());
+        let cancel = CancellationToken::new();
+        assert!(!cancel.is_cancelled());
+
+        cancel.cancel();
+        assert!(cancel.is_cancelled());
+        let handle = start_cleanup_task(store, Duration::from_secs(3600), cancel.clone());
+        handle.await.unwrap();
+
+        // Ожидание на уже отменённом токене завершается сразу
+        cancel.cancelled().await;
+    }
+}
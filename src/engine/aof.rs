@@ -1,7 +1,7 @@
 use std::{
     fs::{File, OpenOptions},
     io::{self, BufWriter, Read, Seek, Write},
-    path::Path,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
         mpsc::{self, Receiver, Sender},
@@ -12,6 +12,7 @@ use std::{
 };
 
 use tempfile::NamedTempFile;
+use tokio::task;
 
 use crate::engine::aof_integrity::{
     AofValidator, IntegrityStats, RepairMode, RepairResult, ValidationResult,
@@ -19,6 +20,8 @@ use crate::engine::aof_integrity::{
 
 /// AOF2 включает checksumming для каждой записи
 const MAGIC: &[u8; 4] = b"AOF2";
+/// AOFT хранит команды в текстовом виде (RESP3-массивы)
+const TEXT_MAGIC: &[u8; 4] = b"AOFT";
 
 /// Коды операций в AOF-логе.
 /// Используются для сериализации и восстановления команд.
@@ -31,6 +34,41 @@ pub enum AofOp {
     Del = 2,
 }
 
+/// Операция, записанная в журнал во время фоновой перезаписи.
+///
+/// Накапливается в памяти, пока [`AofLog::rewrite_async`] пишет снимок, и
+/// дописывается в новый файл перед заменой старого.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AofRecord {
+    /// Установка значения ключа
+    Set { key: Vec<u8>, value: Vec<u8> },
+    /// Удаление ключа
+    Del { key: Vec<u8> },
+}
+
+/// Формат записей AOF-журнала.
+///
+/// Определяется по магическому заголовку файла: `AOF2` или `AOFT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AofFormat {
+    /// Компактный бинарный формат AOF2 с checksum для каждой записи
+    #[default]
+    Binary,
+    /// Текстовый формат: каждая команда — RESP3-массив bulk-строк
+    /// (`*3\r\n$3\r\nSET\r\n…`). Удобен для отладки и ручного replay.
+    Text,
+}
+
+/// Кодировщик команд AOF в текстовый формат RESP3.
+///
+/// Каждая операция записывается как массив bulk-строк, поэтому ключи и
+/// значения могут содержать произвольные байты. Всё после 4-байтового
+/// заголовка `AOFT` — обычный поток команд, который понимают стандартные
+/// инструменты Redis (например, `tail -c +5 appendonly.aof | redis-cli
+/// --pipe`).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AofTextEncoder;
+
 /// Политика синхронизации AOF.
 /// Определяет, когда именно сбрасывать данные из буфера на диск.
 #[derive(Debug, Clone, Copy)]
@@ -84,8 +122,9 @@ pub struct AofMetrics {
 pub struct AofLog {
     /// Буферизованный writer, защищённый мьютексом для потокобезопасности.
     writer: Arc<Mutex<BufWriter<File>>>,
-    /// Reader для воспроизведения операций из начала файла.
-    reader: File,
+    /// Reader для воспроизведения операций из начала файла. Разделяется с
+    /// фоновой перезаписью, которая переоткрывает его после замены файла.
+    reader: Arc<Mutex<File>>,
     /// Выбранная политика синхронизации.
     policy: SyncPolicy,
     /// Политика обработки corruption
@@ -109,6 +148,15 @@ pub struct AofLog {
     metrics_flush_total_ns: AtomicU64,
     metrics_replay_skipped: AtomicUsize,
     metrics_last_integrity_check: AtomicU64,
+    /// Операции, записанные во время фоновой перезаписи. `Some`, пока
+    /// перезапись выполняется.
+    rewrite_buffer: Arc<Mutex<Option<Vec<AofRecord>>>>,
+    /// Текущий размер журнала в байтах (включая ещё не сброшенный буфер).
+    size: Arc<AtomicU64>,
+    /// Размер журнала после открытия или последней перезаписи.
+    rewrite_base_size: Arc<AtomicU64>,
+    /// Формат записей, определённый по заголовку файла.
+    format: AofFormat,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -122,7 +170,8 @@ impl AofLog {
     /// Открывает (или создаёт) AOF-файл по указанному пути.
     ///
     /// Проверяет или записывает магический заголовок и настраивает буферизацию.
-    /// Если политика `EverySec`, запускается фоновый поток сброса.
+    /// Если политика `EverySec`, запускается фоновый поток сброса. Новый файл
+    /// создаётся в формате [`AofFormat::Binary`].
     ///
     /// # Возвращает:
     /// - `Ok(AofLog)` при успешном открытии и инициализации файла
@@ -131,6 +180,24 @@ impl AofLog {
         path: P,
         policy: SyncPolicy,
         corruption_policy: CorruptionPolicy,
+    ) -> io::Result<Self> {
+        Self::open_with_format(path, policy, corruption_policy, AofFormat::Binary)
+    }
+
+    /// Открывает (или создаёт) AOF-файл, используя `format` для нового файла.
+    ///
+    /// Формат существующего файла всегда определяется по его заголовку
+    /// (`AOF2`/`AOF1` — бинарный, `AOFT` — текстовый), аргумент `format` в
+    /// этом случае игнорируется.
+    ///
+    /// # Возвращает:
+    /// - `Ok(AofLog)` при успешном открытии и инициализации файла
+    /// - `Err(io::Error)` при некорректном заголовке или ошибке открытия
+    pub fn open_with_format<P: AsRef<Path>>(
+        path: P,
+        policy: SyncPolicy,
+        corruption_policy: CorruptionPolicy,
+        format: AofFormat,
     ) -> io::Result<Self> {
         // Читаем или создаём файл для проверки заголовка и для replay.
         let mut file = OpenOptions::new()
@@ -139,26 +206,30 @@ impl AofLog {
             .append(true)
             .open(&path)?;
 
-        {
+        let format = {
             let mut header = [0u8; 4];
             let n = file.read(&mut header)?;
             if n == 4 {
-                if &header != MAGIC {
+                if &header == MAGIC {
+                    AofFormat::Binary
+                } else if &header == TEXT_MAGIC {
+                    AofFormat::Text
+                } else if &header == b"AOF1" {
                     // backward compat AOF1
-                    if &header == b"AOF1" {
-                        eprintln!("Warning: Found AOF1 format. Consider upgrading to AOF2 for integrity protection.");
-                    } else {
-                        return Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            format!("Invalid AOF magic header: {header:?}"),
-                        ));
-                    }
+                    eprintln!("Warning: Found AOF1 format. Consider upgrading to AOF2 for integrity protection.");
+                    AofFormat::Binary
+                } else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid AOF magic header: {header:?}"),
+                    ));
                 }
             } else if n == 0 {
                 // New empty file -> write magic
                 file.seek(io::SeekFrom::Start(0))?;
-                file.write_all(MAGIC)?;
+                file.write_all(format.magic())?;
                 file.flush()?;
+                format
             } else {
                 // Partial header -> consider file corrupted
                 return Err(io::Error::new(
@@ -166,9 +237,10 @@ impl AofLog {
                     format!("Partial AOF header ({} bytes): {:?}", n, &header[..n]),
                 ));
             }
-        }
+        };
+        let size = file.metadata()?.len();
         file.seek(io::SeekFrom::Start(0))?;
-        let reader = file;
+        let reader = Arc::new(Mutex::new(file));
 
         let write_file = OpenOptions::new().create(true).append(true).open(&path)?;
         let writer = Arc::new(Mutex::new(BufWriter::new(write_file)));
@@ -197,6 +269,10 @@ impl AofLog {
             metrics_flush_total_ns: AtomicU64::new(0),
             metrics_replay_skipped: AtomicUsize::new(0),
             metrics_last_integrity_check: AtomicU64::new(0),
+            rewrite_buffer: Arc::new(Mutex::new(None)),
+            size: Arc::new(AtomicU64::new(size)),
+            rewrite_base_size: Arc::new(AtomicU64::new(size)),
+            format,
         };
 
         // Если политика EverySec — запускаем фоновый флешер
@@ -233,7 +309,8 @@ impl AofLog {
 
     /// Добавляет в AOF журнал команду `SET` с ключом и значением.
     ///
-    /// Формат записи: `[AofOp::Set][checksum][key_len][key][val_len][val]`.
+    /// Формат записи: `[AofOp::Set][checksum][key_len][key][val_len][val]`
+    /// или RESP3-массив `SET key value` для [`AofFormat::Text`].
     /// В зависимости от политики синхронизации выполняется немедленный или
     /// отложенный flush.
     ///
@@ -250,22 +327,22 @@ impl AofLog {
         self.metrics_ops_set
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
-        // Создаем payload для checksum вычисления
-        let mut payload = Vec::new();
-        payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
-        payload.extend_from_slice(key);
-        payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
-        payload.extend_from_slice(value);
-
-        // Вычисляем checksum
-        let checksum = self.validator.compute_checksum(&payload);
+        let record =
+            Self::encode_record(self.format, &self.validator, AofOp::Set, key, Some(value));
 
         {
             let mut buf = self.writer.lock().unwrap();
-            // Записываем: operation + checksum + payload
-            buf.write_all(&[AofOp::Set as u8])?;
-            Self::write_32(&mut *buf, checksum)?;
-            buf.write_all(&payload)?;
+            buf.write_all(&record)?;
+            self.size.fetch_add(record.len() as u64, Ordering::Relaxed);
+            // Пока идёт фоновая перезапись, операция должна попасть и в новый
+            // файл. Буфер дополняется под блокировкой writer'а, чтобы
+            // перезапись не пропустила запись между снимком и заменой файла.
+            if let Some(records) = self.rewrite_buffer.lock().unwrap().as_mut() {
+                records.push(AofRecord::Set {
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                });
+            }
         }
 
         let now_s = SystemTime::now()
@@ -290,7 +367,8 @@ impl AofLog {
 
     /// Добавляет в AOF журнал команду `DEL` с ключом.
     ///
-    /// Формат записи (AOF2): `[AofOp::Del][checksum][key_len][key]`.
+    /// Формат записи (AOF2): `[AofOp::Del][checksum][key_len][key]`, для
+    /// [`AofFormat::Text`] — RESP3-массив `DEL key`.
     /// В зависимости от политики синхронизации выполняется немедленный или
     /// отложенный flush.
     ///
@@ -306,17 +384,15 @@ impl AofLog {
         self.metrics_ops_del
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
-        let mut payload = Vec::new();
-        payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
-        payload.extend_from_slice(key);
-
-        let checksum = self.validator.compute_checksum(&payload);
+        let record = Self::encode_record(self.format, &self.validator, AofOp::Del, key, None);
 
         {
             let mut buf = self.writer.lock().unwrap();
-            buf.write_all(&[AofOp::Del as u8])?;
-            Self::write_32(&mut *buf, checksum)?;
-            buf.write_all(&payload)?;
+            buf.write_all(&record)?;
+            self.size.fetch_add(record.len() as u64, Ordering::Relaxed);
+            if let Some(records) = self.rewrite_buffer.lock().unwrap().as_mut() {
+                records.push(AofRecord::Del { key: key.to_vec() });
+            }
         }
 
         let now_s = SystemTime::now()
@@ -353,14 +429,16 @@ impl AofLog {
     where
         F: FnMut(AofOp, Vec<u8>, Option<Vec<u8>>),
     {
-        self.reader.seek(io::SeekFrom::Start(0))?;
+        let mut reader = self.reader.lock().unwrap();
+        reader.seek(io::SeekFrom::Start(0))?;
         let mut header = [0u8; 4];
-        self.reader.read_exact(&mut header)?;
+        reader.read_exact(&mut header)?;
 
         let is_aof1 = &header == b"AOF1";
         let is_aof2 = &header == MAGIC;
+        let is_text = &header == TEXT_MAGIC;
 
-        if !is_aof1 && !is_aof2 {
+        if !is_aof1 && !is_aof2 && !is_text {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Bad AOF header: {header:?}"),
@@ -368,10 +446,13 @@ impl AofLog {
         }
 
         let mut buf = Vec::new();
-        self.reader.read_to_end(&mut buf)?;
+        reader.read_to_end(&mut buf)?;
+        drop(reader);
 
         if is_aof1 {
             self.replay_aof1_format(&buf, f)
+        } else if is_text {
+            self.replay_text_format(&buf, f)
         } else {
             self.replay_aof2_format(&buf, f)
         }
@@ -380,7 +461,8 @@ impl AofLog {
     /// Компактирует AOF-журнал, записывая только актуальные ключи.
     ///
     /// Используется временный файл, затем атомарно заменяет оригинальный.
-    /// Всегда применяется формат AOF2 с checksum.
+    /// Текстовый журнал остаётся текстовым, остальные переписываются в AOF2 с
+    /// checksum.
     ///
     /// # Возвращает:
     /// - `Ok(())` при успешной переписи
@@ -395,54 +477,195 @@ impl AofLog {
         P: AsRef<Path>,
     {
         let path = path.as_ref();
+        if self.is_rewriting() {
+            return Err(Self::rewrite_in_progress());
+        }
         // 1. Создаём временный файл, в той же директории.
         let mut tmp = NamedTempFile::new_in(path.parent().unwrap_or_else(|| Path::new(".")))?;
         // 2. Записываем MAGIC
-        tmp.write_all(MAGIC)?;
+        tmp.write_all(self.format.magic())?;
         tmp.flush()?;
         // 3. Записываем только SET-операции для каждого живого key/value.
         for (key, value) in live {
-            // Создаём payload
-            let mut payload = Vec::new();
-            payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
-            payload.extend_from_slice(&key);
-            payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
-            payload.extend_from_slice(&value);
-
-            // Вычисляем checksum
-            let checksum = self.validator.compute_checksum(&payload);
-
-            // Записываем: op + checksum + payload
-            tmp.write_all(&[AofOp::Set as u8])?;
-            Self::write_32(&mut tmp, checksum)?;
-            tmp.write_all(&payload)?;
+            Self::write_record(
+                &mut tmp,
+                self.format,
+                &self.validator,
+                AofOp::Set,
+                &key,
+                Some(&value),
+            )?;
         }
         tmp.flush()?;
 
-        // Перед атомарной заменой - убедимся, что текущий writer зафлашен,
-        // чтобы избежать состояния, когда старый writer держит данные в буфере.
+        // 4. Атомарно заменяем старый файл и переоткрываем writer и reader.
+        let mut guard = self.writer.lock().unwrap();
+        let size = Self::replace_file(tmp, path, &mut guard, &self.reader)?;
+        self.size.store(size, Ordering::Relaxed);
+        self.rewrite_base_size.store(size, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Перезаписывает AOF-журнал в фоне, не блокируя вызывающий поток.
+    ///
+    /// В задаче `spawn_blocking` создаётся временный файл рядом с `path`, в
+    /// него пишется снимок `snapshot_fn()` в формате журнала, затем операции,
+    /// добавленные в журнал за время перезаписи, и файл атомарно заменяет
+    /// старый. Пока задача работает, `append_set`/`append_del` продолжают
+    /// писать в старый файл и одновременно копят записи в памяти, поэтому при
+    /// ошибке перезаписи старый журнал остаётся полным.
+    ///
+    /// `snapshot_fn` вызывается уже после начала накопления, так что
+    /// повторно применённые записи лишь перезаписывают ключи теми же
+    /// значениями. Требуется запущенный Tokio runtime.
+    ///
+    /// # Возвращает:
+    /// - `JoinHandle`, который завершается `Ok(())` после замены файла или
+    ///   `Err(io::Error)`, если перезапись не удалась или уже выполняется
+    pub fn rewrite_async<F, I>(
+        &self,
+        path: PathBuf,
+        snapshot_fn: F,
+    ) -> task::JoinHandle<io::Result<()>>
+    where
+        F: FnOnce() -> I + Send + 'static,
+        I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>,
+    {
         {
-            let mut guard = self.writer.lock().unwrap();
-            let _ = guard.flush();
+            let mut buffer = self.rewrite_buffer.lock().unwrap();
+            if buffer.is_some() {
+                return task::spawn_blocking(|| Err(Self::rewrite_in_progress()));
+            }
+            *buffer = Some(Vec::new());
         }
 
-        // Атомарно заменяем старый файл.
-        tmp.persist(path)?;
+        let writer = Arc::clone(&self.writer);
+        let reader = Arc::clone(&self.reader);
+        let buffer = Arc::clone(&self.rewrite_buffer);
+        let size = Arc::clone(&self.size);
+        let base_size = Arc::clone(&self.rewrite_base_size);
+        let format = self.format;
+
+        task::spawn_blocking(move || {
+            let result: io::Result<()> = (|| {
+                let validator = AofValidator::new();
+                let tmp = NamedTempFile::new_in(path.parent().unwrap_or_else(|| Path::new(".")))?;
+                let mut out = BufWriter::new(tmp);
+                out.write_all(format.magic())?;
+                for (key, value) in snapshot_fn() {
+                    Self::write_record(
+                        &mut out,
+                        format,
+                        &validator,
+                        AofOp::Set,
+                        &key,
+                        Some(&value),
+                    )?;
+                }
 
-        // После замены - нужно обновить writer и reader в AofLog:
-        // Закрываем текущие дескрипторы, открываем новый файл.
-        let mut file = OpenOptions::new()
-            .create(true)
-            .read(true)
-            .append(true)
-            .open(path)?;
-        file.seek(io::SeekFrom::Start(0))?;
-        self.reader = file;
+                // Под блокировкой writer'а новые операции не добавляются, так
+                // что хвост буфера и замена файла видят одно и то же состояние.
+                let mut guard = writer.lock().unwrap();
+                let records = buffer.lock().unwrap().take().unwrap_or_default();
+                for record in &records {
+                    match record {
+                        AofRecord::Set { key, value } => Self::write_record(
+                            &mut out,
+                            format,
+                            &validator,
+                            AofOp::Set,
+                            key,
+                            Some(value),
+                        )?,
+                        AofRecord::Del { key } => {
+                            Self::write_record(&mut out, format, &validator, AofOp::Del, key, None)?
+                        }
+                    }
+                }
+                let tmp = out.into_inner().map_err(|e| e.into_error())?;
 
-        // Обновляем writer внутри Arc<Mutex<...>>
-        let writer_file = OpenOptions::new().create(true).append(true).open(path)?;
-        let mut guard = self.writer.lock().unwrap();
-        *guard = BufWriter::new(writer_file);
+                let new_size = Self::replace_file(tmp, &path, &mut guard, &reader)?;
+                size.store(new_size, Ordering::Relaxed);
+                base_size.store(new_size, Ordering::Relaxed);
+                Ok(())
+            })();
+
+            // При ошибке все операции уже есть в старом файле — просто
+            // прекращаем их накопление.
+            if result.is_err() {
+                buffer.lock().unwrap().take();
+            }
+            result
+        })
+    }
+
+    /// Проверяет, выполняется ли сейчас фоновая перезапись журнала.
+    pub fn is_rewriting(&self) -> bool {
+        self.rewrite_buffer.lock().unwrap().is_some()
+    }
+
+    /// Сбрасывает буфер журнала в файл.
+    pub fn flush(&self) -> io::Result<()> {
+        self.flush_immediate()
+    }
+
+    /// Возвращает текущий размер журнала в байтах, включая данные, ещё не
+    /// сброшенные на диск.
+    pub fn size(&self) -> u64 {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает размер журнала сразу после открытия или последней
+    /// перезаписи.
+    pub fn rewrite_base_size(&self) -> u64 {
+        self.rewrite_base_size.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает формат записей журнала.
+    pub fn format(&self) -> AofFormat {
+        self.format
+    }
+
+    /// Преобразует AOF-журнал `src` в текстовый формат и записывает его в
+    /// `dst`.
+    ///
+    /// Операции переносятся один к одному и в том же порядке, без
+    /// компактирования. `src` может быть в любом поддерживаемом формате;
+    /// повреждённая запись прерывает преобразование. `dst` заменяется
+    /// атомарно.
+    ///
+    /// # Возвращает:
+    /// - `Ok(())` при успешном преобразовании
+    /// - `Err(io::Error)` если `src` не существует, повреждён или `dst` не
+    ///   удалось записать
+    pub fn convert_to_text(
+        src: &Path,
+        dst: &Path,
+    ) -> io::Result<()> {
+        // `open` создаёт отсутствующий файл — здесь это было бы ошибкой.
+        File::open(src)?;
+        let mut log = Self::open(src, SyncPolicy::No, CorruptionPolicy::Strict)?;
+        let mut records = Vec::new();
+        log.replay(|op, key, val| match op {
+            AofOp::Set => records.push(AofRecord::Set {
+                key,
+                value: val.unwrap_or_default(),
+            }),
+            AofOp::Del => records.push(AofRecord::Del { key }),
+        })?;
+        drop(log);
+
+        let tmp = NamedTempFile::new_in(dst.parent().unwrap_or_else(|| Path::new(".")))?;
+        let mut out = BufWriter::new(tmp);
+        out.write_all(TEXT_MAGIC)?;
+        let encoder = AofTextEncoder;
+        for record in &records {
+            out.write_all(&encoder.encode(record))?;
+        }
+        let tmp = out.into_inner().map_err(|e| e.into_error())?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(dst)?;
 
         Ok(())
     }
@@ -569,17 +792,90 @@ impl AofLog {
         }
     }
 
-    /// Записывает `u32` в формате big-endian.
-    ///
-    /// # Возвращает:
-    /// - `Ok(())` при успешной записи
-    /// - `Err(io::Error)` при ошибке записи
-    #[inline]
-    fn write_32<W: Write>(
+    /// Записывает одну запись в формате `format`, см.
+    /// [`AofLog::encode_record`].
+    fn write_record<W: Write>(
         w: &mut W,
-        v: u32,
+        format: AofFormat,
+        validator: &AofValidator,
+        op: AofOp,
+        key: &[u8],
+        value: Option<&[u8]>,
     ) -> io::Result<()> {
-        w.write_all(&v.to_be_bytes())
+        w.write_all(&Self::encode_record(format, validator, op, key, value))
+    }
+
+    /// Кодирует одну запись журнала.
+    ///
+    /// Для AOF2: `[op][checksum][key_len][key]` и, для `SET`,
+    /// `[val_len][val]`. Для текстового формата — RESP3-массив команды.
+    fn encode_record(
+        format: AofFormat,
+        validator: &AofValidator,
+        op: AofOp,
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Vec<u8> {
+        if format == AofFormat::Text {
+            return match op {
+                AofOp::Set => AofTextEncoder.encode_set(key, value.unwrap_or_default()),
+                AofOp::Del => AofTextEncoder.encode_del(key),
+            };
+        }
+
+        let mut payload = Vec::with_capacity(8 + key.len() + value.map_or(0, <[u8]>::len));
+        payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+        payload.extend_from_slice(key);
+        if let Some(value) = value {
+            payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            payload.extend_from_slice(value);
+        }
+        let checksum = validator.compute_checksum(&payload);
+
+        let mut record = Vec::with_capacity(5 + payload.len());
+        record.push(op as u8);
+        record.extend_from_slice(&checksum.to_be_bytes());
+        record.extend_from_slice(&payload);
+        record
+    }
+
+    /// Сбрасывает временный файл на диск, атомарно заменяет им `path` и
+    /// переоткрывает writer и reader на новый файл.
+    ///
+    /// Вызывается под блокировкой writer'а, чтобы в старый файл ничего не
+    /// дописывалось между заменой и переоткрытием.
+    ///
+    /// # Возвращает:
+    /// - `Ok(u64)` с размером нового файла
+    /// - `Err(io::Error)` при ошибке записи, замены или открытия файла
+    fn replace_file(
+        tmp: NamedTempFile,
+        path: &Path,
+        writer: &mut BufWriter<File>,
+        reader: &Mutex<File>,
+    ) -> io::Result<u64> {
+        tmp.as_file().sync_all()?;
+        let size = tmp.as_file().metadata()?.len();
+
+        // Старый writer сбрасываем, чтобы он не держал данные в буфере.
+        let _ = writer.flush();
+        tmp.persist(path)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        file.seek(io::SeekFrom::Start(0))?;
+        *reader.lock().unwrap() = file;
+        *writer = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+
+        Ok(size)
+    }
+
+    /// Ошибка повторного запуска перезаписи.
+    fn rewrite_in_progress() -> io::Error {
+        io::Error::other("AOF rewrite already in progress")
     }
 
     /// Безопасно читает `u32` в формате big-endian из буфера.
@@ -993,6 +1289,50 @@ impl AofLog {
         Ok(())
     }
 
+    /// Воспроизводит AOF-журнал в текстовом формате (RESP3-массивы).
+    ///
+    /// Checksum в этом формате нет: повреждённой считается запись, которую не
+    /// удалось разобрать как команду `SET`/`DEL`.
+    ///
+    /// # Возвращает:
+    /// - `Ok(())` при успешном воспроизведении
+    /// - `Err(io::Error)` при ошибке разбора в режиме `Strict`
+    fn replay_text_format<F>(
+        &mut self,
+        buf: &[u8],
+        mut f: F,
+    ) -> io::Result<()>
+    where
+        F: FnMut(AofOp, Vec<u8>, Option<Vec<u8>>),
+    {
+        let mut pos = 0;
+
+        while pos < buf.len() {
+            match AofTextEncoder::decode(buf, &mut pos) {
+                Ok(AofRecord::Set { key, value }) => f(AofOp::Set, key, Some(value)),
+                Ok(AofRecord::Del { key }) => f(AofOp::Del, key, None),
+                Err(e) => match self.corruption_policy {
+                    CorruptionPolicy::Strict => return Err(e),
+                    CorruptionPolicy::Skip | CorruptionPolicy::Log => {
+                        if matches!(self.corruption_policy, CorruptionPolicy::Log) {
+                            eprintln!("AOF replay warning: {e}, skipping record at position {pos}");
+                        }
+                        self.validator.mark_skipped();
+                        self.metrics_replay_skipped.fetch_add(1, Ordering::Relaxed);
+
+                        // Следующая команда начинается с `*` в начале строки.
+                        match buf[pos + 1..].windows(2).position(|w| w == b"\n*") {
+                            Some(offset) => pos += offset + 2,
+                            None => break,
+                        }
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+
     /// Находит следующую потенциально валидную запись в буфере после ошибки.
     ///
     /// Используется при replay с CorruptionPolicy::Skip/Log для пропуска
@@ -1020,6 +1360,139 @@ impl AofLog {
     }
 }
 
+impl AofFormat {
+    /// Возвращает магический заголовок файла для этого формата.
+    pub fn magic(self) -> &'static [u8; 4] {
+        match self {
+            AofFormat::Binary => MAGIC,
+            AofFormat::Text => TEXT_MAGIC,
+        }
+    }
+}
+
+impl AofTextEncoder {
+    /// Кодирует команду `SET key value` как RESP3-массив.
+    pub fn encode_set(
+        &self,
+        key: &[u8],
+        value: &[u8],
+    ) -> Vec<u8> {
+        Self::encode_command(&[b"SET", key, value])
+    }
+
+    /// Кодирует команду `DEL key` как RESP3-массив.
+    pub fn encode_del(
+        &self,
+        key: &[u8],
+    ) -> Vec<u8> {
+        Self::encode_command(&[b"DEL", key])
+    }
+
+    /// Кодирует операцию журнала как RESP3-массив.
+    pub fn encode(
+        &self,
+        record: &AofRecord,
+    ) -> Vec<u8> {
+        match record {
+            AofRecord::Set { key, value } => self.encode_set(key, value),
+            AofRecord::Del { key } => self.encode_del(key),
+        }
+    }
+
+    /// Разбирает одну команду, начиная с `*pos`.
+    ///
+    /// Имя команды сравнивается без учёта регистра. `*pos` сдвигается только
+    /// при успешном разборе.
+    ///
+    /// # Возвращает:
+    /// - `Ok(AofRecord)` для корректной команды `SET` или `DEL`
+    /// - `Err(io::Error)` с `UnexpectedEof` для усечённой записи и
+    ///   `InvalidData` для некорректной или неизвестной команды
+    pub fn decode(
+        buf: &[u8],
+        pos: &mut usize,
+    ) -> io::Result<AofRecord> {
+        let mut cur = *pos;
+        let count = Self::read_header(buf, &mut cur, b'*')?;
+        let mut parts = Vec::with_capacity(count.min(3));
+        for _ in 0..count {
+            let len = Self::read_header(buf, &mut cur, b'$')?;
+            let end = cur
+                .checked_add(len)
+                .filter(|&end| end + 2 <= buf.len())
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated bulk string")
+                })?;
+            if &buf[end..end + 2] != b"\r\n" {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Bulk string is not terminated by CRLF",
+                ));
+            }
+            parts.push(buf[cur..end].to_vec());
+            cur = end + 2;
+        }
+
+        let record = match parts.as_mut_slice() {
+            [cmd, key, value] if cmd.eq_ignore_ascii_case(b"SET") => AofRecord::Set {
+                key: std::mem::take(key),
+                value: std::mem::take(value),
+            },
+            [cmd, key] if cmd.eq_ignore_ascii_case(b"DEL") => AofRecord::Del {
+                key: std::mem::take(key),
+            },
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unsupported AOF text command",
+                ))
+            }
+        };
+        *pos = cur;
+        Ok(record)
+    }
+
+    /// Кодирует массив bulk-строк: `*<n>\r\n` и `$<len>\r\n<data>\r\n`
+    /// для каждого элемента.
+    fn encode_command(parts: &[&[u8]]) -> Vec<u8> {
+        let size: usize = parts.iter().map(|p| p.len() + 16).sum();
+        let mut out = Vec::with_capacity(size + 16);
+        out.extend_from_slice(format!("*{}\r\n", parts.len()).as_bytes());
+        for part in parts {
+            out.extend_from_slice(format!("${}\r\n", part.len()).as_bytes());
+            out.extend_from_slice(part);
+            out.extend_from_slice(b"\r\n");
+        }
+        out
+    }
+
+    /// Читает строку вида `<prefix><число>\r\n` и возвращает число.
+    fn read_header(
+        buf: &[u8],
+        pos: &mut usize,
+        prefix: u8,
+    ) -> io::Result<usize> {
+        let rest = buf.get(*pos..).unwrap_or_default();
+        let line_end = rest
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated RESP header"))?;
+        let line = &rest[..line_end];
+        if line.first() != Some(&prefix) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Expected '{}' in RESP header", prefix as char),
+            ));
+        }
+        let n = std::str::from_utf8(&line[1..])
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid RESP length"))?;
+        *pos += line_end + 2;
+        Ok(n)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для AofOp, AofLog
 ////////////////////////////////////////////////////////////////////////////////
@@ -1192,6 +1665,70 @@ mod tests {
         Ok(())
     }
 
+    /// Тест проверяет, что `rewrite_async()` сжимает журнал и не теряет
+    /// операции, записанные, пока снимок ещё не готов.
+    #[tokio::test]
+    async fn test_rewrite_async_keeps_concurrent_writes() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("appendonly.aof");
+        let mut log = AofLog::open(&path, SyncPolicy::No, CorruptionPolicy::Log)?;
+        for i in 0..100 {
+            log.append_set(b"k1", format!("v{i}").as_bytes())?;
+        }
+        log.append_set(b"k2", b"v2")?;
+        let size_before = log.size();
+
+        // Снимок ждёт сигнала, чтобы записи пришлись на время перезаписи.
+        let (started_tx, started_rx) = mpsc::channel();
+        let (go_tx, go_rx) = mpsc::channel::<()>();
+        let handle = log.rewrite_async(path.clone(), move || {
+            started_tx.send(()).unwrap();
+            go_rx.recv().unwrap();
+            vec![
+                (b"k1".to_vec(), b"v99".to_vec()),
+                (b"k2".to_vec(), b"v2".to_vec()),
+            ]
+        });
+        started_rx.recv().unwrap();
+        assert!(log.is_rewriting());
+
+        log.append_set(b"k3", b"v3")?;
+        log.append_del(b"k2")?;
+        assert!(log.rewrite_async(path.clone(), Vec::new).await?.is_err());
+
+        go_tx.send(()).unwrap();
+        handle.await??;
+        assert!(!log.is_rewriting());
+        assert!(log.size() < size_before);
+        assert_eq!(log.size(), std::fs::metadata(&path)?.len());
+
+        // Запись после замены идёт уже в новый файл
+        log.append_set(b"k4", b"v4")?;
+        drop(log);
+
+        let mut live = std::collections::HashMap::new();
+        let mut rlog = AofLog::open(&path, SyncPolicy::No, CorruptionPolicy::Strict)?;
+        rlog.replay(|op, key, val| match op {
+            AofOp::Set => {
+                live.insert(key, val.unwrap());
+            }
+            AofOp::Del => {
+                live.remove(&key);
+            }
+        })?;
+
+        let expected: std::collections::HashMap<Vec<u8>, Vec<u8>> = [
+            (b"k1".to_vec(), b"v99".to_vec()),
+            (b"k3".to_vec(), b"v3".to_vec()),
+            (b"k4".to_vec(), b"v4".to_vec()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(live, expected);
+
+        Ok(())
+    }
+
     /// Тест проверяет, что метрики операций и flush корректно считаются при
     /// SyncPolicy::Always.
     #[test]
@@ -1257,4 +1794,110 @@ mod tests {
 
         Ok(())
     }
+
+    /// Тест проверяет, что текстовый журнал пишется RESP3-массивами и
+    /// воспроизводится после повторного открытия.
+    #[test]
+    fn test_text_format_append_replay() -> io::Result<()> {
+        let temp = NamedTempFile::new()?;
+        let path = temp.path();
+        {
+            let mut log = AofLog::open_with_format(
+                path,
+                SyncPolicy::Always,
+                CorruptionPolicy::Strict,
+                AofFormat::Text,
+            )?;
+            log.append_set(b"key", b"a\r\nb")?;
+            log.append_del(b"key")?;
+            log.flush_immediate()?;
+        }
+
+        let raw = std::fs::read(path)?;
+        assert_eq!(
+            raw,
+            b"AOFT*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$4\r\na\r\nb\r\n*2\r\n$3\r\nDEL\r\n$3\r\nkey\r\n"
+        );
+
+        // Формат определяется по заголовку, а не по аргументу
+        let mut log = AofLog::open(path, SyncPolicy::No, CorruptionPolicy::Strict)?;
+        assert_eq!(log.format(), AofFormat::Text);
+        let mut seq = Vec::new();
+        log.replay(|op, key, val| seq.push((op, key, val)))?;
+        assert_eq!(
+            seq,
+            vec![
+                (AofOp::Set, b"key".to_vec(), Some(b"a\r\nb".to_vec())),
+                (AofOp::Del, b"key".to_vec(), None),
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// Тест проверяет, что повреждённая текстовая запись пропускается при
+    /// CorruptionPolicy::Skip и прерывает replay при Strict.
+    #[test]
+    fn test_text_format_corrupted_record() -> io::Result<()> {
+        let temp = NamedTempFile::new()?;
+        let path = temp.path();
+        let encoder = AofTextEncoder;
+        let mut raw = TEXT_MAGIC.to_vec();
+        raw.extend(encoder.encode_set(b"k1", b"v1"));
+        raw.extend_from_slice(b"*2\r\n$4\r\nPING\r\n$1\r\nx\r\n");
+        raw.extend(encoder.encode_set(b"k2", b"v2"));
+        std::fs::write(path, raw)?;
+
+        let mut log = AofLog::open(path, SyncPolicy::No, CorruptionPolicy::Skip)?;
+        let mut keys = Vec::new();
+        log.replay(|_, key, _| keys.push(key))?;
+        assert_eq!(keys, vec![b"k1".to_vec(), b"k2".to_vec()]);
+        assert_eq!(log.metrics().replay_skipped, 1);
+
+        let mut log = AofLog::open(path, SyncPolicy::No, CorruptionPolicy::Strict)?;
+        assert!(log.replay(|_, _, _| {}).is_err());
+
+        Ok(())
+    }
+
+    /// Тест проверяет, что `convert_to_text()` переносит все операции AOF2 в
+    /// текстовый журнал, а `rewrite()` сохраняет текстовый формат.
+    #[test]
+    fn test_convert_to_text() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let src = dir.path().join("binary.aof");
+        let dst = dir.path().join("text.aof");
+        {
+            let mut log = AofLog::open(&src, SyncPolicy::Always, CorruptionPolicy::Strict)?;
+            log.append_set(b"k1", b"v1")?;
+            log.append_set(b"k2", b"v2")?;
+            log.append_del(b"k1")?;
+            log.flush_immediate()?;
+        }
+
+        AofLog::convert_to_text(&src, &dst)?;
+        assert!(std::fs::read(&dst)?.starts_with(TEXT_MAGIC));
+
+        let mut log = AofLog::open(&dst, SyncPolicy::No, CorruptionPolicy::Strict)?;
+        let mut seq = Vec::new();
+        log.replay(|op, key, _| seq.push((op, key)))?;
+        assert_eq!(
+            seq,
+            vec![
+                (AofOp::Set, b"k1".to_vec()),
+                (AofOp::Set, b"k2".to_vec()),
+                (AofOp::Del, b"k1".to_vec()),
+            ]
+        );
+
+        log.rewrite(&dst, vec![(b"k2".to_vec(), b"v2".to_vec())])?;
+        assert_eq!(
+            std::fs::read(&dst)?,
+            b"AOFT*3\r\n$3\r\nSET\r\n$2\r\nk2\r\n$2\r\nv2\r\n"
+        );
+
+        assert!(AofLog::convert_to_text(&dir.path().join("missing.aof"), &dst).is_err());
+
+        Ok(())
+    }
 }
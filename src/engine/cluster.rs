@@ -1,13 +1,32 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex, RwLock},
+    fs,
+    future::Future,
+    io,
+    ops::Deref,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, RwLockReadGuard,
+    },
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+use uuid::Uuid;
+
 use crate::{
-    engine::slot_manager::{ShardId, SlotManager},
-    GeoPoint, Sds, Storage, StoreError, StoreResult, Value,
+    engine::{
+        metrics::HealthStatus,
+        ready_storage_methods,
+        replication::{ConsistencyLevel, ReplicaSet, ReplicationOp, FAILOVER_CATCHUP_TIMEOUT},
+        slot_manager::{
+            ConsistentHashRing, ShardId, SlotId, SlotManager, DEFAULT_VIRTUAL_NODES, TOTAL_SLOTS,
+        },
+    },
+    EvictionPolicy, FlushMode, GeoAddOptions, GeoPoint, GeoSearchParams, GetExExpiry, ListDir, Sds,
+    Storage, StoreError, StoreResult, StreamNotifiers, Value, ValueType, ZCombineOptions, ZPopDir,
+    ZSetEntries,
 };
 
 /// `InClusterStore` — распределённое key-value хранилище,
@@ -15,7 +34,20 @@ use crate::{
 /// `SlotManager`.
 pub struct InClusterStore {
     /// Список всех shard'ов (каждый реализует `Storage`).
-    shards: Vec<Arc<dyn Storage>>,
+    shards: RwLock<Vec<Arc<dyn Storage>>>,
+    /// Кольцо consistent hashing, по которому ключи распределяются между
+    /// shard'ами.
+    ring: RwLock<ConsistentHashRing>,
+    /// Состояния миграций слотов.
+    migrations: Arc<SlotMigrations>,
+    /// Журналы изменений и реплики чтения, по одному набору на shard.
+    replicas: RwLock<Vec<Arc<ReplicaSet>>>,
+    /// Уровень согласованности чтений.
+    consistency: RwLock<ConsistencyLevel>,
+    /// Идентификатор этого узла (`CLUSTER MYID`).
+    node_id: String,
+    /// Эпоха конфигурации: растёт при каждом изменении топологии.
+    current_epoch: AtomicU64,
     /// Менеджер распределения ключей по shard'ам.
     slot_manager: Arc<SlotManager>,
     /// Хэндл фонового треда-ребалансера.
@@ -24,6 +56,8 @@ pub struct InClusterStore {
     shutdown_flag: Arc<Mutex<bool>>,
     /// Примитивные метрики операций по кластеру.
     operation_metrics: Arc<RwLock<OperationMetrics>>,
+    /// Ожидающие `XREAD BLOCK` по ключам всех shard'ов.
+    stream_notifiers: StreamNotifiers,
 }
 
 /// Метрики операций в кластере.
@@ -45,6 +79,121 @@ pub struct OperationMetrics {
     pub last_reset: Instant,
 }
 
+/// Ход ребалансировки, передаваемый в callback
+/// [`InClusterStore::rebalance`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebalanceProgress {
+    /// Всего ключей на shard'ах до ребалансировки.
+    pub total_keys: usize,
+    /// Проверено ключей.
+    pub scanned_keys: usize,
+    /// Перенесено ключей на другой shard.
+    pub moved_keys: usize,
+}
+
+/// Состояние миграции слота.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MigrationState {
+    /// Ключи слота распределяются кольцом consistent hashing.
+    #[default]
+    Idle,
+    /// Ключи слота переносятся на shard `to`. Источник — shard `from` или,
+    /// если `from` равен `None`, shard'ы, которым ключи назначает кольцо.
+    ///
+    /// Ключ, ещё лежащий на источнике, обслуживается источником; остальные
+    /// ключи слота, включая новые, — shard'ом `to`.
+    Migrating { from: Option<ShardId>, to: ShardId },
+    /// Слот закреплён за shard'ом `to`.
+    Complete { to: ShardId },
+}
+
+/// Хэндл фоновой миграции слота, созданный
+/// [`InClusterStore::migrate_slot`].
+///
+/// Отменённая миграция оставляет слот в состоянии
+/// [`MigrationState::Migrating`]: ключи остаются доступны, а повторный
+/// `migrate_slot` продолжает перенос с места остановки.
+#[derive(Debug)]
+pub struct MigrationHandle {
+    slot: SlotId,
+    cancel: Arc<AtomicBool>,
+    moved: Arc<AtomicUsize>,
+    worker: thread::JoinHandle<StoreResult<MigrationState>>,
+}
+
+/// Общие для кластера и фоновых миграций состояния слотов.
+#[derive(Debug, Default)]
+struct SlotMigrations {
+    /// Слоты, маршрутизация которых отличается от кольца.
+    states: RwLock<HashMap<SlotId, MigrationState>>,
+    /// Флаги отмены активных миграций.
+    cancel_flags: Mutex<HashMap<SlotId, Arc<AtomicBool>>>,
+    /// Операции над ключом держат блокировку на чтение, перенос ключа — на
+    /// запись, поэтому ключ не переносится посреди операции.
+    key_lock: RwLock<()>,
+}
+
+/// Фоновая задача переноса ключей слота.
+struct SlotMigrationWorker {
+    slot: SlotId,
+    from: Option<ShardId>,
+    to: ShardId,
+    /// Shard'ы-источники вместе с их журналами изменений.
+    sources: Vec<(Arc<dyn Storage>, Arc<ReplicaSet>)>,
+    target: Arc<dyn Storage>,
+    target_replicas: Arc<ReplicaSet>,
+    slot_manager: Arc<SlotManager>,
+    migrations: Arc<SlotMigrations>,
+    operation_metrics: Arc<RwLock<OperationMetrics>>,
+    cancel: Arc<AtomicBool>,
+    moved: Arc<AtomicUsize>,
+}
+
+/// Shard ключа вместе с блокировкой, не дающей перенести ключ до конца
+/// операции.
+struct RoutedShard<'a> {
+    id: ShardId,
+    shard: Arc<dyn Storage>,
+    _guard: RwLockReadGuard<'a, ()>,
+}
+
+/// Сводка состояния кластера для `CLUSTER INFO` и `CLUSTER NODES`.
+#[derive(Debug, Clone)]
+pub struct ClusterInfo {
+    /// Идентификатор этого узла.
+    pub node_id: String,
+    /// Эпоха конфигурации.
+    pub current_epoch: u64,
+    /// Количество слотов, обслуживаемых кластером.
+    pub slots_assigned: usize,
+    /// Shard'ы кластера.
+    pub shards: Vec<ShardInfo>,
+}
+
+/// Состояние shard'а кластера.
+#[derive(Debug, Clone)]
+pub struct ShardInfo {
+    /// Номер shard'а.
+    pub id: ShardId,
+    /// Primary shard'а.
+    pub primary: NodeInfo,
+    /// Реплики чтения.
+    pub replicas: Vec<NodeInfo>,
+    /// Слоты, закреплённые за shard'ом миграцией.
+    pub pinned_slots: Vec<SlotId>,
+}
+
+/// Состояние узла (primary или реплики) shard'а.
+#[derive(Debug, Clone)]
+pub struct NodeInfo {
+    /// Идентификатор узла.
+    pub node_id: String,
+    /// Результат проверки доступности узла.
+    pub health: HealthStatus,
+    /// Количество ключей на узле.
+    pub keys: usize,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
@@ -52,31 +201,31 @@ pub struct OperationMetrics {
 impl InClusterStore {
     /// Создаёт новый кластер из переданного списка shard'ов.
     ///
+    /// Ключи распределяются кольцом consistent hashing с
+    /// [`DEFAULT_VIRTUAL_NODES`] виртуальными узлами на shard.
+    ///
     /// # Возвращает:
     /// - `Cluster` - новый объект кластера с настроенной балансировкой и
     ///   метриками операций
     pub fn new(shards: Vec<Arc<dyn Storage>>) -> Self {
-        let shard_count = shards.len();
-        let slot_manager = Arc::new(SlotManager::new(shard_count));
-        let shutdown_flag = Arc::new(Mutex::new(false));
-
-        let rebalancer_handle = Self::start_rebalancer(slot_manager.clone(), shutdown_flag.clone());
+        Self::with_virtual_nodes(shards, DEFAULT_VIRTUAL_NODES)
+    }
 
-        Self {
-            shards,
-            slot_manager,
-            rebalancer_handle: Some(rebalancer_handle),
-            shutdown_flag,
-            operation_metrics: Arc::new(RwLock::new(OperationMetrics {
-                last_reset: Instant::now(),
-                ..Default::default()
-            })),
-        }
+    /// Создаёт кластер с `virtual_nodes` виртуальными узлами на shard в
+    /// кольце consistent hashing.
+    ///
+    /// Больше узлов — равномернее распределение ключей ценой памяти кольца.
+    pub fn with_virtual_nodes(
+        shards: Vec<Arc<dyn Storage>>,
+        virtual_nodes: usize,
+    ) -> Self {
+        let slot_manager = Arc::new(SlotManager::new(shards.len()));
+        Self::build(shards, slot_manager, virtual_nodes)
     }
 
     /// Создаёт кластер с кастомным `SlotManager`.
     ///
-    /// Полезно для тестов или при явном контроле распределения ключей.
+    /// Полезно для тестов или при явном контроле метрик и миграций слотов.
     ///
     /// # Возвращает:
     /// - `Cluster` - новый объект кластера с указанным `SlotManager`,
@@ -84,12 +233,31 @@ impl InClusterStore {
     pub fn new_with_slot_manager(
         shards: Vec<Arc<dyn Storage>>,
         slot_manager: Arc<SlotManager>,
+    ) -> Self {
+        Self::build(shards, slot_manager, DEFAULT_VIRTUAL_NODES)
+    }
+
+    fn build(
+        shards: Vec<Arc<dyn Storage>>,
+        slot_manager: Arc<SlotManager>,
+        virtual_nodes: usize,
     ) -> Self {
         let shutdown_flag = Arc::new(Mutex::new(false));
         let rebalancer_handle = Self::start_rebalancer(slot_manager.clone(), shutdown_flag.clone());
+        let ring = ConsistentHashRing::new(shards.len(), virtual_nodes);
+        let replicas = shards
+            .iter()
+            .map(|shard| Arc::new(ReplicaSet::new(new_node_id(), shard.clone())))
+            .collect();
 
         Self {
-            shards,
+            shards: RwLock::new(shards),
+            ring: RwLock::new(ring),
+            migrations: Arc::new(SlotMigrations::default()),
+            replicas: RwLock::new(replicas),
+            consistency: RwLock::new(ConsistencyLevel::default()),
+            node_id: new_node_id(),
+            current_epoch: AtomicU64::new(0),
             slot_manager,
             rebalancer_handle: Some(rebalancer_handle),
             shutdown_flag,
@@ -97,7 +265,453 @@ impl InClusterStore {
                 last_reset: Instant::now(),
                 ..Default::default()
             })),
+            stream_notifiers: StreamNotifiers::new(),
+        }
+    }
+
+    /// Задаёт идентификатор узла вместо случайного.
+    ///
+    /// Идентификатор должен переживать перезапуск, см.
+    /// [`load_or_create_node_id`].
+    pub fn with_node_id(
+        mut self,
+        node_id: impl Into<String>,
+    ) -> Self {
+        self.node_id = node_id.into();
+        self
+    }
+
+    /// Возвращает идентификатор узла.
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Возвращает реестр ожидающих новых записей потоков.
+    pub fn stream_notifiers(&self) -> &StreamNotifiers {
+        &self.stream_notifiers
+    }
+
+    /// Собирает состояние кластера, проверяя доступность каждого узла.
+    pub fn cluster_info(&self) -> ClusterInfo {
+        let mut pinned: HashMap<ShardId, Vec<SlotId>> = HashMap::new();
+        for (&slot, state) in self.migrations.states.read().unwrap().iter() {
+            if let MigrationState::Complete { to } = state {
+                pinned.entry(*to).or_default().push(slot);
+            }
+        }
+
+        let shards = self
+            .replicas
+            .read()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(id, set)| {
+                let mut pinned_slots = pinned.remove(&id).unwrap_or_default();
+                pinned_slots.sort_unstable();
+                ShardInfo {
+                    id,
+                    primary: NodeInfo::probe(set.node_id(), set.primary().as_ref()),
+                    replicas: set
+                        .replicas()
+                        .into_iter()
+                        .map(|(node_id, store)| NodeInfo::probe(node_id, store.as_ref()))
+                        .collect(),
+                    pinned_slots,
+                }
+            })
+            .collect();
+
+        ClusterInfo {
+            node_id: self.node_id.clone(),
+            current_epoch: self.current_epoch.load(Ordering::Relaxed),
+            slots_assigned: TOTAL_SLOTS as usize,
+            shards,
+        }
+    }
+
+    /// Переключает shard `shard` на его первую реплику.
+    ///
+    /// Записи в кластер на время переключения останавливаются; реплика перед
+    /// переключением догоняет журнал изменений. Доступный бывший primary
+    /// становится репликой нового.
+    ///
+    /// # Возвращает:
+    /// - `Err(StoreError::WrongShard)` - если shard'а нет
+    /// - `Err(StoreError::InvalidOperation)` - если у shard'а нет реплик или
+    ///   идёт миграция слота
+    pub fn failover(
+        &self,
+        shard: ShardId,
+    ) -> StoreResult<()> {
+        let set = self.replica_set(shard)?;
+        let _guard = self.migrations.key_lock.write().unwrap();
+        if self
+            .migrations
+            .states
+            .read()
+            .unwrap()
+            .values()
+            .any(|state| matches!(state, MigrationState::Migrating { .. }))
+        {
+            return Err(StoreError::InvalidOperation(
+                "cannot fail over while a slot migration is in progress".to_string(),
+            ));
+        }
+
+        let Some((old_id, old_primary)) = set.promote(FAILOVER_CATCHUP_TIMEOUT) else {
+            return Err(StoreError::InvalidOperation(format!(
+                "shard {shard} has no replicas"
+            )));
+        };
+        self.shards.write().unwrap()[shard] = set.primary();
+        self.current_epoch.fetch_add(1, Ordering::Relaxed);
+
+        if old_primary.dbsize().is_ok() {
+            set.add_replica(old_id, old_primary)?;
+        }
+        Ok(())
+    }
+
+    /// Подключает к shard'у `shard` реплику чтения.
+    ///
+    /// Реплика получает копию текущего содержимого shard'а и дальше следует
+    /// за его журналом изменений. Запись всегда идёт в primary; чтения
+    /// обслуживаются репликами при [`ConsistencyLevel::Eventual`].
+    ///
+    /// # Возвращает:
+    /// - `Err(StoreError::WrongShard)` - если shard'а `shard` нет
+    pub fn add_replica(
+        &self,
+        shard: ShardId,
+        replica: Arc<dyn Storage>,
+    ) -> StoreResult<()> {
+        self.replica_set(shard)?.add_replica(new_node_id(), replica)
+    }
+
+    /// Возвращает количество реплик shard'а.
+    pub fn replica_count(
+        &self,
+        shard: ShardId,
+    ) -> usize {
+        self.replicas
+            .read()
+            .unwrap()
+            .get(shard)
+            .map_or(0, |replicas| replicas.len())
+    }
+
+    /// Возвращает уровень согласованности чтений.
+    pub fn consistency_level(&self) -> ConsistencyLevel {
+        *self.consistency.read().unwrap()
+    }
+
+    /// Устанавливает уровень согласованности чтений.
+    pub fn set_consistency_level(
+        &self,
+        level: ConsistencyLevel,
+    ) {
+        *self.consistency.write().unwrap() = level;
+    }
+
+    /// Заменяет набор shard'ов на `new_shards` и переносит ключи, у которых
+    /// в новом кольце сменился владелец.
+    ///
+    /// Shard с номером `i` сохраняет свои токены, поэтому при добавлении
+    /// shard'а переезжает ~1/N ключей, а при удалении — только ключи
+    /// удалённых shard'ов. Ключи переносятся по одному вместе с TTL. На время
+    /// ребалансировки остальные операции кластера ждут её окончания.
+    /// `progress` вызывается после каждого проверенного ключа.
+    ///
+    /// Слоты, закреплённые за shard'ом миграцией, остаются за ним, если он
+    /// есть в `new_shards`; иначе их ключи распределяются кольцом.
+    ///
+    /// # Возвращает:
+    /// - `Ok(RebalanceProgress)` - итог ребалансировки
+    /// - `Err(StoreError::InvalidArgument)` - если `new_shards` пуст
+    /// - `Err(StoreError::InvalidOperation)` - если идёт миграция слота
+    pub fn rebalance(
+        &self,
+        new_shards: Vec<Arc<dyn Storage>>,
+        mut progress: impl FnMut(RebalanceProgress),
+    ) -> StoreResult<RebalanceProgress> {
+        if new_shards.is_empty() {
+            return Err(StoreError::InvalidArgument(
+                "cluster requires at least one shard".to_string(),
+            ));
+        }
+
+        let _guard = self.migrations.key_lock.write().unwrap();
+        let mut ring = self.ring.write().unwrap();
+        let mut states = self.migrations.states.write().unwrap();
+        if states
+            .values()
+            .any(|state| matches!(state, MigrationState::Migrating { .. }))
+        {
+            return Err(StoreError::InvalidOperation(
+                "cannot rebalance while a slot migration is in progress".to_string(),
+            ));
+        }
+        states.retain(
+            |_, state| matches!(state, MigrationState::Complete { to } if *to < new_shards.len()),
+        );
+
+        let new_ring = ConsistentHashRing::new(new_shards.len(), ring.virtual_nodes_per_shard());
+        let old_shards = self.all_shards();
+
+        let mut stats = RebalanceProgress::default();
+        let mut shard_keys = Vec::with_capacity(old_shards.len());
+        for shard in &old_shards {
+            let keys = shard.keys("*")?;
+            stats.total_keys += keys.len();
+            shard_keys.push(keys);
+        }
+
+        // Shard, оставшийся в кластере, сохраняет свои реплики.
+        let mut replicas = self.replicas.write().unwrap();
+        let new_replicas: Vec<Arc<ReplicaSet>> = new_shards
+            .iter()
+            .map(|shard| {
+                old_shards
+                    .iter()
+                    .position(|old| Arc::ptr_eq(old, shard))
+                    .map_or_else(
+                        || Arc::new(ReplicaSet::new(new_node_id(), shard.clone())),
+                        |i| replicas[i].clone(),
+                    )
+            })
+            .collect();
+
+        for ((shard, keys), shard_replicas) in old_shards.iter().zip(shard_keys).zip(&*replicas) {
+            for key in keys {
+                let ks = Self::sds_to_str(&key);
+                let target_id = match states.get(&self.slot_manager.calculate_slot(&ks)) {
+                    Some(MigrationState::Complete { to }) => *to,
+                    _ => new_ring.get_shard(&ks),
+                };
+                let target = &new_shards[target_id];
+                if !Arc::ptr_eq(shard, target)
+                    && Self::move_key(shard.as_ref(), target.as_ref(), &key)?
+                {
+                    shard_replicas.publish_keys(&[&key])?;
+                    new_replicas[target_id].publish_keys(&[&key])?;
+                    stats.moved_keys += 1;
+                }
+                stats.scanned_keys += 1;
+                progress(stats);
+            }
+        }
+
+        *self.shards.write().unwrap() = new_shards;
+        *replicas = new_replicas;
+        self.current_epoch.fetch_add(1, Ordering::Relaxed);
+        *ring = new_ring;
+        Ok(stats)
+    }
+
+    /// Переносит ключ из `src` в `dst` вместе с TTL.
+    ///
+    /// # Возвращает:
+    /// - `Ok(false)` - если ключа в `src` уже нет
+    fn move_key(
+        src: &dyn Storage,
+        dst: &dyn Storage,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        let Some(value) = src.get(key)? else {
+            return Ok(false);
+        };
+        let expires_at = src.expiretime(key)?.filter(|&at| at >= 0);
+
+        dst.set(key, value)?;
+        if let Some(at) = expires_at {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as i64;
+            let ttl = Duration::from_millis(at.saturating_sub(now).max(0) as u64);
+            dst.set_expiry(key, Some(ttl))?;
+        }
+        src.del(key)?;
+        Ok(true)
+    }
+
+    /// Возвращает состояние миграции слота.
+    pub fn slot_state(
+        &self,
+        slot: SlotId,
+    ) -> MigrationState {
+        self.migrations
+            .states
+            .read()
+            .unwrap()
+            .get(&slot)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Запускает фоновый перенос ключей слота `slot` на shard `target`.
+    ///
+    /// Ключи переносятся по одному: каждый перенос атомарен относительно
+    /// операций над этим ключом. Пока идёт миграция, ключ читается и пишется
+    /// там, где он сейчас лежит; новые ключи слота создаются на `target`.
+    /// После переноса всех ключей слот закрепляется за `target`
+    /// ([`MigrationState::Complete`]).
+    ///
+    /// Если слот уже мигрирует на `target`, перенос продолжается с места
+    /// остановки.
+    ///
+    /// # Возвращает:
+    /// - `Ok(MigrationHandle)` - хэндл фоновой миграции
+    /// - `Err(StoreError::WrongShard)` - если `target` не существует
+    /// - `Err(StoreError::InvalidOperation)` - если слот мигрирует на другой
+    ///   shard
+    pub fn migrate_slot(
+        &self,
+        slot: SlotId,
+        target: ShardId,
+    ) -> StoreResult<MigrationHandle> {
+        let from = match self.slot_state(slot) {
+            MigrationState::Idle => None,
+            MigrationState::Migrating { from, to } if to == target => from,
+            MigrationState::Migrating { to, .. } => {
+                return Err(StoreError::InvalidOperation(format!(
+                    "slot {slot} is already migrating to shard {to}"
+                )))
+            }
+            MigrationState::Complete { to } => Some(to),
+        };
+        self.start_migration(slot, from, target)
+    }
+
+    /// Запускает перенос ключей слота `slot` с shard'а `source` на shard,
+    /// за которым слот закреплён (`CLUSTER SETSLOT IMPORTING`).
+    ///
+    /// # Возвращает:
+    /// - `Err(StoreError::InvalidOperation)` - если слот не закреплён за
+    ///   shard'ом или уже мигрирует с другого источника
+    pub fn import_slot(
+        &self,
+        slot: SlotId,
+        source: ShardId,
+    ) -> StoreResult<MigrationHandle> {
+        let target = match self.slot_state(slot) {
+            MigrationState::Migrating { from, to } if from == Some(source) => to,
+            MigrationState::Complete { to } => to,
+            _ => {
+                return Err(StoreError::InvalidOperation(format!(
+                    "slot {slot} is not assigned to a single shard"
+                )))
+            }
+        };
+        self.start_migration(slot, Some(source), target)
+    }
+
+    /// Закрепляет слот за shard'ом `target`, синхронно перенося на него все
+    /// ключи слота (`CLUSTER SETSLOT NODE`).
+    pub fn set_slot_node(
+        &self,
+        slot: SlotId,
+        target: ShardId,
+    ) -> StoreResult<()> {
+        if self.slot_state(slot) == (MigrationState::Complete { to: target }) {
+            return Ok(());
+        }
+        match self.migrate_slot(slot, target)?.wait()? {
+            MigrationState::Complete { .. } => Ok(()),
+            _ => Err(StoreError::InvalidOperation(format!(
+                "migration of slot {slot} was cancelled"
+            ))),
+        }
+    }
+
+    /// Останавливает фоновую миграцию слота (`CLUSTER SETSLOT STABLE`).
+    ///
+    /// Слот остаётся в состоянии [`MigrationState::Migrating`], поэтому все
+    /// его ключи доступны, а миграцию можно продолжить.
+    ///
+    /// # Возвращает:
+    /// - `true` - если у слота была активная миграция
+    pub fn cancel_slot_migration(
+        &self,
+        slot: SlotId,
+    ) -> bool {
+        match self.migrations.cancel_flags.lock().unwrap().remove(&slot) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn start_migration(
+        &self,
+        slot: SlotId,
+        from: Option<ShardId>,
+        to: ShardId,
+    ) -> StoreResult<MigrationHandle> {
+        let shards = self.all_shards();
+        let target = shards.get(to).cloned().ok_or(StoreError::WrongShard)?;
+        match from {
+            Some(from) if from >= shards.len() => return Err(StoreError::WrongShard),
+            Some(from) if from == to => {
+                return Err(StoreError::InvalidArgument(format!(
+                    "slot {slot} already belongs to shard {to}"
+                )))
+            }
+            _ => {}
+        }
+        let replicas = self.replicas.read().unwrap().clone();
+        let sources = shards
+            .into_iter()
+            .zip(replicas.iter().cloned())
+            .enumerate()
+            .filter(|&(id, _)| id != to && from.is_none_or(|from| from == id))
+            .map(|(_, source)| source)
+            .collect();
+
+        {
+            let _guard = self.migrations.key_lock.write().unwrap();
+            self.migrations
+                .states
+                .write()
+                .unwrap()
+                .insert(slot, MigrationState::Migrating { from, to });
         }
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let previous = self
+            .migrations
+            .cancel_flags
+            .lock()
+            .unwrap()
+            .insert(slot, cancel.clone());
+        if let Some(previous) = previous {
+            previous.store(true, Ordering::Relaxed);
+        }
+
+        let moved = Arc::new(AtomicUsize::new(0));
+        let worker = SlotMigrationWorker {
+            slot,
+            from,
+            to,
+            sources,
+            target,
+            target_replicas: replicas.get(to).cloned().ok_or(StoreError::WrongShard)?,
+            slot_manager: self.slot_manager.clone(),
+            migrations: self.migrations.clone(),
+            operation_metrics: self.operation_metrics.clone(),
+            cancel: cancel.clone(),
+            moved: moved.clone(),
+        };
+
+        Ok(MigrationHandle {
+            slot,
+            cancel,
+            moved,
+            worker: thread::spawn(move || worker.run()),
+        })
     }
 
     /// Запускает фоновый поток, который периодически проверяет необходимость
@@ -150,6 +764,19 @@ impl InClusterStore {
         })
     }
 
+    /// Возвращает набор реплик shard'а.
+    fn replica_set(
+        &self,
+        shard_id: ShardId,
+    ) -> StoreResult<Arc<ReplicaSet>> {
+        self.replicas
+            .read()
+            .unwrap()
+            .get(shard_id)
+            .cloned()
+            .ok_or(StoreError::WrongShard)
+    }
+
     /// Получает shard по его идентификатору.
     ///
     /// # Возвращает:
@@ -160,7 +787,7 @@ impl InClusterStore {
         &self,
         shard_id: ShardId,
     ) -> Result<Arc<dyn Storage>, StoreError> {
-        match self.shards.get(shard_id).cloned() {
+        match self.shards.read().unwrap().get(shard_id).cloned() {
             Some(s) => Ok(s),
             None => {
                 self.record_failed_operation();
@@ -169,103 +796,529 @@ impl InClusterStore {
         }
     }
 
-    /// Преобразует `Sds` в строку `&str` с потерей некорректных UTF-8 байт.
-    ///
-    /// # Возвращает:
-    /// - `Cow<str>` - строковое представление данных; некорректные UTF-8 байты
-    ///   заменяются символом `�`
-    fn sds_to_str<'a>(s: &'a Sds) -> std::borrow::Cow<'a, str> {
-        std::string::String::from_utf8_lossy(s.as_bytes())
-    }
-
-    /// Регистрирует обычную операцию в кластере.
-    ///
-    /// Увеличивает общий счётчик операций и передаёт информацию в
-    /// `SlotManager`.
-    fn record_operation(
+    /// Возвращает shard, обслуживающий ключ, с учётом миграций слотов.
+    fn key_shard(
         &self,
         key: &Sds,
-    ) {
+    ) -> ShardId {
         let ks = Self::sds_to_str(key);
-        self.slot_manager.record_operation(ks.as_ref());
-        let mut m = self.operation_metrics.write().unwrap();
-        m.total_operations += 1;
+        match self.slot_state(self.slot_manager.calculate_slot(&ks)) {
+            MigrationState::Idle => self.ring.read().unwrap().get_shard(&ks),
+            MigrationState::Migrating { from, to } => {
+                let source = self.migration_source(key, from);
+                if source != to && self.holds_key(source, key) {
+                    source
+                } else {
+                    to
+                }
+            }
+            MigrationState::Complete { to } => to,
+        }
     }
 
-    /// Регистрирует cross-shard операцию.
+    /// Берёт блокировку переноса ключей на чтение для операции над
+    /// несколькими ключами, которые должны оказаться на одном shard'е.
     ///
-    /// Увеличивает счётчик операций, затрагивающих несколько shard'ов.
-    fn record_cross_shard_operation(&self) {
-        let mut m = self.operation_metrics.write().unwrap();
-        m.cross_shard_operations += 1;
+    /// Ключи мигрирующего слота, ещё лежащие на источнике, сначала
+    /// переносятся на целевой shard под блокировкой на запись. После этого
+    /// все ключи слота, включая отсутствующие, обслуживает целевой shard, и
+    /// `RENAME` или `*STORE` внутри слота не получают `WrongShard` посреди
+    /// миграции.
+    fn lock_keys(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<RwLockReadGuard<'_, ()>> {
+        loop {
+            let guard = self.migrations.key_lock.read().unwrap();
+            if self.stray_keys(keys).is_empty() {
+                return Ok(guard);
+            }
+            drop(guard);
+
+            let _guard = self.migrations.key_lock.write().unwrap();
+            let shards = self.all_shards();
+            for (key, source, to) in self.stray_keys(keys) {
+                let (Some(src), Some(dst)) = (shards.get(source), shards.get(to)) else {
+                    return Err(StoreError::WrongShard);
+                };
+                if Self::move_key(src.as_ref(), dst.as_ref(), key)? {
+                    self.publish(source, &[key])?;
+                    self.publish(to, &[key])?;
+                }
+            }
+        }
     }
 
-    /// Регистрирует неудачную операцию.
-    ///
-    /// Увеличивает счётчик неудачных операций в метриках.
-    fn record_failed_operation(&self) {
-        let mut m = self.operation_metrics.write().unwrap();
-        m.failed_operations += 1;
+    /// Возвращает ключи мигрирующих слотов, ещё лежащие на источнике, вместе
+    /// с источником и целевым shard'ом.
+    fn stray_keys<'k>(
+        &self,
+        keys: &[&'k Sds],
+    ) -> Vec<(&'k Sds, ShardId, ShardId)> {
+        keys.iter()
+            .filter_map(|&key| {
+                let slot = self.slot_manager.calculate_slot(&Self::sds_to_str(key));
+                let MigrationState::Migrating { from, to } = self.slot_state(slot) else {
+                    return None;
+                };
+                let source = self.migration_source(key, from);
+                (source != to && self.holds_key(source, key)).then_some((key, source, to))
+            })
+            .collect()
     }
 
-    /// Возвращает доступ к `SlotManager`.
-    ///
-    /// Полезно для тестов, мониторинга или анализа распределения слотов.
-    ///
-    /// # Возвращает:
-    /// - `&Arc<SlotManager>` - ссылка на текущий `SlotManager` кластера
-    pub fn get_slot_manager(&self) -> &Arc<SlotManager> {
-        &self.slot_manager
+    /// Возвращает shard-источник ключа мигрирующего слота.
+    fn migration_source(
+        &self,
+        key: &Sds,
+        from: Option<ShardId>,
+    ) -> ShardId {
+        from.unwrap_or_else(|| self.ring.read().unwrap().get_shard(&Self::sds_to_str(key)))
     }
-}
 
-impl Storage for InClusterStore {
-    fn set(
+    /// Проверяет, лежит ли ключ на shard'е `shard_id`.
+    fn holds_key(
         &self,
+        shard_id: ShardId,
         key: &Sds,
-        value: Value,
-    ) -> StoreResult<()> {
-        self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.set(key, value)
+    ) -> bool {
+        self.shards
+            .read()
+            .unwrap()
+            .get(shard_id)
+            .is_some_and(|shard| matches!(shard.type_of(key), Ok(Some(_))))
     }
 
-    fn get(
+    /// Возвращает shard ключа, удерживая блокировку переноса ключей до конца
+    /// операции.
+    fn route(
         &self,
         key: &Sds,
-    ) -> StoreResult<Option<Value>> {
-        self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.get(key)
+    ) -> StoreResult<RoutedShard<'_>> {
+        let guard = self.migrations.key_lock.read().unwrap();
+        let id = self.key_shard(key);
+        Ok(RoutedShard {
+            id,
+            shard: self.shard_by_id(id)?,
+            _guard: guard,
+        })
     }
 
-    fn del(
+    /// Возвращает хранилище для чтения ключа: при
+    /// [`ConsistencyLevel::Eventual`] — очередную реплику его shard'а, иначе
+    /// primary.
+    fn route_read(
         &self,
         key: &Sds,
-    ) -> StoreResult<bool> {
-        self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.del(key)
+    ) -> StoreResult<RoutedShard<'_>> {
+        let mut routed = self.route(key)?;
+        if let Some(replica) = self.read_replica(routed.id) {
+            routed.shard = replica;
+        }
+        Ok(routed)
     }
 
-    fn mset(
+    /// Возвращает хранилище для чтения с shard'а `shard_id`.
+    fn read_shard(
         &self,
-        entries: Vec<(&Sds, Value)>,
-    ) -> StoreResult<()> {
-        if entries.is_empty() {
+        shard_id: ShardId,
+    ) -> StoreResult<Arc<dyn Storage>> {
+        match self.read_replica(shard_id) {
+            Some(replica) => Ok(replica),
+            None => self.shard_by_id(shard_id),
+        }
+    }
+
+    /// Возвращает очередную реплику shard'а, если чтения разрешено
+    /// обслуживать репликами.
+    fn read_replica(
+        &self,
+        shard_id: ShardId,
+    ) -> Option<Arc<dyn Storage>> {
+        if self.consistency_level() != ConsistencyLevel::Eventual {
+            return None;
+        }
+        self.replicas.read().unwrap().get(shard_id)?.next_replica()
+    }
+
+    /// Публикует в журнал shard'а новое состояние ключей после записи.
+    fn replicate(
+        &self,
+        shard: &RoutedShard<'_>,
+        keys: &[&Sds],
+    ) -> StoreResult<()> {
+        self.publish(shard.id, keys)
+    }
+
+    /// Публикует в журнал shard'а `shard_id` состояние ключей на primary.
+    fn publish(
+        &self,
+        shard_id: ShardId,
+        keys: &[&Sds],
+    ) -> StoreResult<()> {
+        let Some(replicas) = self.replicas.read().unwrap().get(shard_id).cloned() else {
             return Ok(());
+        };
+        replicas.publish_keys(keys)
+    }
+
+    /// Публикует операцию в журналы всех shard'ов.
+    fn publish_all(
+        &self,
+        op: ReplicationOp,
+    ) {
+        for replicas in self.replicas.read().unwrap().iter() {
+            replicas.publish(op.clone());
+        }
+    }
+
+    /// Возвращает снимок текущего списка shard'ов.
+    fn all_shards(&self) -> Vec<Arc<dyn Storage>> {
+        self.shards.read().unwrap().clone()
+    }
+
+    /// Преобразует `Sds` в строку `&str` с потерей некорректных UTF-8 байт.
+    ///
+    /// # Возвращает:
+    /// - `Cow<str>` - строковое представление данных; некорректные UTF-8 байты
+    ///   заменяются символом `�`
+    fn sds_to_str<'a>(s: &'a Sds) -> std::borrow::Cow<'a, str> {
+        std::string::String::from_utf8_lossy(s.as_bytes())
+    }
+
+    /// Регистрирует обычную операцию в кластере.
+    ///
+    /// Увеличивает общий счётчик операций и передаёт информацию в
+    /// `SlotManager`.
+    fn record_operation(
+        &self,
+        key: &Sds,
+    ) {
+        let ks = Self::sds_to_str(key);
+        self.slot_manager.record_operation(ks.as_ref());
+        let mut m = self.operation_metrics.write().unwrap();
+        m.total_operations += 1;
+    }
+
+    /// Регистрирует операции над `keys` и возвращает shard, которому
+    /// принадлежат все ключи, удерживая блокировку переноса ключей до конца
+    /// операции.
+    ///
+    /// # Возвращает:
+    /// - `Err(StoreError::WrongShard)` - если ключи относятся к разным shard'ам
+    fn route_keys(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<RoutedShard<'_>> {
+        let guard = self.lock_keys(keys)?;
+        let mut shard_id = None;
+        for key in keys {
+            self.record_operation(key);
+            let id = self.key_shard(key);
+            if *shard_id.get_or_insert(id) != id {
+                return Err(StoreError::WrongShard);
+            }
+        }
+        let id = shard_id.unwrap_or_default();
+        Ok(RoutedShard {
+            id,
+            shard: self.shard_by_id(id)?,
+            _guard: guard,
+        })
+    }
+
+    /// Как [`Self::route_keys`], но для чтения: при
+    /// [`ConsistencyLevel::Eventual`] возвращает реплику shard'а.
+    fn route_keys_read(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<RoutedShard<'_>> {
+        let mut routed = self.route_keys(keys)?;
+        if let Some(replica) = self.read_replica(routed.id) {
+            routed.shard = replica;
         }
+        Ok(routed)
+    }
+
+    /// Регистрирует cross-shard операцию.
+    ///
+    /// Увеличивает счётчик операций, затрагивающих несколько shard'ов.
+    fn record_cross_shard_operation(&self) {
+        let mut m = self.operation_metrics.write().unwrap();
+        m.cross_shard_operations += 1;
+    }
 
+    /// Регистрирует неудачную операцию.
+    ///
+    /// Увеличивает счётчик неудачных операций в метриках.
+    fn record_failed_operation(&self) {
+        let mut m = self.operation_metrics.write().unwrap();
+        m.failed_operations += 1;
+    }
+
+    /// Возвращает доступ к `SlotManager`.
+    ///
+    /// Полезно для тестов, мониторинга или анализа распределения слотов.
+    ///
+    /// # Возвращает:
+    /// - `&Arc<SlotManager>` - ссылка на текущий `SlotManager` кластера
+    pub fn get_slot_manager(&self) -> &Arc<SlotManager> {
+        &self.slot_manager
+    }
+}
+
+impl MigrationHandle {
+    /// Возвращает номер мигрирующего слота.
+    pub fn slot(&self) -> SlotId {
+        self.slot
+    }
+
+    /// Возвращает количество уже перенесённых ключей.
+    pub fn moved_keys(&self) -> usize {
+        self.moved.load(Ordering::Relaxed)
+    }
+
+    /// Просит фоновую задачу остановиться после текущего ключа.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Проверяет, завершилась ли фоновая задача.
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Ждёт окончания фоновой задачи.
+    ///
+    /// # Возвращает:
+    /// - `Ok(MigrationState::Complete)` - все ключи перенесены
+    /// - `Ok(MigrationState::Migrating)` - миграция отменена
+    pub fn wait(self) -> StoreResult<MigrationState> {
+        self.worker
+            .join()
+            .map_err(|_| StoreError::Internal("slot migration thread panicked".to_string()))?
+    }
+}
+
+impl SlotMigrationWorker {
+    fn run(self) -> StoreResult<MigrationState> {
+        let migrating = MigrationState::Migrating {
+            from: self.from,
+            to: self.to,
+        };
+
+        for (source, source_replicas) in &self.sources {
+            for key in source.keys("*")? {
+                let ks = InClusterStore::sds_to_str(&key);
+                if self.slot_manager.calculate_slot(&ks) != self.slot {
+                    continue;
+                }
+                if self.cancel.load(Ordering::Relaxed) {
+                    return Ok(migrating);
+                }
+                let _guard = self.migrations.key_lock.write().unwrap();
+                if self.cancel.load(Ordering::Relaxed) {
+                    return Ok(migrating);
+                }
+                if InClusterStore::move_key(source.as_ref(), self.target.as_ref(), &key)? {
+                    source_replicas.publish_keys(&[&key])?;
+                    self.target_replicas.publish_keys(&[&key])?;
+                    self.moved.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        let _guard = self.migrations.key_lock.write().unwrap();
+        if self.cancel.load(Ordering::Relaxed) {
+            return Ok(migrating);
+        }
+        let complete = MigrationState::Complete { to: self.to };
+        self.migrations
+            .states
+            .write()
+            .unwrap()
+            .insert(self.slot, complete);
+        self.migrations
+            .cancel_flags
+            .lock()
+            .unwrap()
+            .remove(&self.slot);
+        self.operation_metrics.write().unwrap().migration_operations += 1;
+        Ok(complete)
+    }
+}
+
+impl ClusterInfo {
+    /// Возвращает итоговое состояние кластера: `Critical`, если недоступен
+    /// хотя бы один primary, `Warning`, если недоступна реплика.
+    pub fn state(&self) -> HealthStatus {
+        let healthy = |node: &NodeInfo| node.health == HealthStatus::Healthy;
+        if !self.shards.iter().all(|shard| healthy(&shard.primary)) {
+            HealthStatus::Critical
+        } else if !self
+            .shards
+            .iter()
+            .all(|shard| shard.replicas.iter().all(healthy))
+        {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Healthy
+        }
+    }
+
+    /// Возвращает количество известных узлов: primary и реплик.
+    pub fn known_nodes(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| 1 + shard.replicas.len())
+            .sum()
+    }
+
+    /// Формирует ответ `CLUSTER INFO`.
+    pub fn to_info_string(&self) -> String {
+        let ok = self.state() != HealthStatus::Critical;
+        let mut info = String::new();
+        info.push_str("cluster_enabled:1\r\n");
+        info.push_str(&format!(
+            "cluster_state:{}\r\n",
+            if ok { "ok" } else { "fail" }
+        ));
+        info.push_str(&format!(
+            "cluster_slots_assigned:{}\r\n",
+            self.slots_assigned
+        ));
+        info.push_str(&format!(
+            "cluster_slots_ok:{}\r\n",
+            if ok { self.slots_assigned } else { 0 }
+        ));
+        info.push_str(&format!("cluster_known_nodes:{}\r\n", self.known_nodes()));
+        info.push_str(&format!("cluster_size:{}\r\n", self.shards.len()));
+        info.push_str(&format!("cluster_current_epoch:{}\r\n", self.current_epoch));
+        info.push_str(&format!("cluster_my_id:{}\r\n", self.node_id));
+        info
+    }
+
+    /// Формирует ответ `CLUSTER NODES`: по строке на узел в формате
+    /// `<id> <addr> <flags> <primary> <ping> <pong> <epoch> <link> [slots]`.
+    pub fn to_nodes_table(&self) -> String {
+        let mut table = String::new();
+        for shard in &self.shards {
+            let slots: Vec<String> = shard.pinned_slots.iter().map(u16::to_string).collect();
+            table.push_str(&shard.primary.node_line("master", "-", self.current_epoch));
+            if !slots.is_empty() {
+                table.push(' ');
+                table.push_str(&slots.join(" "));
+            }
+            table.push('\n');
+            for replica in &shard.replicas {
+                table.push_str(&replica.node_line(
+                    "slave",
+                    &shard.primary.node_id,
+                    self.current_epoch,
+                ));
+                table.push('\n');
+            }
+        }
+        table
+    }
+}
+
+impl NodeInfo {
+    /// Проверяет доступность узла запросом количества ключей.
+    fn probe(
+        node_id: String,
+        store: &dyn Storage,
+    ) -> Self {
+        let (health, keys) = match store.dbsize() {
+            Ok(keys) => (HealthStatus::Healthy, keys),
+            Err(_) => (HealthStatus::Critical, 0),
+        };
+        Self {
+            node_id,
+            health,
+            keys,
+        }
+    }
+
+    fn node_line(
+        &self,
+        role: &str,
+        primary: &str,
+        epoch: u64,
+    ) -> String {
+        let (flags, link) = match self.health {
+            HealthStatus::Healthy => (role.to_string(), "connected"),
+            _ => (format!("{role},fail"), "disconnected"),
+        };
+        format!("{} :0@0 {flags} {primary} 0 0 {epoch} {link}", self.node_id)
+    }
+}
+
+impl Deref for RoutedShard<'_> {
+    type Target = dyn Storage;
+
+    fn deref(&self) -> &Self::Target {
+        self.shard.as_ref()
+    }
+}
+
+impl Storage for InClusterStore {
+    fn set(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<()> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        shard.set(key, value)?;
+        self.replicate(&shard, &[key])
+    }
+
+    fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.set_get(key, value)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn get(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Value>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.get(key)
+    }
+
+    fn del(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.del(key)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn mset(
+        &self,
+        entries: Vec<(&Sds, Value)>,
+    ) -> StoreResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let _guard = self.migrations.key_lock.read().unwrap();
         let mut groups: HashMap<ShardId, Vec<(&Sds, Value)>> = HashMap::new();
         for (k, v) in entries {
             let ks = Self::sds_to_str(k);
-            let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
+            let shard_id = self.key_shard(k);
             groups.entry(shard_id).or_default().push((k, v));
             self.slot_manager.record_operation(ks.as_ref());
         }
@@ -274,7 +1327,9 @@ impl Storage for InClusterStore {
 
         for (shard_id, vec) in groups.into_iter() {
             let shard = self.shard_by_id(shard_id)?;
+            let keys: Vec<&Sds> = vec.iter().map(|(k, _)| *k).collect();
             shard.mset(vec)?;
+            self.publish(shard_id, &keys)?;
         }
 
         if groups_count > 1 {
@@ -298,10 +1353,11 @@ impl Storage for InClusterStore {
             return Ok(vec![]);
         }
 
+        let _guard = self.migrations.key_lock.read().unwrap();
         let mut groups: HashMap<ShardId, Vec<(usize, &Sds)>> = HashMap::new();
         for (i, &k) in keys.iter().enumerate() {
             let ks = Self::sds_to_str(k);
-            let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
+            let shard_id = self.key_shard(k);
             groups.entry(shard_id).or_default().push((i, k));
             self.slot_manager.record_operation(ks.as_ref());
         }
@@ -309,7 +1365,7 @@ impl Storage for InClusterStore {
         let mut results: Vec<Option<Value>> = vec![None; keys.len()];
 
         for (shard_id, list) in groups.iter() {
-            let shard = self.shard_by_id(*shard_id)?;
+            let shard = self.read_shard(*shard_id)?;
             let shard_keys: Vec<&Sds> = list.iter().map(|(_, k)| *k).collect();
             let shard_results = shard.mget(&shard_keys)?;
             for ((idx, _), res) in list.iter().zip(shard_results.into_iter()) {
@@ -326,216 +1382,634 @@ impl Storage for InClusterStore {
                 metrics.cross_shard_operations
             );
         }
-
-        Ok(results)
+
+        Ok(results)
+    }
+
+    fn rename(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) -> StoreResult<()> {
+        let shard = self.route_keys(&[from, to])?;
+        shard.shard.rename(from, to)?;
+        self.replicate(&shard, &[from, to])
+    }
+
+    fn renamenx(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) -> StoreResult<bool> {
+        let shard = self.route_keys(&[from, to])?;
+        let renamed = shard.shard.renamenx(from, to)?;
+        self.replicate(&shard, &[from, to])?;
+        Ok(renamed)
+    }
+
+    fn flushdb(&self) -> StoreResult<()> {
+        for shard in self.all_shards() {
+            shard.flushdb()?;
+        }
+        self.publish_all(ReplicationOp::FlushDb);
+        self.slot_manager.reset_metrics();
+        Ok(())
+    }
+
+    fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        for shard in self.all_shards() {
+            shard.flushdb_mode(mode)?;
+        }
+        self.publish_all(ReplicationOp::FlushDb);
+        self.slot_manager.reset_metrics();
+        Ok(())
+    }
+
+    fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        for shard in self.all_shards() {
+            shard.flushall(mode)?;
+        }
+        self.publish_all(ReplicationOp::FlushAll);
+        self.slot_manager.reset_metrics();
+        Ok(())
+    }
+
+    fn geo_add(
+        &self,
+        key: &Sds,
+        lon: f64,
+        lat: f64,
+        member: &Sds,
+        opts: GeoAddOptions,
+    ) -> StoreResult<bool> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.geo_add(key, lon, lat, member, opts)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn geo_dist(
+        &self,
+        key: &Sds,
+        member1: &Sds,
+        member2: &Sds,
+        unit: &str,
+    ) -> StoreResult<Option<f64>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.geo_dist(key, member1, member2, unit)
+    }
+
+    fn geo_pos(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<Option<GeoPoint>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.geo_pos(key, member)
+    }
+
+    fn geo_radius(
+        &self,
+        key: &Sds,
+        lon: f64,
+        lat: f64,
+        radius: f64,
+        unit: &str,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.geo_radius(key, lon, lat, radius, unit)
+    }
+
+    fn geo_radius_by_member(
+        &self,
+        key: &Sds,
+        member: &Sds,
+        radius: f64,
+        unit: &str,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.geo_radius_by_member(key, member, radius, unit)
+    }
+
+    fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.geo_search(key, params)
+    }
+
+    fn sadd(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.sadd(key, members)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn smembers(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Vec<Sds>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.smembers(key)
+    }
+
+    fn scard(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<usize> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.scard(key)
+    }
+
+    fn sismember(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.sismember(key, member)
+    }
+
+    fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.smismember(key, members)
+    }
+
+    fn srem(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.srem(key, members)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn srandmember(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.srandmember(key, count)
+    }
+
+    fn spop(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.spop(key, count)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        let shard = self.route_keys(&[src, dst])?;
+        let moved = shard.shard.smove(src, dst, member)?;
+        self.replicate(&shard, &[src, dst])?;
+        Ok(moved)
+    }
+
+    fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let shard = self.route_keys_read(keys)?;
+        shard.sintercard(keys, limit)
+    }
+
+    fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let all: Vec<&Sds> = std::iter::once(dst).chain(keys.iter().copied()).collect();
+        let shard = self.route_keys(&all)?;
+        let stored = shard.shard.sinterstore(dst, keys)?;
+        self.replicate(&shard, &[dst])?;
+        Ok(stored)
+    }
+
+    fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let all: Vec<&Sds> = std::iter::once(dst).chain(keys.iter().copied()).collect();
+        let shard = self.route_keys(&all)?;
+        let stored = shard.shard.sunionstore(dst, keys)?;
+        self.replicate(&shard, &[dst])?;
+        Ok(stored)
+    }
+
+    fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let all: Vec<&Sds> = std::iter::once(dst).chain(keys.iter().copied()).collect();
+        let shard = self.route_keys(&all)?;
+        let stored = shard.shard.sdiffstore(dst, keys)?;
+        self.replicate(&shard, &[dst])?;
+        Ok(stored)
+    }
+
+    fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.route_keys_read(keys)?.shard.zunion(keys, options)
     }
 
-    fn rename(
+    fn zinter(
         &self,
-        from: &Sds,
-        to: &Sds,
-    ) -> StoreResult<()> {
-        let from_str = Self::sds_to_str(from);
-        let to_str = Self::sds_to_str(to);
-
-        let from_shard = self.slot_manager.get_key_shard(from_str.as_ref());
-        let to_shard = self.slot_manager.get_key_shard(to_str.as_ref());
-
-        self.slot_manager.record_operation(from_str.as_ref());
-        self.slot_manager.record_operation(to_str.as_ref());
-
-        if from_shard != to_shard {
-            return Err(StoreError::WrongShard);
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
         }
+        self.route_keys_read(keys)?.shard.zinter(keys, options)
+    }
 
-        let shard = self.shard_by_id(from_shard)?;
-        shard.rename(from, to)
+    fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.route_keys_read(keys)?.shard.zdiff(keys)
     }
 
-    fn renamenx(
+    fn zunionstore(
         &self,
-        from: &Sds,
-        to: &Sds,
-    ) -> StoreResult<bool> {
-        let from_str = Self::sds_to_str(from);
-        let to_str = Self::sds_to_str(to);
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        let all: Vec<&Sds> = std::iter::once(dst).chain(keys.iter().copied()).collect();
+        let shard = self.route_keys(&all)?;
+        let stored = shard.shard.zunionstore(dst, keys, options)?;
+        self.replicate(&shard, &[dst])?;
+        Ok(stored)
+    }
 
-        let from_shard = self.slot_manager.get_key_shard(from_str.as_ref());
-        let to_shard = self.slot_manager.get_key_shard(to_str.as_ref());
+    fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        let all: Vec<&Sds> = std::iter::once(dst).chain(keys.iter().copied()).collect();
+        let shard = self.route_keys(&all)?;
+        let stored = shard.shard.zinterstore(dst, keys, options)?;
+        self.replicate(&shard, &[dst])?;
+        Ok(stored)
+    }
 
-        self.slot_manager.record_operation(from_str.as_ref());
-        self.slot_manager.record_operation(to_str.as_ref());
+    fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let all: Vec<&Sds> = std::iter::once(dst).chain(keys.iter().copied()).collect();
+        let shard = self.route_keys(&all)?;
+        let stored = shard.shard.zdiffstore(dst, keys)?;
+        self.replicate(&shard, &[dst])?;
+        Ok(stored)
+    }
 
-        if from_shard != to_shard {
-            return Err(StoreError::WrongShard);
+    fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>> {
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        let shard = self.route_keys(keys)?;
+        let popped = shard.shard.zmpop(keys, dir, count)?;
+        if let Some((key, _)) = &popped {
+            self.replicate(&shard, &[key])?;
         }
+        Ok(popped)
+    }
 
-        let shard = self.shard_by_id(from_shard)?;
-        shard.renamenx(from, to)
+    fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.zmscore(key, members)
     }
 
-    fn flushdb(&self) -> StoreResult<()> {
-        for shard in &self.shards {
-            shard.flushdb()?;
-        }
-        self.slot_manager.reset_metrics();
-        Ok(())
+    fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.hrandfield(key, count, with_values)
     }
 
-    fn geo_add(
+    fn hincrby(
         &self,
         key: &Sds,
-        lon: f64,
-        lat: f64,
-        member: &Sds,
-    ) -> StoreResult<bool> {
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
         self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.geo_add(key, lon, lat, member)
+        let shard = self.route(key)?;
+        let result = shard.hincrby(key, field, delta)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
     }
 
-    fn geo_dist(
+    fn hincrbyfloat(
         &self,
         key: &Sds,
-        member1: &Sds,
-        member2: &Sds,
-        unit: &str,
-    ) -> StoreResult<Option<f64>> {
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
         self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.geo_dist(key, member1, member2, unit)
+        let shard = self.route(key)?;
+        let result = shard.hincrbyfloat(key, field, delta)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
     }
 
-    fn geo_pos(
+    fn hmget(
         &self,
         key: &Sds,
-        member: &Sds,
-    ) -> StoreResult<Option<GeoPoint>> {
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>> {
         self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.geo_pos(key, member)
+        let shard = self.route_read(key)?;
+        shard.hmget(key, fields)
     }
 
-    fn geo_radius(
+    fn memory_usage(
         &self,
         key: &Sds,
-        lon: f64,
-        lat: f64,
-        radius: f64,
-        unit: &str,
-    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        samples: usize,
+    ) -> StoreResult<Option<usize>> {
         self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.geo_radius(key, lon, lat, radius, unit)
+        let shard = self.route(key)?;
+        shard.memory_usage(key, samples)
     }
 
-    fn geo_radius_by_member(
+    fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>> {
+        let shard = self.route_keys(&[src, dst])?;
+        let moved = shard.shard.lmove(src, dst, src_dir, dst_dir)?;
+        self.replicate(&shard, &[src, dst])?;
+        Ok(moved)
+    }
+
+    fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>> {
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        let shard = self.route_keys(keys)?;
+        let popped = shard.shard.lmpop(keys, dir, count)?;
+        if let Some((key, _)) = &popped {
+            self.replicate(&shard, &[key])?;
+        }
+        Ok(popped)
+    }
+
+    fn lset(
         &self,
         key: &Sds,
-        member: &Sds,
-        radius: f64,
-        unit: &str,
-    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        index: isize,
+        value: Sds,
+    ) -> StoreResult<()> {
         self.record_operation(key);
-        let key_str = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(key_str.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.geo_radius_by_member(key, member, radius, unit)
+        let shard = self.route(key)?;
+        shard.lset(key, index, value)?;
+        self.replicate(&shard, &[key])
     }
 
-    fn sadd(
+    fn getdel(
         &self,
         key: &Sds,
-        members: &[Sds],
-    ) -> StoreResult<usize> {
+    ) -> StoreResult<Option<Sds>> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.sadd(key, members)
+        let shard = self.route(key)?;
+        let result = shard.getdel(key)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
     }
 
-    fn smembers(
+    fn getex(
         &self,
         key: &Sds,
-    ) -> StoreResult<Vec<Sds>> {
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.smembers(key)
+        let shard = self.route(key)?;
+        let result = shard.getex(key, expiry)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
     }
 
-    fn scard(
+    fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool> {
+        let shard = self.route_keys(&[src, dst])?;
+        let copied = shard.shard.copy(src, dst, dst_db, replace)?;
+        self.replicate(&shard, &[src, dst])?;
+        Ok(copied)
+    }
+
+    fn expiretime(
         &self,
         key: &Sds,
-    ) -> StoreResult<usize> {
+    ) -> StoreResult<Option<i64>> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.scard(key)
+        let shard = self.route_read(key)?;
+        shard.expiretime(key)
     }
 
-    fn sismember(
+    fn set_expiry(
         &self,
         key: &Sds,
-        member: &Sds,
+        ttl: Option<Duration>,
     ) -> StoreResult<bool> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.sismember(key, member)
+        let shard = self.route(key)?;
+        let result = shard.set_expiry(key, ttl)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
     }
 
-    fn srem(
+    fn type_of(
         &self,
         key: &Sds,
-        members: &[Sds],
+    ) -> StoreResult<Option<ValueType>> {
+        self.record_operation(key);
+        let shard = self.route_read(key)?;
+        shard.type_of(key)
+    }
+
+    fn touch(
+        &self,
+        keys: &[&Sds],
     ) -> StoreResult<usize> {
+        let _guard = self.migrations.key_lock.read().unwrap();
+        let mut touched = 0;
+        for key in keys {
+            self.record_operation(key);
+            let shard_id = self.key_shard(key);
+            touched += self.shard_by_id(shard_id)?.touch(&[*key])?;
+        }
+        Ok(touched)
+    }
+
+    fn idletime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u64>> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.srem(key, members)
+        let shard = self.route(key)?;
+        shard.idletime(key)
     }
 
-    fn srandmember(
+    fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> StoreResult<()> {
+        for shard in self.all_shards() {
+            shard.set_eviction_policy(policy)?;
+        }
+        Ok(())
+    }
+
+    fn freq(
         &self,
         key: &Sds,
-        count: isize,
-    ) -> StoreResult<Vec<Sds>> {
+    ) -> StoreResult<Option<u8>> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.srandmember(key, count)
+        let shard = self.route(key)?;
+        shard.freq(key)
     }
 
-    fn spop(
+    fn set_access_meta(
         &self,
         key: &Sds,
-        count: isize,
-    ) -> StoreResult<Vec<Sds>> {
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> StoreResult<bool> {
         self.record_operation(key);
-        let ks = Self::sds_to_str(key);
-        let shard_id = self.slot_manager.get_key_shard(ks.as_ref());
-        let shard = self.shard_by_id(shard_id)?;
-        shard.spop(key, count)
+        let shard = self.route(key)?;
+        shard.set_access_meta(key, idle_secs, freq)
+    }
+
+    fn persist(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        self.record_operation(key);
+        let shard = self.route(key)?;
+        let result = shard.persist(key)?;
+        self.replicate(&shard, &[key])?;
+        Ok(result)
+    }
+
+    fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64> {
+        let shard = self.route_keys(&[src, dst])?;
+        let stored = shard.shard.geosearchstore(dst, src, params)?;
+        self.replicate(&shard, &[src, dst])?;
+        Ok(stored)
+    }
+
+    fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()> {
+        let all: Vec<&Sds> = std::iter::once(dest)
+            .chain(sources.iter().copied())
+            .collect();
+        let shard = self.route_keys(&all)?;
+        shard.shard.pfmerge(dest, sources)?;
+        self.replicate(&shard, &[dest])
     }
 
     fn dbsize(&self) -> StoreResult<usize> {
         let mut total: usize = 0;
-        for shard in &self.shards {
+        for shard in self.all_shards() {
             match shard.dbsize() {
                 Ok(n) => total = total.saturating_add(n),
                 Err(e) => {
@@ -547,9 +2021,37 @@ impl Storage for InClusterStore {
         Ok(total)
     }
 
+    fn keys(
+        &self,
+        pattern: &str,
+    ) -> StoreResult<Vec<Sds>> {
+        let mut keys = Vec::new();
+        for shard in self.all_shards() {
+            match shard.keys(pattern) {
+                Ok(shard_keys) => keys.extend(shard_keys),
+                Err(e) => {
+                    self.record_failed_operation();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    fn swapdb(
+        &self,
+        _db1: usize,
+        _db2: usize,
+    ) -> StoreResult<()> {
+        // Как и в Redis Cluster, в кластере доступна только база 0.
+        Err(StoreError::InvalidOperation(
+            "SWAPDB is not allowed in cluster mode".to_string(),
+        ))
+    }
+
     fn save(&self) -> StoreResult<()> {
         let mut any_saved = false;
-        for shard in &self.shards {
+        for shard in self.all_shards() {
             match shard.save() {
                 Ok(_) => {
                     any_saved = true;
@@ -576,6 +2078,35 @@ impl Storage for InClusterStore {
     }
 }
 
+impl crate::engine::AsyncStorage for InClusterStore {
+    ready_storage_methods!();
+
+    fn save(&self) -> impl Future<Output = StoreResult<()>> + Send {
+        std::future::ready(Storage::save(self))
+    }
+}
+
+/// Читает идентификатор узла из `path`, а если файла нет — создаёт новый
+/// идентификатор и сохраняет его туда.
+pub fn load_or_create_node_id(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(id) if !id.trim().is_empty() => return Ok(id.trim().to_string()),
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let id = new_node_id();
+    fs::write(path, &id)?;
+    Ok(id)
+}
+
+/// Создаёт случайный идентификатор узла (40 шестнадцатеричных символов, как
+/// в Redis Cluster).
+fn new_node_id() -> String {
+    let id = Uuid::new_v4().simple().to_string();
+    format!("{id}{}", &Uuid::new_v4().simple().to_string()[..8])
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для InClusterStore, OperationMetrics
 ////////////////////////////////////////////////////////////////////////////////
@@ -706,13 +2237,17 @@ mod tests {
         let key = Sds::from_str("geo");
         let member = Sds::from_str("rome");
 
-        assert!(cluster.geo_add(&key, 12.5, 41.9, &member).unwrap());
+        assert!(cluster
+            .geo_add(&key, 12.5, 41.9, &member, GeoAddOptions::default())
+            .unwrap());
         let pos = cluster.geo_pos(&key, &member).unwrap().unwrap();
         assert!((pos.lon - 12.5).abs() < 1e-6);
         assert!((pos.lat - 41.9).abs() < 1e-6);
 
         let member2 = Sds::from_str("milan");
-        cluster.geo_add(&key, 9.19, 45.46, &member2).unwrap();
+        cluster
+            .geo_add(&key, 9.19, 45.46, &member2, GeoAddOptions::default())
+            .unwrap();
 
         let dist = cluster
             .geo_dist(&key, &member, &member2, "km")
@@ -720,4 +2255,300 @@ mod tests {
             .unwrap();
         assert!(dist > 400.0); // реальное расстояние ~ 480км
     }
+
+    /// Тест проверяет, что добавление пятого shard'а к четырём переносит
+    /// около 1/5 ключей, все ключи остаются доступны, а callback получает
+    /// прогресс по каждому ключу.
+    #[test]
+    fn test_rebalance_add_shard_moves_fraction() {
+        let cluster = make_cluster(4);
+        let total = 2000;
+        for i in 0..total {
+            cluster
+                .set(&Sds::from(format!("key:{i}").as_bytes()), Value::Int(i))
+                .unwrap();
+        }
+
+        let mut shards = cluster.all_shards();
+        shards.push(Arc::new(InMemoryStore::new()));
+        let mut calls = 0;
+        let stats = cluster
+            .rebalance(shards, |p| {
+                calls += 1;
+                assert_eq!(p.scanned_keys, calls);
+                assert_eq!(p.total_keys, total as usize);
+            })
+            .unwrap();
+
+        assert_eq!(calls, total as usize);
+        let fraction = stats.moved_keys as f64 / total as f64;
+        assert!((0.1..0.3).contains(&fraction), "moved fraction {fraction}");
+        assert_eq!(cluster.all_shards()[4].dbsize().unwrap(), stats.moved_keys);
+        for i in 0..total {
+            assert_eq!(
+                cluster
+                    .get(&Sds::from(format!("key:{i}").as_bytes()))
+                    .unwrap(),
+                Some(Value::Int(i))
+            );
+        }
+    }
+
+    /// Тест проверяет, что при удалении shard'а переносятся только его
+    /// ключи и вместе с TTL.
+    #[test]
+    fn test_rebalance_remove_shard() {
+        let cluster = make_cluster(3);
+        let keys: Vec<Sds> = (0..300)
+            .map(|i| Sds::from(format!("key:{i}").as_bytes()))
+            .collect();
+        for key in &keys {
+            cluster.set(key, Value::Int(1)).unwrap();
+            cluster
+                .set_expiry(key, Some(Duration::from_secs(100)))
+                .unwrap();
+        }
+
+        let mut shards = cluster.all_shards();
+        let removed = shards.pop().unwrap().dbsize().unwrap();
+        let stats = cluster.rebalance(shards, |_| {}).unwrap();
+
+        assert_eq!(stats.moved_keys, removed);
+        assert_eq!(cluster.dbsize().unwrap(), keys.len());
+        for key in &keys {
+            assert_eq!(cluster.get(key).unwrap(), Some(Value::Int(1)));
+            assert!(cluster.expiretime(key).unwrap().unwrap() > 0);
+        }
+        assert!(matches!(
+            cluster.rebalance(Vec::new(), |_| {}),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    fn tagged_keys(n: usize) -> Vec<Sds> {
+        (0..n)
+            .map(|i| Sds::from(format!("{{user}}:{i}").as_bytes()))
+            .collect()
+    }
+
+    /// Тест проверяет, что миграция переносит все ключи слота на целевой
+    /// shard вместе с TTL и закрепляет слот за ним.
+    #[test]
+    fn test_migrate_slot() {
+        let cluster = make_cluster(3);
+        let keys = tagged_keys(50);
+        for key in &keys {
+            cluster.set(key, Value::Int(1)).unwrap();
+        }
+        cluster
+            .set_expiry(&keys[0], Some(Duration::from_secs(100)))
+            .unwrap();
+
+        let slot = cluster.slot_manager.calculate_slot("{user}");
+        let owner = cluster.key_shard(&keys[0]);
+        let target = (owner + 1) % 3;
+        assert_eq!(cluster.slot_state(slot), MigrationState::Idle);
+
+        let handle = cluster.migrate_slot(slot, target).unwrap();
+        assert_eq!(handle.slot(), slot);
+        assert_eq!(
+            handle.wait().unwrap(),
+            MigrationState::Complete { to: target }
+        );
+
+        assert_eq!(
+            cluster.slot_state(slot),
+            MigrationState::Complete { to: target }
+        );
+        assert_eq!(cluster.all_shards()[target].dbsize().unwrap(), keys.len());
+        assert_eq!(cluster.all_shards()[owner].dbsize().unwrap(), 0);
+        for key in &keys {
+            assert_eq!(cluster.get(key).unwrap(), Some(Value::Int(1)));
+        }
+        assert!(cluster.expiretime(&keys[0]).unwrap().unwrap() > 0);
+
+        let new_key = Sds::from_str("{user}:new");
+        cluster.set(&new_key, Value::Int(2)).unwrap();
+        assert_eq!(
+            cluster.all_shards()[target].get(&new_key).unwrap(),
+            Some(Value::Int(2))
+        );
+    }
+
+    /// Тест проверяет, что во время миграции ключ читается там, где он
+    /// лежит, новые ключи пишутся на целевой shard, а прерванная миграция
+    /// продолжается с места остановки.
+    #[test]
+    fn test_migrate_slot_resume() {
+        let cluster = make_cluster(2);
+        let keys = tagged_keys(20);
+        for key in &keys {
+            cluster.set(key, Value::Int(1)).unwrap();
+        }
+        let slot = cluster.slot_manager.calculate_slot("{user}");
+        let owner = cluster.key_shard(&keys[0]);
+        let target = 1 - owner;
+        assert!(!cluster.cancel_slot_migration(slot));
+
+        // Состояние миграции, прерванной после переноса половины ключей.
+        let migrating = MigrationState::Migrating {
+            from: None,
+            to: target,
+        };
+        cluster
+            .migrations
+            .states
+            .write()
+            .unwrap()
+            .insert(slot, migrating);
+        let shards = cluster.all_shards();
+        for key in &keys[..10] {
+            assert!(InClusterStore::move_key(&*shards[owner], &*shards[target], key).unwrap());
+        }
+
+        for key in &keys {
+            assert_eq!(cluster.get(key).unwrap(), Some(Value::Int(1)));
+        }
+        cluster.set(&keys[15], Value::Int(3)).unwrap();
+        assert_eq!(shards[owner].get(&keys[15]).unwrap(), Some(Value::Int(3)));
+        let new_key = Sds::from_str("{user}:new");
+        cluster.set(&new_key, Value::Int(2)).unwrap();
+        assert_eq!(shards[target].get(&new_key).unwrap(), Some(Value::Int(2)));
+
+        assert!(matches!(
+            cluster.migrate_slot(slot, owner),
+            Err(StoreError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            cluster.rebalance(cluster.all_shards(), |_| {}),
+            Err(StoreError::InvalidOperation(_))
+        ));
+
+        let handle = cluster.migrate_slot(slot, target).unwrap();
+        assert_eq!(
+            handle.wait().unwrap(),
+            MigrationState::Complete { to: target }
+        );
+        assert_eq!(shards[target].dbsize().unwrap(), keys.len() + 1);
+        assert_eq!(shards[owner].dbsize().unwrap(), 0);
+        assert_eq!(cluster.get(&keys[15]).unwrap(), Some(Value::Int(3)));
+    }
+
+    /// Тест проверяет, что `MSET`, идущий параллельно с миграцией слота, не
+    /// теряет записей: ключ не переносится между маршрутизацией и записью.
+    #[test]
+    fn test_mset_during_migration() {
+        let cluster = make_cluster(2);
+        let keys = tagged_keys(200);
+        for key in &keys {
+            cluster.set(key, Value::Int(0)).unwrap();
+        }
+        let slot = cluster.slot_manager.calculate_slot("{user}");
+        let owner = cluster.key_shard(&keys[0]);
+        let target = 1 - owner;
+
+        let handle = cluster.migrate_slot(slot, target).unwrap();
+        let mut round = 0;
+        while round < 3 || !handle.is_finished() {
+            round += 1;
+            let entries = keys.iter().map(|key| (key, Value::Int(round))).collect();
+            cluster.mset(entries).unwrap();
+        }
+        assert_eq!(
+            handle.wait().unwrap(),
+            MigrationState::Complete { to: target }
+        );
+
+        let shards = cluster.all_shards();
+        assert_eq!(shards[owner].dbsize().unwrap(), 0);
+        assert_eq!(shards[target].dbsize().unwrap(), keys.len());
+        let refs: Vec<&Sds> = keys.iter().collect();
+        for value in cluster.mget(&refs).unwrap() {
+            assert_eq!(value, Some(Value::Int(round)));
+        }
+    }
+
+    /// Тест проверяет, что многоключевые операции внутри мигрирующего слота
+    /// выполняются на одном shard'е, даже если часть ключей уже перенесена.
+    #[test]
+    fn test_multi_key_ops_in_migrating_slot() {
+        let cluster = make_cluster(2);
+        let keys = tagged_keys(4);
+        for key in &keys {
+            cluster.sadd(key, &[Sds::from_str("m")]).unwrap();
+        }
+        let slot = cluster.slot_manager.calculate_slot("{user}");
+        let owner = cluster.key_shard(&keys[0]);
+        let target = 1 - owner;
+        cluster.migrations.states.write().unwrap().insert(
+            slot,
+            MigrationState::Migrating {
+                from: None,
+                to: target,
+            },
+        );
+        let shards = cluster.all_shards();
+        assert!(InClusterStore::move_key(&*shards[owner], &*shards[target], &keys[0]).unwrap());
+
+        // keys[0] уже на целевом shard'е, keys[1] — ещё на источнике.
+        let dst = Sds::from_str("{user}:dst");
+        assert_eq!(cluster.sunionstore(&dst, &[&keys[0], &keys[1]]).unwrap(), 1);
+        cluster.rename(&keys[2], &keys[0]).unwrap();
+        assert!(cluster
+            .renamenx(&keys[3], &Sds::from_str("{user}:new"))
+            .unwrap());
+
+        assert_eq!(shards[owner].dbsize().unwrap(), 0);
+        assert_eq!(shards[target].dbsize().unwrap(), 4);
+        assert_eq!(cluster.type_of(&keys[2]).unwrap(), None);
+        assert!(cluster.sismember(&dst, &Sds::from_str("m")).unwrap());
+    }
+
+    /// Тест проверяет, что запись в кластер доходит до реплики, а чтения
+    /// идут на реплику только при `ConsistencyLevel::Eventual`.
+    #[test]
+    fn test_replica_reads() {
+        let cluster = make_cluster(2);
+        let keys = tagged_keys(2);
+        cluster.set(&keys[0], Value::Int(1)).unwrap();
+        let owner = cluster.key_shard(&keys[0]);
+
+        let replica: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        cluster.add_replica(owner, replica.clone()).unwrap();
+        assert_eq!(cluster.replica_count(owner), 1);
+        assert_eq!(replica.get(&keys[0]).unwrap(), Some(Value::Int(1)));
+
+        cluster.set(&keys[1], Value::Int(2)).unwrap();
+        cluster.del(&keys[0]).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while replica.get(&keys[1]).unwrap().is_none() || replica.get(&keys[0]).unwrap().is_some() {
+            assert!(Instant::now() < deadline, "replica did not catch up");
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        // Ключ, который есть только на реплике, виден лишь при чтении с реплик.
+        let marker = Sds::from_str("{user}:replica-only");
+        replica.set(&marker, Value::Int(3)).unwrap();
+        assert_eq!(cluster.consistency_level(), ConsistencyLevel::Strong);
+        assert_eq!(cluster.get(&marker).unwrap(), None);
+        cluster.set_consistency_level(ConsistencyLevel::Eventual);
+        assert_eq!(cluster.get(&marker).unwrap(), Some(Value::Int(3)));
+        assert!(matches!(
+            cluster.add_replica(2, replica),
+            Err(StoreError::WrongShard)
+        ));
+    }
+
+    /// Тест проверяет, что идентификатор узла сохраняется в файл и читается
+    /// из него при следующем запуске.
+    #[test]
+    fn test_node_id_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node.id");
+
+        let id = load_or_create_node_id(&path).unwrap();
+        assert_eq!(id.len(), 40);
+        assert_eq!(load_or_create_node_id(&path).unwrap(), id);
+        assert_eq!(make_cluster(1).with_node_id(id.clone()).node_id(), id);
+    }
 }
@@ -0,0 +1,165 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use rand::Rng;
+
+/// Начальное значение LFU-счётчика нового ключа: новый ключ не должен
+/// вытесняться раньше, чем к нему успеют обратиться.
+pub const LFU_INIT_VAL: u8 = 5;
+/// Множитель логарифмического роста LFU-счётчика (`lfu-log-factor`).
+pub const LFU_LOG_FACTOR: f64 = 10.0;
+/// Период уменьшения LFU-счётчиков фоновой задачей (`lfu-decay-time`).
+pub const LFU_DECAY_PERIOD: Duration = Duration::from_secs(60);
+
+/// Политика вытеснения ключей (`maxmemory-policy`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Ключи не вытесняются.
+    #[default]
+    NoEviction,
+    /// Давно не использовавшиеся ключи среди всех.
+    AllKeysLru,
+    /// Давно не использовавшиеся ключи среди ключей с TTL.
+    VolatileLru,
+    /// Редко используемые ключи среди всех.
+    AllKeysLfu,
+    /// Редко используемые ключи среди ключей с TTL.
+    VolatileLfu,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl EvictionPolicy {
+    /// Все политики в порядке их кодов.
+    const ALL: [EvictionPolicy; 5] = [
+        EvictionPolicy::NoEviction,
+        EvictionPolicy::AllKeysLru,
+        EvictionPolicy::VolatileLru,
+        EvictionPolicy::AllKeysLfu,
+        EvictionPolicy::VolatileLfu,
+    ];
+
+    /// Возвращает имя политики в формате `maxmemory-policy`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionPolicy::NoEviction => "noeviction",
+            EvictionPolicy::AllKeysLru => "allkeys-lru",
+            EvictionPolicy::VolatileLru => "volatile-lru",
+            EvictionPolicy::AllKeysLfu => "allkeys-lfu",
+            EvictionPolicy::VolatileLfu => "volatile-lfu",
+        }
+    }
+
+    /// Проверяет, выбирает ли политика ключи по LFU-счётчику.
+    pub fn is_lfu(&self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::AllKeysLfu | EvictionPolicy::VolatileLfu
+        )
+    }
+
+    /// Проверяет, ограничена ли политика ключами с TTL.
+    pub fn is_volatile(&self) -> bool {
+        matches!(
+            self,
+            EvictionPolicy::VolatileLru | EvictionPolicy::VolatileLfu
+        )
+    }
+
+    /// Возвращает компактный код политики (для хранения в атомике).
+    pub fn code(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Восстанавливает политику по коду; неизвестный код — `NoEviction`.
+    pub fn from_code(code: u8) -> Self {
+        Self::ALL.get(code as usize).copied().unwrap_or_default()
+    }
+}
+
+/// Логарифмически увеличивает LFU-счётчик (счётчик Морриса, как в Redis):
+/// чем больше счётчик, тем меньше вероятность его увеличения. Счётчик
+/// насыщается на 255.
+pub fn lfu_log_incr(counter: u8) -> u8 {
+    if counter == u8::MAX {
+        return counter;
+    }
+    let base = counter.saturating_sub(LFU_INIT_VAL) as f64;
+    if base == 0.0 {
+        return counter + 1;
+    }
+    let p = 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+    if rand::thread_rng().gen::<f64>() < p {
+        counter + 1
+    } else {
+        counter
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для EvictionPolicy
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for EvictionPolicy {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown eviction policy '{s}'"))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет разбор и коды политик.
+    #[test]
+    fn test_policy_parse_and_code() {
+        for policy in EvictionPolicy::ALL {
+            assert_eq!(policy.as_str().parse::<EvictionPolicy>(), Ok(policy));
+            assert_eq!(EvictionPolicy::from_code(policy.code()), policy);
+        }
+        assert_eq!(
+            "ALLKEYS-LFU".parse::<EvictionPolicy>(),
+            Ok(EvictionPolicy::AllKeysLfu)
+        );
+        assert!("lfu".parse::<EvictionPolicy>().is_err());
+        assert!(EvictionPolicy::VolatileLfu.is_lfu());
+        assert!(EvictionPolicy::VolatileLfu.is_volatile());
+        assert!(!EvictionPolicy::AllKeysLru.is_lfu());
+    }
+
+    /// Тест проверяет, что счётчик растёт логарифмически: до начального
+    /// значения — на каждое обращение, дальше всё медленнее, и насыщается на
+    /// 255.
+    #[test]
+    fn test_lfu_log_incr() {
+        assert_eq!(lfu_log_incr(0), 1);
+        assert_eq!(lfu_log_incr(LFU_INIT_VAL), LFU_INIT_VAL + 1);
+        assert_eq!(lfu_log_incr(u8::MAX), u8::MAX);
+
+        let mut counter = LFU_INIT_VAL;
+        for _ in 0..1000 {
+            counter = lfu_log_incr(counter);
+        }
+        // После 1000 обращений счётчик далёк от насыщения.
+        assert!(counter > LFU_INIT_VAL + 5 && counter < 40, "{counter}");
+    }
+}
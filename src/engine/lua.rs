@@ -3,19 +3,53 @@
 //! Модуль предоставляет безопасную и ограниченную среду для выполнения
 //! Lua-скриптов внутри базы данных Zumic. Используется для расширяемости,
 //! написания пользовательских процедур, атомарных операций и скриптовых команд.
+//!
+//! Скрипты `EVAL`/`EVALSHA` компилируются один раз и кэшируются по SHA1
+//! исходного текста (см. [`LuaEngine::load_script`]); внутри скрипта
+//! доступны `KEYS`, `ARGV`, `redis.call` и `redis.pcall`.
 
 use std::{
-    sync::{Arc, Mutex},
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 
 use mlua::{
-    Error as LuaError, HookTriggers, Lua, MultiValue, Result as LuaResult, String as LuaString,
-    UserData, UserDataMethods, Value as LuaValue, VmState,
+    Error as LuaError, Function, HookTriggers, Lua, LuaOptions, MultiValue, Result as LuaResult,
+    StdLib, String as LuaString, Table, UserData, UserDataMethods, Value as LuaValue, VmState,
 };
 
-use super::{Sds, SmartHash, Value};
-use crate::{Dict, Hll, QuickList, SkipList};
+use crate::{Dict, Hll, QuickList, Sds, SkipList, SmartHash, StoreError, Value};
+
+/// Через сколько инструкций Lua вызывается хук контроля лимитов.
+const HOOK_INSTRUCTIONS: u32 = 1000;
+
+/// Базовые функции Lua, недоступные скриптам: они читают файлы или загружают
+/// произвольный код в обход кэша скриптов.
+const FORBIDDEN_GLOBALS: [&str; 3] = ["dofile", "loadfile", "load"];
+
+/// Вспомогательные функции `redis.*`, не требующие доступа к хранилищу.
+const REDIS_HELPERS: &str = r#"
+redis = {}
+function redis.error_reply(msg) return { err = msg } end
+function redis.status_reply(msg) return { ok = msg } end
+"#;
+
+/// Вызов команды хранилища из скрипта (`redis.call`): аргументы — имя команды
+/// и её аргументы.
+pub type ScriptCall<'a> = dyn FnMut(&[Sds]) -> Result<Value, StoreError> + 'a;
+
+thread_local! {
+    /// Движок скриптов потока. Lua VM нельзя передавать между потоками, а
+    /// сервер выполняет команды в одном потоке, поэтому кэш скриптов потока
+    /// общий для всех соединений.
+    static SCRIPT_ENGINE: RefCell<Option<LuaEngine>> = const { RefCell::new(None) };
+}
 
 /// Ошибка выполнения Lua скрипта.
 ///
@@ -33,6 +67,11 @@ pub enum LuaExecutionError {
     InvalidType(String),
     /// Ошибка конвертации между типами
     ConversionError(String),
+    /// Скрипт с указанным SHA1 отсутствует в кэше
+    NoScript,
+    /// Ошибка, возвращённая скриптом (`{ err = ... }`) или командой из
+    /// `redis.call`
+    ScriptError(String),
 }
 
 /// Конфигурация для выполнения Lua скриптов.
@@ -79,10 +118,65 @@ pub struct LuaConfig {
 /// - `nil` ↔ `Value::Null`
 pub struct LuaEngine {
     lua: Lua,
-    #[allow(dead_code)]
     config: LuaConfig,
-    start_time: Option<Instant>,
+    /// Момент, после которого выполнение скрипта прерывается хуком.
+    deadline: Arc<Mutex<Option<Instant>>>,
+    /// Было ли последнее выполнение прервано по времени.
+    timed_out: Arc<AtomicBool>,
     instruction_count: Arc<Mutex<u64>>,
+    /// Скомпилированные скрипты по SHA1 исходного текста.
+    scripts: HashMap<String, Function>,
+    /// Библиотеки именованных функций (`FUNCTION LOAD`).
+    functions: FunctionLibrary,
+}
+
+/// Описание функции библиотеки.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionMeta {
+    /// Имя функции, по которому она вызывается `FCALL`.
+    pub name: String,
+    /// Имя библиотеки, зарегистрировавшей функцию.
+    pub library: String,
+    /// Флаг `no-writes`: функции запрещено изменять данные.
+    pub no_writes: bool,
+}
+
+/// Библиотека функций, загруженная `FUNCTION LOAD`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryInfo {
+    pub name: String,
+    pub engine: String,
+    /// Исходный текст библиотеки вместе с заголовком `#!lua name=...`.
+    pub code: String,
+    pub functions: Vec<FunctionMeta>,
+}
+
+/// Политика `FUNCTION RESTORE` при совпадении имён с существующими
+/// библиотеками.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FunctionRestorePolicy {
+    /// Ошибка при совпадении имени библиотеки или функции.
+    #[default]
+    Append,
+    /// Совпадающие библиотеки заменяются.
+    Replace,
+    /// Все существующие библиотеки удаляются перед восстановлением.
+    Flush,
+}
+
+/// Реестр именованных функций: библиотеки по имени и скомпилированные
+/// функции по имени функции.
+#[derive(Clone, Default)]
+pub struct FunctionLibrary {
+    libraries: BTreeMap<String, LibraryInfo>,
+    functions: HashMap<String, (FunctionMeta, Function)>,
+}
+
+/// Библиотека после выполнения её кода: `callbacks[i]` реализует
+/// `info.functions[i]`.
+struct CompiledLibrary {
+    info: LibraryInfo,
+    callbacks: Vec<Function>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -101,31 +195,67 @@ impl LuaEngine {
     /// - `Ok(LuaEngine)`: Успешно созданный движок
     /// - `Err(LuaError)`: Ошибка инициализации Lua
     pub fn new(config: LuaConfig) -> LuaResult<Self> {
-        let lua = Lua::new();
+        // Песочница: только безопасные библиотеки, без io/os/package.
+        let lua = Lua::new_with(
+            StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8,
+            LuaOptions::default(),
+        )?;
 
         // Настройка ограничений памяти
         lua.set_memory_limit(config.max_memory_limit)?;
 
+        let globals = lua.globals();
+        for name in FORBIDDEN_GLOBALS {
+            globals.raw_set(name, LuaValue::Nil)?;
+        }
+        lua.load(REDIS_HELPERS).set_name("=redis").exec()?;
+        // Скрипты не могут создавать глобальные переменные: иначе они
+        // переживали бы вызов и влияли на другие скрипты.
+        let protect = lua.create_table()?;
+        protect.raw_set(
+            "__newindex",
+            lua.create_function(|_, (_, name): (Table, LuaValue)| -> LuaResult<()> {
+                Err(LuaError::RuntimeError(format!(
+                    "Script attempted to create global variable '{}'",
+                    name.to_string()?
+                )))
+            })?,
+        )?;
+        globals.set_metatable(Some(protect));
+
         let instruction_count = Arc::new(Mutex::new(0));
         let instruction_count_clone = instruction_count.clone();
+        let deadline = Arc::new(Mutex::new(None::<Instant>));
+        let deadline_clone = deadline.clone();
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let timed_out_clone = timed_out.clone();
+        let max_instruction_count = config.max_instruction_count;
 
         // Установка хука для контроля времени и инструкций
         lua.set_hook(
             HookTriggers {
-                on_calls: false,
-                on_returns: false,
-                every_line: true,
-                every_nth_instruction: Some(1),
+                every_nth_instruction: Some(HOOK_INSTRUCTIONS),
+                ..Default::default()
             },
             move |_lua, _debug| {
                 let mut count = instruction_count_clone.lock().unwrap();
-                *count += 1;
+                *count += HOOK_INSTRUCTIONS as u64;
 
-                if *count > config.max_instruction_count {
+                if *count > max_instruction_count {
                     return Err(LuaError::RuntimeError(
                         "Instruction limit exceeded".to_string(),
                     ));
                 }
+                if deadline_clone
+                    .lock()
+                    .unwrap()
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    timed_out_clone.store(true, Ordering::Relaxed);
+                    return Err(LuaError::RuntimeError(
+                        "Script killed by timeout".to_string(),
+                    ));
+                }
 
                 Ok(VmState::Continue)
             },
@@ -134,11 +264,381 @@ impl LuaEngine {
         Ok(Self {
             lua,
             config,
-            start_time: None,
+            deadline,
+            timed_out,
             instruction_count,
+            scripts: HashMap::new(),
+            functions: FunctionLibrary::default(),
+        })
+    }
+
+    /// Устанавливает лимит времени выполнения одного скрипта.
+    pub fn set_time_limit(
+        &mut self,
+        limit: Duration,
+    ) {
+        self.config.max_execution_time = limit;
+    }
+
+    /// Компилирует скрипт и кэширует его по SHA1 исходного текста. Повторная
+    /// загрузка того же текста не компилирует его заново.
+    ///
+    /// # Возвращает
+    /// - `Ok(sha)` — SHA1 скрипта в виде 40 шестнадцатеричных символов
+    /// - `Err(LuaExecutionError)` — синтаксическая ошибка скрипта
+    pub fn load_script(
+        &mut self,
+        source: &str,
+    ) -> Result<String, LuaExecutionError> {
+        let sha = sha1_hex(source.as_bytes());
+        if !self.scripts.contains_key(&sha) {
+            let function = self
+                .lua
+                .load(source)
+                .set_name(format!("=user_script:{sha}"))
+                .into_function()?;
+            self.scripts.insert(sha.clone(), function);
+        }
+        Ok(sha)
+    }
+
+    /// Проверяет, есть ли скрипт с SHA1 `sha` в кэше.
+    pub fn has_script(
+        &self,
+        sha: &str,
+    ) -> bool {
+        self.scripts.contains_key(&sha.to_ascii_lowercase())
+    }
+
+    /// Выполняет закэшированный скрипт `sha`.
+    ///
+    /// Таблицы `KEYS` и `ARGV` заполняются из `keys` и `args`;
+    /// `redis.call`/`redis.pcall` выполняют команды через `call`. Ответы
+    /// команд и результат скрипта преобразуются по правилам Redis: `nil`
+    /// ответа — `false`, `{ ok = ... }` — статус, `{ err = ... }` — ошибка.
+    ///
+    /// # Возвращает
+    /// - `Err(NoScript)` — скрипта нет в кэше
+    /// - `Err(Timeout)` — скрипт прерван по `max_execution_time`
+    /// - `Err(ScriptError)` — ошибка скрипта или вызванной им команды
+    pub fn eval_script(
+        &mut self,
+        sha: &str,
+        keys: &[Sds],
+        args: &[Sds],
+        call: &mut ScriptCall<'_>,
+    ) -> Result<Value, LuaExecutionError> {
+        let function = self
+            .scripts
+            .get(&sha.to_ascii_lowercase())
+            .cloned()
+            .ok_or(LuaExecutionError::NoScript)?;
+
+        let keys = self.string_table(keys)?;
+        let args = self.string_table(args)?;
+        let globals = self.lua.globals();
+        globals.raw_set("KEYS", keys.clone())?;
+        globals.raw_set("ARGV", args.clone())?;
+        self.invoke(&function, (keys, args), call)
+    }
+
+    /// Возвращает реестр функций, загруженных `FUNCTION LOAD`.
+    pub fn functions(&self) -> &FunctionLibrary {
+        &self.functions
+    }
+
+    /// Загружает библиотеку функций (`FUNCTION LOAD`).
+    ///
+    /// Первая строка `code` — заголовок `#!<engine> name=<library>`; код
+    /// библиотеки регистрирует функции через `redis.register_function`.
+    /// Библиотека и её функции заменяются целиком: при ошибке реестр не
+    /// меняется.
+    ///
+    /// # Возвращает
+    /// - `Ok(name)` — имя загруженной библиотеки
+    /// - `Err(ScriptError)` — ошибка заголовка или кода, библиотека существует
+    ///   и `replace == false`, имя функции занято другой библиотекой
+    pub fn load_library(
+        &mut self,
+        engine: &str,
+        code: &str,
+        replace: bool,
+    ) -> Result<String, LuaExecutionError> {
+        let library = self.compile_library(Some(engine), code)?;
+        let name = library.info.name.clone();
+        let mut staged = self.functions.clone();
+        staged.install(library, replace)?;
+        self.functions = staged;
+        Ok(name)
+    }
+
+    /// Удаляет библиотеку и все её функции (`FUNCTION DELETE`).
+    pub fn delete_library(
+        &mut self,
+        name: &str,
+    ) -> Result<(), LuaExecutionError> {
+        self.functions
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| LuaExecutionError::ScriptError("Library not found".into()))
+    }
+
+    /// Загружает библиотеки из исходных текстов (`FUNCTION RESTORE`) по
+    /// политике `policy`. Все библиотеки применяются атомарно.
+    pub fn restore_libraries(
+        &mut self,
+        codes: &[String],
+        policy: FunctionRestorePolicy,
+    ) -> Result<(), LuaExecutionError> {
+        let libraries = codes
+            .iter()
+            .map(|code| self.compile_library(None, code))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut staged = match policy {
+            FunctionRestorePolicy::Flush => FunctionLibrary::default(),
+            _ => self.functions.clone(),
+        };
+        for library in libraries {
+            staged.install(library, policy == FunctionRestorePolicy::Replace)?;
+        }
+        self.functions = staged;
+        Ok(())
+    }
+
+    /// Вызывает функцию библиотеки (`FCALL`/`FCALL_RO`). Функция получает
+    /// ключи и аргументы первым и вторым параметром.
+    pub fn fcall(
+        &mut self,
+        name: &str,
+        keys: &[Sds],
+        args: &[Sds],
+        call: &mut ScriptCall<'_>,
+    ) -> Result<Value, LuaExecutionError> {
+        let function = self
+            .functions
+            .functions
+            .get(name)
+            .map(|(_, callback)| callback.clone())
+            .ok_or_else(|| LuaExecutionError::ScriptError("Function not found".into()))?;
+        let keys = self.string_table(keys)?;
+        let args = self.string_table(args)?;
+        self.invoke(&function, (keys, args), call)
+    }
+
+    /// Выполняет `function` с `redis.call`/`redis.pcall`, направленными в
+    /// `call`, под контролем лимита времени.
+    fn invoke(
+        &mut self,
+        function: &Function,
+        params: (Table, Table),
+        call: &mut ScriptCall<'_>,
+    ) -> Result<Value, LuaExecutionError> {
+        let globals = self.lua.globals();
+        self.arm_deadline();
+        let call = RefCell::new(call);
+        let result = self.lua.scope(|scope| {
+            let redis: Table = globals.raw_get("redis")?;
+            redis.raw_set(
+                "call",
+                scope.create_function(|lua, args: MultiValue| {
+                    let args = script_call_args(args)?;
+                    let reply = (call.borrow_mut())(&args)
+                        .map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+                    reply_to_lua(lua, reply)
+                })?,
+            )?;
+            redis.raw_set(
+                "pcall",
+                scope.create_function(|lua, args: MultiValue| {
+                    let args = script_call_args(args)?;
+                    match (call.borrow_mut())(&args) {
+                        Ok(reply) => reply_to_lua(lua, reply),
+                        Err(e) => {
+                            let err = lua.create_table()?;
+                            err.raw_set("err", e.to_string())?;
+                            Ok(LuaValue::Table(err))
+                        }
+                    }
+                })?,
+            )?;
+            let value = function.call::<LuaValue>(params)?;
+            Ok(lua_to_reply(value))
+        });
+        *self.deadline.lock().unwrap() = None;
+
+        result.map_err(|e| self.run_error(e))?
+    }
+
+    /// Компилирует библиотеку функций и выполняет её код, собирая функции,
+    /// зарегистрированные через `redis.register_function`. Если `engine` не
+    /// задан, движок берётся из заголовка.
+    fn compile_library(
+        &mut self,
+        engine: Option<&str>,
+        code: &str,
+    ) -> Result<CompiledLibrary, LuaExecutionError> {
+        let script_error = |msg: String| LuaExecutionError::ScriptError(msg);
+        let (header, body) = code.split_once('\n').unwrap_or((code, ""));
+        let mut meta = header
+            .strip_prefix("#!")
+            .ok_or_else(|| script_error("Missing library metadata".into()))?
+            .split_whitespace();
+        let header_engine = meta.next().unwrap_or_default();
+        if engine.is_some_and(|engine| !engine.eq_ignore_ascii_case(header_engine)) {
+            return Err(script_error(format!(
+                "Library engine '{header_engine}' does not match '{}'",
+                engine.unwrap_or_default()
+            )));
+        }
+        if !header_engine.eq_ignore_ascii_case("lua") {
+            return Err(script_error(format!("Engine '{header_engine}' not found")));
+        }
+        let mut name = None;
+        for field in meta {
+            match field.split_once('=') {
+                Some(("name", value)) => name = Some(value.to_string()),
+                _ => {
+                    return Err(script_error(format!(
+                        "Invalid metadata value given: {field}"
+                    )))
+                }
+            }
+        }
+        let name = name.ok_or_else(|| script_error("Library name was not given".into()))?;
+        if !is_valid_function_name(&name) {
+            return Err(script_error(
+                "Library names can only contain letters, numbers, or underscores(_) and must be at least one character long".into(),
+            ));
+        }
+
+        let env = self.library_env()?;
+        let registered = RefCell::new(Vec::<(FunctionMeta, Function)>::new());
+        self.arm_deadline();
+        let result = self.lua.scope(|scope| {
+            let redis: Table = env.raw_get("redis")?;
+            redis.raw_set(
+                "register_function",
+                scope.create_function(|_, args: MultiValue| {
+                    let (function_name, callback, no_writes) = register_function_args(args)?;
+                    let mut registered = registered.borrow_mut();
+                    if registered
+                        .iter()
+                        .any(|(meta, _)| meta.name == function_name)
+                    {
+                        return Err(LuaError::RuntimeError(
+                            "Function already exists in the library".into(),
+                        ));
+                    }
+                    registered.push((
+                        FunctionMeta {
+                            name: function_name,
+                            library: name.clone(),
+                            no_writes,
+                        },
+                        callback,
+                    ));
+                    Ok(())
+                })?,
+            )?;
+            // Пустая первая строка вместо заголовка сохраняет номера строк.
+            self.lua
+                .load(format!("\n{body}"))
+                .set_name(format!("=user_function:{name}"))
+                .set_environment(env.clone())
+                .exec()
+        });
+        *self.deadline.lock().unwrap() = None;
+        result.map_err(|e| self.run_error(e))?;
+
+        let (functions, callbacks): (Vec<_>, Vec<_>) = registered.into_inner().into_iter().unzip();
+        if functions.is_empty() {
+            return Err(script_error("No functions registered".into()));
+        }
+        Ok(CompiledLibrary {
+            info: LibraryInfo {
+                name,
+                engine: "LUA".into(),
+                code: code.to_string(),
+                functions,
+            },
+            callbacks,
+        })
+    }
+
+    /// Создаёт окружение кода библиотеки: чтение глобальных переменных
+    /// проходит в общее окружение, запись запрещена, а собственная таблица
+    /// `redis` дополняется `register_function`.
+    fn library_env(&self) -> LuaResult<Table> {
+        let globals = self.lua.globals();
+
+        let redis = self.lua.create_table()?;
+        let redis_meta = self.lua.create_table()?;
+        redis_meta.raw_set("__index", globals.raw_get::<Table>("redis")?)?;
+        redis.set_metatable(Some(redis_meta));
+
+        let env = self.lua.create_table()?;
+        env.raw_set("redis", redis)?;
+        let env_meta = self.lua.create_table()?;
+        env_meta.raw_set("__index", globals.clone())?;
+        if let Some(protect) = globals.metatable() {
+            env_meta.raw_set("__newindex", protect.raw_get::<LuaValue>("__newindex")?)?;
+        }
+        env.set_metatable(Some(env_meta));
+        Ok(env)
+    }
+
+    /// Сбрасывает счётчик инструкций и взводит таймер выполнения.
+    fn arm_deadline(&self) {
+        *self.instruction_count.lock().unwrap() = 0;
+        *self.deadline.lock().unwrap() = Some(Instant::now() + self.config.max_execution_time);
+        self.timed_out.store(false, Ordering::Relaxed);
+    }
+
+    /// Преобразует ошибку выполнения скрипта или функции.
+    fn run_error(
+        &self,
+        err: LuaError,
+    ) -> LuaExecutionError {
+        match err {
+            _ if self.timed_out.load(Ordering::Relaxed) => LuaExecutionError::Timeout,
+            LuaError::MemoryError(_) => LuaExecutionError::MemoryLimit,
+            e => LuaExecutionError::ScriptError(root_cause_message(&e)),
+        }
+    }
+
+    /// Выполняет `f` с движком скриптов текущего потока, создавая его при
+    /// первом обращении (см. [`LuaConfig::for_scripts`]).
+    ///
+    /// # Возвращает
+    /// - `Err(ScriptError)` при повторном входе — скрипты не могут вызывать
+    ///   скрипты
+    pub fn with_scripts<R>(
+        f: impl FnOnce(&mut LuaEngine) -> Result<R, LuaExecutionError>
+    ) -> Result<R, LuaExecutionError> {
+        SCRIPT_ENGINE.with(|cell| {
+            let mut slot = cell.try_borrow_mut().map_err(|_| {
+                LuaExecutionError::ScriptError("Scripts cannot be called from scripts".into())
+            })?;
+            let engine = match slot.as_mut() {
+                Some(engine) => engine,
+                None => slot.insert(LuaEngine::new(LuaConfig::for_scripts())?),
+            };
+            f(engine)
         })
     }
 
+    /// Создаёт Lua-массив строк.
+    fn string_table(
+        &self,
+        items: &[Sds],
+    ) -> LuaResult<Table> {
+        let table = self.lua.create_table_with_capacity(items.len(), 0)?;
+        for (i, item) in items.iter().enumerate() {
+            table.raw_set(i + 1, self.lua.create_string(item.as_bytes())?)?;
+        }
+        Ok(table)
+    }
+
     /// Выполняет Lua скрипт с переданными аргументами.
     ///
     /// # Аргументы
@@ -162,7 +662,8 @@ impl LuaEngine {
         args: Vec<Value>,
     ) -> Result<Value, LuaExecutionError> {
         // Сбрасываем таймер и счётчик
-        self.start_time = Some(Instant::now());
+        *self.deadline.lock().unwrap() = Some(Instant::now() + self.config.max_execution_time);
+        self.timed_out.store(false, Ordering::Relaxed);
         *self.instruction_count.lock().unwrap() = 0;
 
         // Конвертация наших Value в LuaValue
@@ -178,15 +679,13 @@ impl LuaEngine {
         }
 
         // Выполнение скрипта: указываем только возвращаемый тип
-        let result: LuaValue = self
-            .lua
-            .load(script)
-            .set_name("eval")
-            .call::<LuaValue>(mv)
-            .map_err(|e| match e {
-                LuaError::MemoryError(_) => LuaExecutionError::MemoryLimit,
-                other => other.into(),
-            })?;
+        let result = self.lua.load(script).set_name("eval").call::<LuaValue>(mv);
+        *self.deadline.lock().unwrap() = None;
+        let result = result.map_err(|e| match e {
+            _ if self.timed_out.load(Ordering::Relaxed) => LuaExecutionError::Timeout,
+            LuaError::MemoryError(_) => LuaExecutionError::MemoryLimit,
+            other => other.into(),
+        })?;
 
         // Преобразуем в наш Value и возвращаем
         self.lua_to_value(result)
@@ -541,6 +1040,88 @@ impl UserData for Value {
     }
 }
 
+impl FunctionLibrary {
+    /// Возвращает библиотеки в порядке имён.
+    pub fn libraries(&self) -> impl Iterator<Item = &LibraryInfo> {
+        self.libraries.values()
+    }
+
+    /// Возвращает библиотеку по имени.
+    pub fn library(
+        &self,
+        name: &str,
+    ) -> Option<&LibraryInfo> {
+        self.libraries.get(name)
+    }
+
+    /// Возвращает описание функции по имени.
+    pub fn function(
+        &self,
+        name: &str,
+    ) -> Option<&FunctionMeta> {
+        self.functions.get(name).map(|(meta, _)| meta)
+    }
+
+    /// Удаляет библиотеку и её функции.
+    fn remove(
+        &mut self,
+        name: &str,
+    ) -> Option<LibraryInfo> {
+        let library = self.libraries.remove(name)?;
+        for function in &library.functions {
+            self.functions.remove(&function.name);
+        }
+        Some(library)
+    }
+
+    /// Добавляет библиотеку, заменяя одноимённую при `replace`. Имена
+    /// функций не могут совпадать с функциями других библиотек.
+    fn install(
+        &mut self,
+        library: CompiledLibrary,
+        replace: bool,
+    ) -> Result<(), LuaExecutionError> {
+        let name = &library.info.name;
+        if !replace && self.libraries.contains_key(name) {
+            return Err(LuaExecutionError::ScriptError(format!(
+                "Library '{name}' already exists"
+            )));
+        }
+        for function in &library.info.functions {
+            if self
+                .function(&function.name)
+                .is_some_and(|existing| existing.library != *name)
+            {
+                return Err(LuaExecutionError::ScriptError(format!(
+                    "Function {} already exists",
+                    function.name
+                )));
+            }
+        }
+
+        self.remove(&library.info.name);
+        for (meta, callback) in library.info.functions.iter().zip(library.callbacks) {
+            self.functions
+                .insert(meta.name.clone(), (meta.clone(), callback));
+        }
+        self.libraries
+            .insert(library.info.name.clone(), library.info);
+        Ok(())
+    }
+}
+
+impl LuaConfig {
+    /// Конфигурация для `EVAL`/`FCALL`: скрипты ограничены временем (5
+    /// секунд) и памятью (64 МБ), но не числом инструкций.
+    pub fn for_scripts() -> Self {
+        Self {
+            max_execution_time: Duration::from_secs(5),
+            max_memory_limit: 64 * 1024 * 1024,
+            max_instruction_count: u64::MAX,
+        }
+    }
+}
+
 impl Default for LuaConfig {
     /// Создает конфигурацию по умолчанию с безопасными лимитами.
     ///
@@ -576,6 +1157,10 @@ impl std::fmt::Display for LuaExecutionError {
             LuaExecutionError::MemoryLimit => write!(f, "Memory limit exceeded"),
             LuaExecutionError::InvalidType(msg) => write!(f, "Invalid type: {msg}"),
             LuaExecutionError::ConversionError(msg) => write!(f, "Conversion error: {msg}"),
+            LuaExecutionError::NoScript => {
+                write!(f, "NOSCRIPT No matching script. Please use EVAL.")
+            }
+            LuaExecutionError::ScriptError(msg) => f.write_str(msg),
         }
     }
 }
@@ -586,6 +1171,253 @@ impl std::fmt::Display for LuaExecutionError {
 /// ошибок Rust.
 impl std::error::Error for LuaExecutionError {}
 
+impl From<LuaExecutionError> for StoreError {
+    fn from(err: LuaExecutionError) -> Self {
+        match err {
+            LuaExecutionError::NoScript => StoreError::NoScript,
+            LuaExecutionError::LuaError(e) => e.into(),
+            other => StoreError::Script(other.to_string()),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Преобразует аргументы `redis.call` в строки: допустимы строки и числа.
+fn script_call_args(args: MultiValue) -> LuaResult<Vec<Sds>> {
+    if args.is_empty() {
+        return Err(LuaError::RuntimeError(
+            "Please specify at least one argument for this redis lib call".into(),
+        ));
+    }
+    args.into_iter()
+        .map(|arg| match arg {
+            LuaValue::String(s) => Ok(Sds::from_vec(s.as_bytes().to_vec())),
+            LuaValue::Integer(i) => Ok(Sds::from_str(&i.to_string())),
+            LuaValue::Number(n) => Ok(Sds::from_str(&n.to_string())),
+            _ => Err(LuaError::RuntimeError(
+                "Lua redis lib command arguments must be strings or integers".into(),
+            )),
+        })
+        .collect()
+}
+
+/// Преобразует ответ команды в Lua-значение (как `redis.call` в Redis).
+fn reply_to_lua(
+    lua: &Lua,
+    reply: Value,
+) -> LuaResult<LuaValue> {
+    let seq = |items: Vec<Value>| -> LuaResult<LuaValue> {
+        let table = lua.create_table_with_capacity(items.len(), 0)?;
+        for (i, item) in items.into_iter().enumerate() {
+            table.raw_set(i + 1, reply_to_lua(lua, item)?)?;
+        }
+        Ok(LuaValue::Table(table))
+    };
+    Ok(match reply {
+        Value::Str(s) => LuaValue::String(lua.create_string(s.as_bytes())?),
+        Value::Int(i) => LuaValue::Integer(i),
+        Value::Bool(b) => LuaValue::Integer(b as i64),
+        Value::Float(f) => LuaValue::String(lua.create_string(f.to_string())?),
+        Value::Null => LuaValue::Boolean(false),
+        Value::Array(items) => seq(items)?,
+        Value::List(list) => seq(list.into_iter().map(Value::Str).collect())?,
+        Value::Set(set) => seq(set.into_iter().map(Value::Str).collect())?,
+        Value::Hash(mut hash) => seq(hash
+            .iter()
+            .flat_map(|(f, v)| [Value::Str(f.clone()), Value::Str(v.clone())])
+            .collect())?,
+        Value::ZSet { sorted, .. } => seq(sorted
+            .iter()
+            .map(|(_, member)| Value::Str(member.clone()))
+            .collect())?,
+        Value::Bitmap(bitmap) => LuaValue::String(lua.create_string(bitmap.as_bytes())?),
+        Value::HyperLogLog(_) | Value::SStream(_) => {
+            return Err(LuaError::RuntimeError(
+                "Reply type is not supported in scripts".into(),
+            ))
+        }
+    })
+}
+
+/// Преобразует результат скрипта в ответ клиенту (правила Redis): число
+/// усекается до целого, `true` — `1`, `false` и `nil` — `Null`, массив
+/// обрывается на первом `nil`.
+fn lua_to_reply(value: LuaValue) -> Result<Value, LuaExecutionError> {
+    Ok(match value {
+        LuaValue::String(s) => Value::Str(Sds::from_vec(s.as_bytes().to_vec())),
+        LuaValue::Integer(i) => Value::Int(i),
+        LuaValue::Number(n) => Value::Int(n as i64),
+        LuaValue::Boolean(true) => Value::Int(1),
+        LuaValue::Table(table) => {
+            if let LuaValue::String(err) = table.raw_get::<LuaValue>("err")? {
+                return Err(LuaExecutionError::ScriptError(
+                    err.to_string_lossy().to_string(),
+                ));
+            }
+            if let LuaValue::String(ok) = table.raw_get::<LuaValue>("ok")? {
+                return Ok(Value::Str(Sds::from_vec(ok.as_bytes().to_vec())));
+            }
+            let mut items = Vec::new();
+            for i in 1.. {
+                match table.raw_get::<LuaValue>(i)? {
+                    LuaValue::Nil => break,
+                    item => items.push(lua_to_reply(item)?),
+                }
+            }
+            Value::Array(items)
+        }
+        LuaValue::UserData(ud) => match ud.borrow::<Sds>() {
+            Ok(sds) => Value::Str(sds.clone()),
+            Err(_) => Value::Null,
+        },
+        _ => Value::Null,
+    })
+}
+
+/// Флаги `redis.register_function`, допустимые для функций.
+const FUNCTION_FLAGS: [&str; 5] = [
+    "no-writes",
+    "allow-oom",
+    "allow-stale",
+    "no-cluster",
+    "allow-cross-slot-keys",
+];
+
+/// Разбирает аргументы `redis.register_function`: `(name, callback)` или
+/// таблицу `{ function_name = ..., callback = ..., flags = { ... } }`.
+///
+/// # Возвращает
+/// Имя функции, её тело и признак флага `no-writes`.
+fn register_function_args(args: MultiValue) -> LuaResult<(String, Function, bool)> {
+    let invalid = |msg: &str| LuaError::RuntimeError(msg.to_string());
+    let mut args = args.into_iter();
+    let (name, callback, flags) = match (args.next(), args.next(), args.next()) {
+        (Some(LuaValue::String(name)), Some(LuaValue::Function(callback)), None) => {
+            (name.to_str()?.to_string(), callback, None)
+        }
+        (Some(LuaValue::Table(spec)), None, None) => {
+            let name =
+                match spec.raw_get::<LuaValue>("function_name")? {
+                    LuaValue::String(name) => name.to_str()?.to_string(),
+                    _ => return Err(invalid(
+                        "function_name argument given to redis.register_function must be a string",
+                    )),
+                };
+            let callback = match spec.raw_get::<LuaValue>("callback")? {
+                LuaValue::Function(callback) => callback,
+                _ => {
+                    return Err(invalid(
+                        "callback argument given to redis.register_function must be a function",
+                    ))
+                }
+            };
+            match spec.raw_get::<LuaValue>("flags")? {
+                LuaValue::Nil => (name, callback, None),
+                LuaValue::Table(flags) => (name, callback, Some(flags)),
+                _ => return Err(invalid("flags argument to redis.register_function must be a table representing function flags")),
+            }
+        }
+        _ => {
+            return Err(invalid(
+                "wrong number of arguments to redis.register_function",
+            ))
+        }
+    };
+
+    if !is_valid_function_name(&name) {
+        return Err(invalid(
+            "Function names can only contain letters, numbers, or underscores(_) and must be at least one character long",
+        ));
+    }
+    let mut no_writes = false;
+    if let Some(flags) = flags {
+        for flag in flags.sequence_values::<LuaString>() {
+            let flag = flag?;
+            let flag = flag.to_str()?;
+            if !FUNCTION_FLAGS.contains(&&*flag) {
+                return Err(invalid("unknown flag given"));
+            }
+            no_writes |= &*flag == "no-writes";
+        }
+    }
+    Ok((name, callback, no_writes))
+}
+
+/// Имена библиотек и функций: непустые, из латинских букв, цифр и `_`.
+fn is_valid_function_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Возвращает сообщение исходной ошибки, сняв обёртки `CallbackError`.
+fn root_cause_message(err: &LuaError) -> String {
+    match err {
+        LuaError::CallbackError { cause, .. } => root_cause_message(cause),
+        LuaError::RuntimeError(msg) => msg.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Вычисляет SHA1 и возвращает его в виде 40 шестнадцатеричных символов в
+/// нижнем регистре (идентификатор скрипта, как в Redis).
+fn sha1_hex(data: &[u8]) -> String {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (hi, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *hi = hi.wrapping_add(v);
+        }
+    }
+
+    h.iter().fold(String::with_capacity(40), |mut out, word| {
+        let _ = write!(out, "{word:08x}");
+        out
+    })
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -657,7 +1489,7 @@ mod tests {
         let result = engine.eval(script, vec![]).unwrap();
 
         if let Value::List(items) = result {
-            let vec: Vec<_> = items.iter().cloned().collect(); // Преобразуем QuickList → Vec
+            let vec: Vec<_> = items.iter().map(|s| s.into_owned()).collect(); // Преобразуем QuickList → Vec
             assert_eq!(vec.len(), 2);
             assert_eq!(vec[0], Sds::from_str("first"));
             assert_eq!(vec[1], Sds::from_str("second"));
@@ -742,4 +1574,131 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("test error"));
     }
+
+    fn sds(items: &[&str]) -> Vec<Sds> {
+        items.iter().map(|s| Sds::from_str(s)).collect()
+    }
+
+    /// Тест проверяет SHA1 на известных векторах.
+    #[test]
+    fn test_sha1_hex() {
+        assert_eq!(sha1_hex(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            sha1_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+
+    /// Тест проверяет кэш скриптов, таблицы KEYS/ARGV и redis.call.
+    #[test]
+    fn test_eval_script_keys_argv_and_call() {
+        let mut engine = LuaEngine::new(LuaConfig::for_scripts()).unwrap();
+        let sha = engine
+            .load_script("return { KEYS[1], ARGV[2], redis.call('ECHO', ARGV[1]) }")
+            .unwrap();
+        assert_eq!(sha.len(), 40);
+        assert!(engine.has_script(&sha.to_uppercase()));
+
+        let mut calls = Vec::new();
+        let mut call = |args: &[Sds]| {
+            calls.push(args.to_vec());
+            Ok(Value::Str(args[1].clone()))
+        };
+        let result = engine
+            .eval_script(&sha, &sds(&["k"]), &sds(&["a", "b"]), &mut call)
+            .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![
+                Value::Str(Sds::from_str("k")),
+                Value::Str(Sds::from_str("b")),
+                Value::Str(Sds::from_str("a")),
+            ])
+        );
+        assert!(matches!(
+            engine.eval_script("0000", &[], &[], &mut call),
+            Err(LuaExecutionError::NoScript)
+        ));
+        assert_eq!(calls, vec![sds(&["ECHO", "a"])]);
+    }
+
+    /// Тест проверяет различие redis.call и redis.pcall при ошибке команды.
+    #[test]
+    fn test_eval_script_call_errors() {
+        let mut engine = LuaEngine::new(LuaConfig::for_scripts()).unwrap();
+        let mut call =
+            |_: &[Sds]| -> Result<Value, StoreError> { Err(StoreError::WrongType("bad".into())) };
+
+        let sha = engine
+            .load_script("return redis.pcall('GET', 'k')")
+            .unwrap();
+        let err = engine.eval_script(&sha, &[], &[], &mut call).unwrap_err();
+        assert!(err.to_string().contains("bad"));
+
+        let sha = engine
+            .load_script("local r = redis.pcall('GET', 'k'); return type(r.err)")
+            .unwrap();
+        assert_eq!(
+            engine.eval_script(&sha, &[], &[], &mut call).unwrap(),
+            Value::Str(Sds::from_str("string"))
+        );
+
+        let sha = engine
+            .load_script("redis.call('GET', 'k'); return 1")
+            .unwrap();
+        assert!(matches!(
+            engine.eval_script(&sha, &[], &[], &mut call),
+            Err(LuaExecutionError::ScriptError(_))
+        ));
+    }
+
+    /// Тест проверяет песочницу: запрет глобальных переменных и загрузки
+    /// кода, отсутствие библиотек os/io.
+    #[test]
+    fn test_eval_script_sandbox() {
+        let mut engine = LuaEngine::new(LuaConfig::for_scripts()).unwrap();
+        let mut call = |_: &[Sds]| Ok(Value::Null);
+        for script in [
+            "x = 1",
+            "return os.time()",
+            "return io",
+            "return load('return 1')()",
+        ] {
+            let sha = engine.load_script(script).unwrap();
+            let result = engine.eval_script(&sha, &[], &[], &mut call);
+            assert!(
+                matches!(result, Err(_) | Ok(Value::Null)),
+                "{script}: {result:?}"
+            );
+        }
+        let sha = engine
+            .load_script("return redis.status_reply('PONG')")
+            .unwrap();
+        assert_eq!(
+            engine.eval_script(&sha, &[], &[], &mut call).unwrap(),
+            Value::Str(Sds::from_str("PONG"))
+        );
+    }
+
+    /// Тест проверяет прерывание скрипта по лимиту времени.
+    #[test]
+    fn test_eval_script_timeout() {
+        let mut engine = LuaEngine::new(LuaConfig::for_scripts()).unwrap();
+        engine.set_time_limit(Duration::from_millis(50));
+        let mut call = |_: &[Sds]| Ok(Value::Null);
+
+        let sha = engine.load_script("while true do end").unwrap();
+        assert!(matches!(
+            engine.eval_script(&sha, &[], &[], &mut call),
+            Err(LuaExecutionError::Timeout)
+        ));
+
+        // После таймаута движок пригоден для следующих скриптов.
+        let sha = engine.load_script("return 7").unwrap();
+        assert_eq!(
+            engine.eval_script(&sha, &[], &[], &mut call).unwrap(),
+            Value::Int(7)
+        );
+    }
 }
@@ -1,44 +1,333 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::HashSet,
+    future::Future,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use rand::{seq::IteratorRandom, thread_rng};
 use zumic_error::SessionError;
 
 use crate::{
     auth::session::{SessionData, SessionId},
-    engine::SessionStorage,
-    GeoPoint, GeoSet, Sds, Storage, StoreError, StoreResult, Value,
+    database::{zdiff, zinter, zset_from_entries, zset_pop, zunion},
+    engine::{lfu_log_incr, ready_storage_methods, SessionStorage, LFU_INIT_VAL},
+    glob_match, metrics, Dict, EvictionPolicy, ExpireMap, FlushMode, GeoAddOptions, GeoPoint,
+    GeoSearchParams, GeoSet, GetExExpiry, Hll, KeyspaceEvent, KeyspaceNotifier, ListDir, QuickList,
+    Sds, SmartHash, Storage, StoreError, StoreResult, StreamNotifiers, Value, ValueType,
+    ZCombineOptions, ZPopDir, ZSetEntries,
 };
 
+/// Число логических баз данных, создаваемых [`InMemoryStore::new`].
+pub const DEFAULT_DATABASES: usize = 16;
+
+/// Сколько ключей с TTL проверяется за один шаг активного удаления.
+pub const ACTIVE_EXPIRE_SAMPLE: usize = 20;
+
+/// Максимум шагов активного удаления на базу за один цикл, чтобы цикл не
+/// занимал поток надолго при массовом истечении ключей.
+const ACTIVE_EXPIRE_MAX_ROUNDS: usize = 16;
+
+/// Логическая база данных: ключи, их TTL и гео-индексы.
+///
+/// Клонирование дёшево: клоны ссылаются на те же карты.
+#[derive(Debug, Clone, Default)]
+pub struct DbShard {
+    #[allow(clippy::arc_with_non_send_sync)] // NOTE: временно
+    pub data: Arc<DashMap<Sds, KeyEntry>>,
+    #[allow(clippy::arc_with_non_send_sync)] // NOTE: временно
+    pub expires: Arc<Mutex<ExpireMap>>,
+    #[allow(clippy::arc_with_non_send_sync)] // NOTE: временно
+    pub geo: Arc<DashMap<Sds, GeoSet>>,
+}
+
+/// Значение ключа вместе с метаданными доступа для LRU и LFU.
+///
+/// Разыменование (`Deref`/`DerefMut`) отмечает обращение: любое чтение или
+/// запись значения через хранилище обновляет время доступа и LFU-счётчик
+/// relaxed-записями в атомики, без блокировок.
+#[derive(Debug)]
+pub struct KeyEntry {
+    value: Value,
+    /// Время последнего обращения (unix-время в секундах).
+    accessed_at: AtomicU64,
+    /// Логарифмический счётчик частоты обращений (см. [`lfu_log_incr`]).
+    freq: AtomicU8,
+}
+
 /// Потокобезопасное in-memory хранилище ключ-значение.
+///
+/// Хранит набор логических баз данных; экземпляр работает с одной из них
+/// (по умолчанию с базой 0), а [`InMemoryStore::select`] возвращает
+/// представление другой базы поверх тех же данных.
 #[derive(Debug)]
 pub struct InMemoryStore {
     #[allow(clippy::arc_with_non_send_sync)] // NOTE: временно
-    pub data: Arc<DashMap<Sds, Value>>,
-    #[allow(clippy::arc_with_non_send_sync)] // NOTE: временно
-    geo: Arc<DashMap<Sds, GeoSet>>,
+    databases: Arc<RwLock<Vec<DbShard>>>,
+    /// Индекс базы, с которой работает этот экземпляр.
+    index: usize,
     #[allow(clippy::arc_with_non_send_sync)] // NOTE: временно
     sessions: Arc<DashMap<SessionId, SessionData>>,
+    /// Код политики вытеснения, общий для всех баз.
+    policy: Arc<AtomicU8>,
+    /// Уведомления о событиях пространства ключей, общие для всех баз.
+    notifier: Arc<KeyspaceNotifier>,
+    /// Ожидающие `XREAD BLOCK`, общие для всех баз: одноимённый поток
+    /// другой базы лишь будит ожидающего на повторное чтение.
+    stream_notifiers: Arc<StreamNotifiers>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
 
+impl KeyEntry {
+    /// Создаёт запись, обращение к которой произошло сейчас.
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            accessed_at: AtomicU64::new(unix_secs()),
+            freq: AtomicU8::new(LFU_INIT_VAL),
+        }
+    }
+
+    /// Отмечает обращение к ключу (`TOUCH`): обновляет время доступа и
+    /// вероятностно увеличивает LFU-счётчик.
+    pub fn touch(&self) {
+        self.accessed_at.store(unix_secs(), Ordering::Relaxed);
+        let freq = self.freq.load(Ordering::Relaxed);
+        let next = lfu_log_incr(freq);
+        if next != freq {
+            self.freq.store(next, Ordering::Relaxed);
+        }
+    }
+
+    /// Возвращает LFU-счётчик ключа, не отмечая обращение.
+    pub fn freq(&self) -> u8 {
+        self.freq.load(Ordering::Relaxed)
+    }
+
+    /// Уменьшает LFU-счётчик на единицу (периодическое затухание).
+    pub fn decay(&self) {
+        let freq = self.freq.load(Ordering::Relaxed);
+        if freq > 0 {
+            self.freq.store(freq - 1, Ordering::Relaxed);
+        }
+    }
+
+    /// Возвращает время последнего обращения в unix-секундах, не отмечая
+    /// обращение.
+    pub fn accessed_at(&self) -> u64 {
+        self.accessed_at.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает значение, не отмечая обращение (для обхода базы, например
+    /// при сохранении снимка).
+    pub fn peek(&self) -> &Value {
+        &self.value
+    }
+
+    /// Извлекает значение.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+}
+
 impl InMemoryStore {
-    /// Создаёт новый, пустой `InMemoryStore`.
+    /// Создаёт новый, пустой `InMemoryStore` с [`DEFAULT_DATABASES`]
+    /// логическими базами.
     /// # Возвращает
-    /// - новый экземпляр `InMemoryStore` с инициализированными
-    /// - in-memory хранилищами данных и гео-индексов
+    /// - новый экземпляр `InMemoryStore`, работающий с базой 0
     pub fn new() -> Self {
+        Self::with_databases(DEFAULT_DATABASES)
+    }
+
+    /// Создаёт пустое хранилище с `count` логическими базами (не меньше
+    /// одной).
+    pub fn with_databases(count: usize) -> Self {
+        let databases = (0..count.max(1)).map(|_| DbShard::default()).collect();
         Self {
             #[allow(clippy::arc_with_non_send_sync)]
-            data: Arc::new(DashMap::new()),
-            #[allow(clippy::arc_with_non_send_sync)]
-            geo: Arc::new(DashMap::new()),
+            databases: Arc::new(RwLock::new(databases)),
+            index: 0,
             #[allow(clippy::arc_with_non_send_sync)]
             sessions: Arc::new(DashMap::new()),
+            policy: Arc::new(AtomicU8::new(EvictionPolicy::default().code())),
+            notifier: Arc::new(KeyspaceNotifier::new()),
+            stream_notifiers: Arc::new(StreamNotifiers::new()),
+        }
+    }
+
+    /// Возвращает представление базы `index` поверх тех же данных.
+    ///
+    /// # Возвращает
+    /// - `Ok(InMemoryStore)` — экземпляр, работающий с базой `index`
+    /// - `Err(StoreError::InvalidArgument)` — если такой базы нет
+    pub fn select(
+        &self,
+        index: usize,
+    ) -> StoreResult<Self> {
+        if index >= self.databases() {
+            return Err(db_out_of_range());
+        }
+        Ok(Self {
+            databases: self.databases.clone(),
+            index,
+            sessions: self.sessions.clone(),
+            policy: self.policy.clone(),
+            notifier: self.notifier.clone(),
+            stream_notifiers: self.stream_notifiers.clone(),
+        })
+    }
+
+    /// Возвращает индекс базы, с которой работает экземпляр.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Возвращает число логических баз.
+    pub fn databases(&self) -> usize {
+        self.databases.read().unwrap().len()
+    }
+
+    /// Возвращает уведомитель о событиях пространства ключей, общий для
+    /// всех баз хранилища.
+    pub fn keyspace_notifier(&self) -> &Arc<KeyspaceNotifier> {
+        &self.notifier
+    }
+
+    /// Возвращает реестр ожидающих новых записей потоков.
+    pub fn stream_notifiers(&self) -> &StreamNotifiers {
+        &self.stream_notifiers
+    }
+
+    /// Сообщает о событии `event` для ключа `key` текущей базы. Используется
+    /// командами, собранными поверх `get`/`set` (например, `LPUSH`).
+    pub fn notify_keyspace_event(
+        &self,
+        event: KeyspaceEvent,
+        key: &Sds,
+    ) {
+        self.notifier.notify(self.index, event, key.as_bytes());
+    }
+
+    /// Возвращает текущую политику вытеснения.
+    pub fn eviction_policy(&self) -> EvictionPolicy {
+        EvictionPolicy::from_code(self.policy.load(Ordering::Relaxed))
+    }
+
+    /// Уменьшает LFU-счётчики всех ключей во всех базах на единицу.
+    pub fn decay_lfu(&self) {
+        decay_databases(&self.databases);
+    }
+
+    /// Запускает фоновую задачу, уменьшающую LFU-счётчики каждые `period`.
+    /// Задача завершается, когда хранилище уничтожено.
+    pub fn spawn_lfu_decay(
+        &self,
+        period: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let databases = Arc::downgrade(&self.databases);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(databases) = databases.upgrade() else {
+                    break;
+                };
+                decay_databases(&databases);
+            }
+        })
+    }
+
+    /// Цикл активного удаления истёкших ключей во всех базах (как
+    /// `activeExpireCycle` в Redis): для каждой базы берётся выборка из
+    /// [`ACTIVE_EXPIRE_SAMPLE`] ключей с TTL, истёкшие удаляются, и шаг
+    /// повторяется, пока истёкших в выборке больше 25%.
+    ///
+    /// # Возвращает
+    /// Общее число удалённых ключей.
+    pub fn active_expire_cycle(&self) -> usize {
+        let shards = self.databases.read().unwrap().clone();
+        let mut removed = 0;
+        for (db, shard) in shards.iter().enumerate() {
+            for _ in 0..ACTIVE_EXPIRE_MAX_ROUNDS {
+                let (sampled, expired) = shard
+                    .expires
+                    .lock()
+                    .unwrap()
+                    .expired_sample(ACTIVE_EXPIRE_SAMPLE);
+                let expired_count = expired.len();
+                removed += self.remove_expired(shard, db, expired);
+                if expired_count * 4 <= sampled {
+                    break;
+                }
+            }
+        }
+        removed
+    }
+
+    /// Выбирает ключ текущей базы для вытеснения по политике: при LFU — с
+    /// наименьшим счётчиком, при LRU — с самым старым обращением (при
+    /// равенстве счётчиков — тоже). `volatile-*` рассматривают только ключи с
+    /// TTL.
+    ///
+    /// # Возвращает
+    /// - `None`, если политика `noeviction` или подходящих ключей нет
+    pub fn eviction_candidate(&self) -> Option<Sds> {
+        let policy = self.eviction_policy();
+        if policy == EvictionPolicy::NoEviction {
+            return None;
         }
+        self.purge_expired();
+        let expires = self.expires();
+        let expires = expires.lock().unwrap();
+        self.data()
+            .iter()
+            .filter(|entry| !policy.is_volatile() || expires.ttl(entry.key().as_bytes()).is_some())
+            .min_by_key(|entry| {
+                let freq = if policy.is_lfu() { entry.freq() } else { 0 };
+                (freq, entry.accessed_at())
+            })
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Вытесняет один ключ текущей базы по политике (см.
+    /// [`InMemoryStore::eviction_candidate`]).
+    ///
+    /// # Возвращает
+    /// - `Some(key)` — вытесненный ключ
+    pub fn evict(&self) -> Option<Sds> {
+        let key = self.eviction_candidate()?;
+        self.data().remove(&key);
+        self.forget_expiry(&key);
+        self.notify_keyspace_event(KeyspaceEvent::Evicted, &key);
+        metrics::registry().record_evicted(1);
+        Some(key)
+    }
+
+    /// Возвращает текущую базу. Ссылки на её карты остаются валидными и
+    /// после `SWAPDB`, но относятся уже к другому индексу.
+    fn shard(&self) -> DbShard {
+        self.databases.read().unwrap()[self.index].clone()
+    }
+
+    fn data(&self) -> Arc<DashMap<Sds, KeyEntry>> {
+        self.shard().data
+    }
+
+    fn expires(&self) -> Arc<Mutex<ExpireMap>> {
+        self.shard().expires
+    }
+
+    fn geo(&self) -> Arc<DashMap<Sds, GeoSet>> {
+        self.shard().geo
     }
 
     /// Возвращает итератор по всем ключам и значениям в хранилище.
@@ -47,9 +336,167 @@ impl InMemoryStore {
     /// - итератор, выдающий клонированные пары `(Sds, Value)` для всех
     ///   элементов, находящихся в хранилище
     pub fn iter(&self) -> impl Iterator<Item = (Sds, Value)> + '_ {
-        self.data
+        let entries: Vec<(Sds, Value)> = self
+            .data()
             .iter()
-            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .map(|entry| (entry.key().clone(), entry.value().peek().clone()))
+            .collect();
+        entries.into_iter()
+    }
+
+    /// Удаляет ключи с истёкшим TTL. Вызывается при каждом обращении к
+    /// ключам (ленивое удаление).
+    fn purge_expired(&self) {
+        let shard = self.shard();
+        let expired = shard.expires.lock().unwrap().purge();
+        self.remove_expired(&shard, self.index, expired);
+    }
+
+    /// Удаляет из базы `db` ключи, чей TTL истёк (они уже убраны из
+    /// `ExpireMap`), сообщает о событии `expired` и учитывает их в метриках.
+    ///
+    /// # Возвращает
+    /// Число действительно удалённых ключей.
+    fn remove_expired(
+        &self,
+        shard: &DbShard,
+        db: usize,
+        expired: Vec<Vec<u8>>,
+    ) -> usize {
+        let mut removed = 0;
+        for key in expired {
+            if shard.data.remove(key.as_slice()).is_some() {
+                self.notifier.notify(db, KeyspaceEvent::Expired, &key);
+                removed += 1;
+            }
+        }
+        self.notifier.record_expired(removed);
+        metrics::registry().record_expired(removed as u64);
+        removed
+    }
+
+    /// Снимает TTL с ключа. Возвращает `true`, если TTL был установлен.
+    fn forget_expiry(
+        &self,
+        key: &Sds,
+    ) -> bool {
+        self.expires().lock().unwrap().remove(key.as_bytes())
+    }
+
+    /// Копирует TTL ключа `from` на ключ `to` (TTL `to` при этом теряется).
+    fn copy_expiry(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) {
+        self.copy_expiry_to(from, &self.shard(), to);
+    }
+
+    /// Копирует TTL ключа `from` текущей базы на ключ `to` базы `target`
+    /// (TTL `to` при этом теряется).
+    fn copy_expiry_to(
+        &self,
+        from: &Sds,
+        target: &DbShard,
+        to: &Sds,
+    ) {
+        let ttl = self.expires().lock().unwrap().ttl(from.as_bytes());
+        let mut expires = target.expires.lock().unwrap();
+        expires.remove(to.as_bytes());
+        if let Some(ttl) = ttl {
+            expires.set(to.as_bytes().to_vec(), ttl);
+        }
+    }
+
+    /// Переносит TTL ключа `from` на ключ `to` (TTL `to` при этом теряется).
+    fn move_expiry(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) {
+        self.copy_expiry(from, to);
+        self.forget_expiry(from);
+    }
+
+    /// Читает снимок множеств `keys`; отсутствующему ключу соответствует
+    /// `None`. Ключ другого типа даёт `WrongType`.
+    fn read_sets(
+        &self,
+        keys: &[&Sds],
+        cmd: &str,
+    ) -> StoreResult<Vec<Option<HashSet<Sds>>>> {
+        keys.iter()
+            .map(|key| match self.data().get(*key) {
+                Some(entry) => match &**entry {
+                    Value::Set(set) => Ok(Some(set.clone())),
+                    _ => Err(StoreError::WrongType(format!("{cmd}: key is not a set"))),
+                },
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Читает снимок упорядоченных множеств `keys`; отсутствующему ключу
+    /// соответствует `None`. Ключ другого типа даёт `WrongType`.
+    fn read_zsets(
+        &self,
+        keys: &[&Sds],
+        cmd: &str,
+    ) -> StoreResult<Vec<Option<Dict<Sds, f64>>>> {
+        keys.iter()
+            .map(|key| match self.data().get(*key) {
+                Some(entry) => match &**entry {
+                    Value::ZSet { dict, .. } => Ok(Some(dict.clone())),
+                    _ => Err(StoreError::WrongType(format!(
+                        "{cmd}: key is not a sorted set"
+                    ))),
+                },
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Записывает результат операции над коллекциями в `dst`: при `len == 0`
+    /// ключ удаляется. TTL приёмника сбрасывается.
+    ///
+    /// # Возвращает:
+    /// - мощность записанной коллекции
+    fn store_result(
+        &self,
+        dst: &Sds,
+        value: Value,
+        len: usize,
+    ) -> usize {
+        if len == 0 {
+            self.data().remove(dst);
+        } else {
+            self.data().insert(dst.clone(), value.into());
+        }
+        self.forget_expiry(dst);
+        len
+    }
+
+    /// Применяет `f` к хешу по ключу `key` под блокировкой записи, создавая
+    /// пустой хеш для отсутствующего ключа. Новый хеш сохраняется только при
+    /// успешном завершении `f`; TTL существующего ключа не меняется.
+    fn update_hash<R>(
+        &self,
+        key: &Sds,
+        cmd: &str,
+        f: impl FnOnce(&mut SmartHash) -> StoreResult<R>,
+    ) -> StoreResult<R> {
+        match self.data().entry(key.clone()) {
+            Entry::Occupied(mut entry) => match &mut **entry.get_mut() {
+                Value::Hash(hash) => f(hash),
+                _ => Err(StoreError::WrongType(format!("{cmd}: key is not a hash"))),
+            },
+            Entry::Vacant(entry) => {
+                let mut hash = SmartHash::new();
+                let result = f(&mut hash)?;
+                entry.insert(Value::Hash(hash).into());
+                Ok(result)
+            }
+        }
     }
 }
 
@@ -63,10 +510,30 @@ impl Storage for InMemoryStore {
         key: &Sds,
         value: Value,
     ) -> StoreResult<()> {
-        self.data.insert(key.clone(), value);
+        self.purge_expired();
+        self.data().insert(key.clone(), value.into());
         Ok(())
     }
 
+    /// Устанавливает значение по ключу и возвращает прежнее.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Some(Value))` — прежнее значение, если ключ существовал
+    /// - `Ok(None)`, если ключ отсутствовал
+    fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>> {
+        self.purge_expired();
+        let previous = self
+            .data()
+            .insert(key.clone(), value.into())
+            .map(KeyEntry::into_value);
+        self.notify_keyspace_event(KeyspaceEvent::Set, key);
+        Ok(previous)
+    }
+
     /// Получает значение по указанному ключу.
     ///
     /// # Возвращает:
@@ -76,7 +543,13 @@ impl Storage for InMemoryStore {
         &self,
         key: &Sds,
     ) -> StoreResult<Option<Value>> {
-        Ok(self.data.get(key).map(|entry| entry.value().clone()))
+        self.purge_expired();
+        let value = self
+            .data()
+            .get(key)
+            .map(|entry| Value::clone(entry.value()));
+        metrics::registry().record_keyspace_lookup(value.is_some());
+        Ok(value)
     }
 
     /// Удаляет значение по указанному ключу.
@@ -88,7 +561,13 @@ impl Storage for InMemoryStore {
         &self,
         key: &Sds,
     ) -> StoreResult<bool> {
-        Ok(self.data.remove(key).is_some())
+        self.purge_expired();
+        let existed = self.data().remove(key).is_some();
+        self.forget_expiry(key);
+        if existed {
+            self.notify_keyspace_event(KeyspaceEvent::Del, key);
+        }
+        Ok(existed)
     }
 
     /// Массово устанавливает значения по ключам.
@@ -99,8 +578,10 @@ impl Storage for InMemoryStore {
         &self,
         entries: Vec<(&Sds, Value)>,
     ) -> StoreResult<()> {
+        self.purge_expired();
         for (key, value) in entries {
-            self.data.insert(key.clone(), value);
+            self.data().insert(key.clone(), value.into());
+            self.notify_keyspace_event(KeyspaceEvent::Set, key);
         }
         Ok(())
     }
@@ -114,10 +595,13 @@ impl Storage for InMemoryStore {
         &self,
         keys: &[&Sds],
     ) -> StoreResult<Vec<Option<Value>>> {
+        self.purge_expired();
         let mut result = Vec::with_capacity(keys.len());
 
         for &key in keys {
-            result.push(self.data.get(key).map(|e| e.value().clone()));
+            let value = self.data().get(key).map(|e| Value::clone(e.value()));
+            metrics::registry().record_keyspace_lookup(value.is_some());
+            result.push(value);
         }
 
         Ok(result)
@@ -133,8 +617,11 @@ impl Storage for InMemoryStore {
         from: &Sds,
         to: &Sds,
     ) -> StoreResult<()> {
-        if let Some((_, value)) = self.data.remove(from) {
-            self.data.insert(to.clone(), value);
+        self.purge_expired();
+        if let Some((_, value)) = self.data().remove(from) {
+            self.data().insert(to.clone(), value);
+            self.move_expiry(from, to);
+            self.notify_keyspace_event(KeyspaceEvent::Rename, from);
             Ok(())
         } else {
             Err(StoreError::KeyNotFound)
@@ -152,43 +639,107 @@ impl Storage for InMemoryStore {
         from: &Sds,
         to: &Sds,
     ) -> StoreResult<bool> {
-        if self.data.contains_key(to) {
+        self.purge_expired();
+        if self.data().contains_key(to) {
             return Ok(false);
         }
-        if let Some((_, value)) = self.data.remove(from) {
-            self.data.insert(to.clone(), value);
+        if let Some((_, value)) = self.data().remove(from) {
+            self.data().insert(to.clone(), value);
+            self.move_expiry(from, to);
+            self.notify_keyspace_event(KeyspaceEvent::Rename, from);
             Ok(true)
         } else {
             Err(StoreError::KeyNotFound)
         }
     }
 
-    /// Очищает всё содержимое хранилища.
+    /// Очищает текущую логическую базу: ключи, TTL и гео-индексы.
     ///
     /// # Возвращает:
     /// - `Ok(())` после успешной очистки
     fn flushdb(&self) -> StoreResult<()> {
-        self.data.clear();
+        let shard = self.shard();
+        shard.data.clear();
+        shard.expires.lock().unwrap().clear();
+        shard.geo.clear();
+        Ok(())
+    }
+
+    /// Очищает текущую логическую базу. В режиме `Async` база под
+    /// блокировкой записи заменяется пустой, а старые карты освобождаются в
+    /// фоне; операции, успевшие взять ссылку на старую базу, завершаются
+    /// на ней.
+    ///
+    /// # Возвращает:
+    /// - `Ok(())` сразу после замены базы
+    fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        match mode {
+            FlushMode::Sync => self.flushdb(),
+            FlushMode::Async => {
+                let old = std::mem::take(&mut self.databases.write().unwrap()[self.index]);
+                lazy_free(vec![old]);
+                Ok(())
+            }
+        }
+    }
+
+    /// Очищает все логические базы (см. [`Storage::flushdb_mode`]).
+    ///
+    /// # Возвращает:
+    /// - `Ok(())` после очистки (или замены баз в режиме `Async`)
+    fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        match mode {
+            FlushMode::Sync => {
+                let shards = self.databases.read().unwrap().clone();
+                for shard in shards {
+                    shard.data.clear();
+                    shard.expires.lock().unwrap().clear();
+                    shard.geo.clear();
+                }
+            }
+            FlushMode::Async => {
+                let old = self
+                    .databases
+                    .write()
+                    .unwrap()
+                    .iter_mut()
+                    .map(std::mem::take)
+                    .collect();
+                lazy_free(old);
+            }
+        }
         Ok(())
     }
 
     /// Добавляет участника с координатами в гео-набор по ключу.
     ///
     /// # Возвращает:
-    /// - `Ok(true)`, если участник добавлен впервые
-    /// - `Ok(false)`, если участник уже существовал
+    /// - `Ok(true)`, если участник добавлен впервые или, при `opts.ch`, его
+    ///   координаты изменились
+    /// - `Ok(false)`, если участник не добавлен и не изменён (в том числе из-за
+    ///   `NX`/`XX`)
     fn geo_add(
         &self,
         key: &Sds,
         lon: f64,
         lat: f64,
         member: &Sds,
+        opts: GeoAddOptions,
     ) -> StoreResult<bool> {
-        let mut entry = self.geo.entry(key.clone()).or_default();
         let member_str = member.as_str()?;
-        let existed = entry.get(member_str).is_some();
-        entry.add(member.to_string(), lon, lat);
-        Ok(!existed)
+        // XX не создаёт новый гео-набор
+        if opts.xx && !self.geo().contains_key(key) {
+            return Ok(false);
+        }
+        let geo = self.geo();
+        let mut entry = geo.entry(key.clone()).or_default();
+        Ok(entry.add(member_str.to_string(), lon, lat, opts))
     }
 
     /// Вычисляет расстояние между двумя участниками в гео-наборе.
@@ -203,7 +754,8 @@ impl Storage for InMemoryStore {
         member2: &Sds,
         unit: &str,
     ) -> StoreResult<Option<f64>> {
-        let set = match self.geo.get(key) {
+        let geo = self.geo();
+        let set = match geo.get(key) {
             Some(s) => s,
             None => return Ok(None),
         };
@@ -211,7 +763,7 @@ impl Storage for InMemoryStore {
         let converted = meters.map(|d| match unit {
             "km" => d / 1000.0,
             "mi" => d / 1609.344,
-            "ft" => d / 3.28084,
+            "ft" => d * 3.28084,
             _ => d,
         });
         Ok(converted)
@@ -227,7 +779,8 @@ impl Storage for InMemoryStore {
         key: &Sds,
         member: &Sds,
     ) -> StoreResult<Option<GeoPoint>> {
-        let set = match self.geo.get(key) {
+        let geo = self.geo();
+        let set = match geo.get(key) {
             Some(s) => s,
             None => return Ok(None),
         };
@@ -248,7 +801,8 @@ impl Storage for InMemoryStore {
         radius: f64,
         unit: &str,
     ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
-        let mut set = match self.geo.get_mut(key) {
+        let geo = self.geo();
+        let set = match geo.get(key) {
             Some(s) => s,
             None => return Ok(vec![]),
         };
@@ -287,7 +841,8 @@ impl Storage for InMemoryStore {
         radius: f64,
         unit: &str,
     ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
-        let set = match self.geo.get(key) {
+        let geo = self.geo();
+        let set = match geo.get(key) {
             Some(s) => s,
             None => return Ok(vec![]),
         };
@@ -299,6 +854,23 @@ impl Storage for InMemoryStore {
         self.geo_radius(key, center.lon, center.lat, radius, unit)
     }
 
+    /// Выполняет GEOSEARCH по гео-набору `key`.
+    ///
+    /// # Возвращает:
+    /// - список кортежей `(member, distance, GeoPoint)`, пустой для
+    ///   отсутствующего ключа
+    /// - `Err(KeyNotFound)`, если участник `FROMMEMBER` отсутствует
+    fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        match self.geo().get(key) {
+            Some(set) => set.search(params).ok_or(StoreError::KeyNotFound),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Добавляет элементы в множество.
     ///
     /// # Возвращает:
@@ -308,9 +880,10 @@ impl Storage for InMemoryStore {
         key: &Sds,
         members: &[Sds],
     ) -> StoreResult<usize> {
+        self.purge_expired();
         // Попробуем получить мутируемый доступ, если ключ уже существует
-        if let Some(mut entry) = self.data.get_mut(key) {
-            match &mut *entry {
+        if let Some(mut entry) = self.data().get_mut(key) {
+            match &mut **entry {
                 Value::Set(set) => {
                     let mut added = 0usize;
                     for m in members {
@@ -318,6 +891,10 @@ impl Storage for InMemoryStore {
                             added += 1;
                         }
                     }
+                    drop(entry);
+                    if added > 0 {
+                        self.notify_keyspace_event(KeyspaceEvent::SAdd, key);
+                    }
                     return Ok(added);
                 }
                 _ => return Err(StoreError::WrongType("SADD: key is not a set".into())),
@@ -332,185 +909,1100 @@ impl Storage for InMemoryStore {
                 added += 1;
             }
         }
-        self.data.insert(key.clone(), Value::Set(set));
+        self.data().insert(key.clone(), Value::Set(set).into());
+        self.notify_keyspace_event(KeyspaceEvent::SAdd, key);
         Ok(added)
     }
 
     /// Возвращает все элементы множества.
     ///
     /// # Возвращает:
-    /// - вектор элементов множества
-    fn smembers(
+    /// - вектор элементов множества
+    fn smembers(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Set(set) => Ok(set.iter().cloned().collect()),
+                _ => Err(StoreError::WrongType("SMEMBERS: key is not a set".into())),
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Возвращает размер множества.
+    ///
+    /// # Возвращает:
+    /// - количество элементов множества
+    fn scard(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Set(set) => Ok(set.len()),
+                _ => Err(StoreError::WrongType("SCARD: key is not a set".into())),
+            },
+            None => Ok(0),
+        }
+    }
+
+    /// Проверяет, является ли элемент членом множества.
+    ///
+    /// # Возвращает:
+    /// - `true`, если элемент присутствует
+    /// - `false`, если отсутствует
+    fn sismember(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Set(set) => Ok(set.contains(member)),
+                _ => Err(StoreError::WrongType("SISMEMBER: key is not a set".into())),
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Проверяет принадлежность нескольких элементов множеству.
+    ///
+    /// # Возвращает:
+    /// - флаг для каждого запрошенного элемента (для отсутствующего ключа — все
+    ///   `false`)
+    fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Set(set) => Ok(members.iter().map(|m| set.contains(*m)).collect()),
+                _ => Err(StoreError::WrongType("SMISMEMBER: key is not a set".into())),
+            },
+            None => Ok(vec![false; members.len()]),
+        }
+    }
+
+    /// Удаляет элементы из множества.
+    ///
+    /// # Возвращает:
+    /// - количество реально удалённых элементов
+    fn srem(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let mut removed = 0usize;
+        let mut remove_key = false;
+
+        if let Some(mut entry) = self.data().get_mut(key) {
+            match &mut **entry {
+                Value::Set(set) => {
+                    for m in members {
+                        if set.remove(m) {
+                            removed += 1;
+                        }
+                    }
+                    if set.is_empty() {
+                        // не удаляем сразу — выставляем флаг и удалим после выхода из блока
+                        remove_key = true;
+                    }
+                }
+                _ => return Err(StoreError::WrongType("SREM: key is not a set".into())),
+            }
+            // entry dropped here (end of this scope)
+        } else {
+            return Ok(0);
+        }
+
+        if remove_key {
+            // безопасно удалить, так как entry уже вышел из области видимости
+            self.data().remove(key);
+            self.forget_expiry(key);
+        }
+
+        Ok(removed)
+    }
+
+    /// Возвращает случайные элементы множества.
+    ///
+    /// # Возвращает:
+    /// - вектор выбранных элементов (может быть пустым)
+    fn srandmember(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Set(set) => {
+                    let mut rng = thread_rng();
+                    if count == 1 {
+                        let opt = set.iter().cloned().choose(&mut rng);
+                        Ok(opt.into_iter().collect())
+                    } else if count > 1 {
+                        let cnt = count as usize;
+                        let chosen = set.iter().cloned().choose_multiple(&mut rng, cnt);
+                        Ok(chosen)
+                    } else {
+                        // count <= 0 semantics: возвращаем пустой вектор
+                        Ok(Vec::new())
+                    }
+                }
+                _ => Err(StoreError::WrongType(
+                    "SRANDMEMBER: key is not a set".into(),
+                )),
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Извлекает и удаляет элементы множества случайным образом.
+    ///
+    /// # Возвращает:
+    /// - вектор удалённых элементов
+    fn spop(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired();
+        let mut out = Vec::new();
+        let mut remove_key = false;
+
+        if let Some(mut entry) = self.data().get_mut(key) {
+            match &mut **entry {
+                Value::Set(set) => {
+                    let mut rng = thread_rng();
+                    let cnt = if count <= 0 { 1 } else { count as usize };
+                    for _ in 0..cnt {
+                        if let Some(item) = set.iter().cloned().choose(&mut rng) {
+                            set.remove(&item);
+                            out.push(item);
+                        } else {
+                            break;
+                        }
+                    }
+                    if set.is_empty() {
+                        remove_key = true;
+                    }
+                }
+                _ => return Err(StoreError::WrongType("SPOP: key is not a set".into())),
+            }
+            // entry dropped here
+        } else {
+            return Ok(Vec::new());
+        }
+
+        if remove_key {
+            self.data().remove(key);
+            self.forget_expiry(key);
+        }
+
+        Ok(out)
+    }
+
+    /// Перемещает элемент из одного множества в другое.
+    ///
+    /// # Возвращает:
+    /// - `true`, если элемент был найден в `src` и перемещён
+    fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        self.purge_expired();
+        let wrong_type = || StoreError::WrongType("SMOVE: key is not a set".into());
+
+        // Один и тот же ключ: достаточно проверить наличие элемента.
+        if src == dst {
+            return match self.data().get(src) {
+                Some(entry) => match &**entry {
+                    Value::Set(set) => Ok(set.contains(member)),
+                    _ => Err(wrong_type()),
+                },
+                None => Ok(false),
+            };
+        }
+
+        // Проверяем тип приёмника до изменения источника.
+        if let Some(entry) = self.data().get(dst) {
+            if !matches!(&**entry, Value::Set(_)) {
+                return Err(wrong_type());
+            }
+        }
+
+        let removed = match self.data().get_mut(src) {
+            Some(mut entry) => match &mut **entry {
+                Value::Set(set) => set.remove(member),
+                _ => return Err(wrong_type()),
+            },
+            None => false,
+        };
+        if !removed {
+            return Ok(false);
+        }
+        if self
+            .data()
+            .remove_if(
+                src,
+                |_, v| matches!(&**v, Value::Set(set) if set.is_empty()),
+            )
+            .is_some()
+        {
+            self.forget_expiry(src);
+        }
+
+        let data = self.data();
+        let mut entry = data
+            .entry(dst.clone())
+            .or_insert_with(|| Value::Set(HashSet::new()).into());
+        match &mut **entry.value_mut() {
+            Value::Set(set) => {
+                set.insert(member.clone());
+                Ok(true)
+            }
+            _ => Err(wrong_type()),
+        }
+    }
+
+    /// Считает мощность пересечения множеств.
+    ///
+    /// # Возвращает:
+    /// - число общих элементов, не больше `limit` (если он задан и не равен 0)
+    fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let limit = limit.filter(|&l| l > 0).unwrap_or(usize::MAX);
+
+        // Отсутствующий ключ делает пересечение пустым, но типы остальных
+        // ключей всё равно проверяются.
+        let Some(mut sets) = self
+            .read_sets(keys, "SINTERCARD")?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(0);
+        };
+
+        // Перебираем наименьшее множество и проверяем остальные.
+        sets.sort_by_key(|set| set.len());
+        let Some((probe, rest)) = sets.split_first() else {
+            return Ok(0);
+        };
+        let mut count = 0;
+        for member in probe {
+            if rest.iter().all(|set| set.contains(member)) {
+                count += 1;
+                if count >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Записывает пересечение множеств в `dst`.
+    ///
+    /// # Возвращает:
+    /// - мощность записанного множества
+    fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let result = match self
+            .read_sets(keys, "SINTERSTORE")?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(mut sets) => {
+                sets.sort_by_key(|set| set.len());
+                match sets.split_first() {
+                    Some((probe, rest)) => probe
+                        .iter()
+                        .filter(|member| rest.iter().all(|set| set.contains(*member)))
+                        .cloned()
+                        .collect(),
+                    None => HashSet::new(),
+                }
+            }
+            None => HashSet::new(),
+        };
+        let len = result.len();
+        Ok(self.store_result(dst, Value::Set(result), len))
+    }
+
+    /// Записывает объединение множеств в `dst`.
+    ///
+    /// # Возвращает:
+    /// - мощность записанного множества
+    fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let result: HashSet<Sds> = self
+            .read_sets(keys, "SUNIONSTORE")?
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+        let len = result.len();
+        Ok(self.store_result(dst, Value::Set(result), len))
+    }
+
+    /// Записывает разность первого множества и остальных в `dst`.
+    ///
+    /// # Возвращает:
+    /// - мощность записанного множества
+    fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let mut sets = self.read_sets(keys, "SDIFFSTORE")?.into_iter();
+        let mut result = sets.next().flatten().unwrap_or_default();
+        for set in sets.flatten() {
+            result.retain(|member| !set.contains(member));
+        }
+        let len = result.len();
+        Ok(self.store_result(dst, Value::Set(result), len))
+    }
+
+    /// Возвращает объединение упорядоченных множеств.
+    fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        self.purge_expired();
+        Ok(zunion(&self.read_zsets(keys, "ZUNION")?, options))
+    }
+
+    /// Возвращает пересечение упорядоченных множеств.
+    fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        self.purge_expired();
+        Ok(zinter(&self.read_zsets(keys, "ZINTER")?, options))
+    }
+
+    /// Возвращает разность упорядоченных множеств.
+    fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        self.purge_expired();
+        Ok(zdiff(&self.read_zsets(keys, "ZDIFF")?))
+    }
+
+    /// Записывает объединение упорядоченных множеств в `dst`.
+    fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let entries = zunion(&self.read_zsets(keys, "ZUNIONSTORE")?, options);
+        let len = entries.len();
+        Ok(self.store_result(dst, zset_from_entries(entries), len))
+    }
+
+    /// Записывает пересечение упорядоченных множеств в `dst`.
+    fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let entries = zinter(&self.read_zsets(keys, "ZINTERSTORE")?, options);
+        let len = entries.len();
+        Ok(self.store_result(dst, zset_from_entries(entries), len))
+    }
+
+    /// Записывает разность упорядоченных множеств в `dst`.
+    fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let entries = zdiff(&self.read_zsets(keys, "ZDIFFSTORE")?);
+        let len = entries.len();
+        Ok(self.store_result(dst, zset_from_entries(entries), len))
+    }
+
+    /// Извлекает элементы из первого непустого упорядоченного множества.
+    ///
+    /// # Возвращает:
+    /// - имя ключа и извлечённые пары `(member, score)`, либо `None`
+    fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>> {
+        self.purge_expired();
+        for key in keys {
+            let (popped, emptied) = match self.data().get_mut(*key) {
+                Some(mut entry) => match &mut **entry {
+                    Value::ZSet { dict, sorted } => {
+                        let popped = zset_pop(dict, sorted, count, dir);
+                        (popped, dict.is_empty())
+                    }
+                    _ => {
+                        return Err(StoreError::WrongType(
+                            "ZMPOP: key is not a sorted set".into(),
+                        ))
+                    }
+                },
+                None => continue,
+            };
+
+            if emptied {
+                self.data().remove(*key);
+                self.forget_expiry(key);
+            }
+            if !popped.is_empty() {
+                return Ok(Some(((*key).clone(), popped)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Возвращает score нескольких элементов упорядоченного множества.
+    ///
+    /// # Возвращает:
+    /// - score для каждого запрошенного элемента (`None`, если элемента нет)
+    fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::ZSet { dict, .. } => {
+                    Ok(members.iter().map(|m| dict.get(m).copied()).collect())
+                }
+                _ => Err(StoreError::WrongType(
+                    "ZMSCORE: key is not a sorted set".into(),
+                )),
+            },
+            None => Ok(vec![None; members.len()]),
+        }
+    }
+
+    /// Возвращает случайные поля хеша.
+    ///
+    /// # Возвращает:
+    /// - поля (и значения при `with_values`) плоским списком
+    fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Hash(hash) => hash.random_fields(count, with_values),
+                _ => Err(StoreError::InvalidType),
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Атомарно увеличивает целочисленное поле хеша.
+    ///
+    /// # Возвращает:
+    /// - новое значение поля
+    fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
+        self.purge_expired();
+        self.update_hash(key, "HINCRBY", |hash| hash.incr_by(field.clone(), delta))
+    }
+
+    /// Атомарно увеличивает поле хеша с плавающей точкой.
+    ///
+    /// # Возвращает:
+    /// - новое значение поля
+    fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
+        self.purge_expired();
+        self.update_hash(key, "HINCRBYFLOAT", |hash| {
+            hash.incr_by_float(field.clone(), delta)
+        })
+    }
+
+    /// Возвращает значения нескольких полей хеша.
+    ///
+    /// # Возвращает:
+    /// - значения в порядке запроса, `None` для отсутствующих полей
+    fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>> {
+        self.purge_expired();
+        match self.data().get(key) {
+            Some(entry) => match &**entry {
+                Value::Hash(hash) => Ok(hash.get_many(fields)),
+                _ => Err(StoreError::WrongType("HMGET: key is not a hash".into())),
+            },
+            None => Ok(vec![None; fields.len()]),
+        }
+    }
+
+    /// Оценивает память, занятую ключом и значением.
+    ///
+    /// # Возвращает:
+    /// - размер самого ключа и его буфера плюс оценку [`Value::memory_usage`],
+    ///   или `None`, если ключа нет
+    fn memory_usage(
+        &self,
+        key: &Sds,
+        samples: usize,
+    ) -> StoreResult<Option<usize>> {
+        self.purge_expired();
+        Ok(self.data().get(key).map(|entry| {
+            std::mem::size_of::<Sds>() + entry.key().heap_size() + entry.memory_usage(samples)
+        }))
+    }
+
+    /// Перемещает элемент между списками.
+    ///
+    /// # Возвращает:
+    /// - перемещённый элемент или `None`, если исходный список пуст
+    fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>> {
+        self.purge_expired();
+        // Один и тот же ключ: вращаем список под одной блокировкой.
+        if src == dst {
+            let data = self.data();
+            let Some(mut entry) = data.get_mut(src) else {
+                return Ok(None);
+            };
+            return match &mut **entry {
+                Value::List(list) => {
+                    let item = list.pop(src_dir);
+                    if let Some(item) = &item {
+                        list.push(dst_dir, item.clone());
+                    }
+                    Ok(item)
+                }
+                _ => Err(StoreError::WrongType("LMOVE: key is not a list".into())),
+            };
+        }
+
+        // Проверяем тип приёмника до изменения источника.
+        if let Some(entry) = self.data().get(dst) {
+            if !matches!(&**entry, Value::List(_)) {
+                return Err(StoreError::WrongType("LMOVE: key is not a list".into()));
+            }
+        }
+
+        let item = match self.data().get_mut(src) {
+            Some(mut entry) => match &mut **entry {
+                Value::List(list) => list.pop(src_dir),
+                _ => return Err(StoreError::WrongType("LMOVE: key is not a list".into())),
+            },
+            None => None,
+        };
+        let Some(item) = item else {
+            return Ok(None);
+        };
+        if self
+            .data()
+            .remove_if(
+                src,
+                |_, v| matches!(&**v, Value::List(list) if list.is_empty()),
+            )
+            .is_some()
+        {
+            self.forget_expiry(src);
+        }
+
+        let data = self.data();
+        let mut entry = data
+            .entry(dst.clone())
+            .or_insert_with(|| Value::List(QuickList::new(64)).into());
+        match &mut **entry.value_mut() {
+            Value::List(list) => {
+                list.push(dst_dir, item.clone());
+                Ok(Some(item))
+            }
+            _ => Err(StoreError::WrongType("LMOVE: key is not a list".into())),
+        }
+    }
+
+    /// Извлекает элементы из первого непустого списка.
+    ///
+    /// # Возвращает:
+    /// - имя ключа и извлечённые элементы, либо `None`
+    fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>> {
+        self.purge_expired();
+        for key in keys {
+            let popped: Vec<Sds> = match self.data().get_mut(*key) {
+                Some(mut entry) => match &mut **entry {
+                    Value::List(list) => (0..count).map_while(|_| list.pop(dir)).collect(),
+                    _ => return Err(StoreError::WrongType("LMPOP: key is not a list".into())),
+                },
+                None => continue,
+            };
+            if popped.is_empty() {
+                continue;
+            }
+
+            if self
+                .data()
+                .remove_if(
+                    *key,
+                    |_, v| matches!(&**v, Value::List(list) if list.is_empty()),
+                )
+                .is_some()
+            {
+                self.forget_expiry(key);
+            }
+            return Ok(Some(((*key).clone(), popped)));
+        }
+        Ok(None)
+    }
+
+    /// Заменяет элемент списка по индексу на месте.
+    ///
+    /// # Возвращает:
+    /// - `Ok(())`, если элемент заменён
+    /// - `Err(KeyNotFound)`, если ключ отсутствует
+    /// - `Err(InvalidArgument)`, если индекс вне диапазона
+    fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
+    ) -> StoreResult<()> {
+        self.purge_expired();
+        match self.data().get_mut(key) {
+            Some(mut entry) => match &mut **entry {
+                Value::List(list) => {
+                    if list.set(index, value) {
+                        Ok(())
+                    } else {
+                        Err(StoreError::InvalidArgument("index out of range".into()))
+                    }
+                }
+                _ => Err(StoreError::WrongType("LSET: key is not a list".into())),
+            },
+            None => Err(StoreError::KeyNotFound),
+        }
+    }
+
+    /// Возвращает строковое значение и удаляет ключ под одной блокировкой.
+    ///
+    /// # Возвращает:
+    /// - значение удалённого ключа или `None`
+    fn getdel(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Sds>> {
+        self.purge_expired();
+        match self
+            .data()
+            .remove_if(key, |_, v| matches!(&**v, Value::Str(_)))
+            .map(|(_, entry)| entry.into_value())
+        {
+            Some(Value::Str(s)) => {
+                self.forget_expiry(key);
+                self.notify_keyspace_event(KeyspaceEvent::Del, key);
+                Ok(Some(s))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Возвращает строковое значение и изменяет TTL ключа.
+    ///
+    /// # Возвращает:
+    /// - значение ключа или `None`, если ключ отсутствует
+    /// - ошибку `WrongType`, если значение не является строкой
+    fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>> {
+        self.purge_expired();
+        let value = match self.data().get(key) {
+            Some(entry) => match &**entry.value() {
+                Value::Str(s) => s.clone(),
+                _ => return Err(StoreError::WrongType("GETEX: key is not a string".into())),
+            },
+            None => return Ok(None),
+        };
+
+        match expiry.ttl() {
+            // Время истечения уже прошло — ключ удаляется сразу.
+            Some(ttl) if ttl.is_zero() => {
+                self.data().remove(key);
+                self.forget_expiry(key);
+            }
+            Some(ttl) => self
+                .expires()
+                .lock()
+                .unwrap()
+                .set(key.as_bytes().to_vec(), ttl),
+            None if expiry == GetExExpiry::Persist => {
+                self.forget_expiry(key);
+            }
+            None => {}
+        }
+        Ok(Some(value))
+    }
+
+    /// Копирует значение и TTL ключа `src` в `dst`.
+    ///
+    /// # Возвращает:
+    /// - `Ok(true)`, если значение скопировано
+    /// - `Ok(false)`, если `src` отсутствует или `dst` существует без `replace`
+    /// - ошибку `InvalidArgument`, если `src == dst` в той же базе или базы
+    ///   `dst_db` нет
+    fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool> {
+        self.purge_expired();
+        let db = dst_db.unwrap_or(self.index);
+        if db >= self.databases() {
+            return Err(db_out_of_range());
+        }
+        if src == dst && db == self.index {
+            return Err(StoreError::InvalidArgument(
+                "source and destination objects are the same".into(),
+            ));
+        }
+
+        let Some(value) = self
+            .data()
+            .get(src)
+            .map(|entry| Value::clone(entry.value()))
+        else {
+            return Ok(false);
+        };
+        let target = self.databases.read().unwrap()[db].clone();
+        match target.data.entry(dst.clone()) {
+            Entry::Occupied(mut entry) => {
+                if !replace {
+                    return Ok(false);
+                }
+                entry.insert(value.into());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(value.into());
+            }
+        }
+        self.copy_expiry_to(src, &target, dst);
+        Ok(true)
+    }
+
+    /// Возвращает абсолютное время истечения ключа в unix-миллисекундах.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Some(ms))`, если у ключа есть TTL
+    /// - `Ok(Some(-1))`, если ключ существует без TTL
+    /// - `Ok(None)`, если ключ отсутствует
+    fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<i64>> {
+        self.purge_expired();
+        if !self.data().contains_key(key) {
+            return Ok(None);
+        }
+        let at = self
+            .expires()
+            .lock()
+            .unwrap()
+            .expire_at_unix_ms(key.as_bytes());
+        Ok(Some(at.map_or(-1, |ms| ms as i64)))
+    }
+
+    /// Устанавливает или снимает TTL ключа.
+    ///
+    /// # Возвращает:
+    /// - `Ok(true)`, если ключ существует (при нулевом TTL он удаляется)
+    /// - `Ok(false)`, если ключ отсутствует
+    fn set_expiry(
+        &self,
+        key: &Sds,
+        ttl: Option<Duration>,
+    ) -> StoreResult<bool> {
+        self.purge_expired();
+        if !self.data().contains_key(key) {
+            return Ok(false);
+        }
+        match ttl {
+            Some(ttl) if ttl.is_zero() => {
+                self.data().remove(key);
+                self.forget_expiry(key);
+                self.notify_keyspace_event(KeyspaceEvent::Del, key);
+            }
+            Some(ttl) => {
+                self.expires()
+                    .lock()
+                    .unwrap()
+                    .set(key.as_bytes().to_vec(), ttl);
+                self.notify_keyspace_event(KeyspaceEvent::Expire, key);
+            }
+            None => {
+                self.forget_expiry(key);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Возвращает тип значения ключа.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Some(type))`, если ключ существует
+    /// - `Ok(None)`, если ключ отсутствует
+    fn type_of(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<ValueType>> {
+        self.purge_expired();
+        Ok(self
+            .data()
+            .get(key)
+            .and_then(|entry| entry.value().value_type()))
+    }
+
+    /// Отмечает обращение к каждому существующему ключу из `keys`.
+    ///
+    /// # Возвращает:
+    /// - число существующих ключей (повторы считаются каждый раз)
+    fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired();
+        let data = self.data();
+        Ok(keys
+            .iter()
+            .filter(|key| data.get(**key).inspect(|entry| entry.touch()).is_some())
+            .count())
+    }
+
+    /// Возвращает число секунд с последнего обращения к ключу, не отмечая
+    /// обращение.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Some(secs))`, если ключ существует
+    /// - `Ok(None)`, если ключ отсутствует
+    fn idletime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u64>> {
+        self.purge_expired();
+        Ok(self
+            .data()
+            .get(key)
+            .map(|entry| unix_secs().saturating_sub(entry.accessed_at())))
+    }
+
+    /// Устанавливает политику вытеснения для всех баз хранилища.
+    fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> StoreResult<()> {
+        self.policy.store(policy.code(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Возвращает LFU-счётчик ключа, не отмечая обращение.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Some(freq))`, если ключ существует
+    /// - `Ok(None)`, если ключ отсутствует
+    /// - `Err(InvalidOperation)`, если политика вытеснения не LFU
+    fn freq(
         &self,
         key: &Sds,
-    ) -> StoreResult<Vec<Sds>> {
-        match self.data.get(key) {
-            Some(entry) => match &*entry {
-                Value::Set(set) => Ok(set.iter().cloned().collect()),
-                _ => Err(StoreError::WrongType("SMEMBERS: key is not a set".into())),
-            },
-            None => Ok(Vec::new()),
+    ) -> StoreResult<Option<u8>> {
+        if !self.eviction_policy().is_lfu() {
+            return Err(StoreError::InvalidOperation(
+                "An LFU maxmemory policy is not selected, access frequency not tracked".into(),
+            ));
         }
+        self.purge_expired();
+        Ok(self.data().get(key).map(|entry| entry.freq()))
     }
 
-    /// Возвращает размер множества.
+    /// Задаёт время простоя и LFU-счётчик ключа, не отмечая обращение.
     ///
     /// # Возвращает:
-    /// - количество элементов множества
-    fn scard(
+    /// - `Ok(true)`, если ключ существует
+    /// - `Ok(false)`, если ключ отсутствует
+    fn set_access_meta(
         &self,
         key: &Sds,
-    ) -> StoreResult<usize> {
-        match self.data.get(key) {
-            Some(entry) => match &*entry {
-                Value::Set(set) => Ok(set.len()),
-                _ => Err(StoreError::WrongType("SCARD: key is not a set".into())),
-            },
-            None => Ok(0),
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> StoreResult<bool> {
+        self.purge_expired();
+        let data = self.data();
+        let Some(entry) = data.get(key) else {
+            return Ok(false);
+        };
+        if let Some(idle) = idle_secs {
+            entry
+                .accessed_at
+                .store(unix_secs().saturating_sub(idle), Ordering::Relaxed);
+        }
+        if let Some(freq) = freq {
+            entry.freq.store(freq, Ordering::Relaxed);
         }
+        Ok(true)
     }
 
-    /// Проверяет, является ли элемент членом множества.
+    /// Снимает TTL с ключа (как `GETEX ... PERSIST`).
     ///
     /// # Возвращает:
-    /// - `true`, если элемент присутствует
-    /// - `false`, если отсутствует
-    fn sismember(
+    /// - `Ok(true)`, если TTL был снят
+    /// - `Ok(false)`, если у ключа нет TTL или ключ отсутствует
+    fn persist(
         &self,
         key: &Sds,
-        member: &Sds,
     ) -> StoreResult<bool> {
-        match self.data.get(key) {
-            Some(entry) => match &*entry {
-                Value::Set(set) => Ok(set.contains(member)),
-                _ => Err(StoreError::WrongType("SISMEMBER: key is not a set".into())),
-            },
-            None => Ok(false),
-        }
+        self.purge_expired();
+        Ok(self.data().contains_key(key) && self.forget_expiry(key))
     }
 
-    /// Удаляет элементы из множества.
+    /// Сохраняет результаты гео-поиска по набору `src` в `dst` как
+    /// отсортированное множество.
     ///
     /// # Возвращает:
-    /// - количество реально удалённых элементов
-    fn srem(
+    /// - количество сохранённых элементов (при пустом результате `dst`
+    ///   удаляется)
+    /// - `Err(KeyNotFound)`, если участник `FROMMEMBER` отсутствует
+    fn geosearchstore(
         &self,
-        key: &Sds,
-        members: &[Sds],
-    ) -> StoreResult<usize> {
-        let mut removed = 0usize;
-        let mut remove_key = false;
-
-        if let Some(mut entry) = self.data.get_mut(key) {
-            match &mut *entry {
-                Value::Set(set) => {
-                    for m in members {
-                        if set.remove(m) {
-                            removed += 1;
-                        }
-                    }
-                    if set.is_empty() {
-                        // не удаляем сразу — выставляем флаг и удалим после выхода из блока
-                        remove_key = true;
-                    }
-                }
-                _ => return Err(StoreError::WrongType("SREM: key is not a set".into())),
-            }
-            // entry dropped here (end of this scope)
-        } else {
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64> {
+        let hits = self.geo_search(src, &params)?;
+
+        self.geo().remove(dst);
+        self.del(dst)?;
+        if hits.is_empty() {
             return Ok(0);
         }
 
-        if remove_key {
-            // безопасно удалить, так как entry уже вышел из области видимости
-            self.data.remove(key);
-        }
+        let stored = hits.len() as i64;
+        self.set(dst, params.to_zset(hits))?;
+        Ok(stored)
+    }
 
-        Ok(removed)
+    fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()> {
+        let mut hlls = Vec::with_capacity(sources.len() + 1);
+        for key in std::iter::once(dest).chain(sources.iter().copied()) {
+            match self.get(key)? {
+                Some(Value::HyperLogLog(hll)) => hlls.push(hll),
+                Some(_) => return Err(StoreError::InvalidType),
+                None => {}
+            }
+        }
+        let refs: Vec<&Hll> = hlls.iter().map(Box::as_ref).collect();
+        self.set(dest, Value::HyperLogLog(Box::new(Hll::merge_all(&refs))))
     }
 
-    /// Возвращает случайные элементы множества.
+    /// Возвращает количество ключей в базе.
     ///
     /// # Возвращает:
-    /// - вектор выбранных элементов (может быть пустым)
-    fn srandmember(
-        &self,
-        key: &Sds,
-        count: isize,
-    ) -> StoreResult<Vec<Sds>> {
-        match self.data.get(key) {
-            Some(entry) => match &*entry {
-                Value::Set(set) => {
-                    let mut rng = thread_rng();
-                    if count == 1 {
-                        let opt = set.iter().cloned().choose(&mut rng);
-                        Ok(opt.into_iter().collect())
-                    } else if count > 1 {
-                        let cnt = count as usize;
-                        let chosen = set.iter().cloned().choose_multiple(&mut rng, cnt);
-                        Ok(chosen)
-                    } else {
-                        // count <= 0 semantics: возвращаем пустой вектор
-                        Ok(Vec::new())
-                    }
-                }
-                _ => Err(StoreError::WrongType(
-                    "SRANDMEMBER: key is not a set".into(),
-                )),
-            },
-            None => Ok(Vec::new()),
-        }
+    /// - количество ключей
+    fn dbsize(&self) -> StoreResult<usize> {
+        self.purge_expired();
+        Ok(self.data().len())
     }
 
-    /// Извлекает и удаляет элементы множества случайным образом.
+    /// Возвращает ключи текущей базы, подходящие под `pattern`.
     ///
     /// # Возвращает:
-    /// - вектор удалённых элементов
-    fn spop(
+    /// - ключи в произвольном порядке
+    fn keys(
         &self,
-        key: &Sds,
-        count: isize,
+        pattern: &str,
     ) -> StoreResult<Vec<Sds>> {
-        let mut out = Vec::new();
-        let mut remove_key = false;
-
-        if let Some(mut entry) = self.data.get_mut(key) {
-            match &mut *entry {
-                Value::Set(set) => {
-                    let mut rng = thread_rng();
-                    let cnt = if count <= 0 { 1 } else { count as usize };
-                    for _ in 0..cnt {
-                        if let Some(item) = set.iter().cloned().choose(&mut rng) {
-                            set.remove(&item);
-                            out.push(item);
-                        } else {
-                            break;
-                        }
-                    }
-                    if set.is_empty() {
-                        remove_key = true;
-                    }
-                }
-                _ => return Err(StoreError::WrongType("SPOP: key is not a set".into())),
-            }
-            // entry dropped here
-        } else {
-            return Ok(Vec::new());
-        }
-
-        if remove_key {
-            self.data.remove(key);
-        }
-
-        Ok(out)
+        self.purge_expired();
+        Ok(self
+            .data()
+            .iter()
+            .filter(|entry| glob_match(pattern.as_bytes(), entry.key().as_bytes()))
+            .map(|entry| entry.key().clone())
+            .collect())
     }
 
-    /// Возвращает количество ключей в базе.
+    /// Меняет местами базы `db1` и `db2` под блокировкой записи: обмениваются
+    /// только указатели на карты ключей, TTL и гео-индексов.
     ///
     /// # Возвращает:
-    /// - количество ключей
-    fn dbsize(&self) -> StoreResult<usize> {
-        Ok(self.data.len())
+    /// - `Err(StoreError::InvalidArgument)`, если одной из баз нет
+    fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> StoreResult<()> {
+        let mut databases = self.databases.write().unwrap();
+        if db1 >= databases.len() || db2 >= databases.len() {
+            return Err(db_out_of_range());
+        }
+        databases.swap(db1, db2);
+        Ok(())
     }
 
     /// Сохранение не поддерживается для in-memory хранилища.
@@ -525,6 +2017,14 @@ impl Storage for InMemoryStore {
     }
 }
 
+impl crate::engine::AsyncStorage for InMemoryStore {
+    ready_storage_methods!();
+
+    fn save(&self) -> impl Future<Output = StoreResult<()>> + Send {
+        std::future::ready(Storage::save(self))
+    }
+}
+
 impl SessionStorage for InMemoryStore {
     fn insert_session(
         &self,
@@ -612,15 +2112,76 @@ impl SessionStorage for InMemoryStore {
 }
 
 ////////////////////////////////////////////////////////////////////////////////
-// Общие реализации трейтов для InMemoryStore
+// Общие реализации трейтов для KeyEntry и InMemoryStore
 ////////////////////////////////////////////////////////////////////////////////
 
+impl From<Value> for KeyEntry {
+    fn from(value: Value) -> Self {
+        Self::new(value)
+    }
+}
+
+impl Deref for KeyEntry {
+    type Target = Value;
+
+    fn deref(&self) -> &Value {
+        self.touch();
+        &self.value
+    }
+}
+
+impl DerefMut for KeyEntry {
+    fn deref_mut(&mut self) -> &mut Value {
+        self.touch();
+        &mut self.value
+    }
+}
+
 impl Default for InMemoryStore {
     fn default() -> Self {
         Self::new()
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Освобождает память баз `shards` в фоновом потоке, не задерживая
+/// обработку команд. Вне рантайма Tokio используется отдельный поток.
+fn lazy_free(shards: Vec<DbShard>) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn_blocking(move || drop(shards));
+        }
+        Err(_) => {
+            std::thread::spawn(move || drop(shards));
+        }
+    }
+}
+
+/// Уменьшает LFU-счётчики всех ключей во всех базах.
+fn decay_databases(databases: &RwLock<Vec<DbShard>>) {
+    let shards = databases.read().unwrap().clone();
+    for shard in shards {
+        for entry in shard.data.iter() {
+            entry.decay();
+        }
+    }
+}
+
+/// Возвращает текущее unix-время в секундах.
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Ошибка обращения к несуществующей логической базе.
+fn db_out_of_range() -> StoreError {
+    StoreError::InvalidArgument("DB index is out of range".to_string())
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -907,6 +2468,29 @@ mod tests {
         assert_eq!(store.get(&long_key).unwrap(), Some(long_value));
     }
 
+    #[test]
+    fn test_getex_ttl_follows_rename_and_del() {
+        let store = InMemoryStore::new();
+        let k = key("k");
+        store.set(&k, Value::Str(key("v"))).unwrap();
+        store.getex(&k, GetExExpiry::Px(50)).unwrap();
+
+        // TTL переезжает вместе с ключом.
+        let renamed = key("renamed");
+        store.rename(&k, &renamed).unwrap();
+
+        // Удаление снимает TTL: новый ключ с тем же именем не истекает.
+        let other = key("other");
+        store.set(&other, Value::Str(key("x"))).unwrap();
+        store.getex(&other, GetExExpiry::Px(50)).unwrap();
+        store.del(&other).unwrap();
+        store.set(&other, Value::Str(key("y"))).unwrap();
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert_eq!(store.get(&renamed).unwrap(), None);
+        assert_eq!(store.get(&other).unwrap(), Some(Value::Str(key("y"))));
+    }
+
     #[test]
     fn test_geo_add_and_pos() {
         let store = InMemoryStore::new();
@@ -914,12 +2498,24 @@ mod tests {
         let paris_member = key("paris");
 
         let added = store
-            .geo_add(&cities_key, 2.3522, 48.8566, &paris_member)
+            .geo_add(
+                &cities_key,
+                2.3522,
+                48.8566,
+                &paris_member,
+                GeoAddOptions::default(),
+            )
             .unwrap();
         assert!(added);
 
         let added_again = store
-            .geo_add(&cities_key, 2.3522, 48.8566, &paris_member)
+            .geo_add(
+                &cities_key,
+                2.3522,
+                48.8566,
+                &paris_member,
+                GeoAddOptions::default(),
+            )
             .unwrap();
         assert!(!added_again);
 
@@ -937,9 +2533,23 @@ mod tests {
         let paris = key("paris");
         let berlin = key("berlin");
 
-        store.geo_add(&cities_key, 2.3522, 48.8566, &paris).unwrap();
         store
-            .geo_add(&cities_key, 13.4050, 52.5200, &berlin)
+            .geo_add(
+                &cities_key,
+                2.3522,
+                48.8566,
+                &paris,
+                GeoAddOptions::default(),
+            )
+            .unwrap();
+        store
+            .geo_add(
+                &cities_key,
+                13.4050,
+                52.5200,
+                &berlin,
+                GeoAddOptions::default(),
+            )
             .unwrap();
 
         let dist_km = store
@@ -953,6 +2563,12 @@ mod tests {
             .unwrap()
             .unwrap();
         assert!((dist_m - 878_000.0).abs() < 20_000.0);
+
+        let dist_ft = store
+            .geo_dist(&cities_key, &paris, &berlin, "ft")
+            .unwrap()
+            .unwrap();
+        assert!((dist_ft - dist_m * 3.28084).abs() < 1.0);
     }
 
     #[test]
@@ -961,13 +2577,31 @@ mod tests {
         let landmarks_key = key("landmarks");
 
         store
-            .geo_add(&landmarks_key, 0.0, 0.0, &key("center"))
+            .geo_add(
+                &landmarks_key,
+                0.0,
+                0.0,
+                &key("center"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         store
-            .geo_add(&landmarks_key, 0.001, 0.001, &key("near"))
+            .geo_add(
+                &landmarks_key,
+                0.001,
+                0.001,
+                &key("near"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         store
-            .geo_add(&landmarks_key, 10.0, 10.0, &key("far"))
+            .geo_add(
+                &landmarks_key,
+                10.0,
+                10.0,
+                &key("far"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
 
         // Радиус 200 м (0.2 км)
@@ -980,4 +2614,285 @@ mod tests {
         assert!(members.contains(&"near".to_string()));
         assert!(!members.contains(&"far".to_string()));
     }
+
+    #[test]
+    fn test_select_isolates_databases() {
+        let db0 = InMemoryStore::new();
+        let db1 = db0.select(1).unwrap();
+        assert_eq!(db0.databases(), DEFAULT_DATABASES);
+        assert_eq!(db1.index(), 1);
+
+        db0.set(&key("k"), Value::Str(key("zero"))).unwrap();
+        db1.set(&key("k"), Value::Str(key("one"))).unwrap();
+        assert_eq!(db0.get(&key("k")).unwrap(), Some(Value::Str(key("zero"))));
+        assert_eq!(db1.get(&key("k")).unwrap(), Some(Value::Str(key("one"))));
+
+        db1.flushdb().unwrap();
+        assert_eq!(db1.dbsize().unwrap(), 0);
+        assert_eq!(db0.dbsize().unwrap(), 1);
+
+        assert!(matches!(
+            db0.select(DEFAULT_DATABASES),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    /// Тест проверяет COPY в другую базу: значение и TTL попадают в базу
+    /// назначения, `DB` текущей базы не уводит копию в другую, а
+    /// несуществующая база отклоняется.
+    #[test]
+    fn test_copy_between_databases() {
+        let db0 = InMemoryStore::new();
+        let db3 = db0.select(3).unwrap();
+        db0.set(&key("src"), Value::Str(key("v"))).unwrap();
+        db0.set_expiry(&key("src"), Some(Duration::from_secs(60)))
+            .unwrap();
+
+        assert!(db0.copy(&key("src"), &key("src"), Some(3), false).unwrap());
+        assert_eq!(db3.get(&key("src")).unwrap(), Some(Value::Str(key("v"))));
+        let (Some(src_at), Some(dst_at)) = (
+            db0.expiretime(&key("src")).unwrap(),
+            db3.expiretime(&key("src")).unwrap(),
+        ) else {
+            panic!("both keys must exist");
+        };
+        assert!(dst_at > 0 && (dst_at - src_at).abs() <= 1000);
+
+        assert!(db3.copy(&key("src"), &key("back"), Some(0), false).unwrap());
+        assert!(db0.get(&key("back")).unwrap().is_some());
+        assert!(db3.get(&key("back")).unwrap().is_none());
+
+        assert!(matches!(
+            db0.copy(&key("src"), &key("dst"), Some(DEFAULT_DATABASES), false),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    /// Возвращает время последнего обращения к ключу, не отмечая обращение.
+    fn accessed_at(
+        store: &InMemoryStore,
+        k: &Sds,
+    ) -> u64 {
+        store.data().get(k).unwrap().accessed_at()
+    }
+
+    /// Сдвигает время последнего обращения к ключу в прошлое.
+    fn age(
+        store: &InMemoryStore,
+        k: &Sds,
+    ) {
+        store
+            .data()
+            .get(k)
+            .unwrap()
+            .accessed_at
+            .store(0, Ordering::Relaxed);
+    }
+
+    /// Тест проверяет, что чтение, запись и `touch` обновляют время
+    /// последнего обращения, а обход через `iter` — нет.
+    #[test]
+    fn test_access_time_tracking() {
+        let store = InMemoryStore::new();
+        let k = key("k");
+        store.set(&k, Value::Str(Sds::from_str("v"))).unwrap();
+        let now = unix_secs();
+        assert!(accessed_at(&store, &k) >= now - 1);
+
+        age(&store, &k);
+        let _ = store.iter().count();
+        assert_eq!(accessed_at(&store, &k), 0);
+
+        store.get(&k).unwrap();
+        assert!(accessed_at(&store, &k) >= now);
+
+        let set = key("set");
+        store.sadd(&set, &[key("a")]).unwrap();
+        age(&store, &set);
+        store.sadd(&set, &[key("b")]).unwrap();
+        assert!(accessed_at(&store, &set) >= now);
+
+        age(&store, &k);
+        assert_eq!(store.touch(&[&k, &key("missing")]).unwrap(), 1);
+        assert!(accessed_at(&store, &k) >= now);
+    }
+
+    /// Тест проверяет, что `idletime` считает секунды с последнего обращения
+    /// и сам обращением не считается.
+    #[test]
+    fn test_idletime() {
+        let store = InMemoryStore::new();
+        let k = key("k");
+        assert_eq!(store.idletime(&k).unwrap(), None);
+
+        store.set(&k, Value::Str(Sds::from_str("v"))).unwrap();
+        assert!(store.idletime(&k).unwrap().unwrap() <= 1);
+
+        age(&store, &k);
+        let idle = store.idletime(&k).unwrap().unwrap();
+        assert!(idle >= unix_secs() - 1);
+        assert!(store.idletime(&k).unwrap().unwrap() >= idle);
+
+        store.get(&k).unwrap();
+        assert!(store.idletime(&k).unwrap().unwrap() <= 1);
+    }
+
+    /// Тест проверяет, что LFU-счётчик часто читаемого ключа растёт быстрее,
+    /// чем редко читаемого, а затухание уменьшает его, но не ниже нуля.
+    #[test]
+    fn test_lfu_counter_and_decay() {
+        let store = InMemoryStore::new();
+        let (hot, cold) = (key("hot"), key("cold"));
+        store.set(&hot, Value::Str(Sds::from_str("v"))).unwrap();
+        store.set(&cold, Value::Str(Sds::from_str("v"))).unwrap();
+        assert!(store.freq(&hot).is_err());
+
+        store
+            .set_eviction_policy(EvictionPolicy::AllKeysLfu)
+            .unwrap();
+        for _ in 0..1000 {
+            store.get(&hot).unwrap();
+        }
+        let hot_freq = store.freq(&hot).unwrap().unwrap();
+        let cold_freq = store.freq(&cold).unwrap().unwrap();
+        assert!(hot_freq > cold_freq, "hot={hot_freq} cold={cold_freq}");
+        assert_eq!(store.freq(&hot).unwrap(), Some(hot_freq));
+        assert_eq!(store.freq(&key("missing")).unwrap(), None);
+
+        store.decay_lfu();
+        assert_eq!(store.freq(&hot).unwrap(), Some(hot_freq - 1));
+        for _ in 0..=u8::MAX {
+            store.decay_lfu();
+        }
+        assert_eq!(store.freq(&cold).unwrap(), Some(0));
+    }
+
+    /// Тест проверяет выбор ключа для вытеснения: LFU — наименьший счётчик,
+    /// LRU — самое старое обращение, `volatile-*` — только ключи с TTL.
+    #[test]
+    fn test_evict_by_policy() {
+        let store = InMemoryStore::new();
+        let (hot, cold, old) = (key("hot"), key("cold"), key("old"));
+        for k in [&hot, &cold, &old] {
+            store.set(k, Value::Str(Sds::from_str("v"))).unwrap();
+        }
+        assert_eq!(store.eviction_candidate(), None);
+
+        store
+            .set_eviction_policy(EvictionPolicy::AllKeysLfu)
+            .unwrap();
+        for _ in 0..100 {
+            store.get(&hot).unwrap();
+            store.get(&old).unwrap();
+        }
+        assert_eq!(store.eviction_candidate(), Some(cold.clone()));
+
+        store
+            .set_eviction_policy(EvictionPolicy::AllKeysLru)
+            .unwrap();
+        age(&store, &old);
+        assert_eq!(store.eviction_candidate(), Some(old.clone()));
+
+        store
+            .set_eviction_policy(EvictionPolicy::VolatileLru)
+            .unwrap();
+        store
+            .set_expiry(&hot, Some(Duration::from_secs(60)))
+            .unwrap();
+        assert_eq!(store.evict(), Some(hot.clone()));
+        assert_eq!(store.get(&hot).unwrap(), None);
+        assert_eq!(store.evict(), None);
+    }
+
+    #[test]
+    fn test_swapdb_exchanges_keys_and_ttls() {
+        let db0 = InMemoryStore::new();
+        let db1 = db0.select(1).unwrap();
+
+        db0.set(&key("a"), Value::Str(key("from0"))).unwrap();
+        db0.set_expiry(&key("a"), Some(Duration::from_secs(100)))
+            .unwrap();
+        db1.set(&key("b"), Value::Str(key("from1"))).unwrap();
+        db0.geo_add(&key("geo"), 1.0, 1.0, &key("m"), GeoAddOptions::default())
+            .unwrap();
+
+        db0.swapdb(0, 1).unwrap();
+
+        // Представления указывают на индексы, а не на данные.
+        assert_eq!(db1.get(&key("a")).unwrap(), Some(Value::Str(key("from0"))));
+        assert_eq!(db0.get(&key("b")).unwrap(), Some(Value::Str(key("from1"))));
+        assert_eq!(db0.get(&key("a")).unwrap(), None);
+        assert_eq!(db1.get(&key("b")).unwrap(), None);
+        assert!(db1.geo_pos(&key("geo"), &key("m")).unwrap().is_some());
+
+        let at = db1.expiretime(&key("a")).unwrap().unwrap();
+        assert!(at > 0);
+        assert_eq!(db0.expiretime(&key("b")).unwrap(), Some(-1));
+
+        // Обмен базы самой с собой ничего не меняет.
+        db0.swapdb(1, 1).unwrap();
+        assert_eq!(db1.get(&key("a")).unwrap(), Some(Value::Str(key("from0"))));
+
+        assert!(matches!(
+            db0.swapdb(0, DEFAULT_DATABASES),
+            Err(StoreError::InvalidArgument(_))
+        ));
+    }
+
+    /// Тест проверяет, что подписчик `__keyevent@0__:expired` получает имя
+    /// ключа, удалённого по истечении TTL.
+    #[tokio::test]
+    async fn test_keyspace_expired_event() {
+        let broker = Arc::new(crate::Broker::new());
+        let store = InMemoryStore::new();
+        store.keyspace_notifier().set_broker(&broker);
+        store.keyspace_notifier().set_flags("Ex").unwrap();
+        let mut sub = broker.subscribe("__keyevent@0__:expired").unwrap();
+
+        store.set(&key("session"), Value::Str(key("v"))).unwrap();
+        store
+            .set_expiry(&key("session"), Some(Duration::from_millis(10)))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(store.get(&key("session")).unwrap(), None);
+
+        let msg = sub.recv().await.unwrap();
+        assert_eq!(
+            msg.payload,
+            crate::MessagePayload::Bytes(bytes::Bytes::from("session"))
+        );
+    }
+
+    /// Тест проверяет события `del` и `sadd` в канале своей базы и
+    /// отсутствие событий выключенных классов.
+    #[tokio::test]
+    async fn test_keyspace_write_events() {
+        let broker = Arc::new(crate::Broker::new());
+        let db0 = InMemoryStore::new();
+        let db1 = db0.select(1).unwrap();
+        db0.keyspace_notifier().set_broker(&broker);
+        db0.keyspace_notifier().set_flags("Egs").unwrap();
+        let mut del = broker.subscribe("__keyevent@1__:del").unwrap();
+        let mut sadd = broker.subscribe("__keyevent@1__:sadd").unwrap();
+        let mut mset = broker.subscribe("__keyevent@1__:set").unwrap();
+
+        db1.sadd(&key("tags"), &[key("a")]).unwrap();
+        // Повторное добавление ничего не меняет и не порождает события.
+        db1.sadd(&key("tags"), &[key("a")]).unwrap();
+        db1.mset(vec![(&key("k"), Value::Str(key("v")))]).unwrap();
+        assert!(db1.del(&key("k")).unwrap());
+        assert!(!db1.del(&key("missing")).unwrap());
+
+        assert_eq!(
+            sadd.recv().await.unwrap().payload,
+            crate::MessagePayload::Bytes(bytes::Bytes::from("tags"))
+        );
+        assert!(sadd.try_recv().is_err());
+        assert_eq!(
+            del.recv().await.unwrap().payload,
+            crate::MessagePayload::Bytes(bytes::Bytes::from("k"))
+        );
+        assert!(del.try_recv().is_err());
+        assert!(mset.try_recv().is_err());
+    }
 }
@@ -7,7 +7,7 @@ use std::{
 use crate::engine::{AdvancedRebalancer, SlotManager};
 
 /// Статус здоровья кластера
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HealthStatus {
     Healthy,
     Warning,
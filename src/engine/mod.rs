@@ -8,6 +8,7 @@
 //! Этот модуль предоставляет основные типы данных и абстракции для реализации
 //! базы данных в оперативной памяти и долговременного хранения:
 //!
+//! - `active_expire`: фоновое активное удаление ключей с истёкшим TTL.
 //! - `aof`: ведение append-only логов для долговременного хранения и
 //!   восстановления данных.
 //! - `aof_integrity`: проверка целостности AOF, верификация checksum и
@@ -16,6 +17,8 @@
 //!   синхронизации между нодами.
 //! - `compaction`: инкрементальная и фоновая компактификация AOF для экономии
 //!   дискового пространства.
+//! - `eviction`: политики вытеснения ключей и LFU-счётчик.
+//! - `lua`: выполнение Lua-скриптов (`EVAL`/`EVALSHA`) в песочнице.
 //! - `memory`: внутренние структуры управления памятью, метрики и кеши.
 //! - `metrics`: сбор и экспорт статистики работы базы данных и отдельных
 //!   компонентов.
@@ -23,6 +26,7 @@
 //!   файловой системой.
 //! - `rebalancer`: алгоритмы перераспределения ключей между шардами для
 //!   балансировки нагрузки.
+//! - `replication`: реплики чтения shard'ов кластера и журнал изменений.
 //! - `recovery`: стратегии восстановления данных из AOF или snapshot'ов.
 //! - `sharding`: распределение ключей по нескольким шардам для уменьшения lock
 //!   contention и повышения параллельности.
@@ -35,15 +39,19 @@
 //! - `zdb_protocol`: протокол взаимодействия с клиентами, сериализация и
 //!   десериализация команд.
 
+pub mod active_expire;
 pub mod aof;
 pub mod aof_integrity;
 pub mod cluster;
 pub mod compaction;
+pub mod eviction;
+pub mod lua;
 pub mod memory;
 pub mod metrics;
 pub mod persistent;
 pub mod rebalancer;
 pub mod recovery;
+pub mod replication;
 pub mod sharding;
 pub mod slot_manager;
 pub mod storage;
@@ -53,11 +61,15 @@ pub mod zdb_protocol;
 
 // Publicly re-export all error types and functions from the submodules to
 // simplify access from external code.
+pub use active_expire::*;
 pub use aof::*;
 pub use cluster::*;
+pub use eviction::*;
+pub use lua::*;
 pub use memory::*;
 pub use persistent::*;
 pub use rebalancer::*;
+pub use replication::*;
 pub use sharding::*;
 pub use slot_manager::*;
 pub use storage::*;
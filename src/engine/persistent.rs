@@ -1,24 +1,33 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    fs,
     io::Cursor,
     path::{Path, PathBuf},
-    sync::{atomic::Ordering, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use rand::{seq::IteratorRandom, thread_rng};
 
 use super::{
     aof::{AofOp, SyncPolicy},
-    write_stream, AofLog, Storage, StreamReader,
+    delta_path_for, read_delta, snapshot_crc, write_stream, write_stream_versioned, AofLog,
+    DeltaEntry, DeltaWriter, FlushMode, FormatVersion, Storage, StreamReader,
 };
 use crate::{
+    database::{zdiff, zinter, zset_from_entries, zset_pop, zunion},
     engine::{
         compaction::{CompactionConfig, CompactionMetrics, RecoveryStrategy, SnapshotInfo},
         recovery::{RecoveryManager, RecoveryMetrics},
-        AofMetrics, CorruptionPolicy,
+        AofMetrics, CorruptionPolicy, EvictionPolicy,
     },
-    GeoPoint, GeoSet, GlobalShardStats, Sds, ShardMetricsSnapshot, ShardedIndex, ShardingConfig,
-    StoreError, StoreResult, Value,
+    glob_match, Dict, ExpireMap, GeoAddOptions, GeoPoint, GeoSearchParams, GeoSet, GetExExpiry,
+    GlobalShardStats, Hll, ListDir, QuickList, Sds, Shard, ShardMetricsSnapshot, ShardedIndex,
+    ShardingConfig, SmartHash, StoreError, StoreResult, StreamNotifiers, Value, ValueType,
+    ZCombineOptions, ZPopDir, ZSetEntries,
 };
 
 /// Конфигурация для InPersistentStore с поддержкой компактизации.
@@ -36,13 +45,31 @@ pub struct PersistentStoreConfig {
     pub compaction: CompactionConfig,
     /// Стратегия восстановления
     pub recovery_strategy: RecoveryStrategy,
+    /// Минимальный размер AOF в байтах для автоматической фоновой
+    /// перезаписи. Перезапись запускается, когда журнал достиг этого размера
+    /// и вырос вдвое с последней перезаписи; `0` отключает её.
+    pub aof_rewrite_min_size: u64,
+    /// Минимальный суммарный размер снимка ZDB и его дельты для фоновой
+    /// полной перезаписи снимка. Перезапись запускается, когда сумма достигла
+    /// этого размера и вдвое превысила снимок; `0` отключает её.
+    pub zdb_rewrite_min_size: u64,
 }
 
 /// Хранилище с поддержкой постоянства через AOF и sharded индекс.
 /// Ключи и значения распределены по шардам, изменения логируются на диск.
+///
+/// Клонирование дёшево и даёт дескриптор того же хранилища: так
+/// асинхронные методы передают его в пул блокирующих задач Tokio.
+#[derive(Clone)]
 pub struct InPersistentStore {
-    /// Sharded in-memory индекс для concurrent access
-    index: ShardedIndex<Vec<u8>>,
+    inner: Arc<InPersistentStoreInner>,
+}
+
+/// Состояние [`InPersistentStore`], общее для всех его дескрипторов.
+struct InPersistentStoreInner {
+    /// Sharded in-memory индекс для concurrent access. Разделяется с
+    /// фоновой перезаписью AOF, которая читает из него снимок.
+    index: Arc<ShardedIndex<Vec<u8>>>,
     /// Журнал AOF, логирующий изменения (один для всех шардов)
     aof: Mutex<AofLog>,
     /// Менеджер компактирования и восстановления
@@ -52,6 +79,33 @@ pub struct InPersistentStore {
     config: PersistentStoreConfig,
     /// Путь к AOF файлу
     aof_path: PathBuf,
+    /// TTL ключей. Хранится только в памяти и не попадает в AOF: истечение
+    /// ключа логируется как обычный `DEL`.
+    expires: Mutex<ExpireMap>,
+    /// Ключи, изменённые после последнего сохранения ZDB. Из них строится
+    /// дельта-снимок; очищается при каждом успешном сохранении.
+    dirty_keys: Mutex<HashSet<Sds>>,
+    /// Идёт ли фоновая полная перезапись снимка ZDB.
+    zdb_rewriting: Arc<AtomicBool>,
+    /// Ожидающие `XREAD BLOCK`. Значения хранятся сериализованными, поэтому
+    /// уведомления потоков живут здесь, а не в `Stream`.
+    stream_notifiers: StreamNotifiers,
+}
+
+/// Изменение значения ключа, вычисленное под блокировкой шарда.
+enum ValueUpdate {
+    /// Оставить значение без изменений.
+    Keep,
+    /// Записать новое значение.
+    Set(Value),
+    /// Удалить ключ.
+    Delete,
+}
+
+/// Запись, которую нужно добавить в AOF после применения `ValueUpdate`.
+enum PendingAof {
+    Set(Vec<u8>),
+    Del,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -67,7 +121,7 @@ impl InPersistentStore {
     ) -> Result<Self, StoreError> {
         let aof_path = path.as_ref().to_path_buf();
         let aof = AofLog::open(path, config.sync_policy, config.corruption_policy)?;
-        let index = ShardedIndex::new(config.sharding.clone());
+        let index = Arc::new(ShardedIndex::new(config.sharding.clone()));
 
         // Создаём менеджер восстановления
         let recovery_manager = RecoveryManager::new(
@@ -77,11 +131,17 @@ impl InPersistentStore {
         );
 
         let store = Self {
-            index,
-            aof: Mutex::new(aof),
-            recovery_manager: Mutex::new(recovery_manager),
-            config,
-            aof_path,
+            inner: Arc::new(InPersistentStoreInner {
+                index,
+                aof: Mutex::new(aof),
+                recovery_manager: Mutex::new(recovery_manager),
+                config,
+                aof_path,
+                expires: Mutex::new(ExpireMap::new()),
+                dirty_keys: Mutex::new(HashSet::new()),
+                zdb_rewriting: Arc::new(AtomicBool::new(false)),
+                stream_notifiers: StreamNotifiers::new(),
+            }),
         };
 
         // Восстанавливаем состояние из AOF
@@ -92,41 +152,44 @@ impl InPersistentStore {
 
     /// Возвращает информацию о шардировании.
     pub fn sharding_info(&self) -> (usize, GlobalShardStats) {
-        (self.index.num_shards(), self.index.global_stats())
+        (
+            self.inner.index.num_shards(),
+            self.inner.index.global_stats(),
+        )
     }
 
     /// Возвращает метрики по всем шардам (для мониторинга).
     pub fn get_shard_metrics(&self) -> Vec<ShardMetricsSnapshot> {
-        self.index.collect_metrics()
+        self.inner.index.collect_metrics()
     }
 
     /// Возвращает метрики компактирования
     pub fn get_compaction_metrics(&self) -> Option<CompactionMetrics> {
-        let recovery_guard = self.recovery_manager.lock().unwrap();
+        let recovery_guard = self.inner.recovery_manager.lock().unwrap();
         recovery_guard.compaction_manager().map(|cm| cm.metrics())
     }
 
     /// Возвращение метрики восстановления
     pub fn get_recovery_metrics(&self) -> RecoveryMetrics {
-        let recovery_guard = self.recovery_manager.lock().unwrap();
+        let recovery_guard = self.inner.recovery_manager.lock().unwrap();
         recovery_guard.recovery_metrics()
     }
 
     /// Вручную запускает компактирование
     pub fn trigger_compaction(&self) -> StoreResult<()> {
-        let recovery_guard = self.recovery_manager.lock().unwrap();
+        let recovery_guard = self.inner.recovery_manager.lock().unwrap();
         recovery_guard.trigger_compaction()
     }
 
     /// Создаёт снимки вручную
     pub fn create_snapshot(&self) -> StoreResult<SnapshotInfo> {
-        let recovery_guard = self.recovery_manager.lock().unwrap();
+        let recovery_guard = self.inner.recovery_manager.lock().unwrap();
         recovery_guard.create_snapshot()
     }
 
     /// Получает информацию о последнем снимке
     pub fn get_latest_snapshot_info(&self) -> StoreResult<Option<SnapshotInfo>> {
-        let recovery_guard = self.recovery_manager.lock().unwrap();
+        let recovery_guard = self.inner.recovery_manager.lock().unwrap();
         if let Some(cm) = recovery_guard.compaction_manager() {
             cm.find_latest_snapshot()
         } else {
@@ -136,41 +199,73 @@ impl InPersistentStore {
 
     /// Возвращает размер AOF файла
     pub fn get_aof_size(&self) -> u64 {
-        std::fs::metadata(&self.aof_path)
+        std::fs::metadata(&self.inner.aof_path)
             .map(|m| m.len())
             .unwrap_or(0)
     }
 
     /// Возвращает метрики AOF
     pub fn get_aof_metrics(&self) -> AofMetrics {
-        let aof_guard = self.aof.lock().unwrap();
+        let aof_guard = self.inner.aof.lock().unwrap();
         aof_guard.metrics()
     }
 
-    /// Graceful shutdown с сохранением состояния
-    pub fn shutdown(&self) -> StoreResult<()> {
-        // Финальный flush AOF
-        {
-            let _aof_guard = self.aof.lock().unwrap();
-            // Убеждаемся что все данные записаны
-            // Note: AofLog не имеет публичного flush метода, но drop сделает
-            // это автоматически
-        }
+    /// Сохраняет полный снимок ZDB в `path` и очищает `dirty_keys`.
+    ///
+    /// Снимок пишется во временный файл и атомарно заменяет `path`; дельта
+    /// `<path>.delta` после этого устаревает и удаляется.
+    pub fn save_zdb(
+        &self,
+        path: &Path,
+    ) -> StoreResult<()> {
+        self.ensure_no_zdb_rewrite()?;
+        // Ключи, изменённые после этой точки, снова попадут в `dirty_keys`
+        let taken = std::mem::take(&mut *self.inner.dirty_keys.lock().unwrap());
+        Self::write_zdb_snapshot(&self.inner.index, path).inspect_err(|_| self.restore_dirty(taken))
+    }
 
-        // Останавливаем компактирование
-        {
-            let mut recovery_guard = self.recovery_manager.lock().unwrap();
-            recovery_guard.shutdown()?;
+    /// Сохраняет дельту к полному снимку `base` в `<base>.delta` и очищает
+    /// `dirty_keys`.
+    ///
+    /// Дельта накопительная: кроме ключей из `dirty_keys` в неё заново
+    /// попадают ключи прежней дельты к той же базе. Если суммарный размер
+    /// базы и дельты достиг `zdb_rewrite_min_size` и вдвое превысил базу,
+    /// запускается фоновая полная перезапись `base` (только внутри Tokio
+    /// runtime).
+    ///
+    /// # Возвращает
+    /// - путь записанной дельты
+    pub fn save_zdb_delta(
+        &self,
+        base: &Path,
+    ) -> StoreResult<PathBuf> {
+        self.ensure_no_zdb_rewrite()?;
+        let base_crc = snapshot_crc(base)?;
+        let delta_path = delta_path_for(base);
+
+        let taken = std::mem::take(&mut *self.inner.dirty_keys.lock().unwrap());
+        if let Err(e) = self.write_zdb_delta(base_crc, &delta_path, &taken) {
+            self.restore_dirty(taken);
+            return Err(e);
         }
 
-        // Опционально создаём финальный снапшот
-        if self.config.compaction.enable_snapshots {
-            if let Err(e) = self.create_snapshot() {
-                eprintln!("Warning: Failed to create shutdown snapshot: {e:?}");
-            }
-        }
+        self.maybe_rewrite_zdb(base, &delta_path);
+        Ok(delta_path)
+    }
 
-        Ok(())
+    /// Возвращает реестр ожидающих новых записей потоков.
+    pub fn stream_notifiers(&self) -> &StreamNotifiers {
+        &self.inner.stream_notifiers
+    }
+
+    /// Проверяет, идёт ли фоновая полная перезапись снимка ZDB.
+    pub fn is_zdb_rewriting(&self) -> bool {
+        self.inner.zdb_rewriting.load(Ordering::Acquire)
+    }
+
+    /// Graceful shutdown с сохранением состояния
+    pub fn shutdown(&self) -> StoreResult<()> {
+        self.inner.shutdown()
     }
 
     /// Инициализация компактирования и восстановления состояния
@@ -178,16 +273,16 @@ impl InPersistentStore {
     fn initialize_and_recover(&self) -> StoreResult<()> {
         // Инициализируем менеджер восстановления
         {
-            let mut recovery_guard = self.recovery_manager.lock().unwrap();
+            let mut recovery_guard = self.inner.recovery_manager.lock().unwrap();
             recovery_guard.initialize(
-                std::sync::Arc::new(self.index.clone()),
-                self.config.compaction.clone(),
+                Arc::new(ShardedIndex::clone(&self.inner.index)),
+                self.inner.config.compaction.clone(),
             )?;
 
             // Выполняем восстановление
-            let recovery_stats = recovery_guard.recover(&self.index)?;
+            let recovery_stats = recovery_guard.recover(&self.inner.index)?;
 
-            if self.config.enable_operation_logging {
+            if self.inner.config.enable_operation_logging {
                 println!("Recovery completed: {recovery_stats:?}");
                 println!(
                     "Recovery rate: {:.2} keys/sec",
@@ -203,10 +298,10 @@ impl InPersistentStore {
     /// Восстанавливает состояние из AOF журнала.
     /// Восстанавливает состояние из AOF журнала.
     fn replay_aof(&self) -> StoreResult<()> {
-        let mut aof_guard = self.aof.lock().unwrap();
+        let mut aof_guard = self.inner.aof.lock().unwrap();
 
         aof_guard.replay(|op, key, val| {
-            let shard = self.index.get_shard(&key);
+            let shard = self.inner.index.get_shard(&key);
 
             shard.write(|data| {
                 match op {
@@ -236,21 +331,173 @@ impl InPersistentStore {
         Ok(())
     }
 
+    /// Удаляет ключи с истёкшим TTL, логируя удаление в AOF. Вызывается при
+    /// каждом обращении к ключам (ленивое удаление).
+    fn purge_expired(&self) -> StoreResult<()> {
+        let expired = self.inner.expires.lock().unwrap().purge();
+        for key in expired {
+            self.remove_key(&Sds::from_vec(key))?;
+        }
+        Ok(())
+    }
+
+    /// Снимает TTL с ключа. Возвращает `true`, если TTL был установлен.
+    fn forget_expiry(
+        &self,
+        key_b: &[u8],
+    ) -> bool {
+        self.inner.expires.lock().unwrap().remove(key_b)
+    }
+
+    /// Копирует TTL ключа `from` на ключ `to` (TTL `to` при этом теряется).
+    fn copy_expiry(
+        &self,
+        from_b: &[u8],
+        to_b: &[u8],
+    ) {
+        let mut expires = self.inner.expires.lock().unwrap();
+        let ttl = expires.ttl(from_b);
+        expires.remove(to_b);
+        if let Some(ttl) = ttl {
+            expires.set(to_b.to_vec(), ttl);
+        }
+    }
+
+    /// Переносит TTL ключа `from` на ключ `to` (TTL `to` при этом теряется).
+    fn move_expiry(
+        &self,
+        from_b: &[u8],
+        to_b: &[u8],
+    ) {
+        self.copy_expiry(from_b, to_b);
+        self.forget_expiry(from_b);
+    }
+
+    /// Читает множества `keys`; отсутствующему ключу соответствует `None`.
+    /// Ключ другого типа даёт `WrongType`.
+    fn read_sets(
+        &self,
+        keys: &[&Sds],
+        cmd: &str,
+    ) -> StoreResult<Vec<Option<HashSet<Sds>>>> {
+        keys.iter()
+            .map(|key| {
+                let key_b = key.as_bytes();
+                self.inner.index.get_shard(key_b).read(|data| {
+                    match Self::decode_entry(data, key_b)? {
+                        Some(Value::Set(set)) => Ok(Some(set)),
+                        Some(_) => Err(StoreError::WrongType(format!("{cmd}: key is not a set"))),
+                        None => Ok(None),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Читает упорядоченные множества `keys`; отсутствующему ключу
+    /// соответствует `None`. Ключ другого типа даёт `WrongType`.
+    fn read_zsets(
+        &self,
+        keys: &[&Sds],
+        cmd: &str,
+    ) -> StoreResult<Vec<Option<Dict<Sds, f64>>>> {
+        keys.iter()
+            .map(|key| {
+                let key_b = key.as_bytes();
+                self.inner.index.get_shard(key_b).read(|data| {
+                    match Self::decode_entry(data, key_b)? {
+                        Some(Value::ZSet { dict, .. }) => Ok(Some(dict)),
+                        Some(_) => Err(StoreError::WrongType(format!(
+                            "{cmd}: key is not a sorted set"
+                        ))),
+                        None => Ok(None),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Записывает результат операции над коллекциями в `dst`: при `len == 0`
+    /// ключ удаляется. TTL приёмника сбрасывается.
+    fn store_result(
+        &self,
+        dst: &Sds,
+        value: Value,
+        len: usize,
+    ) -> StoreResult<usize> {
+        let update = if len == 0 {
+            ValueUpdate::Delete
+        } else {
+            ValueUpdate::Set(value)
+        };
+        self.update_value(dst, |_| Ok((update, ())))?;
+        self.forget_expiry(dst.as_bytes());
+        Ok(len)
+    }
+
+    /// Применяет `f` к хешу по ключу `key` под блокировкой шарда, создавая
+    /// пустой хеш для отсутствующего ключа. При ошибке `f` значение не
+    /// изменяется.
+    fn update_hash<R>(
+        &self,
+        key: &Sds,
+        cmd: &str,
+        f: impl FnOnce(&mut SmartHash) -> StoreResult<R>,
+    ) -> StoreResult<R> {
+        self.update_value(key, |current| {
+            let mut hash = match current {
+                Some(Value::Hash(hash)) => hash,
+                Some(_) => return Err(StoreError::WrongType(format!("{cmd}: key is not a hash"))),
+                None => SmartHash::new(),
+            };
+            let result = f(&mut hash)?;
+            Ok((ValueUpdate::Set(Value::Hash(hash)), result))
+        })
+    }
+
+    /// Удаляет ключ вместе с его TTL и логирует удаление в AOF.
+    fn remove_key(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        let key_b = key.as_bytes();
+
+        let shard = self.inner.index.get_shard(key_b);
+        let existed = shard.write(|data| {
+            if data.remove(key_b).is_some() {
+                // Берём ссылку на метрики, чтобы не перемещать Option<ShardMetrics>
+                if let Some(metrics) = shard.metrics.as_ref() {
+                    metrics.decrement_key_count();
+                }
+                true
+            } else {
+                false
+            }
+        });
+        self.forget_expiry(key_b);
+
+        if existed {
+            self.append_aof(&[key_b], |aof| aof.append_del(key_b))?;
+        }
+
+        Ok(existed)
+    }
+
     /// Блокирует два шарда для записи (в порядке id) и выполняет замыкание,
     /// передавая mutable reference на их HashMap'ы.
     fn with_two_shards_write<F, R>(
         &self,
         a: usize,
         b: usize,
-        mut f: F,
+        f: F,
     ) -> R
     where
-        F: FnMut(
+        F: FnOnce(
             &mut std::collections::HashMap<Vec<u8>, Vec<u8>>,
             &mut std::collections::HashMap<Vec<u8>, Vec<u8>>,
         ) -> R,
     {
-        let shards = self.index.all_shards();
+        let shards = self.inner.index.all_shards();
         let (first_id, second_id) = if a <= b { (a, b) } else { (b, a) };
         let first = &shards[first_id];
         let second = &shards[second_id];
@@ -265,6 +512,437 @@ impl InPersistentStore {
             f(&mut g2, &mut g1)
         }
     }
+
+    /// Читает, изменяет и записывает значение ключа под одной блокировкой
+    /// шарда, после чего логирует изменение в AOF.
+    ///
+    /// Замыкание получает текущее значение и возвращает `ValueUpdate` вместе
+    /// с результатом операции.
+    fn update_value<F, R>(
+        &self,
+        key: &Sds,
+        f: F,
+    ) -> StoreResult<R>
+    where
+        F: FnOnce(Option<Value>) -> StoreResult<(ValueUpdate, R)>,
+    {
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
+
+        let (pending, result) = shard.write(|data| -> StoreResult<_> {
+            let (update, result) = f(Self::decode_entry(data, key_b)?)?;
+            Ok((Self::apply_update(shard, data, key_b, update), result))
+        })?;
+
+        self.log_pending(key_b, pending)?;
+        Ok(result)
+    }
+
+    /// То же, что `update_value`, но для двух различных ключей: оба шарда
+    /// блокируются на всё время операции.
+    fn update_two_values<F, R>(
+        &self,
+        a: &Sds,
+        b: &Sds,
+        f: F,
+    ) -> StoreResult<R>
+    where
+        F: FnOnce(Option<Value>, Option<Value>) -> StoreResult<(ValueUpdate, ValueUpdate, R)>,
+    {
+        let a_b = a.as_bytes();
+        let b_b = b.as_bytes();
+        let a_id = self.inner.index.shard_for_key(a_b);
+        let b_id = self.inner.index.shard_for_key(b_b);
+        let shards = self.inner.index.all_shards();
+
+        let (pending_a, pending_b, result) = if a_id == b_id {
+            let shard = &shards[a_id];
+            shard.write(|data| -> StoreResult<_> {
+                let (ua, ub, result) = f(
+                    Self::decode_entry(data, a_b)?,
+                    Self::decode_entry(data, b_b)?,
+                )?;
+                Ok((
+                    Self::apply_update(shard, data, a_b, ua),
+                    Self::apply_update(shard, data, b_b, ub),
+                    result,
+                ))
+            })?
+        } else {
+            self.with_two_shards_write(a_id, b_id, |a_map, b_map| -> StoreResult<_> {
+                let (ua, ub, result) = f(
+                    Self::decode_entry(a_map, a_b)?,
+                    Self::decode_entry(b_map, b_b)?,
+                )?;
+                Ok((
+                    Self::apply_update(&shards[a_id], a_map, a_b, ua),
+                    Self::apply_update(&shards[b_id], b_map, b_b, ub),
+                    result,
+                ))
+            })?
+        };
+
+        self.log_pending(a_b, pending_a)?;
+        self.log_pending(b_b, pending_b)?;
+        Ok(result)
+    }
+
+    /// Декодирует значение ключа из данных шарда.
+    fn decode_entry(
+        data: &HashMap<Vec<u8>, Vec<u8>>,
+        key_b: &[u8],
+    ) -> StoreResult<Option<Value>> {
+        data.get(key_b)
+            .map(|raw| Value::from_bytes(raw))
+            .transpose()
+    }
+
+    /// Применяет `ValueUpdate` к данным шарда и обновляет метрики.
+    fn apply_update(
+        shard: &Shard<Vec<u8>>,
+        data: &mut HashMap<Vec<u8>, Vec<u8>>,
+        key_b: &[u8],
+        update: ValueUpdate,
+    ) -> Option<PendingAof> {
+        match update {
+            ValueUpdate::Keep => None,
+            ValueUpdate::Set(value) => {
+                let buf = value.to_bytes();
+                if data.insert(key_b.to_vec(), buf.clone()).is_none() {
+                    if let Some(metrics) = shard.metrics.as_ref() {
+                        metrics.increment_key_count();
+                    }
+                }
+                Some(PendingAof::Set(buf))
+            }
+            ValueUpdate::Delete => {
+                data.remove(key_b)?;
+                if let Some(metrics) = shard.metrics.as_ref() {
+                    metrics.decrement_key_count();
+                }
+                Some(PendingAof::Del)
+            }
+        }
+    }
+
+    /// Восстанавливает GeoSet из сериализованного списка участников. R-tree
+    /// и geohash-индекс не хранятся и строятся заново bulk loading'ом.
+    fn decode_geo_set(raw: &[u8]) -> StoreResult<GeoSet> {
+        let mut rdr = StreamReader::new(Cursor::new(raw)).map_err(|e| StoreError::Io(e.into()))?;
+        let mut members = Vec::new();
+        while let Some(Ok((m_sds, val))) = rdr.next() {
+            if let Value::Array(arr) = val {
+                if let [Value::Float(lon), Value::Float(lat)] = &arr[..] {
+                    let point = GeoPoint {
+                        lon: *lon,
+                        lat: *lat,
+                    };
+                    members.push((m_sds.as_str()?.to_string(), point));
+                }
+            }
+        }
+        Ok(GeoSet::from_members(members))
+    }
+
+    /// Сериализует GeoSet как отсортированный по имени список участников
+    /// `member -> [lon, lat]`.
+    fn encode_geo_set(gs: &GeoSet) -> StoreResult<Vec<u8>> {
+        let entries = gs.sorted_members().into_iter().map(|(m, p)| {
+            let v = Value::Array(vec![Value::Float(p.lon), Value::Float(p.lat)]);
+            (Sds::from_str(m), v)
+        });
+
+        // V3: индекс ключей V4 для встроенного блоба не нужен
+        let mut buf = Vec::new();
+        write_stream_versioned(&mut buf, entries, FormatVersion::V3)
+            .map_err(|e| StoreError::Io(e.into()))?;
+        Ok(buf)
+    }
+
+    /// Возвращает ошибку, пока идёт фоновая перезапись снимка ZDB: новое
+    /// сохранение разошлось бы с перезаписываемой базой.
+    fn ensure_no_zdb_rewrite(&self) -> StoreResult<()> {
+        if self.is_zdb_rewriting() {
+            return Err(StoreError::InvalidOperation(
+                "ZDB rewrite in progress".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Возвращает ключи в `dirty_keys` после неудачного сохранения.
+    fn restore_dirty(
+        &self,
+        keys: HashSet<Sds>,
+    ) {
+        self.inner.dirty_keys.lock().unwrap().extend(keys);
+    }
+
+    /// Записывает полный снимок `index` в `path` через временный файл и
+    /// удаляет устаревшую дельту.
+    fn write_zdb_snapshot(
+        index: &ShardedIndex<Vec<u8>>,
+        path: &Path,
+    ) -> StoreResult<()> {
+        let mut entries = Vec::new();
+        for shard in index.all_shards() {
+            shard.read(|data| -> StoreResult<()> {
+                for (k, raw) in data.iter() {
+                    entries.push((Sds::from_vec(k.clone()), Value::from_bytes(raw)?));
+                }
+                Ok(())
+            })?;
+        }
+
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+        let mut tmp = tempfile::NamedTempFile::new_in(dir.unwrap_or(Path::new(".")))?;
+        write_stream(&mut tmp, entries.into_iter()).map_err(|e| StoreError::Io(e.into()))?;
+        tmp.as_file_mut().sync_all()?;
+        tmp.persist(path).map_err(|e| StoreError::Io(e.error))?;
+
+        match fs::remove_file(delta_path_for(path)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Записывает дельту к базе с CRC `base_crc`: текущие значения `keys` и
+    /// ключей прежней дельты к той же базе, отсутствующие ключи — как
+    /// удалённые.
+    fn write_zdb_delta(
+        &self,
+        base_crc: u32,
+        delta_path: &Path,
+        keys: &HashSet<Sds>,
+    ) -> StoreResult<()> {
+        let mut keys: HashSet<Sds> = keys.clone();
+        if let Ok(file) = fs::File::open(delta_path) {
+            let (header, previous) = read_delta(&mut std::io::BufReader::new(file))
+                .map_err(|e| StoreError::Io(e.into()))?;
+            if header.base_crc == base_crc {
+                keys.extend(previous.into_iter().map(|entry| match entry {
+                    DeltaEntry::Set(key, _) | DeltaEntry::Delete(key) => key,
+                }));
+            }
+        }
+        let mut keys: Vec<Sds> = keys.into_iter().collect();
+        keys.sort_unstable();
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let dir = delta_path.parent().filter(|p| !p.as_os_str().is_empty());
+        let tmp = tempfile::NamedTempFile::new_in(dir.unwrap_or(Path::new(".")))?;
+        let mut writer = DeltaWriter::new(std::io::BufWriter::new(tmp), base_crc, timestamp)
+            .map_err(|e| StoreError::Io(e.into()))?;
+
+        for key in &keys {
+            let raw = self
+                .inner
+                .index
+                .get_shard(key.as_bytes())
+                .read(|data| data.get(key.as_bytes()).cloned());
+            let entry = match raw {
+                Some(raw) => writer.write_set(key, &Value::from_bytes(&raw)?),
+                None => writer.write_delete(key),
+            };
+            entry.map_err(|e| StoreError::Io(e.into()))?;
+        }
+
+        let tmp = writer
+            .finish()
+            .map_err(|e| StoreError::Io(e.into()))?
+            .into_inner()
+            .map_err(|e| StoreError::Io(e.into_error()))?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(delta_path)
+            .map_err(|e| StoreError::Io(e.error))?;
+        Ok(())
+    }
+
+    /// Запускает фоновую полную перезапись снимка `base`, если суммарный
+    /// размер базы и дельты не меньше `zdb_rewrite_min_size` и вдвое больше
+    /// базы.
+    ///
+    /// Пока перезапись идёт, новые сохранения отклоняются. Ошибки перезаписи
+    /// только логируются: база и дельта при этом остаются согласованными.
+    fn maybe_rewrite_zdb(
+        &self,
+        base: &Path,
+        delta_path: &Path,
+    ) {
+        let min_size = self.inner.config.zdb_rewrite_min_size;
+        if min_size == 0 {
+            return;
+        }
+        let base_size = fs::metadata(base).map(|m| m.len()).unwrap_or(0);
+        let delta_size = fs::metadata(delta_path).map(|m| m.len()).unwrap_or(0);
+        let total = base_size + delta_size;
+        if total < min_size || total < base_size.saturating_mul(2) {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        if self.inner.zdb_rewriting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let index = Arc::clone(&self.inner.index);
+        let rewriting = Arc::clone(&self.inner.zdb_rewriting);
+        let base = base.to_path_buf();
+        runtime.spawn_blocking(move || {
+            match Self::write_zdb_snapshot(&index, &base) {
+                Ok(()) => tracing::debug!("ZDB rewrite finished"),
+                Err(e) => tracing::warn!("ZDB rewrite failed: {}", e),
+            }
+            rewriting.store(false, Ordering::Release);
+        });
+    }
+
+    /// Логирует отложенное изменение в AOF.
+    fn log_pending(
+        &self,
+        key_b: &[u8],
+        pending: Option<PendingAof>,
+    ) -> StoreResult<()> {
+        match pending {
+            Some(PendingAof::Set(buf)) => {
+                self.append_aof(&[key_b], |aof| aof.append_set(key_b, &buf))?
+            }
+            Some(PendingAof::Del) => {
+                self.forget_expiry(key_b);
+                self.append_aof(&[key_b], |aof| aof.append_del(key_b))?
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Выполняет запись в AOF под одной блокировкой журнала, помечает `keys`
+    /// изменёнными для дельта-снимка и при необходимости запускает фоновую
+    /// перезапись журнала.
+    fn append_aof<F>(
+        &self,
+        keys: &[&[u8]],
+        f: F,
+    ) -> StoreResult<()>
+    where
+        F: FnOnce(&mut AofLog) -> std::io::Result<()>,
+    {
+        let mut aof = self.inner.aof.lock().unwrap();
+        f(&mut aof)?;
+        self.mark_dirty(keys.iter().copied());
+        self.maybe_rewrite_aof(&aof);
+        Ok(())
+    }
+
+    /// Помечает ключи изменёнными с последнего сохранения ZDB.
+    fn mark_dirty<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a [u8]>,
+    ) {
+        let mut dirty = self.inner.dirty_keys.lock().unwrap();
+        dirty.extend(keys.into_iter().map(|k| Sds::from_vec(k.to_vec())));
+    }
+
+    /// Запускает [`AofLog::rewrite_async`], если журнал не меньше
+    /// `aof_rewrite_min_size` и вырос вдвое с последней перезаписи.
+    ///
+    /// Перезапись выполняется в blocking-пуле Tokio, поэтому вне runtime
+    /// ничего не делает. Ошибки перезаписи только логируются: старый журнал
+    /// при этом остаётся полным.
+    fn maybe_rewrite_aof(
+        &self,
+        aof: &AofLog,
+    ) {
+        let min_size = self.inner.config.aof_rewrite_min_size;
+        if min_size == 0 || aof.is_rewriting() {
+            return;
+        }
+        let size = aof.size();
+        if size < min_size || size < aof.rewrite_base_size().saturating_mul(2) {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let index = Arc::clone(&self.inner.index);
+        let handle = aof.rewrite_async(self.inner.aof_path.clone(), move || {
+            (0..index.num_shards()).flat_map(move |id| {
+                index.all_shards()[id].read(|data| {
+                    data.iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect::<Vec<_>>()
+                })
+            })
+        });
+        runtime.spawn(async move {
+            match handle.await {
+                Ok(Ok(())) => tracing::debug!("AOF rewrite finished"),
+                Ok(Err(e)) => tracing::warn!("AOF rewrite failed: {}", e),
+                Err(e) => tracing::warn!("AOF rewrite task failed: {}", e),
+            }
+        });
+    }
+
+    /// Выполняет `op` над дескриптором хранилища в пуле блокирующих задач
+    /// Tokio: операции пишут в AOF и могут делать fsync, поэтому не должны
+    /// занимать поток runtime.
+    async fn blocking<T, F>(
+        &self,
+        op: F,
+    ) -> StoreResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&InPersistentStore) -> StoreResult<T> + Send + 'static,
+    {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || op(&store))
+            .await
+            .map_err(std::io::Error::from)?
+    }
+
+    /// Копирует ключи, чтобы передать их в блокирующую задачу.
+    fn owned_keys(keys: &[&Sds]) -> Vec<Sds> {
+        keys.iter().map(|&key| key.clone()).collect()
+    }
+
+    /// Возвращает ссылки на ключи в виде, который принимает [`Storage`].
+    fn key_refs(keys: &[Sds]) -> Vec<&Sds> {
+        keys.iter().collect()
+    }
+}
+
+impl InPersistentStoreInner {
+    /// Graceful shutdown с сохранением состояния
+    fn shutdown(&self) -> StoreResult<()> {
+        // Финальный flush AOF
+        {
+            let _aof_guard = self.aof.lock().unwrap();
+            // Убеждаемся что все данные записаны
+            // Note: AofLog не имеет публичного flush метода, но drop сделает
+            // это автоматически
+        }
+
+        // Останавливаем компактирование
+        {
+            let mut recovery_guard = self.recovery_manager.lock().unwrap();
+            recovery_guard.shutdown()?;
+        }
+
+        // Опционально создаём финальный снапшот
+        if self.config.compaction.enable_snapshots {
+            let snapshot = self.recovery_manager.lock().unwrap().create_snapshot();
+            if let Err(e) = snapshot {
+                eprintln!("Warning: Failed to create shutdown snapshot: {e:?}");
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Storage for InPersistentStore {
@@ -274,20 +952,15 @@ impl Storage for InPersistentStore {
         key: &Sds,
         value: Value,
     ) -> StoreResult<()> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
         let val_b = value.to_bytes();
 
-        // Логируем в AOF (синхронно для консистентности)
-        {
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_set(key_b, &val_b)?;
-        }
-
         // Записываем в соответствующий шард
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
         shard.write(|data| {
             let was_new = !data.contains_key(key_b);
-            data.insert(key_b.to_vec(), val_b);
+            data.insert(key_b.to_vec(), val_b.clone());
 
             if was_new {
                 // Берём ссылку на metrics, чтобы не перемещать Option<ShardMetrics>
@@ -297,16 +970,32 @@ impl Storage for InPersistentStore {
             }
         });
 
+        // Логируем в AOF после применения, как и остальные команды: иначе
+        // фоновая перезапись может снять снимок без уже залогированной записи.
+        self.append_aof(&[key_b], |aof| aof.append_set(key_b, &val_b))?;
+
         Ok(())
     }
 
+    /// Устанавливает значение по ключу и возвращает прежнее под одной
+    /// блокировкой шарда.
+    fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>> {
+        self.purge_expired()?;
+        self.update_value(key, |current| Ok((ValueUpdate::Set(value), current)))
+    }
+
     /// Получает значение по ключу, если оно существует.
     fn get(
         &self,
         key: &Sds,
     ) -> StoreResult<Option<Value>> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| match data.get(key_b) {
             Some(val) => Ok(Some(Value::from_bytes(val)?)),
@@ -319,28 +1008,9 @@ impl Storage for InPersistentStore {
         &self,
         key: &Sds,
     ) -> StoreResult<bool> {
-        let key_b = key.as_bytes();
-
-        let shard = self.index.get_shard(key_b);
-        let existed = shard.write(|data| {
-            if data.remove(key_b).is_some() {
-                // Берём ссылку на метрики, чтобы не перемещать Option<ShardMetrics>
-                if let Some(metrics) = shard.metrics.as_ref() {
-                    metrics.decrement_key_count();
-                }
-                true
-            } else {
-                false
-            }
-        });
-
-        if existed {
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_del(key_b)?;
-        }
-
-        Ok(existed)
-    }
+        self.purge_expired()?;
+        self.remove_key(key)
+    }
 
     /// Устанавливает несколько пар ключ-значение сразу.
     /// Оптимизирован для минимизации cross-shard locks.
@@ -348,25 +1018,18 @@ impl Storage for InPersistentStore {
         &self,
         entries: Vec<(&Sds, Value)>,
     ) -> StoreResult<()> {
+        self.purge_expired()?;
         let mut kv_lookup: HashMap<Vec<u8>, Vec<u8>> = HashMap::with_capacity(entries.len());
         for (k, v) in &entries {
             kv_lookup.insert(k.as_bytes().to_vec(), v.to_bytes());
         }
 
         let keys_bytes: Vec<_> = entries.iter().map(|(k, _)| k.as_bytes()).collect();
-        let groups = self.index.group_keys_by_shard(&keys_bytes);
-
-        // WAL: логируем все операции
-        {
-            let mut aof = self.aof.lock().unwrap();
-            for (k, v) in &kv_lookup {
-                aof.append_set(k.as_slice(), v)?;
-            }
-        }
+        let groups = self.inner.index.group_keys_by_shard(&keys_bytes);
 
         // применяем изменения по шардам
         for (shard_id, shard_keys) in groups {
-            let shard = &self.index.all_shards()[shard_id];
+            let shard = &self.inner.index.all_shards()[shard_id];
             shard.write(|data| {
                 let mut new_keys = 0u64;
                 for key_bytes in shard_keys {
@@ -387,6 +1050,14 @@ impl Storage for InPersistentStore {
             });
         }
 
+        // Логируем все операции одним захватом журнала
+        self.append_aof(&keys_bytes, |aof| {
+            for (k, v) in &kv_lookup {
+                aof.append_set(k.as_slice(), v)?;
+            }
+            Ok(())
+        })?;
+
         Ok(())
     }
 
@@ -396,10 +1067,11 @@ impl Storage for InPersistentStore {
         &self,
         keys: &[&Sds],
     ) -> StoreResult<Vec<Option<Value>>> {
+        self.purge_expired()?;
         let mut groups: HashMap<usize, Vec<(usize, &[u8])>> = HashMap::new();
         for (i, k) in keys.iter().enumerate() {
             let kb = k.as_bytes();
-            let sid = self.index.shard_for_key(kb);
+            let sid = self.inner.index.shard_for_key(kb);
             groups.entry(sid).or_default().push((i, kb));
         }
 
@@ -409,7 +1081,7 @@ impl Storage for InPersistentStore {
 
         for sid in shard_ids {
             let items = groups.remove(&sid).unwrap();
-            let shard = &self.index.all_shards()[sid];
+            let shard = &self.inner.index.all_shards()[sid];
 
             let shard_results: Vec<Option<Value>> = shard.read(|data| {
                 items
@@ -437,14 +1109,15 @@ impl Storage for InPersistentStore {
         from: &Sds,
         to: &Sds,
     ) -> StoreResult<()> {
+        self.purge_expired()?;
         let from_b = from.as_bytes();
         let to_b = to.as_bytes();
 
-        let from_shard_id = self.index.shard_for_key(from_b);
-        let to_shard_id = self.index.shard_for_key(to_b);
+        let from_shard_id = self.inner.index.shard_for_key(from_b);
+        let to_shard_id = self.inner.index.shard_for_key(to_b);
 
         if from_shard_id == to_shard_id {
-            let shard = &self.index.all_shards()[from_shard_id];
+            let shard = &self.inner.index.all_shards()[from_shard_id];
             let value = shard.write(|data| {
                 if let Some(val) = data.remove(from_b) {
                     let to_was_new = !data.contains_key(to_b);
@@ -465,9 +1138,11 @@ impl Storage for InPersistentStore {
             });
 
             if let Some(val) = value {
-                let mut aof = self.aof.lock().unwrap();
-                aof.append_del(from_b)?;
-                aof.append_set(to_b, &val)?;
+                self.move_expiry(from_b, to_b);
+                self.append_aof(&[from_b, to_b], |aof| {
+                    aof.append_del(from_b)?;
+                    aof.append_set(to_b, &val)
+                })?;
                 Ok(())
             } else {
                 Err(StoreError::KeyNotFound)
@@ -483,11 +1158,15 @@ impl Storage for InPersistentStore {
                     to_map.insert(to_b.to_vec(), val.clone());
 
                     // Здесь тоже берём ссылки на метрики (as_ref), чтобы не перемещать их
-                    if let Some(m) = self.index.all_shards()[from_shard_id].metrics.as_ref() {
+                    if let Some(m) = self.inner.index.all_shards()[from_shard_id]
+                        .metrics
+                        .as_ref()
+                    {
                         m.decrement_key_count();
                     }
                     if to_was_new {
-                        if let Some(m) = self.index.all_shards()[to_shard_id].metrics.as_ref() {
+                        if let Some(m) = self.inner.index.all_shards()[to_shard_id].metrics.as_ref()
+                        {
                             m.increment_key_count();
                         }
                     }
@@ -496,10 +1175,12 @@ impl Storage for InPersistentStore {
                 });
 
             let value = val_res?;
+            self.move_expiry(from_b, to_b);
 
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_del(from_b)?;
-            aof.append_set(to_b, &value)?;
+            self.append_aof(&[from_b, to_b], |aof| {
+                aof.append_del(from_b)?;
+                aof.append_set(to_b, &value)
+            })?;
 
             Ok(())
         }
@@ -511,14 +1192,15 @@ impl Storage for InPersistentStore {
         from: &Sds,
         to: &Sds,
     ) -> StoreResult<bool> {
+        self.purge_expired()?;
         let from_b = from.as_bytes();
         let to_b = to.as_bytes();
 
-        let from_shard_id = self.index.shard_for_key(from_b);
-        let to_shard_id = self.index.shard_for_key(to_b);
+        let from_shard_id = self.inner.index.shard_for_key(from_b);
+        let to_shard_id = self.inner.index.shard_for_key(to_b);
 
         if from_shard_id == to_shard_id {
-            let shard = &self.index.all_shards()[from_shard_id];
+            let shard = &self.inner.index.all_shards()[from_shard_id];
             let result = shard.write(|data| {
                 if !data.contains_key(from_b) {
                     return Err(StoreError::KeyNotFound);
@@ -537,9 +1219,11 @@ impl Storage for InPersistentStore {
             })?;
 
             if result {
-                let mut aof = self.aof.lock().unwrap();
-                aof.append_del(from_b)?;
-                aof.append_set(to_b, shard.data.read().unwrap().get(to_b).unwrap())?;
+                self.move_expiry(from_b, to_b);
+                self.append_aof(&[from_b, to_b], |aof| {
+                    aof.append_del(from_b)?;
+                    aof.append_set(to_b, shard.data.read().unwrap().get(to_b).unwrap())
+                })?;
             }
 
             Ok(result)
@@ -557,11 +1241,11 @@ impl Storage for InPersistentStore {
                     let to_was_new = !to_map.contains_key(to_b);
                     to_map.insert(to_b.to_vec(), val.clone());
 
-                    if let Some(ref m) = self.index.all_shards()[from_shard_id].metrics {
+                    if let Some(ref m) = self.inner.index.all_shards()[from_shard_id].metrics {
                         m.decrement_key_count();
                     }
                     if to_was_new {
-                        if let Some(ref m) = self.index.all_shards()[to_shard_id].metrics {
+                        if let Some(ref m) = self.inner.index.all_shards()[to_shard_id].metrics {
                             m.increment_key_count();
                         }
                     }
@@ -572,13 +1256,15 @@ impl Storage for InPersistentStore {
             let performed = res?;
 
             if performed {
+                self.move_expiry(from_b, to_b);
                 let value = {
-                    let shard = &self.index.all_shards()[to_shard_id];
+                    let shard = &self.inner.index.all_shards()[to_shard_id];
                     shard.read(|data| data.get(to_b).cloned().unwrap())
                 };
-                let mut aof = self.aof.lock().unwrap();
-                aof.append_del(from_b)?;
-                aof.append_set(to_b, &value)?;
+                self.append_aof(&[from_b, to_b], |aof| {
+                    aof.append_del(from_b)?;
+                    aof.append_set(to_b, &value)
+                })?;
             }
 
             Ok(performed)
@@ -588,8 +1274,9 @@ impl Storage for InPersistentStore {
     /// Очищает всё in-memory содержимое всех шардов.
     /// Очищает всё in-memory содержимое всех шардов.
     fn flushdb(&self) -> StoreResult<()> {
-        for shard in self.index.all_shards().iter() {
+        for shard in self.inner.index.all_shards().iter() {
             shard.write(|data| {
+                self.mark_dirty(data.keys().map(Vec::as_slice));
                 let old_count = data.len() as u64;
                 data.clear();
 
@@ -599,60 +1286,56 @@ impl Storage for InPersistentStore {
                 }
             });
         }
+        self.inner.expires.lock().unwrap().clear();
         Ok(())
     }
 
+    fn flushdb_mode(
+        &self,
+        _mode: FlushMode,
+    ) -> StoreResult<()> {
+        // Данные шардов живут в их блокировках и очищаются на месте.
+        self.flushdb()
+    }
+
+    fn flushall(
+        &self,
+        _mode: FlushMode,
+    ) -> StoreResult<()> {
+        // Единственная база 0 совпадает с текущей.
+        self.flushdb()
+    }
+
     /// Добавляет точку (member, lon, lat) в гео-множество по ключу.
-    /// Возвращает `true`, если member был добавлен впервые.
+    /// Возвращает `true`, если member был добавлен впервые (или, при
+    /// `opts.ch`, его координаты изменились).
     fn geo_add(
         &self,
         key: &Sds,
         lon: f64,
         lat: f64,
         member: &Sds,
+        opts: GeoAddOptions,
     ) -> StoreResult<bool> {
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
-        let result: StoreResult<(Vec<u8>, bool)> = shard.write(|data| {
+        let result: StoreResult<(Option<Vec<u8>>, bool)> = shard.write(|data| {
             // Восстанавливаем существующий GeoSet из байтов, если есть
-            let mut gs = if let Some(raw) = data.get(key_b) {
-                let mut rdr = StreamReader::new(Cursor::new(raw.as_slice()))
-                    .map_err(|e| StoreError::Io(e.into()))?;
-                let mut tmp = GeoSet::new();
-                while let Some(Ok((m_sds, val))) = rdr.next() {
-                    if let Value::Array(arr) = val {
-                        if let [Value::Float(lon0), Value::Float(lat0)] = &arr[..] {
-                            let m = m_sds.as_str()?;
-                            tmp.add(m.to_string(), *lon0, *lat0);
-                        }
-                    }
-                }
-                tmp
-            } else {
-                GeoSet::new()
+            let mut gs = match data.get(key_b) {
+                Some(raw) => Self::decode_geo_set(raw)?,
+                None => GeoSet::new(),
             };
 
             // Добавляем/обновляем member
-            let existed = gs.get(member.as_str()?).is_some();
-            gs.add(member.to_string(), lon, lat);
-            let added = !existed;
-
-            // Сериализуем все записи через итератор (gs.iter()), для детерминированности
-            // сортируем по имени.
-            let mut entries_vec: Vec<(String, Value)> = gs
-                .iter()
-                .map(|(m, p)| {
-                    let v = Value::Array(vec![Value::Float(p.lon), Value::Float(p.lat)]);
-                    (m.clone(), v)
-                })
-                .collect();
-
-            entries_vec.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+            let added = gs.add(member.as_str()?.to_string(), lon, lat, opts);
+            // XX (или некорректные координаты) для отсутствующего ключа —
+            // гео-набор не создаётся
+            if gs.is_empty() {
+                return Ok((None, false));
+            }
 
-            let mut buf = Vec::new();
-            let entries_iter = entries_vec.into_iter().map(|(m, v)| (Sds::from_str(&m), v));
-            write_stream(&mut buf, entries_iter).map_err(|e| StoreError::Io(e.into()))?;
+            let buf = Self::encode_geo_set(&gs)?;
 
             // Сохраняем в shard
             let was_new_key = !data.contains_key(key_b);
@@ -665,18 +1348,17 @@ impl Storage for InPersistentStore {
                 }
             }
 
-            Ok((buf, added))
+            Ok((Some(buf), added))
         });
 
-        let result = result?;
+        let (buf, added) = result?;
 
         // Логируем новое состояние в AOF
-        {
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_set(key_b, &result.0)?;
+        if let Some(buf) = buf {
+            self.append_aof(&[key_b], |aof| aof.append_set(key_b, &buf))?;
         }
 
-        Ok(result.1)
+        Ok(added)
     }
 
     /// Вычисляет расстояние между двумя членами множества в единицах `unit`.
@@ -689,7 +1371,7 @@ impl Storage for InPersistentStore {
         unit: &str,
     ) -> StoreResult<Option<f64>> {
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             let raw = match data.get(key_b) {
@@ -697,17 +1379,7 @@ impl Storage for InPersistentStore {
                 None => return Ok(None),
             };
 
-            let mut gs = GeoSet::new();
-            let mut rdr = StreamReader::new(Cursor::new(raw.as_slice()))
-                .map_err(|e| StoreError::Io(e.into()))?;
-            while let Some(Ok((m_sds, val))) = rdr.next() {
-                if let Value::Array(arr) = val {
-                    if let [Value::Float(lon0), Value::Float(lat0)] = &arr[..] {
-                        let m = m_sds.as_str()?;
-                        gs.add(m.to_string(), *lon0, *lat0);
-                    }
-                }
-            }
+            let gs = Self::decode_geo_set(raw)?;
 
             let m1 = member1.as_str()?;
             let m2 = member2.as_str()?;
@@ -730,7 +1402,7 @@ impl Storage for InPersistentStore {
         member: &Sds,
     ) -> StoreResult<Option<GeoPoint>> {
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             let raw = match data.get(key_b) {
@@ -767,7 +1439,7 @@ impl Storage for InPersistentStore {
         unit: &str,
     ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             let raw = match data.get(key_b) {
@@ -775,17 +1447,7 @@ impl Storage for InPersistentStore {
                 None => return Ok(vec![]),
             };
 
-            let mut gs = GeoSet::new();
-            let mut rdr = StreamReader::new(Cursor::new(raw.as_slice()))
-                .map_err(|e| StoreError::Io(e.into()))?;
-            while let Some(Ok((m_sds, val))) = rdr.next() {
-                if let Value::Array(arr) = val {
-                    if let [Value::Float(lon0), Value::Float(lat0)] = &arr[..] {
-                        let m = m_sds.as_str()?;
-                        gs.add(m.to_string(), *lon0, *lat0);
-                    }
-                }
-            }
+            let gs = Self::decode_geo_set(raw)?;
 
             let r_m = match unit {
                 "km" => radius * 1000.0,
@@ -825,13 +1487,36 @@ impl Storage for InPersistentStore {
         }
     }
 
+    /// Выполняет GEOSEARCH: набор восстанавливается из сериализованного
+    /// значения и ищется в памяти.
+    fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
+
+        shard.read(|data| {
+            let raw = match data.get(key_b) {
+                Some(r) => r,
+                None => return Ok(vec![]),
+            };
+
+            let gs = Self::decode_geo_set(raw)?;
+
+            gs.search(params).ok_or(StoreError::KeyNotFound)
+        })
+    }
+
     fn sadd(
         &self,
         key: &Sds,
         members: &[Sds],
     ) -> StoreResult<usize> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         // результат и буфер для новой сериализации чтобы залогировать в AOF позже
         let mut added = 0usize;
@@ -882,8 +1567,7 @@ impl Storage for InPersistentStore {
 
         // Логируем в AOF только если изменили или создали ключ
         if let Some(buf) = new_buf_opt {
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_set(key_b, &buf)?;
+            self.append_aof(&[key_b], |aof| aof.append_set(key_b, &buf))?;
         }
 
         Ok(added)
@@ -893,8 +1577,9 @@ impl Storage for InPersistentStore {
         &self,
         key: &Sds,
     ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             if let Some(raw) = data.get(key_b) {
@@ -913,8 +1598,9 @@ impl Storage for InPersistentStore {
         &self,
         key: &Sds,
     ) -> StoreResult<usize> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             if let Some(raw) = data.get(key_b) {
@@ -934,8 +1620,9 @@ impl Storage for InPersistentStore {
         key: &Sds,
         member: &Sds,
     ) -> StoreResult<bool> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             if let Some(raw) = data.get(key_b) {
@@ -950,13 +1637,30 @@ impl Storage for InPersistentStore {
         })
     }
 
+    fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
+
+        shard.read(|data| match Self::decode_entry(data, key_b)? {
+            Some(Value::Set(set)) => Ok(members.iter().map(|m| set.contains(*m)).collect()),
+            Some(_) => Err(StoreError::WrongType("SMISMEMBER: key is not a set".into())),
+            None => Ok(vec![false; members.len()]),
+        })
+    }
+
     fn srem(
         &self,
         key: &Sds,
         members: &[Sds],
     ) -> StoreResult<usize> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         let mut removed = 0usize;
         let mut new_buf_opt: Option<Vec<u8>> = None;
@@ -993,7 +1697,7 @@ impl Storage for InPersistentStore {
 
         // если нужно — удаляем ключ и обновляем метрики
         if remove_key {
-            let shard = self.index.get_shard(key_b);
+            let shard = self.inner.index.get_shard(key_b);
             shard.write(|data| {
                 if data.remove(key_b).is_some() {
                     if let Some(metrics) = shard.metrics.as_ref() {
@@ -1001,11 +1705,10 @@ impl Storage for InPersistentStore {
                     }
                 }
             });
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_del(key_b)?;
+            self.forget_expiry(key_b);
+            self.append_aof(&[key_b], |aof| aof.append_del(key_b))?;
         } else if let Some(buf) = new_buf_opt {
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_set(key_b, &buf)?;
+            self.append_aof(&[key_b], |aof| aof.append_set(key_b, &buf))?;
         }
 
         Ok(removed)
@@ -1016,8 +1719,9 @@ impl Storage for InPersistentStore {
         key: &Sds,
         count: isize,
     ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         shard.read(|data| {
             if let Some(raw) = data.get(key_b) {
@@ -1049,8 +1753,9 @@ impl Storage for InPersistentStore {
         key: &Sds,
         count: isize,
     ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired()?;
         let key_b = key.as_bytes();
-        let shard = self.index.get_shard(key_b);
+        let shard = self.inner.index.get_shard(key_b);
 
         let mut out = Vec::new();
         let mut new_buf_opt: Option<Vec<u8>> = None;
@@ -1089,7 +1794,7 @@ impl Storage for InPersistentStore {
         })?;
 
         if remove_key {
-            let shard = self.index.get_shard(key_b);
+            let shard = self.inner.index.get_shard(key_b);
             shard.write(|data| {
                 if data.remove(key_b).is_some() {
                     if let Some(metrics) = shard.metrics.as_ref() {
@@ -1097,139 +1802,1658 @@ impl Storage for InPersistentStore {
                     }
                 }
             });
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_del(key_b)?;
+            self.forget_expiry(key_b);
+            self.append_aof(&[key_b], |aof| aof.append_del(key_b))?;
         } else if let Some(buf) = new_buf_opt {
-            let mut aof = self.aof.lock().unwrap();
-            aof.append_set(key_b, &buf)?;
+            self.append_aof(&[key_b], |aof| aof.append_set(key_b, &buf))?;
         }
 
         Ok(out)
     }
 
-    /// Возвращает общее количество ключей во всех шардах.
-    fn dbsize(&self) -> StoreResult<usize> {
-        let stats = self.index.global_stats();
-        Ok(stats.total_keys as usize)
-    }
-
-    /// Сохраняет текущее состояние базы данных на диск.
-    /// Выполняет flush AOF и создаёт snapshot.
-    fn save(&self) -> StoreResult<()> {
-        // 1. Сначала делаем fsync AOF файла
-        {
-            let aof_guard = self.aof.lock().unwrap();
-            // AOF автоматически синхронизируется при drop guard'а
-            // но мы можем явно вызвать sync, если есть такой метод
-            drop(aof_guard);
+    fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        self.purge_expired()?;
+        let wrong_type = || StoreError::WrongType("SMOVE: key is not a set".into());
+
+        if src == dst {
+            return self.update_value(src, |current| match current {
+                Some(Value::Set(set)) => Ok((ValueUpdate::Keep, set.contains(member))),
+                Some(_) => Err(wrong_type()),
+                None => Ok((ValueUpdate::Keep, false)),
+            });
         }
 
-        // 2. Создаём снапшот, если включено
-        if self.config.compaction.enable_snapshots {
-            let snapshot_info = self.create_snapshot()?;
-
-            if self.config.enable_operation_logging {
-                println!("Snapshot created: {snapshot_info:?}");
+        self.update_two_values(src, dst, |src_val, dst_val| {
+            let mut dst_set = match dst_val {
+                Some(Value::Set(set)) => set,
+                Some(_) => return Err(wrong_type()),
+                None => HashSet::new(),
+            };
+            let mut src_set = match src_val {
+                Some(Value::Set(set)) => set,
+                Some(_) => return Err(wrong_type()),
+                None => return Ok((ValueUpdate::Keep, ValueUpdate::Keep, false)),
+            };
+            if !src_set.remove(member) {
+                return Ok((ValueUpdate::Keep, ValueUpdate::Keep, false));
             }
-        }
+            dst_set.insert(member.clone());
 
-        Ok(())
+            let src_update = if src_set.is_empty() {
+                ValueUpdate::Delete
+            } else {
+                ValueUpdate::Set(Value::Set(src_set))
+            };
+            Ok((src_update, ValueUpdate::Set(Value::Set(dst_set)), true))
+        })
     }
-}
 
-////////////////////////////////////////////////////////////////////////////////
-// Общие реализации трейтов для ListPack
-////////////////////////////////////////////////////////////////////////////////
+    fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let limit = limit.filter(|&l| l > 0).unwrap_or(usize::MAX);
+
+        let Some(mut sets) = self
+            .read_sets(keys, "SINTERCARD")?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(0);
+        };
 
-impl Default for PersistentStoreConfig {
-    fn default() -> Self {
-        Self {
-            sharding: ShardingConfig::default(),
-            sync_policy: SyncPolicy::Always,
-            corruption_policy: CorruptionPolicy::Log,
-            enable_operation_logging: false,
-            compaction: CompactionConfig::default(),
-            recovery_strategy: RecoveryStrategy::Auto,
+        sets.sort_by_key(|set| set.len());
+        let Some((probe, rest)) = sets.split_first() else {
+            return Ok(0);
+        };
+        let mut count = 0;
+        for member in probe {
+            if rest.iter().all(|set| set.contains(member)) {
+                count += 1;
+                if count >= limit {
+                    break;
+                }
+            }
         }
+        Ok(count)
     }
-}
 
-impl Drop for InPersistentStore {
-    fn drop(&mut self) {
-        if let Err(e) = self.shutdown() {
-            eprintln!("Error during store shutdown: {e:?}");
-        }
+    fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let result = match self
+            .read_sets(keys, "SINTERSTORE")?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(mut sets) => {
+                sets.sort_by_key(|set| set.len());
+                match sets.split_first() {
+                    Some((probe, rest)) => probe
+                        .iter()
+                        .filter(|member| rest.iter().all(|set| set.contains(*member)))
+                        .cloned()
+                        .collect(),
+                    None => HashSet::new(),
+                }
+            }
+            None => HashSet::new(),
+        };
+        let len = result.len();
+        self.store_result(dst, Value::Set(result), len)
     }
-}
 
-////////////////////////////////////////////////////////////////////////////////
-// Тесты
-////////////////////////////////////////////////////////////////////////////////
+    fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let result: HashSet<Sds> = self
+            .read_sets(keys, "SUNIONSTORE")?
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect();
+        let len = result.len();
+        self.store_result(dst, Value::Set(result), len)
+    }
 
-#[cfg(test)]
-mod tests {
-    use tempfile::NamedTempFile;
+    fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let mut sets = self.read_sets(keys, "SDIFFSTORE")?.into_iter();
+        let mut result = sets.next().flatten().unwrap_or_default();
+        for set in sets.flatten() {
+            result.retain(|member| !set.contains(member));
+        }
+        let len = result.len();
+        self.store_result(dst, Value::Set(result), len)
+    }
 
-    use super::*;
+    fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        self.purge_expired()?;
+        Ok(zunion(&self.read_zsets(keys, "ZUNION")?, options))
+    }
 
-    // Вспомогательная функция для создания хранилища с sharding
-    fn new_sharded_store(num_shards: usize) -> Result<InPersistentStore, StoreError> {
-        let temp_file = NamedTempFile::new()?;
-        let config = PersistentStoreConfig {
-            sharding: ShardingConfig {
-                num_shards,
-                enable_metrics: true,
-                slow_operation_threshold_us: 1000,
-            },
-            sync_policy: SyncPolicy::Always,
-            corruption_policy: CorruptionPolicy::Log,
-            enable_operation_logging: false,
-            compaction: CompactionConfig::default(),
-            recovery_strategy: RecoveryStrategy::Auto,
-        };
-        InPersistentStore::new(temp_file, config)
+    fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        self.purge_expired()?;
+        Ok(zinter(&self.read_zsets(keys, "ZINTER")?, options))
     }
 
-    /// Тест проверяет, что можно записать ключ и получить его значение, и ключ
-    /// попадает в правильный шард
-    #[test]
-    fn test_set_and_get() -> StoreResult<()> {
-        let store = new_sharded_store(4)?;
+    fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        self.purge_expired()?;
+        Ok(zdiff(&self.read_zsets(keys, "ZDIFF")?))
+    }
 
-        let key = Sds::from_str("test_key");
-        let value = Value::Str(Sds::from_str("test_value"));
+    fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let entries = zunion(&self.read_zsets(keys, "ZUNIONSTORE")?, options);
+        let len = entries.len();
+        self.store_result(dst, zset_from_entries(entries), len)
+    }
 
-        store.set(&key, value.clone())?;
-        let retrieved = store.get(&key)?;
+    fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let entries = zinter(&self.read_zsets(keys, "ZINTERSTORE")?, options);
+        let len = entries.len();
+        self.store_result(dst, zset_from_entries(entries), len)
+    }
 
-        assert_eq!(retrieved, Some(value));
+    fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let entries = zdiff(&self.read_zsets(keys, "ZDIFFSTORE")?);
+        let len = entries.len();
+        self.store_result(dst, zset_from_entries(entries), len)
+    }
 
-        // Проверяем, что ключ попал в правильный шард
-        let stats = store.sharding_info().1;
-        assert_eq!(stats.total_keys, 1);
+    fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>> {
+        self.purge_expired()?;
+        for key in keys {
+            let popped = self.update_value(key, |current| match current {
+                Some(Value::ZSet {
+                    mut dict,
+                    mut sorted,
+                }) => {
+                    let popped = zset_pop(&mut dict, &mut sorted, count, dir);
+                    let update = if popped.is_empty() {
+                        ValueUpdate::Keep
+                    } else if dict.is_empty() {
+                        ValueUpdate::Delete
+                    } else {
+                        ValueUpdate::Set(Value::ZSet { dict, sorted })
+                    };
+                    Ok((update, popped))
+                }
+                Some(_) => Err(StoreError::WrongType(
+                    "ZMPOP: key is not a sorted set".into(),
+                )),
+                None => Ok((ValueUpdate::Keep, Vec::new())),
+            })?;
 
-        Ok(())
+            if !popped.is_empty() {
+                return Ok(Some(((*key).clone(), popped)));
+            }
+        }
+        Ok(None)
     }
 
-    /// Тест проверяет, что можно массово записать и прочитать ключи (mset/mget)
-    /// и шардирование распределяет ключи сбалансировано
-    #[test]
-    fn test_sharded_mset_mget() -> StoreResult<()> {
-        let store = new_sharded_store(3)?;
+    fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
 
-        // --- базовый сценарий: небольшое количество ключей ---
-        let keys: Vec<Sds> = (0..10)
-            .map(|i| Sds::from_str(&format!("key_{i}")))
-            .collect();
-        let values: Vec<Value> = (0..10)
-            .map(|i| Value::Str(Sds::from_str(&format!("value_{i}"))))
-            .collect();
+        shard.read(|data| match Self::decode_entry(data, key_b)? {
+            Some(Value::ZSet { dict, .. }) => {
+                Ok(members.iter().map(|m| dict.get(m).copied()).collect())
+            }
+            Some(_) => Err(StoreError::WrongType(
+                "ZMSCORE: key is not a sorted set".into(),
+            )),
+            None => Ok(vec![None; members.len()]),
+        })
+    }
 
-        let entries: Vec<(&Sds, Value)> = keys.iter().zip(values.iter().cloned()).collect();
-        store.mset(entries)?;
+    fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
 
-        let key_refs: Vec<&Sds> = keys.iter().collect();
-        let retrieved = store.mget(&key_refs)?;
+        shard.read(|data| {
+            if let Some(raw) = data.get(key_b) {
+                match Value::from_bytes(raw)? {
+                    Value::Hash(hash) => hash.random_fields(count, with_values),
+                    _ => Err(StoreError::InvalidType),
+                }
+            } else {
+                Ok(Vec::new())
+            }
+        })
+    }
+
+    fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
+        self.purge_expired()?;
+        self.update_hash(key, "HINCRBY", |hash| hash.incr_by(field.clone(), delta))
+    }
+
+    fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
+        self.purge_expired()?;
+        self.update_hash(key, "HINCRBYFLOAT", |hash| {
+            hash.incr_by_float(field.clone(), delta)
+        })
+    }
+
+    fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
+
+        shard.read(|data| {
+            if let Some(raw) = data.get(key_b) {
+                match Value::from_bytes(raw)? {
+                    Value::Hash(hash) => Ok(hash.get_many(fields)),
+                    _ => Err(StoreError::WrongType("HMGET: key is not a hash".into())),
+                }
+            } else {
+                Ok(vec![None; fields.len()])
+            }
+        })
+    }
+
+    fn memory_usage(
+        &self,
+        key: &Sds,
+        _samples: usize,
+    ) -> StoreResult<Option<usize>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let shard = self.inner.index.get_shard(key_b);
+
+        // Значения хранятся сериализованными, поэтому учитываются буферы
+        // ключа и значения; выборка не нужна.
+        Ok(shard.read(|data| {
+            data.get_key_value(key_b)
+                .map(|(k, raw)| 2 * std::mem::size_of::<Vec<u8>>() + k.capacity() + raw.capacity())
+        }))
+    }
+
+    fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>> {
+        self.purge_expired()?;
+        let wrong_type = || StoreError::WrongType("LMOVE: key is not a list".into());
+
+        if src == dst {
+            return self.update_value(src, |current| match current {
+                Some(Value::List(mut list)) => match list.pop(src_dir) {
+                    Some(item) => {
+                        list.push(dst_dir, item.clone());
+                        Ok((ValueUpdate::Set(Value::List(list)), Some(item)))
+                    }
+                    None => Ok((ValueUpdate::Keep, None)),
+                },
+                Some(_) => Err(wrong_type()),
+                None => Ok((ValueUpdate::Keep, None)),
+            });
+        }
+
+        self.update_two_values(src, dst, |src_val, dst_val| {
+            let mut dst_list = match dst_val {
+                Some(Value::List(list)) => list,
+                Some(_) => return Err(wrong_type()),
+                None => QuickList::new(64),
+            };
+            let mut src_list = match src_val {
+                Some(Value::List(list)) => list,
+                Some(_) => return Err(wrong_type()),
+                None => return Ok((ValueUpdate::Keep, ValueUpdate::Keep, None)),
+            };
+            let Some(item) = src_list.pop(src_dir) else {
+                return Ok((ValueUpdate::Keep, ValueUpdate::Keep, None));
+            };
+            dst_list.push(dst_dir, item.clone());
+
+            let src_update = if src_list.is_empty() {
+                ValueUpdate::Delete
+            } else {
+                ValueUpdate::Set(Value::List(src_list))
+            };
+            Ok((
+                src_update,
+                ValueUpdate::Set(Value::List(dst_list)),
+                Some(item),
+            ))
+        })
+    }
+
+    fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>> {
+        self.purge_expired()?;
+        for key in keys {
+            let popped = self.update_value(key, |current| match current {
+                Some(Value::List(mut list)) => {
+                    let popped: Vec<Sds> = (0..count).map_while(|_| list.pop(dir)).collect();
+                    let update = if popped.is_empty() {
+                        ValueUpdate::Keep
+                    } else if list.is_empty() {
+                        ValueUpdate::Delete
+                    } else {
+                        ValueUpdate::Set(Value::List(list))
+                    };
+                    Ok((update, popped))
+                }
+                Some(_) => Err(StoreError::WrongType("LMPOP: key is not a list".into())),
+                None => Ok((ValueUpdate::Keep, Vec::new())),
+            })?;
+
+            if !popped.is_empty() {
+                return Ok(Some(((*key).clone(), popped)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
+    ) -> StoreResult<()> {
+        self.purge_expired()?;
+        self.update_value(key, |current| match current {
+            Some(Value::List(mut list)) => {
+                if list.set(index, value) {
+                    Ok((ValueUpdate::Set(Value::List(list)), ()))
+                } else {
+                    Err(StoreError::InvalidArgument("index out of range".into()))
+                }
+            }
+            Some(_) => Err(StoreError::WrongType("LSET: key is not a list".into())),
+            None => Err(StoreError::KeyNotFound),
+        })
+    }
+
+    fn getdel(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Sds>> {
+        self.purge_expired()?;
+        self.update_value(key, |current| match current {
+            Some(Value::Str(s)) => Ok((ValueUpdate::Delete, Some(s))),
+            _ => Ok((ValueUpdate::Keep, None)),
+        })
+    }
+
+    fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>> {
+        self.purge_expired()?;
+        let ttl = expiry.ttl();
+        // Время истечения уже прошло — ключ удаляется сразу.
+        let expire_now = ttl.is_some_and(|ttl| ttl.is_zero());
+
+        let value = self.update_value(key, |current| match current {
+            Some(Value::Str(s)) => {
+                let update = if expire_now {
+                    ValueUpdate::Delete
+                } else {
+                    ValueUpdate::Keep
+                };
+                Ok((update, Some(s)))
+            }
+            Some(_) => Err(StoreError::WrongType("GETEX: key is not a string".into())),
+            None => Ok((ValueUpdate::Keep, None)),
+        })?;
+
+        if value.is_some() && !expire_now {
+            match ttl {
+                Some(ttl) => self
+                    .inner
+                    .expires
+                    .lock()
+                    .unwrap()
+                    .set(key.as_bytes().to_vec(), ttl),
+                None if expiry == GetExExpiry::Persist => {
+                    self.forget_expiry(key.as_bytes());
+                }
+                None => {}
+            }
+        }
+        Ok(value)
+    }
+
+    fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool> {
+        self.purge_expired()?;
+        if let Some(db) = dst_db.filter(|&db| db != 0) {
+            return Err(StoreError::InvalidArgument(format!(
+                "DB index is out of range. Zumic supports only DB 0, got {db}"
+            )));
+        }
+        if src == dst {
+            return Err(StoreError::InvalidArgument(
+                "source and destination objects are the same".into(),
+            ));
+        }
+
+        let copied =
+            self.update_two_values(src, dst, |src_val, dst_val| match (src_val, dst_val) {
+                (None, _) => Ok((ValueUpdate::Keep, ValueUpdate::Keep, false)),
+                (Some(_), Some(_)) if !replace => Ok((ValueUpdate::Keep, ValueUpdate::Keep, false)),
+                (Some(value), _) => Ok((ValueUpdate::Keep, ValueUpdate::Set(value), true)),
+            })?;
+
+        if copied {
+            self.copy_expiry(src.as_bytes(), dst.as_bytes());
+        }
+        Ok(copied)
+    }
+
+    /// Возвращает абсолютное время истечения ключа в unix-миллисекундах:
+    /// `Some(-1)` для ключа без TTL и `None` для отсутствующего ключа.
+    fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<i64>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let exists = self
+            .inner
+            .index
+            .get_shard(key_b)
+            .read(|data| data.contains_key(key_b));
+        if !exists {
+            return Ok(None);
+        }
+        let at = self.inner.expires.lock().unwrap().expire_at_unix_ms(key_b);
+        Ok(Some(at.map_or(-1, |ms| ms as i64)))
+    }
+
+    /// Устанавливает или снимает TTL ключа. Нулевой TTL удаляет ключ (с
+    /// записью `DEL` в AOF).
+    fn set_expiry(
+        &self,
+        key: &Sds,
+        ttl: Option<Duration>,
+    ) -> StoreResult<bool> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let exists = self
+            .inner
+            .index
+            .get_shard(key_b)
+            .read(|data| data.contains_key(key_b));
+        if !exists {
+            return Ok(false);
+        }
+        match ttl {
+            Some(ttl) if ttl.is_zero() => {
+                self.remove_key(key)?;
+            }
+            Some(ttl) => self.inner.expires.lock().unwrap().set(key_b.to_vec(), ttl),
+            None => {
+                self.forget_expiry(key_b);
+            }
+        }
+        Ok(true)
+    }
+
+    fn type_of(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<ValueType>> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let value = self
+            .inner
+            .index
+            .get_shard(key_b)
+            .read(|data| data.get(key_b).map(|bytes| Value::from_bytes(bytes)))
+            .transpose()?;
+        Ok(value.and_then(|value| value.value_type()))
+    }
+
+    /// Время доступа к ключам не отслеживается: возвращает число
+    /// существующих ключей.
+    fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        self.purge_expired()?;
+        Ok(keys
+            .iter()
+            .filter(|key| {
+                let key_b = key.as_bytes();
+                self.inner
+                    .index
+                    .get_shard(key_b)
+                    .read(|data| data.contains_key(key_b))
+            })
+            .count())
+    }
+
+    /// Время доступа к ключам не отслеживается.
+    fn idletime(
+        &self,
+        _key: &Sds,
+    ) -> StoreResult<Option<u64>> {
+        Err(StoreError::NotImplemented(
+            "OBJECT IDLETIME is not supported by the persistent engine".into(),
+        ))
+    }
+
+    fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> StoreResult<()> {
+        match policy {
+            EvictionPolicy::NoEviction => Ok(()),
+            _ => Err(StoreError::NotImplemented(
+                "Key eviction is not supported by the persistent engine".into(),
+            )),
+        }
+    }
+
+    fn freq(
+        &self,
+        _key: &Sds,
+    ) -> StoreResult<Option<u8>> {
+        Err(StoreError::NotImplemented(
+            "OBJECT FREQ is not supported by the persistent engine".into(),
+        ))
+    }
+
+    fn set_access_meta(
+        &self,
+        _key: &Sds,
+        _idle_secs: Option<u64>,
+        _freq: Option<u8>,
+    ) -> StoreResult<bool> {
+        Err(StoreError::NotImplemented(
+            "Key access metadata is not tracked by the persistent engine".into(),
+        ))
+    }
+
+    fn persist(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        self.purge_expired()?;
+        let key_b = key.as_bytes();
+        let exists = self
+            .inner
+            .index
+            .get_shard(key_b)
+            .read(|data| data.contains_key(key_b));
+        Ok(exists && self.forget_expiry(key_b))
+    }
+
+    /// Сохраняет результаты гео-поиска по набору `src` в `dst` как
+    /// отсортированное множество. Прежнее значение `dst` удаляется, при
+    /// пустом результате новое не создаётся.
+    fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64> {
+        let hits = self.geo_search(src, &params)?;
+
+        self.del(dst)?;
+        if hits.is_empty() {
+            return Ok(0);
+        }
+
+        let stored = hits.len() as i64;
+        self.set(dst, params.to_zset(hits))?;
+        Ok(stored)
+    }
+
+    fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()> {
+        let mut hlls = Vec::with_capacity(sources.len() + 1);
+        for key in std::iter::once(dest).chain(sources.iter().copied()) {
+            match self.get(key)? {
+                Some(Value::HyperLogLog(hll)) => hlls.push(hll),
+                Some(_) => return Err(StoreError::InvalidType),
+                None => {}
+            }
+        }
+        let refs: Vec<&Hll> = hlls.iter().map(Box::as_ref).collect();
+        self.set(dest, Value::HyperLogLog(Box::new(Hll::merge_all(&refs))))
+    }
+
+    /// Возвращает общее количество ключей во всех шардах.
+    fn dbsize(&self) -> StoreResult<usize> {
+        self.purge_expired()?;
+        let stats = self.inner.index.global_stats();
+        Ok(stats.total_keys as usize)
+    }
+
+    fn keys(
+        &self,
+        pattern: &str,
+    ) -> StoreResult<Vec<Sds>> {
+        self.purge_expired()?;
+        let mut keys = Vec::new();
+        for shard in self.inner.index.all_shards() {
+            shard.read(|data| {
+                keys.extend(
+                    data.keys()
+                        .filter(|key| glob_match(pattern.as_bytes(), key))
+                        .map(|key| Sds::from_vec(key.clone())),
+                );
+            });
+        }
+        Ok(keys)
+    }
+
+    fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> StoreResult<()> {
+        // Персистентное хранилище содержит единственную базу 0.
+        if db1 == 0 && db2 == 0 {
+            Ok(())
+        } else {
+            Err(StoreError::InvalidArgument(
+                "DB index is out of range".to_string(),
+            ))
+        }
+    }
+
+    /// Сохраняет текущее состояние базы данных на диск.
+    /// Выполняет flush AOF и создаёт snapshot.
+    fn save(&self) -> StoreResult<()> {
+        // 1. Сначала делаем fsync AOF файла
+        {
+            let aof_guard = self.inner.aof.lock().unwrap();
+            // AOF автоматически синхронизируется при drop guard'а
+            // но мы можем явно вызвать sync, если есть такой метод
+            drop(aof_guard);
+        }
+
+        // 2. Создаём снапшот, если включено
+        if self.inner.config.compaction.enable_snapshots {
+            let snapshot_info = self.create_snapshot()?;
+
+            if self.inner.config.enable_operation_logging {
+                println!("Snapshot created: {snapshot_info:?}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl crate::engine::AsyncStorage for InPersistentStore {
+    async fn set(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<()> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::set(store, &key, value))
+            .await
+    }
+
+    async fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::set_get(store, &key, value))
+            .await
+    }
+
+    async fn get(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Value>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::get(store, &key)).await
+    }
+
+    async fn del(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::del(store, &key)).await
+    }
+
+    async fn mset(
+        &self,
+        entries: Vec<(&Sds, Value)>,
+    ) -> StoreResult<()> {
+        let (keys, values): (Vec<Sds>, Vec<Value>) = entries
+            .into_iter()
+            .map(|(key, value)| (key.clone(), value))
+            .unzip();
+        self.blocking(move |store| Storage::mset(store, keys.iter().zip(values).collect()))
+            .await
+    }
+
+    async fn mget(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<Option<Value>>> {
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::mget(store, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn rename(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) -> StoreResult<()> {
+        let from = from.clone();
+        let to = to.clone();
+        self.blocking(move |store| Storage::rename(store, &from, &to))
+            .await
+    }
+
+    async fn renamenx(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) -> StoreResult<bool> {
+        let from = from.clone();
+        let to = to.clone();
+        self.blocking(move |store| Storage::renamenx(store, &from, &to))
+            .await
+    }
+
+    async fn flushdb(&self) -> StoreResult<()> {
+        self.blocking(Storage::flushdb).await
+    }
+
+    async fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        self.blocking(move |store| Storage::flushdb_mode(store, mode))
+            .await
+    }
+
+    async fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        self.blocking(move |store| Storage::flushall(store, mode))
+            .await
+    }
+
+    async fn dbsize(&self) -> StoreResult<usize> {
+        self.blocking(Storage::dbsize).await
+    }
+
+    async fn keys(
+        &self,
+        pattern: &str,
+    ) -> StoreResult<Vec<Sds>> {
+        let pattern = pattern.to_owned();
+        self.blocking(move |store| Storage::keys(store, &pattern))
+            .await
+    }
+
+    async fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> StoreResult<()> {
+        self.blocking(move |store| Storage::swapdb(store, db1, db2))
+            .await
+    }
+
+    async fn geo_add(
+        &self,
+        key: &Sds,
+        lon: f64,
+        lat: f64,
+        member: &Sds,
+        opts: GeoAddOptions,
+    ) -> StoreResult<bool> {
+        let key = key.clone();
+        let member = member.clone();
+        self.blocking(move |store| Storage::geo_add(store, &key, lon, lat, &member, opts))
+            .await
+    }
+
+    async fn geo_dist(
+        &self,
+        key: &Sds,
+        member1: &Sds,
+        member2: &Sds,
+        unit: &str,
+    ) -> StoreResult<Option<f64>> {
+        let key = key.clone();
+        let member1 = member1.clone();
+        let member2 = member2.clone();
+        let unit = unit.to_owned();
+        self.blocking(move |store| Storage::geo_dist(store, &key, &member1, &member2, &unit))
+            .await
+    }
+
+    async fn geo_pos(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<Option<GeoPoint>> {
+        let key = key.clone();
+        let member = member.clone();
+        self.blocking(move |store| Storage::geo_pos(store, &key, &member))
+            .await
+    }
+
+    async fn geo_radius(
+        &self,
+        key: &Sds,
+        lon: f64,
+        lat: f64,
+        radius: f64,
+        unit: &str,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        let key = key.clone();
+        let unit = unit.to_owned();
+        self.blocking(move |store| Storage::geo_radius(store, &key, lon, lat, radius, &unit))
+            .await
+    }
+
+    async fn geo_radius_by_member(
+        &self,
+        key: &Sds,
+        member: &Sds,
+        radius: f64,
+        unit: &str,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        let key = key.clone();
+        let member = member.clone();
+        let unit = unit.to_owned();
+        self.blocking(move |store| {
+            Storage::geo_radius_by_member(store, &key, &member, radius, &unit)
+        })
+        .await
+    }
+
+    async fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        let key = key.clone();
+        let params = params.clone();
+        self.blocking(move |store| Storage::geo_search(store, &key, &params))
+            .await
+    }
+
+    async fn sadd(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        let key = key.clone();
+        let members = members.to_vec();
+        self.blocking(move |store| Storage::sadd(store, &key, &members))
+            .await
+    }
+
+    async fn smembers(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Vec<Sds>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::smembers(store, &key))
+            .await
+    }
+
+    async fn scard(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<usize> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::scard(store, &key))
+            .await
+    }
+
+    async fn sismember(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        let key = key.clone();
+        let member = member.clone();
+        self.blocking(move |store| Storage::sismember(store, &key, &member))
+            .await
+    }
+
+    async fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>> {
+        let key = key.clone();
+        let members = Self::owned_keys(members);
+        self.blocking(move |store| Storage::smismember(store, &key, &Self::key_refs(&members)))
+            .await
+    }
+
+    async fn srem(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        let key = key.clone();
+        let members = members.to_vec();
+        self.blocking(move |store| Storage::srem(store, &key, &members))
+            .await
+    }
+
+    async fn srandmember(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::srandmember(store, &key, count))
+            .await
+    }
+
+    async fn spop(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::spop(store, &key, count))
+            .await
+    }
+
+    async fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        let src = src.clone();
+        let dst = dst.clone();
+        let member = member.clone();
+        self.blocking(move |store| Storage::smove(store, &src, &dst, &member))
+            .await
+    }
+
+    async fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize> {
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::sintercard(store, &Self::key_refs(&keys), limit))
+            .await
+    }
+
+    async fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let dst = dst.clone();
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::sinterstore(store, &dst, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let dst = dst.clone();
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::sunionstore(store, &dst, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let dst = dst.clone();
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::sdiffstore(store, &dst, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        let keys = Self::owned_keys(keys);
+        let options = options.clone();
+        self.blocking(move |store| Storage::zunion(store, &Self::key_refs(&keys), &options))
+            .await
+    }
+
+    async fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        let keys = Self::owned_keys(keys);
+        let options = options.clone();
+        self.blocking(move |store| Storage::zinter(store, &Self::key_refs(&keys), &options))
+            .await
+    }
+
+    async fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::zdiff(store, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        let dst = dst.clone();
+        let keys = Self::owned_keys(keys);
+        let options = options.clone();
+        self.blocking(move |store| {
+            Storage::zunionstore(store, &dst, &Self::key_refs(&keys), &options)
+        })
+        .await
+    }
+
+    async fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        let dst = dst.clone();
+        let keys = Self::owned_keys(keys);
+        let options = options.clone();
+        self.blocking(move |store| {
+            Storage::zinterstore(store, &dst, &Self::key_refs(&keys), &options)
+        })
+        .await
+    }
+
+    async fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let dst = dst.clone();
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::zdiffstore(store, &dst, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>> {
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::zmpop(store, &Self::key_refs(&keys), dir, count))
+            .await
+    }
+
+    async fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>> {
+        let key = key.clone();
+        let members = Self::owned_keys(members);
+        self.blocking(move |store| Storage::zmscore(store, &key, &Self::key_refs(&members)))
+            .await
+    }
+
+    async fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::hrandfield(store, &key, count, with_values))
+            .await
+    }
+
+    async fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
+        let key = key.clone();
+        let field = field.clone();
+        self.blocking(move |store| Storage::hincrby(store, &key, &field, delta))
+            .await
+    }
+
+    async fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
+        let key = key.clone();
+        let field = field.clone();
+        self.blocking(move |store| Storage::hincrbyfloat(store, &key, &field, delta))
+            .await
+    }
+
+    async fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>> {
+        let key = key.clone();
+        let fields = Self::owned_keys(fields);
+        self.blocking(move |store| Storage::hmget(store, &key, &Self::key_refs(&fields)))
+            .await
+    }
+
+    async fn memory_usage(
+        &self,
+        key: &Sds,
+        samples: usize,
+    ) -> StoreResult<Option<usize>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::memory_usage(store, &key, samples))
+            .await
+    }
+
+    async fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>> {
+        let src = src.clone();
+        let dst = dst.clone();
+        self.blocking(move |store| Storage::lmove(store, &src, &dst, src_dir, dst_dir))
+            .await
+    }
+
+    async fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>> {
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::lmpop(store, &Self::key_refs(&keys), dir, count))
+            .await
+    }
+
+    async fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
+    ) -> StoreResult<()> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::lset(store, &key, index, value))
+            .await
+    }
+
+    async fn getdel(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Sds>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::getdel(store, &key))
+            .await
+    }
+
+    async fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::getex(store, &key, expiry))
+            .await
+    }
+
+    async fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool> {
+        let src = src.clone();
+        let dst = dst.clone();
+        self.blocking(move |store| Storage::copy(store, &src, &dst, dst_db, replace))
+            .await
+    }
+
+    async fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<i64>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::expiretime(store, &key))
+            .await
+    }
+
+    async fn set_expiry(
+        &self,
+        key: &Sds,
+        ttl: Option<Duration>,
+    ) -> StoreResult<bool> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::set_expiry(store, &key, ttl))
+            .await
+    }
+
+    async fn type_of(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<ValueType>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::type_of(store, &key))
+            .await
+    }
+
+    async fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        let keys = Self::owned_keys(keys);
+        self.blocking(move |store| Storage::touch(store, &Self::key_refs(&keys)))
+            .await
+    }
+
+    async fn idletime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u64>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::idletime(store, &key))
+            .await
+    }
+
+    async fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> StoreResult<()> {
+        self.blocking(move |store| Storage::set_eviction_policy(store, policy))
+            .await
+    }
+
+    async fn freq(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u8>> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::freq(store, &key)).await
+    }
+
+    async fn set_access_meta(
+        &self,
+        key: &Sds,
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> StoreResult<bool> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::set_access_meta(store, &key, idle_secs, freq))
+            .await
+    }
+
+    async fn persist(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        let key = key.clone();
+        self.blocking(move |store| Storage::persist(store, &key))
+            .await
+    }
+
+    async fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64> {
+        let dst = dst.clone();
+        let src = src.clone();
+        self.blocking(move |store| Storage::geosearchstore(store, &dst, &src, params))
+            .await
+    }
+
+    async fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()> {
+        let dest = dest.clone();
+        let sources = Self::owned_keys(sources);
+        self.blocking(move |store| Storage::pfmerge(store, &dest, &Self::key_refs(&sources)))
+            .await
+    }
+
+    /// Сбрасывает буфер AOF, делает fsync файла журнала и создаёт снапшот,
+    /// если они включены.
+    async fn save(&self) -> StoreResult<()> {
+        self.blocking(|store| {
+            store.inner.aof.lock().unwrap().flush()?;
+            fs::File::open(&store.inner.aof_path)?.sync_all()?;
+
+            if store.inner.config.compaction.enable_snapshots {
+                let snapshot_info = store.create_snapshot()?;
+                tracing::debug!("Snapshot created: {:?}", snapshot_info);
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для ListPack
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for PersistentStoreConfig {
+    fn default() -> Self {
+        Self {
+            sharding: ShardingConfig::default(),
+            sync_policy: SyncPolicy::Always,
+            corruption_policy: CorruptionPolicy::Log,
+            enable_operation_logging: false,
+            compaction: CompactionConfig::default(),
+            recovery_strategy: RecoveryStrategy::Auto,
+            aof_rewrite_min_size: 64 * 1024 * 1024,
+            zdb_rewrite_min_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl Drop for InPersistentStoreInner {
+    /// Останавливает хранилище, когда удаляется последний дескриптор:
+    /// состояние принадлежит `Arc`, поэтому это происходит ровно один раз.
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown() {
+            eprintln!("Error during store shutdown: {e:?}");
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::{
+        database::geo_distance::DistanceUnit, engine::load_from_zdb, GeoSearchFrom, GeoSearchShape,
+        InMemoryStore,
+    };
+
+    // Вспомогательная функция для создания хранилища с sharding
+    fn new_sharded_store(num_shards: usize) -> Result<InPersistentStore, StoreError> {
+        let temp_file = NamedTempFile::new()?;
+        let config = PersistentStoreConfig {
+            sharding: ShardingConfig {
+                num_shards,
+                enable_metrics: true,
+                slow_operation_threshold_us: 1000,
+            },
+            sync_policy: SyncPolicy::Always,
+            corruption_policy: CorruptionPolicy::Log,
+            enable_operation_logging: false,
+            compaction: CompactionConfig::default(),
+            recovery_strategy: RecoveryStrategy::Auto,
+            aof_rewrite_min_size: 0,
+            zdb_rewrite_min_size: 0,
+        };
+        InPersistentStore::new(temp_file, config)
+    }
+
+    /// Тест проверяет, что журнал, выросший до `aof_rewrite_min_size`,
+    /// перезаписывается в фоне и после перезапуска даёт то же состояние.
+    #[tokio::test]
+    async fn test_auto_aof_rewrite() -> StoreResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("appendonly.aof");
+        let config = PersistentStoreConfig {
+            aof_rewrite_min_size: 4096,
+            ..PersistentStoreConfig::default()
+        };
+
+        let store = InPersistentStore::new(&path, config.clone())?;
+        for i in 0..500 {
+            let key = Sds::from_str(&format!("key{}", i % 10));
+            store.set(&key, Value::Int(i))?;
+        }
+        while store.inner.aof.lock().unwrap().is_rewriting() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        // После перезаписи базовый размер больше одного заголовка `AOF2`
+        assert!(store.inner.aof.lock().unwrap().rewrite_base_size() > 4);
+        drop(store);
+
+        let store = InPersistentStore::new(&path, config)?;
+        for i in 0..10 {
+            let key = Sds::from_str(&format!("key{i}"));
+            assert_eq!(store.get(&key)?, Some(Value::Int(490 + i)));
+        }
+
+        Ok(())
+    }
+
+    /// Тест проверяет, что дельта содержит изменения и удаления после полного
+    /// снимка, накапливается между сохранениями и загружается поверх базы.
+    #[test]
+    fn test_zdb_delta_snapshot() -> StoreResult<()> {
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("dump.zdb");
+        let store = new_sharded_store(4)?;
+        for (k, v) in [("a", 1), ("b", 2), ("c", 3)] {
+            store.set(&Sds::from_str(k), Value::Int(v))?;
+        }
+        store.save_zdb(&base)?;
+        assert!(store.inner.dirty_keys.lock().unwrap().is_empty());
+
+        store.set(&Sds::from_str("a"), Value::Int(10))?;
+        store.del(&Sds::from_str("b"))?;
+        let delta = store.save_zdb_delta(&base)?;
+        assert!(store.inner.dirty_keys.lock().unwrap().is_empty());
+
+        store.set(&Sds::from_str("d"), Value::Int(4))?;
+        store.save_zdb_delta(&base)?;
+
+        let mut loaded = InMemoryStore::new();
+        load_from_zdb(&mut loaded, delta.to_str().unwrap(), None).unwrap();
+        let mut keys: Vec<_> = loaded.iter().collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            keys,
+            vec![
+                (Sds::from_str("a"), Value::Int(10)),
+                (Sds::from_str("c"), Value::Int(3)),
+                (Sds::from_str("d"), Value::Int(4)),
+            ]
+        );
+
+        // Полный снимок делает дельту устаревшей
+        store.save_zdb(&base)?;
+        assert!(!delta.exists());
+        Ok(())
+    }
+
+    /// Тест проверяет, что дельта, выросшая больше базы, запускает фоновую
+    /// полную перезапись снимка.
+    #[tokio::test]
+    async fn test_zdb_delta_triggers_rewrite() -> StoreResult<()> {
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("dump.zdb");
+        let config = PersistentStoreConfig {
+            zdb_rewrite_min_size: 1,
+            ..PersistentStoreConfig::default()
+        };
+        let store = InPersistentStore::new(dir.path().join("appendonly.aof"), config)?;
+        store.save_zdb(&base)?;
+
+        for i in 0..50 {
+            store.set(&Sds::from_str(&format!("key{i}")), Value::Int(i))?;
+        }
+        let delta = store.save_zdb_delta(&base)?;
+        while store.is_zdb_rewriting() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert!(!delta.exists());
+
+        let mut loaded = InMemoryStore::new();
+        load_from_zdb(&mut loaded, base.to_str().unwrap(), None).unwrap();
+        assert_eq!(loaded.iter().count(), 50);
+        Ok(())
+    }
+
+    /// Тест проверяет, что можно записать ключ и получить его значение, и ключ
+    /// попадает в правильный шард
+    #[test]
+    fn test_set_and_get() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+
+        let key = Sds::from_str("test_key");
+        let value = Value::Str(Sds::from_str("test_value"));
+
+        store.set(&key, value.clone())?;
+        let retrieved = store.get(&key)?;
+
+        assert_eq!(retrieved, Some(value));
+
+        // Проверяем, что ключ попал в правильный шард
+        let stats = store.sharding_info().1;
+        assert_eq!(stats.total_keys, 1);
+
+        Ok(())
+    }
+
+    /// Тест проверяет, что можно массово записать и прочитать ключи (mset/mget)
+    /// и шардирование распределяет ключи сбалансировано
+    #[test]
+    fn test_sharded_mset_mget() -> StoreResult<()> {
+        let store = new_sharded_store(3)?;
+
+        // --- базовый сценарий: небольшое количество ключей ---
+        let keys: Vec<Sds> = (0..10)
+            .map(|i| Sds::from_str(&format!("key_{i}")))
+            .collect();
+        let values: Vec<Value> = (0..10)
+            .map(|i| Value::Str(Sds::from_str(&format!("value_{i}"))))
+            .collect();
+
+        let entries: Vec<(&Sds, Value)> = keys.iter().zip(values.iter().cloned()).collect();
+        store.mset(entries)?;
+
+        let key_refs: Vec<&Sds> = keys.iter().collect();
+        let retrieved = store.mget(&key_refs)?;
 
         assert_eq!(retrieved.len(), 10);
         for (i, result) in retrieved.iter().enumerate() {
@@ -1282,6 +3506,8 @@ mod tests {
             enable_operation_logging: false,
             compaction: CompactionConfig::default(),
             recovery_strategy: RecoveryStrategy::Auto,
+            aof_rewrite_min_size: 0,
+            zdb_rewrite_min_size: 0,
         };
 
         // 1) open, write, rename across shards
@@ -1291,7 +3517,9 @@ mod tests {
         // разные шарды
         let k1 = Sds::from_str("k_0");
         let mut k2 = Sds::from_str("k_1");
-        while store.index.shard_for_key(k1.as_bytes()) == store.index.shard_for_key(k2.as_bytes()) {
+        while store.inner.index.shard_for_key(k1.as_bytes())
+            == store.inner.index.shard_for_key(k2.as_bytes())
+        {
             // модифицируем k2, пока не попадут в разные шарды
             let n = rand::random::<u32>() % 10000;
             k2 = Sds::from_str(&format!("k_{n}"));
@@ -1358,8 +3586,8 @@ mod tests {
         let m2 = Sds::from_str("m2");
 
         // добавляем две точки: (0,0) и (0,1) в метрах примерно 111km? (примерно)
-        store.geo_add(&key, 0.0, 0.0, &m1)?;
-        store.geo_add(&key, 0.0, 1.0, &m2)?;
+        store.geo_add(&key, 0.0, 0.0, &m1, GeoAddOptions::default())?;
+        store.geo_add(&key, 0.0, 1.0, &m2, GeoAddOptions::default())?;
 
         // позиции
         let p1 = store.geo_pos(&key, &m1)?;
@@ -1374,4 +3602,574 @@ mod tests {
 
         Ok(())
     }
+
+    /// Тест проверяет LMOVE между ключами (в т.ч. из разных шардов): элемент
+    /// перемещается, опустевший источник удаляется.
+    #[test]
+    fn test_lmove_between_shards() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let dst = Sds::from_str("dst");
+        store.set(
+            &dst,
+            Value::List(QuickList::from_iter([Sds::from_str("x")], 64)),
+        )?;
+
+        for i in 0..8 {
+            let src = Sds::from(format!("src{i}"));
+            store.set(
+                &src,
+                Value::List(QuickList::from_iter([Sds::from_str("a")], 64)),
+            )?;
+
+            let moved = store.lmove(&src, &dst, ListDir::Left, ListDir::Right)?;
+            assert_eq!(moved, Some(Sds::from_str("a")));
+            assert_eq!(store.get(&src)?, None);
+        }
+
+        match store.get(&dst)? {
+            Some(Value::List(list)) => assert_eq!(list.len(), 9),
+            other => panic!("unexpected value {other:?}"),
+        }
+        assert_eq!(store.dbsize()?, 1);
+        Ok(())
+    }
+
+    /// Тест проверяет SMOVE между ключами из разных шардов: элемент
+    /// переносится, опустевший источник удаляется.
+    #[test]
+    fn test_smove_between_shards() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let dst = Sds::from_str("dst");
+        store.sadd(&dst, &[Sds::from_str("x")])?;
+
+        for i in 0..8 {
+            let src = Sds::from(format!("src{i}"));
+            let member = Sds::from(format!("m{i}"));
+            store.sadd(&src, std::slice::from_ref(&member))?;
+
+            assert!(store.smove(&src, &dst, &member)?);
+            assert!(!store.smove(&src, &dst, &member)?);
+            assert_eq!(store.get(&src)?, None);
+        }
+
+        assert_eq!(store.scard(&dst)?, 9);
+        assert_eq!(store.dbsize()?, 1);
+        Ok(())
+    }
+
+    /// Тест проверяет HINCRBY/HINCRBYFLOAT: создание поля, увеличение
+    /// существующего и отказ без изменения значения при ошибке.
+    #[test]
+    fn test_hincrby_and_hincrbyfloat() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("h");
+        let counter = Sds::from_str("counter");
+        let ratio = Sds::from_str("ratio");
+
+        assert_eq!(store.hincrby(&key, &counter, 5)?, 5);
+        assert_eq!(store.hincrby(&key, &counter, -2)?, 3);
+        assert!(matches!(
+            store.hincrby(&key, &counter, i64::MAX),
+            Err(StoreError::Overflow)
+        ));
+        assert_eq!(store.hincrby(&key, &counter, 0)?, 3);
+
+        assert_eq!(store.hincrbyfloat(&key, &ratio, 1.5)?, 1.5);
+        assert!(matches!(
+            store.hincrbyfloat(&key, &ratio, f64::INFINITY),
+            Err(StoreError::InvalidValue)
+        ));
+        assert!(matches!(
+            store.hincrby(&key, &ratio, 1),
+            Err(StoreError::InvalidValue)
+        ));
+        assert_eq!(store.hincrbyfloat(&key, &ratio, 0.25)?, 1.75);
+
+        store.set(&Sds::from_str("s"), Value::Str(Sds::from_str("v")))?;
+        assert!(matches!(
+            store.hincrby(&Sds::from_str("s"), &counter, 1),
+            Err(StoreError::WrongType(_))
+        ));
+        Ok(())
+    }
+
+    /// Тест проверяет HMGET: значения в порядке запроса, `None` для
+    /// отсутствующих полей и отсутствующего ключа.
+    #[test]
+    fn test_hmget() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("h");
+        let (a, b, c) = (Sds::from_str("a"), Sds::from_str("b"), Sds::from_str("c"));
+        store.hincrby(&key, &a, 1)?;
+        store.hincrby(&key, &c, 3)?;
+
+        assert_eq!(
+            store.hmget(&key, &[&c, &b, &a])?,
+            vec![Some(Sds::from_str("3")), None, Some(Sds::from_str("1"))]
+        );
+        assert_eq!(
+            store.hmget(&Sds::from_str("missing"), &[&a, &b])?,
+            vec![None, None]
+        );
+        Ok(())
+    }
+
+    /// Тест проверяет, что MEMORY USAGE учитывает сериализованные буферы
+    /// ключа и значения.
+    #[test]
+    fn test_memory_usage() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("k");
+        let value = Value::Str(Sds::from_vec(vec![b'x'; 1000]));
+        store.set(&key, value.clone())?;
+
+        let usage = store.memory_usage(&key, 0)?.unwrap();
+        assert!(usage >= key.len() + value.to_bytes().len());
+        assert_eq!(store.memory_usage(&Sds::from_str("missing"), 0)?, None);
+        Ok(())
+    }
+
+    /// Тест проверяет SINTERCARD по ключам из разных шардов, включая LIMIT и
+    /// отсутствующий ключ.
+    #[test]
+    fn test_sintercard() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let a = Sds::from_str("a");
+        let b = Sds::from_str("b");
+        let members: Vec<Sds> = (0..10).map(|i| Sds::from(format!("m{i}"))).collect();
+        store.sadd(&a, &members)?;
+        store.sadd(&b, &members[5..])?;
+
+        assert_eq!(store.sintercard(&[&a, &b], None)?, 5);
+        assert_eq!(store.sintercard(&[&a, &b], Some(2))?, 2);
+        assert_eq!(store.sintercard(&[&a, &b], Some(0))?, 5);
+        assert_eq!(store.sintercard(&[&a], None)?, 10);
+        assert_eq!(store.sintercard(&[&a, &Sds::from_str("none")], None)?, 0);
+        Ok(())
+    }
+
+    /// Тест проверяет SINTERSTORE/SUNIONSTORE/SDIFFSTORE по ключам разных
+    /// шардов: приёмник перезаписывается, теряет TTL и удаляется при пустом
+    /// результате.
+    #[test]
+    fn test_set_store_ops() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let a = Sds::from_str("a");
+        let b = Sds::from_str("b");
+        let dst = Sds::from_str("dst");
+        let members: Vec<Sds> = (0..6).map(|i| Sds::from(format!("m{i}"))).collect();
+        store.sadd(&a, &members[..4])?;
+        store.sadd(&b, &members[2..])?;
+        store.set(&dst, Value::Str(Sds::from_str("old")))?;
+        store.set_expiry(&dst, Some(Duration::from_secs(100)))?;
+
+        assert_eq!(store.sinterstore(&dst, &[&a, &b])?, 2);
+        assert_eq!(store.expiretime(&dst)?, Some(-1));
+        assert_eq!(store.sunionstore(&dst, &[&a, &b])?, 6);
+        assert_eq!(store.sdiffstore(&dst, &[&a, &b])?, 2);
+        assert_eq!(store.sdiffstore(&dst, &[&a, &a])?, 0);
+        assert_eq!(store.get(&dst)?, None);
+
+        // Приёмник совпадает с источником.
+        assert_eq!(store.sinterstore(&a, &[&a, &b])?, 2);
+        assert_eq!(store.scard(&a)?, 2);
+        Ok(())
+    }
+
+    /// Тест проверяет ZUNIONSTORE/ZINTERSTORE/ZDIFFSTORE по ключам разных
+    /// шардов: приёмник перезаписывается и удаляется при пустом результате.
+    #[test]
+    fn test_zset_store_ops() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let a = Sds::from_str("a");
+        let b = Sds::from_str("b");
+        let dst = Sds::from_str("dst");
+        store.set(
+            &a,
+            zset_from_entries([(Sds::from_str("x"), 1.0), (Sds::from_str("y"), 2.0)]),
+        )?;
+        store.set(&b, zset_from_entries([(Sds::from_str("y"), 3.0)]))?;
+        let options = ZCombineOptions::default();
+
+        assert_eq!(store.zunionstore(&dst, &[&a, &b], &options)?, 2);
+        assert_eq!(
+            store.zunion(&[&a, &b], &options)?,
+            vec![(Sds::from_str("x"), 1.0), (Sds::from_str("y"), 5.0)]
+        );
+        assert_eq!(store.zinterstore(&dst, &[&a, &b], &options)?, 1);
+        assert_eq!(store.zdiffstore(&dst, &[&b, &a])?, 0);
+        assert_eq!(store.get(&dst)?, None);
+        Ok(())
+    }
+
+    /// Тест проверяет ZMPOP: элементы снимаются с нужного края, опустевшее
+    /// множество удаляется вместе с TTL.
+    #[test]
+    fn test_zmpop() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let empty = Sds::from_str("empty");
+        let z = Sds::from_str("z");
+        store.set(
+            &z,
+            zset_from_entries([(Sds::from_str("a"), 1.0), (Sds::from_str("b"), 2.0)]),
+        )?;
+        store.set_expiry(&z, Some(Duration::from_secs(100)))?;
+
+        assert_eq!(
+            store.zmpop(&[&empty, &z], ZPopDir::Max, 1)?,
+            Some((z.clone(), vec![(Sds::from_str("b"), 2.0)]))
+        );
+        assert_eq!(
+            store.zmpop(&[&z], ZPopDir::Min, 5)?,
+            Some((z.clone(), vec![(Sds::from_str("a"), 1.0)]))
+        );
+        assert_eq!(store.get(&z)?, None);
+        assert_eq!(store.expiretime(&z)?, None);
+        assert_eq!(store.zmpop(&[&empty, &z], ZPopDir::Min, 1)?, None);
+        Ok(())
+    }
+
+    /// Тест проверяет GETDEL: значение возвращается и ключ удаляется, ключ
+    /// другого типа остаётся нетронутым.
+    #[test]
+    fn test_getdel() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("token");
+        store.set(&key, Value::Str(Sds::from_str("abc")))?;
+
+        assert_eq!(store.getdel(&key)?, Some(Sds::from_str("abc")));
+        assert_eq!(store.get(&key)?, None);
+        assert_eq!(store.getdel(&key)?, None);
+
+        let num = Sds::from_str("num");
+        store.set(&num, Value::Int(7))?;
+        assert_eq!(store.getdel(&num)?, None);
+        assert_eq!(store.get(&num)?, Some(Value::Int(7)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("session");
+        store.set(&key, Value::Str(Sds::from_str("abc")))?;
+
+        assert_eq!(
+            store.getex(&key, GetExExpiry::Px(50))?,
+            Some(Sds::from_str("abc"))
+        );
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        assert_eq!(store.get(&key)?, None);
+        assert_eq!(store.dbsize()?, 0);
+
+        store.set(&key, Value::Str(Sds::from_str("abc")))?;
+        assert_eq!(
+            store.getex(&key, GetExExpiry::ExAt(1))?,
+            Some(Sds::from_str("abc"))
+        );
+        assert_eq!(store.get(&key)?, None);
+
+        let missing = Sds::from_str("missing");
+        assert_eq!(store.getex(&missing, GetExExpiry::Ex(10))?, None);
+        assert_eq!(store.get(&missing)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_with_ttl() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let src = Sds::from_str("src");
+        let dst = Sds::from_str("dst");
+        store.set(&src, Value::Str(Sds::from_str("v")))?;
+        store.getex(&src, GetExExpiry::Px(50))?;
+
+        assert!(store.copy(&src, &dst, None, false)?);
+        assert!(!store.copy(&src, &dst, None, false)?);
+        assert_eq!(store.get(&dst)?, Some(Value::Str(Sds::from_str("v"))));
+        assert!(store.copy(&src, &src, None, true).is_err());
+
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        assert_eq!(store.get(&src)?, None);
+        assert_eq!(store.get(&dst)?, None);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `geosearchstore` сохраняет найденных участников в
+    /// `dst` как ZSet, а при пустом результате удаляет `dst`.
+    #[test]
+    fn test_geosearchstore() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("geo");
+        let dst = Sds::from_str("found");
+        store.geo_add(
+            &key,
+            0.0,
+            0.0,
+            &Sds::from_str("a"),
+            GeoAddOptions::default(),
+        )?;
+        store.geo_add(
+            &key,
+            0.0,
+            1.0,
+            &Sds::from_str("b"),
+            GeoAddOptions::default(),
+        )?;
+
+        let mut params = GeoSearchParams {
+            from: GeoSearchFrom::Member("a".into()),
+            shape: GeoSearchShape::Radius(200.0),
+            unit: DistanceUnit::Kilometers,
+            order: None,
+            count: None,
+            store_dist: true,
+        };
+        assert_eq!(store.geosearchstore(&dst, &key, params.clone())?, 2);
+        match store.get(&dst)? {
+            Some(Value::ZSet { dict, .. }) => {
+                assert_eq!(dict.get(&Sds::from_str("a")), Some(&0.0));
+                let b = *dict.get(&Sds::from_str("b")).unwrap();
+                assert!((b - 111.2).abs() < 0.5);
+            }
+            other => panic!("Expected ZSet, got {other:?}"),
+        }
+
+        params.shape = GeoSearchShape::Radius(1.0);
+        params.from = GeoSearchFrom::LoLat {
+            lon: 50.0,
+            lat: 0.0,
+        };
+        assert_eq!(store.geosearchstore(&dst, &key, params.clone())?, 0);
+        assert_eq!(store.get(&dst)?, None);
+
+        params.from = GeoSearchFrom::Member("missing".into());
+        assert!(matches!(
+            store.geosearchstore(&dst, &key, params),
+            Err(StoreError::KeyNotFound)
+        ));
+        Ok(())
+    }
+
+    /// Тест проверяет, что `expiretime` различает отсутствующий ключ, ключ
+    /// без TTL и ключ с TTL.
+    #[test]
+    fn test_expiretime() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("k");
+        assert_eq!(store.expiretime(&key)?, None);
+
+        store.set(&key, Value::Str(Sds::from_str("v")))?;
+        assert_eq!(store.expiretime(&key)?, Some(-1));
+
+        store.getex(&key, GetExExpiry::Ex(60))?;
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        let at = store.expiretime(&key)?.unwrap();
+        assert!((at - (now_ms + 60_000)).abs() < 100);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `set_expiry` ставит и снимает TTL, а нулевой TTL
+    /// удаляет ключ.
+    #[test]
+    fn test_set_expiry() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("k");
+        assert!(!store.set_expiry(&key, Some(Duration::from_secs(60)))?);
+
+        store.set(&key, Value::Str(Sds::from_str("v")))?;
+        assert!(store.set_expiry(&key, Some(Duration::from_secs(60)))?);
+        assert!(store.expiretime(&key)?.unwrap() > 0);
+
+        assert!(store.set_expiry(&key, None)?);
+        assert_eq!(store.expiretime(&key)?, Some(-1));
+
+        assert!(store.set_expiry(&key, Some(Duration::ZERO))?);
+        assert_eq!(store.get(&key)?, None);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `keys` собирает подходящие ключи со всех шардов.
+    #[test]
+    fn test_keys_across_shards() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        for i in 0..20 {
+            store.set(&Sds::from_str(&format!("k{i}")), Value::Int(i))?;
+        }
+        store.set(&Sds::from_str("other"), Value::Int(0))?;
+
+        assert_eq!(store.keys("*")?.len(), 21);
+        let mut keys = store.keys("k1?")?;
+        keys.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        let expected: Vec<Sds> = (10..20).map(|i| Sds::from_str(&format!("k{i}"))).collect();
+        assert_eq!(keys, expected);
+        assert_eq!(store.keys("[^k]*")?, vec![Sds::from_str("other")]);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `type_of` декодирует значение и сообщает его тип.
+    #[test]
+    fn test_type_of() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        assert_eq!(store.type_of(&Sds::from_str("missing"))?, None);
+
+        store.set(&Sds::from_str("s"), Value::Str(Sds::from_str("v")))?;
+        store.set(&Sds::from_str("h"), Value::Hash(SmartHash::new()))?;
+        assert_eq!(store.type_of(&Sds::from_str("s"))?, Some(ValueType::String));
+        assert_eq!(store.type_of(&Sds::from_str("h"))?, Some(ValueType::Hash));
+        Ok(())
+    }
+
+    /// Тест проверяет, что `persist` снимает TTL только у существующего ключа
+    /// с TTL.
+    #[test]
+    fn test_persist() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("k");
+        assert!(!store.persist(&key)?);
+
+        store.set(&key, Value::Str(Sds::from_str("v")))?;
+        assert!(!store.persist(&key)?);
+
+        store.set_expiry(&key, Some(Duration::from_secs(60)))?;
+        assert!(store.persist(&key)?);
+        assert_eq!(store.expiretime(&key)?, Some(-1));
+        assert!(!store.persist(&key)?);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `set_get` возвращает прежнее значение и не
+    /// снимает TTL.
+    #[test]
+    fn test_set_get() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("k");
+        assert_eq!(store.set_get(&key, Value::Str(Sds::from_str("a")))?, None);
+
+        store.set_expiry(&key, Some(Duration::from_secs(60)))?;
+        assert_eq!(
+            store.set_get(&key, Value::Int(7))?,
+            Some(Value::Str(Sds::from_str("a")))
+        );
+        assert_eq!(store.get(&key)?, Some(Value::Int(7)));
+        assert!(store.expiretime(&key)?.unwrap() > 0);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `lset` заменяет элемент и сохраняет список, а
+    /// индекс вне диапазона и отсутствующий ключ дают ошибки.
+    #[test]
+    fn test_lset() -> StoreResult<()> {
+        let store = new_sharded_store(4)?;
+        let key = Sds::from_str("l");
+        store.set(
+            &key,
+            Value::List(QuickList::from_iter(
+                [Sds::from_str("a"), Sds::from_str("b")],
+                64,
+            )),
+        )?;
+
+        store.lset(&key, -1, Sds::from_str("z"))?;
+        match store.get(&key)? {
+            Some(Value::List(list)) => assert_eq!(
+                list.iter().map(|s| s.into_owned()).collect::<Vec<_>>(),
+                vec![Sds::from_str("a"), Sds::from_str("z")]
+            ),
+            other => panic!("unexpected value {other:?}"),
+        }
+
+        assert!(matches!(
+            store.lset(&key, 2, Sds::from_str("x")),
+            Err(StoreError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            store.lset(&Sds::from_str("none"), 0, Sds::from_str("x")),
+            Err(StoreError::KeyNotFound)
+        ));
+        Ok(())
+    }
+
+    /// Тест проверяет, что асинхронный `save` сбрасывает журнал на диск и
+    /// данные переживают переоткрытие хранилища.
+    #[tokio::test]
+    async fn test_async_save() -> StoreResult<()> {
+        use crate::engine::AsyncStorage;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("store.inner.aof");
+        let config = PersistentStoreConfig {
+            sync_policy: SyncPolicy::EverySec,
+            compaction: CompactionConfig {
+                enable_snapshots: false,
+                ..Default::default()
+            },
+            aof_rewrite_min_size: 0,
+            zdb_rewrite_min_size: 0,
+            ..Default::default()
+        };
+
+        let store = InPersistentStore::new(&path, config.clone())?;
+        AsyncStorage::set(&store, &Sds::from_str("k"), Value::Int(1)).await?;
+        AsyncStorage::save(&store).await?;
+        drop(store);
+
+        let store = InPersistentStore::new(&path, config)?;
+        assert_eq!(
+            Storage::get(&store, &Sds::from_str("k"))?,
+            Some(Value::Int(1))
+        );
+        Ok(())
+    }
+
+    /// Тест проверяет асинхронные методы, выполняемые в блокирующих задачах:
+    /// изменения попадают в AOF, а удаление клона дескриптора не
+    /// останавливает хранилище.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_methods_use_blocking_pool() -> StoreResult<()> {
+        use crate::engine::AsyncStorage;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("store.aof");
+        let config = PersistentStoreConfig {
+            compaction: CompactionConfig {
+                enable_snapshots: false,
+                ..Default::default()
+            },
+            aof_rewrite_min_size: 0,
+            zdb_rewrite_min_size: 0,
+            ..Default::default()
+        };
+        let (a, b, dst) = (Sds::from_str("a"), Sds::from_str("b"), Sds::from_str("dst"));
+
+        let store = InPersistentStore::new(&path, config.clone())?;
+        drop(store.clone());
+        AsyncStorage::sadd(&store, &a, &[Sds::from_str("x"), Sds::from_str("y")]).await?;
+        AsyncStorage::sadd(&store, &b, &[Sds::from_str("y")]).await?;
+        assert_eq!(AsyncStorage::sinterstore(&store, &dst, &[&a, &b]).await?, 1);
+        assert_eq!(
+            AsyncStorage::hincrby(&store, &Sds::from_str("h"), &Sds::from_str("f"), 5).await?,
+            5
+        );
+        AsyncStorage::mset(
+            &store,
+            vec![
+                (&Sds::from_str("k1"), Value::Int(1)),
+                (&Sds::from_str("k2"), Value::Int(2)),
+            ],
+        )
+        .await?;
+        assert_eq!(AsyncStorage::keys(&store, "k*").await?.len(), 2);
+        drop(store);
+
+        let store = InPersistentStore::new(&path, config)?;
+        assert_eq!(Storage::smembers(&store, &dst)?, vec![Sds::from_str("y")]);
+        assert_eq!(
+            Storage::get(&store, &Sds::from_str("k2"))?,
+            Some(Value::Int(2))
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,400 @@
+//! Реплики чтения shard'ов [`InClusterStore`](super::InClusterStore).
+//!
+//! Каждый shard кластера ведёт журнал изменений — канал
+//! `tokio::sync::broadcast` с операциями [`ReplicationOp`]. После записи
+//! кластер публикует в журнал новое состояние изменённых ключей, а поток
+//! каждой реплики читает журнал и применяет операции к своему хранилищу.
+//! Реплика, отставшая больше чем на [`REPLICATION_LOG_CAPACITY`] операций,
+//! заново синхронизируется с primary целиком.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{FlushMode, Sds, Storage, StoreResult, Value};
+
+/// Ёмкость журнала изменений shard'а.
+pub const REPLICATION_LOG_CAPACITY: usize = 1024;
+
+/// Уровень согласованности чтений в кластере.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsistencyLevel {
+    /// Все чтения обслуживает primary shard'а.
+    #[default]
+    Strong,
+    /// Чтения распределяются по репликам shard'а по кругу; реплика может
+    /// отставать от primary.
+    Eventual,
+}
+
+/// Время, которое failover ждёт, пока реплика догонит журнал.
+pub const FAILOVER_CATCHUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Операция журнала изменений shard'а.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplicationOp {
+    /// Ключ получил значение; `expires_at` — абсолютное время истечения в
+    /// миллисекундах UNIX.
+    Set {
+        key: Sds,
+        value: Value,
+        expires_at: Option<i64>,
+    },
+    /// Ключ удалён.
+    Del { key: Sds },
+    /// Текущая база очищена.
+    FlushDb,
+    /// Все базы очищены.
+    FlushAll,
+    /// Отметка позиции в журнале: реплика запоминает номер последней
+    /// применённой отметки.
+    Mark(u64),
+}
+
+/// Реплика shard'а.
+struct Replica {
+    node_id: String,
+    store: Arc<dyn Storage>,
+    /// Номер последней применённой [`ReplicationOp::Mark`].
+    applied_mark: Arc<AtomicU64>,
+    /// Останавливает поток реплики.
+    stop: Arc<AtomicBool>,
+}
+
+/// Shard кластера: primary, его реплики и журнал изменений.
+pub struct ReplicaSet {
+    node_id: RwLock<String>,
+    /// Общая с потоками реплик ссылка на primary: по ней отставшая реплика
+    /// синхронизируется заново, в том числе после failover.
+    primary: Arc<RwLock<Arc<dyn Storage>>>,
+    log: broadcast::Sender<ReplicationOp>,
+    replicas: RwLock<Vec<Replica>>,
+    /// Счётчик round-robin для чтений с реплик.
+    next: AtomicUsize,
+    /// Последняя выданная отметка журнала.
+    mark: AtomicU64,
+    /// Упорядочивает чтение состояния ключа и его публикацию, чтобы более
+    /// старое состояние не попало в журнал после нового.
+    publish_lock: Mutex<()>,
+}
+
+impl ReplicaSet {
+    /// Создаёт shard из primary без реплик.
+    pub fn new(
+        node_id: String,
+        primary: Arc<dyn Storage>,
+    ) -> Self {
+        let (log, _) = broadcast::channel(REPLICATION_LOG_CAPACITY);
+        Self {
+            node_id: RwLock::new(node_id),
+            primary: Arc::new(RwLock::new(primary)),
+            log,
+            replicas: RwLock::new(Vec::new()),
+            next: AtomicUsize::new(0),
+            mark: AtomicU64::new(0),
+            publish_lock: Mutex::new(()),
+        }
+    }
+
+    /// Возвращает идентификатор узла primary.
+    pub fn node_id(&self) -> String {
+        self.node_id.read().unwrap().clone()
+    }
+
+    /// Возвращает primary shard'а.
+    pub fn primary(&self) -> Arc<dyn Storage> {
+        self.primary.read().unwrap().clone()
+    }
+
+    /// Подключает реплику `replica` с идентификатором узла `node_id`.
+    ///
+    /// Реплика подписывается на журнал, копирует текущее содержимое primary и
+    /// дальше в фоновом потоке применяет операции журнала.
+    pub fn add_replica(
+        &self,
+        node_id: String,
+        replica: Arc<dyn Storage>,
+    ) -> StoreResult<()> {
+        let rx = self.log.subscribe();
+        full_sync(self.primary().as_ref(), replica.as_ref())?;
+
+        let node = Replica {
+            node_id,
+            store: replica,
+            applied_mark: Arc::new(AtomicU64::new(0)),
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+        let worker = ReplicaWorker {
+            primary: self.primary.clone(),
+            replica: node.store.clone(),
+            applied_mark: node.applied_mark.clone(),
+            stop: node.stop.clone(),
+        };
+        self.replicas.write().unwrap().push(node);
+        thread::spawn(move || worker.run(rx));
+        Ok(())
+    }
+
+    /// Возвращает идентификаторы узлов и хранилища реплик.
+    pub fn replicas(&self) -> Vec<(String, Arc<dyn Storage>)> {
+        self.replicas
+            .read()
+            .unwrap()
+            .iter()
+            .map(|r| (r.node_id.clone(), r.store.clone()))
+            .collect()
+    }
+
+    /// Возвращает количество реплик.
+    pub fn len(&self) -> usize {
+        self.replicas.read().unwrap().len()
+    }
+
+    /// Проверяет, что у shard'а нет реплик.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Возвращает следующую по кругу реплику.
+    pub fn next_replica(&self) -> Option<Arc<dyn Storage>> {
+        let replicas = self.replicas.read().unwrap();
+        if replicas.is_empty() {
+            return None;
+        }
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % replicas.len();
+        Some(replicas[i].store.clone())
+    }
+
+    /// Публикует в журнал текущее состояние `keys` на primary.
+    pub fn publish_keys(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<()> {
+        if self.log.receiver_count() == 0 {
+            return Ok(());
+        }
+        let primary = self.primary();
+        let _guard = self.publish_lock.lock().unwrap();
+        for &key in keys {
+            let op = match primary.get(key)? {
+                Some(value) => ReplicationOp::Set {
+                    key: key.clone(),
+                    value,
+                    expires_at: primary.expiretime(key)?.filter(|&at| at >= 0),
+                },
+                None => ReplicationOp::Del { key: key.clone() },
+            };
+            let _ = self.log.send(op);
+        }
+        Ok(())
+    }
+
+    /// Публикует операцию в журнал.
+    pub fn publish(
+        &self,
+        op: ReplicationOp,
+    ) {
+        let _guard = self.publish_lock.lock().unwrap();
+        let _ = self.log.send(op);
+    }
+
+    /// Делает первую реплику primary'ем shard'а.
+    ///
+    /// Перед переключением реплика до `timeout` догоняет журнал; если она
+    /// не успела (например, primary недоступен), переключение всё равно
+    /// выполняется. Остальные реплики продолжают следовать журналу и при
+    /// отставании синхронизируются уже с новым primary. Записи в shard на
+    /// время вызова должны быть остановлены.
+    ///
+    /// # Возвращает:
+    /// - `Some((node_id, store))` - бывший primary
+    /// - `None` - если у shard'а нет реплик
+    pub fn promote(
+        &self,
+        timeout: Duration,
+    ) -> Option<(String, Arc<dyn Storage>)> {
+        let replica = {
+            let mut replicas = self.replicas.write().unwrap();
+            if replicas.is_empty() {
+                return None;
+            }
+            replicas.remove(0)
+        };
+
+        let mark = self.mark.fetch_add(1, Ordering::Relaxed) + 1;
+        self.publish(ReplicationOp::Mark(mark));
+        let deadline = Instant::now() + timeout;
+        while replica.applied_mark.load(Ordering::Acquire) < mark && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        replica.stop.store(true, Ordering::Release);
+
+        let old_primary =
+            std::mem::replace(&mut *self.primary.write().unwrap(), replica.store.clone());
+        let old_id = std::mem::replace(&mut *self.node_id.write().unwrap(), replica.node_id);
+        Some((old_id, old_primary))
+    }
+}
+
+/// Применяет операцию журнала к реплике.
+pub fn apply_op(
+    replica: &dyn Storage,
+    op: ReplicationOp,
+) -> StoreResult<()> {
+    match op {
+        ReplicationOp::Set {
+            key,
+            value,
+            expires_at,
+        } => {
+            replica.set(&key, value)?;
+            if let Some(at) = expires_at {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as i64;
+                let ttl = Duration::from_millis(at.saturating_sub(now).max(0) as u64);
+                replica.set_expiry(&key, Some(ttl))?;
+            }
+        }
+        ReplicationOp::Del { key } => {
+            replica.del(&key)?;
+        }
+        ReplicationOp::FlushDb => replica.flushdb()?,
+        ReplicationOp::FlushAll => replica.flushall(FlushMode::Sync)?,
+        ReplicationOp::Mark(_) => {}
+    }
+    Ok(())
+}
+
+/// Заменяет содержимое реплики содержимым primary.
+fn full_sync(
+    primary: &dyn Storage,
+    replica: &dyn Storage,
+) -> StoreResult<()> {
+    replica.flushdb()?;
+    for key in primary.keys("*")? {
+        if let Some(value) = primary.get(&key)? {
+            let expires_at = primary.expiretime(&key)?.filter(|&at| at >= 0);
+            apply_op(
+                replica,
+                ReplicationOp::Set {
+                    key,
+                    value,
+                    expires_at,
+                },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Фоновый поток реплики.
+struct ReplicaWorker {
+    primary: Arc<RwLock<Arc<dyn Storage>>>,
+    replica: Arc<dyn Storage>,
+    applied_mark: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ReplicaWorker {
+    /// Применяет операции журнала, пока shard существует и реплика не
+    /// остановлена.
+    fn run(
+        self,
+        mut rx: broadcast::Receiver<ReplicationOp>,
+    ) {
+        loop {
+            let op = rx.blocking_recv();
+            if self.stop.load(Ordering::Acquire) {
+                return;
+            }
+            let result = match op {
+                Ok(ReplicationOp::Mark(mark)) => {
+                    self.applied_mark.store(mark, Ordering::Release);
+                    Ok(())
+                }
+                Ok(op) => apply_op(self.replica.as_ref(), op),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Replica lagged by {skipped} operations, resyncing");
+                    let primary = self.primary.read().unwrap().clone();
+                    full_sync(primary.as_ref(), self.replica.as_ref())
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to apply replication op: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryStore;
+
+    /// Тест проверяет начальную синхронизацию реплики и применение
+    /// опубликованных изменений.
+    #[test]
+    fn test_replica_follows_primary() {
+        let primary: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let replica: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let a = Sds::from_str("a");
+        let b = Sds::from_str("b");
+        primary.set(&a, Value::Int(1)).unwrap();
+
+        let set = ReplicaSet::new("primary".to_string(), primary.clone());
+        assert!(set.next_replica().is_none());
+        set.add_replica("replica".to_string(), replica.clone())
+            .unwrap();
+        assert_eq!(replica.get(&a).unwrap(), Some(Value::Int(1)));
+        assert_eq!(set.len(), 1);
+
+        primary.set(&b, Value::Int(2)).unwrap();
+        primary
+            .set_expiry(&b, Some(Duration::from_secs(100)))
+            .unwrap();
+        primary.del(&a).unwrap();
+        set.publish_keys(&[&a, &b]).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while replica.get(&b).unwrap().is_none() || replica.get(&a).unwrap().is_some() {
+            assert!(Instant::now() < deadline, "replica did not catch up");
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(replica.expiretime(&b).unwrap().unwrap() > 0);
+        assert!(Arc::ptr_eq(&set.next_replica().unwrap(), &replica));
+    }
+
+    /// Тест проверяет, что promote дожидается применения журнала и меняет
+    /// primary и его идентификатор местами с репликой.
+    #[test]
+    fn test_promote_replica() {
+        let primary: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let replica: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let set = ReplicaSet::new("primary".to_string(), primary.clone());
+        assert!(set.promote(Duration::ZERO).is_none());
+        set.add_replica("replica".to_string(), replica.clone())
+            .unwrap();
+
+        let key = Sds::from_str("k");
+        primary.set(&key, Value::Int(1)).unwrap();
+        set.publish_keys(&[&key]).unwrap();
+
+        let (old_id, old_primary) = set.promote(FAILOVER_CATCHUP_TIMEOUT).unwrap();
+        assert_eq!(old_id, "primary");
+        assert!(Arc::ptr_eq(&old_primary, &primary));
+        assert!(Arc::ptr_eq(&set.primary(), &replica));
+        assert_eq!(set.node_id(), "replica");
+        assert!(set.is_empty());
+        assert_eq!(replica.get(&key).unwrap(), Some(Value::Int(1)));
+    }
+}
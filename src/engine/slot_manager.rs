@@ -1,6 +1,5 @@
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
-    hash::{Hash, Hasher},
+    collections::{BTreeMap, HashMap, VecDeque},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc, Mutex, RwLock,
@@ -8,6 +7,8 @@ use std::{
     time::{Duration, Instant},
 };
 
+use xxhash_rust::xxh64::xxh64;
+
 use crate::{Result as SmResult, SlotManagerError};
 
 /// Идентификатор шарда (node) в кластере.
@@ -16,7 +17,7 @@ pub type ShardId = usize;
 pub type SlotId = u16;
 
 /// Количество слотов (фиксировано, совместимо с Redis).
-const TOTAL_SLOTS: u16 = 16384;
+pub const TOTAL_SLOTS: u16 = 16384;
 /// Коэффициент для принятия решения о ребалансинге:
 /// если max_load / min_load > REBALANCE_THRESHOLD → рассматриваем ребаланс.
 const REBALANCE_THRESHOLD: f64 = 1.5;
@@ -24,6 +25,9 @@ const REBALANCE_THRESHOLD: f64 = 1.5;
 const HOT_KEY_THRESHOLD: u64 = 100;
 /// Максимальное количество слотов, планируемых к миграции за одну операцию.
 const MIGRATION_BATCH_SIZE: usize = 64;
+/// Количество виртуальных узлов на shard в кольце consistent hashing по
+/// умолчанию.
+pub const DEFAULT_VIRTUAL_NODES: usize = 150;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SlotState {
@@ -100,13 +104,16 @@ pub struct SlotManager {
     hash_ring: Arc<RwLock<ConsistentHashRing>>,
 }
 
-/// Небольшая реализация consistent-hash кольца для альтернативной
-/// маршрутизации. Хранит map hash -> shard и отсортированный массив хешей для
-/// быстрых обходов.
-#[derive(Debug)]
+/// Кольцо consistent hashing: токен → shard.
+///
+/// Каждый shard занимает `virtual_nodes_per_shard` токенов. Ключ
+/// принадлежит shard'у первого токена не меньше хеша ключа (по часовой
+/// стрелке, с переходом через ноль). Токены shard'а зависят только от его
+/// номера, поэтому добавление или удаление shard'а перемещает лишь ключи из
+/// затронутых диапазонов (~1/N).
+#[derive(Debug, Clone)]
 pub struct ConsistentHashRing {
-    nodes: HashMap<u64, ShardId>,
-    sorted_hashes: Vec<u64>,
+    tokens: BTreeMap<u64, ShardId>,
     virtual_nodes_per_shard: usize,
 }
 
@@ -115,85 +122,82 @@ pub struct ConsistentHashRing {
 ////////////////////////////////////////////////////////////////////////////////
 
 impl ConsistentHashRing {
-    /// Создание кольца с `shard_count` узлами и `virtual_nodes_per_shard`
-    /// виртуальными нодами. Использует `DefaultHasher` (SipHash или
-    /// реализация std).
+    /// Создаёт кольцо из `shard_count` shard'ов по `virtual_nodes_per_shard`
+    /// токенов на каждый.
     pub fn new(
         shard_count: usize,
         virtual_nodes_per_shard: usize,
     ) -> Self {
-        let mut nodes = HashMap::new();
-
-        for shard_id in 0..shard_count {
-            for virtual_node in 0..virtual_nodes_per_shard {
-                let mut hasher = DefaultHasher::new();
-                format!("shard-{shard_id}-vnode-{virtual_node}").hash(&mut hasher);
-                let hash = hasher.finish();
-                nodes.insert(hash, shard_id);
-            }
-        }
-
-        let mut sorted_hashes: Vec<u64> = nodes.keys().cloned().collect();
-        sorted_hashes.sort_unstable();
-
-        Self {
-            nodes,
-            sorted_hashes,
+        let mut ring = Self {
+            tokens: BTreeMap::new(),
             virtual_nodes_per_shard,
+        };
+        for shard_id in 0..shard_count {
+            ring.add_shard(shard_id);
         }
+        ring
     }
 
-    /// Поиск шарда по строковому ключу.
+    /// Возвращает shard, владеющий ключом. Учитывает hash-tag `{...}`, как и
+    /// [`SlotManager::calculate_slot`], чтобы ключи с общим тегом попадали на
+    /// один shard.
     ///
-    /// Примечание: это упрощённая реализация; для больших колец имеет смысл
-    /// избегать выделения/сортировки при каждом изменении, а также
-    /// использовать более быстрый hasher.
+    /// Для пустого кольца возвращает `0`.
     pub fn get_shard(
         &self,
         key: &str,
     ) -> ShardId {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        let key_hash = hasher.finish();
-
-        match self.sorted_hashes.binary_search(&key_hash) {
-            Ok(idx) => self.nodes[&self.sorted_hashes[idx]],
-            Err(idx) => {
-                let pos = if idx == self.sorted_hashes.len() {
-                    0
-                } else {
-                    idx
-                };
-                self.nodes[&self.sorted_hashes[pos]]
-            }
-        }
+        let hash = xxh64(hash_tag(key).as_bytes(), 0);
+        self.tokens
+            .range(hash..)
+            .next()
+            .or_else(|| self.tokens.iter().next())
+            .map_or(0, |(_, &shard_id)| shard_id)
     }
 
-    /// Добавление шарда в кольцо (пересобирает `sorted_hashes`).
+    /// Добавляет токены shard'а в кольцо.
     pub fn add_shard(
         &mut self,
         shard_id: ShardId,
     ) {
         for virtual_node in 0..self.virtual_nodes_per_shard {
-            let mut hasher = DefaultHasher::new();
-            format!("shard-{shard_id}-vnode-{virtual_node}").hash(&mut hasher);
-            let hash = hasher.finish();
-            self.nodes.insert(hash, shard_id);
-        }
-        let mut v: Vec<u64> = self.nodes.keys().cloned().collect();
-        v.sort_unstable();
-        self.sorted_hashes = v;
+            self.tokens
+                .insert(Self::token(shard_id, virtual_node), shard_id);
+        }
     }
 
-    /// Удаление шарда из кольца (пересобирает `sorted_hashes`).
+    /// Удаляет токены shard'а из кольца.
     pub fn remove_shard(
         &mut self,
         shard_id: ShardId,
     ) {
-        self.nodes.retain(|_, &mut id| id != shard_id);
-        let mut v: Vec<u64> = self.nodes.keys().cloned().collect();
-        v.sort_unstable();
-        self.sorted_hashes = v;
+        self.tokens.retain(|_, &mut id| id != shard_id);
+    }
+
+    /// Возвращает количество виртуальных узлов на shard.
+    pub fn virtual_nodes_per_shard(&self) -> usize {
+        self.virtual_nodes_per_shard
+    }
+
+    /// Возвращает количество токенов в кольце.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Проверяет, пусто ли кольцо.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Токен виртуального узла `virtual_node` shard'а `shard_id`.
+    fn token(
+        shard_id: ShardId,
+        virtual_node: usize,
+    ) -> u64 {
+        xxh64(
+            format!("shard-{shard_id}-vnode-{virtual_node}").as_bytes(),
+            0,
+        )
     }
 }
 
@@ -241,7 +245,10 @@ impl SlotManager {
             shard_count,
             rebalance_interval: Duration::from_secs(30),
             last_rebalance: Arc::new(Mutex::new(Instant::now())),
-            hash_ring: Arc::new(RwLock::new(ConsistentHashRing::new(shard_count, 160))),
+            hash_ring: Arc::new(RwLock::new(ConsistentHashRing::new(
+                shard_count,
+                DEFAULT_VIRTUAL_NODES,
+            ))),
         }
     }
 
@@ -281,22 +288,7 @@ impl SlotManager {
         &self,
         key: &str,
     ) -> SlotId {
-        let hash_key = if let Some(start) = key.find('{') {
-            if let Some(end) = key[start + 1..].find('}') {
-                let tag = &key[start + 1..start + 1 + end];
-                if !tag.is_empty() {
-                    tag
-                } else {
-                    key
-                }
-            } else {
-                key
-            }
-        } else {
-            key
-        };
-
-        crc16(hash_key.as_bytes()) % TOTAL_SLOTS
+        crc16(hash_tag(key).as_bytes()) % TOTAL_SLOTS
     }
 
     /// Записывает факт операции по ключу — hot-path: только атомики.
@@ -723,6 +715,18 @@ impl SlotManager {
 // Внутренние методы и функции
 ////////////////////////////////////////////////////////////////////////////////
 
+/// Возвращает часть ключа, по которой он хешируется: содержимое первого
+/// непустого `{...}` или весь ключ.
+pub fn hash_tag(key: &str) -> &str {
+    key.find('{')
+        .and_then(|start| {
+            let rest = &key[start + 1..];
+            rest.find('}').map(|end| &rest[..end])
+        })
+        .filter(|tag| !tag.is_empty())
+        .unwrap_or(key)
+}
+
 /// CRC16 (табличная реализация) совместимая с Redis.
 /// Используется для вычисления слота (crc16(key) % 16384).
 fn crc16(data: &[u8]) -> u16 {
@@ -825,4 +829,29 @@ mod tests {
         assert!(manager.complete_slot_migration(slot).is_ok());
         assert_eq!(manager.get_slot_shard(slot), Some(1));
     }
+
+    /// Тест проверяет кольцо consistent hashing: ключи распределяются по
+    /// всем shard'ам, ключи с общим hash-tag попадают на один shard, а
+    /// удаление shard'а переносит только его ключи.
+    #[test]
+    fn test_consistent_hash_ring() {
+        let mut ring = ConsistentHashRing::new(4, DEFAULT_VIRTUAL_NODES);
+        assert_eq!(ring.len(), 4 * DEFAULT_VIRTUAL_NODES);
+
+        let keys: Vec<String> = (0..1000).map(|i| format!("key:{i}")).collect();
+        let before: Vec<ShardId> = keys.iter().map(|k| ring.get_shard(k)).collect();
+        for shard in 0..4 {
+            assert!(before.contains(&shard));
+        }
+        assert_eq!(ring.get_shard("user:{42}:a"), ring.get_shard("user:{42}:b"));
+
+        ring.remove_shard(3);
+        for (key, &old) in keys.iter().zip(&before) {
+            let new = ring.get_shard(key);
+            assert_ne!(new, 3);
+            if old != 3 {
+                assert_eq!(new, old);
+            }
+        }
+    }
 }
@@ -1,16 +1,40 @@
-use async_trait::async_trait;
+use std::{future::Future, pin::Pin, time::Duration};
+
 use zumic_error::SessionError;
 
 use crate::{
     auth::session::{SessionData, SessionId},
-    GeoPoint, Sds, StoreResult, Value,
+    EvictionPolicy, GeoAddOptions, GeoPoint, GeoSearchParams, GetExExpiry, ListDir, Sds,
+    StoreResult, Value, ValueType, ZCombineOptions, ZPopDir, ZSetEntries,
 };
 
+/// Режим очистки базы командами `FLUSHDB`/`FLUSHALL`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlushMode {
+    /// Память освобождается до возврата из команды.
+    #[default]
+    Sync,
+    /// Данные заменяются пустыми сразу, а память старых освобождается в
+    /// фоне (аналог `lazyfree` в Redis).
+    Async,
+}
+
+impl FlushMode {
+    /// Разбирает аргумент `ASYNC`/`SYNC` (без учёта регистра).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "SYNC" => Some(Self::Sync),
+            "ASYNC" => Some(Self::Async),
+            _ => None,
+        }
+    }
+}
+
 /// Трейт `Storage` определяет интерфейс для реализаций хранилища
 /// ключ-значение.
 /// Все методы могут возвращать ошибку и используют `StoreResult`
 /// как тип результата.
-pub trait Storage {
+pub trait Storage: Send + Sync {
     /// Устанавливает значение по заданному ключу.
     /// Если значение по ключу уже существует, оно будет перезаписано.
     fn set(
@@ -19,6 +43,14 @@ pub trait Storage {
         value: Value,
     ) -> StoreResult<()>;
 
+    /// Атомарно устанавливает значение по ключу и возвращает прежнее значение
+    /// (или `None`, если ключа не было). TTL ключа не изменяется.
+    fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>>;
+
     /// Возвращает значение по заданному ключу, либо `None`, если ключ не
     /// существует.
     fn get(
@@ -68,22 +100,51 @@ pub trait Storage {
     /// Очищает базу данных, удаляя все ключи.
     fn flushdb(&self) -> StoreResult<()>;
 
+    /// Очищает текущую базу в режиме `mode`.
+    fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()>;
+
+    /// Очищает все логические базы в режиме `mode`.
+    fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()>;
+
     /// Очищает базу данных, удаляя все ключи.
     fn dbsize(&self) -> StoreResult<usize>;
 
+    /// Возвращает все ключи, подходящие под glob-шаблон `pattern` (см.
+    /// [`glob_match`](crate::glob_match)). Обходит всё пространство ключей.
+    fn keys(
+        &self,
+        pattern: &str,
+    ) -> StoreResult<Vec<Sds>>;
+
+    /// Атомарно меняет местами содержимое логических баз `db1` и `db2`
+    /// вместе с TTL ключей.
+    fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> StoreResult<()>;
+
     /// Сохраняет состояние базы данных на диск (для персистентных хранилищ).
     /// Для in-memory хранилищ может быть пустой реализацией или возвращать
     /// ошибку.
     fn save(&self) -> StoreResult<()>;
 
-    /// Добавляет точку в гео-множество.
-    /// Возвращает `Ok(true)`, если member новый, иначе `Ok(false)`.
+    /// Добавляет точку в гео-множество с учётом флагов `NX`/`XX`/`CH`.
+    /// Возвращает `Ok(true)`, если member новый (или, при `opts.ch`, его
+    /// координаты изменились), иначе `Ok(false)`.
     fn geo_add(
         &self,
         key: &Sds,
         lon: f64,
         lat: f64,
         member: &Sds,
+        opts: GeoAddOptions,
     ) -> StoreResult<bool>;
 
     /// Расстояние между двумя членами множества.
@@ -123,6 +184,18 @@ pub trait Storage {
         unit: &str,
     ) -> StoreResult<Vec<(String, f64, GeoPoint)>>;
 
+    /// Выполняет GEOSEARCH по набору `key`: круг или прямоугольник вокруг
+    /// точки или существующего member.
+    ///
+    /// Возвращает `(member, distance, GeoPoint)` с расстоянием в
+    /// `params.unit`; для отсутствующего ключа — пустой вектор. Если
+    /// участник `FROMMEMBER` отсутствует, возвращает `KeyNotFound`.
+    fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>>;
+
     fn sadd(
         &self,
         key: &Sds,
@@ -145,6 +218,14 @@ pub trait Storage {
         member: &Sds,
     ) -> StoreResult<bool>;
 
+    /// Проверяет принадлежность каждого из `members` множеству `key`; ответ
+    /// содержит по одному флагу на каждый запрошенный элемент.
+    fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>>;
+
     fn srem(
         &self,
         key: &Sds,
@@ -162,133 +243,1233 @@ pub trait Storage {
         key: &Sds,
         count: isize,
     ) -> StoreResult<Vec<Sds>>;
-}
 
-/// Трейт `AsyncStorage` определяет интерфейс для реализаций хранилища
-/// ключ-значение.
-/// Все методы могут возвращать ошибку и используют `StoreResult`
-/// как тип результата.
-#[async_trait]
-pub trait AsyncStorage: Send + Sync + 'static {
-    async fn set(
+    /// Атомарно перемещает элемент `member` из множества `src` во множество
+    /// `dst`. Возвращает `true`, если элемент был в `src`.
+    fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool>;
+
+    /// Возвращает мощность пересечения множеств `keys`, не строя его целиком.
+    /// При `limit` подсчёт останавливается по достижении предела; `Some(0)`
+    /// равносилен отсутствию предела.
+    fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize>;
+
+    /// Записывает пересечение множеств `keys` в `dst` и возвращает его
+    /// мощность. Пустой результат удаляет `dst`, TTL `dst` сбрасывается.
+    fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize>;
+
+    /// Записывает объединение множеств `keys` в `dst`; семантика как у
+    /// `sinterstore`.
+    fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize>;
+
+    /// Записывает разность первого множества и остальных в `dst`; семантика
+    /// как у `sinterstore`.
+    fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize>;
+
+    /// Возвращает объединение упорядоченных множеств `keys` с учётом весов
+    /// и способа агрегации, упорядоченное по `(score, member)`.
+    fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>>;
+
+    /// Возвращает пересечение упорядоченных множеств `keys`; семантика как у
+    /// `zunion`.
+    fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>>;
+
+    /// Возвращает элементы первого упорядоченного множества, отсутствующие
+    /// в остальных, со score из первого множества.
+    fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>>;
+
+    /// Записывает результат `zunion` в `dst` и возвращает его мощность.
+    /// Пустой результат удаляет `dst`, TTL `dst` сбрасывается.
+    fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize>;
+
+    /// Записывает результат `zinter` в `dst`; семантика как у `zunionstore`.
+    fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize>;
+
+    /// Записывает результат `zdiff` в `dst`; семантика как у `zunionstore`.
+    fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize>;
+
+    /// Извлекает до `count` элементов с минимальным или максимальным score
+    /// из первого непустого упорядоченного множества среди `keys`.
+    /// Возвращает имя ключа и пары `(member, score)`, либо `None`, если все
+    /// множества пусты или отсутствуют. Опустевшее множество удаляется.
+    fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>>;
+
+    /// Возвращает score каждого из `members` в упорядоченном множестве `key`
+    /// (`None` для отсутствующих элементов) в порядке запроса.
+    fn zmscore(
         &self,
         key: &Sds,
-        value: Value,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>>;
+
+    /// Возвращает случайные поля хеша плоским списком.
+    /// Положительный `count` выбирает различные поля, отрицательный допускает
+    /// повторы. При `with_values` после каждого поля следует его значение.
+    fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>>;
+
+    /// Атомарно увеличивает целочисленное поле хеша на `delta`, создавая хеш
+    /// и поле при необходимости. Возвращает новое значение поля.
+    fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64>;
+
+    /// Атомарно увеличивает поле хеша с плавающей точкой на `delta`.
+    /// Возвращает новое значение поля.
+    fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64>;
+
+    /// Возвращает значения полей `fields` хеша `key` в порядке запроса
+    /// (`None` для отсутствующих полей и отсутствующего ключа).
+    fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>>;
+
+    /// Оценивает число байт, занятых ключом `key` и его значением
+    /// (`MEMORY USAGE`). Для коллекций учитываются `samples` элементов, `0` —
+    /// точный подсчёт. Возвращает `None`, если ключ отсутствует.
+    fn memory_usage(
+        &self,
+        key: &Sds,
+        samples: usize,
+    ) -> StoreResult<Option<usize>>;
+
+    /// Атомарно извлекает элемент с края списка `src` и вставляет его в
+    /// `dst`. Возвращает перемещённый элемент или `None`, если `src` пуст.
+    fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>>;
+
+    /// Извлекает до `count` элементов из первого непустого списка среди
+    /// `keys`. Возвращает имя ключа и извлечённые элементы, либо `None`, если
+    /// все списки пусты или отсутствуют.
+    fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>>;
+
+    /// Заменяет элемент списка по индексу (отрицательный индекс считается с
+    /// хвоста).
+    ///
+    /// Возвращает `KeyNotFound`, если ключ отсутствует, и `InvalidArgument`,
+    /// если индекс вне диапазона.
+    fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
     ) -> StoreResult<()>;
 
-    async fn get(
+    /// Атомарно возвращает строковое значение ключа и удаляет ключ.
+    /// Если ключ отсутствует или хранит не строку, возвращает `None` и ничего
+    /// не удаляет.
+    fn getdel(
         &self,
         key: &Sds,
-    ) -> StoreResult<Option<Value>>;
+    ) -> StoreResult<Option<Sds>>;
+
+    /// Атомарно возвращает строковое значение ключа и изменяет его TTL.
+    /// Если ключ отсутствует, возвращает `None` и не создаёт ключ. Время
+    /// истечения в прошлом удаляет ключ сразу (значение при этом
+    /// возвращается).
+    fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>>;
 
-    async fn del(
+    /// Копирует значение (вместе с TTL) из `src` в `dst`, при необходимости в
+    /// другую базу `dst_db`. Без `replace` существующий `dst` не
+    /// перезаписывается.
+    ///
+    /// Возвращает `true`, если значение скопировано, и `false`, если `src`
+    /// отсутствует или `dst` уже существует. Если `src == dst`, возвращает
+    /// ошибку.
+    fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool>;
+
+    /// Возвращает абсолютное время истечения ключа в unix-миллисекундах.
+    ///
+    /// Возвращает `Ok(None)`, если ключ отсутствует, и `Ok(Some(-1))`, если
+    /// у ключа нет TTL.
+    fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<i64>>;
+
+    /// Устанавливает (`Some(ttl)`) или снимает (`None`) TTL существующего
+    /// ключа. Нулевой TTL удаляет ключ сразу.
+    ///
+    /// Возвращает `false`, если ключ отсутствует.
+    fn set_expiry(
         &self,
         key: &Sds,
+        ttl: Option<Duration>,
     ) -> StoreResult<bool>;
 
-    async fn mset(
+    /// Возвращает тип значения ключа или `None`, если ключ отсутствует.
+    fn type_of(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<ValueType>>;
+
+    /// Отмечает обращение к ключам `keys` (время последнего доступа).
+    ///
+    /// Возвращает число существующих ключей из списка.
+    fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<usize>;
+
+    /// Возвращает число секунд с последнего обращения к ключу или `None`,
+    /// если ключ отсутствует. Само чтение обращением не считается.
+    fn idletime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u64>>;
+
+    /// Устанавливает политику вытеснения ключей (`maxmemory-policy`).
+    fn set_eviction_policy(
         &self,
-        entries: Vec<(Sds, Value)>,
+        policy: EvictionPolicy,
     ) -> StoreResult<()>;
 
-    async fn mget(
+    /// Возвращает LFU-счётчик ключа или `None`, если ключ отсутствует.
+    /// Само чтение обращением не считается.
+    ///
+    /// Ошибка, если текущая политика вытеснения не LFU: счётчик тогда не
+    /// отражает частоту обращений.
+    fn freq(
         &self,
-        keys: Vec<Sds>,
-    ) -> StoreResult<Vec<Option<Value>>>;
+        key: &Sds,
+    ) -> StoreResult<Option<u8>>;
+
+    /// Задаёт метаданные доступа ключа (`RESTORE ... IDLETIME/FREQ`): время
+    /// простоя в секундах и LFU-счётчик. `None` оставляет значение как есть.
+    ///
+    /// Возвращает `false`, если ключ отсутствует.
+    fn set_access_meta(
+        &self,
+        key: &Sds,
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> StoreResult<bool>;
+
+    /// Снимает TTL с ключа, делая его постоянным.
+    ///
+    /// Возвращает `true`, если TTL был снят, и `false`, если у ключа нет TTL
+    /// или ключ отсутствует.
+    fn persist(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool>;
+
+    /// Выполняет гео-поиск по набору `src` и сохраняет результат в `dst` как
+    /// отсортированное множество (скор — geohash или, при `STOREDIST`,
+    /// расстояние). Прежнее значение `dst` заменяется; при пустом результате
+    /// `dst` удаляется.
+    ///
+    /// Возвращает количество сохранённых элементов. Если участник
+    /// `FROMMEMBER` отсутствует, возвращает `KeyNotFound`.
+    fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64>;
+
+    /// Объединяет HyperLogLog `dest` и `sources` и сохраняет результат в
+    /// `dest`. Отсутствующие ключи считаются пустыми HLL; TTL `dest`
+    /// сохраняется.
+    ///
+    /// Возвращает `InvalidType`, если один из ключей содержит не HLL.
+    fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()>;
+}
+
+/// Упакованный future операции хранилища. Используется там, где конкретный тип
+/// future зависит от движка (см. `impl AsyncStorage for StorageEngine`).
+pub type BoxStoreFuture<'a, T> = Pin<Box<dyn Future<Output = StoreResult<T>> + Send + 'a>>;
+
+/// Асинхронный интерфейс хранилища ключ-значение.
+///
+/// Методы повторяют одноимённые методы [`Storage`], но возвращают futures
+/// (`async fn` в трейте, RPITIT), поэтому сетевой слой может ждать хранилище,
+/// не блокируя поток runtime. Движкам, работающим в памяти, достаточно
+/// [`ready_storage_methods!`], который отдаёт `std::future::ready` с
+/// результатом синхронного вызова; `InPersistentStore`, пишущий AOF,
+/// выполняет методы в пуле блокирующих задач Tokio.
+pub trait AsyncStorage: Send + Sync {
+    fn set(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
+
+    fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> impl Future<Output = StoreResult<Option<Value>>> + Send;
+
+    fn get(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<Option<Value>>> + Send;
+
+    fn del(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn mset(
+        &self,
+        entries: Vec<(&Sds, Value)>,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
+
+    fn mget(
+        &self,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<Vec<Option<Value>>>> + Send;
 
-    async fn rename(
+    fn rename(
         &self,
         from: &Sds,
         to: &Sds,
-    ) -> StoreResult<()>;
+    ) -> impl Future<Output = StoreResult<()>> + Send;
 
-    async fn renamenx(
+    fn renamenx(
         &self,
         from: &Sds,
         to: &Sds,
-    ) -> StoreResult<bool>;
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn flushdb(&self) -> impl Future<Output = StoreResult<()>> + Send;
+
+    fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
 
-    async fn flushdb(&self) -> StoreResult<()>;
+    fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
+
+    fn dbsize(&self) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn keys(
+        &self,
+        pattern: &str,
+    ) -> impl Future<Output = StoreResult<Vec<Sds>>> + Send;
 
-    async fn dbsize(&self) -> StoreResult<usize>;
+    fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
 
-    async fn save(&self) -> StoreResult<()>;
+    fn save(&self) -> impl Future<Output = StoreResult<()>> + Send;
 
-    // GEO методы
-    async fn geo_add(
+    fn geo_add(
         &self,
         key: &Sds,
         lon: f64,
         lat: f64,
         member: &Sds,
-    ) -> StoreResult<bool>;
+        opts: GeoAddOptions,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
 
-    async fn geo_dist(
+    fn geo_dist(
         &self,
         key: &Sds,
         member1: &Sds,
         member2: &Sds,
         unit: &str,
-    ) -> StoreResult<Option<f64>>;
+    ) -> impl Future<Output = StoreResult<Option<f64>>> + Send;
 
-    async fn geo_pos(
+    fn geo_pos(
         &self,
         key: &Sds,
         member: &Sds,
-    ) -> StoreResult<Option<GeoPoint>>;
+    ) -> impl Future<Output = StoreResult<Option<GeoPoint>>> + Send;
 
-    async fn geo_radius(
+    fn geo_radius(
         &self,
         key: &Sds,
         lon: f64,
         lat: f64,
         radius: f64,
         unit: &str,
-    ) -> StoreResult<Vec<(String, f64, GeoPoint)>>;
+    ) -> impl Future<Output = StoreResult<Vec<(String, f64, GeoPoint)>>> + Send;
 
-    async fn geo_radius_by_member(
+    fn geo_radius_by_member(
         &self,
         key: &Sds,
         member: &Sds,
         radius: f64,
         unit: &str,
-    ) -> StoreResult<Vec<(String, f64, GeoPoint)>>;
+    ) -> impl Future<Output = StoreResult<Vec<(String, f64, GeoPoint)>>> + Send;
 
-    async fn sadd(
+    fn geo_search(
         &self,
         key: &Sds,
-        members: Vec<Sds>,
-    ) -> StoreResult<usize>;
-    async fn smembers(
+        params: &GeoSearchParams,
+    ) -> impl Future<Output = StoreResult<Vec<(String, f64, GeoPoint)>>> + Send;
+
+    fn sadd(
         &self,
         key: &Sds,
-    ) -> StoreResult<Vec<Sds>>;
-    async fn scard(
+        members: &[Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn smembers(
         &self,
         key: &Sds,
-    ) -> StoreResult<usize>;
-    async fn sismember(
+    ) -> impl Future<Output = StoreResult<Vec<Sds>>> + Send;
+
+    fn scard(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn sismember(
         &self,
         key: &Sds,
         member: &Sds,
-    ) -> StoreResult<bool>;
-    async fn srem(
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn smismember(
         &self,
         key: &Sds,
-        members: Vec<Sds>,
-    ) -> StoreResult<usize>;
-    async fn srandmember(
+        members: &[&Sds],
+    ) -> impl Future<Output = StoreResult<Vec<bool>>> + Send;
+
+    fn srem(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn srandmember(
         &self,
         key: &Sds,
         count: isize,
-    ) -> StoreResult<Vec<Sds>>;
-    async fn spop(
+    ) -> impl Future<Output = StoreResult<Vec<Sds>>> + Send;
+
+    fn spop(
         &self,
         key: &Sds,
         count: isize,
-    ) -> StoreResult<Vec<Sds>>;
+    ) -> impl Future<Output = StoreResult<Vec<Sds>>> + Send;
+
+    fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> impl Future<Output = StoreResult<Vec<(Sds, f64)>>> + Send;
+
+    fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> impl Future<Output = StoreResult<Vec<(Sds, f64)>>> + Send;
+
+    fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<Vec<(Sds, f64)>>> + Send;
+
+    fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> impl Future<Output = StoreResult<Option<(Sds, ZSetEntries)>>> + Send;
+
+    fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> impl Future<Output = StoreResult<Vec<Option<f64>>>> + Send;
+
+    fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> impl Future<Output = StoreResult<Vec<Sds>>> + Send;
+
+    fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> impl Future<Output = StoreResult<i64>> + Send;
+
+    fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> impl Future<Output = StoreResult<f64>> + Send;
+
+    fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> impl Future<Output = StoreResult<Vec<Option<Sds>>>> + Send;
+
+    fn memory_usage(
+        &self,
+        key: &Sds,
+        samples: usize,
+    ) -> impl Future<Output = StoreResult<Option<usize>>> + Send;
+
+    fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> impl Future<Output = StoreResult<Option<Sds>>> + Send;
+
+    fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> impl Future<Output = StoreResult<Option<(Sds, Vec<Sds>)>>> + Send;
+
+    fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
+
+    fn getdel(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<Option<Sds>>> + Send;
+
+    fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> impl Future<Output = StoreResult<Option<Sds>>> + Send;
+
+    fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<Option<i64>>> + Send;
+
+    fn set_expiry(
+        &self,
+        key: &Sds,
+        ttl: Option<Duration>,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn type_of(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<Option<ValueType>>> + Send;
+
+    fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> impl Future<Output = StoreResult<usize>> + Send;
+
+    fn idletime(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<Option<u64>>> + Send;
+
+    fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> impl Future<Output = StoreResult<()>> + Send;
+
+    fn freq(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<Option<u8>>> + Send;
+
+    fn set_access_meta(
+        &self,
+        key: &Sds,
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn persist(
+        &self,
+        key: &Sds,
+    ) -> impl Future<Output = StoreResult<bool>> + Send;
+
+    fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> impl Future<Output = StoreResult<i64>> + Send;
+
+    fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> impl Future<Output = StoreResult<()>> + Send;
 }
 
+/// Реализует методы [`AsyncStorage`], кроме `save`, через готовые futures:
+/// каждый метод синхронно вызывает одноимённый метод [`Storage`] и отдаёт
+/// результат в `std::future::ready`. Используется внутри
+/// `impl AsyncStorage for ...` движков, которые не выполняют ввод-вывод.
+macro_rules! ready_storage_methods {
+    () => {
+        fn set(
+            &self,
+            key: &$crate::Sds,
+            value: $crate::Value,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::set(self, key, value))
+        }
+
+        fn set_get(
+            &self,
+            key: &$crate::Sds,
+            value: $crate::Value,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::Value>>> + Send {
+            ::std::future::ready($crate::engine::Storage::set_get(self, key, value))
+        }
+
+        fn get(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::Value>>> + Send {
+            ::std::future::ready($crate::engine::Storage::get(self, key))
+        }
+
+        fn del(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::del(self, key))
+        }
+
+        fn mset(
+            &self,
+            entries: Vec<(&$crate::Sds, $crate::Value)>,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::mset(self, entries))
+        }
+
+        fn mget(
+            &self,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<Option<$crate::Value>>>> + Send {
+            ::std::future::ready($crate::engine::Storage::mget(self, keys))
+        }
+
+        fn rename(
+            &self,
+            from: &$crate::Sds,
+            to: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::rename(self, from, to))
+        }
+
+        fn renamenx(
+            &self,
+            from: &$crate::Sds,
+            to: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::renamenx(self, from, to))
+        }
+
+        fn flushdb(&self) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::flushdb(self))
+        }
+
+        fn flushdb_mode(
+            &self,
+            mode: $crate::engine::FlushMode,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::flushdb_mode(self, mode))
+        }
+
+        fn flushall(
+            &self,
+            mode: $crate::engine::FlushMode,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::flushall(self, mode))
+        }
+
+        fn dbsize(&self) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::dbsize(self))
+        }
+
+        fn keys(
+            &self,
+            pattern: &str,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::keys(self, pattern))
+        }
+
+        fn swapdb(
+            &self,
+            db1: usize,
+            db2: usize,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::swapdb(self, db1, db2))
+        }
+
+        fn geo_add(
+            &self,
+            key: &$crate::Sds,
+            lon: f64,
+            lat: f64,
+            member: &$crate::Sds,
+            opts: $crate::GeoAddOptions,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::geo_add(self, key, lon, lat, member, opts))
+        }
+
+        fn geo_dist(
+            &self,
+            key: &$crate::Sds,
+            member1: &$crate::Sds,
+            member2: &$crate::Sds,
+            unit: &str,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<f64>>> + Send {
+            ::std::future::ready($crate::engine::Storage::geo_dist(self, key, member1, member2, unit))
+        }
+
+        fn geo_pos(
+            &self,
+            key: &$crate::Sds,
+            member: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::GeoPoint>>> + Send {
+            ::std::future::ready($crate::engine::Storage::geo_pos(self, key, member))
+        }
+
+        fn geo_radius(
+            &self,
+            key: &$crate::Sds,
+            lon: f64,
+            lat: f64,
+            radius: f64,
+            unit: &str,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<(String, f64, $crate::GeoPoint)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::geo_radius(self, key, lon, lat, radius, unit))
+        }
+
+        fn geo_radius_by_member(
+            &self,
+            key: &$crate::Sds,
+            member: &$crate::Sds,
+            radius: f64,
+            unit: &str,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<(String, f64, $crate::GeoPoint)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::geo_radius_by_member(self, key, member, radius, unit))
+        }
+
+        fn geo_search(
+            &self,
+            key: &$crate::Sds,
+            params: &$crate::GeoSearchParams,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<(String, f64, $crate::GeoPoint)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::geo_search(self, key, params))
+        }
+
+        fn sadd(
+            &self,
+            key: &$crate::Sds,
+            members: &[$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::sadd(self, key, members))
+        }
+
+        fn smembers(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::smembers(self, key))
+        }
+
+        fn scard(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::scard(self, key))
+        }
+
+        fn sismember(
+            &self,
+            key: &$crate::Sds,
+            member: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::sismember(self, key, member))
+        }
+
+        fn smismember(
+            &self,
+            key: &$crate::Sds,
+            members: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<bool>>> + Send {
+            ::std::future::ready($crate::engine::Storage::smismember(self, key, members))
+        }
+
+        fn srem(
+            &self,
+            key: &$crate::Sds,
+            members: &[$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::srem(self, key, members))
+        }
+
+        fn srandmember(
+            &self,
+            key: &$crate::Sds,
+            count: isize,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::srandmember(self, key, count))
+        }
+
+        fn spop(
+            &self,
+            key: &$crate::Sds,
+            count: isize,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::spop(self, key, count))
+        }
+
+        fn smove(
+            &self,
+            src: &$crate::Sds,
+            dst: &$crate::Sds,
+            member: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::smove(self, src, dst, member))
+        }
+
+        fn sintercard(
+            &self,
+            keys: &[&$crate::Sds],
+            limit: Option<usize>,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::sintercard(self, keys, limit))
+        }
+
+        fn sinterstore(
+            &self,
+            dst: &$crate::Sds,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::sinterstore(self, dst, keys))
+        }
+
+        fn sunionstore(
+            &self,
+            dst: &$crate::Sds,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::sunionstore(self, dst, keys))
+        }
+
+        fn sdiffstore(
+            &self,
+            dst: &$crate::Sds,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::sdiffstore(self, dst, keys))
+        }
+
+        fn zunion(
+            &self,
+            keys: &[&$crate::Sds],
+            options: &$crate::ZCombineOptions,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<($crate::Sds, f64)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::zunion(self, keys, options))
+        }
+
+        fn zinter(
+            &self,
+            keys: &[&$crate::Sds],
+            options: &$crate::ZCombineOptions,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<($crate::Sds, f64)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::zinter(self, keys, options))
+        }
+
+        fn zdiff(
+            &self,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<($crate::Sds, f64)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::zdiff(self, keys))
+        }
+
+        fn zunionstore(
+            &self,
+            dst: &$crate::Sds,
+            keys: &[&$crate::Sds],
+            options: &$crate::ZCombineOptions,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::zunionstore(self, dst, keys, options))
+        }
+
+        fn zinterstore(
+            &self,
+            dst: &$crate::Sds,
+            keys: &[&$crate::Sds],
+            options: &$crate::ZCombineOptions,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::zinterstore(self, dst, keys, options))
+        }
+
+        fn zdiffstore(
+            &self,
+            dst: &$crate::Sds,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::zdiffstore(self, dst, keys))
+        }
+
+        fn zmpop(
+            &self,
+            keys: &[&$crate::Sds],
+            dir: $crate::ZPopDir,
+            count: usize,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<($crate::Sds, $crate::ZSetEntries)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::zmpop(self, keys, dir, count))
+        }
+
+        fn zmscore(
+            &self,
+            key: &$crate::Sds,
+            members: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<Option<f64>>>> + Send {
+            ::std::future::ready($crate::engine::Storage::zmscore(self, key, members))
+        }
+
+        fn hrandfield(
+            &self,
+            key: &$crate::Sds,
+            count: isize,
+            with_values: bool,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::hrandfield(self, key, count, with_values))
+        }
+
+        fn hincrby(
+            &self,
+            key: &$crate::Sds,
+            field: &$crate::Sds,
+            delta: i64,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<i64>> + Send {
+            ::std::future::ready($crate::engine::Storage::hincrby(self, key, field, delta))
+        }
+
+        fn hincrbyfloat(
+            &self,
+            key: &$crate::Sds,
+            field: &$crate::Sds,
+            delta: f64,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<f64>> + Send {
+            ::std::future::ready($crate::engine::Storage::hincrbyfloat(self, key, field, delta))
+        }
+
+        fn hmget(
+            &self,
+            key: &$crate::Sds,
+            fields: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Vec<Option<$crate::Sds>>>> + Send {
+            ::std::future::ready($crate::engine::Storage::hmget(self, key, fields))
+        }
+
+        fn memory_usage(
+            &self,
+            key: &$crate::Sds,
+            samples: usize,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<usize>>> + Send {
+            ::std::future::ready($crate::engine::Storage::memory_usage(self, key, samples))
+        }
+
+        fn lmove(
+            &self,
+            src: &$crate::Sds,
+            dst: &$crate::Sds,
+            src_dir: $crate::ListDir,
+            dst_dir: $crate::ListDir,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::lmove(self, src, dst, src_dir, dst_dir))
+        }
+
+        fn lmpop(
+            &self,
+            keys: &[&$crate::Sds],
+            dir: $crate::ListDir,
+            count: usize,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<($crate::Sds, Vec<$crate::Sds>)>>> + Send {
+            ::std::future::ready($crate::engine::Storage::lmpop(self, keys, dir, count))
+        }
+
+        fn lset(
+            &self,
+            key: &$crate::Sds,
+            index: isize,
+            value: $crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::lset(self, key, index, value))
+        }
+
+        fn getdel(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::getdel(self, key))
+        }
+
+        fn getex(
+            &self,
+            key: &$crate::Sds,
+            expiry: $crate::GetExExpiry,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::Sds>>> + Send {
+            ::std::future::ready($crate::engine::Storage::getex(self, key, expiry))
+        }
+
+        fn copy(
+            &self,
+            src: &$crate::Sds,
+            dst: &$crate::Sds,
+            dst_db: Option<usize>,
+            replace: bool,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::copy(self, src, dst, dst_db, replace))
+        }
+
+        fn expiretime(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<i64>>> + Send {
+            ::std::future::ready($crate::engine::Storage::expiretime(self, key))
+        }
+
+        fn set_expiry(
+            &self,
+            key: &$crate::Sds,
+            ttl: Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::set_expiry(self, key, ttl))
+        }
+
+        fn type_of(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<$crate::ValueType>>> + Send {
+            ::std::future::ready($crate::engine::Storage::type_of(self, key))
+        }
+
+        fn touch(
+            &self,
+            keys: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<usize>> + Send {
+            ::std::future::ready($crate::engine::Storage::touch(self, keys))
+        }
+
+        fn idletime(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<u64>>> + Send {
+            ::std::future::ready($crate::engine::Storage::idletime(self, key))
+        }
+
+        fn set_eviction_policy(
+            &self,
+            policy: $crate::EvictionPolicy,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::set_eviction_policy(self, policy))
+        }
+
+        fn freq(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<Option<u8>>> + Send {
+            ::std::future::ready($crate::engine::Storage::freq(self, key))
+        }
+
+        fn set_access_meta(
+            &self,
+            key: &$crate::Sds,
+            idle_secs: Option<u64>,
+            freq: Option<u8>,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::set_access_meta(self, key, idle_secs, freq))
+        }
+
+        fn persist(
+            &self,
+            key: &$crate::Sds,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<bool>> + Send {
+            ::std::future::ready($crate::engine::Storage::persist(self, key))
+        }
+
+        fn geosearchstore(
+            &self,
+            dst: &$crate::Sds,
+            src: &$crate::Sds,
+            params: $crate::GeoSearchParams,
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<i64>> + Send {
+            ::std::future::ready($crate::engine::Storage::geosearchstore(self, dst, src, params))
+        }
+
+        fn pfmerge(
+            &self,
+            dest: &$crate::Sds,
+            sources: &[&$crate::Sds],
+        ) -> impl ::std::future::Future<Output = $crate::StoreResult<()>> + Send {
+            ::std::future::ready($crate::engine::Storage::pfmerge(self, dest, sources))
+        }
+    };
+}
+
+pub(crate) use ready_storage_methods;
+
 pub trait SessionStorage: Send + Sync {
     /// Сохраняет сессию в хранилище.
     fn insert_session(
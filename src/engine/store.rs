@@ -1,10 +1,16 @@
-use std::io::{self};
+use std::{
+    io::{self},
+    sync::Arc,
+    time::Duration,
+};
 
-use super::{InMemoryStore, InPersistentStore};
+use super::{BoxStoreFuture, FlushMode, InMemoryStore, InPersistentStore};
 use crate::{
     config::settings::{StorageConfig, StorageType},
     engine::cluster::InClusterStore,
-    GeoPoint, Sds, Storage, StoreResult, Value,
+    EvictionPolicy, GeoAddOptions, GeoPoint, GeoSearchParams, GetExExpiry, KeyspaceEvent,
+    KeyspaceNotifier, ListDir, Sds, Storage, StoreError, StoreResult, StreamNotifiers, Value,
+    ValueType, ZCombineOptions, ZPopDir, ZSetEntries,
 };
 
 /// Координата для географических данных.
@@ -46,6 +52,19 @@ impl StorageEngine {
         }
     }
 
+    /// Устанавливает значение по ключу и возвращает прежнее значение.
+    pub fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>> {
+        match self {
+            StorageEngine::Memory(store) => store.set_get(key, value),
+            StorageEngine::Cluster(store) => store.set_get(key, value),
+            StorageEngine::Persistent(store) => store.set_get(key, value),
+        }
+    }
+
     /// Получает значение по ключу.
     /// Если ключ отсутствует, возвращает `None`.
     pub fn get(
@@ -173,19 +192,23 @@ impl StorageEngine {
     /// Добавляет точку `(lon, lat)` с именем `member` в гео-набор под ключом
     /// `key`.
     ///
-    /// Возвращает `Ok(true)`, если `member` был добавлен впервые,
-    /// и `Ok(false)`, если он уже присутствовал.
+    /// Флаги `opts` соответствуют `GEOADD [NX|XX] [CH]`.
+    ///
+    /// Возвращает `Ok(true)`, если `member` был добавлен впервые (или, при
+    /// `opts.ch`, его координаты изменились), и `Ok(false)` в остальных
+    /// случаях.
     pub fn geo_add(
         &self,
         key: &Sds,
         lon: f64,
         lat: f64,
         member: &Sds,
+        opts: GeoAddOptions,
     ) -> StoreResult<bool> {
         match self {
-            StorageEngine::Memory(store) => store.geo_add(key, lon, lat, member),
-            StorageEngine::Cluster(store) => store.geo_add(key, lon, lat, member),
-            StorageEngine::Persistent(store) => store.geo_add(key, lon, lat, member),
+            StorageEngine::Memory(store) => store.geo_add(key, lon, lat, member, opts),
+            StorageEngine::Cluster(store) => store.geo_add(key, lon, lat, member, opts),
+            StorageEngine::Persistent(store) => store.geo_add(key, lon, lat, member, opts),
         }
     }
 
@@ -242,6 +265,20 @@ impl StorageEngine {
         }
     }
 
+    /// Выполняет GEOSEARCH по набору `key` и возвращает кортежи `(member,
+    /// distance, GeoPoint)` с расстоянием в `params.unit`.
+    pub fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        match self {
+            StorageEngine::Memory(store) => store.geo_search(key, params),
+            StorageEngine::Cluster(store) => store.geo_search(key, params),
+            StorageEngine::Persistent(store) => store.geo_search(key, params),
+        }
+    }
+
     /// То же, что `geo_radius`, но центр задаётся координатами уже
     /// существующего `member`.
     ///
@@ -307,6 +344,18 @@ impl StorageEngine {
         }
     }
 
+    pub fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>> {
+        match self {
+            StorageEngine::Memory(store) => store.smismember(key, members),
+            StorageEngine::Cluster(store) => store.smismember(key, members),
+            StorageEngine::Persistent(store) => store.smismember(key, members),
+        }
+    }
+
     pub fn srem(
         &self,
         key: &Sds,
@@ -343,199 +392,1282 @@ impl StorageEngine {
         }
     }
 
-    pub fn dbsize(&self) -> StoreResult<usize> {
+    pub fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
         match self {
-            StorageEngine::Memory(store) => store.dbsize(),
-            StorageEngine::Cluster(store) => store.dbsize(),
-            StorageEngine::Persistent(store) => store.dbsize(),
+            StorageEngine::Memory(store) => store.smove(src, dst, member),
+            StorageEngine::Cluster(store) => store.smove(src, dst, member),
+            StorageEngine::Persistent(store) => store.smove(src, dst, member),
         }
     }
 
-    pub fn save(&self) -> StoreResult<()> {
+    pub fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize> {
         match self {
-            StorageEngine::Memory(store) => store.save(),
-            StorageEngine::Cluster(store) => store.save(),
-            StorageEngine::Persistent(store) => store.save(),
+            StorageEngine::Memory(store) => store.sintercard(keys, limit),
+            StorageEngine::Cluster(store) => store.sintercard(keys, limit),
+            StorageEngine::Persistent(store) => store.sintercard(keys, limit),
         }
     }
-}
-
-////////////////////////////////////////////////////////////////////////////////
-// Тесты
-////////////////////////////////////////////////////////////////////////////////
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    pub fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.sinterstore(dst, keys),
+            StorageEngine::Cluster(store) => store.sinterstore(dst, keys),
+            StorageEngine::Persistent(store) => store.sinterstore(dst, keys),
+        }
+    }
 
-    fn key(data: &str) -> Sds {
-        Sds::from(data.as_bytes())
+    pub fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.sunionstore(dst, keys),
+            StorageEngine::Cluster(store) => store.sunionstore(dst, keys),
+            StorageEngine::Persistent(store) => store.sunionstore(dst, keys),
+        }
     }
 
-    /// Тест проверяет установку значения и последующее получение должно вернуть
-    /// то же значение.
-    #[test]
-    fn test_engine_set_and_get() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k = key("kin");
-        let v = Value::Str(Sds::from_str("dzadza"));
-        engine.set(&k, v.clone()).unwrap();
-        let got = engine.get(&k).unwrap();
-        assert_eq!(got, Some(v));
+    pub fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.sdiffstore(dst, keys),
+            StorageEngine::Cluster(store) => store.sdiffstore(dst, keys),
+            StorageEngine::Persistent(store) => store.sdiffstore(dst, keys),
+        }
     }
 
-    /// Тест проверяет получение по несуществующему ключу возвращает None.
-    #[test]
-    fn test_engine_get_nonexistent_key() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k = key("not_found");
-        let got = engine.get(&k).unwrap();
-        assert_eq!(got, None);
+    pub fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        match self {
+            StorageEngine::Memory(store) => store.zunion(keys, options),
+            StorageEngine::Cluster(store) => store.zunion(keys, options),
+            StorageEngine::Persistent(store) => store.zunion(keys, options),
+        }
     }
 
-    /// Тест проверяет удаление существующего ключа должно удалить ключ.
-    #[test]
-    fn test_engine_delete() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k = key("hello");
-        let v = Value::Str(Sds::from_str("world"));
-        engine.set(&k, v).unwrap();
-        engine.del(&k).unwrap();
-        let got = engine.get(&k).unwrap();
-        assert_eq!(got, None)
+    pub fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        match self {
+            StorageEngine::Memory(store) => store.zinter(keys, options),
+            StorageEngine::Cluster(store) => store.zinter(keys, options),
+            StorageEngine::Persistent(store) => store.zinter(keys, options),
+        }
     }
 
-    /// Тест проверяет удаление несуществующего ключа не должно вызывать ошибку.
-    #[test]
-    fn test_engine_delete_nonexistent_key() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k = key("ghost");
-        // Удаление не должно вызывать паники или ошибки.
-        let result = engine.del(&k);
-        assert!(result.is_ok());
+    pub fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        match self {
+            StorageEngine::Memory(store) => store.zdiff(keys),
+            StorageEngine::Cluster(store) => store.zdiff(keys),
+            StorageEngine::Persistent(store) => store.zdiff(keys),
+        }
     }
 
-    /// Тест проверяет установка нескольких пар ключ-значение с помощью mset.
-    #[test]
-    fn test_engine_mset() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        // Текущие переменные, поэтому ссылки действительны до конца работы функции.
-        let k1 = key("kin1");
-        let k2 = key("kin2");
-        let v1 = Value::Str(Sds::from_str("dza1"));
-        let v2 = Value::Str(Sds::from_str("dza2"));
-        // Собирать Vec<(&Sds, значение)>
-        let entries = vec![(&k1, v1.clone()), (&k2, v2.clone())];
-        engine.mset(entries).unwrap();
-        // Проверяю, что должно было быть сделано
-        assert_eq!(engine.get(&k1).unwrap(), Some(v1));
-        assert_eq!(engine.get(&k2).unwrap(), Some(v2));
+    pub fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.zunionstore(dst, keys, options),
+            StorageEngine::Cluster(store) => store.zunionstore(dst, keys, options),
+            StorageEngine::Persistent(store) => store.zunionstore(dst, keys, options),
+        }
     }
 
-    /// Тест проверяет получение нескольких значений с помощью mget возвращает
-    /// их в правильном порядке.
-    #[test]
-    fn test_engine_mget() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k1 = key("kin1");
-        let k2 = key("kin2");
-        let v1 = Value::Str(Sds::from_str("dza1"));
-        let v2 = Value::Str(Sds::from_str("dza2"));
-        engine.set(&k1, v1.clone()).unwrap();
-        engine.set(&k2, v2.clone()).unwrap();
-        let got = engine.mget(&[&k1, &k2]).unwrap();
-        assert_eq!(got, vec![Some(v1), Some(v2)]);
+    pub fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.zinterstore(dst, keys, options),
+            StorageEngine::Cluster(store) => store.zinterstore(dst, keys, options),
+            StorageEngine::Persistent(store) => store.zinterstore(dst, keys, options),
+        }
     }
 
-    /// Тест проверяет успешное переименование ключа.
-    #[test]
-    fn test_engine_rename() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k1 = key("old_key");
-        let k2 = key("new_key");
-        let v = Value::Str(Sds::from_str("value"));
-        engine.set(&k1, v.clone()).unwrap();
-        engine.rename(&k1, &k2).unwrap();
-        let got = engine.get(&k2).unwrap();
-        assert_eq!(got, Some(v));
+    pub fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.zdiffstore(dst, keys),
+            StorageEngine::Cluster(store) => store.zdiffstore(dst, keys),
+            StorageEngine::Persistent(store) => store.zdiffstore(dst, keys),
+        }
     }
 
-    /// Тест проверяет попытку переименовать несуществующий ключ должна вернуть
-    /// ошибку.
-    #[test]
-    fn test_engine_rename_nonexistent_key() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k1 = key("old_key");
-        let k2 = key("new_key");
-        // При попытке переименовать несуществующий ключ должна быть возвращена ошибка.
-        let result = engine.rename(&k1, &k2);
-        assert!(result.is_err());
+    pub fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>> {
+        match self {
+            StorageEngine::Memory(store) => store.zmpop(keys, dir, count),
+            StorageEngine::Cluster(store) => store.zmpop(keys, dir, count),
+            StorageEngine::Persistent(store) => store.zmpop(keys, dir, count),
+        }
     }
 
-    /// Тест проверяет renamenx переименовывает ключ только если новый ключ
-    /// отсутствует.
-    #[test]
-    fn test_engine_renamenx() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let k1 = key("old_key");
-        let k2 = key("new_key");
-        let v = Value::Str(Sds::from_str("value"));
-        engine.set(&k1, v.clone()).unwrap();
-        let result = engine.renamenx(&k1, &k2).unwrap();
-        assert!(result);
-        // Убедитесь, что старый ключ удален, а новый присутствует.
-        let got = engine.get(&k2).unwrap();
-        assert_eq!(got, Some(v));
-        // // Повторная попытка переименования должна завершиться неудачей, поскольку
-        // новый ключ уже существует.
-        let result = engine.renamenx(&k1, &k2).unwrap();
-        assert!(!result);
+    pub fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>> {
+        match self {
+            StorageEngine::Memory(store) => store.zmscore(key, members),
+            StorageEngine::Cluster(store) => store.zmscore(key, members),
+            StorageEngine::Persistent(store) => store.zmscore(key, members),
+        }
     }
 
-    /// Тест проверяет, что `flushdb` очищает все данные из хранилища.
-    #[test]
-    fn test_engine_flushdb() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        engine
-            .set(&key("a"), Value::Str(Sds::from_str("x")))
-            .unwrap();
-        engine
-            .set(&key("b"), Value::Str(Sds::from_str("y")))
-            .unwrap();
-        engine.flushdb().unwrap();
-        let a = engine.get(&key("a")).unwrap();
-        let b = engine.get(&key("b")).unwrap();
-        assert_eq!(a, None);
-        assert_eq!(b, None);
+    pub fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        match self {
+            StorageEngine::Memory(store) => store.hrandfield(key, count, with_values),
+            StorageEngine::Cluster(store) => store.hrandfield(key, count, with_values),
+            StorageEngine::Persistent(store) => store.hrandfield(key, count, with_values),
+        }
     }
 
-    /// Тест проверяет инициализацию движка с конфигурацией памяти.
-    #[test]
-    fn test_engine_initialize_memory() {
-        let config = StorageConfig {
-            storage_type: StorageType::Memory,
-        };
-        let engine = StorageEngine::initialize(&config);
-        assert!(engine.is_ok());
+    pub fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
+        match self {
+            StorageEngine::Memory(store) => store.hincrby(key, field, delta),
+            StorageEngine::Cluster(store) => store.hincrby(key, field, delta),
+            StorageEngine::Persistent(store) => store.hincrby(key, field, delta),
+        }
     }
 
-    /// Тест проверяет, что `get_store` возвращает объект трейта, с которым
-    /// можно работать.
-    #[test]
-    fn test_engine_get_store() {
-        let engine = StorageEngine::Memory(InMemoryStore::new());
-        let store = engine.get_store();
-        assert!(store.mget(&[]).is_ok());
+    pub fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
+        match self {
+            StorageEngine::Memory(store) => store.hincrbyfloat(key, field, delta),
+            StorageEngine::Cluster(store) => store.hincrbyfloat(key, field, delta),
+            StorageEngine::Persistent(store) => store.hincrbyfloat(key, field, delta),
+        }
     }
 
-    /// Тест проверяет, что `get_store_mut` возвращает изменяемый объект трейта.
-    #[test]
-    fn test_engine_get_store_mut() {
-        let mut engine = StorageEngine::Memory(InMemoryStore::new());
-        let store_mut = engine.get_store_mut();
-        assert!(store_mut.set(&key("x"), Value::Int(42)).is_ok());
-        let got = store_mut.get(&key("x")).unwrap();
-        assert_eq!(got, Some(Value::Int(42)));
+    pub fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>> {
+        match self {
+            StorageEngine::Memory(store) => store.hmget(key, fields),
+            StorageEngine::Cluster(store) => store.hmget(key, fields),
+            StorageEngine::Persistent(store) => store.hmget(key, fields),
+        }
+    }
+
+    pub fn memory_usage(
+        &self,
+        key: &Sds,
+        samples: usize,
+    ) -> StoreResult<Option<usize>> {
+        match self {
+            StorageEngine::Memory(store) => store.memory_usage(key, samples),
+            StorageEngine::Cluster(store) => store.memory_usage(key, samples),
+            StorageEngine::Persistent(store) => store.memory_usage(key, samples),
+        }
+    }
+
+    pub fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>> {
+        match self {
+            StorageEngine::Memory(store) => store.lmove(src, dst, src_dir, dst_dir),
+            StorageEngine::Cluster(store) => store.lmove(src, dst, src_dir, dst_dir),
+            StorageEngine::Persistent(store) => store.lmove(src, dst, src_dir, dst_dir),
+        }
+    }
+
+    pub fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>> {
+        match self {
+            StorageEngine::Memory(store) => store.lmpop(keys, dir, count),
+            StorageEngine::Cluster(store) => store.lmpop(keys, dir, count),
+            StorageEngine::Persistent(store) => store.lmpop(keys, dir, count),
+        }
+    }
+
+    /// Заменяет элемент списка по индексу.
+    pub fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
+    ) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.lset(key, index, value),
+            StorageEngine::Cluster(store) => store.lset(key, index, value),
+            StorageEngine::Persistent(store) => store.lset(key, index, value),
+        }
+    }
+
+    pub fn getdel(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Sds>> {
+        match self {
+            StorageEngine::Memory(store) => store.getdel(key),
+            StorageEngine::Cluster(store) => store.getdel(key),
+            StorageEngine::Persistent(store) => store.getdel(key),
+        }
+    }
+
+    pub fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>> {
+        match self {
+            StorageEngine::Memory(store) => store.getex(key, expiry),
+            StorageEngine::Cluster(store) => store.getex(key, expiry),
+            StorageEngine::Persistent(store) => store.getex(key, expiry),
+        }
+    }
+
+    pub fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool> {
+        match self {
+            StorageEngine::Memory(store) => store.copy(src, dst, dst_db, replace),
+            StorageEngine::Cluster(store) => store.copy(src, dst, dst_db, replace),
+            StorageEngine::Persistent(store) => store.copy(src, dst, dst_db, replace),
+        }
+    }
+
+    pub fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<i64>> {
+        match self {
+            StorageEngine::Memory(store) => store.expiretime(key),
+            StorageEngine::Cluster(store) => store.expiretime(key),
+            StorageEngine::Persistent(store) => store.expiretime(key),
+        }
+    }
+
+    /// Устанавливает или снимает TTL существующего ключа.
+    pub fn set_expiry(
+        &self,
+        key: &Sds,
+        ttl: Option<Duration>,
+    ) -> StoreResult<bool> {
+        match self {
+            StorageEngine::Memory(store) => store.set_expiry(key, ttl),
+            StorageEngine::Cluster(store) => store.set_expiry(key, ttl),
+            StorageEngine::Persistent(store) => store.set_expiry(key, ttl),
+        }
+    }
+
+    /// Возвращает тип значения ключа.
+    pub fn type_of(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<ValueType>> {
+        match self {
+            StorageEngine::Memory(store) => store.type_of(key),
+            StorageEngine::Cluster(store) => store.type_of(key),
+            StorageEngine::Persistent(store) => store.type_of(key),
+        }
+    }
+
+    /// Отмечает обращение к ключам и возвращает число существующих.
+    pub fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.touch(keys),
+            StorageEngine::Cluster(store) => store.touch(keys),
+            StorageEngine::Persistent(store) => store.touch(keys),
+        }
+    }
+
+    /// Возвращает число секунд с последнего обращения к ключу.
+    pub fn idletime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u64>> {
+        match self {
+            StorageEngine::Memory(store) => store.idletime(key),
+            StorageEngine::Cluster(store) => store.idletime(key),
+            StorageEngine::Persistent(store) => store.idletime(key),
+        }
+    }
+
+    /// Устанавливает политику вытеснения ключей.
+    pub fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.set_eviction_policy(policy),
+            StorageEngine::Cluster(store) => store.set_eviction_policy(policy),
+            StorageEngine::Persistent(store) => store.set_eviction_policy(policy),
+        }
+    }
+
+    /// Возвращает LFU-счётчик ключа.
+    pub fn freq(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u8>> {
+        match self {
+            StorageEngine::Memory(store) => store.freq(key),
+            StorageEngine::Cluster(store) => store.freq(key),
+            StorageEngine::Persistent(store) => store.freq(key),
+        }
+    }
+
+    /// Задаёт время простоя и LFU-счётчик ключа.
+    pub fn set_access_meta(
+        &self,
+        key: &Sds,
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> StoreResult<bool> {
+        match self {
+            StorageEngine::Memory(store) => store.set_access_meta(key, idle_secs, freq),
+            StorageEngine::Cluster(store) => store.set_access_meta(key, idle_secs, freq),
+            StorageEngine::Persistent(store) => store.set_access_meta(key, idle_secs, freq),
+        }
+    }
+
+    /// Снимает TTL с ключа.
+    pub fn persist(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        match self {
+            StorageEngine::Memory(store) => store.persist(key),
+            StorageEngine::Cluster(store) => store.persist(key),
+            StorageEngine::Persistent(store) => store.persist(key),
+        }
+    }
+
+    /// Сохраняет результаты гео-поиска по `src` в `dst` как отсортированное
+    /// множество и возвращает количество сохранённых элементов.
+    pub fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64> {
+        match self {
+            StorageEngine::Memory(store) => store.geosearchstore(dst, src, params),
+            StorageEngine::Cluster(store) => store.geosearchstore(dst, src, params),
+            StorageEngine::Persistent(store) => store.geosearchstore(dst, src, params),
+        }
+    }
+
+    /// Объединяет HyperLogLog `dest` и `sources` в `dest`.
+    pub fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.pfmerge(dest, sources),
+            StorageEngine::Cluster(store) => store.pfmerge(dest, sources),
+            StorageEngine::Persistent(store) => store.pfmerge(dest, sources),
+        }
+    }
+
+    /// Возвращает ключи, подходящие под glob-шаблон.
+    pub fn keys(
+        &self,
+        pattern: &str,
+    ) -> StoreResult<Vec<Sds>> {
+        match self {
+            StorageEngine::Memory(store) => store.keys(pattern),
+            StorageEngine::Cluster(store) => store.keys(pattern),
+            StorageEngine::Persistent(store) => store.keys(pattern),
+        }
+    }
+
+    pub fn dbsize(&self) -> StoreResult<usize> {
+        match self {
+            StorageEngine::Memory(store) => store.dbsize(),
+            StorageEngine::Cluster(store) => store.dbsize(),
+            StorageEngine::Persistent(store) => store.dbsize(),
+        }
+    }
+
+    /// Сообщает о событии пространства ключей для `key`. Уведомления
+    /// поддерживает только хранилище в памяти.
+    pub fn notify_keyspace_event(
+        &self,
+        event: KeyspaceEvent,
+        key: &Sds,
+    ) {
+        if let StorageEngine::Memory(store) = self {
+            store.notify_keyspace_event(event, key);
+        }
+    }
+
+    /// Возвращает уведомитель о событиях пространства ключей, если движок
+    /// их поддерживает.
+    pub fn keyspace_notifier(&self) -> Option<&Arc<KeyspaceNotifier>> {
+        match self {
+            StorageEngine::Memory(store) => Some(store.keyspace_notifier()),
+            StorageEngine::Cluster(_) | StorageEngine::Persistent(_) => None,
+        }
+    }
+
+    /// Возвращает реестр ожидающих новых записей потоков: `XADD` будит
+    /// через него `XREAD BLOCK`.
+    pub fn stream_notifiers(&self) -> &StreamNotifiers {
+        match self {
+            StorageEngine::Memory(store) => store.stream_notifiers(),
+            StorageEngine::Cluster(store) => store.stream_notifiers(),
+            StorageEngine::Persistent(store) => store.stream_notifiers(),
+        }
+    }
+
+    /// Возвращает число логических баз движка.
+    pub fn databases(&self) -> usize {
+        match self {
+            StorageEngine::Memory(store) => store.databases(),
+            // Персистентное хранилище и кластер содержат только базу 0.
+            StorageEngine::Cluster(_) | StorageEngine::Persistent(_) => 1,
+        }
+    }
+
+    /// Возвращает движок, работающий с логической базой `index` поверх тех же
+    /// данных.
+    ///
+    /// # Возвращает
+    /// - `Ok(StorageEngine)` — представление базы in-memory хранилища
+    /// - `Err(StoreError::InvalidArgument)` — если базы нет
+    /// - `Err(StoreError::UnsupportedOperation)` — для движков с единственной
+    ///   базой 0: для неё используется сам движок
+    pub fn select(
+        &self,
+        index: usize,
+    ) -> StoreResult<StorageEngine> {
+        match self {
+            StorageEngine::Memory(store) => store.select(index).map(StorageEngine::Memory),
+            StorageEngine::Cluster(_) | StorageEngine::Persistent(_) if index > 0 => Err(
+                StoreError::InvalidArgument("DB index is out of range".to_string()),
+            ),
+            StorageEngine::Cluster(_) | StorageEngine::Persistent(_) => Err(
+                StoreError::UnsupportedOperation("SELECT on a single-database engine".to_string()),
+            ),
+        }
+    }
+
+    pub fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.flushdb_mode(mode),
+            StorageEngine::Cluster(store) => store.flushdb_mode(mode),
+            StorageEngine::Persistent(store) => store.flushdb_mode(mode),
+        }
+    }
+
+    pub fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.flushall(mode),
+            StorageEngine::Cluster(store) => store.flushall(mode),
+            StorageEngine::Persistent(store) => store.flushall(mode),
+        }
+    }
+
+    pub fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.swapdb(db1, db2),
+            StorageEngine::Cluster(store) => store.swapdb(db1, db2),
+            StorageEngine::Persistent(store) => store.swapdb(db1, db2),
+        }
+    }
+
+    pub fn save(&self) -> StoreResult<()> {
+        match self {
+            StorageEngine::Memory(store) => store.save(),
+            StorageEngine::Cluster(store) => store.save(),
+            StorageEngine::Persistent(store) => store.save(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Асинхронный интерфейс
+////////////////////////////////////////////////////////////////////////////////
+
+/// Вызывает метод [`AsyncStorage`](crate::engine::AsyncStorage) активного
+/// движка. Futures вариантов имеют разные типы, поэтому каждый упаковывается в
+/// [`BoxStoreFuture`].
+macro_rules! dispatch_async {
+    ($self:ident, $method:ident($($arg:expr),*)) => {
+        {
+            let fut: BoxStoreFuture<'_, _> = match $self {
+                StorageEngine::Memory(store) => {
+                    Box::pin(crate::engine::AsyncStorage::$method(store, $($arg),*))
+                }
+                StorageEngine::Cluster(store) => {
+                    Box::pin(crate::engine::AsyncStorage::$method(store, $($arg),*))
+                }
+                StorageEngine::Persistent(store) => {
+                    Box::pin(crate::engine::AsyncStorage::$method(store, $($arg),*))
+                }
+            };
+            fut.await
+        }
+    };
+}
+
+impl crate::engine::AsyncStorage for StorageEngine {
+    async fn set(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, set(key, value))
+    }
+
+    async fn set_get(
+        &self,
+        key: &Sds,
+        value: Value,
+    ) -> StoreResult<Option<Value>> {
+        dispatch_async!(self, set_get(key, value))
+    }
+
+    async fn get(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Value>> {
+        dispatch_async!(self, get(key))
+    }
+
+    async fn del(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, del(key))
+    }
+
+    async fn mset(
+        &self,
+        entries: Vec<(&Sds, Value)>,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, mset(entries))
+    }
+
+    async fn mget(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<Option<Value>>> {
+        dispatch_async!(self, mget(keys))
+    }
+
+    async fn rename(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, rename(from, to))
+    }
+
+    async fn renamenx(
+        &self,
+        from: &Sds,
+        to: &Sds,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, renamenx(from, to))
+    }
+
+    async fn flushdb(&self) -> StoreResult<()> {
+        dispatch_async!(self, flushdb())
+    }
+
+    async fn flushdb_mode(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, flushdb_mode(mode))
+    }
+
+    async fn flushall(
+        &self,
+        mode: FlushMode,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, flushall(mode))
+    }
+
+    async fn dbsize(&self) -> StoreResult<usize> {
+        dispatch_async!(self, dbsize())
+    }
+
+    async fn keys(
+        &self,
+        pattern: &str,
+    ) -> StoreResult<Vec<Sds>> {
+        dispatch_async!(self, keys(pattern))
+    }
+
+    async fn swapdb(
+        &self,
+        db1: usize,
+        db2: usize,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, swapdb(db1, db2))
+    }
+
+    async fn save(&self) -> StoreResult<()> {
+        dispatch_async!(self, save())
+    }
+
+    async fn geo_add(
+        &self,
+        key: &Sds,
+        lon: f64,
+        lat: f64,
+        member: &Sds,
+        opts: GeoAddOptions,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, geo_add(key, lon, lat, member, opts))
+    }
+
+    async fn geo_dist(
+        &self,
+        key: &Sds,
+        member1: &Sds,
+        member2: &Sds,
+        unit: &str,
+    ) -> StoreResult<Option<f64>> {
+        dispatch_async!(self, geo_dist(key, member1, member2, unit))
+    }
+
+    async fn geo_pos(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<Option<GeoPoint>> {
+        dispatch_async!(self, geo_pos(key, member))
+    }
+
+    async fn geo_radius(
+        &self,
+        key: &Sds,
+        lon: f64,
+        lat: f64,
+        radius: f64,
+        unit: &str,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        dispatch_async!(self, geo_radius(key, lon, lat, radius, unit))
+    }
+
+    async fn geo_radius_by_member(
+        &self,
+        key: &Sds,
+        member: &Sds,
+        radius: f64,
+        unit: &str,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        dispatch_async!(self, geo_radius_by_member(key, member, radius, unit))
+    }
+
+    async fn geo_search(
+        &self,
+        key: &Sds,
+        params: &GeoSearchParams,
+    ) -> StoreResult<Vec<(String, f64, GeoPoint)>> {
+        dispatch_async!(self, geo_search(key, params))
+    }
+
+    async fn sadd(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, sadd(key, members))
+    }
+
+    async fn smembers(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Vec<Sds>> {
+        dispatch_async!(self, smembers(key))
+    }
+
+    async fn scard(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, scard(key))
+    }
+
+    async fn sismember(
+        &self,
+        key: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, sismember(key, member))
+    }
+
+    async fn smismember(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<bool>> {
+        dispatch_async!(self, smismember(key, members))
+    }
+
+    async fn srem(
+        &self,
+        key: &Sds,
+        members: &[Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, srem(key, members))
+    }
+
+    async fn srandmember(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        dispatch_async!(self, srandmember(key, count))
+    }
+
+    async fn spop(
+        &self,
+        key: &Sds,
+        count: isize,
+    ) -> StoreResult<Vec<Sds>> {
+        dispatch_async!(self, spop(key, count))
+    }
+
+    async fn smove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        member: &Sds,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, smove(src, dst, member))
+    }
+
+    async fn sintercard(
+        &self,
+        keys: &[&Sds],
+        limit: Option<usize>,
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, sintercard(keys, limit))
+    }
+
+    async fn sinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, sinterstore(dst, keys))
+    }
+
+    async fn sunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, sunionstore(dst, keys))
+    }
+
+    async fn sdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, sdiffstore(dst, keys))
+    }
+
+    async fn zunion(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        dispatch_async!(self, zunion(keys, options))
+    }
+
+    async fn zinter(
+        &self,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        dispatch_async!(self, zinter(keys, options))
+    }
+
+    async fn zdiff(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<Vec<(Sds, f64)>> {
+        dispatch_async!(self, zdiff(keys))
+    }
+
+    async fn zunionstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, zunionstore(dst, keys, options))
+    }
+
+    async fn zinterstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+        options: &ZCombineOptions,
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, zinterstore(dst, keys, options))
+    }
+
+    async fn zdiffstore(
+        &self,
+        dst: &Sds,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, zdiffstore(dst, keys))
+    }
+
+    async fn zmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ZPopDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, ZSetEntries)>> {
+        dispatch_async!(self, zmpop(keys, dir, count))
+    }
+
+    async fn zmscore(
+        &self,
+        key: &Sds,
+        members: &[&Sds],
+    ) -> StoreResult<Vec<Option<f64>>> {
+        dispatch_async!(self, zmscore(key, members))
+    }
+
+    async fn hrandfield(
+        &self,
+        key: &Sds,
+        count: isize,
+        with_values: bool,
+    ) -> StoreResult<Vec<Sds>> {
+        dispatch_async!(self, hrandfield(key, count, with_values))
+    }
+
+    async fn hincrby(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: i64,
+    ) -> StoreResult<i64> {
+        dispatch_async!(self, hincrby(key, field, delta))
+    }
+
+    async fn hincrbyfloat(
+        &self,
+        key: &Sds,
+        field: &Sds,
+        delta: f64,
+    ) -> StoreResult<f64> {
+        dispatch_async!(self, hincrbyfloat(key, field, delta))
+    }
+
+    async fn hmget(
+        &self,
+        key: &Sds,
+        fields: &[&Sds],
+    ) -> StoreResult<Vec<Option<Sds>>> {
+        dispatch_async!(self, hmget(key, fields))
+    }
+
+    async fn memory_usage(
+        &self,
+        key: &Sds,
+        samples: usize,
+    ) -> StoreResult<Option<usize>> {
+        dispatch_async!(self, memory_usage(key, samples))
+    }
+
+    async fn lmove(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        src_dir: ListDir,
+        dst_dir: ListDir,
+    ) -> StoreResult<Option<Sds>> {
+        dispatch_async!(self, lmove(src, dst, src_dir, dst_dir))
+    }
+
+    async fn lmpop(
+        &self,
+        keys: &[&Sds],
+        dir: ListDir,
+        count: usize,
+    ) -> StoreResult<Option<(Sds, Vec<Sds>)>> {
+        dispatch_async!(self, lmpop(keys, dir, count))
+    }
+
+    async fn lset(
+        &self,
+        key: &Sds,
+        index: isize,
+        value: Sds,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, lset(key, index, value))
+    }
+
+    async fn getdel(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<Sds>> {
+        dispatch_async!(self, getdel(key))
+    }
+
+    async fn getex(
+        &self,
+        key: &Sds,
+        expiry: GetExExpiry,
+    ) -> StoreResult<Option<Sds>> {
+        dispatch_async!(self, getex(key, expiry))
+    }
+
+    async fn copy(
+        &self,
+        src: &Sds,
+        dst: &Sds,
+        dst_db: Option<usize>,
+        replace: bool,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, copy(src, dst, dst_db, replace))
+    }
+
+    async fn expiretime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<i64>> {
+        dispatch_async!(self, expiretime(key))
+    }
+
+    async fn set_expiry(
+        &self,
+        key: &Sds,
+        ttl: Option<Duration>,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, set_expiry(key, ttl))
+    }
+
+    async fn type_of(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<ValueType>> {
+        dispatch_async!(self, type_of(key))
+    }
+
+    async fn touch(
+        &self,
+        keys: &[&Sds],
+    ) -> StoreResult<usize> {
+        dispatch_async!(self, touch(keys))
+    }
+
+    async fn idletime(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u64>> {
+        dispatch_async!(self, idletime(key))
+    }
+
+    async fn set_eviction_policy(
+        &self,
+        policy: EvictionPolicy,
+    ) -> StoreResult<()> {
+        dispatch_async!(self, set_eviction_policy(policy))
+    }
+
+    async fn freq(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<Option<u8>> {
+        dispatch_async!(self, freq(key))
+    }
+
+    async fn set_access_meta(
+        &self,
+        key: &Sds,
+        idle_secs: Option<u64>,
+        freq: Option<u8>,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, set_access_meta(key, idle_secs, freq))
+    }
+
+    async fn persist(
+        &self,
+        key: &Sds,
+    ) -> StoreResult<bool> {
+        dispatch_async!(self, persist(key))
+    }
+
+    async fn geosearchstore(
+        &self,
+        dst: &Sds,
+        src: &Sds,
+        params: GeoSearchParams,
+    ) -> StoreResult<i64> {
+        dispatch_async!(self, geosearchstore(dst, src, params))
+    }
+
+    async fn pfmerge(
+        &self,
+        dest: &Sds,
+        sources: &[&Sds],
+    ) -> StoreResult<()> {
+        dispatch_async!(self, pfmerge(dest, sources))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SmartHash, StoreError};
+
+    fn key(data: &str) -> Sds {
+        Sds::from(data.as_bytes())
+    }
+
+    /// Тест проверяет установку значения и последующее получение должно вернуть
+    /// то же значение.
+    #[test]
+    fn test_engine_set_and_get() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k = key("kin");
+        let v = Value::Str(Sds::from_str("dzadza"));
+        engine.set(&k, v.clone()).unwrap();
+        let got = engine.get(&k).unwrap();
+        assert_eq!(got, Some(v));
+    }
+
+    /// Тест проверяет получение по несуществующему ключу возвращает None.
+    #[test]
+    fn test_engine_get_nonexistent_key() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k = key("not_found");
+        let got = engine.get(&k).unwrap();
+        assert_eq!(got, None);
+    }
+
+    /// Тест проверяет удаление существующего ключа должно удалить ключ.
+    #[test]
+    fn test_engine_delete() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k = key("hello");
+        let v = Value::Str(Sds::from_str("world"));
+        engine.set(&k, v).unwrap();
+        engine.del(&k).unwrap();
+        let got = engine.get(&k).unwrap();
+        assert_eq!(got, None)
+    }
+
+    /// Тест проверяет удаление несуществующего ключа не должно вызывать ошибку.
+    #[test]
+    fn test_engine_delete_nonexistent_key() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k = key("ghost");
+        // Удаление не должно вызывать паники или ошибки.
+        let result = engine.del(&k);
+        assert!(result.is_ok());
+    }
+
+    /// Тест проверяет установка нескольких пар ключ-значение с помощью mset.
+    #[test]
+    fn test_engine_mset() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        // Текущие переменные, поэтому ссылки действительны до конца работы функции.
+        let k1 = key("kin1");
+        let k2 = key("kin2");
+        let v1 = Value::Str(Sds::from_str("dza1"));
+        let v2 = Value::Str(Sds::from_str("dza2"));
+        // Собирать Vec<(&Sds, значение)>
+        let entries = vec![(&k1, v1.clone()), (&k2, v2.clone())];
+        engine.mset(entries).unwrap();
+        // Проверяю, что должно было быть сделано
+        assert_eq!(engine.get(&k1).unwrap(), Some(v1));
+        assert_eq!(engine.get(&k2).unwrap(), Some(v2));
+    }
+
+    /// Тест проверяет получение нескольких значений с помощью mget возвращает
+    /// их в правильном порядке.
+    #[test]
+    fn test_engine_mget() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k1 = key("kin1");
+        let k2 = key("kin2");
+        let v1 = Value::Str(Sds::from_str("dza1"));
+        let v2 = Value::Str(Sds::from_str("dza2"));
+        engine.set(&k1, v1.clone()).unwrap();
+        engine.set(&k2, v2.clone()).unwrap();
+        let got = engine.mget(&[&k1, &k2]).unwrap();
+        assert_eq!(got, vec![Some(v1), Some(v2)]);
+    }
+
+    /// Тест проверяет успешное переименование ключа.
+    #[test]
+    fn test_engine_rename() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k1 = key("old_key");
+        let k2 = key("new_key");
+        let v = Value::Str(Sds::from_str("value"));
+        engine.set(&k1, v.clone()).unwrap();
+        engine.rename(&k1, &k2).unwrap();
+        let got = engine.get(&k2).unwrap();
+        assert_eq!(got, Some(v));
+    }
+
+    /// Тест проверяет попытку переименовать несуществующий ключ должна вернуть
+    /// ошибку.
+    #[test]
+    fn test_engine_rename_nonexistent_key() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k1 = key("old_key");
+        let k2 = key("new_key");
+        // При попытке переименовать несуществующий ключ должна быть возвращена ошибка.
+        let result = engine.rename(&k1, &k2);
+        assert!(result.is_err());
+    }
+
+    /// Тест проверяет renamenx переименовывает ключ только если новый ключ
+    /// отсутствует.
+    #[test]
+    fn test_engine_renamenx() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let k1 = key("old_key");
+        let k2 = key("new_key");
+        let v = Value::Str(Sds::from_str("value"));
+        engine.set(&k1, v.clone()).unwrap();
+        let result = engine.renamenx(&k1, &k2).unwrap();
+        assert!(result);
+        // Убедитесь, что старый ключ удален, а новый присутствует.
+        let got = engine.get(&k2).unwrap();
+        assert_eq!(got, Some(v));
+        // // Повторная попытка переименования должна завершиться неудачей, поскольку
+        // новый ключ уже существует.
+        let result = engine.renamenx(&k1, &k2).unwrap();
+        assert!(!result);
+    }
+
+    /// Тест проверяет, что `flushdb` очищает все данные из хранилища.
+    #[test]
+    fn test_engine_flushdb() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        engine
+            .set(&key("a"), Value::Str(Sds::from_str("x")))
+            .unwrap();
+        engine
+            .set(&key("b"), Value::Str(Sds::from_str("y")))
+            .unwrap();
+        engine.flushdb().unwrap();
+        let a = engine.get(&key("a")).unwrap();
+        let b = engine.get(&key("b")).unwrap();
+        assert_eq!(a, None);
+        assert_eq!(b, None);
+    }
+
+    /// Тест проверяет инициализацию движка с конфигурацией памяти.
+    #[test]
+    fn test_engine_initialize_memory() {
+        let config = StorageConfig {
+            storage_type: StorageType::Memory,
+        };
+        let engine = StorageEngine::initialize(&config);
+        assert!(engine.is_ok());
+    }
+
+    /// Тест проверяет, что `get_store` возвращает объект трейта, с которым
+    /// можно работать.
+    #[test]
+    fn test_engine_get_store() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let store = engine.get_store();
+        assert!(store.mget(&[]).is_ok());
+    }
+
+    /// Тест проверяет, что `get_store_mut` возвращает изменяемый объект трейта.
+    #[test]
+    fn test_engine_get_store_mut() {
+        let mut engine = StorageEngine::Memory(InMemoryStore::new());
+        let store_mut = engine.get_store_mut();
+        assert!(store_mut.set(&key("x"), Value::Int(42)).is_ok());
+        let got = store_mut.get(&key("x")).unwrap();
+        assert_eq!(got, Some(Value::Int(42)));
     }
 
     /// Тестирует geo_add и geo_pos: добавление точки и получение её координат.
@@ -546,9 +1678,25 @@ mod tests {
         let paris = key("paris");
 
         // Добавляем координаты.
-        let added = engine.geo_add(&paris_key, 2.3522, 48.8566, &paris).unwrap();
+        let added = engine
+            .geo_add(
+                &paris_key,
+                2.3522,
+                48.8566,
+                &paris,
+                GeoAddOptions::default(),
+            )
+            .unwrap();
         assert!(added, "Первое добавление должно вернуть true");
-        let added_again = engine.geo_add(&paris_key, 2.3522, 48.8566, &paris).unwrap();
+        let added_again = engine
+            .geo_add(
+                &paris_key,
+                2.3522,
+                48.8566,
+                &paris,
+                GeoAddOptions::default(),
+            )
+            .unwrap();
         assert!(
             !added_again,
             "Повторное добавление того же члена должно вернуть false"
@@ -568,8 +1716,12 @@ mod tests {
         let a = key("a"); // пусть это Париж
         let b = key("b"); // пусть это Берлин
 
-        engine.geo_add(&cities_key, 2.3522, 48.8566, &a).unwrap();
-        engine.geo_add(&cities_key, 13.4050, 52.5200, &b).unwrap();
+        engine
+            .geo_add(&cities_key, 2.3522, 48.8566, &a, GeoAddOptions::default())
+            .unwrap();
+        engine
+            .geo_add(&cities_key, 13.4050, 52.5200, &b, GeoAddOptions::default())
+            .unwrap();
 
         // Расстояние в километрах
         let d_km = engine.geo_dist(&cities_key, &a, &b, "km").unwrap().unwrap();
@@ -587,13 +1739,31 @@ mod tests {
         let landmarks_key = key("landmarks");
 
         engine
-            .geo_add(&landmarks_key, 0.0, 0.0, &key("center"))
+            .geo_add(
+                &landmarks_key,
+                0.0,
+                0.0,
+                &key("center"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         engine
-            .geo_add(&landmarks_key, 0.001, 0.001, &key("near"))
+            .geo_add(
+                &landmarks_key,
+                0.001,
+                0.001,
+                &key("near"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
         engine
-            .geo_add(&landmarks_key, 10.0, 10.0, &key("far"))
+            .geo_add(
+                &landmarks_key,
+                10.0,
+                10.0,
+                &key("far"),
+                GeoAddOptions::default(),
+            )
             .unwrap();
 
         // Ищем в радиусе 0.2 km (200 м)
@@ -606,4 +1776,56 @@ mod tests {
         assert!(names.contains(&"near".to_string()));
         assert!(!names.contains(&"far".to_string()));
     }
+
+    /// Тест проверяет HRANDFIELD через движок: различные поля, повторы и
+    /// ошибку типа.
+    #[test]
+    fn test_engine_hrandfield() {
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        let hash: SmartHash = [(key("a"), key("1")), (key("b"), key("2"))]
+            .into_iter()
+            .collect();
+        engine.set(&key("h"), Value::Hash(hash)).unwrap();
+
+        let mut fields = engine.hrandfield(&key("h"), 5, false).unwrap();
+        fields.sort();
+        assert_eq!(fields, vec![key("a"), key("b")]);
+
+        assert_eq!(engine.hrandfield(&key("h"), -4, true).unwrap().len(), 8);
+        assert!(engine
+            .hrandfield(&key("none"), 2, false)
+            .unwrap()
+            .is_empty());
+
+        engine.set(&key("s"), Value::Int(1)).unwrap();
+        assert!(matches!(
+            engine.hrandfield(&key("s"), 1, false),
+            Err(StoreError::InvalidType)
+        ));
+    }
+
+    /// Тест проверяет, что асинхронные операции через `StorageEngine`
+    /// доходят до движка и видны синхронному интерфейсу.
+    #[tokio::test]
+    async fn test_engine_async_storage() {
+        use crate::engine::AsyncStorage;
+
+        let engine = StorageEngine::Memory(InMemoryStore::new());
+        AsyncStorage::set(&engine, &key("a"), Value::Int(1))
+            .await
+            .unwrap();
+        AsyncStorage::sadd(&engine, &key("s"), &[key("x"), key("y")])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            AsyncStorage::get(&engine, &key("a")).await.unwrap(),
+            Some(Value::Int(1))
+        );
+        assert_eq!(AsyncStorage::scard(&engine, &key("s")).await.unwrap(), 2);
+        assert_eq!(AsyncStorage::dbsize(&engine).await.unwrap(), 2);
+        assert!(AsyncStorage::del(&engine, &key("a")).await.unwrap());
+        assert_eq!(engine.get(&key("a")).unwrap(), None);
+        assert!(AsyncStorage::save(&engine).await.is_err());
+    }
 }
@@ -7,12 +7,112 @@ use std::io;
 
 use zstd::stream::{decode_all, encode_all};
 
+use super::{
+    TAG_ARRAY, TAG_BITMAP, TAG_HASH, TAG_HLL_ENCODED, TAG_LIST, TAG_SET, TAG_SSTREAM, TAG_STR,
+    TAG_ZSET,
+};
+
 /// Минимальный размер в байтах, при котором стоит применять
 /// сжатие.
 /// Если длина блока данных меньше этой константы, сжатие не
 /// выполняется.
 const MIN_COMPRESSION_SIZE: usize = 64;
 
+/// Политика сжатия по типам значений для формата ZDB V4.
+///
+/// Хранится в заголовке дампа как битовая маска `u16`: бит с номером тега
+/// (`TAG_STR`, `TAG_HASH`, …) разрешает сжимать значения этого типа.
+/// Значения с неразрешённым тегом пишутся без сжатия независимо от размера.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionPolicy {
+    mask: u16,
+}
+
+impl CompressionPolicy {
+    /// Политика, запрещающая сжатие любых значений.
+    pub const fn none() -> Self {
+        Self { mask: 0 }
+    }
+
+    /// Политика, разрешающая сжатие любых значений (поведение V1-V3).
+    pub const fn all() -> Self {
+        Self { mask: u16::MAX }
+    }
+
+    /// Восстанавливает политику из маски, прочитанной из заголовка.
+    pub const fn from_mask(mask: u16) -> Self {
+        Self { mask }
+    }
+
+    /// Возвращает маску для записи в заголовок.
+    pub const fn mask(&self) -> u16 {
+        self.mask
+    }
+
+    /// Разрешает сжатие значений с тегом `tag`.
+    pub const fn with_tag(
+        self,
+        tag: u8,
+    ) -> Self {
+        match Self::bit(tag) {
+            Some(bit) => Self {
+                mask: self.mask | bit,
+            },
+            None => self,
+        }
+    }
+
+    /// Запрещает сжатие значений с тегом `tag`.
+    pub const fn without_tag(
+        self,
+        tag: u8,
+    ) -> Self {
+        match Self::bit(tag) {
+            Some(bit) => Self {
+                mask: self.mask & !bit,
+            },
+            None => self,
+        }
+    }
+
+    /// Проверяет, разрешено ли сжимать значения с тегом `tag`.
+    pub const fn compresses(
+        &self,
+        tag: u8,
+    ) -> bool {
+        match Self::bit(tag) {
+            Some(bit) => self.mask & bit != 0,
+            None => false,
+        }
+    }
+
+    /// Бит маски для тега; теги вне диапазона `0..16` не сжимаются.
+    const fn bit(tag: u8) -> Option<u16> {
+        if tag < 16 {
+            Some(1 << tag)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for CompressionPolicy {
+    /// Сжимаются строки и коллекции; числа, `bool` и `null` — нет: они
+    /// короче порога сжатия и только тратили бы время на проверку.
+    fn default() -> Self {
+        Self::none()
+            .with_tag(TAG_STR)
+            .with_tag(TAG_LIST)
+            .with_tag(TAG_HASH)
+            .with_tag(TAG_ZSET)
+            .with_tag(TAG_SET)
+            .with_tag(TAG_HLL_ENCODED)
+            .with_tag(TAG_SSTREAM)
+            .with_tag(TAG_ARRAY)
+            .with_tag(TAG_BITMAP)
+    }
+}
+
 /// Проверяет, нужно ли пытаться сжать блок данных заданного
 /// размера.
 ///
@@ -63,6 +163,7 @@ pub fn decompress_block(data: &[u8]) -> io::Result<Vec<u8>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::zdb::{TAG_EOF, TAG_INT};
 
     /// Тест проверяет пограничные условия функции `should_compress`:
     /// - возврат `false` для размеров ниже порога;
@@ -105,6 +206,24 @@ mod tests {
         assert_eq!(decompressed, data);
     }
 
+    /// Тест проверяет, что политика по умолчанию сжимает строки, но не
+    /// числа, и что маска переживает запись в заголовок.
+    #[test]
+    fn test_compression_policy_mask() {
+        let policy = CompressionPolicy::default();
+        assert!(policy.compresses(TAG_STR));
+        assert!(policy.compresses(TAG_HASH));
+        assert!(!policy.compresses(TAG_INT));
+        assert!(!policy.compresses(TAG_EOF));
+
+        let custom = policy.without_tag(TAG_STR);
+        assert!(!custom.compresses(TAG_STR));
+        assert_eq!(CompressionPolicy::from_mask(custom.mask()), custom);
+
+        assert!(!CompressionPolicy::none().compresses(TAG_STR));
+        assert!(CompressionPolicy::all().compresses(TAG_STR));
+    }
+
     /// Тест проверяет, что при передаче некорректных данных в
     /// `decompress_block` возвращается ошибка с типом `ErrorKind::Other`.
     #[test]
@@ -7,7 +7,7 @@
 //! данные.
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::File,
     io::{self, Read, Seek, SeekFrom},
 };
@@ -19,14 +19,17 @@ use zumic_error::{ensure, ResultExt, ZdbError, ZdbVersionError, ZumicResult};
 
 use super::{
     streaming::{CollectHandler, StreamingParser},
-    CompatibilityInfo, Crc32Read, FormatVersion, VersionUtils, FILE_MAGIC, TAG_ARRAY, TAG_BITMAP,
-    TAG_BOOL, TAG_COMPRESSED, TAG_EOF, TAG_FLOAT, TAG_HASH, TAG_HLL, TAG_INT, TAG_NULL, TAG_SET,
-    TAG_STR, TAG_ZSET,
+    CompatibilityInfo, Crc32Read, FormatVersion, VersionUtils, FILE_MAGIC, HLL_DENSE, HLL_SPARSE,
+    TAG_ARRAY, TAG_BITMAP, TAG_BOOL, TAG_COMPRESSED, TAG_EOF, TAG_FLOAT, TAG_HASH, TAG_HLL,
+    TAG_HLL_ENCODED, TAG_INT, TAG_LIST, TAG_NULL, TAG_SET, TAG_SSTREAM, TAG_STR, TAG_ZSET,
 };
 use crate::{
-    database::{Bitmap, HllDense, HllEncoding, MurmurHasher, SERIALIZATION_VERSION},
+    database::{
+        num_registers, Bitmap, HllDense, HllEncoding, HllSparse, MurmurHasher, DEFAULT_PRECISION,
+        SERIALIZATION_VERSION,
+    },
     engine::varint,
-    Dict, Hll, Sds, SkipList, SmartHash, Value,
+    Dict, Hll, PendingEntry, QuickList, Sds, SkipList, SmartHash, Stream, StreamId, Value,
 };
 
 const DENSE_SIZE: usize = 16 * 1024;
@@ -77,7 +80,7 @@ impl<R: Read> StreamReader<R> {
         let dump_version = FormatVersion::try_from(version_byte).map_err(|_| {
             ZdbError::Version(ZdbVersionError::UnsupportedVersion {
                 found: version_byte,
-                supported: vec![0, 1, 2, 3],
+                supported: vec![0, 1, 2, 3, 4],
                 offset: Some(start_pos + 3),
                 key: None,
             })
@@ -87,12 +90,20 @@ impl<R: Read> StreamReader<R> {
         let compatibility_info = VersionUtils::validate_compatibility(reader_version, dump_version)
             .map_err(ZdbError::from)?;
 
+        // V4: маска сжатия не нужна для чтения — TAG_COMPRESSED самоописуем
+        let mut bytes_read = 4;
+        if dump_version.has_type_compression() {
+            r.read_u16::<BigEndian>()
+                .context("Failed to read compression mask")?;
+            bytes_read += 2;
+        }
+
         Ok(Self {
             inner: r,
             version: dump_version,
             done: false,
             compatibility_info,
-            bytes_read: 4,
+            bytes_read,
         })
     }
 
@@ -257,8 +268,11 @@ pub fn read_value_with_version<R: Read>(
         TAG_ZSET => read_zset_value(r, version, key, offset),
         TAG_SET => read_set_value(r, version, key, offset),
         TAG_HLL => read_hll_value(r, version, key, offset),
+        TAG_HLL_ENCODED => read_hll_encoded_value(r, version, key, offset),
         TAG_ARRAY => read_array_value(r, version, key, offset),
+        TAG_LIST => read_list_value(r, version, key, offset),
         TAG_BITMAP => read_bitmap_value(r, version, key, offset),
+        TAG_SSTREAM => read_stream_value(r, version, key, offset),
         other => Err(ZdbError::InvalidTag {
             tag: other,
             offset: Some(offset),
@@ -274,8 +288,11 @@ pub fn read_value_with_version<R: Read>(
                 TAG_ZSET,
                 TAG_SET,
                 TAG_HLL,
+                TAG_HLL_ENCODED,
                 TAG_ARRAY,
+                TAG_LIST,
                 TAG_BITMAP,
+                TAG_SSTREAM,
             ],
         }
         .into()),
@@ -301,6 +318,19 @@ pub fn read_dump<R: Read>(r: &mut R) -> ZumicResult<Vec<(Sds, Value)>> {
 /// Для файлов: читает тело (file_len - 4) через Crc32Read, парсит, затем
 /// сверяет CRC.
 pub fn read_dump_streaming_file(path: &str) -> ZumicResult<Vec<(Sds, Value)>> {
+    read_dump_streaming_file_filtered(path, None)
+}
+
+/// То же, что [`read_dump_streaming_file`], но при заданном `keys` загружает
+/// только эти ключи (в порядке `keys`, отсутствующие пропускаются).
+///
+/// Если дамп V4 содержит индекс, записи читаются по смещениям из него без
+/// сканирования файла; CRC всего тела в этом случае не проверяется.
+/// Без индекса файл читается целиком, с проверкой CRC, и фильтруется.
+pub fn read_dump_streaming_file_filtered(
+    path: &str,
+    keys: Option<&[Sds]>,
+) -> ZumicResult<Vec<(Sds, Value)>> {
     let mut file = File::open(path).context("Failed ti open dump file")?;
     let file_size = file
         .metadata()
@@ -317,6 +347,23 @@ pub fn read_dump_streaming_file(path: &str) -> ZumicResult<Vec<(Sds, Value)>> {
 
     let body_len = file_size - 4;
 
+    if let Some(keys) = keys {
+        let mut parser =
+            StreamingParser::new(file.try_clone().context("Failed to clone file handle")?)?;
+        if let Some(index) = parser.load_index_at(body_len)? {
+            let mut items = Vec::with_capacity(keys.len());
+            for key in keys {
+                if let Some(value) = parser.load_key(&index, key)? {
+                    items.push((key.clone(), value));
+                }
+            }
+            return Ok(items);
+        }
+        // Клон делит позицию с `file`: возвращаемся к началу для полного чтения
+        file.seek(SeekFrom::Start(0))
+            .context("Failed to rewind dump file")?;
+    }
+
     // Создаём take поверх клона файла, чтобы не двигать основную позицию
     let take = file
         .try_clone()
@@ -348,7 +395,21 @@ pub fn read_dump_streaming_file(path: &str) -> ZumicResult<Vec<(Sds, Value)>> {
         }
     );
 
-    Ok(handler.into_items())
+    let items = handler.into_items();
+    match keys {
+        Some(keys) => {
+            let wanted: HashSet<&Sds> = keys.iter().collect();
+            let mut found: HashMap<Sds, Value> = items
+                .into_iter()
+                .filter(|(k, _)| wanted.contains(k))
+                .collect();
+            Ok(keys
+                .iter()
+                .filter_map(|k| found.remove(k).map(|v| (k.clone(), v)))
+                .collect())
+        }
+        None => Ok(items),
+    }
 }
 
 /// Читает дамп с явно указанной версией читателя.
@@ -424,7 +485,7 @@ pub fn read_dump_with_version<R: Read>(
         let dump_version = FormatVersion::try_from(version_byte).map_err(|_| {
             ZdbError::Version(ZdbVersionError::UnsupportedVersion {
                 found: version_byte,
-                supported: vec![0, 1, 2, 3],
+                supported: vec![0, 1, 2, 3, 4],
                 offset: Some(3),
                 key: None,
             })
@@ -433,6 +494,12 @@ pub fn read_dump_with_version<R: Read>(
         let _compat = VersionUtils::validate_compatibility(reader_version, dump_version)
             .map_err(ZdbError::from)?;
 
+        if dump_version.has_type_compression() {
+            cursor
+                .read_u16::<BigEndian>()
+                .context("Failed to read compression mask")?;
+        }
+
         // читаем count
         let count = read_length(&mut cursor, dump_version)?;
 
@@ -521,7 +588,7 @@ pub fn skip_value<R: Read>(
             skip_bytes(r, len)?;
             Ok(())
         }
-        TAG_ARRAY => {
+        TAG_ARRAY | TAG_LIST => {
             let count = read_length(r, version)?;
             for _ in 0..count {
                 skip_value(r, version)?;
@@ -555,7 +622,10 @@ pub fn skip_value<R: Read>(
                         r.read_f32::<BigEndian>()
                             .context("Failed to skip zset score (f32)")?;
                     }
-                    FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 => {
+                    FormatVersion::V1
+                    | FormatVersion::V2
+                    | FormatVersion::V3
+                    | FormatVersion::V4 => {
                         r.read_f64::<BigEndian>()
                             .context("Failed to skip zset score (f64)")?;
                     }
@@ -568,11 +638,28 @@ pub fn skip_value<R: Read>(
             skip_bytes(r, len)?;
             Ok(())
         }
+        TAG_HLL_ENCODED => {
+            let sub_tag = r.read_u8().context("Failed to skip HLL sub-tag")?;
+            if sub_tag == HLL_SPARSE {
+                let count = read_length(r, version)?;
+                for _ in 0..count {
+                    read_length(r, version)?;
+                    skip_bytes(r, 1)?;
+                }
+            } else {
+                let len = read_length(r, version)? as u64;
+                skip_bytes(r, len)?;
+            }
+            Ok(())
+        }
         TAG_BITMAP => {
             let len = read_length(r, version)? as u64;
             skip_bytes(r, len)?;
             Ok(())
         }
+        // Записи потока содержат вложенные значения, а за ними следуют
+        // группы: проще прочитать поток целиком.
+        TAG_SSTREAM => read_stream_value(r, version, None, 0).map(drop),
         other => Err(ZdbError::InvalidTag {
             tag: other,
             offset: None,
@@ -588,8 +675,11 @@ pub fn skip_value<R: Read>(
                 TAG_ZSET,
                 TAG_SET,
                 TAG_HLL,
+                TAG_HLL_ENCODED,
                 TAG_ARRAY,
+                TAG_LIST,
                 TAG_BITMAP,
+                TAG_SSTREAM,
             ],
         }
         .into()),
@@ -824,7 +914,7 @@ fn read_zset_value<R: Read>(
                 .read_f32::<BigEndian>()
                 .with_context(|| format!("Failed to read zset score (f32) at index {i}"))?
                 as f64,
-            FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 => r
+            FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 | FormatVersion::V4 => r
                 .read_f64::<BigEndian>()
                 .with_context(|| format!("Failed to read zset score (f64) at index {i}"))?,
         };
@@ -902,7 +992,7 @@ fn read_hll_value<R: Read>(
                 }
             );
         }
-        FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 => {
+        FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 | FormatVersion::V4 => {
             ensure!(
                 n <= DENSE_SIZE as u32 * 2,
                 ZdbError::SizeLimit {
@@ -937,6 +1027,78 @@ fn read_hll_value<R: Read>(
     Ok(Value::HyperLogLog(Box::new(hll)))
 }
 
+/// Читает HLL с подтегом представления (`TAG_HLL_ENCODED`).
+fn read_hll_encoded_value<R: Read>(
+    r: &mut R,
+    version: FormatVersion,
+    key: Option<&str>,
+    offset: u64,
+) -> ZumicResult<Value> {
+    let sub_tag = r.read_u8().context("Failed to read HLL sub-tag")?;
+    match sub_tag {
+        HLL_DENSE => read_hll_value(r, version, key, offset),
+        HLL_SPARSE => read_sparse_hll_value(r, version, key, offset),
+        other => Err(ZdbError::InvalidTag {
+            tag: other,
+            offset: Some(offset),
+            key: key.map(|s| s.to_string()),
+            valid_tags: vec![HLL_DENSE, HLL_SPARSE],
+        }
+        .into()),
+    }
+}
+
+/// Читает разреженный HLL: число пар и пары (индекс, значение).
+fn read_sparse_hll_value<R: Read>(
+    r: &mut R,
+    version: FormatVersion,
+    key: Option<&str>,
+    offset: u64,
+) -> ZumicResult<Value> {
+    let registers = num_registers(DEFAULT_PRECISION);
+    let count = read_length(r, version)?;
+    ensure!(
+        count as usize <= registers,
+        ZdbError::SizeLimit {
+            what: "HLL (sparse)".to_string(),
+            size: count as u64,
+            limit: registers as u64,
+            offset: Some(offset),
+            key: key.map(|s| s.to_string())
+        }
+    );
+
+    let mut sparse = HllSparse::new();
+    for _ in 0..count {
+        let index = read_length(r, version)? as usize;
+        let value = r.read_u8().context("Failed to read HLL register")?;
+        if index >= registers || value == 0 || value > 63 {
+            return Err(ZdbError::CorruptedData {
+                reason: format!("Invalid HLL register {index} = {value}"),
+                offset: Some(offset),
+                key: key.map(|s| s.to_string()),
+                expected: Some(format!("index < {registers}, value in 1..=63")),
+                got: None,
+            }
+            .into());
+        }
+        sparse.set_register(index, value);
+    }
+
+    let mut hll = Hll {
+        encoding: HllEncoding::Sparse(sparse),
+        version: SERIALIZATION_VERSION,
+        hasher: MurmurHasher::default(),
+    };
+    if let HllEncoding::Sparse(sparse) = &hll.encoding {
+        if sparse.should_convert_to_dense() {
+            hll.convert_to_dense();
+        }
+    }
+
+    Ok(Value::HyperLogLog(Box::new(hll)))
+}
+
 fn read_array_value<R: Read>(
     r: &mut R,
     version: FormatVersion,
@@ -967,6 +1129,49 @@ fn read_array_value<R: Read>(
     Ok(Value::Array(items))
 }
 
+fn read_list_value<R: Read>(
+    r: &mut R,
+    version: FormatVersion,
+    key: Option<&str>,
+    offset: u64,
+) -> ZumicResult<Value> {
+    let len = read_length(r, version)?;
+
+    ensure!(
+        len <= MAX_COLLECTION_SIZE,
+        ZdbError::SizeLimit {
+            what: "List".to_string(),
+            size: len as u64,
+            limit: MAX_COLLECTION_SIZE as u64,
+            offset: Some(offset),
+            key: key.map(|s| s.to_string())
+        }
+    );
+
+    let mut list = QuickList::new(64);
+
+    for i in 0..len {
+        // Элементы списка кодируются как строковые значения.
+        match read_value_with_version(r, version, key, offset)
+            .with_context(|| format!("Failed to read list element at index {i}"))?
+        {
+            Value::Str(item) => list.push_back(item),
+            _ => {
+                return Err(ZdbError::CorruptedData {
+                    reason: format!("List element at index {i} is not a string"),
+                    offset: Some(offset),
+                    key: key.map(|s| s.to_string()),
+                    expected: Some("string".to_string()),
+                    got: None,
+                }
+                .into())
+            }
+        }
+    }
+
+    Ok(Value::List(list))
+}
+
 fn read_bitmap_value<R: Read>(
     r: &mut R,
     version: FormatVersion,
@@ -988,7 +1193,7 @@ fn read_bitmap_value<R: Read>(
                 }
             );
         }
-        FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 => {
+        FormatVersion::V1 | FormatVersion::V2 | FormatVersion::V3 | FormatVersion::V4 => {
             ensure!(
                 byte_len <= MAX_BITMAP_SIZE,
                 ZdbError::SizeLimit {
@@ -1012,6 +1217,143 @@ fn read_bitmap_value<R: Read>(
     Ok(Value::Bitmap(bmp))
 }
 
+fn read_stream_value<R: Read>(
+    r: &mut R,
+    version: FormatVersion,
+    key: Option<&str>,
+    offset: u64,
+) -> ZumicResult<Value> {
+    let mut stream = Stream::new();
+
+    let len = read_stream_count(r, version, "Stream", key, offset)?;
+    for i in 0..len {
+        let id = read_stream_id(r)?;
+        let fields = read_stream_count(r, version, "Stream entry", key, offset)?;
+        let mut data = HashMap::with_capacity(fields as usize);
+        for _ in 0..fields {
+            let field = read_stream_name(r, version, key, offset)?;
+            let value = read_value_with_version(r, version, key, offset)
+                .with_context(|| format!("Failed to read stream entry value at index {i}"))?;
+            data.insert(field, value);
+        }
+        if stream.add_with_id(id, data).is_none() {
+            return Err(ZdbError::CorruptedData {
+                reason: format!("Stream entry ID {id} at index {i} is not increasing"),
+                offset: Some(offset),
+                key: key.map(|s| s.to_string()),
+                expected: None,
+                got: None,
+            }
+            .into());
+        }
+    }
+    stream.advance_last_id(read_stream_id(r)?);
+
+    let groups = read_stream_count(r, version, "Stream groups", key, offset)?;
+    for _ in 0..groups {
+        let name = read_stream_name(r, version, key, offset)?;
+        stream.create_group(&name, read_stream_id(r)?);
+        let group = stream.group_mut(&name).expect("group was just created");
+
+        let consumers = read_stream_count(r, version, "Stream consumers", key, offset)?;
+        for _ in 0..consumers {
+            let consumer = read_stream_name(r, version, key, offset)?;
+            let seen_time = r
+                .read_u64::<BigEndian>()
+                .context("Failed to read stream consumer seen time")?;
+            group.touch_consumer(&consumer, seen_time);
+        }
+
+        let pending = read_stream_count(r, version, "Stream PEL", key, offset)?;
+        for _ in 0..pending {
+            let id = read_stream_id(r)?;
+            let consumer = read_stream_name(r, version, key, offset)?;
+            let delivery_time = r
+                .read_u64::<BigEndian>()
+                .context("Failed to read pending entry delivery time")?;
+            let delivery_count = r
+                .read_u64::<BigEndian>()
+                .context("Failed to read pending entry delivery count")?;
+            group.pel_mut().insert(
+                id,
+                PendingEntry {
+                    consumer,
+                    delivery_time,
+                    delivery_count,
+                },
+            );
+        }
+    }
+
+    Ok(Value::SStream(stream))
+}
+
+/// Читает идентификатор записи потока.
+fn read_stream_id<R: Read>(r: &mut R) -> ZumicResult<StreamId> {
+    let ms_time = r
+        .read_u64::<BigEndian>()
+        .context("Failed to read stream ID ms_time")?;
+    let sequence = r
+        .read_u64::<BigEndian>()
+        .context("Failed to read stream ID sequence")?;
+    Ok(StreamId::new(ms_time, sequence))
+}
+
+/// Читает число элементов потока (`what`) с проверкой ограничения.
+fn read_stream_count<R: Read>(
+    r: &mut R,
+    version: FormatVersion,
+    what: &str,
+    key: Option<&str>,
+    offset: u64,
+) -> ZumicResult<u32> {
+    let n = read_length(r, version)?;
+    ensure!(
+        n <= MAX_COLLECTION_SIZE,
+        ZdbError::SizeLimit {
+            what: what.to_string(),
+            size: n as u64,
+            limit: MAX_COLLECTION_SIZE as u64,
+            offset: Some(offset),
+            key: key.map(|s| s.to_string())
+        }
+    );
+    Ok(n)
+}
+
+/// Читает строку потока: поле записи, имя группы или потребителя.
+fn read_stream_name<R: Read>(
+    r: &mut R,
+    version: FormatVersion,
+    key: Option<&str>,
+    offset: u64,
+) -> ZumicResult<String> {
+    let len = read_length(r, version)?;
+    ensure!(
+        len <= MAX_STRING_SIZE,
+        ZdbError::SizeLimit {
+            what: "Stream name".to_string(),
+            size: len as u64,
+            limit: MAX_STRING_SIZE as u64,
+            offset: Some(offset),
+            key: key.map(|s| s.to_string())
+        }
+    );
+    let mut buf = vec![0; len as usize];
+    r.read_exact(&mut buf)
+        .context("Failed to read stream name bytes")?;
+    String::from_utf8(buf).map_err(|_| {
+        ZdbError::CorruptedData {
+            reason: "Stream name is not valid UTF-8".to_string(),
+            offset: Some(offset),
+            key: key.map(|s| s.to_string()),
+            expected: Some("UTF-8".to_string()),
+            got: None,
+        }
+        .into()
+    })
+}
+
 // ============================================================================
 // Skip utilities - пропуск значений без десериализации
 // ============================================================================
@@ -1066,7 +1408,9 @@ mod tests {
     use std::io::Cursor;
 
     use super::*;
-    use crate::engine::{compress_block, write_dump, write_stream};
+    use crate::engine::{
+        compress_block, write_dump, write_stream, write_stream_versioned, write_value_no_compress,
+    };
 
     // Используем V1 для всех тестов, где мы вручную пишем 4-байтовые BE длины,
     // потому что V3 ожидает varint-encoding.
@@ -1390,6 +1734,96 @@ mod tests {
         }
     }
 
+    /// Тест проверяет, что разреженный HLL пишется с подтегом `HLL_SPARSE`,
+    /// занимает меньше плотного и читается обратно без изменений.
+    #[test]
+    fn test_hll_sparse_roundtrip() {
+        let mut hll = Hll::new();
+        for i in 0..100 {
+            hll.add(format!("e_{i}").as_bytes());
+        }
+        assert!(hll.is_sparse());
+        let value = Value::HyperLogLog(Box::new(hll.clone()));
+
+        let mut buf = Vec::new();
+        write_value_no_compress(&mut buf, &value).unwrap();
+        assert_eq!(&buf[..2], &[TAG_HLL_ENCODED, HLL_SPARSE]);
+        assert!(buf.len() < 1024);
+
+        let mut cursor = Cursor::new(buf);
+        let val = read_value_with_version(&mut cursor, FormatVersion::current(), None, 0).unwrap();
+        match val {
+            Value::HyperLogLog(decoded) => assert_eq!(*decoded, hll),
+            _ => panic!("Expected Value::HyperLogLog"),
+        }
+    }
+
+    /// Тест проверяет, что список со сжатыми внутренними узлами QuickList
+    /// пишется поэлементно и после чтения снова сжимается без потери данных.
+    #[test]
+    fn test_compressed_list_roundtrip() {
+        let list = QuickList::from_iter(
+            (0..1000).map(|i| Sds::from_str(&format!("item-{i:04}"))),
+            64,
+        );
+        assert!(list.compressed_nodes() > 0);
+        let value = Value::List(list.clone());
+
+        let mut buf = Vec::new();
+        write_value_no_compress(&mut buf, &value).unwrap();
+
+        let mut cursor = Cursor::new(buf);
+        let val = read_value_with_version(&mut cursor, FormatVersion::current(), None, 0).unwrap();
+        match val {
+            Value::List(decoded) => {
+                assert!(decoded.compressed_nodes() > 0);
+                assert_eq!(decoded, list);
+            }
+            _ => panic!("Expected Value::List"),
+        }
+    }
+
+    /// Тест проверяет, что плотный HLL пишется с подтегом `HLL_DENSE` и
+    /// сохраняет оценку мощности.
+    #[test]
+    fn test_hll_dense_roundtrip() {
+        let mut hll = Hll::new();
+        for i in 0..20_000 {
+            hll.add(format!("e_{i}").as_bytes());
+        }
+        assert!(!hll.is_sparse());
+        let value = Value::HyperLogLog(Box::new(hll.clone()));
+
+        let mut buf = Vec::new();
+        write_value_no_compress(&mut buf, &value).unwrap();
+        assert_eq!(&buf[..2], &[TAG_HLL_ENCODED, HLL_DENSE]);
+
+        let mut cursor = Cursor::new(buf);
+        let val = read_value_with_version(&mut cursor, FormatVersion::current(), None, 0).unwrap();
+        match val {
+            Value::HyperLogLog(decoded) => {
+                assert!(!decoded.is_sparse());
+                assert_eq!(decoded.estimate_cardinality(), hll.estimate_cardinality());
+            }
+            _ => panic!("Expected Value::HyperLogLog"),
+        }
+    }
+
+    /// Тест проверяет, что регистр разреженного HLL вне диапазона и
+    /// неизвестный подтег отклоняются.
+    #[test]
+    fn test_hll_encoded_rejects_invalid_data() {
+        let mut data = vec![TAG_HLL_ENCODED, HLL_SPARSE];
+        data.extend(&1u32.to_be_bytes());
+        data.extend(&(1u32 << 14).to_be_bytes());
+        data.push(1);
+        let mut cursor = Cursor::new(data);
+        assert!(read_value_with_version(&mut cursor, LEGACY, None, 0).is_err());
+
+        let mut cursor = Cursor::new(vec![TAG_HLL_ENCODED, 0x7F]);
+        assert!(read_value_with_version(&mut cursor, LEGACY, None, 0).is_err());
+    }
+
     /// Тест проверяет, что неизвестный тег вызывает ошибку `InvalidData` с
     /// сообщением "Unknown tag"
     #[test]
@@ -1452,6 +1886,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_list() {
+        let mut data = Vec::new();
+        data.push(TAG_LIST);
+        data.extend(&(2u32).to_be_bytes());
+        for s in [b"a", b"b"] {
+            data.push(TAG_STR);
+            data.extend(&(s.len() as u32).to_be_bytes());
+            data.extend(s);
+        }
+
+        let mut cursor = Cursor::new(data);
+        let val = read_value_with_version(&mut cursor, LEGACY, None, 0).unwrap();
+        match val {
+            Value::List(list) => assert_eq!(
+                list.iter().map(|s| s.into_owned()).collect::<Vec<_>>(),
+                vec![Sds::from_str("a"), Sds::from_str("b")]
+            ),
+            other => panic!("unexpected value {other:?}"),
+        }
+    }
+
     #[test]
     fn test_read_bitmap() {
         let mut data = Vec::new();
@@ -1588,4 +2044,39 @@ mod tests {
             );
         }
     }
+
+    /// Тест проверяет выборочную загрузку ключей из файла: по индексу для V4
+    /// и полным сканированием для V3.
+    #[test]
+    fn test_read_dump_streaming_file_filtered() {
+        let items = vec![
+            (Sds::from_str("a"), Value::Int(1)),
+            (Sds::from_str("b"), Value::Int(2)),
+            (Sds::from_str("c"), Value::Int(3)),
+        ];
+        let keys = [
+            Sds::from_str("c"),
+            Sds::from_str("missing"),
+            Sds::from_str("a"),
+        ];
+        let expected = vec![
+            (Sds::from_str("c"), Value::Int(3)),
+            (Sds::from_str("a"), Value::Int(1)),
+        ];
+
+        for version in [FormatVersion::V4, FormatVersion::V3] {
+            let mut buf = Vec::new();
+            write_stream_versioned(&mut buf, items.clone().into_iter(), version).unwrap();
+            let crc = crc32fast::hash(&buf);
+            buf.extend(&crc.to_be_bytes());
+
+            let file = tempfile::NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), &buf).unwrap();
+            let path = file.path().to_str().unwrap();
+
+            let got = read_dump_streaming_file_filtered(path, Some(&keys)).unwrap();
+            assert_eq!(got, expected);
+            assert_eq!(read_dump_streaming_file(path).unwrap(), items);
+        }
+    }
 }
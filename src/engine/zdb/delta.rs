@@ -0,0 +1,335 @@
+//! Дельта-снимки ZDB.
+//!
+//! Дельта содержит только ключи, изменённые после полного снимка (базы), и
+//! применяется поверх него при загрузке. Файл дельты лежит рядом с базой:
+//! `<base>.delta` (см. [`delta_path_for`]).
+//!
+//! Формат:
+//! `[DELTA_MAGIC][version: u8][base_crc: u32 BE][timestamp: u64 BE]`, затем
+//! записи `[key_len: varint][key][value | TAG_TOMBSTONE]`, `TAG_EOF` и CRC32
+//! всего предшествующего содержимого. `base_crc` — CRC32 файла базы: дельта к
+//! другой базе при загрузке отвергается.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use crc32fast::Hasher;
+use zumic_error::{ensure, ResultExt, ZdbError, ZumicResult};
+
+use super::{
+    read_value_with_version, varint, write_value_versioned, FormatVersion, TAG_EOF, TAG_TOMBSTONE,
+};
+use crate::{Sds, Value};
+
+/// Магическая подпись дельта-снимка.
+pub const DELTA_MAGIC: &[u8; 9] = b"ZDB_DELTA";
+
+/// Расширение файла дельты, добавляемое к пути базы.
+pub const DELTA_EXTENSION: &str = "delta";
+
+/// Заголовок дельта-снимка.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeltaHeader {
+    /// Версия формата значений
+    pub version: FormatVersion,
+    /// CRC32 файла базового снимка
+    pub base_crc: u32,
+    /// Время создания дельты (секунды UNIX)
+    pub timestamp: u64,
+}
+
+/// Запись дельта-снимка.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeltaEntry {
+    /// Ключ изменён или создан.
+    Set(Sds, Value),
+    /// Ключ удалён.
+    Delete(Sds),
+}
+
+/// Писатель дельта-снимка.
+///
+/// Заголовок пишется в [`DeltaWriter::new`], завершающие `TAG_EOF` и CRC — в
+/// [`DeltaWriter::finish`].
+pub struct DeltaWriter<W: Write> {
+    inner: W,
+    hasher: Hasher,
+    version: FormatVersion,
+}
+
+impl<W: Write> DeltaWriter<W> {
+    /// Создаёт писатель и записывает заголовок дельты к базе с CRC
+    /// `base_crc`.
+    pub fn new(
+        inner: W,
+        base_crc: u32,
+        timestamp: u64,
+    ) -> ZumicResult<Self> {
+        let mut writer = Self {
+            inner,
+            hasher: Hasher::new(),
+            version: FormatVersion::current(),
+        };
+
+        let mut header = Vec::with_capacity(DELTA_MAGIC.len() + 13);
+        header.extend_from_slice(DELTA_MAGIC);
+        header.push(writer.version as u8);
+        header.write_u32::<BigEndian>(base_crc)?;
+        header.write_u64::<BigEndian>(timestamp)?;
+        writer.write_hashed(&header)?;
+
+        Ok(writer)
+    }
+
+    /// Записывает новое значение ключа.
+    pub fn write_set(
+        &mut self,
+        key: &Sds,
+        value: &Value,
+    ) -> ZumicResult<()> {
+        let mut buf = Vec::new();
+        varint::write_varint(&mut buf, key.len() as u32)?;
+        buf.extend_from_slice(key.as_bytes());
+        write_value_versioned(&mut buf, value, self.version)?;
+        self.write_hashed(&buf)
+    }
+
+    /// Записывает удаление ключа.
+    pub fn write_delete(
+        &mut self,
+        key: &Sds,
+    ) -> ZumicResult<()> {
+        let mut buf = Vec::new();
+        varint::write_varint(&mut buf, key.len() as u32)?;
+        buf.extend_from_slice(key.as_bytes());
+        buf.push(TAG_TOMBSTONE);
+        self.write_hashed(&buf)
+    }
+
+    /// Записывает запись дельты.
+    pub fn write_entry(
+        &mut self,
+        entry: &DeltaEntry,
+    ) -> ZumicResult<()> {
+        match entry {
+            DeltaEntry::Set(key, value) => self.write_set(key, value),
+            DeltaEntry::Delete(key) => self.write_delete(key),
+        }
+    }
+
+    /// Завершает дельту маркером `TAG_EOF` и CRC32, возвращает `inner`.
+    pub fn finish(mut self) -> ZumicResult<W> {
+        self.write_hashed(&[TAG_EOF])?;
+        let crc = self.hasher.clone().finalize();
+        self.inner
+            .write_u32::<BigEndian>(crc)
+            .context("Failed to write delta CRC")?;
+        self.inner.flush().context("Failed to flush delta")?;
+        Ok(self.inner)
+    }
+
+    fn write_hashed(
+        &mut self,
+        buf: &[u8],
+    ) -> ZumicResult<()> {
+        self.hasher.update(buf);
+        self.inner.write_all(buf).context("Failed to write delta")?;
+        Ok(())
+    }
+}
+
+/// Читает дельта-снимок целиком, проверяя CRC.
+pub fn read_delta<R: Read>(r: &mut R) -> ZumicResult<(DeltaHeader, Vec<DeltaEntry>)> {
+    let mut data = Vec::new();
+    r.read_to_end(&mut data).context("Failed to read delta")?;
+
+    let min_len = DELTA_MAGIC.len() + 13 + 1 + 4;
+    ensure!(
+        data.len() >= min_len,
+        ZdbError::FileTooSmall {
+            size: data.len() as u64,
+            minimum: min_len as u64,
+        }
+    );
+
+    let (body, crc_bytes) = data.split_at(data.len() - 4);
+    let recorded = u32::from_be_bytes(crc_bytes.try_into().unwrap());
+    let computed = crc32fast::hash(body);
+    ensure!(
+        computed == recorded,
+        ZdbError::CrcMismatch {
+            computed,
+            recorded,
+            offset: Some(body.len() as u64),
+        }
+    );
+
+    ensure!(
+        body.starts_with(DELTA_MAGIC),
+        ZdbError::CorruptedData {
+            reason: "Invalid delta magic".to_string(),
+            offset: Some(0),
+            key: None,
+            expected: Some(String::from_utf8_lossy(DELTA_MAGIC).to_string()),
+            got: None,
+        }
+    );
+
+    let mut cursor = &body[DELTA_MAGIC.len()..];
+    let version = FormatVersion::try_from(cursor.read_u8().context("Failed to read version")?)
+        .map_err(ZdbError::from)?;
+    let header = DeltaHeader {
+        version,
+        base_crc: cursor
+            .read_u32::<BigEndian>()
+            .context("Failed to read base CRC")?,
+        timestamp: cursor
+            .read_u64::<BigEndian>()
+            .context("Failed to read timestamp")?,
+    };
+
+    // `TAG_EOF` ищется по позиции: первый байт varint-длины ключа тоже может
+    // быть равен 0xFF.
+    ensure!(
+        body.last() == Some(&TAG_EOF),
+        ZdbError::CorruptedData {
+            reason: "Delta is missing EOF marker".to_string(),
+            offset: Some(body.len() as u64 - 1),
+            key: None,
+            expected: Some(format!("{TAG_EOF:#04x}")),
+            got: body.last().map(|b| format!("{b:#04x}")),
+        }
+    );
+    let end = body.len() - 1;
+    let mut cursor = &cursor[..cursor.len() - 1];
+
+    let mut entries = Vec::new();
+    while !cursor.is_empty() {
+        let offset = (end - cursor.len()) as u64;
+        let key_len = varint::read_varint(&mut cursor)? as usize;
+        ensure!(
+            key_len <= cursor.len(),
+            ZdbError::UnexpectedEof {
+                context: "reading delta key".to_string(),
+                offset: Some(offset),
+                key: None,
+                expected_bytes: Some(key_len as u64),
+                got_bytes: Some(cursor.len() as u64),
+            }
+        );
+        let (key_bytes, rest) = cursor.split_at(key_len);
+        cursor = rest;
+        let key = Sds::from_vec(key_bytes.to_vec());
+
+        if cursor.first() == Some(&TAG_TOMBSTONE) {
+            cursor = &cursor[1..];
+            entries.push(DeltaEntry::Delete(key));
+            continue;
+        }
+
+        let key_str = String::from_utf8_lossy(key.as_bytes()).to_string();
+        let value_offset = (end - cursor.len()) as u64;
+        let value = read_value_with_version(&mut cursor, version, Some(&key_str), value_offset)?;
+        entries.push(DeltaEntry::Set(key, value));
+    }
+
+    Ok((header, entries))
+}
+
+/// Проверяет, начинается ли файл с [`DELTA_MAGIC`].
+pub fn is_delta_file(path: &Path) -> io::Result<bool> {
+    let mut magic = [0u8; DELTA_MAGIC.len()];
+    let mut file = File::open(path)?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == DELTA_MAGIC),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Вычисляет CRC32 файла снимка для поля `base_crc` дельты.
+pub fn snapshot_crc(path: &Path) -> io::Result<u32> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Hasher::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(hasher.finalize());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+/// Возвращает путь дельты для базового снимка: `<base>.delta`.
+pub fn delta_path_for(base: &Path) -> PathBuf {
+    let mut path = base.as_os_str().to_owned();
+    path.push(".");
+    path.push(DELTA_EXTENSION);
+    PathBuf::from(path)
+}
+
+/// Возвращает путь базового снимка для дельты `<base>.delta`.
+///
+/// # Возвращает
+/// - `None`, если у пути нет расширения [`DELTA_EXTENSION`]
+pub fn base_path_for(delta: &Path) -> Option<PathBuf> {
+    (delta.extension()? == DELTA_EXTENSION).then(|| delta.with_extension(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет, что записи и заголовок дельты переживают запись и
+    /// чтение, включая удаления.
+    #[test]
+    fn test_delta_roundtrip() {
+        let entries = vec![
+            DeltaEntry::Set(Sds::from_str("a"), Value::Int(1)),
+            DeltaEntry::Delete(Sds::from_str("b")),
+            DeltaEntry::Set(Sds::from_str("c"), Value::Str(Sds::from_str("x"))),
+        ];
+
+        let mut writer = DeltaWriter::new(Vec::new(), 0xDEAD_BEEF, 42).unwrap();
+        for entry in &entries {
+            writer.write_entry(entry).unwrap();
+        }
+        let buf = writer.finish().unwrap();
+        assert!(buf.starts_with(DELTA_MAGIC));
+
+        let (header, got) = read_delta(&mut &buf[..]).unwrap();
+        assert_eq!(header.base_crc, 0xDEAD_BEEF);
+        assert_eq!(header.timestamp, 42);
+        assert_eq!(header.version, FormatVersion::current());
+        assert_eq!(got, entries);
+    }
+
+    /// Тест проверяет, что повреждённая дельта отвергается по CRC.
+    #[test]
+    fn test_delta_crc_mismatch() {
+        let mut writer = DeltaWriter::new(Vec::new(), 1, 2).unwrap();
+        writer
+            .write_set(&Sds::from_str("k"), &Value::Int(7))
+            .unwrap();
+        let mut buf = writer.finish().unwrap();
+        let mid = buf.len() / 2;
+        buf[mid] ^= 0xFF;
+
+        assert!(read_delta(&mut &buf[..]).is_err());
+    }
+
+    /// Тест проверяет соответствие путей базы и дельты.
+    #[test]
+    fn test_delta_paths() {
+        let base = Path::new("/tmp/dump.zdb");
+        let delta = delta_path_for(base);
+        assert_eq!(delta, Path::new("/tmp/dump.zdb.delta"));
+        assert_eq!(base_path_for(&delta).as_deref(), Some(base));
+        assert_eq!(base_path_for(base), None);
+    }
+}
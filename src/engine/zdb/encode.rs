@@ -12,15 +12,12 @@ use crc32fast::Hasher;
 use zumic_error::{ResultExt, ZdbError, ZumicResult};
 
 use super::{
-    compress_block, should_compress, FormatVersion, FILE_MAGIC, TAG_ARRAY, TAG_BITMAP, TAG_BOOL,
-    TAG_COMPRESSED, TAG_EOF, TAG_FLOAT, TAG_HASH, TAG_HLL, TAG_INT, TAG_LIST, TAG_NULL, TAG_SET,
-    TAG_SSTREAM, TAG_STR, TAG_ZSET,
-};
-use crate::{
-    database::{HllDense, HllEncoding},
-    engine::varint,
-    Sds, Value,
+    compress_block, should_compress, CompressionPolicy, FormatVersion, ZdbIndex, FILE_MAGIC,
+    HLL_DENSE, HLL_SPARSE, TAG_ARRAY, TAG_BITMAP, TAG_BOOL, TAG_COMPRESSED, TAG_EOF, TAG_FLOAT,
+    TAG_HASH, TAG_HLL_ENCODED, TAG_INT, TAG_LIST, TAG_NULL, TAG_SET, TAG_SSTREAM, TAG_STR,
+    TAG_ZSET,
 };
+use crate::{database::HllEncoding, engine::varint, Sds, StreamId, Value};
 
 /// Сериализует значение с авто-сжатием (как в оригинальном коде).
 pub fn write_value<W: Write>(
@@ -35,11 +32,22 @@ pub fn write_value_versioned<W: Write>(
     w: &mut W,
     v: &Value,
     version: FormatVersion,
+) -> ZumicResult<()> {
+    write_value_with_policy(w, v, version, CompressionPolicy::all())
+}
+
+/// Сериализация значения с авто-сжатием только для типов, разрешённых
+/// `policy`.
+pub fn write_value_with_policy<W: Write>(
+    w: &mut W,
+    v: &Value,
+    version: FormatVersion,
+    policy: CompressionPolicy,
 ) -> ZumicResult<()> {
     let mut buf = Vec::new();
     write_value_inner(&mut buf, v, version)?;
 
-    if should_compress(buf.len()) {
+    if policy.compresses(buf[0]) && should_compress(buf.len()) {
         let compressed = compress_block(&buf).map_err(|e| ZdbError::CompressionError {
             operation: zumic_error::CompressionOp::Compress,
             reason: format!("zstd compression failed: {e}"),
@@ -103,7 +111,7 @@ pub fn write_value_inner<W: Write>(
             w.write_u8(TAG_LIST).context("Failed to write LIST tag")?;
             write_length(w, list.len() as u32, version)?;
             for item in list.iter() {
-                write_value_inner(w, &Value::Str(item.clone()), version)?;
+                write_value_inner(w, &Value::Str(item.into_owned()), version)?;
             }
             Ok(())
         }
@@ -155,44 +163,67 @@ pub fn write_value_inner<W: Write>(
             Ok(())
         }
         Value::HyperLogLog(hll) => {
-            w.write_u8(TAG_HLL).context("Failed to write HLL tag")?;
+            w.write_u8(TAG_HLL_ENCODED)
+                .context("Failed to write HLL tag")?;
 
             match &hll.encoding {
                 HllEncoding::Dense(dense) => {
+                    w.write_u8(HLL_DENSE)
+                        .context("Failed to write HLL sub-tag")?;
                     write_length(w, dense.data.len() as u32, version)?;
                     w.write_all(&dense.data)
                         .context("Failed to write HLL data")?;
                 }
                 HllEncoding::Sparse(sparse) => {
-                    // ВАЖНО: пока формат дампа ожидает dense, поэтому ЯВНО конвертируем sparse ->
-                    // dense
-                    let dense = HllDense::from_sparse(sparse);
-
-                    write_length(w, dense.data.len() as u32, version)?;
-                    w.write_all(&dense.data)
-                        .context("Failed to write converted dense HLL data")?;
+                    w.write_u8(HLL_SPARSE)
+                        .context("Failed to write HLL sub-tag")?;
+                    write_length(w, sparse.len() as u32, version)?;
+                    for (index, value) in sparse.iter() {
+                        write_length(w, index as u32, version)?;
+                        w.write_u8(value).context("Failed to write HLL register")?;
+                    }
                 }
             }
 
             Ok(())
         }
-        Value::SStream(entries) => {
+        Value::SStream(stream) => {
             w.write_u8(TAG_SSTREAM)
                 .context("Failed to write SSTREAM tag")?;
-            write_length(w, entries.len() as u32, version)?;
-            for entry in entries {
-                w.write_u64::<BigEndian>(entry.id.ms_time)
-                    .context("Failed to write stream entry ms_time")?;
-                w.write_u64::<BigEndian>(entry.id.sequence)
-                    .context("Failed to write stream entry sequence")?;
+            write_length(w, stream.len() as u32, version)?;
+            for entry in stream.iter() {
+                write_stream_id(w, entry.id)?;
                 write_length(w, entry.data.len() as u32, version)?;
                 for (field, val) in entry.data.iter() {
-                    let fb = field.as_bytes();
-                    write_length(w, fb.len() as u32, version)?;
-                    w.write_all(fb).context("Failed to write stream field")?;
+                    write_name(w, field, version)?;
                     write_value_inner(w, val, version)?;
                 }
             }
+
+            // Последний ID и группы потребителей: без них поток после
+            // удаления записей выдал бы уже использованные ID, а группы
+            // пропали бы.
+            write_stream_id(w, stream.last_id())?;
+            write_length(w, stream.groups().len() as u32, version)?;
+            for (name, group) in stream.groups() {
+                write_name(w, name, version)?;
+                write_stream_id(w, group.last_delivered_id)?;
+                write_length(w, group.consumers().len() as u32, version)?;
+                for (consumer, state) in group.consumers() {
+                    write_name(w, consumer, version)?;
+                    w.write_u64::<BigEndian>(state.seen_time)
+                        .context("Failed to write stream consumer seen time")?;
+                }
+                write_length(w, group.pel().len() as u32, version)?;
+                for (id, pending) in group.pel().iter() {
+                    write_stream_id(w, *id)?;
+                    write_name(w, &pending.consumer, version)?;
+                    w.write_u64::<BigEndian>(pending.delivery_time)
+                        .context("Failed to write pending entry delivery time")?;
+                    w.write_u64::<BigEndian>(pending.delivery_count)
+                        .context("Failed to write pending entry delivery count")?;
+                }
+            }
             Ok(())
         }
         Value::Bitmap(bm) => {
@@ -224,6 +255,7 @@ pub fn write_dump_versioned<W: Write>(
     let mut buf = Vec::new();
     buf.extend_from_slice(FILE_MAGIC);
     buf.push(version as u8);
+    write_compression_mask(&mut buf, version)?;
 
     let items: Vec<_> = kvs.collect();
     buf.reserve(items.len().saturating_mul(64));
@@ -279,6 +311,9 @@ where
     write_and_hash(FILE_MAGIC).context("Failed to write magic")?;
     let ver_byte = [version as u8];
     write_and_hash(&ver_byte).context("Failed to write version")?;
+    let mut mask_buf = Vec::new();
+    write_compression_mask(&mut mask_buf, version)?;
+    write_and_hash(&mask_buf).context("Failed to write compression mask")?;
 
     let count = kvs.len() as u32;
     let mut cnt_buf = Vec::new();
@@ -312,11 +347,18 @@ pub fn write_stream<W: Write>(
 }
 
 /// write_stream с явной версией.
+///
+/// Для V4 используется [`CompressionPolicy::default`] и дописывается индекс
+/// ключей, см. [`write_stream_with_policy`].
 pub fn write_stream_versioned<W: Write>(
     w: &mut W,
     kvs: impl Iterator<Item = (Sds, Value)>,
     version: FormatVersion,
 ) -> ZumicResult<()> {
+    if version.has_type_compression() {
+        return write_stream_with_policy(w, kvs, CompressionPolicy::default());
+    }
+
     w.write_all(FILE_MAGIC).context("Failed to write magic")?;
     w.write_u8(version as u8)
         .context("Failed to write version")?;
@@ -332,6 +374,80 @@ pub fn write_stream_versioned<W: Write>(
     Ok(())
 }
 
+/// Запись стрима в формате V4.
+///
+/// Формат: `[ZDB][4][mask: u16 BE]`, записи `[keylen, key, value]`, где
+/// значения сжимаются только для типов, разрешённых `policy`, затем
+/// `TAG_EOF`, индекс ключей и его футер (см. [`ZdbIndex`]). Читатели, не
+/// использующие индекс, останавливаются на `TAG_EOF`.
+pub fn write_stream_with_policy<W: Write>(
+    w: &mut W,
+    kvs: impl Iterator<Item = (Sds, Value)>,
+    policy: CompressionPolicy,
+) -> ZumicResult<()> {
+    let version = FormatVersion::V4;
+
+    w.write_all(FILE_MAGIC).context("Failed to write magic")?;
+    w.write_u8(version as u8)
+        .context("Failed to write version")?;
+    w.write_u16::<BigEndian>(policy.mask())
+        .context("Failed to write compression mask")?;
+    let mut offset = (FILE_MAGIC.len() + 3) as u64;
+
+    let mut index = ZdbIndex::new();
+    let mut record = Vec::new();
+    for (key, val) in kvs {
+        record.clear();
+        let kb = key.as_bytes();
+        write_length(&mut record, kb.len() as u32, version)?;
+        record.extend_from_slice(kb);
+        write_value_with_policy(&mut record, &val, version, policy)?;
+
+        w.write_all(&record).context("Failed to write record")?;
+        index.insert(key, offset);
+        offset += record.len() as u64;
+    }
+
+    w.write_u8(TAG_EOF).context("Failed to write EOF tag")?;
+    offset += 1;
+
+    index.write_to(w, offset)
+}
+
+/// Пишет маску сжатия по умолчанию, если версия хранит её в заголовке.
+fn write_compression_mask<W: Write>(
+    w: &mut W,
+    version: FormatVersion,
+) -> ZumicResult<()> {
+    if version.has_type_compression() {
+        w.write_u16::<BigEndian>(CompressionPolicy::default().mask())
+            .context("Failed to write compression mask")?;
+    }
+    Ok(())
+}
+
+/// Записывает идентификатор записи потока: время и sequence, u64 BigEndian.
+fn write_stream_id<W: Write>(
+    w: &mut W,
+    id: StreamId,
+) -> ZumicResult<()> {
+    w.write_u64::<BigEndian>(id.ms_time)
+        .context("Failed to write stream ID ms_time")?;
+    w.write_u64::<BigEndian>(id.sequence)
+        .context("Failed to write stream ID sequence")
+}
+
+/// Записывает строку потока (поле, группу или потребителя): длина и байты.
+fn write_name<W: Write>(
+    w: &mut W,
+    name: &str,
+    version: FormatVersion,
+) -> ZumicResult<()> {
+    write_length(w, name.len() as u32, version)?;
+    w.write_all(name.as_bytes())
+        .context("Failed to write stream name")
+}
+
 /// Записывает длину: u32 BigEndian (V1/V2) or varint (V3).
 #[inline]
 fn write_length<W: Write>(
@@ -355,8 +471,8 @@ mod tests {
     use super::*;
     use crate::{
         database::Bitmap,
-        engine::{decompress_block, read_dump, read_value_with_version, StreamReader},
-        Sds,
+        engine::{decompress_block, read_dump, read_value_with_version, skip_value, StreamReader},
+        Sds, Stream, StreamId,
     };
 
     /// Тест проверяет сериализацию и десериализацию целого числа.
@@ -577,6 +693,44 @@ mod tests {
         }
     }
 
+    /// Проверяет сериализацию потока вместе с последним ID и группами
+    /// потребителей, а также пропуск потока без десериализации.
+    #[test]
+    fn test_write_read_sstream() {
+        let mut stream = Stream::new();
+        for ms in 1..=3 {
+            let data = [("f".to_string(), Value::Int(ms as i64))].into();
+            stream.add_with_id(StreamId::new(ms, 0), data).unwrap();
+        }
+        stream.delete(&[StreamId::new(3, 0)]);
+        stream.create_group("g", StreamId::new(2, 0));
+        let group = stream.group_mut("g").unwrap();
+        group.touch_consumer("alice", 100);
+        group.pel_mut().deliver(StreamId::new(2, 0), "alice", 100);
+        let original = Value::SStream(stream);
+
+        for version in [FormatVersion::V1, FormatVersion::current()] {
+            let mut buf = Vec::new();
+            write_value_inner(&mut buf, &original, version).unwrap();
+            buf.push(TAG_NULL);
+
+            let decoded =
+                read_value_with_version(&mut Cursor::new(&buf), version, None, 0).unwrap();
+            assert_eq!(decoded, original);
+            let Value::SStream(decoded) = decoded else {
+                unreachable!()
+            };
+            assert_eq!(decoded.last_id(), StreamId::new(3, 0));
+
+            let mut cursor = Cursor::new(&buf);
+            skip_value(&mut cursor, version).unwrap();
+            assert_eq!(
+                read_value_with_version(&mut cursor, version, None, 0).unwrap(),
+                Value::Null
+            );
+        }
+    }
+
     /// Проверяет, что потоковая сериализация останавливается на TAG_EOF.
     #[test]
     fn test_write_stream_eof() {
@@ -588,4 +742,36 @@ mod tests {
         let mut reader = StreamReader::new(&buf[..]).unwrap();
         assert!(reader.next().is_none());
     }
+
+    /// Тест проверяет, что маска сжатия V4 отключает сжатие выбранного типа,
+    /// записывается в заголовок и не мешает чтению.
+    #[test]
+    fn test_write_stream_with_policy() {
+        let items = vec![(
+            Sds::from_str("k"),
+            Value::Str(Sds::from_vec(vec![b'a'; 4096])),
+        )];
+
+        let mut compressed = Vec::new();
+        write_stream_with_policy(
+            &mut compressed,
+            items.clone().into_iter(),
+            CompressionPolicy::default(),
+        )
+        .unwrap();
+        let mut plain = Vec::new();
+        let policy = CompressionPolicy::default().without_tag(TAG_STR);
+        write_stream_with_policy(&mut plain, items.clone().into_iter(), policy).unwrap();
+
+        assert!(compressed.len() < plain.len());
+        assert_eq!(u16::from_be_bytes([plain[4], plain[5]]), policy.mask());
+
+        for buf in [compressed, plain] {
+            let got: Vec<_> = StreamReader::new(&buf[..])
+                .unwrap()
+                .map(|res| res.unwrap())
+                .collect();
+            assert_eq!(got, items);
+        }
+    }
 }
@@ -0,0 +1,323 @@
+//! Шифрование дампов ZDB (AES-256-GCM).
+//!
+//! [`EncryptedZdbWriter`] и [`EncryptedZdbReader`] — прозрачные обёртки над
+//! `Write`/`Read`: сериализация пишет и читает обычный поток ZDB, не зная о
+//! шифровании.
+//!
+//! Формат зашифрованных данных: `[iv: 12 байт]`, затем фреймы
+//! `[flags: u8][len: u32 BE][ciphertext + tag]`. Каждый фрейм шифрует до
+//! [`ENCRYPTED_CHUNK_SIZE`] байт открытого текста. Nonce фрейма — случайный
+//! `iv`, у которого последние 8 байт XOR-ятся с порядковым номером фрейма:
+//! свежий `iv` на каждое сохранение и счётчик внутри него исключают повторное
+//! использование nonce. Заголовок фрейма входит в AAD, последний фрейм помечен
+//! флагом [`FRAME_LAST`], поэтому обрезка файла обнаруживается.
+//!
+//! Любая ошибка расшифровки (неверный ключ, подмена или усечение данных)
+//! возвращается как `io::ErrorKind::InvalidData`.
+
+use std::io::{self, Read, Write};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use rand::RngCore;
+
+/// Размер случайного IV (96 бит).
+pub const ENCRYPTION_IV_LEN: usize = 12;
+
+/// Максимальный размер открытого текста в одном фрейме.
+pub const ENCRYPTED_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Флаг последнего фрейма потока.
+pub const FRAME_LAST: u8 = 0x01;
+
+/// Размер тега аутентификации AES-GCM.
+const TAG_LEN: usize = 16;
+
+/// Размер заголовка фрейма: флаги и длина шифротекста.
+const FRAME_HEADER_LEN: usize = 5;
+
+/// Шифрующая обёртка над `Write`.
+///
+/// Данные буферизуются и шифруются фреймами. Поток обязательно завершается
+/// вызовом [`finish`](Self::finish) — без него в конце не будет последнего
+/// фрейма и чтение завершится ошибкой.
+pub struct EncryptedZdbWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+    iv: [u8; ENCRYPTION_IV_LEN],
+    counter: u64,
+    buf: Vec<u8>,
+}
+
+/// Расшифровывающая обёртка над `Read`, парная к [`EncryptedZdbWriter`].
+pub struct EncryptedZdbReader<R: Read> {
+    inner: R,
+    cipher: Aes256Gcm,
+    iv: [u8; ENCRYPTION_IV_LEN],
+    counter: u64,
+    plain: Vec<u8>,
+    pos: usize,
+    done: bool,
+    /// Сообщение первой ошибки расшифровки
+    error: Option<String>,
+}
+
+impl<W: Write> EncryptedZdbWriter<W> {
+    /// Создаёт писатель со случайным IV и сразу записывает IV в `inner`.
+    pub fn new(
+        mut inner: W,
+        key: &[u8; 32],
+    ) -> io::Result<Self> {
+        let mut iv = [0u8; ENCRYPTION_IV_LEN];
+        rand::thread_rng().fill_bytes(&mut iv);
+        inner.write_all(&iv)?;
+
+        Ok(Self {
+            inner,
+            cipher: Aes256Gcm::new(key.into()),
+            iv,
+            counter: 0,
+            buf: Vec::with_capacity(ENCRYPTED_CHUNK_SIZE),
+        })
+    }
+
+    /// Шифрует остаток буфера последним фреймом и возвращает `inner`.
+    pub fn finish(mut self) -> io::Result<W> {
+        let chunk = std::mem::take(&mut self.buf);
+        self.write_frame(&chunk, FRAME_LAST)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_frame(
+        &mut self,
+        chunk: &[u8],
+        flags: u8,
+    ) -> io::Result<()> {
+        let len = (chunk.len() + TAG_LEN) as u32;
+        let header = frame_header(flags, len);
+        let nonce = frame_nonce(&self.iv, self.counter);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: chunk,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| io::Error::other("ZDB encryption failed"))?;
+
+        self.counter += 1;
+        self.inner.write_all(&header)?;
+        self.inner.write_all(&ciphertext)
+    }
+}
+
+impl<W: Write> Write for EncryptedZdbWriter<W> {
+    fn write(
+        &mut self,
+        data: &[u8],
+    ) -> io::Result<usize> {
+        let n = data.len().min(ENCRYPTED_CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+
+        if self.buf.len() == ENCRYPTED_CHUNK_SIZE {
+            let chunk = std::mem::take(&mut self.buf);
+            self.write_frame(&chunk, 0)?;
+            self.buf = chunk;
+            self.buf.clear();
+        }
+        Ok(n)
+    }
+
+    /// Сбрасывает только `inner`: неполный фрейм остаётся в буфере до
+    /// заполнения или [`finish`](Self::finish).
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: Read> EncryptedZdbReader<R> {
+    /// Создаёт читатель, считывая IV из начала `inner`.
+    pub fn new(
+        mut inner: R,
+        key: &[u8; 32],
+    ) -> io::Result<Self> {
+        let mut iv = [0u8; ENCRYPTION_IV_LEN];
+        inner
+            .read_exact(&mut iv)
+            .map_err(|_| invalid_data("missing encryption IV"))?;
+
+        Ok(Self {
+            inner,
+            cipher: Aes256Gcm::new(key.into()),
+            iv,
+            counter: 0,
+            plain: Vec::new(),
+            pos: 0,
+            done: false,
+            error: None,
+        })
+    }
+
+    /// Возвращает ошибку расшифровки, если она произошла.
+    ///
+    /// Потребитель потока (например, парсер ZDB) может заменить её своей
+    /// ошибкой чтения; этот метод позволяет восстановить исходную причину.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take().map(|msg| invalid_data(&msg))
+    }
+
+    /// Читает и расшифровывает следующий фрейм в `self.plain`.
+    fn read_frame(&mut self) -> io::Result<()> {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        self.inner
+            .read_exact(&mut header)
+            .map_err(|_| invalid_data("encrypted dump is truncated"))?;
+
+        let flags = header[0];
+        let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if !(TAG_LEN..=ENCRYPTED_CHUNK_SIZE + TAG_LEN).contains(&len) {
+            return Err(invalid_data("invalid encrypted frame length"));
+        }
+
+        let mut ciphertext = vec![0u8; len];
+        self.inner
+            .read_exact(&mut ciphertext)
+            .map_err(|_| invalid_data("encrypted dump is truncated"))?;
+
+        let nonce = frame_nonce(&self.iv, self.counter);
+        self.plain = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| invalid_data("ZDB decryption failed (wrong key or tampered data)"))?;
+
+        self.pos = 0;
+        self.counter += 1;
+        self.done = flags & FRAME_LAST != 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for EncryptedZdbReader<R> {
+    fn read(
+        &mut self,
+        out: &mut [u8],
+    ) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            if self.done {
+                return Ok(0);
+            }
+            if let Err(e) = self.read_frame() {
+                self.error = Some(e.to_string());
+                return Err(e);
+            }
+        }
+
+        let n = out.len().min(self.plain.len() - self.pos);
+        out[..n].copy_from_slice(&self.plain[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn frame_header(
+    flags: u8,
+    len: u32,
+) -> [u8; FRAME_HEADER_LEN] {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    header[0] = flags;
+    header[1..].copy_from_slice(&len.to_be_bytes());
+    header
+}
+
+/// Nonce фрейма: IV, последние 8 байт которого XOR-ятся с номером фрейма.
+fn frame_nonce(
+    iv: &[u8; ENCRYPTION_IV_LEN],
+    counter: u64,
+) -> [u8; ENCRYPTION_IV_LEN] {
+    let mut nonce = *iv;
+    for (b, c) in nonce[4..].iter_mut().zip(counter.to_be_bytes()) {
+        *b ^= c;
+    }
+    nonce
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    fn encrypt(data: &[u8]) -> Vec<u8> {
+        let mut w = EncryptedZdbWriter::new(Vec::new(), &KEY).unwrap();
+        w.write_all(data).unwrap();
+        w.finish().unwrap()
+    }
+
+    /// Тест проверяет, что данные из нескольких фреймов переживают
+    /// шифрование и расшифровку, а шифротекст не содержит открытого текста.
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let data: Vec<u8> = (0..ENCRYPTED_CHUNK_SIZE * 2 + 123)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let encrypted = encrypt(&data);
+        assert_ne!(&encrypted[ENCRYPTION_IV_LEN..][..64], &data[..64]);
+
+        let mut out = Vec::new();
+        EncryptedZdbReader::new(&encrypted[..], &KEY)
+            .unwrap()
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, data);
+    }
+
+    /// Тест проверяет, что неверный ключ, подмена и усечение данных дают
+    /// `InvalidData`.
+    #[test]
+    fn test_decrypt_failures_are_invalid_data() {
+        let encrypted = encrypt(b"secret dump body");
+
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xFF;
+
+        let mut truncated = encrypt(&vec![1u8; ENCRYPTED_CHUNK_SIZE + 10]);
+        truncated.truncate(ENCRYPTION_IV_LEN + FRAME_HEADER_LEN + ENCRYPTED_CHUNK_SIZE + TAG_LEN);
+
+        let cases: [(&[u8], [u8; 32]); 3] =
+            [(&encrypted, [8u8; 32]), (&tampered, KEY), (&truncated, KEY)];
+        for (data, key) in cases {
+            let mut out = Vec::new();
+            let err = EncryptedZdbReader::new(data, &key)
+                .unwrap()
+                .read_to_end(&mut out)
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    /// Тест проверяет, что два сохранения одних данных используют разные IV.
+    #[test]
+    fn test_iv_differs_between_saves() {
+        let a = encrypt(b"same");
+        let b = encrypt(b"same");
+        assert_ne!(a[..ENCRYPTION_IV_LEN], b[..ENCRYPTION_IV_LEN]);
+        assert_ne!(a, b);
+    }
+}
@@ -14,7 +14,10 @@ pub enum FormatVersion {
     /// Версия 2 - с улучшенным сжатием и новыми типами данных
     V2 = 2,
     /// Версия 3 - с varint encoding для размеров (экономия 20-30%)
-    V3,
+    V3 = 3,
+    /// Версия 4 - маска сжатия по типам в заголовке и индекс ключей в конце
+    /// файла
+    V4 = 4,
 }
 
 #[derive(Debug, Clone)]
@@ -32,12 +35,18 @@ pub struct VersionUtils;
 impl FormatVersion {
     /// Возвращает текущую версию формата по умолчанию.
     pub const fn current() -> Self {
-        FormatVersion::V3
+        FormatVersion::V4
     }
 
     /// Проверяем, использует ли версия varint encoding для размеров.
     pub const fn uses_varint(&self) -> bool {
-        matches!(self, FormatVersion::V3)
+        matches!(self, FormatVersion::V3 | FormatVersion::V4)
+    }
+
+    /// Проверяет, хранит ли заголовок дампа маску сжатия по типам (и может ли
+    /// за `TAG_EOF` следовать индекс ключей).
+    pub const fn has_type_compression(&self) -> bool {
+        matches!(self, FormatVersion::V4)
     }
 
     /// Проверяет, может ли данная версия читать указанную версию.
@@ -51,12 +60,14 @@ impl FormatVersion {
             (Legacy, _) => false,
             (V1, Legacy) => true,
             (V1, V1) => true,
-            (V1, V2 | V3) => false,
+            (V1, V2 | V3 | V4) => false,
             (V2, Legacy) => true,
             (V2, V1) => true,
             (V2, V2) => true,
-            (V2, V3) => false,
+            (V2, V3 | V4) => false,
+            (V3, V4) => false,
             (V3, _) => true,
+            (V4, _) => true,
         }
     }
 
@@ -75,6 +86,7 @@ impl FormatVersion {
             FormatVersion::V1,
             FormatVersion::V2,
             FormatVersion::V3,
+            FormatVersion::V4,
         ]
     }
 
@@ -85,6 +97,7 @@ impl FormatVersion {
             FormatVersion::V1 => "Version 1 (basic versioning)",
             FormatVersion::V2 => "Version 2 (enhanced compression)",
             FormatVersion::V3 => "Version 3 (varint encoding, 20-30% smaller)",
+            FormatVersion::V4 => "Version 4 (per-type compression, key index)",
         }
     }
 
@@ -99,7 +112,8 @@ impl FormatVersion {
             FormatVersion::Legacy => Some(FormatVersion::V1),
             FormatVersion::V1 => Some(FormatVersion::V2),
             FormatVersion::V2 => Some(FormatVersion::V3),
-            FormatVersion::V3 => None,
+            FormatVersion::V3 => Some(FormatVersion::V4),
+            FormatVersion::V4 => None,
         }
     }
 }
@@ -114,6 +128,7 @@ impl std::fmt::Display for FormatVersion {
             FormatVersion::V1 => write!(f, "V1"),
             FormatVersion::V2 => write!(f, "V2"),
             FormatVersion::V3 => write!(f, "V3"),
+            FormatVersion::V4 => write!(f, "V4"),
         }
     }
 }
@@ -159,6 +174,13 @@ impl CompatibilityInfo {
             );
         }
 
+        if dump_version == FormatVersion::V4 && reader_version < FormatVersion::V4 {
+            warnings.push(
+                "V4 format stores a compression mask in the header. Older reader cannot parse this format."
+                    .to_string(),
+            );
+        }
+
         CompatibilityInfo {
             reader_version,
             dump_version,
@@ -250,6 +272,11 @@ impl VersionUtils {
             changes.push("Backward compatible reader".to_string());
         }
 
+        if from < V4 && to >= V4 {
+            changes.push("Per-type compression bitmask in header".to_string());
+            changes.push("Trailing key index for random-access loading".to_string());
+        }
+
         changes
     }
 }
@@ -263,6 +290,7 @@ impl TryFrom<u8> for FormatVersion {
             1 => Ok(FormatVersion::V1),
             2 => Ok(FormatVersion::V2),
             3 => Ok(FormatVersion::V3),
+            4 => Ok(FormatVersion::V4),
             other => Err(ZdbVersionError::UnsupportedVersion {
                 found: other,
                 supported: FormatVersion::supported_versions()
@@ -277,7 +305,7 @@ impl TryFrom<u8> for FormatVersion {
 }
 
 /// Текущая версия формата дампа, как число (для совместимости).
-pub const DUMP_VERSION: u8 = FormatVersion::V3 as u8;
+pub const DUMP_VERSION: u8 = FormatVersion::V4 as u8;
 
 #[cfg(test)]
 mod tests {
@@ -290,11 +318,12 @@ mod tests {
         assert!(FormatVersion::Legacy < FormatVersion::V1);
         assert!(FormatVersion::V1 < FormatVersion::V2);
         assert!(FormatVersion::V2 < FormatVersion::V3);
+        assert!(FormatVersion::V3 < FormatVersion::V4);
     }
 
     #[test]
     fn test_current_version() {
-        assert_eq!(FormatVersion::current(), FormatVersion::V3);
+        assert_eq!(FormatVersion::current(), FormatVersion::V4);
     }
 
     #[test]
@@ -303,6 +332,10 @@ mod tests {
         assert!(!FormatVersion::V1.uses_varint());
         assert!(!FormatVersion::V2.uses_varint());
         assert!(FormatVersion::V3.uses_varint());
+        assert!(FormatVersion::V4.uses_varint());
+
+        assert!(!FormatVersion::V3.has_type_compression());
+        assert!(FormatVersion::V4.has_type_compression());
     }
 
     #[test]
@@ -311,6 +344,7 @@ mod tests {
         assert_eq!(FormatVersion::try_from(1).unwrap(), FormatVersion::V1);
         assert_eq!(FormatVersion::try_from(2).unwrap(), FormatVersion::V2);
         assert_eq!(FormatVersion::try_from(3).unwrap(), FormatVersion::V3);
+        assert_eq!(FormatVersion::try_from(4).unwrap(), FormatVersion::V4);
 
         let err = FormatVersion::try_from(99).unwrap_err();
         assert!(matches!(
@@ -338,7 +372,11 @@ mod tests {
             FormatVersion::V2.recommended_upgrade(),
             Some(FormatVersion::V3)
         );
-        assert_eq!(FormatVersion::V3.recommended_upgrade(), None);
+        assert_eq!(
+            FormatVersion::V3.recommended_upgrade(),
+            Some(FormatVersion::V4)
+        );
+        assert_eq!(FormatVersion::V4.recommended_upgrade(), None);
     }
 
     #[test]
@@ -347,6 +385,9 @@ mod tests {
         assert!(FormatVersion::V3.can_read(FormatVersion::V1));
         assert!(FormatVersion::V3.can_read(FormatVersion::V2));
         assert!(FormatVersion::V3.can_read(FormatVersion::V3));
+        assert!(FormatVersion::V4.can_read(FormatVersion::V3));
+        assert!(FormatVersion::V4.can_read(FormatVersion::V4));
+        assert!(!FormatVersion::V3.can_read(FormatVersion::V4));
 
         assert!(!FormatVersion::V1.can_read(FormatVersion::V3));
         assert!(!FormatVersion::V2.can_read(FormatVersion::V3));
@@ -384,11 +425,16 @@ mod tests {
             FormatVersion::V3.description(),
             "Version 3 (varint encoding, 20-30% smaller)"
         );
+        assert_eq!(
+            FormatVersion::V4.description(),
+            "Version 4 (per-type compression, key index)"
+        );
 
         assert_eq!(format!("{}", FormatVersion::Legacy), "Legacy");
         assert_eq!(format!("{}", FormatVersion::V1), "V1");
         assert_eq!(format!("{}", FormatVersion::V2), "V2");
         assert_eq!(format!("{}", FormatVersion::V3), "V3");
+        assert_eq!(format!("{}", FormatVersion::V4), "V4");
     }
 
     #[test]
@@ -402,6 +448,9 @@ mod tests {
         let changes = VersionUtils::version_changes(FormatVersion::V2, FormatVersion::V3);
         assert!(changes.iter().any(|c| c.contains("Varint encoding")));
         assert!(changes.iter().any(|c| c.contains("20-30%")));
+
+        let changes = VersionUtils::version_changes(FormatVersion::V3, FormatVersion::V4);
+        assert!(changes.iter().any(|c| c.contains("key index")));
     }
 
     #[test]
@@ -426,11 +475,12 @@ mod tests {
     #[test]
     fn test_supported_versions() {
         let versions = FormatVersion::supported_versions();
-        assert_eq!(versions.len(), 4);
+        assert_eq!(versions.len(), 5);
         assert!(versions.contains(&FormatVersion::Legacy));
         assert!(versions.contains(&FormatVersion::V1));
         assert!(versions.contains(&FormatVersion::V2));
         assert!(versions.contains(&FormatVersion::V3));
+        assert!(versions.contains(&FormatVersion::V4));
     }
 
     #[test]
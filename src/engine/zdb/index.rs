@@ -0,0 +1,220 @@
+//! Индекс ключей дампа ZDB V4.
+//!
+//! Индекс пишется после `TAG_EOF` и позволяет загрузить отдельный ключ без
+//! чтения всего файла.
+//!
+//! Формат секции: `[count: varint]`, затем для каждого ключа
+//! `[key_len: varint][key][offset: u64 BE]`, где `offset` — смещение записи
+//! ключа от начала файла. Секция завершается футером
+//! `[index_offset: u64 BE][INDEX_MAGIC]`, по которому индекс находится с конца
+//! файла.
+
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use zumic_error::{ensure, ResultExt, ZdbError, ZumicResult};
+
+use super::varint;
+use crate::Sds;
+
+/// Магическая подпись футера индекса.
+pub const INDEX_MAGIC: &[u8; 4] = b"ZIDX";
+
+/// Размер футера индекса: смещение секции и [`INDEX_MAGIC`].
+pub const INDEX_FOOTER_LEN: u64 = 8 + INDEX_MAGIC.len() as u64;
+
+/// Таблица `ключ → смещение записи` для random-access загрузки.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZdbIndex {
+    offsets: HashMap<Sds, u64>,
+}
+
+impl ZdbIndex {
+    /// Создаёт пустой индекс.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Запоминает смещение записи ключа.
+    pub fn insert(
+        &mut self,
+        key: Sds,
+        offset: u64,
+    ) {
+        self.offsets.insert(key, offset);
+    }
+
+    /// Возвращает смещение записи ключа, если он есть в дампе.
+    pub fn get(
+        &self,
+        key: &Sds,
+    ) -> Option<u64> {
+        self.offsets.get(key).copied()
+    }
+
+    /// Возвращает количество ключей в индексе.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Проверяет, пуст ли индекс.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Возвращает итератор по парам `(ключ, смещение)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&Sds, u64)> {
+        self.offsets.iter().map(|(k, &o)| (k, o))
+    }
+
+    /// Возвращает размер секции индекса в байтах (без футера).
+    pub fn encoded_len(&self) -> u64 {
+        let entries: usize = self
+            .offsets
+            .keys()
+            .map(|k| varint::varint_size(k.len() as u32) + k.len() + 8)
+            .sum();
+        (varint::varint_size(self.offsets.len() as u32) + entries) as u64
+    }
+
+    /// Записывает секцию индекса и футер, указывающий на неё.
+    ///
+    /// Ключи пишутся в порядке смещений, чтобы одинаковые дампы давали
+    /// одинаковые файлы.
+    pub fn write_to<W: Write>(
+        &self,
+        w: &mut W,
+        index_offset: u64,
+    ) -> ZumicResult<()> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by_key(|&(_, offset)| offset);
+
+        varint::write_varint(w, entries.len() as u32)?;
+        for (key, offset) in entries {
+            varint::write_varint(w, key.len() as u32)?;
+            w.write_all(key.as_bytes())
+                .context("Failed to write index key")?;
+            w.write_u64::<BigEndian>(offset)
+                .context("Failed to write index offset")?;
+        }
+
+        w.write_u64::<BigEndian>(index_offset)
+            .context("Failed to write index footer")?;
+        w.write_all(INDEX_MAGIC)
+            .context("Failed to write index magic")?;
+        Ok(())
+    }
+
+    /// Читает секцию индекса (без футера) с текущей позиции.
+    pub fn read_from<R: Read>(r: &mut R) -> ZumicResult<Self> {
+        let count = varint::read_varint(r)?;
+        let mut offsets = HashMap::with_capacity(count.min(1 << 16) as usize);
+        for _ in 0..count {
+            let klen = varint::read_varint(r)? as usize;
+            let mut kb = vec![0u8; klen];
+            r.read_exact(&mut kb).map_err(|_| ZdbError::UnexpectedEof {
+                context: "reading index key".to_string(),
+                offset: None,
+                key: None,
+                expected_bytes: Some(klen as u64),
+                got_bytes: None,
+            })?;
+            let offset = r
+                .read_u64::<BigEndian>()
+                .context("Failed to read index offset")?;
+            offsets.insert(Sds::from_vec(kb), offset);
+        }
+        Ok(Self { offsets })
+    }
+
+    /// Читает футер, заканчивающийся на позиции `end`, и загружает индекс.
+    ///
+    /// # Возвращает
+    /// - `Ok(Some(index))` — индекс найден
+    /// - `Ok(None)` — футера нет (дамп без индекса)
+    /// - `Err` — футер есть, но указывает за пределы данных или секция
+    ///   повреждена
+    pub fn read_footer<R: Read + Seek>(
+        r: &mut R,
+        end: u64,
+    ) -> ZumicResult<Option<Self>> {
+        if end < INDEX_FOOTER_LEN {
+            return Ok(None);
+        }
+        let footer_start = end - INDEX_FOOTER_LEN;
+        r.seek(SeekFrom::Start(footer_start))
+            .context("Failed to seek to index footer")?;
+        let index_offset = r
+            .read_u64::<BigEndian>()
+            .context("Failed to read index footer")?;
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)
+            .context("Failed to read index magic")?;
+        if &magic != INDEX_MAGIC {
+            return Ok(None);
+        }
+
+        ensure!(
+            index_offset < footer_start,
+            ZdbError::CorruptedData {
+                reason: "Index offset points past the index footer".to_string(),
+                offset: Some(footer_start),
+                key: None,
+                expected: Some(format!("< {footer_start}")),
+                got: Some(index_offset.to_string()),
+            }
+        );
+
+        r.seek(SeekFrom::Start(index_offset))
+            .context("Failed to seek to index")?;
+        Self::read_from(r).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Тест проверяет, что индекс переживает запись и чтение через футер, а
+    /// `encoded_len` совпадает с размером секции.
+    #[test]
+    fn test_index_roundtrip_via_footer() {
+        let mut index = ZdbIndex::new();
+        index.insert(Sds::from_str("a"), 6);
+        index.insert(Sds::from_str("bb"), 20);
+
+        let mut buf = b"prefix".to_vec();
+        let index_offset = buf.len() as u64;
+        index.write_to(&mut buf, index_offset).unwrap();
+        assert_eq!(
+            buf.len() as u64,
+            index_offset + index.encoded_len() + INDEX_FOOTER_LEN
+        );
+
+        let end = buf.len() as u64;
+        let loaded = ZdbIndex::read_footer(&mut Cursor::new(buf), end)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.get(&Sds::from_str("bb")), Some(20));
+        assert_eq!(loaded.get(&Sds::from_str("c")), None);
+    }
+
+    /// Тест проверяет, что данные без футера не считаются индексом.
+    #[test]
+    fn test_read_footer_without_index() {
+        let data = vec![0u8; 32];
+        let end = data.len() as u64;
+        assert!(ZdbIndex::read_footer(&mut Cursor::new(data), end)
+            .unwrap()
+            .is_none());
+        assert!(ZdbIndex::read_footer(&mut Cursor::new(vec![1u8; 4]), 4)
+            .unwrap()
+            .is_none());
+    }
+}
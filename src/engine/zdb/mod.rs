@@ -43,16 +43,26 @@
 //! - [`streaming`] - SAX-style event-driven parser
 //! - [`encode`] — сериализация значений в бинарный формат
 //! - [`decode`] — десериализация из бинарного формата
+//! - [`delta`] — дельта-снимки поверх полного дампа
 //! - [`compression`] — сжатие и распаковка данных
+//! - [`crc64`] — контрольная сумма CRC-64/Jones
+//! - [`encryption`] — шифрование дампов AES-256-GCM
+//! - [`payload`] — полезная нагрузка `DUMP`/`RESTORE` для одного ключа
 //! - [`file`] — версионирование и форматы дампов
+//! - [`index`] — индекс ключей ZDB V4 для random-access загрузки
 //! - [`tags`] — константы тегов для типов данных
 //!
 //! Используется в хранилище для записи и восстановления данных на диске.
 
 pub mod compression;
+pub mod crc64;
 pub mod decode;
+pub mod delta;
 pub mod encode;
+pub mod encryption;
 pub mod file;
+pub mod index;
+pub mod payload;
 pub mod streaming;
 pub mod tags;
 pub mod varint;
@@ -60,9 +70,14 @@ pub mod varint;
 // Publicly re-export all error types and functions from the submodules to
 // simplify access from external code.
 pub use compression::*;
+pub use crc64::*;
 pub use decode::*;
+pub use delta::*;
 pub use encode::*;
+pub use encryption::*;
 pub use file::*;
+pub use index::*;
+pub use payload::*;
 pub use streaming::*;
 pub use tags::*;
 pub use varint::*;
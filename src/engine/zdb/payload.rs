@@ -0,0 +1,138 @@
+//! Полезная нагрузка `DUMP`/`RESTORE` — сериализованное значение одного
+//! ключа.
+//!
+//! Формат: `[версия: u16 BE][значение ZDB][CRC64: u64 BE]`. Значение
+//! кодируется [`write_value`], контрольная сумма CRC-64/Jones считается по
+//! версии и значению.
+
+use std::io::Cursor;
+
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+use zumic_error::{ensure, ResultExt, ZdbError, ZdbVersionError, ZumicResult};
+
+use super::{crc64, read_value_with_version, write_value, FormatVersion};
+use crate::Value;
+
+/// Размер префикса версии в байтах.
+const VERSION_LEN: usize = 2;
+/// Размер контрольной суммы в байтах.
+const CRC_LEN: usize = 8;
+
+/// Сериализует значение в полезную нагрузку `DUMP`.
+pub fn dump_payload(value: &Value) -> ZumicResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    buf.write_u16::<BigEndian>(FormatVersion::current() as u16)
+        .context("Failed to write payload version")?;
+    write_value(&mut buf, value)?;
+    let crc = crc64(0, &buf);
+    buf.write_u64::<BigEndian>(crc)
+        .context("Failed to write payload CRC")?;
+    Ok(buf)
+}
+
+/// Проверяет контрольную сумму и версию полезной нагрузки `DUMP` и
+/// восстанавливает значение.
+///
+/// # Ошибки
+/// - `FileTooSmall` — нагрузка короче версии и CRC
+/// - `CorruptedData` — CRC не совпадает или после значения остались байты
+/// - `Version` — версия формата не поддерживается
+pub fn restore_payload(payload: &[u8]) -> ZumicResult<Value> {
+    ensure!(
+        payload.len() > VERSION_LEN + CRC_LEN,
+        ZdbError::FileTooSmall {
+            size: payload.len() as u64,
+            minimum: (VERSION_LEN + CRC_LEN + 1) as u64,
+        }
+    );
+
+    let (body, crc_bytes) = payload.split_at(payload.len() - CRC_LEN);
+    let recorded = BigEndian::read_u64(crc_bytes);
+    let computed = crc64(0, body);
+    ensure!(
+        computed == recorded,
+        ZdbError::CorruptedData {
+            reason: "DUMP payload checksum mismatch".to_string(),
+            offset: Some(body.len() as u64),
+            key: None,
+            expected: Some(format!("{recorded:016x}")),
+            got: Some(format!("{computed:016x}")),
+        }
+    );
+
+    let raw_version = BigEndian::read_u16(&body[..VERSION_LEN]);
+    let version = u8::try_from(raw_version)
+        .ok()
+        .and_then(|v| FormatVersion::try_from(v).ok())
+        .filter(|v| FormatVersion::current().can_read(*v))
+        .ok_or_else(|| {
+            ZdbError::Version(ZdbVersionError::UnsupportedVersion {
+                found: raw_version.min(u8::MAX as u16) as u8,
+                supported: FormatVersion::supported_versions()
+                    .into_iter()
+                    .map(|v| v as u8)
+                    .collect(),
+                offset: Some(0),
+                key: None,
+            })
+        })?;
+
+    let mut cursor = Cursor::new(&body[VERSION_LEN..]);
+    let value = read_value_with_version(&mut cursor, version, None, VERSION_LEN as u64)?;
+    ensure!(
+        cursor.position() as usize == body.len() - VERSION_LEN,
+        ZdbError::CorruptedData {
+            reason: "Trailing bytes after DUMP payload value".to_string(),
+            offset: Some(VERSION_LEN as u64 + cursor.position()),
+            key: None,
+            expected: None,
+            got: None,
+        }
+    );
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Sds, SmartHash};
+
+    /// Тест проверяет, что значение переживает DUMP/RESTORE без изменений.
+    #[test]
+    fn test_payload_roundtrip() {
+        let mut hash = SmartHash::new();
+        hash.insert(Sds::from_str("f"), Sds::from_str("v"));
+        for value in [
+            Value::Str(Sds::from_str("hello")),
+            Value::Int(-42),
+            Value::Hash(hash),
+        ] {
+            let payload = dump_payload(&value).unwrap();
+            assert_eq!(
+                BigEndian::read_u16(&payload),
+                FormatVersion::current() as u16
+            );
+            assert_eq!(restore_payload(&payload).unwrap(), value);
+        }
+    }
+
+    /// Тест проверяет, что повреждённая, усечённая или чужой версии нагрузка
+    /// отклоняется.
+    #[test]
+    fn test_payload_rejects_corruption() {
+        let payload = dump_payload(&Value::Str(Sds::from_str("hello"))).unwrap();
+
+        let mut corrupted = payload.clone();
+        corrupted[VERSION_LEN + 1] ^= 0xff;
+        assert!(restore_payload(&corrupted).is_err());
+
+        assert!(restore_payload(&payload[..payload.len() - 1]).is_err());
+        assert!(restore_payload(&[0; VERSION_LEN + CRC_LEN]).is_err());
+
+        let mut body = payload[..payload.len() - CRC_LEN].to_vec();
+        body[1] = 0xee;
+        let crc = crc64(0, &body);
+        body.write_u64::<BigEndian>(crc).unwrap();
+        assert!(restore_payload(&body).is_err());
+    }
+}
@@ -10,13 +10,20 @@
 //! - `on_entry()` - каждая пара ключ-значение
 //! - `on_end()` - конец дампа
 //! - `on_error()` - ошибка парсинга (опционально recoverable)
+//!
+//! Для дампов V4 с индексом ключей парсер поверх `Read + Seek` умеет загружать
+//! отдельные ключи без сканирования файла: [`StreamingParser::load_index`] и
+//! [`StreamingParser::load_key`].
 
-use std::io::{self, BufReader, Read, Write};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 
-use byteorder::{BigEndian, WriteBytesExt};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use zumic_error::{ensure, ResultExt, StackError, ZdbError, ZumicResult};
 
-use super::{write_value, CompatibilityInfo, FormatVersion, VersionUtils, FILE_MAGIC, TAG_EOF};
+use super::{
+    write_value, CompatibilityInfo, CompressionPolicy, FormatVersion, VersionUtils, ZdbIndex,
+    FILE_MAGIC, INDEX_FOOTER_LEN, INDEX_MAGIC, TAG_EOF,
+};
 use crate::{
     engine::{read_value_with_version, varint},
     Sds, Value,
@@ -95,6 +102,10 @@ pub struct StreamingParser<R: Read> {
     version: Option<FormatVersion>,
     stats: ParseStats,
     reader_version: FormatVersion,
+    /// Маска сжатия из заголовка V4
+    compression_policy: Option<CompressionPolicy>,
+    /// Индекс ключей, прочитанный после `TAG_EOF` (V4)
+    index: Option<ZdbIndex>,
 }
 
 /// Handler для сбора всех записей в Vec (обратная совместимость).
@@ -206,6 +217,8 @@ impl<R: Read> StreamingParser<R> {
             version: None,
             stats: ParseStats::default(),
             reader_version,
+            compression_policy: None,
+            index: None,
         })
     }
 
@@ -255,7 +268,10 @@ impl<R: Read> StreamingParser<R> {
                     }
                 }
                 Ok(None) => {
-                    // EOF достигнут
+                    // EOF достигнут; в V4 за ним может идти индекс ключей
+                    if version.has_type_compression() {
+                        self.index = self.read_trailing_index()?;
+                    }
                     break;
                 }
                 Err(e) => {
@@ -305,6 +321,17 @@ impl<R: Read> StreamingParser<R> {
         self.version
     }
 
+    /// Возвращает маску сжатия по типам из заголовка (только V4).
+    pub fn compression_policy(&self) -> Option<CompressionPolicy> {
+        self.compression_policy
+    }
+
+    /// Возвращает индекс ключей, прочитанный в конце [`parse`](Self::parse)
+    /// (только V4 с индексом).
+    pub fn index(&self) -> Option<&ZdbIndex> {
+        self.index.as_ref()
+    }
+
     /// Потребляет парсер и возвращает внутренний BufReader<R>.
     pub fn into_inner(self) -> BufReader<R> {
         self.reader
@@ -349,9 +376,67 @@ impl<R: Read> StreamingParser<R> {
 
         let version = FormatVersion::try_from(version_bytes[0]).map_err(ZdbError::from)?;
 
+        if version.has_type_compression() {
+            let mask =
+                self.reader
+                    .read_u16::<BigEndian>()
+                    .map_err(|_| ZdbError::UnexpectedEof {
+                        context: "reading compression mask".to_string(),
+                        offset: Some(start_offset + 4),
+                        key: None,
+                        expected_bytes: Some(2),
+                        got_bytes: None,
+                    })?;
+            self.stats.bytes_read += 2;
+            self.compression_policy = Some(CompressionPolicy::from_mask(mask));
+        }
+
         Ok(version)
     }
 
+    /// Читает индекс ключей и футер, следующие за `TAG_EOF` в дампе V4.
+    ///
+    /// Дамп без индекса (данные закончились сразу после `TAG_EOF`) допустим.
+    /// Усечённый индекс или футер без [`INDEX_MAGIC`] — ошибка, так как
+    /// означает повреждение файла.
+    fn read_trailing_index(&mut self) -> ZumicResult<Option<ZdbIndex>> {
+        if self
+            .reader
+            .fill_buf()
+            .context("Failed to read index section")?
+            .is_empty()
+        {
+            return Ok(None);
+        }
+
+        let index = ZdbIndex::read_from(&mut self.reader)?;
+        let footer_offset = self.stats.bytes_read + index.encoded_len();
+
+        let mut footer = [0u8; INDEX_FOOTER_LEN as usize];
+        self.reader
+            .read_exact(&mut footer)
+            .map_err(|_| ZdbError::UnexpectedEof {
+                context: "reading index footer".to_string(),
+                offset: Some(footer_offset),
+                key: None,
+                expected_bytes: Some(INDEX_FOOTER_LEN),
+                got_bytes: None,
+            })?;
+        ensure!(
+            &footer[8..] == INDEX_MAGIC,
+            ZdbError::CorruptedData {
+                reason: "Invalid index footer".to_string(),
+                offset: Some(footer_offset),
+                key: None,
+                expected: Some(String::from_utf8_lossy(INDEX_MAGIC).to_string()),
+                got: Some(String::from_utf8_lossy(&footer[8..]).to_string()),
+            }
+        );
+
+        self.stats.bytes_read += index.encoded_len() + INDEX_FOOTER_LEN;
+        Ok(Some(index))
+    }
+
     fn read_next_entry(
         &mut self,
         version: FormatVersion,
@@ -502,6 +587,93 @@ impl<R: Read> StreamingParser<R> {
     }
 }
 
+impl<R: Read + Seek> StreamingParser<R> {
+    /// Загружает индекс ключей дампа V4 по футеру в конце данных.
+    ///
+    /// Вместе с [`load_key`](Self::load_key) заменяет [`parse`](Self::parse)
+    /// для загрузки отдельных ключей: оба метода сдвигают позицию чтения.
+    ///
+    /// # Возвращает
+    /// - `Ok(Some(index))` — индекс найден
+    /// - `Ok(None)` — дамп старше V4 или записан без индекса
+    pub fn load_index(&mut self) -> ZumicResult<Option<ZdbIndex>> {
+        let end = self
+            .reader
+            .seek(SeekFrom::End(0))
+            .context("Failed to seek to end of dump")?;
+        self.load_index_at(end)
+    }
+
+    /// То же, что [`load_index`](Self::load_index), но данные дампа
+    /// заканчиваются на `end` (например, перед завершающим CRC).
+    pub fn load_index_at(
+        &mut self,
+        end: u64,
+    ) -> ZumicResult<Option<ZdbIndex>> {
+        let version = self.ensure_header()?;
+        if !version.has_type_compression() {
+            return Ok(None);
+        }
+        ZdbIndex::read_footer(&mut self.reader, end)
+    }
+
+    /// Загружает значение одного ключа по смещению из `index`.
+    ///
+    /// # Возвращает
+    /// - `Ok(Some(value))` — ключ найден
+    /// - `Ok(None)` — ключа нет в индексе
+    /// - `Err` — запись по смещению повреждена или принадлежит другому ключу
+    pub fn load_key(
+        &mut self,
+        index: &ZdbIndex,
+        key: &Sds,
+    ) -> ZumicResult<Option<Value>> {
+        let Some(offset) = index.get(key) else {
+            return Ok(None);
+        };
+        let version = self.ensure_header()?;
+
+        self.reader
+            .seek(SeekFrom::Start(offset))
+            .context("Failed to seek to indexed entry")?;
+        self.stats.bytes_read = offset;
+
+        match self.read_next_entry(version, offset)? {
+            Some((found, value)) if &found == key => {
+                self.stats.records_parsed += 1;
+                Ok(Some(value))
+            }
+            _ => Err(ZdbError::CorruptedData {
+                reason: "Index entry does not point to its key".to_string(),
+                offset: Some(offset),
+                key: Some(String::from_utf8_lossy(key.as_bytes()).to_string()),
+                expected: None,
+                got: None,
+            }
+            .into()),
+        }
+    }
+
+    /// Читает заголовок с начала дампа, если он ещё не прочитан.
+    fn ensure_header(&mut self) -> ZumicResult<FormatVersion> {
+        if let Some(version) = self.version {
+            return Ok(version);
+        }
+
+        self.reader
+            .seek(SeekFrom::Start(0))
+            .context("Failed to seek to dump header")?;
+        self.stats.bytes_read = 0;
+        let version = self.read_and_validate_header()?;
+        VersionUtils::validate_compatibility(self.reader_version, version)
+            .map_err(ZdbError::from)?;
+
+        self.version = Some(version);
+        self.stats.version = Some(version);
+        Ok(version)
+    }
+}
+
 impl<R: Read> Crc32Read<R> {
     /// Создаёт новый Crc32Read.
     pub fn new(inner: R) -> Self {
@@ -666,6 +838,11 @@ where
                 self.writer
                     .write_all(&[version as u8])
                     .context("Failed to write version")?;
+                if version.has_type_compression() {
+                    self.writer
+                        .write_u16::<BigEndian>(CompressionPolicy::default().mask())
+                        .context("Failed to write compression mask")?;
+                }
             }
             ParseEvent::Entry { key, value } => {
                 // Применяем трансформацию
@@ -708,7 +885,7 @@ mod tests {
     use std::io::Cursor;
 
     use super::*;
-    use crate::engine::write_stream;
+    use crate::engine::{write_stream, write_stream_versioned};
 
     /// Тест проверяет, что CollectHandler собирает все записи из дампа в Vec.
     #[test]
@@ -851,4 +1028,51 @@ mod tests {
 
         assert_eq!(handler.items().len(), 0);
     }
+
+    /// Тест проверяет random-access загрузку ключей по индексу дампа V4 и
+    /// чтение индекса при полном разборе.
+    #[test]
+    fn test_load_key_by_index() {
+        let items = vec![
+            (Sds::from_str("a"), Value::Int(1)),
+            (Sds::from_str("b"), Value::Str(Sds::from_str("value"))),
+            (Sds::from_str("c"), Value::Bool(true)),
+        ];
+        let mut buf = Vec::new();
+        write_stream(&mut buf, items.clone().into_iter()).unwrap();
+
+        let mut parser = StreamingParser::new(Cursor::new(buf.clone())).unwrap();
+        let index = parser.load_index().unwrap().expect("V4 dump has index");
+        assert_eq!(index.len(), 3);
+        assert_eq!(
+            parser.load_key(&index, &Sds::from_str("b")).unwrap(),
+            Some(Value::Str(Sds::from_str("value")))
+        );
+        assert_eq!(
+            parser.load_key(&index, &Sds::from_str("a")).unwrap(),
+            Some(Value::Int(1))
+        );
+        assert_eq!(parser.load_key(&index, &Sds::from_str("zz")).unwrap(), None);
+
+        let mut parser = StreamingParser::new(Cursor::new(buf)).unwrap();
+        let mut handler = CollectHandler::new();
+        parser.parse(&mut handler).unwrap();
+        assert_eq!(handler.into_items(), items);
+        assert_eq!(parser.index(), Some(&index));
+        assert_eq!(
+            parser.compression_policy(),
+            Some(CompressionPolicy::default())
+        );
+    }
+
+    /// Тест проверяет, что у дампа V3 индекса нет.
+    #[test]
+    fn test_load_index_v3() {
+        let items = vec![(Sds::from_str("a"), Value::Int(1))];
+        let mut buf = Vec::new();
+        write_stream_versioned(&mut buf, items.into_iter(), FormatVersion::V3).unwrap();
+
+        let mut parser = StreamingParser::new(Cursor::new(buf)).unwrap();
+        assert!(parser.load_index().unwrap().is_none());
+    }
 }
@@ -21,7 +21,7 @@ pub const TAG_HASH: u8 = 0x06;
 pub const TAG_ZSET: u8 = 0x07;
 /// Множество (Set<Sds>)
 pub const TAG_SET: u8 = 0x08;
-/// HyperLogLog
+/// HyperLogLog в плотном представлении (формат до введения подтегов)
 pub const TAG_HLL: u8 = 0x09;
 /// Поток (Stream)
 pub const TAG_SSTREAM: u8 = 0x0A;
@@ -33,3 +33,14 @@ pub const TAG_EOF: u8 = 0xFF;
 pub const TAG_ARRAY: u8 = 0x0D;
 /// Битовый массив (`Value::Bitmap`)
 pub const TAG_BITMAP: u8 = 0x0E;
+/// HyperLogLog, за тегом которого следует подтег представления
+/// (`HLL_DENSE` или `HLL_SPARSE`)
+pub const TAG_HLL_ENCODED: u8 = 0x0F;
+/// Удалённый ключ в дельта-снимке (вместо значения)
+pub const TAG_TOMBSTONE: u8 = 0x10;
+
+/// Подтег `TAG_HLL_ENCODED`: длина и байты плотных регистров
+pub const HLL_DENSE: u8 = 0x00;
+/// Подтег `TAG_HLL_ENCODED`: число пар и пары (индекс, значение) ненулевых
+/// регистров по возрастанию индекса
+pub const HLL_SPARSE: u8 = 0x01;
@@ -1,8 +1,16 @@
-use std::{fs::File, io::BufWriter};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
 
-use zumic_error::{ResultExt, StatusCode, ZumicResult};
+use zumic_error::{ensure, ResultExt, StatusCode, ZdbError, ZumicResult};
 
-use super::{write_stream, CallbackHandler, InMemoryStore, Storage, StreamingParser};
+use super::{
+    base_path_for, is_delta_file, read_delta, snapshot_crc, write_stream, CallbackHandler,
+    DeltaEntry, EncryptedZdbReader, EncryptedZdbWriter, InMemoryStore, Storage, StreamingParser,
+    FILE_MAGIC,
+};
 
 // NOTE: ВРЕМЕННАЯ локальная обёртка — можно жить с ней до полной миграции
 // NOTE: ошибок
@@ -15,24 +23,125 @@ struct SimpleError(String);
 
 /// Сохраняет все ключи и значения из хранилища в файл ZDB.
 /// Ключи и значения записываются попарно: сначала ключ, затем значение.
+///
+/// При заданном `encryption_key` после [`FILE_MAGIC`] пишется поток,
+/// зашифрованный [`EncryptedZdbWriter`] (AES-256-GCM).
 pub fn save_to_zdb(
     store: &InMemoryStore,
     path: &str,
+    encryption_key: Option<[u8; 32]>,
 ) -> ZumicResult<()> {
     let file = File::create(path).context("Failed to create ZDB file")?;
     let mut writer = BufWriter::new(file);
     let items = store.iter().map(|(k, v)| (k.clone(), v.clone()));
-    write_stream(&mut writer, items)
+
+    let Some(key) = encryption_key else {
+        return write_stream(&mut writer, items);
+    };
+
+    writer
+        .write_all(FILE_MAGIC)
+        .context("Failed to write magic")?;
+    let mut encrypted =
+        EncryptedZdbWriter::new(writer, &key).context("Failed to start encrypted ZDB")?;
+    write_stream(&mut encrypted, items)?;
+    encrypted
+        .finish()
+        .context("Failed to finish encrypted ZDB")?;
+    Ok(())
 }
 
 /// Загружает ключи и значения из файла ZDB в указанное хранилище.
 /// Ожидается, что каждая пара состоит из строки-ключа и произвольного значения.
+///
+/// Файл, сохранённый с `encryption_key`, читается только с тем же ключом;
+/// неверный ключ или повреждённые данные дают ошибку `InvalidData`.
+///
+/// Если `path` — дельта-снимок (`<base>.delta`), сначала загружается базовый
+/// снимок `<base>` (с тем же `encryption_key`), затем поверх него
+/// применяется дельта.
 pub fn load_from_zdb(
     store: &mut InMemoryStore,
     path: &str,
+    encryption_key: Option<[u8; 32]>,
 ) -> ZumicResult<()> {
+    if is_delta_file(Path::new(path)).context("Failed to open ZDB file")? {
+        return load_delta(store, Path::new(path), encryption_key);
+    }
+
     let file = File::open(path).context("Failed to open ZDB file")?;
-    let mut parser = StreamingParser::new(file)?;
+    let Some(key) = encryption_key else {
+        return load_stream(store, file);
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut magic = [0u8; 3];
+    reader
+        .read_exact(&mut magic)
+        .context("Failed to read magic")?;
+    ensure!(
+        &magic == FILE_MAGIC,
+        ZdbError::InvalidMagic {
+            expected: *FILE_MAGIC,
+            got: magic,
+        }
+    );
+
+    let mut decrypted = EncryptedZdbReader::new(reader, &key).map_err(ZdbError::from)?;
+    let result = load_stream(store, &mut decrypted);
+    // Парсер подменяет ошибку чтения своей; причина — ошибка расшифровки
+    match decrypted.take_error() {
+        Some(e) => Err(ZdbError::from(e).into()),
+        None => result,
+    }
+}
+
+/// Загружает базовый снимок дельты и применяет дельту поверх него.
+fn load_delta(
+    store: &mut InMemoryStore,
+    delta_path: &Path,
+    encryption_key: Option<[u8; 32]>,
+) -> ZumicResult<()> {
+    let base = base_path_for(delta_path).ok_or_else(|| {
+        zumic_error::StackError::new(SimpleError(format!(
+            "delta file {} must be named <base>.delta",
+            delta_path.display()
+        )))
+    })?;
+
+    let file = File::open(delta_path).context("Failed to open delta file")?;
+    let (header, entries) = read_delta(&mut BufReader::new(file))?;
+
+    let base_crc = snapshot_crc(&base).context("Failed to read base snapshot")?;
+    ensure!(
+        base_crc == header.base_crc,
+        ZdbError::CrcMismatch {
+            computed: base_crc,
+            recorded: header.base_crc,
+            offset: None,
+        }
+    );
+
+    load_from_zdb(store, &base.to_string_lossy(), encryption_key)?;
+
+    for entry in entries {
+        let result = match entry {
+            DeltaEntry::Set(key, value) => store.set(&key, value),
+            DeltaEntry::Delete(key) => store.del(&key).map(|_| ()),
+        };
+        result.map_err(|e| {
+            zumic_error::StackError::new(SimpleError(format!("applying delta failed: {e}")))
+        })?;
+    }
+    Ok(())
+}
+
+/// Разбирает поток ZDB из `reader` в хранилище.
+fn load_stream<R: Read>(
+    store: &mut InMemoryStore,
+    reader: R,
+) -> ZumicResult<()> {
+    let mut parser = StreamingParser::new(reader)?;
 
     let mut handler = CallbackHandler::new(|key, value| {
         store.set(&key, value).map_err(|e| {
@@ -114,11 +223,11 @@ mod tests {
             .unwrap();
 
         // 2. Сохраняем его в файл
-        save_to_zdb(&store, &test_path).unwrap();
+        save_to_zdb(&store, &test_path, None).unwrap();
 
         // 3. Загружаем в другое хранилище
         let mut loaded = InMemoryStore::default();
-        load_from_zdb(&mut loaded, &test_path).unwrap();
+        load_from_zdb(&mut loaded, &test_path, None).unwrap();
 
         // 4. Проверяем, что количество ключей совпадает
         let store_count = store.iter().count();
@@ -152,10 +261,10 @@ mod tests {
         let test_path = format!("test_zdb_empty_{ts}.zdb");
 
         let store = InMemoryStore::default();
-        save_to_zdb(&store, &test_path).unwrap();
+        save_to_zdb(&store, &test_path, None).unwrap();
 
         let mut loaded = InMemoryStore::default();
-        load_from_zdb(&mut loaded, &test_path).unwrap();
+        load_from_zdb(&mut loaded, &test_path, None).unwrap();
 
         assert_eq!(store.iter().count(), 0);
         assert_eq!(loaded.iter().count(), 0);
@@ -180,10 +289,10 @@ mod tests {
             )
             .unwrap();
 
-        save_to_zdb(&store, &test_path).unwrap();
+        save_to_zdb(&store, &test_path, None).unwrap();
 
         let mut loaded = InMemoryStore::default();
-        load_from_zdb(&mut loaded, &test_path).unwrap();
+        load_from_zdb(&mut loaded, &test_path, None).unwrap();
 
         assert_eq!(store.iter().count(), loaded.iter().count());
         assert_eq!(
@@ -211,10 +320,10 @@ mod tests {
                 .unwrap();
         }
 
-        save_to_zdb(&store, &test_path).unwrap();
+        save_to_zdb(&store, &test_path, None).unwrap();
 
         let mut loaded = InMemoryStore::default();
-        load_from_zdb(&mut loaded, &test_path).unwrap();
+        load_from_zdb(&mut loaded, &test_path, None).unwrap();
 
         assert_eq!(store.iter().count(), loaded.iter().count());
         for i in 0..n {
@@ -239,7 +348,7 @@ mod tests {
         store
             .set(&crate::Sds::from_str("k"), crate::Value::Int(1))
             .unwrap();
-        save_to_zdb(&store, &test_path).unwrap();
+        save_to_zdb(&store, &test_path, None).unwrap();
 
         // truncate last byte(s)
         let meta = std::fs::metadata(&test_path).unwrap();
@@ -256,7 +365,7 @@ mod tests {
 
         // load should return Err (Unexpected EOF / InvalidData)
         let mut loaded = InMemoryStore::default();
-        let res = load_from_zdb(&mut loaded, &test_path);
+        let res = load_from_zdb(&mut loaded, &test_path, None);
         assert!(
             res.is_err(),
             "Expected error when loading truncated file, got Ok"
@@ -264,4 +373,47 @@ mod tests {
 
         let _ = std::fs::remove_file(&test_path);
     }
+
+    /// Тест проверяет, что зашифрованный дамп загружается тем же ключом, не
+    /// содержит значений в открытом виде и не читается без ключа или с
+    /// неверным ключом.
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let test_path = format!("test_zdb_encrypted_{ts}.zdb");
+        let key = [42u8; 32];
+
+        let store = InMemoryStore::default();
+        store
+            .set(
+                &Sds::from_str("secret"),
+                Value::Str(Sds::from_str("plaintext-value")),
+            )
+            .unwrap();
+        save_to_zdb(&store, &test_path, Some(key)).unwrap();
+
+        let raw = fs::read(&test_path).unwrap();
+        assert!(raw.starts_with(FILE_MAGIC));
+        assert!(!raw.windows(15).any(|w| w == b"plaintext-value"));
+
+        let mut loaded = InMemoryStore::default();
+        load_from_zdb(&mut loaded, &test_path, Some(key)).unwrap();
+        assert_eq!(
+            loaded.get(&Sds::from_str("secret")).unwrap(),
+            Some(Value::Str(Sds::from_str("plaintext-value")))
+        );
+
+        let mut wrong = InMemoryStore::default();
+        let err = load_from_zdb(&mut wrong, &test_path, Some([1u8; 32])).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::CorruptedData);
+        assert_eq!(wrong.iter().count(), 0);
+
+        let mut plain = InMemoryStore::default();
+        assert!(load_from_zdb(&mut plain, &test_path, None).is_err());
+
+        let _ = fs::remove_file(&test_path);
+    }
 }
@@ -16,8 +16,12 @@ pub enum StoreError {
     #[error("Serialization error: {0}")]
     Serde(#[from] serde_json::Error),
 
+    /// Текст ошибки Lua: `mlua::Error` не `Send`, а `StoreError` должен
+    /// пересекать границы потоков вместе с futures [`AsyncStorage`].
+    ///
+    /// [`AsyncStorage`]: crate::engine::AsyncStorage
     #[error("Lua VM error: {0}")]
-    Lua(#[from] mlua::Error),
+    Lua(String),
 
     // ==== Ошибки команды ====
     #[error("Invalid command: {0}")]
@@ -47,12 +51,19 @@ pub enum StoreError {
     #[error("Invalid value")]
     InvalidValue,
 
-    #[error("Overflow value")]
+    #[error("overflow")]
     Overflow,
 
     #[error("Operation not implemented: {0}")]
     NotImplemented(String),
 
+    // ==== Скрипты ====
+    #[error("NOSCRIPT No matching script. Please use EVAL.")]
+    NoScript,
+
+    #[error("{0}")]
+    Script(String),
+
     // ==== Сеть и кластер ====
     #[error("Connection error: {0}")]
     Connection(String),
@@ -98,3 +109,9 @@ pub enum StoreError {
     #[error("Unsupported operation: {0}")]
     UnsupportedOperation(String),
 }
+
+impl From<mlua::Error> for StoreError {
+    fn from(err: mlua::Error) -> Self {
+        StoreError::Lua(err.to_string())
+    }
+}
@@ -11,6 +11,7 @@
 //!   Cluster)
 //! - `error` — типы ошибок (кодирование/декодирование, парсинг, хранение)
 //! - `logging` — гибкая система логирования (форматирование, фильтры, вывод)
+//! - `metrics` — счётчики сервера и экспорт в формате Prometheus
 //! - `network` — работа с сетью: протокол ZSP и сервер на базе Tokio
 //! - `pubsub` — Pub/Sub: брокер, подписки, сообщения
 
@@ -34,6 +35,8 @@ pub mod engine;
 pub mod error;
 /// Гибкая система логирования (форматирование, фильтры, вывод).
 pub mod logging;
+/// Счётчики сервера и экспорт в формате Prometheus.
+pub mod metrics;
 /// Модули расширения: API для загрузки, управления и интеграции плагинов.
 pub mod modules;
 /// Работа с сетью: протокол ZSP и сервер на Tokio.
@@ -47,48 +50,79 @@ pub mod pubsub;
 
 /// Реэкспорт основных структур и функций для работы с ACL и аутентификацией.
 pub use auth::{
-    hash_password, verify_password, Acl, AclRule, AclUser, AuthManager, CmdCategory, ServerConfig,
-    UserConfig,
+    category_commands, command_category, hash_password, verify_password, Acl, AclDenyReason,
+    AclLog, AclLogEntry, AclRule, AclUser, AuthManager, CmdCategory, ServerConfig, UserConfig,
+    CATEGORY_NAMES, DEFAULT_ACL_LOG_GET_COUNT,
 };
 /// Реэкспорт основных команд key-value.
 pub use command::{
-    AclDelUserCommand, AclGetUserCommand, AclSetUserCommand, AppendCommand, AuthCommand,
-    BgSaveCommand, BitCountCommand, BitOpCommand, BitPosCommand, Command as StoreCommand,
-    CommandExecute, CommandExecutor, DbSizeCommand, DecrByCommand, DecrByFloatCommand, DecrCommand,
-    DelCommand, EchoCommand, ExistsCommand, FlushDbCommand, GeoAddCommand, GeoPosCommand,
-    GeoRadiusByMemberCommand, GeoRadiusCommand, GetBitCommand, GetCommand, GetDistCommand,
-    GetRangeCommand, HDelCommand, HExistsCommand, HGetAllCommand, HGetCommand, HIncrByCommand,
-    HIncrByFloatCommand, HKeysCommand, HLenCommand, HRandFieldCommand, HSetCommand, HValsCommand,
-    HmGetCommand, IncrByCommand, IncrByFloatCommand, IncrCommand, InfoCommand, LLenCommand,
-    LPopCommand, LPushCommand, LRangeCommand, LRemCommand, LSetCommand, MGetCommand, MSetCommand,
-    PfAddCommand, PfCountCommand, PfMergeCommand, PingCommand, RPopCommand, RPushCommand,
-    RenameCommand, RenameNxCommand, SAddCommand, SCardCommand, SDiffCommand, SInterCommand,
-    SIsMemberCommand, SMembersCommand, SPopCommand, SRandMemberCommand, SRemCommand, SUnionCommand,
+    AclCatCommand, AclDelUserCommand, AclGetUserCommand, AclLoadCommand, AclLogCommand,
+    AclLogResetCommand, AclSaveCommand, AclSetUserCommand, AclWhoAmICommand, AppendCommand,
+    AuthCommand, BgSaveCommand, BitCountCommand, BitFieldCommand, BitFieldRoCommand, BitOpCommand,
+    BitPosCommand, ClientKillCommand, ClusterFailoverCommand, ClusterInfoCommand,
+    ClusterMyIdCommand, ClusterNodesCommand, ClusterSetSlotCommand, Command as StoreCommand,
+    CommandExecute, CommandExecutor, CopyCommand, DbSizeCommand, DecrByCommand, DecrByFloatCommand,
+    DecrCommand, DelCommand, DumpCommand, EchoCommand, EvalCommand, EvalShaCommand, ExistsCommand,
+    ExpireTimeCommand, FCallCommand, FCallRoCommand, FlushAllCommand, FlushDbCommand,
+    FunctionDeleteCommand, FunctionDumpCommand, FunctionListCommand, FunctionLoadCommand,
+    FunctionRestoreCommand, GeoAddCommand, GeoPosCommand, GeoRadiusByMemberCommand,
+    GeoRadiusCommand, GeoSearchCommand, GeoSearchStoreCommand, GetBitCommand, GetCommand,
+    GetDelCommand, GetDistCommand, GetExCommand, GetRangeCommand, HDelCommand, HExistsCommand,
+    HGetAllCommand, HGetCommand, HIncrByCommand, HIncrByFloatCommand, HKeysCommand, HLenCommand,
+    HRandFieldCommand, HScanCommand, HSetCommand, HValsCommand, HmGetCommand, IncrByCommand,
+    IncrByFloatCommand, IncrCommand, InfoCommand, KeysCommand, LIndexCommand, LInsertCommand,
+    LLenCommand, LMoveCommand, LPopCommand, LPosCommand, LPushCommand, LRangeCommand, LRemCommand,
+    LSetCommand, LTrimCommand, LmPopCommand, MGetCommand, MSetCommand, MemoryUsageCommand,
+    MonitorCommand, ObjectEncodingCommand, ObjectFreqCommand, ObjectIdleTimeCommand,
+    PExpireTimeCommand, PTtlCommand, PersistCommand, PfAddCommand, PfCountCommand, PfMergeCommand,
+    PingCommand, RPopCommand, RPushCommand, RenameCommand, RenameNxCommand, ResetCommand,
+    RestoreCommand, SAddCommand, SCardCommand, SDiffCommand, SDiffStoreCommand, SInterCardCommand,
+    SInterCommand, SInterStoreCommand, SIsMemberCommand, SMembersCommand, SMoveCommand,
+    SPopCommand, SRandMemberCommand, SRemCommand, SScanCommand, SUnionCommand, SUnionStoreCommand,
     SaveCommand, SelectCommand, SetBitCommand, SetCommand, SetFloatCommand, SetNxCommand,
-    ShutdownCommand, StrLenCommand, TimeCommand, XAckCommand, XAddCommand, XDelCommand,
-    XGroupCreateCommand, XLenCommand, XRangeCommand, XReadCommand, XRevRangeCommand, XTrimCommand,
-    ZAddCommand, ZCardCommand, ZCountCommand, ZIncrByCommand, ZRangeCommand, ZRankCommand,
-    ZRemCommand, ZRevRangeCommand, ZRevRankCommand, ZScoreCommand,
+    SetOptions, SetRangeCommand, SetSlotAction, ShutdownCommand, SlowLogGetCommand,
+    SlowLogLenCommand, SlowLogResetCommand, SmIsMemberCommand, SortCommand, StrLenCommand,
+    SwapDbCommand, TimeCommand, TouchCommand, TtlCommand, TypeCommand, XAckCommand, XAddCommand,
+    XAutoClaimCommand, XClaimCommand, XDelCommand, XGroupCreateCommand, XInfoCommand, XLenCommand,
+    XPendingCommand, XRangeCommand, XReadCommand, XRevRangeCommand, XTrimCommand, ZAddCommand,
+    ZCardCommand, ZCountCommand, ZDiffCommand, ZDiffStoreCommand, ZIncrByCommand, ZInterCommand,
+    ZInterStoreCommand, ZLexCountCommand, ZPopMaxCommand, ZPopMinCommand, ZRandMemberCommand,
+    ZRangeByLexCommand, ZRangeCommand, ZRankCommand, ZRemCommand, ZRevRangeByLexCommand,
+    ZRevRangeCommand, ZRevRankCommand, ZScanCommand, ZScoreCommand, ZUnionCommand,
+    ZUnionStoreCommand, ZmPopCommand, ZmScoreCommand, DEFAULT_MEMORY_SAMPLES,
 };
 /// Реэкспорт настроек конфигурации.
-pub use config::settings::{Settings, StorageConfig, StorageType};
+pub use config::{
+    reload::{ReloadReport, SettingChange, SettingsReloader, SharedSettings},
+    settings::{
+        PubSubSettings, RuntimeConfig, RuntimeParams, Settings, StorageConfig, StorageType,
+        TlsAuthClients,
+    },
+};
 /// Реэкспорт встроенных структур данных.
 pub use database::{
-    haversine_distance, Bitmap, BoundingBox, ConcurrentSkipList, ContentionMetrics,
-    ContentionSnapshot, Dict, DictIter, Direction, ExpireMap, FragmentationInfo, GeoEntry,
-    GeoModuleStats, GeoPoint, GeoSet, Geohash, GeohashPrecision, GeohashStats, HashMetrics, Hll,
-    HllBuilder, HllCompact, HllDefault, HllDense, HllEncoding, HllHasher, HllMaxPrecision,
-    HllPrecise, HllSparse, HllStats, IntSet, IntSetIter, IntSetRangeIter, ListPack, MurmurHasher,
-    Node, QuickList, RTree, RadiusOptions, RangeIter, ReverseIter, Sds, SipHasher, SkipList,
-    SkipListIter, SkipListStatistics, SmartHash, SmartHashIter, Stream, StreamEntry, StreamId,
-    TreeStats, ValidationError, Value, XxHasher, BIT_COUNT_TABLE, DEFAULT_PRECISION,
+    glob_match, haversine_distance, scan_page, AutoClaim, BitUnit, Bitmap, BoundingBox,
+    ClaimOptions, ConcurrentSkipList, ConsumerInfo, ContentionMetrics, ContentionSnapshot, Dict,
+    DictIter, Direction, ExpireMap, FragmentationInfo, GeoAddOptions, GeoEntry, GeoModuleStats,
+    GeoPoint, GeoSearchFrom, GeoSearchParams, GeoSearchShape, GeoSet, GeoSortOrder, Geohash,
+    GeohashPrecision, GeohashStats, GetExExpiry, GroupInfo, HashMetrics, HashRepr, Hll, HllBuilder,
+    HllCompact, HllDefault, HllDense, HllEncoding, HllHasher, HllMaxPrecision, HllPrecise,
+    HllSparse, HllStats, IntSet, IntSetIter, KeyspaceEvent, KeyspaceNotifier, LexBound, ListDir,
+    ListPack, MurmurHasher, Node, PendingEntry, PendingEntryList, QuickList, RTree, RadiusOptions,
+    RangeIter, ReverseIter, Sds, SipHasher, SkipList, SkipListIter, SkipListStatistics, SmartHash,
+    SmartHashIter, SmartHashKeys, SmartHashValues, Stream, StreamConsumer, StreamEntry,
+    StreamGroup, StreamId, StreamInfo, StreamNotifiers, StreamSubscription, StreamTrim,
+    StreamTrimStrategy, TreeStats, ValidationError, Value, ValueType, XxHasher, ZAggregate,
+    ZCombineOptions, ZPopDir, ZSetEntries, BIT_COUNT_TABLE, DEFAULT_PRECISION, DEFAULT_SCAN_COUNT,
     DEFAULT_SPARSE_THRESHOLD, GEO_VERSION, MAX_PRECISION, MIN_PRECISION, SERIALIZATION_VERSION,
 };
 /// Реэкспорт движков хранения.
 pub use engine::{
-    load_from_zdb, save_to_zdb, AofLog, GlobalShardStats, InMemoryStore, InPersistentStore, Shard,
-    ShardId, ShardMetrics, ShardMetricsSnapshot, ShardedIndex, ShardingConfig, SlotId, SlotManager,
-    SlotState, Storage, StorageEngine, SyncPolicy,
+    load_from_zdb, save_to_zdb, AofLog, DbShard, EvictionPolicy, FlushMode, FunctionLibrary,
+    FunctionMeta, FunctionRestorePolicy, GlobalShardStats, InMemoryStore, InPersistentStore,
+    KeyEntry, LibraryInfo, LuaConfig, LuaEngine, LuaExecutionError, Shard, ShardId, ShardMetrics,
+    ShardMetricsSnapshot, ShardedIndex, ShardingConfig, SlotId, SlotManager, SlotState, Storage,
+    StorageEngine, SyncPolicy, DEFAULT_DATABASES,
 };
 /// Реэкспорт основных типов ошибок.
 pub use error::{
@@ -99,7 +133,13 @@ pub use error::{
 /// Реэкспорт API для работы с модулями и плагинами.
 pub use modules::{DynamicModule, Manager, Module, Plugin, WasmPlugin};
 /// Реэкспорт сетевого сервера и протокола.
-pub use network::{banner, server, zsp};
+pub use network::{
+    banner,
+    monitor::{Monitor, MonitorSubscription, DEFAULT_MONITOR_CAPACITY},
+    server,
+    slowlog::{SlowLog, SlowLogEntry, DEFAULT_SLOWLOG_GET_COUNT},
+    zsp,
+};
 /// Реэкспорт API для Pub/Sub.
 pub use pubsub::{
     Broker, BrokerConfig, BrokerMetrics, BrokerSnapshot, ChannelSnapshot, ChannelStats,
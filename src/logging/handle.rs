@@ -7,6 +7,9 @@ use std::{
 };
 
 use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use super::{config::LoggingConfig, filters};
 
 /// Метрики для LoggingHandle.
 #[derive(Debug, Default)]
@@ -29,8 +32,17 @@ pub struct LoggingHandle {
     pub metrics: Arc<LoggingMetrics>,
     /// Timeout для flush при shutdown (по умолчанию 5 секунд)
     flush_timeout: Duration,
+    /// Провайдер OTLP-трассировки (если экспорт включён)
+    #[cfg(feature = "otlp")]
+    tracer_provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+    /// Фильтр уровней, заменяемый без перезапуска
+    filter: Option<LogFilterHandle>,
 }
 
+/// Handle для замены фильтра уровней логирования во время работы.
+#[derive(Clone)]
+pub struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
 /// Статистика логирования.
 #[derive(Debug, Clone, Copy)]
 pub struct LoggingStats {
@@ -39,6 +51,24 @@ pub struct LoggingStats {
     pub shutdown_in_progress: bool,
 }
 
+impl LogFilterHandle {
+    /// Оборачивает handle перезагружаемого слоя фильтра.
+    pub fn new(handle: reload::Handle<EnvFilter, Registry>) -> Self {
+        Self(handle)
+    }
+
+    /// Пересобирает фильтр из `config` (например, после смены
+    /// `logging.level`). Заданный `RUST_LOG` по-прежнему имеет приоритет.
+    pub fn apply(
+        &self,
+        config: &LoggingConfig,
+    ) -> Result<(), String> {
+        self.0
+            .reload(filters::build_filter_from_config(config))
+            .map_err(|e| e.to_string())
+    }
+}
+
 impl LoggingMetrics {
     pub fn new() -> Self {
         Self::default()
@@ -83,9 +113,38 @@ impl LoggingHandle {
             _network_guard: network_guard,
             metrics: Arc::new(LoggingMetrics::new()),
             flush_timeout: Duration::from_secs(5),
+            #[cfg(feature = "otlp")]
+            tracer_provider: None,
+            filter: None,
         }
     }
 
+    /// Передаёт handle фильтр уровней, который можно заменить без
+    /// перезапуска (см. [`LoggingHandle::filter_handle`]).
+    pub fn with_filter_handle(
+        mut self,
+        filter: LogFilterHandle,
+    ) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Возвращает handle фильтра уровней, если он доступен.
+    pub fn filter_handle(&self) -> Option<LogFilterHandle> {
+        self.filter.clone()
+    }
+
+    /// Передаёт handle провайдер OTLP-трассировки, который будет
+    /// остановлен (с отправкой накопленных спанов) при shutdown.
+    #[cfg(feature = "otlp")]
+    pub fn with_tracer_provider(
+        mut self,
+        provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+    ) -> Self {
+        self.tracer_provider = provider;
+        self
+    }
+
     /// Устанавливает custom flush timeout.
     pub fn with_flush_timeout(
         mut self,
@@ -127,6 +186,12 @@ impl LoggingHandle {
 
         drop(self._file_guard.take());
         drop(self._network_guard.take());
+        // Остановка ждёт gRPC-канал, работающий в рантайме сервера, поэтому
+        // выполняется в отдельном потоке, чтобы не заблокировать рантайм.
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.tracer_provider.take() {
+            std::thread::spawn(move || provider.shutdown());
+        }
 
         let elapsed = start.elapsed();
 
@@ -162,11 +227,19 @@ impl LoggingHandle {
         // Берем Option::take() для guard-ов, чтобы безопасно вызвать drop
         let file_guard = self._file_guard.take();
         let network_guard = self._network_guard.take();
+        #[cfg(feature = "otlp")]
+        let tracer_provider = self.tracer_provider.take();
 
         // Выполняем shutdown в блокирующем потоке с таймаутом
         match tokio::time::timeout(
             timeout,
             tokio::task::spawn_blocking(move || {
+                #[cfg(feature = "otlp")]
+                if let Some(provider) = tracer_provider {
+                    if let Err(e) = provider.shutdown() {
+                        eprintln!("OTLP tracer provider shutdown failed: {e}");
+                    }
+                }
                 drop(file_guard);
                 drop(network_guard);
             }),
@@ -6,22 +6,31 @@ pub mod handle;
 pub mod sinks;
 pub mod slow_log;
 pub mod slow_query_layer;
+pub mod telemetry;
 
 pub use config::LoggingConfig;
-pub use handle::LoggingHandle;
+pub use handle::{LogFilterHandle, LoggingHandle};
 pub use slow_log::{SlowLogConfig, SlowLogStats, SlowQueryTracker};
 pub use slow_query_layer::SlowQueryLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
+pub use telemetry::TelemetryConfig;
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, Layer};
 
-/// Инициализация логирования с конфигурацией
+/// Инициализация логирования с конфигурацией.
+///
+/// Если в `telemetry` задан `otlp_endpoint`, спаны дополнительно
+/// экспортируются в OTLP-коллектор (требуется фича `otlp`).
 pub fn init_logging(
-    mut config: LoggingConfig
+    mut config: LoggingConfig,
+    telemetry: &TelemetryConfig,
 ) -> Result<LoggingHandle, Box<dyn std::error::Error>> {
     config.apply_env_overrides();
     config.validate()?;
     config.ensure_log_dir()?;
 
-    let env_filter = filters::build_filter_from_config(&config);
+    // Фильтр оборачивается в reload-слой, чтобы уровень можно было сменить
+    // без перезапуска (SIGHUP).
+    let (env_filter, filter_handle) =
+        reload::Layer::new(filters::build_filter_from_config(&config));
     let mut layers = Vec::new();
 
     // Console layer
@@ -46,6 +55,23 @@ pub fn init_logging(
         layers.push(slow_layer.boxed());
     }
 
+    // OpenTelemetry layer
+    #[cfg(feature = "otlp")]
+    let tracer_provider = match telemetry::init(telemetry)? {
+        Some((otlp_layer, provider)) => {
+            layers.push(otlp_layer);
+            Some(provider)
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "otlp"))]
+    if let Some(endpoint) = &telemetry.otlp_endpoint {
+        return Err(format!(
+            "OTLP endpoint {endpoint} is configured, but zumic was built without the `otlp` feature"
+        )
+        .into());
+    }
+
     // Initialize subscriber
     tracing_subscriber::registry()
         .with(env_filter)
@@ -59,10 +85,14 @@ pub fn init_logging(
         console_enabled = config.console_enabled,
         file_enabled = config.file_enabled,
         slow_log_enabled = config.slow_log.enabled,
+        otlp_endpoint = telemetry.otlp_endpoint.as_deref(),
         "Logging system initialized"
     );
 
-    let handle = LoggingHandle::new(file_guard, None);
+    let handle = LoggingHandle::new(file_guard, None)
+        .with_filter_handle(LogFilterHandle::new(filter_handle));
+    #[cfg(feature = "otlp")]
+    let handle = handle.with_tracer_provider(tracer_provider);
     Ok(handle)
 }
 
@@ -103,7 +133,7 @@ fn init_slow_log(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Error
 #[deprecated(note = "Use init_logging() instead")]
 pub fn init_logging_simple() {
     let config = LoggingConfig::default();
-    if let Err(e) = init_logging(config) {
+    if let Err(e) = init_logging(config, &TelemetryConfig::default()) {
         eprintln!("Failed to initialize logging: {e}");
     }
 }
@@ -0,0 +1,175 @@
+//! Экспорт трассировки в OpenTelemetry (OTLP).
+//!
+//! Спаны соединений и команд создаются только после того, как
+//! [`init_logging`](super::init_logging) подключил OTLP-экспортер; без
+//! `telemetry.otlp_endpoint` функции модуля возвращают `Span::none()` и
+//! ничего не стоят. Сам экспортер доступен только с Cargo-фичей `otlp`.
+
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+
+/// Включён ли экспорт спанов. Выставляется один раз при инициализации.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Конфигурация экспорта трассировки (`[telemetry]`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelemetryConfig {
+    /// Адрес OTLP/gRPC коллектора, например `http://localhost:4317`;
+    /// `None` — экспорт выключен
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+    /// Значение `service.name` в ресурсе OpenTelemetry
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: default_service_name(),
+        }
+    }
+}
+
+fn default_service_name() -> String {
+    "zumic".to_string()
+}
+
+/// Возвращает `true`, если спаны экспортируются в OTLP.
+#[inline]
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Создаёт корневой спан соединения.
+///
+/// # Возвращает
+/// - спан `connection` с `net.peer.addr` и `session.id` или `Span::none()`,
+///   если экспорт выключен
+pub fn connection_span(
+    connection_id: u32,
+    addr: SocketAddr,
+) -> Span {
+    if !enabled() {
+        return Span::none();
+    }
+    tracing::info_span!(
+        parent: None,
+        "connection",
+        db.system = "zumic",
+        net.peer.addr = %addr,
+        session.id = connection_id,
+    )
+}
+
+/// Создаёт дочерний спан команды `args` внутри спана соединения.
+///
+/// # Возвращает
+/// - спан `command` с `db.operation` и `db.key` (первый аргумент) или
+///   `Span::none()`, если экспорт выключен
+pub fn command_span<S: AsRef<str>>(
+    parent: &Span,
+    args: &[S],
+) -> Span {
+    if !enabled() || parent.is_none() {
+        return Span::none();
+    }
+    let operation = args
+        .first()
+        .map(|cmd| cmd.as_ref().to_ascii_uppercase())
+        .unwrap_or_default();
+    let key = args.get(1).map(|key| key.as_ref());
+    tracing::info_span!(
+        parent: parent,
+        "command",
+        otel.name = %operation,
+        db.system = "zumic",
+        db.operation = %operation,
+        db.key = key,
+    )
+}
+
+#[cfg(feature = "otlp")]
+pub(crate) use otlp::init;
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use std::sync::atomic::Ordering;
+
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{
+        runtime::TokioCurrentThread,
+        trace::{span_processor_with_async_runtime::BatchSpanProcessor, SdkTracerProvider},
+        Resource,
+    };
+    use tracing_subscriber::{registry::LookupSpan, Layer};
+
+    use super::{TelemetryConfig, ENABLED};
+
+    /// Собирает OTLP-экспортер и слой `tracing_opentelemetry`.
+    ///
+    /// Пакеты спанов отправляются из отдельного потока, поэтому экспорт не
+    /// занимает рантайм сервера. Должна вызываться внутри рантайма Tokio:
+    /// gRPC-канал создаётся в нём.
+    ///
+    /// # Возвращает
+    /// - `Ok(None)` — `otlp_endpoint` не задан
+    /// - `Ok(Some((layer, provider)))` — слой для подписчика и провайдер,
+    ///   который нужно остановить при завершении
+    /// - `Err` — если экспортер не удалось создать
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn init<S>(
+        config: &TelemetryConfig
+    ) -> Result<
+        Option<(Box<dyn Layer<S> + Send + Sync>, SdkTracerProvider)>,
+        Box<dyn std::error::Error>,
+    >
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+    {
+        let Some(endpoint) = config.otlp_endpoint.as_deref() else {
+            return Ok(None);
+        };
+
+        let exporter = SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()?;
+        let processor = BatchSpanProcessor::builder(exporter, TokioCurrentThread).build();
+        let provider = SdkTracerProvider::builder()
+            .with_span_processor(processor)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(config.service_name.clone())
+                    .build(),
+            )
+            .build();
+
+        let tracer = provider.tracer("zumic");
+        let layer = tracing_opentelemetry::layer().with_tracer(tracer).boxed();
+        ENABLED.store(true, Ordering::Relaxed);
+        Ok(Some((layer, provider)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет, что без подключённого экспортера спаны не
+    /// создаются.
+    #[test]
+    fn test_spans_disabled_without_exporter() {
+        let addr: SocketAddr = "127.0.0.1:6174".parse().unwrap();
+        let conn = connection_span(1, addr);
+        assert!(conn.is_none());
+        assert!(command_span(&conn, &["GET", "key"]).is_none());
+    }
+}
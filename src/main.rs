@@ -1,13 +1,18 @@
-use std::{sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
+use arc_swap::ArcSwap;
 use tracing::{error, info, warn};
 use zumic::{
     banner,
-    engine::{InClusterStore, PersistentStoreConfig},
+    engine::{
+        load_or_create_node_id, start_cleanup_task, CancellationToken, InClusterStore,
+        PersistentStoreConfig, ACTIVE_EXPIRE_PERIOD, LFU_DECAY_PERIOD,
+    },
     logging,
     network::connection::ConnectionConfig,
     server::{Server, ServerConfig},
-    InMemoryStore, InPersistentStore, Settings, Storage, StorageEngine, StorageType,
+    AuthManager, InMemoryStore, InPersistentStore, RuntimeConfig, RuntimeParams, Settings,
+    SettingsReloader, SmartHash, Storage, StorageEngine, StorageType,
 };
 
 #[tokio::main(flavor = "current_thread")]
@@ -29,7 +34,7 @@ async fn main() -> anyhow::Result<()> {
 async fn run_server() -> anyhow::Result<()> {
     let settings = Settings::load()?;
 
-    let logging_handle = logging::init_logging(settings.logging.clone())
+    let logging_handle = logging::init_logging(settings.logging.clone(), &settings.telemetry)
         .map_err(|e| anyhow::anyhow!("Failed to initialize logging: {e}"))?
         .with_flush_timeout(Duration::from_secs(10));
 
@@ -59,7 +64,20 @@ async fn run_server() -> anyhow::Result<()> {
     let engine = match settings.storage_type {
         StorageType::Memory => {
             info!("Initializing in-memory storage");
-            Arc::new(StorageEngine::Memory(InMemoryStore::new()))
+            let store = InMemoryStore::new();
+            store.spawn_lfu_decay(LFU_DECAY_PERIOD);
+            start_cleanup_task(
+                Arc::new(store.select(0).map_err(|e| anyhow::anyhow!("{e}"))?),
+                ACTIVE_EXPIRE_PERIOD,
+                CancellationToken::new(),
+            );
+            if let Some(flags) = settings.notify_keyspace_events.as_deref() {
+                store
+                    .keyspace_notifier()
+                    .set_flags(flags)
+                    .map_err(|e| anyhow::anyhow!("{e}"))?;
+            }
+            Arc::new(StorageEngine::Memory(store))
         }
         StorageType::Persistent => {
             info!("Initializing persistent storage");
@@ -73,25 +91,50 @@ async fn run_server() -> anyhow::Result<()> {
             let shards: Vec<Arc<dyn Storage>> = (0..3)
                 .map(|_| Arc::new(InMemoryStore::new()) as Arc<dyn Storage>)
                 .collect();
-            let cluster_store = InClusterStore::new(shards);
+            let node_id = load_or_create_node_id(Path::new("zumic-node.id"))?;
+            let cluster_store = InClusterStore::new(shards).with_node_id(node_id);
             Arc::new(StorageEngine::Cluster(cluster_store))
         }
     };
 
+    let runtime_params = RuntimeParams::from_settings(&settings);
+    SmartHash::set_listpack_limits(
+        runtime_params.hash_max_listpack_entries,
+        runtime_params.hash_max_listpack_value,
+    );
+    let mut connection_config = ConnectionConfig {
+        read_buffer_size: settings.read_buffer_size.unwrap_or(8192),
+        ..ConnectionConfig::default()
+    };
+    connection_config.apply_runtime(&runtime_params);
+    configure_tls(&settings, &mut connection_config)?;
+
     let server_config = ServerConfig {
         listen_address: settings.listen_address,
-        connection_config: ConnectionConfig {
-            max_connections: settings.max_connections as usize,
-            max_connections_per_ip: settings.max_connections_per_ip.unwrap_or(100),
-            idle_timeout: Duration::from_secs(settings.connection_timeout.unwrap_or(300)),
-            read_timeout: Duration::from_secs(settings.read_timeout.unwrap_or(30)),
-            write_timeout: Duration::from_secs(settings.write_timeout.unwrap_or(10)),
-            read_buffer_size: settings.read_buffer_size.unwrap_or(8192),
-        },
+        connection_config,
         shutdown_timeout: Duration::from_secs(settings.shutdown_timeout.unwrap_or(30)),
     };
 
-    let mut server = Server::new(server_config, engine);
+    let runtime = RuntimeConfig::new(runtime_params);
+    let mut server = Server::with_runtime_config(server_config, engine, runtime.clone());
+    if let Some(aclfile) = settings.aclfile.as_deref().map(Path::new) {
+        if aclfile.exists() {
+            info!("Loading ACL users from {}", aclfile.display());
+            server = server.with_auth(AuthManager::load_from_file(aclfile)?);
+        }
+    }
+
+    let broker = server.connection_manager().broker().clone();
+    broker.set_config(settings.pubsub.broker_config());
+    zumic::metrics::registry().register_broker(broker.clone());
+
+    start_metrics_endpoint(&settings).await?;
+    start_admin_api(&settings, &server).await?;
+
+    let reloader = SettingsReloader::new(Arc::new(ArcSwap::from_pointee(settings)), runtime)
+        .with_broker(broker)
+        .with_log_filter(logging_handle.filter_handle());
+    spawn_reload_on_sighup(reloader)?;
 
     match server.start().await {
         Ok(_) => {
@@ -108,6 +151,139 @@ async fn run_server() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Включает TLS, если в настройках заданы сертификат и ключ сервера.
+///
+/// Ошибки загрузки сертификатов фатальны: сервер не стартует без
+/// шифрования, если его явно потребовали.
+fn configure_tls(
+    settings: &Settings,
+    connection_config: &mut ConnectionConfig,
+) -> anyhow::Result<()> {
+    let (cert_file, key_file) = match (&settings.tls_cert_file, &settings.tls_key_file) {
+        (None, None) => return Ok(()),
+        (Some(cert_file), Some(key_file)) => (Path::new(cert_file), Path::new(key_file)),
+        _ => anyhow::bail!("both tls_cert_file and tls_key_file must be set to enable TLS"),
+    };
+
+    #[cfg(feature = "tls")]
+    {
+        let tls_config = zumic::network::tls::load_server_config(
+            cert_file,
+            key_file,
+            settings.tls_ca_cert_file.as_deref().map(Path::new),
+            settings.tls_auth_clients,
+        )?;
+        info!("TLS enabled with certificate {}", cert_file.display());
+        connection_config.tls_config = Some(tls_config);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "tls"))]
+    {
+        let _ = (key_file, connection_config);
+        anyhow::bail!(
+            "TLS certificate {} is configured, but zumic was built without the `tls` feature",
+            cert_file.display()
+        )
+    }
+}
+
+/// Запускает HTTP-эндпоинт `GET /metrics`, если задан `metrics_bind`.
+///
+/// Эндпоинт не может делить порт с основным сервером; ошибка привязки
+/// фатальна, как и запрос метрик в сборке без фичи `metrics`.
+async fn start_metrics_endpoint(settings: &Settings) -> anyhow::Result<()> {
+    let Some(addr) = settings.metrics_bind else {
+        return Ok(());
+    };
+    if addr.port() == settings.listen_address.port() {
+        anyhow::bail!("metrics_bind {addr} must not share the server port");
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+        zumic::metrics::exporter::serve(addr, zumic::metrics::registry())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind metrics endpoint {addr}: {e}"))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    {
+        anyhow::bail!(
+            "metrics_bind {addr} is configured, but zumic was built without the `metrics` feature"
+        )
+    }
+}
+
+/// Запускает административный HTTP API, если `admin.enabled = true`.
+///
+/// API делит с сервером движок хранения и реестр соединений. Без токена,
+/// на порту основного сервера или в сборке без фичи `admin` запуск
+/// фатален.
+async fn start_admin_api(
+    settings: &Settings,
+    server: &Server,
+) -> anyhow::Result<()> {
+    let config = &settings.admin;
+    if !config.enabled {
+        return Ok(());
+    }
+    let Some(token) = config.token.as_deref().filter(|token| !token.is_empty()) else {
+        anyhow::bail!("admin.token must be set to enable the admin API");
+    };
+    if config.bind.port() == settings.listen_address.port() {
+        anyhow::bail!("admin.bind {} must not share the server port", config.bind);
+    }
+
+    #[cfg(feature = "admin")]
+    {
+        use zumic::network::admin_api::{self, AdminApiState};
+
+        let manager = server.connection_manager();
+        let state = AdminApiState::new(
+            server.engine().clone(),
+            manager.registry().clone(),
+            manager.admin().clone(),
+            token,
+        );
+        admin_api::serve(config.bind, state)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind admin API {}: {e}", config.bind))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "admin"))]
+    {
+        let _ = (server, token);
+        anyhow::bail!("admin API is enabled, but zumic was built without the `admin` feature")
+    }
+}
+
+/// Запускает фоновую задачу, которая по `SIGHUP` перечитывает настройки и
+/// применяет неразрушающие изменения через `reloader` (только Unix).
+fn spawn_reload_on_sighup(reloader: SettingsReloader) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sighup = signal(SignalKind::hangup()).map_err(|e| anyhow::anyhow!(e))?;
+        tokio::task::spawn_local(async move {
+            while sighup.recv().await.is_some() {
+                info!("Received SIGHUP, reloading configuration...");
+                if let Err(e) = reloader.reload() {
+                    error!("Failed to reload configuration: {e}");
+                }
+            }
+        });
+    }
+
+    #[cfg(not(unix))]
+    let _ = reloader;
+
+    Ok(())
+}
+
 /// Настройка обработчиков сигналов для graceful shutdown
 async fn setup_signal_handlers(
     server: &mut Server,
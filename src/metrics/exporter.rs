@@ -0,0 +1,334 @@
+//! HTTP-эндпоинт `GET /metrics` в текстовом формате Prometheus.
+//!
+//! Работает на отдельном адресе (`metrics_bind`), а не на порту сервера:
+//! каждое соединение обслуживает один запрос и закрывается.
+
+use std::{io, net::SocketAddr, time::Duration};
+
+use metrics_exporter_prometheus::formatting::{
+    sanitize_label_value, write_help_line, write_metric_line, write_type_line,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+    time::timeout,
+};
+use tracing::{debug, info, warn};
+
+use super::{memory_used_bytes, MetricsRegistry, DURATION_BUCKETS};
+
+/// Максимальный размер заголовков запроса.
+const MAX_REQUEST_SIZE: usize = 8192;
+/// Время ожидания запроса и записи ответа.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// `Content-Type` текстового формата Prometheus.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Формирует снимок метрик `registry` в текстовом формате Prometheus.
+///
+/// # Возвращает
+/// - тело ответа `GET /metrics`
+pub fn render(registry: &MetricsRegistry) -> String {
+    let mut buf = String::new();
+
+    write_scalar(
+        &mut buf,
+        "zumic_connections_active",
+        "gauge",
+        "Number of open client connections.",
+        registry.connections_active(),
+    );
+
+    let commands = registry.commands();
+    let labels: Vec<Vec<String>> = commands
+        .iter()
+        .map(|(name, _)| vec![format!("command=\"{}\"", sanitize_label_value(name))])
+        .collect();
+
+    write_help_line(
+        &mut buf,
+        "zumic_commands_total",
+        "Total number of executed commands.",
+    );
+    write_type_line(&mut buf, "zumic_commands_total", "counter");
+    for ((_, stats), labels) in commands.iter().zip(&labels) {
+        write_metric_line(
+            &mut buf,
+            "zumic_commands_total",
+            None,
+            labels,
+            None::<(&str, u64)>,
+            stats.calls(),
+            None,
+        );
+    }
+
+    let histogram = "zumic_command_duration_seconds";
+    write_help_line(&mut buf, histogram, "Command execution time in seconds.");
+    write_type_line(&mut buf, histogram, "histogram");
+    for ((_, stats), labels) in commands.iter().zip(&labels) {
+        for (le, count) in DURATION_BUCKETS.iter().zip(stats.cumulative_buckets()) {
+            write_metric_line(
+                &mut buf,
+                histogram,
+                Some("bucket"),
+                labels,
+                Some(("le", le)),
+                count,
+                None,
+            );
+        }
+        write_metric_line(
+            &mut buf,
+            histogram,
+            Some("bucket"),
+            labels,
+            Some(("le", "+Inf")),
+            stats.calls(),
+            None,
+        );
+        write_metric_line(
+            &mut buf,
+            histogram,
+            Some("sum"),
+            labels,
+            None::<(&str, u64)>,
+            stats.duration_sum(),
+            None,
+        );
+        write_metric_line(
+            &mut buf,
+            histogram,
+            Some("count"),
+            labels,
+            None::<(&str, u64)>,
+            stats.calls(),
+            None,
+        );
+    }
+
+    write_scalar(
+        &mut buf,
+        "zumic_memory_used_bytes",
+        "gauge",
+        "Resident memory used by the server process in bytes.",
+        memory_used_bytes(),
+    );
+    write_scalar(
+        &mut buf,
+        "zumic_evicted_keys_total",
+        "counter",
+        "Total number of keys evicted by the maxmemory policy.",
+        registry.evicted_keys(),
+    );
+    write_scalar(
+        &mut buf,
+        "zumic_expired_keys_total",
+        "counter",
+        "Total number of keys removed after their TTL expired.",
+        registry.expired_keys(),
+    );
+    write_scalar(
+        &mut buf,
+        "zumic_keyspace_hits_total",
+        "counter",
+        "Total number of successful key lookups.",
+        registry.keyspace_hits(),
+    );
+    write_scalar(
+        &mut buf,
+        "zumic_keyspace_misses_total",
+        "counter",
+        "Total number of lookups of missing keys.",
+        registry.keyspace_misses(),
+    );
+
+    if let Some(broker) = registry.broker() {
+        buf.push_str(&broker.snapshot().to_prometheus_lines());
+    }
+
+    buf
+}
+
+/// Запускает HTTP-эндпоинт метрик на адресе `addr`.
+///
+/// # Возвращает
+/// - `Ok((addr, handle))` — фактический адрес (полезно при порте 0) и фоновая
+///   задача, обслуживающая запросы
+/// - `Err` — если адрес не удалось занять
+pub async fn serve(
+    addr: SocketAddr,
+    registry: &'static MetricsRegistry,
+) -> io::Result<(SocketAddr, JoinHandle<()>)> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    info!("Metrics endpoint listening on http://{local_addr}/metrics");
+
+    let handle = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_request(stream, registry).await {
+                            debug!("Metrics request from {peer} failed: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!("Metrics endpoint accept error: {e}"),
+            }
+        }
+    });
+    Ok((local_addr, handle))
+}
+
+/// Читает один HTTP-запрос и отвечает на него.
+async fn handle_request(
+    mut stream: TcpStream,
+    registry: &MetricsRegistry,
+) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buf.len() >= MAX_REQUEST_SIZE {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", "").await;
+        }
+        let n = timeout(IO_TIMEOUT, stream.read(&mut chunk)).await??;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let request_line = buf.split(|&b| b == b'\r').next().unwrap_or_default();
+    let request_line = String::from_utf8_lossy(request_line);
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+
+    match (method, path) {
+        ("GET", "/metrics") => write_response(&mut stream, "200 OK", &render(registry)).await,
+        (_, "/metrics") => write_response(&mut stream, "405 Method Not Allowed", "").await,
+        _ => write_response(&mut stream, "404 Not Found", "").await,
+    }
+}
+
+/// Отправляет ответ и закрывает соединение.
+async fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    body: &str,
+) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    timeout(IO_TIMEOUT, stream.write_all(response.as_bytes())).await??;
+    timeout(IO_TIMEOUT, stream.shutdown()).await?
+}
+
+/// Записывает метрику без меток вместе с `HELP` и `TYPE`.
+fn write_scalar(
+    buf: &mut String,
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    value: u64,
+) {
+    write_help_line(buf, name, help);
+    write_type_line(buf, name, metric_type);
+    write_metric_line(buf, name, None, &[], None::<(&str, u64)>, value, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Тест проверяет текстовый формат: скалярные метрики, счётчик команд и
+    /// накопительную гистограмму с `+Inf`, суммой и числом выполнений.
+    #[test]
+    fn test_render_prometheus_text() {
+        let registry = MetricsRegistry::default();
+        registry.connection_opened();
+        registry.record_command("get", Duration::from_millis(2));
+        registry.record_command("GET", Duration::from_secs(5));
+        registry.record_keyspace_lookup(false);
+
+        let text = render(&registry);
+        assert!(
+            text.contains("# TYPE zumic_connections_active gauge\nzumic_connections_active 1\n")
+        );
+        assert!(text.contains("zumic_commands_total{command=\"GET\"} 2\n"));
+        assert!(text.contains("# TYPE zumic_command_duration_seconds histogram\n"));
+        assert!(text
+            .contains("zumic_command_duration_seconds_bucket{command=\"GET\",le=\"0.001\"} 0\n"));
+        assert!(text
+            .contains("zumic_command_duration_seconds_bucket{command=\"GET\",le=\"0.0025\"} 1\n"));
+        assert!(
+            text.contains("zumic_command_duration_seconds_bucket{command=\"GET\",le=\"+Inf\"} 2\n")
+        );
+        assert!(text.contains("zumic_command_duration_seconds_sum{command=\"GET\"} 5.002\n"));
+        assert!(text.contains("zumic_command_duration_seconds_count{command=\"GET\"} 2\n"));
+        assert!(text.contains("zumic_keyspace_misses_total 1\n"));
+        assert!(text.contains("zumic_keyspace_hits_total 0\n"));
+        assert!(text.contains("# TYPE zumic_memory_used_bytes gauge\n"));
+        assert!(text.contains("zumic_evicted_keys_total 0\n"));
+        assert!(text.contains("zumic_expired_keys_total 0\n"));
+        assert!(!text.contains("zumic_pubsub_"));
+    }
+
+    /// Тест проверяет, что метрики зарегистрированного брокера попадают в
+    /// снимок вместе со строками по каналам.
+    #[test]
+    fn test_render_includes_broker_metrics() {
+        let registry = MetricsRegistry::default();
+        let broker = std::sync::Arc::new(crate::Broker::new());
+        let _sub = broker.subscribe("news").unwrap();
+        broker
+            .publish("news", crate::MessagePayload::String("hi".into()))
+            .unwrap();
+        registry.register_broker(broker);
+
+        let text = render(&registry);
+        assert!(text.contains("# TYPE zumic_pubsub_messages_published_total counter\n"));
+        assert!(text.contains("zumic_pubsub_messages_published_total{channel=\"news\"} 1\n"));
+        assert!(text.ends_with("zumic_pubsub_backpressure_blocks_total 0\n"));
+    }
+
+    /// Тест проверяет, что эндпоинт отдаёт метрики на `GET /metrics` и 404
+    /// на остальные пути.
+    #[tokio::test]
+    async fn test_serve_metrics_endpoint() {
+        static REGISTRY: once_cell::sync::Lazy<MetricsRegistry> =
+            once_cell::sync::Lazy::new(MetricsRegistry::default);
+        REGISTRY.record_expired(4);
+
+        let (addr, handle) = serve("127.0.0.1:0".parse().unwrap(), &REGISTRY)
+            .await
+            .unwrap();
+
+        let request = |req: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(req.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let ok = request("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(ok.ends_with("zumic_keyspace_misses_total 0\n"));
+        assert!(ok.contains("zumic_expired_keys_total 4\n"));
+
+        let missing = request("GET / HTTP/1.1\r\n\r\n").await;
+        assert!(missing.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let wrong_method = request("POST /metrics HTTP/1.1\r\n\r\n").await;
+        assert!(wrong_method.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+        handle.abort();
+    }
+}
@@ -0,0 +1,323 @@
+//! Метрики сервера для мониторинга.
+//!
+//! Счётчики собраны в глобальном [`MetricsRegistry`] и обновляются на
+//! горячем пути атомарными операциями, поэтому учёт не зависит от того,
+//! включён ли экспорт. HTTP-эндпоинт `GET /metrics` в формате Prometheus
+//! доступен только с Cargo-фичей `metrics` (см. [`exporter`]). Метрики
+//! брокера Pub/Sub, зарегистрированного через
+//! [`MetricsRegistry::register_broker`], добавляются в тот же снимок.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+use crate::Broker;
+
+#[cfg(feature = "metrics")]
+pub mod exporter;
+
+/// Верхние границы корзин гистограммы длительности команд (в секундах).
+pub const DURATION_BUCKETS: [f64; 12] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5, 1.0,
+];
+/// Максимальное число различных меток `command`; остальные команды
+/// учитываются под [`OTHER_COMMAND`], чтобы произвольные имена от клиентов
+/// не раздували реестр.
+pub const MAX_COMMAND_LABELS: usize = 256;
+/// Метка команд сверх [`MAX_COMMAND_LABELS`].
+pub const OTHER_COMMAND: &str = "OTHER";
+
+static REGISTRY: Lazy<MetricsRegistry> = Lazy::new(MetricsRegistry::default);
+
+/// Возвращает глобальный реестр метрик.
+#[inline]
+pub fn registry() -> &'static MetricsRegistry {
+    &REGISTRY
+}
+
+/// Статистика выполнения одной команды.
+#[derive(Debug, Default)]
+pub struct CommandStats {
+    /// Число выполнений.
+    calls: AtomicU64,
+    /// Суммарная длительность в наносекундах.
+    duration_ns: AtomicU64,
+    /// Число выполнений по корзинам [`DURATION_BUCKETS`] (не накопительно);
+    /// выполнения дольше последней границы учитываются только в `calls`.
+    buckets: [AtomicU64; DURATION_BUCKETS.len()],
+}
+
+/// Глобальный реестр метрик сервера.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    /// Число открытых клиентских соединений.
+    connections_active: AtomicU64,
+    /// Число ключей, вытесненных по политике `maxmemory-policy`.
+    evicted_keys: AtomicU64,
+    /// Число ключей, удалённых по истечении TTL.
+    expired_keys: AtomicU64,
+    /// Число чтений существующих ключей.
+    keyspace_hits: AtomicU64,
+    /// Число чтений отсутствующих ключей.
+    keyspace_misses: AtomicU64,
+    /// Статистика по командам, ключ — имя команды в верхнем регистре.
+    commands: DashMap<String, Arc<CommandStats>>,
+    /// Брокер Pub/Sub, метрики которого входят в снимок.
+    broker: RwLock<Option<Arc<Broker>>>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl CommandStats {
+    /// Учитывает одно выполнение длительностью `elapsed`.
+    pub fn record(
+        &self,
+        elapsed: Duration,
+    ) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.duration_ns
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        let secs = elapsed.as_secs_f64();
+        if let Some(bucket) = DURATION_BUCKETS.iter().position(|&le| secs <= le) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Возвращает число выполнений.
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает суммарную длительность выполнений в секундах.
+    pub fn duration_sum(&self) -> f64 {
+        self.duration_ns.load(Ordering::Relaxed) as f64 / 1e9
+    }
+
+    /// Возвращает накопительные счётчики корзин гистограммы: `i`-й элемент —
+    /// число выполнений не дольше `DURATION_BUCKETS[i]`.
+    pub fn cumulative_buckets(&self) -> [u64; DURATION_BUCKETS.len()] {
+        let mut total = 0;
+        std::array::from_fn(|i| {
+            total += self.buckets[i].load(Ordering::Relaxed);
+            total
+        })
+    }
+}
+
+impl MetricsRegistry {
+    /// Учитывает открытие клиентского соединения.
+    pub fn connection_opened(&self) {
+        self.connections_active.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Учитывает закрытие клиентского соединения.
+    pub fn connection_closed(&self) {
+        let _ = self
+            .connections_active
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+    }
+
+    /// Учитывает выполнение команды `command` длительностью `elapsed`.
+    /// Имя приводится к верхнему регистру.
+    pub fn record_command(
+        &self,
+        command: &str,
+        elapsed: Duration,
+    ) {
+        self.command_stats(command).record(elapsed);
+    }
+
+    /// Учитывает `count` вытесненных ключей.
+    pub fn record_evicted(
+        &self,
+        count: u64,
+    ) {
+        self.evicted_keys.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Учитывает `count` ключей, удалённых по истечении TTL.
+    pub fn record_expired(
+        &self,
+        count: u64,
+    ) {
+        self.expired_keys.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Учитывает чтение ключа: попадание, если `hit`, иначе промах.
+    pub fn record_keyspace_lookup(
+        &self,
+        hit: bool,
+    ) {
+        let counter = if hit {
+            &self.keyspace_hits
+        } else {
+            &self.keyspace_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Возвращает число открытых соединений.
+    pub fn connections_active(&self) -> u64 {
+        self.connections_active.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает число вытесненных ключей.
+    pub fn evicted_keys(&self) -> u64 {
+        self.evicted_keys.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает число ключей, удалённых по истечении TTL.
+    pub fn expired_keys(&self) -> u64 {
+        self.expired_keys.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает число попаданий при чтении ключей.
+    pub fn keyspace_hits(&self) -> u64 {
+        self.keyspace_hits.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает число промахов при чтении ключей.
+    pub fn keyspace_misses(&self) -> u64 {
+        self.keyspace_misses.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает статистику команд, отсортированную по имени.
+    pub fn commands(&self) -> Vec<(String, Arc<CommandStats>)> {
+        let mut commands: Vec<_> = self
+            .commands
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        commands.sort_by(|a, b| a.0.cmp(&b.0));
+        commands
+    }
+
+    /// Регистрирует брокер Pub/Sub: его метрики добавляются в снимок
+    /// `GET /metrics`. Повторная регистрация заменяет прежний брокер.
+    pub fn register_broker(
+        &self,
+        broker: Arc<Broker>,
+    ) {
+        *self.broker.write() = Some(broker);
+    }
+
+    /// Возвращает зарегистрированный брокер Pub/Sub.
+    pub fn broker(&self) -> Option<Arc<Broker>> {
+        self.broker.read().clone()
+    }
+
+    /// Возвращает статистику команды `command`, создавая её при первом
+    /// обращении, пока не исчерпан лимит [`MAX_COMMAND_LABELS`].
+    fn command_stats(
+        &self,
+        command: &str,
+    ) -> Arc<CommandStats> {
+        if let Some(stats) = self.commands.get(command) {
+            return stats.clone();
+        }
+        let mut name = command.to_ascii_uppercase();
+        if let Some(stats) = self.commands.get(&name) {
+            return stats.clone();
+        }
+        if self.commands.len() >= MAX_COMMAND_LABELS {
+            name = OTHER_COMMAND.to_string();
+        }
+        self.commands.entry(name).or_default().clone()
+    }
+}
+
+/// Возвращает объём резидентной памяти процесса в байтах (`VmRSS` из
+/// `/proc/self/status`); на платформах без procfs — 0.
+pub fn memory_used_bytes() -> u64 {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("VmRSS:"))
+                .and_then(|rest| {
+                    rest.trim()
+                        .trim_end_matches("kB")
+                        .trim()
+                        .parse::<u64>()
+                        .ok()
+                })
+        })
+        .map_or(0, |kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Тест проверяет раскладку длительностей по корзинам гистограммы и
+    /// сумму длительностей.
+    #[test]
+    fn test_command_stats_histogram() {
+        let stats = CommandStats::default();
+        stats.record(Duration::from_micros(50));
+        stats.record(Duration::from_millis(3));
+        stats.record(Duration::from_secs(2));
+
+        assert_eq!(stats.calls(), 3);
+        let buckets = stats.cumulative_buckets();
+        assert_eq!(buckets[0], 1);
+        assert_eq!(buckets[DURATION_BUCKETS.len() - 1], 2);
+        assert!((stats.duration_sum() - 2.00305).abs() < 1e-9);
+    }
+
+    /// Тест проверяет, что имена команд нормализуются к верхнему регистру, а
+    /// сверх лимита меток учитываются как `OTHER`.
+    #[test]
+    fn test_command_labels_capped() {
+        let registry = MetricsRegistry::default();
+        registry.record_command("get", Duration::ZERO);
+        registry.record_command("GET", Duration::ZERO);
+        for i in 0..MAX_COMMAND_LABELS + 10 {
+            registry.record_command(&format!("cmd{i}"), Duration::ZERO);
+        }
+
+        let commands = registry.commands();
+        assert_eq!(commands.len(), MAX_COMMAND_LABELS + 1);
+        let calls = |name: &str| {
+            commands
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, s)| s.calls())
+        };
+        assert_eq!(calls("GET"), Some(2));
+        assert_eq!(calls(OTHER_COMMAND), Some(11));
+    }
+
+    /// Тест проверяет счётчики соединений и пространства ключей.
+    #[test]
+    fn test_registry_counters() {
+        let registry = MetricsRegistry::default();
+        registry.connection_opened();
+        registry.connection_opened();
+        registry.connection_closed();
+        registry.connection_closed();
+        registry.connection_closed();
+        assert_eq!(registry.connections_active(), 0);
+
+        registry.record_keyspace_lookup(true);
+        registry.record_keyspace_lookup(false);
+        registry.record_keyspace_lookup(false);
+        registry.record_evicted(2);
+        registry.record_expired(3);
+        assert_eq!(registry.keyspace_hits(), 1);
+        assert_eq!(registry.keyspace_misses(), 2);
+        assert_eq!(registry.evicted_keys(), 2);
+        assert_eq!(registry.expired_keys(), 3);
+    }
+}
@@ -0,0 +1,440 @@
+//! Административный HTTP JSON API (`[admin]`).
+//!
+//! Отдельный HTTP-сервер (по умолчанию `127.0.0.1:6175`) для операций без
+//! клиента протокола: информация о сервере, список и закрытие соединений,
+//! изменение параметров, журнал медленных команд и `FLUSHDB`. Каждый запрос
+//! должен содержать заголовок `Authorization: Bearer <token>`. Сам сервер
+//! доступен только с Cargo-фичей `admin`.
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+/// Конфигурация административного API (`[admin]`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AdminApiConfig {
+    /// Запускать ли административный API
+    #[serde(default)]
+    pub enabled: bool,
+    /// Адрес HTTP-сервера; не должен совпадать с портом основного сервера
+    #[serde(default = "default_bind")]
+    pub bind: SocketAddr,
+    /// Токен для заголовка `Authorization: Bearer <token>`; обязателен при
+    /// `enabled = true`
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for AdminApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_bind(),
+            token: None,
+        }
+    }
+}
+
+fn default_bind() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 6175))
+}
+
+#[cfg(feature = "admin")]
+pub use http::{router, serve, AdminApiState};
+
+#[cfg(feature = "admin")]
+mod http {
+    use std::{collections::BTreeMap, io, net::SocketAddr, sync::Arc, time::Instant};
+
+    use axum::{
+        extract::{Path, Query, Request, State},
+        http::{header, StatusCode},
+        middleware::{self, Next},
+        response::{IntoResponse, Response},
+        routing::{delete, get, post},
+        Json, Router,
+    };
+    use serde::Deserialize;
+    use serde_json::{json, Value as JsonValue};
+    use tokio::{net::TcpListener, task::JoinHandle};
+    use tracing::{error, info};
+
+    use crate::{
+        metrics,
+        network::{
+            admin_commands::AdminCommands,
+            connection_registry::{ClientSelector, ConnectionRegistry},
+            connection_state::ConnectionSnapshot,
+        },
+        SlowLogEntry, StorageEngine,
+    };
+
+    /// Состояние, общее для всех обработчиков API.
+    #[derive(Clone)]
+    pub struct AdminApiState {
+        /// Движок хранения основного сервера (логическая база 0)
+        engine: Arc<StorageEngine>,
+        /// Реестр соединений основного сервера
+        registry: Arc<ConnectionRegistry>,
+        /// Параметры времени выполнения и журнал медленных команд
+        admin: Arc<AdminCommands>,
+        /// Ожидаемый bearer-токен
+        token: Arc<str>,
+        /// Момент запуска API, от которого считается `uptime_secs`
+        started: Instant,
+    }
+
+    /// Ошибка обработчика: HTTP-статус и сообщение для тела
+    /// `{"error": "..."}`.
+    struct ApiError(StatusCode, String);
+
+    /// Параметры `GET /api/v1/slowlog`.
+    #[derive(Deserialize)]
+    struct SlowLogQuery {
+        /// Максимальное число записей; по умолчанию как у `SLOWLOG GET`
+        count: Option<usize>,
+    }
+
+    impl AdminApiState {
+        /// Создаёт состояние API поверх объектов основного сервера.
+        pub fn new(
+            engine: Arc<StorageEngine>,
+            registry: Arc<ConnectionRegistry>,
+            admin: Arc<AdminCommands>,
+            token: &str,
+        ) -> Self {
+            Self {
+                engine,
+                registry,
+                admin,
+                token: token.into(),
+                started: Instant::now(),
+            }
+        }
+    }
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            (self.0, Json(json!({ "error": self.1 }))).into_response()
+        }
+    }
+
+    /// Собирает маршруты API с проверкой токена.
+    pub fn router(state: AdminApiState) -> Router {
+        Router::new()
+            .route("/api/v1/info", get(info))
+            .route("/api/v1/clients", get(list_clients))
+            .route("/api/v1/clients/{id}", delete(kill_client))
+            .route("/api/v1/config", post(set_config))
+            .route("/api/v1/slowlog", get(slowlog))
+            .route("/api/v1/command/flush", post(flush))
+            .layer(middleware::from_fn_with_state(state.clone(), require_token))
+            .with_state(state)
+    }
+
+    /// Запускает административный API на адресе `addr`.
+    ///
+    /// # Возвращает
+    /// - `Ok((addr, handle))` — фактический адрес (полезно при порте 0) и
+    ///   фоновая задача HTTP-сервера
+    /// - `Err` — если адрес не удалось занять
+    pub async fn serve(
+        addr: SocketAddr,
+        state: AdminApiState,
+    ) -> io::Result<(SocketAddr, JoinHandle<()>)> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        info!("Admin API listening on http://{local_addr}/api/v1");
+
+        let app = router(state);
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Admin API server failed: {e}");
+            }
+        });
+        Ok((local_addr, handle))
+    }
+
+    /// Отклоняет запросы без верного `Authorization: Bearer <token>`.
+    async fn require_token(
+        State(state): State<AdminApiState>,
+        request: Request,
+        next: Next,
+    ) -> Result<Response, ApiError> {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match presented {
+            Some(token) if constant_time_eq(token.as_bytes(), state.token.as_bytes()) => {
+                Ok(next.run(request).await)
+            }
+            _ => Err(ApiError(
+                StatusCode::UNAUTHORIZED,
+                "missing or invalid bearer token".into(),
+            )),
+        }
+    }
+
+    /// `GET /api/v1/info` — сводка о сервере.
+    async fn info(State(state): State<AdminApiState>) -> Result<Json<JsonValue>, ApiError> {
+        let keys = state.engine.dbsize().map_err(internal)?;
+        let registry = metrics::registry();
+        Ok(Json(json!({
+            "server": {
+                "version": env!("CARGO_PKG_VERSION"),
+                "uptime_secs": state.started.elapsed().as_secs(),
+            },
+            "clients": state.registry.global_stats(),
+            "memory": { "used_bytes": metrics::memory_used_bytes() },
+            "keyspace": { "db0": { "keys": keys } },
+            "stats": {
+                "keyspace_hits": registry.keyspace_hits(),
+                "keyspace_misses": registry.keyspace_misses(),
+                "expired_keys": registry.expired_keys(),
+                "evicted_keys": registry.evicted_keys(),
+            },
+        })))
+    }
+
+    /// `GET /api/v1/clients` — список соединений.
+    async fn list_clients(State(state): State<AdminApiState>) -> Json<Vec<ConnectionSnapshot>> {
+        Json(state.registry.list_all())
+    }
+
+    /// `DELETE /api/v1/clients/{id}` — закрывает соединение.
+    async fn kill_client(
+        State(state): State<AdminApiState>,
+        Path(id): Path<u32>,
+    ) -> Result<Json<JsonValue>, ApiError> {
+        let killed = state.registry.kill(ClientSelector {
+            id: Some(id),
+            ..ClientSelector::default()
+        });
+        if killed == 0 {
+            return Err(ApiError(
+                StatusCode::NOT_FOUND,
+                format!("connection {id} not found"),
+            ));
+        }
+        Ok(Json(json!({ "killed": killed })))
+    }
+
+    /// `POST /api/v1/config` — изменяет параметры, переданные объектом
+    /// `{"имя": "значение", ...}` (как `CONFIG SET`).
+    async fn set_config(
+        State(state): State<AdminApiState>,
+        Json(changes): Json<BTreeMap<String, JsonValue>>,
+    ) -> Result<Json<JsonValue>, ApiError> {
+        if changes.is_empty() {
+            return Err(ApiError(
+                StatusCode::BAD_REQUEST,
+                "no parameters to set".into(),
+            ));
+        }
+        let values: Vec<(&str, String)> = changes
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    JsonValue::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (name.as_str(), value)
+            })
+            .collect();
+        let pairs: Vec<(&str, &str)> = values
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        state
+            .admin
+            .set_config(&pairs)
+            .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+        Ok(Json(json!({ "ok": true })))
+    }
+
+    /// `GET /api/v1/slowlog[?count=N]` — последние записи журнала
+    /// медленных команд.
+    async fn slowlog(
+        State(state): State<AdminApiState>,
+        Query(query): Query<SlowLogQuery>,
+    ) -> Json<Vec<SlowLogEntry>> {
+        Json(state.admin.slowlog().get(query.count))
+    }
+
+    /// `POST /api/v1/command/flush` — `FLUSHDB` для базы 0.
+    async fn flush(State(state): State<AdminApiState>) -> Result<Json<JsonValue>, ApiError> {
+        state.engine.flushdb().map_err(internal)?;
+        Ok(Json(json!({ "ok": true })))
+    }
+
+    /// Превращает ошибку хранилища в ответ 500.
+    fn internal(error: impl std::fmt::Display) -> ApiError {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+    }
+
+    /// Сравнивает строки за время, не зависящее от позиции первого
+    /// расхождения.
+    fn constant_time_eq(
+        a: &[u8],
+        b: &[u8],
+    ) -> bool {
+        a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpStream,
+        };
+
+        use super::*;
+        use crate::{InMemoryStore, Sds, Value};
+
+        const TOKEN: &str = "secret";
+
+        /// Поднимает API на случайном порту поверх пустого хранилища.
+        async fn start() -> (SocketAddr, AdminApiState) {
+            let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+            let registry = Arc::new(ConnectionRegistry::new());
+            let admin = Arc::new(AdminCommands::new(registry.clone()));
+            let state = AdminApiState::new(engine, registry, admin, TOKEN);
+            let (addr, _) = serve("127.0.0.1:0".parse().unwrap(), state.clone())
+                .await
+                .unwrap();
+            (addr, state)
+        }
+
+        /// Выполняет HTTP-запрос и возвращает статус и JSON-тело ответа.
+        async fn request(
+            addr: SocketAddr,
+            method: &str,
+            path: &str,
+            token: Option<&str>,
+            body: Option<&str>,
+        ) -> (u16, JsonValue) {
+            let mut req =
+                format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n");
+            if let Some(token) = token {
+                req.push_str(&format!("Authorization: Bearer {token}\r\n"));
+            }
+            let body = body.unwrap_or_default();
+            if !body.is_empty() {
+                req.push_str("Content-Type: application/json\r\n");
+            }
+            req.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(req.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+
+            let status = response[9..12].parse().unwrap();
+            let (_, body) = response.split_once("\r\n\r\n").unwrap();
+            (
+                status,
+                serde_json::from_str(body).unwrap_or(JsonValue::Null),
+            )
+        }
+
+        /// Тест проверяет, что запросы без токена или с неверным токеном
+        /// отклоняются.
+        #[tokio::test]
+        async fn test_requires_bearer_token() {
+            let (addr, _) = start().await;
+
+            let (status, body) = request(addr, "GET", "/api/v1/info", None, None).await;
+            assert_eq!(status, 401);
+            assert_eq!(body["error"], "missing or invalid bearer token");
+
+            let (status, _) = request(addr, "GET", "/api/v1/info", Some("wrong"), None).await;
+            assert_eq!(status, 401);
+
+            let (status, _) = request(addr, "GET", "/api/v1/info", Some(TOKEN), None).await;
+            assert_eq!(status, 200);
+        }
+
+        /// Тест проверяет `info` и очистку базы через `command/flush`.
+        #[tokio::test]
+        async fn test_info_and_flush() {
+            let (addr, state) = start().await;
+            state
+                .engine
+                .set(&Sds::from_str("key"), Value::Str(Sds::from_str("value")))
+                .unwrap();
+
+            let (status, info) = request(addr, "GET", "/api/v1/info", Some(TOKEN), None).await;
+            assert_eq!(status, 200);
+            assert_eq!(info["server"]["version"], env!("CARGO_PKG_VERSION"));
+            assert_eq!(info["keyspace"]["db0"]["keys"], 1);
+            assert_eq!(info["clients"]["active_connections"], 0);
+
+            let (status, body) =
+                request(addr, "POST", "/api/v1/command/flush", Some(TOKEN), None).await;
+            assert_eq!(status, 200);
+            assert_eq!(body["ok"], true);
+            assert_eq!(state.engine.dbsize().unwrap(), 0);
+        }
+
+        /// Тест проверяет список соединений и их закрытие по ID.
+        #[tokio::test]
+        async fn test_list_and_kill_clients() {
+            let (addr, state) = start().await;
+            let (id, info) = state.registry.register("127.0.0.1:5000".parse().unwrap());
+
+            let (status, clients) =
+                request(addr, "GET", "/api/v1/clients", Some(TOKEN), None).await;
+            assert_eq!(status, 200);
+            assert_eq!(clients[0]["connection_id"], id);
+            assert_eq!(clients[0]["client_addr"], "127.0.0.1:5000");
+
+            let killed = info.kill_signal.notified();
+            let path = format!("/api/v1/clients/{id}");
+            let (status, body) = request(addr, "DELETE", &path, Some(TOKEN), None).await;
+            assert_eq!(status, 200);
+            assert_eq!(body["killed"], 1);
+            killed.await;
+
+            let (status, _) =
+                request(addr, "DELETE", "/api/v1/clients/9999", Some(TOKEN), None).await;
+            assert_eq!(status, 404);
+        }
+
+        /// Тест проверяет изменение параметров и чтение журнала медленных
+        /// команд.
+        #[tokio::test]
+        async fn test_config_and_slowlog() {
+            let (addr, state) = start().await;
+
+            let body = r#"{"slowlog-log-slower-than": 0, "slowlog-max-len": "8"}"#;
+            let (status, _) =
+                request(addr, "POST", "/api/v1/config", Some(TOKEN), Some(body)).await;
+            assert_eq!(status, 200);
+            assert_eq!(state.admin.slowlog().threshold_us(), 0);
+
+            let (status, body) = request(
+                addr,
+                "POST",
+                "/api/v1/config",
+                Some(TOKEN),
+                Some(r#"{"no-such-param": "1"}"#),
+            )
+            .await;
+            assert_eq!(status, 400);
+            assert!(body["error"].is_string());
+
+            state.admin.slowlog().record(
+                std::time::Duration::from_millis(1),
+                || vec!["GET".into(), "key".into()],
+                "127.0.0.1:5000".parse().unwrap(),
+            );
+            let (status, entries) =
+                request(addr, "GET", "/api/v1/slowlog?count=5", Some(TOKEN), None).await;
+            assert_eq!(status, 200);
+            assert_eq!(entries[0]["args"][0], "GET");
+            assert_eq!(entries[0]["client_addr"], "127.0.0.1:5000");
+        }
+    }
+}
@@ -1,13 +1,31 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
-use crate::network::connection_registry::ConnectionRegistry;
+use config::ConfigError;
+
+use crate::{
+    command_registry::{builtin_commands, CommandFilter, CommandTable, GetKeysError},
+    network::{
+        connection_registry::{ClientSelector, ConnectionRegistry},
+        connection_state::{ClientKind, ConnectionSnapshot},
+        monitor::Monitor,
+        slowlog::SlowLog,
+    },
+    zsp::{ZspEncoder, ZspFrame},
+    RuntimeConfig, Sds, SlowLogEntry, Value,
+};
 
 /// Административные команды для управления и инспекции соединений.
 ///
-/// Оборачивает `ConnectionRegistry` и предоставляет текстовые обработчики,
-/// возвращающие строки в ZSP формате.
+/// Оборачивает `ConnectionRegistry`, `RuntimeConfig`, `SlowLog`, `Monitor` и
+/// таблицу команд и предоставляет текстовые обработчики, возвращающие строки
+/// в ZSP формате.
+#[derive(Debug)]
 pub struct AdminCommands {
     registry: Arc<ConnectionRegistry>,
+    runtime: RuntimeConfig,
+    slowlog: SlowLog,
+    monitor: Monitor,
+    commands: Arc<CommandTable>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -20,55 +38,114 @@ impl AdminCommands {
     /// # Возвращает
     /// - `Self` — инициализированный обработчик команд
     pub fn new(registry: Arc<ConnectionRegistry>) -> Self {
-        Self { registry }
+        Self::with_runtime_config(registry, RuntimeConfig::default())
     }
 
-    /// Возвращает список подключённых клиентов в ZSP-подобном формате.
+    /// Создаёт обработчик, который читает и изменяет параметры `runtime`
+    /// через `CONFIG GET`/`CONFIG SET`.
     ///
-    /// Формат совпадает с ожиданием клиентской команды `CLIENT LIST`.
+    /// Журнал медленных команд создаётся по параметрам `slowlog-*` из
+    /// `runtime` и обновляется при их изменении через `CONFIG SET`.
     ///
     /// # Возвращает
-    /// - `String` — текстовый ответ: количество элементов и последовательность
-    ///   bulk-строк с информацией о каждом соединении.
-    pub fn handle_client_list(&self) -> String {
-        let snapshots = self.registry.all_snapshots();
-
-        if snapshots.is_empty() {
-            return "*0\r\n".to_string();
+    /// - `Self` — инициализированный обработчик команд
+    pub fn with_runtime_config(
+        registry: Arc<ConnectionRegistry>,
+        runtime: RuntimeConfig,
+    ) -> Self {
+        let slowlog = SlowLog::from_params(&runtime.snapshot());
+        Self {
+            registry,
+            runtime,
+            slowlog,
+            monitor: Monitor::default(),
+            commands: Arc::new(builtin_commands().clone()),
         }
+    }
 
-        let mut response = format!("*{}\r\n", snapshots.len());
+    /// Заменяет рассылку `MONITOR` (например, на канал другой ёмкости).
+    pub fn with_monitor(
+        mut self,
+        monitor: Monitor,
+    ) -> Self {
+        self.monitor = monitor;
+        self
+    }
 
-        for snapshot in snapshots {
-            let info = format!(
-                "id={} addr={} state={} uptime={} idle={} cmd={} sent={} recv={}{}",
-                snapshot.connection_id,
-                snapshot.client_addr,
-                snapshot.state,
-                snapshot.uptime_secs,
-                snapshot.idle_secs,
-                snapshot.commands_processed,
-                snapshot.bytes_sent,
-                snapshot.bytes_received,
-                snapshot
-                    .username
-                    .as_ref()
-                    .map(|u| format!(" user={u}"))
-                    .unwrap_or_default()
-            );
-            response.push_str(&format!("${}\r\n{}\r\n", info.len(), info));
-        }
+    /// Заменяет таблицу команд, которую описывает `COMMAND` (например,
+    /// дополненную командами модулей).
+    pub fn with_commands(
+        mut self,
+        commands: Arc<CommandTable>,
+    ) -> Self {
+        self.commands = commands;
+        self
+    }
 
-        response
+    /// Возвращает таблицу команд, которую описывает `COMMAND`.
+    pub fn commands(&self) -> &CommandTable {
+        &self.commands
     }
 
-    /// Возвращает подробную информацию по конкретному соединению.
+    /// Возвращает параметры времени выполнения, изменяемые `CONFIG SET`.
+    pub fn runtime(&self) -> &RuntimeConfig {
+        &self.runtime
+    }
+
+    /// Возвращает журнал медленных команд, общий для всех соединений.
+    pub fn slowlog(&self) -> &SlowLog {
+        &self.slowlog
+    }
+
+    /// Возвращает рассылку `MONITOR`, общую для всех соединений.
+    pub fn monitor(&self) -> &Monitor {
+        &self.monitor
+    }
+
+    /// Возвращает список подключённых клиентов (`CLIENT LIST`).
+    ///
+    /// Соединения фильтруются по типу `kind` и, если `ids` не пуст, по
+    /// идентификаторам.
+    ///
+    /// # Возвращает
+    /// - `String` — bulk-строка, по одной строке формата `redis-cli` на
+    ///   соединение
+    pub fn handle_client_list(
+        &self,
+        kind: Option<ClientKind>,
+        ids: &[u32],
+    ) -> String {
+        let lines: String = self
+            .registry
+            .list_all()
+            .iter()
+            .filter(|snapshot| kind.is_none_or(|kind| snapshot.kind == kind))
+            .filter(|snapshot| ids.is_empty() || ids.contains(&snapshot.connection_id))
+            .map(client_line)
+            .collect();
+
+        format!("${}\r\n{}\r\n", lines.len(), lines)
+    }
+
+    /// Возвращает ID соединения (`CLIENT ID`).
     ///
-    /// Если соединение найдено — возвращает многострочный human-readable блок,
-    /// иначе возвращает ошибочный ответ.
+    /// # Возвращает
+    /// - `String` — integer-ответ с ID
+    /// - `-ERR` строка при отсутствии соединения
+    pub fn handle_client_id(
+        &self,
+        connection_id: u32,
+    ) -> String {
+        match self.registry.get(connection_id) {
+            Some(info) => format!(":{}\r\n", info.metadata.read().connection_id),
+            None => "-ERR Connection not found\r\n".to_string(),
+        }
+    }
+
+    /// Возвращает информацию о соединении (`CLIENT INFO`).
     ///
     /// # Возвращает
-    /// - `String` — bulk-ответ с деталями
+    /// - `String` — bulk-строка в формате одной строки `CLIENT LIST`
     /// - `-ERR` строка при отсутствии соединения
     pub fn handle_client_info(
         &self,
@@ -76,36 +153,14 @@ impl AdminCommands {
     ) -> String {
         match self.registry.get_snapshot(connection_id) {
             Some(snapshot) => {
-                let info = format!(
-                    "Connection ID: {}\n\
-                             Client Address: {}\n\
-                             State: {}\n\
-                             Uptime: {} seconds\n\
-                             Idle Time: {} seconds\n\
-                             Commands Processed: {}\n\
-                             Bytes Sent: {}\n\
-                             Bytes Received: {}\n\
-                             Username: {}",
-                    snapshot.connection_id,
-                    snapshot.client_addr,
-                    snapshot.state,
-                    snapshot.uptime_secs,
-                    snapshot.idle_secs,
-                    snapshot.commands_processed,
-                    snapshot.bytes_sent,
-                    snapshot.bytes_received,
-                    snapshot.username.unwrap_or_else(|| "N/A".to_string())
-                );
-
+                let info = client_line(&snapshot);
                 format!("${}\r\n{}\r\n", info.len(), info)
             }
             None => "-ERR Connection not found\r\n".to_string(),
         }
     }
 
-    /// Возвращает имя пользователя, связанное с соединением.
-    ///
-    /// Если имя задано — возвращается bulk-строка с именем, иначе ZSP `null`.
+    /// Возвращает имя соединения, заданное через `CLIENT SETNAME`.
     ///
     /// # Возвращает
     /// - `String` — bulk с именем, `"$-1\r\n"` если имя отсутствует
@@ -114,18 +169,113 @@ impl AdminCommands {
         &self,
         connection_id: u32,
     ) -> String {
-        match self.registry.get_snapshot(connection_id) {
-            Some(snapshot) => {
-                if let Some(username) = snapshot.username {
-                    format!("${}\r\n{}\r\n", username.len(), username)
-                } else {
-                    "$-1\r\n".to_string()
-                }
+        match self.registry.get(connection_id) {
+            Some(info) => match info.name() {
+                Some(name) => format!("${}\r\n{}\r\n", name.len(), name),
+                None => "$-1\r\n".to_string(),
+            },
+            None => "-ERR Connection not found\r\n".to_string(),
+        }
+    }
+
+    /// Задаёт имя соединения (`CLIENT SETNAME`).
+    ///
+    /// Имя может содержать только видимые ASCII-символы без пробелов.
+    ///
+    /// # Возвращает
+    /// - `+OK` при успехе
+    /// - `-ERR` если имя недопустимо или соединения нет
+    pub fn handle_client_setname(
+        &self,
+        connection_id: u32,
+        name: &str,
+    ) -> String {
+        if !name.bytes().all(|b| b.is_ascii_graphic()) {
+            return "-ERR Client names cannot contain spaces, newlines or special characters.\r\n"
+                .to_string();
+        }
+        match self.registry.get(connection_id) {
+            Some(info) => {
+                info.set_name((!name.is_empty()).then(|| name.to_string()));
+                "+OK\r\n".to_string()
             }
             None => "-ERR Connection not found\r\n".to_string(),
         }
     }
 
+    /// Включает или выключает защиту соединения от вытеснения
+    /// (`CLIENT NO-EVICT on|off`).
+    ///
+    /// # Возвращает
+    /// - `+OK` при успехе
+    /// - `-ERR` если соединения нет
+    pub fn handle_client_no_evict(
+        &self,
+        connection_id: u32,
+        no_evict: bool,
+    ) -> String {
+        match self.registry.get(connection_id) {
+            Some(info) => {
+                info.set_no_evict(no_evict);
+                "+OK\r\n".to_string()
+            }
+            None => "-ERR Connection not found\r\n".to_string(),
+        }
+    }
+
+    /// Переводит соединение в режим `MONITOR`.
+    ///
+    /// Подписку на поток команд оформляет обработчик соединения, увидев
+    /// флаг режима; до выхода через `RESET` другие команды отклоняются.
+    ///
+    /// # Возвращает
+    /// - `+OK` при успехе
+    /// - `-ERR` если соединения нет
+    pub fn handle_monitor(
+        &self,
+        connection_id: u32,
+    ) -> String {
+        match self.registry.get(connection_id) {
+            Some(info) => {
+                info.set_monitor(true);
+                "+OK\r\n".to_string()
+            }
+            None => "-ERR Connection not found\r\n".to_string(),
+        }
+    }
+
+    /// Сбрасывает состояние соединения (`RESET`): выводит его из режима
+    /// `MONITOR` и снимает защиту от вытеснения.
+    ///
+    /// # Возвращает
+    /// - `+RESET` при успехе
+    /// - `-ERR` если соединения нет
+    pub fn handle_reset(
+        &self,
+        connection_id: u32,
+    ) -> String {
+        match self.registry.get(connection_id) {
+            Some(info) => {
+                info.set_monitor(false);
+                info.set_no_evict(false);
+                info.transaction().discard();
+                "+RESET\r\n".to_string()
+            }
+            None => "-ERR Connection not found\r\n".to_string(),
+        }
+    }
+
+    /// Закрывает соединения, подходящие под `selector` (`CLIENT KILL`).
+    ///
+    /// # Возвращает
+    /// - `String` — integer-ответ с числом закрытых соединений
+    pub fn handle_client_kill(
+        &self,
+        selector: ClientSelector,
+    ) -> String {
+        format!(":{}\r\n", self.registry.kill(selector))
+    }
+
     /// Возвращает глобальные статистики сервера в виде bulk-ответа.
     ///
     /// # Возвращает
@@ -196,11 +346,212 @@ impl AdminCommands {
         response
     }
 
-    /// Разбирает и выполняет административную команду.
+    /// Возвращает параметры, имена которых соответствуют glob-шаблону.
     ///
-    /// Поддерживаемые вызовы:
-    /// - `CLIENT LIST`, `CLIENT COUNT`, `CLIENT INFO <id>`, `CLIENT GETNAME
-    ///   <id>`, `CLIENT BY IP <ip>`, `SERVER STATS`.
+    /// # Возвращает
+    /// - `String` — ZSP-массив чередующихся имён и значений параметров
+    pub fn handle_config_get(
+        &self,
+        pattern: &str,
+    ) -> String {
+        let params = self.runtime.get_matching(pattern);
+
+        let mut response = format!("*{}\r\n", params.len() * 2);
+        for (name, value) in params {
+            response.push_str(&format!("${}\r\n{}\r\n", name.len(), name));
+            response.push_str(&format!("${}\r\n{}\r\n", value.len(), value));
+        }
+
+        response
+    }
+
+    /// Атомарно изменяет параметры, переданные парами `имя значение`.
+    ///
+    /// # Возвращает
+    /// - `+OK` при успехе
+    /// - `-ERR` строка, если число аргументов нечётное или хотя бы один
+    ///   параметр неизвестен либо имеет недопустимое значение
+    pub fn handle_config_set(
+        &self,
+        args: &[&str],
+    ) -> String {
+        if args.is_empty() || !args.len().is_multiple_of(2) {
+            return "-ERR wrong number of arguments for 'config|set' command\r\n".to_string();
+        }
+
+        let changes: Vec<(&str, &str)> = args.chunks(2).map(|c| (c[0], c[1])).collect();
+        match self.set_config(&changes) {
+            Ok(()) => "+OK\r\n".to_string(),
+            Err(e) => format!("-ERR {e}\r\n"),
+        }
+    }
+
+    /// Атомарно применяет изменения параметров `changes` и перенастраивает
+    /// журнал медленных команд.
+    ///
+    /// # Возвращает
+    /// - `Ok(())` при успехе
+    /// - `Err(ConfigError)`, если хотя бы один параметр неизвестен или имеет
+    ///   недопустимое значение; в этом случае ничего не меняется
+    pub fn set_config(
+        &self,
+        changes: &[(&str, &str)],
+    ) -> Result<(), ConfigError> {
+        self.runtime.set(changes)?;
+        self.slowlog.apply_params(&self.runtime.snapshot());
+        Ok(())
+    }
+
+    /// Обнуляет счётчики статистики всех соединений.
+    ///
+    /// # Возвращает
+    /// - `String` — `+OK`
+    pub fn handle_config_resetstat(&self) -> String {
+        self.registry.reset_stats();
+        "+OK\r\n".to_string()
+    }
+
+    /// Возвращает до `count` последних записей журнала медленных команд.
+    ///
+    /// # Возвращает
+    /// - `String` — ZSP-массив записей `[id, timestamp, duration_us, [args],
+    ///   addr]`
+    pub fn handle_slowlog_get(
+        &self,
+        count: Option<usize>,
+    ) -> String {
+        let entries = self.slowlog.get(count);
+        encode_value(Value::Array(
+            entries.iter().map(SlowLogEntry::to_value).collect(),
+        ))
+    }
+
+    /// Возвращает число записей журнала медленных команд.
+    ///
+    /// # Возвращает
+    /// - `String` — integer-ответ `:<len>\r\n`
+    pub fn handle_slowlog_len(&self) -> String {
+        format!(":{}\r\n", self.slowlog.len())
+    }
+
+    /// Очищает журнал медленных команд.
+    ///
+    /// # Возвращает
+    /// - `String` — `+OK`
+    pub fn handle_slowlog_reset(&self) -> String {
+        self.slowlog.reset();
+        "+OK\r\n".to_string()
+    }
+
+    /// Возвращает число команд (`COMMAND COUNT`).
+    ///
+    /// # Возвращает
+    /// - `String` — integer-ответ `:<count>\r\n`
+    pub fn handle_command_count(&self) -> String {
+        format!(":{}\r\n", self.commands.len())
+    }
+
+    /// Возвращает описания команд `names` (`COMMAND INFO`); без имён —
+    /// описания всех команд.
+    ///
+    /// # Возвращает
+    /// - `String` — ZSP-массив кортежей (имя, арность, флаги, первый ключ,
+    ///   последний ключ, шаг); `nil` на месте неизвестной команды
+    pub fn handle_command_info(
+        &self,
+        names: &[&str],
+    ) -> String {
+        let infos = if names.is_empty() {
+            self.commands.iter().map(|spec| spec.to_value()).collect()
+        } else {
+            names
+                .iter()
+                .map(|name| {
+                    self.commands
+                        .get(name)
+                        .map_or(Value::Null, |spec| spec.to_value())
+                })
+                .collect()
+        };
+        encode_value(Value::Array(infos))
+    }
+
+    /// Возвращает описания команд `names` (`COMMAND DOCS`); без имён —
+    /// описания всех команд. Неизвестные команды пропускаются.
+    ///
+    /// # Возвращает
+    /// - `String` — ZSP-массив чередующихся имён и описаний
+    pub fn handle_command_docs(
+        &self,
+        names: &[&str],
+    ) -> String {
+        let specs: Vec<_> = if names.is_empty() {
+            self.commands.iter().collect()
+        } else {
+            names
+                .iter()
+                .filter_map(|name| self.commands.get(name))
+                .collect()
+        };
+        encode_value(Value::Array(
+            specs
+                .into_iter()
+                .flat_map(|spec| {
+                    [
+                        Value::Str(Sds::from_str(&spec.name)),
+                        Value::Str(Sds::from_str(&spec.doc)),
+                    ]
+                })
+                .collect(),
+        ))
+    }
+
+    /// Возвращает ключи вызова `args` (`COMMAND GETKEYS command arg ...`).
+    ///
+    /// # Возвращает
+    /// - `String` — ZSP-массив ключей
+    /// - `-ERR` если команда неизвестна, арность не соблюдена или у команды нет
+    ///   ключей
+    pub fn handle_command_getkeys(
+        &self,
+        args: &[&str],
+    ) -> String {
+        match self.commands.get_keys(args) {
+            Ok(keys) => encode_value(Value::Array(
+                keys.into_iter()
+                    .map(|key| Value::Str(Sds::from_str(key)))
+                    .collect(),
+            )),
+            Err(GetKeysError::UnknownCommand) => "-ERR Invalid command specified\r\n".to_string(),
+            Err(GetKeysError::WrongArity) => {
+                "-ERR Invalid number of arguments specified for command\r\n".to_string()
+            }
+            Err(GetKeysError::NoKeys) => "-ERR The command has no key arguments\r\n".to_string(),
+        }
+    }
+
+    /// Возвращает имена команд, подходящих под `filter` (`COMMAND LIST`).
+    ///
+    /// # Возвращает
+    /// - `String` — ZSP-массив имён, упорядоченных по алфавиту
+    pub fn handle_command_list(
+        &self,
+        filter: Option<&CommandFilter>,
+    ) -> String {
+        encode_value(Value::Array(
+            self.commands
+                .list(filter)
+                .into_iter()
+                .map(|name| Value::Str(Sds::from_str(name)))
+                .collect(),
+        ))
+    }
+
+    /// Разбирает и выполняет административную команду вне контекста
+    /// соединения.
+    ///
+    /// Команды, относящиеся к текущему соединению (`CLIENT ID`, `CLIENT
+    /// SETNAME` и т.п.), возвращают ошибку; см. [`AdminCommands::execute_for`].
     ///
     /// # Возвращает
     /// - `Option<String>` — `Some(response)` если команда распознана и
@@ -209,37 +560,332 @@ impl AdminCommands {
     pub fn execute(
         &self,
         parts: &[&str],
+    ) -> Option<String> {
+        self.dispatch(None, parts)
+    }
+
+    /// Разбирает и выполняет административную команду от имени соединения
+    /// `connection_id`.
+    ///
+    /// Поддерживаемые вызовы:
+    /// - `CLIENT ID`, `CLIENT INFO [id]`, `CLIENT SETNAME <name>`, `CLIENT
+    ///   GETNAME [id]`, `CLIENT NO-EVICT on|off`.
+    /// - `CLIENT LIST [TYPE normal|pubsub|replica] [ID id ...]`, `CLIENT
+    ///   COUNT`, `CLIENT BY IP <ip>`, `SERVER STATS`.
+    /// - `CLIENT KILL [ID id] [ADDR ip:port] [USER username] [SKIPME yes|no]`,
+    ///   `CLIENT KILL ip:port`.
+    /// - `CONFIG GET <pattern>`, `CONFIG SET <param> <value> [...]`, `CONFIG
+    ///   RESETSTAT`.
+    /// - `SLOWLOG GET [count]`, `SLOWLOG LEN`, `SLOWLOG RESET`.
+    /// - `COMMAND [COUNT | INFO [name ...] | DOCS [name ...] | GETKEYS command
+    ///   [arg ...] | LIST [FILTERBY MODULE module | ACLCAT category | PATTERN
+    ///   pattern]]`.
+    ///
+    /// # Возвращает
+    /// - `Option<String>` — `Some(response)` если команда распознана и
+    ///   обработана,
+    /// - `None` если команда не относится к административным.
+    pub fn execute_for(
+        &self,
+        connection_id: u32,
+        parts: &[&str],
+    ) -> Option<String> {
+        self.dispatch(Some(connection_id), parts)
+    }
+
+    /// Выполняет подкоманду `CLIENT`; `current` — ID текущего соединения.
+    fn execute_client(
+        &self,
+        current: Option<u32>,
+        sub: &str,
+        args: &[&str],
+    ) -> String {
+        let no_connection = || "-ERR No current connection\r\n".to_string();
+        let parse_id = |id: &str, f: &dyn Fn(u32) -> String| match id.parse::<u32>() {
+            Ok(id) => f(id),
+            Err(_) => "-ERR Invalid connection ID\r\n".to_string(),
+        };
+
+        match (sub.to_uppercase().as_str(), args) {
+            ("LIST", args) => match parse_client_list_args(args) {
+                Ok((kind, ids)) => self.handle_client_list(kind, &ids),
+                Err(e) => e,
+            },
+            ("COUNT", []) => self.handle_client_count(),
+            ("ID", []) => current.map_or_else(no_connection, |id| self.handle_client_id(id)),
+            ("INFO", []) => current.map_or_else(no_connection, |id| self.handle_client_info(id)),
+            ("INFO", [id]) => parse_id(id, &|id| self.handle_client_info(id)),
+            ("GETNAME", []) => {
+                current.map_or_else(no_connection, |id| self.handle_client_getname(id))
+            }
+            ("GETNAME", [id]) => parse_id(id, &|id| self.handle_client_getname(id)),
+            ("SETNAME", [name]) => {
+                current.map_or_else(no_connection, |id| self.handle_client_setname(id, name))
+            }
+            ("NO-EVICT", [flag]) => {
+                let no_evict = match flag.to_lowercase().as_str() {
+                    "on" => true,
+                    "off" => false,
+                    _ => return "-ERR syntax error\r\n".to_string(),
+                };
+                current.map_or_else(no_connection, |id| {
+                    self.handle_client_no_evict(id, no_evict)
+                })
+            }
+            // Старая форма: единственное соединение, включая текущее — оно
+            // закроется после отправки ответа.
+            ("KILL", [addr]) => match addr.parse::<SocketAddr>() {
+                Ok(addr) => {
+                    let selector = ClientSelector {
+                        addr: Some(addr),
+                        ..Default::default()
+                    };
+                    match self.registry.kill(selector) {
+                        0 => "-ERR No such client\r\n".to_string(),
+                        _ => "+OK\r\n".to_string(),
+                    }
+                }
+                Err(_) => "-ERR Invalid client address\r\n".to_string(),
+            },
+            ("KILL", args) if !args.is_empty() => match parse_client_kill_args(args, current) {
+                Ok(selector) => self.handle_client_kill(selector),
+                Err(e) => e,
+            },
+            ("BY", [by, ip]) if by.eq_ignore_ascii_case("IP") => self.handle_client_by_ip(ip),
+            _ => format!(
+                "-ERR unknown subcommand or wrong number of arguments for 'CLIENT {sub}'\r\n"
+            ),
+        }
+    }
+
+    /// Выполняет подкоманду `COMMAND`; без подкоманды возвращает описания
+    /// всех команд.
+    fn execute_command(
+        &self,
+        args: &[&str],
+    ) -> String {
+        let Some((sub, rest)) = args.split_first() else {
+            return self.handle_command_info(&[]);
+        };
+        match (sub.to_uppercase().as_str(), rest) {
+            ("COUNT", []) => self.handle_command_count(),
+            ("INFO", names) => self.handle_command_info(names),
+            ("DOCS", names) => self.handle_command_docs(names),
+            ("GETKEYS", args) if !args.is_empty() => self.handle_command_getkeys(args),
+            ("LIST", args) => match parse_command_list_args(args) {
+                Ok(filter) => self.handle_command_list(filter.as_ref()),
+                Err(e) => e,
+            },
+            _ => format!(
+                "-ERR unknown subcommand or wrong number of arguments for 'COMMAND {sub}'\r\n"
+            ),
+        }
+    }
+
+    /// Разбирает и выполняет административную команду; `current` — ID
+    /// текущего соединения, если он известен.
+    fn dispatch(
+        &self,
+        current: Option<u32>,
+        parts: &[&str],
     ) -> Option<String> {
         if parts.is_empty() {
             return None;
         }
 
         match (parts[0].to_uppercase().as_str(), parts.get(1)) {
-            ("CLIENT", Some(&"LIST")) => Some(self.handle_client_list()),
-            ("CLIENT", Some(&"COUNT")) => Some(self.handle_client_count()),
-            ("CLIENT", Some(&"INFO")) if parts.len() >= 3 => {
-                if let Ok(id) = parts[2].parse::<u32>() {
-                    Some(self.handle_client_info(id))
-                } else {
-                    Some("-ERR Invalid connection ID\r\n".to_string())
-                }
+            ("CLIENT", Some(sub)) => Some(self.execute_client(current, sub, &parts[2..])),
+            ("SERVER", Some(&"STATS")) => Some(self.handle_server_stats()),
+            ("COMMAND", _) => Some(self.execute_command(&parts[1..])),
+            ("MONITOR", None) => Some(match current {
+                Some(id) => self.handle_monitor(id),
+                None => "-ERR No current connection\r\n".to_string(),
+            }),
+            ("RESET", None) => Some(match current {
+                Some(id) => self.handle_reset(id),
+                None => "-ERR No current connection\r\n".to_string(),
+            }),
+            ("CONFIG", Some(sub)) => Some(match sub.to_uppercase().as_str() {
+                "GET" if parts.len() == 3 => self.handle_config_get(parts[2]),
+                "SET" => self.handle_config_set(&parts[2..]),
+                "RESETSTAT" if parts.len() == 2 => self.handle_config_resetstat(),
+                _ => format!(
+                    "-ERR unknown subcommand or wrong number of arguments for 'CONFIG {sub}'\r\n"
+                ),
+            }),
+            ("SLOWLOG", Some(sub)) => Some(match (sub.to_uppercase().as_str(), parts.len()) {
+                ("GET", 2) => self.handle_slowlog_get(None),
+                ("GET", 3) => match parts[2].parse::<usize>() {
+                    Ok(count) => self.handle_slowlog_get(Some(count)),
+                    Err(_) => "-ERR value is out of range, must be positive\r\n".to_string(),
+                },
+                ("LEN", 2) => self.handle_slowlog_len(),
+                ("RESET", 2) => self.handle_slowlog_reset(),
+                _ => format!(
+                    "-ERR unknown subcommand or wrong number of arguments for 'SLOWLOG {sub}'\r\n"
+                ),
+            }),
+            _ => None, // Не админ-команда, пропускаем
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Форматирует соединение одной строкой `CLIENT LIST`, совместимой с
+/// `redis-cli`.
+fn client_line(snapshot: &ConnectionSnapshot) -> String {
+    let mut flags = String::new();
+    match snapshot.kind {
+        ClientKind::Normal => {}
+        ClientKind::PubSub => flags.push('P'),
+        ClientKind::Replica => flags.push('S'),
+    }
+    if snapshot.monitor {
+        flags.push('M');
+    }
+    if snapshot.no_evict {
+        flags.push('e');
+    }
+    if flags.is_empty() {
+        flags.push('N');
+    }
+
+    format!(
+        "id={} addr={} name={} age={} idle={} flags={} db={} tot-cmds={} tot-net-in={} \
+         tot-net-out={} user={}\n",
+        snapshot.connection_id,
+        snapshot.client_addr,
+        snapshot.name.as_deref().unwrap_or_default(),
+        snapshot.age_secs,
+        snapshot.idle_secs,
+        flags,
+        snapshot.db_index,
+        snapshot.commands_processed,
+        snapshot.bytes_received,
+        snapshot.bytes_sent,
+        snapshot.username.as_deref().unwrap_or("default"),
+    )
+}
+
+/// Разбирает аргументы `CLIENT LIST [TYPE type] [ID id ...]`.
+///
+/// # Возвращает
+/// - `(тип, список ID)` или готовый `-ERR` ответ
+fn parse_client_list_args(args: &[&str]) -> Result<(Option<ClientKind>, Vec<u32>), String> {
+    let syntax_error = || "-ERR syntax error\r\n".to_string();
+    let mut kind = None;
+    let mut ids = Vec::new();
+    let mut rest = args;
+
+    while let Some((option, tail)) = rest.split_first() {
+        match option.to_uppercase().as_str() {
+            "TYPE" => {
+                let (name, tail) = tail.split_first().ok_or_else(syntax_error)?;
+                kind = Some(
+                    ClientKind::parse(name)
+                        .ok_or_else(|| format!("-ERR Unknown client type '{name}'\r\n"))?,
+                );
+                rest = tail;
             }
-            ("CLIENT", Some(&"GETNAME")) if parts.len() >= 3 => {
-                if let Ok(id) = parts[2].parse::<u32>() {
-                    Some(self.handle_client_getname(id))
-                } else {
-                    Some("-ERR Invalid connection ID\r\n".to_string())
+            "ID" if !tail.is_empty() => {
+                for id in tail {
+                    ids.push(
+                        id.parse::<u32>()
+                            .map_err(|_| "-ERR Invalid client ID\r\n".to_string())?,
+                    );
                 }
+                rest = &[];
+            }
+            _ => return Err(syntax_error()),
+        }
+    }
+
+    Ok((kind, ids))
+}
+
+/// Разбирает фильтры `CLIENT KILL [ID id] [ADDR ip:port] [USER username]
+/// [SKIPME yes|no]`.
+///
+/// По умолчанию текущее соединение `current` не закрывается (`SKIPME yes`).
+///
+/// # Возвращает
+/// - `ClientSelector` или готовый `-ERR` ответ
+fn parse_client_kill_args(
+    args: &[&str],
+    current: Option<u32>,
+) -> Result<ClientSelector, String> {
+    let syntax_error = || "-ERR syntax error\r\n".to_string();
+    if !args.len().is_multiple_of(2) {
+        return Err(syntax_error());
+    }
+
+    let mut selector = ClientSelector {
+        skip: current,
+        ..Default::default()
+    };
+    for pair in args.chunks(2) {
+        let (option, value) = (pair[0], pair[1]);
+        match option.to_uppercase().as_str() {
+            "ID" => {
+                let id = value
+                    .parse::<u32>()
+                    .map_err(|_| "-ERR client-id should be greater than 0\r\n".to_string())?;
+                selector.id = Some(id);
             }
-            ("CLIENT", Some(&"BY")) if parts.len() >= 4 && parts[2].to_uppercase() == "IP" => {
-                Some(self.handle_client_by_ip(parts[3]))
+            "ADDR" => {
+                let addr = value
+                    .parse::<SocketAddr>()
+                    .map_err(|_| "-ERR Invalid client address\r\n".to_string())?;
+                selector.addr = Some(addr);
+            }
+            "USER" => selector.user = Some(value.to_string()),
+            "SKIPME" => match value.to_lowercase().as_str() {
+                "yes" => selector.skip = current,
+                "no" => selector.skip = None,
+                _ => return Err(syntax_error()),
+            },
+            _ => return Err(syntax_error()),
+        }
+    }
+
+    if selector.id.is_none() && selector.addr.is_none() && selector.user.is_none() {
+        return Err(syntax_error());
+    }
+    Ok(selector)
+}
+
+/// Разбирает аргументы `COMMAND LIST [FILTERBY MODULE module | ACLCAT
+/// category | PATTERN pattern]`.
+///
+/// # Возвращает
+/// - фильтр (или `None` без `FILTERBY`) либо готовый `-ERR` ответ
+fn parse_command_list_args(args: &[&str]) -> Result<Option<CommandFilter>, String> {
+    match args {
+        [] => Ok(None),
+        [filterby, kind, value] if filterby.eq_ignore_ascii_case("FILTERBY") => {
+            match kind.to_uppercase().as_str() {
+                "MODULE" => Ok(Some(CommandFilter::Module(value.to_string()))),
+                "ACLCAT" => Ok(Some(CommandFilter::AclCategory(value.to_string()))),
+                "PATTERN" => Ok(Some(CommandFilter::Pattern(value.to_string()))),
+                _ => Err("-ERR syntax error\r\n".to_string()),
             }
-            ("SERVER", Some(&"STATS")) => Some(self.handle_server_stats()),
-            _ => None, // Не админ-команда, пропускаем
         }
+        _ => Err("-ERR syntax error\r\n".to_string()),
     }
 }
 
+/// Кодирует значение в ZSP-ответ.
+fn encode_value(value: Value) -> String {
+    ZspFrame::try_from(value)
+        .map_err(|e| e.to_string())
+        .and_then(|frame| ZspEncoder::encode(&frame).map_err(|e| e.to_string()))
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_else(|e| format!("-ERR {e}\r\n"))
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -276,10 +922,38 @@ mod tests {
         info1.record_command(100, 200);
         info2.record_command(200, 300);
 
-        let response = admin.handle_client_list();
-        assert!(response.starts_with("*2\r\n"));
-        assert!(response.contains(&format!("id={id1}")));
-        assert!(response.contains(&format!("id={id2}")));
+        let response = admin.handle_client_list(None, &[]);
+        assert!(response.starts_with('$'));
+        let lines: Vec<&str> = response.lines().skip(1).filter(|l| !l.is_empty()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!("id={id1} addr=127.0.0.1:1234 name= ")));
+        assert!(lines[1].starts_with(&format!("id={id2} ")));
+        assert!(lines[1].contains(" flags=N db=0 tot-cmds=1 tot-net-in=200 tot-net-out=300 "));
+        assert!(lines[1].ends_with("user=default"));
+
+        let response = admin
+            .execute(&["CLIENT", "LIST", "ID", &id2.to_string()])
+            .unwrap();
+        assert!(!response.contains(&format!("id={id1} ")));
+        assert!(response.contains(&format!("id={id2} ")));
+
+        let response = admin
+            .execute(&["client", "list", "type", "pubsub"])
+            .unwrap();
+        assert_eq!(response, "$0\r\n\r\n");
+        info1.set_kind(ClientKind::PubSub);
+        let response = admin
+            .execute(&["CLIENT", "LIST", "TYPE", "pubsub"])
+            .unwrap();
+        assert!(response.contains(&format!("id={id1} ")));
+        assert!(response.contains("flags=P "));
+
+        let response = admin
+            .execute(&["CLIENT", "LIST", "TYPE", "master"])
+            .unwrap();
+        assert!(response.starts_with("-ERR"));
+        let response = admin.execute(&["CLIENT", "LIST", "ID"]).unwrap();
+        assert!(response.starts_with("-ERR"));
     }
 
     #[test]
@@ -292,10 +966,77 @@ mod tests {
 
         info.record_command(100, 200);
 
-        let response = admin.handle_client_info(id);
-        assert!(response.contains("Connection ID:"));
-        assert!(response.contains("127.0.0.1:1234"));
-        assert!(response.contains("Commands Processed: 1"));
+        let response = admin.execute_for(id, &["CLIENT", "INFO"]).unwrap();
+        assert!(response.contains(&format!("id={id} addr=127.0.0.1:1234 ")));
+        assert!(response.contains("tot-cmds=1 "));
+        assert_eq!(
+            admin.execute(&["CLIENT", "INFO", &id.to_string()]),
+            Some(response)
+        );
+
+        let response = admin.execute(&["CLIENT", "INFO"]).unwrap();
+        assert!(response.starts_with("-ERR"));
+    }
+
+    #[test]
+    fn test_client_id_and_names() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = AdminCommands::new(registry.clone());
+
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let (id, info) = registry.register(addr);
+
+        assert_eq!(
+            admin.execute_for(id, &["CLIENT", "ID"]),
+            Some(format!(":{id}\r\n"))
+        );
+        assert_eq!(
+            admin.execute_for(id, &["CLIENT", "GETNAME"]),
+            Some("$-1\r\n".to_string())
+        );
+        assert_eq!(
+            admin.execute_for(id, &["CLIENT", "SETNAME", "worker-1"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert_eq!(info.name().as_deref(), Some("worker-1"));
+        assert_eq!(
+            admin.execute_for(id, &["client", "getname"]),
+            Some("$8\r\nworker-1\r\n".to_string())
+        );
+        assert!(admin
+            .handle_client_list(None, &[])
+            .contains(" name=worker-1 "));
+
+        let response = admin.handle_client_setname(id, "bad\nname");
+        assert!(response.starts_with("-ERR"));
+        assert_eq!(info.name().as_deref(), Some("worker-1"));
+    }
+
+    #[test]
+    fn test_client_no_evict() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = AdminCommands::new(registry.clone());
+
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let (id, info) = registry.register(addr);
+
+        assert_eq!(
+            admin.execute_for(id, &["CLIENT", "NO-EVICT", "on"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert!(info.is_no_evict());
+        assert!(admin.handle_client_info(id).contains(" flags=e "));
+
+        assert_eq!(
+            admin.execute_for(id, &["CLIENT", "NO-EVICT", "OFF"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert!(!info.is_no_evict());
+
+        let response = admin
+            .execute_for(id, &["CLIENT", "NO-EVICT", "maybe"])
+            .unwrap();
+        assert!(response.starts_with("-ERR"));
     }
 
     #[test]
@@ -340,4 +1081,226 @@ mod tests {
         let result = admin.execute(&["UNKNOWN"]);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_config_get_set() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let runtime = RuntimeConfig::default();
+        let admin = AdminCommands::with_runtime_config(registry, runtime.clone());
+
+        assert_eq!(
+            admin.execute(&["CONFIG", "SET", "read_timeout", "5", "log_level", "warn"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert_eq!(runtime.snapshot().read_timeout, 5);
+        assert_eq!(
+            admin.execute(&["config", "get", "read_*"]),
+            Some("*2\r\n$12\r\nread_timeout\r\n$1\r\n5\r\n".to_string())
+        );
+
+        let result = admin.execute(&["CONFIG", "SET", "read_timeout"]).unwrap();
+        assert!(result.starts_with("-ERR"));
+        let result = admin.execute(&["CONFIG", "SET", "no_such", "1"]).unwrap();
+        assert!(result.starts_with("-ERR"));
+        assert_eq!(
+            admin.execute(&["CONFIG", "GET", "nothing*"]),
+            Some("*0\r\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_config_resetstat() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = AdminCommands::new(registry.clone());
+
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+        let (_, info) = registry.register(addr);
+        info.record_command(100, 200);
+        info.record_error();
+
+        assert_eq!(
+            admin.execute(&["CONFIG", "RESETSTAT"]),
+            Some("+OK\r\n".to_string())
+        );
+
+        let stats = registry.global_stats();
+        assert_eq!(stats.active_connections, 1);
+        assert_eq!(stats.total_commands, 0);
+        assert_eq!(stats.total_bytes_sent, 0);
+        assert_eq!(stats.total_errors, 0);
+    }
+
+    #[test]
+    fn test_slowlog_commands() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = AdminCommands::new(registry);
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+
+        assert_eq!(
+            admin.execute(&["CONFIG", "SET", "slowlog-log-slower-than", "0"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert_eq!(admin.slowlog().threshold_us(), 0);
+
+        admin.slowlog().record(
+            std::time::Duration::from_micros(42),
+            || vec!["GET".to_string(), "k".to_string()],
+            addr,
+        );
+        assert_eq!(
+            admin.execute(&["SLOWLOG", "LEN"]),
+            Some(":1\r\n".to_string())
+        );
+
+        let response = admin.execute(&["slowlog", "get", "5"]).unwrap();
+        assert!(response.starts_with("*1\r\n*5\r\n:0\r\n"));
+        assert!(response.contains(":42\r\n"));
+        assert!(response.contains("GET"));
+        assert!(response.contains("127.0.0.1:1234"));
+
+        assert_eq!(
+            admin.execute(&["SLOWLOG", "RESET"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert_eq!(
+            admin.execute(&["SLOWLOG", "LEN"]),
+            Some(":0\r\n".to_string())
+        );
+        assert!(admin
+            .execute(&["SLOWLOG", "GET", "-1"])
+            .unwrap()
+            .starts_with("-ERR"));
+    }
+
+    #[test]
+    fn test_client_kill() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = AdminCommands::new(registry.clone());
+
+        let addr1: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:1002".parse().unwrap();
+        let (me, _) = registry.register(addr1);
+        let (other, _) = registry.register(addr2);
+
+        // Новая форма по умолчанию не трогает текущее соединение
+        assert_eq!(
+            admin.execute_for(me, &["CLIENT", "KILL", "ADDR", "127.0.0.1:1001"]),
+            Some(":0\r\n".to_string())
+        );
+        assert_eq!(
+            admin.execute_for(me, &["CLIENT", "KILL", "USER", "default"]),
+            Some(":1\r\n".to_string())
+        );
+        assert_eq!(
+            admin.execute_for(
+                me,
+                &["CLIENT", "KILL", "ID", &me.to_string(), "SKIPME", "no"]
+            ),
+            Some(":1\r\n".to_string())
+        );
+        assert_eq!(
+            admin.execute_for(me, &["client", "kill", "id", &other.to_string()]),
+            Some(":1\r\n".to_string())
+        );
+
+        // Старая форма
+        assert_eq!(
+            admin.execute_for(me, &["CLIENT", "KILL", "127.0.0.1:1002"]),
+            Some("+OK\r\n".to_string())
+        );
+        let response = admin
+            .execute_for(me, &["CLIENT", "KILL", "127.0.0.1:9"])
+            .unwrap();
+        assert!(response.starts_with("-ERR No such client"));
+
+        for args in [
+            &["CLIENT", "KILL", "ID"][..],
+            &["CLIENT", "KILL", "ID", "x"],
+            &["CLIENT", "KILL", "SKIPME", "no"],
+            &["CLIENT", "KILL", "NAME", "a"],
+            &["CLIENT", "KILL", "not-an-addr"],
+        ] {
+            let response = admin.execute_for(me, args).unwrap();
+            assert!(response.starts_with("-ERR"), "{args:?}: {response}");
+        }
+    }
+
+    #[test]
+    fn test_monitor_and_reset() {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = AdminCommands::new(registry.clone());
+
+        let addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let (id, info) = registry.register(addr);
+
+        assert_eq!(
+            admin.execute_for(id, &["MONITOR"]),
+            Some("+OK\r\n".to_string())
+        );
+        assert!(info.is_monitor());
+        info.set_no_evict(true);
+        let list = admin.execute(&["CLIENT", "LIST"]).unwrap();
+        assert!(list.contains(" flags=Me "), "{list}");
+
+        assert_eq!(
+            admin.execute_for(id, &["reset"]),
+            Some("+RESET\r\n".to_string())
+        );
+        assert!(!info.is_monitor());
+        assert!(!info.is_no_evict());
+
+        assert!(admin.execute(&["MONITOR"]).unwrap().starts_with("-ERR"));
+        assert_eq!(admin.execute(&["MONITOR", "x"]), None);
+    }
+
+    #[test]
+    fn test_command_introspection() {
+        let admin = AdminCommands::new(Arc::new(ConnectionRegistry::new()));
+        let count = admin.commands().len();
+
+        assert_eq!(
+            admin.execute(&["COMMAND", "COUNT"]),
+            Some(format!(":{count}\r\n"))
+        );
+        assert_eq!(
+            admin.execute(&["command", "info", "GET", "nope"]).unwrap(),
+            "*2\r\n*6\r\n+get\r\n:2\r\n*2\r\n+readonly\r\n+fast\r\n:1\r\n:1\r\n:1\r\n_\r\n"
+        );
+        assert!(admin
+            .execute(&["COMMAND"])
+            .unwrap()
+            .starts_with(&format!("*{count}\r\n")));
+
+        let docs = admin.execute(&["COMMAND", "DOCS", "ping", "nope"]).unwrap();
+        assert!(docs.starts_with("*2\r\n+ping\r\n"), "{docs}");
+
+        assert_eq!(
+            admin.execute(&["COMMAND", "GETKEYS", "MSET", "a", "1", "b", "2"]),
+            Some("*2\r\n+a\r\n+b\r\n".to_string())
+        );
+        for args in [
+            &["COMMAND", "GETKEYS", "nope", "k"][..],
+            &["COMMAND", "GETKEYS", "get"],
+            &["COMMAND", "GETKEYS", "ping"],
+        ] {
+            assert!(admin.execute(args).unwrap().starts_with("-ERR"), "{args:?}");
+        }
+
+        assert_eq!(
+            admin.execute(&["COMMAND", "LIST", "FILTERBY", "PATTERN", "s*member*"]),
+            Some("*3\r\n+sismember\r\n+smembers\r\n+srandmember\r\n".to_string())
+        );
+        let admin_cat = admin
+            .execute(&["COMMAND", "LIST", "FILTERBY", "ACLCAT", "admin"])
+            .unwrap();
+        assert!(admin_cat.contains("+config\r\n") && !admin_cat.contains("+get\r\n"));
+        assert_eq!(
+            admin.execute(&["COMMAND", "LIST", "FILTERBY", "MODULE", "none"]),
+            Some("*0\r\n".to_string())
+        );
+        assert!(admin
+            .execute(&["COMMAND", "LIST", "FILTERBY", "NAME", "x"])
+            .unwrap()
+            .starts_with("-ERR"));
+    }
 }
@@ -11,28 +11,41 @@ use std::{
 };
 
 use anyhow::{anyhow, Context, Result};
+use parking_lot::Mutex;
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt, BufReader},
-    net::{tcp::OwnedWriteHalf, TcpStream},
+    io::{AsyncReadExt, AsyncWriteExt, BufReader, ReadHalf, WriteHalf},
     select,
-    sync::Semaphore,
     time::{sleep, timeout, Instant},
 };
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, trace, warn, Instrument, Span};
 
 use crate::{
+    command_category,
+    command_registry::GetKeysError,
+    engine::AsyncStorage,
+    logging::telemetry,
+    metrics,
     network::{
+        admin_commands::AdminCommands,
         connection_registry::ConnectionRegistry,
-        connection_state::{ConnectionInfo, ConnectionState},
+        connection_state::{
+            ClientKind, ConnectionInfo, ConnectionState, ExecOutcome, QueuedCommand,
+        },
+        monitor::{Monitor, MonitorSubscription, DEFAULT_MONITOR_CAPACITY},
+        slowlog::SlowLog,
+        tls::MaybeTls,
     },
-    zsp::{ZspDecoder, ZspEncoder, ZspFrame},
-    Sds, StorageEngine, Value,
+    pubsub::{parse_pubsub_frame, Broker, PubSubSession},
+    zsp::{parse_command, CompressionAlgo, ZspDecoder, ZspEncoder, ZspFrame},
+    AclDenyReason, AclError, AuthError, AuthManager, FlushMode, GeoAddOptions, LuaEngine,
+    RuntimeConfig, RuntimeParams, Sds, SmartHash, StorageEngine, Value,
 };
 
 /// Конфигурация для обработки соединений.
 ///
 /// Используется `ConnectionManager` и `ConnectionHandler` для настройки
-/// лимитов, таймаутов и размеров буферов.
+/// лимитов, таймаутов и размеров буферов. Лимиты и таймауты — начальные
+/// значения: во время работы они берутся из `RuntimeConfig`.
 #[derive(Debug, Clone)]
 pub struct ConnectionConfig {
     /// Максимальное кол-во одновременных соединений
@@ -47,6 +60,12 @@ pub struct ConnectionConfig {
     pub write_timeout: Duration,
     /// Размер буфера для чтения
     pub read_buffer_size: usize,
+    /// Ёмкость канала `MONITOR` (в строках); отставшие клиенты теряют
+    /// старые строки, не блокируя обработку команд
+    pub monitor_capacity: usize,
+    /// Конфигурация TLS; `None` — соединения принимаются без шифрования
+    #[cfg(feature = "tls")]
+    pub tls_config: Option<Arc<rustls::ServerConfig>>,
 }
 
 /// Менеджер соединений.
@@ -56,8 +75,10 @@ pub struct ConnectionConfig {
 #[derive(Debug)]
 pub struct ConnectionManager {
     config: ConnectionConfig,
-    /// Семафор для ограничений по IP адресам
-    connection_semaphore: Arc<Semaphore>,
+    /// Параметры, изменяемые через `CONFIG SET`
+    runtime: RuntimeConfig,
+    /// Обработчик административных команд (`CLIENT`, `CONFIG`, ...)
+    admin: Arc<AdminCommands>,
     /// Счётчик соединений по IP адресам
     ip_connections: Arc<RwLock<HashMap<std::net::IpAddr, AtomicU32>>>,
     /// Общий счётчик активных соединений
@@ -66,6 +87,13 @@ pub struct ConnectionManager {
     shutdown_signal: Arc<tokio::sync::Notify>,
     /// Реестр активных соединений (NEW)
     registry: Arc<ConnectionRegistry>,
+    /// Пользователи и права доступа; `None` — аутентификация отключена
+    auth: Option<AuthManager>,
+    /// Брокер сообщений Pub/Sub, общий для всех соединений
+    broker: Arc<Broker>,
+    /// Блокировка выполнения команд: обычные команды берут её на чтение,
+    /// `EXEC` — на запись, чтобы очередь транзакции выполнялась атомарно
+    exec_lock: Arc<tokio::sync::RwLock<()>>,
 }
 
 /// Обработчик отдельного соединения.
@@ -76,15 +104,19 @@ pub struct ConnectionHandler {
     /// Уникальный идентификатор соединения.
     connection_id: u32,
     /// Буфер для чтения данных.
-    reader: BufReader<tokio::net::tcp::OwnedReadHalf>,
-    /// Половина сокета для записи.
-    writer: OwnedWriteHalf,
+    reader: BufReader<ReadHalf<MaybeTls>>,
+    /// Половина потока для записи.
+    writer: WriteHalf<MaybeTls>,
     /// Адрес клиента.
     addr: SocketAddr,
     /// Ссылка на движок хранения
     engine: Arc<StorageEngine>,
     /// Конфигурация соединения
     config: ConnectionConfig,
+    /// Параметры, изменяемые через `CONFIG SET`
+    runtime: RuntimeConfig,
+    /// Обработчик административных команд
+    admin: Arc<AdminCommands>,
     /// Сигнал для gracefull shutdown
     shutdown_signal: Arc<tokio::sync::Notify>,
     /// Время последней активности
@@ -95,6 +127,12 @@ pub struct ConnectionHandler {
     recv_buf: Vec<u8>,
     /// Информация о соединении
     connection_info: Arc<ConnectionInfo>,
+    /// Пользователи и права доступа
+    auth: Option<AuthManager>,
+    /// Брокер сообщений Pub/Sub
+    broker: Arc<Broker>,
+    /// Блокировка выполнения команд для атомарного `EXEC`
+    exec_lock: Arc<tokio::sync::RwLock<()>>,
 }
 
 /// Контекст обработки соединения.
@@ -102,16 +140,26 @@ pub struct ConnectionHandler {
 /// Используется внутри `ConnectionHandler` для передачи движка, конфига
 /// и информации о соединении.
 struct ProcessContext<'a> {
-    /// Движок хранения данных.
+    /// Движок хранения данных (логическая база 0).
     engine: &'a Arc<StorageEngine>,
+    /// Движок логической базы, выбранной соединением через `SELECT`.
+    db: &'a Mutex<Arc<StorageEngine>>,
     /// Конфигурация соединения.
     config: &'a ConnectionConfig,
+    /// Обработчик административных команд.
+    admin: &'a AdminCommands,
     /// Информация о соединении.
     connection_info: &'a Arc<ConnectionInfo>,
+    /// Пользователи и права доступа.
+    auth: Option<&'a AuthManager>,
     /// Идентификатор соединения.
     connection_id: u32,
     /// Адрес клиента.
     addr: SocketAddr,
+    /// Корневой спан соединения (`Span::none()`, если трассировка выключена).
+    span: &'a Span,
+    /// Блокировка выполнения команд для атомарного `EXEC`.
+    exec_lock: &'a tokio::sync::RwLock<()>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -124,16 +172,65 @@ impl ConnectionManager {
     /// # Возвращает
     /// - `Self` - инициализированный `ConnectionManager`
     pub fn new(config: ConnectionConfig) -> Self {
+        let runtime = RuntimeConfig::new(RuntimeParams::from(&config));
+        Self::with_runtime_config(config, runtime)
+    }
+
+    /// Создаёт менеджер, который берёт лимиты и таймауты из разделяемой
+    /// конфигурации `runtime`.
+    ///
+    /// # Возвращает
+    /// - `Self` - инициализированный `ConnectionManager`
+    pub fn with_runtime_config(
+        config: ConnectionConfig,
+        runtime: RuntimeConfig,
+    ) -> Self {
+        let registry = Arc::new(ConnectionRegistry::new());
+        let admin = Arc::new(
+            AdminCommands::with_runtime_config(registry.clone(), runtime.clone())
+                .with_monitor(Monitor::new(config.monitor_capacity)),
+        );
+
         Self {
-            connection_semaphore: Arc::new(Semaphore::new(config.max_connections)),
             config,
+            runtime,
+            admin,
             ip_connections: Arc::new(RwLock::new(HashMap::new())),
             active_connections: Arc::new(AtomicUsize::new(0)),
             shutdown_signal: Arc::new(tokio::sync::Notify::new()),
-            registry: Arc::new(ConnectionRegistry::new()),
+            registry,
+            auth: None,
+            broker: Arc::new(Broker::new()),
+            exec_lock: Arc::new(tokio::sync::RwLock::new(())),
         }
     }
 
+    /// Включает аутентификацию и проверку прав доступа по пользователям
+    /// `auth`.
+    ///
+    /// # Возвращает
+    /// - `Self` - менеджер, проверяющий `AUTH` и ACL для каждой команды
+    pub fn with_auth(
+        mut self,
+        auth: AuthManager,
+    ) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Заменяет брокер Pub/Sub, например чтобы сохранить подписки при
+    /// пересборке менеджера.
+    ///
+    /// # Возвращает
+    /// - `Self` - менеджер, публикующий сообщения через `broker`
+    pub fn with_broker(
+        mut self,
+        broker: Arc<Broker>,
+    ) -> Self {
+        self.broker = broker;
+        self
+    }
+
     /// Возвращает текущее количество активных соединений.
     ///
     /// # Возвращает
@@ -150,6 +247,38 @@ impl ConnectionManager {
         &self.registry
     }
 
+    /// Получить обработчик административных команд.
+    ///
+    /// # Возвращает
+    /// - `&Arc<AdminCommands>` - обработчик, общий для всех соединений
+    pub fn admin(&self) -> &Arc<AdminCommands> {
+        &self.admin
+    }
+
+    /// Получить брокер сообщений Pub/Sub.
+    ///
+    /// # Возвращает
+    /// - `&Arc<Broker>` - брокер, через который соединения публикуют сообщения
+    pub fn broker(&self) -> &Arc<Broker> {
+        &self.broker
+    }
+
+    /// Получить начальную конфигурацию соединений.
+    ///
+    /// # Возвращает
+    /// - `&ConnectionConfig` - конфигурация, с которой создан менеджер
+    pub fn config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    /// Получить разделяемую конфигурацию времени выполнения.
+    ///
+    /// # Возвращает
+    /// - `&RuntimeConfig` - параметры, изменяемые через `CONFIG SET`
+    pub fn runtime_config(&self) -> &RuntimeConfig {
+        &self.runtime
+    }
+
     /// Инициализация graceful shutdown для всех соединений.
     ///
     /// Уведомляет все обработчики соединений о необходимости завершить работу.
@@ -192,27 +321,22 @@ impl ConnectionManager {
     /// - `Err(anyhow::Error)` в случае ошибок во время обработки
     pub async fn handle_connection(
         &self,
-        socket: TcpStream,
+        socket: MaybeTls,
         addr: SocketAddr,
         engine: Arc<StorageEngine>,
     ) -> Result<()> {
-        // Проверяем лимиты
-        self.can_accept_connection(addr)
+        // Проверяем лимиты по текущим значениям и резервируем слот
+        let params = self.runtime.snapshot();
+        let connection_count = self
+            .reserve_connection(addr, &params)
             .context("Connection limit check failed")?;
 
-        // Получаем семафор (может заблокироваться если лимит достигнут)
-        let _permit = self
-            .connection_semaphore
-            .acquire()
-            .await
-            .context("Failed to acquire connection semaphore")?;
-
         // Регистрируем соединение в реестре (NEW)
         let (connection_id, connection_info) = self.registry.register(addr);
 
         // Увеличиваем счетчики
         self.increment_ip_connections(addr);
-        let connection_count = self.active_connections.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::registry().connection_opened();
 
         info!(
             "Connection {} established from {} (active: {})",
@@ -226,8 +350,13 @@ impl ConnectionManager {
             addr,
             engine,
             self.config.clone(),
+            self.runtime.clone(),
+            self.admin.clone(),
             self.shutdown_signal.clone(),
             connection_info,
+            self.auth.clone(),
+            self.broker.clone(),
+            self.exec_lock.clone(),
         );
 
         let result = handler.run().await;
@@ -238,6 +367,7 @@ impl ConnectionManager {
         // Уменьшаем счетчики при завершении
         self.decrement_ip_connections(addr);
         let remaining_connections = self.active_connections.fetch_sub(1, Ordering::Relaxed) - 1;
+        metrics::registry().connection_closed();
 
         match &result {
             Ok(_) => debug!(
@@ -253,33 +383,37 @@ impl ConnectionManager {
         result
     }
 
-    /// Проверяет, можно ли принять новое соединение с данного IP.
+    /// Проверяет лимиты `params` и резервирует место под новое соединение с
+    /// данного IP, увеличивая счётчик активных соединений.
     ///
     /// # Возвращает
-    /// - `Ok(())` — если соединение можно принять.
+    /// - `Ok(usize)` — число активных соединений с учётом нового.
     /// - `Err(anyhow::Error)` — если превышен общий лимит соединений или лимит
     ///   по IP.
-    fn can_accept_connection(
+    fn reserve_connection(
         &self,
         addr: SocketAddr,
-    ) -> Result<()> {
-        // проверяем общий лимит
-        if self.connection_semaphore.available_permits() == 0 {
-            return Err(anyhow!("Maximum connections limit reached"));
-        }
-
+        params: &RuntimeParams,
+    ) -> Result<usize> {
         // проверяем лимит по IP
         let ip = addr.ip();
-        let ip_connections = self.ip_connections.read().unwrap();
-
-        if let Some(counter) = ip_connections.get(&ip) {
-            let current_count = counter.load(Ordering::Relaxed);
-            if current_count >= self.config.max_connections_per_ip as u32 {
-                return Err(anyhow!("Too many connections from IP: {ip}"));
+        {
+            let ip_connections = self.ip_connections.read().unwrap();
+            if let Some(counter) = ip_connections.get(&ip) {
+                let current_count = counter.load(Ordering::Relaxed);
+                if current_count >= params.max_connections_per_ip as u32 {
+                    return Err(anyhow!("Too many connections from IP: {ip}"));
+                }
             }
         }
 
-        Ok(())
+        // проверяем общий лимит и занимаем слот одной атомарной операцией
+        self.active_connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < params.max_connections).then_some(active + 1)
+            })
+            .map(|previous| previous + 1)
+            .map_err(|_| anyhow!("Maximum connections limit reached"))
     }
 
     /// Увеличивает счётчик активных соединений для указанного IP.
@@ -340,17 +474,23 @@ impl ConnectionHandler {
     ///
     /// # Возвращает
     /// - `Self` - инициализированный обработчик соединения
+    #[allow(clippy::too_many_arguments)]
     fn new(
         connection_id: u32,
-        socket: TcpStream,
+        socket: MaybeTls,
         addr: SocketAddr,
         engine: Arc<StorageEngine>,
         config: ConnectionConfig,
+        runtime: RuntimeConfig,
+        admin: Arc<AdminCommands>,
         shutdown_signal: Arc<tokio::sync::Notify>,
         connection_info: Arc<ConnectionInfo>,
+        auth: Option<AuthManager>,
+        broker: Arc<Broker>,
+        exec_lock: Arc<tokio::sync::RwLock<()>>,
     ) -> Self {
-        // Разделяем socket на части для чтения и записи
-        let (read_half, write_half) = socket.into_split();
+        // Разделяем поток на части для чтения и записи
+        let (read_half, write_half) = tokio::io::split(socket);
         let reader = BufReader::with_capacity(config.read_buffer_size, read_half);
 
         // Устанавливаем начальное состояние (NEW)
@@ -363,11 +503,16 @@ impl ConnectionHandler {
             addr,
             engine,
             config,
+            runtime,
+            admin,
             shutdown_signal,
             last_activity: Instant::now(),
             decoder: ZspDecoder::new(),
             recv_buf: Vec::new(),
             connection_info,
+            auth,
+            broker,
+            exec_lock,
         }
     }
 
@@ -383,19 +528,37 @@ impl ConnectionHandler {
         let connection_id = self.connection_id;
         let addr = self.addr;
         let shutdown = self.shutdown_signal.clone();
+        let kill = self.connection_info.kill_signal.clone();
         let mut last_activity = self.last_activity;
 
+        // Таймауты берём из текущих параметров, а не из значений на момент
+        // создания менеджера
+        let mut config = self.config.clone();
+        config.apply_runtime(&self.runtime.snapshot());
+
+        let db = Mutex::new(self.engine.clone());
+        let span = telemetry::connection_span(connection_id, addr);
         let ctx = ProcessContext {
             engine: &self.engine,
-            config: &self.config,
+            db: &db,
+            config: &config,
+            admin: &self.admin,
             connection_info: &self.connection_info,
+            auth: self.auth.as_ref(),
             connection_id,
             addr,
+            span: &span,
+            exec_lock: &self.exec_lock,
         };
 
         // Временный буфер для чтения
         let mut tmp = vec![0u8; self.config.read_buffer_size];
 
+        // Подписка на поток команд, пока соединение в режиме MONITOR
+        let mut monitor: Option<MonitorSubscription> = None;
+        // Подписки соединения на каналы и шаблоны Pub/Sub
+        let mut pubsub = PubSubSession::new(self.broker.clone());
+
         loop {
             select! {
                 _ = shutdown.notified() => {
@@ -405,6 +568,27 @@ impl ConnectionHandler {
                     break;
                 }
 
+                _ = kill.notified() => {
+                    info!("Connection {} ({}): Killed by CLIENT KILL", connection_id, addr);
+                    ctx.connection_info.set_state(ConnectionState::Closing);
+                    break;
+                }
+
+                Some(line) = async {
+                    match monitor.as_mut() {
+                        Some(subscription) => subscription.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    last_activity = Instant::now();
+                    Self::send_response_to_writer(&mut self.writer, &line, ctx.config.write_timeout).await?;
+                }
+
+                Some(frame) = pubsub.recv() => {
+                    last_activity = Instant::now();
+                    Self::send_frames_to_writer(&mut self.writer, &[frame], ctx.config.write_timeout, ctx.connection_info.compression()).await?;
+                }
+
                 _ = sleep(ctx.config.idle_timeout) => {
                     if last_activity.elapsed() >= ctx.config.idle_timeout {
                         warn!("Connection {} ({}): Idle timeout", connection_id, addr);
@@ -434,6 +618,7 @@ impl ConnectionHandler {
                                 &mut self.decoder,
                                 &mut self.writer,
                                 &ctx,
+                                &mut pubsub,
                                 n as u64,
                             ).await {
                                 error!("Connection {} ({}): Processing error: {}", connection_id, addr, e);
@@ -441,6 +626,14 @@ impl ConnectionHandler {
                                 ctx.connection_info.set_state(ConnectionState::Closing);
                                 break;
                             }
+
+                            // MONITOR и RESET переключают режим соединения
+                            if ctx.connection_info.is_monitor() != monitor.is_some() {
+                                monitor = ctx
+                                    .connection_info
+                                    .is_monitor()
+                                    .then(|| ctx.admin.monitor().subscribe());
+                            }
                         }
                         Ok(Err(e)) => {
                             if e.kind() == ErrorKind::InvalidData {
@@ -458,6 +651,8 @@ impl ConnectionHandler {
                                 return Err(e.into());
                             }
                         }
+                        // Клиент в режиме MONITOR или подписки только читает поток
+                        Err(_) if monitor.is_some() || pubsub.subscription_count() > 0 => continue,
                         Err(_) => {
                             warn!("Connection {} ({}): Read timeout", connection_id, addr);
                             ctx.connection_info.record_error();
@@ -484,8 +679,9 @@ impl ConnectionHandler {
     async fn process_buffer(
         recv_buf: &mut Vec<u8>,
         decoder: &mut ZspDecoder<'static>,
-        writer: &mut OwnedWriteHalf,
+        writer: &mut WriteHalf<MaybeTls>,
         ctx: &ProcessContext<'_>,
+        pubsub: &mut PubSubSession,
         bytes_received: u64,
     ) -> Result<()> {
         if recv_buf.is_empty() {
@@ -495,7 +691,18 @@ impl ConnectionHandler {
         let first = recv_buf[0];
         let is_zsp = matches!(
             first,
-            b'+' | b'-' | b':' | b',' | b'#' | b'$' | b'_' | b'*' | b'%' | b'~' | b'>' | b'^'
+            b'+' | b'-'
+                | b':'
+                | b','
+                | b'#'
+                | b'$'
+                | b'_'
+                | b'*'
+                | b'%'
+                | b'~'
+                | b'>'
+                | b'^'
+                | b'|'
         );
 
         if !is_zsp {
@@ -512,7 +719,39 @@ impl ConnectionHandler {
 
                 ctx.connection_info.set_state(ConnectionState::Processing);
 
-                match Self::process_command(ctx.engine, &line) {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                let slowlog = ctx.admin.slowlog();
+                let started = Instant::now();
+                let denied = Self::execute_auth(ctx, &parts).await;
+                let result = match denied.or_else(|| Self::feed_monitor(ctx, &parts)) {
+                    Some(response) => Ok(response),
+                    None => {
+                        let command = parts.first().copied().unwrap_or_default();
+                        let queue = || Ok(QueuedCommand::Text(line.clone()));
+                        match Self::execute_transaction(ctx, command, queue).await {
+                            Some(reply) => ZspEncoder::encode(&reply)
+                                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                                .map_err(|e| anyhow!(e.to_string())),
+                            None => {
+                                let _shared = ctx.exec_lock.read().await;
+                                Self::execute_text(ctx, &parts, &line)
+                                    .instrument(telemetry::command_span(ctx.span, &parts))
+                                    .await
+                            }
+                        }
+                    }
+                };
+                let elapsed = started.elapsed();
+                if let Some(command) = parts.first() {
+                    metrics::registry().record_command(command, elapsed);
+                }
+                slowlog.record(
+                    elapsed,
+                    || parts.iter().map(|s| s.to_string()).collect(),
+                    ctx.addr,
+                );
+
+                match result {
                     Ok(response) => {
                         let response_bytes = response.len() as u64;
                         if let Err(e) = Self::send_response_to_writer(
@@ -581,14 +820,101 @@ impl ConnectionHandler {
 
                     ctx.connection_info.set_state(ConnectionState::Processing);
 
-                    if let Err(e) = Self::handle_zsp_frame(
-                        ctx.engine,
-                        frame,
-                        writer,
-                        ctx.config,
-                        ctx.connection_info,
-                    )
-                    .await
+                    if let Some(response) = Self::execute_auth(ctx, &frame_args(&frame)).await {
+                        Self::send_response_to_writer(writer, &response, ctx.config.write_timeout)
+                            .await?;
+                        ctx.connection_info.set_state(ConnectionState::Idle);
+                        return Ok(());
+                    }
+
+                    if ctx.admin.monitor().is_active() {
+                        if let Some(response) = Self::feed_monitor(ctx, &frame_args(&frame)) {
+                            Self::send_response_to_writer(
+                                writer,
+                                &response,
+                                ctx.config.write_timeout,
+                            )
+                            .await?;
+                            ctx.connection_info.set_state(ConnectionState::Idle);
+                            return Ok(());
+                        }
+                    }
+
+                    let command = frame_command(&frame).unwrap_or_default();
+                    let queue = || Self::validate_queued(&frame).map(QueuedCommand::Zsp);
+                    if let Some(reply) = Self::execute_transaction(ctx, command, queue).await {
+                        Self::send_frames_to_writer(
+                            writer,
+                            &[reply],
+                            ctx.config.write_timeout,
+                            ctx.connection_info.compression(),
+                        )
+                        .await?;
+                        ctx.connection_info.set_state(ConnectionState::Idle);
+                        return Ok(());
+                    }
+
+                    if frame_command_is(&frame, "HELLO") {
+                        // Ответ на HELLO всегда уходит без сжатия: клиент
+                        // узнаёт о согласованном алгоритме только из него.
+                        let reply = Self::execute_hello(ctx, &frame_args(&frame));
+                        Self::send_frames_to_writer(
+                            writer,
+                            &[reply],
+                            ctx.config.write_timeout,
+                            None,
+                        )
+                        .await?;
+                        ctx.connection_info.set_state(ConnectionState::Idle);
+                        return Ok(());
+                    }
+
+                    if let Some(response) = Self::execute_zsp_admin(ctx, &frame) {
+                        Self::send_response_to_writer(writer, &response, ctx.config.write_timeout)
+                            .await?;
+                        ctx.connection_info.set_state(ConnectionState::Idle);
+                        return Ok(());
+                    }
+
+                    if frame_command_is(&frame, "SELECT") {
+                        if let Some(response) = Self::execute_select(ctx, &frame_args(&frame)) {
+                            Self::send_response_to_writer(
+                                writer,
+                                &response,
+                                ctx.config.write_timeout,
+                            )
+                            .await?;
+                            ctx.connection_info.set_state(ConnectionState::Idle);
+                            return Ok(());
+                        }
+                    }
+
+                    if let Some(command) = parse_pubsub_frame(&frame) {
+                        let frames = match command.and_then(|command| pubsub.execute(command)) {
+                            Ok(frames) => frames,
+                            Err(e) => vec![ZspFrame::FrameError(format!("ERR {e}"))],
+                        };
+                        Self::send_frames_to_writer(
+                            writer,
+                            &frames,
+                            ctx.config.write_timeout,
+                            ctx.connection_info.compression(),
+                        )
+                        .await?;
+                        Self::update_client_kind(ctx, pubsub);
+                        ctx.connection_info.set_state(ConnectionState::Idle);
+                        return Ok(());
+                    }
+
+                    // Аргументы фрейма копируются только при включённой трассировке.
+                    let span = if telemetry::enabled() {
+                        telemetry::command_span(ctx.span, &frame_args(&frame))
+                    } else {
+                        Span::none()
+                    };
+                    if let Err(e) = Self::handle_zsp_frame(frame, writer, ctx)
+                        .instrument(span)
+                        .await
                     {
                         error!(
                             "Connection {} ({}): ZSP handling error: {}",
@@ -621,6 +947,417 @@ impl ConnectionHandler {
         Ok(())
     }
 
+    /// Рассылает команду `args` клиентам в режиме `MONITOR`.
+    ///
+    /// Команды соединения, которое само находится в режиме `MONITOR`, не
+    /// рассылаются и не выполняются: допустим только `RESET`. Пока
+    /// подписчиков нет, ни одно соединение не может быть в этом режиме, и
+    /// проверка сводится к чтению `AtomicBool`.
+    ///
+    /// # Возвращает
+    /// - `Some(String)` — готовый ответ соединению в режиме `MONITOR`
+    /// - `None` — команду нужно выполнить обычным образом
+    fn feed_monitor<S: AsRef<str>>(
+        ctx: &ProcessContext<'_>,
+        args: &[S],
+    ) -> Option<String> {
+        let monitor = ctx.admin.monitor();
+        if !monitor.is_active() {
+            return None;
+        }
+        if ctx.connection_info.is_monitor() {
+            return Some(match args {
+                [cmd] if cmd.as_ref().eq_ignore_ascii_case("RESET") => {
+                    ctx.admin.handle_reset(ctx.connection_id)
+                }
+                _ => "-ERR only RESET is allowed in MONITOR mode\r\n".to_string(),
+            });
+        }
+        monitor.feed(ctx.addr, ctx.connection_info.db_index(), args);
+        None
+    }
+
+    /// Выполняет `AUTH [username] password` и проверяет права соединения на
+    /// остальные команды.
+    ///
+    /// Пока соединение не аутентифицировано, оно действует от имени
+    /// пользователя `default`. Если аутентификация обязательна (см.
+    /// [`AuthManager::requires_auth`]), до успешного `AUTH` разрешены только
+    /// `AUTH`, `HELLO` и `QUIT`. Отказы записываются в журнал ACL.
+    ///
+    /// # Возвращает
+    /// - `Some(String)` — ответ на `AUTH` или ошибка доступа
+    /// - `None` — команду можно выполнять
+    async fn execute_auth<S: AsRef<str>>(
+        ctx: &ProcessContext<'_>,
+        args: &[S],
+    ) -> Option<String> {
+        let auth = ctx.auth?;
+        let [cmd, rest @ ..] = args else {
+            return None;
+        };
+        let cmd = cmd.as_ref();
+        let client_info = || format!("id={} addr={}", ctx.connection_id, ctx.addr);
+
+        if cmd.eq_ignore_ascii_case("AUTH") {
+            let (username, password) = match rest {
+                [password] => ("default", password.as_ref()),
+                [username, password] => (username.as_ref(), password.as_ref()),
+                _ => return Some("-ERR wrong number of arguments for 'auth' command\r\n".into()),
+            };
+            return Some(match auth.authenticate(username, password).await {
+                Ok(()) => {
+                    ctx.connection_info
+                        .metadata
+                        .write()
+                        .set_username(username.to_string());
+                    "+OK\r\n".to_string()
+                }
+                Err(AuthError::TooManyAttempts) => {
+                    "-ERR too many failed authentication attempts\r\n".to_string()
+                }
+                Err(_) => {
+                    auth.acl_log()
+                        .record(AclDenyReason::Auth, "AUTH", username, &client_info());
+                    "-WRONGPASS invalid username-password pair or user is disabled.\r\n".to_string()
+                }
+            });
+        }
+
+        let username = ctx.connection_info.metadata.read().username.clone();
+        let username = match username {
+            Some(username) => username,
+            None if ["HELLO", "QUIT"]
+                .iter()
+                .any(|c| cmd.eq_ignore_ascii_case(c)) =>
+            {
+                return None
+            }
+            None if auth.requires_auth().await => {
+                return Some("-NOAUTH Authentication required.\r\n".to_string())
+            }
+            None => "default".to_string(),
+        };
+
+        let category = command_category(cmd);
+        // Ключи берутся по позициям из описания команды; для команд вне
+        // таблицы ключом считается первый аргумент.
+        let keys: Vec<&str> = match ctx.admin.commands().get_keys(args) {
+            Ok(keys) => keys,
+            Err(GetKeysError::UnknownCommand) if category != "admin" => {
+                rest.first().map(|key| key.as_ref()).into_iter().collect()
+            }
+            Err(_) => Vec::new(),
+        };
+        match auth
+            .check_permission(&username, category, cmd, &keys, &client_info())
+            .await
+        {
+            // Без пользователя `default` анонимные соединения не ограничены.
+            Err(AuthError::UserNotFound) if username == "default" => None,
+            Err(AuthError::Acl(AclError::PermissionDenied)) => Some(format!(
+                "-NOPERM User {username} has no permissions to run the '{}' command\r\n",
+                cmd.to_ascii_lowercase()
+            )),
+            Err(e) => Some(format!("-ERR {e}\r\n")),
+            Ok(()) => None,
+        }
+    }
+
+    /// Выполняет административную команду. После успешного `CONFIG SET`
+    /// политика вытеснения из параметров применяется к движку, а лимиты
+    /// компактных хешей — к `SmartHash`.
+    ///
+    /// # Возвращает
+    /// - `Some(String)` — ответ, если команда административная
+    /// - `None` — для остальных команд
+    fn execute_admin(
+        ctx: &ProcessContext<'_>,
+        args: &[&str],
+    ) -> Option<String> {
+        let response = ctx.admin.execute_for(ctx.connection_id, args)?;
+        if let [cmd, sub, ..] = args {
+            if cmd.eq_ignore_ascii_case("CONFIG")
+                && sub.eq_ignore_ascii_case("SET")
+                && response.starts_with('+')
+            {
+                let snapshot = ctx.admin.runtime().snapshot();
+                if let Err(e) = ctx.engine.set_eviction_policy(snapshot.maxmemory_policy) {
+                    return Some(format!("-ERR {e}\r\n"));
+                }
+                SmartHash::set_listpack_limits(
+                    snapshot.hash_max_listpack_entries,
+                    snapshot.hash_max_listpack_value,
+                );
+                let limit = Duration::from_millis(snapshot.lua_time_limit);
+                // Внутри скрипта CONFIG недоступен, поэтому движок свободен.
+                let _ = LuaEngine::with_scripts(|engine| {
+                    engine.set_time_limit(limit);
+                    Ok(())
+                });
+            }
+        }
+        Some(response)
+    }
+
+    /// Выполняет `COMMAND`, пришедшую ZSP-фреймом: остальные
+    /// административные команды доступны только в текстовом протоколе.
+    ///
+    /// # Возвращает
+    /// - `Some(String)` — ответ, если `frame` — команда `COMMAND`
+    /// - `None` — для остальных команд
+    fn execute_zsp_admin(
+        ctx: &ProcessContext<'_>,
+        frame: &ZspFrame<'static>,
+    ) -> Option<String> {
+        if !frame_command_is(frame, "COMMAND") {
+            return None;
+        }
+        let args = frame_args(frame);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        Self::execute_admin(ctx, &args)
+    }
+
+    /// Выполняет `SELECT index`: последующие команды соединения идут в
+    /// логическую базу `index`.
+    ///
+    /// # Возвращает
+    /// - `Some(String)` — ответ, если `args` — команда `SELECT`
+    /// - `None` — для остальных команд
+    fn execute_select<S: AsRef<str>>(
+        ctx: &ProcessContext<'_>,
+        args: &[S],
+    ) -> Option<String> {
+        let [cmd, rest @ ..] = args else {
+            return None;
+        };
+        if !cmd.as_ref().eq_ignore_ascii_case("SELECT") {
+            return None;
+        }
+        let [index] = rest else {
+            return Some("-ERR wrong number of arguments for 'select' command\r\n".to_string());
+        };
+        let Ok(index) = index.as_ref().parse::<usize>() else {
+            return Some("-ERR value is not an integer or out of range\r\n".to_string());
+        };
+        if index >= ctx.engine.databases() {
+            return Some("-ERR DB index is out of range\r\n".to_string());
+        }
+
+        let engine = if index == 0 {
+            ctx.engine.clone()
+        } else {
+            match ctx.engine.select(index) {
+                #[allow(clippy::arc_with_non_send_sync)]
+                Ok(engine) => Arc::new(engine),
+                Err(e) => return Some(format!("-ERR {e}\r\n")),
+            }
+        };
+        *ctx.db.lock() = engine;
+        ctx.connection_info.set_db_index(index);
+        Some("+OK\r\n".to_string())
+    }
+
+    /// Выполняет `HELLO [protover] [COMPRESS lz4|zstd]`.
+    ///
+    /// Согласует сжатие фреймов: после `HELLO 3 COMPRESS <algo>` ответы
+    /// соединения длиннее [`COMPRESSION_THRESHOLD`] сжимаются выбранным
+    /// алгоритмом, а `HELLO` без `COMPRESS` сжатие выключает.
+    ///
+    /// [`COMPRESSION_THRESHOLD`]: crate::network::zsp::COMPRESSION_THRESHOLD
+    ///
+    /// # Возвращает
+    /// - словарь со сведениями о сервере и соединении или ошибку
+    fn execute_hello<S: AsRef<str>>(
+        ctx: &ProcessContext<'_>,
+        args: &[S],
+    ) -> ZspFrame<'static> {
+        let rest = args.get(1..).unwrap_or_default();
+        let (proto, options) = match rest {
+            [] => (2, rest),
+            [version, options @ ..] => match version.as_ref().parse::<i64>() {
+                Ok(version @ (2 | 3)) => (version, options),
+                _ => {
+                    return ZspFrame::FrameError("NOPROTO unsupported protocol version".to_string())
+                }
+            },
+        };
+
+        let compression = match options {
+            [] => None,
+            [opt, algo] if opt.as_ref().eq_ignore_ascii_case("COMPRESS") => {
+                if proto < 3 {
+                    return ZspFrame::FrameError("ERR COMPRESS requires protocol 3".to_string());
+                }
+                match CompressionAlgo::parse(algo.as_ref()) {
+                    Some(algo) => Some(algo),
+                    None => {
+                        return ZspFrame::FrameError(format!(
+                            "ERR unsupported compression algorithm '{}'",
+                            algo.as_ref()
+                        ))
+                    }
+                }
+            }
+            _ => return ZspFrame::FrameError("ERR syntax error".to_string()),
+        };
+        ctx.connection_info.set_compression(compression);
+
+        let mut reply = HashMap::new();
+        reply.insert(
+            Cow::Borrowed("server"),
+            ZspFrame::InlineString("zumic".into()),
+        );
+        reply.insert(
+            Cow::Borrowed("version"),
+            ZspFrame::InlineString(env!("CARGO_PKG_VERSION").into()),
+        );
+        reply.insert(Cow::Borrowed("proto"), ZspFrame::Integer(proto));
+        reply.insert(
+            Cow::Borrowed("id"),
+            ZspFrame::Integer(i64::from(ctx.connection_id)),
+        );
+        reply.insert(
+            Cow::Borrowed("mode"),
+            ZspFrame::InlineString("standalone".into()),
+        );
+        reply.insert(
+            Cow::Borrowed("role"),
+            ZspFrame::InlineString("master".into()),
+        );
+        reply.insert(
+            Cow::Borrowed("compression"),
+            compression.map_or(ZspFrame::Null, |algo| {
+                ZspFrame::InlineString(algo.as_str().into())
+            }),
+        );
+        ZspFrame::Dictionary(reply)
+    }
+
+    /// Выполняет `MULTI`, `EXEC` и `DISCARD`, а пока блок `MULTI` открыт,
+    /// ставит остальные команды в очередь транзакции соединения, общую для
+    /// текстового протокола и ZSP. `queue` готовит команду к постановке в
+    /// очередь; ошибка отменяет транзакцию при `EXEC`.
+    ///
+    /// Очередь выполняется под блокировкой `exec_lock` на запись, поэтому
+    /// команды других соединений не вклиниваются между командами транзакции.
+    ///
+    /// # Возвращает
+    /// - `Some(ZspFrame)` — ответ, если команда относится к транзакции или
+    ///   поставлена в очередь (`QUEUED`)
+    /// - `None` — если команду нужно выполнить сразу
+    async fn execute_transaction(
+        ctx: &ProcessContext<'_>,
+        command: &str,
+        queue: impl FnOnce() -> Result<QueuedCommand, String>,
+    ) -> Option<ZspFrame<'static>> {
+        let ok = || ZspFrame::InlineString("OK".into());
+        let error = |msg: &str| ZspFrame::FrameError(msg.to_string());
+
+        if command.eq_ignore_ascii_case("MULTI") {
+            let begun = ctx.connection_info.transaction().begin();
+            return Some(if begun {
+                ok()
+            } else {
+                error("ERR MULTI calls can not be nested")
+            });
+        }
+        if command.eq_ignore_ascii_case("DISCARD") {
+            let discarded = ctx.connection_info.transaction().discard();
+            return Some(if discarded {
+                ok()
+            } else {
+                error("ERR DISCARD without MULTI")
+            });
+        }
+        if command.eq_ignore_ascii_case("EXEC") {
+            let outcome = ctx.connection_info.transaction().exec();
+            return Some(match outcome {
+                ExecOutcome::NotActive => error("ERR EXEC without MULTI"),
+                ExecOutcome::Aborted => {
+                    error("EXECABORT Transaction discarded because of previous errors.")
+                }
+                ExecOutcome::Run(commands) => {
+                    let _exclusive = ctx.exec_lock.write().await;
+                    let mut replies = Vec::with_capacity(commands.len());
+                    for queued in commands {
+                        replies.push(Self::execute_queued(ctx, queued).await);
+                    }
+                    ZspFrame::Array(replies)
+                }
+            });
+        }
+
+        // QUIT закрывает соединение сразу, даже внутри транзакции.
+        if command.eq_ignore_ascii_case("QUIT") || !ctx.connection_info.transaction().is_active() {
+            return None;
+        }
+        let queued = queue();
+        let mut transaction = ctx.connection_info.transaction();
+        match queued {
+            Ok(queued) => {
+                transaction.enqueue(queued);
+                Some(ZspFrame::InlineString("QUEUED".into()))
+            }
+            Err(e) => {
+                transaction.abort();
+                Some(ZspFrame::FrameError(e))
+            }
+        }
+    }
+
+    /// Выполняет команду из очереди транзакции тем же путём, что и вне
+    /// транзакции.
+    ///
+    /// # Возвращает
+    /// - ответ на команду; ошибки выполнения возвращаются как `FrameError`
+    async fn execute_queued(
+        ctx: &ProcessContext<'_>,
+        queued: QueuedCommand,
+    ) -> ZspFrame<'static> {
+        match queued {
+            QueuedCommand::Text(line) => {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                match Self::execute_text(ctx, &parts, &line).await {
+                    Ok(response) => resp_to_frame(&response),
+                    Err(e) => ZspFrame::FrameError(format!("ERR {e}")),
+                }
+            }
+            QueuedCommand::Zsp(frame) => {
+                let response = Self::execute_select(ctx, &frame_args(&frame))
+                    .or_else(|| Self::execute_zsp_admin(ctx, &frame));
+                if let Some(response) = response {
+                    return resp_to_frame(&response);
+                }
+                let engine = ctx.db.lock().clone();
+                match Self::execute_zsp_command(&engine, frame, ctx.admin.slowlog(), ctx.addr).await
+                {
+                    Ok(reply) | Err(reply) => reply,
+                }
+            }
+        }
+    }
+
+    /// Выполняет команду текстового протокола: `SELECT`, административные
+    /// команды и команды хранилища.
+    ///
+    /// # Возвращает
+    /// - `Ok(String)` — ответ в формате протокола Redis
+    /// - `Err(anyhow::Error)` — при внутренней ошибке обработки команды
+    async fn execute_text(
+        ctx: &ProcessContext<'_>,
+        parts: &[&str],
+        line: &str,
+    ) -> Result<String> {
+        if let Some(response) =
+            Self::execute_select(ctx, parts).or_else(|| Self::execute_admin(ctx, parts))
+        {
+            return Ok(response);
+        }
+        let engine = ctx.db.lock().clone();
+        Self::process_command(&engine, line).await
+    }
+
     /// Обрабатывает команду клиента (статический метод).
     ///
     /// Парсит строку `line`, определяет команду и её аргументы, выполняет
@@ -632,7 +1369,7 @@ impl ConnectionHandler {
     ///   `+OK\r\n`, `$-1\r\n` и т.д.).
     /// - `Err(anyhow::Error)` — если произошла внутренняя ошибка обработки
     ///   команды.
-    fn process_command(
+    async fn process_command(
         engine: &Arc<StorageEngine>,
         line: &str,
     ) -> Result<String> {
@@ -653,7 +1390,7 @@ impl ConnectionHandler {
             "SET" if parts.len() == 3 => {
                 let k = Sds::from(parts[1].as_bytes());
                 let v = Value::Str(Sds::from(parts[2].as_bytes()));
-                match engine.set(&k, v) {
+                match AsyncStorage::set(&**engine, &k, v).await {
                     Ok(_) => "+OK\r\n".to_string(),
                     Err(e) => {
                         error!("SET command failed: {}", e);
@@ -663,7 +1400,7 @@ impl ConnectionHandler {
             }
             "GET" if parts.len() == 2 => {
                 let k = Sds::from(parts[1].as_bytes());
-                match engine.get(&k) {
+                match AsyncStorage::get(&**engine, &k).await {
                     Ok(Some(Value::Str(s))) => match String::from_utf8(s.to_vec()) {
                         Ok(s) => format!("+{s}\r\n"),
                         Err(_) => "-ERR Invalid UTF-8\r\n".to_string(),
@@ -676,9 +1413,23 @@ impl ConnectionHandler {
                     }
                 }
             }
+            "GETDEL" if parts.len() == 2 => {
+                let k = Sds::from(parts[1].as_bytes());
+                match AsyncStorage::getdel(&**engine, &k).await {
+                    Ok(Some(s)) => match String::from_utf8(s.to_vec()) {
+                        Ok(s) => format!("+{s}\r\n"),
+                        Err(_) => "-ERR Invalid UTF-8\r\n".to_string(),
+                    },
+                    Ok(None) => "$-1\r\n".to_string(),
+                    Err(e) => {
+                        error!("GETDEL command failed: {}", e);
+                        "-ERR GETDEL failed\r\n".to_string()
+                    }
+                }
+            }
             "DEL" if parts.len() == 2 => {
                 let k = Sds::from(parts[1].as_bytes());
-                match engine.del(&k) {
+                match AsyncStorage::del(&**engine, &k).await {
                     Ok(true) => ":1\r\n".to_string(),
                     Ok(false) => ":0\r\n".to_string(),
                     Err(e) => {
@@ -687,6 +1438,36 @@ impl ConnectionHandler {
                     }
                 }
             }
+            "TYPE" if parts.len() == 2 => {
+                let k = Sds::from(parts[1].as_bytes());
+                match AsyncStorage::type_of(&**engine, &k).await {
+                    Ok(ty) => format!("+{}\r\n", ty.map_or("none", |ty| ty.as_str())),
+                    Err(e) => {
+                        error!("TYPE command failed: {}", e);
+                        "-ERR TYPE failed\r\n".to_string()
+                    }
+                }
+            }
+            cmd @ ("FLUSHDB" | "FLUSHALL") if parts.len() <= 2 => {
+                let mode = match parts.get(1) {
+                    None => Some(FlushMode::Sync),
+                    Some(arg) => FlushMode::parse(arg),
+                };
+                let result = match mode {
+                    Some(mode) if cmd == "FLUSHDB" => {
+                        AsyncStorage::flushdb_mode(&**engine, mode).await
+                    }
+                    Some(mode) => AsyncStorage::flushall(&**engine, mode).await,
+                    None => return Ok("-ERR syntax error\r\n".to_string()),
+                };
+                match result {
+                    Ok(()) => "+OK\r\n".to_string(),
+                    Err(e) => {
+                        error!("{} command failed: {}", cmd, e);
+                        format!("-ERR {cmd} failed\r\n")
+                    }
+                }
+            }
             "MSET" if parts.len() > 1 => {
                 let args = &parts[1..];
                 if !args.len().is_multiple_of(2) {
@@ -696,7 +1477,7 @@ impl ConnectionHandler {
                     for chunk in args.chunks(2) {
                         let k = Sds::from(chunk[0].as_bytes());
                         let v = Value::Str(Sds::from(chunk[1].as_bytes()));
-                        if let Err(e) = engine.set(&k, v) {
+                        if let Err(e) = AsyncStorage::set(&**engine, &k, v).await {
                             error!("MSET command failed on key {}: {}", chunk[0], e);
                             all_success = false;
                             break;
@@ -715,7 +1496,7 @@ impl ConnectionHandler {
                     .map(|&k| Sds::from(k.as_bytes()))
                     .collect();
                 let refs: Vec<&Sds> = sds_keys.iter().collect();
-                match engine.mget(&refs) {
+                match AsyncStorage::mget(&**engine, &refs).await {
                     Ok(vals) => {
                         let mut resp = format!("*{}\r\n", vals.len());
                         for opt in vals {
@@ -741,7 +1522,9 @@ impl ConnectionHandler {
                 let lon: f64 = parts[2].parse().unwrap_or(0.0);
                 let lat: f64 = parts[3].parse().unwrap_or(0.0);
                 let m = Sds::from(parts[4].as_bytes());
-                match engine.geo_add(&k, lon, lat, &m) {
+                match AsyncStorage::geo_add(&**engine, &k, lon, lat, &m, GeoAddOptions::default())
+                    .await
+                {
                     Ok(true) => ":1\r\n".to_string(),
                     Ok(false) => ":0\r\n".to_string(),
                     Err(e) => {
@@ -753,7 +1536,7 @@ impl ConnectionHandler {
             "GEOPOS" if parts.len() == 3 => {
                 let k = Sds::from(parts[1].as_bytes());
                 let m = Sds::from(parts[2].as_bytes());
-                match engine.geo_pos(&k, &m) {
+                match AsyncStorage::geo_pos(&**engine, &k, &m).await {
                     Ok(Some(pt)) => format!(
                         "*2\r\n${}\r\n{}\r\n${}\r\n{}\r\n",
                         pt.lon.to_string().len(),
@@ -773,7 +1556,7 @@ impl ConnectionHandler {
                 let m1 = Sds::from(parts[2].as_bytes());
                 let m2 = Sds::from(parts[3].as_bytes());
                 let unit = parts.get(4).copied().unwrap_or("m");
-                match engine.geo_dist(&k, &m1, &m2, unit) {
+                match AsyncStorage::geo_dist(&**engine, &k, &m1, &m2, unit).await {
                     Ok(Some(d)) => format!("+{d}\r\n"),
                     Ok(None) => "$-1\r\n".to_string(),
                     Err(e) => {
@@ -791,7 +1574,7 @@ impl ConnectionHandler {
                 // unit опционально, если есть 6-й аргумент
                 let unit = parts.get(5).copied().unwrap_or("km");
 
-                match engine.geo_radius(&key, lon, lat, radius, unit) {
+                match AsyncStorage::geo_radius(&**engine, &key, lon, lat, radius, unit).await {
                     Ok(results) => {
                         let mut resp = format!("*{}\r\n", results.len());
                         for (name, distance, point) in results {
@@ -818,7 +1601,7 @@ impl ConnectionHandler {
                 let k = Sds::from(parts[1].as_bytes());
                 let members: Vec<Sds> =
                     parts[2..].iter().map(|s| Sds::from(s.as_bytes())).collect();
-                match engine.sadd(&k, &members) {
+                match AsyncStorage::sadd(&**engine, &k, &members).await {
                     Ok(added) => format!(":{added}\r\n"),
                     Err(e) => {
                         error!("SADD command failed: {e}");
@@ -828,7 +1611,7 @@ impl ConnectionHandler {
             }
             "SMEMBERS" if parts.len() == 2 => {
                 let k = Sds::from(parts[1].as_bytes());
-                match engine.smembers(&k) {
+                match AsyncStorage::smembers(&**engine, &k).await {
                     Ok(members) => {
                         let mut resp = format!("*{}\r\n", members.len());
                         for m in members {
@@ -847,7 +1630,7 @@ impl ConnectionHandler {
             }
             "SCARD" if parts.len() == 2 => {
                 let k = Sds::from(parts[1].as_bytes());
-                match engine.scard(&k) {
+                match AsyncStorage::scard(&**engine, &k).await {
                     Ok(n) => format!(":{n}\r\n"),
                     Err(e) => {
                         error!("SCARD failed: {}", e);
@@ -858,7 +1641,7 @@ impl ConnectionHandler {
             "SISMEMBER" if parts.len() == 3 => {
                 let k = Sds::from(parts[1].as_bytes());
                 let m = Sds::from(parts[2].as_bytes());
-                match engine.sismember(&k, &m) {
+                match AsyncStorage::sismember(&**engine, &k, &m).await {
                     Ok(true) => ":1\r\n".to_string(),
                     Ok(false) => ":0\r\n".to_string(),
                     Err(e) => {
@@ -871,7 +1654,7 @@ impl ConnectionHandler {
                 let k = Sds::from(parts[1].as_bytes());
                 let members: Vec<Sds> =
                     parts[2..].iter().map(|s| Sds::from(s.as_bytes())).collect();
-                match engine.srem(&k, &members) {
+                match AsyncStorage::srem(&**engine, &k, &members).await {
                     Ok(removed) => format!(":{removed}\r\n"),
                     Err(e) => {
                         error!("SREM failed: {}", e);
@@ -883,7 +1666,7 @@ impl ConnectionHandler {
                 let k = Sds::from(parts[1].as_bytes());
                 if parts.len() == 3 {
                     let cnt: isize = parts[2].parse().unwrap_or(1);
-                    match engine.srandmember(&k, cnt) {
+                    match AsyncStorage::srandmember(&**engine, &k, cnt).await {
                         Ok(vec) => {
                             if cnt == 1 {
                                 if vec.is_empty() {
@@ -911,7 +1694,7 @@ impl ConnectionHandler {
                     }
                 } else {
                     // single
-                    match engine.srandmember(&k, 1) {
+                    match AsyncStorage::srandmember(&**engine, &k, 1).await {
                         Ok(mut v) => {
                             if v.is_empty() {
                                 "$-1\r\n".to_string()
@@ -934,7 +1717,7 @@ impl ConnectionHandler {
                 } else {
                     1
                 };
-                match engine.spop(&k, cnt) {
+                match AsyncStorage::spop(&**engine, &k, cnt).await {
                     Ok(vec) => {
                         if cnt == 1 {
                             if vec.is_empty() {
@@ -972,7 +1755,7 @@ impl ConnectionHandler {
     /// - `Ok(())` если запись успешна
     /// - `Err(anyhow::Error)` при ошибке записи или таймауте
     async fn send_response_to_writer(
-        writer: &mut OwnedWriteHalf,
+        writer: &mut WriteHalf<MaybeTls>,
         response: &str,
         write_timeout: Duration,
     ) -> Result<()> {
@@ -984,6 +1767,44 @@ impl ConnectionHandler {
         Ok(())
     }
 
+    /// Кодирует `frames` в ZSP и отправляет их клиенту одной записью.
+    /// Если задан `compression`, крупные фреймы сжимаются.
+    ///
+    /// # Возвращает
+    /// - `Ok(())` если фреймы отправлены успешно
+    /// - `Err(anyhow::Error)` при ошибке кодирования или записи
+    async fn send_frames_to_writer(
+        writer: &mut WriteHalf<MaybeTls>,
+        frames: &[ZspFrame<'_>],
+        write_timeout: Duration,
+        compression: Option<CompressionAlgo>,
+    ) -> Result<()> {
+        let mut encoded = Vec::new();
+        for frame in frames {
+            encoded.extend(
+                ZspEncoder::encode_with(frame, compression).map_err(|e| anyhow!(e.to_string()))?,
+            );
+        }
+        timeout(write_timeout, writer.write_all(&encoded))
+            .await
+            .context("Write timeout")?
+            .context("Failed to write response")?;
+
+        Ok(())
+    }
+
+    /// Отмечает соединение как клиента Pub/Sub, пока у него есть подписки.
+    fn update_client_kind(
+        ctx: &ProcessContext<'_>,
+        pubsub: &PubSubSession,
+    ) {
+        if pubsub.subscription_count() > 0 {
+            ctx.connection_info.set_kind(ClientKind::PubSub);
+        } else if ctx.connection_info.snapshot().kind == ClientKind::PubSub {
+            ctx.connection_info.set_kind(ClientKind::Normal);
+        }
+    }
+
     /// Проверяет, является ли ошибка ввода/вывода восстанавливаемой.
     ///
     /// # Возвращает
@@ -1009,7 +1830,7 @@ impl ConnectionHandler {
     /// - `Err(anyhow::Error)` при ошибке закрытия
     async fn graceful_close_writer(
         connection_id: u32,
-        mut writer: OwnedWriteHalf,
+        mut writer: WriteHalf<MaybeTls>,
     ) -> Result<()> {
         if let Err(e) = writer.shutdown().await {
             if e.kind() != ErrorKind::NotConnected {
@@ -1023,75 +1844,129 @@ impl ConnectionHandler {
     /// Обрабатывает один ZSP-фрейм от клиента.
     ///
     /// Функция:
-    /// 1. Парсит команду из `frame`.
-    /// 2. Выполняет команду через `StorageEngine`.
-    /// 3. Кодирует и отправляет ответ обратно клиенту через `writer`.
-    /// 4. Обновляет статистику и ошибки соединения в `connection_info`.
+    /// 1. Парсит и выполняет команду через `StorageEngine` под блокировкой
+    ///    `exec_lock` на чтение, не пересекаясь с очередью `EXEC`.
+    /// 2. Кодирует и отправляет ответ обратно клиенту через `writer`.
+    /// 3. Обновляет статистику и ошибки соединения в `connection_info`.
     ///
     /// # Возвращает
     /// - `Ok(())` — если фрейм обработан успешно (ответ отправлен клиенту).
     /// - `Err(anyhow::Error)` — если произошла критическая ошибка при обработке
     ///   или кодировании фрейма.
     async fn handle_zsp_frame(
-        engine: &Arc<StorageEngine>,
         frame: ZspFrame<'static>,
-        writer: &mut OwnedWriteHalf,
-        config: &ConnectionConfig,
-        connection_info: &Arc<ConnectionInfo>,
+        writer: &mut WriteHalf<MaybeTls>,
+        ctx: &ProcessContext<'_>,
     ) -> Result<(), anyhow::Error> {
-        use crate::network::zsp::protocol::parser::parse_command;
-
-        match parse_command(frame) {
-            Ok(store_cmd) => {
-                let resp = execute_store_command(engine, store_cmd);
-                match resp {
-                    Ok(frame) => {
-                        let encoded = ZspEncoder::encode(&frame)
-                            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
-                        let bytes_sent = encoded.len() as u64;
-
-                        timeout(config.write_timeout, writer.write_all(&encoded))
-                            .await
-                            .context("Write timeout")??;
-                        timeout(config.write_timeout, writer.flush())
-                            .await
-                            .context("Write timeout")??;
+        let engine = ctx.db.lock().clone();
+        let result = {
+            let _shared = ctx.exec_lock.read().await;
+            Self::execute_zsp_command(&engine, frame, ctx.admin.slowlog(), ctx.addr).await
+        };
 
-                        // Записываем статистику
-                        connection_info.record_command(0, bytes_sent);
+        match result {
+            Ok(frame) => {
+                let encoded = ZspEncoder::encode_with(&frame, ctx.connection_info.compression())
+                    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+                let bytes_sent = encoded.len() as u64;
 
-                        Ok(())
-                    }
-                    Err(e) => {
-                        connection_info.record_error();
-                        let err_frame = ZspFrame::FrameError(format!("ERR exec: {e}"));
-                        let enc = ZspEncoder::encode(&err_frame)
-                            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
-                        timeout(config.write_timeout, writer.write_all(&enc))
-                            .await
-                            .context("Write timeout")??;
-                        Ok(())
-                    }
-                }
+                timeout(ctx.config.write_timeout, writer.write_all(&encoded))
+                    .await
+                    .context("Write timeout")??;
+                timeout(ctx.config.write_timeout, writer.flush())
+                    .await
+                    .context("Write timeout")??;
+
+                // Записываем статистику
+                ctx.connection_info.record_command(0, bytes_sent);
+
+                Ok(())
             }
-            Err(e) => {
-                connection_info.record_error();
-                let err_frame = ZspFrame::FrameError(format!("ERR parse: {e}"));
+            Err(err_frame) => {
+                ctx.connection_info.record_error();
                 let enc =
                     ZspEncoder::encode(&err_frame).map_err(|e| anyhow::anyhow!(e.to_string()))?;
-                timeout(config.write_timeout, writer.write_all(&enc))
+                timeout(ctx.config.write_timeout, writer.write_all(&enc))
                     .await
                     .context("Write timeout")??;
                 Ok(())
             }
         }
     }
+
+    /// Разбирает ZSP-фрейм в команду хранилища и выполняет её, учитывая
+    /// время выполнения в метриках и slowlog.
+    ///
+    /// # Возвращает
+    /// - `Ok(ZspFrame)` — ответ на команду
+    /// - `Err(ZspFrame)` — `FrameError` при ошибке разбора или выполнения
+    async fn execute_zsp_command(
+        engine: &Arc<StorageEngine>,
+        frame: ZspFrame<'static>,
+        slowlog: &SlowLog,
+        addr: SocketAddr,
+    ) -> Result<ZspFrame<'static>, ZspFrame<'static>> {
+        // Копия фрейма нужна только для аргументов записи slowlog.
+        let slowlog_frame = slowlog.is_enabled().then(|| frame.clone());
+
+        let store_cmd =
+            parse_command(frame).map_err(|e| ZspFrame::FrameError(format!("ERR parse: {e}")))?;
+        let command = store_cmd.name();
+        let started = Instant::now();
+        let resp = execute_store_command(engine, store_cmd).await;
+        let elapsed = started.elapsed();
+        metrics::registry().record_command(command, elapsed);
+        if let Some(frame) = slowlog_frame {
+            slowlog.record(elapsed, || frame_args(&frame), addr);
+        }
+        resp.map_err(|e| ZspFrame::FrameError(format!("ERR exec: {e}")))
+    }
+
+    /// Проверяет ZSP-фрейм перед постановкой в очередь транзакции: команда
+    /// должна разбираться так же, как при немедленном выполнении.
+    ///
+    /// # Возвращает
+    /// - `Ok(frame)` — фрейм для очереди
+    /// - `Err(String)` — текст ошибки разбора
+    fn validate_queued(frame: &ZspFrame<'static>) -> Result<ZspFrame<'static>, String> {
+        if !frame_command_is(frame, "SELECT") && !frame_command_is(frame, "COMMAND") {
+            parse_command(frame.clone()).map_err(|e| format!("ERR parse: {e}"))?;
+        }
+        Ok(frame.clone())
+    }
+}
+
+impl ConnectionConfig {
+    /// Переносит в конфигурацию лимиты и таймауты из `params`.
+    pub fn apply_runtime(
+        &mut self,
+        params: &RuntimeParams,
+    ) {
+        self.max_connections = params.max_connections;
+        self.max_connections_per_ip = params.max_connections_per_ip;
+        self.idle_timeout = Duration::from_secs(params.connection_timeout);
+        self.read_timeout = Duration::from_secs(params.read_timeout);
+        self.write_timeout = Duration::from_secs(params.write_timeout);
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для ConnectionConfig
 ////////////////////////////////////////////////////////////////////////////////
 
+impl From<&ConnectionConfig> for RuntimeParams {
+    fn from(config: &ConnectionConfig) -> Self {
+        Self {
+            max_connections: config.max_connections,
+            max_connections_per_ip: config.max_connections_per_ip,
+            connection_timeout: config.idle_timeout.as_secs(),
+            read_timeout: config.read_timeout.as_secs(),
+            write_timeout: config.write_timeout.as_secs(),
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for ConnectionConfig {
     fn default() -> Self {
         Self {
@@ -1101,6 +1976,9 @@ impl Default for ConnectionConfig {
             read_timeout: Duration::from_secs(30),
             write_timeout: Duration::from_secs(10),
             read_buffer_size: 8192,
+            monitor_capacity: DEFAULT_MONITOR_CAPACITY,
+            #[cfg(feature = "tls")]
+            tls_config: None,
         }
     }
 }
@@ -1109,6 +1987,54 @@ impl Default for ConnectionConfig {
 // Внутренние методы и функции
 ////////////////////////////////////////////////////////////////////////////////
 
+/// Извлекает аргументы команды из ZSP-фрейма для записи в slowlog.
+fn frame_args(frame: &ZspFrame) -> Vec<String> {
+    let arg = |f: &ZspFrame| match f {
+        ZspFrame::InlineString(s) => s.to_string(),
+        ZspFrame::BinaryString(Some(b)) => String::from_utf8_lossy(b).into_owned(),
+        ZspFrame::Integer(i) => i.to_string(),
+        ZspFrame::Float(f) => f.to_string(),
+        other => format!("{other:?}"),
+    };
+    match frame {
+        ZspFrame::Array(items) => items.iter().map(arg).collect(),
+        other => vec![arg(other)],
+    }
+}
+
+/// Проверяет, что фрейм — команда `name` (без учёта регистра), не разбирая
+/// остальные аргументы.
+fn frame_command_is(
+    frame: &ZspFrame,
+    name: &str,
+) -> bool {
+    frame_command(frame).is_some_and(|command| command.eq_ignore_ascii_case(name))
+}
+
+/// Возвращает имя команды из ZSP-фрейма — первый элемент массива или сам
+/// строковый фрейм.
+fn frame_command<'f>(frame: &'f ZspFrame) -> Option<&'f str> {
+    let first = match frame {
+        ZspFrame::Array(items) => items.first(),
+        other => Some(other),
+    };
+    match first {
+        Some(ZspFrame::InlineString(s)) => Some(s),
+        Some(ZspFrame::BinaryString(Some(b))) => std::str::from_utf8(b).ok(),
+        _ => None,
+    }
+}
+
+/// Разбирает ответ текстового протокола в ZSP-фрейм, чтобы собрать ответы
+/// `EXEC` в один массив.
+fn resp_to_frame(response: &str) -> ZspFrame<'static> {
+    let mut slice = response.as_bytes();
+    match ZspDecoder::new().decode(&mut slice) {
+        Ok(Some(frame)) => frame.into_owned(),
+        _ => ZspFrame::FrameError(format!("ERR invalid reply: {}", response.trim_end())),
+    }
+}
+
 /// Выполняет команду хранилища и возвращает соответствующий ZSP-фрейм.
 ///
 /// Функция преобразует `StoreCommand` в действие на `StorageEngine` и формирует
@@ -1119,11 +2045,12 @@ impl Default for ConnectionConfig {
 ///     - `InlineString("OK")` для успешных команд типа SET/MSET
 ///     - `BinaryString(Some(...))` для GET с найденными значениями
 ///     - `BinaryString(None)` для GET с отсутствующими ключами
+///     - `BinaryString(...)` для GETDEL с удалённым значением или `None`
 ///     - `Integer(1|0)` для DEL в зависимости от того, был ли удалён ключ
 ///     - `Array([...])` для MGET с результатами по каждому ключу
 ///     - `FrameError` для неподдерживаемых типов или ошибок
 /// - `Err(String)` — строковое представление ошибки при выполнении команды.
-fn execute_store_command(
+async fn execute_store_command(
     engine: &Arc<StorageEngine>,
     cmd: crate::StoreCommand,
 ) -> Result<ZspFrame<'static>, String> {
@@ -1131,33 +2058,54 @@ fn execute_store_command(
     match cmd {
         crate::StoreCommand::Set(set) => {
             let k = Sds::from_str(&set.key);
-            engine.set(&k, set.value).map_err(|e| e.to_string())?;
+            AsyncStorage::set(&**engine, &k, set.value)
+                .await
+                .map_err(|e| e.to_string())?;
             Ok(ZspFrame::InlineString(Cow::Owned("OK".into())))
         }
         crate::StoreCommand::Get(get) => {
             let k = Sds::from_str(&get.key);
-            match engine.get(&k).map_err(|e| e.to_string())? {
+            match AsyncStorage::get(&**engine, &k)
+                .await
+                .map_err(|e| e.to_string())?
+            {
                 Some(Value::Str(s)) => Ok(ZspFrame::BinaryString(Some(s.to_vec()))),
                 Some(_) => Ok(ZspFrame::FrameError("ERR Unsupported type".into())),
                 None => Ok(ZspFrame::BinaryString(None)),
             }
         }
+        crate::StoreCommand::GetDel(getdel) => {
+            let k = Sds::from_str(&getdel.key);
+            match AsyncStorage::getdel(&**engine, &k)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                Some(s) => Ok(ZspFrame::BinaryString(Some(s.to_vec()))),
+                None => Ok(ZspFrame::BinaryString(None)),
+            }
+        }
         crate::StoreCommand::Del(del) => {
             let k = Sds::from_str(&del.key);
-            let r = engine.del(&k).map_err(|e| e.to_string())?;
+            let r = AsyncStorage::del(&**engine, &k)
+                .await
+                .map_err(|e| e.to_string())?;
             Ok(ZspFrame::Integer(if r { 1 } else { 0 }))
         }
         crate::StoreCommand::MSet(mset) => {
             for (k_s, v) in mset.entries {
                 let k = Sds::from_str(&k_s);
-                engine.set(&k, v).map_err(|e| e.to_string())?;
+                AsyncStorage::set(&**engine, &k, v)
+                    .await
+                    .map_err(|e| e.to_string())?;
             }
             Ok(ZspFrame::InlineString(Cow::Owned("OK".into())))
         }
         crate::StoreCommand::MGet(mget) => {
             let keys: Vec<Sds> = mget.keys.into_iter().map(|s| Sds::from_str(&s)).collect();
             let refs: Vec<&Sds> = keys.iter().collect();
-            let vals = engine.mget(&refs).map_err(|e| e.to_string())?;
+            let vals = AsyncStorage::mget(&**engine, &refs)
+                .await
+                .map_err(|e| e.to_string())?;
             {
                 let arr = vals
                     .into_iter()
@@ -1170,6 +2118,20 @@ fn execute_store_command(
                 Ok(ZspFrame::Array(arr))
             }
         }
+        crate::StoreCommand::ObjectEncoding(cmd) => {
+            let k = Sds::from_str(&cmd.key);
+            match AsyncStorage::get(&**engine, &k)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                // Имя кодировки — текст для показа пользователю
+                Some(value) => Ok(ZspFrame::VerbatimString {
+                    format: *b"txt",
+                    data: value.encoding().as_bytes().to_vec(),
+                }),
+                None => Ok(ZspFrame::Null),
+            }
+        }
         _ => Ok(ZspFrame::FrameError("ERR unsupported command".into())),
     }
 }
@@ -1187,7 +2149,9 @@ mod tests {
     };
 
     use super::*;
-    use crate::InMemoryStore;
+    use crate::{
+        command_registry::builtin_commands, network::zsp::COMPRESSED_MAGIC, InMemoryStore,
+    };
 
     #[tokio::test(flavor = "current_thread")]
     #[allow(clippy::arc_with_non_send_sync)]
@@ -1211,15 +2175,25 @@ mod tests {
             let shutdown_notify = Arc::new(tokio::sync::Notify::new());
             let registry = Arc::new(ConnectionRegistry::new());
             let (_, conn_info) = registry.register(addr);
+            let runtime = RuntimeConfig::new(RuntimeParams::from(&cfg_server));
+            let admin = Arc::new(AdminCommands::with_runtime_config(
+                registry.clone(),
+                runtime.clone(),
+            ));
 
             let handler = ConnectionHandler::new(
                 1,
-                socket,
+                socket.into(),
                 addr,
                 engine_server,
                 cfg_server,
+                runtime,
+                admin,
                 shutdown_notify,
                 conn_info,
+                None,
+                Arc::new(Broker::new()),
+                Arc::new(tokio::sync::RwLock::new(())),
             );
             handler.run().await?;
             Ok::<(), anyhow::Error>(())
@@ -1244,4 +2218,603 @@ mod tests {
         tokio::try_join!(server_fut, client_fut)?;
         Ok(())
     }
+
+    /// Тест проверяет, что OBJECT ENCODING по ZSP отвечает verbatim-строкой
+    /// формата `txt`, а для отсутствующего ключа — Null.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn object_encoding_replies_with_verbatim_string() -> anyhow::Result<()> {
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        AsyncStorage::set(
+            &*engine,
+            &Sds::from_str("n"),
+            Value::Str(Sds::from_str("42")),
+        )
+        .await?;
+
+        let object_encoding = |key: &str| {
+            crate::StoreCommand::ObjectEncoding(crate::ObjectEncodingCommand { key: key.into() })
+        };
+        let frame = execute_store_command(&engine, object_encoding("n"))
+            .await
+            .map_err(anyhow::Error::msg)?;
+        assert_eq!(
+            frame,
+            ZspFrame::VerbatimString {
+                format: *b"txt",
+                data: b"int".to_vec(),
+            }
+        );
+
+        let frame = execute_store_command(&engine, object_encoding("missing"))
+            .await
+            .map_err(anyhow::Error::msg)?;
+        assert_eq!(frame, ZspFrame::Null);
+        Ok(())
+    }
+
+    /// Тест проверяет, что изменение `max_connections` через `CONFIG SET`
+    /// сразу применяется к новым соединениям.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn max_connections_change_applies_to_new_connections() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+
+        let admin_reply = manager
+            .admin
+            .execute(&["CONFIG", "SET", "max_connections", "1"]);
+        assert_eq!(admin_reply, Some("+OK\r\n".to_string()));
+
+        let first_client = TcpStream::connect(local_addr).await?;
+        let (first_socket, first_addr) = listener.accept().await?;
+        let second_client = TcpStream::connect(local_addr).await?;
+        let (second_socket, second_addr) = listener.accept().await?;
+        let third_client = TcpStream::connect(local_addr).await?;
+        let (third_socket, third_addr) = listener.accept().await?;
+
+        // Первое соединение занимает единственный слот, пока клиент открыт.
+        let first = manager.handle_connection(first_socket.into(), first_addr, engine.clone());
+
+        let others = async {
+            let rejected = manager
+                .handle_connection(second_socket.into(), second_addr, engine.clone())
+                .await;
+            assert!(rejected.is_err());
+            drop(second_client);
+
+            manager.runtime_config().set(&[("max_connections", "2")])?;
+
+            // Клиент уже закрыт, поэтому принятое соединение сразу завершится.
+            drop(third_client);
+            manager
+                .handle_connection(third_socket.into(), third_addr, engine.clone())
+                .await?;
+
+            drop(first_client);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (first_res, others_res) = tokio::join!(first, others);
+        first_res?;
+        others_res?;
+        assert_eq!(manager.active_connections(), 0);
+        Ok(())
+    }
+
+    /// Тест проверяет, что CLIENT KILL закрывает чужое соединение, а
+    /// вызывающий клиент получает ответ.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn client_kill_closes_target_connection() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+
+        let mut victim = TcpStream::connect(local_addr).await?;
+        let (victim_socket, victim_addr) = listener.accept().await?;
+        let mut killer = TcpStream::connect(local_addr).await?;
+        let (killer_socket, killer_addr) = listener.accept().await?;
+
+        let victim_conn =
+            manager.handle_connection(victim_socket.into(), victim_addr, engine.clone());
+        let killer_conn =
+            manager.handle_connection(killer_socket.into(), killer_addr, engine.clone());
+
+        let client = async {
+            let mut buf = [0u8; 64];
+            let command = format!("CLIENT KILL ADDR {}\r\n", victim.local_addr()?);
+            killer.write_all(command.as_bytes()).await?;
+            let n = killer.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b":1\r\n");
+
+            // Сервер закрывает соединение жертвы без ответа.
+            let n = timeout(Duration::from_secs(1), victim.read(&mut buf)).await??;
+            assert_eq!(n, 0);
+
+            drop(killer);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (victim_res, killer_res, client_res) = tokio::join!(victim_conn, killer_conn, client);
+        victim_res?;
+        killer_res?;
+        client_res?;
+        assert_eq!(manager.active_connections(), 0);
+        Ok(())
+    }
+
+    /// Тест проверяет, что клиент в режиме MONITOR получает команды других
+    /// соединений, не может выполнять свои команды, кроме RESET, и после
+    /// RESET перестаёт получать поток.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn monitor_streams_commands_until_reset() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+
+        let mut watcher = TcpStream::connect(local_addr).await?;
+        let (watcher_socket, watcher_addr) = listener.accept().await?;
+        let mut worker = TcpStream::connect(local_addr).await?;
+        let (worker_socket, worker_addr) = listener.accept().await?;
+
+        let watcher_conn =
+            manager.handle_connection(watcher_socket.into(), watcher_addr, engine.clone());
+        let worker_conn =
+            manager.handle_connection(worker_socket.into(), worker_addr, engine.clone());
+
+        let client = async {
+            let mut buf = [0u8; 256];
+            watcher.write_all(b"MONITOR\r\n").await?;
+            let n = watcher.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b"+OK\r\n");
+            assert!(manager.admin.monitor().is_active());
+
+            let list = manager.admin.handle_client_list(None, &[]);
+            assert!(list.contains(" flags=M "), "{list}");
+
+            worker.write_all(b"SET k v\r\n").await?;
+            let n = worker.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b"+OK\r\n");
+
+            let n = timeout(Duration::from_secs(1), watcher.read(&mut buf)).await??;
+            let line = std::str::from_utf8(&buf[..n])?;
+            let expected = format!(" [0 {}] \"SET\" \"k\" \"v\"\r\n", worker.local_addr()?);
+            assert!(line.starts_with('+') && line.ends_with(&expected), "{line}");
+
+            watcher.write_all(b"GET k\r\n").await?;
+            let n = watcher.read(&mut buf).await?;
+            assert!(buf[..n].starts_with(b"-ERR"));
+
+            watcher.write_all(b"RESET\r\n").await?;
+            let n = watcher.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b"+RESET\r\n");
+            assert!(!manager.admin.monitor().is_active());
+
+            watcher.write_all(b"GET k\r\n").await?;
+            let n = watcher.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b"+v\r\n");
+
+            drop(watcher);
+            drop(worker);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (watcher_res, worker_res, client_res) = tokio::join!(watcher_conn, worker_conn, client);
+        watcher_res?;
+        worker_res?;
+        client_res?;
+        assert_eq!(manager.active_connections(), 0);
+        Ok(())
+    }
+
+    /// Тест проверяет, что после PSUBSCRIBE news.* по ZSP соединение
+    /// получает push-фрейм PMESSAGE для PUBLISH в news.sports из другого
+    /// соединения и отмечается как клиент Pub/Sub.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn psubscribe_receives_pmessage_over_zsp() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+
+        let mut subscriber = TcpStream::connect(local_addr).await?;
+        let (subscriber_socket, subscriber_addr) = listener.accept().await?;
+        let mut publisher = TcpStream::connect(local_addr).await?;
+        let (publisher_socket, publisher_addr) = listener.accept().await?;
+
+        let subscriber_conn =
+            manager.handle_connection(subscriber_socket.into(), subscriber_addr, engine.clone());
+        let publisher_conn =
+            manager.handle_connection(publisher_socket.into(), publisher_addr, engine.clone());
+
+        let command = |args: &[&str]| {
+            let items = args
+                .iter()
+                .map(|arg| ZspFrame::BinaryString(Some(arg.as_bytes().to_vec())))
+                .collect();
+            ZspEncoder::encode(&ZspFrame::Array(items)).unwrap()
+        };
+        let push = |items: &[&str]| {
+            let (kind, rest) = items.split_first().unwrap();
+            let mut frames = vec![ZspFrame::InlineString(Cow::Owned(kind.to_string()))];
+            frames.extend(
+                rest.iter()
+                    .map(|item| ZspFrame::BinaryString(Some(item.as_bytes().to_vec()))),
+            );
+            frames
+        };
+
+        let client = async {
+            let mut buf = [0u8; 256];
+            subscriber
+                .write_all(&command(&["PSUBSCRIBE", "news.*"]))
+                .await?;
+            let n = subscriber.read(&mut buf).await?;
+            let mut expected = push(&["psubscribe", "news.*"]);
+            expected.push(ZspFrame::Integer(1));
+            assert_eq!(&buf[..n], ZspEncoder::encode(&ZspFrame::Push(expected))?);
+
+            let list = manager
+                .admin
+                .handle_client_list(Some(ClientKind::PubSub), &[]);
+            assert!(
+                list.contains(&subscriber.local_addr()?.to_string()),
+                "{list}"
+            );
+
+            publisher.write_all(&command(&["PUBSUB", "NUMPAT"])).await?;
+            let n = publisher.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b":1\r\n");
+
+            publisher
+                .write_all(&command(&["PUBLISH", "news.sports", "goal"]))
+                .await?;
+            let n = publisher.read(&mut buf).await?;
+            assert_eq!(&buf[..n], b":1\r\n");
+
+            let n = timeout(Duration::from_secs(1), subscriber.read(&mut buf)).await??;
+            let expected = push(&["pmessage", "news.*", "news.sports", "goal"]);
+            assert_eq!(&buf[..n], ZspEncoder::encode(&ZspFrame::Push(expected))?);
+
+            drop(subscriber);
+            drop(publisher);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (subscriber_res, publisher_res, client_res) =
+            tokio::join!(subscriber_conn, publisher_conn, client);
+        subscriber_res?;
+        publisher_res?;
+        client_res?;
+        assert_eq!(manager.active_connections(), 0);
+        Ok(())
+    }
+
+    /// Тест проверяет, что `HELLO 3 COMPRESS lz4` включает сжатие: крупные
+    /// ответы приходят фреймом `|compressed|`, мелкие — как есть.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn hello_negotiates_frame_compression() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let value = "x".repeat(4096);
+        AsyncStorage::set(
+            &*engine,
+            &Sds::from_str("big"),
+            Value::Str(Sds::from_str(&value)),
+        )
+        .await?;
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+        let mut client = TcpStream::connect(local_addr).await?;
+        let (socket, addr) = listener.accept().await?;
+        let conn = manager.handle_connection(socket.into(), addr, engine.clone());
+
+        let command = |args: &[&str]| {
+            let items = args
+                .iter()
+                .map(|arg| ZspFrame::BinaryString(Some(arg.as_bytes().to_vec())))
+                .collect();
+            ZspEncoder::encode(&ZspFrame::Array(items)).unwrap()
+        };
+
+        let client_fut = async {
+            let mut buf = [0u8; 1024];
+            client
+                .write_all(&command(&["HELLO", "2", "COMPRESS", "lz4"]))
+                .await?;
+            let n = client.read(&mut buf).await?;
+            assert!(buf[..n].starts_with(b"-ERR"));
+
+            client.write_all(&command(&["HELLO", "4"])).await?;
+            let n = client.read(&mut buf).await?;
+            assert!(buf[..n].starts_with(b"-NOPROTO"));
+
+            client
+                .write_all(&command(&["HELLO", "3", "COMPRESS", "lz4"]))
+                .await?;
+            let n = client.read(&mut buf).await?;
+            let Some(ZspFrame::Dictionary(reply)) = ZspDecoder::new().decode(&mut &buf[..n])?
+            else {
+                panic!("expected HELLO map reply");
+            };
+            assert_eq!(reply["proto"], ZspFrame::Integer(3));
+            assert_eq!(reply["compression"], ZspFrame::InlineString("lz4".into()));
+
+            client.write_all(&command(&["GET", "big"])).await?;
+            let n = client.read(&mut buf).await?;
+            assert!(buf[..n].starts_with(COMPRESSED_MAGIC));
+            let decoded = ZspDecoder::new().decode(&mut &buf[..n])?;
+            assert_eq!(
+                decoded,
+                Some(ZspFrame::BinaryString(Some(value.clone().into_bytes())))
+            );
+
+            client.write_all(&command(&["GET", "missing"])).await?;
+            let n = client.read(&mut buf).await?;
+            assert!(!buf[..n].starts_with(COMPRESSED_MAGIC));
+
+            drop(client);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (conn_res, client_res) = tokio::join!(conn, client_fut);
+        conn_res?;
+        client_res?;
+        Ok(())
+    }
+
+    /// Отправляет команду и читает один ответ.
+    async fn roundtrip(
+        stream: &mut TcpStream,
+        command: &[u8],
+    ) -> anyhow::Result<String> {
+        let mut buf = [0u8; 128];
+        stream.write_all(command).await?;
+        let n = stream.read(&mut buf).await?;
+        Ok(String::from_utf8_lossy(&buf[..n]).into_owned())
+    }
+
+    /// Тест проверяет, что SELECT переключает базу только для своего
+    /// соединения, FLUSHDB очищает только выбранную базу, а неверный индекс
+    /// отклоняется.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn select_switches_database_per_connection() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+
+        let mut first = TcpStream::connect(local_addr).await?;
+        let (first_socket, first_addr) = listener.accept().await?;
+        let mut second = TcpStream::connect(local_addr).await?;
+        let (second_socket, second_addr) = listener.accept().await?;
+
+        let first_conn = manager.handle_connection(first_socket.into(), first_addr, engine.clone());
+        let second_conn =
+            manager.handle_connection(second_socket.into(), second_addr, engine.clone());
+
+        let client = async {
+            assert_eq!(roundtrip(&mut first, b"SET k zero\r\n").await?, "+OK\r\n");
+            assert_eq!(roundtrip(&mut first, b"SELECT 1\r\n").await?, "+OK\r\n");
+            assert_eq!(roundtrip(&mut first, b"GET k\r\n").await?, "$-1\r\n");
+            assert_eq!(roundtrip(&mut first, b"SET k one\r\n").await?, "+OK\r\n");
+            assert_eq!(
+                roundtrip(&mut first, b"SELECT 16\r\n").await?,
+                "-ERR DB index is out of range\r\n"
+            );
+
+            // Второе соединение по-прежнему работает с базой 0.
+            assert_eq!(roundtrip(&mut second, b"GET k\r\n").await?, "+zero\r\n");
+
+            assert_eq!(
+                roundtrip(&mut first, b"FLUSHDB ASYNC\r\n").await?,
+                "+OK\r\n"
+            );
+            assert_eq!(roundtrip(&mut first, b"GET k\r\n").await?, "$-1\r\n");
+            assert_eq!(roundtrip(&mut second, b"GET k\r\n").await?, "+zero\r\n");
+
+            let list = manager.admin.handle_client_list(None, &[]);
+            assert!(list.contains(" db=1 ") && list.contains(" db=0 "), "{list}");
+
+            assert_eq!(
+                roundtrip(&mut first, b"FLUSHALL LATER\r\n").await?,
+                "-ERR syntax error\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut first, b"flushall sync\r\n").await?,
+                "+OK\r\n"
+            );
+            assert_eq!(roundtrip(&mut second, b"GET k\r\n").await?, "$-1\r\n");
+            assert_eq!(roundtrip(&mut second, b"TYPE k\r\n").await?, "+none\r\n");
+            assert_eq!(roundtrip(&mut second, b"SET k v\r\n").await?, "+OK\r\n");
+            assert_eq!(roundtrip(&mut second, b"TYPE k\r\n").await?, "+string\r\n");
+
+            drop(first);
+            drop(second);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (first_res, second_res, client_res) = tokio::join!(first_conn, second_conn, client);
+        first_res?;
+        second_res?;
+        client_res?;
+        Ok(())
+    }
+
+    /// Тест проверяет `COMMAND` в обоих протоколах, в том числе внутри
+    /// транзакции ZSP.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn command_introspection_in_both_protocols() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+        let mut client = TcpStream::connect(local_addr).await?;
+        let (socket, addr) = listener.accept().await?;
+        let conn = manager.handle_connection(socket.into(), addr, engine);
+
+        let command = |args: &[&str]| {
+            let items = args
+                .iter()
+                .map(|arg| ZspFrame::BinaryString(Some(arg.as_bytes().to_vec())))
+                .collect();
+            ZspEncoder::encode(&ZspFrame::Array(items)).unwrap()
+        };
+        let count = format!(":{}\r\n", builtin_commands().len());
+
+        let client_fut = async {
+            assert_eq!(roundtrip(&mut client, b"COMMAND COUNT\r\n").await?, count);
+            assert_eq!(
+                roundtrip(&mut client, &command(&["COMMAND", "COUNT"])).await?,
+                count
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["COMMAND", "GETKEYS", "GET", "k"])).await?,
+                "*1\r\n+k\r\n"
+            );
+
+            assert_eq!(
+                roundtrip(&mut client, &command(&["MULTI"])).await?,
+                "+OK\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["COMMAND", "COUNT"])).await?,
+                "+QUEUED\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["EXEC"])).await?,
+                format!("*1\r\n{count}")
+            );
+
+            drop(client);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (conn_res, client_res) = tokio::join!(conn, client_fut);
+        conn_res?;
+        client_res?;
+        Ok(())
+    }
+
+    /// Тест проверяет MULTI/EXEC/DISCARD в обоих протоколах: команды
+    /// ставятся в очередь с ответом `QUEUED`, выполняются одним массивом
+    /// ответов на EXEC, а ошибка разбора при постановке отменяет транзакцию.
+    #[tokio::test(flavor = "current_thread")]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn multi_exec_queues_commands_in_both_protocols() -> anyhow::Result<()> {
+        let manager = ConnectionManager::new(ConnectionConfig::default());
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr = listener.local_addr()?;
+        let mut client = TcpStream::connect(local_addr).await?;
+        let (socket, addr) = listener.accept().await?;
+        let conn = manager.handle_connection(socket.into(), addr, engine.clone());
+
+        let command = |args: &[&str]| {
+            let items = args
+                .iter()
+                .map(|arg| ZspFrame::BinaryString(Some(arg.as_bytes().to_vec())))
+                .collect();
+            ZspEncoder::encode(&ZspFrame::Array(items)).unwrap()
+        };
+
+        let client_fut = async {
+            assert_eq!(
+                roundtrip(&mut client, b"EXEC\r\n").await?,
+                "-ERR EXEC without MULTI\r\n"
+            );
+            assert_eq!(roundtrip(&mut client, b"MULTI\r\n").await?, "+OK\r\n");
+            assert_eq!(
+                roundtrip(&mut client, b"MULTI\r\n").await?,
+                "-ERR MULTI calls can not be nested\r\n"
+            );
+            assert_eq!(roundtrip(&mut client, b"SET t 1\r\n").await?, "+QUEUED\r\n");
+            assert_eq!(roundtrip(&mut client, b"GET t\r\n").await?, "+QUEUED\r\n");
+            // Очередь общая: ZSP-команда попадает в ту же транзакцию.
+            assert_eq!(
+                roundtrip(&mut client, &command(&["SET", "z", "2"])).await?,
+                "+QUEUED\r\n"
+            );
+            assert!(AsyncStorage::get(&*engine, &Sds::from_str("t"))
+                .await?
+                .is_none());
+            assert_eq!(
+                roundtrip(&mut client, b"EXEC\r\n").await?,
+                "*3\r\n+OK\r\n+1\r\n+OK\r\n"
+            );
+
+            assert_eq!(
+                roundtrip(&mut client, &command(&["MULTI"])).await?,
+                "+OK\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["GET", "z"])).await?,
+                "+QUEUED\r\n"
+            );
+            let reply = roundtrip(&mut client, &command(&["EXEC"])).await?;
+            let decoded = ZspDecoder::new().decode(&mut reply.as_bytes())?;
+            assert_eq!(
+                decoded,
+                Some(ZspFrame::Array(vec![ZspFrame::BinaryString(Some(
+                    b"2".to_vec()
+                ))]))
+            );
+
+            assert_eq!(
+                roundtrip(&mut client, &command(&["MULTI"])).await?,
+                "+OK\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["SET", "z", "3"])).await?,
+                "+QUEUED\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["DISCARD"])).await?,
+                "+OK\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["DISCARD"])).await?,
+                "-ERR DISCARD without MULTI\r\n"
+            );
+
+            assert_eq!(
+                roundtrip(&mut client, &command(&["MULTI"])).await?,
+                "+OK\r\n"
+            );
+            assert!(roundtrip(&mut client, &command(&["NOSUCHCMD"]))
+                .await?
+                .starts_with("-ERR parse"));
+            assert_eq!(
+                roundtrip(&mut client, &command(&["SET", "z", "4"])).await?,
+                "+QUEUED\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["EXEC"])).await?,
+                "-EXECABORT Transaction discarded because of previous errors.\r\n"
+            );
+            assert_eq!(
+                roundtrip(&mut client, &command(&["GET", "z"])).await?,
+                "$1\r\n2\r\n"
+            );
+
+            drop(client);
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (conn_res, client_res) = tokio::join!(conn, client_fut);
+        conn_res?;
+        client_res?;
+        Ok(())
+    }
 }
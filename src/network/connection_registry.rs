@@ -8,8 +8,9 @@ use std::{
 };
 
 use parking_lot::RwLock;
+use serde::Serialize;
 
-use crate::network::connection_state::{ConnectionInfo, ConnectionSnapshot};
+use crate::network::connection_state::{ConnectionInfo, ConnectionMetadata, ConnectionSnapshot};
 
 /// Реестр активных соединений
 ///
@@ -32,7 +33,7 @@ pub struct ConnectionRegistry {
 /// * `total_bytes_sent` - общее число отправленных байт.
 /// * `total_bytes_received` - общее число полученных байт.
 /// * `total_errors` - общее число ошибок, зарегистрированных на соединениях.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct GlobalConnectionStats {
     pub active_connections: usize,
     pub total_commands: u64,
@@ -41,6 +42,23 @@ pub struct GlobalConnectionStats {
     pub total_errors: usize,
 }
 
+/// Критерии выбора соединений для `CLIENT KILL`.
+///
+/// Все заданные критерии должны выполняться одновременно; селектор без
+/// критериев не выбирает ни одного соединения.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientSelector {
+    /// ID соединения (`ID id`).
+    pub id: Option<u32>,
+    /// Адрес клиента (`ADDR ip:port`).
+    pub addr: Option<SocketAddr>,
+    /// Имя пользователя (`USER username`); неаутентифицированные соединения
+    /// считаются пользователем `default`.
+    pub user: Option<String>,
+    /// ID соединения, которое не закрывается (`SKIPME yes`).
+    pub skip: Option<u32>,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
@@ -127,6 +145,17 @@ impl ConnectionRegistry {
             .collect()
     }
 
+    /// Возвращает snapshots всех активных соединений в порядке возрастания
+    /// ID (`CLIENT LIST`).
+    ///
+    /// # Возвращает
+    /// - Вектор `ConnectionSnapshot`, отсортированный по `connection_id`.
+    pub fn list_all(&self) -> Vec<ConnectionSnapshot> {
+        let mut snapshots = self.all_snapshots();
+        snapshots.sort_unstable_by_key(|snapshot| snapshot.connection_id);
+        snapshots
+    }
+
     /// Возвращает snapshots соединений, отфильтрованных по предикату.
     ///
     /// # Возвращает
@@ -199,6 +228,40 @@ impl ConnectionRegistry {
         }
     }
 
+    /// Обнуляет счётчики статистики всех активных соединений
+    /// (`CONFIG RESETSTAT`).
+    ///
+    /// # Примечание
+    /// Сами соединения остаются в реестре.
+    pub fn reset_stats(&self) {
+        for info in self.connections.read().values() {
+            info.stats.reset();
+        }
+    }
+
+    /// Посылает сигнал закрытия всем соединениям, подходящим под `selector`
+    /// (`CLIENT KILL`).
+    ///
+    /// # Возвращает
+    /// - Количество соединений, которым отправлен сигнал.
+    ///
+    /// # Примечание
+    /// Соединения удаляются из реестра своими обработчиками после закрытия.
+    pub fn kill(
+        &self,
+        selector: ClientSelector,
+    ) -> usize {
+        let connections = self.connections.read();
+        let mut killed = 0;
+        for info in connections.values() {
+            if selector.matches(&info.metadata.read()) {
+                info.kill();
+                killed += 1;
+            }
+        }
+        killed
+    }
+
     /// Очищает все соединения (только для тестов или при graceful shutdown).
     ///
     /// # Примечание
@@ -209,6 +272,25 @@ impl ConnectionRegistry {
     }
 }
 
+impl ClientSelector {
+    /// Проверяет, подходит ли соединение под все заданные критерии.
+    pub fn matches(
+        &self,
+        meta: &ConnectionMetadata,
+    ) -> bool {
+        if self.id.is_none() && self.addr.is_none() && self.user.is_none() {
+            return false;
+        }
+        self.skip != Some(meta.connection_id)
+            && self.id.is_none_or(|id| id == meta.connection_id)
+            && self.addr.is_none_or(|addr| addr == meta.client_addr)
+            && self
+                .user
+                .as_deref()
+                .is_none_or(|user| user == meta.username.as_deref().unwrap_or("default"))
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для CommandRegistry, ConnectionRegistry
 ////////////////////////////////////////////////////////////////////////////////
@@ -225,7 +307,7 @@ impl Default for ConnectionRegistry {
 
 #[cfg(test)]
 mod tests {
-    use std::net::SocketAddr;
+    use std::{net::SocketAddr, time::Duration};
 
     use super::*;
 
@@ -275,6 +357,63 @@ mod tests {
         assert!(ids.contains(&id3));
     }
 
+    #[test]
+    fn test_registry_list_all_sorted() {
+        let registry = ConnectionRegistry::new();
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+
+        let ids: Vec<u32> = (0..5).map(|_| registry.register(addr).0).collect();
+        registry.unregister(ids[2]);
+        registry.get(ids[4]).unwrap().set_name(Some("last".into()));
+
+        let snapshots = registry.list_all();
+        let listed: Vec<u32> = snapshots.iter().map(|s| s.connection_id).collect();
+        assert_eq!(listed, vec![ids[0], ids[1], ids[3], ids[4]]);
+        assert_eq!(snapshots[3].name.as_deref(), Some("last"));
+    }
+
+    #[tokio::test]
+    async fn test_registry_kill_by_selector() {
+        let registry = ConnectionRegistry::new();
+        let addr1: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let addr2: SocketAddr = "127.0.0.1:1002".parse().unwrap();
+
+        let (id1, info1) = registry.register(addr1);
+        let (id2, info2) = registry.register(addr2);
+        let (_, info3) = registry.register(addr2);
+        info3.metadata.write().set_username("alice".into());
+
+        // Пустой селектор ничего не выбирает
+        assert_eq!(registry.kill(ClientSelector::default()), 0);
+
+        let by_id = ClientSelector {
+            id: Some(id1),
+            ..Default::default()
+        };
+        assert_eq!(registry.kill(by_id), 1);
+
+        let by_addr = ClientSelector {
+            addr: Some(addr2),
+            skip: Some(id2),
+            ..Default::default()
+        };
+        assert_eq!(registry.kill(by_addr), 1);
+
+        let by_user = ClientSelector {
+            user: Some("default".into()),
+            ..Default::default()
+        };
+        assert_eq!(registry.kill(by_user), 2);
+
+        // Сигналы запоминаются до ожидания обработчиком
+        for info in [info1, info2, info3] {
+            let notified = info.kill_signal.notified();
+            assert!(tokio::time::timeout(Duration::from_millis(10), notified)
+                .await
+                .is_ok());
+        }
+    }
+
     #[test]
     fn test_registry_snapshots() {
         let registry = ConnectionRegistry::new();
@@ -8,6 +8,9 @@ use std::{
 };
 
 use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::network::zsp::{CompressionAlgo, ZspFrame};
 
 /// Состояние соединения в его жизненном цикле.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -24,6 +27,18 @@ pub enum ConnectionState {
     Closing,
 }
 
+/// Тип клиента для фильтра `CLIENT LIST TYPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum ClientKind {
+    /// Обычный клиент
+    #[default]
+    Normal,
+    /// Клиент в режиме подписки Pub/Sub
+    PubSub,
+    /// Реплика
+    Replica,
+}
+
 /// Метаданные соединения.
 #[derive(Debug, Clone, Serialize)]
 pub struct ConnectionMetadata {
@@ -47,6 +62,18 @@ pub struct ConnectionMetadata {
     pub last_activity: Instant,
     /// Имя пользователя (если аутентифицирован)
     pub username: Option<String>,
+    /// Имя соединения, заданное через `CLIENT SETNAME`
+    pub name: Option<String>,
+    /// Тип клиента
+    pub kind: ClientKind,
+    /// Соединение не закрывается при вытеснении клиентов (`CLIENT NO-EVICT`)
+    pub no_evict: bool,
+    /// Соединение в режиме `MONITOR`
+    pub monitor: bool,
+    /// Индекс логической базы, выбранной через `SELECT`
+    pub db_index: usize,
+    /// Сжатие ZSP-фреймов, согласованное через `HELLO 3 COMPRESS`
+    pub compression: Option<CompressionAlgo>,
 }
 
 /// Потокобезопасная статистика соединения.
@@ -62,7 +89,40 @@ pub struct ConnectionStats {
     pub errors: AtomicUsize,
 }
 
+/// Команда, поставленная в очередь транзакции `MULTI`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueuedCommand {
+    /// Строка текстового протокола
+    Text(String),
+    /// Фрейм протокола ZSP
+    Zsp(ZspFrame<'static>),
+}
+
+/// Результат `EXEC` для состояния транзакции.
+#[derive(Debug, PartialEq)]
+pub enum ExecOutcome {
+    /// Блок `MULTI` не открыт
+    NotActive,
+    /// При постановке в очередь была ошибка: транзакция отменена
+    Aborted,
+    /// Команды, которые нужно выполнить, в порядке поступления
+    Run(Vec<QueuedCommand>),
+}
+
+/// Состояние транзакции `MULTI`/`EXEC` соединения, общее для текстового
+/// протокола и ZSP.
+#[derive(Debug, Default)]
+pub struct TransactionState {
+    /// Открыт ли блок `MULTI`
+    active: bool,
+    /// Была ли ошибка при постановке команды в очередь
+    aborted: bool,
+    /// Команды в порядке поступления
+    queue: Vec<QueuedCommand>,
+}
+
 /// Snapshot ьетаданных соединения для отправки клиенту.
+#[derive(Serialize)]
 pub struct ConnectionSnapshot {
     pub connection_id: u32,
     pub client_addr: String,
@@ -73,6 +133,12 @@ pub struct ConnectionSnapshot {
     pub bytes_sent: u64,
     pub bytes_received: u64,
     pub username: Option<String>,
+    pub name: Option<String>,
+    pub kind: ClientKind,
+    pub no_evict: bool,
+    pub monitor: bool,
+    pub db_index: usize,
+    pub age_secs: u64,
 }
 
 /// Информация о соединении для внутреннего использования.
@@ -80,6 +146,10 @@ pub struct ConnectionSnapshot {
 pub struct ConnectionInfo {
     pub metadata: Arc<parking_lot::RwLock<ConnectionMetadata>>,
     pub stats: Arc<ConnectionStats>,
+    /// Сигнал принудительного закрытия соединения (`CLIENT KILL`)
+    pub kill_signal: Arc<Notify>,
+    /// Транзакция `MULTI`/`EXEC`
+    pub transaction: parking_lot::Mutex<TransactionState>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -112,6 +182,12 @@ impl ConnectionMetadata {
             bytes_received: 0,
             last_activity: now,
             username: None,
+            name: None,
+            kind: ClientKind::Normal,
+            no_evict: false,
+            monitor: false,
+            db_index: 0,
+            compression: None,
         }
     }
 
@@ -233,6 +309,22 @@ impl ConnectionMetadata {
     }
 }
 
+impl ClientKind {
+    /// Разбирает тип клиента без учёта регистра (`normal`, `pubsub`,
+    /// `replica`).
+    ///
+    /// # Возвращает
+    /// - `Some(ClientKind)` для известного типа, иначе `None`
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "normal" => Some(Self::Normal),
+            "pubsub" => Some(Self::PubSub),
+            "replica" | "slave" => Some(Self::Replica),
+            _ => None,
+        }
+    }
+}
+
 impl ConnectionStats {
     /// Создаёт новую структуру статистики соединения.
     ///
@@ -337,6 +429,84 @@ impl ConnectionStats {
     pub fn get_errors(&self) -> usize {
         self.errors.load(Ordering::Relaxed)
     }
+
+    /// Обнуляет все счётчики (`CONFIG RESETSTAT`).
+    ///
+    /// # Примечания
+    /// - Счётчики сбрасываются независимо друг от друга, без общей блокировки
+    pub fn reset(&self) {
+        self.command_processed.store(0, Ordering::Relaxed);
+        self.bytes_sent.store(0, Ordering::Relaxed);
+        self.bytes_received.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+    }
+}
+
+impl TransactionState {
+    /// Возвращает `true`, если открыт блок `MULTI`.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Открывает блок `MULTI`.
+    ///
+    /// # Возвращает
+    /// - `false`, если блок уже открыт (вложенный `MULTI`)
+    pub fn begin(&mut self) -> bool {
+        if self.active {
+            return false;
+        }
+        self.active = true;
+        true
+    }
+
+    /// Ставит команду в очередь открытой транзакции.
+    pub fn enqueue(
+        &mut self,
+        command: QueuedCommand,
+    ) {
+        self.queue.push(command);
+    }
+
+    /// Помечает транзакцию как ошибочную: `EXEC` её отменит.
+    pub fn abort(&mut self) {
+        self.aborted = true;
+    }
+
+    /// Возвращает число команд в очереди.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Возвращает `true`, если очередь пуста.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Закрывает блок `MULTI` для `EXEC` и забирает очередь.
+    pub fn exec(&mut self) -> ExecOutcome {
+        if !self.active {
+            return ExecOutcome::NotActive;
+        }
+        let aborted = self.aborted;
+        let queue = std::mem::take(&mut self.queue);
+        *self = Self::default();
+        if aborted {
+            ExecOutcome::Aborted
+        } else {
+            ExecOutcome::Run(queue)
+        }
+    }
+
+    /// Отменяет транзакцию (`DISCARD`, `RESET`).
+    ///
+    /// # Возвращает
+    /// - `false`, если блок `MULTI` не был открыт
+    pub fn discard(&mut self) -> bool {
+        let active = self.active;
+        *self = Self::default();
+        active
+    }
 }
 
 impl ConnectionInfo {
@@ -359,6 +529,8 @@ impl ConnectionInfo {
                 client_addr,
             ))),
             stats: Arc::new(ConnectionStats::new()),
+            kill_signal: Arc::new(Notify::new()),
+            transaction: parking_lot::Mutex::new(TransactionState::default()),
         }
     }
 
@@ -433,13 +605,104 @@ impl ConnectionInfo {
         self.stats.add_bytes_sent(bytes_sent);
     }
 
+    /// Устанавливает или сбрасывает имя соединения (`CLIENT SETNAME`).
+    pub fn set_name(
+        &self,
+        name: Option<String>,
+    ) {
+        self.metadata.write().name = name;
+    }
+
+    /// Возвращает имя соединения, если оно задано.
+    pub fn name(&self) -> Option<String> {
+        self.metadata.read().name.clone()
+    }
+
+    /// Устанавливает тип клиента.
+    pub fn set_kind(
+        &self,
+        kind: ClientKind,
+    ) {
+        self.metadata.write().kind = kind;
+    }
+
+    /// Включает или выключает защиту соединения от вытеснения
+    /// (`CLIENT NO-EVICT`).
+    pub fn set_no_evict(
+        &self,
+        no_evict: bool,
+    ) {
+        self.metadata.write().no_evict = no_evict;
+    }
+
+    /// Проверяет, защищено ли соединение от вытеснения.
+    pub fn is_no_evict(&self) -> bool {
+        self.metadata.read().no_evict
+    }
+
+    /// Включает или выключает режим `MONITOR`.
+    pub fn set_monitor(
+        &self,
+        monitor: bool,
+    ) {
+        self.metadata.write().monitor = monitor;
+    }
+
+    /// Проверяет, находится ли соединение в режиме `MONITOR`.
+    pub fn is_monitor(&self) -> bool {
+        self.metadata.read().monitor
+    }
+
+    /// Запоминает логическую базу, выбранную через `SELECT`.
+    pub fn set_db_index(
+        &self,
+        db_index: usize,
+    ) {
+        self.metadata.write().db_index = db_index;
+    }
+
+    /// Возвращает индекс текущей логической базы соединения.
+    pub fn db_index(&self) -> usize {
+        self.metadata.read().db_index
+    }
+
+    /// Включает или выключает сжатие ответов (`HELLO 3 COMPRESS`).
+    pub fn set_compression(
+        &self,
+        compression: Option<CompressionAlgo>,
+    ) {
+        self.metadata.write().compression = compression;
+    }
+
+    /// Возвращает алгоритм сжатия ответов, если оно активно.
+    pub fn compression(&self) -> Option<CompressionAlgo> {
+        self.metadata.read().compression
+    }
+
+    /// Просит обработчик соединения закрыть его (`CLIENT KILL`).
+    ///
+    /// # Примечания
+    /// - Сигнал запоминается, если обработчик сейчас выполняет команду:
+    ///   соединение закроется после отправки текущего ответа
+    pub fn kill(&self) {
+        self.kill_signal.notify_one();
+    }
+
     pub fn record_error(&self) {
         self.stats.increment_errors();
     }
+
+    /// Возвращает транзакцию `MULTI`/`EXEC` соединения.
+    ///
+    /// # Примечания
+    /// - Блокировку нельзя удерживать через `.await`
+    pub fn transaction(&self) -> parking_lot::MutexGuard<'_, TransactionState> {
+        self.transaction.lock()
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
-// Общие реализации трейтов для ConnectionState, ConnectionStats,
+// Общие реализации трейтов для ConnectionState, ClientKind, ConnectionStats,
 // ConnectionSnapshot
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -474,6 +737,20 @@ impl std::fmt::Display for ConnectionState {
     }
 }
 
+impl std::fmt::Display for ClientKind {
+    /// Форматирует тип клиента так же, как он задаётся в `CLIENT LIST TYPE`.
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::Normal => write!(f, "normal"),
+            Self::PubSub => write!(f, "pubsub"),
+            Self::Replica => write!(f, "replica"),
+        }
+    }
+}
+
 impl From<&ConnectionMetadata> for ConnectionSnapshot {
     /// Создаёт снимок состояния соединения на основе метаданных.
     ///
@@ -501,6 +778,12 @@ impl From<&ConnectionMetadata> for ConnectionSnapshot {
             bytes_sent: meta.bytes_sent,
             bytes_received: meta.bytes_received,
             username: meta.username.clone(),
+            name: meta.name.clone(),
+            kind: meta.kind,
+            no_evict: meta.no_evict,
+            monitor: meta.monitor,
+            db_index: meta.db_index,
+            age_secs: meta.connected_at.elapsed().as_secs(),
         }
     }
 }
@@ -639,4 +922,37 @@ mod tests {
         assert_eq!(snapshot.bytes_sent, 200);
         assert_eq!(snapshot.bytes_received, 100);
     }
+
+    /// Тест проверяет жизненный цикл транзакции: `MULTI`, очередь, отмену
+    /// и `EXEC`.
+    #[test]
+    fn test_transaction_state() {
+        let mut tx = TransactionState::default();
+        assert_eq!(tx.exec(), ExecOutcome::NotActive);
+        assert!(!tx.discard());
+
+        assert!(tx.begin());
+        assert!(!tx.begin());
+        tx.enqueue(QueuedCommand::Text("SET a 1".into()));
+        tx.enqueue(QueuedCommand::Zsp(ZspFrame::InlineString("PING".into())));
+        assert_eq!(tx.len(), 2);
+        assert_eq!(
+            tx.exec(),
+            ExecOutcome::Run(vec![
+                QueuedCommand::Text("SET a 1".into()),
+                QueuedCommand::Zsp(ZspFrame::InlineString("PING".into())),
+            ])
+        );
+        assert!(!tx.is_active());
+        assert!(tx.is_empty());
+
+        assert!(tx.begin());
+        tx.abort();
+        assert_eq!(tx.exec(), ExecOutcome::Aborted);
+
+        assert!(tx.begin());
+        tx.enqueue(QueuedCommand::Text("GET a".into()));
+        assert!(tx.discard());
+        assert_eq!(tx.exec(), ExecOutcome::NotActive);
+    }
 }
@@ -4,6 +4,7 @@
 //!
 //! ## Подмодули
 //!
+//! - `admin_api` — административный HTTP JSON API (фича `admin`).
 //! - `banner`: реализация банера для консольного вывода информации при старте
 //!   сервера Зумик
 //! - `server`: реализация сетевого сервера (приём и обработка соединений).
@@ -11,17 +12,26 @@
 //!   TCP-клиентов/серверов.
 //! - `connection_state` — определения состояний соединений и связанные с ними
 //!   перечисления.
+//! - `monitor` — рассылка выполняемых команд клиентам в режиме `MONITOR`.
+//! - `slowlog` — журнал медленных команд (`SLOWLOG`), общий для всех
+//!   соединений.
+//! - `tls` — поток соединения с опциональным TLS (`MaybeTls`) и загрузка
+//!   сертификатов (фича `tls`).
 //! - `zsp`: реализация собственного протокола ZSP: фрейминг, парсинг,
 //!   сериализация.
 //!
 //! Импортируя `network`, вы получаете полный набор для работы с сетью.
 
+pub mod admin_api;
 pub mod admin_commands;
 pub mod banner;
 pub mod connection;
 pub mod connection_registry;
 pub mod connection_state;
+pub mod monitor;
 pub mod server;
+pub mod slowlog;
+pub mod tls;
 pub mod zsp;
 
 // Publicly re-export all error types and functions from the submodules to
@@ -0,0 +1,262 @@
+use std::{
+    fmt::Write,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use parking_lot::Mutex;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Ёмкость канала `MONITOR` по умолчанию (в строках).
+pub const DEFAULT_MONITOR_CAPACITY: usize = 1024;
+
+/// Рассылка команд клиентам в режиме `MONITOR`.
+///
+/// Каждая разобранная команда форматируется строкой
+/// `+timestamp [db addr] "cmd" "arg"...` и отправляется в
+/// `tokio::sync::broadcast`-канал. Отправка не блокирует обработку команд:
+/// отставший подписчик теряет самые старые строки. Клонирование дёшево: все
+/// клоны ссылаются на один канал.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    sender: broadcast::Sender<Arc<str>>,
+    /// Есть ли хотя бы один подписчик; единственная проверка на горячем пути.
+    active: Arc<AtomicBool>,
+    /// Число подписчиков. Под блокировкой, чтобы `active` не расходился с
+    /// ним при одновременной подписке и отписке.
+    subscribers: Arc<Mutex<usize>>,
+}
+
+/// Подписка соединения на поток `MONITOR`.
+///
+/// При уничтожении уменьшает число подписчиков; после ухода последнего
+/// рассылка отключается.
+#[derive(Debug)]
+pub struct MonitorSubscription {
+    receiver: broadcast::Receiver<Arc<str>>,
+    monitor: Monitor,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Monitor {
+    /// Создаёт рассылку с каналом на `capacity` строк.
+    ///
+    /// # Примечания
+    /// - Нулевая ёмкость заменяется на 1 (требование `broadcast`)
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            active: Arc::new(AtomicBool::new(false)),
+            subscribers: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Проверяет, есть ли подписчики. Не берёт блокировку.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Возвращает число подписчиков.
+    pub fn subscribers(&self) -> usize {
+        *self.subscribers.lock()
+    }
+
+    /// Подписывает соединение на поток команд.
+    pub fn subscribe(&self) -> MonitorSubscription {
+        let mut subscribers = self.subscribers.lock();
+        let receiver = self.sender.subscribe();
+        *subscribers += 1;
+        self.active.store(true, Ordering::Relaxed);
+        MonitorSubscription {
+            receiver,
+            monitor: self.clone(),
+        }
+    }
+
+    /// Рассылает команду `args`, полученную от клиента `addr` в базе `db`,
+    /// всем подписчикам.
+    ///
+    /// # Возвращает
+    /// - `true`, если строка отправлена хотя бы одному подписчику
+    pub fn feed<S: AsRef<str>>(
+        &self,
+        addr: SocketAddr,
+        db: usize,
+        args: &[S],
+    ) -> bool {
+        if !self.is_active() || args.is_empty() {
+            return false;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        self.sender
+            .send(format_line(now, addr, db, args).into())
+            .is_ok()
+    }
+
+    fn unsubscribe(&self) {
+        let mut subscribers = self.subscribers.lock();
+        *subscribers = subscribers.saturating_sub(1);
+        self.active.store(*subscribers > 0, Ordering::Relaxed);
+    }
+}
+
+impl MonitorSubscription {
+    /// Ожидает следующую строку потока.
+    ///
+    /// Строки, вытесненные из канала из-за отставания подписчика,
+    /// пропускаются.
+    ///
+    /// # Возвращает
+    /// - `Some(line)` — строка вида `+timestamp [db addr] "cmd" ...\r\n`
+    /// - `None` — канал закрыт
+    pub async fn recv(&mut self) -> Option<Arc<str>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(line) => return Some(line),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для Monitor и MonitorSubscription
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new(DEFAULT_MONITOR_CAPACITY)
+    }
+}
+
+impl Drop for MonitorSubscription {
+    fn drop(&mut self) {
+        self.monitor.unsubscribe();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Форматирует команду строкой потока `MONITOR`.
+fn format_line<S: AsRef<str>>(
+    timestamp: Duration,
+    addr: SocketAddr,
+    db: usize,
+    args: &[S],
+) -> String {
+    let mut line = format!(
+        "+{}.{:06} [{db} {addr}]",
+        timestamp.as_secs(),
+        timestamp.subsec_micros()
+    );
+    for arg in args {
+        line.push(' ');
+        quote_arg(&mut line, arg.as_ref());
+    }
+    line.push_str("\r\n");
+    line
+}
+
+/// Дописывает `arg` в кавычках, экранируя спецсимволы как `redis-cli`.
+fn quote_arg(
+    out: &mut String,
+    arg: &str,
+) {
+    out.push('"');
+    for b in arg.bytes() {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            b if b.is_ascii_graphic() || b == b' ' => out.push(b as char),
+            b => {
+                let _ = write!(out, "\\x{b:02x}");
+            }
+        }
+    }
+    out.push('"');
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> SocketAddr {
+        "127.0.0.1:60866".parse().unwrap()
+    }
+
+    /// Тест проверяет формат строки и экранирование аргументов.
+    #[test]
+    fn test_format_line() {
+        let line = format_line(
+            Duration::new(1339518083, 107_412_000),
+            addr(),
+            0,
+            &["SET", "key", "a \"b\"\n", "é"],
+        );
+        assert_eq!(
+            line,
+            "+1339518083.107412 [0 127.0.0.1:60866] \"SET\" \"key\" \"a \\\"b\\\"\\n\" \
+             \"\\xc3\\xa9\"\r\n"
+        );
+    }
+
+    /// Тест проверяет, что без подписчиков рассылка выключена, а с уходом
+    /// последнего подписчика выключается снова.
+    #[tokio::test]
+    async fn test_active_follows_subscribers() {
+        let monitor = Monitor::new(16);
+        assert!(!monitor.is_active());
+        assert!(!monitor.feed(addr(), 0, &["PING"]));
+
+        let mut first = monitor.subscribe();
+        let second = monitor.subscribe();
+        assert!(monitor.is_active());
+        assert_eq!(monitor.subscribers(), 2);
+
+        assert!(monitor.feed(addr(), 3, &["GET", "k"]));
+        let line = first.recv().await.unwrap();
+        assert!(line.ends_with("[3 127.0.0.1:60866] \"GET\" \"k\"\r\n"));
+
+        drop(second);
+        assert!(monitor.is_active());
+        drop(first);
+        assert!(!monitor.is_active());
+        assert_eq!(monitor.subscribers(), 0);
+    }
+
+    /// Тест проверяет, что отставший подписчик пропускает вытесненные строки
+    /// и получает последние.
+    #[tokio::test]
+    async fn test_lagged_subscriber_skips_old_lines() {
+        let monitor = Monitor::new(2);
+        let mut sub = monitor.subscribe();
+        for i in 0..5 {
+            monitor.feed(addr(), 0, &[i.to_string()]);
+        }
+
+        assert!(sub.recv().await.unwrap().contains("\"3\""));
+        assert!(sub.recv().await.unwrap().contains("\"4\""));
+    }
+}
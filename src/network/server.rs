@@ -5,8 +5,11 @@ use tokio::{net::TcpListener, signal, sync::oneshot, task::JoinHandle};
 use tracing::{error, info, warn};
 
 use crate::{
-    network::connection::{ConnectionConfig, ConnectionManager},
-    StorageEngine,
+    network::{
+        connection::{ConnectionConfig, ConnectionManager},
+        tls::StreamAcceptor,
+    },
+    AuthManager, RuntimeConfig, StorageEngine,
 };
 
 /// Конфигурация сервера.
@@ -45,7 +48,48 @@ impl Server {
         engine: Arc<StorageEngine>,
     ) -> Self {
         let connection_manager = Arc::new(ConnectionManager::new(config.connection_config.clone()));
+        Self::with_connection_manager(config, engine, connection_manager)
+    }
 
+    /// Создаёт сервер, лимиты и таймауты соединений которого берутся из
+    /// `runtime` и могут изменяться через `CONFIG SET`.
+    pub fn with_runtime_config(
+        config: ServerConfig,
+        engine: Arc<StorageEngine>,
+        runtime: RuntimeConfig,
+    ) -> Self {
+        let connection_manager = Arc::new(ConnectionManager::with_runtime_config(
+            config.connection_config.clone(),
+            runtime,
+        ));
+        Self::with_connection_manager(config, engine, connection_manager)
+    }
+
+    /// Включает аутентификацию: команды клиентов проверяются по пользователям
+    /// и правам `auth`. Вызывается до [`Server::start`].
+    pub fn with_auth(
+        mut self,
+        auth: AuthManager,
+    ) -> Self {
+        let manager = ConnectionManager::with_runtime_config(
+            self.config.connection_config.clone(),
+            self.connection_manager.runtime_config().clone(),
+        );
+        let broker = self.connection_manager.broker().clone();
+        self.connection_manager = Arc::new(manager.with_broker(broker).with_auth(auth));
+        self
+    }
+
+    /// Собирает сервер вокруг готового менеджера соединений.
+    fn with_connection_manager(
+        config: ServerConfig,
+        engine: Arc<StorageEngine>,
+        connection_manager: Arc<ConnectionManager>,
+    ) -> Self {
+        // События пространства ключей публикуются в брокер соединений.
+        if let Some(notifier) = engine.keyspace_notifier() {
+            notifier.set_broker(connection_manager.broker());
+        }
         Self {
             config,
             connection_manager,
@@ -97,6 +141,16 @@ impl Server {
         }
     }
 
+    /// Возвращает менеджер соединений сервера.
+    pub fn connection_manager(&self) -> &Arc<ConnectionManager> {
+        &self.connection_manager
+    }
+
+    /// Возвращает движок хранения сервера.
+    pub fn engine(&self) -> &Arc<StorageEngine> {
+        &self.engine
+    }
+
     /// Получение текущей статистики по соединениям.
     pub fn connection_stats(&self) -> ConnectionStats {
         ConnectionStats {
@@ -114,6 +168,7 @@ impl Server {
         shutdown_timeout: Duration,
     ) -> Result<()> {
         let mut connection_tasks: Vec<JoinHandle<()>> = Vec::new();
+        let acceptor = StreamAcceptor::new(connection_manager.config());
 
         // На Unix: подготовим обработчики сигналов заранее
         #[cfg(unix)]
@@ -135,8 +190,16 @@ impl Server {
 
                             let manager = connection_manager.clone();
                             let eng = engine.clone();
+                            let acceptor = acceptor.clone();
 
                             let task = tokio::task::spawn_local(async move {
+                                let socket = match acceptor.accept(socket).await {
+                                    Ok(socket) => socket,
+                                    Err(e) => {
+                                        warn!("TLS handshake with {addr} failed: {e}");
+                                        return;
+                                    }
+                                };
                                 if let Err(e) = manager.handle_connection(socket, addr, eng).await {
                                     match e.downcast_ref::<std::io::Error>() {
                                         Some(io_err) if Server::is_expected_error(io_err) => {
@@ -189,8 +252,16 @@ impl Server {
 
                             let manager = connection_manager.clone();
                             let eng = engine.clone();
+                            let acceptor = acceptor.clone();
 
                             let task = tokio::spawn(async move {
+                                let socket = match acceptor.accept(socket).await {
+                                    Ok(socket) => socket,
+                                    Err(e) => {
+                                        warn!("TLS handshake with {addr} failed: {e}");
+                                        return;
+                                    }
+                                };
                                 if let Err(e) = manager.handle_connection(socket, addr, eng).await {
                                     match e.downcast_ref::<std::io::Error>() {
                                         Some(io_err) if Server::is_expected_error(io_err) => {
@@ -443,6 +514,58 @@ mod tests {
         Ok(())
     }
 
+    /// Тест проверяет, что до `AUTH` команды отклоняются, а после входа права
+    /// пользователя ограничивают команды и ключи.
+    #[tokio::test(flavor = "current_thread")]
+    async fn server_requires_auth_and_checks_acl() -> Result<()> {
+        let auth = AuthManager::new();
+        auth.create_user("anton", "secret", &["~data:*", "+@read"])
+            .await?;
+        let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+        let manager = Arc::new(ConnectionManager::new(ConnectionConfig::default()).with_auth(auth));
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_addr: SocketAddr = listener.local_addr()?;
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let local = LocalSet::new();
+        local
+            .run_until(async move {
+                let server_task = tokio::task::spawn_local(async move {
+                    Server::run_server(listener, manager, engine, rx, Duration::from_secs(2))
+                        .await
+                        .expect("run_server failed");
+                });
+                sleep(Duration::from_millis(20)).await;
+
+                let mut stream = TcpStream::connect(local_addr).await?;
+                let mut buf = vec![0u8; 256];
+                for (request, expected) in [
+                    ("GET data:x\r\n", "-NOAUTH"),
+                    ("AUTH anton wrong\r\n", "-WRONGPASS"),
+                    ("AUTH anton secret\r\n", "+OK"),
+                    ("GET data:x\r\n", "$-1"),
+                    ("GET other\r\n", "-NOPERM"),
+                    ("SET data:x 1\r\n", "-NOPERM"),
+                ] {
+                    stream.write_all(request.as_bytes()).await?;
+                    let n = timeout(Duration::from_secs(2), stream.read(&mut buf)).await??;
+                    let got = String::from_utf8_lossy(&buf[..n]).to_string();
+                    assert!(
+                        got.starts_with(expected),
+                        "{request:?}: expected {expected}, got: {got:?}"
+                    );
+                }
+
+                let _ = tx.send(());
+                server_task.await.expect("server task panicked");
+                Ok::<(), anyhow::Error>(())
+            })
+            .await?;
+
+        Ok(())
+    }
+
     /// Тест проверяет сервер корректно завершает работу при получении shutdown
     /// через oneshot без входящих соединений.
     #[tokio::test(flavor = "current_thread")]
@@ -0,0 +1,345 @@
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::{RuntimeParams, Sds, Value};
+
+/// Максимальное число аргументов команды, сохраняемых в записи.
+const MAX_ARGS: usize = 32;
+/// Максимальная длина одного аргумента в байтах.
+const MAX_ARG_LEN: usize = 128;
+/// Число записей, возвращаемых `SLOWLOG GET` без аргумента.
+pub const DEFAULT_SLOWLOG_GET_COUNT: usize = 10;
+
+/// Запись журнала медленных команд.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlowLogEntry {
+    /// Уникальный возрастающий идентификатор записи.
+    pub id: u64,
+    /// Время выполнения команды (unix-время в секундах).
+    pub timestamp: u64,
+    /// Длительность выполнения в микросекундах.
+    pub duration_us: u64,
+    /// Аргументы команды (усечённые, см. [`SlowLog::record`]).
+    pub args: Vec<String>,
+    /// Адрес клиента, выполнившего команду.
+    pub client_addr: SocketAddr,
+}
+
+/// Журнал медленных команд (`SLOWLOG`).
+///
+/// Кольцевой буфер фиксированного размера, общий для всех соединений.
+/// Клонирование дёшево: все клоны ссылаются на один буфер. Порог и размер
+/// хранятся в атомиках, поэтому при отключённом журнале проверка на горячем
+/// пути не берёт блокировку.
+#[derive(Debug, Clone)]
+pub struct SlowLog {
+    /// Записи, от новых к старым.
+    entries: Arc<Mutex<VecDeque<SlowLogEntry>>>,
+    /// Счётчик для генерации идентификаторов записей.
+    next_id: Arc<AtomicU64>,
+    /// Порог в микросекундах; отрицательное значение отключает журнал.
+    threshold_us: Arc<AtomicI64>,
+    /// Максимальное число записей.
+    max_len: Arc<AtomicUsize>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SlowLogEntry {
+    /// Преобразует запись в ответ `SLOWLOG GET`:
+    /// `[id, timestamp, duration_us, [args...], client_addr]`.
+    pub fn to_value(&self) -> Value {
+        Value::Array(vec![
+            Value::Int(self.id as i64),
+            Value::Int(self.timestamp as i64),
+            Value::Int(self.duration_us as i64),
+            Value::Array(
+                self.args
+                    .iter()
+                    .map(|arg| Value::Str(Sds::from_str(arg)))
+                    .collect(),
+            ),
+            Value::Str(Sds::from_str(&self.client_addr.to_string())),
+        ])
+    }
+}
+
+impl SlowLog {
+    /// Создаёт журнал с порогом `threshold_us` микросекунд и ёмкостью
+    /// `max_len` записей.
+    pub fn new(
+        threshold_us: i64,
+        max_len: usize,
+    ) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(max_len))),
+            next_id: Arc::new(AtomicU64::new(0)),
+            threshold_us: Arc::new(AtomicI64::new(threshold_us)),
+            max_len: Arc::new(AtomicUsize::new(max_len)),
+        }
+    }
+
+    /// Создаёт журнал с параметрами `slowlog-*` из `params`.
+    pub fn from_params(params: &RuntimeParams) -> Self {
+        Self::new(params.slowlog_log_slower_than, params.slowlog_max_len)
+    }
+
+    /// Применяет параметры `slowlog-*` из `params` (после `CONFIG SET`).
+    pub fn apply_params(
+        &self,
+        params: &RuntimeParams,
+    ) {
+        self.set_threshold_us(params.slowlog_log_slower_than);
+        self.set_max_len(params.slowlog_max_len);
+    }
+
+    /// Возвращает порог в микросекундах.
+    pub fn threshold_us(&self) -> i64 {
+        self.threshold_us.load(Ordering::Relaxed)
+    }
+
+    /// Устанавливает порог в микросекундах; отрицательное значение отключает
+    /// журнал, `0` записывает все команды.
+    pub fn set_threshold_us(
+        &self,
+        threshold_us: i64,
+    ) {
+        self.threshold_us.store(threshold_us, Ordering::Relaxed);
+    }
+
+    /// Устанавливает ёмкость журнала, отбрасывая самые старые записи сверх
+    /// неё.
+    pub fn set_max_len(
+        &self,
+        max_len: usize,
+    ) {
+        self.max_len.store(max_len, Ordering::Relaxed);
+        self.entries.lock().truncate(max_len);
+    }
+
+    /// Проверяет, ведётся ли журнал. Не берёт блокировку.
+    pub fn is_enabled(&self) -> bool {
+        self.threshold_us() >= 0 && self.max_len.load(Ordering::Relaxed) > 0
+    }
+
+    /// Добавляет запись, если `duration` не меньше порога.
+    ///
+    /// Аргументы вычисляются лениво — только для попавших в журнал команд.
+    /// Сохраняется не более 32 аргументов по 128 байт, остаток заменяется
+    /// пометкой о числе пропущенных аргументов или байтов.
+    ///
+    /// # Возвращает
+    /// - `true`, если запись добавлена
+    pub fn record<F>(
+        &self,
+        duration: Duration,
+        args: F,
+        client_addr: SocketAddr,
+    ) -> bool
+    where
+        F: FnOnce() -> Vec<String>,
+    {
+        let threshold = self.threshold_us();
+        let duration_us = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        if threshold < 0 || duration_us < threshold as u64 {
+            return false;
+        }
+        let max_len = self.max_len.load(Ordering::Relaxed);
+        if max_len == 0 {
+            return false;
+        }
+
+        let entry = SlowLogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            duration_us,
+            args: truncate_args(args()),
+            client_addr,
+        };
+
+        let mut entries = self.entries.lock();
+        entries.push_front(entry);
+        entries.truncate(max_len);
+        true
+    }
+
+    /// Возвращает до `count` последних записей (по умолчанию 10), от новых к
+    /// старым.
+    pub fn get(
+        &self,
+        count: Option<usize>,
+    ) -> Vec<SlowLogEntry> {
+        let count = count.unwrap_or(DEFAULT_SLOWLOG_GET_COUNT);
+        self.entries.lock().iter().take(count).cloned().collect()
+    }
+
+    /// Возвращает число записей в журнале.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Проверяет, пуст ли журнал.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Очищает журнал. Идентификаторы продолжают возрастать.
+    pub fn reset(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для SlowLog
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for SlowLog {
+    fn default() -> Self {
+        Self::from_params(&RuntimeParams::default())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Ограничивает число и длину аргументов, сохраняемых в записи.
+fn truncate_args(mut args: Vec<String>) -> Vec<String> {
+    if args.len() > MAX_ARGS {
+        let skipped = args.len() - (MAX_ARGS - 1);
+        args.truncate(MAX_ARGS - 1);
+        args.push(format!("... ({skipped} more arguments)"));
+    }
+    for arg in &mut args {
+        if arg.len() > MAX_ARG_LEN {
+            let mut cut = MAX_ARG_LEN;
+            while !arg.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            let skipped = arg.len() - cut;
+            arg.truncate(cut);
+            arg.push_str(&format!("... ({skipped} more bytes)"));
+        }
+    }
+    args
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> SocketAddr {
+        "127.0.0.1:1234".parse().unwrap()
+    }
+
+    fn args(cmd: &str) -> impl FnOnce() -> Vec<String> + '_ {
+        move || vec![cmd.to_string()]
+    }
+
+    /// Тест проверяет, что в журнал попадают только команды не быстрее
+    /// порога, а записи возвращаются от новых к старым.
+    #[test]
+    fn test_record_respects_threshold() {
+        let log = SlowLog::new(100, 128);
+
+        assert!(!log.record(Duration::from_micros(99), args("GET"), addr()));
+        assert!(log.record(Duration::from_micros(100), args("SET"), addr()));
+        assert!(log.record(Duration::from_millis(5), args("KEYS"), addr()));
+
+        let entries = log.get(None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].args, vec!["KEYS"]);
+        assert_eq!(entries[0].duration_us, 5000);
+        assert_eq!(entries[1].args, vec!["SET"]);
+        assert!(entries[0].id > entries[1].id);
+        assert_eq!(entries[0].client_addr, addr());
+    }
+
+    /// Тест проверяет, что буфер хранит не больше `max_len` записей и
+    /// вытесняет самые старые.
+    #[test]
+    fn test_ring_buffer_evicts_oldest() {
+        let log = SlowLog::new(0, 3);
+        for i in 0..5 {
+            log.record(Duration::ZERO, || vec![i.to_string()], addr());
+        }
+
+        assert_eq!(log.len(), 3);
+        let ids: Vec<u64> = log.get(Some(10)).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![4, 3, 2]);
+        assert_eq!(log.get(Some(1)).len(), 1);
+
+        log.set_max_len(1);
+        assert_eq!(log.get(None)[0].id, 4);
+    }
+
+    /// Тест проверяет отключение журнала отрицательным порогом или нулевой
+    /// ёмкостью: аргументы при этом не вычисляются.
+    #[test]
+    fn test_disabled_log_skips_entries() {
+        let log = SlowLog::new(-1, 128);
+        assert!(!log.is_enabled());
+        assert!(!log.record(
+            Duration::from_secs(1),
+            || panic!("args must not be built"),
+            addr()
+        ));
+
+        log.set_threshold_us(0);
+        log.set_max_len(0);
+        assert!(!log.is_enabled());
+        assert!(log.is_empty());
+    }
+
+    /// Тест проверяет, что RESET очищает журнал, но не сбрасывает
+    /// идентификаторы.
+    #[test]
+    fn test_reset_keeps_ids_increasing() {
+        let log = SlowLog::new(0, 128);
+        log.record(Duration::ZERO, args("A"), addr());
+        log.reset();
+        assert!(log.is_empty());
+
+        log.record(Duration::ZERO, args("B"), addr());
+        assert_eq!(log.get(None)[0].id, 1);
+    }
+
+    /// Тест проверяет усечение длинных списков аргументов и длинных
+    /// аргументов.
+    #[test]
+    fn test_args_are_truncated() {
+        let log = SlowLog::new(0, 128);
+        log.record(
+            Duration::ZERO,
+            || (0..40).map(|_| "x".repeat(200)).collect(),
+            addr(),
+        );
+
+        let entry = &log.get(None)[0];
+        assert_eq!(entry.args.len(), MAX_ARGS);
+        assert_eq!(entry.args[MAX_ARGS - 1], "... (9 more arguments)");
+        assert_eq!(
+            entry.args[0],
+            format!("{}... (72 more bytes)", "x".repeat(MAX_ARG_LEN))
+        );
+    }
+}
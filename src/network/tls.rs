@@ -0,0 +1,247 @@
+//! Поддержка TLS для входящих соединений.
+//!
+//! [`MaybeTls`] объединяет обычный TCP-поток и поток поверх TLS, чтобы
+//! `ConnectionHandler` работал с ними одинаково. Загрузка сертификатов и
+//! сборка `rustls::ServerConfig` доступны только с Cargo-фичей `tls`.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+
+use crate::network::connection::ConnectionConfig;
+
+/// Поток клиентского соединения: обычный TCP или TCP поверх TLS.
+///
+/// Оба варианта реализуют `AsyncRead + AsyncWrite`, поэтому обработчик
+/// соединения не зависит от того, завершено ли TLS-рукопожатие на сокете.
+#[derive(Debug)]
+pub enum MaybeTls {
+    /// Незашифрованное TCP-соединение.
+    Plain(TcpStream),
+    /// Соединение после успешного TLS-рукопожатия.
+    #[cfg(feature = "tls")]
+    Tls(Box<tokio_rustls::server::TlsStream<TcpStream>>),
+}
+
+impl MaybeTls {
+    /// Возвращает `true`, если соединение зашифровано.
+    pub fn is_tls(&self) -> bool {
+        match self {
+            MaybeTls::Plain(_) => false,
+            #[cfg(feature = "tls")]
+            MaybeTls::Tls(_) => true,
+        }
+    }
+
+    /// Возвращает нижележащий TCP-поток.
+    pub fn tcp(&self) -> &TcpStream {
+        match self {
+            MaybeTls::Plain(stream) => stream,
+            #[cfg(feature = "tls")]
+            MaybeTls::Tls(stream) => stream.get_ref().0,
+        }
+    }
+}
+
+impl From<TcpStream> for MaybeTls {
+    fn from(stream: TcpStream) -> Self {
+        MaybeTls::Plain(stream)
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<tokio_rustls::server::TlsStream<TcpStream>> for MaybeTls {
+    fn from(stream: tokio_rustls::server::TlsStream<TcpStream>) -> Self {
+        MaybeTls::Tls(Box::new(stream))
+    }
+}
+
+impl AsyncRead for MaybeTls {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            MaybeTls::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(feature = "tls")]
+            MaybeTls::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for MaybeTls {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            MaybeTls::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(feature = "tls")]
+            MaybeTls::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            MaybeTls::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(feature = "tls")]
+            MaybeTls::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            MaybeTls::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(feature = "tls")]
+            MaybeTls::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+/// Принимает TCP-сокеты и, если в `ConnectionConfig` задан `tls_config`,
+/// выполняет TLS-рукопожатие.
+#[derive(Clone, Default)]
+pub struct StreamAcceptor {
+    #[cfg(feature = "tls")]
+    tls: Option<tokio_rustls::TlsAcceptor>,
+    #[cfg(feature = "tls")]
+    handshake_timeout: std::time::Duration,
+}
+
+impl StreamAcceptor {
+    /// Создаёт приёмник по конфигурации соединений. Рукопожатие ограничено
+    /// `read_timeout`.
+    pub fn new(config: &ConnectionConfig) -> Self {
+        #[cfg(not(feature = "tls"))]
+        let _ = config;
+        Self {
+            #[cfg(feature = "tls")]
+            tls: config
+                .tls_config
+                .clone()
+                .map(tokio_rustls::TlsAcceptor::from),
+            #[cfg(feature = "tls")]
+            handshake_timeout: config.read_timeout,
+        }
+    }
+
+    /// Превращает принятый сокет в поток соединения.
+    ///
+    /// # Возвращает:
+    /// - `Ok(MaybeTls)` — обычный поток или поток после рукопожатия;
+    /// - `Err(io::Error)` — если рукопожатие не удалось или не уложилось в
+    ///   таймаут.
+    pub async fn accept(
+        &self,
+        socket: TcpStream,
+    ) -> io::Result<MaybeTls> {
+        #[cfg(feature = "tls")]
+        if let Some(acceptor) = &self.tls {
+            let stream = tokio::time::timeout(self.handshake_timeout, acceptor.accept(socket))
+                .await
+                .map_err(|_| {
+                    io::Error::new(io::ErrorKind::TimedOut, "TLS handshake timed out")
+                })??;
+            return Ok(stream.into());
+        }
+        Ok(MaybeTls::Plain(socket))
+    }
+}
+
+#[cfg(feature = "tls")]
+pub use config::load_server_config;
+
+#[cfg(feature = "tls")]
+mod config {
+    use std::{path::Path, sync::Arc};
+
+    use anyhow::{anyhow, Context, Result};
+    use rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        server::WebPkiClientVerifier,
+        RootCertStore, ServerConfig,
+    };
+
+    use crate::TlsAuthClients;
+
+    /// Загружает сертификат и ключ сервера и собирает `rustls::ServerConfig`.
+    ///
+    /// Если задан `client_ca_file`, сервер проверяет сертификаты клиентов
+    /// (mutual TLS) по указанным корневым сертификатам; `auth_clients`
+    /// определяет, обязателен ли сертификат клиента. Без `client_ca_file`
+    /// или при `TlsAuthClients::No` сертификат клиента не запрашивается.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Arc<ServerConfig>)` — готовая конфигурация для `TlsAcceptor`;
+    /// - `Err` — если файлы не читаются, не содержат PEM-данных или ключ не
+    ///   подходит к сертификату.
+    pub fn load_server_config(
+        cert_file: &Path,
+        key_file: &Path,
+        client_ca_file: Option<&Path>,
+        auth_clients: TlsAuthClients,
+    ) -> Result<Arc<ServerConfig>> {
+        let certs = CertificateDer::pem_file_iter(cert_file)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("failed to read TLS certificate {}", cert_file.display()))?;
+        if certs.is_empty() {
+            return Err(anyhow!("no certificates found in {}", cert_file.display()));
+        }
+        let key = PrivateKeyDer::from_pem_file(key_file)
+            .with_context(|| format!("failed to read TLS private key {}", key_file.display()))?;
+
+        let provider = Arc::new(ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .context("failed to select TLS protocol versions")?;
+
+        let builder = match (client_ca_file, auth_clients) {
+            (Some(ca_file), TlsAuthClients::Yes | TlsAuthClients::Optional) => {
+                let mut roots = RootCertStore::empty();
+                for cert in CertificateDer::pem_file_iter(ca_file).with_context(|| {
+                    format!("failed to read TLS CA certificate {}", ca_file.display())
+                })? {
+                    let cert = cert.with_context(|| {
+                        format!("invalid TLS CA certificate {}", ca_file.display())
+                    })?;
+                    roots
+                        .add(cert)
+                        .context("failed to add TLS CA certificate")?;
+                }
+
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                let verifier = if auth_clients == TlsAuthClients::Optional {
+                    verifier.allow_unauthenticated()
+                } else {
+                    verifier
+                }
+                .build()
+                .context("failed to build TLS client verifier")?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            _ => builder.with_no_client_auth(),
+        };
+
+        let config = builder
+            .with_single_cert(certs, key)
+            .context("TLS certificate does not match private key")?;
+        Ok(Arc::new(config))
+    }
+}
@@ -18,7 +18,7 @@ use std::{
 use bytes::Buf;
 use memchr::memchr;
 
-use super::zsp_types::ZspFrame;
+use super::zsp_types::{CompressionAlgo, ZspFrame};
 use crate::ZspDecodeError;
 
 /// Максимальная длина строки в протоколе ZSP (1 МБ).
@@ -40,6 +40,15 @@ pub const MAX_BINARY_LENGTH: usize = 512 * 1024 * 1024;
 /// ошибке декодирования.
 pub const MAX_ARRAY_DEPTH: usize = 32;
 
+/// Длина префикса verbatim-строки: три байта формата и `:`.
+const VERBATIM_PREFIX_LEN: usize = 4;
+
+/// Префикс сжатого фрейма: `|compressed|<algo>|<len>\r\n<data>\r\n`.
+///
+/// Начинается с сигила атрибута `|`, но за ним идёт не число, поэтому
+/// не пересекается с RESP3 Attribute.
+pub const COMPRESSED_MAGIC: &[u8] = b"|compressed|";
+
 #[derive(Debug)]
 pub enum ZspDecodeState<'a> {
     Initial,
@@ -47,6 +56,15 @@ pub enum ZspDecodeState<'a> {
         len: usize,
         data: Vec<u8>,
     },
+    PartialVerbatimString {
+        len: usize,
+        data: Vec<u8>,
+    },
+    PartialCompressed {
+        algorithm: CompressionAlgo,
+        len: usize,
+        data: Vec<u8>,
+    },
     PartialArray {
         len: usize,
         items: Vec<ZspFrame<'a>>,
@@ -74,6 +92,14 @@ pub enum ZspDecodeState<'a> {
         pending_member: Option<String>,
         remaining: usize,
     },
+    /// Атрибут: `remaining == 0` означает, что словарь прочитан и
+    /// ожидается вложенный фрейм.
+    PartialAttribute {
+        len: usize,
+        items: HashMap<Cow<'a, str>, ZspFrame<'a>>,
+        remaining: usize,
+        pending_key: Option<Cow<'a, str>>,
+    },
 }
 
 pub struct ZspDecoder<'a> {
@@ -98,6 +124,24 @@ impl<'a> ZspDecoder<'a> {
             ZspDecodeState::PartialBinaryString { len, mut data } => {
                 self.continue_binary_string(slice, len, &mut data)
             }
+            ZspDecodeState::PartialVerbatimString { len, mut data } => {
+                self.continue_verbatim_string(slice, len, &mut data)
+            }
+            ZspDecodeState::PartialCompressed {
+                algorithm,
+                len,
+                mut data,
+            } => match self.continue_compressed(slice, algorithm, len, &mut data)? {
+                Some(frame) => Ok(Some(frame)),
+                None => {
+                    self.state = ZspDecodeState::PartialCompressed {
+                        algorithm,
+                        len,
+                        data,
+                    };
+                    Ok(None)
+                }
+            },
             ZspDecodeState::PartialArray {
                 len,
                 mut items,
@@ -192,6 +236,30 @@ impl<'a> ZspDecoder<'a> {
                     }
                 }
             }
+            ZspDecodeState::PartialAttribute {
+                len,
+                mut items,
+                mut remaining,
+                mut pending_key,
+            } => {
+                match self.continue_attribute(
+                    slice,
+                    &mut items,
+                    &mut remaining,
+                    &mut pending_key,
+                )? {
+                    Some(attribute) => Ok(Some(attribute)),
+                    None => {
+                        self.state = ZspDecodeState::PartialAttribute {
+                            len,
+                            items,
+                            remaining,
+                            pending_key,
+                        };
+                        Ok(None)
+                    }
+                }
+            }
         }
     }
 
@@ -215,6 +283,13 @@ impl<'a> ZspDecoder<'a> {
             b'~' => self.parse_set(slice),
             b'>' => self.parse_push(slice),
             b'^' => self.parse_zset(slice),
+            b'|' if slice.starts_with(&COMPRESSED_MAGIC[1..]) => {
+                slice.advance(COMPRESSED_MAGIC.len() - 1);
+                self.parse_compressed(slice)
+            }
+            b'|' => self.parse_attribute(slice),
+            b'=' => self.parse_verbatim_string(slice),
+            b'(' => self.parse_big_number(slice),
             _ => Err(ZspDecodeError::InvalidData(
                 "Unknown ZSP/RESP3 type".to_string(),
             )),
@@ -364,6 +439,147 @@ impl<'a> ZspDecoder<'a> {
         }
     }
 
+    /// ZSP: Verbatim String - =9\r\ntxt:hello\r\n
+    fn parse_verbatim_string(
+        &mut self,
+        slice: &mut &'a [u8],
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let len = self
+            .read_line(slice)?
+            .parse::<usize>()
+            .map_err(|_| ZspDecodeError::InvalidData("Invalid verbatim length".to_string()))?;
+
+        if len < VERBATIM_PREFIX_LEN {
+            return Err(ZspDecodeError::InvalidData(
+                "Verbatim string missing format".to_string(),
+            ));
+        }
+        if len > MAX_BINARY_LENGTH {
+            let err_msg = format!("Verbatim string too long ({len} > {MAX_BINARY_LENGTH})");
+            return Err(ZspDecodeError::InvalidData(err_msg));
+        }
+
+        let mut data = Vec::with_capacity(len);
+        match self.continue_verbatim_string(slice, len, &mut data)? {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                self.state = ZspDecodeState::PartialVerbatimString { len, data };
+                Ok(None)
+            }
+        }
+    }
+
+    fn continue_verbatim_string(
+        &mut self,
+        slice: &mut &'a [u8],
+        len: usize,
+        data: &mut Vec<u8>,
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let available = slice.remaining().min(len - data.len());
+        data.extend_from_slice(&slice[..available]);
+        slice.advance(available);
+
+        if data.len() < len {
+            return Ok(None);
+        }
+
+        self.expect_crlf(slice)?;
+        if data[VERBATIM_PREFIX_LEN - 1] != b':' {
+            return Err(ZspDecodeError::InvalidData(
+                "Invalid verbatim string format".to_string(),
+            ));
+        }
+        let format = [data[0], data[1], data[2]];
+        let text = data.split_off(VERBATIM_PREFIX_LEN);
+        Ok(Some(ZspFrame::VerbatimString { format, data: text }))
+    }
+
+    /// ZSP: Big Number - (12345678901234567890\r\n
+    fn parse_big_number(
+        &mut self,
+        slice: &mut &'a [u8],
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let line = self.read_line(slice)?;
+        if !is_big_number(line) {
+            return Err(ZspDecodeError::InvalidData(
+                "Invalid big number".to_string(),
+            ));
+        }
+        Ok(Some(ZspFrame::BigNumber(line.to_string())))
+    }
+
+    /// ZSP: Compressed - |compressed|lz4|<len>\r\n<data>\r\n
+    ///
+    /// Магический префикс уже прочитан. Сжатые данные распаковываются и
+    /// декодируются как обычный фрейм, который и возвращается вызывающему.
+    fn parse_compressed(
+        &mut self,
+        slice: &mut &'a [u8],
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let header = self.read_line(slice)?;
+        let (algo, len) = header.split_once('|').ok_or_else(|| {
+            ZspDecodeError::InvalidData("Invalid compressed frame header".to_string())
+        })?;
+        let algorithm = CompressionAlgo::parse(algo).ok_or_else(|| {
+            ZspDecodeError::InvalidData(format!("Unknown compression algorithm: {algo}"))
+        })?;
+        let len = len
+            .parse::<usize>()
+            .map_err(|_| ZspDecodeError::InvalidData("Invalid compressed length".to_string()))?;
+        if len > MAX_BINARY_LENGTH {
+            let err_msg = format!("Compressed frame too long ({len} > {MAX_BINARY_LENGTH})");
+            return Err(ZspDecodeError::InvalidData(err_msg));
+        }
+
+        let mut data = Vec::with_capacity(len);
+        match self.continue_compressed(slice, algorithm, len, &mut data)? {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                self.state = ZspDecodeState::PartialCompressed {
+                    algorithm,
+                    len,
+                    data,
+                };
+                Ok(None)
+            }
+        }
+    }
+
+    fn continue_compressed(
+        &mut self,
+        slice: &mut &'a [u8],
+        algorithm: CompressionAlgo,
+        len: usize,
+        data: &mut Vec<u8>,
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let available = slice.remaining().min(len - data.len());
+        data.extend_from_slice(&slice[..available]);
+        slice.advance(available);
+
+        if data.len() < len {
+            return Ok(None);
+        }
+        self.expect_crlf(slice)?;
+
+        let raw = decompress(algorithm, data)?;
+        if raw.starts_with(COMPRESSED_MAGIC) {
+            return Err(ZspDecodeError::InvalidData(
+                "Nested compressed frame".to_string(),
+            ));
+        }
+
+        let mut inner = raw.as_slice();
+        match ZspDecoder::new().decode(&mut inner)? {
+            Some(frame) if inner.is_empty() => Ok(Some(frame.into_owned())),
+            Some(_) => Err(ZspDecodeError::InvalidData(
+                "Trailing data in compressed frame".to_string(),
+            )),
+            None => Err(ZspDecodeError::InvalidData(
+                "Incomplete compressed frame".to_string(),
+            )),
+        }
+    }
+
     fn parse_array(
         &mut self,
         slice: &mut &'a [u8],
@@ -716,6 +932,62 @@ impl<'a> ZspDecoder<'a> {
         Ok(Some(ZspFrame::ZSet(std::mem::take(items))))
     }
 
+    /// ZSP: Attribute - |1\r\n$3\r\nttl\r\n:10\r\n+value\r\n
+    ///
+    /// Читает словарь метаданных и оборачивает следующий за ним фрейм.
+    fn parse_attribute(
+        &mut self,
+        slice: &mut &'a [u8],
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let len_str = self.read_line(slice)?;
+        let len = len_str
+            .parse::<usize>()
+            .map_err(|_| ZspDecodeError::InvalidData("Invalid attribute length".to_string()))?;
+
+        let mut items = HashMap::with_capacity(len);
+        let mut remaining = len;
+        let mut pending_key = None;
+
+        match self.continue_attribute(slice, &mut items, &mut remaining, &mut pending_key)? {
+            Some(attribute) => Ok(Some(attribute)),
+            None => {
+                self.state = ZspDecodeState::PartialAttribute {
+                    len,
+                    items,
+                    remaining,
+                    pending_key,
+                };
+                Ok(None)
+            }
+        }
+    }
+
+    fn continue_attribute(
+        &mut self,
+        slice: &mut &'a [u8],
+        items: &mut HashMap<Cow<'a, str>, ZspFrame<'a>>,
+        remaining: &mut usize,
+        pending_key: &mut Option<Cow<'a, str>>,
+    ) -> Result<Option<ZspFrame<'a>>, ZspDecodeError> {
+        let Some(ZspFrame::Dictionary(data)) =
+            self.continue_dictionary(slice, items, remaining, pending_key)?
+        else {
+            return Ok(None);
+        };
+
+        match self.decode(slice)? {
+            Some(inner) => Ok(Some(ZspFrame::Attribute {
+                data,
+                inner: Box::new(inner),
+            })),
+            None => {
+                // Словарь уже прочитан — ждём только вложенный фрейм
+                *items = data;
+                Ok(None)
+            }
+        }
+    }
+
     fn read_line(
         &mut self,
         slice: &mut &'a [u8],
@@ -745,6 +1017,37 @@ impl<'a> ZspDecoder<'a> {
     }
 }
 
+/// Проверяет, что строка — десятичное целое с необязательным знаком `-`
+/// (формат RESP3 Big Number).
+pub(crate) fn is_big_number(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Распаковывает данные сжатого фрейма, ограничивая результат
+/// [`MAX_BINARY_LENGTH`].
+fn decompress(
+    algorithm: CompressionAlgo,
+    data: &[u8],
+) -> Result<Vec<u8>, ZspDecodeError> {
+    match algorithm {
+        CompressionAlgo::Lz4 => {
+            let size = data
+                .get(..4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                .ok_or_else(|| ZspDecodeError::InvalidData("Truncated lz4 frame".to_string()))?;
+            if size > MAX_BINARY_LENGTH {
+                let err_msg = format!("Decompressed frame too long ({size} > {MAX_BINARY_LENGTH})");
+                return Err(ZspDecodeError::InvalidData(err_msg));
+            }
+            lz4_flex::decompress_size_prepended(data)
+                .map_err(|e| ZspDecodeError::InvalidData(format!("lz4 decompression failed: {e}")))
+        }
+        CompressionAlgo::Zstd => zstd::bulk::decompress(data, MAX_BINARY_LENGTH)
+            .map_err(|e| ZspDecodeError::InvalidData(format!("zstd decompression failed: {e}"))),
+    }
+}
+
 impl Default for ZspDecoder<'_> {
     fn default() -> Self {
         Self::new()
@@ -1090,4 +1393,143 @@ mod tests {
             ])
         );
     }
+
+    /// Тест проверяет декодирование атрибута: словарь метаданных
+    /// оборачивает следующий за ним фрейм.
+    #[test]
+    fn test_resp3_attribute() {
+        let mut decoder = ZspDecoder::new();
+        let data = b"|2\r\n$3\r\nttl\r\n:10\r\n+key\r\n+user:1\r\n$5\r\nvalue\r\n".to_vec();
+        let mut slice = data.as_slice();
+        let frame = decoder.decode(&mut slice).unwrap().unwrap();
+
+        let mut expected = HashMap::new();
+        expected.insert(Cow::from("ttl"), ZspFrame::Integer(10));
+        expected.insert(Cow::from("key"), ZspFrame::InlineString("user:1".into()));
+        assert_eq!(
+            frame,
+            ZspFrame::Attribute {
+                data: expected,
+                inner: Box::new(ZspFrame::BinaryString(Some(b"value".to_vec()))),
+            }
+        );
+        assert!(slice.is_empty());
+    }
+
+    /// Тест проверяет, что атрибут, закодированный `ZspEncoder`,
+    /// декодируется обратно, в том числе когда вложенный фрейм приходит
+    /// отдельной порцией.
+    #[test]
+    fn test_resp3_attribute_roundtrip_partial() {
+        let mut data = HashMap::new();
+        data.insert(Cow::from("event"), ZspFrame::InlineString("expired".into()));
+        let frame = ZspFrame::Attribute {
+            data,
+            inner: Box::new(ZspFrame::Push(vec![
+                ZspFrame::InlineString("message".into()),
+                ZspFrame::Integer(1),
+            ])),
+        };
+        let encoded = ZspEncoder::encode(&frame).unwrap();
+
+        let mut decoder = ZspDecoder::new();
+        let split = encoded.iter().position(|&b| b == b'>').unwrap();
+        let mut head = &encoded[..split];
+        assert_eq!(decoder.decode(&mut head).unwrap(), None);
+
+        let mut tail = &encoded[split..];
+        assert_eq!(decoder.decode(&mut tail).unwrap(), Some(frame));
+    }
+
+    /// Тест проверяет round-trip verbatim-строки, в том числе при
+    /// получении данных по частям.
+    #[test]
+    fn test_resp3_verbatim_string_roundtrip() {
+        let frame = ZspFrame::VerbatimString {
+            format: *b"txt",
+            data: b"line1\r\nline2".to_vec(),
+        };
+        let encoded = ZspEncoder::encode(&frame).unwrap();
+
+        let mut decoder = ZspDecoder::new();
+        let mut slice = encoded.as_slice();
+        assert_eq!(decoder.decode(&mut slice).unwrap(), Some(frame.clone()));
+
+        let mut decoder = ZspDecoder::new();
+        let (head, tail) = encoded.split_at(7);
+        let mut head = head;
+        assert_eq!(decoder.decode(&mut head).unwrap(), None);
+        let mut tail = tail;
+        assert_eq!(decoder.decode(&mut tail).unwrap(), Some(frame));
+    }
+
+    /// Тест проверяет, что verbatim-строка без `:` после формата
+    /// отклоняется.
+    #[test]
+    fn test_resp3_verbatim_string_invalid_format() {
+        let mut decoder = ZspDecoder::new();
+        let mut slice = b"=9\r\ntxt-hello\r\n".as_ref();
+        assert!(decoder.decode(&mut slice).is_err());
+
+        let mut decoder = ZspDecoder::new();
+        let mut slice = b"=2\r\ntx\r\n".as_ref();
+        assert!(decoder.decode(&mut slice).is_err());
+    }
+
+    /// Тест проверяет round-trip больших чисел и отказ для нечисловых
+    /// строк.
+    #[test]
+    fn test_resp3_big_number_roundtrip() {
+        for digits in ["0", "3492890328409238509324850943850943825024385", "-42"] {
+            let frame = ZspFrame::BigNumber(digits.to_string());
+            let encoded = ZspEncoder::encode(&frame).unwrap();
+            let mut slice = encoded.as_slice();
+            let decoded = ZspDecoder::new().decode(&mut slice).unwrap();
+            assert_eq!(decoded, Some(frame));
+        }
+
+        let mut slice = b"(12x\r\n".as_ref();
+        assert!(ZspDecoder::new().decode(&mut slice).is_err());
+    }
+
+    /// Тест проверяет, что сжатые `lz4` и `zstd` фреймы прозрачно
+    /// распаковываются декодером, в том числе при получении по частям.
+    #[test]
+    fn test_compressed_roundtrip() {
+        let frame = ZspFrame::Array(vec![
+            ZspFrame::BinaryString(Some(vec![b'z'; 4096])),
+            ZspFrame::InlineString("tail".into()),
+        ]);
+
+        for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+            let encoded = ZspEncoder::encode_with(&frame, Some(algo)).unwrap();
+            assert!(encoded.starts_with(COMPRESSED_MAGIC));
+
+            let mut slice = encoded.as_slice();
+            let decoded = ZspDecoder::new().decode(&mut slice).unwrap();
+            assert_eq!(decoded, Some(frame.clone()));
+            assert!(slice.is_empty());
+
+            let mut decoder = ZspDecoder::new();
+            let (head, tail) = encoded.split_at(encoded.len() - 5);
+            let mut head = head;
+            assert_eq!(decoder.decode(&mut head).unwrap(), None);
+            let mut tail = tail;
+            assert_eq!(decoder.decode(&mut tail).unwrap(), Some(frame.clone()));
+        }
+    }
+
+    /// Тест проверяет отказ для неизвестного алгоритма и повреждённых
+    /// сжатых данных.
+    #[test]
+    fn test_compressed_invalid() {
+        let mut slice = b"|compressed|gzip|3\r\nabc\r\n".as_ref();
+        assert!(ZspDecoder::new().decode(&mut slice).is_err());
+
+        let mut slice = b"|compressed|lz4|3\r\nabc\r\n".as_ref();
+        assert!(ZspDecoder::new().decode(&mut slice).is_err());
+
+        let mut slice = b"|compressed|zstd|3\r\nabc\r\n".as_ref();
+        assert!(ZspDecoder::new().decode(&mut slice).is_err());
+    }
 }
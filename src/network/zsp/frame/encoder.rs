@@ -6,12 +6,21 @@
 //! словари и ZSet. Также реализована валидация строк и глубины
 //! вложенности массивов для предотвращения ошибок сериализации.
 
+use std::{borrow::Cow, collections::HashMap};
+
 use super::{
-    decoder::{MAX_ARRAY_DEPTH, MAX_BINARY_LENGTH},
-    zsp_types::ZspFrame,
+    decoder::{is_big_number, COMPRESSED_MAGIC, MAX_ARRAY_DEPTH, MAX_BINARY_LENGTH},
+    zsp_types::{CompressionAlgo, ZspFrame},
 };
 use crate::ZspEncodeError;
 
+/// Минимальный размер закодированного фрейма (в байтах), начиная с
+/// которого он сжимается. Более короткие ответы сжатие только удлиняет.
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Уровень сжатия `zstd` для фреймов: быстрый, как и для узлов QuickList.
+const ZSTD_FRAME_LEVEL: i32 = 1;
+
 /// Структура энкодера для кодирования в формат ZSP.
 pub struct ZspEncoder;
 
@@ -24,6 +33,27 @@ impl ZspEncoder {
         Self::encode_frame(frame, 0)
     }
 
+    /// Кодирует фрейм и, если для соединения согласовано сжатие, а
+    /// результат не короче [`COMPRESSION_THRESHOLD`], оборачивает его во
+    /// фрейм `Compressed`.
+    ///
+    /// # Возвращает:
+    /// - `Ok(Vec<u8>)` — обычное или сжатое представление фрейма;
+    /// - `Err(ZspEncodeError)` — если фрейм не удалось закодировать или сжать.
+    pub fn encode_with(
+        frame: &ZspFrame,
+        compression: Option<CompressionAlgo>,
+    ) -> Result<Vec<u8>, ZspEncodeError> {
+        let encoded = Self::encode_frame(frame, 0)?;
+        match compression {
+            Some(algorithm) if encoded.len() >= COMPRESSION_THRESHOLD => {
+                let data = Self::compress(algorithm, &encoded)?;
+                Self::encode_frame(&ZspFrame::Compressed { algorithm, data }, 0)
+            }
+            _ => Ok(encoded),
+        }
+    }
+
     fn encode_frame(
         frame: &ZspFrame,
         current_depth: usize,
@@ -103,13 +133,7 @@ impl ZspEncoder {
                 }
 
                 let mut out = format!("%{}\r\n", items.len()).into_bytes();
-                for (key, value) in items {
-                    let key_bytes = key.as_bytes();
-                    out.extend(format!("${}\r\n", key_bytes.len()).into_bytes());
-                    out.extend(key_bytes);
-                    out.extend(b"\r\n");
-                    out.extend(Self::encode_frame(value, current_depth + 1)?);
-                }
+                Self::encode_map_entries(&mut out, items, current_depth)?;
                 Ok(out)
             }
             // ZSP: Set - ~3\r\n+member1\r\n+member2\r\n+member3\r\n
@@ -147,11 +171,86 @@ impl ZspEncoder {
                 }
                 Ok(out)
             }
+            // ZSP: Attribute - |1\r\n$3\r\nttl\r\n:10\r\n+value\r\n
+            // Словарь метаданных, затем сам фрейм ответа
+            ZspFrame::Attribute { data, inner } => {
+                let mut out = format!("|{}\r\n", data.len()).into_bytes();
+                Self::encode_map_entries(&mut out, data, current_depth)?;
+                out.extend(Self::encode_frame(inner, current_depth)?);
+                Ok(out)
+            }
+            // ZSP: Verbatim String - =9\r\ntxt:hello\r\n
+            ZspFrame::VerbatimString { format, data } => {
+                let len = data.len() + format.len() + 1;
+                if len > MAX_BINARY_LENGTH {
+                    let err_msg = format!("Verbatim string too long ({len} > {MAX_BINARY_LENGTH})");
+                    return Err(ZspEncodeError::InvalidData(err_msg));
+                }
+
+                let mut out = format!("={len}\r\n").into_bytes();
+                out.extend(format);
+                out.push(b':');
+                out.extend(data);
+                out.extend(b"\r\n");
+                Ok(out)
+            }
+            // ZSP: Big Number - (12345678901234567890\r\n
+            ZspFrame::BigNumber(digits) => {
+                Self::validate_big_number(digits)?;
+                Ok(format!("({digits}\r\n").into_bytes())
+            }
+            // ZSP: Compressed - |compressed|lz4|<len>\r\n<data>\r\n
+            ZspFrame::Compressed { algorithm, data } => {
+                if data.len() > MAX_BINARY_LENGTH {
+                    let err_msg = format!(
+                        "Compressed frame too long ({} > {})",
+                        data.len(),
+                        MAX_BINARY_LENGTH
+                    );
+                    return Err(ZspEncodeError::InvalidData(err_msg));
+                }
+
+                let mut out = COMPRESSED_MAGIC.to_vec();
+                out.extend(format!("{}|{}\r\n", algorithm.as_str(), data.len()).into_bytes());
+                out.extend(data);
+                out.extend(b"\r\n");
+                Ok(out)
+            }
             // ZSP: Null - _\r\n
             ZspFrame::Null => Ok(b"_\r\n".to_vec()),
         }
     }
 
+    /// Сжимает закодированный фрейм. Для `lz4` длина исходных данных
+    /// записывается в начало блока.
+    fn compress(
+        algorithm: CompressionAlgo,
+        encoded: &[u8],
+    ) -> Result<Vec<u8>, ZspEncodeError> {
+        match algorithm {
+            CompressionAlgo::Lz4 => Ok(lz4_flex::compress_prepend_size(encoded)),
+            CompressionAlgo::Zstd => zstd::bulk::compress(encoded, ZSTD_FRAME_LEVEL)
+                .map_err(|e| ZspEncodeError::InvalidState(format!("zstd compression failed: {e}"))),
+        }
+    }
+
+    /// Кодирует пары словаря (`%`) или атрибута (`|`): ключи как
+    /// BinaryString, значения — рекурсивно.
+    fn encode_map_entries(
+        out: &mut Vec<u8>,
+        items: &HashMap<Cow<'_, str>, ZspFrame<'_>>,
+        current_depth: usize,
+    ) -> Result<(), ZspEncodeError> {
+        for (key, value) in items {
+            let key_bytes = key.as_bytes();
+            out.extend(format!("${}\r\n", key_bytes.len()).into_bytes());
+            out.extend(key_bytes);
+            out.extend(b"\r\n");
+            out.extend(Self::encode_frame(value, current_depth + 1)?);
+        }
+        Ok(())
+    }
+
     fn validate_simple_string(s: &str) -> Result<(), ZspEncodeError> {
         if s.contains('\r') || s.contains('\n') {
             let err_msg = "Simple string contains CR or LF characters";
@@ -161,6 +260,15 @@ impl ZspEncoder {
         }
     }
 
+    fn validate_big_number(s: &str) -> Result<(), ZspEncodeError> {
+        if is_big_number(s) {
+            Ok(())
+        } else {
+            let err_msg = format!("Invalid big number: {s:?}");
+            Err(ZspEncodeError::InvalidData(err_msg))
+        }
+    }
+
     fn validate_error_string(s: &str) -> Result<(), ZspEncodeError> {
         if s.contains('\r') || s.contains('\n') {
             let err_msg = "Error message contains CR or LF characters";
@@ -345,6 +453,81 @@ mod tests {
         );
     }
 
+    /// Тест проверяет, что атрибут кодируется словарём с сигилом `|`,
+    /// за которым следует вложенный фрейм.
+    #[test]
+    fn test_attribute() {
+        let mut data = HashMap::new();
+        data.insert("ttl".into(), ZspFrame::Integer(10));
+        let frame = ZspFrame::Attribute {
+            data,
+            inner: Box::new(ZspFrame::InlineString("value".into())),
+        };
+
+        let encoded = ZspEncoder::encode(&frame).unwrap();
+        assert_eq!(encoded, b"|1\r\n$3\r\nttl\r\n:10\r\n+value\r\n");
+    }
+
+    /// Тест проверяет, что длина verbatim-строки учитывает префикс
+    /// формата `txt:`.
+    #[test]
+    fn test_verbatim_string() {
+        let frame = ZspFrame::VerbatimString {
+            format: *b"txt",
+            data: b"hello".to_vec(),
+        };
+        let encoded = ZspEncoder::encode(&frame).unwrap();
+        assert_eq!(encoded, b"=9\r\ntxt:hello\r\n");
+    }
+
+    /// Тест проверяет кодирование больших чисел и отказ для строк, не
+    /// являющихся десятичным целым.
+    #[test]
+    fn test_big_number() {
+        let frame = ZspFrame::BigNumber("-3492890328409238509324850943850943825024385".into());
+        let encoded = ZspEncoder::encode(&frame).unwrap();
+        assert_eq!(
+            encoded,
+            b"(-3492890328409238509324850943850943825024385\r\n"
+        );
+
+        for bad in ["", "-", "12a", "1.5"] {
+            let frame = ZspFrame::BigNumber(bad.into());
+            assert!(ZspEncoder::encode(&frame).is_err(), "{bad:?}");
+        }
+    }
+
+    /// Тест проверяет, что сжатый фрейм кодируется с магическим
+    /// префиксом, именем алгоритма и длиной данных.
+    #[test]
+    fn test_compressed() {
+        let frame = ZspFrame::Compressed {
+            algorithm: CompressionAlgo::Lz4,
+            data: b"abc".to_vec(),
+        };
+        let encoded = ZspEncoder::encode(&frame).unwrap();
+        assert_eq!(encoded, b"|compressed|lz4|3\r\nabc\r\n");
+    }
+
+    /// Тест проверяет, что `encode_with` сжимает только фреймы не короче
+    /// порога и только при согласованном сжатии.
+    #[test]
+    fn test_encode_with_threshold() {
+        let small = ZspFrame::InlineString("OK".into());
+        let encoded = ZspEncoder::encode_with(&small, Some(CompressionAlgo::Lz4)).unwrap();
+        assert_eq!(encoded, b"+OK\r\n");
+
+        let large = ZspFrame::BinaryString(Some(vec![b'x'; COMPRESSION_THRESHOLD]));
+        let plain = ZspEncoder::encode(&large).unwrap();
+        assert_eq!(ZspEncoder::encode_with(&large, None).unwrap(), plain);
+
+        for algo in [CompressionAlgo::Lz4, CompressionAlgo::Zstd] {
+            let encoded = ZspEncoder::encode_with(&large, Some(algo)).unwrap();
+            assert!(encoded.starts_with(COMPRESSED_MAGIC));
+            assert!(encoded.len() < plain.len());
+        }
+    }
+
     #[test]
     fn test_invalid_simple_string() {
         let frame = ZspFrame::InlineString("bad\r\nstring".into());
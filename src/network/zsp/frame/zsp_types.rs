@@ -11,6 +11,8 @@ use std::{
     convert::TryFrom,
 };
 
+use serde::Serialize;
+
 use crate::{Dict, QuickList, Sds, SmartHash, Value};
 
 /// Типы фреймов, поддерживаемые протоколом ZSP/RESP3.
@@ -27,6 +29,10 @@ use crate::{Dict, QuickList, Sds, SmartHash, Value};
 /// - Dictionary (`%`) - Map
 /// - Set (`~`) - Set
 /// - Push (`>`) - Push (для pub/sub)
+/// - Attribute (`|`) - метаданные, предшествующие ответу
+/// - VerbatimString (`=`) - строка с трёхбайтовым указанием формата
+/// - BigNumber (`(`) - целое произвольной точности
+/// - Compressed (`|compressed|`) - сжатый фрейм, согласуется в `HELLO`
 ///
 /// ZSP расширения:
 /// - ZSet (`^`) - Sorted Set с членами и счётом
@@ -54,14 +60,105 @@ pub enum ZspFrame<'a> {
     /// ZSP: Push - >3\r\n+message\r\n+channel\r\n$7\r\npayload\r\n
     /// Используется для server-initiated messages (pub/sub)
     Push(Vec<ZspFrame<'a>>),
+    /// ZSP: Attribute - |1\r\n$3\r\nttl\r\n:10\r\n+value\r\n
+    /// Словарь метаданных, за которым следует сам фрейм ответа
+    Attribute {
+        data: HashMap<Cow<'a, str>, ZspFrame<'a>>,
+        inner: Box<ZspFrame<'a>>,
+    },
     /// ZSP РАСШИРЕНИЕ: ZSet -
     /// ^2\r\n$7\r\nmember1\r\n,1.5\r\n$7\r\nmember2\r\n,2.5\r\n Sorted Set
     /// с парами (member, score)
     ZSet(Vec<(String, f64)>),
+    /// ZSP: Verbatim String - =9\r\ntxt:hello\r\n
+    /// `format` — формат данных (`txt`, `mkd`), длина включает `txt:`
+    VerbatimString { format: [u8; 3], data: Vec<u8> },
+    /// ZSP: Big Number - (3492890328409238509324850943850943825024385\r\n
+    /// Десятичные цифры с необязательным знаком `-`
+    BigNumber(String),
+    /// ZSP РАСШИРЕНИЕ: Compressed - |compressed|lz4|<len>\r\n<data>\r\n
+    /// Сжатое представление обычного закодированного фрейма
+    Compressed {
+        algorithm: CompressionAlgo,
+        data: Vec<u8>,
+    },
     /// ZSP: Null - _\r\n
     Null,
 }
 
+/// Алгоритм сжатия фреймов, согласуемый командой
+/// `HELLO 3 COMPRESS <algo>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgo {
+    /// `lz4_flex` — быстрое сжатие, используется по умолчанию.
+    Lz4,
+    /// `zstd` — лучшая степень сжатия ценой скорости.
+    Zstd,
+}
+
+impl CompressionAlgo {
+    /// Разбирает имя алгоритма без учёта регистра.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "lz4" => Some(Self::Lz4),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Возвращает имя алгоритма в том виде, в каком оно передаётся по
+    /// сети.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lz4 => "lz4",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+impl ZspFrame<'_> {
+    /// Копирует все заимствованные данные фрейма и возвращает фрейм, не
+    /// зависящий от исходного буфера.
+    pub fn into_owned(self) -> ZspFrame<'static> {
+        fn owned_map(
+            map: HashMap<Cow<'_, str>, ZspFrame<'_>>
+        ) -> HashMap<Cow<'static, str>, ZspFrame<'static>> {
+            map.into_iter()
+                .map(|(k, v)| (Cow::Owned(k.into_owned()), v.into_owned()))
+                .collect()
+        }
+
+        match self {
+            ZspFrame::InlineString(s) => ZspFrame::InlineString(Cow::Owned(s.into_owned())),
+            ZspFrame::FrameError(e) => ZspFrame::FrameError(e),
+            ZspFrame::Integer(i) => ZspFrame::Integer(i),
+            ZspFrame::Float(f) => ZspFrame::Float(f),
+            ZspFrame::Bool(b) => ZspFrame::Bool(b),
+            ZspFrame::BinaryString(b) => ZspFrame::BinaryString(b),
+            ZspFrame::Array(items) => {
+                ZspFrame::Array(items.into_iter().map(ZspFrame::into_owned).collect())
+            }
+            ZspFrame::Dictionary(map) => ZspFrame::Dictionary(owned_map(map)),
+            ZspFrame::Set(items) => {
+                ZspFrame::Set(items.into_iter().map(ZspFrame::into_owned).collect())
+            }
+            ZspFrame::Push(items) => {
+                ZspFrame::Push(items.into_iter().map(ZspFrame::into_owned).collect())
+            }
+            ZspFrame::Attribute { data, inner } => ZspFrame::Attribute {
+                data: owned_map(data),
+                inner: Box::new(inner.into_owned()),
+            },
+            ZspFrame::ZSet(entries) => ZspFrame::ZSet(entries),
+            ZspFrame::VerbatimString { format, data } => ZspFrame::VerbatimString { format, data },
+            ZspFrame::BigNumber(digits) => ZspFrame::BigNumber(digits),
+            ZspFrame::Compressed { algorithm, data } => ZspFrame::Compressed { algorithm, data },
+            ZspFrame::Null => ZspFrame::Null,
+        }
+    }
+}
+
 impl TryFrom<Value> for ZspFrame<'_> {
     type Error = String;
 
@@ -148,6 +245,25 @@ impl std::hash::Hash for ZspFrame<'_> {
                 11u8.hash(state);
                 zset.len().hash(state);
             }
+            ZspFrame::Attribute { data, inner } => {
+                12u8.hash(state);
+                data.len().hash(state);
+                inner.hash(state);
+            }
+            ZspFrame::VerbatimString { format, data } => {
+                13u8.hash(state);
+                format.hash(state);
+                data.hash(state);
+            }
+            ZspFrame::BigNumber(digits) => {
+                14u8.hash(state);
+                digits.hash(state);
+            }
+            ZspFrame::Compressed { algorithm, data } => {
+                15u8.hash(state);
+                algorithm.hash(state);
+                data.hash(state);
+            }
         }
     }
 }
@@ -175,8 +291,8 @@ pub fn convert_sds_to_frame<'a>(sds: Sds) -> Result<ZspFrame<'a>, String> {
 /// каждый элемент в BinaryString.
 pub fn convert_quicklist<'a>(list: QuickList<Sds>) -> Result<ZspFrame<'a>, String> {
     let mut frames = Vec::with_capacity(list.len());
-    for item in list.iter() {
-        frames.push(item.clone().into());
+    for item in list {
+        frames.push(item.into());
     }
     Ok(ZspFrame::Array(frames))
 }
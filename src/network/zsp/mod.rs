@@ -7,8 +7,9 @@
 //!   высокоуровневая логика протокола).
 //!
 //! Из этого модуля переэкспортируются ключевые типы протокола:
-//! - `ZspDecoder`, `ZspEncoder`, `ZspFrame` и константы `MAX_ARRAY_DEPTH`,
-//!   `MAX_BINARY_LENGTH`, `MAX_LINE_LENGTH` для работы с фреймами.
+//! - `ZspDecoder`, `ZspEncoder`, `ZspFrame`, `CompressionAlgo` и константы
+//!   `MAX_ARRAY_DEPTH`, `MAX_BINARY_LENGTH`, `MAX_LINE_LENGTH` для работы с
+//!   фреймами.
 //! - `Command` и `Response` – для представления команд клиента и ответов
 //!   сервера.
 
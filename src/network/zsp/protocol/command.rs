@@ -17,6 +17,9 @@ pub enum Command {
     Del {
         key: String,
     },
+    GetDel {
+        key: String,
+    },
     MSet {
         entries: Vec<(String, Value)>,
     },
@@ -36,6 +39,11 @@ pub enum Command {
         to: String,
     },
 
+    // --- Интроспекция ---
+    ObjectEncoding {
+        key: String,
+    },
+
     // Авторизация
     Auth {
         user: Option<String>,
@@ -73,11 +81,13 @@ impl Command {
             Command::Set { .. } => "set",
             Command::Get { .. } => "get",
             Command::Del { .. } => "del",
+            Command::GetDel { .. } => "getdel",
             Command::MSet { .. } => "mset",
             Command::MGet { .. } => "mget",
             Command::SetNx { .. } => "setnx",
             Command::Rename { .. } => "rename",
             Command::RenameNx { .. } => "renamenx",
+            Command::ObjectEncoding { .. } => "object",
             Command::Auth { .. } => "auth",
 
             // PubSub команды
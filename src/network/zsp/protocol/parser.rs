@@ -1,7 +1,8 @@
 use crate::{
     zsp::{command::Command as ZSPCommand, zsp_types::ZspFrame, PubSubMessage},
-    AuthCommand, DelCommand, GetCommand, MGetCommand, MSetCommand, ParseError, RenameCommand,
-    RenameNxCommand, Sds, SetCommand, SetNxCommand, StoreCommand, Value,
+    AuthCommand, DelCommand, GetCommand, GetDelCommand, MGetCommand, MSetCommand,
+    ObjectEncodingCommand, ParseError, RenameCommand, RenameNxCommand, Sds, SetCommand,
+    SetNxCommand, SetOptions, StoreCommand, Value,
 };
 
 /// RawCommand → ExeCommand
@@ -12,9 +13,14 @@ trait IntoExecutable {
 impl IntoExecutable for ZSPCommand {
     fn into_executable(self) -> Result<StoreCommand, ParseError> {
         match self {
-            ZSPCommand::Set { key, value } => Ok(StoreCommand::Set(SetCommand { key, value })),
+            ZSPCommand::Set { key, value } => Ok(StoreCommand::Set(SetCommand {
+                key,
+                value,
+                options: SetOptions::default(),
+            })),
             ZSPCommand::Get { key } => Ok(StoreCommand::Get(GetCommand { key })),
             ZSPCommand::Del { key } => Ok(StoreCommand::Del(DelCommand { key })),
+            ZSPCommand::GetDel { key } => Ok(StoreCommand::GetDel(GetDelCommand { key })),
             ZSPCommand::MSet { entries } => Ok(StoreCommand::MSet(MSetCommand { entries })),
             ZSPCommand::MGet { keys } => Ok(StoreCommand::MGet(MGetCommand { keys })),
             ZSPCommand::SetNx { key, value } => {
@@ -24,10 +30,10 @@ impl IntoExecutable for ZSPCommand {
             ZSPCommand::RenameNx { from, to } => {
                 Ok(StoreCommand::Renamenx(RenameNxCommand { from, to }))
             }
-            ZSPCommand::Auth { user, pass } => Ok(StoreCommand::Auth(AuthCommand {
-                user: user.unwrap(),
-                pass,
-            })),
+            ZSPCommand::ObjectEncoding { key } => {
+                Ok(StoreCommand::ObjectEncoding(ObjectEncodingCommand { key }))
+            }
+            ZSPCommand::Auth { user, pass } => Ok(StoreCommand::Auth(AuthCommand { user, pass })),
 
             // Заглушки для pub/sub
             ZSPCommand::Publish { .. } => Err(ParseError::UnknownCommand),
@@ -99,6 +105,14 @@ fn parse_from_str_command(
             let key = parse_key(&items[1], "DEL")?;
             Ok(ZSPCommand::Del { key })
         }
+        "getdel" => {
+            if items.len() != 2 {
+                return Err(ParseError::WrongArgCount("GETDEL", 1));
+            }
+
+            let key = parse_key(&items[1], "GETDEL")?;
+            Ok(ZSPCommand::GetDel { key })
+        }
         "mset" => {
             if items.len() < 3 || items.len().is_multiple_of(2) {
                 return Err(ParseError::MSetWrongArgCount);
@@ -150,6 +164,19 @@ fn parse_from_str_command(
             let to = parse_key(&items[2], "RENAMENX")?;
             Ok(ZSPCommand::RenameNx { from, to })
         }
+        "object" => {
+            // Поддерживается только OBJECT ENCODING <key>
+            if items.len() != 3 {
+                return Err(ParseError::WrongArgCount("OBJECT", 2));
+            }
+
+            let subcommand = parse_key(&items[1], "OBJECT")?;
+            if !subcommand.eq_ignore_ascii_case("encoding") {
+                return Err(ParseError::UnknownCommand);
+            }
+            let key = parse_key(&items[2], "OBJECT")?;
+            Ok(ZSPCommand::ObjectEncoding { key })
+        }
         "auth" => {
             // AUTH <password> или AUTH <user> <password>
             match items.len() {
@@ -329,6 +356,32 @@ mod tests {
         }
     }
 
+    /// Тест проверяет парсинг OBJECT ENCODING и отказ для других
+    /// подкоманд OBJECT.
+    #[test]
+    fn test_parse_object_encoding() {
+        let frame = ZspFrame::Array(vec![
+            ZspFrame::InlineString(Cow::Borrowed("OBJECT")),
+            ZspFrame::InlineString(Cow::Borrowed("encoding")),
+            ZspFrame::BinaryString(Some(b"anton".to_vec())),
+        ]);
+
+        match parse_command(frame).unwrap() {
+            StoreCommand::ObjectEncoding(cmd) => assert_eq!(cmd.key, "anton"),
+            _ => panic!("Expected ObjectEncodingCommand"),
+        }
+
+        let frame = ZspFrame::Array(vec![
+            ZspFrame::InlineString(Cow::Borrowed("OBJECT")),
+            ZspFrame::InlineString(Cow::Borrowed("FREQ")),
+            ZspFrame::InlineString(Cow::Borrowed("anton")),
+        ]);
+        assert!(matches!(
+            parse_command(frame),
+            Err(ParseError::UnknownCommand)
+        ));
+    }
+
     /// Тест проверяет парсинг команды GET с аргументом в виде
     /// BinaryString
     #[test]
@@ -367,6 +420,26 @@ mod tests {
         }
     }
 
+    /// Тест проверяет парсинг команды GETDEL
+    #[test]
+    fn test_parse_getdel_command() {
+        let frame = ZspFrame::Array(vec![
+            ZspFrame::InlineString(Cow::Borrowed("GETDEL")),
+            ZspFrame::BinaryString(Some(b"token".to_vec())),
+        ]);
+
+        match parse_command(frame).unwrap() {
+            StoreCommand::GetDel(cmd) => assert_eq!(cmd.key, "token"),
+            _ => panic!("Expected GetDelCommand"),
+        }
+
+        let frame = ZspFrame::Array(vec![ZspFrame::InlineString(Cow::Borrowed("GETDEL"))]);
+        assert!(matches!(
+            parse_command(frame),
+            Err(ParseError::WrongArgCount("GETDEL", 1))
+        ));
+    }
+
     /// Тест проверяет парсинг SET с числовым значением
     #[test]
     fn test_parse_set_command_with_int_value() {
@@ -149,7 +149,7 @@ mod tests {
     use std::collections::HashSet;
 
     use super::*;
-    use crate::{Dict, Hll, QuickList, Sds, SkipList, SmartHash};
+    use crate::{Dict, Hll, QuickList, Sds, SkipList, SmartHash, Stream};
 
     /// Тест проверяет сериализацию `Response::Ok` в
     /// `ZspFrame::InlineString("OK")`
@@ -294,7 +294,7 @@ mod tests {
 
     #[test]
     fn test_serialize_sstream() {
-        let value = Value::SStream(vec![]);
+        let value = Value::SStream(Stream::new());
         let frame = serialize_response(Response::Value(value));
         assert_eq!(
             frame,
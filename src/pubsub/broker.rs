@@ -2,18 +2,26 @@ use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
-        Arc,
+        Arc, Weak,
     },
     time::{Duration, Instant},
 };
 
+use arc_swap::ArcSwap;
 use dashmap::DashMap;
 use serde::Serialize;
-use tokio::{sync::broadcast, time::timeout};
+use tokio::{
+    sync::{broadcast, Notify},
+    time::timeout,
+};
 
 use super::{intern_channel, Message};
 use crate::{
-    pubsub::{MessagePayload, SerializationFormat, Subscriber, SubscriptionOptions},
+    glob_match,
+    pubsub::{
+        BackpressureHandle, LagHandling, MessagePayload, SerializationFormat, Subscriber,
+        SubscriptionOptions,
+    },
     RecvError,
 };
 
@@ -22,10 +30,14 @@ use crate::{
 pub struct Broker {
     /// Каналы для обычных подписок (точные имена каналов)
     channels: DashMap<Arc<str>, broadcast::Sender<Message>>,
+    /// Каналы для подписок по glob-шаблону (`PSUBSCRIBE`)
+    patterns: DashMap<Arc<str>, broadcast::Sender<Message>>,
     /// Статистика по каналам
     stats: DashMap<Arc<str>, ChannelStats>,
-    /// Конфигурация брокера
-    config: BrokerConfig,
+    /// Подписчики каналов в режиме `LagHandling::Backpressure`
+    backpressure: DashMap<Arc<str>, ChannelBackpressure>,
+    /// Конфигурация брокера; заменяется целиком через [`Broker::set_config`]
+    config: ArcSwap<BrokerConfig>,
     /// Глобальные метрики
     metrics: Arc<BrokerMetrics>,
 }
@@ -45,6 +57,9 @@ pub struct BrokerConfig {
     pub enable_compression: bool,
     /// Порог размера для сжатия (в байтах)
     pub compression_threshold: usize,
+    /// Обработка отставания для подписчиков, созданных без явных
+    /// `SubscriptionOptions`
+    pub lag_handling: LagHandling,
 }
 
 /// Статистика по каналу
@@ -62,6 +77,8 @@ pub struct ChannelStats {
     pub last_activity: Instant,
     /// Счетчик отброшенных сообщений (из-за переполнения буфера)
     pub dropped_messages: u64,
+    /// Запрошенная ёмкость буфера канала
+    pub capacity: usize,
 }
 
 /// Глобальные метрики брокера
@@ -75,6 +92,12 @@ pub struct BrokerMetrics {
     pub total_bytes: AtomicU64,
     /// Количество активных подписчиков
     pub active_subscribers: AtomicUsize,
+    /// Количество сообщений, отброшенных при доставке из-за истёкшего TTL
+    pub expired_messages: AtomicU64,
+    /// Сколько раз издателю пришлось ждать освобождения буфера подписчика
+    pub backpressure_blocks: AtomicU64,
+    /// Количество ключей хранилища, удалённых по истечении TTL
+    pub expired_keys: AtomicU64,
 }
 
 /// Результат публикации сообщения
@@ -103,6 +126,9 @@ pub struct PublishOptions {
     pub add_timestamp: bool,
     /// Пользовательские заголовки
     pub headers: Option<HashMap<String, String>>,
+    /// Время жизни сообщения: подписчики, получившие его позже, отбрасывают
+    /// его вместо доставки
+    pub ttl: Option<Duration>,
 }
 
 /// Снимок состояния брокера.
@@ -122,6 +148,15 @@ pub struct ChannelSnapshot {
     pub stats: ChannelStats,
 }
 
+/// Подписчики канала, ради которых издатель ждёт освобождения буфера.
+#[derive(Debug, Default)]
+struct ChannelBackpressure {
+    /// Будит ожидающих издателей после чтения сообщения подписчиком
+    drained: Arc<Notify>,
+    /// Активные подписчики; закрытые удаляются при публикации
+    subscribers: Vec<Weak<BackpressureHandle>>,
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Собственные методы
 ////////////////////////////////////////////////////////////////////////////////
@@ -136,12 +171,29 @@ impl Broker {
     pub fn with_config(config: BrokerConfig) -> Self {
         Self {
             channels: DashMap::new(),
+            patterns: DashMap::new(),
             stats: DashMap::new(),
-            config,
+            backpressure: DashMap::new(),
+            config: ArcSwap::from_pointee(config),
             metrics: Arc::new(BrokerMetrics::default()),
         }
     }
 
+    /// Возвращает текущую конфигурацию брокера.
+    pub fn config(&self) -> Arc<BrokerConfig> {
+        self.config.load_full()
+    }
+
+    /// Заменяет конфигурацию брокера без перезапуска. Лимиты и сжатие
+    /// действуют для следующих операций; ёмкость уже созданных каналов не
+    /// меняется.
+    pub fn set_config(
+        &self,
+        config: BrokerConfig,
+    ) {
+        self.config.store(Arc::new(config));
+    }
+
     /// Публикует сообщение в канал.
     pub fn publish<S>(
         &self,
@@ -167,7 +219,7 @@ impl Broker {
         let channel_key = intern_channel(channel);
 
         // Проверяем размер сообщения
-        if let Some(max_size) = self.config.max_message_size {
+        if let Some(max_size) = self.config.load().max_message_size {
             if payload.len() > max_size {
                 return Err(RecvError::SerializationError(format!(
                     "Message size {} exceeds maximum {}",
@@ -179,8 +231,9 @@ impl Broker {
 
         // Применяем сжатие если нужно
         let mut compressed = false;
-        if self.config.enable_compression
-            && (options.force_compression || payload.len() > self.config.compression_threshold)
+        if self.config.load().enable_compression
+            && (options.force_compression
+                || payload.len() > self.config.load().compression_threshold)
         {
             payload = self.compress_payload(payload)?;
             compressed = true;
@@ -201,23 +254,33 @@ impl Broker {
             }
         }
 
+        if let Some(ttl) = options.ttl {
+            message = message.with_ttl(ttl);
+        }
+
         let message_size = message.size();
         let message_id = message.metadata.as_ref().and_then(|m| m.message_id.clone());
 
-        // Публикуем сообщение
-        let subscribers_reached = if let Some(sender) = self.channels.get(&channel_key) {
-            match sender.send(message) {
-                Ok(subscriber_count) => subscriber_count,
-                Err(_) => {
-                    // Канал закрыт, удаляем его
-                    self.channels.remove(&channel_key);
-                    self.stats.remove(&channel_key);
-                    0
+        // Подписчики по шаблону получают копию сообщения
+        let pattern_reached = self.publish_to_patterns(&message);
+
+        // Публикуем сообщение. Отправитель клонируется, чтобы не держать
+        // блокировку DashMap при удалении закрытого канала.
+        let exact_sender = self.channels.get(&channel_key).map(|s| s.clone());
+        let subscribers_reached = pattern_reached
+            + if let Some(sender) = exact_sender {
+                match sender.send(message) {
+                    Ok(subscriber_count) => subscriber_count,
+                    Err(_) => {
+                        // Канал закрыт, удаляем его
+                        self.channels.remove(&channel_key);
+                        self.stats.remove(&channel_key);
+                        0
+                    }
                 }
-            }
-        } else {
-            0 // Нет подписчиков
-        };
+            } else {
+                0 // Нет подписчиков
+            };
 
         // Обновляем статистику
         self.update_channel_stats(&channel_key, message_size, subscribers_reached > 0);
@@ -231,6 +294,52 @@ impl Broker {
         })
     }
 
+    /// Публикует сообщение, дожидаясь подписчиков в режиме
+    /// `LagHandling::Backpressure`.
+    ///
+    /// Если буфер канала заполнен, издатель ждёт, пока подписчики его
+    /// освободят, но не дольше наибольшего `timeout` среди них; после этого
+    /// сообщение публикуется как обычно и отстающие подписчики теряют старые
+    /// сообщения. Каждое ожидание учитывается в `backpressure_blocks`.
+    pub async fn publish_async<S>(
+        &self,
+        channel: S,
+        payload: MessagePayload,
+        options: PublishOptions,
+    ) -> Result<PublishResult, RecvError>
+    where
+        S: AsRef<str>,
+    {
+        let channel_key = intern_channel(channel);
+
+        if let Some((drained, wait)) = self.backpressure_wait(&channel_key) {
+            let sender = self.channels.get(&channel_key).map(|s| s.clone());
+            let capacity = self
+                .stats
+                .get(&channel_key)
+                .map_or(self.config.load().default_channel_capacity, |s| s.capacity);
+            if let Some(sender) = sender.filter(|s| s.len() >= capacity) {
+                self.metrics
+                    .backpressure_blocks
+                    .fetch_add(1, Ordering::Relaxed);
+                let drain = async {
+                    loop {
+                        let notified = drained.notified();
+                        if sender.len() < capacity {
+                            break;
+                        }
+                        notified.await;
+                    }
+                };
+                // По истечении таймаута сообщение публикуется с потерей
+                // старых сообщений у отстающих подписчиков.
+                let _ = timeout(wait, drain).await;
+            }
+        }
+
+        self.publish_with_options(channel_key, payload, options)
+    }
+
     /// Публикует сериализуемый объект.
     pub fn publish_serializable<S, T>(
         &self,
@@ -246,7 +355,8 @@ impl Broker {
         self.publish(channel, payload)
     }
 
-    /// Создаёт подписчика на канал.
+    /// Создаёт подписчика на канал с обработкой отставания из
+    /// `BrokerConfig::lag_handling`.
     pub fn subscribe<S>(
         &self,
         channel: S,
@@ -254,7 +364,17 @@ impl Broker {
     where
         S: AsRef<str>,
     {
-        self.subscribe_with_options(channel, SubscriptionOptions::default())
+        self.subscribe_with_options(channel, self.subscription_options())
+    }
+
+    /// Возвращает опции подписки по умолчанию с обработкой отставания из
+    /// конфигурации брокера. `SubscriptionOptions::lag_handling` отдельного
+    /// подписчика переопределяет это значение.
+    pub fn subscription_options(&self) -> SubscriptionOptions {
+        SubscriptionOptions {
+            lag_handling: self.config.load().lag_handling.clone(),
+            ..Default::default()
+        }
     }
 
     pub fn subscribe_with_options<S>(
@@ -273,7 +393,7 @@ impl Broker {
             .or_insert_with(|| {
                 let capacity = options
                     .buffer_size
-                    .unwrap_or(self.config.default_channel_capacity);
+                    .unwrap_or(self.config.load().default_channel_capacity);
                 let (sender, _) = broadcast::channel(capacity);
 
                 // Инициализируем статистику для нового канала
@@ -282,6 +402,7 @@ impl Broker {
                     ChannelStats {
                         created_at: std::time::Instant::now(),
                         last_activity: std::time::Instant::now(),
+                        capacity,
                         ..Default::default()
                     },
                 );
@@ -291,7 +412,7 @@ impl Broker {
             .clone();
 
         // Проверяем лимит подписчиков
-        if let Some(max_subs) = self.config.max_subscribers_per_channel {
+        if let Some(max_subs) = self.config.load().max_subscribers_per_channel {
             let current_subs = sender.receiver_count();
             if current_subs >= max_subs {
                 return Err(RecvError::SubscriberLimitExceeded);
@@ -305,7 +426,92 @@ impl Broker {
             stats.subscriber_count = sender.receiver_count();
         }
 
-        Ok(Subscriber::new(receiver, channel_key, options))
+        let backpressure = match options.lag_handling {
+            LagHandling::Backpressure { timeout } => {
+                Some(self.register_backpressure(&channel_key, timeout))
+            }
+            _ => None,
+        };
+        let subscriber =
+            Subscriber::new(receiver, channel_key, options).with_metrics(self.metrics.clone());
+
+        Ok(match backpressure {
+            Some(handle) => subscriber.with_backpressure(handle),
+            None => subscriber,
+        })
+    }
+
+    /// Создаёт подписчика на все каналы, имена которых подходят под
+    /// glob-шаблон `pattern` (`PSUBSCRIBE`).
+    pub fn psubscribe<S>(
+        &self,
+        pattern: S,
+    ) -> Result<Subscriber, RecvError>
+    where
+        S: AsRef<str>,
+    {
+        self.psubscribe_with_options(pattern, self.subscription_options())
+    }
+
+    /// Создаёт подписчика по шаблону с заданными опциями.
+    pub fn psubscribe_with_options<S>(
+        &self,
+        pattern: S,
+        options: SubscriptionOptions,
+    ) -> Result<Subscriber, RecvError>
+    where
+        S: AsRef<str>,
+    {
+        let pattern_key = intern_channel(pattern);
+
+        let sender = self
+            .patterns
+            .entry(pattern_key.clone())
+            .or_insert_with(|| {
+                let capacity = options
+                    .buffer_size
+                    .unwrap_or(self.config.load().default_channel_capacity);
+                broadcast::channel(capacity).0
+            })
+            .clone();
+
+        if let Some(max_subs) = self.config.load().max_subscribers_per_channel {
+            if sender.receiver_count() >= max_subs {
+                return Err(RecvError::SubscriberLimitExceeded);
+            }
+        }
+
+        Ok(
+            Subscriber::new_pattern(sender.subscribe(), pattern_key, options)
+                .with_metrics(self.metrics.clone()),
+        )
+    }
+
+    /// Удаляет шаблон и закрывает всех подписчиков на него
+    /// (`PUNSUBSCRIBE`).
+    pub fn punsubscribe(
+        &self,
+        pattern: &str,
+    ) -> bool {
+        self.patterns.remove(&intern_channel(pattern)).is_some()
+    }
+
+    /// Возвращает общее число подписок по шаблону у всех клиентов
+    /// (`PUBSUB NUMPAT`).
+    pub fn pattern_count(&self) -> usize {
+        self.patterns
+            .iter()
+            .map(|entry| entry.value().receiver_count())
+            .sum()
+    }
+
+    /// Возвращает список шаблонов, на которые есть подписки.
+    pub fn active_patterns(&self) -> Vec<String> {
+        self.patterns
+            .iter()
+            .filter(|entry| entry.value().receiver_count() > 0)
+            .map(|entry| entry.key().to_string())
+            .collect()
     }
 
     /// Создаёт подписчика на несколько каналов.
@@ -383,15 +589,30 @@ impl Broker {
             active_subscribers: AtomicUsize::new(
                 self.metrics.active_subscribers.load(Ordering::Relaxed),
             ),
+            expired_messages: AtomicU64::new(self.metrics.expired_messages.load(Ordering::Relaxed)),
+            backpressure_blocks: AtomicU64::new(
+                self.metrics.backpressure_blocks.load(Ordering::Relaxed),
+            ),
+            expired_keys: AtomicU64::new(self.metrics.expired_keys.load(Ordering::Relaxed)),
         }
     }
 
+    /// Учитывает `count` ключей, удалённых хранилищем по истечении TTL.
+    pub fn record_expired_keys(
+        &self,
+        count: u64,
+    ) {
+        self.metrics
+            .expired_keys
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
     /// Очищает неактивные каналы.
     pub fn cleanup_inactive_channels(&self) -> usize {
         let mut removed_count = 0;
         let now = Instant::now();
 
-        if let Some(ttl) = self.config.channel_ttl {
+        if let Some(ttl) = self.config.load().channel_ttl {
             let channel_to_remove: Vec<_> = self
                 .stats
                 .iter()
@@ -433,14 +654,14 @@ impl Broker {
         };
 
         if let Some(timeout_duration) = options.timeout {
-            timeout(timeout_duration, async {
-                // В реальной async версии здесь была бы async публикация
-                self.publish_with_options(channel, payload, options)
-            })
+            timeout(
+                timeout_duration,
+                self.publish_async(channel, payload, options),
+            )
             .await
             .map_err(|_| RecvError::Timeout)?
         } else {
-            self.publish_with_options(channel, payload, options)
+            self.publish_async(channel, payload, options).await
         }
     }
 
@@ -485,12 +706,85 @@ impl Broker {
 
         BrokerSnapshot {
             channels,
-            config: self.config.clone(),
+            config: BrokerConfig::clone(&self.config.load()),
             metrics: self.metrics(),
             timestamp: std::time::SystemTime::now(),
         }
     }
 
+    /// Регистрирует подписчика канала в режиме `LagHandling::Backpressure`.
+    fn register_backpressure(
+        &self,
+        channel_key: &Arc<str>,
+        timeout: Duration,
+    ) -> Arc<BackpressureHandle> {
+        let mut entry = self.backpressure.entry(channel_key.clone()).or_default();
+        let handle = Arc::new(BackpressureHandle {
+            timeout,
+            drained: entry.drained.clone(),
+        });
+        entry.subscribers.push(Arc::downgrade(&handle));
+        handle
+    }
+
+    /// Возвращает уведомление об освобождении буфера и наибольшее время
+    /// ожидания среди подписчиков канала в режиме `Backpressure`, удаляя
+    /// закрытых подписчиков.
+    fn backpressure_wait(
+        &self,
+        channel_key: &Arc<str>,
+    ) -> Option<(Arc<Notify>, Duration)> {
+        let mut entry = self.backpressure.get_mut(channel_key)?;
+        entry.subscribers.retain(|handle| handle.strong_count() > 0);
+        let wait = entry
+            .subscribers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|handle| handle.timeout)
+            .max();
+        match wait {
+            Some(wait) => Some((entry.drained.clone(), wait)),
+            None => {
+                drop(entry);
+                self.backpressure
+                    .remove_if(channel_key, |_, e| e.subscribers.is_empty());
+                None
+            }
+        }
+    }
+
+    /// Рассылает сообщение подписчикам шаблонов, под которые подходит его
+    /// канал. Шаблоны без подписчиков удаляются.
+    ///
+    /// Возвращает число получателей.
+    fn publish_to_patterns(
+        &self,
+        message: &Message,
+    ) -> usize {
+        if self.patterns.is_empty() {
+            return 0;
+        }
+
+        let matched: Vec<_> = self
+            .patterns
+            .iter()
+            .filter(|entry| glob_match(entry.key().as_bytes(), message.channel.as_bytes()))
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+
+        let mut reached = 0;
+        for (pattern, sender) in matched {
+            match sender.send(message.clone()) {
+                Ok(count) => reached += count,
+                Err(_) => {
+                    self.patterns
+                        .remove_if(&pattern, |_, sender| sender.receiver_count() == 0);
+                }
+            }
+        }
+        reached
+    }
+
     /// Сжимает payload сообщение.
     fn compress_payload(
         &self,
@@ -613,6 +907,7 @@ impl Default for BrokerConfig {
             channel_ttl: Some(Duration::from_secs(300)), // 5 минут
             enable_compression: false,
             compression_threshold: 1024, // 1KБ
+            lag_handling: LagHandling::Ignore,
         }
     }
 }
@@ -625,6 +920,7 @@ impl Default for PublishOptions {
             timeout: Some(Duration::from_secs(5)),
             add_timestamp: false,
             headers: None,
+            ttl: None,
         }
     }
 }
@@ -639,6 +935,7 @@ impl Default for ChannelStats {
             created_at: now,
             last_activity: now,
             dropped_messages: 0,
+            capacity: 0,
         }
     }
 }
@@ -650,6 +947,9 @@ impl Clone for BrokerMetrics {
             total_messages: AtomicU64::new(self.total_messages.load(Ordering::Relaxed)),
             total_bytes: AtomicU64::new(self.total_bytes.load(Ordering::Relaxed)),
             active_subscribers: AtomicUsize::new(self.active_subscribers.load(Ordering::Relaxed)),
+            expired_messages: AtomicU64::new(self.expired_messages.load(Ordering::Relaxed)),
+            backpressure_blocks: AtomicU64::new(self.backpressure_blocks.load(Ordering::Relaxed)),
+            expired_keys: AtomicU64::new(self.expired_keys.load(Ordering::Relaxed)),
         }
     }
 }
@@ -892,4 +1192,222 @@ mod tests {
         let _ = sub.recv().await.unwrap();
         assert_eq!(sub.buffered_message_count(), 0);
     }
+
+    /// Тест проверяет, что подписка по шаблону `news.*` получает сообщение из
+    /// `news.sports`, учитывается в `pattern_count` и снимается
+    /// `punsubscribe`.
+    #[tokio::test]
+    async fn test_psubscribe_receives_matching_publish() {
+        let broker = Broker::new();
+        let mut sub = broker.psubscribe("news.*").unwrap();
+        let mut exact = broker.subscribe("news.sports").unwrap();
+        assert_eq!(sub.pattern(), Some("news.*"));
+        assert_eq!(broker.pattern_count(), 1);
+
+        let result = broker
+            .publish("news.sports", MessagePayload::Bytes(Bytes::from("goal")))
+            .unwrap();
+        assert_eq!(result.subscribers_reached, 2);
+
+        let msg = sub.recv().await.unwrap();
+        assert_eq!(&*msg.channel, "news.sports");
+        assert_eq!(&*exact.recv().await.unwrap().channel, "news.sports");
+
+        let result = broker
+            .publish("weather.today", MessagePayload::Bytes(Bytes::from("rain")))
+            .unwrap();
+        assert_eq!(result.subscribers_reached, 0);
+
+        assert!(broker.punsubscribe("news.*"));
+        assert_eq!(broker.pattern_count(), 0);
+        assert!(!broker.punsubscribe("news.*"));
+    }
+
+    /// Тест проверяет, что сообщение с истёкшим TTL не доставляется
+    /// подписчику и учитывается в `expired_messages`, а свежее сообщение
+    /// доставляется.
+    #[tokio::test]
+    async fn test_expired_message_is_not_delivered() {
+        let broker = Broker::new();
+        let mut sub = broker.subscribe("events").unwrap();
+
+        let stale = PublishOptions {
+            ttl: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        broker
+            .publish_with_options("events", MessagePayload::String("stale".into()), stale)
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        let fresh = PublishOptions {
+            ttl: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        broker
+            .publish_with_options("events", MessagePayload::String("fresh".into()), fresh)
+            .unwrap();
+
+        let msg = sub.recv().await.unwrap();
+        assert_eq!(msg.payload, MessagePayload::String("fresh".into()));
+        assert_eq!(sub.stats().expired_messages, 1);
+        assert_eq!(broker.metrics().expired_messages.load(Ordering::Relaxed), 1);
+        assert!(matches!(sub.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    /// Тест проверяет, что в режиме `Backpressure` издатель ждёт, пока
+    /// подписчик освободит полный буфер, и сообщения не теряются.
+    #[tokio::test]
+    async fn test_backpressure_blocks_publisher_until_drained() {
+        let broker = Broker::new();
+        let options = SubscriptionOptions {
+            buffer_size: Some(2),
+            lag_handling: LagHandling::Backpressure {
+                timeout: Duration::from_secs(5),
+            },
+            ..Default::default()
+        };
+        let mut sub = broker.subscribe_with_options("bp", options).unwrap();
+
+        for i in 0..2 {
+            broker
+                .publish_async(
+                    "bp",
+                    MessagePayload::String(i.to_string()),
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            broker.metrics().backpressure_blocks.load(Ordering::Relaxed),
+            0
+        );
+
+        let reader = async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            sub.recv().await.unwrap()
+        };
+        let publisher =
+            broker.publish_async("bp", MessagePayload::String("2".into()), Default::default());
+        let (first, published) = tokio::join!(reader, publisher);
+        assert_eq!(first.payload, MessagePayload::String("0".into()));
+        assert_eq!(published.unwrap().subscribers_reached, 1);
+        assert_eq!(
+            broker.metrics().backpressure_blocks.load(Ordering::Relaxed),
+            1
+        );
+
+        for expected in ["1", "2"] {
+            let msg = sub.recv().await.unwrap();
+            assert_eq!(msg.payload, MessagePayload::String(expected.into()));
+        }
+        assert_eq!(sub.stats().lagged_messages, 0);
+    }
+
+    /// Тест проверяет, что по истечении таймаута `Backpressure` сообщение
+    /// всё же публикуется, а отстающий подписчик теряет старое.
+    #[tokio::test]
+    async fn test_backpressure_falls_back_to_drop_after_timeout() {
+        let broker = Broker::new();
+        let options = SubscriptionOptions {
+            buffer_size: Some(1),
+            lag_handling: LagHandling::Backpressure {
+                timeout: Duration::from_millis(20),
+            },
+            ..Default::default()
+        };
+        let mut sub = broker.subscribe_with_options("bp", options).unwrap();
+
+        for payload in ["old", "new"] {
+            broker
+                .publish_async(
+                    "bp",
+                    MessagePayload::String(payload.into()),
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            broker.metrics().backpressure_blocks.load(Ordering::Relaxed),
+            1
+        );
+
+        let msg = sub.recv().await.unwrap();
+        assert_eq!(msg.payload, MessagePayload::String("new".into()));
+        assert_eq!(sub.stats().lagged_messages, 1);
+    }
+
+    /// Тест проверяет, что `BrokerConfig::lag_handling` применяется к
+    /// подпискам по умолчанию, а опции подписчика его переопределяют.
+    #[tokio::test]
+    async fn test_broker_lag_handling_is_overridable_per_subscriber() {
+        let broker = Broker::with_config(BrokerConfig {
+            default_channel_capacity: 1,
+            lag_handling: LagHandling::Backpressure {
+                timeout: Duration::from_millis(10),
+            },
+            ..Default::default()
+        });
+        assert!(matches!(
+            broker.subscription_options().lag_handling,
+            LagHandling::Backpressure { .. }
+        ));
+
+        let ignore = SubscriptionOptions {
+            lag_handling: LagHandling::Ignore,
+            ..Default::default()
+        };
+        let _fast = broker.subscribe_with_options("plain", ignore).unwrap();
+        for _ in 0..2 {
+            broker
+                .publish_async(
+                    "plain",
+                    MessagePayload::String("x".into()),
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            broker.metrics().backpressure_blocks.load(Ordering::Relaxed),
+            0
+        );
+
+        let _slow = broker.subscribe("guarded").unwrap();
+        for _ in 0..2 {
+            broker
+                .publish_async(
+                    "guarded",
+                    MessagePayload::String("x".into()),
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            broker.metrics().backpressure_blocks.load(Ordering::Relaxed),
+            1
+        );
+    }
+
+    /// Тест проверяет, что `set_config` заменяет конфигурацию: новый лимит
+    /// размера сообщения действует для следующих публикаций.
+    #[test]
+    fn test_broker_set_config_applies_to_next_operations() {
+        let broker = Broker::new();
+        assert!(broker
+            .publish("cfg", MessagePayload::String("hello".into()))
+            .is_ok());
+
+        broker.set_config(BrokerConfig {
+            max_message_size: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(broker.config().max_message_size, Some(2));
+        assert!(broker
+            .publish("cfg", MessagePayload::String("hello".into()))
+            .is_err());
+    }
 }
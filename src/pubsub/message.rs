@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
@@ -56,6 +56,10 @@ pub struct MessageMetadata {
     pub message_id: Option<String>,
     /// Временная метка отправки (Unix timestamp в миллисекундах)
     pub timestamp: Option<u64>,
+    /// Время жизни сообщения от `timestamp`; просроченное сообщение не
+    /// доставляется подписчикам
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<Duration>,
     /// Пользовательские заголовки
     pub headers: HashMap<String, String>,
 }
@@ -178,17 +182,39 @@ impl Message {
 
     /// Добавляет временную метку к сообщению.
     pub fn with_timestamp(mut self) -> Self {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
+        let mut metadata = self.metadata.unwrap_or_default();
+        metadata.timestamp = Some(unix_millis());
+        self.metadata = Some(metadata);
+        self
+    }
 
+    /// Задаёт время жизни сообщения. Если временной метки ещё нет, она
+    /// устанавливается в текущее время.
+    pub fn with_ttl(
+        mut self,
+        ttl: Duration,
+    ) -> Self {
         let mut metadata = self.metadata.unwrap_or_default();
-        metadata.timestamp = Some(timestamp);
+        metadata.timestamp.get_or_insert_with(unix_millis);
+        metadata.ttl = Some(ttl);
         self.metadata = Some(metadata);
         self
     }
 
+    /// Проверяет, истекло ли время жизни сообщения (`timestamp + ttl`).
+    /// Сообщения без TTL или без временной метки не устаревают.
+    pub fn is_expired(&self) -> bool {
+        let Some(MessageMetadata {
+            timestamp: Some(timestamp),
+            ttl: Some(ttl),
+            ..
+        }) = &self.metadata
+        else {
+            return false;
+        };
+        unix_millis() >= timestamp.saturating_add(ttl.as_millis() as u64)
+    }
+
     /// Добавляет ID сообщения
     pub fn with_message_id<S: Into<String>>(
         mut self,
@@ -369,6 +395,18 @@ impl MessagePayload {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Текущее время в миллисекундах от начала эпохи Unix.
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для MessagePayload
 ////////////////////////////////////////////////////////////////////////////////
@@ -708,4 +746,26 @@ mod tests {
         let deserialized: TestStruct = msg.deserialize().unwrap();
         assert_eq!(deserialized, test_data);
     }
+
+    /// Тест проверяет, что сообщение устаревает по `timestamp + ttl`, а
+    /// сообщение без TTL не устаревает никогда.
+    #[test]
+    fn test_message_ttl_expiry() {
+        let msg = Message::new("events", b"x".to_vec());
+        assert!(!msg.is_expired());
+
+        let msg = msg.with_ttl(Duration::from_secs(60));
+        let metadata = msg.metadata.as_ref().unwrap();
+        assert!(metadata.timestamp.is_some());
+        assert_eq!(metadata.ttl, Some(Duration::from_secs(60)));
+        assert!(!msg.is_expired());
+
+        let mut stale = msg.clone();
+        stale.metadata.as_mut().unwrap().timestamp = Some(unix_millis() - 61_000);
+        assert!(stale.is_expired());
+
+        let json = serde_json::to_string(stale.metadata.as_ref().unwrap()).unwrap();
+        let decoded: MessageMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.ttl, Some(Duration::from_secs(60)));
+    }
 }
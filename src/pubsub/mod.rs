@@ -7,6 +7,8 @@
 //! - `intern` (приватный): внутренние утилиты каналов для координации
 //!   подписчиков.
 //! - `message`: структура сообщений и метаданные для публикуемых событий.
+//! - `prometheus` (приватный, фича `metrics`): экспорт метрик брокера в формате
+//!   Prometheus.
 //! - `subscriber`: логика подписок и интерфейсы потоков для потребителей.
 //! - `zsp_integration`: интеграция с ZSP-протоколом для pub/sub.
 //!
@@ -19,6 +21,8 @@
 pub mod broker;
 mod intern;
 pub mod message;
+#[cfg(feature = "metrics")]
+mod prometheus;
 pub mod subscriber;
 pub mod zsp_integration;
 
@@ -0,0 +1,218 @@
+//! Экспорт метрик брокера Pub/Sub в текстовом формате Prometheus.
+//!
+//! Метрики с меткой `channel` содержат строку `channel="*"` с суммой по
+//! брокеру; снимок [`BrokerSnapshot`] добавляет к ней строки по каждому
+//! каналу. Доступно только с Cargo-фичей `metrics`.
+
+use std::sync::atomic::Ordering;
+
+use metrics_exporter_prometheus::formatting::{
+    sanitize_label_value, write_help_line, write_metric_line, write_type_line,
+};
+
+use super::{BrokerMetrics, BrokerSnapshot, ChannelSnapshot};
+
+/// Метка суммарного значения по всем каналам.
+const ALL_CHANNELS: &str = "*";
+
+impl BrokerMetrics {
+    /// Формирует глобальные метрики брокера в текстовом формате Prometheus.
+    ///
+    /// # Возвращает
+    /// - строки `# HELP`, `# TYPE` и значения метрик `zumic_pubsub_*`
+    pub fn to_prometheus_lines(&self) -> String {
+        render(self, None)
+    }
+}
+
+impl BrokerSnapshot {
+    /// Формирует метрики снимка в текстовом формате Prometheus: глобальные
+    /// значения и статистику по каждому каналу с меткой `channel`.
+    ///
+    /// # Возвращает
+    /// - строки `# HELP`, `# TYPE` и значения метрик `zumic_pubsub_*`
+    pub fn to_prometheus_lines(&self) -> String {
+        render(&self.metrics, Some(&self.channels))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Записывает метрики брокера; при заданных `channels` добавляет строки по
+/// каналам и метрики, которые ведутся только по каналам.
+fn render(
+    metrics: &BrokerMetrics,
+    channels: Option<&[ChannelSnapshot]>,
+) -> String {
+    let mut buf = String::new();
+    let channels = channels.map(|channels| {
+        channels
+            .iter()
+            .map(|channel| {
+                let label = format!("channel=\"{}\"", sanitize_label_value(&channel.name));
+                (vec![label], channel)
+            })
+            .collect::<Vec<_>>()
+    });
+    let per_channel = channels.as_deref().unwrap_or_default();
+
+    write_scalar(
+        &mut buf,
+        "zumic_pubsub_channels",
+        "gauge",
+        "Number of Pub/Sub channels.",
+        metrics.total_channels.load(Ordering::Relaxed) as u64,
+    );
+    write_family(
+        &mut buf,
+        "zumic_pubsub_subscribers",
+        "gauge",
+        "Number of active Pub/Sub subscribers.",
+        metrics.active_subscribers.load(Ordering::Relaxed) as u64,
+        per_channel
+            .iter()
+            .map(|(labels, channel)| (labels, channel.subscriber_count as u64)),
+    );
+    write_family(
+        &mut buf,
+        "zumic_pubsub_messages_published_total",
+        "counter",
+        "Total number of published Pub/Sub messages.",
+        metrics.total_messages.load(Ordering::Relaxed),
+        per_channel.iter().map(|(labels, channel)| {
+            (
+                labels,
+                channel.stats.messages_sent + channel.stats.dropped_messages,
+            )
+        }),
+    );
+    write_scalar(
+        &mut buf,
+        "zumic_pubsub_published_bytes_total",
+        "counter",
+        "Total size of published Pub/Sub messages in bytes.",
+        metrics.total_bytes.load(Ordering::Relaxed),
+    );
+
+    if channels.is_some() {
+        let delivered = per_channel
+            .iter()
+            .map(|(labels, channel)| (labels, channel.stats.messages_sent));
+        write_family(
+            &mut buf,
+            "zumic_pubsub_messages_delivered_total",
+            "counter",
+            "Total number of Pub/Sub messages delivered to at least one subscriber.",
+            delivered.clone().map(|(_, count)| count).sum(),
+            delivered,
+        );
+        let dropped = per_channel
+            .iter()
+            .map(|(labels, channel)| (labels, channel.stats.dropped_messages));
+        write_family(
+            &mut buf,
+            "zumic_pubsub_messages_dropped_total",
+            "counter",
+            "Total number of Pub/Sub messages published without subscribers.",
+            dropped.clone().map(|(_, count)| count).sum(),
+            dropped,
+        );
+    }
+
+    write_scalar(
+        &mut buf,
+        "zumic_pubsub_messages_expired_total",
+        "counter",
+        "Total number of Pub/Sub messages discarded after their TTL expired.",
+        metrics.expired_messages.load(Ordering::Relaxed),
+    );
+    write_scalar(
+        &mut buf,
+        "zumic_pubsub_backpressure_blocks_total",
+        "counter",
+        "Total number of times a publisher waited for a slow subscriber.",
+        metrics.backpressure_blocks.load(Ordering::Relaxed),
+    );
+
+    buf
+}
+
+/// Записывает метрику с меткой `channel`: сумму с `channel="*"` и
+/// значения по каналам.
+fn write_family<'a>(
+    buf: &mut String,
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    total: u64,
+    per_channel: impl Iterator<Item = (&'a Vec<String>, u64)>,
+) {
+    write_help_line(buf, name, help);
+    write_type_line(buf, name, metric_type);
+    let all = [format!("channel=\"{ALL_CHANNELS}\"")];
+    write_metric_line(buf, name, None, &all, None::<(&str, u64)>, total, None);
+    for (labels, value) in per_channel {
+        write_metric_line(buf, name, None, labels, None::<(&str, u64)>, value, None);
+    }
+}
+
+/// Записывает метрику без меток вместе с `HELP` и `TYPE`.
+fn write_scalar(
+    buf: &mut String,
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    value: u64,
+) {
+    write_help_line(buf, name, help);
+    write_type_line(buf, name, metric_type);
+    write_metric_line(buf, name, None, &[], None::<(&str, u64)>, value, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Broker, MessagePayload};
+
+    /// Тест проверяет глобальные метрики брокера: строки `channel="*"` и
+    /// отсутствие метрик, которые ведутся только по каналам.
+    #[test]
+    fn test_broker_metrics_to_prometheus_lines() {
+        let broker = Broker::new();
+        let _sub = broker.subscribe("news").unwrap();
+        broker
+            .publish("news", MessagePayload::String("hi".into()))
+            .unwrap();
+
+        let text = broker.metrics().to_prometheus_lines();
+        assert!(text.contains("# TYPE zumic_pubsub_messages_published_total counter\n"));
+        assert!(text.contains("zumic_pubsub_messages_published_total{channel=\"*\"} 1\n"));
+        assert!(text.contains("# TYPE zumic_pubsub_subscribers gauge\n"));
+        assert!(text.contains("zumic_pubsub_subscribers{channel=\"*\"} 1\n"));
+        assert!(text.contains("zumic_pubsub_channels 1\n"));
+        assert!(!text.contains("zumic_pubsub_messages_dropped_total"));
+    }
+
+    /// Тест проверяет строки по каналам в снимке брокера, суммы по каналам и
+    /// экранирование имени канала в метке.
+    #[test]
+    fn test_snapshot_to_prometheus_lines_per_channel() {
+        let broker = Broker::new();
+        let _sub = broker.subscribe("a\"b").unwrap();
+        let _quiet = broker.subscribe("quiet").unwrap();
+        for _ in 0..2 {
+            broker
+                .publish("a\"b", MessagePayload::String("x".into()))
+                .unwrap();
+        }
+
+        let text = broker.snapshot().to_prometheus_lines();
+        assert!(text.contains("zumic_pubsub_messages_published_total{channel=\"*\"} 2\n"));
+        assert!(text.contains("zumic_pubsub_messages_published_total{channel=\"a\\\"b\"} 2\n"));
+        assert!(text.contains("zumic_pubsub_messages_published_total{channel=\"quiet\"} 0\n"));
+        assert!(text.contains("zumic_pubsub_messages_delivered_total{channel=\"*\"} 2\n"));
+        assert!(text.contains("zumic_pubsub_messages_dropped_total{channel=\"*\"} 0\n"));
+        assert!(text.contains("zumic_pubsub_subscribers{channel=\"quiet\"} 1\n"));
+    }
+}
@@ -1,14 +1,19 @@
-use std::{collections::VecDeque, fmt, str::from_utf8, sync::Arc};
+use std::{
+    collections::VecDeque,
+    fmt,
+    str::from_utf8,
+    sync::{atomic::Ordering, Arc},
+};
 
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::Deserialize;
 use tokio::{
-    sync::broadcast,
+    sync::{broadcast, Notify},
     time::{timeout, Duration, Instant},
 };
 
 use super::Message;
-use crate::{MessagePayload, RecvError, TryRecvError};
+use crate::{BrokerMetrics, MessagePayload, RecvError, TryRecvError};
 
 type CustomFilter = Arc<dyn Fn(&Message) -> bool + Send + Sync + 'static>;
 
@@ -45,6 +50,21 @@ pub enum LagHandling {
     Log,
     /// Пропускать отстающие сообщения
     Skip,
+    /// Издатель (`Broker::publish_async`) ждёт до `timeout`, пока подписчик
+    /// освободит буфер канала, и только затем отбрасывает старые сообщения
+    Backpressure {
+        /// Максимальное время ожидания издателя
+        timeout: Duration,
+    },
+}
+
+/// Связь подписчика в режиме [`LagHandling::Backpressure`] с брокером.
+#[derive(Debug)]
+pub(crate) struct BackpressureHandle {
+    /// Сколько издатель может ждать освобождения буфера
+    pub(crate) timeout: Duration,
+    /// Будит издателя, когда подписчик прочитал сообщение
+    pub(crate) drained: Arc<Notify>,
 }
 
 /// Подписчик с расширенными возможностями фильтрации и обработки.
@@ -52,8 +72,10 @@ pub enum LagHandling {
 pub struct Subscriber {
     /// Основной приёмник сообщений
     receiver: broadcast::Receiver<Message>,
-    /// Канал, на который подписан подписчик
+    /// Канал, на который подписан подписчик, или glob-шаблон каналов
     channel: Arc<str>,
+    /// Подписка по шаблону (`PSUBSCRIBE`), а не на точное имя канала
+    is_pattern: bool,
     /// Опции подписки.
     options: SubscriptionOptions,
     /// Буфер для хранения сообщений (если включён)
@@ -62,6 +84,10 @@ pub struct Subscriber {
     stats: SubscriberStats,
     /// Фильтры сообщений
     filters: MessageFilters,
+    /// Метрики брокера, в которых учитываются просроченные сообщения
+    metrics: Option<Arc<BrokerMetrics>>,
+    /// Ожидающие издатели в режиме `LagHandling::Backpressure`
+    backpressure: Option<Arc<BackpressureHandle>>,
 }
 
 /// Массовый подписчик для работы с несколькими каналами.
@@ -109,6 +135,12 @@ pub struct SubscriberStats {
     pub last_message_at: Option<Instant>,
     /// Количество ошибок десериализации
     pub deserialization_errors: u64,
+    /// Количество сообщений, отброшенных из-за истёкшего TTL
+    pub expired_messages: u64,
+    /// Количество подписок на точные имена каналов
+    pub channel_subscriptions: usize,
+    /// Количество подписок по шаблону (`PSUBSCRIBE`)
+    pub pattern_subscriptions: usize,
 }
 
 /// Фильтры сообщений.
@@ -186,16 +218,51 @@ impl Subscriber {
         Self {
             receiver,
             channel,
+            is_pattern: false,
             options,
             message_buffer,
             stats: SubscriberStats {
                 created_at: Instant::now(),
+                channel_subscriptions: 1,
                 ..Default::default()
             },
             filters: MessageFilters::default(),
+            metrics: None,
+            backpressure: None,
         }
     }
 
+    /// Привязывает подписчика к метрикам брокера.
+    pub(crate) fn with_metrics(
+        mut self,
+        metrics: Arc<BrokerMetrics>,
+    ) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Включает ожидание издателей, пока подписчик не освободит буфер.
+    pub(crate) fn with_backpressure(
+        mut self,
+        handle: Arc<BackpressureHandle>,
+    ) -> Self {
+        self.backpressure = Some(handle);
+        self
+    }
+
+    /// Создаёт подписчика на каналы, подходящие под glob-шаблон `pattern`.
+    pub(crate) fn new_pattern(
+        receiver: broadcast::Receiver<Message>,
+        pattern: Arc<str>,
+        options: SubscriptionOptions,
+    ) -> Self {
+        let mut subscriber = Self::new(receiver, pattern, options);
+        subscriber.is_pattern = true;
+        subscriber.stats.channel_subscriptions = 0;
+        subscriber.stats.pattern_subscriptions = 1;
+        subscriber
+    }
+
     /// Получает следующее сообщение (блокирующий вызов).
     pub async fn recv(&mut self) -> Result<Message, RecvError> {
         // Сначала проверяем локальный буфер.
@@ -227,7 +294,12 @@ impl Subscriber {
         }
 
         loop {
-            match self.receiver.try_recv() {
+            let received = self.receiver.try_recv();
+            if received.is_ok() {
+                self.notify_drained();
+            }
+            match received {
+                Ok(message) if message.is_expired() => self.record_expired(),
                 Ok(message) => {
                     if self.should_process_message(&message) {
                         let processed_message = self.process_message(message)?;
@@ -386,6 +458,11 @@ impl Subscriber {
         &self.channel
     }
 
+    /// Возвращает шаблон, если это подписка по шаблону (`PSUBSCRIBE`).
+    pub fn pattern(&self) -> Option<&str> {
+        self.is_pattern.then_some(&*self.channel)
+    }
+
     /// Возвращает кол-во сообщений в локальном буфере
     pub fn buffered_message_count(&self) -> usize {
         self.message_buffer.as_ref().map(|b| b.len()).unwrap_or(0)
@@ -412,7 +489,12 @@ impl Subscriber {
     /// Внутренний метод получения сообщения.
     async fn recv_internal(&mut self) -> Result<Message, RecvError> {
         loop {
-            match self.receiver.recv().await {
+            let received = self.receiver.recv().await;
+            if received.is_ok() {
+                self.notify_drained();
+            }
+            match received {
+                Ok(message) if message.is_expired() => self.record_expired(),
                 Ok(message) => {
                     if self.should_process_message(&message) {
                         let processed_message = self.process_message(message)?;
@@ -678,6 +760,24 @@ impl Subscriber {
                 // Просто пропускаем отстающие сообщения.
                 Ok(())
             }
+            // Издатель не дождался освобождения буфера: сообщения уже
+            // отброшены, продолжаем со следующего.
+            LagHandling::Backpressure { .. } => Ok(()),
+        }
+    }
+
+    /// Будит издателей, ожидающих освобождения буфера канала.
+    fn notify_drained(&self) {
+        if let Some(handle) = &self.backpressure {
+            handle.drained.notify_waiters();
+        }
+    }
+
+    /// Учитывает сообщение, отброшенное из-за истёкшего TTL.
+    fn record_expired(&mut self) {
+        self.stats.expired_messages += 1;
+        if let Some(metrics) = &self.metrics {
+            metrics.expired_messages.fetch_add(1, Ordering::Relaxed);
         }
     }
 
@@ -778,6 +878,9 @@ impl MultiSubscriber {
             total_stats.messages_filtered += stats.messages_filtered;
             total_stats.lagged_messages += stats.lagged_messages;
             total_stats.deserialization_errors += stats.deserialization_errors;
+            total_stats.expired_messages += stats.expired_messages;
+            total_stats.channel_subscriptions += stats.channel_subscriptions;
+            total_stats.pattern_subscriptions += stats.pattern_subscriptions;
 
             if stats.created_at < earliest_created {
                 earliest_created = stats.created_at;
@@ -801,10 +904,17 @@ impl MultiSubscriber {
 }
 
 ////////////////////////////////////////////////////////////////////////////////
-// Общие реализации трейтов для SubscriptionOptions, SubscriberStats,
-// MessageFilters
+// Общие реализации трейтов для Subscriber, SubscriptionOptions,
+// SubscriberStats, MessageFilters
 ////////////////////////////////////////////////////////////////////////////////
 
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        // Закрытый приёмник освобождает буфер канала
+        self.notify_drained();
+    }
+}
+
 impl Default for SubscriptionOptions {
     fn default() -> Self {
         Self {
@@ -830,6 +940,9 @@ impl Default for SubscriberStats {
             created_at: Instant::now(),
             last_message_at: None,
             deserialization_errors: 0,
+            expired_messages: 0,
+            channel_subscriptions: 0,
+            pattern_subscriptions: 0,
         }
     }
 }
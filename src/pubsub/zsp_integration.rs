@@ -1,13 +1,17 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, sync::Arc, time::Duration};
 
 use bytes::Bytes;
+use tokio::{sync::mpsc, task::JoinHandle, time::Instant};
 
 use crate::{
     network::zsp::{
         frame::{ZspDecoder, ZspEncoder, ZspFrame},
         protocol::command::{Command as ZspCommand, PubSubMessage},
     },
-    pubsub::{Message, MessageMetadata, MessagePayload},
+    pubsub::{
+        Broker, Message, MessageMetadata, MessagePayload, PublishOptions, Subscriber,
+        SubscriberStats,
+    },
     RecvError, ZspDecodeError, ZspEncodeError,
 };
 
@@ -24,7 +28,41 @@ pub enum ZspIntegrationError {
 pub enum PubSubCommand {
     Subscribe(Vec<String>),
     Unsubscribe(Vec<String>),
-    Publish(String, MessagePayload),
+    /// `PUBLISH channel message [EX seconds]`
+    Publish(String, MessagePayload, Option<Duration>),
+    /// `PSUBSCRIBE pattern [pattern ...]`
+    PSubscribe(Vec<String>),
+    /// `PUNSUBSCRIBE [pattern ...]`; пустой список — отписка от всех шаблонов
+    PUnsubscribe(Vec<String>),
+    /// `PUBSUB NUMPAT`
+    NumPat,
+}
+
+/// Pub/Sub-состояние одного соединения.
+///
+/// Каждая подписка — отдельная задача, которая пересылает сообщения своего
+/// `Subscriber` в общую очередь соединения. `ConnectionHandler` читает
+/// очередь через [`PubSubSession::recv`] и отправляет клиенту push-фреймы
+/// `message` и `pmessage`. При удалении сессии все подписки закрываются.
+#[derive(Debug)]
+pub struct PubSubSession {
+    broker: Arc<Broker>,
+    /// Подписки на точные имена каналов.
+    channels: HashMap<String, JoinHandle<()>>,
+    /// Подписки по шаблону.
+    patterns: HashMap<String, JoinHandle<()>>,
+    tx: mpsc::UnboundedSender<Delivery>,
+    rx: mpsc::UnboundedReceiver<Delivery>,
+    /// Статистика доставленных соединению сообщений.
+    stats: SubscriberStats,
+}
+
+/// Сообщение, полученное подпиской сессии.
+#[derive(Debug)]
+struct Delivery {
+    /// Шаблон, если сообщение получено подпиской `PSUBSCRIBE`.
+    pattern: Option<Arc<str>>,
+    message: Message,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -72,6 +110,16 @@ pub fn encode_subscribe(channels: &[String]) -> Result<Bytes, RecvError> {
     Ok(Bytes::from(encoded))
 }
 
+/// Кодирует PSUBSCRIBE команду
+pub fn encode_psubscribe(patterns: &[String]) -> Result<Bytes, RecvError> {
+    encode_names_command("PSUBSCRIBE", patterns)
+}
+
+/// Кодирует PUNSUBSCRIBE команду
+pub fn encode_punsubscribe(patterns: &[String]) -> Result<Bytes, RecvError> {
+    encode_names_command("PUNSUBSCRIBE", patterns)
+}
+
 /// Кодирует UNSUBSCRIBE команду
 pub fn encode_unsubscribe(channels: &[String]) -> Result<Bytes, RecvError> {
     let mut components = vec![ZspFrame::InlineString(Cow::Borrowed("UNSUBSCRIBE"))];
@@ -89,6 +137,17 @@ pub fn encode_unsubscribe(channels: &[String]) -> Result<Bytes, RecvError> {
 pub fn encode_publish(
     channel: &str,
     payload: &MessagePayload,
+) -> Result<Bytes, RecvError> {
+    encode_publish_with_ttl(channel, payload, None)
+}
+
+/// Кодирует команду `PUBLISH channel message EX seconds`: сообщение
+/// отбрасывается, если подписчик получает его позже `ttl` (с точностью до
+/// секунды).
+pub fn encode_publish_with_ttl(
+    channel: &str,
+    payload: &MessagePayload,
+    ttl: Option<Duration>,
 ) -> Result<Bytes, RecvError> {
     let pubsub_message = message_payload_to_pubsub(payload);
     let _cmd = ZspCommand::Publish {
@@ -127,6 +186,13 @@ pub fn encode_publish(
         }
     }
 
+    if let Some(ttl) = ttl {
+        components.push(ZspFrame::InlineString(Cow::Borrowed("EX")));
+        components.push(ZspFrame::BinaryString(Some(
+            ttl.as_secs().to_string().into_bytes(),
+        )));
+    }
+
     let frame = ZspFrame::Array(components);
     let encoded = ZspEncoder::encode(&frame)?;
     Ok(Bytes::from(encoded))
@@ -146,144 +212,377 @@ pub fn decode_command(data: &mut Bytes) -> Result<Option<PubSubCommand>, RecvErr
         match decoder.decode(&mut slice)? {
             Some(ZspFrame::Array(items)) if !items.is_empty() => {
                 let consumed = data.len() - slice.len();
+                (Some(command_from_items(&items)?), consumed)
+            }
+            Some(_) => {
+                return Err(RecvError::SerializationError(
+                    "Expected array command".to_string(),
+                ))
+            }
+            None => (None, 0),
+        }
+    }; // slice и decoder умирают здесь
 
-                // Разбираем команду здесь, возвращая owned значения.
-                let cmd_str = match &items[0] {
-                    ZspFrame::InlineString(s) => s.as_ref().to_uppercase(),
-                    ZspFrame::BinaryString(Some(bytes)) => {
-                        String::from_utf8_lossy(bytes).to_uppercase()
-                    }
+    if let Some(cmd) = cmd_opt {
+        *data = data.slice(consumed..);
+        return Ok(Some(cmd));
+    }
+
+    Ok(None)
+}
+
+/// Разбирает фрейм команды Pub/Sub, полученный соединением.
+///
+/// # Возвращает
+/// - `None` — фрейм не является командой `SUBSCRIBE`, `UNSUBSCRIBE`,
+///   `PSUBSCRIBE`, `PUNSUBSCRIBE`, `PUBLISH` или `PUBSUB`
+/// - `Some(Err(_))` — команда Pub/Sub с неверными аргументами
+pub fn parse_pubsub_frame(frame: &ZspFrame) -> Option<Result<PubSubCommand, RecvError>> {
+    const COMMANDS: [&str; 6] = [
+        "SUBSCRIBE",
+        "UNSUBSCRIBE",
+        "PSUBSCRIBE",
+        "PUNSUBSCRIBE",
+        "PUBLISH",
+        "PUBSUB",
+    ];
+    let ZspFrame::Array(items) = frame else {
+        return None;
+    };
+    let name = match items.first()? {
+        ZspFrame::InlineString(s) => s.as_bytes(),
+        ZspFrame::BinaryString(Some(bytes)) => bytes.as_slice(),
+        _ => return None,
+    };
+    COMMANDS
+        .iter()
+        .any(|cmd| name.eq_ignore_ascii_case(cmd.as_bytes()))
+        .then(|| command_from_items(items))
+}
+
+/// Формирует push-фрейм с сообщением для подписчика: `message channel
+/// payload` или, для подписки по шаблону, `pmessage pattern channel
+/// payload`.
+pub fn message_push_frame(
+    pattern: Option<&str>,
+    msg: &Message,
+) -> ZspFrame<'static> {
+    let mut components = Vec::with_capacity(4);
+    match pattern {
+        Some(pattern) => {
+            components.push(ZspFrame::InlineString(Cow::Borrowed("pmessage")));
+            components.push(ZspFrame::BinaryString(Some(pattern.as_bytes().to_vec())));
+        }
+        None => components.push(ZspFrame::InlineString(Cow::Borrowed("message"))),
+    }
+    components.push(ZspFrame::BinaryString(Some(
+        msg.channel.as_bytes().to_vec(),
+    )));
+    components.push(ZspFrame::BinaryString(Some(payload_bytes(&msg.payload))));
+    ZspFrame::Push(components)
+}
+
+impl PubSubSession {
+    /// Создаёт сессию без подписок.
+    pub fn new(broker: Arc<Broker>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            broker,
+            channels: HashMap::new(),
+            patterns: HashMap::new(),
+            tx,
+            rx,
+            stats: SubscriberStats::default(),
+        }
+    }
+
+    /// Выполняет команду Pub/Sub и возвращает фреймы ответа: по одному
+    /// push-фрейму на каждый канал или шаблон (`subscribe`, `psubscribe`,
+    /// ...) либо число для `PUBLISH` и `PUBSUB NUMPAT`.
+    pub fn execute(
+        &mut self,
+        command: PubSubCommand,
+    ) -> Result<Vec<ZspFrame<'static>>, RecvError> {
+        match command {
+            PubSubCommand::Subscribe(channels) => self.subscribe(channels, false),
+            PubSubCommand::PSubscribe(patterns) => self.subscribe(patterns, true),
+            PubSubCommand::Unsubscribe(channels) => Ok(self.unsubscribe(channels, false)),
+            PubSubCommand::PUnsubscribe(patterns) => Ok(self.unsubscribe(patterns, true)),
+            PubSubCommand::Publish(channel, payload, ttl) => {
+                let options = PublishOptions {
+                    ttl,
+                    ..Default::default()
+                };
+                let result = self
+                    .broker
+                    .publish_with_options(channel, payload, options)?;
+                Ok(vec![ZspFrame::Integer(result.subscribers_reached as i64)])
+            }
+            PubSubCommand::NumPat => {
+                Ok(vec![ZspFrame::Integer(self.broker.pattern_count() as i64)])
+            }
+        }
+    }
+
+    /// Возвращает общее число подписок соединения (каналы и шаблоны).
+    pub fn subscription_count(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    /// Возвращает статистику сессии: доставленные сообщения и число подписок
+    /// на каналы и по шаблону.
+    pub fn stats(&self) -> SubscriberStats {
+        SubscriberStats {
+            channel_subscriptions: self.channels.len(),
+            pattern_subscriptions: self.patterns.len(),
+            ..self.stats.clone()
+        }
+    }
+
+    /// Ожидает следующее сообщение любой из подписок и возвращает его в виде
+    /// push-фрейма. Без подписок ожидает бесконечно.
+    pub async fn recv(&mut self) -> Option<ZspFrame<'static>> {
+        let delivery = self.rx.recv().await?;
+        self.stats.messages_received += 1;
+        self.stats.bytes_received += delivery.message.size() as u64;
+        self.stats.last_message_at = Some(Instant::now());
+        Some(message_push_frame(
+            delivery.pattern.as_deref(),
+            &delivery.message,
+        ))
+    }
+
+    /// Подписывается на каналы или шаблоны `names`.
+    fn subscribe(
+        &mut self,
+        names: Vec<String>,
+        pattern: bool,
+    ) -> Result<Vec<ZspFrame<'static>>, RecvError> {
+        let kind = if pattern { "psubscribe" } else { "subscribe" };
+        let mut replies = Vec::with_capacity(names.len());
+        for name in names {
+            let subscriptions = if pattern {
+                &mut self.patterns
+            } else {
+                &mut self.channels
+            };
+            if !subscriptions.contains_key(&name) {
+                let subscriber = if pattern {
+                    self.broker.psubscribe(&name)?
+                } else {
+                    self.broker.subscribe(&name)?
+                };
+                subscriptions.insert(name.clone(), forward(subscriber, self.tx.clone()));
+            }
+            replies.push(subscription_reply(
+                kind,
+                Some(&name),
+                self.subscription_count(),
+            ));
+        }
+        Ok(replies)
+    }
+
+    /// Отписывается от каналов или шаблонов `names`; пустой список — от всех.
+    fn unsubscribe(
+        &mut self,
+        names: Vec<String>,
+        pattern: bool,
+    ) -> Vec<ZspFrame<'static>> {
+        let kind = if pattern {
+            "punsubscribe"
+        } else {
+            "unsubscribe"
+        };
+        let subscriptions = if pattern {
+            &mut self.patterns
+        } else {
+            &mut self.channels
+        };
+        let names = if names.is_empty() {
+            subscriptions.keys().cloned().collect()
+        } else {
+            names
+        };
+        if names.is_empty() {
+            return vec![subscription_reply(kind, None, self.subscription_count())];
+        }
+
+        let mut replies = Vec::with_capacity(names.len());
+        for name in names {
+            let subscriptions = if pattern {
+                &mut self.patterns
+            } else {
+                &mut self.channels
+            };
+            if let Some(task) = subscriptions.remove(&name) {
+                task.abort();
+            }
+            replies.push(subscription_reply(
+                kind,
+                Some(&name),
+                self.subscription_count(),
+            ));
+        }
+        replies
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Внутренние методы и функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Разбирает элементы массива-команды в `PubSubCommand`.
+fn command_from_items(items: &[ZspFrame]) -> Result<PubSubCommand, RecvError> {
+    let cmd_str = match &items[0] {
+        ZspFrame::InlineString(s) => s.as_ref().to_uppercase(),
+        ZspFrame::BinaryString(Some(bytes)) => String::from_utf8_lossy(bytes).to_uppercase(),
+        _ => {
+            return Err(RecvError::SerializationError(
+                "Invalid command format".to_string(),
+            ))
+        }
+    };
+
+    Ok(match cmd_str.as_str() {
+        "SUBSCRIBE" => PubSubCommand::Subscribe(names_from_items(&items[1..])?),
+        "UNSUBSCRIBE" => PubSubCommand::Unsubscribe(names_from_items(&items[1..])?),
+        "PUBLISH" => {
+            let (items, ttl) = split_publish_ttl(items)?;
+            if items.len() < 3 {
+                return Err(RecvError::SerializationError(
+                    "PUBLISH requires channel and payload".to_string(),
+                ));
+            }
+
+            let channel_bytes = extract_binary_string(&items[1])?;
+            let channel = String::from_utf8_lossy(&channel_bytes).to_string();
+
+            // Собираем PubSubMessage (owned), затем конвертируем в MessagePayload
+            let pubsub_msg = if items.len() == 3 {
+                // legacy: assume bytes
+                let d = extract_binary_string(&items[2])?;
+                PubSubMessage::Bytes(d)
+            } else {
+                let payload_type = match &items[2] {
+                    ZspFrame::InlineString(s) => s.as_ref(),
                     _ => {
                         return Err(RecvError::SerializationError(
-                            "Invalid command format".to_string(),
+                            "Payload type must be string".to_string(),
                         ))
                     }
                 };
 
-                let command = match cmd_str.as_str() {
-                    "SUBSCRIBE" => {
-                        let channels = items[1..]
-                            .iter()
-                            .map(|frame| extract_binary_string(frame))
-                            .collect::<Result<Vec<_>, _>>()?
-                            .into_iter()
-                            .map(|b| String::from_utf8_lossy(&b).to_string())
-                            .collect();
-                        PubSubCommand::Subscribe(channels)
+                match payload_type.to_uppercase().as_str() {
+                    "BYTES" => {
+                        let d = extract_binary_string(&items[3])?;
+                        PubSubMessage::Bytes(d)
                     }
-                    "UNSUBSCRIBE" => {
-                        let channels = items[1..]
-                            .iter()
-                            .map(|frame| extract_binary_string(frame))
-                            .collect::<Result<Vec<_>, _>>()?
-                            .into_iter()
-                            .map(|b| String::from_utf8_lossy(&b).to_string())
-                            .collect();
-                        PubSubCommand::Unsubscribe(channels)
+                    "STRING" => {
+                        let d = extract_binary_string(&items[3])?;
+                        let s = String::from_utf8(d)
+                            .map_err(|e| RecvError::SerializationError(e.to_string()))?;
+                        PubSubMessage::String(s)
                     }
-                    "PUBLISH" => {
-                        if items.len() < 3 {
+                    "JSON" => {
+                        let d = extract_binary_string(&items[3])?;
+                        let s = String::from_utf8(d)
+                            .map_err(|e| RecvError::SerializationError(e.to_string()))?;
+                        let v = serde_json::from_str(&s)
+                            .map_err(|e| RecvError::SerializationError(e.to_string()))?;
+                        PubSubMessage::Json(v)
+                    }
+                    "SERIALIZED" => {
+                        if items.len() < 5 {
                             return Err(RecvError::SerializationError(
-                                "PUBLISH requires channel and payload".to_string(),
+                                "SERIALIZED requires content_type and data".to_string(),
                             ));
                         }
-
-                        let channel_bytes = extract_binary_string(&items[1])?;
-                        let channel = String::from_utf8_lossy(&channel_bytes).to_string();
-
-                        // Собираем PubSubMessage (owned), затем конвертируем в MessagePayload
-                        let pubsub_msg = if items.len() == 3 {
-                            // legacy: assume bytes
-                            let d = extract_binary_string(&items[2])?;
-                            PubSubMessage::Bytes(d)
-                        } else {
-                            let payload_type = match &items[2] {
-                                ZspFrame::InlineString(s) => s.as_ref(),
-                                _ => {
-                                    return Err(RecvError::SerializationError(
-                                        "Payload type must be string".to_string(),
-                                    ))
-                                }
-                            };
-
-                            match payload_type.to_uppercase().as_str() {
-                                "BYTES" => {
-                                    let d = extract_binary_string(&items[3])?;
-                                    PubSubMessage::Bytes(d)
-                                }
-                                "STRING" => {
-                                    let d = extract_binary_string(&items[3])?;
-                                    let s = String::from_utf8(d).map_err(|e| {
-                                        RecvError::SerializationError(e.to_string())
-                                    })?;
-                                    PubSubMessage::String(s)
-                                }
-                                "JSON" => {
-                                    let d = extract_binary_string(&items[3])?;
-                                    let s = String::from_utf8(d).map_err(|e| {
-                                        RecvError::SerializationError(e.to_string())
-                                    })?;
-                                    let v = serde_json::from_str(&s).map_err(|e| {
-                                        RecvError::SerializationError(e.to_string())
-                                    })?;
-                                    PubSubMessage::Json(v)
-                                }
-                                "SERIALIZED" => {
-                                    if items.len() < 5 {
-                                        return Err(RecvError::SerializationError(
-                                            "SERIALIZED requires content_type and data".to_string(),
-                                        ));
-                                    }
-                                    let ct = extract_binary_string(&items[3])?;
-                                    let content_type = String::from_utf8_lossy(&ct).to_string();
-                                    let d = extract_binary_string(&items[4])?;
-                                    PubSubMessage::Serialized {
-                                        data: d,
-                                        content_type,
-                                    }
-                                }
-                                other => {
-                                    return Err(RecvError::SerializationError(format!(
-                                        "Unknown payload type: {other}"
-                                    )))
-                                }
-                            }
-                        };
-
-                        // Конвертируем централизованно в MessagePayload
-                        let payload = pubsub_to_message_payload(pubsub_msg);
-
-                        PubSubCommand::Publish(channel, payload)
+                        let ct = extract_binary_string(&items[3])?;
+                        let content_type = String::from_utf8_lossy(&ct).to_string();
+                        let d = extract_binary_string(&items[4])?;
+                        PubSubMessage::Serialized {
+                            data: d,
+                            content_type,
+                        }
                     }
                     other => {
                         return Err(RecvError::SerializationError(format!(
-                            "Unknown command: {other}"
+                            "Unknown payload type: {other}"
                         )))
                     }
-                };
+                }
+            };
 
-                (Some(command), consumed)
-            }
-            Some(_) => {
+            // Конвертируем централизованно в MessagePayload
+            let payload = pubsub_to_message_payload(pubsub_msg);
+
+            PubSubCommand::Publish(channel, payload, ttl)
+        }
+        "PSUBSCRIBE" => PubSubCommand::PSubscribe(names_from_items(&items[1..])?),
+        "PUNSUBSCRIBE" => PubSubCommand::PUnsubscribe(names_from_items(&items[1..])?),
+        "PUBSUB" => match items.get(1).map(extract_binary_string).transpose()? {
+            Some(sub) if sub.eq_ignore_ascii_case(b"NUMPAT") => PubSubCommand::NumPat,
+            _ => {
                 return Err(RecvError::SerializationError(
-                    "Expected array command".to_string(),
+                    "Unsupported PUBSUB subcommand".to_string(),
                 ))
             }
-            None => (None, 0),
+        },
+        other => {
+            return Err(RecvError::SerializationError(format!(
+                "Unknown command: {other}"
+            )))
         }
-    }; // slice и decoder умирают здесь
+    })
+}
 
-    if let Some(cmd) = cmd_opt {
-        *data = data.slice(consumed..);
-        return Ok(Some(cmd));
+/// Отделяет необязательный хвост `EX seconds` от аргументов PUBLISH.
+fn split_publish_ttl<'a, 'b>(
+    items: &'a [ZspFrame<'b>]
+) -> Result<(&'a [ZspFrame<'b>], Option<Duration>), RecvError> {
+    let [rest @ .., option, seconds] = items else {
+        return Ok((items, None));
+    };
+    if rest.len() < 3 || !extract_binary_string(option).is_ok_and(|o| o.eq_ignore_ascii_case(b"EX"))
+    {
+        return Ok((items, None));
     }
 
-    Ok(None)
+    let seconds = match seconds {
+        ZspFrame::Integer(n) => u64::try_from(*n).ok(),
+        frame => extract_binary_string(frame)
+            .ok()
+            .and_then(|s| std::str::from_utf8(&s).ok()?.parse().ok()),
+    };
+    match seconds {
+        Some(seconds) if seconds > 0 => Ok((rest, Some(Duration::from_secs(seconds)))),
+        _ => Err(RecvError::SerializationError(
+            "invalid expire time in 'publish' command".to_string(),
+        )),
+    }
 }
 
-////////////////////////////////////////////////////////////////////////////////
-// Внутренние методы и функции
-////////////////////////////////////////////////////////////////////////////////
+/// Извлекает имена каналов или шаблонов из аргументов команды.
+fn names_from_items(items: &[ZspFrame]) -> Result<Vec<String>, RecvError> {
+    items
+        .iter()
+        .map(|frame| Ok(String::from_utf8_lossy(&extract_binary_string(frame)?).to_string()))
+        .collect()
+}
+
+/// Кодирует команду вида `NAME arg [arg ...]`.
+fn encode_names_command(
+    name: &'static str,
+    names: &[String],
+) -> Result<Bytes, RecvError> {
+    let mut components = vec![ZspFrame::InlineString(Cow::Borrowed(name))];
+    for name in names {
+        components.push(ZspFrame::BinaryString(Some(name.as_bytes().to_vec())));
+    }
+    let encoded = ZspEncoder::encode(&ZspFrame::Array(components))?;
+    Ok(Bytes::from(encoded))
+}
 
 /// Преобразует Message в ZspFrame для кодирования
 fn message_to_zsp_frame(msg: &Message) -> ZspFrame<'_> {
@@ -478,6 +777,70 @@ fn extract_binary_string(frame: &ZspFrame) -> Result<Vec<u8>, ZspIntegrationErro
     }
 }
 
+/// Запускает задачу, пересылающую сообщения подписчика в очередь сессии.
+fn forward(
+    mut subscriber: Subscriber,
+    tx: mpsc::UnboundedSender<Delivery>,
+) -> JoinHandle<()> {
+    let pattern: Option<Arc<str>> = subscriber.pattern().map(Arc::from);
+    tokio::spawn(async move {
+        loop {
+            match subscriber.recv().await {
+                Ok(message) => {
+                    let delivery = Delivery {
+                        pattern: pattern.clone(),
+                        message,
+                    };
+                    if tx.send(delivery).is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Closed) => break,
+                // Отставание и отфильтрованные сообщения не прерывают подписку.
+                Err(_) => continue,
+            }
+        }
+    })
+}
+
+/// Формирует push-ответ на (P)SUBSCRIBE/(P)UNSUBSCRIBE: вид операции, имя
+/// канала или шаблона и число оставшихся подписок соединения.
+fn subscription_reply(
+    kind: &'static str,
+    name: Option<&str>,
+    count: usize,
+) -> ZspFrame<'static> {
+    ZspFrame::Push(vec![
+        ZspFrame::InlineString(Cow::Borrowed(kind)),
+        name.map_or(ZspFrame::Null, |name| {
+            ZspFrame::BinaryString(Some(name.as_bytes().to_vec()))
+        }),
+        ZspFrame::Integer(count as i64),
+    ])
+}
+
+/// Возвращает содержимое сообщения в виде байтов для push-фрейма.
+fn payload_bytes(payload: &MessagePayload) -> Vec<u8> {
+    match payload {
+        MessagePayload::Bytes(bytes) => bytes.to_vec(),
+        MessagePayload::String(s) => s.as_bytes().to_vec(),
+        MessagePayload::Json(json) => serde_json::to_vec(json).unwrap_or_default(),
+        MessagePayload::Serialized { data, .. } => data.to_vec(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для PubSubSession
+////////////////////////////////////////////////////////////////////////////////
+
+impl Drop for PubSubSession {
+    fn drop(&mut self) {
+        for task in self.channels.values().chain(self.patterns.values()) {
+            task.abort();
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Общие реализации трейтов для RecvError
 ////////////////////////////////////////////////////////////////////////////////
@@ -616,7 +979,7 @@ mod tests {
         let decoded_cmd = decode_command(&mut data).unwrap().unwrap();
 
         match decoded_cmd {
-            PubSubCommand::Publish(decoded_channel, decoded_payload) => {
+            PubSubCommand::Publish(decoded_channel, decoded_payload, None) => {
                 assert_eq!(channel, decoded_channel);
                 match decoded_payload {
                     MessagePayload::String(s) => assert_eq!("Hello, World!", s),
@@ -644,7 +1007,7 @@ mod tests {
 
         let decoded = decode_command(&mut data).unwrap().unwrap();
         match decoded {
-            PubSubCommand::Publish(ch, payload) => {
+            PubSubCommand::Publish(ch, payload, None) => {
                 assert_eq!("legacy_channel", ch);
                 match payload {
                     MessagePayload::Bytes(bytes) => {
@@ -668,7 +1031,7 @@ mod tests {
         let decoded_cmd = decode_command(&mut data).unwrap().unwrap();
 
         match decoded_cmd {
-            PubSubCommand::Publish(channel, decoded_payload) => {
+            PubSubCommand::Publish(channel, decoded_payload, None) => {
                 assert_eq!("events", channel);
                 match decoded_payload {
                     MessagePayload::Json(decoded_json) => {
@@ -680,4 +1043,128 @@ mod tests {
             _ => panic!("Expected Publish command"),
         }
     }
+
+    /// Тест проверяет кодирование и декодирование команд PSUBSCRIBE и
+    /// PUNSUBSCRIBE
+    #[test]
+    fn test_psubscribe_command_roundtrip() {
+        let patterns = vec!["news.*".to_string(), "h?llo".to_string()];
+
+        let mut data = encode_psubscribe(&patterns).unwrap();
+        match decode_command(&mut data).unwrap().unwrap() {
+            PubSubCommand::PSubscribe(decoded) => assert_eq!(patterns, decoded),
+            _ => panic!("Expected PSubscribe command"),
+        }
+
+        let mut data = encode_punsubscribe(&[]).unwrap();
+        match decode_command(&mut data).unwrap().unwrap() {
+            PubSubCommand::PUnsubscribe(decoded) => assert!(decoded.is_empty()),
+            _ => panic!("Expected PUnsubscribe command"),
+        }
+    }
+
+    /// Тест проверяет, что сессия с подпиской `news.*` получает PMESSAGE для
+    /// `news.sports`, а статистика и `PUBSUB NUMPAT` учитывают подписку по
+    /// шаблону отдельно.
+    #[tokio::test]
+    async fn test_session_psubscribe_delivers_pmessage() {
+        let broker = Arc::new(Broker::new());
+        let mut session = PubSubSession::new(broker.clone());
+        let mut publisher = PubSubSession::new(broker.clone());
+
+        let replies = session
+            .execute(PubSubCommand::PSubscribe(vec!["news.*".into()]))
+            .unwrap();
+        assert_eq!(
+            replies,
+            vec![ZspFrame::Push(vec![
+                ZspFrame::InlineString("psubscribe".into()),
+                ZspFrame::BinaryString(Some(b"news.*".to_vec())),
+                ZspFrame::Integer(1),
+            ])]
+        );
+        session
+            .execute(PubSubCommand::Subscribe(vec!["alerts".into()]))
+            .unwrap();
+
+        let stats = session.stats();
+        assert_eq!(stats.channel_subscriptions, 1);
+        assert_eq!(stats.pattern_subscriptions, 1);
+        assert_eq!(
+            publisher.execute(PubSubCommand::NumPat).unwrap(),
+            vec![ZspFrame::Integer(1)]
+        );
+
+        let reached = publisher
+            .execute(PubSubCommand::Publish(
+                "news.sports".into(),
+                MessagePayload::String("goal".into()),
+                None,
+            ))
+            .unwrap();
+        assert_eq!(reached, vec![ZspFrame::Integer(1)]);
+
+        let frame = session.recv().await.unwrap();
+        assert_eq!(
+            frame,
+            ZspFrame::Push(vec![
+                ZspFrame::InlineString("pmessage".into()),
+                ZspFrame::BinaryString(Some(b"news.*".to_vec())),
+                ZspFrame::BinaryString(Some(b"news.sports".to_vec())),
+                ZspFrame::BinaryString(Some(b"goal".to_vec())),
+            ])
+        );
+        assert_eq!(session.stats().messages_received, 1);
+
+        let replies = session
+            .execute(PubSubCommand::PUnsubscribe(vec![]))
+            .unwrap();
+        assert_eq!(
+            replies,
+            vec![ZspFrame::Push(vec![
+                ZspFrame::InlineString("punsubscribe".into()),
+                ZspFrame::BinaryString(Some(b"news.*".to_vec())),
+                ZspFrame::Integer(1),
+            ])]
+        );
+        tokio::task::yield_now().await;
+        assert_eq!(broker.pattern_count(), 0);
+    }
+
+    /// Тест проверяет разбор `PUBLISH channel message EX seconds` в
+    /// расширенном и простом формате и отказ при неверном времени жизни
+    #[test]
+    fn test_publish_command_with_ttl() {
+        let payload = MessagePayload::String("flash".to_string());
+        let mut data =
+            encode_publish_with_ttl("events", &payload, Some(Duration::from_secs(30))).unwrap();
+        match decode_command(&mut data).unwrap().unwrap() {
+            PubSubCommand::Publish(channel, decoded, ttl) => {
+                assert_eq!(channel, "events");
+                assert_eq!(decoded, payload);
+                assert_eq!(ttl, Some(Duration::from_secs(30)));
+            }
+            _ => panic!("Expected Publish command"),
+        }
+
+        let frame = |args: &[&str]| {
+            ZspFrame::Array(
+                args.iter()
+                    .map(|arg| ZspFrame::BinaryString(Some(arg.as_bytes().to_vec())))
+                    .collect(),
+            )
+        };
+        match parse_pubsub_frame(&frame(&["PUBLISH", "events", "flash", "ex", "5"])) {
+            Some(Ok(PubSubCommand::Publish(_, MessagePayload::Bytes(bytes), ttl))) => {
+                assert_eq!(bytes, Bytes::from("flash"));
+                assert_eq!(ttl, Some(Duration::from_secs(5)));
+            }
+            other => panic!("Expected Publish command, got {other:?}"),
+        }
+        assert!(
+            parse_pubsub_frame(&frame(&["PUBLISH", "events", "flash", "EX", "0"]))
+                .unwrap()
+                .is_err()
+        );
+    }
 }
@@ -35,7 +35,7 @@ async fn connection_handler_ping_and_quit() -> Result<()> {
         let (socket, addr) = listener.accept().await?;
         // Выполняем обработчик в этом же (current_thread) потоке
         manager_server
-            .handle_connection(socket, addr, engine_server)
+            .handle_connection(socket.into(), addr, engine_server)
             .await?;
         Ok::<(), anyhow::Error>(())
     };
@@ -36,7 +36,7 @@ async fn connection_handler_shutdown_notify() -> Result<()> {
         let (socket, addr) = listener.accept().await?;
         // Запускаем обработку (в этом же потоке)
         manager_server
-            .handle_connection(socket, addr, engine_server)
+            .handle_connection(socket.into(), addr, engine_server)
             .await?;
         Ok::<(), anyhow::Error>(())
     };
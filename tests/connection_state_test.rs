@@ -31,6 +31,9 @@ async fn test_full_connection_lifecycle_with_admin() -> anyhow::Result<()> {
                 max_connections: 100,
                 max_connections_per_ip: 10,
                 read_buffer_size: 8192,
+                monitor_capacity: 1024,
+                #[cfg(feature = "tls")]
+                tls_config: None,
             };
 
             #[allow(clippy::arc_with_non_send_sync)]
@@ -51,7 +54,7 @@ async fn test_full_connection_lifecycle_with_admin() -> anyhow::Result<()> {
                     let eng = engine_clone.clone();
 
                     tokio::task::spawn_local(async move {
-                        let _ = mgr.handle_connection(socket, addr, eng).await;
+                        let _ = mgr.handle_connection(socket.into(), addr, eng).await;
                     });
                 }
             });
@@ -129,8 +132,8 @@ async fn test_full_connection_lifecycle_with_admin() -> anyhow::Result<()> {
             assert!(count_response.contains(":3"));
 
             // CLIENT LIST
-            let list_response = admin.handle_client_list();
-            assert!(list_response.starts_with("*3\r\n"));
+            let list_response = admin.handle_client_list(None, &[]);
+            assert_eq!(list_response.matches(" addr=").count(), 3);
 
             // SERVER STATS
             let stats_response = admin.handle_server_stats();
@@ -7,7 +7,7 @@ use std::{cmp, f64, ops::RangeInclusive};
 
 use ordered_float::OrderedFloat;
 use proptest::{prelude::*, string::string_regex};
-use zumic::{Bitmap, Dict, Hll, Sds, SkipList, SmartHash, StreamEntry, StreamId, Value};
+use zumic::{Bitmap, Dict, Hll, Sds, SkipList, SmartHash, Stream, StreamEntry, StreamId, Value};
 
 /// Размеры для тестирования - от очень маленьких до больших
 const SMALL_SIZE: RangeInclusive<usize> = 0..=10;
@@ -205,7 +205,14 @@ pub fn sstream_strategy() -> impl Strategy<Value = Value> {
     let stream_entry_strategy =
         (stream_id_strategy, stream_data_strategy).prop_map(|(id, data)| StreamEntry { id, data }); // Adjust to your StreamEntry type
 
-    prop::collection::vec(stream_entry_strategy, SMALL_SIZE).prop_map(Value::SStream)
+    // Записи с невозрастающими ID поток отклоняет
+    prop::collection::vec(stream_entry_strategy, SMALL_SIZE).prop_map(|entries| {
+        let mut stream = Stream::new();
+        for entry in entries {
+            stream.add_with_id(entry.id, entry.data);
+        }
+        Value::SStream(stream)
+    })
 }
 
 /// Главный генератор - объединяет все типы Value
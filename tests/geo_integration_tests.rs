@@ -1,5 +1,6 @@
 use zumic::{
-    haversine_distance, Direction, GeoPoint, GeoSet, Geohash, GeohashPrecision, RadiusOptions,
+    haversine_distance, Direction, GeoAddOptions, GeoPoint, GeoSet, Geohash, GeohashPrecision,
+    RadiusOptions,
 };
 
 #[test]
@@ -7,11 +8,36 @@ fn test_restaurant_search_scenario() {
     let mut restaurants = GeoSet::new();
 
     // Добавляем рестораны в центре Кунгура
-    restaurants.add("Pizza Place".into(), 57.4500, 57.4498);
-    restaurants.add("Burger Joint".into(), 57.4505, 57.4502);
-    restaurants.add("Sushi Bar".into(), 57.4495, 57.4505);
-    restaurants.add("Italian Bistro".into(), 57.4502, 57.4508);
-    restaurants.add("Thai Restaurant".into(), 57.4498, 57.4495);
+    restaurants.add(
+        "Pizza Place".into(),
+        57.4500,
+        57.4498,
+        GeoAddOptions::default(),
+    );
+    restaurants.add(
+        "Burger Joint".into(),
+        57.4505,
+        57.4502,
+        GeoAddOptions::default(),
+    );
+    restaurants.add(
+        "Sushi Bar".into(),
+        57.4495,
+        57.4505,
+        GeoAddOptions::default(),
+    );
+    restaurants.add(
+        "Italian Bistro".into(),
+        57.4502,
+        57.4508,
+        GeoAddOptions::default(),
+    );
+    restaurants.add(
+        "Thai Restaurant".into(),
+        57.4498,
+        57.4495,
+        GeoAddOptions::default(),
+    );
 
     // Пользователь находится примерно в центре города
     let user_lon = 57.4500;
@@ -38,7 +64,7 @@ fn test_geohash_neighbor_coverage() {
         for j in 0..10 {
             let lon = i as f64 * 0.01;
             let lat = j as f64 * 0.01;
-            gs.add(format!("P_{i}_{j}"), lon, lat);
+            gs.add(format!("P_{i}_{j}"), lon, lat, GeoAddOptions::default());
         }
     }
 
@@ -155,7 +181,7 @@ fn test_large_dataset_performance() {
     for i in 0..10_000 {
         let lon = ((i % 100) as f64) * 0.1 - 5.0; // [-5, 4.9] ок
         let lat = ((i / 100) % 100) as f64 * 0.1 - 5.0; // теперь lat ∈ [-5, 4.9], все валидно
-        gs.add(format!("P{i}"), lon, lat);
+        gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
     }
 
     assert_eq!(gs.len(), 10_000);
@@ -191,7 +217,7 @@ fn test_query_consistency() {
     for i in 0..1_000 {
         let lon = (i % 50) as f64 * 0.02;
         let lat = (i / 50) as f64 * 0.02;
-        gs.add(format!("P{i}"), lon, lat);
+        gs.add(format!("P{i}"), lon, lat, GeoAddOptions::default());
     }
 
     let center_lon = 0.5;
@@ -248,7 +274,7 @@ fn test_edge_coordinates() {
     ];
 
     for (name, lon, lat) in test_cases {
-        gs.add(name.into(), lon, lat);
+        gs.add(name.into(), lon, lat, GeoAddOptions::default());
 
         // Должны уметь извлечь обратно
         let retrieved = gs.get(name).unwrap();
@@ -275,12 +301,12 @@ fn test_geohash_prefix_search() {
     let mut gs = GeoSet::new();
 
     // Добавляем точки в районе Кунгура
-    gs.add("P1".into(), 57.44, 56.99); // центр города
-    gs.add("P2".into(), 57.45, 56.995);
-    gs.add("P3".into(), 57.46, 56.992);
+    gs.add("P1".into(), 57.44, 56.99, GeoAddOptions::default()); // центр города
+    gs.add("P2".into(), 57.45, 56.995, GeoAddOptions::default());
+    gs.add("P3".into(), 57.46, 56.992, GeoAddOptions::default());
 
     // Далёкая точка (Пермь)
-    gs.add("P4".into(), 56.0, 58.0);
+    gs.add("P4".into(), 56.0, 58.0, GeoAddOptions::default());
 
     // Получаем geohash для одной точки
     let gh1 = gs.get_geohash("P1", GeohashPrecision::Medium).unwrap();
@@ -0,0 +1,226 @@
+#![cfg(feature = "tls")]
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair};
+use rustls::{
+    crypto::ring,
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName},
+    ClientConfig, RootCertStore,
+};
+use tempfile::TempDir;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_rustls::TlsConnector;
+use zumic::{
+    network::{
+        connection::{ConnectionConfig, ConnectionManager},
+        tls::{load_server_config, StreamAcceptor},
+    },
+    InMemoryStore, StorageEngine, TlsAuthClients,
+};
+
+/// Тестовая инфраструктура сертификатов: CA, сертификат сервера для
+/// `localhost` и сертификат клиента, подписанные этим CA.
+struct TestPki {
+    dir: TempDir,
+    ca: CertificateDer<'static>,
+    client_cert: CertificateDer<'static>,
+    client_key: KeyPair,
+}
+
+impl TestPki {
+    fn generate() -> Result<Self> {
+        let mut ca_params = CertificateParams::new(Vec::<String>::new())?;
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate()?)?;
+
+        let server_key = KeyPair::generate()?;
+        let server_cert =
+            CertificateParams::new(vec!["localhost".to_string()])?.signed_by(&server_key, &ca)?;
+
+        let client_key = KeyPair::generate()?;
+        let client_cert =
+            CertificateParams::new(vec!["client".to_string()])?.signed_by(&client_key, &ca)?;
+
+        let dir = TempDir::new()?;
+        std::fs::write(dir.path().join("server.crt"), server_cert.pem())?;
+        std::fs::write(dir.path().join("server.key"), server_key.serialize_pem())?;
+        std::fs::write(dir.path().join("ca.crt"), ca.pem())?;
+
+        Ok(Self {
+            dir,
+            ca: ca.der().clone(),
+            client_cert: client_cert.der().clone(),
+            client_key,
+        })
+    }
+
+    fn path(
+        &self,
+        name: &str,
+    ) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    fn server_config(
+        &self,
+        auth_clients: TlsAuthClients,
+    ) -> Result<Arc<rustls::ServerConfig>> {
+        let ca = self.path("ca.crt");
+        load_server_config(
+            &self.path("server.crt"),
+            &self.path("server.key"),
+            Some(ca.as_path()),
+            auth_clients,
+        )
+    }
+
+    fn connector(
+        &self,
+        with_client_cert: bool,
+    ) -> Result<TlsConnector> {
+        let mut roots = RootCertStore::empty();
+        roots.add(self.ca.clone())?;
+        let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots);
+        let config = if with_client_cert {
+            let key = PrivateKeyDer::try_from(self.client_key.serialize_der())
+                .map_err(anyhow::Error::msg)?;
+            builder.with_client_auth_cert(vec![self.client_cert.clone()], key)?
+        } else {
+            builder.with_no_client_auth()
+        };
+        Ok(TlsConnector::from(Arc::new(config)))
+    }
+}
+
+/// Отправляет строку команды и возвращает ответ сервера.
+async fn roundtrip<S>(
+    stream: &mut S,
+    request: &[u8],
+) -> Result<String>
+where
+    S: AsyncReadExt + AsyncWriteExt + Unpin,
+{
+    stream.write_all(request).await?;
+    let mut buf = vec![0u8; 128];
+    let n = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf)).await??;
+    Ok(String::from_utf8_lossy(&buf[..n]).into_owned())
+}
+
+/// Тест проверяет, что команды выполняются поверх TLS после рукопожатия
+/// в `StreamAcceptor`.
+#[tokio::test(flavor = "current_thread")]
+#[allow(clippy::arc_with_non_send_sync)]
+async fn tls_connection_ping_and_quit() -> Result<()> {
+    let pki = TestPki::generate()?;
+    let cfg = ConnectionConfig {
+        tls_config: Some(pki.server_config(TlsAuthClients::No)?),
+        ..Default::default()
+    };
+    let engine = Arc::new(StorageEngine::Memory(InMemoryStore::new()));
+    let manager = Arc::new(ConnectionManager::new(cfg.clone()));
+    let acceptor = StreamAcceptor::new(&cfg);
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let local_addr = listener.local_addr()?;
+
+    let server_fut = async move {
+        let (socket, addr) = listener.accept().await?;
+        let stream = acceptor.accept(socket).await?;
+        assert!(stream.is_tls());
+        manager.handle_connection(stream, addr, engine).await?;
+        Ok::<(), anyhow::Error>(())
+    };
+
+    let client_fut = async move {
+        let tcp = TcpStream::connect(local_addr).await?;
+        let mut client = pki
+            .connector(false)?
+            .connect(ServerName::try_from("localhost")?, tcp)
+            .await?;
+        let got = roundtrip(&mut client, b"PING\r\n").await?;
+        assert!(got.contains("+PONG"), "expected +PONG, got {got:?}");
+        let got = roundtrip(&mut client, b"QUIT\r\n").await?;
+        assert!(got.contains("+OK"), "expected +OK, got {got:?}");
+        Ok::<(), anyhow::Error>(())
+    };
+
+    tokio::try_join!(server_fut, client_fut)?;
+    Ok(())
+}
+
+/// Тест проверяет mutual TLS: без сертификата клиента рукопожатие
+/// отклоняется, с сертификатом от доверенного CA — проходит.
+#[tokio::test(flavor = "current_thread")]
+async fn mutual_tls_requires_client_certificate() -> Result<()> {
+    let pki = TestPki::generate()?;
+    let cfg = ConnectionConfig {
+        tls_config: Some(pki.server_config(TlsAuthClients::Yes)?),
+        ..Default::default()
+    };
+    let acceptor = StreamAcceptor::new(&cfg);
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let local_addr = listener.local_addr()?;
+
+    for with_client_cert in [false, true] {
+        let server_fut = async {
+            let (socket, _) = listener.accept().await?;
+            // Поток сразу закрывается, чтобы клиент не ждал ответа.
+            Ok::<_, anyhow::Error>(acceptor.accept(socket).await.is_ok())
+        };
+        let client_fut = async {
+            let tcp = TcpStream::connect(local_addr).await?;
+            let mut client = pki
+                .connector(with_client_cert)?
+                .connect(ServerName::try_from("localhost")?, tcp)
+                .await?;
+            // В TLS 1.3 отказ сервера клиент видит только при следующем чтении.
+            let _ = client.write_all(b"PING\r\n").await;
+            let mut buf = [0u8; 16];
+            let _ = client.read(&mut buf).await;
+            Ok::<(), anyhow::Error>(())
+        };
+
+        let (accepted, _) = tokio::join!(server_fut, client_fut);
+        assert_eq!(accepted?, with_client_cert);
+    }
+    Ok(())
+}
+
+/// Тест проверяет, что отсутствующие или неподходящие файлы сертификатов
+/// приводят к ошибке загрузки конфигурации.
+#[test]
+fn load_server_config_rejects_invalid_files() -> Result<()> {
+    let pki = TestPki::generate()?;
+
+    let missing = load_server_config(
+        &pki.path("missing.crt"),
+        &pki.path("server.key"),
+        None,
+        TlsAuthClients::No,
+    );
+    assert!(missing.is_err());
+
+    let key_as_cert = load_server_config(
+        &pki.path("server.key"),
+        &pki.path("server.key"),
+        None,
+        TlsAuthClients::No,
+    );
+    assert!(key_as_cert.is_err());
+
+    let ok = load_server_config(
+        &pki.path("server.crt"),
+        &pki.path("server.key"),
+        None,
+        TlsAuthClients::No,
+    );
+    assert!(ok.is_ok());
+    Ok(())
+}
@@ -25,7 +25,7 @@ fn test_full_pubsub_roundtrip() {
         .expect("some cmd");
 
     match cmd {
-        PubSubCommand::Publish(ch, pl) => {
+        PubSubCommand::Publish(ch, pl, None) => {
             assert_eq!(ch, channel);
             match pl {
                 MessagePayload::Json(ref v) => assert_eq!(v["user_id"], 42),
@@ -69,7 +69,7 @@ fn test_serialized_binary_payload_integration() {
         .expect("decode cmd")
         .expect("got cmd");
     match cmd {
-        PubSubCommand::Publish(ch, pl) => {
+        PubSubCommand::Publish(ch, pl, None) => {
             assert_eq!(ch, channel);
             match pl {
                 MessagePayload::Serialized { data, content_type } => {